@@ -13,7 +13,7 @@ use blockstack_lib::types::chainstate::{StacksAddress, StacksBlockId};
 use blockstack_lib::types::{BlockHeaderHash, BurnchainHeaderHash, VRFSeed};
 use blockstack_lib::util::boot::boot_code_id;
 use blockstack_lib::{
-    chainstate::{self, stacks::index::MarfTrieId},
+    chainstate::{self, burn::ConsensusHash, stacks::index::MarfTrieId},
     vm::clarity::ClarityInstance,
     vm::costs::ExecutionCost,
     vm::{
@@ -62,6 +62,10 @@ impl HeadersDB for TestHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+
+    fn get_consensus_hash_for_block(&self, _id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        None
+    }
 }
 
 fn as_hash160(inp: u32) -> [u8; 20] {
@@ -0,0 +1,250 @@
+//! Measure the wall-clock time of Clarity natives across input sizes, and propose recalibrated
+//! cost-function constants.
+//!
+//! Run on the reference hardware with
+//!
+//!     cargo bench --bench cost_functions_bench
+//!
+//! Each native is timed for a range of input sizes, where the input size is the value the
+//! interpreter passes to the native's cost function.  The timings are fit to the shape the
+//! native's cost function has in the `costs-2` contract, and a copy of that contract with the
+//! recalibrated definitions is written to `$COSTS_PROPOSAL_PATH` (default
+//! `costs-proposal.clar`), ready to be proposed through `cost-voting`.
+//!
+//! Runtime units are scaled so that a block exhausting the mainnet runtime limit takes
+//! `$REFERENCE_BLOCK_MS` milliseconds (default 5000) to evaluate.  The timings include evaluating
+//! the natives' literal arguments.
+
+extern crate blockstack_lib;
+extern crate criterion;
+
+use std::env;
+use std::fs;
+use std::time::Instant;
+
+use criterion::{black_box, BenchmarkId, Criterion};
+
+use blockstack_lib::chainstate::stacks::boot::BOOT_CODE_COSTS_2;
+use blockstack_lib::clarity_vm::database::MemoryBackingStore;
+use blockstack_lib::core::{StacksEpochId, BLOCK_LIMIT_MAINNET_205};
+use blockstack_lib::vm::ast;
+use blockstack_lib::vm::contexts::OwnedEnvironment;
+use blockstack_lib::vm::costs::calibration::{
+    propose_costs_contract, runtime_units_per_ns, CostCalibration, CostModel, CostSample,
+};
+use blockstack_lib::vm::costs::cost_functions::ClarityCostFunction;
+use blockstack_lib::vm::types::QualifiedContractIdentifier;
+use blockstack_lib::vm::{eval, LocalContext};
+
+/// At roughly one runtime unit per nanosecond, `costs-2` is consistent with this
+const DEFAULT_REFERENCE_BLOCK_MS: u64 = 5_000;
+
+const ARG_COUNTS: &'static [u64] = &[1, 2, 4, 8, 16, 32, 64, 128];
+const BUFFER_LENGTHS: &'static [u64] = &[1, 16, 64, 256, 1024, 4096, 16384];
+/// Serialized size of an int
+const INT_SIZE: u64 = 16;
+
+/// How to exercise one native
+struct Workload {
+    function: ClarityCostFunction,
+    input_sizes: &'static [u64],
+    /// Make a program whose evaluation passes `n` to the cost function
+    program: fn(u64) -> String,
+}
+
+fn repeat(item: &str, count: u64) -> String {
+    vec![item; count as usize].join(" ")
+}
+
+fn buffer(len: u64) -> String {
+    format!("0x{}", "00".repeat(len as usize))
+}
+
+fn workloads() -> Vec<Workload> {
+    vec![
+        Workload {
+            function: ClarityCostFunction::Add,
+            input_sizes: ARG_COUNTS,
+            program: |n| format!("(+ {})", repeat("1", n)),
+        },
+        Workload {
+            function: ClarityCostFunction::Sub,
+            input_sizes: ARG_COUNTS,
+            program: |n| format!("(- {})", repeat("1", n)),
+        },
+        Workload {
+            function: ClarityCostFunction::Mul,
+            input_sizes: ARG_COUNTS,
+            program: |n| format!("(* {})", repeat("1", n)),
+        },
+        Workload {
+            function: ClarityCostFunction::Div,
+            input_sizes: ARG_COUNTS,
+            program: |n| format!("(/ {})", repeat("1", n)),
+        },
+        Workload {
+            function: ClarityCostFunction::And,
+            input_sizes: ARG_COUNTS,
+            program: |n| format!("(and {})", repeat("true", n)),
+        },
+        Workload {
+            function: ClarityCostFunction::Or,
+            input_sizes: ARG_COUNTS,
+            program: |n| format!("(or {})", repeat("false", n)),
+        },
+        Workload {
+            function: ClarityCostFunction::Not,
+            input_sizes: &[1],
+            program: |_| "(not true)".to_string(),
+        },
+        Workload {
+            function: ClarityCostFunction::Begin,
+            input_sizes: ARG_COUNTS,
+            program: |n| format!("(begin {})", repeat("1", n)),
+        },
+        Workload {
+            function: ClarityCostFunction::ListCons,
+            input_sizes: &[16, 64, 256, 1024, 4096],
+            program: |n| format!("(list {})", repeat("1", n / INT_SIZE)),
+        },
+        Workload {
+            function: ClarityCostFunction::TupleCons,
+            input_sizes: ARG_COUNTS,
+            program: |n| {
+                let bindings: Vec<String> = (0..n).map(|i| format!("(a{} 1)", i)).collect();
+                format!("(tuple {})", bindings.join(" "))
+            },
+        },
+        Workload {
+            function: ClarityCostFunction::Concat,
+            input_sizes: &[2, 16, 64, 256, 1024, 4096, 16384],
+            program: |n| format!("(concat {} {})", buffer(n / 2), buffer(n - n / 2)),
+        },
+        Workload {
+            function: ClarityCostFunction::Hash160,
+            input_sizes: BUFFER_LENGTHS,
+            program: |n| format!("(hash160 {})", buffer(n)),
+        },
+        Workload {
+            function: ClarityCostFunction::Sha256,
+            input_sizes: BUFFER_LENGTHS,
+            program: |n| format!("(sha256 {})", buffer(n)),
+        },
+        Workload {
+            function: ClarityCostFunction::Sha512,
+            input_sizes: BUFFER_LENGTHS,
+            program: |n| format!("(sha512 {})", buffer(n)),
+        },
+        Workload {
+            function: ClarityCostFunction::Sha512t256,
+            input_sizes: BUFFER_LENGTHS,
+            program: |n| format!("(sha512/256 {})", buffer(n)),
+        },
+        Workload {
+            function: ClarityCostFunction::Keccak256,
+            input_sizes: BUFFER_LENGTHS,
+            program: |n| format!("(keccak256 {})", buffer(n)),
+        },
+    ]
+}
+
+/// Time `workload` at each of its input sizes.  Each sample is the median time per evaluation
+/// across criterion's measurements.  Returns None if criterion skipped any of them (e.g. because
+/// they were filtered out on the command line).
+fn measure(c: &mut Criterion, workload: &Workload) -> Option<Vec<CostSample>> {
+    let contract_id = QualifiedContractIdentifier::transient();
+    let mut store = MemoryBackingStore::new();
+    let mut owned_env =
+        OwnedEnvironment::new_free(false, store.as_clarity_db(), StacksEpochId::Epoch2_05);
+
+    let mut group = c.benchmark_group(workload.function.get_name_str());
+    let mut samples = vec![];
+    for input_size in workload.input_sizes.iter() {
+        let program = (workload.program)(*input_size);
+        let parsed = ast::build_ast(&contract_id, &program, &mut ())
+            .expect("Failed to parse benchmark program")
+            .expressions;
+
+        let mut nanos_per_eval = vec![];
+        group.bench_with_input(
+            BenchmarkId::from_parameter(input_size),
+            &parsed[0],
+            |b, expr| {
+                b.iter_custom(|iters| {
+                    let mut env = owned_env.get_exec_environment(None);
+                    let context = LocalContext::new();
+                    let start = Instant::now();
+                    for _ in 0..iters {
+                        black_box(
+                            eval(expr, &mut env, &context)
+                                .expect("Failed to evaluate benchmark program"),
+                        );
+                    }
+                    let elapsed = start.elapsed();
+                    nanos_per_eval.push(elapsed.as_nanos() as f64 / iters as f64);
+                    elapsed
+                })
+            },
+        );
+
+        if nanos_per_eval.len() == 0 {
+            return None;
+        }
+        nanos_per_eval.sort_by(|a, b| a.partial_cmp(b).expect("BUG: NaN timing"));
+        samples.push(CostSample {
+            input_size: *input_size,
+            nanos: nanos_per_eval[nanos_per_eval.len() / 2],
+        });
+    }
+    group.finish();
+    Some(samples)
+}
+
+fn main() {
+    let mut c = Criterion::default().configure_from_args();
+
+    let reference_block_ms = env::var("REFERENCE_BLOCK_MS")
+        .map(|ms| ms.parse().expect("Failed to parse $REFERENCE_BLOCK_MS"))
+        .unwrap_or(DEFAULT_REFERENCE_BLOCK_MS);
+    let units_per_ns = runtime_units_per_ns(BLOCK_LIMIT_MAINNET_205.runtime, reference_block_ms);
+
+    let mut calibrations = vec![];
+    for workload in workloads().iter() {
+        let model = match CostModel::of_definition(BOOT_CODE_COSTS_2, &workload.function) {
+            Some(model) => model,
+            None => {
+                eprintln!(
+                    "Skipping {}: costs-2 does not define it as a runtime cost",
+                    &workload.function
+                );
+                continue;
+            }
+        };
+        if let Some(samples) = measure(&mut c, workload) {
+            calibrations.push(CostCalibration::fit(
+                workload.function,
+                model,
+                &samples,
+                units_per_ns,
+            ));
+        }
+    }
+    c.final_summary();
+
+    for calibration in calibrations.iter() {
+        println!("{}\n", calibration);
+    }
+
+    let proposal_path =
+        env::var("COSTS_PROPOSAL_PATH").unwrap_or("costs-proposal.clar".to_string());
+    fs::write(
+        &proposal_path,
+        propose_costs_contract(BOOT_CODE_COSTS_2, &calibrations),
+    )
+    .expect(&format!("Failed to write {}", &proposal_path));
+    println!(
+        "Wrote proposed costs contract with {} recalibrated functions to {}",
+        calibrations.len(),
+        &proposal_path
+    );
+}
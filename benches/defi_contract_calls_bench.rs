@@ -0,0 +1,190 @@
+//! Time representative DeFi contract calls, which nest `let`s, `match`es and private function
+//! calls deeply enough that setting up their evaluation contexts is a large share of their cost.
+//!
+//! Each call is timed twice: "pooled", with its contexts' variable maps reused from a
+//! `LocalContextPool`, and "unpooled", with every context allocating its own map as the
+//! interpreter did before the pool existed.  So a single run compares the two:
+//!
+//!     cargo bench --bench defi_contract_calls_bench
+
+#[macro_use]
+extern crate criterion;
+extern crate blockstack_lib;
+
+use criterion::{black_box, Criterion};
+
+use blockstack_lib::clarity_vm::database::MemoryBackingStore;
+use blockstack_lib::core::StacksEpochId;
+use blockstack_lib::vm::contexts::{LocalContextPool, OwnedEnvironment};
+use blockstack_lib::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use blockstack_lib::vm::{SymbolicExpression, Value};
+
+const SENDER: &'static str = "S1G2081040G2081040G2081040G208105NK8PE5";
+
+/// A constant-product AMM with a handful of pools, in the style of the swap contracts deployed
+/// on mainnet
+const AMM_CONTRACT: &'static str = "
+(define-fungible-token lp-token)
+(define-map pools { pool-id: uint } { balance-x: uint, balance-y: uint, shares: uint, fee-bps: uint })
+(define-map balances { owner: principal, token-id: uint } { amount: uint })
+
+(define-read-only (get-balance (owner principal) (token-id uint))
+    (default-to u0 (get amount (map-get? balances { owner: owner, token-id: token-id }))))
+
+(define-private (credit (owner principal) (token-id uint) (amount uint))
+    (let ((balance (get-balance owner token-id)))
+        (map-set balances { owner: owner, token-id: token-id } { amount: (+ balance amount) })))
+
+(define-private (debit (owner principal) (token-id uint) (amount uint))
+    (let ((balance (get-balance owner token-id)))
+        (asserts! (>= balance amount) (err u1))
+        (ok (map-set balances { owner: owner, token-id: token-id } { amount: (- balance amount) }))))
+
+(define-private (get-pool (pool-id uint))
+    (unwrap-panic (map-get? pools { pool-id: pool-id })))
+
+(define-read-only (get-amount-out (pool-id uint) (amount-in uint))
+    (let (
+        (pool (get-pool pool-id))
+        (amount-in-with-fee (/ (* amount-in (- u10000 (get fee-bps pool))) u10000))
+        (numerator (* amount-in-with-fee (get balance-y pool)))
+        (denominator (+ (get balance-x pool) amount-in-with-fee)))
+        (/ numerator denominator)))
+
+(define-private (quote-hop (pool-id uint) (amount-in uint))
+    (let ((amount-out (get-amount-out pool-id amount-in)))
+        (match (map-get? pools { pool-id: pool-id })
+            pool (if (< amount-out (get balance-y pool)) amount-out u0)
+            u0)))
+
+(define-read-only (get-route-quote (route (list 8 uint)) (amount-in uint))
+    (fold quote-hop route amount-in))
+
+(define-public (faucet (amount uint))
+    (begin
+        (credit tx-sender u0 amount)
+        (credit tx-sender u1 amount)
+        (ok true)))
+
+(define-public (create-pool (pool-id uint) (amount-x uint) (amount-y uint) (fee-bps uint))
+    (begin
+        (try! (debit tx-sender u0 amount-x))
+        (try! (debit tx-sender u1 amount-y))
+        (map-set pools { pool-id: pool-id }
+            { balance-x: amount-x, balance-y: amount-y, shares: amount-x, fee-bps: fee-bps })
+        (ft-mint? lp-token amount-x tx-sender)))
+
+(define-public (add-liquidity (pool-id uint) (amount-x uint))
+    (let (
+        (pool (get-pool pool-id))
+        (amount-y (/ (* amount-x (get balance-y pool)) (get balance-x pool)))
+        (new-shares (/ (* amount-x (get shares pool)) (get balance-x pool))))
+        (try! (debit tx-sender u0 amount-x))
+        (try! (debit tx-sender u1 amount-y))
+        (map-set pools { pool-id: pool-id }
+            (merge pool {
+                balance-x: (+ (get balance-x pool) amount-x),
+                balance-y: (+ (get balance-y pool) amount-y),
+                shares: (+ (get shares pool) new-shares) }))
+        (ft-mint? lp-token new-shares tx-sender)))
+
+(define-public (swap-x-for-y (pool-id uint) (amount-in uint) (min-amount-out uint))
+    (let (
+        (pool (get-pool pool-id))
+        (amount-out (get-amount-out pool-id amount-in)))
+        (asserts! (>= amount-out min-amount-out) (err u2))
+        (try! (debit tx-sender u0 amount-in))
+        (credit tx-sender u1 amount-out)
+        (map-set pools { pool-id: pool-id }
+            (merge pool {
+                balance-x: (+ (get balance-x pool) amount-in),
+                balance-y: (- (get balance-y pool) amount-out) }))
+        (ok amount-out)))
+";
+
+fn uint(x: u128) -> SymbolicExpression {
+    SymbolicExpression::atom_value(Value::UInt(x))
+}
+
+fn execute(
+    owned_env: &mut OwnedEnvironment,
+    sender: &PrincipalData,
+    contract_id: &QualifiedContractIdentifier,
+    function: &str,
+    args: &[SymbolicExpression],
+) -> Value {
+    owned_env
+        .execute_transaction(sender.clone(), contract_id.clone(), function, args)
+        .unwrap()
+        .0
+}
+
+fn bench_defi_contract_calls(c: &mut Criterion) {
+    let sender = PrincipalData::parse(SENDER).unwrap();
+    let contract_id = QualifiedContractIdentifier::local("amm").unwrap();
+
+    let mut store = MemoryBackingStore::new();
+    let mut owned_env =
+        OwnedEnvironment::new_free(false, store.as_clarity_db(), StacksEpochId::Epoch2_05);
+    owned_env
+        .initialize_contract(contract_id.clone(), AMM_CONTRACT)
+        .unwrap();
+
+    execute(
+        &mut owned_env,
+        &sender,
+        &contract_id,
+        "faucet",
+        &[uint(1_000_000_000_000_000)],
+    );
+    for pool_id in 0..8 {
+        execute(
+            &mut owned_env,
+            &sender,
+            &contract_id,
+            "create-pool",
+            &[
+                uint(pool_id),
+                uint(1_000_000_000 + pool_id * 1_000),
+                uint(2_000_000_000),
+                uint(30),
+            ],
+        );
+    }
+
+    let route = SymbolicExpression::atom_value(
+        Value::list_from((0..8u128).map(Value::UInt).collect()).unwrap(),
+    );
+    let calls = vec![
+        ("swap-x-for-y", vec![uint(3), uint(1_000), uint(1)]),
+        ("add-liquidity", vec![uint(5), uint(1_000)]),
+        ("get-route-quote", vec![route, uint(1_000_000)]),
+    ];
+
+    for (function, args) in calls.iter() {
+        let mut group = c.benchmark_group(*function);
+        for pooled in [true, false].iter() {
+            let (name, pool) = if *pooled {
+                ("pooled", LocalContextPool::default())
+            } else {
+                ("unpooled", LocalContextPool::unpooled())
+            };
+            owned_env.set_local_context_pool(pool);
+            group.bench_function(name, |b| {
+                b.iter(|| {
+                    black_box(execute(
+                        &mut owned_env,
+                        &sender,
+                        &contract_id,
+                        function,
+                        args,
+                    ))
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_defi_contract_calls);
+criterion_main!(benches);
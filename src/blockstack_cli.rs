@@ -73,6 +73,8 @@ For usage information on those methods, call `blockstack-cli [method] -h`
 `blockstack-cli` accepts flag options as well:
 
    --testnet       instruct the transaction generator to use a testnet version byte instead of MAINNET (default)
+   --chain-id      hex chain ID to sign transactions for, e.g. when targeting an app-chain
+                   (defaults to the mainnet or testnet chain ID)
 
 ";
 
@@ -760,7 +762,15 @@ fn main_handler(mut argv: Vec<String>) -> Result<String, CliError> {
         TransactionVersion::Mainnet
     };
 
-    let chain_id = if tx_version == TransactionVersion::Testnet {
+    let chain_id = if let Some(ix) = argv.iter().position(|x| x == "--chain-id") {
+        if ix + 1 >= argv.len() {
+            return Err(CliError::Usage);
+        }
+        argv.remove(ix);
+        let chain_id_hex = argv.remove(ix);
+        u32::from_str_radix(chain_id_hex.trim_start_matches("0x"), 16)
+            .map_err(|_| CliError::Message(format!("Invalid chain ID: {}", &chain_id_hex)))?
+    } else if tx_version == TransactionVersion::Testnet {
         CHAIN_ID_TESTNET
     } else {
         CHAIN_ID_MAINNET
@@ -827,6 +837,33 @@ mod test {
         .contains("IO error"));
     }
 
+    #[test]
+    fn custom_chain_id() {
+        let tt_args = [
+            "--testnet",
+            "--chain-id",
+            "0x00c0ffee",
+            "token-transfer",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "10",
+        ];
+
+        let tx_hex = main_handler(to_string_vec(&tt_args)).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut &hex_bytes(&tx_hex).unwrap()[..])
+            .unwrap();
+        assert_eq!(tx.chain_id, 0x00c0ffee);
+        assert_eq!(tx.version, TransactionVersion::Testnet);
+
+        let bad_args = ["--chain-id", "not-hex", "generate-sk"];
+        assert!(main_handler(to_string_vec(&bad_args)).is_err());
+
+        let missing_args = ["generate-sk", "--chain-id"];
+        assert!(main_handler(to_string_vec(&missing_args)).is_err());
+    }
+
     #[test]
     fn simple_token_transfer() {
         let tt_args = [
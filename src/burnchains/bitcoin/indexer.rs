@@ -90,8 +90,8 @@ impl TryFrom<u32> for BitcoinNetworkType {
 
 /// Get the default epochs definitions for the given BitcoinNetworkType.
 /// Should *not* be used except by the BitcoinIndexer when no epochs vector
-/// was specified.
-fn get_bitcoin_stacks_epochs(network_id: BitcoinNetworkType) -> Vec<StacksEpoch> {
+/// was specified, or to derive a node's configured epochs from these defaults.
+pub fn get_bitcoin_stacks_epochs(network_id: BitcoinNetworkType) -> Vec<StacksEpoch> {
     match network_id {
         BitcoinNetworkType::Mainnet => STACKS_EPOCHS_MAINNET.to_vec(),
         BitcoinNetworkType::Testnet => STACKS_EPOCHS_TESTNET.to_vec(),
@@ -370,6 +370,14 @@ impl BitcoinIndexer {
             true,
             false,
         )?;
+
+        // fetch everything up to the last checkpoint in parallel first
+        let checkpointed_height =
+            spv_client.sync_checkpointed_headers(self, SPV_CHUNK_DOWNLOAD_WORKERS)?;
+        if checkpointed_height > start_block {
+            spv_client.set_scan_range(checkpointed_height, last_block);
+        }
+
         spv_client
             .run(self)
             .and_then(|_r| Ok(spv_client.end_block_height.unwrap()))
@@ -19,6 +19,9 @@ use std::collections::VecDeque;
 use std::fs;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::ops::Deref;
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 use deps::bitcoin::blockdata::block::{BlockHeader, LoneBlockHeader};
 use deps::bitcoin::blockdata::constants::genesis_block;
@@ -82,6 +85,207 @@ const SPV_INITIAL_SCHEMA: &[&'static str] = &[
     "CREATE TABLE db_config(version TEXT NOT NULL);",
 ];
 
+/// Headers downloaded ahead of our header chain, one checkpoint-bounded chunk at a time.  A chunk
+/// is only stored once all of its headers have been fetched and checked, so its rows survive a
+/// restart and are moved into `headers` once the header chain reaches the chunk's start.
+const SPV_CHUNK_SCHEMA: &'static str = r#"
+    CREATE TABLE IF NOT EXISTS chunk_headers(
+        version INTEGER NOT NULL,
+        prev_blockhash TEXT NOT NULL,
+        merkle_root TEXT NOT NULL,
+        time INTEGER NOT NULL,
+        bits INTEGER NOT NULL,
+        nonce INTEGER NOT NULL,
+        height INTEGER PRIMARY KEY NOT NULL,
+        chunk_start INTEGER NOT NULL,
+        chunk_end INTEGER NOT NULL
+    );
+    "#;
+
+/// Number of connections to open to the Bitcoin peer when downloading header chunks in parallel
+pub const SPV_CHUNK_DOWNLOAD_WORKERS: usize = 8;
+
+/// Known-good block hashes on mainnet, taken from Bitcoin Core.  Headers between two checkpoints
+/// can be downloaded independently of all others, since both ends of the chunk are known.
+const BITCOIN_CHECKPOINTS_MAINNET: &[(u64, &'static str)] = &[
+    (
+        11111,
+        "0000000069e244f73d78e8fd29ba2fd2ed618bd6fa2ee92559f542fdb26e7c1d",
+    ),
+    (
+        33333,
+        "000000002dd5588a74784eaa7ab0507a18ad16a236e7b1ce69f00d7ddfb5d0a6",
+    ),
+    (
+        74000,
+        "0000000000573993a3c9e41ce34471c079dcf5f52a0e824a81e7f953b8661a20",
+    ),
+    (
+        105000,
+        "00000000000291ce28027faea320c8d2b054b2e0fe44a773f3eefb151d6bdc97",
+    ),
+    (
+        134444,
+        "00000000000005b12ffd4cd315cd34ffd4a594f430ac814c91184a0d42d2b0fe",
+    ),
+    (
+        168000,
+        "000000000000099e61ea72015e79632f216fe6cb33d7899acb35b75c8303b763",
+    ),
+    (
+        193000,
+        "000000000000059f452a5f7340de6682a977387c17010ff6e6c3bd83ca8b1317",
+    ),
+    (
+        210000,
+        "000000000000048b95347e83192f69cf0366076336c639f9b7228e9ba171342e",
+    ),
+    (
+        216116,
+        "00000000000001b4f4b433e81ee46494af945cf96014816a4e2370f11b23df4e",
+    ),
+    (
+        225430,
+        "00000000000001c108384350f74090433e7fcf79a606b8e797f065b130575932",
+    ),
+    (
+        250000,
+        "000000000000003887df1f29024b06fc2200b55f8af8f35453d7be294df2d214",
+    ),
+    (
+        279000,
+        "0000000000000001ae8c72a0b0c301f67e3afca10e819efa9041e458e9bd7e40",
+    ),
+    (
+        295000,
+        "00000000000000004d9b4ef50f0f9d686fd69db2e03af35a100370c64632a983",
+    ),
+];
+
+const BITCOIN_CHECKPOINTS_TESTNET: &[(u64, &'static str)] = &[(
+    546,
+    "000000002a936ca763904c3c35fce2f3556c559c0214345d31b1bcebf76acb70",
+)];
+
+/// A run of headers to download, from just after `start_height` up to and including
+/// `end_height`.  Both ends are checkpoints (or the genesis block).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpvHeaderChunk {
+    pub start_height: u64,
+    pub start_hash: Sha256dHash,
+    pub end_height: u64,
+    pub end_hash: Sha256dHash,
+}
+
+/// Downloads the headers of a single chunk over its own connection to the Bitcoin peer
+struct SpvChunkDownloader {
+    chunk: SpvHeaderChunk,
+    headers: Vec<LoneBlockHeader>,
+}
+
+impl SpvChunkDownloader {
+    fn new(chunk: SpvHeaderChunk) -> SpvChunkDownloader {
+        SpvChunkDownloader {
+            chunk,
+            headers: vec![],
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.chunk.start_height + (self.headers.len() as u64) >= self.chunk.end_height
+    }
+
+    fn last_hash(&self) -> Sha256dHash {
+        match self.headers.last() {
+            Some(header) => header.header.bitcoin_hash(),
+            None => self.chunk.start_hash.clone(),
+        }
+    }
+
+    /// Take a batch of headers that should extend the ones we have.  Each header's proof-of-work
+    /// is checked against its own difficulty bits; whether those bits are right is only checked
+    /// once the chunk joins the header chain, since that needs the preceding headers.
+    fn add_headers(&mut self, mut headers: Vec<LoneBlockHeader>) -> Result<(), btc_error> {
+        if headers.len() == 0 {
+            warn!(
+                "Peer sent no headers for chunk {}-{}",
+                self.chunk.start_height, self.chunk.end_height
+            );
+            return Err(btc_error::InvalidReply);
+        }
+        let remaining =
+            self.chunk.end_height - self.chunk.start_height - (self.headers.len() as u64);
+        headers.truncate(cmp::min(remaining, headers.len() as u64) as usize);
+
+        let first_height = self.chunk.start_height + (self.headers.len() as u64) + 1;
+        SpvClient::validate_header_integrity(first_height, &headers)?;
+        if headers[0].header.prev_blockhash != self.last_hash() {
+            warn!(
+                "Bad SPV header for block {}: does not extend chunk {}-{}",
+                first_height, self.chunk.start_height, self.chunk.end_height
+            );
+            return Err(btc_error::NoncontiguousHeader);
+        }
+        for (i, header) in headers.iter().enumerate() {
+            if !SpvClient::check_header_pow(&header.header) {
+                error!(
+                    "block {} hash {} does not meet its own difficulty target",
+                    first_height + (i as u64),
+                    header.header.bitcoin_hash()
+                );
+                return Err(btc_error::InvalidPoW);
+            }
+        }
+
+        self.headers.append(&mut headers);
+        if self.is_done() && self.last_hash() != self.chunk.end_hash {
+            error!(
+                "Header chain at block {} does not match checkpoint {} (got {})",
+                self.chunk.end_height,
+                &self.chunk.end_hash,
+                self.last_hash()
+            );
+            return Err(btc_error::InvalidPoW);
+        }
+        Ok(())
+    }
+}
+
+impl BitcoinMessageHandler for SpvChunkDownloader {
+    fn begin_session(&mut self, indexer: &mut BitcoinIndexer) -> Result<bool, btc_error> {
+        if self.is_done() {
+            return Ok(false);
+        }
+        indexer.runtime.last_getheaders_send_time = get_epoch_time_secs();
+        indexer
+            .send_getheaders(self.last_hash())
+            .and_then(|_| Ok(true))
+    }
+
+    fn handle_message(
+        &mut self,
+        indexer: &mut BitcoinIndexer,
+        msg: PeerMessage,
+    ) -> Result<bool, btc_error> {
+        match msg {
+            btc_message::NetworkMessage::Headers(block_headers) => {
+                if self.is_done() {
+                    return Ok(false);
+                }
+                self.add_headers(block_headers)?;
+                indexer.runtime.last_getheaders_send_time = 0;
+                if self.is_done() {
+                    return Ok(false);
+                }
+                indexer
+                    .send_getheaders(self.last_hash())
+                    .and_then(|_| Ok(true))
+            }
+            x => Err(btc_error::UnhandledMessage(x)),
+        }
+    }
+}
+
 pub struct SpvClient {
     pub headers_path: String,
     pub start_block_height: u64,
@@ -218,6 +422,10 @@ impl SpvClient {
         if create_flag {
             SpvClient::db_instantiate(&mut conn)?;
         }
+        if readwrite {
+            conn.execute_batch(SPV_CHUNK_SCHEMA)
+                .map_err(db_error::SqliteError)?;
+        }
 
         Ok(conn)
     }
@@ -337,6 +545,276 @@ impl SpvClient {
         return Ok(());
     }
 
+    /// Does this header's hash meet the difficulty target given by its own bits?
+    pub fn check_header_pow(header: &BlockHeader) -> bool {
+        header.bitcoin_hash().into_le() <= header.target()
+    }
+
+    /// Get the checkpoints for a network, in ascending order of height
+    pub fn get_checkpoints(network_id: BitcoinNetworkType) -> Vec<(u64, Sha256dHash)> {
+        let checkpoints = match network_id {
+            BitcoinNetworkType::Mainnet => BITCOIN_CHECKPOINTS_MAINNET,
+            BitcoinNetworkType::Testnet => BITCOIN_CHECKPOINTS_TESTNET,
+            BitcoinNetworkType::Regtest => &[],
+        };
+        checkpoints
+            .iter()
+            .map(|(height, hash_str)| {
+                (
+                    *height,
+                    Sha256dHash::from_hex(hash_str).expect("FATAL: bad checkpoint hash"),
+                )
+            })
+            .collect()
+    }
+
+    /// Split the headers between `tip_height` and the last checkpoint into chunks that each end
+    /// at a checkpoint.  The first chunk starts at `tip_hash`, the hash of the header at
+    /// `tip_height`.
+    pub fn plan_header_chunks(
+        checkpoints: &[(u64, Sha256dHash)],
+        tip_height: u64,
+        tip_hash: Sha256dHash,
+    ) -> Vec<SpvHeaderChunk> {
+        let mut chunks = vec![];
+        let mut start = (tip_height, tip_hash);
+        for (height, hash) in checkpoints.iter() {
+            if *height <= start.0 {
+                continue;
+            }
+            chunks.push(SpvHeaderChunk {
+                start_height: start.0,
+                start_hash: start.1,
+                end_height: *height,
+                end_hash: hash.clone(),
+            });
+            start = (*height, hash.clone());
+        }
+        chunks
+    }
+
+    /// Has this chunk already been downloaded and stored?
+    fn has_header_chunk(&self, chunk: &SpvHeaderChunk) -> Result<bool, btc_error> {
+        let count = query_row::<u64, _>(
+            &self.headers_db,
+            "SELECT COUNT(*) FROM chunk_headers WHERE chunk_start = ?1 AND chunk_end = ?2",
+            &[
+                &u64_to_sql(chunk.start_height)?,
+                &u64_to_sql(chunk.end_height)?,
+            ],
+        )?
+        .unwrap_or(0);
+        Ok(count == chunk.end_height - chunk.start_height)
+    }
+
+    /// Store a downloaded chunk, all at once
+    fn store_header_chunk(
+        &mut self,
+        chunk: &SpvHeaderChunk,
+        headers: &[LoneBlockHeader],
+    ) -> Result<(), btc_error> {
+        assert_eq!(
+            headers.len() as u64,
+            chunk.end_height - chunk.start_height,
+            "BUG: incomplete header chunk"
+        );
+        let tx = self.tx_begin()?;
+        for (i, header) in headers.iter().enumerate() {
+            let args: &[&dyn ToSql] = &[
+                &header.header.version,
+                &header.header.prev_blockhash,
+                &header.header.merkle_root,
+                &header.header.time,
+                &header.header.bits,
+                &header.header.nonce,
+                &u64_to_sql(chunk.start_height + (i as u64) + 1)?,
+                &u64_to_sql(chunk.start_height)?,
+                &u64_to_sql(chunk.end_height)?,
+            ];
+            tx.execute(
+                "INSERT OR REPLACE INTO chunk_headers
+                (version, prev_blockhash, merkle_root, time, bits, nonce, height, chunk_start, chunk_end)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                args,
+            )
+            .map_err(db_error::SqliteError)?;
+        }
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Move stored chunks into the header chain, for as long as the next chunk starts at the
+    /// chain tip, and check the difficulty of the headers moved.  Chunks the header chain has
+    /// already passed are discarded.
+    fn promote_header_chunks(&mut self) -> Result<(), btc_error> {
+        loop {
+            let tip_height = self.get_highest_header_height()?;
+            let tip_hash = match self.read_block_header(tip_height)? {
+                Some(header) => header.header.bitcoin_hash(),
+                None => return Err(btc_error::MissingHeader),
+            };
+
+            let tx = self.tx_begin()?;
+            tx.execute(
+                "DELETE FROM chunk_headers WHERE chunk_start < ?1",
+                &[&u64_to_sql(tip_height)?],
+            )
+            .map_err(db_error::SqliteError)?;
+            let chunk_end = query_row::<u64, _>(
+                &tx,
+                "SELECT chunk_end FROM chunk_headers WHERE height = ?1 AND prev_blockhash = ?2",
+                &[
+                    &u64_to_sql(tip_height + 1)? as &dyn ToSql,
+                    &tip_hash as &dyn ToSql,
+                ],
+            )?;
+            let chunk_end = match chunk_end {
+                Some(chunk_end) => chunk_end,
+                None => {
+                    tx.commit().map_err(db_error::SqliteError)?;
+                    return Ok(());
+                }
+            };
+            tx.execute(
+                "INSERT OR REPLACE INTO headers
+                (version, prev_blockhash, merkle_root, time, bits, nonce, height)
+                SELECT version, prev_blockhash, merkle_root, time, bits, nonce, height
+                FROM chunk_headers WHERE chunk_start = ?1",
+                &[&u64_to_sql(tip_height)?],
+            )
+            .map_err(db_error::SqliteError)?;
+            tx.execute(
+                "DELETE FROM chunk_headers WHERE chunk_start = ?1",
+                &[&u64_to_sql(tip_height)?],
+            )
+            .map_err(db_error::SqliteError)?;
+            tx.commit().map_err(db_error::SqliteError)?;
+
+            let interval_start = cmp::max(1, (tip_height + 1) / BLOCK_DIFFICULTY_CHUNK_SIZE);
+            let interval_end = chunk_end / BLOCK_DIFFICULTY_CHUNK_SIZE + 1;
+            if let Err(e) = self.validate_header_work(interval_start, interval_end) {
+                error!(
+                    "Downloaded headers {}-{} failed difficulty checks",
+                    tip_height + 1,
+                    chunk_end
+                );
+                self.drop_headers(tip_height)?;
+                return Err(e);
+            }
+            info!(
+                "Syncing Bitcoin headers: have all headers up to {}",
+                chunk_end
+            );
+        }
+    }
+
+    /// Download all headers up to the last checkpoint for this network, using up to
+    /// `num_workers` connections to the peer at once.  Each worker fetches chunks of headers that
+    /// end at a checkpoint, and each chunk is stored as soon as it is complete, so a restarted
+    /// sync only needs to fetch the chunks it is missing.  Stored chunks join the header chain in
+    /// order.  A chunk that fails to download is left for the usual one-peer header sync to fill
+    /// in.  Returns the height of the highest header we have afterwards.
+    pub fn sync_checkpointed_headers(
+        &mut self,
+        indexer: &BitcoinIndexer,
+        num_workers: usize,
+    ) -> Result<u64, btc_error> {
+        self.promote_header_chunks()?;
+
+        let tip_height = self.get_highest_header_height()?;
+        let tip_hash = match self.read_block_header(tip_height)? {
+            Some(header) => header.header.bitcoin_hash(),
+            None => return Err(btc_error::MissingHeader),
+        };
+        let mut pending = vec![];
+        for chunk in SpvClient::plan_header_chunks(
+            &SpvClient::get_checkpoints(self.network_id),
+            tip_height,
+            tip_hash,
+        )
+        .into_iter()
+        {
+            if !self.has_header_chunk(&chunk)? {
+                pending.push(chunk);
+            }
+        }
+        if pending.len() == 0 {
+            return Ok(tip_height);
+        }
+
+        info!(
+            "Syncing Bitcoin headers: download {} checkpointed chunk(s) from {} up to {}",
+            pending.len(),
+            tip_height,
+            pending[pending.len() - 1].end_height
+        );
+
+        let num_workers = cmp::max(1, cmp::min(num_workers, pending.len()));
+        let queue = Arc::new(Mutex::new(VecDeque::from(pending)));
+        let (result_tx, result_rx) = channel();
+        let mut workers = vec![];
+        for i in 0..num_workers {
+            let queue = queue.clone();
+            let result_tx = result_tx.clone();
+            let mut worker_indexer = indexer.dup();
+            let worker = thread::Builder::new()
+                .name(format!("spv-chunk-{}", i))
+                .spawn(move || loop {
+                    let chunk = match queue.lock() {
+                        Ok(mut queue) => match queue.pop_front() {
+                            Some(chunk) => chunk,
+                            None => break,
+                        },
+                        Err(_) => break,
+                    };
+                    let mut downloader = SpvChunkDownloader::new(chunk.clone());
+                    let result = worker_indexer
+                        .peer_communicate(&mut downloader, true)
+                        .and_then(|_| {
+                            if downloader.is_done() {
+                                Ok(downloader.headers)
+                            } else {
+                                Err(btc_error::InvalidReply)
+                            }
+                        });
+                    if result_tx.send((chunk, result)).is_err() {
+                        break;
+                    }
+                })
+                .expect("FATAL: failed to start SPV header download thread");
+            workers.push(worker);
+        }
+        drop(result_tx);
+
+        // store chunks as they arrive, and add them to the header chain as soon as they can be
+        for (chunk, result) in result_rx.iter() {
+            match result {
+                Ok(headers) => {
+                    debug!(
+                        "Downloaded headers {}-{}",
+                        chunk.start_height + 1,
+                        chunk.end_height
+                    );
+                    self.store_header_chunk(&chunk, &headers)?;
+                    self.promote_header_chunks()?;
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to download headers {}-{}: {:?}",
+                        chunk.start_height + 1,
+                        chunk.end_height,
+                        &e
+                    );
+                }
+            }
+        }
+        for worker in workers.into_iter() {
+            let _ = worker.join();
+        }
+
+        self.get_highest_header_height()
+    }
+
     /// Report how many block headers (+ 1) we have downloaded to the given path.
     pub fn get_headers_height(&self) -> Result<u64, btc_error> {
         let max = self.get_highest_header_height()?;
@@ -1293,6 +1771,132 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn test_spv_header_chunks() {
+        if fs::metadata("/tmp/test-spv-header_chunks.dat").is_ok() {
+            fs::remove_file("/tmp/test-spv-header_chunks.dat").unwrap();
+        }
+        let genesis_regtest_header = get_genesis_regtest_header();
+        let headers = vec![
+            LoneBlockHeader {
+                header: BlockHeader {
+                    bits: 545259519,
+                    merkle_root: Sha256dHash::from_hex(
+                        "20bee96458517fc5082a9720ce6207b5742f2b18e4e0a7e7373342725d80f88c",
+                    )
+                    .unwrap(),
+                    nonce: 2,
+                    prev_blockhash: Sha256dHash::from_hex(
+                        "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206",
+                    )
+                    .unwrap(),
+                    time: 1587626881,
+                    version: 0x20000000,
+                },
+                tx_count: VarInt(0),
+            },
+            LoneBlockHeader {
+                header: BlockHeader {
+                    bits: 545259519,
+                    merkle_root: Sha256dHash::from_hex(
+                        "39d1a6f1ee7a5903797f92ec89e4c58549013f38114186fc2eb6e5218cb2d0ac",
+                    )
+                    .unwrap(),
+                    nonce: 1,
+                    prev_blockhash: Sha256dHash::from_hex(
+                        "606d31daaaa5919f3720d8440dd99d31f2a4e4189c65879f19ae43268425e74b",
+                    )
+                    .unwrap(),
+                    time: 1587626882,
+                    version: 0x20000000,
+                },
+                tx_count: VarInt(0),
+            },
+            LoneBlockHeader {
+                header: BlockHeader {
+                    bits: 545259519,
+                    merkle_root: Sha256dHash::from_hex(
+                        "a7e04ed25f589938eb5627abb7b5913dd77b8955bcdf72d7f111d0a71e346e47",
+                    )
+                    .unwrap(),
+                    nonce: 4,
+                    prev_blockhash: Sha256dHash::from_hex(
+                        "2fa2f451ac27f0e5cd3760ba6cdf34ef46adb76a44d96bc0f3bf3e713dd955f0",
+                    )
+                    .unwrap(),
+                    time: 1587626882,
+                    version: 0x20000000,
+                },
+                tx_count: VarInt(0),
+            },
+        ];
+        for header in headers.iter() {
+            assert!(SpvClient::check_header_pow(&header.header));
+        }
+
+        // chunks: 0-1, 1-3
+        let genesis_hash = genesis_regtest_header.header.bitcoin_hash();
+        let checkpoints = vec![
+            (1, headers[0].header.bitcoin_hash()),
+            (3, headers[2].header.bitcoin_hash()),
+        ];
+        let chunks = SpvClient::plan_header_chunks(&checkpoints, 0, genesis_hash.clone());
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].start_hash, genesis_hash);
+        assert_eq!(chunks[1].start_height, 1);
+        assert_eq!(chunks[1].end_height, 3);
+        assert_eq!(
+            SpvClient::plan_header_chunks(&checkpoints, 1, checkpoints[0].1.clone()),
+            vec![chunks[1].clone()]
+        );
+        assert_eq!(
+            SpvClient::plan_header_chunks(&checkpoints, 3, checkpoints[1].1.clone()),
+            vec![]
+        );
+        assert_eq!(
+            SpvClient::get_checkpoints(BitcoinNetworkType::Mainnet).len(),
+            13
+        );
+
+        // headers must extend the chunk and end at its checkpoint
+        let mut downloader = SpvChunkDownloader::new(chunks[1].clone());
+        assert!(downloader.add_headers(vec![headers[0].clone()]).is_err());
+        assert!(downloader.add_headers(vec![]).is_err());
+        downloader.add_headers(headers[1..].to_vec()).unwrap();
+        assert!(downloader.is_done());
+
+        let mut wrong_end = chunks[1].clone();
+        wrong_end.end_hash = genesis_hash.clone();
+        let mut downloader = SpvChunkDownloader::new(wrong_end);
+        assert!(downloader.add_headers(headers[1..].to_vec()).is_err());
+
+        // chunks join the header chain in order
+        let mut spv_client = SpvClient::new(
+            "/tmp/test-spv-header_chunks.dat",
+            0,
+            None,
+            BitcoinNetworkType::Regtest,
+            true,
+            false,
+        )
+        .unwrap();
+        spv_client
+            .store_header_chunk(&chunks[1], &headers[1..])
+            .unwrap();
+        assert!(spv_client.has_header_chunk(&chunks[1]).unwrap());
+        assert!(!spv_client.has_header_chunk(&chunks[0]).unwrap());
+        spv_client.promote_header_chunks().unwrap();
+        assert_eq!(spv_client.get_highest_header_height().unwrap(), 0);
+
+        spv_client
+            .store_header_chunk(&chunks[0], &headers[0..1])
+            .unwrap();
+        spv_client.promote_header_chunks().unwrap();
+        assert_eq!(spv_client.get_highest_header_height().unwrap(), 3);
+        assert_eq!(spv_client.read_block_headers(1, 4).unwrap(), headers);
+        assert!(!spv_client.has_header_chunk(&chunks[1]).unwrap());
+    }
+
     #[test]
     fn test_spv_check_pow() {
         if !env::var("BLOCKSTACK_SPV_HEADERS_DB").is_ok() {
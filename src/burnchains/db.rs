@@ -34,6 +34,7 @@ use util::db::{
 use crate::types::chainstate::BurnchainHeaderHash;
 use crate::types::proof::ClarityMarfTrieId;
 
+#[derive(Debug)]
 pub struct BurnchainDB {
     conn: Connection,
 }
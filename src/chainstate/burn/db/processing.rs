@@ -95,23 +95,28 @@ impl<'a> SortitionHandleTx<'a> {
         }
     }
 
-    /// Process all block's checked transactions
-    /// * make the burn distribution
-    /// * insert the ones that went into the burn distribution
-    /// * snapshot the block and run the sortition
-    /// * return the snapshot (and sortition results)
-    fn process_checked_block_ops(
+    /// Make the burn distribution for a block's checked transactions, snapshot the block, and
+    /// run the sortition.  Nothing is stored.
+    /// Returns the snapshot (without its index root), the sortition results, and the initial
+    /// mining bonus if this is the first sortition with a winner.
+    fn make_checked_block_snapshot(
         &mut self,
         burnchain: &Burnchain,
         parent_snapshot: &BlockSnapshot,
         block_header: &BurnchainBlockHeader,
         this_block_ops: &Vec<BlockstackOperationType>,
         missed_commits: &Vec<MissedBlockCommit>,
-        next_pox_info: Option<RewardCycleInfo>,
+        next_pox_info: Option<&RewardCycleInfo>,
         parent_pox: PoxId,
-        reward_info: Option<&RewardSetInfo>,
         initial_mining_bonus_ustx: u128,
-    ) -> Result<(BlockSnapshot, BurnchainStateTransition), BurnchainError> {
+    ) -> Result<
+        (
+            BlockSnapshot,
+            BurnchainStateTransition,
+            Option<InitialMiningBonus>,
+        ),
+        BurnchainError,
+    > {
         let this_block_height = block_header.block_height;
         let this_block_hash = block_header.block_hash.clone();
 
@@ -145,7 +150,7 @@ impl<'a> SortitionHandleTx<'a> {
             .collect();
 
         let mut next_pox = parent_pox;
-        if let Some(ref next_pox_info) = next_pox_info {
+        if let Some(next_pox_info) = next_pox_info {
             if next_pox_info.is_reward_info_known() {
                 debug!(
                     "Begin reward-cycle sortition with present anchor block={:?}",
@@ -215,6 +220,38 @@ impl<'a> SortitionHandleTx<'a> {
             None
         };
 
+        Ok((snapshot, state_transition, initialize_bonus))
+    }
+
+    /// Process all block's checked transactions
+    /// * make the burn distribution
+    /// * insert the ones that went into the burn distribution
+    /// * snapshot the block and run the sortition
+    /// * return the snapshot (and sortition results)
+    fn process_checked_block_ops(
+        &mut self,
+        burnchain: &Burnchain,
+        parent_snapshot: &BlockSnapshot,
+        block_header: &BurnchainBlockHeader,
+        this_block_ops: &Vec<BlockstackOperationType>,
+        missed_commits: &Vec<MissedBlockCommit>,
+        next_pox_info: Option<RewardCycleInfo>,
+        parent_pox: PoxId,
+        reward_info: Option<&RewardSetInfo>,
+        initial_mining_bonus_ustx: u128,
+    ) -> Result<(BlockSnapshot, BurnchainStateTransition), BurnchainError> {
+        let this_block_height = block_header.block_height;
+        let (mut snapshot, state_transition, initialize_bonus) = self.make_checked_block_snapshot(
+            burnchain,
+            parent_snapshot,
+            block_header,
+            this_block_ops,
+            missed_commits,
+            next_pox_info.as_ref(),
+            parent_pox,
+            initial_mining_bonus_ustx,
+        )?;
+
         // store the snapshot
         let index_root = self.append_chain_tip_snapshot(
             parent_snapshot,
@@ -244,6 +281,81 @@ impl<'a> SortitionHandleTx<'a> {
         Ok((snapshot, state_transition))
     }
 
+    /// Check a block's blockstack operations against the sortition history, and keep only the
+    /// valid ones (sorted by vtxindex).
+    /// Returns the valid operations, and the block-commits that missed their target block.
+    fn check_block_ops(
+        &mut self,
+        burnchain: &Burnchain,
+        mut blockstack_txs: Vec<BlockstackOperationType>,
+        reward_set_info: Option<&RewardSetInfo>,
+    ) -> (Vec<BlockstackOperationType>, Vec<MissedBlockCommit>) {
+        blockstack_txs.sort_by(|ref a, ref b| a.vtxindex().partial_cmp(&b.vtxindex()).unwrap());
+
+        // check each transaction, and filter out only the ones that are valid
+        debug!(
+            "Check Blockstack transactions from sortition_id: {}",
+            &self.context.chain_tip
+        );
+
+        let mut missed_block_commits = vec![];
+
+        // classify and check each transaction
+        blockstack_txs.retain(|blockstack_op| {
+            match self.check_transaction(burnchain, blockstack_op, reward_set_info) {
+                Ok(_) => true,
+                Err(BurnchainError::OpError(OpError::MissedBlockCommit(missed_op))) => {
+                    missed_block_commits.push(missed_op);
+                    false
+                }
+                Err(_) => false,
+            }
+        });
+
+        // block-wide check: no duplicate keys registered
+        let block_ops = Burnchain::filter_block_VRF_dups(blockstack_txs);
+        assert!(Burnchain::ops_are_sorted(&block_ops));
+
+        (block_ops, missed_block_commits)
+    }
+
+    /// Re-run the sortition for a block whose parent has already been processed, and return the
+    /// snapshot it produces, without storing anything.  Used to audit the sortition DB.
+    /// The returned snapshot's index_root is not set, since it's only known once the snapshot is
+    /// stored.
+    pub fn replay_block_ops(
+        &mut self,
+        burnchain: &Burnchain,
+        parent_snapshot: &BlockSnapshot,
+        block_header: &BurnchainBlockHeader,
+        blockstack_txs: Vec<BlockstackOperationType>,
+        next_pox_info: Option<&RewardCycleInfo>,
+        parent_pox: PoxId,
+        reward_set_info: Option<&RewardSetInfo>,
+        initial_mining_bonus_ustx: u128,
+    ) -> Result<BlockSnapshot, BurnchainError> {
+        assert_eq!(parent_snapshot.block_height + 1, block_header.block_height);
+        assert_eq!(
+            parent_snapshot.burn_header_hash,
+            block_header.parent_block_hash
+        );
+
+        let (block_ops, missed_block_commits) =
+            self.check_block_ops(burnchain, blockstack_txs, reward_set_info);
+
+        let (snapshot, _, _) = self.make_checked_block_snapshot(
+            burnchain,
+            parent_snapshot,
+            block_header,
+            &block_ops,
+            &missed_block_commits,
+            next_pox_info,
+            parent_pox,
+            initial_mining_bonus_ustx,
+        )?;
+        Ok(snapshot)
+    }
+
     /// Check and then commit all blockstack operations to our chainstate.
     /// * pull out all the transactions that are blockstack ops
     /// * select the ones that are _valid_
@@ -256,7 +368,7 @@ impl<'a> SortitionHandleTx<'a> {
         burnchain: &Burnchain,
         parent_snapshot: &BlockSnapshot,
         block_header: &BurnchainBlockHeader,
-        mut blockstack_txs: Vec<BlockstackOperationType>,
+        blockstack_txs: Vec<BlockstackOperationType>,
         next_pox_info: Option<RewardCycleInfo>,
         parent_pox: PoxId,
         reward_set_info: Option<&RewardSetInfo>,
@@ -276,31 +388,8 @@ impl<'a> SortitionHandleTx<'a> {
             &block_header.block_hash
         );
 
-        blockstack_txs.sort_by(|ref a, ref b| a.vtxindex().partial_cmp(&b.vtxindex()).unwrap());
-
-        // check each transaction, and filter out only the ones that are valid
-        debug!(
-            "Check Blockstack transactions from sortition_id: {}",
-            &self.context.chain_tip
-        );
-
-        let mut missed_block_commits = vec![];
-
-        // classify and check each transaction
-        blockstack_txs.retain(|blockstack_op| {
-            match self.check_transaction(burnchain, blockstack_op, reward_set_info) {
-                Ok(_) => true,
-                Err(BurnchainError::OpError(OpError::MissedBlockCommit(missed_op))) => {
-                    missed_block_commits.push(missed_op);
-                    false
-                }
-                Err(_) => false,
-            }
-        });
-
-        // block-wide check: no duplicate keys registered
-        let block_ops = Burnchain::filter_block_VRF_dups(blockstack_txs);
-        assert!(Burnchain::ops_are_sorted(&block_ops));
+        let (block_ops, missed_block_commits) =
+            self.check_block_ops(burnchain, blockstack_txs, reward_set_info);
 
         // process them
         let res = self
@@ -21,6 +21,7 @@ use std::convert::{From, TryFrom, TryInto};
 use std::io::{ErrorKind, Write};
 use std::ops::Deref;
 use std::ops::DerefMut;
+use std::sync::{Mutex, MutexGuard};
 use std::{cmp, fmt, fs, str::FromStr};
 
 use rand;
@@ -66,8 +67,9 @@ use util::db::tx_begin_immediate;
 use util::db::tx_busy_handler;
 use util::db::Error as db_error;
 use util::db::{
-    db_mkdirs, query_count, query_row, query_row_columns, query_row_panic, query_rows, sql_pragma,
-    u64_to_sql, DBConn, FromColumn, FromRow, IndexDBConn, IndexDBTx,
+    db_mkdirs, get_ancestor_by_skips, insert_ancestor_skip, query_count, query_row,
+    query_row_columns, query_row_panic, query_rows, sql_pragma, table_exists, u64_to_sql, DBConn,
+    FromColumn, FromRow, IndexDBConn, IndexDBTx, ANCESTOR_SKIPS_SCHEMA,
 };
 use util::get_epoch_time_secs;
 use util::hash::{hex_bytes, to_hex, Hash160, Sha512Trunc256Sum};
@@ -405,6 +407,71 @@ pub struct InitialMiningBonus {
     pub per_block: u128,
 }
 
+/// Summary of the burn amounts spent by the winning block-commits in a range of sortitions
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BurnAmountDistribution {
+    pub count: u64,
+    pub min: u64,
+    pub max: u64,
+    pub mean: u64,
+    pub p25: u64,
+    pub median: u64,
+    pub p75: u64,
+    pub p95: u64,
+}
+
+impl BurnAmountDistribution {
+    /// Summarize a list of burn amounts.  The percentiles are nearest-rank.
+    pub fn from_amounts(mut amounts: Vec<u64>) -> BurnAmountDistribution {
+        if amounts.len() == 0 {
+            return BurnAmountDistribution::default();
+        }
+        amounts.sort();
+        let percentile = |p: usize| amounts[((amounts.len() * p + 99) / 100).saturating_sub(1)];
+        let total: u128 = amounts.iter().map(|amt| *amt as u128).sum();
+        BurnAmountDistribution {
+            count: amounts.len() as u64,
+            min: amounts[0],
+            max: amounts[amounts.len() - 1],
+            mean: (total / (amounts.len() as u128)) as u64,
+            p25: percentile(25),
+            median: percentile(50),
+            p75: percentile(75),
+            p95: percentile(95),
+        }
+    }
+}
+
+/// A run of burnchain blocks we processed which is no longer part of the canonical burnchain fork
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurnchainReorg {
+    /// height of the first orphaned block (i.e. one more than the height of the fork point)
+    pub fork_height: u64,
+    /// how many orphaned blocks there were
+    pub depth: u64,
+    /// the last orphaned block
+    pub orphaned_tip: BurnchainHeaderHash,
+}
+
+/// Sortition statistics over a range of burnchain blocks on the canonical burnchain fork
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortitionStats {
+    pub start_height: u64,
+    pub end_height: u64,
+    pub num_blocks: u64,
+    /// blocks in which a block-commit won sortition
+    pub num_sortitions: u64,
+    /// blocks with no sortition at all, usually because they arrived too quickly after their
+    /// parents for any block-commits to get into them
+    pub num_flash_blocks: u64,
+    /// sortitions whose winning Stacks block was never processed
+    pub num_missed_sortitions: u64,
+    pub winning_burn_amounts: BurnAmountDistribution,
+    /// burnchain reorgs whose orphaned blocks start in this range, in ascending height order.
+    /// Orphaned blocks above the end of the range are not counted.
+    pub reorgs: Vec<BurnchainReorg>,
+}
+
 impl FromRow<AcceptedStacksBlockHeader> for AcceptedStacksBlockHeader {
     fn from_row<'a>(row: &'a Row) -> Result<AcceptedStacksBlockHeader, db_error> {
         let tip_consensus_hash = ConsensusHash::from_column(row, "tip_consensus_hash")?;
@@ -442,7 +509,7 @@ impl FromRow<StacksEpoch> for StacksEpoch {
     }
 }
 
-pub const SORTITION_DB_VERSION: &'static str = "2";
+pub const SORTITION_DB_VERSION: &'static str = "3";
 
 const SORTITION_DB_INITIAL_SCHEMA: &'static [&'static str] = &[
     r#"
@@ -616,6 +683,8 @@ const SORTITION_DB_SCHEMA_2: &'static [&'static str] = &[r#"
          PRIMARY KEY(start_block_height,epoch_id)
      );"#];
 
+const SORTITION_DB_SCHEMA_3: &'static [&'static str] = &[ANCESTOR_SKIPS_SCHEMA];
+
 pub struct SortitionDB {
     pub readwrite: bool,
     pub marf: MARF<SortitionId>,
@@ -626,12 +695,148 @@ pub struct SortitionDB {
 #[derive(Clone)]
 pub struct SortitionDBTxContext {
     pub first_block_height: u64,
+    pub burn_state_cache: BurnStateCache,
 }
 
 #[derive(Clone)]
 pub struct SortitionHandleContext {
     pub first_block_height: u64,
     pub chain_tip: SortitionId,
+    pub burn_state_cache: BurnStateCache,
+}
+
+/// Upper bound on the number of burn block heights, and of burn header hashes, a BurnStateCache
+/// will remember before it starts over.
+pub const BURN_STATE_CACHE_MAX_ENTRIES: usize = 4096;
+
+#[derive(Debug, Clone, Default)]
+struct BurnStateCacheInner {
+    epochs: Option<Vec<StacksEpoch>>,
+    burn_block_heights: HashMap<SortitionId, u32>,
+    burn_header_hashes: HashMap<(SortitionId, u32), BurnchainHeaderHash>,
+}
+
+/// Memoized answers to the `BurnStateDB` queries that Clarity makes while evaluating a block.
+/// Only positive answers are cached.  A snapshot's height and the burn header hashes of its
+/// ancestors never change once the snapshot exists, and the epochs table is only written when
+/// the DB is instantiated, so nothing in here can go stale while its handle is open.
+#[derive(Debug, Default)]
+pub struct BurnStateCache {
+    inner: Mutex<BurnStateCacheInner>,
+}
+
+impl Clone for BurnStateCache {
+    fn clone(&self) -> BurnStateCache {
+        BurnStateCache {
+            inner: Mutex::new(self.lock().clone()),
+        }
+    }
+}
+
+impl BurnStateCache {
+    pub fn new() -> BurnStateCache {
+        BurnStateCache::default()
+    }
+
+    fn lock(&self) -> MutexGuard<BurnStateCacheInner> {
+        self.inner
+            .lock()
+            .expect("FATAL: burn state cache mutex poisoned")
+    }
+
+    /// Get the burn block height of a sortition, calling `load` on a cache miss.
+    pub fn get_burn_block_height<F>(&self, sortition_id: &SortitionId, load: F) -> Option<u32>
+    where
+        F: FnOnce() -> Option<u32>,
+    {
+        if let Some(height) = self.lock().burn_block_heights.get(sortition_id) {
+            return Some(*height);
+        }
+        let height = load()?;
+        let mut inner = self.lock();
+        if inner.burn_block_heights.len() >= BURN_STATE_CACHE_MAX_ENTRIES {
+            inner.burn_block_heights.clear();
+        }
+        inner
+            .burn_block_heights
+            .insert(sortition_id.clone(), height);
+        Some(height)
+    }
+
+    /// Get the burn header hash at `height` in the fork identified by `sortition_id`, calling
+    /// `load` on a cache miss.
+    pub fn get_burn_header_hash<F>(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+        load: F,
+    ) -> Option<BurnchainHeaderHash>
+    where
+        F: FnOnce() -> Option<BurnchainHeaderHash>,
+    {
+        let key = (sortition_id.clone(), height);
+        if let Some(burn_header_hash) = self.lock().burn_header_hashes.get(&key) {
+            return Some(burn_header_hash.clone());
+        }
+        let burn_header_hash = load()?;
+        let mut inner = self.lock();
+        if inner.burn_header_hashes.len() >= BURN_STATE_CACHE_MAX_ENTRIES {
+            inner.burn_header_hashes.clear();
+        }
+        inner
+            .burn_header_hashes
+            .insert(key, burn_header_hash.clone());
+        Some(burn_header_hash)
+    }
+
+    /// Run `f` over all epochs, calling `load` to read them the first time they are needed.
+    fn with_epochs<F, G, R>(&self, load: F, f: G) -> R
+    where
+        F: FnOnce() -> Vec<StacksEpoch>,
+        G: FnOnce(&[StacksEpoch]) -> R,
+    {
+        let mut inner = self.lock();
+        if inner.epochs.is_none() {
+            inner.epochs = Some(load());
+        }
+        f(inner
+            .epochs
+            .as_ref()
+            .expect("unreachable: epochs were just loaded"))
+    }
+
+    /// Get the epoch that contains the given burn block height, calling `load` to read the
+    /// epochs table the first time it is needed.
+    pub fn get_stacks_epoch<F>(&self, height: u32, load: F) -> Option<StacksEpoch>
+    where
+        F: FnOnce() -> Vec<StacksEpoch>,
+    {
+        let height = height as u64;
+        self.with_epochs(load, |epochs| {
+            epochs
+                .iter()
+                .find(|epoch| epoch.start_height <= height && height < epoch.end_height)
+                .cloned()
+        })
+    }
+
+    /// Get the epoch with the given ID, calling `load` to read the epochs table the first time
+    /// it is needed.
+    pub fn get_stacks_epoch_by_epoch_id<F>(
+        &self,
+        epoch_id: &StacksEpochId,
+        load: F,
+    ) -> Option<StacksEpoch>
+    where
+        F: FnOnce() -> Vec<StacksEpoch>,
+    {
+        self.with_epochs(load, |epochs| {
+            epochs
+                .iter()
+                .find(|epoch| epoch.epoch_id == *epoch_id)
+                .cloned()
+        })
+    }
 }
 
 pub type SortitionDBConn<'a> = IndexDBConn<'a, SortitionDBTxContext, SortitionId>;
@@ -685,6 +890,9 @@ fn get_ancestor_sort_id<C: SortitionContext>(
         None => return Ok(None),
     };
 
+    if let Some(ancestor) = get_ancestor_by_skips(ic.conn(), tip_block_hash, adjusted_height)? {
+        return Ok(Some(ancestor));
+    }
     ic.get_ancestor_block_hash(adjusted_height, &tip_block_hash)
 }
 
@@ -698,6 +906,9 @@ fn get_ancestor_sort_id_tx<C: SortitionContext>(
         None => return Ok(None),
     };
 
+    if let Some(ancestor) = get_ancestor_by_skips(ic.tx(), tip_block_hash, adjusted_height)? {
+        return Ok(Some(ancestor));
+    }
     ic.get_ancestor_block_hash(adjusted_height, &tip_block_hash)
 }
 
@@ -807,6 +1018,7 @@ impl<'a> SortitionHandleTx<'a> {
             SortitionHandleContext {
                 chain_tip: parent_chain_tip.clone(),
                 first_block_height: conn.first_block_height,
+                burn_state_cache: BurnStateCache::new(),
             },
         );
 
@@ -1486,6 +1698,7 @@ impl<'a> SortitionHandleConn<'a> {
             context: SortitionHandleContext {
                 chain_tip: chain_tip.clone(),
                 first_block_height: connection.context.first_block_height,
+                burn_state_cache: connection.context.burn_state_cache.clone(),
             },
             index: &connection.index,
         })
@@ -1970,6 +2183,7 @@ impl SortitionDB {
             &mut self.marf,
             SortitionDBTxContext {
                 first_block_height: self.first_block_height,
+                burn_state_cache: BurnStateCache::new(),
             },
         );
         Ok(index_tx)
@@ -1981,6 +2195,7 @@ impl SortitionDB {
             &self.marf,
             SortitionDBTxContext {
                 first_block_height: self.first_block_height,
+                burn_state_cache: BurnStateCache::new(),
             },
         )
     }
@@ -1991,6 +2206,7 @@ impl SortitionDB {
             SortitionHandleContext {
                 first_block_height: self.first_block_height,
                 chain_tip: chain_tip.clone(),
+                burn_state_cache: BurnStateCache::new(),
             },
         )
     }
@@ -2008,6 +2224,7 @@ impl SortitionDB {
             SortitionHandleContext {
                 first_block_height: self.first_block_height,
                 chain_tip: chain_tip.clone(),
+                burn_state_cache: BurnStateCache::new(),
             },
         ))
     }
@@ -2201,42 +2418,30 @@ impl SortitionDB {
         Ok(db)
     }
 
-    /// Validate all Stacks Epochs. Since this is data that always comes from a static variable,
-    /// any invalid StacksEpoch structuring should result in a runtime panic.
+    /// Validate all Stacks Epochs. Since this is data that always comes from a static variable
+    /// or an already-validated config file, any invalid StacksEpoch structuring should result in
+    /// a runtime panic.
     fn validate_epochs(epochs_ref: &[StacksEpoch]) -> Vec<StacksEpoch> {
-        // sanity check -- epochs must all be contiguous, each epoch must be unique,
-        // and the range of epochs should span the whole non-negative i64 space.
-        let mut epochs = epochs_ref.to_vec();
-        let mut seen_epochs = HashSet::new();
-        epochs.sort();
-
-        let mut epoch_end_height = 0;
-        for epoch in epochs.iter() {
-            assert!(
-                epoch.start_height <= epoch.end_height,
-                "{} <= {} for {:?}",
-                epoch.start_height,
-                epoch.end_height,
-                &epoch.epoch_id
-            );
-
-            if epoch_end_height == 0 {
-                // first ever epoch must be defined for all of the prior chain history
-                assert_eq!(epoch.start_height, 0);
-                epoch_end_height = epoch.end_height;
-            } else {
-                assert_eq!(epoch_end_height, epoch.start_height);
-                epoch_end_height = epoch.end_height;
-            }
-            if seen_epochs.contains(&epoch.epoch_id) {
-                panic!("BUG: duplicate epoch");
-            }
-
-            seen_epochs.insert(epoch.epoch_id);
+        match StacksEpoch::validate_epochs(epochs_ref) {
+            Ok(epochs) => epochs,
+            Err(msg) => panic!("BUG: invalid epochs: {}", msg),
         }
+    }
 
-        assert_eq!(epoch_end_height, STACKS_EPOCH_MAX);
-        epochs
+    /// Get the epochs recorded in the sortition DB, given the DB path, if the DB has them.
+    /// Importantly, this will *not* apply any schema migrations.
+    /// This is used to check that a node's configured epochs match the ones its DB was
+    /// instantiated with.
+    pub fn get_stacks_epochs_from_path(path: &str) -> Result<Option<Vec<StacksEpoch>>, db_error> {
+        if fs::metadata(path).is_err() {
+            return Err(db_error::NoDBError);
+        }
+        let index_path = db_mkdirs(path)?;
+        let marf = SortitionDB::open_index(&index_path)?;
+        if !table_exists(marf.sqlite_conn(), "epochs")? {
+            return Ok(None);
+        }
+        Ok(Some(SortitionDB::get_stacks_epochs(marf.sqlite_conn())?))
     }
 
     fn instantiate(
@@ -2273,6 +2478,9 @@ impl SortitionDB {
         for row_text in SORTITION_DB_SCHEMA_2 {
             db_tx.execute_batch(row_text)?;
         }
+        for row_text in SORTITION_DB_SCHEMA_3 {
+            db_tx.execute_batch(row_text)?;
+        }
 
         SortitionDB::validate_and_insert_epochs(&db_tx, epochs_ref)?;
 
@@ -2354,6 +2562,10 @@ impl SortitionDB {
             db_tx.execute_batch(row_text)?;
         }
 
+        // insert_block_snapshot() always maintains the ancestor skip index.  The table is
+        // created idempotently, so a later migration to schema 3 is unaffected.
+        db_tx.execute_batch(ANCESTOR_SKIPS_SCHEMA)?;
+
         db_tx.execute(
             "INSERT OR REPLACE INTO db_config (version) VALUES (?1)",
             &[&"1"],
@@ -2424,8 +2636,8 @@ impl SortitionDB {
     pub fn is_db_version_supported_in_epoch(epoch: StacksEpochId, version: &str) -> bool {
         match epoch {
             StacksEpochId::Epoch10 => false,
-            StacksEpochId::Epoch20 => (version == "1" || version == "2"),
-            StacksEpochId::Epoch2_05 => version == "2",
+            StacksEpochId::Epoch20 => (version == "1" || version == "2" || version == "3"),
+            StacksEpochId::Epoch2_05 => (version == "2" || version == "3"),
         }
     }
 
@@ -2456,6 +2668,40 @@ impl SortitionDB {
         Ok(())
     }
 
+    fn apply_schema_3(tx: &SortitionDBTx, first_block_height: u64) -> Result<(), db_error> {
+        for sql_exec in SORTITION_DB_SCHEMA_3 {
+            tx.execute_batch(sql_exec)?;
+        }
+
+        // index the ancestry of every snapshot we already have.  Parents always come before
+        // their children in height order.
+        let qry = "SELECT sortition_id, parent_sortition_id, block_height FROM snapshots ORDER BY block_height ASC";
+        let mut stmt = tx.prepare(qry)?;
+        let mut rows = stmt.query(NO_PARAMS)?;
+        let mut num_indexed = 0;
+        while let Some(row) = rows.next()? {
+            let sortition_id = SortitionId::from_column(row, "sortition_id")?;
+            let parent_sortition_id = SortitionId::from_column(row, "parent_sortition_id")?;
+            let block_height = u64::from_column(row, "block_height")?;
+            if insert_ancestor_skip(
+                tx,
+                &sortition_id,
+                block_height - first_block_height,
+                &parent_sortition_id,
+            )? {
+                num_indexed += 1;
+            }
+        }
+        debug!("Indexed the ancestry of {} snapshots", num_indexed);
+
+        tx.execute(
+            "INSERT OR REPLACE INTO db_config (version) VALUES (?1)",
+            &["3"],
+        )?;
+
+        Ok(())
+    }
+
     fn check_schema_version_or_error(&mut self) -> Result<(), db_error> {
         match SortitionDB::get_schema_version(self.conn()) {
             Ok(Some(version)) => {
@@ -2484,6 +2730,11 @@ impl SortitionDB {
                 }
                 if version == "1" {
                     SortitionDB::apply_schema_2(&tx, epochs)?;
+                    SortitionDB::apply_schema_3(&tx, self.first_block_height)?;
+                    tx.commit()?;
+                    Ok(())
+                } else if version == "2" {
+                    SortitionDB::apply_schema_3(&tx, self.first_block_height)?;
                     tx.commit()?;
                     Ok(())
                 } else {
@@ -2503,6 +2754,7 @@ impl<'a> SortitionDBConn<'a> {
             context: SortitionHandleContext {
                 first_block_height: self.context.first_block_height.clone(),
                 chain_tip: chain_tip.clone(),
+                burn_state_cache: self.context.burn_state_cache.clone(),
             },
         }
     }
@@ -2845,6 +3097,84 @@ impl SortitionDB {
         Ok((new_snapshot.0, new_snapshot.1, reward_set_info))
     }
 
+    /// Re-run the sortition for an already-processed burnchain block on top of its recorded
+    /// parent sortition, and return the snapshot that it produces.  Nothing is stored.  The
+    /// returned snapshot's index_root is not set, and its memoized Stacks chain tip fields are
+    /// those of the parent (since they get updated as Stacks blocks arrive).
+    ///
+    /// This opens a write transaction even though it writes nothing: operation checks and
+    /// snapshot construction are the same code that block processing runs, and that code reads
+    /// the sortition MARF through a `SortitionHandleTx`.  The transaction is rolled back when the
+    /// handle is dropped, but it holds the DB's write lock until then, so a replay against a
+    /// running node's DB stalls the node's burnchain processing while each sortition is re-run.
+    ///
+    /// # Arguments
+    /// * `burn_header` - the burnchain block header to re-run the sortition for
+    /// * `ops` - the parsed blockstack operations (will be validated in this function)
+    /// * `burnchain` - a reference to the burnchain information struct
+    /// * `parent_sort_id` - the recorded sortition of the burnchain block's parent
+    /// * `next_pox_info` - iff this sortition is the first block in a reward cycle, this should be Some
+    ///
+    pub fn replay_sortition(
+        &mut self,
+        burn_header: &BurnchainBlockHeader,
+        ops: Vec<BlockstackOperationType>,
+        burnchain: &Burnchain,
+        parent_sort_id: &SortitionId,
+        next_pox_info: Option<RewardCycleInfo>,
+    ) -> Result<BlockSnapshot, BurnchainError> {
+        let mut sortition_db_handle = SortitionHandleTx::begin(self, parent_sort_id)?;
+        let parent_snapshot = sortition_db_handle
+            .get_block_snapshot(&burn_header.parent_block_hash, parent_sort_id)?
+            .ok_or_else(|| {
+                warn!("Unknown block {:?}", burn_header.parent_block_hash);
+                BurnchainError::MissingParentBlock
+            })?;
+
+        let parent_pox = sortition_db_handle.get_pox_id()?;
+
+        let reward_set_vrf_hash = parent_snapshot
+            .sortition_hash
+            .mix_burn_header(&parent_snapshot.burn_header_hash);
+
+        let reward_set_info = if burn_header.block_height >= burnchain.pox_constants.sunset_end {
+            None
+        } else {
+            sortition_db_handle.pick_recipients(
+                burnchain,
+                burn_header.block_height,
+                &reward_set_vrf_hash,
+                next_pox_info.as_ref(),
+            )?
+        };
+
+        let bonus_remaining =
+            sortition_db_handle.get_initial_mining_bonus_remaining(parent_sort_id)?;
+
+        let initial_mining_bonus = if bonus_remaining > 0 {
+            let mining_bonus_per_block = sortition_db_handle
+                .get_initial_mining_bonus_per_block(parent_sort_id)?
+                .expect("BUG: initial mining bonus amount written, but not the per block amount.");
+            cmp::min(bonus_remaining, mining_bonus_per_block)
+        } else {
+            0
+        };
+
+        let snapshot = sortition_db_handle.replay_block_ops(
+            burnchain,
+            &parent_snapshot,
+            burn_header,
+            ops,
+            next_pox_info.as_ref(),
+            parent_pox,
+            reward_set_info.as_ref(),
+            initial_mining_bonus,
+        )?;
+
+        // NOTE: dropping the handle rolls back the transaction
+        Ok(snapshot)
+    }
+
     #[cfg(test)]
     pub fn test_get_next_block_recipients(
         &mut self,
@@ -3042,6 +3372,126 @@ impl SortitionDB {
         )
     }
 
+    /// Gather statistics on the sortitions and burnchain reorgs in the canonical burnchain fork
+    /// between start_height and end_height, inclusive.  The range is clipped to the canonical
+    /// burnchain tip.
+    pub fn get_sortition_stats(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<SortitionStats, db_error> {
+        let tip = SortitionDB::get_canonical_burn_chain_tip(self.conn())?;
+        let end_height = cmp::min(end_height, tip.block_height);
+
+        let mut stats = SortitionStats {
+            start_height,
+            end_height,
+            num_blocks: 0,
+            num_sortitions: 0,
+            num_flash_blocks: 0,
+            num_missed_sortitions: 0,
+            winning_burn_amounts: BurnAmountDistribution::default(),
+            reorgs: vec![],
+        };
+        if start_height > end_height {
+            return Ok(stats);
+        }
+
+        let mut canonical_hashes = HashSet::new();
+        let mut burn_amounts = vec![];
+        let ic = self.index_conn();
+        for height in start_height..(end_height + 1) {
+            let snapshot = match SortitionDB::get_ancestor_snapshot(&ic, height, &tip.sortition_id)?
+            {
+                Some(sn) => sn,
+                None => continue,
+            };
+
+            stats.num_blocks += 1;
+            if snapshot.sortition {
+                stats.num_sortitions += 1;
+                if !snapshot.stacks_block_accepted {
+                    stats.num_missed_sortitions += 1;
+                }
+                if let Some(commit) = SortitionDB::get_block_commit(
+                    self.conn(),
+                    &snapshot.winning_block_txid,
+                    &snapshot.sortition_id,
+                )? {
+                    burn_amounts.push(commit.burn_fee);
+                }
+            } else {
+                stats.num_flash_blocks += 1;
+            }
+            canonical_hashes.insert(snapshot.burn_header_hash);
+        }
+        stats.winning_burn_amounts = BurnAmountDistribution::from_amounts(burn_amounts);
+        stats.reorgs = SortitionDB::find_burnchain_reorgs(
+            self.conn(),
+            start_height,
+            end_height,
+            &canonical_hashes,
+        )?;
+        Ok(stats)
+    }
+
+    /// Find the runs of non-canonical burnchain blocks in a height range.  Each run starts with a
+    /// block whose parent is canonical (or below the range), and ends with a block with no
+    /// non-canonical children in the range.
+    fn find_burnchain_reorgs(
+        conn: &Connection,
+        start_height: u64,
+        end_height: u64,
+        canonical_hashes: &HashSet<BurnchainHeaderHash>,
+    ) -> Result<Vec<BurnchainReorg>, db_error> {
+        let qry = "SELECT DISTINCT block_height, burn_header_hash, parent_burn_header_hash FROM snapshots \
+                   WHERE block_height >= ?1 AND block_height <= ?2 ORDER BY block_height ASC";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(start_height)?, &u64_to_sql(end_height)?];
+        let mut stmt = conn.prepare(qry)?;
+        let mut rows = stmt.query(args)?;
+
+        // orphaned block hash --> (height, parent hash)
+        let mut orphans = HashMap::new();
+        while let Some(row) = rows.next()? {
+            let height = u64::from_column(row, "block_height")?;
+            let burn_header_hash = BurnchainHeaderHash::from_column(row, "burn_header_hash")?;
+            let parent_burn_header_hash =
+                BurnchainHeaderHash::from_column(row, "parent_burn_header_hash")?;
+            if !canonical_hashes.contains(&burn_header_hash) {
+                orphans.insert(burn_header_hash, (height, parent_burn_header_hash));
+            }
+        }
+
+        let orphan_parents: HashSet<_> =
+            orphans.values().map(|(_, parent)| parent.clone()).collect();
+        let mut reorgs = vec![];
+        for (orphan_hash, (height, parent)) in orphans.iter() {
+            if orphan_parents.contains(orphan_hash) {
+                // not the last block in its run
+                continue;
+            }
+            let mut fork_height = *height;
+            let mut cursor = parent;
+            while let Some((parent_height, grandparent)) = orphans.get(cursor) {
+                fork_height = *parent_height;
+                cursor = grandparent;
+            }
+            reorgs.push(BurnchainReorg {
+                fork_height,
+                depth: height - fork_height + 1,
+                orphaned_tip: orphan_hash.clone(),
+            });
+        }
+        reorgs.sort_by(|r1, r2| {
+            (r1.fork_height, r1.depth, &r1.orphaned_tip).cmp(&(
+                r2.fork_height,
+                r2.depth,
+                &r2.orphaned_tip,
+            ))
+        });
+        Ok(reorgs)
+    }
+
     pub fn index_handle_at_tip<'a>(&'a self) -> SortitionHandleConn<'a> {
         let sortition_id = SortitionDB::get_canonical_sortition_tip(self.conn()).unwrap();
         self.index_handle(&sortition_id)
@@ -3950,6 +4400,18 @@ impl<'a> SortitionHandleTx<'a> {
                       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)", args)
             .map_err(db_error::SqliteError)?;
 
+        if !insert_ancestor_skip(
+            self,
+            &snapshot.sortition_id,
+            snapshot.block_height - self.context.first_block_height,
+            &snapshot.parent_sortition_id,
+        )? {
+            debug!(
+                "Parent sortition {} of {} is not in the ancestor index",
+                &snapshot.parent_sortition_id, &snapshot.sortition_id
+            );
+        }
+
         Ok(())
     }
 
@@ -6565,6 +7027,195 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_sortition_stats() {
+        let first_burn_hash = BurnchainHeaderHash::from_hex(
+            "10000000000000000000000000000000000000000000000000000000000000ff",
+        )
+        .unwrap();
+        let mut db = SortitionDB::connect_test(0, &first_burn_hash).unwrap();
+        let first_snapshot = SortitionDB::get_first_block_snapshot(db.conn()).unwrap();
+
+        // canonical fork: 0-1-2-3-4-5-6
+        make_fork_run(&mut db, &first_snapshot, 6, 0);
+
+        // orphaned runs: 2-3'-4' and 4-5''
+        let sn_2 = SortitionDB::get_block_snapshot(db.conn(), &SortitionId([1u8; 32]))
+            .unwrap()
+            .unwrap();
+        make_fork_run(&mut db, &sn_2, 2, 0x80);
+        let sn_4 = SortitionDB::get_block_snapshot(db.conn(), &SortitionId([3u8; 32]))
+            .unwrap()
+            .unwrap();
+        make_fork_run(&mut db, &sn_4, 1, 0x40);
+
+        let tip = SortitionDB::get_canonical_burn_chain_tip(db.conn()).unwrap();
+        assert_eq!(tip.block_height, 6);
+
+        let stats = db.get_sortition_stats(1, 100).unwrap();
+        assert_eq!(stats.end_height, 6);
+        assert_eq!(stats.num_blocks, 6);
+        assert_eq!(stats.num_sortitions, 6);
+        assert_eq!(stats.num_flash_blocks, 0);
+        assert_eq!(stats.num_missed_sortitions, 6);
+        assert_eq!(
+            stats.reorgs,
+            vec![
+                BurnchainReorg {
+                    fork_height: 3,
+                    depth: 2,
+                    orphaned_tip: BurnchainHeaderHash([3 | 0x80; 32]),
+                },
+                BurnchainReorg {
+                    fork_height: 5,
+                    depth: 1,
+                    orphaned_tip: BurnchainHeaderHash([4 | 0x40; 32]),
+                },
+            ]
+        );
+
+        // orphaned blocks above the range are not counted
+        let stats = db.get_sortition_stats(1, 3).unwrap();
+        assert_eq!(stats.num_blocks, 3);
+        assert_eq!(
+            stats.reorgs,
+            vec![BurnchainReorg {
+                fork_height: 3,
+                depth: 1,
+                orphaned_tip: BurnchainHeaderHash([2 | 0x80; 32]),
+            }]
+        );
+
+        // empty range
+        let stats = db.get_sortition_stats(7, 10).unwrap();
+        assert_eq!(stats.num_blocks, 0);
+        assert_eq!(stats.reorgs, vec![]);
+    }
+
+    #[test]
+    fn test_burn_amount_distribution() {
+        assert_eq!(
+            BurnAmountDistribution::from_amounts(vec![]),
+            BurnAmountDistribution::default()
+        );
+
+        let dist = BurnAmountDistribution::from_amounts((1..101).rev().collect());
+        assert_eq!(
+            dist,
+            BurnAmountDistribution {
+                count: 100,
+                min: 1,
+                max: 100,
+                mean: 50,
+                p25: 25,
+                median: 50,
+                p75: 75,
+                p95: 95,
+            }
+        );
+
+        let dist = BurnAmountDistribution::from_amounts(vec![7]);
+        assert_eq!(dist.min, 7);
+        assert_eq!(dist.median, 7);
+        assert_eq!(dist.p95, 7);
+    }
+
+    #[test]
+    fn test_burn_state_cache() {
+        use vm::database::BurnStateDB;
+
+        let first_burn_hash = BurnchainHeaderHash::from_hex(
+            "10000000000000000000000000000000000000000000000000000000000000ff",
+        )
+        .unwrap();
+        let mut db = SortitionDB::connect_test(0, &first_burn_hash).unwrap();
+        let first_snapshot = SortitionDB::get_first_block_snapshot(db.conn()).unwrap();
+        make_fork_run(&mut db, &first_snapshot, 5, 0);
+        let tip = SortitionDB::get_canonical_burn_chain_tip(db.conn()).unwrap();
+
+        // cached answers match what the DB says, both on the miss and on the hit
+        let ic = db.index_conn();
+        for _ in 0..2 {
+            assert_eq!(
+                ic.get_burn_block_height(&tip.sortition_id),
+                Some(tip.block_height as u32)
+            );
+            for height in 0..(tip.block_height + 1) {
+                let expected = SortitionDB::get_ancestor_snapshot(&ic, height, &tip.sortition_id)
+                    .unwrap()
+                    .unwrap()
+                    .burn_header_hash;
+                assert_eq!(
+                    ic.get_burn_header_hash(height as u32, &tip.sortition_id),
+                    Some(expected)
+                );
+            }
+            assert_eq!(
+                ic.get_stacks_epoch(tip.block_height as u32),
+                SortitionDB::get_stacks_epoch(db.conn(), tip.block_height).unwrap()
+            );
+            assert_eq!(
+                ic.get_stacks_epoch_by_epoch_id(&StacksEpochId::Epoch20),
+                SortitionDB::get_stacks_epoch_by_epoch_id(db.conn(), &StacksEpochId::Epoch20)
+                    .unwrap()
+            );
+        }
+        assert_eq!(
+            ic.get_burn_header_hash(tip.block_height as u32 + 1, &tip.sortition_id),
+            None
+        );
+        assert_eq!(
+            ic.get_burn_header_timestamp(tip.block_height as u32, &tip.sortition_id),
+            Some(tip.burn_header_timestamp)
+        );
+        assert_eq!(
+            ic.get_burn_header_timestamp(tip.block_height as u32 + 1, &tip.sortition_id),
+            None
+        );
+        assert_eq!(
+            ic.get_sortition_id_from_consensus_hash(&tip.consensus_hash),
+            Some(tip.sortition_id.clone())
+        );
+
+        // loaders only run on a miss, and misses are not remembered
+        let cache = BurnStateCache::new();
+        assert_eq!(
+            cache.get_burn_block_height(&tip.sortition_id, || None),
+            None
+        );
+        assert_eq!(
+            cache.get_burn_block_height(&tip.sortition_id, || Some(5)),
+            Some(5)
+        );
+        assert_eq!(
+            cache.get_burn_block_height(&tip.sortition_id, || panic!("should be cached")),
+            Some(5)
+        );
+
+        // the cache starts over once it is full
+        for i in 1..BURN_STATE_CACHE_MAX_ENTRIES {
+            let mut bytes = [0u8; 32];
+            bytes[0..8].copy_from_slice(&(i as u64).to_be_bytes());
+            cache.get_burn_block_height(&SortitionId(bytes), || Some(i as u32));
+        }
+        assert_eq!(
+            cache.get_burn_block_height(&tip.sortition_id, || panic!("should be cached")),
+            Some(5)
+        );
+        cache.get_burn_block_height(&SortitionId([0xff; 32]), || Some(0));
+        assert_eq!(
+            cache.get_burn_block_height(&tip.sortition_id, || Some(6)),
+            Some(6)
+        );
+        assert_eq!(
+            cache.get_stacks_epoch(0, || SortitionDB::get_stacks_epochs(db.conn()).unwrap()),
+            SortitionDB::get_stacks_epoch(db.conn(), 0).unwrap()
+        );
+        assert!(cache
+            .get_stacks_epoch_by_epoch_id(&StacksEpochId::Epoch20, || panic!("should be cached"))
+            .is_some());
+    }
+
     #[test]
     fn test_set_stacks_block_accepted() {
         let first_burn_hash = BurnchainHeaderHash::from_hex(
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{
     atomic::{AtomicU64, Ordering},
     Arc, Condvar, LockResult, Mutex, MutexGuard, RwLock, TryLockResult,
@@ -21,16 +22,77 @@ use std::sync::{
 use std::time::{Duration, Instant};
 use std::{process, thread};
 
+use crate::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, SortitionId, StacksBlockId};
+use chainstate::burn::ConsensusHash;
+use chainstate::coordinator::CanonicalForkChange;
+
 /// Trait for use by the ChainsCoordinator
 ///
 pub trait CoordinatorNotices {
     fn notify_stacks_block_processed(&mut self);
     fn notify_sortition_processed(&mut self);
+    /// Tell subscribers about chain progress.  Does nothing by default.
+    fn notify_event(&mut self, _event: CoordinatorEvent) {}
 }
 
 pub struct ArcCounterCoordinatorNotices {
     pub stacks_blocks_processed: Arc<AtomicU64>,
     pub sortitions_processed: Arc<AtomicU64>,
+    pub subscribers: CoordinatorSubscribers,
+}
+
+/// Chain progress made by the ChainsCoordinator, as delivered to subscribers
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoordinatorEvent {
+    /// A burnchain block's sortition was evaluated
+    SortitionProcessed {
+        sortition_id: SortitionId,
+        consensus_hash: ConsensusHash,
+        burn_header_hash: BurnchainHeaderHash,
+        burn_block_height: u64,
+    },
+    /// A Stacks block was processed and attached to the chain state in the canonical
+    /// sortition history
+    StacksBlockAttached {
+        index_block_hash: StacksBlockId,
+        consensus_hash: ConsensusHash,
+        block_hash: BlockHeaderHash,
+        block_height: u64,
+    },
+    /// The canonical Stacks tip moved to a block that does not descend from the old tip
+    ForkSwitch(CanonicalForkChange),
+}
+
+/// Registry of channels that receive CoordinatorEvents.  Subscribers whose receivers have been
+/// dropped are forgotten the next time an event is published.
+#[derive(Clone)]
+pub struct CoordinatorSubscribers {
+    senders: Arc<Mutex<Vec<Sender<CoordinatorEvent>>>>,
+}
+
+impl CoordinatorSubscribers {
+    pub fn new() -> CoordinatorSubscribers {
+        CoordinatorSubscribers {
+            senders: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Get a new channel on which all subsequent events will be delivered
+    pub fn subscribe(&self) -> Receiver<CoordinatorEvent> {
+        let (sender, receiver) = channel();
+        self.senders.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Send an event to all subscribers
+    pub fn publish(&self, event: CoordinatorEvent) {
+        let mut senders = self.senders.lock().unwrap();
+        senders.retain(|sender| sender.send(event.clone()).is_ok());
+    }
+
+    pub fn num_subscribers(&self) -> usize {
+        self.senders.lock().unwrap().len()
+    }
 }
 
 impl CoordinatorNotices for () {
@@ -45,6 +107,9 @@ impl CoordinatorNotices for ArcCounterCoordinatorNotices {
     fn notify_sortition_processed(&mut self) {
         self.sortitions_processed.fetch_add(1, Ordering::SeqCst);
     }
+    fn notify_event(&mut self, event: CoordinatorEvent) {
+        self.subscribers.publish(event);
+    }
 }
 
 /// Structure used for communication _with_ a running
@@ -60,6 +125,8 @@ pub struct CoordinatorChannels {
     stacks_blocks_processed: Arc<AtomicU64>,
     /// how many sortitions have been processed by this Coordinator thread since startup?
     sortitions_processed: Arc<AtomicU64>,
+    /// channels to deliver chain progress events on
+    subscribers: CoordinatorSubscribers,
 }
 
 /// Notification struct for communicating to
@@ -83,6 +150,7 @@ pub struct CoordinatorReceivers {
     signal_wakeup: Arc<Condvar>,
     pub stacks_blocks_processed: Arc<AtomicU64>,
     pub sortitions_processed: Arc<AtomicU64>,
+    pub subscribers: CoordinatorSubscribers,
 }
 
 /// Static struct used to hold all the static methods
@@ -160,6 +228,13 @@ impl CoordinatorChannels {
         self.sortitions_processed.load(Ordering::SeqCst)
     }
 
+    /// Receive the coordinator's chain progress events from now on, so a program embedding
+    /// the coordinator can react to new sortitions, new blocks, and fork switches without
+    /// polling the databases.
+    pub fn subscribe(&self) -> Receiver<CoordinatorEvent> {
+        self.subscribers.subscribe()
+    }
+
     pub fn wait_for_sortitions_processed(&self, current: u64, timeout_millis: u64) -> bool {
         let start = Instant::now();
         while self.get_sortitions_processed() <= current {
@@ -197,6 +272,7 @@ impl CoordinatorCommunication {
 
         let stacks_blocks_processed = Arc::new(AtomicU64::new(0));
         let sortitions_processed = Arc::new(AtomicU64::new(0));
+        let subscribers = CoordinatorSubscribers::new();
 
         let senders = CoordinatorChannels {
             signal_bools: signal_bools.clone(),
@@ -204,6 +280,7 @@ impl CoordinatorCommunication {
             stacks_blocks_processed: stacks_blocks_processed.clone(),
 
             sortitions_processed: sortitions_processed.clone(),
+            subscribers: subscribers.clone(),
         };
 
         let rcvrs = CoordinatorReceivers {
@@ -211,8 +288,49 @@ impl CoordinatorCommunication {
             signal_wakeup: signal_wakeup,
             stacks_blocks_processed,
             sortitions_processed,
+            subscribers,
         };
 
         (rcvrs, senders)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_coordinator_subscribers() {
+        let (rcvrs, channels) = CoordinatorCommunication::instantiate();
+        let mut notices = ArcCounterCoordinatorNotices {
+            stacks_blocks_processed: rcvrs.stacks_blocks_processed.clone(),
+            sortitions_processed: rcvrs.sortitions_processed.clone(),
+            subscribers: rcvrs.subscribers.clone(),
+        };
+
+        let event = CoordinatorEvent::StacksBlockAttached {
+            index_block_hash: StacksBlockId([1u8; 32]),
+            consensus_hash: ConsensusHash([2u8; 20]),
+            block_hash: BlockHeaderHash([3u8; 32]),
+            block_height: 4,
+        };
+
+        // no subscribers yet
+        notices.notify_event(event.clone());
+
+        let sub_1 = channels.subscribe();
+        let sub_2 = channels.subscribe();
+        assert_eq!(rcvrs.subscribers.num_subscribers(), 2);
+
+        notices.notify_event(event.clone());
+        assert_eq!(sub_1.try_recv().unwrap(), event);
+        assert_eq!(sub_2.try_recv().unwrap(), event);
+        assert!(sub_1.try_recv().is_err());
+
+        // dropped subscribers are forgotten
+        drop(sub_2);
+        notices.notify_event(event.clone());
+        assert_eq!(rcvrs.subscribers.num_subscribers(), 1);
+        assert_eq!(sub_1.try_recv().unwrap(), event);
+    }
+}
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::{TryFrom, TryInto};
 use std::fs;
@@ -30,7 +31,8 @@ use chainstate::burn::{
     operations::BlockstackOperationType, BlockSnapshot, ConsensusHash,
 };
 use chainstate::coordinator::comm::{
-    ArcCounterCoordinatorNotices, CoordinatorEvents, CoordinatorNotices, CoordinatorReceivers,
+    ArcCounterCoordinatorNotices, CoordinatorEvent, CoordinatorEvents, CoordinatorNotices,
+    CoordinatorReceivers,
 };
 use chainstate::stacks::index::MarfTrieId;
 use chainstate::stacks::{
@@ -115,6 +117,98 @@ impl RewardCycleInfo {
     }
 }
 
+/// A change of the canonical Stacks fork to one that does not build on the previous canonical
+/// tip.  Blocks are identified by index block hash and height.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanonicalForkChange {
+    pub old_tip: (StacksBlockId, u64),
+    pub new_tip: (StacksBlockId, u64),
+    /// last block the old and new forks have in common
+    pub fork_point: (StacksBlockId, u64),
+    /// blocks that left the canonical fork, from the old tip down to the fork point
+    pub orphaned: Vec<(StacksBlockId, u64)>,
+    /// blocks that joined the canonical fork, from the fork point up to the new tip
+    pub joined: Vec<(StacksBlockId, u64)>,
+}
+
+impl CanonicalForkChange {
+    /// Find out how the canonical fork changed when the tip moved from `old_tip` to `new_tip`.
+    /// Returns None if `new_tip` builds on `old_tip` (i.e. this is not a reorg), or if either
+    /// tip's ancestry isn't known.
+    pub fn find(
+        chainstate: &StacksChainState,
+        old_tip: &StacksBlockId,
+        new_tip: &StacksBlockId,
+    ) -> Result<Option<CanonicalForkChange>, Error> {
+        if old_tip == new_tip {
+            return Ok(None);
+        }
+        let old_height = match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            chainstate.db(),
+            old_tip,
+        )? {
+            Some(header) => header.block_height,
+            None => return Ok(None),
+        };
+        let new_height = match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            chainstate.db(),
+            new_tip,
+        )? {
+            Some(header) => header.block_height,
+            None => return Ok(None),
+        };
+
+        let mut orphaned = vec![];
+        let mut joined = vec![];
+        let (mut old_cursor, mut old_cursor_height) = (old_tip.clone(), old_height);
+        let (mut new_cursor, mut new_cursor_height) = (new_tip.clone(), new_height);
+
+        while old_cursor_height > new_cursor_height {
+            orphaned.push((old_cursor.clone(), old_cursor_height));
+            old_cursor = chainstate.get_parent(&old_cursor)?;
+            old_cursor_height -= 1;
+        }
+        while new_cursor_height > old_cursor_height {
+            joined.push((new_cursor.clone(), new_cursor_height));
+            new_cursor = chainstate.get_parent(&new_cursor)?;
+            new_cursor_height -= 1;
+        }
+        while old_cursor != new_cursor {
+            if old_cursor_height == 0 {
+                // no common ancestor
+                return Ok(None);
+            }
+            orphaned.push((old_cursor.clone(), old_cursor_height));
+            joined.push((new_cursor.clone(), new_cursor_height));
+            old_cursor = chainstate.get_parent(&old_cursor)?;
+            new_cursor = chainstate.get_parent(&new_cursor)?;
+            old_cursor_height -= 1;
+            new_cursor_height -= 1;
+        }
+
+        if orphaned.is_empty() {
+            // the new tip descends from the old tip
+            return Ok(None);
+        }
+
+        joined.reverse();
+        Ok(Some(CanonicalForkChange {
+            old_tip: (old_tip.clone(), old_height),
+            new_tip: (new_tip.clone(), new_height),
+            fork_point: (old_cursor, old_cursor_height),
+            orphaned,
+            joined,
+        }))
+    }
+}
+
+/// The index block hash of the canonical Stacks chain tip, according to the sortition DB
+fn get_canonical_stacks_tip(sortition_db: &SortitionDB) -> Result<StacksBlockId, DBError> {
+    let (consensus_hash, block_hash) =
+        SortitionDB::get_canonical_stacks_chain_tip_hash(sortition_db.conn())?;
+    Ok(StacksBlockId::new(&consensus_hash, &block_hash))
+}
+
 pub trait BlockEventDispatcher {
     fn announce_block(
         &self,
@@ -146,6 +240,10 @@ pub trait BlockEventDispatcher {
     );
 
     fn dispatch_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>);
+
+    /// called whenever the canonical Stacks tip moves to a block that
+    ///  does not descend from the previous canonical tip.
+    fn announce_reorg(&self, fork_change: &CanonicalForkChange);
 }
 
 pub struct ChainsCoordinator<
@@ -159,6 +257,8 @@ pub struct ChainsCoordinator<
     canonical_sortition_tip: Option<SortitionId>,
     canonical_chain_tip: Option<StacksBlockId>,
     canonical_pox_id: Option<PoxId>,
+    /// canonical Stacks tip as of the last check for a reorg
+    last_canonical_stacks_tip: Option<StacksBlockId>,
     burnchain_blocks_db: BurnchainDB,
     chain_state_db: StacksChainState,
     sortition_db: SortitionDB,
@@ -285,16 +385,21 @@ impl<'a, T: BlockEventDispatcher, CE: CostEstimator + ?Sized, FE: FeeEstimator +
 
         let canonical_sortition_tip =
             SortitionDB::get_canonical_sortition_tip(sortition_db.conn()).unwrap();
+        // start from the tip we had before shutting down, so a reorg right after
+        //  the restart is still announced
+        let last_canonical_stacks_tip = get_canonical_stacks_tip(&sortition_db).ok();
 
         let arc_notices = ArcCounterCoordinatorNotices {
             stacks_blocks_processed,
             sortitions_processed,
+            subscribers: comms.subscribers.clone(),
         };
 
         let mut inst = ChainsCoordinator {
             canonical_chain_tip: None,
             canonical_sortition_tip: Some(canonical_sortition_tip),
             canonical_pox_id: None,
+            last_canonical_stacks_tip,
             burnchain_blocks_db,
             chain_state_db,
             sortition_db,
@@ -377,11 +482,15 @@ impl<'a, T: BlockEventDispatcher, U: RewardSetProvider> ChainsCoordinator<'a, T,
         .unwrap();
         let canonical_sortition_tip =
             SortitionDB::get_canonical_sortition_tip(sortition_db.conn()).unwrap();
+        // start from the tip we had before shutting down, so a reorg right after
+        //  the restart is still announced
+        let last_canonical_stacks_tip = get_canonical_stacks_tip(&sortition_db).ok();
 
         ChainsCoordinator {
             canonical_chain_tip: None,
             canonical_sortition_tip: Some(canonical_sortition_tip),
             canonical_pox_id: None,
+            last_canonical_stacks_tip,
             burnchain_blocks_db,
             chain_state_db,
             sortition_db,
@@ -480,6 +589,157 @@ pub fn get_reward_cycle_info<U: RewardSetProvider>(
     }
 }
 
+/// Get the PoX anchor block and reward set chosen for a reward cycle in the canonical burnchain
+///  fork.  The reward set is computed at the anchor block by `provider`, just as it was when the
+///  reward cycle began.
+/// Returns None if the reward cycle's prepare phase has not ended yet.
+pub fn get_reward_set_for_cycle<U: RewardSetProvider>(
+    reward_cycle: u64,
+    burnchain: &Burnchain,
+    chain_state: &mut StacksChainState,
+    sort_db: &SortitionDB,
+    provider: &U,
+) -> Result<Option<RewardCycleInfo>, Error> {
+    let sortition_tip = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn())?;
+    match burnchain.block_height_to_reward_cycle(sortition_tip.block_height) {
+        // the last block of a cycle's prepare phase is the first block counted in that cycle
+        Some(tip_reward_cycle) if reward_cycle <= tip_reward_cycle => {}
+        _ => return Ok(None),
+    }
+
+    // the anchor block is chosen by the last block of the prepare phase
+    let cycle_start_height = burnchain.reward_cycle_to_block_height(reward_cycle);
+    let prepare_end = match SortitionDB::get_ancestor_snapshot(
+        &sort_db.index_conn(),
+        cycle_start_height - 1,
+        &sortition_tip.sortition_id,
+    )? {
+        Some(snapshot) => snapshot,
+        None => return Ok(None),
+    };
+
+    get_reward_cycle_info(
+        cycle_start_height,
+        &prepare_end.burn_header_hash,
+        &sortition_tip.sortition_id,
+        burnchain,
+        chain_state,
+        sort_db,
+        provider,
+    )
+}
+
+/// A difference between a recorded sortition and the sortition obtained by re-running it from
+///  the stored burnchain operations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortitionDivergence {
+    pub burn_block_height: u64,
+    pub burn_header_hash: BurnchainHeaderHash,
+    pub field: String,
+    pub recorded: String,
+    pub replayed: String,
+}
+
+/// Compare the consensus-relevant fields of a recorded snapshot against a replayed one.
+/// The index root and the memoized Stacks chain tip are skipped, since the former is only known
+///  once the snapshot is stored and the latter changes as Stacks blocks arrive.
+fn diff_snapshots(recorded: &BlockSnapshot, replayed: &BlockSnapshot) -> Vec<SortitionDivergence> {
+    let mut divergences = vec![];
+    macro_rules! check_field {
+        ($field:ident) => {
+            if recorded.$field != replayed.$field {
+                divergences.push(SortitionDivergence {
+                    burn_block_height: recorded.block_height,
+                    burn_header_hash: recorded.burn_header_hash.clone(),
+                    field: stringify!($field).to_string(),
+                    recorded: format!("{:?}", &recorded.$field),
+                    replayed: format!("{:?}", &replayed.$field),
+                });
+            }
+        };
+    }
+
+    check_field!(burn_header_timestamp);
+    check_field!(parent_burn_header_hash);
+    check_field!(consensus_hash);
+    check_field!(ops_hash);
+    check_field!(total_burn);
+    check_field!(sortition);
+    check_field!(sortition_hash);
+    check_field!(winning_block_txid);
+    check_field!(winning_stacks_block_hash);
+    check_field!(num_sortitions);
+    check_field!(sortition_id);
+    check_field!(parent_sortition_id);
+    check_field!(pox_valid);
+    check_field!(accumulated_coinbase_ustx);
+    divergences
+}
+
+/// Re-run sortition selection for the canonical burnchain blocks in
+///  `[start_height, end_height]`, using the operations stored in the burnchain DB, and report
+///  every way in which the result differs from the recorded snapshots.  Each sortition is
+///  replayed on top of its recorded parent, so a divergence at one height does not cascade into
+///  the heights after it.  Nothing is written to the sortition DB, but it must be opened
+///  read-write, since each replay runs in a transaction that is rolled back afterwards (see
+///  `SortitionDB::replay_sortition`).
+pub fn replay_sortitions<U: RewardSetProvider>(
+    burnchain: &Burnchain,
+    burnchain_db: &BurnchainDB,
+    sortition_db: &mut SortitionDB,
+    chain_state: &mut StacksChainState,
+    provider: &U,
+    start_height: u64,
+    end_height: u64,
+) -> Result<Vec<SortitionDivergence>, Error> {
+    let sortition_tip = SortitionDB::get_canonical_burn_chain_tip(sortition_db.conn())?;
+    let start_height = cmp::max(start_height, sortition_db.first_block_height + 1);
+    let end_height = cmp::min(end_height, sortition_tip.block_height);
+
+    let mut divergences = vec![];
+    for height in start_height..(end_height + 1) {
+        let recorded = SortitionDB::get_ancestor_snapshot(
+            &sortition_db.index_conn(),
+            height,
+            &sortition_tip.sortition_id,
+        )?
+        .ok_or(Error::DBError(DBError::NotFoundError))?;
+
+        let BurnchainBlockData { header, ops } =
+            burnchain_db.get_burnchain_block(&recorded.burn_header_hash)?;
+
+        let reward_cycle_info = get_reward_cycle_info(
+            header.block_height,
+            &header.parent_block_hash,
+            &recorded.parent_sortition_id,
+            burnchain,
+            chain_state,
+            sortition_db,
+            provider,
+        )?;
+
+        let replayed = sortition_db
+            .replay_sortition(
+                &header,
+                ops,
+                burnchain,
+                &recorded.parent_sortition_id,
+                reward_cycle_info,
+            )
+            .map_err(Error::FailedToProcessSortition)?;
+
+        let mut found = diff_snapshots(&recorded, &replayed);
+        if found.len() > 0 {
+            warn!("Replayed sortition diverges from recorded sortition";
+                  "burn_block_height" => height,
+                  "burn_header_hash" => %recorded.burn_header_hash,
+                  "num_fields" => found.len());
+        }
+        divergences.append(&mut found);
+    }
+    Ok(divergences)
+}
+
 struct PaidRewards {
     pox: Vec<(StacksAddress, u64)>,
     burns: u64,
@@ -549,13 +809,48 @@ impl<
 {
     pub fn handle_new_stacks_block(&mut self) -> Result<(), Error> {
         if let Some(pox_anchor) = self.process_ready_blocks()? {
-            self.process_new_pox_anchor(pox_anchor)
-        } else {
-            Ok(())
+            self.process_new_pox_anchor(pox_anchor)?;
         }
+        self.announce_canonical_fork_change()
     }
 
     pub fn handle_new_burnchain_block(&mut self) -> Result<(), Error> {
+        self.process_new_burnchain_blocks()?;
+        self.announce_canonical_fork_change()
+    }
+
+    /// Check whether the canonical Stacks fork changed since the last check, and if so, tell
+    ///  the dispatcher and subscribers which blocks were orphaned and which became canonical.
+    fn announce_canonical_fork_change(&mut self) -> Result<(), Error> {
+        let canonical_tip = get_canonical_stacks_tip(&self.sortition_db)?;
+
+        let last_tip = match self
+            .last_canonical_stacks_tip
+            .replace(canonical_tip.clone())
+        {
+            Some(last_tip) => last_tip,
+            None => return Ok(()),
+        };
+
+        if let Some(fork_change) =
+            CanonicalForkChange::find(&self.chain_state_db, &last_tip, &canonical_tip)?
+        {
+            info!("Canonical Stacks fork changed";
+                  "old_tip" => %fork_change.old_tip.0,
+                  "new_tip" => %fork_change.new_tip.0,
+                  "fork_point" => %fork_change.fork_point.0,
+                  "orphaned" => fork_change.orphaned.len(),
+                  "joined" => fork_change.joined.len());
+            if let Some(dispatcher) = self.dispatcher {
+                dispatcher.announce_reorg(&fork_change);
+            }
+            self.notifier
+                .notify_event(CoordinatorEvent::ForkSwitch(fork_change));
+        }
+        Ok(())
+    }
+
+    fn process_new_burnchain_blocks(&mut self) -> Result<(), Error> {
         // Retrieve canonical burnchain chain tip from the BurnchainBlocksDB
         let canonical_burnchain_tip = self.burnchain_blocks_db.get_canonical_chain_tip()?;
         debug!("Handle new canonical burnchain tip";
@@ -636,6 +931,13 @@ impl<
             let sortition_id = next_snapshot.sortition_id;
 
             self.notifier.notify_sortition_processed();
+            self.notifier
+                .notify_event(CoordinatorEvent::SortitionProcessed {
+                    sortition_id: sortition_id.clone(),
+                    consensus_hash: next_snapshot.consensus_hash.clone(),
+                    burn_header_hash: next_snapshot.burn_header_hash.clone(),
+                    burn_block_height: next_snapshot.block_height,
+                });
 
             debug!(
                 "Sortition processed";
@@ -702,7 +1004,14 @@ impl<
         update_stacks_tip_height(stacks_tip.canonical_stacks_tip_height as i64);
 
         while let Some(block_result) = processed_blocks.pop() {
-            if let (Some(block_receipt), _) = block_result {
+            let (block_receipt_opt, poison_opt) = block_result;
+            if let Some(ref poison_payload) = poison_opt {
+                // keep the evidence, so the fork can be reported
+                if let Err(e) = self.chain_state_db.record_poison_microblock(poison_payload) {
+                    warn!("Failed to record microblock stream fork: {:?}", &e);
+                }
+            }
+            if let Some(block_receipt) = block_receipt_opt {
                 // only bump the coordinator's state if the processed block
                 //   is in our sortition fork
                 //  TODO: we should update the staging block logic to prevent
@@ -728,6 +1037,13 @@ impl<
                     increment_stx_blocks_processed_counter();
 
                     let block_hash = block_receipt.header.anchored_header.block_hash();
+                    self.notifier
+                        .notify_event(CoordinatorEvent::StacksBlockAttached {
+                            index_block_hash: block_receipt.header.index_block_hash(),
+                            consensus_hash: block_receipt.header.consensus_hash.clone(),
+                            block_hash: block_hash.clone(),
+                            block_height: block_receipt.header.block_height,
+                        });
 
                     let mut attachments_instances = HashSet::new();
                     for receipt in block_receipt.tx_receipts.iter() {
@@ -857,7 +1173,6 @@ impl<
                     }
                 }
             }
-            // TODO: do something with a poison result
 
             let sortdb_handle = self.sortition_db.tx_handle_begin(canonical_sortition_tip)?;
             processed_blocks = self.chain_state_db.process_blocks(sortdb_handle, 1)?;
@@ -915,6 +1230,35 @@ impl<
     }
 }
 
+/// Determine whether or not the epochs a node is configured with are the ones its sortition DB
+/// was instantiated with.  The epochs are only written at first boot, so changing them afterwards
+/// would otherwise be silently ignored.  Returns true if the DB does not exist yet, or predates
+/// the epochs table.
+pub fn check_sortition_db_epochs(
+    epochs: &[StacksEpoch],
+    sortdb_path: &str,
+) -> Result<bool, DBError> {
+    if fs::metadata(&sortdb_path).is_err() {
+        return Ok(true);
+    }
+    let db_epochs = match SortitionDB::get_stacks_epochs_from_path(&sortdb_path)? {
+        Some(db_epochs) => db_epochs,
+        None => {
+            return Ok(true);
+        }
+    };
+    let mut epochs = epochs.to_vec();
+    epochs.sort();
+    if epochs != db_epochs {
+        error!(
+            "Sortition DB at {} was instantiated with epochs {:?}, but the configured epochs are {:?}",
+            &sortdb_path, &db_epochs, &epochs
+        );
+        return Ok(false);
+    }
+    Ok(true)
+}
+
 /// Determine whether or not the current chainstate databases are up-to-date with the current
 /// epoch.
 pub fn check_chainstate_db_versions(
@@ -35,7 +35,7 @@ use chainstate::burn::operations::*;
 use chainstate::burn::*;
 use chainstate::coordinator::{Error as CoordError, *};
 use chainstate::stacks::db::{
-    accounts::MinerReward, ClarityTx, StacksChainState, StacksHeaderInfo,
+    accounts::MinerReward, ClarityTx, StacksChainState, StacksHeaderInfo, CHAINSTATE_VERSION,
 };
 use chainstate::stacks::*;
 use clarity_vm::clarity::ClarityConnection;
@@ -330,6 +330,8 @@ impl BlockEventDispatcher for NullEventDispatcher {
     }
 
     fn dispatch_boot_receipts(&mut self, _receipts: Vec<StacksTransactionReceipt>) {}
+
+    fn announce_reorg(&self, _fork_change: &CanonicalForkChange) {}
 }
 
 pub fn make_coordinator<'a>(
@@ -1249,7 +1251,7 @@ fn test_sortition_with_reward_set() {
         StacksEpochId::Epoch20,
     );
 
-    let mut coord = make_reward_set_coordinator(path, reward_set, None);
+    let mut coord = make_reward_set_coordinator(path, reward_set.clone(), None);
 
     coord.handle_new_burnchain_block().unwrap();
 
@@ -1490,6 +1492,180 @@ fn test_sortition_with_reward_set() {
                    "111111111111",
                    "PoX ID should reflect the 10 reward cycles _with_ a known anchor block, plus the 'initial' known reward cycle at genesis");
     }
+
+    // the reward set of the current reward cycle can be recovered from its anchor block
+    let tip = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn()).unwrap();
+    let reward_cycle = b.block_height_to_reward_cycle(tip.block_height).unwrap();
+    let reward_cycle_info = get_reward_set_for_cycle(
+        reward_cycle,
+        &b,
+        &mut chainstate,
+        &sort_db,
+        &StubbedRewardSetProvider(reward_set.clone()),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        reward_cycle_info.anchor_status,
+        PoxAnchorBlockStatus::SelectedAndKnown(
+            anchor_blocks.last().unwrap().clone(),
+            reward_set.clone()
+        )
+    );
+
+    // the next reward cycle's anchor block has not been chosen yet
+    assert!(get_reward_set_for_cycle(
+        reward_cycle + 1,
+        &b,
+        &mut chainstate,
+        &sort_db,
+        &StubbedRewardSetProvider(vec![]),
+    )
+    .unwrap()
+    .is_none());
+}
+
+#[test]
+fn test_replay_sortitions() {
+    let path = "/tmp/stacks-blockchain-replay-sortitions";
+    let _r = std::fs::remove_dir_all(path);
+
+    let vrf_keys: Vec<_> = (0..20).map(|_| VRFPrivateKey::new()).collect();
+    let committers: Vec<_> = (0..20).map(|_| StacksPrivateKey::new()).collect();
+    let reward_set: Vec<_> = (0..4)
+        .map(|_| p2pkh_from(&StacksPrivateKey::new()))
+        .collect();
+
+    setup_states(
+        &[path],
+        &vrf_keys,
+        &committers,
+        None,
+        None,
+        StacksEpochId::Epoch20,
+    );
+
+    let mut coord = make_reward_set_coordinator(path, reward_set.clone(), None);
+    coord.handle_new_burnchain_block().unwrap();
+
+    let sort_db = get_sortition_db(path, None);
+    let b = get_burnchain(path, None);
+
+    // mine a chain that spans a few reward cycles, paying the reward set
+    let mut parent = BlockHeaderHash([0; 32]);
+    for (ix, (vrf_key, miner)) in vrf_keys.iter().zip(committers.iter()).enumerate() {
+        let mut burnchain = get_burnchain_db(path, None);
+        let mut chainstate = get_chainstate(path);
+        let burnchain_tip = burnchain.get_canonical_chain_tip().unwrap();
+        let next_mock_header = BurnchainBlockHeader {
+            block_height: burnchain_tip.block_height + 1,
+            block_hash: BurnchainHeaderHash([0; 32]),
+            parent_block_hash: burnchain_tip.block_hash,
+            num_txs: 0,
+            timestamp: 1,
+        };
+        let reward_cycle_info = coord.get_reward_cycle_info(&next_mock_header).unwrap();
+        let next_block_recipients = get_rw_sortdb(path, None)
+            .test_get_next_block_recipients(&b, reward_cycle_info.as_ref())
+            .unwrap();
+
+        let (op, block) = if ix == 0 {
+            make_genesis_block_with_recipients(
+                &sort_db,
+                &mut chainstate,
+                &parent,
+                miner,
+                10000,
+                vrf_key,
+                ix as u32,
+                next_block_recipients.as_ref(),
+            )
+        } else {
+            make_stacks_block_with_recipients(
+                &sort_db,
+                &mut chainstate,
+                &b,
+                &parent,
+                burnchain_tip.block_height,
+                miner,
+                10000,
+                vrf_key,
+                ix as u32,
+                next_block_recipients.as_ref(),
+            )
+        };
+        produce_burn_block(
+            &mut burnchain,
+            &burnchain_tip.block_hash,
+            vec![op],
+            vec![].iter_mut(),
+        );
+        coord.handle_new_burnchain_block().unwrap();
+
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn()).unwrap();
+        let block_hash = block.header.block_hash();
+        assert_eq!(&tip.winning_stacks_block_hash, &block_hash);
+        preprocess_block(&mut chainstate, &sort_db, &tip, block);
+        coord.handle_new_stacks_block().unwrap();
+        parent = block_hash;
+    }
+
+    let tip = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn()).unwrap();
+    assert_eq!(tip.block_height, 21);
+    let burnchain_db = get_burnchain_db(path, None);
+    let mut chainstate = get_chainstate(path);
+    let mut rw_sort_db = get_rw_sortdb(path, None);
+
+    // re-running every sortition from the stored burnchain ops reproduces the recorded ones
+    let divergences = replay_sortitions(
+        &b,
+        &burnchain_db,
+        &mut rw_sort_db,
+        &mut chainstate,
+        &StubbedRewardSetProvider(reward_set.clone()),
+        0,
+        tip.block_height,
+    )
+    .unwrap();
+    assert_eq!(divergences, vec![]);
+
+    // and the replay didn't store anything
+    let replayed_tip = SortitionDB::get_canonical_burn_chain_tip(rw_sort_db.conn()).unwrap();
+    assert_eq!(replayed_tip, tip);
+
+    // corrupt one recorded snapshot, and the replay reports exactly that field at that height
+    let corrupted =
+        SortitionDB::get_ancestor_snapshot(&rw_sort_db.index_conn(), 12, &tip.sortition_id)
+            .unwrap()
+            .unwrap();
+    rw_sort_db
+        .conn()
+        .execute(
+            "UPDATE snapshots SET burn_header_timestamp = 12345 WHERE sortition_id = ?1",
+            &[&corrupted.sortition_id],
+        )
+        .unwrap();
+
+    let divergences = replay_sortitions(
+        &b,
+        &burnchain_db,
+        &mut rw_sort_db,
+        &mut chainstate,
+        &StubbedRewardSetProvider(reward_set),
+        0,
+        tip.block_height,
+    )
+    .unwrap();
+    assert_eq!(
+        divergences,
+        vec![SortitionDivergence {
+            burn_block_height: 12,
+            burn_header_hash: corrupted.burn_header_hash.clone(),
+            field: "burn_header_timestamp".to_string(),
+            recorded: "12345".to_string(),
+            replayed: format!("{}", corrupted.burn_header_timestamp),
+        }]
+    );
 }
 
 #[test]
@@ -3856,6 +4032,83 @@ fn preprocess_block(
         .unwrap();
 }
 
+/// Store the header of a made-up block at `height` on top of `parent`, without processing it.
+/// Blocks on different forks get different `fork` bytes.
+fn store_fork_header(
+    chainstate: &mut StacksChainState,
+    parent: &StacksBlockId,
+    height: u64,
+    fork: u8,
+) -> (StacksBlockId, u64) {
+    let mut header_info = StacksHeaderInfo::regtest_genesis();
+    header_info.block_height = height;
+    header_info.anchored_header.total_work.work = height;
+    header_info.anchored_header.microblock_pubkey_hash = Hash160([fork; 20]);
+    let mut consensus_hash = [fork; 20];
+    consensus_hash[0] = height as u8;
+    header_info.consensus_hash = ConsensusHash(consensus_hash);
+
+    let mut tx = chainstate.index_tx_begin().unwrap();
+    StacksChainState::insert_stacks_block_header(
+        &mut tx,
+        parent,
+        &header_info,
+        &ExecutionCost::zero(),
+    )
+    .unwrap();
+    tx.commit().unwrap();
+    (header_info.index_block_hash(), height)
+}
+
+#[test]
+fn test_canonical_fork_change_find() {
+    let mut chainstate = chainstate::stacks::db::test::instantiate_chainstate(
+        false,
+        0x80000000,
+        "canonical-fork-change-find",
+    );
+
+    // genesis -> a_1 -> a_2 -> a_3
+    //              \-> b_2 -> b_3 -> b_4
+    let a_1 = store_fork_header(&mut chainstate, &FIRST_STACKS_BLOCK_ID, 1, 0xaa);
+    let a_2 = store_fork_header(&mut chainstate, &a_1.0, 2, 0xaa);
+    let a_3 = store_fork_header(&mut chainstate, &a_2.0, 3, 0xaa);
+    let b_2 = store_fork_header(&mut chainstate, &a_1.0, 2, 0xbb);
+    let b_3 = store_fork_header(&mut chainstate, &b_2.0, 3, 0xbb);
+    let b_4 = store_fork_header(&mut chainstate, &b_3.0, 4, 0xbb);
+
+    let fork_change = CanonicalForkChange::find(&chainstate, &a_3.0, &b_4.0)
+        .unwrap()
+        .unwrap();
+    assert_eq!(fork_change.old_tip, a_3);
+    assert_eq!(fork_change.new_tip, b_4);
+    assert_eq!(fork_change.fork_point, a_1);
+    assert_eq!(fork_change.orphaned, vec![a_3.clone(), a_2.clone()]);
+    assert_eq!(
+        fork_change.joined,
+        vec![b_2.clone(), b_3.clone(), b_4.clone()]
+    );
+
+    // and back again
+    let fork_change = CanonicalForkChange::find(&chainstate, &b_4.0, &a_3.0)
+        .unwrap()
+        .unwrap();
+    assert_eq!(fork_change.fork_point, a_1);
+    assert_eq!(
+        fork_change.orphaned,
+        vec![b_4.clone(), b_3.clone(), b_2.clone()]
+    );
+    assert_eq!(fork_change.joined, vec![a_2.clone(), a_3.clone()]);
+
+    // extending the tip isn't a reorg
+    assert!(CanonicalForkChange::find(&chainstate, &b_2.0, &b_4.0)
+        .unwrap()
+        .is_none());
+    assert!(CanonicalForkChange::find(&chainstate, &b_4.0, &b_4.0)
+        .unwrap()
+        .is_none());
+}
+
 #[test]
 fn test_check_chainstate_db_versions() {
     let path = "/tmp/stacks-blockchain-check_chainstate_db_versions";
@@ -3929,4 +4182,36 @@ fn test_check_chainstate_db_versions() {
         !check_chainstate_db_versions(&[epoch_2_05.clone()], &sortdb_path, &chainstate_path)
             .unwrap()
     );
+
+    // freshly-instantiated DBs are at the latest versions, which work in every epoch
+    let path = "/tmp/stacks-blockchain-check_chainstate_db_versions-latest";
+    let _ = std::fs::remove_dir_all(path);
+
+    let sortdb_path = format!("{}/sortdb", &path);
+    let chainstate_path = format!("{}/chainstate", &path);
+
+    let _sortdb = SortitionDB::connect(
+        &sortdb_path,
+        0,
+        &BurnchainHeaderHash([0x00; 32]),
+        0,
+        &StacksEpoch::unit_test_2_05(0),
+        true,
+    )
+    .unwrap();
+    let _chainstate = StacksChainState::open(false, CHAIN_ID_TESTNET, &chainstate_path).unwrap();
+
+    assert_eq!(
+        StacksChainState::get_db_config_from_path(&chainstate_path)
+            .unwrap()
+            .version,
+        CHAINSTATE_VERSION
+    );
+    assert!(
+        check_chainstate_db_versions(&[epoch_2.clone()], &sortdb_path, &chainstate_path).unwrap()
+    );
+    assert!(
+        check_chainstate_db_versions(&[epoch_2_05.clone()], &sortdb_path, &chainstate_path)
+            .unwrap()
+    );
 }
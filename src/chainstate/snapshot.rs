@@ -0,0 +1,773 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chainstate snapshot archives.
+//!
+//! A snapshot archive holds a consistent copy of a node's chainstate (headers, block files, and
+//! the Clarity MARF) and burnchain state (sortition DB, burnchain DB, and SPV headers), so a
+//! fresh node can start from the snapshot's tip and only sync subsequent blocks.
+//!
+//! The archive layout is:
+//!
+//! ```text
+//! | SNAPSHOT_MAGIC (8 bytes) | manifest length (u64, big-endian) | manifest JSON | file 0 | file 1 | ...
+//! ```
+//!
+//! The manifest records the chain tip and the size and SHA256 of every file, so the SHA256 of
+//! the manifest JSON (the snapshot digest) commits to the entire archive.  Importers verify each
+//! file as it is streamed to disk, and can pin the digest they expect ahead of time.
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use rusqlite::OpenFlags;
+use serde_json;
+use sha2::{Digest, Sha256};
+
+use chainstate::burn::db::sortdb::SortitionDB;
+use chainstate::burn::ConsensusHash;
+use chainstate::stacks::db::StacksChainState;
+use chainstate::stacks::Error as chainstate_error;
+use util::db::sqlite_open;
+use util::db::Error as db_error;
+use util::hash::{to_hex, Sha256Sum};
+
+use crate::types::chainstate::BlockHeaderHash;
+use crate::types::proof::TrieHash;
+
+/// First bytes of every snapshot archive
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"STXSNAP\x00";
+/// Version of the archive layout and manifest
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Refuse manifests larger than this, so a corrupt length prefix can't exhaust memory
+const SNAPSHOT_MAX_MANIFEST_LEN: u64 = 64 * 1024 * 1024;
+const SNAPSHOT_COPY_BUFFER_LEN: usize = 65536;
+
+/// Chainstate subdirectories that go into a snapshot.  The mempool, fee estimator, and peer DBs
+/// are node-local and are left out.
+const SNAPSHOT_CHAINSTATE_DIRS: &[&str] = &["vm", "blocks"];
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\x00";
+
+#[derive(Debug)]
+pub enum Error {
+    /// I/O error reading or writing the archive or the databases
+    IOError(io::Error),
+    /// Error querying the chainstate or sortition DB
+    DBError(db_error),
+    /// Error reading chainstate headers
+    ChainstateError(chainstate_error),
+    /// The manifest could not be encoded or decoded
+    SerializationError(serde_json::Error),
+    /// The archive is malformed or of an unsupported version
+    InvalidArchive(String),
+    /// A file or the manifest does not match its expected digest
+    DigestMismatch(String),
+    /// The snapshot's chain tip is not present in its own databases
+    InvalidTip(String),
+    /// The import destination already holds chainstate
+    AlreadyExists(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::IOError(ref e) => fmt::Display::fmt(e, f),
+            Error::DBError(ref e) => fmt::Display::fmt(e, f),
+            Error::ChainstateError(ref e) => fmt::Display::fmt(e, f),
+            Error::SerializationError(ref e) => fmt::Display::fmt(e, f),
+            Error::InvalidArchive(ref s) => write!(f, "Invalid snapshot archive: {}", s),
+            Error::DigestMismatch(ref s) => write!(f, "Snapshot digest mismatch: {}", s),
+            Error::InvalidTip(ref s) => write!(f, "Invalid snapshot chain tip: {}", s),
+            Error::AlreadyExists(ref s) => write!(f, "Chainstate already exists at {}", s),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            Error::IOError(ref e) => Some(e),
+            Error::DBError(ref e) => Some(e),
+            Error::ChainstateError(ref e) => Some(e),
+            Error::SerializationError(ref e) => Some(e),
+            Error::InvalidArchive(_) => None,
+            Error::DigestMismatch(_) => None,
+            Error::InvalidTip(_) => None,
+            Error::AlreadyExists(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::IOError(e)
+    }
+}
+
+impl From<db_error> for Error {
+    fn from(e: db_error) -> Error {
+        Error::DBError(e)
+    }
+}
+
+impl From<chainstate_error> for Error {
+    fn from(e: chainstate_error) -> Error {
+        Error::ChainstateError(e)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Error {
+        Error::DBError(db_error::SqliteError(e))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::SerializationError(e)
+    }
+}
+
+/// One file in a snapshot archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotFileEntry {
+    /// path relative to the node's working directory, e.g. `chainstate/vm/index.sqlite`
+    pub path: String,
+    pub size: u64,
+    /// hex-encoded SHA256 of the file contents
+    pub sha256: String,
+}
+
+/// Describes the chain tip a snapshot was taken at, and every file it contains
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub mainnet: bool,
+    pub chain_id: u32,
+    /// canonical burnchain tip
+    pub burn_block_height: u64,
+    pub burn_consensus_hash: ConsensusHash,
+    /// canonical Stacks tip
+    pub stacks_block_height: u64,
+    pub stacks_consensus_hash: ConsensusHash,
+    pub stacks_block_hash: BlockHeaderHash,
+    /// root hash of the chainstate MARF at the Stacks tip
+    pub index_root: TrieHash,
+    pub files: Vec<SnapshotFileEntry>,
+}
+
+impl SnapshotManifest {
+    /// The snapshot digest.  Since the manifest records every file's hash, this commits to the
+    /// whole archive.
+    pub fn digest(&self) -> Result<Sha256Sum, Error> {
+        let bytes = serde_json::to_vec(self)?;
+        Ok(Sha256Sum::from_data(&bytes))
+    }
+}
+
+/// Streaming SHA256 over everything written through it
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> HashingWriter<W> {
+        HashingWriter {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    fn finish(self) -> (W, String) {
+        (self.inner, to_hex(self.hasher.result().as_slice()))
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let nw = self.inner.write(buf)?;
+        self.hasher.input(&buf[0..nw]);
+        Ok(nw)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Copy exactly `len` bytes from `fd` to `out`
+fn copy_exact<R: Read, W: Write>(fd: &mut R, out: &mut W, len: u64) -> Result<(), Error> {
+    let mut buf = [0u8; SNAPSHOT_COPY_BUFFER_LEN];
+    let mut remaining = len;
+    while remaining > 0 {
+        let to_read = (buf.len() as u64).min(remaining) as usize;
+        fd.read_exact(&mut buf[0..to_read])?;
+        out.write_all(&buf[0..to_read])?;
+        remaining -= to_read as u64;
+    }
+    Ok(())
+}
+
+fn hash_file(path: &Path) -> Result<(u64, String), Error> {
+    let mut fd = fs::File::open(path)?;
+    let size = fd.metadata()?.len();
+    let mut writer = HashingWriter::new(io::sink());
+    copy_exact(&mut fd, &mut writer, size)?;
+    let (_, sha256) = writer.finish();
+    Ok((size, sha256))
+}
+
+fn is_sqlite_file(path: &Path) -> Result<bool, Error> {
+    let mut header = [0u8; 16];
+    let mut fd = fs::File::open(path)?;
+    match fd.read_exact(&mut header) {
+        Ok(()) => Ok(&header == SQLITE_HEADER),
+        Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// List every file under `root`, relative to `root`, in sorted order.
+/// SQLite journals are skipped, since snapshots copy databases in a consistent state.
+fn list_files(root: &Path, rel: &Path, out: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let mut entries = vec![];
+    for entry in fs::read_dir(root.join(rel))? {
+        entries.push(entry?);
+    }
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries.into_iter() {
+        let rel_path = rel.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            list_files(root, &rel_path, out)?;
+        } else {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with("-wal") || name.ends_with("-shm") || name.ends_with("-journal") {
+                continue;
+            }
+            out.push(rel_path);
+        }
+    }
+    Ok(())
+}
+
+/// Archive paths are always relative, `/`-separated, and free of `..`
+fn archive_entry_path(prefix: &str, rel: &Path) -> String {
+    let mut parts = vec![prefix.to_string()];
+    for component in rel.components() {
+        parts.push(component.as_os_str().to_string_lossy().to_string());
+    }
+    parts.join("/")
+}
+
+fn check_archive_path(path: &str) -> Result<PathBuf, Error> {
+    let pathbuf = PathBuf::from(path);
+    let mut components = pathbuf.components();
+    match components.next() {
+        Some(Component::Normal(first)) if first == "chainstate" || first == "burnchain" => {}
+        _ => {
+            return Err(Error::InvalidArchive(format!("Bad file path '{}'", path)));
+        }
+    }
+    for component in components {
+        match component {
+            Component::Normal(_) => {}
+            _ => {
+                return Err(Error::InvalidArchive(format!("Bad file path '{}'", path)));
+            }
+        }
+    }
+    Ok(pathbuf)
+}
+
+/// Read the chain tip a snapshot would be taken at, and fill in the manifest header
+fn read_snapshot_tip(
+    chainstate_path: &str,
+    burnchain_path: &str,
+) -> Result<SnapshotManifest, Error> {
+    let mut sortition_path = PathBuf::from(burnchain_path);
+    sortition_path.push("sortition");
+    let sortdb = SortitionDB::open(&sortition_path.to_string_lossy(), false)?;
+    let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+    let (stacks_consensus_hash, stacks_block_hash) =
+        SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())?;
+
+    let index_path = StacksChainState::header_index_root_path(PathBuf::from(chainstate_path));
+    let index_conn = sqlite_open(&index_path, OpenFlags::SQLITE_OPEN_READ_ONLY, false)?;
+    let db_config = StacksChainState::load_db_config(&index_conn)?;
+    let header_info = StacksChainState::get_anchored_block_header_info(
+        &index_conn,
+        &stacks_consensus_hash,
+        &stacks_block_hash,
+    )?
+    .ok_or_else(|| {
+        Error::InvalidTip(format!(
+            "No header for canonical Stacks tip {}/{}",
+            &stacks_consensus_hash, &stacks_block_hash
+        ))
+    })?;
+
+    Ok(SnapshotManifest {
+        version: SNAPSHOT_FORMAT_VERSION,
+        mainnet: db_config.mainnet,
+        chain_id: db_config.chain_id,
+        burn_block_height: burn_tip.block_height,
+        burn_consensus_hash: burn_tip.consensus_hash,
+        stacks_block_height: header_info.block_height,
+        stacks_consensus_hash,
+        stacks_block_hash,
+        index_root: header_info.index_root,
+        files: vec![],
+    })
+}
+
+/// Check that an unpacked snapshot's databases contain the tip its manifest claims
+fn verify_snapshot_tip(
+    manifest: &SnapshotManifest,
+    chainstate_path: &str,
+    burnchain_path: &str,
+) -> Result<(), Error> {
+    let found = read_snapshot_tip(chainstate_path, burnchain_path)?;
+    if found.mainnet != manifest.mainnet || found.chain_id != manifest.chain_id {
+        return Err(Error::InvalidTip(format!(
+            "Chainstate is for mainnet={} chain ID {:08x}, but manifest says mainnet={} chain ID {:08x}",
+            found.mainnet, found.chain_id, manifest.mainnet, manifest.chain_id
+        )));
+    }
+    if found.burn_consensus_hash != manifest.burn_consensus_hash
+        || found.burn_block_height != manifest.burn_block_height
+    {
+        return Err(Error::InvalidTip(format!(
+            "Burnchain tip is {} at height {}, but manifest says {} at height {}",
+            &found.burn_consensus_hash,
+            found.burn_block_height,
+            &manifest.burn_consensus_hash,
+            manifest.burn_block_height
+        )));
+    }
+    if found.stacks_consensus_hash != manifest.stacks_consensus_hash
+        || found.stacks_block_hash != manifest.stacks_block_hash
+        || found.stacks_block_height != manifest.stacks_block_height
+        || found.index_root != manifest.index_root
+    {
+        return Err(Error::InvalidTip(format!(
+            "Stacks tip is {}/{} at height {} (root {}), but manifest says {}/{} at height {} (root {})",
+            &found.stacks_consensus_hash,
+            &found.stacks_block_hash,
+            found.stacks_block_height,
+            &found.index_root,
+            &manifest.stacks_consensus_hash,
+            &manifest.stacks_block_hash,
+            manifest.stacks_block_height,
+            &manifest.index_root
+        )));
+    }
+    Ok(())
+}
+
+/// Export a node's chainstate and burnchain state into a snapshot archive at `archive_path`.
+/// SQLite databases are copied with `VACUUM INTO`, so the node may keep running while this
+/// happens.  The burnchain state is copied before the chainstate, so the copied chainstate
+/// always has the headers the copied sortition DB's canonical Stacks tip points to.
+/// Returns the manifest; its digest identifies the archive.
+pub fn export_snapshot(
+    chainstate_path: &str,
+    burnchain_path: &str,
+    archive_path: &str,
+) -> Result<SnapshotManifest, Error> {
+    let staging_dir = PathBuf::from(format!("{}.staging", archive_path));
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+    fs::create_dir_all(&staging_dir)?;
+
+    // (archive path, path of the consistent copy to archive)
+    let mut sources: Vec<(String, PathBuf)> = vec![];
+    let roots: Vec<(&str, PathBuf, Vec<PathBuf>)> = vec![
+        (
+            "burnchain",
+            PathBuf::from(burnchain_path),
+            vec![PathBuf::new()],
+        ),
+        (
+            "chainstate",
+            PathBuf::from(chainstate_path),
+            SNAPSHOT_CHAINSTATE_DIRS
+                .iter()
+                .map(|dir| PathBuf::from(dir))
+                .collect(),
+        ),
+    ];
+    for (prefix, root, subdirs) in roots.into_iter() {
+        let mut files = vec![];
+        for subdir in subdirs.iter() {
+            if root.join(subdir).exists() {
+                list_files(&root, subdir, &mut files)?;
+            }
+        }
+        for rel in files.into_iter() {
+            let src_path = root.join(&rel);
+            let entry_path = archive_entry_path(prefix, &rel);
+            if is_sqlite_file(&src_path)? {
+                // take a transactionally-consistent copy
+                let copy_path = staging_dir.join(prefix).join(&rel);
+                fs::create_dir_all(copy_path.parent().expect("BUG: file has no parent"))?;
+                let conn = sqlite_open(&src_path, OpenFlags::SQLITE_OPEN_READ_ONLY, false)?;
+                conn.execute(
+                    "VACUUM INTO ?1",
+                    &[&copy_path.to_string_lossy().to_string()],
+                )?;
+                sources.push((entry_path, copy_path));
+            } else {
+                // block files are written once and never modified
+                sources.push((entry_path, src_path));
+            }
+        }
+    }
+
+    // the tip is read from the copies, so it is exactly what an importer will find
+    let mut manifest = read_snapshot_tip(
+        &staging_dir.join("chainstate").to_string_lossy(),
+        &staging_dir.join("burnchain").to_string_lossy(),
+    )?;
+    info!(
+        "Exporting chainstate snapshot at Stacks tip {}/{} (height {}), burnchain height {}",
+        &manifest.stacks_consensus_hash,
+        &manifest.stacks_block_hash,
+        manifest.stacks_block_height,
+        manifest.burn_block_height
+    );
+
+    for (entry_path, src_path) in sources.iter() {
+        let (size, sha256) = hash_file(src_path)?;
+        manifest.files.push(SnapshotFileEntry {
+            path: entry_path.clone(),
+            size,
+            sha256,
+        });
+    }
+
+    let manifest_bytes = serde_json::to_vec(&manifest)?;
+    let mut out = io::BufWriter::new(fs::File::create(archive_path)?);
+    out.write_all(SNAPSHOT_MAGIC)?;
+    out.write_all(&(manifest_bytes.len() as u64).to_be_bytes())?;
+    out.write_all(&manifest_bytes)?;
+    for ((_, src_path), entry) in sources.iter().zip(manifest.files.iter()) {
+        let mut fd = fs::File::open(src_path)?;
+        copy_exact(&mut fd, &mut out, entry.size)?;
+    }
+    out.flush()?;
+    fs::remove_dir_all(&staging_dir)?;
+
+    info!(
+        "Exported {} files to chainstate snapshot {} (digest {})",
+        manifest.files.len(),
+        archive_path,
+        manifest.digest()?
+    );
+    Ok(manifest)
+}
+
+/// Read and decode an archive's manifest, leaving `fd` positioned at the first file
+pub fn read_snapshot_manifest<R: Read>(fd: &mut R) -> Result<SnapshotManifest, Error> {
+    let mut magic = [0u8; 8];
+    fd.read_exact(&mut magic)?;
+    if &magic != SNAPSHOT_MAGIC {
+        return Err(Error::InvalidArchive(
+            "Not a chainstate snapshot".to_string(),
+        ));
+    }
+    let mut len_bytes = [0u8; 8];
+    fd.read_exact(&mut len_bytes)?;
+    let len = u64::from_be_bytes(len_bytes);
+    if len > SNAPSHOT_MAX_MANIFEST_LEN {
+        return Err(Error::InvalidArchive(format!(
+            "Manifest is too big ({} bytes)",
+            len
+        )));
+    }
+    let mut manifest_bytes = vec![0u8; len as usize];
+    fd.read_exact(&mut manifest_bytes)?;
+    let manifest: SnapshotManifest = serde_json::from_slice(&manifest_bytes)?;
+    if manifest.version != SNAPSHOT_FORMAT_VERSION {
+        return Err(Error::InvalidArchive(format!(
+            "Unsupported snapshot version {}",
+            manifest.version
+        )));
+    }
+    Ok(manifest)
+}
+
+fn is_empty_or_missing(path: &Path) -> Result<bool, Error> {
+    match fs::read_dir(path) {
+        Ok(mut entries) => Ok(entries.next().is_none()),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Unpack a snapshot archive read from `fd` into a fresh node's chainstate and burnchain
+/// directories.  Every file is hashed as it is streamed to disk, and if `expected_digest` is
+/// given, the manifest must match it.  Nothing is moved into place until every file and the
+/// snapshot tip have been verified.
+pub fn import_snapshot_from_reader<R: Read>(
+    fd: &mut R,
+    chainstate_path: &str,
+    burnchain_path: &str,
+    expected_digest: Option<&Sha256Sum>,
+) -> Result<SnapshotManifest, Error> {
+    for path in [chainstate_path, burnchain_path].iter() {
+        if !is_empty_or_missing(Path::new(path))? {
+            return Err(Error::AlreadyExists(path.to_string()));
+        }
+    }
+
+    let manifest = read_snapshot_manifest(fd)?;
+    let digest = manifest.digest()?;
+    if let Some(expected_digest) = expected_digest {
+        if &digest != expected_digest {
+            return Err(Error::DigestMismatch(format!(
+                "manifest digest is {}, expected {}",
+                &digest, expected_digest
+            )));
+        }
+    }
+    info!(
+        "Importing chainstate snapshot {} at Stacks tip {}/{} (height {}), burnchain height {}",
+        &digest,
+        &manifest.stacks_consensus_hash,
+        &manifest.stacks_block_hash,
+        manifest.stacks_block_height,
+        manifest.burn_block_height
+    );
+
+    let staging_dir = PathBuf::from(format!("{}.snapshot-import", chainstate_path));
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+    fs::create_dir_all(&staging_dir)?;
+
+    let total_bytes: u64 = manifest.files.iter().map(|entry| entry.size).sum();
+    let mut bytes_done: u64 = 0;
+    for entry in manifest.files.iter() {
+        let rel_path = check_archive_path(&entry.path)?;
+        let dest_path = staging_dir.join(&rel_path);
+        fs::create_dir_all(dest_path.parent().expect("BUG: file has no parent"))?;
+
+        let mut writer = HashingWriter::new(io::BufWriter::new(fs::File::create(&dest_path)?));
+        copy_exact(fd, &mut writer, entry.size)?;
+        let (mut out, sha256) = writer.finish();
+        out.flush()?;
+        if sha256 != entry.sha256 {
+            let _ = fs::remove_dir_all(&staging_dir);
+            return Err(Error::DigestMismatch(format!(
+                "{} has SHA256 {}, expected {}",
+                &entry.path, &sha256, &entry.sha256
+            )));
+        }
+
+        bytes_done += entry.size;
+        debug!(
+            "Imported {} ({} of {} bytes)",
+            &entry.path, bytes_done, total_bytes
+        );
+    }
+
+    let staged_chainstate = staging_dir.join("chainstate");
+    let staged_burnchain = staging_dir.join("burnchain");
+    if let Err(e) = verify_snapshot_tip(
+        &manifest,
+        &staged_chainstate.to_string_lossy(),
+        &staged_burnchain.to_string_lossy(),
+    ) {
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(e);
+    }
+
+    for (staged, dest) in [
+        (staged_chainstate, chainstate_path),
+        (staged_burnchain, burnchain_path),
+    ]
+    .iter()
+    {
+        if Path::new(dest).exists() {
+            fs::remove_dir(dest)?;
+        }
+        if let Some(parent) = Path::new(dest).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(staged, dest)?;
+    }
+    fs::remove_dir_all(&staging_dir)?;
+
+    info!(
+        "Imported {} files ({} bytes) from chainstate snapshot {}",
+        manifest.files.len(),
+        total_bytes,
+        &digest
+    );
+    Ok(manifest)
+}
+
+/// Unpack the snapshot archive at `archive_path`.  See `import_snapshot_from_reader`.
+pub fn import_snapshot(
+    archive_path: &str,
+    chainstate_path: &str,
+    burnchain_path: &str,
+    expected_digest: Option<&Sha256Sum>,
+) -> Result<SnapshotManifest, Error> {
+    let mut fd = io::BufReader::new(fs::File::open(archive_path)?);
+    import_snapshot_from_reader(&mut fd, chainstate_path, burnchain_path, expected_digest)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use chainstate::stacks::db::test::instantiate_chainstate;
+    use core::StacksEpoch;
+    use util::get_epoch_time_secs;
+
+    use crate::types::chainstate::BurnchainHeaderHash;
+
+    fn setup_node_dirs(test_name: &str) -> (String, String, String) {
+        let root = format!("/tmp/test-chainstate-snapshot-{}", test_name);
+        if fs::metadata(&root).is_ok() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(&root).unwrap();
+
+        let instantiated_path = instantiate_chainstate(false, 0x80000000, test_name).root_path;
+        let chainstate_path = format!("{}/chainstate", &root);
+        fs::rename(&instantiated_path, &chainstate_path).unwrap();
+
+        let burnchain_path = format!("{}/burnchain", &root);
+        SortitionDB::connect(
+            &format!("{}/sortition", &burnchain_path),
+            0,
+            &BurnchainHeaderHash([0u8; 32]),
+            get_epoch_time_secs(),
+            &StacksEpoch::unit_test_pre_2_05(0),
+            true,
+        )
+        .unwrap();
+
+        (root, chainstate_path, burnchain_path)
+    }
+
+    #[test]
+    fn test_export_import_snapshot() {
+        let (root, chainstate_path, burnchain_path) = setup_node_dirs("export-import");
+        let archive = format!("{}/snapshot.bin", &root);
+
+        let manifest = export_snapshot(&chainstate_path, &burnchain_path, &archive).unwrap();
+        assert!(!manifest.mainnet);
+        assert_eq!(manifest.chain_id, 0x80000000);
+        assert!(manifest
+            .files
+            .iter()
+            .any(|entry| entry.path == "chainstate/vm/index.sqlite"));
+        assert!(manifest
+            .files
+            .iter()
+            .any(|entry| entry.path.starts_with("burnchain/sortition/")));
+        assert!(!Path::new(&format!("{}.staging", &archive)).exists());
+
+        let digest = manifest.digest().unwrap();
+        let import_chainstate = format!("{}/imported/chainstate", &root);
+        let import_burnchain = format!("{}/imported/burnchain", &root);
+
+        // wrong digest is rejected before anything is written
+        match import_snapshot(
+            &archive,
+            &import_chainstate,
+            &import_burnchain,
+            Some(&Sha256Sum([1u8; 32])),
+        ) {
+            Err(Error::DigestMismatch(_)) => {}
+            x => panic!("Expected digest mismatch, got {:?}", x),
+        }
+        assert!(!Path::new(&import_chainstate).exists());
+
+        let imported = import_snapshot(
+            &archive,
+            &import_chainstate,
+            &import_burnchain,
+            Some(&digest),
+        )
+        .unwrap();
+        assert_eq!(imported, manifest);
+        verify_snapshot_tip(&imported, &import_chainstate, &import_burnchain).unwrap();
+
+        // won't clobber existing chainstate
+        match import_snapshot(&archive, &import_chainstate, &import_burnchain, None) {
+            Err(Error::AlreadyExists(_)) => {}
+            x => panic!("Expected already-exists, got {:?}", x),
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_import_corrupt_snapshot() {
+        let (root, chainstate_path, burnchain_path) = setup_node_dirs("corrupt");
+        let archive = format!("{}/snapshot.bin", &root);
+        export_snapshot(&chainstate_path, &burnchain_path, &archive).unwrap();
+
+        // flip the last byte, which belongs to the last file
+        let mut bytes = fs::read(&archive).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+
+        let import_chainstate = format!("{}/imported/chainstate", &root);
+        let import_burnchain = format!("{}/imported/burnchain", &root);
+        match import_snapshot_from_reader(
+            &mut &bytes[..],
+            &import_chainstate,
+            &import_burnchain,
+            None,
+        ) {
+            Err(Error::DigestMismatch(_)) => {}
+            x => panic!("Expected digest mismatch, got {:?}", x),
+        }
+        assert!(!Path::new(&import_chainstate).exists());
+        assert!(!Path::new(&format!("{}.snapshot-import", &import_chainstate)).exists());
+
+        // not a snapshot at all
+        match import_snapshot_from_reader(
+            &mut &b"not a snapshot"[..],
+            &import_chainstate,
+            &import_burnchain,
+            None,
+        ) {
+            Err(Error::InvalidArchive(_)) => {}
+            x => panic!("Expected invalid archive, got {:?}", x),
+        }
+
+        assert!(check_archive_path("chainstate/../../etc/passwd").is_err());
+        assert!(check_archive_path("/etc/passwd").is_err());
+        assert!(check_archive_path("peer.sqlite").is_err());
+        assert!(check_archive_path("chainstate/vm/index.sqlite").is_ok());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
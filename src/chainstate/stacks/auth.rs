@@ -178,8 +178,14 @@ impl StacksMessageCodec for MultisigSpendingCondition {
             };
         }
 
-        // must be given the right number of signatures
-        if num_sigs_given != signatures_required {
+        // must be given the right number of signatures (or, if order-independent, at least
+        // that many)
+        let enough_sigs = if hash_mode.is_order_independent() {
+            num_sigs_given >= signatures_required
+        } else {
+            num_sigs_given == signatures_required
+        };
+        if !enough_sigs {
             test_debug!(
                 "Failed to deserialize multisig spending condition: got {} sigs, expected {}",
                 num_sigs_given,
@@ -192,7 +198,7 @@ impl StacksMessageCodec for MultisigSpendingCondition {
         }
 
         // must all be compressed if we're using P2WSH
-        if have_uncompressed && hash_mode == MultisigHashMode::P2WSH {
+        if have_uncompressed && hash_mode.requires_compressed_keys() {
             test_debug!(
                 "Failed to deserialize multisig spending condition: expected compressed keys only"
             );
@@ -246,6 +252,140 @@ impl MultisigSpendingCondition {
         }
     }
 
+    /// Fill in the fields of an order-independent condition from the ordered list of its
+    /// public keys and from `signatures`, which may be given in any order.  Each signature is
+    /// placed in the slot of the key it recovers to; keys without a signature are included as
+    /// public keys.  `initial_sighash` is the sighash the signers signed.
+    pub fn set_order_independent_fields(
+        &mut self,
+        initial_sighash: &Txid,
+        cond_code: &TransactionAuthFlags,
+        pubkeys: &[StacksPublicKey],
+        signatures: &[TransactionAuthField],
+    ) -> Result<(), net_error> {
+        if !self.hash_mode.is_order_independent() {
+            return Err(net_error::SigningError(
+                "Not an order-independent multisig condition".to_string(),
+            ));
+        }
+
+        let mut fields: Vec<TransactionAuthField> = pubkeys
+            .iter()
+            .map(|pubkey| TransactionAuthField::PublicKey(pubkey.clone()))
+            .collect();
+        for signature in signatures.iter() {
+            let (key_encoding, sig) = match signature {
+                TransactionAuthField::Signature(ref key_encoding, ref sig) => (key_encoding, sig),
+                TransactionAuthField::PublicKey(_) => {
+                    return Err(net_error::SigningError(
+                        "Expected a signature, got a public key".to_string(),
+                    ))
+                }
+            };
+            let (pubkey, _) = TransactionSpendingCondition::next_verification(
+                initial_sighash,
+                cond_code,
+                self.tx_fee,
+                self.nonce,
+                key_encoding,
+                sig,
+            )?;
+            let slot = fields
+                .iter()
+                .position(|field| match field {
+                    TransactionAuthField::PublicKey(ref slot_pubkey) => *slot_pubkey == pubkey,
+                    TransactionAuthField::Signature(..) => false,
+                })
+                .ok_or_else(|| {
+                    net_error::SigningError(format!(
+                        "Signature by {} does not match an unsigned key",
+                        pubkey.to_hex()
+                    ))
+                })?;
+            fields[slot] = signature.clone();
+        }
+
+        self.fields = fields;
+        Ok(())
+    }
+
+    /// Authenticate an order-independent spending condition against an initial sighash.
+    /// Every signature is over the same sighash, and at least `signatures_required` must be
+    /// given.  Returns the initial sighash, which a sponsor signs in turn.
+    fn verify_order_independent(
+        &self,
+        initial_sighash: &Txid,
+        cond_code: &TransactionAuthFlags,
+    ) -> Result<Txid, net_error> {
+        let mut pubkeys = vec![];
+        let mut num_sigs: u16 = 0;
+        let mut have_uncompressed = false;
+        for field in self.fields.iter() {
+            let pubkey = match field {
+                TransactionAuthField::PublicKey(ref pubkey) => {
+                    if !pubkey.compressed() {
+                        have_uncompressed = true;
+                    }
+                    pubkey.clone()
+                }
+                TransactionAuthField::Signature(ref pubkey_encoding, ref sigbuf) => {
+                    if *pubkey_encoding == TransactionPublicKeyEncoding::Uncompressed {
+                        have_uncompressed = true;
+                    }
+
+                    let (pubkey, _) = TransactionSpendingCondition::next_verification(
+                        initial_sighash,
+                        cond_code,
+                        self.tx_fee,
+                        self.nonce,
+                        pubkey_encoding,
+                        sigbuf,
+                    )?;
+                    num_sigs = num_sigs
+                        .checked_add(1)
+                        .ok_or(net_error::VerifyingError("Too many signatures".to_string()))?;
+                    pubkey
+                }
+            };
+            pubkeys.push(pubkey);
+        }
+
+        if num_sigs < self.signatures_required {
+            return Err(net_error::VerifyingError(
+                "Not enough signatures".to_string(),
+            ));
+        }
+
+        if have_uncompressed && self.hash_mode.requires_compressed_keys() {
+            return Err(net_error::VerifyingError(
+                "Uncompressed keys are not allowed in this hash mode".to_string(),
+            ));
+        }
+
+        let addr_bytes = match StacksAddress::from_public_keys(
+            0,
+            &self.hash_mode.to_address_hash_mode(),
+            self.signatures_required as usize,
+            &pubkeys,
+        ) {
+            Some(a) => a.bytes,
+            None => {
+                return Err(net_error::VerifyingError(
+                    "Failed to generate address from public keys".to_string(),
+                ));
+            }
+        };
+
+        if addr_bytes != self.signer {
+            return Err(net_error::VerifyingError(format!(
+                "Signer hash does not equal hash of public key(s): {} != {}",
+                addr_bytes, self.signer
+            )));
+        }
+
+        Ok(initial_sighash.clone())
+    }
+
     /// Authenticate a spending condition against an initial sighash.
     /// In doing so, recover all public keys and verify that they hash to the signer
     /// via the given hash mode.
@@ -254,6 +394,10 @@ impl MultisigSpendingCondition {
         initial_sighash: &Txid,
         cond_code: &TransactionAuthFlags,
     ) -> Result<Txid, net_error> {
+        if self.hash_mode.is_order_independent() {
+            return self.verify_order_independent(initial_sighash, cond_code);
+        }
+
         let mut pubkeys = vec![];
         let mut cur_sighash = initial_sighash.clone();
         let mut num_sigs: u16 = 0;
@@ -295,7 +439,7 @@ impl MultisigSpendingCondition {
             ));
         }
 
-        if have_uncompressed && self.hash_mode == MultisigHashMode::P2WSH {
+        if have_uncompressed && self.hash_mode.requires_compressed_keys() {
             return Err(net_error::VerifyingError(
                 "Uncompressed keys are not allowed in this hash mode".to_string(),
             ));
@@ -592,6 +736,56 @@ impl TransactionSpendingCondition {
         ))
     }
 
+    /// A multisig condition whose signers may sign in any order.  It spends from the same
+    /// address as `new_multisig_p2sh()` would with the same keys.
+    pub fn new_multisig_order_independent_p2sh(
+        num_sigs: u16,
+        pubkeys: Vec<StacksPublicKey>,
+    ) -> Option<TransactionSpendingCondition> {
+        let signer_addr = StacksAddress::from_public_keys(
+            0,
+            &AddressHashMode::SerializeP2SH,
+            num_sigs as usize,
+            &pubkeys,
+        )?;
+
+        Some(TransactionSpendingCondition::Multisig(
+            MultisigSpendingCondition {
+                signer: signer_addr.bytes.clone(),
+                nonce: 0,
+                tx_fee: 0,
+                hash_mode: MultisigHashMode::OrderIndependentP2SH,
+                fields: vec![],
+                signatures_required: num_sigs,
+            },
+        ))
+    }
+
+    /// A multisig condition whose signers may sign in any order.  It spends from the same
+    /// address as `new_multisig_p2wsh()` would with the same keys.
+    pub fn new_multisig_order_independent_p2wsh(
+        num_sigs: u16,
+        pubkeys: Vec<StacksPublicKey>,
+    ) -> Option<TransactionSpendingCondition> {
+        let signer_addr = StacksAddress::from_public_keys(
+            0,
+            &AddressHashMode::SerializeP2WSH,
+            num_sigs as usize,
+            &pubkeys,
+        )?;
+
+        Some(TransactionSpendingCondition::Multisig(
+            MultisigSpendingCondition {
+                signer: signer_addr.bytes.clone(),
+                nonce: 0,
+                tx_fee: 0,
+                hash_mode: MultisigHashMode::OrderIndependentP2WSH,
+                fields: vec![],
+                signatures_required: num_sigs,
+            },
+        ))
+    }
+
     /// When committing to the fact that a transaction is sponsored, the origin doesn't know
     /// anything else.  Instead, it commits to this sentinel value as its sponsor.
     /// It is intractable to calculate a private key that could generate this.
@@ -629,6 +823,15 @@ impl TransactionSpendingCondition {
         }
     }
 
+    pub fn is_order_independent_multisig(&self) -> bool {
+        match *self {
+            TransactionSpendingCondition::Singlesig(_) => false,
+            TransactionSpendingCondition::Multisig(ref data) => {
+                data.hash_mode.is_order_independent()
+            }
+        }
+    }
+
     pub fn signatures_required(&self) -> u16 {
         match *self {
             TransactionSpendingCondition::Singlesig(_) => 1,
@@ -957,6 +1160,16 @@ impl TransactionAuth {
         }
     }
 
+    /// Does the origin or the sponsor use an order-independent multisig condition?
+    pub fn uses_order_independent_multisig(&self) -> bool {
+        match *self {
+            TransactionAuth::Standard(ref origin) => origin.is_order_independent_multisig(),
+            TransactionAuth::Sponsored(ref origin, ref sponsor) => {
+                origin.is_order_independent_multisig() || sponsor.is_order_independent_multisig()
+            }
+        }
+    }
+
     pub fn is_standard(&self) -> bool {
         match *self {
             TransactionAuth::Standard(_) => true,
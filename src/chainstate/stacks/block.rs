@@ -246,6 +246,27 @@ impl StacksBlockHeader {
         leader_key: &LeaderKeyRegisterOp,
         block_commit: &LeaderBlockCommitOp,
         stacks_chain_tip: &BlockSnapshot,
+    ) -> Result<(), Error> {
+        self.validate_burnchain_with_verifier(
+            burn_chain_tip,
+            sortition_chain_tip,
+            leader_key,
+            block_commit,
+            stacks_chain_tip,
+            &mut VRFCachingVerifier::new(),
+        )
+    }
+
+    /// Same as validate_burnchain(), but check the VRF proof with the given verifier (which may
+    /// have already checked it ahead of time).
+    pub fn validate_burnchain_with_verifier(
+        &self,
+        burn_chain_tip: &BlockSnapshot,
+        sortition_chain_tip: &BlockSnapshot,
+        leader_key: &LeaderKeyRegisterOp,
+        block_commit: &LeaderBlockCommitOp,
+        stacks_chain_tip: &BlockSnapshot,
+        vrf_verifier: &mut VRFCachingVerifier,
     ) -> Result<(), Error> {
         // the burn chain tip's sortition must have chosen given block commit
         assert_eq!(
@@ -305,10 +326,10 @@ impl StacksBlockHeader {
 
         // this header's VRF proof must have been generated from the last sortition's sortition
         // hash (which includes the last commit's VRF seed)
-        let valid = match VRF::verify(
+        let valid = match vrf_verifier.verify(
             &leader_key.public_key,
             &self.proof,
-            &sortition_chain_tip.sortition_hash.as_bytes().to_vec(),
+            sortition_chain_tip.sortition_hash.as_bytes(),
         ) {
             Ok(v) => {
                 if !v {
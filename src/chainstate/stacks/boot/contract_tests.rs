@@ -269,6 +269,16 @@ impl HeadersDB for TestSimHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         Some(MINER_ADDR.clone())
     }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        if *id_bhh == *FIRST_INDEX_BLOCK_HASH {
+            Some(FIRST_BURNCHAIN_CONSENSUS_HASH)
+        } else {
+            self.get_burn_block_height_for_block(id_bhh)?;
+            let mut bytes = [0; 20];
+            bytes.copy_from_slice(&id_bhh.0[0..20]);
+            Some(ConsensusHash(bytes))
+        }
+    }
 }
 
 #[test]
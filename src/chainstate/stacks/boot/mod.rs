@@ -16,6 +16,7 @@
 
 use std::boxed::Box;
 use std::cmp;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::convert::TryInto;
 
@@ -57,11 +58,20 @@ const BOOT_CODE_LOCKUP: &'static str = std::include_str!("lockup.clar");
 pub const BOOT_CODE_COSTS: &'static str = std::include_str!("costs.clar");
 pub const BOOT_CODE_COSTS_2: &'static str = std::include_str!("costs-2.clar");
 pub const BOOT_CODE_COSTS_2_TESTNET: &'static str = std::include_str!("costs-2-testnet.clar");
+pub const BOOT_CODE_COSTS_3: &'static str = std::include_str!("costs-3.clar");
 const BOOT_CODE_COST_VOTING_MAINNET: &'static str = std::include_str!("cost-voting.clar");
 const BOOT_CODE_BNS: &'static str = std::include_str!("bns.clar");
 const BOOT_CODE_GENESIS: &'static str = std::include_str!("genesis.clar");
 pub const COSTS_1_NAME: &'static str = "costs";
 pub const COSTS_2_NAME: &'static str = "costs-2";
+/// Extends .costs-2 with the cost functions of the natives introduced after Stacks 2.05.  It is
+/// not deployed until the epoch that enables those natives.
+pub const COSTS_3_NAME: &'static str = "costs-3";
+
+/// NAME_GRACE_PERIOD_DURATION in the BNS contract
+pub const BNS_NAME_GRACE_PERIOD_DURATION: u64 = 5000;
+/// NAMESPACE_LAUNCHABILITY_TTL in the BNS contract
+pub const BNS_NAMESPACE_LAUNCHABILITY_TTL: u64 = 52595;
 
 lazy_static! {
     static ref BOOT_CODE_POX_MAINNET: String =
@@ -295,6 +305,22 @@ impl StacksChainState {
         reward_set
     }
 
+    /// How many reward slots a set of registered addresses would fill at the given threshold,
+    ///   counted the same way as `make_reward_set`.
+    pub fn count_reward_slots(threshold: u128, addresses: &[(StacksAddress, u128)]) -> u128 {
+        if threshold == 0 {
+            return 0;
+        }
+        let mut totals: HashMap<&StacksAddress, u128> = HashMap::new();
+        for (address, stacked_amt) in addresses.iter() {
+            let total = totals.entry(address).or_insert(0);
+            *total = total
+                .checked_add(*stacked_amt)
+                .expect("CORRUPTION: Stacker stacked > u128 max amount");
+        }
+        totals.values().map(|total| total / threshold).sum()
+    }
+
     pub fn get_threshold_from_participation(
         liquid_ustx: u128,
         participation: u128,
@@ -358,6 +384,17 @@ impl StacksChainState {
             .block_height_to_reward_cycle(current_burn_height)
             .ok_or(Error::PoxNoRewardCycle)?;
 
+        self.get_reward_addresses_in_cycle(sortdb, reward_cycle, block_id)
+    }
+
+    /// Get the addresses registered in the PoX contract for the given reward cycle, as of
+    ///   `block_id`.  Empty if PoX was voted disabled for the cycle.
+    pub fn get_reward_addresses_in_cycle(
+        &mut self,
+        sortdb: &SortitionDB,
+        reward_cycle: u64,
+        block_id: &StacksBlockId,
+    ) -> Result<Vec<(StacksAddress, u128)>, Error> {
         if !self.is_pox_active(sortdb, block_id, reward_cycle as u128)? {
             debug!(
                 "PoX was voted disabled in block {} (reward cycle {})",
@@ -486,6 +523,10 @@ pub mod test {
                 400,
             ),
         ];
+        assert_eq!(
+            StacksChainState::count_reward_slots(threshold, &addresses),
+            3
+        );
         assert_eq!(
             StacksChainState::make_reward_set(threshold, addresses).len(),
             3
@@ -94,6 +94,24 @@ impl FromRow<MinerPaymentSchedule> for MinerPaymentSchedule {
     }
 }
 
+/// Why a portion of an account's STX is locked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnlockScheduleKind {
+    /// a genesis lockup, which unlocks at a Stacks block height
+    Vesting,
+    /// a PoX lock, which unlocks at a burnchain block height
+    Pox,
+}
+
+/// One tranche of an account's STX that is (or was) locked
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnlockScheduleEntry {
+    pub kind: UnlockScheduleKind,
+    pub amount: u128,
+    pub unlock_height: u64,
+    pub unlocked: bool,
+}
+
 impl MinerReward {
     pub fn total(&self) -> u128 {
         self.coinbase
@@ -425,6 +443,59 @@ impl StacksChainState {
         Ok(principal_seq_opt.map(|(principal, seq)| (principal.into(), seq)))
     }
 
+    /// Get the genesis lockups for a principal, as (amount, stacks block height) pairs in unlock
+    /// order.
+    pub fn get_genesis_lockups(
+        conn: &DBConn,
+        principal: &PrincipalData,
+    ) -> Result<Vec<(u128, u64)>, Error> {
+        let sql = "SELECT amount, block_height FROM genesis_lockups WHERE recipient = ?1 ORDER BY block_height";
+        let args: &[&dyn ToSql] = &[&principal.to_string()];
+        let mut stmt = conn.prepare(sql).map_err(db_error::SqliteError)?;
+        let mut rows = stmt.query(args).map_err(db_error::SqliteError)?;
+        let mut lockups = vec![];
+        while let Some(row) = rows.next().map_err(db_error::SqliteError)? {
+            let amount_text: String = row.get_unwrap("amount");
+            let amount = amount_text
+                .parse::<u128>()
+                .map_err(|_| Error::DBError(db_error::ParseError))?;
+            let block_height = u64::from_column(row, "block_height")?;
+            lockups.push((amount, block_height));
+        }
+        Ok(lockups)
+    }
+
+    /// Get the full lock/unlock schedule for a principal: its genesis vesting tranches, which
+    /// unlock at Stacks block heights, and its PoX lock (if any), which unlocks at a burnchain
+    /// block height.
+    pub fn get_account_unlock_schedule(
+        conn: &DBConn,
+        principal: &PrincipalData,
+        balance: &STXBalance,
+        stacks_block_height: u64,
+        burn_block_height: u64,
+    ) -> Result<Vec<UnlockScheduleEntry>, Error> {
+        let mut schedule: Vec<_> = StacksChainState::get_genesis_lockups(conn, principal)?
+            .into_iter()
+            .map(|(amount, block_height)| UnlockScheduleEntry {
+                kind: UnlockScheduleKind::Vesting,
+                amount,
+                unlock_height: block_height,
+                unlocked: block_height <= stacks_block_height,
+            })
+            .collect();
+
+        if balance.amount_locked > 0 {
+            schedule.push(UnlockScheduleEntry {
+                kind: UnlockScheduleKind::Pox,
+                amount: balance.amount_locked,
+                unlock_height: balance.unlock_height,
+                unlocked: balance.unlock_height <= burn_block_height,
+            });
+        }
+        Ok(schedule)
+    }
+
     /// Get the scheduled miner rewards at a particular index hash
     pub fn get_scheduled_block_rewards_at_block<'a>(
         tx: &mut StacksDBTx<'a>,
@@ -866,6 +937,7 @@ mod test {
             &user_burns,
             &ExecutionCost::zero(),
             123,
+            StacksBlockStats::empty(),
             false,
         )
         .unwrap();
@@ -1122,4 +1194,86 @@ mod test {
         assert_eq!(parent_reward.tx_fees_streamed_produced, (395 * 2) / 5);
         assert_eq!(parent_reward.tx_fees_streamed_confirmed, 0);
     }
+
+    #[test]
+    fn get_account_unlock_schedule() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "get_account_unlock_schedule");
+        let addr: PrincipalData =
+            StacksAddress::from_string("ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2")
+                .unwrap()
+                .into();
+        let other: PrincipalData =
+            StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                .unwrap()
+                .into();
+
+        {
+            let mut tx = chainstate.index_tx_begin().unwrap();
+            StacksChainState::insert_genesis_lockups(
+                &mut tx,
+                &[
+                    (addr.clone(), 300, 20),
+                    (other.clone(), 100, 5),
+                    (addr.clone(), 200, 10),
+                ],
+            )
+            .unwrap();
+            tx.commit().unwrap();
+        }
+
+        assert_eq!(
+            StacksChainState::get_genesis_lockups(chainstate.db(), &addr).unwrap(),
+            vec![(200, 10), (300, 20)]
+        );
+
+        let balance = STXBalance {
+            amount_unlocked: 1000,
+            amount_locked: 500,
+            unlock_height: 150,
+        };
+        let schedule = StacksChainState::get_account_unlock_schedule(
+            chainstate.db(),
+            &addr,
+            &balance,
+            10,
+            100,
+        )
+        .unwrap();
+        assert_eq!(
+            schedule,
+            vec![
+                UnlockScheduleEntry {
+                    kind: UnlockScheduleKind::Vesting,
+                    amount: 200,
+                    unlock_height: 10,
+                    unlocked: true,
+                },
+                UnlockScheduleEntry {
+                    kind: UnlockScheduleKind::Vesting,
+                    amount: 300,
+                    unlock_height: 20,
+                    unlocked: false,
+                },
+                UnlockScheduleEntry {
+                    kind: UnlockScheduleKind::Pox,
+                    amount: 500,
+                    unlock_height: 150,
+                    unlocked: false,
+                },
+            ]
+        );
+
+        // no lockups and nothing stacked
+        let schedule = StacksChainState::get_account_unlock_schedule(
+            chainstate.db(),
+            &other,
+            &STXBalance::zero(),
+            10,
+            100,
+        )
+        .unwrap();
+        assert_eq!(schedule.len(), 1);
+        assert_eq!(schedule[0].kind, UnlockScheduleKind::Vesting);
+    }
 }
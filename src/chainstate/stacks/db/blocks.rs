@@ -15,7 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::From;
 use std::fmt;
 use std::fs;
@@ -23,6 +23,8 @@ use std::io;
 use std::io::prelude::*;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 use rand::thread_rng;
 use rand::Rng;
@@ -62,6 +64,7 @@ use util::get_epoch_time_secs;
 use util::hash::to_hex;
 use util::retry::BoundReader;
 use util::strings::StacksString;
+use util::vrf::{VRFCachingVerifier, VRFProof, VRFPublicKey};
 pub use vm::analysis::errors::{CheckError, CheckErrors};
 use vm::analysis::run_analysis;
 use vm::ast::build_ast;
@@ -144,6 +147,18 @@ pub enum MemPoolRejection {
         principal: PrincipalData,
         is_origin: bool,
     },
+    TooManyFutureTransactions {
+        principal: PrincipalData,
+        next_nonce: u64,
+        max_future_txs: u64,
+    },
+    TooManyOriginTransactions {
+        principal: PrincipalData,
+        max_txs: u64,
+        max_bytes: u64,
+    },
+    MempoolFull,
+    PolicyRejection(String, String),
     BadTransactionVersion,
     TransferRecipientIsSender(PrincipalData),
     TransferAmountMustBePositive,
@@ -177,6 +192,42 @@ impl MemPoolRejection {
                     }),
                 ),
             ),
+            TooManyFutureTransactions {
+                principal,
+                next_nonce,
+                max_future_txs,
+            } => (
+                "TooManyFutureTransactions",
+                Some(
+                    json!({"message": "Too many transactions are waiting on a missing nonce",
+                                "principal": principal.to_string(),
+                                "next_nonce": next_nonce,
+                                "max_future_txs": max_future_txs
+                    }),
+                ),
+            ),
+            TooManyOriginTransactions {
+                principal,
+                max_txs,
+                max_bytes,
+            } => (
+                "TooManyOriginTransactions",
+                Some(
+                    json!({"message": "Origin has too many pending transactions in mempool",
+                                "principal": principal.to_string(),
+                                "max_txs": max_txs,
+                                "max_bytes": max_bytes
+                    }),
+                ),
+            ),
+            MempoolFull => (
+                "MempoolFull",
+                Some(json!({"message": "Mempool is full of transactions with higher fee rates"})),
+            ),
+            PolicyRejection(policy, message) => (
+                "PolicyRejection",
+                Some(json!({"policy": policy, "message": message})),
+            ),
             BadTransactionVersion => ("BadTransactionVersion", None),
             FailedToValidate(e) => (
                 "SignatureValidation",
@@ -267,12 +318,169 @@ impl From<db_error> for MemPoolRejection {
 pub const MINIMUM_TX_FEE: u64 = 1;
 pub const MINIMUM_TX_FEE_RATE_PER_BYTE: u64 = 1;
 
+/// Number of threads used to check an anchored block's transaction signatures
+pub const TX_AUTH_CHECK_WORKERS: usize = 4;
+/// Maximum number of staging blocks the block prefetcher will hold on to.  Once it is full, the
+/// block it started on longest ago is evicted to make room.
+pub const BLOCK_PREFETCH_MAX_PENDING: usize = 16;
+
+/// Transaction signature checks for an anchored block that's about to be appended
+pub enum BlockAuthChecks {
+    /// checks still running on worker threads
+    Running(Vec<thread::JoinHandle<Result<(), String>>>),
+    /// checks that the block prefetcher already completed
+    Finished(Result<(), String>),
+}
+
+/// Staging blocks that are being loaded and checked ahead of their processing.
+/// While a block's transactions are executed and its state is committed, its staging children
+/// are loaded from the chunk store and have their transaction signatures verified on background
+/// threads, so the next call to process_next_staging_block() can start executing right away.
+/// Blocks are still executed and committed one at a time; only the loading and signature checks
+/// overlap with that work.
+/// Nothing here reads or writes the chainstate, so it has no bearing on which blocks get
+/// processed (or in what order); a block the prefetcher didn't get to is just checked inline.
+/// Children that never get processed (e.g. because a sibling fork won) are evicted oldest-first
+/// once the prefetcher is full.
+#[derive(Clone)]
+pub struct BlockPrefetcher {
+    pending: Arc<Mutex<PendingPrefetches>>,
+}
+
+/// The blocks a BlockPrefetcher is working on, and the order it started on them
+struct PendingPrefetches {
+    workers: HashMap<StacksBlockId, thread::JoinHandle<Option<PrefetchedBlock>>>,
+    order: VecDeque<StacksBlockId>,
+}
+
+/// A block loaded by the block prefetcher, and the result of checking its transactions' signatures
+type PrefetchedBlock = (StacksBlock, Result<(), String>);
+
 impl StagingBlock {
     pub fn is_first_mined(&self) -> bool {
         self.parent_anchored_block_hash == FIRST_STACKS_BLOCK_HASH
     }
 }
 
+impl BlockPrefetcher {
+    pub fn new() -> BlockPrefetcher {
+        BlockPrefetcher {
+            pending: Arc::new(Mutex::new(PendingPrefetches {
+                workers: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// Start loading a staging block from the chunk store and checking its transactions'
+    /// signatures on a background thread.  If the prefetcher is full, the oldest block in it is
+    /// evicted first; its thread runs to completion in the background.
+    /// Returns false if the block is already being prefetched.
+    pub fn start(
+        &self,
+        blocks_path: &String,
+        consensus_hash: ConsensusHash,
+        block_hash: BlockHeaderHash,
+    ) -> bool {
+        let index_block_hash =
+            StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash);
+        let mut pending = self
+            .pending
+            .lock()
+            .expect("FATAL: block prefetcher lock poisoned");
+        if pending.workers.contains_key(&index_block_hash) {
+            return false;
+        }
+        while pending.workers.len() >= BLOCK_PREFETCH_MAX_PENDING {
+            let oldest = match pending.order.pop_front() {
+                Some(oldest) => oldest,
+                None => break,
+            };
+            if pending.workers.remove(&oldest).is_some() {
+                debug!("Evicted stale prefetched block {}", &oldest);
+            }
+        }
+
+        let blocks_path = blocks_path.clone();
+        let worker = thread::Builder::new()
+            .name(format!("block-prefetch-{}", &index_block_hash))
+            .spawn(move || {
+                let block_bytes = match StacksChainState::load_block_bytes(
+                    &blocks_path,
+                    &consensus_hash,
+                    &block_hash,
+                ) {
+                    Ok(Some(bytes)) => bytes,
+                    _ => {
+                        return None;
+                    }
+                };
+                let block = match StacksBlock::consensus_deserialize(&mut &block_bytes[..]) {
+                    Ok(block) => block,
+                    Err(_) => {
+                        return None;
+                    }
+                };
+                if block.block_hash() != block_hash {
+                    return None;
+                }
+                let auth_checks =
+                    StacksChainState::start_block_auth_checks(&block, TX_AUTH_CHECK_WORKERS);
+                let auth_result =
+                    StacksChainState::finish_block_auth_checks(auth_checks).map_err(|e| match e {
+                        Error::NetError(net_error::VerifyingError(msg)) => msg,
+                        e => format!("{:?}", &e),
+                    });
+                Some((block, auth_result))
+            })
+            .expect("FATAL: failed to spawn block prefetcher thread");
+
+        pending.workers.insert(index_block_hash.clone(), worker);
+        pending.order.push_back(index_block_hash);
+        true
+    }
+
+    /// Take a block and its signature check result out of the prefetcher, waiting for the checks
+    /// to finish if need be.  Returns None if the prefetcher wasn't checking this block (or
+    /// evicted it), or couldn't load it.
+    pub fn take(&self, index_block_hash: &StacksBlockId) -> Option<PrefetchedBlock> {
+        let worker = {
+            let mut pending = self
+                .pending
+                .lock()
+                .expect("FATAL: block prefetcher lock poisoned");
+            let worker = pending.workers.remove(index_block_hash)?;
+            pending
+                .order
+                .retain(|block_id| block_id != index_block_hash);
+            worker
+        };
+        worker
+            .join()
+            .expect("FATAL: block prefetcher thread panicked")
+    }
+
+    /// How many blocks are being prefetched
+    pub fn num_pending(&self) -> usize {
+        self.pending
+            .lock()
+            .expect("FATAL: block prefetcher lock poisoned")
+            .workers
+            .len()
+    }
+
+    /// Forget about all blocks being prefetched.  Their threads run to completion in the
+    /// background.
+    pub fn clear(&self) {
+        let mut pending = self
+            .pending
+            .lock()
+            .expect("FATAL: block prefetcher lock poisoned");
+        pending.workers.clear();
+        pending.order.clear();
+    }
+}
+
 impl FromRow<StagingMicroblock> for StagingMicroblock {
     fn from_row<'a>(row: &'a Row) -> Result<StagingMicroblock, db_error> {
         let anchored_block_hash: BlockHeaderHash =
@@ -302,6 +510,59 @@ impl FromRow<StagingMicroblock> for StagingMicroblock {
     }
 }
 
+/// Evidence that the miner of an anchored block forked its microblock stream: two signed
+/// microblocks with the same parent and sequence number.  It can be reported with a
+/// poison-microblock transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoisonMicroblockEvidence {
+    pub parent_index_block_hash: StacksBlockId,
+    pub sequence: u16,
+    pub microblock_header_1: StacksMicroblockHeader,
+    pub microblock_header_2: StacksMicroblockHeader,
+    pub detected_at: u64,
+    /// when a report was attempted, if it has been
+    pub reported_at: Option<u64>,
+    /// the reporting transaction, if it was accepted into the mempool
+    pub report_txid: Option<Txid>,
+}
+
+impl PoisonMicroblockEvidence {
+    pub fn payload(&self) -> TransactionPayload {
+        TransactionPayload::PoisonMicroblock(
+            self.microblock_header_1.clone(),
+            self.microblock_header_2.clone(),
+        )
+    }
+}
+
+impl FromRow<PoisonMicroblockEvidence> for PoisonMicroblockEvidence {
+    fn from_row<'a>(row: &'a Row) -> Result<PoisonMicroblockEvidence, db_error> {
+        let parent_index_block_hash = StacksBlockId::from_column(row, "parent_index_block_hash")?;
+        let sequence: u16 = row.get_unwrap("sequence");
+        let header_1_bytes: Vec<u8> = row.get_unwrap("microblock_header_1");
+        let header_2_bytes: Vec<u8> = row.get_unwrap("microblock_header_2");
+        let microblock_header_1 =
+            StacksMicroblockHeader::consensus_deserialize(&mut &header_1_bytes[..])
+                .map_err(|_e| db_error::ParseError)?;
+        let microblock_header_2 =
+            StacksMicroblockHeader::consensus_deserialize(&mut &header_2_bytes[..])
+                .map_err(|_e| db_error::ParseError)?;
+        let detected_at = u64::from_column(row, "detected_at")?;
+        let reported_at = u64::from_column(row, "reported_at")?;
+        let report_txid: Option<Txid> = row.get_unwrap("report_txid");
+
+        Ok(PoisonMicroblockEvidence {
+            parent_index_block_hash,
+            sequence,
+            microblock_header_1,
+            microblock_header_2,
+            detected_at,
+            reported_at,
+            report_txid,
+        })
+    }
+}
+
 impl FromRow<StagingBlock> for StagingBlock {
     fn from_row<'a>(row: &'a Row) -> Result<StagingBlock, db_error> {
         let anchored_block_hash: BlockHeaderHash =
@@ -2173,7 +2434,8 @@ impl StacksChainState {
                         )
                     );
                 } else {
-                    info!("Reject block {}/{}", consensus_hash, anchored_block_hash);
+                    info!(#"block_rejected", "Reject block {}/{}", consensus_hash, anchored_block_hash;
+                          "index_block_hash" => %StacksBlockHeader::make_index_block_hash(consensus_hash, anchored_block_hash));
                 }
             } else {
                 debug!(
@@ -3139,6 +3401,7 @@ impl StacksChainState {
         block: &StacksBlock,
         mainnet: bool,
         chain_id: u32,
+        vrf_verifier: &mut VRFCachingVerifier,
     ) -> Result<Option<(u64, u64)>, Error> {
         // sortition-winning block commit for this block?
         let block_hash = block.block_hash();
@@ -3186,12 +3449,13 @@ impl StacksChainState {
             .expect("FATAL: have block commit but no leader key");
 
         // attaches to burn chain
-        match block.header.validate_burnchain(
+        match block.header.validate_burnchain_with_verifier(
             &burn_chain_tip,
             &penultimate_sortition_snapshot,
             &leader_key,
             &block_commit,
             &stacks_chain_tip,
+            vrf_verifier,
         ) {
             Ok(_) => {}
             Err(_) => {
@@ -3242,6 +3506,98 @@ impl StacksChainState {
         Ok(Some((block_commit.burn_fee, sortition_burns)))
     }
 
+    /// Find the leader VRF key, VRF proof, and proven-upon sortition hash for a block that we
+    /// have not yet preprocessed, so its proof can be checked ahead of time.
+    /// Returns None if the block doesn't correspond to a sortition we know about.
+    fn get_block_vrf_proof_inputs(
+        sort_handle: &SortitionHandleConn,
+        consensus_hash: &ConsensusHash,
+        block: &StacksBlock,
+    ) -> Result<Option<(VRFPublicKey, VRFProof, Vec<u8>)>, Error> {
+        let block_commit = match sort_handle
+            .get_block_snapshot_of_parent_stacks_block(consensus_hash, &block.block_hash())
+        {
+            Ok(Some((block_commit, _))) => block_commit,
+            Ok(None) | Err(db_error::InvalidPoxSortition) => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(e.into());
+            }
+        };
+
+        let penultimate_sortition_snapshot =
+            match sort_handle.get_block_snapshot_by_height(block_commit.block_height - 1)? {
+                Some(sn) => sn,
+                None => {
+                    return Ok(None);
+                }
+            };
+
+        let leader_key = match sort_handle.get_leader_key_at(
+            block_commit.key_block_ptr as u64,
+            block_commit.key_vtxindex as u32,
+        )? {
+            Some(key) => key,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        Ok(Some((
+            leader_key.public_key,
+            block.header.proof.clone(),
+            penultimate_sortition_snapshot
+                .sortition_hash
+                .as_bytes()
+                .to_vec(),
+        )))
+    }
+
+    /// Verify the VRF proofs of a list of anchored blocks ahead of time, before preprocessing
+    /// them one by one.  The results are remembered by this chainstate's VRF verifier, so
+    /// preprocess_anchored_block() won't need to check them again.  Blocks whose sortitions
+    /// can't be found are skipped here; preprocess_anchored_block() deals with them.
+    /// Returns the number of proofs verified.
+    pub fn preverify_anchored_block_proofs<'a, I>(
+        &mut self,
+        sort_ic: &SortitionDBConn,
+        blocks: I,
+    ) -> Result<usize, Error>
+    where
+        I: Iterator<Item = (&'a ConsensusHash, &'a StacksBlock)>,
+    {
+        let mut batch = vec![];
+        for (consensus_hash, block) in blocks {
+            let sort_handle =
+                match SortitionHandleConn::open_reader_consensus(sort_ic, consensus_hash) {
+                    Ok(handle) => handle,
+                    Err(db_error::NotFoundError) | Err(db_error::InvalidPoxSortition) => {
+                        continue;
+                    }
+                    Err(e) => {
+                        return Err(e.into());
+                    }
+                };
+            if let Some(inputs) =
+                StacksChainState::get_block_vrf_proof_inputs(&sort_handle, consensus_hash, block)?
+            {
+                batch.push(inputs);
+            }
+        }
+
+        if batch.len() > 0 {
+            let start = get_epoch_time_ms();
+            self.vrf_verifier.preverify(&batch);
+            debug!(
+                "Verified {} anchored block VRF proofs in {}ms",
+                batch.len(),
+                get_epoch_time_ms().saturating_sub(start)
+            );
+        }
+        Ok(batch.len())
+    }
+
     /// Pre-process and store an anchored block to staging, queuing it up for
     /// subsequent processing once all of its ancestors have been processed.
     ///
@@ -3277,7 +3633,8 @@ impl StacksChainState {
         let mainnet = self.mainnet;
         let chain_id = self.chain_id;
         let blocks_path = self.blocks_path.clone();
-        let mut block_tx = self.db_tx_begin()?;
+        let vrf_verifier = &mut self.vrf_verifier;
+        let mut block_tx = self.state_index.storage_tx().map_err(Error::DBError)?;
 
         // already in queue or already processed?
         let index_block_hash =
@@ -3328,6 +3685,7 @@ impl StacksChainState {
             block,
             mainnet,
             chain_id,
+            vrf_verifier,
         )?;
         let (commit_burn, sortition_burn) = match validation_res {
             Some((commit_burn, sortition_burn)) => (commit_burn, sortition_burn),
@@ -3468,6 +3826,30 @@ impl StacksChainState {
             return Err(Error::InvalidStacksMicroblock(msg, microblock.block_hash()));
         }
 
+        // is the miner forking its own stream?
+        let sql = "SELECT microblock_hash FROM staging_microblocks WHERE index_block_hash = ?1 AND parent_hash = ?2 AND sequence = ?3 AND microblock_hash != ?4 LIMIT 1";
+        let args: &[&dyn ToSql] = &[
+            &parent_index_hash,
+            &microblock.header.prev_block,
+            &microblock.header.sequence,
+            &microblock.block_hash(),
+        ];
+        let conflicting_hashes: Vec<BlockHeaderHash> =
+            query_row_columns(&blocks_tx, sql, args, "microblock_hash")?;
+        if let Some(conflicting_hash) = conflicting_hashes.first() {
+            if let Some(conflicting_microblock) =
+                StacksChainState::load_staging_microblock_bytes(&blocks_tx, conflicting_hash)?
+                    .and_then(|bytes| StacksMicroblock::consensus_deserialize(&mut &bytes[..]).ok())
+            {
+                StacksChainState::store_poison_microblock(
+                    &blocks_tx,
+                    &parent_index_hash,
+                    &conflicting_microblock.header,
+                    &microblock.header,
+                )?;
+            }
+        }
+
         // add to staging
         StacksChainState::store_staging_microblock(
             &mut blocks_tx,
@@ -3481,6 +3863,122 @@ impl StacksChainState {
         Ok(true)
     }
 
+    /// Store evidence of a fork in the microblock stream off of `parent_index_block_hash`,
+    /// unless there is already evidence of a fork at the same sequence number.  The headers
+    /// must conflict the way a poison-microblock transaction requires.
+    /// Return true if the evidence is new.
+    fn store_poison_microblock(
+        conn: &DBConn,
+        parent_index_block_hash: &StacksBlockId,
+        microblock_header_1: &StacksMicroblockHeader,
+        microblock_header_2: &StacksMicroblockHeader,
+    ) -> Result<bool, Error> {
+        if microblock_header_1.sequence != microblock_header_2.sequence
+            || microblock_header_1.prev_block != microblock_header_2.prev_block
+            || microblock_header_1.version != microblock_header_2.version
+            || microblock_header_1.block_hash() == microblock_header_2.block_hash()
+        {
+            return Ok(false);
+        }
+
+        let sql = "INSERT OR IGNORE INTO poison_microblocks (parent_index_block_hash, sequence, microblock_header_1, microblock_header_2, detected_at) VALUES (?1, ?2, ?3, ?4, ?5)";
+        let args: &[&dyn ToSql] = &[
+            parent_index_block_hash,
+            &microblock_header_1.sequence,
+            &microblock_header_1.serialize_to_vec(),
+            &microblock_header_2.serialize_to_vec(),
+            &u64_to_sql(get_epoch_time_secs())?,
+        ];
+        let inserted = conn
+            .execute(sql, args)
+            .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+        if inserted > 0 {
+            warn!("Detected a fork in the microblock stream";
+                  "parent_index_block_hash" => %parent_index_block_hash,
+                  "sequence" => microblock_header_1.sequence,
+                  "microblock_hash_1" => %microblock_header_1.block_hash(),
+                  "microblock_hash_2" => %microblock_header_2.block_hash());
+        }
+        Ok(inserted > 0)
+    }
+
+    /// Store the evidence in a poison-microblock payload found while processing blocks.  The
+    /// forked stream is identified by the staging microblocks the headers belong to, so
+    /// evidence about microblocks we never stored is ignored.
+    /// Return true if the evidence is new.
+    pub fn record_poison_microblock(
+        &mut self,
+        payload: &TransactionPayload,
+    ) -> Result<bool, Error> {
+        let (microblock_header_1, microblock_header_2) = match payload {
+            TransactionPayload::PoisonMicroblock(ref header_1, ref header_2) => {
+                (header_1, header_2)
+            }
+            _ => return Ok(false),
+        };
+
+        let tx = self.db_tx_begin()?;
+        let sql = "SELECT index_block_hash FROM staging_microblocks WHERE microblock_hash = ?1 OR microblock_hash = ?2 LIMIT 1";
+        let args: &[&dyn ToSql] = &[
+            &microblock_header_1.block_hash(),
+            &microblock_header_2.block_hash(),
+        ];
+        let parent_index_block_hashes: Vec<StacksBlockId> =
+            query_row_columns(&tx, sql, args, "index_block_hash")?;
+        let parent_index_block_hash = match parent_index_block_hashes.first() {
+            Some(parent_index_block_hash) => parent_index_block_hash,
+            None => return Ok(false),
+        };
+
+        let stored = StacksChainState::store_poison_microblock(
+            &tx,
+            parent_index_block_hash,
+            microblock_header_1,
+            microblock_header_2,
+        )?;
+        tx.commit()?;
+        Ok(stored)
+    }
+
+    /// Get all the stored evidence of microblock stream forks, oldest first
+    pub fn get_poison_microblocks(conn: &DBConn) -> Result<Vec<PoisonMicroblockEvidence>, Error> {
+        let sql = "SELECT * FROM poison_microblocks ORDER BY detected_at ASC";
+        query_rows(conn, sql, NO_PARAMS).map_err(Error::DBError)
+    }
+
+    /// Get the stored evidence of microblock stream forks that has not been reported yet,
+    /// oldest first
+    pub fn get_unreported_poison_microblocks(
+        conn: &DBConn,
+    ) -> Result<Vec<PoisonMicroblockEvidence>, Error> {
+        let sql =
+            "SELECT * FROM poison_microblocks WHERE reported_at IS NULL ORDER BY detected_at ASC";
+        query_rows(conn, sql, NO_PARAMS).map_err(Error::DBError)
+    }
+
+    /// Note that the evidence of a fork at `sequence` in the stream off of
+    /// `parent_index_block_hash` has been reported by `report_txid`, or that reporting it failed
+    /// if `report_txid` is None.  Either way, it won't be reported again.
+    pub fn set_poison_microblock_reported(
+        &mut self,
+        parent_index_block_hash: &StacksBlockId,
+        sequence: u16,
+        report_txid: Option<&Txid>,
+    ) -> Result<(), Error> {
+        let tx = self.db_tx_begin()?;
+        let sql = "UPDATE poison_microblocks SET reported_at = ?1, report_txid = ?2 WHERE parent_index_block_hash = ?3 AND sequence = ?4";
+        let args: &[&dyn ToSql] = &[
+            &u64_to_sql(get_epoch_time_secs())?,
+            &report_txid,
+            parent_index_block_hash,
+            &sequence,
+        ];
+        tx.execute(sql, args)
+            .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Given a burnchain snapshot, a Stacks block and a microblock stream, preprocess them all.
     /// This does not work when forking
     #[cfg(test)]
@@ -3712,6 +4210,13 @@ impl StacksChainState {
         Ok(cnt as u64)
     }
 
+    /// How many staging blocks are still waiting to be processed (attachable or not)?
+    pub fn count_pending_staging_blocks(blocks_conn: &DBConn) -> Result<u64, Error> {
+        let sql = "SELECT COUNT(*) FROM staging_blocks WHERE processed = 0 AND orphaned = 0";
+        let cnt = query_count(blocks_conn, sql, NO_PARAMS).map_err(Error::DBError)?;
+        Ok(cnt as u64)
+    }
+
     /// Measure how long a block waited in-between when it arrived and when it got processed.
     /// Includes both orphaned and accepted blocks.
     pub fn measure_block_wait_time(
@@ -4049,6 +4554,7 @@ impl StacksChainState {
                             contract_analysis: None,
                             execution_cost,
                             microblock_header: None,
+                            cost_breakdown: None,
                         };
 
                         all_receipts.push(receipt);
@@ -4103,6 +4609,7 @@ impl StacksChainState {
                                 contract_analysis: None,
                                 execution_cost: ExecutionCost::zero(),
                                 microblock_header: None,
+                                cost_breakdown: None,
                             }),
                             Err(e) => {
                                 info!("TransferStx burn op processing error.";
@@ -4129,8 +4636,9 @@ impl StacksChainState {
         let mut burns = 0u128;
         let mut receipts = vec![];
         for tx in block.txs.iter() {
+            // signatures were checked by start_block_auth_checks()
             let (tx_fee, tx_receipt) =
-                StacksChainState::process_transaction(clarity_tx, tx, false)?;
+                StacksChainState::process_transaction_auth_verified(clarity_tx, tx, false)?;
             fees = fees.checked_add(tx_fee as u128).expect("Fee overflow");
             burns = burns
                 .checked_add(tx_receipt.stx_burned as u128)
@@ -4140,6 +4648,79 @@ impl StacksChainState {
         Ok((fees, burns, receipts))
     }
 
+    /// Begin verifying the authorizations (signatures) of an anchored block's transactions on a
+    /// pool of worker threads.  This is CPU-bound work that doesn't depend on the chainstate, so it
+    /// can overlap with the MARF reads that precede transaction execution in append_block().
+    /// Each worker checks a contiguous run of the block's transactions and stops at the first
+    /// bad one.  Collect the outcome with finish_block_auth_checks().
+    fn start_block_auth_checks(block: &StacksBlock, num_workers: usize) -> BlockAuthChecks {
+        let num_workers = cmp::max(1, num_workers);
+        let chunk_len = cmp::max(1, (block.txs.len() + num_workers - 1) / num_workers);
+
+        let mut workers = vec![];
+        for (i, chunk) in block.txs.chunks(chunk_len).enumerate() {
+            let txs = chunk.to_vec();
+            let worker = thread::Builder::new()
+                .name(format!("tx-auth-{}", i))
+                .spawn(move || {
+                    for tx in txs.iter() {
+                        tx.verify()
+                            .map_err(|e| format!("Invalid tx {}: {:?}", tx.txid(), &e))?;
+                    }
+                    Ok(())
+                })
+                .expect("FATAL: failed to spawn transaction auth check thread");
+            workers.push(worker);
+        }
+        BlockAuthChecks::Running(workers)
+    }
+
+    /// Wait for a block's transaction signature checks to finish.
+    /// Returns the error for the first transaction (in block order) that failed its check, if any.
+    fn finish_block_auth_checks(auth_checks: BlockAuthChecks) -> Result<(), Error> {
+        let result = match auth_checks {
+            BlockAuthChecks::Running(workers) => {
+                let mut result = Ok(());
+                for worker in workers.into_iter() {
+                    let worker_result = worker
+                        .join()
+                        .expect("FATAL: transaction auth check thread panicked");
+                    if result.is_ok() {
+                        result = worker_result;
+                    }
+                }
+                result
+            }
+            BlockAuthChecks::Finished(result) => result,
+        };
+        result.map_err(|msg| Error::NetError(net_error::VerifyingError(msg)))
+    }
+
+    /// Have the block prefetcher start loading and checking the staging children of the given
+    /// block, so they'll be ready to go once it has been processed.
+    /// Returns the number of children newly handed to the prefetcher.
+    fn prefetch_staging_children(
+        blocks_conn: &DBConn,
+        blocks_path: &String,
+        block_prefetcher: &BlockPrefetcher,
+        consensus_hash: &ConsensusHash,
+        block_hash: &BlockHeaderHash,
+    ) -> Result<usize, Error> {
+        let sql = "SELECT * FROM staging_blocks WHERE parent_consensus_hash = ?1 AND parent_anchored_block_hash = ?2 AND processed = 0 AND orphaned = 0";
+        let args: &[&dyn ToSql] = &[consensus_hash, block_hash];
+        let children =
+            query_rows::<StagingBlock, _>(blocks_conn, sql, args).map_err(Error::DBError)?;
+
+        let mut started = 0;
+        for child in children.into_iter() {
+            if block_prefetcher.start(blocks_path, child.consensus_hash, child.anchored_block_hash)
+            {
+                started += 1;
+            }
+        }
+        Ok(started)
+    }
+
     /// Process a single matured miner reward.
     /// Grant it STX tokens.
     fn process_matured_miner_reward<'a>(
@@ -4301,6 +4882,7 @@ impl StacksChainState {
         chain_tip_burn_header_height: u32,
         chain_tip_burn_header_timestamp: u64,
         block: &StacksBlock,
+        auth_checks: BlockAuthChecks,
         block_size: u64,
         microblocks: &Vec<StacksMicroblock>, // parent microblocks
         burnchain_commit_burn: u64,
@@ -4364,6 +4946,7 @@ impl StacksChainState {
             parent_burn_block_height,
             parent_burn_block_timestamp,
             evaluated_epoch,
+            published_contracts,
         ) = {
             let (parent_consensus_hash, parent_block_hash) = if block.is_first_mined() {
                 // has to be the sentinal hashes if this block has no parent
@@ -4589,9 +5172,11 @@ impl StacksChainState {
                 transfer_burn_ops,
             ));
 
-            // process anchored block
+            // process anchored block, once its transactions' signatures have checked out
             let (block_fees, block_burns, txs_receipts) =
-                match StacksChainState::process_block_transactions(&mut clarity_tx, &block) {
+                match StacksChainState::finish_block_auth_checks(auth_checks).and_then(|_| {
+                    StacksChainState::process_block_transactions(&mut clarity_tx, &block)
+                }) {
                     Err(e) => {
                         let msg = format!("Invalid Stacks block {}: {:?}", block.block_hash(), &e);
                         warn!("{}", &msg);
@@ -4706,6 +5291,12 @@ impl StacksChainState {
                    "microblock cost" => %microblock_cost,
                    "block cost" => %block_cost);
 
+            // receipts are reported in this order
+            let published_contracts = StacksChainState::find_published_contracts(
+                &mut clarity_tx,
+                receipts.iter().chain(microblock_txs_receipts.iter()),
+            );
+
             // good to go!
             clarity_tx.commit_to_block(chain_tip_consensus_hash, &block.block_hash());
 
@@ -4755,6 +5346,7 @@ impl StacksChainState {
                 parent_burn_block_height,
                 parent_burn_block_timestamp,
                 evaluated_epoch,
+                published_contracts,
             )
         };
 
@@ -4763,6 +5355,12 @@ impl StacksChainState {
             x => Some(microblocks[x - 1].header.clone()),
         };
 
+        let mut total_cost = microblock_execution_cost.clone();
+        total_cost
+            .add(&block_execution_cost)
+            .expect("BUG: microblock_cost + block_cost overflowed");
+        let block_stats = StacksBlockStats::from_receipts(&tx_receipts, total_cost);
+
         let new_tip = StacksChainState::advance_tip(
             &mut chainstate_tx.tx,
             &parent_chain_tip.anchored_header,
@@ -4777,11 +5375,29 @@ impl StacksChainState {
             user_burns,
             &block_execution_cost,
             block_size,
+            block_stats,
             applied_epoch_transition,
         )
         .expect("FATAL: failed to advance chain tip");
 
         chainstate_tx.log_transactions_processed(&new_tip.index_block_hash(), &tx_receipts);
+        chainstate_tx.index_block_transactions(&new_tip.index_block_hash(), &tx_receipts)?;
+        chainstate_tx.index_address_transactions(
+            &new_tip.index_block_hash(),
+            new_tip.block_height,
+            &tx_receipts,
+        )?;
+        chainstate_tx.index_asset_holdings(
+            &new_tip.index_block_hash(),
+            new_tip.block_height,
+            &tx_receipts,
+        );
+        StacksChainState::insert_published_contracts(
+            &mut chainstate_tx.tx,
+            &new_tip.index_block_hash(),
+            new_tip.block_height,
+            &published_contracts,
+        )?;
 
         let epoch_receipt = StacksEpochReceipt {
             header: new_tip,
@@ -4942,6 +5558,7 @@ impl StacksChainState {
         sort_tx: &mut SortitionHandleTx,
     ) -> Result<(Option<StacksEpochReceipt>, Option<TransactionPayload>), Error> {
         let blocks_path = self.blocks_path.clone();
+        let block_prefetcher = self.block_prefetcher.clone();
         let (mut chainstate_tx, clarity_instance) = self.chainstate_tx_begin()?;
 
         // this is a transaction against both the headers and staging blocks databases!
@@ -4957,6 +5574,7 @@ impl StacksChainState {
                 None => {
                     // no more work to do!
                     debug!("No staging blocks");
+                    block_prefetcher.clear();
                     return Ok((None, None));
                 }
             };
@@ -4996,7 +5614,28 @@ impl StacksChainState {
             None => return Ok((None, None)),
         };
 
-        let block = StacksChainState::extract_stacks_block(&next_staging_block)?;
+        // use the prefetcher's copy of the block if it was loaded and checked ahead of time.
+        // Otherwise, start checking its transaction signatures now, while we load up the
+        // chainstate to process it.
+        let index_block_hash = StacksBlockHeader::make_index_block_hash(
+            &next_staging_block.consensus_hash,
+            &next_staging_block.anchored_block_hash,
+        );
+        let (block, auth_checks) = match block_prefetcher.take(&index_block_hash) {
+            Some((block, auth_result)) => {
+                debug!(
+                    "Block {}/{} was loaded and checked ahead of time",
+                    &next_staging_block.consensus_hash, &next_staging_block.anchored_block_hash
+                );
+                (block, BlockAuthChecks::Finished(auth_result))
+            }
+            None => {
+                let block = StacksChainState::extract_stacks_block(&next_staging_block)?;
+                let auth_checks =
+                    StacksChainState::start_block_auth_checks(&block, TX_AUTH_CHECK_WORKERS);
+                (block, auth_checks)
+            }
+        };
         let block_size = next_staging_block.block_data.len() as u64;
 
         // sanity check -- don't process this block again if we already did so
@@ -5087,6 +5726,32 @@ impl StacksChainState {
             &next_staging_block.anchored_block_hash,
         )?;
 
+        // get this block's children ready while we process it
+        match StacksChainState::prefetch_staging_children(
+            chainstate_tx.deref().deref(),
+            &blocks_path,
+            &block_prefetcher,
+            &next_staging_block.consensus_hash,
+            &next_staging_block.anchored_block_hash,
+        ) {
+            Ok(num_started) => {
+                if num_started > 0 {
+                    debug!(
+                        "Checking {} children of {}/{} ahead of time",
+                        num_started,
+                        &next_staging_block.consensus_hash,
+                        &next_staging_block.anchored_block_hash
+                    );
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to start checking children of {}/{}: {:?}",
+                    &next_staging_block.consensus_hash, &next_staging_block.anchored_block_hash, &e
+                );
+            }
+        }
+
         // attach the block to the chain state and calculate the next chain tip.
         // Execute the confirmed microblocks' transactions against the chain state, and then
         // execute the anchored block's transactions against the chain state.
@@ -5100,6 +5765,7 @@ impl StacksChainState {
             burn_header_height,
             burn_header_timestamp,
             &block,
+            auth_checks,
             block_size,
             &next_microblocks,
             next_staging_block.commit_burn,
@@ -5328,6 +5994,15 @@ impl StacksChainState {
         query_row(&self.db(), sql, args).map_err(Error::DBError)
     }
 
+    /// Get all processed, non-orphaned Stacks blocks at a given height.  Besides the canonical
+    /// chain tip's ancestor (or the tip itself), these are the blocks that compete with it.
+    pub fn get_stacks_chain_tips_at_height(&self, height: u64) -> Result<Vec<StagingBlock>, Error> {
+        let sql =
+            "SELECT * FROM staging_blocks WHERE processed = 1 AND orphaned = 0 AND height = ?1";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(height)?];
+        query_rows(&self.db(), sql, args).map_err(Error::DBError)
+    }
+
     /// Get the height of a staging block
     pub fn get_stacks_block_height(
         &self,
@@ -5460,6 +6135,40 @@ impl StacksChainState {
         }
     }
 
+    /// Get the nonce that the next transaction from `principal` must have in order to be mined on
+    /// top of the given chain tip.  If we have unconfirmed microblock state that has advanced the
+    /// account's nonce further, then that nonce is used instead.
+    pub fn get_mempool_account_nonce(
+        &mut self,
+        current_consensus_hash: &ConsensusHash,
+        current_block: &BlockHeaderHash,
+        principal: &PrincipalData,
+    ) -> Result<u64, MemPoolRejection> {
+        let current_tip =
+            StacksChainState::get_parent_index_block(current_consensus_hash, current_block);
+        let confirmed_nonce = self
+            .with_read_only_clarity_tx(&NULL_BURN_STATE_DB, &current_tip, |conn| {
+                StacksChainState::get_account(conn, principal).nonce
+            })
+            .ok_or_else(|| {
+                MemPoolRejection::NoSuchChainTip(
+                    current_consensus_hash.clone(),
+                    current_block.clone(),
+                )
+            })?;
+
+        let unconfirmed_nonce = if self.unconfirmed_state.is_some() {
+            self.with_read_only_unconfirmed_clarity_tx(&NULL_BURN_STATE_DB, |conn| {
+                StacksChainState::get_account(conn, principal).nonce
+            })
+            .unwrap_or(None)
+        } else {
+            None
+        };
+
+        Ok(cmp::max(confirmed_nonce, unconfirmed_nonce.unwrap_or(0)))
+    }
+
     /// Given an outstanding clarity connection, can we append the tx to the chain state?
     /// Used when mining transactions.
     fn can_include_tx<T: ClarityConnection>(
@@ -5474,6 +6183,8 @@ impl StacksChainState {
         // 2: it must be validly signed.
         StacksChainState::process_transaction_precheck(&chainstate_config, &tx)
             .map_err(|e| MemPoolRejection::FailedToValidate(e))?;
+        StacksChainState::check_transaction_epoch(clarity_connection.get_epoch(), &tx)
+            .map_err(|e| MemPoolRejection::FailedToValidate(e))?;
 
         // 3: it must pay a tx fee
         let fee = tx.get_tx_fee();
@@ -5610,13 +6321,20 @@ impl StacksChainState {
                 let contract_identifier =
                     QualifiedContractIdentifier::new(address.clone().into(), contract_name.clone());
 
+                let allow_trait_subtyping =
+                    clarity_connection.get_epoch().supports_trait_subtyping();
                 clarity_connection.with_analysis_db_readonly(|db| {
                     let function_type = db
                         .get_public_function_type(&contract_identifier, &function_name)
                         .map_err(|_e| MemPoolRejection::NoSuchContract)?
                         .ok_or_else(|| MemPoolRejection::NoSuchPublicFunction)?;
                     function_type
-                        .check_args_by_allowing_trait_cast(db, &function_args)
+                        .check_args_by_allowing_trait_cast(
+                            db,
+                            &mut LimitedCostTracker::new_free(),
+                            &function_args,
+                            allow_trait_subtyping,
+                        )
                         .map_err(|e| MemPoolRejection::BadFunctionArgument(e))
                 })?;
             }
@@ -6334,6 +7052,138 @@ pub mod test {
         tx.commit().unwrap();
     }
 
+    #[test]
+    fn stacks_db_block_auth_checks() {
+        let privk = StacksPrivateKey::from_hex(
+            "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+        )
+        .unwrap();
+
+        let mut block = make_16k_block(&privk);
+        for num_workers in 0..4 {
+            let auth_checks = StacksChainState::start_block_auth_checks(&block, num_workers);
+            match auth_checks {
+                BlockAuthChecks::Running(ref workers) => {
+                    assert!(workers.len() >= 1 && workers.len() <= block.txs.len());
+                }
+                BlockAuthChecks::Finished(_) => panic!("Auth checks should be running"),
+            }
+            StacksChainState::finish_block_auth_checks(auth_checks).unwrap();
+        }
+
+        // changing a signed transaction invalidates its signature
+        let last = block.txs.len() - 1;
+        block.txs[last].set_tx_fee(12345);
+        for num_workers in 1..4 {
+            let workers = StacksChainState::start_block_auth_checks(&block, num_workers);
+            match StacksChainState::finish_block_auth_checks(workers) {
+                Err(Error::NetError(_)) => {}
+                res => panic!("Expected auth failure, got {:?}", &res),
+            }
+        }
+    }
+
+    #[test]
+    fn stacks_db_block_prefetcher() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "stacks_db_block_prefetcher");
+        let privk = StacksPrivateKey::from_hex(
+            "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+        )
+        .unwrap();
+
+        let block = make_16k_block(&privk);
+        let consensus_hash = ConsensusHash([2u8; 20]);
+        let parent_consensus_hash = ConsensusHash([1u8; 20]);
+        let index_block_hash =
+            StacksBlockHeader::make_index_block_hash(&consensus_hash, &block.block_hash());
+
+        store_staging_block(
+            &mut chainstate,
+            &consensus_hash,
+            &block,
+            &parent_consensus_hash,
+            1,
+            2,
+        );
+
+        let block_prefetcher = chainstate.block_prefetcher.clone();
+        let num_started = StacksChainState::prefetch_staging_children(
+            chainstate.db(),
+            &chainstate.blocks_path,
+            &block_prefetcher,
+            &parent_consensus_hash,
+            &block.header.parent_block,
+        )
+        .unwrap();
+        assert_eq!(num_started, 1);
+        assert_eq!(chainstate.block_prefetcher.num_pending(), 1);
+
+        // already pending
+        assert!(!block_prefetcher.start(
+            &chainstate.blocks_path,
+            consensus_hash.clone(),
+            block.block_hash()
+        ));
+
+        let (prefetched_block, auth_result) = block_prefetcher.take(&index_block_hash).unwrap();
+        assert_eq!(prefetched_block, block);
+        assert!(auth_result.is_ok());
+        assert_eq!(block_prefetcher.num_pending(), 0);
+        assert!(block_prefetcher.take(&index_block_hash).is_none());
+
+        // blocks that aren't stored can't be prefetched
+        assert!(block_prefetcher.start(
+            &chainstate.blocks_path,
+            ConsensusHash([3u8; 20]),
+            BlockHeaderHash([4u8; 32])
+        ));
+        assert!(block_prefetcher
+            .take(&StacksBlockHeader::make_index_block_hash(
+                &ConsensusHash([3u8; 20]),
+                &BlockHeaderHash([4u8; 32])
+            ))
+            .is_none());
+
+        block_prefetcher.start(
+            &chainstate.blocks_path,
+            consensus_hash.clone(),
+            block.block_hash(),
+        );
+        block_prefetcher.clear();
+        assert_eq!(chainstate.block_prefetcher.num_pending(), 0);
+
+        // once full, the block started on longest ago is evicted
+        assert!(block_prefetcher.start(
+            &chainstate.blocks_path,
+            consensus_hash.clone(),
+            block.block_hash()
+        ));
+        for i in 0..BLOCK_PREFETCH_MAX_PENDING {
+            assert!(block_prefetcher.start(
+                &chainstate.blocks_path,
+                ConsensusHash([0x10 + i as u8; 20]),
+                BlockHeaderHash([4u8; 32])
+            ));
+        }
+        assert_eq!(block_prefetcher.num_pending(), BLOCK_PREFETCH_MAX_PENDING);
+        assert!(block_prefetcher.take(&index_block_hash).is_none());
+        assert_eq!(block_prefetcher.num_pending(), BLOCK_PREFETCH_MAX_PENDING);
+
+        // an evicted block can be prefetched again
+        assert!(block_prefetcher.start(
+            &chainstate.blocks_path,
+            consensus_hash.clone(),
+            block.block_hash()
+        ));
+        let (prefetched_block, _) = block_prefetcher.take(&index_block_hash).unwrap();
+        assert_eq!(prefetched_block, block);
+        assert_eq!(
+            block_prefetcher.num_pending(),
+            BLOCK_PREFETCH_MAX_PENDING - 1
+        );
+    }
+
     #[test]
     fn stacks_db_block_load_store_empty() {
         let chainstate =
@@ -9540,6 +10390,104 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn stacks_db_staging_microblocks_fork_evidence() {
+        let mut chainstate = instantiate_chainstate(
+            false,
+            0x80000000,
+            "stacks_db_staging_microblocks_fork_evidence",
+        );
+        let privk = StacksPrivateKey::from_hex(
+            "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+        )
+        .unwrap();
+
+        let block = make_empty_coinbase_block(&privk);
+        let consensus_hash = ConsensusHash([2u8; 20]);
+        let parent_index_hash =
+            StacksBlockHeader::make_index_block_hash(&consensus_hash, &block.block_hash());
+
+        let mut mblocks_1 = make_sample_microblock_stream(&privk, &block.block_hash());
+        mblocks_1.truncate(3);
+
+        // forks the stream at sequence 1
+        let mut mblocks_2 =
+            make_sample_microblock_stream_fork(&privk, &mblocks_1[0].block_hash(), 1);
+        mblocks_2.truncate(2);
+
+        store_staging_block(
+            &mut chainstate,
+            &consensus_hash,
+            &block,
+            &ConsensusHash([1u8; 20]),
+            1,
+            2,
+        );
+
+        for mblock in mblocks_1.iter() {
+            assert!(chainstate
+                .preprocess_streamed_microblock(&consensus_hash, &block.block_hash(), mblock)
+                .unwrap());
+        }
+        assert!(StacksChainState::get_poison_microblocks(chainstate.db())
+            .unwrap()
+            .is_empty());
+
+        for mblock in mblocks_2.iter() {
+            assert!(chainstate
+                .preprocess_streamed_microblock(&consensus_hash, &block.block_hash(), mblock)
+                .unwrap());
+        }
+
+        // only the microblocks at sequence 1 share a parent
+        let evidence = StacksChainState::get_poison_microblocks(chainstate.db()).unwrap();
+        assert_eq!(evidence.len(), 1);
+        assert_eq!(evidence[0].parent_index_block_hash, parent_index_hash);
+        assert_eq!(evidence[0].sequence, 1);
+        assert_eq!(
+            evidence[0].payload(),
+            TransactionPayload::PoisonMicroblock(
+                mblocks_1[1].header.clone(),
+                mblocks_2[0].header.clone()
+            )
+        );
+        assert_eq!(evidence[0].reported_at, None);
+
+        // the same fork found while processing blocks is not stored twice, and headers that
+        // don't conflict are not evidence
+        assert!(!chainstate
+            .record_poison_microblock(&TransactionPayload::PoisonMicroblock(
+                mblocks_2[0].header.clone(),
+                mblocks_1[1].header.clone()
+            ))
+            .unwrap());
+        assert!(!chainstate
+            .record_poison_microblock(&TransactionPayload::PoisonMicroblock(
+                mblocks_1[1].header.clone(),
+                mblocks_1[2].header.clone()
+            ))
+            .unwrap());
+        assert_eq!(
+            StacksChainState::get_unreported_poison_microblocks(chainstate.db())
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let report_txid = Txid([0x11; 32]);
+        chainstate
+            .set_poison_microblock_reported(&parent_index_hash, 1, Some(&report_txid))
+            .unwrap();
+        assert!(
+            StacksChainState::get_unreported_poison_microblocks(chainstate.db())
+                .unwrap()
+                .is_empty()
+        );
+        let evidence = StacksChainState::get_poison_microblocks(chainstate.db()).unwrap();
+        assert_eq!(evidence[0].report_txid, Some(report_txid));
+        assert!(evidence[0].reported_at.is_some());
+    }
+
     #[test]
     fn stacks_db_staging_microblocks_multiple_forks() {
         // multiple anchored blocks build off of a microblock stream that gets forked multiple
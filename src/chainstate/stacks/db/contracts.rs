@@ -21,17 +21,22 @@ use std::io;
 use std::io::prelude::*;
 
 use chainstate::stacks::db::*;
+use chainstate::stacks::events::{StacksTransactionReceipt, TransactionOrigin};
 use chainstate::stacks::Error;
 use chainstate::stacks::*;
 
 use std::path::{Path, PathBuf};
 
+use rusqlite::types::ToSql;
+use rusqlite::Row;
+use rusqlite::NO_PARAMS;
+
 use util::db::Error as db_error;
-use util::db::{query_count, query_rows, DBConn};
+use util::db::{query_count, query_rows, u64_to_sql, DBConn, FromColumn, FromRow};
 
 use util::strings::StacksString;
 
-use util::hash::to_hex;
+use util::hash::{to_hex, Sha512Trunc256Sum};
 
 use chainstate::burn::db::sortdb::*;
 
@@ -54,7 +59,131 @@ use vm::contracts::Contract;
 
 use clarity_vm::clarity::ClarityConnection;
 
+use crate::types::chainstate::StacksBlockId;
+
+/// A contract published by a processed block, as recorded in the published contracts index
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublishedContract {
+    pub contract_id: QualifiedContractIdentifier,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    /// index of the publishing transaction's receipt in the block's receipts
+    pub tx_index: u32,
+    /// hash of the contract's code body, as recorded by the Clarity database
+    pub code_body_hash: Sha512Trunc256Sum,
+}
+
+impl FromRow<PublishedContract> for PublishedContract {
+    fn from_row<'a>(row: &'a Row) -> Result<PublishedContract, db_error> {
+        let contract_id_str: String = row.get_unwrap("contract_id");
+        let contract_id = QualifiedContractIdentifier::parse(&contract_id_str)
+            .map_err(|_| db_error::ParseError)?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_index_i64: i64 = row.get_unwrap("tx_index");
+        let code_body_hash = Sha512Trunc256Sum::from_column(row, "code_body_hash")?;
+
+        Ok(PublishedContract {
+            contract_id,
+            index_block_hash,
+            block_height,
+            tx_index: tx_index_i64 as u32,
+            code_body_hash,
+        })
+    }
+}
+
 impl StacksChainState {
+    /// Find the contracts published by the given receipts' transactions, as (receipt index,
+    /// contract ID, code body hash).  This must be called before `clarity_tx` is committed, since
+    /// a smart-contract transaction whose initialization fails at runtime is still mined, but
+    /// does not publish its contract.
+    pub fn find_published_contracts<'a, T: ClarityConnection>(
+        clarity_tx: &mut T,
+        receipts: impl Iterator<Item = &'a StacksTransactionReceipt>,
+    ) -> Vec<(u32, QualifiedContractIdentifier, Sha512Trunc256Sum)> {
+        let mut published = vec![];
+        for (tx_index, receipt) in receipts.enumerate() {
+            if receipt.post_condition_aborted || receipt.contract_analysis.is_none() {
+                continue;
+            }
+            let tx = match receipt.transaction {
+                TransactionOrigin::Stacks(ref tx) => tx,
+                TransactionOrigin::Burn(_) => continue,
+            };
+            let smart_contract = match tx.payload {
+                TransactionPayload::SmartContract(ref smart_contract) => smart_contract,
+                _ => continue,
+            };
+            let contract_id = QualifiedContractIdentifier::new(
+                StandardPrincipalData::from(tx.origin_address()),
+                smart_contract.name.clone(),
+            );
+            if !clarity_tx.with_clarity_db_readonly(|db| db.has_contract(&contract_id)) {
+                continue;
+            }
+            let code_body_hash =
+                Sha512Trunc256Sum::from_data(smart_contract.code_body.to_string().as_bytes());
+            published.push((tx_index as u32, contract_id, code_body_hash));
+        }
+        published
+    }
+
+    /// Record the contracts published by a processed block, as found by
+    /// `find_published_contracts`
+    pub fn insert_published_contracts(
+        tx: &mut StacksDBTx,
+        block_id: &StacksBlockId,
+        block_height: u64,
+        contracts: &[(u32, QualifiedContractIdentifier, Sha512Trunc256Sum)],
+    ) -> Result<(), Error> {
+        let insert = "INSERT OR IGNORE INTO published_contracts (contract_id, index_block_hash, block_height, tx_index, code_body_hash) VALUES (?1, ?2, ?3, ?4, ?5)";
+        let block_height = u64_to_sql(block_height)?;
+        for (tx_index, contract_id, code_body_hash) in contracts.iter() {
+            let args: &[&dyn ToSql] = &[
+                &contract_id.to_string(),
+                block_id,
+                &block_height,
+                &(*tx_index as i64),
+                code_body_hash,
+            ];
+            tx.execute(insert, args)?;
+        }
+        Ok(())
+    }
+
+    /// Get all the contracts published in the fork ending at `tip`, in the order they were
+    /// published.  Boot contracts are included.  Contracts published by blocks that were
+    /// processed before the index was introduced (chainstate schema version 6) are not.
+    pub fn get_published_contracts(
+        &self,
+        tip: &StacksBlockId,
+    ) -> Result<Vec<PublishedContract>, Error> {
+        let sql = "SELECT * FROM published_contracts ORDER BY block_height ASC, tx_index ASC";
+        let rows: Vec<PublishedContract> = query_rows(self.db(), sql, NO_PARAMS)?;
+
+        // the index holds contracts from all forks, so only keep those in this one
+        let index_conn = self.index_conn()?;
+        let mut in_fork: HashMap<StacksBlockId, bool> = HashMap::new();
+        let mut published = vec![];
+        for row in rows.into_iter() {
+            let is_ancestor = match in_fork.get(&row.index_block_hash) {
+                Some(is_ancestor) => *is_ancestor,
+                None => {
+                    let is_ancestor = index_conn
+                        .get_ancestor_block_height(&row.index_block_hash, tip)?
+                        .is_some();
+                    in_fork.insert(row.index_block_hash.clone(), is_ancestor);
+                    is_ancestor
+                }
+            };
+            if is_ancestor {
+                published.push(row);
+            }
+        }
+        Ok(published)
+    }
+
     pub fn get_contract<T: ClarityConnection>(
         clarity_tx: &mut T,
         contract_id: &QualifiedContractIdentifier,
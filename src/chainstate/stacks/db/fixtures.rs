@@ -0,0 +1,659 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Self-contained block fixtures, for regression tests.
+//!
+//! `StacksChainState::record_block_fixture` re-executes a processed block (as
+//! `StacksChainState::replay_block` does) and records everything the execution read: the Clarity
+//! state of the block's parent, the headers DB, and the burnchain state, along with the block's
+//! transactions and their outcomes.  `BlockFixture::replay` then rebuilds just that state in a
+//! scratch Clarity store, re-executes the block, and reports any way in which the outcome
+//! differs.  A fixture is a plain JSON document, so a fix to block processing can ship the block
+//! that exposed the bug as a test, without the chainstate it came from.
+//!
+//! Blocks that read state as of some other block (i.e. with `at-block`) cannot be recorded.
+
+use std::cell::RefCell;
+use std::fs;
+
+use burnchains::Txid;
+use chainstate::stacks::db::*;
+use chainstate::stacks::Error;
+use clarity_vm::database::marf::RecordedReads;
+use codec::{Error as codec_error, StacksMessageCodec};
+use core::{StacksEpoch, StacksEpochId};
+use util::db::Error as db_error;
+use util::hash::{hex_bytes, to_hex};
+use vm::database::{BurnStateDB, ClaritySerializable, HeadersDB};
+
+use crate::types::chainstate::{BurnchainHeaderHash, SortitionId, VRFSeed};
+
+/// The headers DB lookups a recorded block made, and what they returned
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FixtureHeaders {
+    pub stacks_block_header_hashes: Vec<(StacksBlockId, Option<BlockHeaderHash>)>,
+    pub burn_header_hashes: Vec<(StacksBlockId, Option<BurnchainHeaderHash>)>,
+    pub vrf_seeds: Vec<(StacksBlockId, Option<VRFSeed>)>,
+    pub burn_block_times: Vec<(StacksBlockId, Option<u64>)>,
+    pub burn_block_heights: Vec<(StacksBlockId, Option<u32>)>,
+    pub miner_addresses: Vec<(StacksBlockId, Option<StacksAddress>)>,
+    /// Absent from fixtures recorded before this lookup existed
+    #[serde(default)]
+    pub consensus_hashes: Vec<(StacksBlockId, Option<ConsensusHash>)>,
+}
+
+/// The burnchain state lookups a recorded block made, and what they returned
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FixtureBurnState {
+    pub burn_block_heights: Vec<(SortitionId, Option<u32>)>,
+    pub burn_header_hashes: Vec<((u32, SortitionId), Option<BurnchainHeaderHash>)>,
+    /// Absent from fixtures recorded before this lookup existed
+    #[serde(default)]
+    pub burn_header_timestamps: Vec<((u32, SortitionId), Option<u64>)>,
+    /// Absent from fixtures recorded before this lookup existed
+    #[serde(default)]
+    pub sortition_ids: Vec<(ConsensusHash, Option<SortitionId>)>,
+    pub epochs: Vec<(u32, Option<StacksEpoch>)>,
+    pub epochs_by_id: Vec<(StacksEpochId, Option<StacksEpoch>)>,
+}
+
+/// The outcome of one of a recorded block's transactions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixtureTxResult {
+    pub txid: String,
+    /// The hex-encoded Clarity result
+    pub result: String,
+    pub post_condition_aborted: bool,
+    pub num_events: usize,
+    pub execution_cost: ExecutionCost,
+}
+
+/// Everything needed to re-execute one block, and what executing it did
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockFixture {
+    pub mainnet: bool,
+    pub chain_id: u32,
+    pub block_height: u64,
+    pub consensus_hash: ConsensusHash,
+    pub parent_block_id: StacksBlockId,
+    /// The hex-encoded anchored block
+    pub block: String,
+    /// The hex-encoded microblocks the block confirms
+    pub microblocks: Vec<String>,
+    pub state: RecordedReads,
+    pub headers: FixtureHeaders,
+    pub burn_state: FixtureBurnState,
+    /// The outcomes of the microblock transactions, followed by the anchored block's
+    pub results: Vec<FixtureTxResult>,
+    pub microblock_cost: ExecutionCost,
+    pub block_cost: ExecutionCost,
+}
+
+/// A way in which a replayed fixture's outcome differs from the recorded one
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureDivergence {
+    pub field: String,
+    pub recorded: String,
+    pub replayed: String,
+}
+
+fn fixture_lookup<K: PartialEq, V: Clone>(reads: &[(K, Option<V>)], key: &K) -> Option<V> {
+    reads
+        .iter()
+        .find(|(k, _)| k == key)
+        .and_then(|(_, v)| v.clone())
+}
+
+fn fixture_record<K: PartialEq + Clone, V: Clone>(
+    reads: &mut Vec<(K, Option<V>)>,
+    key: &K,
+    value: &Option<V>,
+) {
+    if !reads.iter().any(|(k, _)| k == key) {
+        reads.push((key.clone(), value.clone()));
+    }
+}
+
+impl HeadersDB for FixtureHeaders {
+    fn get_stacks_block_header_hash_for_block(
+        &self,
+        id_bhh: &StacksBlockId,
+    ) -> Option<BlockHeaderHash> {
+        fixture_lookup(&self.stacks_block_header_hashes, id_bhh)
+    }
+    fn get_burn_header_hash_for_block(
+        &self,
+        id_bhh: &StacksBlockId,
+    ) -> Option<BurnchainHeaderHash> {
+        fixture_lookup(&self.burn_header_hashes, id_bhh)
+    }
+    fn get_vrf_seed_for_block(&self, id_bhh: &StacksBlockId) -> Option<VRFSeed> {
+        fixture_lookup(&self.vrf_seeds, id_bhh)
+    }
+    fn get_burn_block_time_for_block(&self, id_bhh: &StacksBlockId) -> Option<u64> {
+        fixture_lookup(&self.burn_block_times, id_bhh)
+    }
+    fn get_burn_block_height_for_block(&self, id_bhh: &StacksBlockId) -> Option<u32> {
+        fixture_lookup(&self.burn_block_heights, id_bhh)
+    }
+    fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress> {
+        fixture_lookup(&self.miner_addresses, id_bhh)
+    }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        fixture_lookup(&self.consensus_hashes, id_bhh)
+    }
+}
+
+impl BurnStateDB for FixtureBurnState {
+    fn get_burn_block_height(&self, sortition_id: &SortitionId) -> Option<u32> {
+        fixture_lookup(&self.burn_block_heights, sortition_id)
+    }
+    fn get_burn_header_hash(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<BurnchainHeaderHash> {
+        fixture_lookup(&self.burn_header_hashes, &(height, sortition_id.clone()))
+    }
+    fn get_burn_header_timestamp(&self, height: u32, sortition_id: &SortitionId) -> Option<u64> {
+        fixture_lookup(
+            &self.burn_header_timestamps,
+            &(height, sortition_id.clone()),
+        )
+    }
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        fixture_lookup(&self.sortition_ids, consensus_hash)
+    }
+    fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch> {
+        fixture_lookup(&self.epochs, &height)
+    }
+    fn get_stacks_epoch_by_epoch_id(&self, epoch_id: &StacksEpochId) -> Option<StacksEpoch> {
+        fixture_lookup(&self.epochs_by_id, epoch_id)
+    }
+}
+
+/// A headers DB that remembers every lookup made through it
+struct RecordingHeadersDB<'a> {
+    inner: &'a dyn HeadersDB,
+    reads: RefCell<FixtureHeaders>,
+}
+
+impl HeadersDB for RecordingHeadersDB<'_> {
+    fn get_stacks_block_header_hash_for_block(
+        &self,
+        id_bhh: &StacksBlockId,
+    ) -> Option<BlockHeaderHash> {
+        let value = self.inner.get_stacks_block_header_hash_for_block(id_bhh);
+        fixture_record(
+            &mut self.reads.borrow_mut().stacks_block_header_hashes,
+            id_bhh,
+            &value,
+        );
+        value
+    }
+    fn get_burn_header_hash_for_block(
+        &self,
+        id_bhh: &StacksBlockId,
+    ) -> Option<BurnchainHeaderHash> {
+        let value = self.inner.get_burn_header_hash_for_block(id_bhh);
+        fixture_record(
+            &mut self.reads.borrow_mut().burn_header_hashes,
+            id_bhh,
+            &value,
+        );
+        value
+    }
+    fn get_vrf_seed_for_block(&self, id_bhh: &StacksBlockId) -> Option<VRFSeed> {
+        let value = self.inner.get_vrf_seed_for_block(id_bhh);
+        fixture_record(&mut self.reads.borrow_mut().vrf_seeds, id_bhh, &value);
+        value
+    }
+    fn get_burn_block_time_for_block(&self, id_bhh: &StacksBlockId) -> Option<u64> {
+        let value = self.inner.get_burn_block_time_for_block(id_bhh);
+        fixture_record(
+            &mut self.reads.borrow_mut().burn_block_times,
+            id_bhh,
+            &value,
+        );
+        value
+    }
+    fn get_burn_block_height_for_block(&self, id_bhh: &StacksBlockId) -> Option<u32> {
+        let value = self.inner.get_burn_block_height_for_block(id_bhh);
+        fixture_record(
+            &mut self.reads.borrow_mut().burn_block_heights,
+            id_bhh,
+            &value,
+        );
+        value
+    }
+    fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress> {
+        let value = self.inner.get_miner_address(id_bhh);
+        fixture_record(&mut self.reads.borrow_mut().miner_addresses, id_bhh, &value);
+        value
+    }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        let value = self.inner.get_consensus_hash_for_block(id_bhh);
+        fixture_record(
+            &mut self.reads.borrow_mut().consensus_hashes,
+            id_bhh,
+            &value,
+        );
+        value
+    }
+}
+
+/// A burnchain state DB that remembers every lookup made through it
+struct RecordingBurnStateDB<'a> {
+    inner: &'a dyn BurnStateDB,
+    reads: RefCell<FixtureBurnState>,
+}
+
+impl BurnStateDB for RecordingBurnStateDB<'_> {
+    fn get_burn_block_height(&self, sortition_id: &SortitionId) -> Option<u32> {
+        let value = self.inner.get_burn_block_height(sortition_id);
+        fixture_record(
+            &mut self.reads.borrow_mut().burn_block_heights,
+            sortition_id,
+            &value,
+        );
+        value
+    }
+    fn get_burn_header_hash(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<BurnchainHeaderHash> {
+        let value = self.inner.get_burn_header_hash(height, sortition_id);
+        fixture_record(
+            &mut self.reads.borrow_mut().burn_header_hashes,
+            &(height, sortition_id.clone()),
+            &value,
+        );
+        value
+    }
+    fn get_burn_header_timestamp(&self, height: u32, sortition_id: &SortitionId) -> Option<u64> {
+        let value = self.inner.get_burn_header_timestamp(height, sortition_id);
+        fixture_record(
+            &mut self.reads.borrow_mut().burn_header_timestamps,
+            &(height, sortition_id.clone()),
+            &value,
+        );
+        value
+    }
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        let value = self
+            .inner
+            .get_sortition_id_from_consensus_hash(consensus_hash);
+        fixture_record(
+            &mut self.reads.borrow_mut().sortition_ids,
+            consensus_hash,
+            &value,
+        );
+        value
+    }
+    fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch> {
+        let value = self.inner.get_stacks_epoch(height);
+        fixture_record(&mut self.reads.borrow_mut().epochs, &height, &value);
+        value
+    }
+    fn get_stacks_epoch_by_epoch_id(&self, epoch_id: &StacksEpochId) -> Option<StacksEpoch> {
+        let value = self.inner.get_stacks_epoch_by_epoch_id(epoch_id);
+        fixture_record(&mut self.reads.borrow_mut().epochs_by_id, epoch_id, &value);
+        value
+    }
+}
+
+fn fixture_tx_results(
+    txids: &[Txid],
+    receipts: &[StacksTransactionReceipt],
+) -> Vec<FixtureTxResult> {
+    txids
+        .iter()
+        .zip(receipts.iter())
+        .map(|(txid, receipt)| FixtureTxResult {
+            txid: txid.to_string(),
+            result: receipt.result.serialize(),
+            post_condition_aborted: receipt.post_condition_aborted,
+            num_events: receipt.events.len(),
+            execution_cost: receipt.execution_cost.clone(),
+        })
+        .collect()
+}
+
+fn fixture_decode<T: StacksMessageCodec>(hex: &str) -> Result<T, Error> {
+    let bytes = hex_bytes(hex).map_err(|e| {
+        Error::CodecError(codec_error::DeserializeError(format!(
+            "Bad fixture hex: {:?}",
+            &e
+        )))
+    })?;
+    T::consensus_deserialize(&mut &bytes[..]).map_err(Error::CodecError)
+}
+
+impl BlockFixture {
+    /// The transactions the block executes, in order: those of its microblocks, then its own
+    fn txids(block: &StacksBlock, microblocks: &[StacksMicroblock]) -> Vec<Txid> {
+        microblocks
+            .iter()
+            .flat_map(|mblock| mblock.txs.iter())
+            .chain(block.txs.iter())
+            .map(|tx| tx.txid())
+            .collect()
+    }
+
+    /// Re-execute this fixture's block against a scratch Clarity store built in `scratch_path`
+    /// from the recorded state, and report every way in which the outcome differs from the
+    /// recorded one.  `scratch_path` is removed afterwards.
+    pub fn replay(&self, scratch_path: &str) -> Result<Vec<FixtureDivergence>, Error> {
+        let block: StacksBlock = fixture_decode(&self.block)?;
+        let microblocks = self
+            .microblocks
+            .iter()
+            .map(|mblock| fixture_decode(mblock))
+            .collect::<Result<Vec<StacksMicroblock>, Error>>()?;
+
+        let parent_height =
+            self.block_height.checked_sub(1).ok_or_else(|| {
+                Error::InvalidStacksBlock("Fixture for the boot block".to_string())
+            })? as u32;
+
+        if fs::metadata(scratch_path).is_ok() {
+            fs::remove_dir_all(scratch_path).map_err(|e| Error::DBError(db_error::IOError(e)))?;
+        }
+        let mut marf_kv =
+            MarfedKV::open(scratch_path, None).map_err(|e| Error::ClarityError(e.into()))?;
+        marf_kv
+            .load_recorded_state(&self.parent_block_id, parent_height, &self.state)
+            .map_err(|e| Error::ClarityError(e.into()))?;
+        let mut clarity_instance = ClarityInstance::new(self.mainnet, marf_kv);
+
+        let result = {
+            let mut clarity_tx = ClarityTx {
+                block: clarity_instance.begin_block(
+                    &self.parent_block_id,
+                    &StacksBlockHeader::make_index_block_hash(
+                        &MINER_BLOCK_CONSENSUS_HASH,
+                        &MINER_BLOCK_HEADER_HASH,
+                    ),
+                    &self.headers,
+                    &self.burn_state,
+                ),
+                config: DBConfig {
+                    version: CHAINSTATE_VERSION.to_string(),
+                    mainnet: self.mainnet,
+                    chain_id: self.chain_id,
+                },
+            };
+            let result = StacksChainState::replay_block_transactions(
+                &mut clarity_tx,
+                &self.consensus_hash,
+                &block,
+                &microblocks,
+            );
+            clarity_tx.rollback_block();
+            result
+        };
+        drop(clarity_instance);
+        let _ = fs::remove_dir_all(scratch_path);
+
+        let mut divergences = vec![];
+        let (microblock_cost, block_cost, receipts) = match result {
+            Ok(x) => x,
+            Err(e) => {
+                divergences.push(FixtureDivergence {
+                    field: "block".to_string(),
+                    recorded: "accepted".to_string(),
+                    replayed: format!("{:?}", &e),
+                });
+                return Ok(divergences);
+            }
+        };
+
+        let results = fixture_tx_results(&BlockFixture::txids(&block, &microblocks), &receipts);
+        if results.len() != self.results.len() {
+            divergences.push(FixtureDivergence {
+                field: "num_results".to_string(),
+                recorded: self.results.len().to_string(),
+                replayed: results.len().to_string(),
+            });
+        }
+        for (recorded, replayed) in self.results.iter().zip(results.iter()) {
+            if recorded != replayed {
+                divergences.push(FixtureDivergence {
+                    field: format!("tx {}", &recorded.txid),
+                    recorded: format!("{:?}", recorded),
+                    replayed: format!("{:?}", replayed),
+                });
+            }
+        }
+        if microblock_cost != self.microblock_cost {
+            divergences.push(FixtureDivergence {
+                field: "microblock_cost".to_string(),
+                recorded: self.microblock_cost.to_string(),
+                replayed: microblock_cost.to_string(),
+            });
+        }
+        if block_cost != self.block_cost {
+            divergences.push(FixtureDivergence {
+                field: "block_cost".to_string(),
+                recorded: self.block_cost.to_string(),
+                replayed: block_cost.to_string(),
+            });
+        }
+        Ok(divergences)
+    }
+}
+
+impl StacksChainState {
+    /// Re-execute the processed block `header` as `replay_block` does, and record everything the
+    /// execution read into a fixture that can be replayed without this chainstate.
+    /// Returns None if the block's body is no longer stored.
+    pub fn record_block_fixture(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        header: &StacksHeaderInfo,
+    ) -> Result<Option<BlockFixture>, Error> {
+        let (block, parent_header, microblocks) = match self.load_block_for_replay(header)? {
+            Some(inputs) => inputs,
+            None => {
+                return Ok(None);
+            }
+        };
+        let parent_block_id = parent_header.index_block_hash();
+
+        let headers_db = RecordingHeadersDB {
+            inner: self.state_index.sqlite_conn(),
+            reads: RefCell::new(FixtureHeaders::default()),
+        };
+        let burn_state_db = RecordingBurnStateDB {
+            inner: burn_dbconn,
+            reads: RefCell::new(FixtureBurnState::default()),
+        };
+        let config = self.config();
+
+        self.clarity_state.start_recording_reads();
+        let result = {
+            let mut clarity_tx = ClarityTx {
+                block: self.clarity_state.begin_block(
+                    &parent_block_id,
+                    &StacksBlockHeader::make_index_block_hash(
+                        &MINER_BLOCK_CONSENSUS_HASH,
+                        &MINER_BLOCK_HEADER_HASH,
+                    ),
+                    &headers_db,
+                    &burn_state_db,
+                ),
+                config: config.clone(),
+            };
+            let result = StacksChainState::replay_block_transactions(
+                &mut clarity_tx,
+                &header.consensus_hash,
+                &block,
+                &microblocks,
+            );
+            clarity_tx.rollback_block();
+            result
+        };
+        let state = self
+            .clarity_state
+            .take_recorded_reads()
+            .expect("BUG: recording was started but nothing was recorded");
+        let (microblock_cost, block_cost, receipts) = result?;
+
+        if state.read_other_blocks {
+            return Err(Error::InvalidStacksBlock(format!(
+                "Block {}/{} reads state as of other blocks, which a fixture cannot record",
+                &header.consensus_hash,
+                &block.block_hash()
+            )));
+        }
+
+        Ok(Some(BlockFixture {
+            mainnet: config.mainnet,
+            chain_id: config.chain_id,
+            block_height: header.block_height,
+            consensus_hash: header.consensus_hash.clone(),
+            parent_block_id,
+            block: to_hex(&block.serialize_to_vec()),
+            microblocks: microblocks
+                .iter()
+                .map(|mblock| to_hex(&mblock.serialize_to_vec()))
+                .collect(),
+            state,
+            headers: headers_db.reads.into_inner(),
+            burn_state: burn_state_db.reads.into_inner(),
+            results: fixture_tx_results(&BlockFixture::txids(&block, &microblocks), &receipts),
+            microblock_cost,
+            block_cost,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chainstate::burn::db::sortdb::*;
+    use chainstate::stacks::boot::test::get_parent_tip;
+    use chainstate::stacks::miner::test::*;
+    use chainstate::stacks::miner::*;
+    use chainstate::stacks::*;
+    use net::test::*;
+    use util::hash::*;
+
+    use super::*;
+
+    #[test]
+    fn test_block_fixture_round_trip() {
+        let peer_config = TestPeerConfig::new("test_block_fixture_round_trip", 21350, 21351);
+        let mut peer = TestPeer::new(peer_config);
+
+        // a block that deploys a contract, then a block that calls it
+        for tenure_id in 0..2 {
+            let tip =
+                SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+                    .unwrap();
+
+            let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+                |ref mut miner,
+                 ref mut sortdb,
+                 ref mut chainstate,
+                 vrf_proof,
+                 ref parent_opt,
+                 ref parent_microblock_header_opt| {
+                    let parent_tip = get_parent_tip(parent_opt, chainstate, sortdb);
+                    miner.test_with_tx_fees = false;
+
+                    let mut block_txs = vec![make_coinbase(miner, tenure_id)];
+                    if tenure_id == 0 {
+                        block_txs.push(make_smart_contract(miner, 0, 1));
+                    } else {
+                        block_txs.push(make_contract_call(miner, 0, 1, 6, 2));
+                    }
+
+                    let block_builder = StacksBlockBuilder::make_regtest_block_builder(
+                        &parent_tip,
+                        vrf_proof,
+                        tip.total_burn,
+                        Hash160([tenure_id as u8; 20]),
+                    )
+                    .unwrap();
+                    let (anchored_block, _size, _cost) =
+                        StacksBlockBuilder::make_anchored_block_from_txs(
+                            block_builder,
+                            chainstate,
+                            &sortdb.index_conn(),
+                            block_txs,
+                        )
+                        .unwrap();
+                    (anchored_block, vec![])
+                },
+            );
+
+            peer.next_burnchain_block(burn_ops.clone());
+            peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+        }
+
+        let (tip_consensus_hash, tip_block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(peer.sortdb.as_ref().unwrap().conn())
+                .unwrap();
+        let tip_header = StacksChainState::get_anchored_block_header_info(
+            peer.chainstate().db(),
+            &tip_consensus_hash,
+            &tip_block_hash,
+        )
+        .unwrap()
+        .unwrap();
+
+        let sortdb = peer.sortdb.take().unwrap();
+        let fixture = peer
+            .chainstate()
+            .record_block_fixture(&sortdb.index_conn(), &tip_header)
+            .unwrap()
+            .unwrap();
+        peer.sortdb = Some(sortdb);
+
+        assert_eq!(fixture.block_height, 2);
+        assert_eq!(fixture.results.len(), 2);
+        // (ok 3)
+        assert_eq!(
+            fixture.results[1].result,
+            Value::okay(Value::Int(3)).unwrap().serialize()
+        );
+        assert!(fixture.state.values.len() > 0);
+        assert!(fixture.state.metadata.len() > 0);
+
+        // the fixture is self-contained
+        let json = serde_json::to_string(&fixture).unwrap();
+        let fixture: BlockFixture = serde_json::from_str(&json).unwrap();
+        let scratch_path = format!("{}.fixture", &peer.chainstate_path);
+        assert_eq!(fixture.replay(&scratch_path).unwrap(), vec![]);
+
+        // a different outcome is reported
+        let mut tampered = fixture.clone();
+        tampered.results[1].result = Value::okay(Value::Int(4)).unwrap().serialize();
+        let divergences = tampered.replay(&scratch_path).unwrap();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(
+            divergences[0].field,
+            format!("tx {}", &fixture.results[1].txid)
+        );
+    }
+}
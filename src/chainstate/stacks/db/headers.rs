@@ -31,8 +31,8 @@ use core::FIRST_BURNCHAIN_CONSENSUS_HASH;
 use core::FIRST_STACKS_BLOCK_HASH;
 use util::db::Error as db_error;
 use util::db::{
-    query_count, query_row, query_row_columns, query_row_panic, query_rows, DBConn, FromColumn,
-    FromRow,
+    get_ancestor_by_skips, insert_ancestor_skip, query_count, query_row, query_row_columns,
+    query_row_panic, query_rows, DBConn, FromColumn, FromRow,
 };
 use vm::costs::ExecutionCost;
 
@@ -137,6 +137,16 @@ impl StacksChainState {
         let total_burn_str = format!("{}", header.total_work.burn);
         let block_size_str = format!("{}", tip_info.anchored_block_size);
 
+        let (tx_count, total_fees_str, total_burn_str, total_cost) = match tip_info.block_stats {
+            Some(ref stats) => (
+                Some(stats.tx_count as i64),
+                Some(format!("{}", stats.total_fees)),
+                Some(format!("{}", stats.total_burn)),
+                Some(stats.total_cost.clone()),
+            ),
+            None => (None, None, None, None),
+        };
+
         let block_hash = header.block_hash();
 
         let index_block_hash =
@@ -166,6 +176,10 @@ impl StacksChainState {
             anchored_block_cost,
             &block_size_str,
             parent_id,
+            &tx_count,
+            &total_fees_str,
+            &total_burn_str,
+            &total_cost,
         ];
 
         tx.execute("INSERT INTO block_headers \
@@ -189,10 +203,21 @@ impl StacksChainState {
                     index_root,
                     cost,
                     block_size,
-                    parent_block_id) \
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)", args)
+                    parent_block_id, \
+                    tx_count, \
+                    total_fees, \
+                    total_burn, \
+                    total_cost) \
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)", args)
             .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
 
+        if !insert_ancestor_skip(tx, &index_block_hash, block_height, parent_id)? {
+            debug!(
+                "Parent {} of {} is not in the ancestor skip index",
+                parent_id, &index_block_hash
+            );
+        }
+
         Ok(())
     }
 
@@ -264,6 +289,9 @@ impl StacksChainState {
         tip_index_hash: &StacksBlockId,
         height: u64,
     ) -> Result<Option<StacksHeaderInfo>, Error> {
+        if let Some(bhh) = get_ancestor_by_skips(tx.tx(), tip_index_hash, height)? {
+            return StacksChainState::get_stacks_block_header_info_by_index_block_hash(tx, &bhh);
+        }
         match tx
             .get_ancestor_block_hash(height, tip_index_hash)
             .map_err(Error::DBError)?
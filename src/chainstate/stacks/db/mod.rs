@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{btree_map::Entry, BTreeMap};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap};
 use std::fmt;
 use std::fs;
 use std::io;
@@ -30,7 +30,7 @@ use rusqlite::Transaction;
 use rusqlite::NO_PARAMS;
 
 use burnchains::bitcoin::address::BitcoinAddress;
-use burnchains::{Address, Burnchain, BurnchainParameters, PoxConstants};
+use burnchains::{Address, Burnchain, BurnchainParameters, PoxConstants, Txid};
 use chainstate::burn::db::sortdb::BlockHeaderCache;
 use chainstate::burn::db::sortdb::*;
 use chainstate::burn::db::sortdb::{SortitionDB, SortitionDBConn};
@@ -60,17 +60,20 @@ use net::atlas::BNS_CHARS_REGEX;
 use net::Error as net_error;
 use util::db::Error as db_error;
 use util::db::{
-    query_count, query_row, tx_begin_immediate, tx_busy_handler, DBConn, DBTx, FromColumn, FromRow,
-    IndexDBConn, IndexDBTx,
+    insert_ancestor_skip, query_count, query_row, query_row_columns, query_rows,
+    tx_begin_immediate, tx_busy_handler, u64_to_sql, DBConn, DBTx, FromColumn, FromRow,
+    IndexDBConn, IndexDBTx, ANCESTOR_SKIPS_SCHEMA,
 };
 use util::hash::to_hex;
+use util::vrf::VRFCachingVerifier;
 use vm::analysis::analysis_db::AnalysisDatabase;
 use vm::analysis::run_analysis;
 use vm::ast::build_ast;
 use vm::contexts::OwnedEnvironment;
 use vm::costs::{ExecutionCost, LimitedCostTracker};
 use vm::database::{
-    BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, NULL_BURN_STATE_DB,
+    BurnStateDB, ClarityDatabase, ClaritySerializable, HeadersDB, STXBalance, SqliteConnection,
+    NULL_BURN_STATE_DB,
 };
 use vm::representations::ClarityName;
 use vm::representations::ContractName;
@@ -88,7 +91,11 @@ use vm::Value;
 pub mod accounts;
 pub mod blocks;
 pub mod contracts;
+pub mod fixtures;
 pub mod headers;
+pub mod prune;
+pub mod reindex;
+pub mod replay;
 pub mod transactions;
 pub mod unconfirmed;
 
@@ -107,6 +114,15 @@ pub struct StacksChainState {
     pub clarity_state_index_root: String, // path to dir containing clarity MARF and side-store
     pub root_path: String,
     pub unconfirmed_state: Option<UnconfirmedState>,
+    /// if true, then record which transactions affected which principals as blocks are processed
+    pub address_txs_index: bool,
+    /// if true, then record each principal's fungible and non-fungible token movements as blocks
+    /// are processed
+    pub asset_holdings_index: bool,
+    /// verifies (and remembers pre-verified) anchored block VRF proofs
+    pub vrf_verifier: VRFCachingVerifier,
+    /// staging blocks being loaded and checked ahead of their processing
+    pub block_prefetcher: BlockPrefetcher,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -145,6 +161,20 @@ pub struct StacksHeaderInfo {
     pub burn_header_height: u32,
     pub burn_header_timestamp: u64,
     pub anchored_block_size: u64,
+    /// None if the block was processed before block statistics were recorded (chainstate schema
+    /// version 9)
+    pub block_stats: Option<StacksBlockStats>,
+}
+
+/// Totals over the transactions processed along with a Stacks block: those in the block itself,
+/// and those in the parent microblock stream it confirms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StacksBlockStats {
+    pub tx_count: u64,
+    pub total_fees: u128,
+    /// STX burned by the transactions
+    pub total_burn: u128,
+    pub total_cost: ExecutionCost,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -182,8 +212,14 @@ impl DBConfig {
     pub fn supports_epoch(&self, epoch_id: StacksEpochId) -> bool {
         match epoch_id {
             StacksEpochId::Epoch10 => false,
-            StacksEpochId::Epoch20 => (self.version == "1" || self.version == "2"),
-            StacksEpochId::Epoch2_05 => self.version == "2",
+            StacksEpochId::Epoch20 => match self.version.as_str() {
+                "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" | "10" | "11" => true,
+                _ => false,
+            },
+            StacksEpochId::Epoch2_05 => match self.version.as_str() {
+                "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" | "10" | "11" => true,
+                _ => false,
+            },
         }
     }
 }
@@ -205,6 +241,7 @@ impl StacksHeaderInfo {
             consensus_hash: ConsensusHash::empty(),
             burn_header_timestamp: 0,
             anchored_block_size: 0,
+            block_stats: Some(StacksBlockStats::empty()),
         }
     }
 
@@ -224,6 +261,7 @@ impl StacksHeaderInfo {
             consensus_hash: FIRST_BURNCHAIN_CONSENSUS_HASH.clone(),
             burn_header_timestamp: first_burnchain_block_timestamp,
             anchored_block_size: 0,
+            block_stats: Some(StacksBlockStats::empty()),
         }
     }
 
@@ -232,6 +270,37 @@ impl StacksHeaderInfo {
     }
 }
 
+impl StacksBlockStats {
+    pub fn empty() -> StacksBlockStats {
+        StacksBlockStats {
+            tx_count: 0,
+            total_fees: 0,
+            total_burn: 0,
+            total_cost: ExecutionCost::zero(),
+        }
+    }
+
+    /// Tally the Stacks transactions in `receipts`, which took `total_cost` to process.  Receipts
+    /// of burnchain operations are not counted.
+    pub fn from_receipts(
+        receipts: &[StacksTransactionReceipt],
+        total_cost: ExecutionCost,
+    ) -> StacksBlockStats {
+        let mut stats = StacksBlockStats {
+            total_cost,
+            ..StacksBlockStats::empty()
+        };
+        for receipt in receipts.iter() {
+            if let TransactionOrigin::Stacks(ref tx) = receipt.transaction {
+                stats.tx_count += 1;
+                stats.total_fees += tx.get_tx_fee() as u128;
+                stats.total_burn += receipt.stx_burned;
+            }
+        }
+        stats
+    }
+}
+
 impl FromRow<DBConfig> for DBConfig {
     fn from_row<'a>(row: &'a Row) -> Result<DBConfig, db_error> {
         let version: String = row.get_unwrap("version");
@@ -249,6 +318,155 @@ impl FromRow<DBConfig> for DBConfig {
     }
 }
 
+/// A transaction that affected a principal, as recorded in the address transaction index
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressTransaction {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+}
+
+impl FromRow<AddressTransaction> for AddressTransaction {
+    fn from_row<'a>(row: &'a Row) -> Result<AddressTransaction, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_index_i64: i64 = row.get_unwrap("tx_index");
+
+        Ok(AddressTransaction {
+            txid,
+            index_block_hash,
+            block_height,
+            tx_index: tx_index_i64 as u32,
+        })
+    }
+}
+
+/// A principal's fungible token balances and non-fungible tokens, as recorded in the asset
+/// holdings index.  Assets are keyed by their fully-qualified identifier
+/// (`<contract-id>::<asset-name>`).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AssetHoldings {
+    /// nonzero fungible token balances
+    pub fungible_tokens: BTreeMap<String, u128>,
+    /// the hex-serialized Clarity values of the non-fungible tokens owned
+    pub non_fungible_tokens: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// What happened to a non-fungible token in one of its events
+#[derive(Debug, Clone, PartialEq)]
+pub enum NftHistoryEventType {
+    Mint,
+    Transfer,
+    Burn,
+}
+
+/// An event in the history of a non-fungible token, as recorded in the asset holdings index
+#[derive(Debug, Clone, PartialEq)]
+pub struct NftHistoryEvent {
+    pub event_type: NftHistoryEventType,
+    /// the previous owner, unless this is a mint
+    pub sender: Option<PrincipalData>,
+    /// the new owner, unless this is a burn
+    pub recipient: Option<PrincipalData>,
+    /// None if the event was indexed before transactions were recorded (chainstate schema
+    /// version 8)
+    pub txid: Option<Txid>,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub event_index: u32,
+}
+
+impl NftHistoryEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NftHistoryEventType::Mint => "mint",
+            NftHistoryEventType::Transfer => "transfer",
+            NftHistoryEventType::Burn => "burn",
+        }
+    }
+}
+
+/// One principal's side of a token movement, as recorded in the asset holdings index
+struct AssetHoldingsDelta {
+    principal: PrincipalData,
+    asset_identifier: String,
+    /// the hex-serialized token, if this is a non-fungible token
+    nft_value: Option<String>,
+    /// whether the principal gained (rather than lost) the tokens
+    credit: bool,
+    amount: u128,
+}
+
+impl AssetHoldingsDelta {
+    /// The deltas of a transaction event.  Only token events have any.
+    fn from_event(event: &StacksTransactionEvent) -> Vec<AssetHoldingsDelta> {
+        // (asset, token if non-fungible, amount, debited principal, credited principal)
+        let (asset, nft_value, amount, sender, recipient) = match event {
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(ref data)) => (
+                &data.asset_identifier,
+                None,
+                data.amount,
+                Some(&data.sender),
+                Some(&data.recipient),
+            ),
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(ref data)) => (
+                &data.asset_identifier,
+                None,
+                data.amount,
+                None,
+                Some(&data.recipient),
+            ),
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(ref data)) => (
+                &data.asset_identifier,
+                None,
+                data.amount,
+                Some(&data.sender),
+                None,
+            ),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(ref data)) => (
+                &data.asset_identifier,
+                Some(&data.value),
+                1,
+                Some(&data.sender),
+                Some(&data.recipient),
+            ),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(ref data)) => (
+                &data.asset_identifier,
+                Some(&data.value),
+                1,
+                None,
+                Some(&data.recipient),
+            ),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(ref data)) => (
+                &data.asset_identifier,
+                Some(&data.value),
+                1,
+                Some(&data.sender),
+                None,
+            ),
+            StacksTransactionEvent::SmartContractEvent(_) | StacksTransactionEvent::STXEvent(_) => {
+                return vec![]
+            }
+        };
+
+        let delta = |principal: &PrincipalData, credit: bool| AssetHoldingsDelta {
+            principal: principal.clone(),
+            asset_identifier: asset.to_string(),
+            nft_value: nft_value.map(|value| value.serialize()),
+            credit,
+            amount,
+        };
+        sender
+            .map(|principal| delta(principal, false))
+            .into_iter()
+            .chain(recipient.map(|principal| delta(principal, true)))
+            .collect()
+    }
+}
+
 impl FromRow<StacksHeaderInfo> for StacksHeaderInfo {
     fn from_row<'a>(row: &'a Row) -> Result<StacksHeaderInfo, db_error> {
         let block_height: u64 = u64::from_column(row, "block_height")?;
@@ -267,6 +485,24 @@ impl FromRow<StacksHeaderInfo> for StacksHeaderInfo {
             return Err(db_error::ParseError);
         }
 
+        let block_stats = match row.get_unwrap::<_, Option<i64>>("tx_count") {
+            Some(tx_count) => {
+                let total_fees_str: String = row.get_unwrap("total_fees");
+                let total_burn_str: String = row.get_unwrap("total_burn");
+                Some(StacksBlockStats {
+                    tx_count: tx_count as u64,
+                    total_fees: total_fees_str
+                        .parse::<u128>()
+                        .map_err(|_| db_error::ParseError)?,
+                    total_burn: total_burn_str
+                        .parse::<u128>()
+                        .map_err(|_| db_error::ParseError)?,
+                    total_cost: row.get_unwrap("total_cost"),
+                })
+            }
+            None => None,
+        };
+
         Ok(StacksHeaderInfo {
             anchored_header: stacks_header,
             microblock_tail: None,
@@ -277,6 +513,7 @@ impl FromRow<StacksHeaderInfo> for StacksHeaderInfo {
             burn_header_height: burn_header_height as u32,
             burn_header_timestamp,
             anchored_block_size,
+            block_stats,
         })
     }
 }
@@ -404,6 +641,8 @@ pub struct ChainstateTx<'a> {
     pub blocks_path: String,
     pub tx: StacksDBTx<'a>,
     pub root_path: String,
+    pub address_txs_index: bool,
+    pub asset_holdings_index: bool,
 }
 
 impl<'a> ChainstateTx<'a> {
@@ -412,12 +651,16 @@ impl<'a> ChainstateTx<'a> {
         blocks_path: String,
         root_path: String,
         config: DBConfig,
+        address_txs_index: bool,
+        asset_holdings_index: bool,
     ) -> ChainstateTx<'a> {
         ChainstateTx {
             config,
             blocks_path,
             tx,
             root_path,
+            address_txs_index,
+            asset_holdings_index,
         }
     }
 
@@ -458,6 +701,97 @@ impl<'a> ChainstateTx<'a> {
             }
         }
     }
+
+    /// Record which transactions were mined in this block.
+    pub fn index_block_transactions(
+        &self,
+        block_id: &StacksBlockId,
+        events: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        let insert =
+            "INSERT OR IGNORE INTO block_transactions (txid, index_block_hash) VALUES (?1, ?2)";
+        for tx_event in events.iter() {
+            let txid = tx_event.transaction.txid();
+            let params: &[&dyn ToSql] = &[&txid, block_id];
+            self.tx.tx().execute(insert, params)?;
+        }
+        Ok(())
+    }
+
+    /// Record each principal affected by each transaction in this block, if the address
+    /// transaction index is enabled.
+    pub fn index_address_transactions(
+        &self,
+        block_id: &StacksBlockId,
+        block_height: u64,
+        events: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        if !self.address_txs_index {
+            return Ok(());
+        }
+        let block_height = u64_to_sql(block_height)?;
+        let insert = "INSERT OR IGNORE INTO address_txs (principal, txid, index_block_hash, block_height, tx_index) VALUES (?1, ?2, ?3, ?4, ?5)";
+        for (tx_index, tx_event) in events.iter().enumerate() {
+            let txid = tx_event.transaction.txid();
+            let tx_index = tx_index as i64;
+            for principal in tx_event.get_affected_principals().into_iter() {
+                let principal = principal.to_string();
+                let params: &[&dyn ToSql] =
+                    &[&principal, &txid, block_id, &block_height, &tx_index];
+                self.tx.tx().execute(insert, params)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Record each fungible and non-fungible token movement in this block against the principals
+    /// it credits and debits, if the asset holdings index is enabled.
+    pub fn index_asset_holdings(
+        &self,
+        block_id: &StacksBlockId,
+        block_height: u64,
+        events: &[StacksTransactionReceipt],
+    ) {
+        if !self.asset_holdings_index {
+            return;
+        }
+        let block_height = match u64_to_sql(block_height) {
+            Ok(height) => height,
+            Err(e) => {
+                warn!("Failed to index asset holdings: {:?}", e);
+                return;
+            }
+        };
+        let insert = "INSERT OR IGNORE INTO asset_holdings_deltas (principal, asset_identifier, nft_value, credit, amount, index_block_hash, block_height, tx_index, event_index, txid) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)";
+        for (tx_index, tx_event) in events.iter().enumerate() {
+            if tx_event.post_condition_aborted {
+                // its token movements were rolled back
+                continue;
+            }
+            let txid = tx_event.transaction.txid();
+            let tx_index = tx_index as i64;
+            for (event_index, event) in tx_event.events.iter().enumerate() {
+                let event_index = event_index as i64;
+                for delta in AssetHoldingsDelta::from_event(event).into_iter() {
+                    let params: &[&dyn ToSql] = &[
+                        &delta.principal.to_string(),
+                        &delta.asset_identifier,
+                        &delta.nft_value,
+                        &delta.credit,
+                        &delta.amount.to_string(),
+                        block_id,
+                        &block_height,
+                        &tx_index,
+                        &event_index,
+                        &txid,
+                    ];
+                    if let Err(e) = self.tx.tx().execute(insert, params) {
+                        warn!("Failed to index asset holdings: {}", e; "txid" => %txid, "principal" => %delta.principal);
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl<'a> Deref for ChainstateTx<'a> {
@@ -491,7 +825,7 @@ pub struct BlockStreamData {
     num_mblocks_ptr: usize,
 }
 
-pub const CHAINSTATE_VERSION: &'static str = "2";
+pub const CHAINSTATE_VERSION: &'static str = "11";
 
 const CHAINSTATE_INITIAL_SCHEMA: &'static [&'static str] = &[
     "PRAGMA foreign_keys = ON;",
@@ -655,6 +989,153 @@ const CHAINSTATE_SCHEMA_2: &'static [&'static str] = &[
     "#,
 ];
 
+const CHAINSTATE_SCHEMA_3: &'static [&'static str] = &[
+    // new in schema version 3
+    // index of the transactions that affected each principal.
+    // only populated if the address transaction index is enabled.
+    r#"
+    CREATE TABLE address_txs(
+        principal TEXT NOT NULL,
+        txid TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        tx_index INTEGER NOT NULL,
+        PRIMARY KEY(principal,txid,index_block_hash)
+    );"#,
+    "CREATE INDEX address_txs_by_height ON address_txs(principal,block_height,tx_index);",
+    r#"
+    UPDATE db_config SET version = "3";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_4: &'static [&'static str] = &[
+    // new in schema version 4
+    // the genesis lockups (which are stored in the lockup contract by unlock height), indexed by
+    // recipient so we can report each account's vesting schedule.
+    r#"
+    CREATE TABLE genesis_lockups(
+        recipient TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        block_height INTEGER NOT NULL
+    );"#,
+    "CREATE INDEX genesis_lockups_by_recipient ON genesis_lockups(recipient,block_height);",
+    r#"
+    UPDATE db_config SET version = "4";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_5: &'static [&'static str] = &[
+    // new in schema version 5
+    // skip-list ancestor index over block_headers, so at-height lookups don't need to walk the
+    // MARF one parent at a time.
+    ANCESTOR_SKIPS_SCHEMA,
+    r#"
+    UPDATE db_config SET version = "5";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_6: &'static [&'static str] = &[
+    // new in schema version 6
+    // index of the contracts published by each processed block, so they can be listed without
+    // scanning the Clarity MARF.
+    r#"
+    CREATE TABLE published_contracts(
+        contract_id TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        tx_index INTEGER NOT NULL,
+        code_body_hash TEXT NOT NULL,
+        PRIMARY KEY(contract_id,index_block_hash)
+    );"#,
+    "CREATE INDEX published_contracts_by_height ON published_contracts(block_height,tx_index);",
+    r#"
+    UPDATE db_config SET version = "6";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_7: &'static [&'static str] = &[
+    // new in schema version 7
+    // index of the fungible and non-fungible token movements that credited or debited each
+    // principal.  only populated if the asset holdings index is enabled.
+    r#"
+    CREATE TABLE asset_holdings_deltas(
+        principal TEXT NOT NULL,
+        asset_identifier TEXT NOT NULL,
+        nft_value TEXT,                 -- hex-serialized token, or NULL for fungible tokens
+        credit INTEGER NOT NULL,        -- 1 if the principal gained the tokens, 0 if it lost them
+        amount TEXT NOT NULL,           -- converted to/from u128
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        tx_index INTEGER NOT NULL,
+        event_index INTEGER NOT NULL,
+        PRIMARY KEY(principal,index_block_hash,tx_index,event_index,credit)
+    );"#,
+    "CREATE INDEX asset_holdings_deltas_by_height ON asset_holdings_deltas(principal,block_height,tx_index,event_index);",
+    r#"
+    UPDATE db_config SET version = "7";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_8: &'static [&'static str] = &[
+    // new in schema version 8
+    // the asset holdings index also records which transaction moved each token, and can be
+    // searched by token, so a non-fungible token's history can be reported.
+    "ALTER TABLE asset_holdings_deltas ADD COLUMN txid TEXT;",
+    "CREATE INDEX asset_holdings_deltas_by_token ON asset_holdings_deltas(asset_identifier,nft_value,block_height,tx_index,event_index);",
+    r#"
+    UPDATE db_config SET version = "8";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_9: &'static [&'static str] = &[
+    // new in schema version 9
+    // block headers record totals over the transactions processed with each block.  These are
+    // NULL for blocks processed before the upgrade.
+    "ALTER TABLE block_headers ADD COLUMN tx_count INTEGER;",
+    "ALTER TABLE block_headers ADD COLUMN total_fees TEXT;",
+    "ALTER TABLE block_headers ADD COLUMN total_burn TEXT;",
+    "ALTER TABLE block_headers ADD COLUMN total_cost TEXT;",
+    r#"
+    UPDATE db_config SET version = "9";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_10: &'static [&'static str] = &[
+    // new in schema version 10
+    // evidence of miners forking their own microblock streams, kept until it has been reported
+    // with a poison-microblock transaction.
+    r#"
+    CREATE TABLE poison_microblocks(
+        parent_index_block_hash TEXT NOT NULL,  -- the anchored block the forked stream builds on
+        sequence INTEGER NOT NULL,
+        microblock_header_1 BLOB NOT NULL,
+        microblock_header_2 BLOB NOT NULL,
+        detected_at INTEGER NOT NULL,
+        reported_at INTEGER,                    -- NULL until a report has been attempted
+        report_txid TEXT,                       -- NULL if the report was not accepted
+        PRIMARY KEY(parent_index_block_hash,sequence)
+    );"#,
+    "CREATE INDEX poison_microblocks_by_report ON poison_microblocks(reported_at,detected_at);",
+    r#"
+    UPDATE db_config SET version = "10";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_11: &'static [&'static str] = &[
+    // new in schema version 11
+    // which processed blocks each transaction was mined in.  Unlike the transaction log, this is
+    // always populated, so mined transactions can be found on any node.
+    r#"
+    CREATE TABLE block_transactions(
+        txid TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        PRIMARY KEY(txid,index_block_hash)
+    );"#,
+    r#"
+    UPDATE db_config SET version = "11";
+    "#,
+];
+
 #[cfg(test)]
 pub const MINER_REWARD_MATURITY: u64 = 2; // small for testing purposes
 
@@ -725,6 +1206,9 @@ pub struct ChainStateBootData {
         Option<Box<dyn FnOnce() -> Box<dyn Iterator<Item = ChainstateBNSNamespace>>>>,
     pub get_bulk_initial_names:
         Option<Box<dyn FnOnce() -> Box<dyn Iterator<Item = ChainstateBNSName>>>>,
+    /// (name, source) of contracts to deploy into the boot address right after the built-in
+    /// boot contracts.  Only allowed on non-mainnet chains.
+    pub extra_boot_contracts: Vec<(String, String)>,
 }
 
 impl ChainStateBootData {
@@ -744,6 +1228,7 @@ impl ChainStateBootData {
             get_bulk_initial_balances: None,
             get_bulk_initial_namespaces: None,
             get_bulk_initial_names: None,
+            extra_boot_contracts: vec![],
         }
     }
 }
@@ -796,7 +1281,7 @@ impl StacksChainState {
         StacksChainState::load_db_config(marf.sqlite_conn())
     }
 
-    fn load_db_config(conn: &DBConn) -> Result<DBConfig, db_error> {
+    pub(crate) fn load_db_config(conn: &DBConn) -> Result<DBConfig, db_error> {
         let config = query_row::<DBConfig, _>(
             conn,
             &"SELECT * FROM db_config LIMIT 1".to_string(),
@@ -805,6 +1290,32 @@ impl StacksChainState {
         Ok(config.expect("BUG: no db_config installed"))
     }
 
+    /// Create the ancestor skip index and backfill it from the headers we already have.
+    fn apply_schema_5<'a>(tx: &DBTx<'a>) -> Result<(), Error> {
+        for cmd in CHAINSTATE_SCHEMA_5.iter() {
+            tx.execute_batch(cmd)?;
+        }
+
+        // parents always come before their children in height order
+        let qry = "SELECT index_block_hash, parent_block_id, block_height FROM block_headers ORDER BY block_height ASC";
+        let mut stmt = tx.prepare(qry)?;
+        let mut rows = stmt.query(NO_PARAMS)?;
+        let mut num_indexed = 0;
+        while let Some(row) = rows.next()? {
+            let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+            let parent_block_id = StacksBlockId::from_column(row, "parent_block_id")?;
+            let block_height = u64::from_column(row, "block_height")?;
+            if insert_ancestor_skip(tx, &index_block_hash, block_height, &parent_block_id)? {
+                num_indexed += 1;
+            }
+        }
+        debug!(
+            "Indexed the ancestry of {} Stacks block headers",
+            num_indexed
+        );
+        Ok(())
+    }
+
     fn apply_schema_migrations<'a>(
         tx: &DBTx<'a>,
         mainnet: bool,
@@ -839,6 +1350,67 @@ impl StacksChainState {
                             tx.execute_batch(cmd)?;
                         }
                     }
+                    "2" => {
+                        // migrate to 3
+                        info!("Migrating chainstate schema from version 2 to 3");
+                        for cmd in CHAINSTATE_SCHEMA_3.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "3" => {
+                        // migrate to 4
+                        info!("Migrating chainstate schema from version 3 to 4");
+                        for cmd in CHAINSTATE_SCHEMA_4.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "4" => {
+                        // migrate to 5
+                        info!("Migrating chainstate schema from version 4 to 5");
+                        StacksChainState::apply_schema_5(tx)?;
+                    }
+                    "5" => {
+                        // migrate to 6
+                        info!("Migrating chainstate schema from version 5 to 6");
+                        for cmd in CHAINSTATE_SCHEMA_6.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "6" => {
+                        // migrate to 7
+                        info!("Migrating chainstate schema from version 6 to 7");
+                        for cmd in CHAINSTATE_SCHEMA_7.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "7" => {
+                        // migrate to 8
+                        info!("Migrating chainstate schema from version 7 to 8");
+                        for cmd in CHAINSTATE_SCHEMA_8.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "8" => {
+                        // migrate to 9
+                        info!("Migrating chainstate schema from version 8 to 9");
+                        for cmd in CHAINSTATE_SCHEMA_9.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "9" => {
+                        // migrate to 10
+                        info!("Migrating chainstate schema from version 9 to 10");
+                        for cmd in CHAINSTATE_SCHEMA_10.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "10" => {
+                        // migrate to 11
+                        info!("Migrating chainstate schema from version 10 to 11");
+                        for cmd in CHAINSTATE_SCHEMA_11.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
                     _ => {
                         error!(
                             "Invalid chain state database: expected version = {}, got {}",
@@ -968,6 +1540,8 @@ impl StacksChainState {
 
         let mut initial_liquid_ustx = 0u128;
         let mut receipts = vec![];
+        let mut genesis_lockups = vec![];
+        let published_contracts;
 
         {
             let mut clarity_tx = chainstate.genesis_block_begin(
@@ -982,7 +1556,22 @@ impl StacksChainState {
             } else {
                 *boot::STACKS_BOOT_CODE_TESTNET
             };
-            for (boot_code_name, boot_code_contract) in boot_code.iter() {
+            if mainnet && boot_data.extra_boot_contracts.len() > 0 {
+                return Err(Error::InvalidStacksTransaction(
+                    "Extra boot contracts are not allowed on mainnet".to_string(),
+                    false,
+                ));
+            }
+            let boot_contracts = boot_code
+                .iter()
+                .map(|(name, contract)| (*name, *contract))
+                .chain(
+                    boot_data
+                        .extra_boot_contracts
+                        .iter()
+                        .map(|(name, contract)| (name.as_str(), contract.as_str())),
+                );
+            for (boot_code_name, boot_code_contract) in boot_contracts {
                 debug!(
                     "Instantiate boot code contract '{}' ({} bytes)...",
                     boot_code_name,
@@ -990,10 +1579,18 @@ impl StacksChainState {
                 );
 
                 let smart_contract = TransactionPayload::SmartContract(TransactionSmartContract {
-                    name: ContractName::try_from(boot_code_name.to_string())
-                        .expect("FATAL: invalid boot-code contract name"),
-                    code_body: StacksString::from_str(boot_code_contract)
-                        .expect("FATAL: invalid boot code body"),
+                    name: ContractName::try_from(boot_code_name.to_string()).map_err(|_| {
+                        Error::InvalidStacksTransaction(
+                            format!("Invalid boot code contract name '{}'", boot_code_name),
+                            false,
+                        )
+                    })?,
+                    code_body: StacksString::from_str(boot_code_contract).ok_or_else(|| {
+                        Error::InvalidStacksTransaction(
+                            format!("Invalid boot code body for '{}'", boot_code_name),
+                            false,
+                        )
+                    })?,
                 });
 
                 let boot_code_smart_contract = StacksTransaction::new(
@@ -1074,6 +1671,11 @@ impl StacksChainState {
                     for schedule in initial_lockups {
                         let stx_address =
                             StacksChainState::parse_genesis_address(&schedule.address, mainnet);
+                        genesis_lockups.push((
+                            stx_address.clone(),
+                            schedule.amount as u128,
+                            schedule.block_height,
+                        ));
                         let value = Value::Tuple(
                             TupleData::from_data(vec![
                                 ("recipient".into(), Value::Principal(stx_address)),
@@ -1332,6 +1934,9 @@ impl StacksChainState {
                 })
                 .expect("FATAL: `ustx-liquid-supply` overflowed");
 
+            published_contracts =
+                StacksChainState::find_published_contracts(&mut clarity_tx, receipts.iter());
+
             clarity_tx.commit_to_block(&FIRST_BURNCHAIN_CONSENSUS_HASH, &FIRST_STACKS_BLOCK_HASH);
         }
 
@@ -1395,6 +2000,13 @@ impl StacksChainState {
                 &first_tip_info,
                 &ExecutionCost::zero(),
             )?;
+            StacksChainState::insert_genesis_lockups(&mut tx, &genesis_lockups)?;
+            StacksChainState::insert_published_contracts(
+                &mut tx,
+                &first_index_hash,
+                0,
+                &published_contracts,
+            )?;
             tx.commit()?;
         }
 
@@ -1402,6 +2014,66 @@ impl StacksChainState {
         Ok(receipts)
     }
 
+    /// Record the genesis lockups by recipient, for reporting vesting schedules
+    fn insert_genesis_lockups(
+        tx: &mut StacksDBTx,
+        lockups: &[(PrincipalData, u128, u64)],
+    ) -> Result<(), Error> {
+        let insert =
+            "INSERT INTO genesis_lockups (recipient, amount, block_height) VALUES (?1, ?2, ?3)";
+        for (recipient, amount, block_height) in lockups.iter() {
+            let args: &[&dyn ToSql] = &[
+                &recipient.to_string(),
+                &amount.to_string(),
+                &u64_to_sql(*block_height)?,
+            ];
+            tx.execute(insert, args)?;
+        }
+        Ok(())
+    }
+
+    /// Chainstates created before the genesis lockups were indexed by recipient have an empty
+    /// index.  Fill it in from the boot data, if we have it.
+    fn backfill_genesis_lockups(
+        chainstate: &mut StacksChainState,
+        mainnet: bool,
+        boot_data: &mut ChainStateBootData,
+    ) -> Result<(), Error> {
+        let num_lockups = query_count(
+            chainstate.db(),
+            "SELECT COUNT(*) FROM genesis_lockups",
+            NO_PARAMS,
+        )?;
+        if num_lockups > 0 {
+            return Ok(());
+        }
+        let get_schedules = match boot_data.get_bulk_initial_lockups.take() {
+            Some(get_schedules) => get_schedules,
+            None => {
+                return Ok(());
+            }
+        };
+
+        let genesis_lockups: Vec<_> = get_schedules()
+            .map(|schedule| {
+                (
+                    StacksChainState::parse_genesis_address(&schedule.address, mainnet),
+                    schedule.amount as u128,
+                    schedule.block_height,
+                )
+            })
+            .collect();
+        if genesis_lockups.len() == 0 {
+            return Ok(());
+        }
+
+        info!("Indexing {} genesis lockups", genesis_lockups.len());
+        let mut tx = chainstate.index_tx_begin()?;
+        StacksChainState::insert_genesis_lockups(&mut tx, &genesis_lockups)?;
+        tx.commit()?;
+        Ok(())
+    }
+
     pub fn open(
         mainnet: bool,
         chain_id: u32,
@@ -1413,7 +2085,27 @@ impl StacksChainState {
     /// Re-open the chainstate -- i.e. to get a new handle to it using an existing chain state's
     /// parameters
     pub fn reopen(&self) -> Result<(StacksChainState, Vec<StacksTransactionReceipt>), Error> {
-        StacksChainState::open(self.mainnet, self.chain_id, &self.root_path)
+        let (mut chainstate, receipts) =
+            StacksChainState::open(self.mainnet, self.chain_id, &self.root_path)?;
+        chainstate.address_txs_index = self.address_txs_index;
+        chainstate.asset_holdings_index = self.asset_holdings_index;
+        chainstate
+            .clarity_state
+            .set_cost_breakdown(self.clarity_state.has_cost_breakdown());
+        if self.clarity_state.is_detached() {
+            chainstate.clarity_state.set_detached(true);
+        }
+        Ok((chainstate, receipts))
+    }
+
+    /// Reopen the chainstate with a detached Clarity datastore, so a block can be assembled on it
+    /// while other blocks are assembled or processed.  Blocks begun on the returned chainstate --
+    /// or on chainstates reopened from it -- see a snapshot of the Clarity state, and can be mined
+    /// but not processed.
+    pub fn reopen_detached(&self) -> Result<StacksChainState, Error> {
+        let (mut chainstate, _) = self.reopen()?;
+        chainstate.clarity_state.set_detached(true);
+        Ok(chainstate)
     }
 
     pub fn open_testnet<F>(
@@ -1526,6 +2218,10 @@ impl StacksChainState {
             clarity_state_index_root: clarity_state_index_root,
             root_path: path_str.to_string(),
             unconfirmed_state: None,
+            address_txs_index: false,
+            asset_holdings_index: false,
+            vrf_verifier: VRFCachingVerifier::new(),
+            block_prefetcher: BlockPrefetcher::new(),
         };
 
         let mut receipts = vec![];
@@ -1540,7 +2236,10 @@ impl StacksChainState {
                     "StacksChainState initialization is required, but boot_data was not passed."
                 );
             }
-            (false, _) => {}
+            (false, Some(boot_data)) => {
+                StacksChainState::backfill_genesis_lockups(&mut chainstate, mainnet, boot_data)?;
+            }
+            (false, None) => {}
         }
 
         Ok((chainstate, receipts))
@@ -1580,8 +2279,14 @@ impl StacksChainState {
         let clarity_instance = &mut self.clarity_state;
         let inner_tx = StacksDBTx::new(&mut self.state_index, ());
 
-        let chainstate_tx =
-            ChainstateTx::new(inner_tx, blocks_path, self.root_path.clone(), config);
+        let chainstate_tx = ChainstateTx::new(
+            inner_tx,
+            blocks_path,
+            self.root_path.clone(),
+            config,
+            self.address_txs_index,
+            self.asset_holdings_index,
+        );
 
         Ok((chainstate_tx, clarity_instance))
     }
@@ -1627,6 +2332,250 @@ impl StacksChainState {
         self.state_index.sqlite_conn()
     }
 
+    /// Get the index block hashes of all processed blocks that contain the given transaction.
+    /// Blocks processed before schema version 11 are only covered if the transaction log
+    /// (STACKS_TRANSACTION_LOG=1) was enabled when they were processed.
+    pub fn get_transaction_block_ids(
+        conn: &DBConn,
+        txid: &Txid,
+    ) -> Result<Vec<StacksBlockId>, Error> {
+        let sql = "SELECT index_block_hash FROM block_transactions WHERE txid = ?1 UNION SELECT index_block_hash FROM transactions WHERE txid = ?1";
+        let args: &[&dyn ToSql] = &[txid];
+        query_row_columns(conn, sql, args, "index_block_hash").map_err(Error::DBError)
+    }
+
+    /// Find the block in the fork ending at `tip` that mined the given transaction, if any.
+    /// Returns its index block hash and height.
+    pub fn get_transaction_block_in_fork(
+        &self,
+        txid: &Txid,
+        tip: &StacksBlockId,
+    ) -> Result<Option<(StacksBlockId, u64)>, Error> {
+        let index_conn = self.index_conn()?;
+        for block_id in StacksChainState::get_transaction_block_ids(self.db(), txid)? {
+            if let Some(block_height) = index_conn.get_ancestor_block_height(&block_id, tip)? {
+                return Ok(Some((block_id, block_height)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get a page of the transactions that affected the given principal in the fork ending at
+    /// `tip`, newest first.  Also returns the total number of such transactions.
+    /// Transactions are only indexed if the address transaction index is enabled.
+    pub fn get_address_transactions(
+        &self,
+        principal: &PrincipalData,
+        tip: &StacksBlockId,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(u64, Vec<AddressTransaction>), Error> {
+        let principal = principal.to_string();
+        let args: &[&dyn ToSql] = &[&principal];
+        let lowest_height: Option<i64> = self
+            .db()
+            .query_row(
+                "SELECT MIN(block_height) FROM address_txs WHERE principal = ?1",
+                args,
+                |row| row.get(0),
+            )
+            .map_err(db_error::SqliteError)?;
+        let lowest_height = match lowest_height {
+            Some(height) => height,
+            None => {
+                return Ok((0, vec![]));
+            }
+        };
+
+        // the index holds transactions from all forks, so only keep those in blocks in this one.
+        // The fork is found by walking back from the tip, no further than the principal's oldest
+        // indexed transaction.
+        let fork = "WITH RECURSIVE fork(index_block_hash, parent_block_id, block_height) AS (
+                SELECT index_block_hash, parent_block_id, block_height FROM block_headers WHERE index_block_hash = ?2
+                UNION ALL
+                SELECT block_headers.index_block_hash, block_headers.parent_block_id, block_headers.block_height
+                FROM block_headers JOIN fork ON block_headers.index_block_hash = fork.parent_block_id
+                WHERE fork.block_height > ?3
+            )";
+        let count_sql = format!("{} SELECT COUNT(*) FROM address_txs JOIN fork ON address_txs.index_block_hash = fork.index_block_hash WHERE address_txs.principal = ?1", fork);
+        let args: &[&dyn ToSql] = &[&principal, tip, &lowest_height];
+        let total = query_count(self.db(), &count_sql, args)?;
+
+        let page_sql = format!("{} SELECT address_txs.* FROM address_txs JOIN fork ON address_txs.index_block_hash = fork.index_block_hash WHERE address_txs.principal = ?1 ORDER BY address_txs.block_height DESC, address_txs.tx_index DESC, address_txs.txid DESC LIMIT ?4 OFFSET ?5", fork);
+        let args: &[&dyn ToSql] = &[
+            &principal,
+            tip,
+            &lowest_height,
+            &u64_to_sql(limit)?,
+            &u64_to_sql(offset)?,
+        ];
+        let page = query_rows(self.db(), &page_sql, args)?;
+        Ok((total as u64, page))
+    }
+
+    /// Get the fungible token balances and non-fungible tokens held by the given principal in the
+    /// fork ending at `tip`.  Token movements are only indexed if the asset holdings index is
+    /// enabled.
+    pub fn get_asset_holdings(
+        &self,
+        principal: &PrincipalData,
+        tip: &StacksBlockId,
+    ) -> Result<AssetHoldings, Error> {
+        let sql = "SELECT asset_identifier, nft_value, credit, amount, index_block_hash FROM asset_holdings_deltas WHERE principal = ?1 ORDER BY block_height ASC, tx_index ASC, event_index ASC";
+        let args: &[&dyn ToSql] = &[&principal.to_string()];
+        let mut stmt = self.db().prepare(sql)?;
+        let mut rows = stmt.query(args)?;
+
+        // the index holds token movements from all forks, so only apply those in this one
+        let index_conn = self.index_conn()?;
+        let mut in_fork: HashMap<StacksBlockId, bool> = HashMap::new();
+        let mut holdings = AssetHoldings::default();
+        while let Some(row) = rows.next()? {
+            let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+            let is_ancestor = match in_fork.get(&index_block_hash) {
+                Some(is_ancestor) => *is_ancestor,
+                None => {
+                    let is_ancestor = index_conn
+                        .get_ancestor_block_height(&index_block_hash, tip)?
+                        .is_some();
+                    in_fork.insert(index_block_hash, is_ancestor);
+                    is_ancestor
+                }
+            };
+            if !is_ancestor {
+                continue;
+            }
+
+            let asset_identifier: String = row.get_unwrap("asset_identifier");
+            let nft_value: Option<String> = row.get_unwrap("nft_value");
+            let credit: bool = row.get_unwrap("credit");
+            let amount_str: String = row.get_unwrap("amount");
+            let amount = amount_str
+                .parse::<u128>()
+                .map_err(|_| db_error::ParseError)?;
+
+            match nft_value {
+                Some(nft_value) => {
+                    let owned = holdings
+                        .non_fungible_tokens
+                        .entry(asset_identifier)
+                        .or_insert_with(BTreeSet::new);
+                    if credit {
+                        owned.insert(nft_value);
+                    } else {
+                        owned.remove(&nft_value);
+                    }
+                }
+                None => {
+                    let balance = holdings
+                        .fungible_tokens
+                        .entry(asset_identifier)
+                        .or_insert(0);
+                    *balance = if credit {
+                        balance.saturating_add(amount)
+                    } else {
+                        balance.saturating_sub(amount)
+                    };
+                }
+            }
+        }
+
+        holdings.fungible_tokens.retain(|_, balance| *balance > 0);
+        holdings
+            .non_fungible_tokens
+            .retain(|_, owned| owned.len() > 0);
+        Ok(holdings)
+    }
+
+    /// Get the mint, transfer, and burn events of the non-fungible token `value` of the asset
+    /// `asset_identifier` (`<contract-id>::<asset-name>`) in the fork ending at `tip`, oldest
+    /// first.  Token movements are only indexed if the asset holdings index is enabled.
+    pub fn get_nft_history(
+        &self,
+        asset_identifier: &str,
+        value: &Value,
+        tip: &StacksBlockId,
+    ) -> Result<Vec<NftHistoryEvent>, Error> {
+        let sql = "SELECT principal, credit, txid, index_block_hash, block_height, tx_index, event_index FROM asset_holdings_deltas WHERE asset_identifier = ?1 AND nft_value = ?2 ORDER BY block_height ASC, tx_index ASC, event_index ASC";
+        let args: &[&dyn ToSql] = &[&asset_identifier, &value.serialize()];
+        let mut stmt = self.db().prepare(sql)?;
+        let mut rows = stmt.query(args)?;
+
+        // the index holds token movements from all forks, so only report those in this one
+        let index_conn = self.index_conn()?;
+        let mut in_fork: HashMap<StacksBlockId, bool> = HashMap::new();
+        let mut history: Vec<NftHistoryEvent> = vec![];
+        while let Some(row) = rows.next()? {
+            let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+            let is_ancestor = match in_fork.get(&index_block_hash) {
+                Some(is_ancestor) => *is_ancestor,
+                None => {
+                    let is_ancestor = index_conn
+                        .get_ancestor_block_height(&index_block_hash, tip)?
+                        .is_some();
+                    in_fork.insert(index_block_hash.clone(), is_ancestor);
+                    is_ancestor
+                }
+            };
+            if !is_ancestor {
+                continue;
+            }
+
+            let principal_str: String = row.get_unwrap("principal");
+            let principal =
+                PrincipalData::parse(&principal_str).map_err(|_| db_error::ParseError)?;
+            let credit: bool = row.get_unwrap("credit");
+            let txid = match row.get_unwrap::<_, Option<String>>("txid") {
+                Some(txid_hex) => {
+                    Some(Txid::from_hex(&txid_hex).map_err(|_| db_error::ParseError)?)
+                }
+                None => None,
+            };
+            let block_height = u64::from_column(row, "block_height")?;
+            let tx_index_i64: i64 = row.get_unwrap("tx_index");
+            let event_index_i64: i64 = row.get_unwrap("event_index");
+
+            // a transfer is indexed as a debit of its sender and a credit of its recipient
+            let tx_index = tx_index_i64 as u32;
+            let event_index = event_index_i64 as u32;
+            let same_event = match history.last() {
+                Some(last) => {
+                    last.index_block_hash == index_block_hash
+                        && last.tx_index == tx_index
+                        && last.event_index == event_index
+                }
+                None => false,
+            };
+            if !same_event {
+                history.push(NftHistoryEvent {
+                    event_type: NftHistoryEventType::Transfer,
+                    sender: None,
+                    recipient: None,
+                    txid,
+                    index_block_hash,
+                    block_height,
+                    tx_index,
+                    event_index,
+                });
+            }
+            let event = history.last_mut().expect("BUG: history event is missing");
+            if credit {
+                event.recipient = Some(principal);
+            } else {
+                event.sender = Some(principal);
+            }
+        }
+
+        for event in history.iter_mut() {
+            event.event_type = match (&event.sender, &event.recipient) {
+                (None, _) => NftHistoryEventType::Mint,
+                (_, None) => NftHistoryEventType::Burn,
+                _ => NftHistoryEventType::Transfer,
+            };
+        }
+        Ok(history)
+    }
+
     /// Begin processing an epoch's transactions within the context of a chainstate transaction
     pub fn chainstate_block_begin<'a>(
         chainstate_tx: &'a ChainstateTx<'a>,
@@ -2008,6 +2957,7 @@ impl StacksChainState {
         user_burns: &Vec<StagingUserBurnSupport>,
         anchor_block_cost: &ExecutionCost,
         anchor_block_size: u64,
+        block_stats: StacksBlockStats,
         applied_epoch_transition: bool,
     ) -> Result<StacksHeaderInfo, Error> {
         if new_tip.parent_block != FIRST_STACKS_BLOCK_HASH {
@@ -2052,6 +3002,7 @@ impl StacksChainState {
             burn_header_height: new_burnchain_height,
             burn_header_timestamp: new_burnchain_timestamp,
             anchored_block_size: anchor_block_size,
+            block_stats: Some(block_stats),
         };
 
         StacksChainState::insert_stacks_block_header(
@@ -2069,11 +3020,11 @@ impl StacksChainState {
             headers_tx.execute(sql, args)?;
         }
 
-        debug!(
-            "Advanced to new tip! {}/{}",
-            new_consensus_hash,
-            new_tip.block_hash()
-        );
+        debug!(#"advanced_tip", "Advanced to new tip! {}/{}",
+               new_consensus_hash,
+               new_tip.block_hash();
+               "index_block_hash" => %index_block_hash,
+               "stacks_height" => new_tip_info.block_height);
         Ok(new_tip_info)
     }
 }
@@ -2085,7 +3036,9 @@ pub mod test {
     use chainstate::stacks::db::*;
     use chainstate::stacks::*;
     use stx_genesis::GenesisData;
+    use util::hash::Sha512Trunc256Sum;
     use vm::tests::TEST_BURN_STATE_DB;
+    use vm::types::AssetIdentifier;
 
     use crate::util::boot::boot_code_test_addr;
 
@@ -2129,6 +3082,7 @@ pub mod test {
             get_bulk_initial_balances: None,
             get_bulk_initial_names: None,
             get_bulk_initial_namespaces: None,
+            extra_boot_contracts: vec![],
         };
 
         StacksChainState::open_and_exec(mainnet, chain_id, &path, Some(&mut boot_data))
@@ -2169,6 +3123,476 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_instantiate_chainstate_with_extra_boot_contracts() {
+        let path = chainstate_path("extra-boot-contracts");
+        if fs::metadata(&path).is_ok() {
+            fs::remove_dir_all(&path).unwrap();
+        }
+
+        let make_boot_data = || ChainStateBootData {
+            initial_balances: vec![],
+            post_flight_callback: None,
+            first_burnchain_block_hash: BurnchainHeaderHash::zero(),
+            first_burnchain_block_height: 0,
+            first_burnchain_block_timestamp: 0,
+            pox_constants: PoxConstants::testnet_default(),
+            get_bulk_initial_lockups: None,
+            get_bulk_initial_balances: None,
+            get_bulk_initial_names: None,
+            get_bulk_initial_namespaces: None,
+            extra_boot_contracts: vec![(
+                "app-chain-config".to_string(),
+                "(define-read-only (get-block-reward) u1000)".to_string(),
+            )],
+        };
+
+        // not allowed on mainnet
+        let mainnet_path = chainstate_path("extra-boot-contracts-mainnet");
+        if fs::metadata(&mainnet_path).is_ok() {
+            fs::remove_dir_all(&mainnet_path).unwrap();
+        }
+        assert!(StacksChainState::open_and_exec(
+            true,
+            0x00000001,
+            &mainnet_path,
+            Some(&mut make_boot_data())
+        )
+        .is_err());
+
+        let mut chainstate =
+            StacksChainState::open_and_exec(false, 0x80000000, &path, Some(&mut make_boot_data()))
+                .unwrap()
+                .0;
+
+        let mut conn = chainstate.block_begin(
+            &TEST_BURN_STATE_DB,
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+            &MINER_BLOCK_CONSENSUS_HASH,
+            &MINER_BLOCK_HEADER_HASH,
+        );
+        let contract_id = QualifiedContractIdentifier::new(
+            boot_code_test_addr().into(),
+            ContractName::try_from("app-chain-config".to_string()).unwrap(),
+        );
+        assert!(StacksChainState::get_contract(&mut conn, &contract_id)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_address_transactions_index() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "address-txs-index");
+
+        let alice = StacksAddress {
+            version: 1,
+            bytes: Hash160([0x11; 20]),
+        }
+        .to_account_principal();
+        let bob = StacksAddress {
+            version: 1,
+            bytes: Hash160([0x22; 20]),
+        }
+        .to_account_principal();
+
+        let receipts: Vec<_> = (0..3)
+            .map(|i| StacksTransactionReceipt {
+                transaction: TransactionOrigin::Burn(Txid([i as u8; 32])),
+                events: vec![StacksTransactionEvent::STXEvent(
+                    STXEventType::STXTransferEvent(STXTransferEventData {
+                        sender: alice.clone(),
+                        recipient: if i == 1 { bob.clone() } else { alice.clone() },
+                        amount: 1,
+                    }),
+                )],
+                post_condition_aborted: false,
+                result: Value::okay_true(),
+                stx_burned: 0,
+                contract_analysis: None,
+                execution_cost: ExecutionCost::zero(),
+                microblock_header: None,
+                cost_breakdown: None,
+            })
+            .collect();
+
+        // not indexed unless enabled
+        {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx
+                .index_address_transactions(&FIRST_STACKS_BLOCK_ID, 0, &receipts)
+                .unwrap();
+            chainstate_tx.commit().unwrap();
+        }
+        let (total, txs) = chainstate
+            .get_address_transactions(&alice, &FIRST_STACKS_BLOCK_ID, 0, 10)
+            .unwrap();
+        assert_eq!(total, 0);
+        assert!(txs.is_empty());
+
+        chainstate.address_txs_index = true;
+        {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx
+                .index_address_transactions(&FIRST_STACKS_BLOCK_ID, 0, &receipts)
+                .unwrap();
+            chainstate_tx.commit().unwrap();
+        }
+
+        // newest first
+        let (total, txs) = chainstate
+            .get_address_transactions(&alice, &FIRST_STACKS_BLOCK_ID, 0, 10)
+            .unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(
+            txs.iter().map(|tx| tx.txid.clone()).collect::<Vec<_>>(),
+            vec![Txid([2; 32]), Txid([1; 32]), Txid([0; 32])]
+        );
+
+        // paginated
+        let (total, txs) = chainstate
+            .get_address_transactions(&alice, &FIRST_STACKS_BLOCK_ID, 1, 1)
+            .unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].txid, Txid([1; 32]));
+        assert_eq!(txs[0].tx_index, 1);
+
+        let (total, txs) = chainstate
+            .get_address_transactions(&bob, &FIRST_STACKS_BLOCK_ID, 0, 10)
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(txs[0].txid, Txid([1; 32]));
+        assert_eq!(txs[0].index_block_hash, *FIRST_STACKS_BLOCK_ID);
+
+        // transactions in blocks that are not in the fork are left out
+        {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx
+                .index_address_transactions(&StacksBlockId([0x33; 32]), 1, &receipts[0..1])
+                .unwrap();
+            chainstate_tx.commit().unwrap();
+        }
+        let (total, txs) = chainstate
+            .get_address_transactions(&alice, &FIRST_STACKS_BLOCK_ID, 0, 10)
+            .unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(txs.len(), 3);
+        assert!(txs
+            .iter()
+            .all(|tx| tx.index_block_hash == *FIRST_STACKS_BLOCK_ID));
+
+        // nothing past the end
+        let (total, txs) = chainstate
+            .get_address_transactions(&alice, &FIRST_STACKS_BLOCK_ID, 3, 10)
+            .unwrap();
+        assert_eq!(total, 3);
+        assert!(txs.is_empty());
+    }
+
+    #[test]
+    fn test_asset_holdings_index() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "asset-holdings-index");
+
+        let alice = StacksAddress {
+            version: 1,
+            bytes: Hash160([0x11; 20]),
+        }
+        .to_account_principal();
+        let bob = StacksAddress {
+            version: 1,
+            bytes: Hash160([0x22; 20]),
+        }
+        .to_account_principal();
+        let contract_id = QualifiedContractIdentifier::local("tokens").unwrap();
+        let ft = AssetIdentifier {
+            contract_identifier: contract_id.clone(),
+            asset_name: "ft".into(),
+        };
+        let nft = AssetIdentifier {
+            contract_identifier: contract_id.clone(),
+            asset_name: "nft".into(),
+        };
+
+        let events = vec![
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(FTMintEventData {
+                asset_identifier: ft.clone(),
+                recipient: alice.clone(),
+                amount: 100,
+            })),
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(FTTransferEventData {
+                asset_identifier: ft.clone(),
+                sender: alice.clone(),
+                recipient: bob.clone(),
+                amount: 30,
+            })),
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(FTBurnEventData {
+                asset_identifier: ft.clone(),
+                sender: bob.clone(),
+                amount: 30,
+            })),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(NFTMintEventData {
+                asset_identifier: nft.clone(),
+                recipient: alice.clone(),
+                value: Value::UInt(1),
+            })),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(NFTMintEventData {
+                asset_identifier: nft.clone(),
+                recipient: alice.clone(),
+                value: Value::UInt(2),
+            })),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(
+                NFTTransferEventData {
+                    asset_identifier: nft.clone(),
+                    sender: alice.clone(),
+                    recipient: bob.clone(),
+                    value: Value::UInt(2),
+                },
+            )),
+        ];
+        let receipts: Vec<_> = (0..2)
+            .map(|i| StacksTransactionReceipt {
+                transaction: TransactionOrigin::Burn(Txid([i as u8; 32])),
+                events: events.clone(),
+                // the second transaction's movements were rolled back
+                post_condition_aborted: i == 1,
+                result: Value::okay_true(),
+                stx_burned: 0,
+                contract_analysis: None,
+                execution_cost: ExecutionCost::zero(),
+                microblock_header: None,
+                cost_breakdown: None,
+            })
+            .collect();
+
+        // not indexed unless enabled
+        {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx.index_asset_holdings(&FIRST_STACKS_BLOCK_ID, 0, &receipts);
+            chainstate_tx.commit().unwrap();
+        }
+        assert_eq!(
+            chainstate
+                .get_asset_holdings(&alice, &FIRST_STACKS_BLOCK_ID)
+                .unwrap(),
+            AssetHoldings::default()
+        );
+
+        chainstate.asset_holdings_index = true;
+        {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx.index_asset_holdings(&FIRST_STACKS_BLOCK_ID, 0, &receipts);
+            chainstate_tx.commit().unwrap();
+        }
+
+        let holdings = chainstate
+            .get_asset_holdings(&alice, &FIRST_STACKS_BLOCK_ID)
+            .unwrap();
+        assert_eq!(
+            holdings.fungible_tokens.get(&ft.to_string()).cloned(),
+            Some(70)
+        );
+        assert_eq!(
+            holdings.non_fungible_tokens.get(&nft.to_string()).cloned(),
+            Some(vec![Value::UInt(1).serialize()].into_iter().collect())
+        );
+
+        // spent balances and transferred tokens are left out
+        let holdings = chainstate
+            .get_asset_holdings(&bob, &FIRST_STACKS_BLOCK_ID)
+            .unwrap();
+        assert!(holdings.fungible_tokens.is_empty());
+        assert_eq!(
+            holdings.non_fungible_tokens.get(&nft.to_string()).cloned(),
+            Some(vec![Value::UInt(2).serialize()].into_iter().collect())
+        );
+    }
+
+    #[test]
+    fn test_nft_history() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "nft-history");
+        chainstate.asset_holdings_index = true;
+
+        let alice = StacksAddress {
+            version: 1,
+            bytes: Hash160([0x11; 20]),
+        }
+        .to_account_principal();
+        let bob = StacksAddress {
+            version: 1,
+            bytes: Hash160([0x22; 20]),
+        }
+        .to_account_principal();
+        let nft = AssetIdentifier {
+            contract_identifier: QualifiedContractIdentifier::local("tokens").unwrap(),
+            asset_name: "nft".into(),
+        };
+
+        let events = vec![
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(NFTMintEventData {
+                asset_identifier: nft.clone(),
+                recipient: alice.clone(),
+                value: Value::UInt(1),
+            })),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(NFTMintEventData {
+                asset_identifier: nft.clone(),
+                recipient: alice.clone(),
+                value: Value::UInt(2),
+            })),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(
+                NFTTransferEventData {
+                    asset_identifier: nft.clone(),
+                    sender: alice.clone(),
+                    recipient: bob.clone(),
+                    value: Value::UInt(2),
+                },
+            )),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(NFTBurnEventData {
+                asset_identifier: nft.clone(),
+                sender: bob.clone(),
+                value: Value::UInt(2),
+            })),
+        ];
+        // one transaction per event
+        let receipts: Vec<_> = events
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| StacksTransactionReceipt {
+                transaction: TransactionOrigin::Burn(Txid([i as u8; 32])),
+                events: vec![event],
+                post_condition_aborted: false,
+                result: Value::okay_true(),
+                stx_burned: 0,
+                contract_analysis: None,
+                execution_cost: ExecutionCost::zero(),
+                microblock_header: None,
+                cost_breakdown: None,
+            })
+            .collect();
+        {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx.index_asset_holdings(&FIRST_STACKS_BLOCK_ID, 0, &receipts);
+            chainstate_tx.commit().unwrap();
+        }
+
+        let history = chainstate
+            .get_nft_history(&nft.to_string(), &Value::UInt(2), &FIRST_STACKS_BLOCK_ID)
+            .unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|event| (
+                    event.event_type.clone(),
+                    event.sender.clone(),
+                    event.recipient.clone(),
+                    event.txid.clone(),
+                    event.tx_index
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    NftHistoryEventType::Mint,
+                    None,
+                    Some(alice.clone()),
+                    Some(Txid([1; 32])),
+                    1
+                ),
+                (
+                    NftHistoryEventType::Transfer,
+                    Some(alice.clone()),
+                    Some(bob.clone()),
+                    Some(Txid([2; 32])),
+                    2
+                ),
+                (
+                    NftHistoryEventType::Burn,
+                    Some(bob.clone()),
+                    None,
+                    Some(Txid([3; 32])),
+                    3
+                ),
+            ]
+        );
+
+        assert_eq!(
+            chainstate
+                .get_nft_history(&nft.to_string(), &Value::UInt(3), &FIRST_STACKS_BLOCK_ID)
+                .unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_block_stats() {
+        let chainstate = instantiate_chainstate(false, 0x80000000, "block-stats");
+
+        // the genesis block has no transactions of its own
+        let genesis = StacksChainState::get_genesis_header_info(chainstate.db()).unwrap();
+        assert_eq!(genesis.block_stats, Some(StacksBlockStats::empty()));
+
+        let mut tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&StacksPrivateKey::new()).unwrap(),
+            TransactionPayload::Coinbase(CoinbasePayload([0; 32])),
+        );
+        tx.set_tx_fee(123);
+        let receipt = |transaction: TransactionOrigin, stx_burned: u128| StacksTransactionReceipt {
+            transaction,
+            events: vec![],
+            post_condition_aborted: false,
+            result: Value::okay_true(),
+            stx_burned,
+            contract_analysis: None,
+            execution_cost: ExecutionCost::zero(),
+            microblock_header: None,
+            cost_breakdown: None,
+        };
+        let receipts = vec![
+            receipt(TransactionOrigin::Stacks(tx.clone()), 10),
+            receipt(TransactionOrigin::Stacks(tx), 20),
+            // burnchain operations are not counted
+            receipt(TransactionOrigin::Burn(Txid([0x01; 32])), 1000),
+        ];
+        let cost = ExecutionCost {
+            write_length: 1,
+            write_count: 2,
+            read_length: 3,
+            read_count: 4,
+            runtime: 5,
+        };
+        assert_eq!(
+            StacksBlockStats::from_receipts(&receipts, cost.clone()),
+            StacksBlockStats {
+                tx_count: 2,
+                total_fees: 246,
+                total_burn: 30,
+                total_cost: cost,
+            }
+        );
+    }
+
+    #[test]
+    fn test_published_contracts_index() {
+        let chainstate = instantiate_chainstate(false, 0x80000000, "published-contracts-index");
+
+        // the boot contracts are published by the genesis block, in order
+        let published = chainstate
+            .get_published_contracts(&FIRST_STACKS_BLOCK_ID)
+            .unwrap();
+        let boot_code = *boot::STACKS_BOOT_CODE_TESTNET;
+        assert_eq!(published.len(), boot_code.len());
+        for (i, (contract, (name, code))) in published.iter().zip(boot_code.iter()).enumerate() {
+            assert_eq!(contract.contract_id, boot_code_id(name, false));
+            assert_eq!(contract.index_block_hash, *FIRST_STACKS_BLOCK_ID);
+            assert_eq!(contract.block_height, 0);
+            assert_eq!(contract.tx_index, i as u32);
+            assert_eq!(
+                contract.code_body_hash,
+                Sha512Trunc256Sum::from_data(code.as_bytes())
+            );
+        }
+    }
+
     #[test]
     fn test_chainstate_sampled_genesis_consistency() {
         // Test root hash for the test chainstate data set
@@ -2221,6 +3645,7 @@ pub mod test {
                         }),
                 )
             })),
+            extra_boot_contracts: vec![],
         };
 
         let path = chainstate_path("genesis-consistency-chainstate-test");
@@ -2311,6 +3736,7 @@ pub mod test {
                         }),
                 )
             })),
+            extra_boot_contracts: vec![],
         };
 
         let path = chainstate_path("genesis-consistency-chainstate");
@@ -0,0 +1,338 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pruning for follower nodes that only need the chain's headers and current state.
+//!
+//! A pruned node discards anchored block bodies, microblock streams, and the Clarity MARF tries
+//! of non-canonical blocks once they are more than a given number of blocks below the canonical
+//! tip.  Block files are truncated to zero bytes rather than removed, which is how the chunk
+//! store already marks blocks that it will not serve: the downloader still sees them as stored,
+//! but the inventory and RPC interfaces report them as missing.
+//!
+//! Headers, the staging metadata, and the canonical fork's MARF tries are all kept, so the node
+//! can keep processing new blocks and answering queries against recent state.  It can no longer
+//! handle a reorg that is deeper than the prune depth, however, since the tries and blocks of any
+//! such fork will be gone.
+
+use std::collections::HashSet;
+use std::fs;
+
+use rusqlite::types::ToSql;
+use rusqlite::OpenFlags;
+
+use chainstate::burn::ConsensusHash;
+use chainstate::stacks::db::*;
+use chainstate::stacks::Error;
+use util::db::Error as db_error;
+use util::db::{query_row_columns, query_rows, sqlite_open, tx_begin_immediate, u64_to_sql};
+
+use crate::types::chainstate::{BlockHeaderHash, StacksBlockId};
+
+/// What a call to `StacksChainState::prune` removed
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PruneStats {
+    /// Everything below this height has been pruned.  Pass it back in as `from_height` on the
+    /// next call so already-pruned heights are not scanned again.
+    pub pruned_height: u64,
+    /// Number of anchored block bodies discarded
+    pub blocks_pruned: u64,
+    /// Number of bytes freed by discarding anchored block bodies
+    pub block_bytes_freed: u64,
+    /// Number of microblocks discarded
+    pub microblocks_pruned: u64,
+    /// Number of non-canonical Clarity MARF tries discarded
+    pub tries_pruned: u64,
+}
+
+impl StacksChainState {
+    /// Prune processed blocks, microblocks, and non-canonical Clarity state that is more than
+    /// `keep_depth` blocks below the given canonical chain tip.  Only heights at or above
+    /// `from_height` are considered.
+    pub fn prune(
+        &mut self,
+        tip_consensus_hash: &ConsensusHash,
+        tip_block_hash: &BlockHeaderHash,
+        keep_depth: u64,
+        from_height: u64,
+    ) -> Result<PruneStats, Error> {
+        let tip = StacksChainState::get_anchored_block_header_info(
+            self.db(),
+            tip_consensus_hash,
+            tip_block_hash,
+        )?
+        .ok_or(Error::NoSuchBlockError)?;
+
+        let mut stats = PruneStats {
+            pruned_height: from_height,
+            ..PruneStats::default()
+        };
+
+        let cutoff_height = tip.block_height.saturating_sub(keep_depth);
+        if cutoff_height <= from_height {
+            return Ok(stats);
+        }
+
+        debug!(
+            "Prune chainstate between heights {} and {} (tip {}/{} at {})",
+            from_height, cutoff_height, tip_consensus_hash, tip_block_hash, tip.block_height
+        );
+
+        let canonical: HashSet<StacksBlockId> =
+            StacksChainState::get_ancestors_headers(self.db(), tip, from_height)?
+                .into_iter()
+                .filter(|header| header.block_height < cutoff_height)
+                .map(|header| header.index_block_hash())
+                .collect();
+
+        let height_args: &[&dyn ToSql] = &[&u64_to_sql(from_height)?, &u64_to_sql(cutoff_height)?];
+
+        // anchored block bodies
+        let sql =
+            "SELECT * FROM staging_blocks WHERE processed = 1 AND height >= ?1 AND height < ?2";
+        let blocks = query_rows::<StagingBlock, _>(self.db(), sql, height_args)?;
+        for block in blocks.iter() {
+            let block_path = StacksChainState::get_block_path(
+                &self.blocks_path,
+                &block.consensus_hash,
+                &block.anchored_block_hash,
+            )?;
+            let sz = match StacksChainState::get_file_size(&block_path) {
+                Ok(sz) => sz,
+                Err(Error::DBError(db_error::NotFoundError)) => {
+                    continue;
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+            };
+            if sz == 0 {
+                // already pruned or freed
+                continue;
+            }
+            fs::OpenOptions::new()
+                .read(false)
+                .write(true)
+                .truncate(true)
+                .open(&block_path)
+                .map_err(|e| Error::DBError(db_error::IOError(e)))?;
+
+            stats.blocks_pruned += 1;
+            stats.block_bytes_freed += sz;
+        }
+
+        // microblock streams confirmed by those blocks' children
+        {
+            let tx = self.db_tx_begin()?;
+            let microblocks_sql = "SELECT microblock_hash FROM staging_microblocks WHERE processed = 1 AND index_block_hash IN (SELECT index_block_hash FROM staging_blocks WHERE processed = 1 AND height >= ?1 AND height < ?2)";
+            tx.execute(
+                &format!(
+                    "DELETE FROM staging_microblocks_data WHERE block_hash IN ({})",
+                    microblocks_sql
+                ),
+                height_args,
+            )
+            .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+            stats.microblocks_pruned = tx
+                .execute(
+                    "DELETE FROM staging_microblocks WHERE processed = 1 AND index_block_hash IN (SELECT index_block_hash FROM staging_blocks WHERE processed = 1 AND height >= ?1 AND height < ?2)",
+                    height_args,
+                )
+                .map_err(|e| Error::DBError(db_error::SqliteError(e)))?
+                as u64;
+            tx.commit()
+                .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+        }
+
+        // Clarity MARF tries and metadata of blocks off of the canonical fork.  A trie only
+        // references its ancestors' tries, so the canonical fork never reads these.
+        let sql = "SELECT index_block_hash FROM block_headers WHERE block_height >= ?1 AND block_height < ?2";
+        let non_canonical: Vec<StacksBlockId> =
+            query_row_columns(self.db(), sql, height_args, "index_block_hash")?
+                .into_iter()
+                .filter(|index_block_hash| !canonical.contains(index_block_hash))
+                .collect();
+
+        if non_canonical.len() > 0 {
+            let mut clarity_conn = sqlite_open(
+                &self.clarity_state_index_path,
+                OpenFlags::SQLITE_OPEN_READ_WRITE,
+                true,
+            )
+            .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+            let tx = tx_begin_immediate(&mut clarity_conn)?;
+            for index_block_hash in non_canonical.iter() {
+                let args: &[&dyn ToSql] = &[index_block_hash];
+                let num_tries = tx
+                    .execute(
+                        "DELETE FROM marf_data WHERE block_hash = ?1 AND unconfirmed = 0",
+                        args,
+                    )
+                    .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+                tx.execute("DELETE FROM metadata_table WHERE blockhash = ?1", args)
+                    .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+                stats.tries_pruned += num_tries as u64;
+            }
+            tx.commit()
+                .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+        }
+
+        stats.pruned_height = cutoff_height;
+        info!(
+            "Pruned chainstate below height {}: {} blocks ({} bytes), {} microblocks, {} non-canonical tries",
+            cutoff_height,
+            stats.blocks_pruned,
+            stats.block_bytes_freed,
+            stats.microblocks_pruned,
+            stats.tries_pruned
+        );
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chainstate::burn::db::sortdb::*;
+    use chainstate::stacks::db::test::*;
+    use chainstate::stacks::miner::test::*;
+    use chainstate::stacks::miner::*;
+    use chainstate::stacks::*;
+    use core::mempool::*;
+    use net::test::*;
+    use util::hash::*;
+
+    use super::*;
+
+    #[test]
+    fn test_prune_linear_chain() {
+        let peer_config = TestPeerConfig::new("test_prune_linear_chain", 21320, 21321);
+        let mut peer = TestPeer::new(peer_config);
+        let chainstate_path = peer.chainstate_path.clone();
+
+        let num_blocks = 10;
+        let mut blocks = vec![];
+        for tenure_id in 0..num_blocks {
+            let tip =
+                SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+                    .unwrap();
+
+            let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+                |ref mut miner,
+                 ref mut sortdb,
+                 ref mut chainstate,
+                 vrf_proof,
+                 ref parent_opt,
+                 ref parent_microblock_header_opt| {
+                    let parent_tip = match parent_opt {
+                        None => StacksChainState::get_genesis_header_info(chainstate.db()).unwrap(),
+                        Some(block) => {
+                            let ic = sortdb.index_conn();
+                            let snapshot =
+                                SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                                    &ic,
+                                    &tip.sortition_id,
+                                    &block.block_hash(),
+                                )
+                                .unwrap()
+                                .unwrap();
+                            StacksChainState::get_anchored_block_header_info(
+                                chainstate.db(),
+                                &snapshot.consensus_hash,
+                                &snapshot.winning_stacks_block_hash,
+                            )
+                            .unwrap()
+                            .unwrap()
+                        }
+                    };
+
+                    let mut mempool =
+                        MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+                    let coinbase_tx = make_coinbase(miner, tenure_id);
+
+                    let anchored_block = StacksBlockBuilder::build_anchored_block(
+                        chainstate,
+                        &sortdb.index_conn(),
+                        &mut mempool,
+                        &parent_tip,
+                        tip.total_burn,
+                        vrf_proof,
+                        Hash160([tenure_id as u8; 20]),
+                        &coinbase_tx,
+                        BlockBuilderSettings::max_value(),
+                        None,
+                    )
+                    .unwrap();
+                    (anchored_block.0, vec![])
+                },
+            );
+
+            let (_, _, consensus_hash) = peer.next_burnchain_block(burn_ops.clone());
+            peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+            blocks.push((consensus_hash, stacks_block));
+        }
+
+        let (tip_consensus_hash, tip_block) = blocks.last().cloned().unwrap();
+        let blocks_path = peer.chainstate().blocks_path.clone();
+
+        // keep the last 4 blocks
+        let stats = peer
+            .chainstate()
+            .prune(&tip_consensus_hash, &tip_block.block_hash(), 4, 0)
+            .unwrap();
+        assert_eq!(stats.pruned_height, 6);
+        assert_eq!(stats.blocks_pruned, 5);
+        assert!(stats.block_bytes_freed > 0);
+        assert_eq!(stats.tries_pruned, 0);
+
+        for (i, (consensus_hash, block)) in blocks.iter().enumerate() {
+            let index_block_hash =
+                StacksBlockHeader::make_index_block_hash(consensus_hash, &block.block_hash());
+            let loaded =
+                StacksChainState::load_block(&blocks_path, consensus_hash, &block.block_hash())
+                    .unwrap();
+            if i + 1 < 6 {
+                assert!(loaded.is_none(), "block {} was not pruned", i);
+            } else {
+                assert_eq!(loaded.as_ref(), Some(block));
+            }
+
+            // headers are all still there
+            assert!(
+                StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                    peer.chainstate().db(),
+                    &index_block_hash
+                )
+                .unwrap()
+                .is_some()
+            );
+
+            // and the downloader won't try to fetch pruned blocks again
+            assert!(StacksChainState::has_block_indexed(&blocks_path, &index_block_hash).unwrap());
+        }
+
+        // nothing new to prune until the tip advances
+        let stats = peer
+            .chainstate()
+            .prune(&tip_consensus_hash, &tip_block.block_hash(), 4, 6)
+            .unwrap();
+        assert_eq!(
+            stats,
+            PruneStats {
+                pruned_height: 6,
+                ..PruneStats::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,338 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Rebuilding a chainstate from the blocks stored in another one.
+//!
+//! If a node's Clarity state or headers MARF is damaged, it does not need to fetch the chain from
+//! its peers again: its chunk store and staging tables still hold every anchored block and
+//! microblock it ever accepted, and its sortition DB still says which of them were chosen.
+//! `StacksChainState::reindex_from` re-stages those blocks into a freshly-booted chainstate and
+//! re-processes them.  Only the source's staging tables and block files are read, so a corrupt
+//! Clarity MARF in the source does not matter.
+//!
+//! Reindexing is resumable: blocks already staged in the new chainstate are skipped, and any
+//! that were staged but not yet processed get processed on the next run.
+
+use std::path::PathBuf;
+
+use rusqlite::OpenFlags;
+
+use chainstate::burn::db::sortdb::SortitionDB;
+use chainstate::stacks::db::*;
+use chainstate::stacks::Error;
+use util::db::sqlite_open;
+use util::db::Error as db_error;
+
+/// Progress of a call to `StacksChainState::reindex_from`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReindexStats {
+    /// Number of accepted anchored blocks in the source chainstate
+    pub blocks_total: u64,
+    /// Number of source blocks considered so far
+    pub blocks_considered: u64,
+    /// Number of anchored blocks staged into the new chainstate
+    pub blocks_staged: u64,
+    /// Number of anchored blocks the source no longer has (e.g. because it was pruned)
+    pub blocks_missing: u64,
+    /// Number of microblocks staged into the new chainstate
+    pub microblocks_staged: u64,
+    /// Number of anchored blocks processed into the new chainstate
+    pub blocks_processed: u64,
+    /// Height of the highest block considered so far
+    pub height: u64,
+}
+
+impl StacksChainState {
+    /// Rebuild this chainstate from the anchored blocks and microblocks stored in the chainstate
+    /// at `source_path`.  This chainstate must have been booted with the same boot data as the
+    /// source, and `sortdb` must be the sortition DB the source was built against.  Blocks are
+    /// staged and then processed `batch_size` at a time, and `progress` is called after each
+    /// batch.
+    pub fn reindex_from<F>(
+        &mut self,
+        source_path: &str,
+        sortdb: &mut SortitionDB,
+        batch_size: usize,
+        mut progress: F,
+    ) -> Result<ReindexStats, Error>
+    where
+        F: FnMut(&ReindexStats),
+    {
+        let source_conn = sqlite_open(
+            StacksChainState::header_index_root_path(PathBuf::from(source_path)),
+            OpenFlags::SQLITE_OPEN_READ_ONLY,
+            false,
+        )
+        .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+        let source_blocks_path = StacksChainState::blocks_path(PathBuf::from(source_path))
+            .to_str()
+            .ok_or_else(|| Error::DBError(db_error::ParseError))?
+            .to_string();
+
+        let source_blocks: Vec<StagingBlock> =
+            StacksChainState::get_all_staging_block_headers(&source_conn)?
+                .into_iter()
+                .filter(|block| block.processed && !block.orphaned)
+                .collect();
+
+        let mut stats = ReindexStats {
+            blocks_total: source_blocks.len() as u64,
+            ..ReindexStats::default()
+        };
+
+        info!(
+            "Reindex {} blocks from {} into {}",
+            stats.blocks_total, source_path, &self.root_path
+        );
+
+        let mut batch_len = 0;
+        for source_block in source_blocks.iter() {
+            stats.blocks_considered += 1;
+            stats.height = source_block.height;
+
+            if StacksChainState::get_staging_block_status(
+                self.db(),
+                &source_block.consensus_hash,
+                &source_block.anchored_block_hash,
+            )?
+            .is_none()
+            {
+                let block = match StacksChainState::load_block(
+                    &source_blocks_path,
+                    &source_block.consensus_hash,
+                    &source_block.anchored_block_hash,
+                )? {
+                    Some(block) => block,
+                    None => {
+                        warn!(
+                            "Source chainstate no longer has block {}/{}; skipping it",
+                            &source_block.consensus_hash, &source_block.anchored_block_hash
+                        );
+                        stats.blocks_missing += 1;
+                        continue;
+                    }
+                };
+
+                // the microblocks this block confirms go in first
+                if block.header.parent_microblock != EMPTY_MICROBLOCK_PARENT_HASH {
+                    let microblocks = StacksChainState::load_processed_microblock_stream_fork(
+                        &source_conn,
+                        &source_block.parent_consensus_hash,
+                        &source_block.parent_anchored_block_hash,
+                        &block.header.parent_microblock,
+                    )?
+                    .unwrap_or(vec![]);
+                    for microblock in microblocks.iter() {
+                        if self.preprocess_streamed_microblock(
+                            &source_block.parent_consensus_hash,
+                            &source_block.parent_anchored_block_hash,
+                            microblock,
+                        )? {
+                            stats.microblocks_staged += 1;
+                        }
+                    }
+                }
+
+                let staged = {
+                    let ic = sortdb.index_conn();
+                    self.preprocess_anchored_block(
+                        &ic,
+                        &source_block.consensus_hash,
+                        &block,
+                        &source_block.parent_consensus_hash,
+                        0,
+                    )?
+                };
+                if staged {
+                    stats.blocks_staged += 1;
+                    batch_len += 1;
+                }
+            }
+
+            if batch_len >= batch_size {
+                stats.blocks_processed += self.reindex_process_staged(sortdb, batch_size)?;
+                batch_len = 0;
+                progress(&stats);
+            }
+        }
+
+        stats.blocks_processed += self.reindex_process_staged(sortdb, batch_size)?;
+        progress(&stats);
+
+        Ok(stats)
+    }
+
+    /// Process all attachable staging blocks, `batch_size` at a time.
+    /// Returns the number of blocks processed.
+    fn reindex_process_staged(
+        &mut self,
+        sortdb: &mut SortitionDB,
+        batch_size: usize,
+    ) -> Result<u64, Error> {
+        let mut num_processed = 0;
+        loop {
+            let processed = self.process_blocks_at_tip(sortdb, batch_size.max(1))?;
+            if processed.len() == 0 {
+                break;
+            }
+            num_processed += processed
+                .iter()
+                .filter(|(receipt_opt, _)| receipt_opt.is_some())
+                .count() as u64;
+        }
+        Ok(num_processed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use chainstate::burn::db::sortdb::*;
+    use chainstate::stacks::miner::test::*;
+    use chainstate::stacks::miner::*;
+    use chainstate::stacks::*;
+    use core::mempool::*;
+    use net::test::*;
+    use util::hash::*;
+
+    use super::*;
+
+    #[test]
+    fn test_reindex_linear_chain() {
+        let peer_config = TestPeerConfig::new("test_reindex_linear_chain", 21330, 21331);
+        let mut peer = TestPeer::new(peer_config);
+        let chainstate_path = peer.chainstate_path.clone();
+
+        let num_blocks = 10;
+        for tenure_id in 0..num_blocks {
+            let tip =
+                SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+                    .unwrap();
+
+            let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+                |ref mut miner,
+                 ref mut sortdb,
+                 ref mut chainstate,
+                 vrf_proof,
+                 ref parent_opt,
+                 ref parent_microblock_header_opt| {
+                    let parent_tip = match parent_opt {
+                        None => StacksChainState::get_genesis_header_info(chainstate.db()).unwrap(),
+                        Some(block) => {
+                            let ic = sortdb.index_conn();
+                            let snapshot =
+                                SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                                    &ic,
+                                    &tip.sortition_id,
+                                    &block.block_hash(),
+                                )
+                                .unwrap()
+                                .unwrap();
+                            StacksChainState::get_anchored_block_header_info(
+                                chainstate.db(),
+                                &snapshot.consensus_hash,
+                                &snapshot.winning_stacks_block_hash,
+                            )
+                            .unwrap()
+                            .unwrap()
+                        }
+                    };
+
+                    let mut mempool =
+                        MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+                    let coinbase_tx = make_coinbase(miner, tenure_id);
+
+                    let anchored_block = StacksBlockBuilder::build_anchored_block(
+                        chainstate,
+                        &sortdb.index_conn(),
+                        &mut mempool,
+                        &parent_tip,
+                        tip.total_burn,
+                        vrf_proof,
+                        Hash160([tenure_id as u8; 20]),
+                        &coinbase_tx,
+                        BlockBuilderSettings::max_value(),
+                        None,
+                    )
+                    .unwrap();
+                    (anchored_block.0, vec![])
+                },
+            );
+
+            peer.next_burnchain_block(burn_ops.clone());
+            peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+        }
+
+        let (tip_consensus_hash, tip_block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(peer.sortdb.as_ref().unwrap().conn())
+                .unwrap();
+        let tip_header = StacksChainState::get_anchored_block_header_info(
+            peer.chainstate().db(),
+            &tip_consensus_hash,
+            &tip_block_hash,
+        )
+        .unwrap()
+        .unwrap();
+
+        let reindex_path = format!("{}.reindex", &chainstate_path);
+        if fs::metadata(&reindex_path).is_ok() {
+            fs::remove_dir_all(&reindex_path).unwrap();
+        }
+        let mut boot_data = ChainStateBootData::new(
+            &peer.config.burnchain,
+            peer.config.initial_balances.clone(),
+            None,
+        );
+        let (mut chainstate, _) = StacksChainState::open_and_exec(
+            false,
+            peer.config.network_id,
+            &reindex_path,
+            Some(&mut boot_data),
+        )
+        .unwrap();
+
+        let mut num_progress_calls = 0;
+        let stats = chainstate
+            .reindex_from(&chainstate_path, peer.sortdb.as_mut().unwrap(), 3, |_| {
+                num_progress_calls += 1
+            })
+            .unwrap();
+        assert_eq!(stats.blocks_total, 10);
+        assert_eq!(stats.blocks_staged, 10);
+        assert_eq!(stats.blocks_processed, 10);
+        assert_eq!(stats.blocks_missing, 0);
+        assert_eq!(num_progress_calls, 4);
+
+        // same chain tip, same state
+        let reindexed_header = StacksChainState::get_anchored_block_header_info(
+            chainstate.db(),
+            &tip_consensus_hash,
+            &tip_block_hash,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(reindexed_header, tip_header);
+
+        // nothing left to do on a second run
+        let stats = chainstate
+            .reindex_from(&chainstate_path, peer.sortdb.as_mut().unwrap(), 3, |_| {})
+            .unwrap();
+        assert_eq!(stats.blocks_total, 10);
+        assert_eq!(stats.blocks_staged, 0);
+        assert_eq!(stats.blocks_processed, 0);
+    }
+}
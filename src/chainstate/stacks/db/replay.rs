@@ -0,0 +1,378 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Re-executing already-processed blocks, for benchmarking.
+//!
+//! `StacksChainState::replay_block` runs a canonical block's confirmed microblock transactions
+//! and anchored transactions against its parent's Clarity state, measures how long that took,
+//! what it cost, and how many MARF nodes it touched, and then rolls the state back.  Nothing is
+//! written to the chainstate, so the same range of blocks can be replayed again and again, and
+//! the reports from two builds of the node can be compared block-for-block.
+//!
+//! Only transaction execution is measured.  Miner reward maturation, burnchain STX operations,
+//! and epoch transitions are not re-run.
+
+use std::time::Instant;
+
+use chainstate::stacks::db::*;
+use chainstate::stacks::index::storage::TrieIOStats;
+use chainstate::stacks::Error;
+use vm::database::BurnStateDB;
+
+/// What it took to re-execute one block
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockReplayStats {
+    pub block_height: u64,
+    pub consensus_hash: ConsensusHash,
+    pub anchored_block_hash: BlockHeaderHash,
+    /// Number of transactions in the anchored block
+    pub num_txs: u64,
+    /// Number of transactions in the microblocks the block confirms
+    pub num_microblock_txs: u64,
+    /// Wall-clock time spent executing the microblock and anchored transactions
+    pub wall_clock_us: u128,
+    /// Cost of the confirmed microblock transactions
+    pub microblock_cost: ExecutionCost,
+    /// Cost of the anchored block's transactions
+    pub block_cost: ExecutionCost,
+    /// Clarity MARF trie nodes read and written while executing the block
+    pub marf_io: TrieIOStats,
+}
+
+impl BlockReplayStats {
+    pub fn index_block_hash(&self) -> StacksBlockId {
+        StacksBlockHeader::make_index_block_hash(&self.consensus_hash, &self.anchored_block_hash)
+    }
+}
+
+impl StacksChainState {
+    /// Load the body of the processed block `header`, its parent's header, and the microblocks it
+    /// confirms.  Returns None if the block's body is no longer stored (e.g. because it was
+    /// pruned).
+    pub fn load_block_for_replay(
+        &self,
+        header: &StacksHeaderInfo,
+    ) -> Result<Option<(StacksBlock, StacksHeaderInfo, Vec<StacksMicroblock>)>, Error> {
+        let block = match StacksChainState::load_block(
+            &self.blocks_path,
+            &header.consensus_hash,
+            &header.anchored_header.block_hash(),
+        )? {
+            Some(block) => block,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        let parent_index_hash =
+            StacksChainState::get_parent_block_id(self.db(), &header.index_block_hash())?
+                .ok_or(Error::NoSuchBlockError)?;
+        let parent_header = StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            self.db(),
+            &parent_index_hash,
+        )?
+        .ok_or(Error::NoSuchBlockError)?;
+
+        let microblocks = if block.header.parent_microblock != EMPTY_MICROBLOCK_PARENT_HASH {
+            StacksChainState::load_processed_microblock_stream_fork(
+                self.db(),
+                &parent_header.consensus_hash,
+                &parent_header.anchored_header.block_hash(),
+                &block.header.parent_microblock,
+            )?
+            .ok_or(Error::NoSuchBlockError)?
+        } else {
+            vec![]
+        };
+
+        Ok(Some((block, parent_header, microblocks)))
+    }
+
+    /// Run the transactions of `microblocks` and then those of `block` (which was elected with
+    /// `consensus_hash`) in `clarity_tx`.  Returns the cost of each, and all of their receipts.
+    /// The caller must roll `clarity_tx` back, whether or not this succeeds.
+    pub fn replay_block_transactions(
+        clarity_tx: &mut ClarityTx,
+        consensus_hash: &ConsensusHash,
+        block: &StacksBlock,
+        microblocks: &Vec<StacksMicroblock>,
+    ) -> Result<(ExecutionCost, ExecutionCost, Vec<StacksTransactionReceipt>), Error> {
+        clarity_tx.reset_cost(ExecutionCost::zero());
+
+        let (_, _, mut receipts) =
+            StacksChainState::process_microblocks_transactions(clarity_tx, microblocks).map_err(
+                |(e, offender)| {
+                    Error::InvalidStacksMicroblock(
+                        format!(
+                            "Replayed microblock {} of {}/{} failed: {:?}",
+                            &offender,
+                            consensus_hash,
+                            &block.block_hash(),
+                            &e
+                        ),
+                        offender,
+                    )
+                },
+            )?;
+
+        let microblock_cost = clarity_tx.cost_so_far();
+        clarity_tx.reset_cost(ExecutionCost::zero());
+
+        for tx in block.txs.iter() {
+            let (_, receipt) = tx
+                .verify()
+                .map_err(Error::NetError)
+                .and_then(|_| {
+                    StacksChainState::process_transaction_auth_verified(clarity_tx, tx, true)
+                })
+                .map_err(|e| {
+                    Error::InvalidStacksBlock(format!(
+                        "Replayed transaction {} of {}/{} failed: {:?}",
+                        &tx.txid(),
+                        consensus_hash,
+                        &block.block_hash(),
+                        &e
+                    ))
+                })?;
+            receipts.push(receipt);
+        }
+
+        let block_cost = clarity_tx.cost_so_far();
+        Ok((microblock_cost, block_cost, receipts))
+    }
+
+    /// Re-execute the transactions of the processed block `header` (and of the microblocks it
+    /// confirms) on top of its parent's state, and roll the result back.
+    /// Returns None if the block's body is no longer stored.
+    pub fn replay_block(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        header: &StacksHeaderInfo,
+    ) -> Result<Option<BlockReplayStats>, Error> {
+        let (block, parent_header, microblocks) = match self.load_block_for_replay(header)? {
+            Some(inputs) => inputs,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        let io_before = self.clarity_state.with_marf(|marf| marf.io_stats());
+        let start = Instant::now();
+
+        let (microblock_cost, block_cost, _) = {
+            let mut clarity_tx = self.block_begin(
+                burn_dbconn,
+                &parent_header.consensus_hash,
+                &parent_header.anchored_header.block_hash(),
+                &MINER_BLOCK_CONSENSUS_HASH,
+                &MINER_BLOCK_HEADER_HASH,
+            );
+            let result = StacksChainState::replay_block_transactions(
+                &mut clarity_tx,
+                &header.consensus_hash,
+                &block,
+                &microblocks,
+            );
+            clarity_tx.rollback_block();
+            result?
+        };
+
+        let wall_clock_us = start.elapsed().as_micros();
+        let marf_io = self
+            .clarity_state
+            .with_marf(|marf| marf.io_stats())
+            .since(&io_before);
+
+        Ok(Some(BlockReplayStats {
+            block_height: header.block_height,
+            consensus_hash: header.consensus_hash.clone(),
+            anchored_block_hash: block.block_hash(),
+            num_txs: block.txs.len() as u64,
+            num_microblock_txs: microblocks
+                .iter()
+                .map(|mblock| mblock.txs.len() as u64)
+                .sum(),
+            wall_clock_us,
+            microblock_cost,
+            block_cost,
+            marf_io,
+        }))
+    }
+
+    /// Replay every block on the canonical Stacks fork with a height in
+    /// `[start_height, end_height]`, in height order.  Blocks whose bodies are no longer stored
+    /// are skipped.
+    pub fn replay_range(
+        &mut self,
+        sortdb: &SortitionDB,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<BlockReplayStats>, Error> {
+        let (tip_consensus_hash, tip_block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())?;
+        let tip = StacksChainState::get_anchored_block_header_info(
+            self.db(),
+            &tip_consensus_hash,
+            &tip_block_hash,
+        )?
+        .ok_or(Error::NoSuchBlockError)?;
+
+        // the boot block has no transactions to replay
+        let mut headers: Vec<StacksHeaderInfo> =
+            StacksChainState::get_ancestors_headers(self.db(), tip, start_height.max(1))?
+                .into_iter()
+                .filter(|header| header.block_height <= end_height)
+                .collect();
+        headers.reverse();
+
+        let burn_dbconn = sortdb.index_conn();
+        let mut replayed = vec![];
+        for header in headers.iter() {
+            match self.replay_block(&burn_dbconn, header)? {
+                Some(stats) => {
+                    debug!(
+                        "Replayed block {}/{} (height {}) in {}us",
+                        &stats.consensus_hash,
+                        &stats.anchored_block_hash,
+                        stats.block_height,
+                        stats.wall_clock_us
+                    );
+                    replayed.push(stats);
+                }
+                None => {
+                    warn!(
+                        "Block {}/{} is no longer stored; not replaying it",
+                        &header.consensus_hash,
+                        &header.anchored_header.block_hash()
+                    );
+                }
+            }
+        }
+        Ok(replayed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chainstate::burn::db::sortdb::*;
+    use chainstate::stacks::miner::test::*;
+    use chainstate::stacks::miner::*;
+    use chainstate::stacks::*;
+    use core::mempool::*;
+    use net::test::*;
+    use util::hash::*;
+
+    use super::*;
+
+    #[test]
+    fn test_replay_range_linear_chain() {
+        let peer_config = TestPeerConfig::new("test_replay_range_linear_chain", 21340, 21341);
+        let mut peer = TestPeer::new(peer_config);
+        let chainstate_path = peer.chainstate_path.clone();
+
+        let num_blocks = 5;
+        for tenure_id in 0..num_blocks {
+            let tip =
+                SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+                    .unwrap();
+
+            let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+                |ref mut miner,
+                 ref mut sortdb,
+                 ref mut chainstate,
+                 vrf_proof,
+                 ref parent_opt,
+                 ref parent_microblock_header_opt| {
+                    let parent_tip = match parent_opt {
+                        None => StacksChainState::get_genesis_header_info(chainstate.db()).unwrap(),
+                        Some(block) => {
+                            let ic = sortdb.index_conn();
+                            let snapshot =
+                                SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                                    &ic,
+                                    &tip.sortition_id,
+                                    &block.block_hash(),
+                                )
+                                .unwrap()
+                                .unwrap();
+                            StacksChainState::get_anchored_block_header_info(
+                                chainstate.db(),
+                                &snapshot.consensus_hash,
+                                &snapshot.winning_stacks_block_hash,
+                            )
+                            .unwrap()
+                            .unwrap()
+                        }
+                    };
+
+                    let mut mempool =
+                        MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+                    let coinbase_tx = make_coinbase(miner, tenure_id);
+
+                    let anchored_block = StacksBlockBuilder::build_anchored_block(
+                        chainstate,
+                        &sortdb.index_conn(),
+                        &mut mempool,
+                        &parent_tip,
+                        tip.total_burn,
+                        vrf_proof,
+                        Hash160([tenure_id as u8; 20]),
+                        &coinbase_tx,
+                        BlockBuilderSettings::max_value(),
+                        None,
+                    )
+                    .unwrap();
+                    (anchored_block.0, vec![])
+                },
+            );
+
+            peer.next_burnchain_block(burn_ops.clone());
+            peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+        }
+
+        let (tip_consensus_hash, tip_block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(peer.sortdb.as_ref().unwrap().conn())
+                .unwrap();
+
+        let sortdb = peer.sortdb.take().unwrap();
+        let replayed = peer.chainstate().replay_range(&sortdb, 2, 4).unwrap();
+        assert_eq!(
+            replayed
+                .iter()
+                .map(|stats| stats.block_height)
+                .collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        for stats in replayed.iter() {
+            assert_eq!(stats.num_txs, 1);
+            assert_eq!(stats.num_microblock_txs, 0);
+            assert!(stats.marf_io.reads > 0);
+        }
+
+        // replaying is repeatable, and doesn't change the chain tip
+        let again = peer.chainstate().replay_range(&sortdb, 0, 100).unwrap();
+        assert_eq!(again.len(), num_blocks);
+        for (first, second) in replayed.iter().zip(again[1..].iter()) {
+            assert_eq!(first.index_block_hash(), second.index_block_hash());
+            assert_eq!(first.block_cost, second.block_cost);
+        }
+        assert_eq!(
+            SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn()).unwrap(),
+            (tip_consensus_hash, tip_block_hash)
+        );
+        peer.sortdb = Some(sortdb);
+    }
+}
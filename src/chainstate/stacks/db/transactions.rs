@@ -21,6 +21,7 @@ use std::io;
 use std::io::prelude::*;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use chainstate::burn::db::sortdb::*;
 use chainstate::stacks::db::*;
@@ -30,6 +31,7 @@ use clarity_vm::clarity::{
     ClarityBlockConnection, ClarityConnection, ClarityInstance, ClarityTransactionConnection,
     Error as clarity_error,
 };
+use core::StacksEpochId;
 use net::Error as net_error;
 use util::db::Error as db_error;
 use util::db::{query_count, query_rows, DBConn};
@@ -46,6 +48,7 @@ use vm::costs::cost_functions::ClarityCostFunction;
 use vm::costs::runtime_cost;
 use vm::costs::CostTracker;
 use vm::costs::ExecutionCost;
+use vm::costs::LimitedCostTracker;
 use vm::database::ClarityDatabase;
 use vm::errors::Error as InterpreterError;
 use vm::representations::ClarityName;
@@ -55,7 +58,19 @@ use vm::types::{
     StandardPrincipalData, TupleData, TypeSignature, Value,
 };
 
-use crate::types::chainstate::StacksMicroblockHeader;
+use vm::database::BurnStateDB;
+
+use crate::types::chainstate::{StacksBlockId, StacksMicroblockHeader};
+
+/// The fungible condition that pins down `amount` sent.  Post-conditions hold 64-bit amounts,
+/// so larger amounts can only be bounded from below.
+fn covering_amount(amount: u128) -> (FungibleConditionCode, u64) {
+    if amount > u64::MAX as u128 {
+        (FungibleConditionCode::SentGt, u64::MAX)
+    } else {
+        (FungibleConditionCode::SentEq, amount as u64)
+    }
+}
 
 // make it possible to have a set of Values
 impl std::hash::Hash for Value {
@@ -67,7 +82,67 @@ impl std::hash::Hash for Value {
     }
 }
 
+/// The budget a simulated transaction runs under
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionSimulationLimits {
+    /// cost budget of the transaction itself.  Replayed microblocks are charged to the block.
+    pub cost: ExecutionCost,
+    /// wall-clock budget, in milliseconds (0 = unlimited)
+    pub time_limit_ms: u64,
+    /// largest transaction that may be simulated, in bytes (0 = unlimited)
+    pub max_tx_len: u64,
+}
+
+/// The outcome of simulating a transaction with its post-conditions lifted
+#[derive(Debug, Clone)]
+pub struct PostConditionSimulation {
+    /// The receipt of the transaction, executed in Allow mode with no post-conditions
+    pub receipt: StacksTransactionReceipt,
+    /// The smallest set of post-conditions that covers every asset the transaction moved
+    pub post_conditions: Vec<TransactionPostCondition>,
+    /// Whether or not the transaction's own post-conditions would pass.  Err describes the
+    /// first failure.
+    pub post_condition_check: Result<(), String>,
+}
+
 impl StacksTransactionReceipt {
+    /// Reconstruct the assets this transaction moved out of each account from its events.  This
+    /// is what the transaction's post-conditions are checked against.
+    pub fn get_asset_map(&self) -> Result<AssetMap, Error> {
+        let mut asset_map = AssetMap::new();
+        for event in self.events.iter() {
+            match event {
+                StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => asset_map
+                    .add_stx_transfer(&data.sender, data.amount)
+                    .map_err(clarity_error::from)?,
+                StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(data)) => asset_map
+                    .add_stx_burn(&data.sender, data.amount)
+                    .map_err(clarity_error::from)?,
+                StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => asset_map
+                    .add_token_transfer(&data.sender, data.asset_identifier.clone(), data.amount)
+                    .map_err(clarity_error::from)?,
+                StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(data)) => asset_map
+                    .add_token_transfer(&data.sender, data.asset_identifier.clone(), data.amount)
+                    .map_err(clarity_error::from)?,
+                StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(data)) => asset_map
+                    .add_asset_transfer(
+                        &data.sender,
+                        data.asset_identifier.clone(),
+                        data.value.clone(),
+                    ),
+                StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(data)) => asset_map
+                    .add_asset_transfer(
+                        &data.sender,
+                        data.asset_identifier.clone(),
+                        data.value.clone(),
+                    ),
+                // mints, locks and print events do not move assets out of an account
+                _ => {}
+            }
+        }
+        Ok(asset_map)
+    }
+
     pub fn from_stx_transfer(
         tx: StacksTransaction,
         events: Vec<StacksTransactionEvent>,
@@ -83,6 +158,7 @@ impl StacksTransactionReceipt {
             transaction: tx.into(),
             execution_cost: cost,
             microblock_header: None,
+            cost_breakdown: None,
         }
     }
 
@@ -102,6 +178,7 @@ impl StacksTransactionReceipt {
             contract_analysis: None,
             execution_cost: cost,
             microblock_header: None,
+            cost_breakdown: None,
         }
     }
 
@@ -121,6 +198,7 @@ impl StacksTransactionReceipt {
             contract_analysis: None,
             execution_cost: cost,
             microblock_header: None,
+            cost_breakdown: None,
         }
     }
 
@@ -140,6 +218,7 @@ impl StacksTransactionReceipt {
             contract_analysis: Some(analysis),
             execution_cost: cost,
             microblock_header: None,
+            cost_breakdown: None,
         }
     }
 
@@ -159,6 +238,7 @@ impl StacksTransactionReceipt {
             contract_analysis: Some(analysis),
             execution_cost: cost,
             microblock_header: None,
+            cost_breakdown: None,
         }
     }
 
@@ -172,6 +252,7 @@ impl StacksTransactionReceipt {
             contract_analysis: None,
             execution_cost: ExecutionCost::zero(),
             microblock_header: None,
+            cost_breakdown: None,
         }
     }
 
@@ -188,6 +269,7 @@ impl StacksTransactionReceipt {
             contract_analysis: None,
             execution_cost: analysis_cost,
             microblock_header: None,
+            cost_breakdown: None,
         }
     }
 
@@ -205,6 +287,7 @@ impl StacksTransactionReceipt {
             contract_analysis: None,
             execution_cost: cost,
             microblock_header: None,
+            cost_breakdown: None,
         }
     }
 
@@ -216,6 +299,59 @@ impl StacksTransactionReceipt {
         }
         false
     }
+
+    /// Get the set of principals this transaction affected: its origin and sponsor (if it is a
+    /// Stacks transaction), and every principal that sent or received STX or tokens, or had
+    /// STX locked.
+    pub fn get_affected_principals(&self) -> HashSet<PrincipalData> {
+        let mut principals = HashSet::new();
+        if let TransactionOrigin::Stacks(ref transaction) = self.transaction {
+            principals.insert(transaction.origin_address().to_account_principal());
+            if let Some(sponsor) = transaction.sponsor_address() {
+                principals.insert(sponsor.to_account_principal());
+            }
+        }
+
+        for event in self.events.iter() {
+            match event {
+                StacksTransactionEvent::SmartContractEvent(_) => {}
+                StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(ref data)) => {
+                    principals.insert(data.sender.clone());
+                    principals.insert(data.recipient.clone());
+                }
+                StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(ref data)) => {
+                    principals.insert(data.recipient.clone());
+                }
+                StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(ref data)) => {
+                    principals.insert(data.sender.clone());
+                }
+                StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(ref data)) => {
+                    principals.insert(data.locked_address.clone());
+                }
+                StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(ref data)) => {
+                    principals.insert(data.sender.clone());
+                    principals.insert(data.recipient.clone());
+                }
+                StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(ref data)) => {
+                    principals.insert(data.recipient.clone());
+                }
+                StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(ref data)) => {
+                    principals.insert(data.sender.clone());
+                }
+                StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(ref data)) => {
+                    principals.insert(data.sender.clone());
+                    principals.insert(data.recipient.clone());
+                }
+                StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(ref data)) => {
+                    principals.insert(data.recipient.clone());
+                }
+                StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(ref data)) => {
+                    principals.insert(data.sender.clone());
+                }
+            }
+        }
+        principals
+    }
 }
 
 #[derive(Debug)]
@@ -396,6 +532,34 @@ impl StacksChainState {
         // valid auth?
         tx.verify().map_err(Error::NetError)?;
 
+        StacksChainState::check_transaction_network(config, tx)
+    }
+
+    /// Check that a transaction only uses features that are enabled in `epoch`
+    pub fn check_transaction_epoch(
+        epoch: StacksEpochId,
+        tx: &StacksTransaction,
+    ) -> Result<(), Error> {
+        if tx.auth.uses_order_independent_multisig() && !epoch.supports_order_independent_multisig()
+        {
+            let msg = format!(
+                "Invalid tx {}: order-independent multisig is not supported in epoch {}",
+                tx.txid(),
+                epoch
+            );
+            warn!("{}", &msg);
+
+            return Err(Error::InvalidStacksTransaction(msg, false));
+        }
+        Ok(())
+    }
+
+    /// Check that a transaction is destined for this chain and network (chain ID and version),
+    /// without checking its signatures.
+    pub fn check_transaction_network(
+        config: &DBConfig,
+        tx: &StacksTransaction,
+    ) -> Result<(), Error> {
         // destined for us?
         if config.chain_id != tx.chain_id {
             let msg = format!(
@@ -440,6 +604,30 @@ impl StacksChainState {
         origin_account: &StacksAccount,
         asset_map: &AssetMap,
     ) -> bool {
+        match StacksChainState::explain_transaction_postconditions(
+            post_conditions,
+            post_condition_mode,
+            &origin_account.principal,
+            asset_map,
+        ) {
+            Ok(()) => true,
+            Err(msg) => {
+                info!("{}", &msg);
+                false
+            }
+        }
+    }
+
+    /// Apply a post-conditions check to the assets moved in `asset_map`, where `origin` is the
+    /// transaction's origin account.
+    /// Return Err with a description of the first post-condition that fails, or of the first
+    /// asset movement that is not covered in Deny mode.
+    pub fn explain_transaction_postconditions(
+        post_conditions: &Vec<TransactionPostCondition>,
+        post_condition_mode: &TransactionPostConditionMode,
+        origin: &PrincipalData,
+        asset_map: &AssetMap,
+    ) -> Result<(), String> {
         let mut checked_fungible_assets: HashMap<PrincipalData, HashSet<AssetIdentifier>> =
             HashMap::new();
         let mut checked_nonfungible_assets: HashMap<
@@ -455,7 +643,7 @@ impl StacksChainState {
                     ref condition_code,
                     ref amount_sent_condition,
                 ) => {
-                    let account_principal = principal.to_principal_data(&origin_account.principal);
+                    let account_principal = principal.to_principal_data(origin);
 
                     let amount_transferred = asset_map.get_stx(&account_principal).unwrap_or(0);
                     let amount_burned = asset_map.get_stx_burned(&account_principal).unwrap_or(0);
@@ -465,11 +653,10 @@ impl StacksChainState {
                         .expect("FATAL: sent waaaaay too much STX");
 
                     if !condition_code.check(*amount_sent_condition as u128, amount_sent) {
-                        info!(
+                        return Err(format!(
                             "Post-condition check failure on STX owned by {}: {:?} {:?} {}",
                             account_principal, amount_sent_condition, condition_code, amount_sent
-                        );
-                        return false;
+                        ));
                     }
 
                     if let Some(ref mut asset_ids) =
@@ -498,7 +685,7 @@ impl StacksChainState {
                     ref condition_code,
                     ref amount_sent_condition,
                 ) => {
-                    let account_principal = principal.to_principal_data(&origin_account.principal);
+                    let account_principal = principal.to_principal_data(origin);
                     let asset_id = AssetIdentifier {
                         contract_identifier: QualifiedContractIdentifier::new(
                             StandardPrincipalData::from(asset_info.contract_address.clone()),
@@ -511,8 +698,7 @@ impl StacksChainState {
                         .get_fungible_tokens(&account_principal, &asset_id)
                         .unwrap_or(0);
                     if !condition_code.check(*amount_sent_condition as u128, amount_sent) {
-                        info!("Post-condition check failure on fungible asset {} owned by {}: {} {:?} {}", &asset_id, account_principal, amount_sent_condition, condition_code, amount_sent);
-                        return false;
+                        return Err(format!("Post-condition check failure on fungible asset {} owned by {}: {} {:?} {}", &asset_id, account_principal, amount_sent_condition, condition_code, amount_sent));
                     }
 
                     if let Some(ref mut asset_ids) =
@@ -531,7 +717,7 @@ impl StacksChainState {
                     ref asset_value,
                     ref condition_code,
                 ) => {
-                    let account_principal = principal.to_principal_data(&origin_account.principal);
+                    let account_principal = principal.to_principal_data(origin);
                     let asset_id = AssetIdentifier {
                         contract_identifier: QualifiedContractIdentifier::new(
                             StandardPrincipalData::from(asset_info.contract_address.clone()),
@@ -545,8 +731,7 @@ impl StacksChainState {
                         .get_nonfungible_tokens(&account_principal, &asset_id)
                         .unwrap_or(&empty_assets);
                     if !condition_code.check(asset_value, assets_sent) {
-                        info!("Post-condition check failure on non-fungible asset {} owned by {}: {:?} {:?}", &asset_id, account_principal, &asset_value, condition_code);
-                        return false;
+                        return Err(format!("Post-condition check failure on non-fungible asset {} owned by {}: {:?} {:?}", &asset_id, account_principal, &asset_value, condition_code));
                     }
 
                     if let Some(ref mut asset_id_map) =
@@ -587,19 +772,16 @@ impl StacksChainState {
                                     // each value must be covered
                                     for v in values {
                                         if !nfts.contains(&v) {
-                                            info!("Post-condition check failure: Non-fungible asset {} value {:?} was moved by {} but not checked", &asset_identifier, &v, &principal);
-                                            return false;
+                                            return Err(format!("Post-condition check failure: Non-fungible asset {} value {:?} was moved by {} but not checked", &asset_identifier, &v, &principal));
                                         }
                                     }
                                 } else {
                                     // no values covered
-                                    info!("Post-condition check failure: No checks for non-fungible asset type {} moved by {}", &asset_identifier, &principal);
-                                    return false;
+                                    return Err(format!("Post-condition check failure: No checks for non-fungible asset type {} moved by {}", &asset_identifier, &principal));
                                 }
                             } else {
                                 // no NFT for this principal
-                                info!("Post-condition check failure: No checks for any non-fungible assets, but moved {} by {}", &asset_identifier, &principal);
-                                return false;
+                                return Err(format!("Post-condition check failure: No checks for any non-fungible assets, but moved {} by {}", &asset_identifier, &principal));
                             }
                         }
                         _ => {
@@ -608,19 +790,148 @@ impl StacksChainState {
                                 checked_fungible_assets.get(&principal)
                             {
                                 if !checked_ft_asset_ids.contains(&asset_identifier) {
-                                    info!("Post-condition check failure: checks did not cover transfer of {} by {}", &asset_identifier, &principal);
-                                    return false;
+                                    return Err(format!("Post-condition check failure: checks did not cover transfer of {} by {}", &asset_identifier, &principal));
                                 }
                             } else {
-                                info!("Post-condition check failure: No checks for fungible token type {} moved by {}", &asset_identifier, &principal);
-                                return false;
+                                return Err(format!("Post-condition check failure: No checks for fungible token type {} moved by {}", &asset_identifier, &principal));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Make the smallest set of post-conditions that covers every asset movement in `asset_map`,
+    /// such that the transaction that made them would pass in Deny mode.  Each account gets an
+    /// exact bound on the STX it sent or burned and on each fungible token it sent, and a
+    /// `Sent` condition on each non-fungible token it sent.  Movements out of `origin` are
+    /// attributed to `PostConditionPrincipal::Origin`.  The conditions are sorted by principal
+    /// and then by asset, so the same movements always produce the same list.
+    pub fn make_covering_postconditions(
+        origin: &PrincipalData,
+        asset_map: &AssetMap,
+    ) -> Vec<TransactionPostCondition> {
+        let mut table: Vec<_> = asset_map
+            .clone()
+            .to_table()
+            .into_iter()
+            .map(|(principal, assets)| {
+                let mut assets: Vec<_> = assets.into_iter().collect();
+                assets.sort_by_key(|(asset_id, _)| asset_id.to_string());
+                (principal, assets)
+            })
+            .collect();
+        table.sort_by_key(|(principal, _)| (principal != origin, principal.to_string()));
+
+        let mut post_conditions = vec![];
+        for (principal, assets) in table.into_iter() {
+            let pc_principal = if principal == *origin {
+                PostConditionPrincipal::Origin
+            } else {
+                match principal {
+                    PrincipalData::Standard(addr) => PostConditionPrincipal::Standard(addr.into()),
+                    PrincipalData::Contract(contract_id) => PostConditionPrincipal::Contract(
+                        contract_id.issuer.into(),
+                        contract_id.name,
+                    ),
+                }
+            };
+
+            // STX transfers and burns count against the same post-condition
+            let stx_sent = assets
+                .iter()
+                .filter_map(|(_, entry)| match entry {
+                    AssetMapEntry::STX(amount) | AssetMapEntry::Burn(amount) => Some(*amount),
+                    _ => None,
+                })
+                .fold(0u128, |total, amount| total.saturating_add(amount));
+            if stx_sent > 0 {
+                let (condition_code, amount) = covering_amount(stx_sent);
+                post_conditions.push(TransactionPostCondition::STX(
+                    pc_principal.clone(),
+                    condition_code,
+                    amount,
+                ));
+            }
+
+            for (asset_id, entry) in assets.into_iter() {
+                let asset_info = AssetInfo {
+                    contract_address: asset_id.contract_identifier.issuer.into(),
+                    contract_name: asset_id.contract_identifier.name,
+                    asset_name: asset_id.asset_name,
+                };
+                match entry {
+                    AssetMapEntry::Token(amount) => {
+                        let (condition_code, amount) = covering_amount(amount);
+                        post_conditions.push(TransactionPostCondition::Fungible(
+                            pc_principal.clone(),
+                            asset_info,
+                            condition_code,
+                            amount,
+                        ));
+                    }
+                    AssetMapEntry::Asset(values) => {
+                        let mut seen = HashSet::new();
+                        for value in values.into_iter() {
+                            if seen.insert(value.clone()) {
+                                post_conditions.push(TransactionPostCondition::Nonfungible(
+                                    pc_principal.clone(),
+                                    asset_info.clone(),
+                                    value,
+                                    NonfungibleConditionCode::Sent,
+                                ));
                             }
                         }
                     }
+                    AssetMapEntry::STX(_) | AssetMapEntry::Burn(_) => {}
                 }
             }
         }
-        return true;
+        post_conditions
+    }
+
+    /// Simulate `tx` (see `simulate_transaction`) with its post-conditions lifted, so that every
+    /// asset it would move is observed, and then work out the post-conditions that cover those
+    /// movements and whether or not `tx`'s own post-conditions would pass.  Post-conditions are
+    /// only enforced on contract-call and smart-contract transactions, so the check always
+    /// passes for other payloads.
+    pub fn simulate_transaction_postconditions(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        tip: &StacksBlockId,
+        microblocks: &[StacksMicroblock],
+        tx: &StacksTransaction,
+        limits: &TransactionSimulationLimits,
+    ) -> Result<PostConditionSimulation, Error> {
+        let mut unchecked_tx = tx.clone();
+        unchecked_tx.post_condition_mode = TransactionPostConditionMode::Allow;
+        unchecked_tx.post_conditions = vec![];
+
+        let receipt =
+            self.simulate_transaction(burn_dbconn, tip, microblocks, &unchecked_tx, limits)?;
+        let asset_map = receipt.get_asset_map()?;
+        let origin = PrincipalData::from(tx.origin_address());
+
+        let post_conditions = StacksChainState::make_covering_postconditions(&origin, &asset_map);
+        let post_condition_check = match tx.payload {
+            TransactionPayload::ContractCall(_) | TransactionPayload::SmartContract(_) => {
+                StacksChainState::explain_transaction_postconditions(
+                    &tx.post_conditions,
+                    &tx.post_condition_mode,
+                    &origin,
+                    &asset_map,
+                )
+            }
+            _ => Ok(()),
+        };
+
+        Ok(PostConditionSimulation {
+            receipt,
+            post_conditions,
+            post_condition_check,
+        })
     }
 
     /// Given two microblock headers, were they signed by the same key?
@@ -1131,13 +1442,44 @@ impl StacksChainState {
         debug!("Process transaction {} ({})", tx.txid(), tx.payload.name());
 
         StacksChainState::process_transaction_precheck(&clarity_block.config, tx)?;
+        StacksChainState::apply_transaction(clarity_block, tx, quiet)
+    }
+
+    /// Process a transaction whose authorization the caller has already verified (i.e. with
+    /// tx.verify()).  Return the fee and the transaction receipt
+    pub fn process_transaction_auth_verified(
+        clarity_block: &mut ClarityTx,
+        tx: &StacksTransaction,
+        quiet: bool,
+    ) -> Result<(u64, StacksTransactionReceipt), Error> {
+        debug!(
+            "Process transaction {} ({}), auth already verified",
+            tx.txid(),
+            tx.payload.name()
+        );
+
+        StacksChainState::check_transaction_network(&clarity_block.config, tx)?;
+        StacksChainState::apply_transaction(clarity_block, tx, quiet)
+    }
+
+    /// Apply a transaction's payload, fee, and nonce updates to the given block.
+    /// The caller must have already validated the transaction.
+    fn apply_transaction(
+        clarity_block: &mut ClarityTx,
+        tx: &StacksTransaction,
+        quiet: bool,
+    ) -> Result<(u64, StacksTransactionReceipt), Error> {
+        StacksChainState::check_transaction_epoch(clarity_block.get_epoch(), tx)?;
 
         let mut transaction = clarity_block.connection().start_transaction_processing();
         let (origin_account, payer_account) =
             StacksChainState::check_transaction_nonces(&mut transaction, tx, quiet)?;
 
-        let tx_receipt =
+        // only attribute this transaction's cost to it
+        transaction.take_cost_breakdown();
+        let mut tx_receipt =
             StacksChainState::process_transaction_payload(&mut transaction, tx, &origin_account)?;
+        tx_receipt.cost_breakdown = transaction.take_cost_breakdown();
 
         let new_payer_account = StacksChainState::get_payer_account(&mut transaction, tx);
         let fee = tx.get_tx_fee();
@@ -1161,6 +1503,100 @@ impl StacksChainState {
 
         Ok((fee, tx_receipt))
     }
+
+    /// Execute a transaction against the chain state at `tip` and return its receipt, without
+    /// committing anything.  The transactions in `microblocks` (a stream that descends from `tip`)
+    /// are applied first, so the transaction can be evaluated against unconfirmed state.
+    /// Signatures are not checked, so unsigned transactions can be simulated as well.
+    ///
+    /// The simulation runs on a detached view of the Clarity state (see
+    /// `ClarityInstance::set_detached`), so like a read-only call, it only reads a snapshot of
+    /// the chainstate and never holds its write lock.  The transaction runs under `limits`
+    /// instead of the block budget.
+    pub fn simulate_transaction(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        tip: &StacksBlockId,
+        microblocks: &[StacksMicroblock],
+        tx: &StacksTransaction,
+        limits: &TransactionSimulationLimits,
+    ) -> Result<StacksTransactionReceipt, Error> {
+        if limits.max_tx_len > 0 && (tx.serialize_to_vec().len() as u64) > limits.max_tx_len {
+            return Err(Error::TransactionTooBigError);
+        }
+        let tip_header =
+            StacksChainState::get_stacks_block_header_info_by_index_block_hash(self.db(), tip)?
+                .ok_or(Error::NoSuchBlockError)?;
+
+        let was_detached = self.clarity_state.is_detached();
+        self.clarity_state.set_detached(true);
+        let result =
+            self.inner_simulate_transaction(burn_dbconn, &tip_header, microblocks, tx, limits);
+        self.clarity_state.set_detached(was_detached);
+        result
+    }
+
+    fn inner_simulate_transaction(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        tip_header: &StacksHeaderInfo,
+        microblocks: &[StacksMicroblock],
+        tx: &StacksTransaction,
+        limits: &TransactionSimulationLimits,
+    ) -> Result<StacksTransactionReceipt, Error> {
+        let mut clarity_tx = self.block_begin(
+            burn_dbconn,
+            &tip_header.consensus_hash,
+            &tip_header.anchored_header.block_hash(),
+            &MINER_BLOCK_CONSENSUS_HASH,
+            &MINER_BLOCK_HEADER_HASH,
+        );
+
+        for mblock in microblocks.iter() {
+            if let Err((e, mblock_hash)) = StacksChainState::process_microblocks_transactions(
+                &mut clarity_tx,
+                &vec![mblock.clone()],
+            ) {
+                // the rest of the stream builds on this microblock, so stop here
+                debug!(
+                    "Stop replaying unconfirmed microblocks for simulation at {}: {:?}",
+                    &mblock_hash, &e
+                );
+                break;
+            }
+        }
+
+        let mainnet = clarity_tx.config.mainnet;
+        let epoch = clarity_tx.get_epoch();
+        let cost_track = clarity_tx
+            .connection()
+            .with_clarity_db_readonly(|clarity_db| {
+                LimitedCostTracker::new_mid_block(mainnet, limits.cost.clone(), clarity_db, epoch)
+            });
+        let result = match cost_track {
+            Ok(mut cost_track) => {
+                if limits.time_limit_ms > 0 {
+                    cost_track
+                        .set_deadline(Instant::now() + Duration::from_millis(limits.time_limit_ms));
+                }
+                let (result, _) =
+                    clarity_tx.with_temporary_cost_tracker(cost_track, |clarity_tx| {
+                        StacksChainState::check_transaction_network(&clarity_tx.config, tx)
+                            .and_then(|_| StacksChainState::apply_transaction(clarity_tx, tx, true))
+                            .map(|(_fee, receipt)| receipt)
+                    });
+                result
+            }
+            Err(_) => {
+                let e =
+                    InterpreterError::from(vm::errors::InterpreterError::CostContractLoadFailure);
+                Err(Error::ClarityError(clarity_error::from(e)))
+            }
+        };
+
+        clarity_tx.rollback_block();
+        result
+    }
 }
 
 #[cfg(test)]
@@ -2050,6 +2486,114 @@ pub mod test {
         assert_eq!(var_res, Some(Value::Int(3)));
     }
 
+    #[test]
+    fn process_contract_call_cost_breakdown() {
+        let contract = "
+        (define-data-var bar int 0)
+        (define-public (set-bar (x int) (y int))
+          (begin (var-set bar (/ x y)) (ok (var-get bar))))";
+        let router = "
+        (define-public (route (x int))
+          (begin
+            (try! (contract-call? .hello-world set-bar x 1))
+            (contract-call? .hello-world set-bar x 2)))";
+
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "process-contract-call-cost-breakdown");
+        chainstate.clarity_state.set_cost_breakdown(true);
+
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let auth = TransactionAuth::from_p2pkh(&privk).unwrap();
+        let addr = auth.origin().address_testnet();
+
+        let privk_2 = StacksPrivateKey::from_hex(
+            "d2c340ebcc0794b6fabdd8ac8b1c983e363b05dc8adcdf7e30db205a3fa54c1601",
+        )
+        .unwrap();
+        let auth_2 = TransactionAuth::from_p2pkh(&privk_2).unwrap();
+
+        let mut signed_txs = vec![];
+        for (nonce, (name, code)) in [("hello-world", contract), ("router", router)]
+            .iter()
+            .enumerate()
+        {
+            let mut tx_contract = StacksTransaction::new(
+                TransactionVersion::Testnet,
+                auth.clone(),
+                TransactionPayload::new_smart_contract(&name.to_string(), &code.to_string())
+                    .unwrap(),
+            );
+            tx_contract.chain_id = 0x80000000;
+            tx_contract.set_tx_fee(0);
+            tx_contract.set_origin_nonce(nonce as u64);
+
+            let mut signer = StacksTransactionSigner::new(&tx_contract);
+            signer.sign_origin(&privk).unwrap();
+            signed_txs.push(signer.get_tx().unwrap());
+        }
+
+        let mut tx_contract_call = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            auth_2.clone(),
+            TransactionPayload::new_contract_call(
+                addr.clone(),
+                "router",
+                "route",
+                vec![Value::Int(6)],
+            )
+            .unwrap(),
+        );
+        tx_contract_call.chain_id = 0x80000000;
+        tx_contract_call.set_tx_fee(0);
+
+        let mut signer_2 = StacksTransactionSigner::new(&tx_contract_call);
+        signer_2.sign_origin(&privk_2).unwrap();
+        signed_txs.push(signer_2.get_tx().unwrap());
+
+        let mut conn = chainstate.block_begin(
+            &TEST_BURN_STATE_DB,
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+            &ConsensusHash([1u8; 20]),
+            &BlockHeaderHash([1u8; 32]),
+        );
+        let receipts: Vec<_> = signed_txs
+            .iter()
+            .map(|tx| {
+                StacksChainState::process_transaction(&mut conn, tx, false)
+                    .unwrap()
+                    .1
+            })
+            .collect();
+        conn.commit_block();
+
+        // deploying makes no contract calls
+        let deploy_breakdown = receipts[1].cost_breakdown.as_ref().unwrap();
+        assert!(deploy_breakdown.contract_calls.is_empty());
+        assert!(deploy_breakdown
+            .cost_functions
+            .contains_key("cost_ast_parse"));
+
+        // the call's cost is attributed to the natives it used and to its contract-call? edge
+        let call_breakdown = receipts[2].cost_breakdown.as_ref().unwrap();
+        assert_eq!(receipts[2].result, Value::okay(Value::Int(3)).unwrap());
+        assert!(call_breakdown.cost_functions["cost_div"] > 0);
+        assert!(call_breakdown.cost_functions["cost_contract_call"] > 0);
+        assert!(!call_breakdown.cost_functions.contains_key("cost_ast_parse"));
+
+        assert_eq!(call_breakdown.contract_calls.len(), 1);
+        let edge = &call_breakdown.contract_calls[0];
+        assert_eq!(edge.caller, format!("{}.router", &addr));
+        assert_eq!(edge.callee, format!("{}.hello-world", &addr));
+        assert_eq!(edge.function, "set-bar");
+        assert_eq!(edge.count, 2);
+        assert!(edge.runtime > 0);
+        assert!(edge.runtime < receipts[2].execution_cost.runtime);
+    }
+
     #[test]
     fn process_smart_contract_contract_call_runtime_error() {
         let contract = "
@@ -7524,4 +8068,240 @@ pub mod test {
 
         conn.commit_block();
     }
+
+    #[test]
+    fn simulate_unsigned_token_transfer() {
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let auth = TransactionAuth::from_p2pkh(&privk).unwrap();
+        let addr = auth.origin().address_testnet();
+        let recv_addr = StacksAddress {
+            version: 1,
+            bytes: Hash160([0xff; 20]),
+        };
+
+        let mut chainstate = instantiate_chainstate_with_balances(
+            false,
+            0x80000000,
+            "simulate-unsigned-token-transfer",
+            vec![(addr.clone(), 1000000)],
+        );
+
+        // never signed
+        let mut tx_stx_transfer = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            auth.clone(),
+            TransactionPayload::TokenTransfer(
+                recv_addr.clone().into(),
+                123,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        );
+        tx_stx_transfer.chain_id = 0x80000000;
+        tx_stx_transfer.post_condition_mode = TransactionPostConditionMode::Allow;
+        tx_stx_transfer.set_tx_fee(0);
+
+        let tip = StacksBlockHeader::make_index_block_hash(
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+        );
+
+        // simulating twice gives the same result, since nothing is committed
+        for _ in 0..2 {
+            let receipt = chainstate
+                .simulate_transaction(
+                    &TEST_BURN_STATE_DB,
+                    &tip,
+                    &[],
+                    &tx_stx_transfer,
+                    &unlimited_simulation(),
+                )
+                .unwrap();
+            assert_eq!(receipt.result, Value::okay_true());
+            assert_eq!(receipt.events.len(), 1);
+            assert!(!receipt.post_condition_aborted);
+        }
+
+        let (sender_balance, recv_balance) = chainstate
+            .with_read_only_clarity_tx(&TEST_BURN_STATE_DB, &tip, |conn| {
+                (
+                    StacksChainState::get_account(conn, &addr.to_account_principal())
+                        .stx_balance
+                        .amount_unlocked,
+                    StacksChainState::get_account(conn, &recv_addr.to_account_principal())
+                        .stx_balance
+                        .amount_unlocked,
+                )
+            })
+            .unwrap();
+        assert_eq!(sender_balance, 1000000);
+        assert_eq!(recv_balance, 0);
+
+        // simulations run on a detached view of the chainstate, which is re-attached afterwards
+        assert!(!chainstate.clarity_state.is_detached());
+
+        // too big to simulate
+        let mut limits = unlimited_simulation();
+        limits.max_tx_len = (tx_stx_transfer.serialize_to_vec().len() - 1) as u64;
+        match chainstate.simulate_transaction(
+            &TEST_BURN_STATE_DB,
+            &tip,
+            &[],
+            &tx_stx_transfer,
+            &limits,
+        ) {
+            Err(Error::TransactionTooBigError) => {}
+            x => panic!("Expected transaction too big, got {:?}", x),
+        }
+        assert!(!chainstate.clarity_state.is_detached());
+
+        // still has to be for this chain
+        tx_stx_transfer.chain_id = 0x80000001;
+        match chainstate.simulate_transaction(
+            &TEST_BURN_STATE_DB,
+            &tip,
+            &[],
+            &tx_stx_transfer,
+            &unlimited_simulation(),
+        ) {
+            Err(Error::InvalidStacksTransaction(..)) => {}
+            x => panic!("Expected invalid transaction, got {:?}", x),
+        }
+    }
+
+    fn unlimited_simulation() -> TransactionSimulationLimits {
+        TransactionSimulationLimits {
+            cost: ExecutionCost::max_value(),
+            time_limit_ms: 0,
+            max_tx_len: 0,
+        }
+    }
+
+    #[test]
+    fn simulate_transaction_postconditions() {
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let auth = TransactionAuth::from_p2pkh(&privk).unwrap();
+        let addr = auth.origin().address_testnet();
+
+        let mut chainstate = instantiate_chainstate_with_balances(
+            false,
+            0x80000000,
+            "simulate-transaction-postconditions",
+            vec![(addr.clone(), 1000000)],
+        );
+
+        let contract = "
+        (define-fungible-token tok)
+        (define-non-fungible-token nft uint)
+        (begin
+            (unwrap-panic (ft-mint? tok u10 tx-sender))
+            (unwrap-panic (nft-mint? nft u1 tx-sender))
+            (unwrap-panic (stx-transfer? u100 tx-sender 'ST000000000000000000002AMW42H))
+            (unwrap-panic (stx-burn? u23 tx-sender))
+            (unwrap-panic (ft-transfer? tok u4 tx-sender 'ST000000000000000000002AMW42H))
+            (unwrap-panic (nft-burn? nft u1 tx-sender)))";
+
+        let mut tx_contract = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            auth.clone(),
+            TransactionPayload::new_smart_contract("movements", contract).unwrap(),
+        );
+        tx_contract.chain_id = 0x80000000;
+        tx_contract.post_condition_mode = TransactionPostConditionMode::Deny;
+        tx_contract.set_tx_fee(0);
+
+        let tip = StacksBlockHeader::make_index_block_hash(
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+        );
+
+        let asset_info = |asset_name: &str| AssetInfo {
+            contract_address: addr.clone(),
+            contract_name: ContractName::try_from("movements").unwrap(),
+            asset_name: ClarityName::try_from(asset_name).unwrap(),
+        };
+        let expected_post_conditions = vec![
+            TransactionPostCondition::STX(
+                PostConditionPrincipal::Origin,
+                FungibleConditionCode::SentEq,
+                123,
+            ),
+            TransactionPostCondition::Nonfungible(
+                PostConditionPrincipal::Origin,
+                asset_info("nft"),
+                Value::UInt(1),
+                NonfungibleConditionCode::Sent,
+            ),
+            TransactionPostCondition::Fungible(
+                PostConditionPrincipal::Origin,
+                asset_info("tok"),
+                FungibleConditionCode::SentEq,
+                4,
+            ),
+        ];
+
+        // nothing is covered in Deny mode, but the movements are still observed
+        let simulation = chainstate
+            .simulate_transaction_postconditions(
+                &TEST_BURN_STATE_DB,
+                &tip,
+                &[],
+                &tx_contract,
+                &unlimited_simulation(),
+            )
+            .unwrap();
+        assert!(!simulation.receipt.post_condition_aborted);
+        assert_eq!(simulation.post_conditions, expected_post_conditions);
+        assert!(simulation.post_condition_check.is_err());
+
+        // the suggested post-conditions pass
+        tx_contract.post_conditions = simulation.post_conditions.clone();
+        let simulation = chainstate
+            .simulate_transaction_postconditions(
+                &TEST_BURN_STATE_DB,
+                &tip,
+                &[],
+                &tx_contract,
+                &unlimited_simulation(),
+            )
+            .unwrap();
+        assert_eq!(simulation.post_conditions, expected_post_conditions);
+        assert_eq!(simulation.post_condition_check, Ok(()));
+
+        // STX burns count against the STX post-condition
+        tx_contract.post_conditions[0] = TransactionPostCondition::STX(
+            PostConditionPrincipal::Origin,
+            FungibleConditionCode::SentLe,
+            100,
+        );
+        let simulation = chainstate
+            .simulate_transaction_postconditions(
+                &TEST_BURN_STATE_DB,
+                &tip,
+                &[],
+                &tx_contract,
+                &unlimited_simulation(),
+            )
+            .unwrap();
+        assert!(simulation.post_condition_check.is_err());
+
+        // Allow mode only needs the supplied post-conditions to hold
+        tx_contract.post_condition_mode = TransactionPostConditionMode::Allow;
+        tx_contract.post_conditions = vec![expected_post_conditions[2].clone()];
+        let simulation = chainstate
+            .simulate_transaction_postconditions(
+                &TEST_BURN_STATE_DB,
+                &tip,
+                &[],
+                &tx_contract,
+                &unlimited_simulation(),
+            )
+            .unwrap();
+        assert_eq!(simulation.post_condition_check, Ok(()));
+    }
 }
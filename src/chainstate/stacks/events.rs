@@ -19,7 +19,7 @@ use crate::{codec::StacksMessageCodec, types::chainstate::StacksMicroblockHeader
 use burnchains::Txid;
 use chainstate::stacks::StacksTransaction;
 use vm::analysis::ContractAnalysis;
-use vm::costs::ExecutionCost;
+use vm::costs::{CostBreakdown, ExecutionCost};
 use vm::types::{
     AssetIdentifier, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
 };
@@ -61,6 +61,8 @@ pub struct StacksTransactionReceipt {
     pub contract_analysis: Option<ContractAnalysis>,
     pub execution_cost: ExecutionCost,
     pub microblock_header: Option<StacksMicroblockHeader>,
+    /// where the transaction's runtime cost went, if the chainstate was asked to break it down
+    pub cost_breakdown: Option<CostBreakdown>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -72,6 +74,53 @@ pub enum StacksTransactionEvent {
 }
 
 impl StacksTransactionEvent {
+    /// The name of this event's type, as reported in the `type` field of its JSON serialization
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            StacksTransactionEvent::SmartContractEvent(_) => "contract_event",
+            StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(_)) => {
+                "stx_transfer_event"
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(_)) => "stx_mint_event",
+            StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(_)) => "stx_burn_event",
+            StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(_)) => "stx_lock_event",
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(_)) => {
+                "nft_transfer_event"
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(_)) => "nft_mint_event",
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(_)) => "nft_burn_event",
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(_)) => "ft_transfer_event",
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(_)) => "ft_mint_event",
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(_)) => "ft_burn_event",
+        }
+    }
+
+    /// The contract that emitted this event, if any.  STX events are not tied to a contract.
+    pub fn contract_identifier(&self) -> Option<&QualifiedContractIdentifier> {
+        match self {
+            StacksTransactionEvent::SmartContractEvent(event_data) => Some(&event_data.key.0),
+            StacksTransactionEvent::STXEvent(_) => None,
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+        }
+    }
+
     pub fn json_serialize(
         &self,
         event_index: usize,
@@ -268,6 +317,7 @@ impl NFTTransferEventData {
             "recipient": format!("{}",self.recipient),
             "value": self.value,
             "raw_value": format!("0x{}", raw_value.join("")),
+            "value_json": self.value.to_json(),
         })
     }
 }
@@ -292,6 +342,7 @@ impl NFTMintEventData {
             "recipient": format!("{}",self.recipient),
             "value": self.value,
             "raw_value": format!("0x{}", raw_value.join("")),
+            "value_json": self.value.to_json(),
         })
     }
 }
@@ -316,6 +367,7 @@ impl NFTBurnEventData {
             "sender": format!("{}",self.sender),
             "value": self.value,
             "raw_value": format!("0x{}", raw_value.join("")),
+            "value_json": self.value.to_json(),
         })
     }
 }
@@ -392,6 +444,7 @@ impl SmartContractEventData {
             "topic": self.key.1,
             "value": self.value,
             "raw_value": format!("0x{}", raw_value.join("")),
+            "value_json": self.value.to_json(),
         })
     }
 }
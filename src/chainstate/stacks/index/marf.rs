@@ -31,7 +31,7 @@ use chainstate::stacks::index::node::{
     TrieNode256, TrieNode4, TrieNode48, TrieNodeID, TrieNodeType, TriePath, TriePtr, TRIEPTR_SIZE,
 };
 use chainstate::stacks::index::storage::{
-    TrieFileStorage, TrieStorageConnection, TrieStorageTransaction,
+    TrieFileStorage, TrieIOStats, TrieStorageConnection, TrieStorageTransaction,
 };
 use chainstate::stacks::index::trie::Trie;
 use chainstate::stacks::index::Error;
@@ -1215,6 +1215,17 @@ impl<T: MarfTrieId> MARF<T> {
         })
     }
 
+    /// Detach (or re-attach) this MARF handle.  A detached MARF can begin and fill in a new trie,
+    /// reading from a snapshot of the rest of the MARF, but can only drop the new trie, not commit
+    /// it.  Many detached handles can each have a trie open at once.
+    pub fn set_detached(&mut self, detached: bool) {
+        self.storage.set_detached(detached)
+    }
+
+    pub fn is_detached(&self) -> bool {
+        self.storage.detached()
+    }
+
     /// Target the MARF's storage at a given block.
     pub fn open_block(&mut self, block_hash: &T) -> Result<(), Error> {
         self.storage.connection().open_block(block_hash)
@@ -1406,6 +1417,11 @@ impl<T: MarfTrieId> MARF<T> {
         self.open_chain_tip.as_ref().map(|x| x.height)
     }
 
+    /// Trie nodes this MARF has read and written so far
+    pub fn io_stats(&self) -> TrieIOStats {
+        self.storage.io_stats()
+    }
+
     /// Access internal storage
     #[cfg(test)]
     pub fn borrow_storage_backend(&mut self) -> TrieStorageConnection<T> {
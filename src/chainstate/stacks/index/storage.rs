@@ -47,6 +47,7 @@ use chainstate::stacks::index::Error;
 use chainstate::stacks::index::{trie_sql, BlockMap, MarfTrieId};
 use util::db::sql_pragma;
 use util::db::sqlite_open;
+use util::db::tx_begin_deferred;
 use util::db::tx_begin_immediate;
 use util::db::tx_busy_handler;
 use util::db::Error as db_error;
@@ -152,6 +153,35 @@ impl<T: MarfTrieId> fmt::Display for FlushOptions<'_, T> {
     }
 }
 
+/// Running totals of the trie nodes a TrieFileStorage has read and written since it was opened.
+/// Take one before and one after some work, and `since()` gives the I/O that work did.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TrieIOStats {
+    pub reads: u64,
+    pub backptr_reads: u64,
+    pub node_reads: u64,
+    pub leaf_reads: u64,
+
+    pub writes: u64,
+    pub node_writes: u64,
+    pub leaf_writes: u64,
+}
+
+impl TrieIOStats {
+    /// The reads and writes done between `earlier` and `self`
+    pub fn since(&self, earlier: &TrieIOStats) -> TrieIOStats {
+        TrieIOStats {
+            reads: self.reads.saturating_sub(earlier.reads),
+            backptr_reads: self.backptr_reads.saturating_sub(earlier.backptr_reads),
+            node_reads: self.node_reads.saturating_sub(earlier.node_reads),
+            leaf_reads: self.leaf_reads.saturating_sub(earlier.leaf_reads),
+            writes: self.writes.saturating_sub(earlier.writes),
+            node_writes: self.node_writes.saturating_sub(earlier.node_writes),
+            leaf_writes: self.leaf_writes.saturating_sub(earlier.leaf_writes),
+        }
+    }
+}
+
 /// In-RAM trie storage.
 /// Used by TrieFileStorage to buffer the next trie being built.
 #[derive(Clone)]
@@ -706,6 +736,10 @@ struct TrieStorageTransientData<T: MarfTrieId> {
 
     readonly: bool,
     unconfirmed: bool,
+    /// A detached storage handle can extend tries, but never writes them or anything else to
+    /// disk.  Its transactions only read, from a snapshot of the database, so detached handles
+    /// do not block each other or the handle that writes.
+    detached: bool,
 }
 
 // disk-backed Trie.
@@ -749,7 +783,11 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         if self.readonly() {
             return Err(Error::ReadOnlyError);
         }
-        let tx = tx_begin_immediate(&mut self.db)?;
+        let tx = if self.data.detached {
+            tx_begin_deferred(&mut self.db)?
+        } else {
+            tx_begin_immediate(&mut self.db)?
+        };
 
         Ok(TrieStorageTransaction(TrieStorageConnection {
             db: SqliteConnection::Tx(tx),
@@ -765,6 +803,20 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         &self.db
     }
 
+    /// Trie nodes read and written through this storage so far.
+    /// Unlike the test-only `stats()` family, this does not reset the counters.
+    pub fn io_stats(&self) -> TrieIOStats {
+        TrieIOStats {
+            reads: self.data.read_count,
+            backptr_reads: self.data.read_backptr_count,
+            node_reads: self.data.read_node_count,
+            leaf_reads: self.data.read_leaf_count,
+            writes: self.data.write_count,
+            node_writes: self.data.write_node_count,
+            leaf_writes: self.data.write_leaf_count,
+        }
+    }
+
     pub fn sqlite_tx<'a>(&'a mut self) -> Result<Transaction<'a>, db_error> {
         tx_begin_immediate(&mut self.db)
     }
@@ -840,6 +892,7 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
 
                 readonly: readonly,
                 unconfirmed: unconfirmed,
+                detached: false,
             },
 
             // used in testing in order to short-circuit block-height lookups
@@ -876,6 +929,16 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         self.data.unconfirmed
     }
 
+    pub fn detached(&self) -> bool {
+        self.data.detached
+    }
+
+    /// Detach (or re-attach) this storage handle.  Detached handles can extend and query tries,
+    /// but any attempt to flush one to disk fails.
+    pub fn set_detached(&mut self, detached: bool) {
+        self.data.detached = detached;
+    }
+
     pub fn reopen_readonly(&self) -> Result<TrieFileStorage<T>, Error> {
         let db = marf_sqlite_open(&self.db_path, OpenFlags::SQLITE_OPEN_READ_ONLY, false)?;
 
@@ -905,6 +968,7 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
 
                 readonly: true,
                 unconfirmed: true,
+                detached: false,
             },
 
             // used in testing in order to short-circuit block-height lookups
@@ -952,6 +1016,7 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
 
                 readonly: true,
                 unconfirmed: true,
+                detached: false,
             },
 
             // used in testing in order to short-circuit block-height lookups
@@ -973,6 +1038,9 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         if self.data.readonly {
             return Err(Error::ReadOnlyError);
         }
+        if self.data.detached && self.data.last_extended.is_some() {
+            return Err(Error::ReadOnlyError);
+        }
         if let Some((bhh, trie_ram)) = self.data.last_extended.take() {
             trace!("Buffering block flush started.");
             let mut buffer = Cursor::new(Vec::new());
@@ -1051,8 +1119,10 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         self.clear_cached_ancestor_hashes_bytes();
         if !self.data.readonly {
             if let Some((ref bhh, _)) = self.data.last_extended.take() {
-                trie_sql::drop_lock(&self.db, bhh)
-                    .expect("Corruption: Failed to drop the extended trie lock");
+                if !self.data.detached {
+                    trie_sql::drop_lock(&self.db, bhh)
+                        .expect("Corruption: Failed to drop the extended trie lock");
+                }
             }
             self.data.last_extended = None;
             self.data.cur_block_id = None;
@@ -1099,8 +1169,9 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
 
         let trie_buf = TrieRAM::new(bhh, size_hint, &self.data.cur_block);
 
-        // place a lock on this block, so we can't extend to it again
-        if !trie_sql::lock_bhh_for_extension(self.sqlite_tx(), bhh, false)? {
+        // place a lock on this block, so we can't extend to it again.  Detached handles never
+        // write the trie, so they can extend to a block someone else is extending to.
+        if !self.data.detached && !trie_sql::lock_bhh_for_extension(self.sqlite_tx(), bhh, false)? {
             warn!("Block already extended: {}", &bhh);
             return Err(Error::ExistsError);
         }
@@ -14,11 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::convert::From;
 use std::fs;
 use std::mem;
+use std::thread;
+use std::thread::JoinHandle;
 
 use crate::cost_estimates::metrics::CostMetric;
 use crate::cost_estimates::CostEstimator;
@@ -37,6 +40,7 @@ use chainstate::stacks::Error;
 use chainstate::stacks::*;
 use clarity_vm::clarity::ClarityConnection;
 use core::mempool::*;
+use core::mempool_walk::MemPoolWalkStrategy;
 use core::*;
 use net::Error as net_error;
 use util::get_epoch_time_ms;
@@ -52,10 +56,73 @@ use crate::types::chainstate::{BlockHeaderHash, StacksAddress, StacksWorkScore};
 use crate::types::chainstate::{StacksBlockHeader, StacksBlockId, StacksMicroblockHeader};
 use crate::types::proof::TrieHash;
 
+/// Least amount of time to give a round of iterative block assembly after the first
+pub const ITERATIVE_ASSEMBLY_MIN_ROUND_MS: u64 = 100;
+
+/// A parent block to assemble a candidate anchored block on, and what the miner needs in order to
+/// build on it.
+#[derive(Debug, Clone)]
+pub struct AnchoredBlockCandidate {
+    pub parent_stacks_header: StacksHeaderInfo,
+    pub total_burn: u64,
+    pub proof: VRFProof,
+    pub pubkey_hash: Hash160,
+    pub coinbase_tx: StacksTransaction,
+}
+
+/// A candidate anchored block, as assembled
+#[derive(Debug, Clone)]
+pub struct AssembledCandidateBlock {
+    pub block: StacksBlock,
+    pub consumed: ExecutionCost,
+    pub size: u64,
+    /// cost of the parent microblocks the block confirms
+    pub confirmed_mblock_cost: ExecutionCost,
+}
+
+/// A candidate anchored block being assembled on its own thread
+pub struct CandidateBlockAssembly {
+    /// index block hash of the candidate's parent
+    pub parent_block_id: StacksBlockId,
+    handle: JoinHandle<Result<AssembledCandidateBlock, Error>>,
+}
+
+impl CandidateBlockAssembly {
+    /// Wait for the candidate to be assembled
+    pub fn join(self) -> Result<AssembledCandidateBlock, Error> {
+        self.handle
+            .join()
+            .expect("FATAL: candidate block assembly thread panicked")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BlockBuilderSettings {
     pub max_miner_time_ms: u64,
     pub mempool_settings: MemPoolWalkSettings,
+    /// Only consulted when mining microblocks
+    pub microblock_policy: MicroblockPolicy,
+}
+
+/// Limits on which transactions a miner streams in microblocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct MicroblockPolicy {
+    /// Most of the block budget, as a percentage in its largest dimension, that the microblock
+    /// stream may spend on top of its anchored block.  The transaction that reaches the limit is
+    /// still mined.
+    pub max_cost_pct: u64,
+    /// Transactions paying a lower fee are left for the next anchored block
+    pub min_tx_fee: u64,
+}
+
+impl MicroblockPolicy {
+    /// No limits beyond the block budget itself
+    pub fn unlimited() -> MicroblockPolicy {
+        MicroblockPolicy {
+            max_cost_pct: 100,
+            min_tx_fee: 0,
+        }
+    }
 }
 
 impl BlockBuilderSettings {
@@ -63,6 +130,7 @@ impl BlockBuilderSettings {
         BlockBuilderSettings {
             max_miner_time_ms: u64::max_value(),
             mempool_settings: MemPoolWalkSettings::default(),
+            microblock_policy: MicroblockPolicy::unlimited(),
         }
     }
 
@@ -70,6 +138,7 @@ impl BlockBuilderSettings {
         BlockBuilderSettings {
             max_miner_time_ms: u64::max_value(),
             mempool_settings: MemPoolWalkSettings::zero(),
+            microblock_policy: MicroblockPolicy::unlimited(),
         }
     }
 }
@@ -127,6 +196,8 @@ pub struct StacksMicroblockBuilder<'a> {
     anchor_block: BlockHeaderHash,
     anchor_block_consensus_hash: ConsensusHash,
     anchor_block_height: u64,
+    /// execution cost of the anchored block, which the microblock stream's cost is counted from
+    anchor_block_cost: ExecutionCost,
     header_reader: StacksChainState,
     clarity_tx: Option<ClarityTx<'a>>,
     unconfirmed: bool,
@@ -190,12 +261,13 @@ impl<'a> StacksMicroblockBuilder<'a> {
             &StacksBlockHeader::make_index_block_hash(&anchor_block_consensus_hash, &anchor_block),
             &cost_so_far
         );
-        clarity_tx.reset_cost(cost_so_far);
+        clarity_tx.reset_cost(cost_so_far.clone());
 
         Ok(StacksMicroblockBuilder {
             anchor_block,
             anchor_block_consensus_hash,
             anchor_block_height,
+            anchor_block_cost: cost_so_far,
             runtime: runtime,
             clarity_tx: Some(clarity_tx),
             header_reader,
@@ -245,6 +317,15 @@ impl<'a> StacksMicroblockBuilder<'a> {
                 return Err(Error::NoSuchBlockError)?;
             };
 
+        let anchored_block_cost = StacksChainState::get_stacks_block_anchored_cost(
+            chainstate.db(),
+            &StacksBlockHeader::make_index_block_hash(
+                &anchored_consensus_hash,
+                &anchored_block_hash,
+            ),
+        )?
+        .ok_or(Error::NoSuchBlockError)?;
+
         let mut clarity_tx = chainstate.begin_unconfirmed(burn_dbconn).ok_or_else(|| {
             warn!(
                 "Failed to begin-unconfirmed on {}/{}",
@@ -267,6 +348,7 @@ impl<'a> StacksMicroblockBuilder<'a> {
             anchor_block: anchored_block_hash,
             anchor_block_consensus_hash: anchored_consensus_hash,
             anchor_block_height: anchored_block_height,
+            anchor_block_cost: anchored_block_cost,
             runtime: runtime,
             clarity_tx: Some(clarity_tx),
             header_reader,
@@ -454,6 +536,8 @@ impl<'a> StacksMicroblockBuilder<'a> {
     ) -> Result<StacksMicroblock, Error> {
         let mut txs_included = vec![];
         let mempool_settings = self.settings.mempool_settings.clone();
+        let microblock_policy = self.settings.microblock_policy.clone();
+        let anchor_block_cost = self.anchor_block_cost.clone();
 
         let mut clarity_tx = self
             .clarity_tx
@@ -477,6 +561,7 @@ impl<'a> StacksMicroblockBuilder<'a> {
             .block_limit()
             .expect("No block limit found for clarity_tx.");
         mem_pool.estimate_tx_rates(100, &block_limit, &stacks_epoch_id)?;
+        mem_pool.begin_block(&block_limit, &stacks_epoch_id);
 
         debug!(
             "Microblock transaction selection begins (child of {}), bytes so far: {}",
@@ -502,6 +587,32 @@ impl<'a> StacksMicroblockBuilder<'a> {
                             return Ok(false);
                         }
 
+                        if mempool_tx.tx.get_tx_fee() < microblock_policy.min_tx_fee {
+                            debug!(
+                                "Leave tx {} for the next anchored block: fee {} is below {}",
+                                mempool_tx.tx.txid(),
+                                mempool_tx.tx.get_tx_fee(),
+                                microblock_policy.min_tx_fee
+                            );
+                            return Ok(true);
+                        }
+
+                        if microblock_policy.max_cost_pct < 100 {
+                            let mut stream_cost = clarity_tx.cost_so_far();
+                            stream_cost
+                                .sub(&anchor_block_cost)
+                                .expect("BUG: microblock stream cost is negative");
+                            if block_limit.proportion_largest_dimension(&stream_cost)
+                                >= microblock_policy.max_cost_pct
+                            {
+                                debug!(
+                                    "Microblock stream reached {}% of the block budget",
+                                    microblock_policy.max_cost_pct
+                                );
+                                return Ok(false);
+                            }
+                        }
+
                         match StacksMicroblockBuilder::mine_next_transaction(
                             clarity_tx,
                             mempool_tx.tx.clone(),
@@ -586,6 +697,201 @@ impl<'a> StacksMicroblockBuilder<'a> {
         return self.make_next_microblock(txs_included, miner_key);
     }
 
+    /// Is either of the nonces `tx_info` uses ahead of its account's nonce?
+    fn is_nonce_ahead(clarity_tx: &mut ClarityTx<'a>, tx_info: &MemPoolTxInfo) -> bool {
+        let origin_nonce = StacksChainState::get_account(
+            clarity_tx,
+            &tx_info.metadata.origin_address.clone().into(),
+        )
+        .nonce;
+        let sponsor_nonce = StacksChainState::get_account(
+            clarity_tx,
+            &tx_info.metadata.sponsor_address.clone().into(),
+        )
+        .nonce;
+        tx_info.metadata.origin_nonce > origin_nonce
+            || tx_info.metadata.sponsor_nonce > sponsor_nonce
+    }
+
+    /// Mine the next microblock from the transactions that arrived in the mempool since
+    /// `subscription` was last polled, instead of walking the whole mempool.  Arrivals are
+    /// retried until none of them can be added, so a transaction may follow one with a later
+    /// nonce that arrived after it.  Arrivals whose nonces are still ahead of their accounts' are
+    /// deferred to the next poll of `subscription`; the other ones that can't be mined are left
+    /// for the next anchored block.
+    pub fn mine_next_microblock_from_subscription(
+        &mut self,
+        mem_pool: &MemPoolDB,
+        subscription: &mut MemPoolSubscription,
+        miner_key: &Secp256k1PrivateKey,
+    ) -> Result<StacksMicroblock, Error> {
+        let mut txs_included = vec![];
+        let mempool_settings = self.settings.mempool_settings.clone();
+        let microblock_policy = self.settings.microblock_policy.clone();
+
+        let arrivals = mem_pool.poll_subscription(subscription)?;
+        let mut pending: Vec<MemPoolTxInfo> = arrivals
+            .into_iter()
+            .filter(|tx_info| {
+                if tx_info.tx.get_tx_fee() < microblock_policy.min_tx_fee {
+                    debug!(
+                        "Leave tx {} for the next anchored block: fee {} is below {}",
+                        tx_info.tx.txid(),
+                        tx_info.tx.get_tx_fee(),
+                        microblock_policy.min_tx_fee
+                    );
+                    return false;
+                }
+                if let Some(ref policy) = mempool_settings.contract_call_policy {
+                    if let Err(reason) = policy.check_tx(&tx_info.tx) {
+                        info!("Skip mempool transaction excluded by miner contract policy";
+                              "txid" => %tx_info.tx.txid(),
+                              "reason" => %reason);
+                        return false;
+                    }
+                }
+                true
+            })
+            .collect();
+
+        let mut clarity_tx = self
+            .clarity_tx
+            .take()
+            .expect("Microblock already open and processing");
+
+        let mut considered = self
+            .runtime
+            .considered
+            .take()
+            .expect("Microblock already open and processing");
+
+        let mut bytes_so_far = self.runtime.bytes_so_far;
+        let mut num_txs = self.runtime.num_mined;
+        let deadline = get_epoch_time_ms() + (self.settings.max_miner_time_ms as u128);
+        let block_limit = clarity_tx
+            .block_limit()
+            .expect("No block limit found for clarity_tx.");
+
+        debug!(
+            "Microblock transaction selection from {} mempool arrivals begins (child of {}), bytes so far: {}",
+            pending.len(),
+            &self.anchor_block,
+            bytes_so_far
+        );
+
+        let mut result = Ok(());
+        let mut done = false;
+        while !done && pending.len() > 0 {
+            let mut num_added = 0;
+            let mut not_added = vec![];
+            for tx_info in pending.into_iter() {
+                if done {
+                    not_added.push(tx_info);
+                    continue;
+                }
+                if get_epoch_time_ms() >= deadline {
+                    debug!(
+                        "Microblock miner deadline exceeded ({} ms)",
+                        self.settings.max_miner_time_ms
+                    );
+                    done = true;
+                    not_added.push(tx_info);
+                    continue;
+                }
+                if microblock_policy.max_cost_pct < 100 {
+                    let mut stream_cost = clarity_tx.cost_so_far();
+                    stream_cost
+                        .sub(&self.anchor_block_cost)
+                        .expect("BUG: microblock stream cost is negative");
+                    if block_limit.proportion_largest_dimension(&stream_cost)
+                        >= microblock_policy.max_cost_pct
+                    {
+                        debug!(
+                            "Microblock stream reached {}% of the block budget",
+                            microblock_policy.max_cost_pct
+                        );
+                        done = true;
+                        not_added.push(tx_info);
+                        continue;
+                    }
+                }
+                if considered.contains(&tx_info.metadata.txid) {
+                    // already mined into this stream
+                    continue;
+                }
+
+                match StacksMicroblockBuilder::mine_next_transaction(
+                    &mut clarity_tx,
+                    tx_info.tx.clone(),
+                    tx_info.metadata.len,
+                    &mut considered,
+                    bytes_so_far,
+                ) {
+                    Ok(Some(_)) => {
+                        debug!(
+                            "Include tx {} ({}) in microblock",
+                            tx_info.tx.txid(),
+                            tx_info.tx.payload.name()
+                        );
+                        bytes_so_far += tx_info.metadata.len;
+                        num_txs += 1;
+                        num_added += 1;
+                        txs_included.push(tx_info.tx);
+                    }
+                    Ok(None) => {
+                        // may be retried once other arrivals are added
+                        considered.remove(&tx_info.metadata.txid);
+                        not_added.push(tx_info);
+                    }
+                    Err(e) => {
+                        considered.remove(&tx_info.metadata.txid);
+                        result = Err(e);
+                        done = true;
+                        not_added.push(tx_info);
+                    }
+                }
+            }
+            pending = not_added;
+            if num_added == 0 {
+                break;
+            }
+        }
+
+        for tx_info in pending.into_iter() {
+            if StacksMicroblockBuilder::is_nonce_ahead(&mut clarity_tx, &tx_info) {
+                subscription.defer(tx_info);
+            }
+        }
+
+        // do fault injection
+        if self.runtime.disable_bytes_check {
+            warn!("Fault injection: disabling miner limit on microblock stream size");
+            bytes_so_far = 0;
+        }
+        if self.runtime.disable_cost_check {
+            warn!("Fault injection: disabling miner limit on microblock runtime cost");
+            clarity_tx.reset_cost(ExecutionCost::zero());
+        }
+
+        self.runtime.bytes_so_far = bytes_so_far;
+        self.clarity_tx.replace(clarity_tx);
+        self.runtime.considered.replace(considered);
+        self.runtime.num_mined = num_txs;
+
+        match result {
+            Err(Error::BlockTooBigError) => {
+                info!("Block size budget reached with microblocks");
+            }
+            Err(e) => {
+                warn!("Error producing microblock: {}", e);
+                return Err(e);
+            }
+            _ => {}
+        }
+
+        return self.make_next_microblock(txs_included, miner_key);
+    }
+
     pub fn get_bytes_so_far(&self) -> u64 {
         self.runtime.bytes_so_far
     }
@@ -699,6 +1005,7 @@ impl StacksBlockBuilder {
             burn_header_timestamp: genesis_burn_header_timestamp,
             burn_header_height: genesis_burn_header_height,
             anchored_block_size: 0,
+            block_stats: None,
         };
 
         let mut builder = StacksBlockBuilder::from_parent_pubkey_hash(
@@ -1500,6 +1807,245 @@ impl StacksBlockBuilder {
         settings: BlockBuilderSettings,
         event_observer: Option<&dyn MemPoolEventDispatcher>,
     ) -> Result<(StacksBlock, ExecutionCost, u64), Error> {
+        let (block, consumed, size, confirmed_mblock_cost) =
+            StacksBlockBuilder::assemble_anchored_block(
+                chainstate_handle,
+                burn_dbconn,
+                mempool,
+                parent_stacks_header,
+                total_burn,
+                proof,
+                pubkey_hash,
+                coinbase_tx,
+                settings,
+                event_observer,
+            )?;
+        if let Some(observer) = event_observer {
+            observer.mined_block_event(
+                SortitionDB::get_canonical_burn_chain_tip(burn_dbconn.conn())?.block_height + 1,
+                &block,
+                size,
+                &consumed,
+                &confirmed_mblock_cost,
+            );
+        }
+        Ok((block, consumed, size))
+    }
+
+    /// Mine an anchored block in rounds until `deadline_ms` (wall-clock time, in milliseconds
+    /// since the epoch), and return the candidate that pays the most in transaction fees.
+    ///
+    /// The first round gets `settings.max_miner_time_ms` to produce a valid candidate quickly.
+    /// Each later round gets twice the time of the one before (at least
+    /// `ITERATIVE_ASSEMBLY_MIN_ROUND_MS`, and up to the deadline), so a
+    /// candidate whose mempool walk was cut short can take in more transactions.  Once a round
+    /// finishes its walk with time to spare, more time will not help; if `resolve_strategy` is
+    /// given, one more round re-solves the block with that walk strategy instead, and then
+    /// assembly stops.  Only the chosen candidate is reported to the event observer.
+    pub fn build_anchored_block_iterative(
+        chainstate_handle: &StacksChainState,
+        burn_dbconn: &SortitionDBConn,
+        mempool: &mut MemPoolDB,
+        parent_stacks_header: &StacksHeaderInfo,
+        total_burn: u64,
+        proof: VRFProof,
+        pubkey_hash: Hash160,
+        coinbase_tx: &StacksTransaction,
+        settings: BlockBuilderSettings,
+        deadline_ms: u128,
+        mut resolve_strategy: Option<Box<dyn MemPoolWalkStrategy>>,
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+    ) -> Result<(StacksBlock, ExecutionCost, u64), Error> {
+        let mut round_settings = settings;
+        let mut round = 0;
+        let mut best = None;
+        let mut best_fees = 0;
+        loop {
+            let round_start = get_epoch_time_ms();
+            let candidate = StacksBlockBuilder::assemble_anchored_block(
+                chainstate_handle,
+                burn_dbconn,
+                mempool,
+                parent_stacks_header,
+                total_burn,
+                proof.clone(),
+                pubkey_hash.clone(),
+                coinbase_tx,
+                round_settings.clone(),
+                event_observer,
+            )?;
+            let elapsed = get_epoch_time_ms().saturating_sub(round_start);
+            let fees = candidate
+                .0
+                .txs
+                .iter()
+                .fold(0, |agg: u64, tx| agg.saturating_add(tx.get_tx_fee()));
+
+            debug!("Miner: assembled candidate anchored block";
+                   "round" => round,
+                   "block_hash" => %candidate.0.block_hash(),
+                   "tx_count" => candidate.0.txs.len(),
+                   "tx_fees_microstacks" => fees,
+                   "assembly_time_ms" => elapsed,
+                   "budget_ms" => round_settings.max_miner_time_ms);
+
+            if best.is_none() || fees > best_fees {
+                best_fees = fees;
+                best = Some(candidate);
+            }
+            round += 1;
+
+            let now = get_epoch_time_ms();
+            if now >= deadline_ms {
+                break;
+            }
+            let remaining_ms = (deadline_ms - now) as u64;
+
+            if elapsed < (round_settings.max_miner_time_ms as u128) {
+                // the walk finished early, so this strategy has nothing more to offer
+                match resolve_strategy.take() {
+                    Some(strategy) => {
+                        let configured_strategy = mempool.replace_walk_strategy(strategy);
+                        round_settings.max_miner_time_ms = remaining_ms;
+                        round_settings.mempool_settings.max_walk_time_ms = remaining_ms;
+                        let resolved = StacksBlockBuilder::assemble_anchored_block(
+                            chainstate_handle,
+                            burn_dbconn,
+                            mempool,
+                            parent_stacks_header,
+                            total_burn,
+                            proof.clone(),
+                            pubkey_hash.clone(),
+                            coinbase_tx,
+                            round_settings.clone(),
+                            event_observer,
+                        );
+                        let resolve_strategy = mempool.replace_walk_strategy(configured_strategy);
+                        let resolved = resolved?;
+                        let fees = resolved
+                            .0
+                            .txs
+                            .iter()
+                            .fold(0, |agg: u64, tx| agg.saturating_add(tx.get_tx_fee()));
+
+                        debug!("Miner: re-solved candidate anchored block";
+                               "strategy" => resolve_strategy.name(),
+                               "block_hash" => %resolved.0.block_hash(),
+                               "tx_count" => resolved.0.txs.len(),
+                               "tx_fees_microstacks" => fees);
+
+                        if fees > best_fees {
+                            best_fees = fees;
+                            best = Some(resolved);
+                        }
+                    }
+                    None => {}
+                }
+                break;
+            }
+
+            let budget_ms = cmp::min(
+                remaining_ms,
+                cmp::max(
+                    round_settings.max_miner_time_ms.saturating_mul(2),
+                    ITERATIVE_ASSEMBLY_MIN_ROUND_MS,
+                ),
+            );
+            round_settings.max_miner_time_ms = budget_ms;
+            round_settings.mempool_settings.max_walk_time_ms = budget_ms;
+        }
+
+        let (block, consumed, size, confirmed_mblock_cost) =
+            best.expect("BUG: no candidate block assembled");
+
+        info!("Miner: chose candidate anchored block";
+              "block_hash" => %block.block_hash(),
+              "rounds" => round,
+              "tx_count" => block.txs.len(),
+              "tx_fees_microstacks" => best_fees);
+
+        if let Some(observer) = event_observer {
+            observer.mined_block_event(
+                SortitionDB::get_canonical_burn_chain_tip(burn_dbconn.conn())?.block_height + 1,
+                &block,
+                size,
+                &consumed,
+                &confirmed_mblock_cost,
+            );
+        }
+        Ok((block, consumed, size))
+    }
+
+    /// Start assembling a candidate anchored block on top of `candidate.parent_stacks_header`, on
+    /// its own thread.  The block is assembled against a detached copy of the chainstate (see
+    /// `StacksChainState::reopen_detached`), so any number of candidates -- on any number of
+    /// parents -- can be assembled alongside each other and alongside the miner's usual block
+    /// assembly.  Each candidate walks its own `mempool`, which should be a snapshot of the
+    /// miner's mempool (see `MemPoolDB::snapshot`), so that walks on different forks do not
+    /// disturb each other.  The candidate is not announced to the event observer.
+    pub fn spawn_candidate_assembly(
+        chainstate_handle: &StacksChainState,
+        sortdb_path: &str,
+        mut mempool: MemPoolDB,
+        candidate: AnchoredBlockCandidate,
+        settings: BlockBuilderSettings,
+    ) -> Result<CandidateBlockAssembly, Error> {
+        let chainstate = chainstate_handle.reopen_detached()?;
+        let sortdb_path = sortdb_path.to_string();
+        let parent_block_id = StacksBlockHeader::make_index_block_hash(
+            &candidate.parent_stacks_header.consensus_hash,
+            &candidate.parent_stacks_header.anchored_header.block_hash(),
+        );
+
+        debug!("Start candidate block assembly"; "parent_block_id" => %parent_block_id);
+
+        let handle = thread::Builder::new()
+            .name(format!("candidate-{}", &parent_block_id))
+            .spawn(move || -> Result<AssembledCandidateBlock, Error> {
+                let sortdb = SortitionDB::open(&sortdb_path, false)?;
+                let (block, consumed, size, confirmed_mblock_cost) =
+                    StacksBlockBuilder::assemble_anchored_block(
+                        &chainstate,
+                        &sortdb.index_conn(),
+                        &mut mempool,
+                        &candidate.parent_stacks_header,
+                        candidate.total_burn,
+                        candidate.proof,
+                        candidate.pubkey_hash,
+                        &candidate.coinbase_tx,
+                        settings,
+                        None,
+                    )?;
+                Ok(AssembledCandidateBlock {
+                    block,
+                    consumed,
+                    size,
+                    confirmed_mblock_cost,
+                })
+            })
+            .expect("FATAL: failed to start candidate block assembly thread");
+
+        Ok(CandidateBlockAssembly {
+            parent_block_id,
+            handle,
+        })
+    }
+
+    /// Assemble an anchored block from the mempool, without announcing it to the event observer.
+    /// Returns the block, the consumed execution budget, the block's size, and the cost of the
+    /// parent microblocks it confirms.
+    fn assemble_anchored_block(
+        chainstate_handle: &StacksChainState,
+        burn_dbconn: &SortitionDBConn,
+        mempool: &mut MemPoolDB,
+        parent_stacks_header: &StacksHeaderInfo,
+        total_burn: u64,
+        proof: VRFProof,
+        pubkey_hash: Hash160,
+        coinbase_tx: &StacksTransaction,
+        settings: BlockBuilderSettings,
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+    ) -> Result<(StacksBlock, ExecutionCost, u64, ExecutionCost), Error> {
         let mempool_settings = settings.mempool_settings;
         let max_miner_time_ms = settings.max_miner_time_ms;
 
@@ -1547,6 +2093,7 @@ impl StacksBlockBuilder {
         mempool.reset_last_known_nonces()?;
 
         mempool.estimate_tx_rates(100, &block_limit, &stacks_epoch_id)?;
+        mempool.begin_block(&block_limit, &stacks_epoch_id);
 
         let mut considered = HashSet::new(); // txids of all transactions we looked at
         let mut mined_origin_nonces: HashMap<StacksAddress, u64> = HashMap::new(); // map addrs of mined transaction origins to the nonces we used
@@ -1686,7 +2233,7 @@ impl StacksBlockBuilder {
             intermediate_result
         };
 
-        mempool.drop_txs(&invalidated_txs)?;
+        mempool.drop_txs(&invalidated_txs, MemPoolDropReason::TOO_EXPENSIVE)?;
         if let Some(observer) = event_observer {
             observer.mempool_txs_dropped(invalidated_txs, MemPoolDropReason::TOO_EXPENSIVE);
         }
@@ -1710,16 +2257,6 @@ impl StacksBlockBuilder {
 
         let ts_end = get_epoch_time_ms();
 
-        if let Some(observer) = event_observer {
-            observer.mined_block_event(
-                SortitionDB::get_canonical_burn_chain_tip(burn_dbconn.conn())?.block_height + 1,
-                &block,
-                size,
-                &consumed,
-                &confirmed_mblock_cost,
-            );
-        }
-
         debug!(
             "Miner: mined anchored block";
             "block_hash" => %block.block_hash(),
@@ -1736,7 +2273,7 @@ impl StacksBlockBuilder {
             })
         );
 
-        Ok((block, consumed, size))
+        Ok((block, consumed, size, confirmed_mblock_cost))
     }
 }
 
@@ -8116,6 +8653,333 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_build_anchored_blocks_iterative() {
+        let private_keys: Vec<_> = (0..10).map(|_| StacksPrivateKey::new()).collect();
+        let addresses: Vec<_> = private_keys
+            .iter()
+            .map(|sk| {
+                StacksAddress::from_public_keys(
+                    C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                    &AddressHashMode::SerializeP2PKH,
+                    1,
+                    &vec![StacksPublicKey::from_private(sk)],
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let initial_balances: Vec<_> = addresses
+            .iter()
+            .map(|addr| (addr.to_account_principal(), 100000000000))
+            .collect();
+
+        let mut peer_config = TestPeerConfig::new("build_anchored_iterative", 2040, 2041);
+        peer_config.initial_balances = initial_balances;
+
+        let mut peer = TestPeer::new(peer_config);
+
+        let chainstate_path = peer.chainstate_path.clone();
+
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let tip = SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+            .unwrap();
+
+        let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+            |ref mut miner,
+             ref mut sortdb,
+             ref mut chainstate,
+             vrf_proof,
+             ref parent_opt,
+             ref parent_microblock_header_opt| {
+                let parent_tip = match parent_opt {
+                    None => StacksChainState::get_genesis_header_info(chainstate.db()).unwrap(),
+                    Some(_) => panic!("Expected a genesis parent"),
+                };
+
+                let parent_header_hash = parent_tip.anchored_header.block_hash();
+                let parent_consensus_hash = parent_tip.consensus_hash.clone();
+                let coinbase_tx = make_coinbase(miner, 0);
+
+                for privk in private_keys.iter() {
+                    for tx_nonce in 0..5 {
+                        let tx = make_user_contract_publish(
+                            privk,
+                            tx_nonce,
+                            200 * (tx_nonce + 1),
+                            &format!("contract-{}", tx_nonce),
+                            "(define-data-var bar int 0)",
+                        );
+                        mempool
+                            .submit(
+                                chainstate,
+                                &parent_consensus_hash,
+                                &parent_header_hash,
+                                &tx,
+                                None,
+                                &ExecutionCost::max_value(),
+                                &StacksEpochId::Epoch20,
+                            )
+                            .unwrap();
+                    }
+                }
+
+                // the first round has no time to consider any transactions, but later rounds do
+                let mut settings = BlockBuilderSettings::limited();
+                settings.max_miner_time_ms = 0;
+                settings.mempool_settings.max_walk_time_ms = 0;
+
+                let anchored_block = StacksBlockBuilder::build_anchored_block_iterative(
+                    chainstate,
+                    &sortdb.index_conn(),
+                    &mut mempool,
+                    &parent_tip,
+                    tip.total_burn,
+                    vrf_proof,
+                    Hash160([0 as u8; 20]),
+                    &coinbase_tx,
+                    settings,
+                    get_epoch_time_ms() + 60_000,
+                    None,
+                    None,
+                )
+                .unwrap();
+                (anchored_block.0, vec![])
+            },
+        );
+
+        peer.next_burnchain_block(burn_ops.clone());
+        peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+
+        assert_eq!(stacks_block.txs.len(), 51);
+    }
+
+    #[test]
+    fn test_build_anchored_block_candidates() {
+        let private_keys: Vec<_> = (0..10).map(|_| StacksPrivateKey::new()).collect();
+        let addresses: Vec<_> = private_keys
+            .iter()
+            .map(|sk| {
+                StacksAddress::from_public_keys(
+                    C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                    &AddressHashMode::SerializeP2PKH,
+                    1,
+                    &vec![StacksPublicKey::from_private(sk)],
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let initial_balances: Vec<_> = addresses
+            .iter()
+            .map(|addr| (addr.to_account_principal(), 100000000000))
+            .collect();
+
+        let mut peer_config = TestPeerConfig::new("build_anchored_candidates", 2042, 2043);
+        peer_config.initial_balances = initial_balances;
+
+        let mut peer = TestPeer::new(peer_config);
+
+        let chainstate_path = peer.chainstate_path.clone();
+        let sortdb_path = peer.config.burnchain.get_db_path();
+
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let make_snapshot = |mempool: &MemPoolDB, name: &str| {
+            mempool
+                .snapshot(
+                    &format!("{}/{}.sqlite", &chainstate_path, name),
+                    Box::new(UnitEstimator),
+                    Box::new(UnitMetric),
+                )
+                .unwrap()
+        };
+
+        // first tenure: candidates on the same parent, assembled alongside the usual block
+        // assembly, have the same transactions
+        let tip = SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+            .unwrap();
+
+        let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+            |ref mut miner,
+             ref mut sortdb,
+             ref mut chainstate,
+             vrf_proof,
+             ref parent_opt,
+             ref parent_microblock_header_opt| {
+                let parent_tip = match parent_opt {
+                    None => StacksChainState::get_genesis_header_info(chainstate.db()).unwrap(),
+                    Some(_) => panic!("Expected a genesis parent"),
+                };
+
+                let parent_header_hash = parent_tip.anchored_header.block_hash();
+                let parent_consensus_hash = parent_tip.consensus_hash.clone();
+                let coinbase_tx = make_coinbase(miner, 0);
+
+                for privk in private_keys.iter() {
+                    let tx = make_user_contract_publish(
+                        privk,
+                        0,
+                        200,
+                        "contract",
+                        "(define-data-var bar int 0)",
+                    );
+                    mempool
+                        .submit(
+                            chainstate,
+                            &parent_consensus_hash,
+                            &parent_header_hash,
+                            &tx,
+                            None,
+                            &ExecutionCost::max_value(),
+                            &StacksEpochId::Epoch20,
+                        )
+                        .unwrap();
+                }
+
+                let candidate = AnchoredBlockCandidate {
+                    parent_stacks_header: parent_tip.clone(),
+                    total_burn: tip.total_burn,
+                    proof: vrf_proof.clone(),
+                    pubkey_hash: Hash160([0 as u8; 20]),
+                    coinbase_tx: coinbase_tx.clone(),
+                };
+                let assemblies: Vec<_> = (0..2)
+                    .map(|i| {
+                        StacksBlockBuilder::spawn_candidate_assembly(
+                            chainstate,
+                            &sortdb_path,
+                            make_snapshot(&mempool, &format!("candidate-{}", i)),
+                            candidate.clone(),
+                            BlockBuilderSettings::max_value(),
+                        )
+                        .unwrap()
+                    })
+                    .collect();
+
+                let anchored_block = StacksBlockBuilder::build_anchored_block(
+                    chainstate,
+                    &sortdb.index_conn(),
+                    &mut mempool,
+                    &parent_tip,
+                    tip.total_burn,
+                    vrf_proof,
+                    Hash160([0 as u8; 20]),
+                    &coinbase_tx,
+                    BlockBuilderSettings::max_value(),
+                    None,
+                )
+                .unwrap();
+
+                let txids: HashSet<_> = anchored_block.0.txs.iter().map(|tx| tx.txid()).collect();
+                let mut candidates = vec![];
+                for assembly in assemblies.into_iter() {
+                    assert_eq!(assembly.parent_block_id, parent_tip.index_block_hash());
+                    let candidate = assembly.join().unwrap();
+                    let candidate_txids: HashSet<_> =
+                        candidate.block.txs.iter().map(|tx| tx.txid()).collect();
+                    assert_eq!(candidate_txids, txids);
+                    candidates.push(candidate);
+                }
+
+                // mine one of the candidates instead of the usual block
+                (candidates.pop().unwrap().block, vec![])
+            },
+        );
+
+        peer.next_burnchain_block(burn_ops.clone());
+        peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+
+        assert_eq!(stacks_block.txs.len(), 11);
+        let (_, stacks_tip) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(peer.sortdb.as_ref().unwrap().conn())
+                .unwrap();
+        assert_eq!(stacks_tip, stacks_block.block_hash());
+
+        // second tenure: a candidate on a fork off of the genesis block can mine the transactions
+        // that the canonical fork already has
+        let tip = SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+            .unwrap();
+
+        let (_, stacks_block, _) = peer.make_tenure(
+            |ref mut miner,
+             ref mut sortdb,
+             ref mut chainstate,
+             vrf_proof,
+             ref parent_opt,
+             ref parent_microblock_header_opt| {
+                let parent_tip = match parent_opt {
+                    None => panic!("Expected a parent block"),
+                    Some(block) => {
+                        let ic = sortdb.index_conn();
+                        let snapshot = SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                            &ic,
+                            &tip.sortition_id,
+                            &block.block_hash(),
+                        )
+                        .unwrap()
+                        .unwrap();
+                        StacksChainState::get_anchored_block_header_info(
+                            chainstate.db(),
+                            &snapshot.consensus_hash,
+                            &snapshot.winning_stacks_block_hash,
+                        )
+                        .unwrap()
+                        .unwrap()
+                    }
+                };
+                let genesis_tip =
+                    StacksChainState::get_genesis_header_info(chainstate.db()).unwrap();
+
+                let fork_assembly = StacksBlockBuilder::spawn_candidate_assembly(
+                    chainstate,
+                    &sortdb_path,
+                    make_snapshot(&mempool, "candidate-fork"),
+                    AnchoredBlockCandidate {
+                        parent_stacks_header: genesis_tip.clone(),
+                        total_burn: tip.total_burn,
+                        proof: vrf_proof.clone(),
+                        pubkey_hash: Hash160([0 as u8; 20]),
+                        coinbase_tx: make_coinbase(miner, 0),
+                    },
+                    BlockBuilderSettings::max_value(),
+                )
+                .unwrap();
+
+                let anchored_block = StacksBlockBuilder::build_anchored_block(
+                    chainstate,
+                    &sortdb.index_conn(),
+                    &mut mempool,
+                    &parent_tip,
+                    tip.total_burn,
+                    vrf_proof,
+                    Hash160([0 as u8; 20]),
+                    &make_coinbase(miner, 1),
+                    BlockBuilderSettings::max_value(),
+                    None,
+                )
+                .unwrap();
+
+                assert_eq!(
+                    fork_assembly.parent_block_id,
+                    genesis_tip.index_block_hash()
+                );
+                let fork_candidate = fork_assembly.join().unwrap();
+                assert_eq!(fork_candidate.block.txs.len(), 11);
+                assert_eq!(
+                    fork_candidate.block.header.parent_block,
+                    genesis_tip.anchored_header.block_hash()
+                );
+
+                (anchored_block.0, vec![])
+            },
+        );
+
+        // nothing left to mine on the canonical fork
+        assert_eq!(stacks_block.txs.len(), 1);
+    }
+
     #[test]
     fn test_build_anchored_blocks_empty_chaintips() {
         let mut privks = vec![];
@@ -9695,6 +10559,7 @@ pub mod test {
             get_bulk_initial_balances: None,
             get_bulk_initial_names: None,
             get_bulk_initial_namespaces: None,
+            extra_boot_contracts: vec![],
         };
 
         StacksChainState::open_and_exec(mainnet, chain_id, &path, Some(&mut boot_data))
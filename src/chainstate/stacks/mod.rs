@@ -494,6 +494,10 @@ pub enum SinglesigHashMode {
 pub enum MultisigHashMode {
     P2SH = 0x01,
     P2WSH = 0x03,
+    /// Like P2SH and P2WSH, but each signer signs the same sighash, so signatures can be
+    /// gathered in any order and more than the required number may be given
+    OrderIndependentP2SH = 0x05,
+    OrderIndependentP2WSH = 0x07,
 }
 
 impl SinglesigHashMode {
@@ -526,6 +530,25 @@ impl MultisigHashMode {
         match *self {
             MultisigHashMode::P2SH => AddressHashMode::SerializeP2SH,
             MultisigHashMode::P2WSH => AddressHashMode::SerializeP2WSH,
+            MultisigHashMode::OrderIndependentP2SH => AddressHashMode::SerializeP2SH,
+            MultisigHashMode::OrderIndependentP2WSH => AddressHashMode::SerializeP2WSH,
+        }
+    }
+
+    pub fn is_order_independent(&self) -> bool {
+        match *self {
+            MultisigHashMode::OrderIndependentP2SH | MultisigHashMode::OrderIndependentP2WSH => {
+                true
+            }
+            MultisigHashMode::P2SH | MultisigHashMode::P2WSH => false,
+        }
+    }
+
+    /// P2WSH conditions may only use compressed public keys
+    pub fn requires_compressed_keys(&self) -> bool {
+        match *self {
+            MultisigHashMode::P2WSH | MultisigHashMode::OrderIndependentP2WSH => true,
+            MultisigHashMode::P2SH | MultisigHashMode::OrderIndependentP2SH => false,
         }
     }
 
@@ -541,6 +564,12 @@ impl MultisigHashMode {
         match n {
             x if x == MultisigHashMode::P2SH as u8 => Some(MultisigHashMode::P2SH),
             x if x == MultisigHashMode::P2WSH as u8 => Some(MultisigHashMode::P2WSH),
+            x if x == MultisigHashMode::OrderIndependentP2SH as u8 => {
+                Some(MultisigHashMode::OrderIndependentP2SH)
+            }
+            x if x == MultisigHashMode::OrderIndependentP2WSH as u8 => {
+                Some(MultisigHashMode::OrderIndependentP2WSH)
+            }
             _ => None,
         }
     }
@@ -833,6 +862,15 @@ pub struct StacksTransactionSigner {
     check_overlap: bool,
 }
 
+/// How the fee of a sponsored transaction is shared between its origin and its sponsor.  The
+/// sponsor always pays the whole `tx_fee` on-chain; `origin_share` is the part of it that the
+/// origin reimburses to the sponsor (e.g. with a transfer in the transaction's payload).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SponsoredFeeSplit {
+    pub tx_fee: u64,
+    pub origin_share: u64,
+}
+
 /// A block that contains blockchain-anchored data
 /// (corresponding to a LeaderBlockCommitOp)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::convert::TryFrom;
 use std::io;
 use std::io::prelude::*;
@@ -565,6 +566,43 @@ impl StacksTransaction {
         }
     }
 
+    /// Create a new sponsored transaction.  The origin signs it first, without committing to
+    /// who will sponsor it; the sponsor then sets its own spending condition, nonce and fee, and
+    /// signs it last (see `StacksTransactionSigner::new_sponsor()`).
+    pub fn new_sponsored(
+        version: TransactionVersion,
+        origin: TransactionSpendingCondition,
+        payload: TransactionPayload,
+    ) -> StacksTransaction {
+        let auth =
+            TransactionAuth::Sponsored(origin, TransactionSpendingCondition::new_initial_sighash());
+        StacksTransaction::new(version, auth, payload)
+    }
+
+    /// Complete this origin-signed sponsored transaction on behalf of the single-signature
+    /// (p2pkh) account of `sponsor_privk`, which pays `tx_fee` at nonce `sponsor_nonce`.
+    pub fn sign_sponsored_p2pkh(
+        &self,
+        sponsor_privk: &StacksPrivateKey,
+        sponsor_nonce: u64,
+        tx_fee: u64,
+    ) -> Result<StacksTransaction, Error> {
+        let mut sponsor_condition = TransactionSpendingCondition::new_singlesig_p2pkh(
+            StacksPublicKey::from_private(sponsor_privk),
+        )
+        .ok_or(Error::IncompatibleSpendingConditionError)?;
+        sponsor_condition.set_nonce(sponsor_nonce);
+        sponsor_condition.set_tx_fee(tx_fee);
+
+        let mut tx_signer = StacksTransactionSigner::new_sponsor(self, sponsor_condition)?;
+        tx_signer
+            .sign_sponsor(sponsor_privk)
+            .map_err(Error::NetError)?;
+        tx_signer
+            .get_tx()
+            .ok_or(Error::IncompatibleSpendingConditionError)
+    }
+
     /// Get fee rate
     pub fn get_tx_fee(&self) -> u64 {
         self.auth.get_tx_fee()
@@ -672,7 +710,12 @@ impl StacksTransaction {
                     },
                     next_sig,
                 );
-                Ok(next_sighash)
+                if cond.hash_mode.is_order_independent() {
+                    // every signer signs the same sighash
+                    Ok(cur_sighash.clone())
+                } else {
+                    Ok(next_sighash)
+                }
             }
             TransactionSpendingCondition::Singlesig(ref mut cond) => {
                 cond.set_signature(next_sig);
@@ -707,6 +750,92 @@ impl StacksTransaction {
         }
     }
 
+    /// Sign this transaction as one of the signers of an order-independent multisig condition,
+    /// without adding the signature to it.  Signers can do this in parallel, and in any order;
+    /// the signatures are then gathered with `set_order_independent_signatures()`.  If
+    /// `sponsor` is true, sign for the sponsor (the origin must have finished signing).
+    pub fn sign_order_independent(
+        &self,
+        privk: &StacksPrivateKey,
+        sponsor: bool,
+    ) -> Result<TransactionAuthField, net_error> {
+        let (condition, sighash, auth_flag) = self.order_independent_signing_state(sponsor)?;
+        let (sig, _) = TransactionSpendingCondition::next_signature(
+            &sighash,
+            &auth_flag,
+            condition.tx_fee(),
+            condition.nonce(),
+            privk,
+        )?;
+        let key_encoding = if privk.compress_public() {
+            TransactionPublicKeyEncoding::Compressed
+        } else {
+            TransactionPublicKeyEncoding::Uncompressed
+        };
+        Ok(TransactionAuthField::Signature(key_encoding, sig))
+    }
+
+    /// Fill in an order-independent multisig condition from the ordered list of its public keys
+    /// and the signatures gathered with `sign_order_independent()`, in any order.  If `sponsor`
+    /// is true, fill in the sponsor's condition.
+    pub fn set_order_independent_signatures(
+        &mut self,
+        pubkeys: &[StacksPublicKey],
+        signatures: &[TransactionAuthField],
+        sponsor: bool,
+    ) -> Result<(), net_error> {
+        let (_, sighash, auth_flag) = self.order_independent_signing_state(sponsor)?;
+        let condition = match (sponsor, &mut self.auth) {
+            (false, TransactionAuth::Standard(origin_condition)) => origin_condition,
+            (false, TransactionAuth::Sponsored(origin_condition, _)) => origin_condition,
+            (true, TransactionAuth::Sponsored(_, sponsor_condition)) => sponsor_condition,
+            (true, TransactionAuth::Standard(_)) => {
+                return Err(net_error::SigningError(
+                    "Cannot sign standard authorization with a sponsoring private key".to_string(),
+                ))
+            }
+        };
+        match condition {
+            TransactionSpendingCondition::Multisig(ref mut data) => {
+                data.set_order_independent_fields(&sighash, &auth_flag, pubkeys, signatures)
+            }
+            TransactionSpendingCondition::Singlesig(_) => Err(net_error::SigningError(
+                "Not a multisig condition".to_string(),
+            )),
+        }
+    }
+
+    /// The spending condition, sighash and auth flag an order-independent signer signs with
+    fn order_independent_signing_state(
+        &self,
+        sponsor: bool,
+    ) -> Result<(TransactionSpendingCondition, Txid, TransactionAuthFlags), net_error> {
+        let (condition, sighash, auth_flag) = if sponsor {
+            let sponsor_condition = self.auth.sponsor().ok_or_else(|| {
+                net_error::SigningError(
+                    "Cannot sign standard authorization with a sponsoring private key".to_string(),
+                )
+            })?;
+            (
+                sponsor_condition.clone(),
+                self.verify_origin()?,
+                TransactionAuthFlags::AuthSponsored,
+            )
+        } else {
+            (
+                self.auth.origin().clone(),
+                self.sign_begin(),
+                TransactionAuthFlags::AuthStandard,
+            )
+        };
+        if !condition.is_order_independent_multisig() {
+            return Err(net_error::SigningError(
+                "Not an order-independent multisig condition".to_string(),
+            ));
+        }
+        Ok((condition, sighash, auth_flag))
+    }
+
     /// Append the next signature from the origin account authorization.
     /// Return the next sighash.
     pub fn sign_next_origin(
@@ -855,6 +984,39 @@ impl StacksTransaction {
     }
 }
 
+impl SponsoredFeeSplit {
+    /// Split `tx_fee` so that the origin covers `origin_pct` percent of it (at most 100),
+    /// rounded down.
+    pub fn from_pct(tx_fee: u64, origin_pct: u8) -> SponsoredFeeSplit {
+        let origin_pct = cmp::min(origin_pct, 100) as u128;
+        SponsoredFeeSplit {
+            tx_fee,
+            origin_share: ((tx_fee as u128) * origin_pct / 100) as u64,
+        }
+    }
+
+    /// Split the fee for a `tx_len`-byte transaction at `fee_rate` microSTX per byte.  A
+    /// single-signature sponsor does not change the length of the origin-signed transaction.
+    pub fn from_fee_rate(tx_len: u64, fee_rate: u64, origin_pct: u8) -> SponsoredFeeSplit {
+        SponsoredFeeSplit::from_pct(tx_len.saturating_mul(fee_rate), origin_pct)
+    }
+
+    /// The part of the fee the sponsor bears itself
+    pub fn sponsor_share(&self) -> u64 {
+        self.tx_fee - self.origin_share
+    }
+
+    /// A post-condition the origin can sign over, so that it sends no more STX than its share
+    /// of the fee.  Only suitable if the payload does not otherwise send the origin's STX.
+    pub fn origin_post_condition(&self) -> TransactionPostCondition {
+        TransactionPostCondition::STX(
+            PostConditionPrincipal::Origin,
+            FungibleConditionCode::SentLe,
+            self.origin_share,
+        )
+    }
+}
+
 impl StacksTransactionSigner {
     pub fn new(tx: &StacksTransaction) -> StacksTransactionSigner {
         StacksTransactionSigner {
@@ -942,7 +1104,15 @@ impl StacksTransactionSigner {
 
     pub fn sign_sponsor(&mut self, privk: &StacksPrivateKey) -> Result<(), net_error> {
         match self.tx.auth {
-            TransactionAuth::Sponsored(_, ref sponsor_condition) => {
+            TransactionAuth::Sponsored(ref origin_condition, ref sponsor_condition) => {
+                if self.check_overlap
+                    && origin_condition.num_signatures() < origin_condition.signatures_required()
+                {
+                    // the sponsor signs over the origin's signatures, so it must go last
+                    return Err(net_error::SigningError(
+                        "Cannot sign sponsor before origin has finished signing".to_string(),
+                    ));
+                }
                 if self.check_oversign
                     && sponsor_condition.num_signatures() >= sponsor_condition.signatures_required()
                 {
@@ -1002,6 +1172,24 @@ impl StacksTransactionSigner {
         self.tx.clone()
     }
 
+    /// Get the sponsored transaction to hand to its sponsor, once the origin has finished
+    /// signing it.  Returns None if the transaction is not sponsored, if the origin still has
+    /// signatures to add, or if the sponsor has already started signing.
+    pub fn get_origin_signed_tx(&self) -> Option<StacksTransaction> {
+        match self.tx.auth {
+            TransactionAuth::Sponsored(ref origin_condition, _) => {
+                if !self.origin_done
+                    && origin_condition.num_signatures() >= origin_condition.signatures_required()
+                {
+                    Some(self.tx.clone())
+                } else {
+                    None
+                }
+            }
+            TransactionAuth::Standard(_) => None,
+        }
+    }
+
     pub fn get_tx(&self) -> Option<StacksTransaction> {
         if self.complete() {
             Some(self.tx.clone())
@@ -1015,6 +1203,7 @@ impl StacksTransactionSigner {
 mod test {
     use std::error::Error;
 
+    use chainstate::stacks::db::StacksChainState;
     use chainstate::stacks::test::codec_all_transactions;
     use chainstate::stacks::StacksPublicKey as PubKey;
     use chainstate::stacks::*;
@@ -3566,6 +3755,94 @@ mod test {
         }
     }
 
+    #[test]
+    fn tx_stacks_transaction_sign_sponsored_helpers() {
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let privk_sponsor = StacksPrivateKey::from_hex(
+            "807bbe9e471ac976592cc35e3056592ecc0f778ee653fced3b491a122dd8d59701",
+        )
+        .unwrap();
+
+        let fee_split = SponsoredFeeSplit::from_fee_rate(200, 10, 25);
+        assert_eq!(fee_split.tx_fee, 2000);
+        assert_eq!(fee_split.origin_share, 500);
+        assert_eq!(fee_split.sponsor_share(), 1500);
+        assert_eq!(SponsoredFeeSplit::from_pct(99, 200).sponsor_share(), 0);
+        assert_eq!(
+            SponsoredFeeSplit::from_pct(u64::MAX, 50).origin_share,
+            u64::MAX / 2
+        );
+
+        let mut tx = StacksTransaction::new_sponsored(
+            TransactionVersion::Mainnet,
+            TransactionSpendingCondition::new_singlesig_p2pkh(StacksPublicKey::from_private(
+                &privk,
+            ))
+            .unwrap(),
+            TransactionPayload::TokenTransfer(
+                PrincipalData::from(QualifiedContractIdentifier::transient()),
+                123,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        );
+        tx.set_origin_nonce(3);
+        tx.add_post_condition(fee_split.origin_post_condition());
+
+        let mut tx_signer = StacksTransactionSigner::new(&tx);
+        assert!(tx_signer.get_origin_signed_tx().is_none());
+
+        // the sponsor can't sign first
+        match tx_signer.sign_sponsor(&privk_sponsor) {
+            Err(net_error::SigningError(msg)) => assert_eq!(
+                &msg,
+                "Cannot sign sponsor before origin has finished signing"
+            ),
+            _ => panic!("Sponsor signed before origin"),
+        }
+
+        tx_signer.sign_origin(&privk).unwrap();
+        let origin_tx = tx_signer.get_origin_signed_tx().unwrap();
+        assert!(tx_signer.get_tx().is_none());
+
+        let signed_tx = origin_tx
+            .sign_sponsored_p2pkh(&privk_sponsor, 7, fee_split.tx_fee)
+            .unwrap();
+        signed_tx.verify().unwrap();
+        assert_eq!(signed_tx.get_origin_nonce(), 3);
+        assert_eq!(signed_tx.get_sponsor_nonce(), Some(7));
+        assert_eq!(signed_tx.get_tx_fee(), 2000);
+        assert_eq!(
+            signed_tx.sponsor_address().unwrap(),
+            StacksAddress {
+                version: C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+                bytes: Hash160::from_hex("3597aaa4bde720be93e3829aae24e76e7fcdfd3e").unwrap()
+            }
+        );
+        // a single-signature sponsor doesn't change the transaction's length
+        assert_eq!(
+            signed_tx.serialize_to_vec().len(),
+            origin_tx.serialize_to_vec().len()
+        );
+
+        // can't sponsor a transaction the origin hasn't signed, or a standard transaction
+        assert!(tx
+            .sign_sponsored_p2pkh(&privk_sponsor, 7, fee_split.tx_fee)
+            .is_err());
+        let mut standard_tx = tx.clone();
+        standard_tx.auth = TransactionAuth::from_p2pkh(&privk).unwrap();
+        let mut tx_signer = StacksTransactionSigner::new(&standard_tx);
+        tx_signer.sign_origin(&privk).unwrap();
+        assert!(tx_signer.get_origin_signed_tx().is_none());
+        assert!(tx_signer
+            .get_tx()
+            .unwrap()
+            .sign_sponsored_p2pkh(&privk_sponsor, 7, fee_split.tx_fee)
+            .is_err());
+    }
+
     #[test]
     fn tx_stacks_transaction_sign_verify_standard_p2pkh_uncompressed() {
         let privk = StacksPrivateKey::from_hex(
@@ -3752,6 +4029,143 @@ mod test {
         }
     }
 
+    #[test]
+    fn tx_stacks_transaction_sign_verify_order_independent_p2sh() {
+        let privk_1 = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let privk_2 = StacksPrivateKey::from_hex(
+            "2a584d899fed1d24e26b524f202763c8ab30260167429f157f1c119f550fa6af01",
+        )
+        .unwrap();
+        let privk_3 = StacksPrivateKey::from_hex(
+            "d5200dee706ee53ae98a03fba6cf4fdcc5084c30cfa9e1b3462dcdeaa3e0f1d201",
+        )
+        .unwrap();
+        let privk_sponsor = StacksPrivateKey::from_hex(
+            "807bbe9e471ac976592cc35e3056592ecc0f778ee653fced3b491a122dd8d59701",
+        )
+        .unwrap();
+
+        let pubk_1 = StacksPublicKey::from_private(&privk_1);
+        let pubk_2 = StacksPublicKey::from_private(&privk_2);
+        let pubk_3 = StacksPublicKey::from_private(&privk_3);
+        let pubkeys = vec![pubk_1.clone(), pubk_2.clone(), pubk_3.clone()];
+
+        let origin =
+            TransactionSpendingCondition::new_multisig_order_independent_p2sh(2, pubkeys.clone())
+                .unwrap();
+
+        // same address as an ordinary 2-of-3 p2sh condition
+        assert_eq!(
+            origin.address_mainnet(),
+            StacksAddress {
+                version: C32_ADDRESS_VERSION_MAINNET_MULTISIG,
+                bytes: Hash160::from_hex("a23ea89d6529ac48ac766f720e480beec7f19273").unwrap()
+            }
+        );
+
+        let origin_auth = TransactionAuth::Standard(origin.clone());
+        assert!(origin_auth.uses_order_independent_multisig());
+
+        for tx in tx_stacks_transaction_test_txs(&origin_auth) {
+            // signers sign independently, and their signatures are gathered in any order
+            let sig_3 = tx.sign_order_independent(&privk_3, false).unwrap();
+            let sig_1 = tx.sign_order_independent(&privk_1, false).unwrap();
+
+            let mut signed_tx = tx.clone();
+            signed_tx
+                .set_order_independent_signatures(&pubkeys, &[sig_3.clone(), sig_1.clone()], false)
+                .unwrap();
+            signed_tx.verify().unwrap();
+            assert_eq!(signed_tx.auth().origin().num_signatures(), 2);
+
+            // the signer makes the same signatures, whatever order the keys sign in
+            let mut tx_signer = StacksTransactionSigner::new(&tx);
+            tx_signer.sign_origin(&privk_3).unwrap();
+            tx_signer.sign_origin(&privk_1).unwrap();
+            assert_eq!(tx_signer.pop_origin_auth_field(), Some(sig_1.clone()));
+            assert_eq!(tx_signer.pop_origin_auth_field(), Some(sig_3.clone()));
+
+            // more signatures than required is fine
+            let sig_2 = tx.sign_order_independent(&privk_2, false).unwrap();
+            let mut all_signed_tx = tx.clone();
+            all_signed_tx
+                .set_order_independent_signatures(
+                    &pubkeys,
+                    &[sig_2.clone(), sig_1.clone(), sig_3.clone()],
+                    false,
+                )
+                .unwrap();
+            all_signed_tx.verify().unwrap();
+
+            let mut bytes = vec![];
+            all_signed_tx.consensus_serialize(&mut bytes).unwrap();
+            assert_eq!(
+                StacksTransaction::consensus_deserialize(&mut &bytes[..]).unwrap(),
+                all_signed_tx
+            );
+
+            // ...but fewer is not
+            let mut under_signed_tx = tx.clone();
+            under_signed_tx
+                .set_order_independent_signatures(&pubkeys, &[sig_2.clone()], false)
+                .unwrap();
+            assert!(under_signed_tx.verify().is_err());
+
+            // a signature from a key that isn't in the condition has nowhere to go
+            let sig_sponsor = tx.sign_order_independent(&privk_sponsor, false).unwrap();
+            assert!(tx
+                .clone()
+                .set_order_independent_signatures(&pubkeys, &[sig_1.clone(), sig_sponsor], false)
+                .is_err());
+
+            // ...and neither does a second signature from the same key
+            assert!(tx
+                .clone()
+                .set_order_independent_signatures(&pubkeys, &[sig_1.clone(), sig_1.clone()], false)
+                .is_err());
+
+            // not enabled in any epoch yet
+            assert!(StacksChainState::check_transaction_epoch(
+                StacksEpochId::Epoch2_05,
+                &signed_tx
+            )
+            .is_err());
+        }
+
+        // an order-independent sponsor signs after the origin
+        let sponsor =
+            TransactionSpendingCondition::new_multisig_order_independent_p2sh(2, pubkeys.clone())
+                .unwrap();
+        let mut tx = StacksTransaction::new_sponsored(
+            TransactionVersion::Testnet,
+            TransactionSpendingCondition::new_singlesig_p2pkh(StacksPublicKey::from_private(
+                &privk_sponsor,
+            ))
+            .unwrap(),
+            TransactionPayload::TokenTransfer(
+                PrincipalData::from(QualifiedContractIdentifier::transient()),
+                123,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        );
+        assert!(tx.sign_order_independent(&privk_2, true).is_err());
+
+        let mut tx_signer = StacksTransactionSigner::new(&tx);
+        tx_signer.sign_origin(&privk_sponsor).unwrap();
+        tx = tx_signer.get_origin_signed_tx().unwrap();
+        tx.auth.set_sponsor(sponsor).unwrap();
+        tx.set_tx_fee(100);
+
+        let sig_2 = tx.sign_order_independent(&privk_2, true).unwrap();
+        let sig_1 = tx.sign_order_independent(&privk_1, true).unwrap();
+        tx.set_order_independent_signatures(&pubkeys, &[sig_2, sig_1], true)
+            .unwrap();
+        tx.verify().unwrap();
+    }
+
     #[test]
     fn tx_stacks_transaction_sign_verify_standard_p2sh() {
         let privk_1 = StacksPrivateKey::from_hex(
@@ -30,19 +30,21 @@ use rusqlite::Transaction;
 use rusqlite::{Connection, OpenFlags, NO_PARAMS};
 
 use address::c32::c32_address;
+use chainstate::burn::ConsensusHash;
 use chainstate::stacks::index::{storage::TrieFileStorage, MarfTrieId};
 use util::db::sqlite_open;
 use util::db::FromColumn;
-use util::hash::{bytes_to_hex, Sha512Trunc256Sum};
+use util::hash::{bytes_to_hex, Hash160, Sha512Trunc256Sum};
 
 use util::log;
-use vm::ContractName;
+use vm::{ClarityName, ContractName};
 
 use vm::analysis;
 use vm::analysis::contract_interface_builder::build_contract_interface;
 use vm::analysis::{errors::CheckError, errors::CheckResult, AnalysisDatabase, ContractAnalysis};
 use vm::ast::build_ast;
 use vm::contexts::{AssetMap, OwnedEnvironment};
+use vm::costs::proposals::{get_cost_proposal_state, CostProposal};
 use vm::costs::ExecutionCost;
 use vm::costs::LimitedCostTracker;
 use vm::database::{
@@ -114,6 +116,8 @@ where command is one of:
   repl               to typecheck and evaluate expressions in a stdin/stdout loop.
   execute            to execute a public function of a defined contract.
   generate_address   to generate a random Stacks public address for testing purposes.
+  make_cost_proposal to check a cost-voting proposal and serialize its submit-proposal payload.
+  cost_proposal_state  to show the votes, vetos and validity of a cost-voting proposal.
 ",
         invoked_by
     );
@@ -408,7 +412,7 @@ where
 ///  for program evaluation, not by consensus critical code.
 pub fn vm_execute(program: &str) -> Result<Option<Value>, Error> {
     let contract_id = QualifiedContractIdentifier::transient();
-    let mut contract_context = ContractContext::new(contract_id.clone());
+    let mut contract_context = ContractContext::new(contract_id.clone(), DEFAULT_CLI_EPOCH);
     let mut marf = MemoryBackingStore::new();
     let conn = marf.as_clarity_db();
     let mut global_context = GlobalContext::new(
@@ -625,6 +629,16 @@ impl HeadersDB for CLIHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        let conn = self.conn();
+        if let Some(_) = get_cli_block_height(&conn, id_bhh) {
+            // mock it
+            let hash_bytes = Hash160::from_data(&id_bhh.0);
+            Some(ConsensusHash(hash_bytes.0))
+        } else {
+            None
+        }
+    }
 }
 
 fn get_eval_input(invoked_by: &str, args: &[String]) -> EvalInput {
@@ -1579,6 +1593,139 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 }
             }
         }
+        "make_cost_proposal" => {
+            if args.len() != 6 {
+                eprintln!(
+                    "Usage: {} {} [function-contract] [function-name] [cost-function-contract] [cost-function-name] [vm/clarity dir]",
+                    invoked_by, &args[0]
+                );
+                panic_test!();
+            }
+
+            let proposal = CostProposal {
+                function_contract: friendly_expect(
+                    QualifiedContractIdentifier::parse(&args[1]),
+                    "Failed to parse function contract identifier.",
+                ),
+                function_name: friendly_expect(
+                    ClarityName::try_from(args[2].clone()),
+                    "Failed to parse function name.",
+                ),
+                cost_function_contract: friendly_expect(
+                    QualifiedContractIdentifier::parse(&args[3]),
+                    "Failed to parse cost function contract identifier.",
+                ),
+                cost_function_name: friendly_expect(
+                    ClarityName::try_from(args[4].clone()),
+                    "Failed to parse cost function name.",
+                ),
+            };
+
+            let vm_filename = &args[5];
+            let header_db =
+                friendly_expect(CLIHeadersDB::resume(vm_filename), "Failed to open CLI DB");
+            let marf_kv = friendly_expect(
+                MarfedKV::open(vm_filename, None),
+                "Failed to open VM database.",
+            );
+            let mainnet = header_db.is_mainnet();
+            let validation = at_chaintip(vm_filename, marf_kv, |mut marf| {
+                let mut db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
+                db.begin();
+                let validation = proposal.validate(mainnet, &mut db);
+                db.roll_back();
+                (marf, validation)
+            });
+
+            match validation {
+                Ok(()) => {
+                    let payload = proposal.submit_payload(mainnet);
+                    (
+                        0,
+                        Some(json!({
+                            "proposal": serde_json::to_value(&proposal).unwrap(),
+                            "payload_serialized": bytes_to_hex(&payload.serialize_to_vec()),
+                            "success": true,
+                        })),
+                    )
+                }
+                Err(error) => (
+                    1,
+                    Some(json!({
+                        "proposal": serde_json::to_value(&proposal).unwrap(),
+                        "error": {
+                            "proposal": error
+                        },
+                        "success": false,
+                    })),
+                ),
+            }
+        }
+        "cost_proposal_state" => {
+            if args.len() != 3 {
+                eprintln!(
+                    "Usage: {} {} [proposal-id] [vm/clarity dir]",
+                    invoked_by, &args[0]
+                );
+                panic_test!();
+            }
+
+            let proposal_id: u128 =
+                friendly_expect(args[1].parse(), "Failed to parse proposal ID.");
+            let vm_filename = &args[2];
+            let header_db =
+                friendly_expect(CLIHeadersDB::resume(vm_filename), "Failed to open CLI DB");
+            let marf_kv = friendly_expect(
+                MarfedKV::open(vm_filename, None),
+                "Failed to open VM database.",
+            );
+            let mainnet = header_db.is_mainnet();
+            let result = at_chaintip(vm_filename, marf_kv, |mut marf| {
+                let mut db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
+                db.begin();
+                let result = get_cost_proposal_state(mainnet, &mut db, proposal_id).map(|state| {
+                    let validation = state.as_ref().map(|state| match state.proposal() {
+                        Some(proposal) => proposal.validate(mainnet, &mut db),
+                        None => Err("proposal does not name contract functions".to_string()),
+                    });
+                    (state, validation)
+                });
+                db.roll_back();
+                (marf, result)
+            });
+
+            match result {
+                Ok((Some(state), Some(validation))) => {
+                    let mut result_json = json!({
+                        "state": serde_json::to_value(&state).unwrap(),
+                        "valid": validation.is_ok(),
+                        "success": true,
+                    });
+                    if let Err(error) = validation {
+                        result_json["invalid_reason"] = serde_json::to_value(error).unwrap();
+                    }
+                    (0, Some(result_json))
+                }
+                Ok(_) => (
+                    1,
+                    Some(json!({
+                        "error": {
+                            "proposal": format!("No such proposal: {}", proposal_id)
+                        },
+                        "success": false,
+                    })),
+                ),
+                Err(error) => (
+                    1,
+                    Some(json!({
+                        "error": {
+                            "runtime": error
+                        },
+                        "success": false,
+                    })),
+                ),
+            }
+        }
         _ => {
             print_usage(invoked_by);
             (1, None)
@@ -2047,4 +2194,170 @@ mod test {
                 })
         );
     }
+
+    #[test]
+    fn test_cost_proposals() {
+        let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
+        let cost_clar = format!("/tmp/cost-definer_{}.clar", rand::thread_rng().gen::<i32>());
+        let target_clar = format!("/tmp/intercepted_{}.clar", rand::thread_rng().gen::<i32>());
+
+        fs::write(
+            &cost_clar,
+            r#"
+(define-read-only (cost-definition (size uint))
+   { runtime: u1, write_length: u0, write_count: u0, read_count: u0, read_length: u0 })
+(define-read-only (cost-definition-multi-arg (a uint) (b uint))
+   { runtime: u1, write_length: u0, write_count: u0, read_count: u0, read_length: u0 })
+(define-read-only (not-a-cost (size uint))
+   { runtime: u1 })
+"#,
+        )
+        .unwrap();
+        fs::write(
+            &target_clar,
+            r#"
+(define-read-only (intercepted-function (a uint) (b uint))
+   (+ a b))
+(define-public (public-function (a uint))
+   (ok a))
+"#,
+        )
+        .unwrap();
+
+        invoke_command("test", &["initialize".to_string(), db_name.clone()]);
+        for (name, path) in [("cost-definer", &cost_clar), ("intercepted", &target_clar)].iter() {
+            let invoked = invoke_command(
+                "test",
+                &[
+                    "launch".to_string(),
+                    format!("S1G2081040G2081040G2081040G208105NK8PE5.{}", name),
+                    path.to_string(),
+                    db_name.clone(),
+                ],
+            );
+            assert_eq!(invoked.0, 0);
+        }
+
+        let make_proposal =
+            |function_contract: &str, function_name: &str, cost_function_name: &str| {
+                invoke_command(
+                    "test",
+                    &[
+                        "make_cost_proposal".to_string(),
+                        function_contract.to_string(),
+                        function_name.to_string(),
+                        "S1G2081040G2081040G2081040G208105NK8PE5.cost-definer".to_string(),
+                        cost_function_name.to_string(),
+                        db_name.clone(),
+                    ],
+                )
+            };
+
+        // replacing a Clarity cost function
+        let (exit, result) = make_proposal(
+            "SP000000000000000000002Q6VF78.costs",
+            "cost_add",
+            "cost-definition",
+        );
+        let result = result.unwrap();
+        assert_eq!(exit, 0);
+        assert_eq!(result["success"], true);
+        assert!(result["payload_serialized"].as_str().unwrap().len() > 0);
+
+        // replacing a user-defined function
+        let (exit, _) = make_proposal(
+            "S1G2081040G2081040G2081040G208105NK8PE5.intercepted",
+            "intercepted-function",
+            "cost-definition-multi-arg",
+        );
+        assert_eq!(exit, 0);
+
+        for (function_contract, function_name, cost_function_name, reason) in [
+            (
+                "SP000000000000000000002Q6VF78.costs",
+                "cost_not_a_function",
+                "cost-definition",
+                "does not reference a Clarity cost function",
+            ),
+            (
+                "SP000000000000000000002Q6VF78.costs",
+                "cost_add",
+                "cost-definition-multi-arg",
+                "must take exactly one argument",
+            ),
+            (
+                "SP000000000000000000002Q6VF78.costs",
+                "cost_add",
+                "not-a-cost",
+                "not a cost tuple",
+            ),
+            (
+                "S1G2081040G2081040G2081040G208105NK8PE5.intercepted",
+                "intercepted-function",
+                "cost-definition",
+                "takes 1 arguments, but function-name intercepted-function takes 2",
+            ),
+            (
+                "S1G2081040G2081040G2081040G208105NK8PE5.intercepted",
+                "public-function",
+                "cost-definition",
+                "is not read-only",
+            ),
+        ]
+        .iter()
+        {
+            let (exit, result) =
+                make_proposal(function_contract, function_name, cost_function_name);
+            let result = result.unwrap();
+            assert_eq!(exit, 1);
+            assert_eq!(result["success"], false);
+            assert!(result["error"]["proposal"]
+                .as_str()
+                .unwrap()
+                .contains(reason));
+        }
+
+        // no proposals submitted yet
+        let (exit, _) = invoke_command(
+            "test",
+            &[
+                "cost_proposal_state".to_string(),
+                "0".to_string(),
+                db_name.clone(),
+            ],
+        );
+        assert_eq!(exit, 1);
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "execute".to_string(),
+                db_name.clone(),
+                "SP000000000000000000002Q6VF78.cost-voting".to_string(),
+                "submit-proposal".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5".to_string(),
+                "'SP000000000000000000002Q6VF78.costs".to_string(),
+                "\"cost_add\"".to_string(),
+                "'S1G2081040G2081040G2081040G208105NK8PE5.cost-definer".to_string(),
+                "\"cost-definition\"".to_string(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+
+        let (exit, result) = invoke_command(
+            "test",
+            &[
+                "cost_proposal_state".to_string(),
+                "0".to_string(),
+                db_name.clone(),
+            ],
+        );
+        let result = result.unwrap();
+        assert_eq!(exit, 0);
+        assert_eq!(result["valid"], true);
+        assert_eq!(result["state"]["function_name"], "cost_add");
+        assert_eq!(result["state"]["votes"], 0);
+        assert_eq!(result["state"]["vetos"], 0);
+        assert!(result["state"]["confirmed_id"].is_null());
+    }
 }
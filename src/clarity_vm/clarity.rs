@@ -36,7 +36,7 @@ use vm::analysis::{errors::CheckError, errors::CheckErrors, ContractAnalysis};
 use vm::ast;
 use vm::ast::{errors::ParseError, errors::ParseErrors, ContractAST};
 use vm::contexts::{AssetMap, Environment, OwnedEnvironment};
-use vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
+use vm::costs::{CostBreakdown, CostTracker, ExecutionCost, LimitedCostTracker};
 use vm::database::{
     BurnStateDB, ClarityDatabase, HeadersDB, RollbackWrapper, RollbackWrapperPersistedLog,
     STXBalance, SqliteConnection, NULL_BURN_STATE_DB, NULL_HEADER_DB,
@@ -57,7 +57,7 @@ use crate::chainstate::stacks::TransactionSmartContract;
 use crate::chainstate::stacks::TransactionSpendingCondition;
 use crate::chainstate::stacks::TransactionVersion;
 use crate::clarity_vm::database::marf::ReadOnlyMarfStore;
-use crate::clarity_vm::database::marf::{MarfedKV, WritableMarfStore};
+use crate::clarity_vm::database::marf::{MarfedKV, RecordedReads, WritableMarfStore};
 use crate::core::StacksEpoch;
 use crate::core::StacksEpochId;
 use crate::core::FIRST_STACKS_BLOCK_ID;
@@ -87,6 +87,9 @@ use types::chainstate::BurnchainHeaderHash;
 pub struct ClarityInstance {
     datastore: MarfedKV,
     mainnet: bool,
+    /// if true, blocks begun on this instance attribute their runtime cost to cost functions and
+    /// contract-call edges
+    cost_breakdown: bool,
 }
 
 ///
@@ -269,7 +272,11 @@ impl ClarityBlockConnection<'_> {
 
 impl ClarityInstance {
     pub fn new(mainnet: bool, datastore: MarfedKV) -> ClarityInstance {
-        ClarityInstance { datastore, mainnet }
+        ClarityInstance {
+            datastore,
+            mainnet,
+            cost_breakdown: false,
+        }
     }
 
     pub fn with_marf<F, R>(&mut self, f: F) -> R
@@ -283,6 +290,38 @@ impl ClarityInstance {
         self.mainnet
     }
 
+    /// Detach (or re-attach) the underlying datastore.  Blocks begun on a detached instance can
+    /// be assembled alongside blocks begun on other instances, but can only be dropped, never
+    /// committed.  See `MarfedKV::set_detached`.
+    pub fn set_detached(&mut self, detached: bool) {
+        self.datastore.set_detached(detached)
+    }
+
+    pub fn is_detached(&self) -> bool {
+        self.datastore.is_detached()
+    }
+
+    /// Have the cost trackers of blocks begun on this instance break down their runtime cost.
+    /// See `LimitedCostTracker::enable_cost_breakdown`.
+    pub fn set_cost_breakdown(&mut self, cost_breakdown: bool) {
+        self.cost_breakdown = cost_breakdown
+    }
+
+    pub fn has_cost_breakdown(&self) -> bool {
+        self.cost_breakdown
+    }
+
+    /// Start recording the parent state read by the blocks begun on this instance.
+    /// See `MarfedKV::start_recording`.
+    pub fn start_recording_reads(&mut self) {
+        self.datastore.start_recording()
+    }
+
+    /// Stop recording, and return what was recorded
+    pub fn take_recorded_reads(&mut self) -> Option<RecordedReads> {
+        self.datastore.take_recording()
+    }
+
     /// Returns the Stacks epoch of the burn block that elected `stacks_block`
     fn get_epoch_of(
         stacks_block: &StacksBlockId,
@@ -321,15 +360,17 @@ impl ClarityInstance {
         let epoch = Self::get_epoch_of(current, header_db, burn_state_db);
         let cost_track = {
             let mut clarity_db = datastore.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
-            Some(
-                LimitedCostTracker::new(
-                    self.mainnet,
-                    epoch.block_limit.clone(),
-                    &mut clarity_db,
-                    epoch.epoch_id,
-                )
-                .expect("FAIL: problem instantiating cost tracking"),
+            let mut cost_track = LimitedCostTracker::new(
+                self.mainnet,
+                epoch.block_limit.clone(),
+                &mut clarity_db,
+                epoch.epoch_id,
             )
+            .expect("FAIL: problem instantiating cost tracking");
+            if self.cost_breakdown {
+                cost_track.enable_cost_breakdown();
+            }
+            Some(cost_track)
         };
 
         ClarityBlockConnection {
@@ -459,15 +500,17 @@ impl ClarityInstance {
 
         let cost_track = {
             let mut clarity_db = datastore.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
-            Some(
-                LimitedCostTracker::new(
-                    self.mainnet,
-                    epoch.block_limit.clone(),
-                    &mut clarity_db,
-                    epoch.epoch_id,
-                )
-                .expect("FAIL: problem instantiating cost tracking"),
+            let mut cost_track = LimitedCostTracker::new(
+                self.mainnet,
+                epoch.block_limit.clone(),
+                &mut clarity_db,
+                epoch.epoch_id,
             )
+            .expect("FAIL: problem instantiating cost tracking");
+            if self.cost_breakdown {
+                cost_track.enable_cost_breakdown();
+            }
+            Some(cost_track)
         };
 
         ClarityBlockConnection {
@@ -876,6 +919,7 @@ impl<'a, 'b> ClarityConnection for ClarityTransactionConnection<'a, 'b> {
 
 impl<'a, 'b> Drop for ClarityTransactionConnection<'a, 'b> {
     fn drop(&mut self) {
+        self.store.clear_at_block_cache();
         if thread::panicking() {
             // if the thread is panicking, we've likely lost our cost_tracker handle,
             //  so don't expect() one, or we'll end up panicking while panicking.
@@ -932,6 +976,15 @@ impl<'a, 'b> ClarityTransactionConnection<'a, 'b> {
         })
     }
 
+    /// Get the runtime cost attributed since the last call, and start afresh.
+    /// Returns None if the block's cost tracker isn't breaking down its cost.
+    pub fn take_cost_breakdown(&mut self) -> Option<CostBreakdown> {
+        self.cost_track
+            .as_mut()
+            .expect("BUG: Transaction connection lost cost_tracker handle.")
+            .take_cost_breakdown()
+    }
+
     /// What's our total (block-wide) resource use so far?
     pub fn cost_so_far(&self) -> ExecutionCost {
         match self.cost_track {
@@ -1208,6 +1261,7 @@ mod tests {
 
     use rusqlite::NO_PARAMS;
 
+    use chainstate::burn::ConsensusHash;
     use chainstate::stacks::index::storage::TrieFileStorage;
     use vm::analysis::errors::CheckErrors;
     use vm::database::{ClarityBackingStore, STXBalance};
@@ -1907,6 +1961,21 @@ mod tests {
                 None
             }
 
+            fn get_burn_header_timestamp(
+                &self,
+                _height: u32,
+                _sortition_id: &SortitionId,
+            ) -> Option<u64> {
+                None
+            }
+
+            fn get_sortition_id_from_consensus_hash(
+                &self,
+                _consensus_hash: &ConsensusHash,
+            ) -> Option<SortitionId> {
+                None
+            }
+
             fn get_stacks_epoch(&self, _height: u32) -> Option<StacksEpoch> {
                 // Note: We return this StacksEpoch for every input, because this test is not exercising
                 // this method.
@@ -1,8 +1,12 @@
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 use rusqlite::Connection;
 
-use chainstate::stacks::index::marf::{MarfConnection, MarfTransaction, MARF};
+use chainstate::stacks::index::marf::{
+    MarfConnection, MarfTransaction, BLOCK_HASH_TO_HEIGHT_MAPPING_KEY,
+    BLOCK_HEIGHT_TO_HASH_MAPPING_KEY, MARF, OWN_BLOCK_HEIGHT_KEY,
+};
 use chainstate::stacks::index::{Error, MarfTrieId};
 use core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
 use util::db::IndexDBConn;
@@ -27,6 +31,66 @@ use crate::types::proof::{ClarityMarfTrieId, TrieHash, TrieMerkleProof};
 pub struct MarfedKV {
     chain_tip: StacksBlockId,
     marf: MARF<StacksBlockId>,
+    /// what blocks begun on this store have read, while recording
+    recorded_reads: Option<RecordedReads>,
+}
+
+/// The parent state a block read while it was being processed: enough to rebuild that state in
+/// an empty store and process the block again.  See `MarfedKV::start_recording`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedReads {
+    /// The first value read for each key that the block had not yet written itself
+    pub values: Vec<(String, Option<String>)>,
+    /// The ancestor block IDs looked up by height
+    pub block_heights: Vec<(u32, Option<StacksBlockId>)>,
+    /// Contract metadata read, as (block ID, contract, key, value)
+    pub metadata: Vec<(StacksBlockId, String, String, Option<String>)>,
+    /// Whether or not state was also read as of some other block (i.e. with `at-block`).
+    /// Such reads are not recorded.
+    pub read_other_blocks: bool,
+    #[serde(skip)]
+    seen_keys: HashSet<String>,
+    #[serde(skip)]
+    written_keys: HashSet<String>,
+}
+
+impl RecordedReads {
+    fn record_value(&mut self, key: &str, value: &Option<String>) {
+        if self.written_keys.contains(key) || self.seen_keys.contains(key) {
+            return;
+        }
+        self.seen_keys.insert(key.to_string());
+        self.values.push((key.to_string(), value.clone()));
+    }
+
+    fn record_block_height(&mut self, height: u32, block_id: &Option<StacksBlockId>) {
+        if self.block_heights.iter().any(|(h, _)| *h == height) {
+            return;
+        }
+        self.block_heights.push((height, block_id.clone()));
+    }
+
+    fn record_metadata(
+        &mut self,
+        bhh: &StacksBlockId,
+        contract: &str,
+        key: &str,
+        value: &Option<String>,
+    ) {
+        if self
+            .metadata
+            .iter()
+            .any(|(b, c, k, _)| b == bhh && c == contract && k == key)
+        {
+            return;
+        }
+        self.metadata.push((
+            bhh.clone(),
+            contract.to_string(),
+            key.to_string(),
+            value.clone(),
+        ));
+    }
 }
 
 impl MarfedKV {
@@ -73,7 +137,11 @@ impl MarfedKV {
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV {
+            marf,
+            chain_tip,
+            recorded_reads: None,
+        })
     }
 
     pub fn open_unconfirmed(
@@ -86,7 +154,11 @@ impl MarfedKV {
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV {
+            marf,
+            chain_tip,
+            recorded_reads: None,
+        })
     }
 
     // used by benchmarks
@@ -108,7 +180,11 @@ impl MarfedKV {
 
         let chain_tip = StacksBlockId::sentinel();
 
-        MarfedKV { marf, chain_tip }
+        MarfedKV {
+            marf,
+            chain_tip,
+            recorded_reads: None,
+        }
     }
 
     pub fn begin_read_only<'a>(
@@ -130,6 +206,7 @@ impl MarfedKV {
         ReadOnlyMarfStore {
             chain_tip,
             marf: &mut self.marf,
+            at_block_cache: AtBlockCache::default(),
         }
     }
 
@@ -154,6 +231,7 @@ impl MarfedKV {
         Ok(ReadOnlyMarfStore {
             chain_tip,
             marf: &mut self.marf,
+            at_block_cache: AtBlockCache::default(),
         })
     }
 
@@ -171,6 +249,15 @@ impl MarfedKV {
         current: &StacksBlockId,
         next: &StacksBlockId,
     ) -> WritableMarfStore<'a> {
+        let detached_side_store = if self.marf.is_detached() {
+            Some(
+                SqliteConnection::memory()
+                    .expect("ERROR: Failed to open side storage for detached MARF block"),
+            )
+        } else {
+            None
+        };
+
         let mut tx = self.marf.begin_tx().expect(&format!(
             "ERROR: Failed to begin new MARF block {} - {})",
             current, next
@@ -188,6 +275,9 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            detached_side_store,
+            recorded_reads: self.recorded_reads.as_mut(),
+            at_block_cache: AtBlockCache::default(),
         }
     }
 
@@ -209,9 +299,93 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            detached_side_store: None,
+            recorded_reads: None,
+            at_block_cache: AtBlockCache::default(),
         }
     }
 
+    /// Detach (or re-attach) this store.  Blocks begun on a detached store read from a snapshot
+    /// of the MARF and its side storage, keep everything they write in memory, and are dropped
+    /// instead of committed.  This lets several blocks be assembled at once, even on top of
+    /// different parents.
+    pub fn set_detached(&mut self, detached: bool) {
+        self.marf.set_detached(detached)
+    }
+
+    pub fn is_detached(&self) -> bool {
+        self.marf.is_detached()
+    }
+
+    /// Start recording what blocks begun on this store read from their parents' state.
+    /// Only confirmed (i.e. not unconfirmed) blocks are recorded.
+    pub fn start_recording(&mut self) {
+        self.recorded_reads = Some(RecordedReads::default());
+    }
+
+    /// Stop recording, and return what was recorded since `start_recording()`
+    pub fn take_recording(&mut self) -> Option<RecordedReads> {
+        self.recorded_reads.take()
+    }
+
+    /// Build block `block_id` in this otherwise-empty store, holding just the state in `reads`
+    /// as of block height `block_height`, so that a block recorded on top of it can be processed
+    /// again without the rest of the chain's state.
+    pub fn load_recorded_state(
+        &mut self,
+        block_id: &StacksBlockId,
+        block_height: u32,
+        reads: &RecordedReads,
+    ) -> InterpreterResult<()> {
+        let mut store = self.begin(&StacksBlockId::sentinel(), block_id);
+        store.put_all(
+            reads
+                .values
+                .iter()
+                .filter_map(|(key, value)| value.clone().map(|value| (key.clone(), value)))
+                .collect(),
+        );
+
+        let mut keys = vec![
+            OWN_BLOCK_HEIGHT_KEY.to_string(),
+            format!("{}::{}", BLOCK_HASH_TO_HEIGHT_MAPPING_KEY, block_id),
+            format!("{}::{}", BLOCK_HEIGHT_TO_HASH_MAPPING_KEY, block_height),
+        ];
+        let mut values = vec![
+            MARFValue::from(block_height),
+            MARFValue::from(block_height),
+            MARFValue::from(block_id.clone()),
+        ];
+        for (height, ancestor) in reads.block_heights.iter() {
+            if let Some(ancestor) = ancestor {
+                if *height < block_height {
+                    keys.push(format!("{}::{}", BLOCK_HEIGHT_TO_HASH_MAPPING_KEY, height));
+                    values.push(MARFValue::from(ancestor.clone()));
+                }
+            }
+        }
+        store
+            .marf
+            .insert_batch(&keys, values)
+            .map_err(|err| InterpreterError::MarfFailure(IncomparableError { err }))?;
+
+        for (bhh, contract, key, value) in reads.metadata.iter() {
+            if let Some(value) = value {
+                SqliteConnection::insert_metadata(
+                    store.marf.sqlite_tx(),
+                    bhh,
+                    contract,
+                    key,
+                    value,
+                );
+            }
+        }
+
+        store.commit_to(block_id);
+        self.chain_tip = block_id.clone();
+        Ok(())
+    }
+
     pub fn get_chain_tip(&self) -> &StacksBlockId {
         &self.chain_tip
     }
@@ -245,14 +419,100 @@ impl MarfedKV {
     }
 }
 
+/// The most bytes of keys and values read at historical blocks that a store keeps cached
+pub const AT_BLOCK_CACHE_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+/// The historical blocks `at-block` has resolved while a store is open, and the values read at
+/// them.  A block's ancestry and state can't change while a transaction is being processed, so
+/// repeated `at-block`s and reads at the same block don't need to go back to the MARF.
+#[derive(Default)]
+struct AtBlockCache {
+    /// blocks already checked to be ancestors of the open block
+    ancestors: HashSet<StacksBlockId>,
+    /// values read at those blocks
+    values: HashMap<StacksBlockId, HashMap<String, Option<String>>>,
+    values_bytes: usize,
+}
+
+impl AtBlockCache {
+    /// Check that `bhh` is an ancestor of the block `marf` is open to, unless it already has
+    ///  been.
+    fn check_ancestor_block_hash<M: MarfConnection<StacksBlockId>>(
+        &mut self,
+        marf: &mut M,
+        bhh: &StacksBlockId,
+    ) -> InterpreterResult<()> {
+        if self.ancestors.contains(bhh) {
+            return Ok(());
+        }
+
+        marf.check_ancestor_block_hash(bhh).map_err(|e| match e {
+            Error::NotFoundError => {
+                test_debug!("No such block {:?} (NotFoundError)", &bhh);
+                RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
+            }
+            Error::NonMatchingForks(_bh1, _bh2) => {
+                test_debug!(
+                    "No such block {:?} (NonMatchingForks({}, {}))",
+                    &bhh,
+                    BlockHeaderHash(_bh1),
+                    BlockHeaderHash(_bh2)
+                );
+                RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
+            }
+            _ => panic!("ERROR: Unexpected MARF failure: {}", e),
+        })?;
+
+        if *bhh != marf.with_conn(|conn| conn.get_cur_block()) {
+            self.ancestors.insert(bhh.clone());
+        }
+        Ok(())
+    }
+
+    fn get_value(&self, block: &StacksBlockId, key: &str) -> Option<Option<String>> {
+        self.values
+            .get(block)
+            .and_then(|values| values.get(key))
+            .cloned()
+    }
+
+    /// Cache `value`, if it was read at a historical block and there's room for it
+    fn insert_value(&mut self, block: &StacksBlockId, key: &str, value: &Option<String>) {
+        if !self.ancestors.contains(block) {
+            return;
+        }
+        let bytes = key.len() + value.as_ref().map(|v| v.len()).unwrap_or(0);
+        if self.values_bytes + bytes > AT_BLOCK_CACHE_MAX_BYTES {
+            return;
+        }
+        self.values_bytes += bytes;
+        self.values
+            .entry(block.clone())
+            .or_insert_with(HashMap::new)
+            .insert(key.to_string(), value.clone());
+    }
+
+    fn clear(&mut self) {
+        self.ancestors.clear();
+        self.values.clear();
+        self.values_bytes = 0;
+    }
+}
+
 pub struct WritableMarfStore<'a> {
     chain_tip: StacksBlockId,
     marf: MarfTransaction<'a, StacksBlockId>,
+    /// side storage for data written by a detached block
+    detached_side_store: Option<Connection>,
+    /// where to record what this block reads, if recording
+    recorded_reads: Option<&'a mut RecordedReads>,
+    at_block_cache: AtBlockCache,
 }
 
 pub struct ReadOnlyMarfStore<'a> {
     chain_tip: StacksBlockId,
     marf: &'a mut MARF<StacksBlockId>,
+    at_block_cache: AtBlockCache,
 }
 
 impl<'a> ReadOnlyMarfStore<'a> {
@@ -275,24 +535,8 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
     }
 
     fn set_block_hash(&mut self, bhh: StacksBlockId) -> InterpreterResult<StacksBlockId> {
-        self.marf
-            .check_ancestor_block_hash(&bhh)
-            .map_err(|e| match e {
-                Error::NotFoundError => {
-                    test_debug!("No such block {:?} (NotFoundError)", &bhh);
-                    RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
-                }
-                Error::NonMatchingForks(_bh1, _bh2) => {
-                    test_debug!(
-                        "No such block {:?} (NonMatchingForks({}, {}))",
-                        &bhh,
-                        BlockHeaderHash(_bh1),
-                        BlockHeaderHash(_bh2)
-                    );
-                    RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
-                }
-                _ => panic!("ERROR: Unexpected MARF failure: {}", e),
-            })?;
+        self.at_block_cache
+            .check_ancestor_block_hash(&mut *self.marf, &bhh)?;
 
         let result = Ok(self.chain_tip);
         self.chain_tip = bhh;
@@ -384,7 +628,11 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
 
     fn get(&mut self, key: &str) -> Option<String> {
         trace!("MarfedKV get: {:?} tip={}", key, &self.chain_tip);
-        self.marf
+        if let Some(value) = self.at_block_cache.get_value(&self.chain_tip, key) {
+            return value;
+        }
+        let value = self
+            .marf
             .get(&self.chain_tip, key)
             .or_else(|e| match e {
                 Error::NotFoundError => {
@@ -405,7 +653,10 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
                     "ERROR: MARF contained value_hash not found in side storage: {}",
                     side_key
                 ))
-            })
+            });
+        self.at_block_cache
+            .insert_value(&self.chain_tip, key, &value);
+        value
     }
 
     fn put_all(&mut self, _items: Vec<(String, String)>) {
@@ -415,6 +666,12 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
 }
 
 impl<'a> WritableMarfStore<'a> {
+    /// Forget the historical blocks resolved by `at-block`, and the values read at them.  Called
+    ///  at the end of each transaction.
+    pub fn clear_at_block_cache(&mut self) {
+        self.at_block_cache.clear();
+    }
+
     pub fn as_clarity_db<'b>(
         &'b mut self,
         headers_db: &'b dyn HeadersDB,
@@ -439,6 +696,7 @@ impl<'a> WritableMarfStore<'a> {
 
     pub fn commit_to(self, final_bhh: &StacksBlockId) {
         debug!("commit_to({})", final_bhh);
+        self.check_attached();
         SqliteConnection::commit_metadata_to(self.marf.sqlite_tx(), &self.chain_tip, final_bhh);
 
         let _ = self.marf.commit_to(final_bhh).map_err(|e| {
@@ -455,6 +713,7 @@ impl<'a> WritableMarfStore<'a> {
 
     pub fn commit_unconfirmed(self) {
         debug!("commit_unconfirmed()");
+        self.check_attached();
         // NOTE: Can omit commit_metadata_to, since the block header hash won't change
         // commit_metadata_to(&self.chain_tip, final_bhh);
         self.marf
@@ -470,6 +729,11 @@ impl<'a> WritableMarfStore<'a> {
             "commit_mined_block: ({}->{})",
             &self.chain_tip, will_move_to
         );
+        if self.detached_side_store.is_some() {
+            // nothing was written, so there is nothing to commit
+            self.marf.drop_current();
+            return;
+        }
         // rollback the side_store
         //    the side_store shouldn't commit data for blocks that won't be
         //    included in the processed chainstate (like a block constructed during mining)
@@ -492,28 +756,73 @@ impl<'a> WritableMarfStore<'a> {
             .get_root_hash_at(&self.chain_tip)
             .expect("FATAL: Failed to read MARF root hash")
     }
+
+    fn check_attached(&self) {
+        if self.detached_side_store.is_some() {
+            error!(
+                "Attempted to commit a detached MARF block {}",
+                &self.chain_tip
+            );
+            panic!("BUG: attempted to commit a detached MARF block");
+        }
+    }
+
+    /// Look up a value in side storage, including anything written by this block if it is
+    /// detached.
+    fn get_side_value(&self, side_key: &str) -> Option<String> {
+        if let Some(ref side_store) = self.detached_side_store {
+            if let Some(value) = SqliteConnection::get(side_store, side_key) {
+                return Some(value);
+            }
+        }
+        SqliteConnection::get(self.marf.sqlite_tx(), side_key)
+    }
+
+    /// Look up contract metadata, including anything written by this block if it is detached.
+    fn get_side_metadata(
+        &mut self,
+        bhh: &StacksBlockId,
+        contract: &str,
+        key: &str,
+    ) -> Option<String> {
+        if let Some(ref side_store) = self.detached_side_store {
+            if let Some(value) = SqliteConnection::get_metadata(side_store, bhh, contract, key) {
+                return Some(value);
+            }
+        }
+        let value = SqliteConnection::get_metadata(self.marf.sqlite_tx(), bhh, contract, key);
+        // metadata this block wrote itself doesn't need to be recorded
+        let written_here = Some(bhh) == self.marf.get_open_chain_tip();
+        if let Some(ref mut recorded_reads) = self.recorded_reads {
+            if !written_here {
+                recorded_reads.record_metadata(bhh, contract, key, &value);
+            }
+        }
+        value
+    }
+
+    /// Get the recording of this block's reads, if recording.  Reads made as of some other block
+    /// are only flagged.
+    fn recording(&mut self) -> Option<&mut RecordedReads> {
+        let reading_open_block = Some(&self.chain_tip) == self.marf.get_open_chain_tip();
+        match self.recorded_reads {
+            Some(ref mut recorded_reads) => {
+                if reading_open_block {
+                    Some(recorded_reads)
+                } else {
+                    recorded_reads.read_other_blocks = true;
+                    None
+                }
+            }
+            None => None,
+        }
+    }
 }
 
 impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
     fn set_block_hash(&mut self, bhh: StacksBlockId) -> InterpreterResult<StacksBlockId> {
-        self.marf
-            .check_ancestor_block_hash(&bhh)
-            .map_err(|e| match e {
-                Error::NotFoundError => {
-                    test_debug!("No such block {:?} (NotFoundError)", &bhh);
-                    RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
-                }
-                Error::NonMatchingForks(_bh1, _bh2) => {
-                    test_debug!(
-                        "No such block {:?} (NonMatchingForks({}, {}))",
-                        &bhh,
-                        BlockHeaderHash(_bh1),
-                        BlockHeaderHash(_bh2)
-                    );
-                    RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
-                }
-                _ => panic!("ERROR: Unexpected MARF failure: {}", e),
-            })?;
+        self.at_block_cache
+            .check_ancestor_block_hash(&mut self.marf, &bhh)?;
 
         let result = Ok(self.chain_tip);
         self.chain_tip = bhh;
@@ -523,32 +832,46 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
 
     fn get(&mut self, key: &str) -> Option<String> {
         trace!("MarfedKV get: {:?} tip={}", key, &self.chain_tip);
-        self.marf
-            .get(&self.chain_tip, key)
-            .or_else(|e| match e {
-                Error::NotFoundError => {
-                    trace!(
-                        "MarfedKV get {:?} off of {:?}: not found",
-                        key,
-                        &self.chain_tip
-                    );
-                    Ok(None)
-                }
-                _ => Err(e),
-            })
-            .expect("ERROR: Unexpected MARF Failure on GET")
-            .map(|marf_value| {
-                let side_key = marf_value.to_hex();
-                trace!("MarfedKV get side-key for {:?}: {:?}", key, &side_key);
-                SqliteConnection::get(self.marf.sqlite_tx(), &side_key).expect(&format!(
-                    "ERROR: MARF contained value_hash not found in side storage: {}",
-                    side_key
-                ))
-            })
+        let value = match self.at_block_cache.get_value(&self.chain_tip, key) {
+            Some(value) => value,
+            None => {
+                let value = self
+                    .marf
+                    .get(&self.chain_tip, key)
+                    .or_else(|e| match e {
+                        Error::NotFoundError => {
+                            trace!(
+                                "MarfedKV get {:?} off of {:?}: not found",
+                                key,
+                                &self.chain_tip
+                            );
+                            Ok(None)
+                        }
+                        _ => Err(e),
+                    })
+                    .expect("ERROR: Unexpected MARF Failure on GET")
+                    .map(|marf_value| {
+                        let side_key = marf_value.to_hex();
+                        trace!("MarfedKV get side-key for {:?}: {:?}", key, &side_key);
+                        self.get_side_value(&side_key).expect(&format!(
+                            "ERROR: MARF contained value_hash not found in side storage: {}",
+                            side_key
+                        ))
+                    });
+                self.at_block_cache
+                    .insert_value(&self.chain_tip, key, &value);
+                value
+            }
+        };
+        if let Some(recorded_reads) = self.recording() {
+            recorded_reads.record_value(key, &value);
+        }
+        value
     }
 
     fn get_with_proof(&mut self, key: &str) -> Option<(String, TrieMerkleProof<StacksBlockId>)> {
-        self.marf
+        let value = self
+            .marf
             .get_with_proof(&self.chain_tip, key)
             .or_else(|e| match e {
                 Error::NotFoundError => Ok(None),
@@ -557,13 +880,16 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
             .expect("ERROR: Unexpected MARF Failure on GET")
             .map(|(marf_value, proof)| {
                 let side_key = marf_value.to_hex();
-                let data =
-                    SqliteConnection::get(self.marf.sqlite_tx(), &side_key).expect(&format!(
-                        "ERROR: MARF contained value_hash not found in side storage: {}",
-                        side_key
-                    ));
+                let data = self.get_side_value(&side_key).expect(&format!(
+                    "ERROR: MARF contained value_hash not found in side storage: {}",
+                    side_key
+                ));
                 (data, proof)
-            })
+            });
+        if let Some(recorded_reads) = self.recording() {
+            recorded_reads.record_value(key, &value.as_ref().map(|(data, _)| data.clone()));
+        }
+        value
     }
 
     fn get_side_store(&mut self) -> &Connection {
@@ -571,12 +897,17 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
     }
 
     fn get_block_at_height(&mut self, height: u32) -> Option<StacksBlockId> {
-        self.marf
+        let block_id = self
+            .marf
             .get_block_at_height(height, &self.chain_tip)
             .expect(&format!(
                 "Unexpected MARF failure: failed to get block at height {} off of {}.",
                 height, &self.chain_tip
-            ))
+            ));
+        if let Some(recorded_reads) = self.recording() {
+            recorded_reads.record_block_height(height, &block_id);
+        }
+        block_id
     }
 
     fn get_open_chain_tip(&mut self) -> StacksBlockId {
@@ -635,7 +966,15 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
         for (key, value) in items.into_iter() {
             trace!("MarfedKV put '{}' = '{}'", &key, &value);
             let marf_value = MARFValue::from_value(&value);
-            SqliteConnection::put(self.get_side_store(), &marf_value.to_hex(), &value);
+            match self.detached_side_store {
+                Some(ref side_store) => {
+                    SqliteConnection::put(side_store, &marf_value.to_hex(), &value)
+                }
+                None => SqliteConnection::put(self.get_side_store(), &marf_value.to_hex(), &value),
+            }
+            if let Some(ref mut recorded_reads) = self.recorded_reads {
+                recorded_reads.written_keys.insert(key.clone());
+            }
             keys.push(key);
             values.push(marf_value);
         }
@@ -643,4 +982,36 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
             .insert_batch(&keys, values)
             .expect("ERROR: Unexpected MARF Failure");
     }
+
+    fn insert_metadata(&mut self, contract: &QualifiedContractIdentifier, key: &str, value: &str) {
+        let bhh = self.get_open_chain_tip();
+        let side_store: &Connection = match self.detached_side_store {
+            Some(ref side_store) => side_store,
+            None => self.marf.sqlite_tx(),
+        };
+        SqliteConnection::insert_metadata(side_store, &bhh, &contract.to_string(), key, value)
+    }
+
+    fn get_metadata(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+    ) -> InterpreterResult<Option<String>> {
+        let (bhh, _) = self.get_contract_hash(contract)?;
+        Ok(self.get_side_metadata(&bhh, &contract.to_string(), key))
+    }
+
+    fn get_metadata_manual(
+        &mut self,
+        at_height: u32,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+    ) -> InterpreterResult<Option<String>> {
+        let bhh = self.get_block_at_height(at_height)
+            .ok_or_else(|| {
+                warn!("Unknown block height when manually querying metadata"; "block_height" => at_height);
+                RuntimeErrorType::BadBlockHeight(at_height.to_string())
+            })?;
+        Ok(self.get_side_metadata(&bhh, &contract.to_string(), key))
+    }
 }
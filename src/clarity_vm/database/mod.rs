@@ -3,6 +3,7 @@ use rusqlite::{Connection, OptionalExtension};
 use chainstate::burn::db::sortdb::{
     SortitionDB, SortitionDBConn, SortitionHandleConn, SortitionHandleTx,
 };
+use chainstate::burn::ConsensusHash;
 use chainstate::stacks::db::{MinerPaymentSchedule, StacksHeaderInfo};
 use chainstate::stacks::index::MarfTrieId;
 use util::db::{DBConn, FromRow};
@@ -53,6 +54,10 @@ impl HeadersDB for DBConn {
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         get_miner_info(self, id_bhh).map(|x| x.address)
     }
+
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        get_stacks_header_info(self, id_bhh).map(|x| x.consensus_hash)
+    }
 }
 
 fn get_stacks_header_info(conn: &DBConn, id_bhh: &StacksBlockId) -> Option<StacksHeaderInfo> {
@@ -77,10 +82,14 @@ fn get_miner_info(conn: &DBConn, id_bhh: &StacksBlockId) -> Option<MinerPaymentS
 
 impl BurnStateDB for SortitionHandleTx<'_> {
     fn get_burn_block_height(&self, sortition_id: &SortitionId) -> Option<u32> {
-        match SortitionDB::get_block_snapshot(self.tx(), sortition_id) {
-            Ok(Some(x)) => Some(x.block_height as u32),
-            _ => return None,
-        }
+        self.context
+            .burn_state_cache
+            .get_burn_block_height(sortition_id, || {
+                match SortitionDB::get_block_snapshot(self.tx(), sortition_id) {
+                    Ok(Some(x)) => Some(x.block_height as u32),
+                    _ => return None,
+                }
+            })
     }
 
     fn get_burn_header_hash(
@@ -88,6 +97,23 @@ impl BurnStateDB for SortitionHandleTx<'_> {
         height: u32,
         sortition_id: &SortitionId,
     ) -> Option<BurnchainHeaderHash> {
+        self.context
+            .burn_state_cache
+            .get_burn_header_hash(height, sortition_id, || {
+                let readonly_marf = self.index().reopen_readonly().expect(
+                    "BUG: failure trying to get a read-only interface into the sortition db.",
+                );
+                let mut context = self.context.clone();
+                context.chain_tip = sortition_id.clone();
+                let db_handle = SortitionHandleConn::new(&readonly_marf, context);
+                match db_handle.get_block_snapshot_by_height(height as u64) {
+                    Ok(Some(x)) => Some(x.burn_header_hash),
+                    _ => return None,
+                }
+            })
+    }
+
+    fn get_burn_header_timestamp(&self, height: u32, sortition_id: &SortitionId) -> Option<u64> {
         let readonly_marf = self
             .index()
             .reopen_readonly()
@@ -96,28 +122,46 @@ impl BurnStateDB for SortitionHandleTx<'_> {
         context.chain_tip = sortition_id.clone();
         let db_handle = SortitionHandleConn::new(&readonly_marf, context);
         match db_handle.get_block_snapshot_by_height(height as u64) {
-            Ok(Some(x)) => Some(x.burn_header_hash),
+            Ok(Some(x)) => Some(x.burn_header_timestamp),
+            _ => return None,
+        }
+    }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        match SortitionDB::get_sortition_id_by_consensus(self.tx(), consensus_hash) {
+            Ok(x) => x,
             _ => return None,
         }
     }
 
     fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch> {
-        SortitionDB::get_stacks_epoch(self.tx(), height as u64)
-            .expect("BUG: failed to get epoch for burn block height")
+        self.context.burn_state_cache.get_stacks_epoch(height, || {
+            SortitionDB::get_stacks_epochs(self.tx()).expect("BUG: failed to load epochs")
+        })
     }
 
     fn get_stacks_epoch_by_epoch_id(&self, epoch_id: &StacksEpochId) -> Option<StacksEpoch> {
-        SortitionDB::get_stacks_epoch_by_epoch_id(self.tx(), epoch_id)
-            .expect("BUG: failed to get epoch for epoch id")
+        self.context
+            .burn_state_cache
+            .get_stacks_epoch_by_epoch_id(epoch_id, || {
+                SortitionDB::get_stacks_epochs(self.tx()).expect("BUG: failed to load epochs")
+            })
     }
 }
 
 impl BurnStateDB for SortitionDBConn<'_> {
     fn get_burn_block_height(&self, sortition_id: &SortitionId) -> Option<u32> {
-        match SortitionDB::get_block_snapshot(self.conn(), sortition_id) {
-            Ok(Some(x)) => Some(x.block_height as u32),
-            _ => return None,
-        }
+        self.context
+            .burn_state_cache
+            .get_burn_block_height(sortition_id, || {
+                match SortitionDB::get_block_snapshot(self.conn(), sortition_id) {
+                    Ok(Some(x)) => Some(x.block_height as u32),
+                    _ => return None,
+                }
+            })
     }
 
     fn get_burn_header_hash(
@@ -125,21 +169,47 @@ impl BurnStateDB for SortitionDBConn<'_> {
         height: u32,
         sortition_id: &SortitionId,
     ) -> Option<BurnchainHeaderHash> {
+        self.context
+            .burn_state_cache
+            .get_burn_header_hash(height, sortition_id, || {
+                let db_handle = SortitionHandleConn::open_reader(self, &sortition_id).ok()?;
+                match db_handle.get_block_snapshot_by_height(height as u64) {
+                    Ok(Some(x)) => Some(x.burn_header_hash),
+                    _ => return None,
+                }
+            })
+    }
+
+    fn get_burn_header_timestamp(&self, height: u32, sortition_id: &SortitionId) -> Option<u64> {
         let db_handle = SortitionHandleConn::open_reader(self, &sortition_id).ok()?;
         match db_handle.get_block_snapshot_by_height(height as u64) {
-            Ok(Some(x)) => Some(x.burn_header_hash),
+            Ok(Some(x)) => Some(x.burn_header_timestamp),
+            _ => return None,
+        }
+    }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        match SortitionDB::get_sortition_id_by_consensus(self.conn(), consensus_hash) {
+            Ok(x) => x,
             _ => return None,
         }
     }
 
     fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch> {
-        SortitionDB::get_stacks_epoch(self.conn(), height as u64)
-            .expect("BUG: failed to get epoch for burn block height")
+        self.context.burn_state_cache.get_stacks_epoch(height, || {
+            SortitionDB::get_stacks_epochs(self.conn()).expect("BUG: failed to load epochs")
+        })
     }
 
     fn get_stacks_epoch_by_epoch_id(&self, epoch_id: &StacksEpochId) -> Option<StacksEpoch> {
-        SortitionDB::get_stacks_epoch_by_epoch_id(self.conn(), epoch_id)
-            .expect("BUG: failed to get epoch for epoch id")
+        self.context
+            .burn_state_cache
+            .get_stacks_epoch_by_epoch_id(epoch_id, || {
+                SortitionDB::get_stacks_epochs(self.conn()).expect("BUG: failed to load epochs")
+            })
     }
 }
 
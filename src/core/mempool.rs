@@ -32,6 +32,7 @@ use rusqlite::Row;
 use rusqlite::Transaction;
 use rusqlite::NO_PARAMS;
 
+use burnchains::Address;
 use burnchains::Txid;
 use chainstate::burn::ConsensusHash;
 use chainstate::stacks::TransactionPayload;
@@ -45,6 +46,7 @@ use core::FIRST_BURNCHAIN_CONSENSUS_HASH;
 use core::FIRST_STACKS_BLOCK_HASH;
 use monitoring::increment_stx_mempool_gc;
 use std::time::Instant;
+use util::db::query_count;
 use util::db::query_row_columns;
 use util::db::query_rows;
 use util::db::sqlite_open;
@@ -57,6 +59,7 @@ use util::db::{query_row, Error};
 use util::db::{sql_pragma, DBConn, DBTx, FromRow};
 use util::get_epoch_time_ms;
 use util::get_epoch_time_secs;
+use vm::database::BurnStateDB;
 use vm::types::PrincipalData;
 
 use clarity_vm::clarity::ClarityConnection;
@@ -64,6 +67,10 @@ use clarity_vm::clarity::ClarityConnection;
 use crate::chainstate::stacks::events::StacksTransactionReceipt;
 use crate::chainstate::stacks::StacksBlock;
 use crate::codec::StacksMessageCodec;
+use crate::core::mempool_policy::{
+    ContractCallPolicy, MemPoolAdmissionInfo, MemPoolAdmissionPolicy,
+};
+use crate::core::mempool_walk::{FeeRateWalkStrategy, MemPoolWalkStrategy};
 use crate::cost_estimates;
 use crate::cost_estimates::metrics::CostMetric;
 use crate::cost_estimates::metrics::UnitMetric;
@@ -71,16 +78,64 @@ use crate::cost_estimates::CostEstimator;
 use crate::cost_estimates::EstimatorError;
 use crate::cost_estimates::UnitEstimator;
 use crate::monitoring;
-use crate::types::chainstate::{BlockHeaderHash, StacksAddress, StacksBlockHeader};
+use crate::net::bloom::TxidBloomFilter;
+use crate::types::chainstate::{BlockHeaderHash, StacksAddress, StacksBlockHeader, StacksBlockId};
 use crate::util::db::table_exists;
 
 // maximum number of confirmations a transaction can have before it's garbage-collected
 pub const MEMPOOL_MAX_TRANSACTION_AGE: u64 = 256;
 pub const MAXIMUM_MEMPOOL_TX_CHAINING: u64 = 25;
 
+// maximum number of pending transactions to announce to peers again after a restart
+pub const MEMPOOL_REBROADCAST_MAX_TXS: u64 = 1000;
+
+// default amount (in microSTX) by which a replacement transaction's fee must exceed the fee of
+// the transaction it replaces
+pub const MEMPOOL_DEFAULT_REPLACE_BY_FEE_INCREMENT: u64 = 1;
+
+// maximum number of transactions an origin account can have waiting behind a nonce gap
+pub const MEMPOOL_MAX_FUTURE_TXS_PER_ORIGIN: u64 = 10;
+
+// default limits on the pending transactions from one origin account, and in the whole mempool
+pub const MEMPOOL_DEFAULT_MAX_TXS_PER_ORIGIN: u64 = 100;
+pub const MEMPOOL_DEFAULT_MAX_BYTES_PER_ORIGIN: u64 = 4 * 1024 * 1024;
+pub const MEMPOOL_DEFAULT_MAX_TXS: u64 = 100_000;
+
+// how many transactions to load at a time when looking for ones a peer is missing
+pub const MEMPOOL_SYNC_PAGE_SIZE: u64 = 256;
+
+// how long (in seconds) to remember why a transaction was dropped from the mempool
+pub const MEMPOOL_DROPPED_TX_RETENTION: u64 = 7 * 24 * 3600;
+
 pub struct MemPoolAdmitter {
     cur_block: BlockHeaderHash,
     cur_consensus_hash: ConsensusHash,
+    replace_by_fee_increment: u64,
+    limits: MemPoolLimits,
+    policies: Vec<Box<dyn MemPoolAdmissionPolicy>>,
+}
+
+/// Limits on how much of the mempool a single origin account, and all pending transactions
+/// together, may take up
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolLimits {
+    /// maximum number of pending transactions from one origin account
+    pub max_txs_per_origin: u64,
+    /// maximum total size (in bytes) of the pending transactions from one origin account
+    pub max_bytes_per_origin: u64,
+    /// maximum number of pending transactions.  Once the mempool is full, the transactions with
+    /// the lowest fee rates are evicted to make room for new ones.
+    pub max_txs: u64,
+}
+
+impl Default for MemPoolLimits {
+    fn default() -> MemPoolLimits {
+        MemPoolLimits {
+            max_txs_per_origin: MEMPOOL_DEFAULT_MAX_TXS_PER_ORIGIN,
+            max_bytes_per_origin: MEMPOOL_DEFAULT_MAX_BYTES_PER_ORIGIN,
+            max_txs: MEMPOOL_DEFAULT_MAX_TXS,
+        }
+    }
 }
 
 enum MemPoolWalkResult {
@@ -94,7 +149,51 @@ impl MemPoolAdmitter {
         MemPoolAdmitter {
             cur_block,
             cur_consensus_hash,
+            replace_by_fee_increment: MEMPOOL_DEFAULT_REPLACE_BY_FEE_INCREMENT,
+            limits: MemPoolLimits::default(),
+            policies: vec![],
+        }
+    }
+
+    /// Register a node-local admission policy.  Policies are checked in the order they were
+    /// added, after a transaction has passed the consensus validity checks.
+    pub fn add_policy(&mut self, policy: Box<dyn MemPoolAdmissionPolicy>) {
+        self.policies.push(policy);
+    }
+
+    /// Check a transaction against every registered admission policy
+    pub fn check_policies(&self, info: &MemPoolAdmissionInfo) -> Result<(), MemPoolRejection> {
+        for policy in self.policies.iter() {
+            if let Err(reason) = policy.check(info) {
+                debug!("Mempool admission policy rejected transaction";
+                       "txid" => %info.tx.txid(),
+                       "policy" => policy.name(),
+                       "reason" => %reason);
+                return Err(MemPoolRejection::PolicyRejection(
+                    policy.name().to_string(),
+                    reason,
+                ));
+            }
         }
+        Ok(())
+    }
+
+    pub fn set_limits(&mut self, limits: MemPoolLimits) {
+        self.limits = limits;
+    }
+
+    pub fn get_limits(&self) -> &MemPoolLimits {
+        &self.limits
+    }
+
+    /// Set how much higher (in microSTX) a replacement transaction's fee must be than the fee of
+    /// the transaction it replaces.
+    pub fn set_replace_by_fee_increment(&mut self, increment: u64) {
+        self.replace_by_fee_increment = increment;
+    }
+
+    pub fn get_replace_by_fee_increment(&self) -> u64 {
+        self.replace_by_fee_increment
     }
 
     pub fn set_block(&mut self, cur_block: &BlockHeaderHash, cur_consensus_hash: ConsensusHash) {
@@ -111,11 +210,22 @@ impl MemPoolAdmitter {
     }
 }
 
+/// Why a transaction was removed from the mempool without being mined.  The string form of each
+/// reason is reported to event observers and over RPC, so it must not change.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MemPoolDropReason {
+    /// replaced by a transaction with the same nonce in a different fork
     REPLACE_ACROSS_FORK,
+    /// replaced by a transaction with the same nonce and a higher fee
     REPLACE_BY_FEE,
+    /// not mined within MEMPOOL_MAX_TRANSACTION_AGE blocks
     STALE_COLLECT,
+    /// too expensive to ever fit in a block
     TOO_EXPENSIVE,
+    /// its nonce was used by another transaction in the canonical fork, e.g. after a reorg
+    STALE_NONCE,
+    /// evicted from a full mempool to make room for a transaction with a higher fee rate
+    EVICTED_BY_FEE_RATE,
 }
 
 pub struct ConsiderTransaction {
@@ -140,10 +250,67 @@ impl std::fmt::Display for MemPoolDropReason {
             MemPoolDropReason::TOO_EXPENSIVE => write!(f, "TooExpensive"),
             MemPoolDropReason::REPLACE_ACROSS_FORK => write!(f, "ReplaceAcrossFork"),
             MemPoolDropReason::REPLACE_BY_FEE => write!(f, "ReplaceByFee"),
+            MemPoolDropReason::STALE_NONCE => write!(f, "StaleNonce"),
+            MemPoolDropReason::EVICTED_BY_FEE_RATE => write!(f, "EvictedByFeeRate"),
+        }
+    }
+}
+
+impl std::str::FromStr for MemPoolDropReason {
+    type Err = db_error;
+
+    fn from_str(s: &str) -> Result<MemPoolDropReason, db_error> {
+        match s {
+            "StaleGarbageCollect" => Ok(MemPoolDropReason::STALE_COLLECT),
+            "TooExpensive" => Ok(MemPoolDropReason::TOO_EXPENSIVE),
+            "ReplaceAcrossFork" => Ok(MemPoolDropReason::REPLACE_ACROSS_FORK),
+            "ReplaceByFee" => Ok(MemPoolDropReason::REPLACE_BY_FEE),
+            "StaleNonce" => Ok(MemPoolDropReason::STALE_NONCE),
+            "EvictedByFeeRate" => Ok(MemPoolDropReason::EVICTED_BY_FEE_RATE),
+            _ => Err(db_error::ParseError),
         }
     }
 }
 
+/// How an origin account's pending transactions line up with the nonce it must use next.
+/// Transactions after a gap in the nonce sequence cannot be mined until the gap is filled; once it
+/// is, they become pending again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolNonceStatus {
+    /// the account's nonce in the chain state
+    pub account_nonce: u64,
+    /// the lowest nonce without a transaction in the mempool
+    pub next_nonce: u64,
+    /// nonces of transactions that can be mined in order from `account_nonce`
+    pub pending_nonces: Vec<u64>,
+    /// nonces of transactions that are waiting for `missing_nonces` to arrive
+    pub future_nonces: Vec<u64>,
+    /// nonces that must be filled before the future transactions can be mined
+    pub missing_nonces: Vec<u64>,
+}
+
+/// Record of a transaction that was removed from the mempool without being mined
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolDroppedTx {
+    pub txid: Txid,
+    pub reason: MemPoolDropReason,
+    pub dropped_at: u64,
+}
+
+impl FromRow<MemPoolDroppedTx> for MemPoolDroppedTx {
+    fn from_row<'a>(row: &'a Row) -> Result<MemPoolDroppedTx, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let reason_str: String = row.get_unwrap("reason");
+        let reason = reason_str.parse::<MemPoolDropReason>()?;
+        let dropped_at = u64::from_column(row, "dropped_at")?;
+        Ok(MemPoolDroppedTx {
+            txid,
+            reason,
+            dropped_at,
+        })
+    }
+}
+
 pub trait MemPoolEventDispatcher {
     fn mempool_txs_dropped(&self, txids: Vec<Txid>, reason: MemPoolDropReason);
     fn mined_block_event(
@@ -179,6 +346,13 @@ pub struct MemPoolTxMetadata {
     pub accept_time: u64,
 }
 
+/// A mempool transaction, along with its estimated fee rate if it has been estimated yet.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MemPoolTxFeeRate {
+    pub tx: MemPoolTxInfo,
+    pub fee_rate: Option<f64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct MemPoolWalkSettings {
     /// Minimum transaction fee that will be considered
@@ -190,6 +364,9 @@ pub struct MemPoolWalkSettings {
     /// That is, with x%, when picking the next transaction to include a block, select one that
     /// either failed to get a cost estimate or has not been estimated yet.
     pub consider_no_estimate_tx_prob: u8,
+    /// If given, transactions calling contracts this policy does not accept are skipped.  This
+    /// only affects which transactions this node mines, not which ones it stores or relays.
+    pub contract_call_policy: Option<ContractCallPolicy>,
 }
 
 impl MemPoolWalkSettings {
@@ -198,6 +375,7 @@ impl MemPoolWalkSettings {
             min_tx_fee: 1,
             max_walk_time_ms: u64::max_value(),
             consider_no_estimate_tx_prob: 5,
+            contract_call_policy: None,
         }
     }
     pub fn zero() -> MemPoolWalkSettings {
@@ -205,6 +383,7 @@ impl MemPoolWalkSettings {
             min_tx_fee: 0,
             max_walk_time_ms: u64::max_value(),
             consider_no_estimate_tx_prob: 5,
+            contract_call_policy: None,
         }
     }
 }
@@ -267,6 +446,14 @@ impl FromRow<MemPoolTxInfo> for MemPoolTxInfo {
     }
 }
 
+impl FromRow<MemPoolTxFeeRate> for MemPoolTxFeeRate {
+    fn from_row<'a>(row: &'a Row) -> Result<MemPoolTxFeeRate, db_error> {
+        let tx = MemPoolTxInfo::from_row(row)?;
+        let fee_rate: Option<f64> = row.get("fee_rate").map_err(db_error::SqliteError)?;
+        Ok(MemPoolTxFeeRate { tx, fee_rate })
+    }
+}
+
 impl FromRow<(u64, u64)> for (u64, u64) {
     fn from_row<'a>(row: &'a Row) -> Result<(u64, u64), db_error> {
         let t1: i64 = row.get_unwrap(0);
@@ -329,12 +516,62 @@ const MEMPOOL_SCHEMA_2: &'static [&'static str] = &[
     "#,
 ];
 
+const MEMPOOL_SCHEMA_3: &'static [&'static str] = &[
+    r#"
+    CREATE TABLE dropped_txs(
+        txid TEXT NOT NULL,
+        reason TEXT NOT NULL,
+        dropped_at INTEGER NOT NULL,
+        PRIMARY KEY (txid)
+    );
+    "#,
+    "CREATE INDEX dropped_by_time ON dropped_txs(dropped_at);",
+    r#"
+    INSERT INTO schema_version (version) VALUES (3)
+    "#,
+];
+
+const MEMPOOL_SCHEMA_4: &'static [&'static str] = &[
+    r#"
+    CREATE TABLE mempool_arrivals(
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        txid TEXT NOT NULL
+    );
+    "#,
+    "CREATE INDEX arrivals_by_txid ON mempool_arrivals(txid);",
+    r#"
+    INSERT INTO schema_version (version) VALUES (4)
+    "#,
+];
+
 pub struct MemPoolDB {
     db: DBConn,
     path: String,
     admitter: MemPoolAdmitter,
     cost_estimator: Box<dyn CostEstimator>,
     metric: Box<dyn CostMetric>,
+    /// decides the order in which the miner considers transactions
+    walk_strategy: Box<dyn MemPoolWalkStrategy>,
+    /// chain tip as of the last check for transactions with stale nonces
+    stale_nonce_check_tip: Option<StacksBlockId>,
+}
+
+/// A cursor over the transactions admitted to the mempool, so a miner can pick up new arrivals
+/// without walking the whole mempool again.  A subscription sees transactions admitted through
+/// any connection to the same mempool database.
+#[derive(Debug, Clone)]
+pub struct MemPoolSubscription {
+    /// arrival sequence number of the last transaction returned
+    last_seq: i64,
+    /// transactions to return again on the next poll
+    deferred: Vec<MemPoolTxInfo>,
+}
+
+impl MemPoolSubscription {
+    /// Return `tx` again on the next poll, e.g. because its nonce is not yet the account's nonce
+    pub fn defer(&mut self, tx: MemPoolTxInfo) {
+        self.deferred.push(tx);
+    }
 }
 
 pub struct MemPoolTx<'a> {
@@ -486,6 +723,12 @@ impl MemPoolDB {
         if version < 2 {
             MemPoolDB::apply_schema_2(&tx)?;
         }
+        if version < 3 {
+            MemPoolDB::apply_schema_3(&tx)?;
+        }
+        if version < 4 {
+            MemPoolDB::apply_schema_4(&tx)?;
+        }
 
         tx.commit()?;
 
@@ -495,6 +738,35 @@ impl MemPoolDB {
             admitter,
             cost_estimator,
             metric,
+            walk_strategy: Box::new(FeeRateWalkStrategy),
+            stale_nonce_check_tip: None,
+        })
+    }
+
+    /// Copy the mempool to a new database at `snapshot_path`, replacing whatever is there, and
+    /// open it.  A miner assembling a block on one fork can walk the copy without disturbing the
+    /// nonces and fee rate estimates recorded by walks of the mempool on other forks.  Changes to
+    /// either database after the copy is made are not reflected in the other.
+    pub fn snapshot(
+        &self,
+        snapshot_path: &str,
+        cost_estimator: Box<dyn CostEstimator>,
+        metric: Box<dyn CostMetric>,
+    ) -> Result<MemPoolDB, db_error> {
+        if fs::metadata(snapshot_path).is_ok() {
+            fs::remove_file(snapshot_path).map_err(db_error::IOError)?;
+        }
+        self.db.execute("VACUUM INTO ?1", &[&snapshot_path])?;
+
+        let conn = sqlite_open(snapshot_path, OpenFlags::SQLITE_OPEN_READ_WRITE, true)?;
+        Ok(MemPoolDB {
+            db: conn,
+            path: snapshot_path.to_string(),
+            admitter: MemPoolAdmitter::new(BlockHeaderHash([0u8; 32]), ConsensusHash([0u8; 20])),
+            cost_estimator,
+            metric,
+            walk_strategy: Box::new(FeeRateWalkStrategy),
+            stale_nonce_check_tip: None,
         })
     }
 
@@ -523,6 +795,22 @@ impl MemPoolDB {
         Ok(())
     }
 
+    fn apply_schema_3(tx: &Transaction) -> Result<(), db_error> {
+        for sql_exec in MEMPOOL_SCHEMA_3 {
+            tx.execute_batch(sql_exec)?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_schema_4(tx: &Transaction) -> Result<(), db_error> {
+        for sql_exec in MEMPOOL_SCHEMA_4 {
+            tx.execute_batch(sql_exec)?;
+        }
+
+        Ok(())
+    }
+
     pub fn reset_last_known_nonces(&mut self) -> Result<(), db_error> {
         let sql =
             "UPDATE mempool SET last_known_origin_nonce = NULL, last_known_sponsor_nonce = NULL";
@@ -530,6 +818,64 @@ impl MemPoolDB {
         Ok(())
     }
 
+    /// Set the fee increase (in microSTX) a transaction needs in order to replace a pending
+    /// transaction with the same origin or sponsor nonce.
+    pub fn set_replace_by_fee_increment(&mut self, increment: u64) {
+        self.admitter.set_replace_by_fee_increment(increment);
+    }
+
+    /// Register a node-local admission policy, to be checked after the consensus validity
+    /// checks when transactions are submitted.
+    pub fn add_admission_policy(&mut self, policy: Box<dyn MemPoolAdmissionPolicy>) {
+        self.admitter.add_policy(policy);
+    }
+
+    /// Set the strategy that orders transactions for the miner
+    pub fn set_walk_strategy(&mut self, walk_strategy: Box<dyn MemPoolWalkStrategy>) {
+        debug!("Mempool walk strategy is {}", walk_strategy.name());
+        self.walk_strategy = walk_strategy;
+    }
+
+    /// Swap in a different walk strategy, and get back the current one
+    pub fn replace_walk_strategy(
+        &mut self,
+        walk_strategy: Box<dyn MemPoolWalkStrategy>,
+    ) -> Box<dyn MemPoolWalkStrategy> {
+        std::mem::replace(&mut self.walk_strategy, walk_strategy)
+    }
+
+    /// Tell the walk strategy that the miner is about to fill a new block or microblock with the
+    /// given budget
+    pub fn begin_block(&mut self, block_limit: &ExecutionCost, epoch_id: &StacksEpochId) {
+        self.walk_strategy.begin_block(block_limit, epoch_id);
+    }
+
+    /// Set the limits on pending transactions per origin account and in total
+    pub fn set_limits(&mut self, limits: MemPoolLimits) {
+        self.admitter.set_limits(limits);
+    }
+
+    /// Prepare a mempool that was kept across a node restart for use.  Its pending transactions
+    /// are all still here, but the cached account nonces may be out of date if the chain moved on
+    /// while the node was down, so they are cleared and recomputed on the next mempool walk.
+    /// Returns the number of pending transactions.
+    pub fn prepare_warm_restart(&mut self) -> Result<u64, db_error> {
+        self.reset_last_known_nonces()?;
+        self.stale_nonce_check_tip = None;
+        let count = query_count(&self.db, "SELECT COUNT(*) FROM mempool", NO_PARAMS)?;
+        Ok(count as u64)
+    }
+
+    /// Get up to `limit` pending transactions, in the order the miner prefers them, to announce
+    /// to peers again.
+    pub fn get_txs_to_rebroadcast(
+        conn: &DBConn,
+        limit: u64,
+    ) -> Result<Vec<StacksTransaction>, db_error> {
+        let (_, txs) = MemPoolDB::get_txs_by_fee_rate(conn, 0, limit)?;
+        Ok(txs.into_iter().map(|txinfo| txinfo.tx.tx).collect())
+    }
+
     fn bump_last_known_nonces(&self, address: &StacksAddress) -> Result<(), db_error> {
         let query_by = address.to_string();
 
@@ -565,55 +911,73 @@ impl MemPoolDB {
     /// Select the next TX to consider from the pool of transactions without cost estimates.
     /// If a transaction is found, returns Some object containing the transaction and a boolean indicating
     ///  whether or not the miner should propagate transaction receipts back to the estimator.
-    fn get_next_tx_to_consider_no_estimate(
-        &self,
+    pub fn get_next_tx_to_consider_no_estimate(
+        conn: &DBConn,
     ) -> Result<Option<(MemPoolTxInfo, bool)>, db_error> {
         let select_no_estimate = "SELECT * FROM mempool LEFT JOIN fee_estimates as f ON mempool.txid = f.txid WHERE
                    ((origin_nonce = last_known_origin_nonce AND
                      sponsor_nonce = last_known_sponsor_nonce) OR (last_known_origin_nonce is NULL) OR (last_known_sponsor_nonce is NULL))
                    AND f.fee_rate IS NULL ORDER BY tx_fee DESC LIMIT 1";
-        query_row(&self.db, select_no_estimate, rusqlite::NO_PARAMS)
+        query_row(conn, select_no_estimate, rusqlite::NO_PARAMS)
             .map(|opt_tx| opt_tx.map(|tx| (tx, true)))
     }
 
     /// Select the next TX to consider from the pool of transactions with cost estimates.
     /// If a transaction is found, returns Some object containing the transaction and a boolean indicating
     ///  whether or not the miner should propagate transaction receipts back to the estimator.
-    fn get_next_tx_to_consider_with_estimate(
-        &self,
+    pub fn get_next_tx_to_consider_with_estimate(
+        conn: &DBConn,
     ) -> Result<Option<(MemPoolTxInfo, bool)>, db_error> {
         let select_estimate = "SELECT * FROM mempool LEFT OUTER JOIN fee_estimates as f ON mempool.txid = f.txid WHERE
                    ((origin_nonce = last_known_origin_nonce AND
                      sponsor_nonce = last_known_sponsor_nonce) OR (last_known_origin_nonce is NULL) OR (last_known_sponsor_nonce is NULL))
                    AND f.fee_rate IS NOT NULL ORDER BY f.fee_rate DESC LIMIT 1";
-        query_row(&self.db, select_estimate, rusqlite::NO_PARAMS)
+        query_row(conn, select_estimate, rusqlite::NO_PARAMS)
             .map(|opt_tx| opt_tx.map(|tx| (tx, false)))
     }
 
-    /// * `start_with_no_estimate` - Pass `true` to make this function
+    /// Select the earliest-accepted TX to consider, along with its fee rate estimate if it has
+    /// one.
+    pub fn get_next_tx_to_consider_fifo(
+        conn: &DBConn,
+    ) -> Result<Option<MemPoolTxFeeRate>, db_error> {
+        let select_fifo = "SELECT * FROM mempool LEFT OUTER JOIN fee_estimates as f ON mempool.txid = f.txid WHERE
+                   ((origin_nonce = last_known_origin_nonce AND
+                     sponsor_nonce = last_known_sponsor_nonce) OR (last_known_origin_nonce is NULL) OR (last_known_sponsor_nonce is NULL))
+                   ORDER BY accept_time ASC, mempool.txid ASC LIMIT 1";
+        query_row(conn, select_fifo, rusqlite::NO_PARAMS)
+    }
+
+    /// Get up to `limit` TXs that could be considered next, along with their fee rate estimates
+    /// if they have them, in decreasing order of fee rate.
+    pub fn get_txs_to_consider(
+        conn: &DBConn,
+        limit: u64,
+    ) -> Result<Vec<MemPoolTxFeeRate>, db_error> {
+        let sql = "SELECT * FROM mempool LEFT OUTER JOIN fee_estimates as f ON mempool.txid = f.txid WHERE
+                   ((origin_nonce = last_known_origin_nonce AND
+                     sponsor_nonce = last_known_sponsor_nonce) OR (last_known_origin_nonce is NULL) OR (last_known_sponsor_nonce is NULL))
+                   ORDER BY f.fee_rate IS NULL ASC, f.fee_rate DESC, mempool.tx_fee DESC LIMIT ?1";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(limit)?];
+        query_rows(conn, sql, args)
+    }
+
+    /// Ask the walk strategy for the next TX to consider.
+    /// * `start_with_no_estimate` - Pass `true` to ask the strategy to
     ///   start by considering transactions without a cost
     ///   estimate, and if none are found, use transactions with a cost estimate.
     ///   Pass `false` for the opposite behavior.
     fn get_next_tx_to_consider(
-        &self,
+        &mut self,
         start_with_no_estimate: bool,
     ) -> Result<ConsiderTransactionResult, db_error> {
-        let (next_tx, update_estimate): (MemPoolTxInfo, bool) = if start_with_no_estimate {
-            match self.get_next_tx_to_consider_no_estimate()? {
-                Some(result) => result,
-                None => match self.get_next_tx_to_consider_with_estimate()? {
-                    Some(result) => result,
-                    None => return Ok(ConsiderTransactionResult::NoTransactions),
-                },
-            }
-        } else {
-            match self.get_next_tx_to_consider_with_estimate()? {
-                Some(result) => result,
-                None => match self.get_next_tx_to_consider_no_estimate()? {
-                    Some(result) => result,
-                    None => return Ok(ConsiderTransactionResult::NoTransactions),
-                },
-            }
+        let (next_tx, update_estimate): (MemPoolTxInfo, bool) = match self.walk_strategy.next_tx(
+            &self.db,
+            self.cost_estimator.as_ref(),
+            start_with_no_estimate,
+        )? {
+            Some(result) => result,
+            None => return Ok(ConsiderTransactionResult::NoTransactions),
         };
 
         let mut needs_nonces = vec![];
@@ -711,7 +1075,7 @@ impl MemPoolDB {
     ///  `todo` will be called once for each transaction whose origin nonce is equal
     ///  to the origin account's nonce. At most one transaction per origin will be
     ///  considered by this method, and transactions will be considered in
-    ///  highest-fee-first order.  This method is interruptable -- in the `settings` struct, the
+    ///  the order chosen by the mempool's walk strategy (highest-fee-first by default).  This method is interruptable -- in the `settings` struct, the
     ///  caller may choose how long to spend iterating before this method stops.
     ///
     ///  Returns the number of transactions considered on success.
@@ -775,6 +1139,18 @@ impl MemPoolDB {
                     // if we actually consider the chosen transaction,
                     //  compute a new start_with_no_estimate on the next loop
                     remember_start_with_estimate = None;
+                    if let Some(ref policy) = settings.contract_call_policy {
+                        if let Err(reason) = policy.check_tx(&consider.tx.tx) {
+                            info!("Skip mempool transaction excluded by miner contract policy";
+                                  "txid" => %consider.tx.tx.txid(),
+                                  "reason" => %reason);
+                            self.bump_last_known_nonces(&consider.tx.metadata.origin_address)?;
+                            if consider.tx.tx.auth.is_sponsored() {
+                                self.bump_last_known_nonces(&consider.tx.metadata.sponsor_address)?;
+                            }
+                            continue;
+                        }
+                    }
                     debug!("Consider mempool transaction";
                            "txid" => %consider.tx.tx.txid(),
                            "origin_addr" => %consider.tx.metadata.origin_address,
@@ -783,6 +1159,8 @@ impl MemPoolDB {
                            "tx_fee" => consider.tx.metadata.tx_fee,
                            "size" => consider.tx.metadata.len);
                     total_considered += 1;
+                    self.walk_strategy
+                        .tx_considered(&consider.tx, self.cost_estimator.as_ref());
 
                     if !todo(clarity_tx, &consider, self.cost_estimator.as_mut())? {
                         debug!("Mempool iteration early exit from iterator");
@@ -814,6 +1192,61 @@ impl MemPoolDB {
         Ok(MemPoolTx::new(tx, &mut self.admitter))
     }
 
+    /// Subscribe to the transactions admitted to the mempool from now on
+    pub fn subscribe(&self) -> Result<MemPoolSubscription, db_error> {
+        let last_seq = self
+            .db
+            .query_row(
+                "SELECT IFNULL(MAX(seq), 0) FROM mempool_arrivals",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .map_err(db_error::SqliteError)?;
+        Ok(MemPoolSubscription {
+            last_seq,
+            deferred: vec![],
+        })
+    }
+
+    /// Get the transactions deferred on `subscription`, followed by the ones admitted since it
+    /// was last polled, in the order they were admitted.  Transactions that have since left the
+    /// mempool (e.g. because they were replaced) are skipped.
+    pub fn poll_subscription(
+        &self,
+        subscription: &mut MemPoolSubscription,
+    ) -> Result<Vec<MemPoolTxInfo>, db_error> {
+        let mut txs = vec![];
+        for tx_info in subscription.deferred.drain(..) {
+            if MemPoolDB::db_has_tx(&self.db, &tx_info.metadata.txid)? {
+                txs.push(tx_info);
+            }
+        }
+
+        let max_seq: i64 = self
+            .db
+            .query_row(
+                "SELECT IFNULL(MAX(seq), 0) FROM mempool_arrivals",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .map_err(db_error::SqliteError)?;
+        let sql = "SELECT mempool.* FROM mempool_arrivals JOIN mempool ON mempool_arrivals.txid = mempool.txid
+                   WHERE mempool_arrivals.seq > ?1 AND mempool_arrivals.seq <= ?2 ORDER BY mempool_arrivals.seq ASC";
+        let args: &[&dyn ToSql] = &[&subscription.last_seq, &max_seq];
+        let arrivals: Vec<MemPoolTxInfo> = query_rows(&self.db, sql, args)?;
+        subscription.last_seq = max_seq;
+
+        for tx_info in arrivals.into_iter() {
+            if !txs
+                .iter()
+                .any(|prior| prior.metadata.txid == tx_info.metadata.txid)
+            {
+                txs.push(tx_info);
+            }
+        }
+        Ok(txs)
+    }
+
     fn db_has_tx(conn: &DBConn, txid: &Txid) -> Result<bool, db_error> {
         query_row(
             conn,
@@ -831,6 +1264,261 @@ impl MemPoolDB {
         )
     }
 
+    /// Remember that the given transactions were dropped from the mempool, and why.
+    fn record_dropped_txs(
+        conn: &DBConn,
+        txids: &[Txid],
+        reason: MemPoolDropReason,
+    ) -> Result<(), db_error> {
+        let sql =
+            "INSERT OR REPLACE INTO dropped_txs (txid, reason, dropped_at) VALUES (?1, ?2, ?3)";
+        let now = u64_to_sql(get_epoch_time_secs())?;
+        let reason_str = reason.to_string();
+        for txid in txids.iter() {
+            let args: &[&dyn ToSql] = &[txid, &reason_str, &now];
+            conn.execute(sql, args)?;
+        }
+        Ok(())
+    }
+
+    /// Find out if and why a transaction was dropped from the mempool.
+    /// Only drops within the last MEMPOOL_DROPPED_TX_RETENTION seconds are remembered.
+    pub fn get_dropped_tx(
+        conn: &DBConn,
+        txid: &Txid,
+    ) -> Result<Option<MemPoolDroppedTx>, db_error> {
+        query_row(
+            conn,
+            "SELECT * FROM dropped_txs WHERE txid = ?1",
+            &[txid as &dyn ToSql],
+        )
+    }
+
+    /// Get the number and total size of the pending transactions from an origin account, not
+    /// counting the one with `exclude_nonce` (which a new transaction would replace).
+    fn get_origin_usage(
+        conn: &DBConn,
+        address: &StacksAddress,
+        exclude_nonce: u64,
+    ) -> Result<(u64, u64), db_error> {
+        let sql = "SELECT COUNT(*), IFNULL(SUM(length), 0) FROM mempool WHERE origin_address = ?1 AND origin_nonce != ?2";
+        let args: &[&dyn ToSql] = &[&address.to_string(), &u64_to_sql(exclude_nonce)?];
+        let (count, bytes): (i64, i64) =
+            conn.query_row(sql, args, |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok((count as u64, bytes as u64))
+    }
+
+    /// If the mempool holds more than `max_txs` transactions, evict the ones with the lowest fee
+    /// rates (oldest first among equals).  If the newly-added transaction `new_txid` would itself
+    /// be evicted, then the mempool is full of better-paying transactions and it is rejected.
+    fn evict_by_fee_rate(
+        tx: &mut MemPoolTx,
+        max_txs: u64,
+        new_txid: &Txid,
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+    ) -> Result<(), MemPoolRejection> {
+        let count = query_count(tx, "SELECT COUNT(*) FROM mempool", NO_PARAMS)? as u64;
+        if count <= max_txs {
+            return Ok(());
+        }
+
+        let sql = "SELECT mempool.txid FROM mempool LEFT OUTER JOIN fee_estimates as f ON mempool.txid = f.txid
+                   ORDER BY IFNULL(f.fee_rate, CAST(mempool.tx_fee AS REAL) / mempool.length) ASC, mempool.accept_time ASC
+                   LIMIT ?1";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(count - max_txs)?];
+        let txids: Vec<Txid> = query_rows(tx, sql, args)?;
+        if txids.contains(new_txid) {
+            return Err(MemPoolRejection::MempoolFull);
+        }
+
+        for txid in txids.iter() {
+            tx.execute("DELETE FROM mempool WHERE txid = ?1", &[txid])
+                .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+        }
+        MemPoolDB::record_dropped_txs(tx, &txids, MemPoolDropReason::EVICTED_BY_FEE_RATE)?;
+
+        debug!(
+            "Mempool: evicted {} transaction(s) with the lowest fee rates",
+            txids.len()
+        );
+        if let Some(event_observer) = event_observer {
+            event_observer.mempool_txs_dropped(txids, MemPoolDropReason::EVICTED_BY_FEE_RATE);
+        }
+        Ok(())
+    }
+
+    /// Work out which of an origin account's transactions are mineable given its current nonce,
+    /// and which are waiting behind a gap in its nonce sequence.
+    pub fn get_nonce_status(
+        conn: &DBConn,
+        address: &StacksAddress,
+        account_nonce: u64,
+    ) -> Result<MemPoolNonceStatus, db_error> {
+        let sql = "SELECT origin_nonce FROM mempool WHERE origin_address = ?1 AND origin_nonce >= ?2 ORDER BY origin_nonce ASC";
+        let args: &[&dyn ToSql] = &[&address.to_string(), &u64_to_sql(account_nonce)?];
+        let mut nonces: Vec<u64> = query_rows(conn, sql, args)?;
+        nonces.dedup();
+
+        let mut next_nonce = account_nonce;
+        let mut pending_nonces = vec![];
+        let mut future_nonces = vec![];
+        for nonce in nonces.into_iter() {
+            if nonce == next_nonce && future_nonces.is_empty() {
+                pending_nonces.push(nonce);
+                next_nonce += 1;
+            } else {
+                future_nonces.push(nonce);
+            }
+        }
+
+        let mut missing_nonces = vec![];
+        if let Some(last_future_nonce) = future_nonces.last() {
+            for nonce in next_nonce..*last_future_nonce {
+                if !future_nonces.contains(&nonce) {
+                    missing_nonces.push(nonce);
+                }
+            }
+        }
+
+        Ok(MemPoolNonceStatus {
+            account_nonce,
+            next_nonce,
+            pending_nonces,
+            future_nonces,
+            missing_nonces,
+        })
+    }
+
+    /// Get a page of the remembered dropped transactions, most recently dropped first.  Also
+    /// returns the total number of remembered drops.
+    pub fn get_dropped_txs(
+        conn: &DBConn,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(u64, Vec<MemPoolDroppedTx>), db_error> {
+        let total = query_count(conn, "SELECT COUNT(*) FROM dropped_txs", NO_PARAMS)?;
+        let sql = "SELECT * FROM dropped_txs ORDER BY dropped_at DESC, txid ASC LIMIT ?1 OFFSET ?2";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(limit)?, &u64_to_sql(offset)?];
+        let txs = query_rows(conn, sql, args)?;
+        Ok((total as u64, txs))
+    }
+
+    /// Get a page of the pending transactions in the order the miner prefers them: transactions
+    /// with a fee rate estimate come first, highest rate first, followed by the rest in order of
+    /// decreasing fee.  Also returns the total number of pending transactions.
+    pub fn get_txs_by_fee_rate(
+        conn: &DBConn,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(u64, Vec<MemPoolTxFeeRate>), db_error> {
+        let total = query_count(conn, "SELECT COUNT(*) FROM mempool", NO_PARAMS)?;
+        let sql = "SELECT * FROM mempool LEFT OUTER JOIN fee_estimates as f ON mempool.txid = f.txid
+                   ORDER BY f.fee_rate IS NULL ASC, f.fee_rate DESC, mempool.tx_fee DESC, mempool.txid ASC
+                   LIMIT ?1 OFFSET ?2";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(limit)?, &u64_to_sql(offset)?];
+        let txs = query_rows(conn, sql, args)?;
+        Ok((total as u64, txs))
+    }
+
+    /// Get the txids of all pending transactions
+    pub fn get_all_txids(conn: &DBConn) -> Result<Vec<Txid>, db_error> {
+        query_row_columns(conn, "SELECT txid FROM mempool", NO_PARAMS, "txid")
+    }
+
+    /// Get the txids of up to `limit` pending transactions, in the order the miner prefers them.
+    pub fn get_txids_by_fee_rate(conn: &DBConn, limit: u64) -> Result<Vec<Txid>, db_error> {
+        let sql = "SELECT mempool.txid FROM mempool LEFT OUTER JOIN fee_estimates as f ON mempool.txid = f.txid
+                   ORDER BY f.fee_rate IS NULL ASC, f.fee_rate DESC, mempool.tx_fee DESC, mempool.txid ASC
+                   LIMIT ?1";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(limit)?];
+        query_row_columns(conn, sql, args, "txid")
+    }
+
+    /// Get up to `max_txs` pending transactions that are not in the given bloom filter, in the
+    /// order the miner prefers them, stopping before their total length would exceed
+    /// `max_bytes`.  Used to answer a peer's mempool sync request.
+    pub fn get_txs_missing_from(
+        conn: &DBConn,
+        txids: &TxidBloomFilter,
+        max_txs: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<StacksTransaction>, db_error> {
+        let mut ret = vec![];
+        if max_txs == 0 {
+            return Ok(ret);
+        }
+        let mut total_bytes = 0;
+        let mut offset = 0;
+        loop {
+            let (_, page) = MemPoolDB::get_txs_by_fee_rate(conn, offset, MEMPOOL_SYNC_PAGE_SIZE)?;
+            if page.len() == 0 {
+                return Ok(ret);
+            }
+            offset += page.len() as u64;
+            for txinfo in page.into_iter() {
+                if txids.contains(&txinfo.tx.metadata.txid) {
+                    continue;
+                }
+                if total_bytes + txinfo.tx.metadata.len > max_bytes {
+                    return Ok(ret);
+                }
+                total_bytes += txinfo.tx.metadata.len;
+                ret.push(txinfo.tx.tx);
+                if ret.len() as u64 >= max_txs {
+                    return Ok(ret);
+                }
+            }
+        }
+    }
+
+    /// Get a page of the pending transactions that the given address sent or sponsored, in
+    /// increasing origin nonce order.  Also returns the total number of such transactions.
+    pub fn get_txs_by_address(
+        conn: &DBConn,
+        address: &StacksAddress,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(u64, Vec<MemPoolTxFeeRate>), db_error> {
+        let address_str = address.to_string();
+        let total = query_count(
+            conn,
+            "SELECT COUNT(*) FROM mempool WHERE origin_address = ?1 OR sponsor_address = ?1",
+            &[&address_str],
+        )?;
+        let sql =
+            "SELECT * FROM mempool LEFT OUTER JOIN fee_estimates as f ON mempool.txid = f.txid
+                   WHERE mempool.origin_address = ?1 OR mempool.sponsor_address = ?1
+                   ORDER BY mempool.origin_nonce ASC, mempool.txid ASC
+                   LIMIT ?2 OFFSET ?3";
+        let args: &[&dyn ToSql] = &[&address_str, &u64_to_sql(limit)?, &u64_to_sql(offset)?];
+        let txs = query_rows(conn, sql, args)?;
+        Ok((total as u64, txs))
+    }
+
+    /// Find how many pending transactions come before this one in the order used by
+    /// `get_txs_by_fee_rate()`.
+    pub fn get_fee_rate_rank(conn: &DBConn, tx: &MemPoolTxFeeRate) -> Result<u64, db_error> {
+        let txid = &tx.tx.metadata.txid;
+        let tx_fee = u64_to_sql(tx.tx.metadata.tx_fee)?;
+        let rank = match tx.fee_rate {
+            Some(fee_rate) => {
+                let sql = "SELECT COUNT(*) FROM mempool JOIN fee_estimates as f ON mempool.txid = f.txid
+                           WHERE f.fee_rate > ?1
+                           OR (f.fee_rate = ?1 AND (mempool.tx_fee > ?2 OR (mempool.tx_fee = ?2 AND mempool.txid < ?3)))";
+                let args: &[&dyn ToSql] = &[&fee_rate, &tx_fee, txid];
+                query_count(conn, sql, args)?
+            }
+            None => {
+                let sql = "SELECT COUNT(*) FROM mempool LEFT OUTER JOIN fee_estimates as f ON mempool.txid = f.txid
+                           WHERE f.fee_rate IS NOT NULL
+                           OR mempool.tx_fee > ?1 OR (mempool.tx_fee = ?1 AND mempool.txid < ?2)";
+                let args: &[&dyn ToSql] = &[&tx_fee, txid];
+                query_count(conn, sql, args)?
+            }
+        };
+        Ok(rank as u64)
+    }
+
     /// Get all transactions across all tips
     #[cfg(test)]
     pub fn get_all_txs(conn: &DBConn) -> Result<Vec<MemPoolTxInfo>, db_error> {
@@ -991,10 +1679,15 @@ impl MemPoolDB {
         };
 
         let mut replace_reason = MemPoolDropReason::REPLACE_BY_FEE;
+        let replace_by_fee_increment = tx.admitter.get_replace_by_fee_increment();
 
         // if so, is this a replace-by-fee? or a replace-in-chain-tip?
         let add_tx = if let Some(ref prior_tx) = prior_tx {
-            if tx_fee > prior_tx.tx_fee {
+            if tx_fee
+                >= prior_tx
+                    .tx_fee
+                    .saturating_add(replace_by_fee_increment.max(1))
+            {
                 // is this a replace-by-fee ?
                 debug!(
                     "Can replace {} with {} for {},{} by fee ({} < {})",
@@ -1018,7 +1711,7 @@ impl MemPoolDB {
                 true
             } else {
                 // there's a >= fee tx in this fork, cannot add
-                info!("TX conflicts with sponsor/origin nonce in same fork with >= fee";
+                info!("TX conflicts with sponsor/origin nonce in same fork without a sufficient fee increase";
                       "new_txid" => %txid, 
                       "old_txid" => %prior_tx.txid,
                       "origin_addr" => %origin_address,
@@ -1026,7 +1719,8 @@ impl MemPoolDB {
                       "sponsor_addr" => %sponsor_address,
                       "sponsor_nonce" => sponsor_nonce,
                       "new_fee" => tx_fee,
-                      "old_fee" => prior_tx.tx_fee);
+                      "old_fee" => prior_tx.tx_fee,
+                      "replace_by_fee_increment" => replace_by_fee_increment);
                 false
             }
         } else {
@@ -1071,6 +1765,17 @@ impl MemPoolDB {
         tx.execute(sql, args)
             .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
 
+        tx.execute("INSERT INTO mempool_arrivals (txid) VALUES (?1)", &[&txid])
+            .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+
+        // a re-submitted transaction is no longer dropped
+        tx.execute("DELETE FROM dropped_txs WHERE txid = ?1", &[&txid])
+            .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+
+        if let Some(ref prior_tx) = prior_tx {
+            MemPoolDB::record_dropped_txs(tx, &[prior_tx.txid.clone()], replace_reason)?;
+        }
+
         // broadcast drop event if a tx is being replaced
         if let (Some(prior_tx), Some(event_observer)) = (prior_tx, event_observer) {
             event_observer.mempool_txs_dropped(vec![prior_tx.txid], replace_reason);
@@ -1080,23 +1785,50 @@ impl MemPoolDB {
     }
 
     /// Garbage-collect the mempool.  Remove transactions that have a given number of
-    /// confirmations.
+    /// confirmations.  If given a chain state and tip, transactions mined in the fork ending at
+    /// that tip are removed without being recorded as dropped.
     pub fn garbage_collect(
         tx: &mut MemPoolTx,
         min_height: u64,
+        canonical_fork: Option<(&StacksChainState, &StacksBlockId)>,
         event_observer: Option<&dyn MemPoolEventDispatcher>,
     ) -> Result<(), db_error> {
         let args: &[&dyn ToSql] = &[&u64_to_sql(min_height)?];
 
+        let sql = "SELECT txid FROM mempool WHERE height < ?1";
+        let mut txids: Vec<Txid> = query_rows(tx, sql, args)?;
+        if let Some((chainstate, tip)) = canonical_fork {
+            let mut unmined = Vec::with_capacity(txids.len());
+            for txid in txids.into_iter() {
+                let mined = chainstate
+                    .get_transaction_block_in_fork(&txid, tip)
+                    .map_err(|e| match e {
+                        ChainstateError::DBError(e) => e,
+                        e => db_error::Other(e.to_string()),
+                    })?;
+                if mined.is_none() {
+                    unmined.push(txid);
+                }
+            }
+            txids = unmined;
+        }
+        MemPoolDB::record_dropped_txs(tx, &txids, MemPoolDropReason::STALE_COLLECT)?;
+
         if let Some(event_observer) = event_observer {
-            let sql = "SELECT txid FROM mempool WHERE height < ?1";
-            let txids = query_rows(tx, sql, args)?;
             event_observer.mempool_txs_dropped(txids, MemPoolDropReason::STALE_COLLECT);
         }
 
         let sql = "DELETE FROM mempool WHERE height < ?1";
 
         tx.execute(sql, args)?;
+
+        let sql = "DELETE FROM mempool_arrivals WHERE txid NOT IN (SELECT txid FROM mempool)";
+        tx.execute(sql, NO_PARAMS)?;
+
+        // forget about long-ago drops
+        let sql = "DELETE FROM dropped_txs WHERE dropped_at < ?1";
+        let cutoff = get_epoch_time_secs().saturating_sub(MEMPOOL_DROPPED_TX_RETENTION);
+        tx.execute(sql, &[&u64_to_sql(cutoff)?])?;
         increment_stx_mempool_gc();
         Ok(())
     }
@@ -1104,7 +1836,7 @@ impl MemPoolDB {
     #[cfg(test)]
     pub fn clear_before_height(&mut self, min_height: u64) -> Result<(), db_error> {
         let mut tx = self.tx_begin()?;
-        MemPoolDB::garbage_collect(&mut tx, min_height, None)?;
+        MemPoolDB::garbage_collect(&mut tx, min_height, None, None)?;
         tx.commit()?;
         Ok(())
     }
@@ -1147,6 +1879,7 @@ impl MemPoolDB {
         do_admission_checks: bool,
         event_observer: Option<&dyn MemPoolEventDispatcher>,
         fee_rate_estimate: Option<f64>,
+        cost_estimate: Option<&ExecutionCost>,
     ) -> Result<(), MemPoolRejection> {
         test_debug!(
             "Mempool submit {} at {}/{}",
@@ -1196,6 +1929,52 @@ impl MemPoolDB {
                 .admitter
                 .set_block(&block_hash, (*consensus_hash).clone());
             mempool_tx.admitter.will_admit_tx(chainstate, tx, len)?;
+            mempool_tx.admitter.check_policies(&MemPoolAdmissionInfo {
+                tx,
+                tx_len: len,
+                fee_rate: fee_rate_estimate,
+                estimated_cost: cost_estimate,
+            })?;
+        }
+
+        // is this transaction behind a gap in its origin's nonces, or does it fill one?
+        let nonce_status = if do_admission_checks {
+            let account_nonce = chainstate.get_mempool_account_nonce(
+                consensus_hash,
+                block_hash,
+                &origin_address.clone().into(),
+            )?;
+            let nonce_status =
+                MemPoolDB::get_nonce_status(mempool_tx, &origin_address, account_nonce)?;
+            if origin_nonce > nonce_status.next_nonce
+                && !nonce_status.future_nonces.contains(&origin_nonce)
+                && nonce_status.future_nonces.len() as u64 >= MEMPOOL_MAX_FUTURE_TXS_PER_ORIGIN
+            {
+                return Err(MemPoolRejection::TooManyFutureTransactions {
+                    principal: origin_address.clone().into(),
+                    next_nonce: nonce_status.next_nonce,
+                    max_future_txs: MEMPOOL_MAX_FUTURE_TXS_PER_ORIGIN,
+                });
+            }
+            Some(nonce_status)
+        } else {
+            None
+        };
+
+        // does this origin already have too much in the mempool?
+        let limits = mempool_tx.admitter.get_limits().clone();
+        if do_admission_checks {
+            let (origin_txs, origin_bytes) =
+                MemPoolDB::get_origin_usage(mempool_tx, &origin_address, origin_nonce)?;
+            if origin_txs + 1 > limits.max_txs_per_origin
+                || origin_bytes + len > limits.max_bytes_per_origin
+            {
+                return Err(MemPoolRejection::TooManyOriginTransactions {
+                    principal: origin_address.clone().into(),
+                    max_txs: limits.max_txs_per_origin,
+                    max_bytes: limits.max_bytes_per_origin,
+                });
+            }
         }
 
         MemPoolDB::try_add_tx(
@@ -1214,6 +1993,23 @@ impl MemPoolDB {
             event_observer,
         )?;
 
+        if let Some(nonce_status) = nonce_status {
+            if origin_nonce == nonce_status.next_nonce && !nonce_status.future_nonces.is_empty() {
+                let mut promoted = 0;
+                while nonce_status
+                    .future_nonces
+                    .contains(&(origin_nonce + 1 + promoted))
+                {
+                    promoted += 1;
+                }
+                debug!("Mempool: transaction fills a nonce gap";
+                       "txid" => %txid,
+                       "origin_addr" => %origin_address,
+                       "origin_nonce" => origin_nonce,
+                       "promoted_txs" => promoted);
+            }
+        }
+
         mempool_tx
             .execute(
                 "INSERT OR REPLACE INTO fee_estimates(txid, fee_rate) VALUES (?, ?)",
@@ -1221,6 +2017,8 @@ impl MemPoolDB {
             )
             .map_err(db_error::from)?;
 
+        MemPoolDB::evict_by_fee_rate(mempool_tx, limits.max_txs, &txid, event_observer)?;
+
         if let Err(e) = monitoring::mempool_accepted(&txid, &chainstate.root_path) {
             warn!("Failed to monitor TX receive: {:?}", e; "txid" => %txid);
         }
@@ -1246,6 +2044,10 @@ impl MemPoolDB {
             block_limit,
             stacks_epoch_id,
         );
+        let cost_estimate = self
+            .cost_estimator
+            .estimate_cost(&tx.payload, stacks_epoch_id)
+            .ok();
 
         let mut mempool_tx = self.tx_begin().map_err(MemPoolRejection::DBError)?;
 
@@ -1269,6 +2071,7 @@ impl MemPoolDB {
             true,
             event_observer,
             fee_rate,
+            cost_estimate.as_ref(),
         )?;
         mempool_tx.commit().map_err(MemPoolRejection::DBError)?;
         Ok(())
@@ -1296,6 +2099,10 @@ impl MemPoolDB {
             block_limit,
             stacks_epoch_id,
         );
+        let cost_estimate = self
+            .cost_estimator
+            .estimate_cost(&tx.payload, stacks_epoch_id)
+            .ok();
 
         let mut mempool_tx = self.tx_begin().map_err(MemPoolRejection::DBError)?;
 
@@ -1321,22 +2128,93 @@ impl MemPoolDB {
             false,
             None,
             fee_rate,
+            cost_estimate.as_ref(),
         )?;
         mempool_tx.commit().map_err(MemPoolRejection::DBError)?;
         Ok(())
     }
 
-    /// Drop transactions from the mempool
-    pub fn drop_txs(&mut self, txids: &[Txid]) -> Result<(), db_error> {
+    /// Drop transactions from the mempool, remembering why they were dropped
+    pub fn drop_txs(&mut self, txids: &[Txid], reason: MemPoolDropReason) -> Result<(), db_error> {
         let mempool_tx = self.tx_begin()?;
         let sql = "DELETE FROM mempool WHERE txid = ?";
         for txid in txids.iter() {
             mempool_tx.execute(sql, &[txid])?;
         }
+        MemPoolDB::record_dropped_txs(&mempool_tx, txids, reason)?;
         mempool_tx.commit()?;
         Ok(())
     }
 
+    /// Drop the transactions whose origin or sponsor nonce was already used in the fork ending at
+    /// `tip` by some other transaction -- for example, because a reorg made a different
+    /// transaction with the same nonce canonical.  Transactions that were themselves mined in
+    /// this fork are left for garbage collection.  This only does work when `tip` changes.
+    /// Returns the txids of the dropped transactions.
+    pub fn drop_stale_nonce_txs(
+        &mut self,
+        chainstate: &mut StacksChainState,
+        burn_dbconn: &dyn BurnStateDB,
+        tip: &StacksBlockId,
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+    ) -> Result<Vec<Txid>, ChainstateError> {
+        if self.stale_nonce_check_tip.as_ref() == Some(tip) {
+            return Ok(vec![]);
+        }
+
+        let addresses: Vec<String> = query_rows(
+            &self.db,
+            "SELECT origin_address FROM mempool UNION SELECT sponsor_address FROM mempool",
+            NO_PARAMS,
+        )?;
+
+        let mut stale_txids = vec![];
+        for address_str in addresses.into_iter() {
+            let address = match StacksAddress::from_string(&address_str) {
+                Some(address) => address,
+                None => continue,
+            };
+            let nonce = match chainstate.with_read_only_clarity_tx(burn_dbconn, tip, |conn| {
+                StacksChainState::get_account(conn, &address.clone().into()).nonce
+            }) {
+                Some(nonce) => nonce,
+                None => {
+                    // tip isn't known yet
+                    return Ok(vec![]);
+                }
+            };
+
+            let sql = "SELECT * FROM mempool WHERE (origin_address = ?1 AND origin_nonce < ?2) OR (sponsor_address = ?1 AND sponsor_nonce < ?2)";
+            let args: &[&dyn ToSql] = &[&address_str, &u64_to_sql(nonce)?];
+            let candidates: Vec<MemPoolTxInfo> = query_rows(&self.db, sql, args)?;
+            for candidate in candidates.into_iter() {
+                let txid = candidate.metadata.txid;
+                if stale_txids.contains(&txid) {
+                    continue;
+                }
+                if chainstate
+                    .get_transaction_block_in_fork(&txid, tip)?
+                    .is_none()
+                {
+                    stale_txids.push(txid);
+                }
+            }
+        }
+
+        if stale_txids.len() > 0 {
+            debug!("Drop {} mempool transaction(s) with stale nonces", stale_txids.len();
+                   "tip" => %tip);
+            self.drop_txs(&stale_txids, MemPoolDropReason::STALE_NONCE)?;
+            if let Some(event_observer) = event_observer {
+                event_observer
+                    .mempool_txs_dropped(stale_txids.clone(), MemPoolDropReason::STALE_NONCE);
+            }
+        }
+
+        self.stale_nonce_check_tip = Some(tip.clone());
+        Ok(stale_txids)
+    }
+
     #[cfg(test)]
     pub fn dump_txs(&self) {
         let sql = "SELECT * FROM mempool";
@@ -1366,6 +2244,7 @@ impl MemPoolDB {
 mod tests {
     use address::AddressHashMode;
     use burnchains::Address;
+    use burnchains::Txid;
     use chainstate::burn::ConsensusHash;
     use chainstate::stacks::db::test::chainstate_path;
     use chainstate::stacks::db::test::instantiate_chainstate;
@@ -1410,7 +2289,10 @@ mod tests {
         chainstate::stacks::db::StacksHeaderInfo, util::vrf::VRFProof, vm::costs::ExecutionCost,
     };
 
-    use super::MemPoolDB;
+    use crate::cost_estimates::metrics::UnitMetric;
+    use crate::cost_estimates::UnitEstimator;
+
+    use super::{MemPoolDB, MemPoolDropReason, MemPoolTx};
 
     const FOO_CONTRACT: &'static str = "(define-public (foo) (ok 1))
                                         (define-public (bar (x uint)) (ok x))";
@@ -1471,6 +2353,7 @@ mod tests {
             burn_header_height: burn_height as u32,
             burn_header_timestamp: 0,
             anchored_block_size: 1,
+            block_stats: None,
         };
 
         c_tx.commit_block();
@@ -2094,6 +2977,15 @@ mod tests {
             assert!(!MemPoolDB::db_has_tx(&mempool_tx, &old_txid).unwrap());
             assert!(MemPoolDB::db_has_tx(&mempool_tx, &txid).unwrap());
 
+            // replaced tx is remembered as dropped
+            let dropped = MemPoolDB::get_dropped_tx(&mempool_tx, &old_txid)
+                .unwrap()
+                .unwrap();
+            assert_eq!(dropped.reason, MemPoolDropReason::REPLACE_BY_FEE);
+            assert!(MemPoolDB::get_dropped_tx(&mempool_tx, &txid)
+                .unwrap()
+                .is_none());
+
             let tx_info_after = MemPoolDB::get_tx_metadata_by_address(
                 &mempool_tx,
                 true,
@@ -2197,11 +3089,60 @@ mod tests {
         .unwrap();
         assert_eq!(txs.len(), 0);
 
+        let txs_before_gc = MemPoolDB::get_txs_after(
+            &mempool.db,
+            &ConsensusHash([0x1; 20]),
+            &BlockHeaderHash([0x2; 32]),
+            0,
+            num_txs,
+        )
+        .unwrap();
+
+        // pretend the first transaction was mined in the genesis block.  The transaction log
+        // is not enabled, so only the block transaction index knows about it.
+        let genesis_block_id = StacksBlockHeader::make_index_block_hash(
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+        );
+        let mined_txid = txs_before_gc[0].metadata.txid.clone();
+        chainstate
+            .db()
+            .execute(
+                "INSERT INTO block_transactions (txid, index_block_hash) VALUES (?1, ?2)",
+                &[&mined_txid as &dyn ToSql, &genesis_block_id],
+            )
+            .unwrap();
+        assert_eq!(
+            chainstate
+                .get_transaction_block_in_fork(&mined_txid, &genesis_block_id)
+                .unwrap(),
+            Some((genesis_block_id.clone(), 0))
+        );
+
         eprintln!("garbage-collect");
         let mut mempool_tx = mempool.tx_begin().unwrap();
-        MemPoolDB::garbage_collect(&mut mempool_tx, 101, None).unwrap();
+        MemPoolDB::garbage_collect(
+            &mut mempool_tx,
+            101,
+            Some((&chainstate, &genesis_block_id)),
+            None,
+        )
+        .unwrap();
         mempool_tx.commit().unwrap();
 
+        // the mined transaction was removed, but not dropped
+        assert!(!MemPoolDB::db_has_tx(&mempool.db, &mined_txid).unwrap());
+        assert!(MemPoolDB::get_dropped_tx(&mempool.db, &mined_txid)
+            .unwrap()
+            .is_none());
+
+        for tx_info in txs_before_gc[1..].iter() {
+            let dropped = MemPoolDB::get_dropped_tx(&mempool.db, &tx_info.metadata.txid)
+                .unwrap()
+                .unwrap();
+            assert_eq!(dropped.reason, MemPoolDropReason::STALE_COLLECT);
+        }
+
         let txs = MemPoolDB::get_txs_after(
             &mempool.db,
             &ConsensusHash([0x1; 20]),
@@ -2363,4 +3304,838 @@ mod tests {
         assert_eq!(tx_info.metadata.len, second_len);
         assert_eq!(tx_info.metadata.tx_fee, 124);
     }
+
+    #[test]
+    fn mempool_db_nonce_status() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "mempool_db_nonce_status");
+        let chainstate_path = chainstate_path("mempool_db_nonce_status");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let origin_address = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x01; 20]),
+        };
+        let other_address = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x02; 20]),
+        };
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        for (address, nonce) in [
+            (&origin_address, 0),
+            (&origin_address, 1),
+            (&origin_address, 4),
+            (&origin_address, 6),
+            (&other_address, 2),
+        ]
+        .iter()
+        {
+            let spending_condition =
+                TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+                    signer: address.bytes.clone(),
+                    hash_mode: SinglesigHashMode::P2PKH,
+                    key_encoding: TransactionPublicKeyEncoding::Uncompressed,
+                    nonce: *nonce,
+                    tx_fee: 100,
+                    signature: MessageSignature::from_raw(&vec![0xff; 65]),
+                });
+            let tx = StacksTransaction {
+                version: TransactionVersion::Testnet,
+                chain_id: 0x80000000,
+                auth: TransactionAuth::Standard(spending_condition),
+                anchor_mode: TransactionAnchorMode::Any,
+                post_condition_mode: TransactionPostConditionMode::Allow,
+                post_conditions: Vec::new(),
+                payload: TransactionPayload::TokenTransfer(
+                    other_address.clone().into(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            };
+            let mut tx_bytes = vec![];
+            tx.consensus_serialize(&mut tx_bytes).unwrap();
+            MemPoolDB::try_add_tx(
+                &mut mempool_tx,
+                &mut chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                tx.txid(),
+                tx_bytes,
+                100,
+                100,
+                address,
+                *nonce,
+                address,
+                *nonce,
+                None,
+            )
+            .unwrap();
+        }
+        mempool_tx.commit().unwrap();
+
+        // nonces 2, 3 and 5 are missing, so 4 and 6 have to wait
+        let status = MemPoolDB::get_nonce_status(mempool.conn(), &origin_address, 0).unwrap();
+        assert_eq!(status.account_nonce, 0);
+        assert_eq!(status.next_nonce, 2);
+        assert_eq!(status.pending_nonces, vec![0, 1]);
+        assert_eq!(status.future_nonces, vec![4, 6]);
+        assert_eq!(status.missing_nonces, vec![2, 3, 5]);
+
+        // once the account's nonce moves past the mined transactions, they no longer count
+        let status = MemPoolDB::get_nonce_status(mempool.conn(), &origin_address, 4).unwrap();
+        assert_eq!(status.next_nonce, 5);
+        assert_eq!(status.pending_nonces, vec![4]);
+        assert_eq!(status.future_nonces, vec![6]);
+        assert_eq!(status.missing_nonces, vec![5]);
+
+        // an account with no gaps
+        let status = MemPoolDB::get_nonce_status(mempool.conn(), &other_address, 2).unwrap();
+        assert_eq!(status.next_nonce, 3);
+        assert_eq!(status.pending_nonces, vec![2]);
+        assert!(status.future_nonces.is_empty());
+        assert!(status.missing_nonces.is_empty());
+    }
+
+    #[test]
+    fn mempool_db_limits_and_eviction() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_db_limits_and_eviction");
+        let chainstate_path = chainstate_path("mempool_db_limits_and_eviction");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let make_tx = |signer: u8, nonce: u64, fee: u64| {
+            let spending_condition =
+                TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+                    signer: Hash160([signer; 20]),
+                    hash_mode: SinglesigHashMode::P2PKH,
+                    key_encoding: TransactionPublicKeyEncoding::Uncompressed,
+                    nonce,
+                    tx_fee: fee,
+                    signature: MessageSignature::from_raw(&vec![0xff; 65]),
+                });
+            StacksTransaction {
+                version: TransactionVersion::Testnet,
+                chain_id: 0x80000000,
+                auth: TransactionAuth::Standard(spending_condition),
+                anchor_mode: TransactionAnchorMode::Any,
+                post_condition_mode: TransactionPostConditionMode::Allow,
+                post_conditions: Vec::new(),
+                payload: TransactionPayload::TokenTransfer(
+                    StacksAddress {
+                        version: 22,
+                        bytes: Hash160([0xff; 20]),
+                    }
+                    .into(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            }
+        };
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        let mut add_tx = |mempool_tx: &mut MemPoolTx, signer: u8, tx: &StacksTransaction| {
+            let origin_address = StacksAddress {
+                version: 22,
+                bytes: Hash160([signer; 20]),
+            };
+            let mut tx_bytes = vec![];
+            tx.consensus_serialize(&mut tx_bytes).unwrap();
+            MemPoolDB::try_add_tx(
+                mempool_tx,
+                &mut chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                tx.txid(),
+                tx_bytes,
+                tx.get_tx_fee(),
+                100,
+                &origin_address,
+                tx.get_origin_nonce(),
+                &origin_address,
+                tx.get_origin_nonce(),
+                None,
+            )
+            .unwrap();
+        };
+
+        let signers = [1, 1, 2, 3, 4];
+        let txs: Vec<_> = vec![
+            make_tx(1, 0, 100),
+            make_tx(1, 1, 200),
+            make_tx(2, 0, 300),
+            make_tx(3, 0, 250),
+            make_tx(4, 0, 50),
+        ];
+        for (signer, tx) in signers[0..3].iter().zip(txs[0..3].iter()) {
+            add_tx(&mut mempool_tx, *signer, tx);
+            MemPoolDB::evict_by_fee_rate(&mut mempool_tx, 3, &tx.txid(), None).unwrap();
+        }
+
+        // per-origin usage doesn't count the transaction being replaced
+        let origin_1 = StacksAddress {
+            version: 22,
+            bytes: Hash160([1; 20]),
+        };
+        let (count, bytes) = MemPoolDB::get_origin_usage(&mempool_tx, &origin_1, 2).unwrap();
+        assert_eq!(count, 2);
+        assert!(bytes > 0);
+        let (count, _) = MemPoolDB::get_origin_usage(&mempool_tx, &origin_1, 1).unwrap();
+        assert_eq!(count, 1);
+
+        // a full mempool evicts its lowest fee-rate transaction to make room
+        add_tx(&mut mempool_tx, signers[3], &txs[3]);
+        MemPoolDB::evict_by_fee_rate(&mut mempool_tx, 3, &txs[3].txid(), None).unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &txs[0].txid()).unwrap());
+        for tx in txs[1..4].iter() {
+            assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx.txid()).unwrap());
+        }
+
+        // ...but won't take a transaction that pays less than everything in it
+        add_tx(&mut mempool_tx, signers[4], &txs[4]);
+        match MemPoolDB::evict_by_fee_rate(&mut mempool_tx, 3, &txs[4].txid(), None) {
+            Err(MemPoolRejection::MempoolFull) => {}
+            res => panic!("Expected a full mempool, got {:?}", res),
+        }
+        mempool_tx.commit().unwrap();
+
+        let (_, dropped) = MemPoolDB::get_dropped_txs(mempool.conn(), 0, 10).unwrap();
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].txid, txs[0].txid());
+        assert_eq!(dropped[0].reason, MemPoolDropReason::EVICTED_BY_FEE_RATE);
+    }
+
+    #[test]
+    fn mempool_db_test_rbf_increment() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_db_test_rbf_increment");
+        let chainstate_path = chainstate_path("mempool_db_test_rbf_increment");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+        mempool.set_replace_by_fee_increment(100);
+
+        let origin_address = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x01; 20]),
+        };
+        let spending_condition =
+            TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+                signer: origin_address.bytes.clone(),
+                hash_mode: SinglesigHashMode::P2PKH,
+                key_encoding: TransactionPublicKeyEncoding::Uncompressed,
+                nonce: 0,
+                tx_fee: 0,
+                signature: MessageSignature::from_raw(&vec![0xff; 65]),
+            });
+        let mut tx = StacksTransaction {
+            version: TransactionVersion::Testnet,
+            chain_id: 0x80000000,
+            auth: TransactionAuth::Standard(spending_condition),
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Allow,
+            post_conditions: Vec::new(),
+            payload: TransactionPayload::TokenTransfer(
+                origin_address.clone().into(),
+                123,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        };
+
+        let mut txids = vec![];
+        let mut results = vec![];
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        for fee in [123, 222, 223].iter() {
+            tx.set_tx_fee(*fee);
+            let txid = tx.txid();
+            let mut tx_bytes = vec![];
+            tx.consensus_serialize(&mut tx_bytes).unwrap();
+            let res = MemPoolDB::try_add_tx(
+                &mut mempool_tx,
+                &mut chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                txid.clone(),
+                tx_bytes,
+                *fee,
+                100,
+                &origin_address,
+                0,
+                &origin_address,
+                0,
+                None,
+            );
+            txids.push(txid);
+            results.push(res);
+        }
+        mempool_tx.commit().unwrap();
+
+        // the first transaction is accepted
+        assert!(results[0].is_ok());
+
+        // a fee bump smaller than the increment is not enough to replace it
+        match results[1] {
+            Err(MemPoolRejection::ConflictingNonceInMempool) => {}
+            ref r => panic!("Expected a nonce conflict, got {:?}", r),
+        }
+        assert!(!MemPoolDB::db_has_tx(mempool.conn(), &txids[1]).unwrap());
+
+        // a fee bump of at least the increment replaces it
+        assert!(results[2].is_ok());
+        assert!(!MemPoolDB::db_has_tx(mempool.conn(), &txids[0]).unwrap());
+        assert!(MemPoolDB::db_has_tx(mempool.conn(), &txids[2]).unwrap());
+
+        let (total, dropped) = MemPoolDB::get_dropped_txs(mempool.conn(), 0, 10).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(dropped[0].txid, txids[0]);
+        assert_eq!(dropped[0].reason, MemPoolDropReason::REPLACE_BY_FEE);
+    }
+
+    #[test]
+    fn mempool_db_inspect_txs() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "mempool_db_inspect_txs");
+        let chainstate_path = chainstate_path("mempool_db_inspect_txs");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let addr_x = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x01; 20]),
+        };
+        let addr_y = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x02; 20]),
+        };
+
+        // (origin, nonce, fee)
+        let tx_params = vec![(&addr_x, 0, 100), (&addr_y, 0, 300), (&addr_x, 1, 200)];
+        let mut txids = vec![];
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        for (origin_address, nonce, fee) in tx_params.into_iter() {
+            let spending_condition =
+                TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+                    signer: origin_address.bytes.clone(),
+                    hash_mode: SinglesigHashMode::P2PKH,
+                    key_encoding: TransactionPublicKeyEncoding::Uncompressed,
+                    nonce,
+                    tx_fee: fee,
+                    signature: MessageSignature::from_raw(&vec![0xff; 65]),
+                });
+            let tx = StacksTransaction {
+                version: TransactionVersion::Testnet,
+                chain_id: 0x80000000,
+                auth: TransactionAuth::Standard(spending_condition),
+                anchor_mode: TransactionAnchorMode::Any,
+                post_condition_mode: TransactionPostConditionMode::Allow,
+                post_conditions: Vec::new(),
+                payload: TransactionPayload::TokenTransfer(
+                    addr_y.clone().into(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            };
+
+            let txid = tx.txid();
+            let mut tx_bytes = vec![];
+            tx.consensus_serialize(&mut tx_bytes).unwrap();
+
+            MemPoolDB::try_add_tx(
+                &mut mempool_tx,
+                &mut chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                txid.clone(),
+                tx_bytes,
+                fee,
+                100,
+                origin_address,
+                nonce,
+                origin_address,
+                nonce,
+                None,
+            )
+            .unwrap();
+            txids.push(txid);
+        }
+
+        // only the last transaction has a fee rate estimate
+        mempool_tx
+            .execute(
+                "INSERT INTO fee_estimates (txid, fee_rate) VALUES (?1, ?2)",
+                rusqlite::params![&txids[2], 5.0f64],
+            )
+            .unwrap();
+        mempool_tx.commit().unwrap();
+
+        // estimated first, then by decreasing fee
+        let (total, txs) = MemPoolDB::get_txs_by_fee_rate(mempool.conn(), 0, 10).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(
+            txs.iter()
+                .map(|tx| tx.tx.metadata.txid.clone())
+                .collect::<Vec<_>>(),
+            vec![txids[2].clone(), txids[1].clone(), txids[0].clone()]
+        );
+        assert_eq!(txs[0].fee_rate, Some(5.0));
+        assert_eq!(txs[1].fee_rate, None);
+
+        for (i, tx) in txs.iter().enumerate() {
+            assert_eq!(
+                MemPoolDB::get_fee_rate_rank(mempool.conn(), tx).unwrap(),
+                i as u64
+            );
+        }
+
+        // pagination
+        let (total, txs) = MemPoolDB::get_txs_by_fee_rate(mempool.conn(), 1, 1).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].tx.metadata.txid, txids[1]);
+
+        // by address, in nonce order
+        let (total, txs) = MemPoolDB::get_txs_by_address(mempool.conn(), &addr_x, 0, 10).unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(txs[0].tx.metadata.txid, txids[0]);
+        assert_eq!(txs[1].tx.metadata.txid, txids[2]);
+
+        let (total, txs) = MemPoolDB::get_txs_by_address(
+            mempool.conn(),
+            &StacksAddress {
+                version: 22,
+                bytes: Hash160([0x03; 20]),
+            },
+            0,
+            10,
+        )
+        .unwrap();
+        assert_eq!(total, 0);
+        assert_eq!(txs.len(), 0);
+    }
+
+    #[test]
+    fn mempool_db_snapshot() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "mempool_db_snapshot");
+        let chainstate_path = chainstate_path("mempool_db_snapshot");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let addr = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x01; 20]),
+        };
+        let mut txids = vec![];
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        for nonce in 0..2 {
+            let spending_condition =
+                TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+                    signer: addr.bytes.clone(),
+                    hash_mode: SinglesigHashMode::P2PKH,
+                    key_encoding: TransactionPublicKeyEncoding::Uncompressed,
+                    nonce,
+                    tx_fee: 100,
+                    signature: MessageSignature::from_raw(&vec![0xff; 65]),
+                });
+            let tx = StacksTransaction {
+                version: TransactionVersion::Testnet,
+                chain_id: 0x80000000,
+                auth: TransactionAuth::Standard(spending_condition),
+                anchor_mode: TransactionAnchorMode::Any,
+                post_condition_mode: TransactionPostConditionMode::Allow,
+                post_conditions: Vec::new(),
+                payload: TransactionPayload::TokenTransfer(
+                    addr.clone().into(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            };
+
+            let txid = tx.txid();
+            let mut tx_bytes = vec![];
+            tx.consensus_serialize(&mut tx_bytes).unwrap();
+
+            MemPoolDB::try_add_tx(
+                &mut mempool_tx,
+                &mut chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                txid.clone(),
+                tx_bytes,
+                100,
+                100,
+                &addr,
+                nonce,
+                &addr,
+                nonce,
+                None,
+            )
+            .unwrap();
+            txids.push(txid);
+        }
+        mempool_tx.commit().unwrap();
+
+        let snapshot_path = format!("{}/mempool-snapshot.sqlite", &chainstate_path);
+        let mut snapshot = mempool
+            .snapshot(
+                &snapshot_path,
+                Box::new(UnitEstimator),
+                Box::new(UnitMetric),
+            )
+            .unwrap();
+        for txid in txids.iter() {
+            assert!(MemPoolDB::get_tx(snapshot.conn(), txid).unwrap().is_some());
+        }
+
+        // the two databases change independently
+        snapshot
+            .drop_txs(&txids[0..1], MemPoolDropReason::REPLACE_BY_FEE)
+            .unwrap();
+        mempool
+            .drop_txs(&txids[1..2], MemPoolDropReason::REPLACE_BY_FEE)
+            .unwrap();
+        assert!(MemPoolDB::get_tx(snapshot.conn(), &txids[0])
+            .unwrap()
+            .is_none());
+        assert!(MemPoolDB::get_tx(snapshot.conn(), &txids[1])
+            .unwrap()
+            .is_some());
+        assert!(MemPoolDB::get_tx(mempool.conn(), &txids[0])
+            .unwrap()
+            .is_some());
+        assert!(MemPoolDB::get_tx(mempool.conn(), &txids[1])
+            .unwrap()
+            .is_none());
+
+        // snapshots replace whatever was there before
+        let snapshot = mempool
+            .snapshot(
+                &snapshot_path,
+                Box::new(UnitEstimator),
+                Box::new(UnitMetric),
+            )
+            .unwrap();
+        assert!(MemPoolDB::get_tx(snapshot.conn(), &txids[0])
+            .unwrap()
+            .is_some());
+        assert!(MemPoolDB::get_tx(snapshot.conn(), &txids[1])
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn mempool_db_dropped_txs_log() {
+        let chainstate_path = chainstate_path("mempool_db_dropped_txs_log");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let replaced = vec![Txid([0x01; 32]), Txid([0x02; 32])];
+        let stale = vec![Txid([0x03; 32])];
+        mempool
+            .drop_txs(&replaced, MemPoolDropReason::REPLACE_BY_FEE)
+            .unwrap();
+        mempool
+            .drop_txs(&stale, MemPoolDropReason::STALE_NONCE)
+            .unwrap();
+
+        let (total, dropped) = MemPoolDB::get_dropped_txs(mempool.conn(), 0, 2).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(dropped.len(), 2);
+
+        let (_, rest) = MemPoolDB::get_dropped_txs(mempool.conn(), 2, 2).unwrap();
+        assert_eq!(rest.len(), 1);
+
+        for entry in dropped.iter().chain(rest.iter()) {
+            if stale.contains(&entry.txid) {
+                assert_eq!(entry.reason, MemPoolDropReason::STALE_NONCE);
+            } else {
+                assert!(replaced.contains(&entry.txid));
+                assert_eq!(entry.reason, MemPoolDropReason::REPLACE_BY_FEE);
+            }
+        }
+
+        // reason codes round-trip through their string form
+        for reason in [
+            MemPoolDropReason::REPLACE_ACROSS_FORK,
+            MemPoolDropReason::REPLACE_BY_FEE,
+            MemPoolDropReason::STALE_COLLECT,
+            MemPoolDropReason::TOO_EXPENSIVE,
+            MemPoolDropReason::STALE_NONCE,
+        ]
+        .iter()
+        {
+            assert_eq!(
+                reason.to_string().parse::<MemPoolDropReason>().unwrap(),
+                *reason
+            );
+        }
+    }
+
+    #[test]
+    fn mempool_db_warm_restart() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "mempool_db_warm_restart");
+        let chainstate_path = chainstate_path("mempool_db_warm_restart");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let addr = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x01; 20]),
+        };
+        let spending_condition =
+            TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+                signer: addr.bytes.clone(),
+                hash_mode: SinglesigHashMode::P2PKH,
+                key_encoding: TransactionPublicKeyEncoding::Uncompressed,
+                nonce: 0,
+                tx_fee: 100,
+                signature: MessageSignature::from_raw(&vec![0xff; 65]),
+            });
+        let tx = StacksTransaction {
+            version: TransactionVersion::Testnet,
+            chain_id: 0x80000000,
+            auth: TransactionAuth::Standard(spending_condition),
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Allow,
+            post_conditions: Vec::new(),
+            payload: TransactionPayload::TokenTransfer(
+                addr.clone().into(),
+                123,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        };
+        let txid = tx.txid();
+        let mut tx_bytes = vec![];
+        tx.consensus_serialize(&mut tx_bytes).unwrap();
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &ConsensusHash([0x1; 20]),
+            &BlockHeaderHash([0x2; 32]),
+            txid.clone(),
+            tx_bytes,
+            100,
+            100,
+            &addr,
+            0,
+            &addr,
+            0,
+            None,
+        )
+        .unwrap();
+        mempool_tx.commit().unwrap();
+        mempool.update_last_known_nonces(&addr, 0).unwrap();
+        drop(mempool);
+
+        // the transaction survives a restart, but its cached nonce is cleared
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+        assert_eq!(mempool.prepare_warm_restart().unwrap(), 1);
+        let txinfo = MemPoolDB::get_tx(mempool.conn(), &txid).unwrap().unwrap();
+        assert_eq!(txinfo.metadata.last_known_origin_nonce, None);
+        assert_eq!(txinfo.metadata.last_known_sponsor_nonce, None);
+
+        let txs = MemPoolDB::get_txs_to_rebroadcast(mempool.conn(), 10).unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].txid(), txid);
+        assert!(MemPoolDB::get_txs_to_rebroadcast(mempool.conn(), 0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn mempool_db_sync_digest() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "mempool_db_sync_digest");
+        let chainstate_path = chainstate_path("mempool_db_sync_digest");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let addr = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x01; 20]),
+        };
+        let mut txids = vec![];
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        for nonce in 0..3 {
+            let tx_fee = 100 * (nonce + 1);
+            let spending_condition =
+                TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+                    signer: addr.bytes.clone(),
+                    hash_mode: SinglesigHashMode::P2PKH,
+                    key_encoding: TransactionPublicKeyEncoding::Uncompressed,
+                    nonce,
+                    tx_fee,
+                    signature: MessageSignature::from_raw(&vec![0xff; 65]),
+                });
+            let tx = StacksTransaction {
+                version: TransactionVersion::Testnet,
+                chain_id: 0x80000000,
+                auth: TransactionAuth::Standard(spending_condition),
+                anchor_mode: TransactionAnchorMode::Any,
+                post_condition_mode: TransactionPostConditionMode::Allow,
+                post_conditions: Vec::new(),
+                payload: TransactionPayload::TokenTransfer(
+                    addr.clone().into(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            };
+            let txid = tx.txid();
+            let mut tx_bytes = vec![];
+            tx.consensus_serialize(&mut tx_bytes).unwrap();
+            let len = tx_bytes.len() as u64;
+
+            MemPoolDB::try_add_tx(
+                &mut mempool_tx,
+                &mut chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                txid.clone(),
+                tx_bytes,
+                tx_fee,
+                100,
+                &addr,
+                nonce,
+                &addr,
+                nonce,
+                None,
+            )
+            .unwrap();
+            txids.push((txid, len));
+        }
+        mempool_tx.commit().unwrap();
+
+        // highest fee first
+        let digest_txids = MemPoolDB::get_txids_by_fee_rate(mempool.conn(), 10).unwrap();
+        assert_eq!(
+            digest_txids,
+            vec![txids[2].0.clone(), txids[1].0.clone(), txids[0].0.clone()]
+        );
+        assert_eq!(
+            MemPoolDB::get_txids_by_fee_rate(mempool.conn(), 1).unwrap(),
+            vec![txids[2].0.clone()]
+        );
+
+        // a peer that has the highest-fee transaction gets the other two
+        let mut filter = TxidBloomFilter::new(1, 0.001);
+        filter.insert(&txids[2].0);
+        let missing = MemPoolDB::get_txs_missing_from(mempool.conn(), &filter, 10, u64::MAX)
+            .unwrap()
+            .into_iter()
+            .map(|tx| tx.txid())
+            .collect::<Vec<_>>();
+        assert_eq!(missing, vec![txids[1].0.clone(), txids[0].0.clone()]);
+
+        // capped by count and size
+        let missing =
+            MemPoolDB::get_txs_missing_from(mempool.conn(), &filter, 1, u64::MAX).unwrap();
+        assert_eq!(missing.len(), 1);
+        let missing =
+            MemPoolDB::get_txs_missing_from(mempool.conn(), &filter, 10, txids[1].1).unwrap();
+        assert_eq!(missing.len(), 1);
+        assert!(
+            MemPoolDB::get_txs_missing_from(mempool.conn(), &filter, 0, u64::MAX)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn mempool_db_subscription() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "mempool_db_subscription");
+        let chainstate_path = chainstate_path("mempool_db_subscription");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let addr = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x01; 20]),
+        };
+        let mut add_tx = |mempool: &mut MemPoolDB, nonce: u64, tx_fee: u64| {
+            let spending_condition =
+                TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+                    signer: addr.bytes.clone(),
+                    hash_mode: SinglesigHashMode::P2PKH,
+                    key_encoding: TransactionPublicKeyEncoding::Uncompressed,
+                    nonce,
+                    tx_fee,
+                    signature: MessageSignature::from_raw(&vec![0xff; 65]),
+                });
+            let tx = StacksTransaction {
+                version: TransactionVersion::Testnet,
+                chain_id: 0x80000000,
+                auth: TransactionAuth::Standard(spending_condition),
+                anchor_mode: TransactionAnchorMode::Any,
+                post_condition_mode: TransactionPostConditionMode::Allow,
+                post_conditions: Vec::new(),
+                payload: TransactionPayload::TokenTransfer(
+                    addr.clone().into(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            };
+            let txid = tx.txid();
+            let mut tx_bytes = vec![];
+            tx.consensus_serialize(&mut tx_bytes).unwrap();
+
+            let mut mempool_tx = mempool.tx_begin().unwrap();
+            MemPoolDB::try_add_tx(
+                &mut mempool_tx,
+                &mut chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                txid.clone(),
+                tx_bytes,
+                tx_fee,
+                100,
+                &addr,
+                nonce,
+                &addr,
+                nonce,
+                None,
+            )
+            .unwrap();
+            mempool_tx.commit().unwrap();
+            txid
+        };
+        let txids = |txs: &[MemPoolTxInfo]| -> Vec<Txid> {
+            txs.iter().map(|tx| tx.metadata.txid.clone()).collect()
+        };
+
+        // transactions admitted before subscribing are not returned
+        add_tx(&mut mempool, 0, 100);
+        let mut subscription = mempool.subscribe().unwrap();
+        assert!(mempool
+            .poll_subscription(&mut subscription)
+            .unwrap()
+            .is_empty());
+
+        let first = add_tx(&mut mempool, 2, 100);
+        let second = add_tx(&mut mempool, 1, 100);
+        let arrivals = mempool.poll_subscription(&mut subscription).unwrap();
+        assert_eq!(txids(&arrivals), vec![first.clone(), second.clone()]);
+        assert!(mempool
+            .poll_subscription(&mut subscription)
+            .unwrap()
+            .is_empty());
+
+        // deferred transactions come back first
+        subscription.defer(arrivals[0].clone());
+        let third = add_tx(&mut mempool, 3, 100);
+        assert_eq!(
+            txids(&mempool.poll_subscription(&mut subscription).unwrap()),
+            vec![first.clone(), third.clone()]
+        );
+
+        // a replaced transaction is superseded by its replacement
+        subscription.defer(arrivals[1].clone());
+        let replacement = add_tx(&mut mempool, 1, 200);
+        assert_eq!(
+            txids(&mempool.poll_subscription(&mut subscription).unwrap()),
+            vec![replacement]
+        );
+
+        // other subscriptions are unaffected
+        let mut other = mempool.subscribe().unwrap();
+        let fourth = add_tx(&mut mempool, 4, 100);
+        assert_eq!(
+            txids(&mempool.poll_subscription(&mut other).unwrap()),
+            vec![fourth.clone()]
+        );
+        assert_eq!(
+            txids(&mempool.poll_subscription(&mut subscription).unwrap()),
+            vec![fourth]
+        );
+    }
 }
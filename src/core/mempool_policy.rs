@@ -0,0 +1,329 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Node-local mempool admission policies.
+//!
+//! These run after a transaction has passed the consensus validity checks, and let a node
+//! operator refuse transactions that would be valid in a block but that they do not want to
+//! relay or mine -- for example, to protect a public RPC endpoint.  They are not part of
+//! consensus.
+//!
+//! The same policies can be given to the `Relayer` to decide which transactions to forward to
+//! our neighbors, independently of which ones we store.
+
+use std::collections::HashSet;
+
+use chainstate::stacks::{StacksTransaction, TransactionPayload};
+use vm::costs::ExecutionCost;
+use vm::types::QualifiedContractIdentifier;
+
+/// What an admission policy gets to look at when deciding whether to admit a transaction
+pub struct MemPoolAdmissionInfo<'a> {
+    pub tx: &'a StacksTransaction,
+    /// serialized length of the transaction
+    pub tx_len: u64,
+    /// estimated fee rate, if the node's cost estimator has one
+    pub fee_rate: Option<f64>,
+    /// estimated execution cost, if the node's cost estimator has one
+    pub estimated_cost: Option<&'a ExecutionCost>,
+}
+
+pub trait MemPoolAdmissionPolicy: Send {
+    /// Name of this policy, reported to the submitter when it rejects a transaction
+    fn name(&self) -> &str;
+
+    /// Decide whether to admit a consensus-valid transaction.  Returns the reason for rejecting
+    /// it, if it is rejected.
+    fn check(&self, info: &MemPoolAdmissionInfo) -> Result<(), String>;
+}
+
+/// Reject transactions that pay less than a minimum fee rate.  If the node has no fee rate
+/// estimate for a transaction, its fee per byte is used instead.
+pub struct MinFeeRatePolicy {
+    pub min_fee_rate: f64,
+}
+
+impl MemPoolAdmissionPolicy for MinFeeRatePolicy {
+    fn name(&self) -> &str {
+        "MinFeeRate"
+    }
+
+    fn check(&self, info: &MemPoolAdmissionInfo) -> Result<(), String> {
+        let fee_rate = match info.fee_rate {
+            Some(fee_rate) => fee_rate,
+            None => info.tx.get_tx_fee() as f64 / (info.tx_len.max(1) as f64),
+        };
+        if fee_rate < self.min_fee_rate {
+            return Err(format!(
+                "Fee rate {} is below the minimum of {}",
+                fee_rate, self.min_fee_rate
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Restrict which contracts transactions may call.  If an allow list is given, only calls to
+/// contracts on it are admitted; calls to contracts on the deny list are always rejected.
+/// Transactions that do not call a contract are unaffected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractCallPolicy {
+    pub allow_list: Option<HashSet<QualifiedContractIdentifier>>,
+    pub deny_list: HashSet<QualifiedContractIdentifier>,
+}
+
+impl ContractCallPolicy {
+    /// Decide whether `tx` may call the contract it calls, if any
+    pub fn check_tx(&self, tx: &StacksTransaction) -> Result<(), String> {
+        let contract_id = match tx.payload {
+            TransactionPayload::ContractCall(ref cc) => cc.to_clarity_contract_id(),
+            _ => return Ok(()),
+        };
+        if self.deny_list.contains(&contract_id) {
+            return Err(format!("Calls to {} are not accepted", &contract_id));
+        }
+        if let Some(ref allow_list) = self.allow_list {
+            if !allow_list.contains(&contract_id) {
+                return Err(format!("Calls to {} are not accepted", &contract_id));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MemPoolAdmissionPolicy for ContractCallPolicy {
+    fn name(&self) -> &str {
+        "ContractCall"
+    }
+
+    fn check(&self, info: &MemPoolAdmissionInfo) -> Result<(), String> {
+        self.check_tx(info.tx)
+    }
+}
+
+/// Reject transactions whose estimated execution cost exceeds a limit in any dimension.
+/// Transactions without a cost estimate are admitted.
+pub struct MaxCostPolicy {
+    pub max_cost: ExecutionCost,
+}
+
+impl MemPoolAdmissionPolicy for MaxCostPolicy {
+    fn name(&self) -> &str {
+        "MaxCost"
+    }
+
+    fn check(&self, info: &MemPoolAdmissionInfo) -> Result<(), String> {
+        match info.estimated_cost {
+            Some(cost) if cost.exceeds(&self.max_cost) => Err(format!(
+                "Estimated cost {} exceeds the maximum of {}",
+                cost, &self.max_cost
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reject transactions larger than a maximum serialized size.
+pub struct MaxTxSizePolicy {
+    pub max_tx_size: u64,
+}
+
+impl MemPoolAdmissionPolicy for MaxTxSizePolicy {
+    fn name(&self) -> &str {
+        "MaxTxSize"
+    }
+
+    fn check(&self, info: &MemPoolAdmissionInfo) -> Result<(), String> {
+        if info.tx_len > self.max_tx_size {
+            return Err(format!(
+                "Transaction size {} exceeds the maximum of {}",
+                info.tx_len, self.max_tx_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::chainstate::StacksAddress;
+    use chainstate::stacks::{
+        SinglesigHashMode, SinglesigSpendingCondition, TokenTransferMemo, TransactionAnchorMode,
+        TransactionAuth, TransactionContractCall, TransactionPostConditionMode,
+        TransactionPublicKeyEncoding, TransactionSpendingCondition, TransactionVersion,
+    };
+    use util::hash::Hash160;
+    use util::secp256k1::MessageSignature;
+
+    fn make_tx(payload: TransactionPayload, fee: u64) -> StacksTransaction {
+        let spending_condition =
+            TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+                signer: Hash160([0x11; 20]),
+                hash_mode: SinglesigHashMode::P2PKH,
+                key_encoding: TransactionPublicKeyEncoding::Uncompressed,
+                nonce: 0,
+                tx_fee: fee,
+                signature: MessageSignature::from_raw(&vec![0xff; 65]),
+            });
+        StacksTransaction {
+            version: TransactionVersion::Testnet,
+            chain_id: 0x80000000,
+            auth: TransactionAuth::Standard(spending_condition),
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Allow,
+            post_conditions: Vec::new(),
+            payload,
+        }
+    }
+
+    fn make_contract_call(contract_name: &str) -> TransactionPayload {
+        TransactionPayload::ContractCall(TransactionContractCall {
+            address: StacksAddress {
+                version: 26,
+                bytes: Hash160([0x22; 20]),
+            },
+            contract_name: contract_name.into(),
+            function_name: "hello".into(),
+            function_args: vec![],
+        })
+    }
+
+    #[test]
+    fn test_min_fee_rate_policy() {
+        let policy = MinFeeRatePolicy { min_fee_rate: 2.0 };
+        let transfer = TransactionPayload::TokenTransfer(
+            StacksAddress {
+                version: 26,
+                bytes: Hash160([0x33; 20]),
+            }
+            .into(),
+            1,
+            TokenTransferMemo([0u8; 34]),
+        );
+        let tx = make_tx(transfer, 100);
+
+        // falls back to the fee per byte
+        let info = MemPoolAdmissionInfo {
+            tx: &tx,
+            tx_len: 100,
+            fee_rate: None,
+            estimated_cost: None,
+        };
+        assert!(policy.check(&info).is_err());
+        let info = MemPoolAdmissionInfo { tx_len: 50, ..info };
+        assert!(policy.check(&info).is_ok());
+
+        // prefers the estimate
+        let info = MemPoolAdmissionInfo {
+            fee_rate: Some(1.5),
+            ..info
+        };
+        assert!(policy.check(&info).is_err());
+    }
+
+    #[test]
+    fn test_contract_call_policy() {
+        let allowed = make_tx(make_contract_call("allowed"), 100);
+        let denied = make_tx(make_contract_call("denied"), 100);
+        let other = make_tx(make_contract_call("other"), 100);
+        let contract_id = |tx: &StacksTransaction| match tx.payload {
+            TransactionPayload::ContractCall(ref cc) => cc.to_clarity_contract_id(),
+            _ => unreachable!(),
+        };
+        let check = |policy: &ContractCallPolicy, tx: &StacksTransaction| {
+            policy.check(&MemPoolAdmissionInfo {
+                tx,
+                tx_len: 100,
+                fee_rate: None,
+                estimated_cost: None,
+            })
+        };
+
+        let mut deny_list = HashSet::new();
+        deny_list.insert(contract_id(&denied));
+        let policy = ContractCallPolicy {
+            allow_list: None,
+            deny_list: deny_list.clone(),
+        };
+        assert!(check(&policy, &allowed).is_ok());
+        assert!(check(&policy, &denied).is_err());
+        assert!(check(&policy, &other).is_ok());
+
+        let mut allow_list = HashSet::new();
+        allow_list.insert(contract_id(&allowed));
+        allow_list.insert(contract_id(&denied));
+        let policy = ContractCallPolicy {
+            allow_list: Some(allow_list),
+            deny_list,
+        };
+        assert!(check(&policy, &allowed).is_ok());
+        assert!(check(&policy, &denied).is_err());
+        assert!(check(&policy, &other).is_err());
+    }
+
+    #[test]
+    fn test_max_cost_policy() {
+        let policy = MaxCostPolicy {
+            max_cost: ExecutionCost {
+                write_length: 100,
+                write_count: 100,
+                read_length: 100,
+                read_count: 100,
+                runtime: 100,
+            },
+        };
+        let tx = make_tx(make_contract_call("hello"), 100);
+        let mut cost = ExecutionCost {
+            write_length: 10,
+            write_count: 10,
+            read_length: 10,
+            read_count: 10,
+            runtime: 100,
+        };
+        let check = |cost: Option<&ExecutionCost>| {
+            policy.check(&MemPoolAdmissionInfo {
+                tx: &tx,
+                tx_len: 100,
+                fee_rate: None,
+                estimated_cost: cost,
+            })
+        };
+
+        assert!(check(None).is_ok());
+        assert!(check(Some(&cost)).is_ok());
+        cost.runtime = 101;
+        assert!(check(Some(&cost)).is_err());
+    }
+
+    #[test]
+    fn test_max_tx_size_policy() {
+        let policy = MaxTxSizePolicy { max_tx_size: 100 };
+        let tx = make_tx(make_contract_call("hello"), 100);
+        let check = |tx_len: u64| {
+            policy.check(&MemPoolAdmissionInfo {
+                tx: &tx,
+                tx_len,
+                fee_rate: None,
+                estimated_cost: None,
+            })
+        };
+
+        assert!(check(99).is_ok());
+        assert!(check(100).is_ok());
+        assert!(check(101).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Strategies for choosing the order in which a miner considers mempool transactions.
+//!
+//! When a miner assembles a block or microblock, it walks the mempool and tries each transaction
+//! it is handed until the block is full or it runs out of time.  A walk strategy decides which
+//! transaction comes next.  Whatever the strategy, only transactions whose origin and sponsor
+//! nonces are the next ones for their accounts are eligible, so every strategy yields a valid
+//! ordering; strategies differ only in how much fee they collect from a block's budget.
+//!
+//! Strategies are node-local and not part of consensus.
+
+use core::mempool::{MemPoolDB, MemPoolTxFeeRate, MemPoolTxInfo};
+use core::StacksEpochId;
+use cost_estimates::CostEstimator;
+use util::db::DBConn;
+use util::db::Error as db_error;
+use vm::costs::ExecutionCost;
+
+/// Number of candidates the knapsack strategy weighs against each other at each step
+pub const KNAPSACK_WALK_CANDIDATES: u64 = 128;
+
+pub trait MemPoolWalkStrategy: Send {
+    /// Name of this strategy, for logging
+    fn name(&self) -> &str;
+
+    /// Called once before the miner starts considering transactions for a new block or
+    /// microblock, with the budget it has to fill.
+    fn begin_block(&mut self, _block_limit: &ExecutionCost, _epoch_id: &StacksEpochId) {}
+
+    /// Choose the next transaction to consider from among the eligible ones, and whether the
+    /// miner should report its execution cost back to the cost estimator.  `prefer_no_estimate`
+    /// is set when the miner has decided (per its `consider_no_estimate_tx_prob` setting) that
+    /// this step should go to a transaction without a fee rate estimate, if there is one.
+    /// Returns None once no transactions are left.
+    fn next_tx(
+        &mut self,
+        conn: &DBConn,
+        cost_estimator: &dyn CostEstimator,
+        prefer_no_estimate: bool,
+    ) -> Result<Option<(MemPoolTxInfo, bool)>, db_error>;
+
+    /// Called when a transaction this strategy chose is handed to the miner
+    fn tx_considered(&mut self, _tx: &MemPoolTxInfo, _cost_estimator: &dyn CostEstimator) {}
+}
+
+/// Consider transactions in decreasing order of fee rate.  Transactions without a fee rate
+/// estimate are considered in decreasing order of fee, either when the miner asks for them or
+/// once there are no estimated transactions left.  This is the default strategy.
+pub struct FeeRateWalkStrategy;
+
+impl MemPoolWalkStrategy for FeeRateWalkStrategy {
+    fn name(&self) -> &str {
+        "FeeRate"
+    }
+
+    fn next_tx(
+        &mut self,
+        conn: &DBConn,
+        _cost_estimator: &dyn CostEstimator,
+        prefer_no_estimate: bool,
+    ) -> Result<Option<(MemPoolTxInfo, bool)>, db_error> {
+        if prefer_no_estimate {
+            match MemPoolDB::get_next_tx_to_consider_no_estimate(conn)? {
+                Some(result) => Ok(Some(result)),
+                None => MemPoolDB::get_next_tx_to_consider_with_estimate(conn),
+            }
+        } else {
+            match MemPoolDB::get_next_tx_to_consider_with_estimate(conn)? {
+                Some(result) => Ok(Some(result)),
+                None => MemPoolDB::get_next_tx_to_consider_no_estimate(conn),
+            }
+        }
+    }
+}
+
+/// Consider transactions in the order the mempool accepted them.
+pub struct FifoWalkStrategy;
+
+impl MemPoolWalkStrategy for FifoWalkStrategy {
+    fn name(&self) -> &str {
+        "Fifo"
+    }
+
+    fn next_tx(
+        &mut self,
+        conn: &DBConn,
+        _cost_estimator: &dyn CostEstimator,
+        _prefer_no_estimate: bool,
+    ) -> Result<Option<(MemPoolTxInfo, bool)>, db_error> {
+        Ok(
+            MemPoolDB::get_next_tx_to_consider_fifo(conn)?.map(|txinfo| {
+                let update_estimate = txinfo.fee_rate.is_none();
+                (txinfo.tx, update_estimate)
+            }),
+        )
+    }
+}
+
+/// Treat block assembly as a multi-dimensional knapsack problem, and fill it greedily by fee
+/// density: at each step, take the transaction that pays the most per unit of the block budget
+/// it would use up.  A transaction's use of the budget is measured in whichever cost dimension
+/// it consumes the largest share of what remains, so transactions that would exhaust a
+/// nearly-full dimension are put off in favor of ones that use the rest of the budget.
+///
+/// The budget used so far is tracked with the cost estimator's estimates of the transactions
+/// handed to the miner.  Transactions without a cost estimate are considered in decreasing
+/// order of fee, when the miner asks for them or once the estimated ones run out.
+pub struct KnapsackWalkStrategy {
+    /// how many of the highest fee rate candidates to weigh at each step
+    pub max_candidates: u64,
+    block_limit: ExecutionCost,
+    epoch_id: Option<StacksEpochId>,
+    used: ExecutionCost,
+}
+
+impl KnapsackWalkStrategy {
+    pub fn new(max_candidates: u64) -> KnapsackWalkStrategy {
+        KnapsackWalkStrategy {
+            max_candidates,
+            block_limit: ExecutionCost::max_value(),
+            epoch_id: None,
+            used: ExecutionCost::zero(),
+        }
+    }
+
+    fn remaining(&self) -> ExecutionCost {
+        ExecutionCost {
+            write_length: self
+                .block_limit
+                .write_length
+                .saturating_sub(self.used.write_length),
+            write_count: self
+                .block_limit
+                .write_count
+                .saturating_sub(self.used.write_count),
+            read_length: self
+                .block_limit
+                .read_length
+                .saturating_sub(self.used.read_length),
+            read_count: self
+                .block_limit
+                .read_count
+                .saturating_sub(self.used.read_count),
+            runtime: self.block_limit.runtime.saturating_sub(self.used.runtime),
+        }
+    }
+
+    fn estimate_cost(
+        &self,
+        txinfo: &MemPoolTxInfo,
+        cost_estimator: &dyn CostEstimator,
+    ) -> Option<ExecutionCost> {
+        let epoch_id = self.epoch_id.as_ref()?;
+        cost_estimator
+            .estimate_cost(&txinfo.tx.payload, epoch_id)
+            .ok()
+    }
+
+    /// Fee paid per percent of the remaining budget used, in the transaction's most-used
+    /// dimension
+    pub fn fee_density(remaining: &ExecutionCost, tx_fee: u64, cost: &ExecutionCost) -> u64 {
+        let percent_used = remaining.proportion_largest_dimension(cost);
+        tx_fee / percent_used.max(1)
+    }
+}
+
+impl MemPoolWalkStrategy for KnapsackWalkStrategy {
+    fn name(&self) -> &str {
+        "Knapsack"
+    }
+
+    fn begin_block(&mut self, block_limit: &ExecutionCost, epoch_id: &StacksEpochId) {
+        self.block_limit = block_limit.clone();
+        self.epoch_id = Some(epoch_id.clone());
+        self.used = ExecutionCost::zero();
+    }
+
+    fn next_tx(
+        &mut self,
+        conn: &DBConn,
+        cost_estimator: &dyn CostEstimator,
+        prefer_no_estimate: bool,
+    ) -> Result<Option<(MemPoolTxInfo, bool)>, db_error> {
+        if prefer_no_estimate {
+            if let Some(result) = MemPoolDB::get_next_tx_to_consider_no_estimate(conn)? {
+                return Ok(Some(result));
+            }
+        }
+
+        let remaining = self.remaining();
+        let mut best: Option<(u64, MemPoolTxFeeRate)> = None;
+        for candidate in MemPoolDB::get_txs_to_consider(conn, self.max_candidates)?.into_iter() {
+            if candidate.fee_rate.is_none() {
+                continue;
+            }
+            let cost = match self.estimate_cost(&candidate.tx, cost_estimator) {
+                Some(cost) => cost,
+                None => continue,
+            };
+            let density =
+                KnapsackWalkStrategy::fee_density(&remaining, candidate.tx.metadata.tx_fee, &cost);
+            let better = match best {
+                Some((best_density, _)) => density > best_density,
+                None => true,
+            };
+            if better {
+                best = Some((density, candidate));
+            }
+        }
+
+        match best {
+            Some((_, candidate)) => Ok(Some((candidate.tx, false))),
+            None => MemPoolDB::get_next_tx_to_consider_no_estimate(conn),
+        }
+    }
+
+    fn tx_considered(&mut self, tx: &MemPoolTxInfo, cost_estimator: &dyn CostEstimator) {
+        if let Some(cost) = self.estimate_cost(tx, cost_estimator) {
+            if self.used.add(&cost).is_err() {
+                self.used = ExecutionCost::max_value();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_knapsack_fee_density() {
+        let remaining = ExecutionCost {
+            write_length: 1000,
+            write_count: 1000,
+            read_length: 1000,
+            read_count: 1000,
+            runtime: 1000,
+        };
+        let small = ExecutionCost {
+            write_length: 10,
+            write_count: 10,
+            read_length: 10,
+            read_count: 10,
+            runtime: 10,
+        };
+        let mut heavy_reads = small.clone();
+        heavy_reads.read_count = 500;
+
+        // the most-used dimension counts
+        assert_eq!(
+            KnapsackWalkStrategy::fee_density(&remaining, 1000, &small),
+            1000
+        );
+        assert_eq!(
+            KnapsackWalkStrategy::fee_density(&remaining, 1000, &heavy_reads),
+            20
+        );
+
+        // the same transaction is worth less as its most-used dimension fills up
+        let mut strategy = KnapsackWalkStrategy::new(KNAPSACK_WALK_CANDIDATES);
+        strategy.begin_block(&remaining, &StacksEpochId::Epoch20);
+        strategy.used.read_count = 400;
+        assert_eq!(
+            KnapsackWalkStrategy::fee_density(&strategy.remaining(), 1000, &heavy_reads),
+            1000 / 83
+        );
+
+        // a tiny transaction in an empty dimension is still charged something
+        assert_eq!(
+            KnapsackWalkStrategy::fee_density(&remaining, 1000, &ExecutionCost::zero()),
+            1000
+        );
+    }
+}
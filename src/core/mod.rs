@@ -16,23 +16,34 @@
 
 use burnchains::Burnchain;
 // This module contains the "main loop" that drives everything
+use burnchains::Address;
 use burnchains::Error as burnchain_error;
+use burnchains::MagicBytes;
 use chainstate::burn::ConsensusHash;
 use chainstate::coordinator::comm::CoordinatorCommunication;
+use chainstate::stacks::{
+    StacksTransaction, TransactionVersion, C32_ADDRESS_VERSION_MAINNET_MULTISIG,
+    C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_MULTISIG,
+    C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+};
 use std::convert::TryFrom;
 use util::log;
 use vm::costs::ExecutionCost;
 
+use crate::types::chainstate::StacksAddress;
 use crate::types::chainstate::StacksBlockId;
 use crate::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash};
 
 pub use self::mempool::MemPoolDB;
 
 pub mod mempool;
+pub mod mempool_policy;
+pub mod mempool_walk;
 
 use std::cmp::Ord;
 use std::cmp::Ordering;
 use std::cmp::PartialOrd;
+use std::collections::HashSet;
 
 // fork set identifier -- to be mixed with the consensus hash (encodes the version)
 pub const SYSTEM_FORK_SET_VERSION: [u8; 4] = [23u8, 0u8, 0u8, 0u8];
@@ -188,14 +199,178 @@ pub fn check_fault_injection(fault_name: &str) -> bool {
     env::var(fault_name) == Ok("1".to_string())
 }
 
+/// The identifiers that keep one Stacks chain's transactions, addresses, and p2p traffic apart
+/// from every other's.  Mainnet and testnet use the fixed constants above; an application chain
+/// picks its own chain ID, peer version, and magic bytes, and uses testnet address versions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkParameters {
+    /// whether or not transactions and addresses use the mainnet version bytes
+    pub mainnet: bool,
+    /// chain ID stamped into every transaction; also the p2p network ID in message preambles
+    pub chain_id: u32,
+    /// p2p peer version.  Peers must agree on the high byte to talk to each other.
+    pub peer_version: u32,
+    /// c32 version byte of single-signature addresses
+    pub address_version_singlesig: u8,
+    /// c32 version byte of multi-signature addresses
+    pub address_version_multisig: u8,
+    /// magic bytes that prefix this chain's burnchain operations
+    pub magic_bytes: MagicBytes,
+}
+
+impl NetworkParameters {
+    pub fn mainnet() -> NetworkParameters {
+        NetworkParameters {
+            mainnet: true,
+            chain_id: CHAIN_ID_MAINNET,
+            peer_version: PEER_VERSION_MAINNET,
+            address_version_singlesig: C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+            address_version_multisig: C32_ADDRESS_VERSION_MAINNET_MULTISIG,
+            magic_bytes: MagicBytes::from("X2".as_bytes()),
+        }
+    }
+
+    pub fn testnet() -> NetworkParameters {
+        NetworkParameters {
+            mainnet: false,
+            chain_id: CHAIN_ID_TESTNET,
+            peer_version: PEER_VERSION_TESTNET,
+            address_version_singlesig: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            address_version_multisig: C32_ADDRESS_VERSION_TESTNET_MULTISIG,
+            magic_bytes: MagicBytes::from("T2".as_bytes()),
+        }
+    }
+
+    /// Parameters for an independent application chain.
+    /// The chain ID and the peer version's major byte must differ from both mainnet's and
+    /// testnet's, so that neither transactions nor p2p messages can be replayed across chains.
+    pub fn app_chain(
+        chain_id: u32,
+        peer_version: u32,
+        magic_bytes: MagicBytes,
+    ) -> Result<NetworkParameters, String> {
+        if chain_id == CHAIN_ID_MAINNET || chain_id == CHAIN_ID_TESTNET {
+            return Err(format!(
+                "Chain ID {:08x} is reserved for a public network",
+                chain_id
+            ));
+        }
+        if (peer_version & 0xff000000) == (PEER_VERSION_MAINNET & 0xff000000)
+            || (peer_version & 0xff000000) == (PEER_VERSION_TESTNET & 0xff000000)
+        {
+            return Err(format!(
+                "Peer version {:08x} has the same major version as a public network",
+                peer_version
+            ));
+        }
+        Ok(NetworkParameters {
+            chain_id,
+            peer_version,
+            magic_bytes,
+            ..NetworkParameters::testnet()
+        })
+    }
+
+    pub fn tx_version(&self) -> TransactionVersion {
+        if self.mainnet {
+            TransactionVersion::Mainnet
+        } else {
+            TransactionVersion::Testnet
+        }
+    }
+
+    pub fn is_valid_address_version(&self, version: u8) -> bool {
+        version == self.address_version_singlesig || version == self.address_version_multisig
+    }
+
+    /// Parse a c32 address, rejecting addresses that belong to a different network.
+    pub fn parse_address(&self, addr: &str) -> Result<StacksAddress, String> {
+        let address = StacksAddress::from_string(addr)
+            .ok_or_else(|| format!("Invalid Stacks address: {}", addr))?;
+        if !self.is_valid_address_version(address.version) {
+            return Err(format!(
+                "Address {} has version {}, which is not used on this network",
+                addr, address.version
+            ));
+        }
+        Ok(address)
+    }
+
+    /// Check that a transaction was made for this chain.
+    pub fn check_transaction(&self, tx: &StacksTransaction) -> Result<(), String> {
+        if tx.chain_id != self.chain_id {
+            return Err(format!(
+                "Tx {} has chain ID {:08x}; expected {:08x}",
+                tx.txid(),
+                tx.chain_id,
+                self.chain_id
+            ));
+        }
+        if tx.version != self.tx_version() {
+            return Err(format!(
+                "Tx {} has version {:?}; expected {:?}",
+                tx.txid(),
+                tx.version,
+                self.tx_version()
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[repr(u32)]
-#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Copy, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Copy, Serialize, Deserialize)]
 pub enum StacksEpochId {
     Epoch10 = 0x01000,
     Epoch20 = 0x02000,
     Epoch2_05 = 0x02005,
 }
 
+impl StacksEpochId {
+    /// Whether transactions may use order-independent multisig spending conditions.  Accepting
+    /// them is a consensus change, so they are not enabled until an epoch is scheduled for it.
+    pub fn supports_order_independent_multisig(&self) -> bool {
+        match self {
+            StacksEpochId::Epoch10 | StacksEpochId::Epoch20 | StacksEpochId::Epoch2_05 => false,
+        }
+    }
+
+    /// Whether a contract may comply with a trait through functions whose trait arguments are
+    /// supertraits of the ones the trait expects, rather than the very same traits.  This
+    /// changes which contracts and contract-calls are valid, so it is not enabled until an epoch
+    /// is scheduled for it.
+    pub fn supports_trait_subtyping(&self) -> bool {
+        match self {
+            StacksEpochId::Epoch10 | StacksEpochId::Epoch20 | StacksEpochId::Epoch2_05 => false,
+        }
+    }
+
+    /// Whether contracts may call the string natives `string-slice?`, `string-index-of?`,
+    /// `to-lowercase` and `to-uppercase`.  Before then, contracts are free to use those names for
+    /// their own definitions, so the natives are not enabled until an epoch is scheduled for them.
+    pub fn supports_string_natives(&self) -> bool {
+        match self {
+            StacksEpochId::Epoch10 | StacksEpochId::Epoch20 | StacksEpochId::Epoch2_05 => false,
+        }
+    }
+
+    /// Whether contracts may call the natives converting between integers and big-endian
+    /// buffers (`buff-to-int-be`, `buff-to-uint-be`, `int-to-buff-be` and `uint-to-buff-be`).
+    pub fn supports_buffer_int_natives(&self) -> bool {
+        match self {
+            StacksEpochId::Epoch10 | StacksEpochId::Epoch20 | StacksEpochId::Epoch2_05 => false,
+        }
+    }
+
+    /// Whether contracts may call `get-burn-block-info?` to read the headers of burnchain blocks
+    /// by their burnchain height.
+    pub fn supports_burn_block_info(&self) -> bool {
+        match self {
+            StacksEpochId::Epoch10 | StacksEpochId::Epoch20 | StacksEpochId::Epoch2_05 => false,
+        }
+    }
+}
+
 impl std::fmt::Display for StacksEpochId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -206,6 +381,36 @@ impl std::fmt::Display for StacksEpochId {
     }
 }
 
+impl StacksEpochId {
+    /// Parse an epoch from its display name (e.g. "2.05")
+    pub fn from_name(name: &str) -> Option<StacksEpochId> {
+        match name {
+            "1.0" => Some(StacksEpochId::Epoch10),
+            "2.0" => Some(StacksEpochId::Epoch20),
+            "2.05" => Some(StacksEpochId::Epoch2_05),
+            _ => None,
+        }
+    }
+
+    /// The peer network epoch that nodes advertise while in this epoch
+    pub fn network_epoch(&self) -> u8 {
+        match self {
+            StacksEpochId::Epoch10 => PEER_VERSION_EPOCH_1_0,
+            StacksEpochId::Epoch20 => PEER_VERSION_EPOCH_2_0,
+            StacksEpochId::Epoch2_05 => PEER_VERSION_EPOCH_2_05,
+        }
+    }
+
+    /// The mainnet block limit for this epoch
+    pub fn default_block_limit(&self) -> ExecutionCost {
+        match self {
+            StacksEpochId::Epoch10 => BLOCK_LIMIT_MAINNET_10.clone(),
+            StacksEpochId::Epoch20 => BLOCK_LIMIT_MAINNET_20.clone(),
+            StacksEpochId::Epoch2_05 => BLOCK_LIMIT_MAINNET_205.clone(),
+        }
+    }
+}
+
 impl TryFrom<u32> for StacksEpochId {
     type Error = &'static str;
 
@@ -219,7 +424,7 @@ impl TryFrom<u32> for StacksEpochId {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct StacksEpoch {
     pub epoch_id: StacksEpochId,
     pub start_height: u64,
@@ -241,6 +446,50 @@ impl StacksEpoch {
         None
     }
 
+    /// Check that a list of epochs is a valid schedule: every epoch appears at most once, the
+    /// epochs are contiguous in epoch ID order, the first one starts at height 0, and the last
+    /// one never ends.  Returns the epochs sorted by epoch ID.
+    pub fn validate_epochs(epochs_ref: &[StacksEpoch]) -> Result<Vec<StacksEpoch>, String> {
+        let mut epochs = epochs_ref.to_vec();
+        let mut seen_epochs = HashSet::new();
+        epochs.sort();
+
+        let mut epoch_end_height = 0;
+        for (i, epoch) in epochs.iter().enumerate() {
+            if epoch.start_height > epoch.end_height {
+                return Err(format!(
+                    "Epoch {} starts at {}, after it ends at {}",
+                    &epoch.epoch_id, epoch.start_height, epoch.end_height
+                ));
+            }
+            if i == 0 && epoch.start_height != 0 {
+                // first ever epoch must be defined for all of the prior chain history
+                return Err(format!(
+                    "First epoch {} starts at {}, not 0",
+                    &epoch.epoch_id, epoch.start_height
+                ));
+            }
+            if i > 0 && epoch.start_height != epoch_end_height {
+                return Err(format!(
+                    "Epoch {} starts at {}, but the previous epoch ends at {}",
+                    &epoch.epoch_id, epoch.start_height, epoch_end_height
+                ));
+            }
+            if !seen_epochs.insert(epoch.epoch_id) {
+                return Err(format!("Duplicate epoch {}", &epoch.epoch_id));
+            }
+            epoch_end_height = epoch.end_height;
+        }
+
+        if epoch_end_height != STACKS_EPOCH_MAX {
+            return Err(format!(
+                "Last epoch ends at {}, not {}",
+                epoch_end_height, STACKS_EPOCH_MAX
+            ));
+        }
+        Ok(epochs)
+    }
+
     #[cfg(test)]
     pub fn unit_test_pre_2_05(first_burnchain_height: u64) -> Vec<StacksEpoch> {
         info!(
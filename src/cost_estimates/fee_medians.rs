@@ -0,0 +1,266 @@
+use std::cmp;
+use std::cmp::Ordering;
+use std::path::Path;
+
+use rusqlite::Transaction as SqlTransaction;
+use rusqlite::{Connection, Error as SqliteError, ToSql};
+
+use chainstate::stacks::{TransactionPayload, MAX_BLOCK_LEN};
+use util::db::sqlite_open;
+use util::db::tx_begin_immediate_sqlite;
+
+use vm::costs::ExecutionCost;
+
+use chainstate::stacks::db::StacksEpochReceipt;
+use chainstate::stacks::events::{StacksTransactionReceipt, TransactionOrigin};
+
+use crate::util::db::table_exists;
+
+use super::metrics::CostMetric;
+use super::FeeRateEstimate;
+use super::{EstimatorError, FeeEstimator};
+
+/// The minimum fee rate.  Any block capacity left unused is treated as if it were filled by
+/// transactions paying this rate.
+pub const MINIMUM_FEE_RATE: f64 = 1f64;
+
+const CREATE_TABLE: &'static str = "
+CREATE TABLE median_fee_estimator (
+    measure_key INTEGER PRIMARY KEY AUTOINCREMENT,
+    high NUMBER NOT NULL,
+    middle NUMBER NOT NULL,
+    low NUMBER NOT NULL
+)";
+
+/// This struct estimates fee rates from how full recent blocks were and what their
+/// transactions paid.  Each transaction's fee rate is weighted by the scalar cost it
+/// consumed (computed with the given `CostMetric`), and any part of the block limit that a
+/// block did not use is counted as if it were filled at `MINIMUM_FEE_RATE`.  The 5th, 50th
+/// and 95th weighted percentile fee rates of each block are recorded, and the estimate is the
+/// median of each of them over the last `window_size` blocks.
+///
+/// This way, blocks that are far from full pull the estimate down towards the minimum fee
+/// rate, and a few high-fee transactions in an otherwise empty block do not dominate it.
+pub struct WeightedMedianFeeRateEstimator<M: CostMetric> {
+    db: Connection,
+    /// how many recent blocks' measurements to take the median over
+    window_size: u32,
+    metric: M,
+}
+
+impl<M: CostMetric> WeightedMedianFeeRateEstimator<M> {
+    /// Open a fee rate estimator at the given db path. Creates if not existent.
+    pub fn open(p: &Path, metric: M, window_size: u32) -> Result<Self, SqliteError> {
+        let db =
+            sqlite_open(p, rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE, false).or_else(|e| {
+                if let SqliteError::SqliteFailure(ref internal, _) = e {
+                    if let rusqlite::ErrorCode::CannotOpen = internal.code {
+                        let mut db = sqlite_open(
+                            p,
+                            rusqlite::OpenFlags::SQLITE_OPEN_CREATE
+                                | rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE,
+                            false,
+                        )?;
+                        let tx = tx_begin_immediate_sqlite(&mut db)?;
+                        Self::instantiate_db(&tx)?;
+                        tx.commit()?;
+                        Ok(db)
+                    } else {
+                        Err(e)
+                    }
+                } else {
+                    Err(e)
+                }
+            })?;
+
+        Ok(Self {
+            db,
+            metric,
+            window_size: cmp::max(window_size, 1),
+        })
+    }
+
+    /// Check if the SQL database was already created. Necessary to avoid races if
+    ///  different threads open an estimator at the same time.
+    fn db_already_instantiated(tx: &SqlTransaction) -> Result<bool, SqliteError> {
+        table_exists(tx, "median_fee_estimator")
+    }
+
+    fn instantiate_db(tx: &SqlTransaction) -> Result<(), SqliteError> {
+        if !Self::db_already_instantiated(tx)? {
+            tx.execute(CREATE_TABLE, rusqlite::NO_PARAMS)?;
+        }
+
+        Ok(())
+    }
+
+    /// Store a new block's measurement, and drop measurements that have left the window.
+    fn update_estimate(&mut self, new_measure: FeeRateEstimate) {
+        debug!("Recording fee rate measurement for new block";
+               "new_measure_high" => new_measure.high,
+               "new_measure_middle" => new_measure.middle,
+               "new_measure_low" => new_measure.low);
+
+        let tx = tx_begin_immediate_sqlite(&mut self.db).expect("SQLite failure");
+
+        let insert_sql = "INSERT INTO median_fee_estimator (high, middle, low) VALUES (?, ?, ?)";
+        tx.execute(
+            insert_sql,
+            rusqlite::params![new_measure.high, new_measure.middle, new_measure.low],
+        )
+        .expect("SQLite failure");
+
+        let prune_sql = "DELETE FROM median_fee_estimator WHERE measure_key <= (SELECT MAX(measure_key) FROM median_fee_estimator) - ?";
+        let args: &[&dyn ToSql] = &[&self.window_size];
+        tx.execute(prune_sql, args).expect("SQLite failure");
+
+        tx.commit().expect("SQLite failure");
+    }
+
+    /// Compute the fee rate and weight of each transaction in a block.  Coinbases and burnchain
+    /// operations don't participate in the fee market, so they are skipped.
+    fn fee_rates_and_weights(
+        &self,
+        tx_receipts: &[StacksTransactionReceipt],
+        block_limit: &ExecutionCost,
+    ) -> Vec<(f64, u64)> {
+        tx_receipts
+            .iter()
+            .filter_map(|tx_receipt| {
+                let (payload, fee, tx_size) = match tx_receipt.transaction {
+                    TransactionOrigin::Stacks(ref tx) => {
+                        Some((&tx.payload, tx.get_tx_fee(), tx.tx_len()))
+                    }
+                    TransactionOrigin::Burn(_) => None,
+                }?;
+                let scalar_cost = match payload {
+                    TransactionPayload::TokenTransfer(_, _, _) => self.metric.from_len(tx_size),
+                    TransactionPayload::Coinbase(_) => {
+                        return None;
+                    }
+                    TransactionPayload::PoisonMicroblock(_, _)
+                    | TransactionPayload::ContractCall(_)
+                    | TransactionPayload::SmartContract(_) => self.metric.from_cost_and_len(
+                        &tx_receipt.execution_cost,
+                        &block_limit,
+                        tx_size,
+                    ),
+                };
+                let weight = cmp::max(scalar_cost, 1);
+                let fee_rate = fee as f64 / weight as f64;
+                if fee_rate >= MINIMUM_FEE_RATE && fee_rate.is_finite() {
+                    Some((fee_rate, weight))
+                } else {
+                    Some((MINIMUM_FEE_RATE, weight))
+                }
+            })
+            .collect()
+    }
+}
+
+/// If the block's transactions did not use up the whole block, count the unused capacity as
+/// having been bought at the minimum fee rate.
+fn maybe_add_minimum_fee_rate(fee_rates_and_weights: &mut Vec<(f64, u64)>, full_block_weight: u64) {
+    let total_weight = fee_rates_and_weights
+        .iter()
+        .fold(0u64, |acc, (_, weight)| acc.saturating_add(*weight));
+    if total_weight < full_block_weight {
+        fee_rates_and_weights.push((MINIMUM_FEE_RATE, full_block_weight - total_weight));
+    }
+}
+
+/// Get the fee rate at the given percentile (out of 100) of the total weight.
+/// `sorted_fee_rates_and_weights` must be non-empty and sorted by fee rate.
+fn weighted_percentile(sorted_fee_rates_and_weights: &[(f64, u64)], percentile: u64) -> f64 {
+    let total_weight = sorted_fee_rates_and_weights
+        .iter()
+        .fold(0u64, |acc, (_, weight)| acc.saturating_add(*weight));
+    let target = (total_weight as u128) * (percentile as u128);
+
+    let mut cumulative_weight: u128 = 0;
+    for (fee_rate, weight) in sorted_fee_rates_and_weights.iter() {
+        cumulative_weight += *weight as u128;
+        if cumulative_weight * 100 >= target {
+            return *fee_rate;
+        }
+    }
+    sorted_fee_rates_and_weights[sorted_fee_rates_and_weights.len() - 1].0
+}
+
+/// Get the 5th, 50th, and 95th weighted percentile fee rates.
+/// `fee_rates_and_weights` must be non-empty.
+fn fee_rate_estimate_from_weighted_fees(
+    mut fee_rates_and_weights: Vec<(f64, u64)>,
+) -> FeeRateEstimate {
+    fee_rates_and_weights.sort_by(|a, b| {
+        a.0.partial_cmp(&b.0)
+            .expect("BUG: Fee rates should be orderable: NaN and infinite values are filtered")
+    });
+    FeeRateEstimate {
+        high: weighted_percentile(&fee_rates_and_weights, 95),
+        middle: weighted_percentile(&fee_rates_and_weights, 50),
+        low: weighted_percentile(&fee_rates_and_weights, 5),
+    }
+}
+
+/// Median of a non-empty list of fee rates
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let len = values.len();
+    if len % 2 == 0 {
+        (values[len / 2 - 1] + values[len / 2]) / 2f64
+    } else {
+        values[len / 2]
+    }
+}
+
+impl<M: CostMetric> FeeEstimator for WeightedMedianFeeRateEstimator<M> {
+    fn notify_block(
+        &mut self,
+        receipt: &StacksEpochReceipt,
+        block_limit: &ExecutionCost,
+    ) -> Result<(), EstimatorError> {
+        let mut fee_rates_and_weights =
+            self.fee_rates_and_weights(&receipt.tx_receipts, block_limit);
+        if fee_rates_and_weights.is_empty() {
+            // nothing was bought in this block
+            return Ok(());
+        }
+
+        let full_block_weight =
+            self.metric
+                .from_cost_and_len(block_limit, block_limit, MAX_BLOCK_LEN as u64);
+        maybe_add_minimum_fee_rate(&mut fee_rates_and_weights, full_block_weight);
+
+        let block_estimate = fee_rate_estimate_from_weighted_fees(fee_rates_and_weights);
+        self.update_estimate(block_estimate);
+        Ok(())
+    }
+
+    fn get_rate_estimates(&self) -> Result<FeeRateEstimate, EstimatorError> {
+        let sql =
+            "SELECT high, middle, low FROM median_fee_estimator ORDER BY measure_key DESC LIMIT ?";
+        let args: &[&dyn ToSql] = &[&self.window_size];
+        let mut stmt = self.db.prepare(sql).expect("SQLite failure");
+        let measures = stmt
+            .query_map(args, |row| {
+                let high: f64 = row.get(0)?;
+                let middle: f64 = row.get(1)?;
+                let low: f64 = row.get(2)?;
+                Ok((high, middle, low))
+            })
+            .expect("SQLite failure")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("SQLite failure");
+
+        if measures.is_empty() {
+            return Err(EstimatorError::NoEstimateAvailable);
+        }
+
+        Ok(FeeRateEstimate {
+            high: median(measures.iter().map(|m| m.0).collect()),
+            middle: median(measures.iter().map(|m| m.1).collect()),
+            low: median(measures.iter().map(|m| m.2).collect()),
+        })
+    }
+}
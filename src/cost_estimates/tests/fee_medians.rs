@@ -0,0 +1,266 @@
+use std::env;
+
+use rand::Rng;
+
+use cost_estimates::fee_medians::WeightedMedianFeeRateEstimator;
+use cost_estimates::metrics::CostMetric;
+use cost_estimates::{EstimatorError, FeeEstimator};
+use vm::costs::ExecutionCost;
+
+use chainstate::burn::ConsensusHash;
+use chainstate::stacks::db::{StacksEpochReceipt, StacksHeaderInfo};
+use chainstate::stacks::events::StacksTransactionReceipt;
+use types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, StacksBlockHeader, StacksWorkScore};
+use types::proof::TrieHash;
+use util::hash::{to_hex, Hash160, Sha512Trunc256Sum};
+use util::vrf::VRFProof;
+
+use crate::chainstate::stacks::{
+    CoinbasePayload, StacksTransaction, TransactionAuth, TransactionContractCall,
+    TransactionPayload, TransactionSpendingCondition, TransactionVersion,
+};
+use crate::core::StacksEpochId;
+use crate::cost_estimates::FeeRateEstimate;
+use crate::types::chainstate::StacksAddress;
+use crate::vm::Value;
+
+fn instantiate_test_db<CM: CostMetric>(
+    m: CM,
+    window_size: u32,
+) -> WeightedMedianFeeRateEstimator<CM> {
+    let mut path = env::temp_dir();
+    let random_bytes = rand::thread_rng().gen::<[u8; 32]>();
+    path.push(&format!("fee_db_{}.sqlite", &to_hex(&random_bytes)[0..8]));
+
+    WeightedMedianFeeRateEstimator::open(&path, m, window_size)
+        .expect("Test failure: could not open fee rate DB")
+}
+
+/// This struct implements a simple metric used for unit testing the
+/// the fee rate estimator. A transaction's scalar cost is its runtime cost,
+/// so the block limit's runtime is the weight of a full block.
+struct TestRuntimeMetric;
+
+impl CostMetric for TestRuntimeMetric {
+    fn from_cost_and_len(
+        &self,
+        cost: &ExecutionCost,
+        _block_limit: &ExecutionCost,
+        _tx_len: u64,
+    ) -> u64 {
+        cost.runtime
+    }
+
+    fn from_len(&self, _tx_len: u64) -> u64 {
+        1
+    }
+
+    fn change_per_byte(&self) -> f64 {
+        0f64
+    }
+}
+
+fn test_block_limit() -> ExecutionCost {
+    ExecutionCost {
+        write_length: 0,
+        write_count: 0,
+        read_length: 0,
+        read_count: 0,
+        runtime: 100,
+    }
+}
+
+fn make_block_receipt(tx_receipts: Vec<StacksTransactionReceipt>) -> StacksEpochReceipt {
+    StacksEpochReceipt {
+        header: StacksHeaderInfo {
+            anchored_header: StacksBlockHeader {
+                version: 1,
+                total_work: StacksWorkScore { burn: 1, work: 1 },
+                proof: VRFProof::empty(),
+                parent_block: BlockHeaderHash([0; 32]),
+                parent_microblock: BlockHeaderHash([0; 32]),
+                parent_microblock_sequence: 0,
+                tx_merkle_root: Sha512Trunc256Sum([0; 32]),
+                state_index_root: TrieHash([0; 32]),
+                microblock_pubkey_hash: Hash160([0; 20]),
+            },
+            microblock_tail: None,
+            block_height: 1,
+            index_root: TrieHash([0; 32]),
+            consensus_hash: ConsensusHash([2; 20]),
+            burn_header_hash: BurnchainHeaderHash([1; 32]),
+            burn_header_height: 2,
+            burn_header_timestamp: 2,
+            anchored_block_size: 1,
+            block_stats: None,
+        },
+        tx_receipts,
+        matured_rewards: vec![],
+        matured_rewards_info: None,
+        parent_microblocks_cost: ExecutionCost::zero(),
+        anchored_block_cost: ExecutionCost::zero(),
+        parent_burn_block_hash: BurnchainHeaderHash([0; 32]),
+        parent_burn_block_height: 1,
+        parent_burn_block_timestamp: 1,
+        evaluated_epoch: StacksEpochId::Epoch20,
+    }
+}
+
+fn make_dummy_coinbase_tx() -> StacksTransaction {
+    StacksTransaction::new(
+        TransactionVersion::Mainnet,
+        TransactionAuth::Standard(TransactionSpendingCondition::new_initial_sighash()),
+        TransactionPayload::Coinbase(CoinbasePayload([0; 32])),
+    )
+}
+
+fn make_dummy_cc_tx(fee: u64, runtime: u64) -> StacksTransactionReceipt {
+    let mut tx = StacksTransaction::new(
+        TransactionVersion::Mainnet,
+        TransactionAuth::Standard(TransactionSpendingCondition::new_initial_sighash()),
+        TransactionPayload::ContractCall(TransactionContractCall {
+            address: StacksAddress::new(0, Hash160([0; 20])),
+            contract_name: "cc-dummy".into(),
+            function_name: "func-name".into(),
+            function_args: vec![],
+        }),
+    );
+    tx.set_tx_fee(fee);
+    StacksTransactionReceipt::from_contract_call(
+        tx,
+        vec![],
+        Value::okay(Value::Bool(true)).unwrap(),
+        0,
+        ExecutionCost {
+            write_length: 0,
+            write_count: 0,
+            read_length: 0,
+            read_count: 0,
+            runtime,
+        },
+    )
+}
+
+#[test]
+fn test_median_fee_estimator_empty_blocks() {
+    let mut estimator = instantiate_test_db(TestRuntimeMetric, 5);
+    let block_limit = test_block_limit();
+
+    assert_eq!(
+        estimator
+            .get_rate_estimates()
+            .expect_err("Empty rate estimator should error."),
+        EstimatorError::NoEstimateAvailable
+    );
+
+    estimator
+        .notify_block(&make_block_receipt(vec![]), &block_limit)
+        .expect("Should be able to process an empty block");
+
+    estimator
+        .notify_block(
+            &make_block_receipt(vec![StacksTransactionReceipt::from_coinbase(
+                make_dummy_coinbase_tx(),
+            )]),
+            &block_limit,
+        )
+        .expect("Should be able to process a coinbase-only block");
+
+    assert_eq!(
+        estimator
+            .get_rate_estimates()
+            .expect_err("Empty rate estimator should error."),
+        EstimatorError::NoEstimateAvailable,
+        "Blocks without fee-paying transactions should not update the estimator"
+    );
+}
+
+#[test]
+fn test_median_fee_estimator_block_fill() {
+    let mut estimator = instantiate_test_db(TestRuntimeMetric, 2);
+    let block_limit = test_block_limit();
+
+    // a full block of 10 equally-weighted transactions, paying fee rates 10, 20, ..., 100
+    let full_block =
+        make_block_receipt((1..11).map(|i| make_dummy_cc_tx(i * 10 * 10, 10)).collect());
+    estimator
+        .notify_block(&full_block, &block_limit)
+        .expect("Should be able to process block receipt");
+
+    assert_eq!(
+        estimator
+            .get_rate_estimates()
+            .expect("Should be able to create estimate now"),
+        FeeRateEstimate {
+            high: 100f64,
+            middle: 50f64,
+            low: 10f64,
+        }
+    );
+
+    // a block that is only 10% full, with one transaction paying a rate of 100.
+    // the remaining 90% counts as being bought at the minimum rate, so this block
+    // measures high = 100, middle = 1, low = 1.
+    let sparse_block = make_block_receipt(vec![
+        StacksTransactionReceipt::from_coinbase(make_dummy_coinbase_tx()),
+        make_dummy_cc_tx(1000, 10),
+    ]);
+    estimator
+        .notify_block(&sparse_block, &block_limit)
+        .expect("Should be able to process block receipt");
+
+    // median of the last two blocks
+    assert_eq!(
+        estimator
+            .get_rate_estimates()
+            .expect("Should be able to create estimate now"),
+        FeeRateEstimate {
+            high: 100f64,
+            middle: 25.5f64,
+            low: 5.5f64,
+        }
+    );
+
+    // the full block falls out of the window
+    estimator
+        .notify_block(&sparse_block, &block_limit)
+        .expect("Should be able to process block receipt");
+
+    assert_eq!(
+        estimator
+            .get_rate_estimates()
+            .expect("Should be able to create estimate now"),
+        FeeRateEstimate {
+            high: 100f64,
+            middle: 1f64,
+            low: 1f64,
+        }
+    );
+}
+
+#[test]
+fn test_median_fee_estimator_weighted() {
+    let mut estimator = instantiate_test_db(TestRuntimeMetric, 5);
+    let block_limit = test_block_limit();
+
+    // one expensive transaction paying a low rate outweighs two cheap ones paying a high rate
+    let block = make_block_receipt(vec![
+        make_dummy_cc_tx(2 * 80, 80),
+        make_dummy_cc_tx(50 * 10, 10),
+        make_dummy_cc_tx(60 * 10, 10),
+    ]);
+    estimator
+        .notify_block(&block, &block_limit)
+        .expect("Should be able to process block receipt");
+
+    assert_eq!(
+        estimator
+            .get_rate_estimates()
+            .expect("Should be able to create estimate now"),
+        FeeRateEstimate {
+            high: 60f64,
+            middle: 2f64,
+            low: 2f64,
+        }
+    );
+}
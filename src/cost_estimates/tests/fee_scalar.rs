@@ -93,6 +93,7 @@ fn make_block_receipt(tx_receipts: Vec<StacksTransactionReceipt>) -> StacksEpoch
             burn_header_height: 2,
             burn_header_timestamp: 2,
             anchored_block_size: 1,
+            block_stats: None,
         },
         tx_receipts,
         matured_rewards: vec![],
@@ -1,6 +1,7 @@
 use cost_estimates::FeeRateEstimate;
 
 pub mod cost_estimators;
+pub mod fee_medians;
 pub mod fee_scalar;
 pub mod metrics;
 
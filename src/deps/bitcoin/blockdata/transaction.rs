@@ -280,6 +280,79 @@ impl Transaction {
         Sha256dHash::from_data(&raw_vec)
     }
 
+    /// Computes a BIP143 signature hash for a given segwit (version 0 witness program) input,
+    /// which spends an output worth `value` satoshis.  `script_code` is the script being
+    /// satisfied -- for a P2WPKH output (native or wrapped in P2SH), this is the P2PKH script of
+    /// the public key hash.
+    ///
+    /// # Panics
+    /// Panics if `input_index` is greater than or equal to `self.input.len()`
+    ///
+    pub fn segwit_signature_hash(
+        &self,
+        input_index: usize,
+        script_code: &Script,
+        value: u64,
+        sighash_u32: u32,
+    ) -> Sha256dHash {
+        use deps::bitcoin::util::hash::Sha256dEncoder;
+
+        assert!(input_index < self.input.len()); // Panic on OOB
+
+        let (sighash, anyone_can_pay) =
+            SigHashType::from_u32(sighash_u32).split_anyonecanpay_flag();
+        let zero_hash = Sha256dHash::default();
+
+        let hash_prevouts = if !anyone_can_pay {
+            let mut enc = Sha256dEncoder::new();
+            for input in self.input.iter() {
+                input.previous_output.consensus_encode(&mut enc).unwrap();
+            }
+            enc.into_hash()
+        } else {
+            zero_hash
+        };
+
+        let hash_sequence =
+            if !anyone_can_pay && sighash != SigHashType::Single && sighash != SigHashType::None {
+                let mut enc = Sha256dEncoder::new();
+                for input in self.input.iter() {
+                    input.sequence.consensus_encode(&mut enc).unwrap();
+                }
+                enc.into_hash()
+            } else {
+                zero_hash
+            };
+
+        let hash_outputs = if sighash != SigHashType::Single && sighash != SigHashType::None {
+            let mut enc = Sha256dEncoder::new();
+            for output in self.output.iter() {
+                output.consensus_encode(&mut enc).unwrap();
+            }
+            enc.into_hash()
+        } else if sighash == SigHashType::Single && input_index < self.output.len() {
+            let mut enc = Sha256dEncoder::new();
+            self.output[input_index].consensus_encode(&mut enc).unwrap();
+            enc.into_hash()
+        } else {
+            zero_hash
+        };
+
+        let input = &self.input[input_index];
+        let mut enc = Sha256dEncoder::new();
+        self.version.consensus_encode(&mut enc).unwrap();
+        hash_prevouts.consensus_encode(&mut enc).unwrap();
+        hash_sequence.consensus_encode(&mut enc).unwrap();
+        input.previous_output.consensus_encode(&mut enc).unwrap();
+        script_code.consensus_encode(&mut enc).unwrap();
+        value.consensus_encode(&mut enc).unwrap();
+        input.sequence.consensus_encode(&mut enc).unwrap();
+        hash_outputs.consensus_encode(&mut enc).unwrap();
+        self.lock_time.consensus_encode(&mut enc).unwrap();
+        sighash_u32.consensus_encode(&mut enc).unwrap();
+        enc.into_hash()
+    }
+
     /// Gets the "weight" of this transaction, as defined by BIP141. For transactions with an empty
     /// witness, this is simply the consensus-serialized size times 4. For transactions with a
     /// witness, this is the non-witness consensus-serialized size multiplied by 3 plus the
@@ -523,6 +596,20 @@ mod tests {
         assert!(txin.is_ok());
     }
 
+    #[test]
+    fn test_segwit_signature_hash() {
+        // P2SH-P2WPKH example from BIP143
+        let hex_tx = hex_bytes("0100000001db6b1b20aa0fd7b23880be2ecbd4a98130974cf4748fb66092ac4d3ceb1a54770100000000feffffff02b8b4eb0b000000001976a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac0008af2f000000001976a914fd270b1ee6abcaea97fea7ad0402e8bd8ad6d77c88ac92040000").unwrap();
+        let tx: Transaction = deserialize(&hex_tx).unwrap();
+        let script_code =
+            Script::from(hex_bytes("76a91479091972186c449eb1ded22b78e40d009bdf008988ac").unwrap());
+        let sighash = tx.segwit_signature_hash(0, &script_code, 1_000_000_000, 0x01);
+        assert_eq!(
+            sighash.le_hex_string(),
+            "64f3b0f4dd2bb3aa1ce8566d220cc74dda9df97d8490cc81d89d735c92e59fb6"
+        );
+    }
+
     #[test]
     fn test_is_coinbase() {
         use deps::bitcoin::blockdata::constants;
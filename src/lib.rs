@@ -43,6 +43,7 @@ extern crate url;
 #[macro_use(o, slog_log, slog_trace, slog_debug, slog_info, slog_warn, slog_error)]
 extern crate slog;
 extern crate chrono;
+extern crate libflate;
 #[cfg(feature = "slog_json")]
 extern crate slog_json;
 extern crate slog_term;
@@ -103,6 +104,9 @@ pub mod types;
 
 pub mod cost_estimates;
 
+/// Build and sign transactions without working with chainstate internals
+pub mod txbuilder;
+
 // set via _compile-time_ envars
 const GIT_BRANCH: Option<&'static str> = option_env!("GIT_BRANCH");
 const GIT_COMMIT: Option<&'static str> = option_env!("GIT_COMMIT");
@@ -23,6 +23,7 @@
 #[macro_use]
 extern crate blockstack_lib;
 extern crate rusqlite;
+extern crate serde_json;
 
 #[macro_use(o, slog_log, slog_trace, slog_debug, slog_info, slog_warn, slog_error)]
 extern crate slog;
@@ -44,6 +45,7 @@ use blockstack_lib::burnchains::bitcoin::indexer::{BitcoinIndexerConfig, Bitcoin
 use blockstack_lib::burnchains::bitcoin::spv;
 use blockstack_lib::burnchains::bitcoin::BitcoinNetworkType;
 use blockstack_lib::chainstate::burn::ConsensusHash;
+use blockstack_lib::chainstate::stacks::db::fixtures::BlockFixture;
 use blockstack_lib::chainstate::stacks::db::ChainStateBootData;
 use blockstack_lib::chainstate::stacks::index::marf::MarfConnection;
 use blockstack_lib::chainstate::stacks::index::marf::MARF;
@@ -422,6 +424,333 @@ check if the associated microblocks can be downloaded
         process::exit(0);
     }
 
+    if argv[1] == "replay-sortitions" {
+        use blockstack_lib::burnchains::db::BurnchainDB;
+        use blockstack_lib::burnchains::Burnchain;
+        use blockstack_lib::chainstate::coordinator::{
+            replay_sortitions, OnChainRewardSetProvider,
+        };
+
+        if argv.len() < 4 {
+            eprintln!(
+                "Usage: {} replay-sortitions <working-dir> <start-height> [end-height]
+
+Re-run sortition selection for the mainnet burnchain blocks in the given range, using the
+burnchain operations stored in <working-dir>, and report any way in which the result differs
+from the recorded sortitions. Exits with a non-zero status if any divergence is found.
+Nothing is written, but each replay briefly holds the sortition DB's write lock, so a node
+using <working-dir> stalls while this runs.
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+        let start_height: u64 = argv[3]
+            .parse()
+            .expect("Failed to parse <start-height> argument");
+        let end_height: u64 = argv
+            .get(4)
+            .map(|x| x.parse().expect("Failed to parse <end-height> argument"))
+            .unwrap_or(start_height);
+
+        let burnchain_path = format!("{}/mainnet/burnchain", &argv[2]);
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+
+        let burnchain = Burnchain::new(&burnchain_path, "bitcoin", "mainnet")
+            .expect("Failed to instantiate burnchain");
+        let burnchain_db = BurnchainDB::open(&burnchain.get_burnchaindb_path(), false)
+            .expect("Failed to open burnchain DB");
+        let mut sort_db = SortitionDB::open(&burnchain.get_db_path(), true)
+            .expect(&format!("Failed to open {}", &burnchain.get_db_path()));
+        let (mut chain_state, _) =
+            StacksChainState::open(true, core::CHAIN_ID_MAINNET, &chain_state_path)
+                .expect("Failed to open stacks chain state");
+
+        let divergences = replay_sortitions(
+            &burnchain,
+            &burnchain_db,
+            &mut sort_db,
+            &mut chain_state,
+            &OnChainRewardSetProvider(),
+            start_height,
+            end_height,
+        )
+        .expect("Failed to replay sortitions");
+
+        println!("Block height, Burn header hash, Field, Recorded, Replayed");
+        for d in divergences.iter() {
+            println!(
+                "{}, {}, {}, {}, {}",
+                d.burn_block_height, &d.burn_header_hash, &d.field, &d.recorded, &d.replayed
+            );
+        }
+
+        if divergences.len() > 0 {
+            process::exit(2);
+        }
+        process::exit(0);
+    }
+
+    if argv[1] == "replay-range" {
+        if argv.len() < 5 {
+            eprintln!(
+                "Usage: {} replay-range <working-dir> <start-height> <end-height> [baseline-csv]
+
+Re-execute the canonical mainnet Stacks blocks with heights in [start-height, end-height], as
+stored in <working-dir>, without changing the chain state. Prints one CSV row per block with
+its wall-clock execution time, Clarity cost, and Clarity MARF I/O counts.
+
+To compare two builds, save one build's output and pass it as [baseline-csv] to the other: each
+row then also reports the baseline's wall-clock time, and the command exits with a non-zero
+status if any block's cost differs from the baseline's.
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+        let start_height: u64 = argv[3]
+            .parse()
+            .expect("Failed to parse <start-height> argument");
+        let end_height: u64 = argv[4]
+            .parse()
+            .expect("Failed to parse <end-height> argument");
+
+        // index block hash --> (wall-clock us, cost columns)
+        let baseline: Option<HashMap<String, (u128, Vec<u64>)>> = argv.get(5).map(|path| {
+            let csv = fs::read_to_string(path).expect(&format!("Failed to read {}", path));
+            csv.lines()
+                .skip(1)
+                .map(|line| {
+                    let fields: Vec<&str> = line.split(", ").collect();
+                    let wall_clock_us = fields[4]
+                        .parse()
+                        .expect(&format!("Malformed baseline row: {}", line));
+                    let cost = fields[5..10]
+                        .iter()
+                        .map(|x| {
+                            x.parse()
+                                .expect(&format!("Malformed baseline row: {}", line))
+                        })
+                        .collect();
+                    (fields[1].to_string(), (wall_clock_us, cost))
+                })
+                .collect()
+        });
+
+        let sort_db_path = format!("{}/mainnet/burnchain/sortition", &argv[2]);
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+
+        let sort_db = SortitionDB::open(&sort_db_path, false)
+            .expect(&format!("Failed to open {}", &sort_db_path));
+        let (mut chain_state, _) =
+            StacksChainState::open(true, core::CHAIN_ID_MAINNET, &chain_state_path)
+                .expect("Failed to open stacks chain state");
+
+        let replayed = chain_state
+            .replay_range(&sort_db, start_height, end_height)
+            .expect("Failed to replay blocks");
+
+        print!("Block height, Index block hash, Txs, Microblock txs, Wall-clock us, Runtime, Read count, Read length, Write count, Write length, MARF reads, MARF backptr reads, MARF node reads, MARF leaf reads, MARF writes");
+        if baseline.is_some() {
+            print!(", Baseline wall-clock us, Cost matches baseline");
+        }
+        println!("");
+
+        let mut num_cost_mismatches = 0;
+        let (mut total_us, mut total_baseline_us) = (0u128, 0u128);
+        for stats in replayed.iter() {
+            let mut cost = stats.microblock_cost.clone();
+            cost.add(&stats.block_cost)
+                .expect("BUG: block cost overflowed");
+            let cost_columns = vec![
+                cost.runtime,
+                cost.read_count,
+                cost.read_length,
+                cost.write_count,
+                cost.write_length,
+            ];
+            print!(
+                "{}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}",
+                stats.block_height,
+                &stats.index_block_hash(),
+                stats.num_txs,
+                stats.num_microblock_txs,
+                stats.wall_clock_us,
+                cost.runtime,
+                cost.read_count,
+                cost.read_length,
+                cost.write_count,
+                cost.write_length,
+                stats.marf_io.reads,
+                stats.marf_io.backptr_reads,
+                stats.marf_io.node_reads,
+                stats.marf_io.leaf_reads,
+                stats.marf_io.writes
+            );
+            if let Some(ref baseline) = baseline {
+                match baseline.get(&stats.index_block_hash().to_string()) {
+                    Some((baseline_us, baseline_cost)) => {
+                        let matches = baseline_cost == &cost_columns;
+                        if !matches {
+                            num_cost_mismatches += 1;
+                        }
+                        total_us += stats.wall_clock_us;
+                        total_baseline_us += baseline_us;
+                        print!(", {}, {}", baseline_us, matches);
+                    }
+                    None => {
+                        print!(", , ");
+                    }
+                }
+            }
+            println!("");
+        }
+
+        if baseline.is_some() {
+            eprintln!(
+                "Replayed blocks in baseline took {}us (baseline {}us); {} cost mismatches",
+                total_us, total_baseline_us, num_cost_mismatches
+            );
+            if num_cost_mismatches > 0 {
+                process::exit(2);
+            }
+        }
+        process::exit(0);
+    }
+
+    if argv[1] == "record-block-fixture" {
+        if argv.len() < 5 {
+            eprintln!(
+                "Usage: {} record-block-fixture <working-dir> <index-block-hash> <fixture-path>
+
+Re-execute the mainnet Stacks block <index-block-hash>, as stored in <working-dir>, and write
+everything it read -- its parent's Clarity state, header and burnchain lookups, and its
+transactions and their outcomes -- as a JSON fixture to <fixture-path>. The fixture can be
+replayed with replay-block-fixture without the chain state.
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+        let index_block_hash =
+            StacksBlockId::from_hex(&argv[3]).expect("Failed to parse <index-block-hash> argument");
+
+        let sort_db_path = format!("{}/mainnet/burnchain/sortition", &argv[2]);
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+
+        let sort_db = SortitionDB::open(&sort_db_path, false)
+            .expect(&format!("Failed to open {}", &sort_db_path));
+        let (mut chain_state, _) =
+            StacksChainState::open(true, core::CHAIN_ID_MAINNET, &chain_state_path)
+                .expect("Failed to open stacks chain state");
+
+        let header = StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            chain_state.db(),
+            &index_block_hash,
+        )
+        .expect("Failed to load block header")
+        .expect(&format!("No such block {}", &index_block_hash));
+
+        let fixture = chain_state
+            .record_block_fixture(&sort_db.index_conn(), &header)
+            .expect("Failed to record block fixture")
+            .expect(&format!("Block {} is no longer stored", &index_block_hash));
+
+        let json = serde_json::to_string_pretty(&fixture).expect("Failed to serialize fixture");
+        fs::write(&argv[4], json).expect(&format!("Failed to write {}", &argv[4]));
+        process::exit(0);
+    }
+
+    if argv[1] == "replay-block-fixture" {
+        if argv.len() < 3 {
+            eprintln!(
+                "Usage: {} replay-block-fixture <fixture-path>
+
+Re-execute the block recorded in the JSON fixture at <fixture-path> (see record-block-fixture)
+against a scratch copy of the state it recorded. Prints each way in which the outcome differs
+from the recorded one, and exits with a non-zero status if there are any.
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+        let json = fs::read_to_string(&argv[2]).expect(&format!("Failed to read {}", &argv[2]));
+        let fixture: BlockFixture = serde_json::from_str(&json).expect("Failed to parse fixture");
+
+        let scratch_path = format!("{}.scratch", &argv[2]);
+        let divergences = fixture
+            .replay(&scratch_path)
+            .expect("Failed to replay fixture");
+
+        for divergence in divergences.iter() {
+            println!(
+                "{}: recorded {}, replayed {}",
+                &divergence.field, &divergence.recorded, &divergence.replayed
+            );
+        }
+        if divergences.len() > 0 {
+            process::exit(2);
+        }
+        println!(
+            "Block at height {} replayed as recorded",
+            fixture.block_height
+        );
+        process::exit(0);
+    }
+
+    if argv[1] == "list-contracts" {
+        if argv.len() < 3 {
+            eprintln!(
+                "Usage: {} list-contracts <working-dir> [index-block-hash]
+
+List the contracts published in the mainnet Stacks fork ending at [index-block-hash], as stored
+in <working-dir>, in the order they were published. Defaults to the canonical Stacks chain tip.
+Prints one CSV row per contract with its publishing block and the hash of its code body.
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+
+        let sort_db_path = format!("{}/mainnet/burnchain/sortition", &argv[2]);
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+
+        let (chain_state, _) =
+            StacksChainState::open(true, core::CHAIN_ID_MAINNET, &chain_state_path)
+                .expect("Failed to open stacks chain state");
+
+        let tip = match argv.get(3) {
+            Some(tip) => {
+                StacksBlockId::from_hex(tip).expect("Failed to parse <index-block-hash> argument")
+            }
+            None => {
+                let sort_db = SortitionDB::open(&sort_db_path, false)
+                    .expect(&format!("Failed to open {}", &sort_db_path));
+                let (consensus_hash, block_hash) =
+                    SortitionDB::get_canonical_stacks_chain_tip_hash(sort_db.conn())
+                        .expect("Failed to get canonical Stacks chain tip");
+                StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash)
+            }
+        };
+
+        let contracts = chain_state
+            .get_published_contracts(&tip)
+            .expect("Failed to list published contracts");
+
+        println!("Contract, Block height, Index block hash, Tx index, Code body hash");
+        for contract in contracts.iter() {
+            println!(
+                "{}, {}, {}, {}, {}",
+                &contract.contract_id,
+                contract.block_height,
+                &contract.index_block_hash,
+                contract.tx_index,
+                &contract.code_body_hash
+            );
+        }
+        process::exit(0);
+    }
+
     if argv[1] == "try-mine" {
         if argv.len() < 3 {
             eprintln!(
@@ -845,6 +1174,7 @@ simulating a miner.
             get_bulk_initial_balances: None,
             get_bulk_initial_namespaces: None,
             get_bulk_initial_names: None,
+            extra_boot_contracts: vec![],
         };
 
         let (mut new_chainstate, _) = StacksChainState::open_and_exec(
@@ -283,6 +283,27 @@ pub fn increment_msg_counter(name: String) {
         .inc();
 }
 
+#[allow(unused_variables)]
+pub fn increment_inbound_connections_rejected_counter(reason: &str) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::INBOUND_CONNECTIONS_REJECTED_COUNTER_VEC
+        .with_label_values(&[reason])
+        .inc();
+}
+
+#[allow(unused_variables)]
+pub fn increment_rpc_rate_limited_counter(class: &str) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::RPC_RATE_LIMITED_COUNTER_VEC
+        .with_label_values(&[class])
+        .inc();
+}
+
+pub fn increment_relay_throttled_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::RELAY_THROTTLED_COUNTER.inc();
+}
+
 pub fn increment_stx_mempool_gc() {
     #[cfg(feature = "monitoring_prom")]
     prometheus::STX_MEMPOOL_GC.inc();
@@ -143,6 +143,23 @@ lazy_static! {
     ).unwrap();
 
 
+    pub static ref INBOUND_CONNECTIONS_REJECTED_COUNTER_VEC: IntCounterVec = register_int_counter_vec!(
+        "stacks_node_inbound_connections_rejected_total",
+        "Inbound p2p connections rejected for exceeding a per-host or per-subnet limit",
+        &["reason"]
+    ).unwrap();
+
+    pub static ref RPC_RATE_LIMITED_COUNTER_VEC: IntCounterVec = register_int_counter_vec!(
+        "stacks_node_rpc_rate_limited_total",
+        "RPC requests refused because the client exceeded its rate limit for the endpoint class",
+        &["class"]
+    ).unwrap();
+
+    pub static ref RELAY_THROTTLED_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_relay_throttled_total",
+        "Total count of pushed blocks, microblocks, and transactions dropped because the sending peer exceeded its bandwidth cap"
+    )).unwrap();
+
     pub static ref STX_MEMPOOL_GC: IntCounter = register_int_counter!(opts!(
         "stacks_node_mempool_gc_count",
         "Total count of all mempool garbage collections"
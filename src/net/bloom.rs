@@ -0,0 +1,177 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A bloom filter over transaction IDs, used to summarize the contents of a mempool to a peer
+//! without sending every txid.
+
+use std::io::{Read, Write};
+
+use rand;
+use rand::Rng;
+
+use burnchains::Txid;
+use codec::{read_next, read_next_at_most, write_next, Error as codec_error, StacksMessageCodec};
+use util::hash::Sha512Trunc256Sum;
+
+/// Largest bloom filter a peer will accept, in bytes
+pub const BLOOM_FILTER_MAX_LEN: u32 = 256 * 1024;
+
+/// Most hash functions a bloom filter can use (each one consumes 4 bytes of a 32-byte digest)
+pub const BLOOM_FILTER_MAX_HASHES: u32 = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxidBloomFilter {
+    /// mixed into every hash, so a false positive in one filter is unlikely to recur in the next
+    pub seed: u32,
+    pub num_hashes: u32,
+    pub bits: Vec<u8>,
+}
+
+impl TxidBloomFilter {
+    /// Make an empty filter sized to hold `num_items` txids with roughly the given false-positive
+    /// rate.  The filter is capped at BLOOM_FILTER_MAX_LEN bytes, so very large sets will have a
+    /// higher false-positive rate.
+    pub fn new(num_items: u64, false_positive_rate: f64) -> TxidBloomFilter {
+        let n = num_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-n * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(8.0);
+        let num_bytes = ((num_bits / 8.0).ceil() as u64).min(BLOOM_FILTER_MAX_LEN as u64);
+        let num_hashes = ((num_bytes * 8) as f64 / n * ln2).round() as u32;
+        TxidBloomFilter {
+            seed: rand::thread_rng().gen(),
+            num_hashes: num_hashes.max(1).min(BLOOM_FILTER_MAX_HASHES),
+            bits: vec![0u8; num_bytes as usize],
+        }
+    }
+
+    fn bit_indexes(&self, txid: &Txid) -> Vec<usize> {
+        let mut preimage = Vec::with_capacity(36);
+        preimage.extend_from_slice(&self.seed.to_be_bytes());
+        preimage.extend_from_slice(&txid.0);
+        let digest = Sha512Trunc256Sum::from_data(&preimage);
+
+        let num_bits = self.bits.len() * 8;
+        (0..(self.num_hashes as usize))
+            .map(|i| {
+                let mut word = [0u8; 4];
+                word.copy_from_slice(&digest.0[4 * i..4 * (i + 1)]);
+                (u32::from_be_bytes(word) as usize) % num_bits
+            })
+            .collect()
+    }
+
+    pub fn insert(&mut self, txid: &Txid) {
+        if self.bits.len() == 0 {
+            return;
+        }
+        for idx in self.bit_indexes(txid) {
+            self.bits[idx / 8] |= 1 << (idx % 8);
+        }
+    }
+
+    /// Could this filter contain the given txid?  Never returns a false negative.
+    pub fn contains(&self, txid: &Txid) -> bool {
+        if self.bits.len() == 0 {
+            return false;
+        }
+        self.bit_indexes(txid)
+            .into_iter()
+            .all(|idx| self.bits[idx / 8] & (1 << (idx % 8)) != 0)
+    }
+}
+
+impl StacksMessageCodec for TxidBloomFilter {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.seed)?;
+        write_next(fd, &self.num_hashes)?;
+        write_next(fd, &self.bits)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<TxidBloomFilter, codec_error> {
+        let seed: u32 = read_next(fd)?;
+        let num_hashes: u32 = read_next(fd)?;
+        if num_hashes == 0 || num_hashes > BLOOM_FILTER_MAX_HASHES {
+            return Err(codec_error::DeserializeError(
+                "Invalid number of bloom filter hashes".to_string(),
+            ));
+        }
+        let bits: Vec<u8> = read_next_at_most::<_, u8>(fd, BLOOM_FILTER_MAX_LEN)?;
+        Ok(TxidBloomFilter {
+            seed,
+            num_hashes,
+            bits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use net::codec::test::check_codec_and_corruption;
+
+    fn make_txid(i: u32) -> Txid {
+        Txid(Sha512Trunc256Sum::from_data(&i.to_be_bytes()).0)
+    }
+
+    #[test]
+    fn test_txid_bloom_filter() {
+        let mut filter = TxidBloomFilter::new(1000, 0.001);
+        for i in 0..1000 {
+            filter.insert(&make_txid(i));
+        }
+
+        // no false negatives
+        for i in 0..1000 {
+            assert!(filter.contains(&make_txid(i)));
+        }
+
+        // few false positives
+        let false_positives = (1000..11000)
+            .filter(|i| filter.contains(&make_txid(*i)))
+            .count();
+        assert!(false_positives < 100);
+
+        // an empty filter contains nothing
+        let empty = TxidBloomFilter::new(1000, 0.001);
+        assert!(!empty.contains(&make_txid(0)));
+    }
+
+    #[test]
+    fn test_txid_bloom_filter_capped() {
+        let filter = TxidBloomFilter::new(u32::MAX as u64, 0.001);
+        assert_eq!(filter.bits.len(), BLOOM_FILTER_MAX_LEN as usize);
+        assert!(filter.num_hashes >= 1);
+    }
+
+    #[test]
+    fn codec_txid_bloom_filter() {
+        let mut filter = TxidBloomFilter {
+            seed: 0x01020304,
+            num_hashes: 2,
+            bits: vec![0u8; 4],
+        };
+        filter.insert(&make_txid(0));
+
+        let mut bytes = vec![
+            0x01, 0x02, 0x03, 0x04, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x04,
+        ];
+        bytes.extend_from_slice(&filter.bits);
+        check_codec_and_corruption::<TxidBloomFilter>(&filter, &bytes);
+    }
+}
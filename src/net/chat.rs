@@ -320,6 +320,10 @@ pub struct ConversationP2P {
     // outbound replies
     pub reply_handles: VecDeque<ReplyHandleP2P>,
 
+    // if we sent this peer a MemPoolSync, the most transactions we asked it for.  A MemPoolTxs
+    // is only accepted from this peer while this is set.
+    pub mempool_sync_max_txs: Option<u32>,
+
     // system epochs
     epochs: Vec<StacksEpoch>,
 }
@@ -515,6 +519,8 @@ impl ConversationP2P {
             stats: NeighborStats::new(outbound),
             reply_handles: VecDeque::new(),
 
+            mempool_sync_max_txs: None,
+
             epochs: epochs,
         }
     }
@@ -933,6 +939,58 @@ impl ConversationP2P {
         Ok(handle)
     }
 
+    /// If we're in a private network, check that the peer's handshake carries a signature over
+    /// its node key from one of the network keys we allow.
+    /// Return Err(net_error::InvalidHandshake) if it doesn't.
+    fn validate_network_auth(&self, handshake_data: &HandshakeData) -> Result<(), net_error> {
+        if self.connection.options.private_network_key.is_none() {
+            return Ok(());
+        }
+
+        let network_auth = match handshake_data.network_auth {
+            Some(ref network_auth) => network_auth,
+            None => {
+                debug!(
+                    "{:?}: invalid handshake -- no private network credentials",
+                    &self
+                );
+                return Err(net_error::InvalidHandshake);
+            }
+        };
+
+        let allowed = match network_auth.network_public_key.to_public_key() {
+            Ok(network_public_key) => self
+                .connection
+                .options
+                .private_network_peers
+                .iter()
+                .any(|pubk| pubk.to_bytes_compressed() == network_public_key.to_bytes_compressed()),
+            Err(_) => false,
+        };
+        if !allowed {
+            debug!(
+                "{:?}: invalid handshake -- network key {} is not allowed",
+                &self,
+                to_hex(&network_auth.network_public_key.0)
+            );
+            return Err(net_error::InvalidHandshake);
+        }
+
+        network_auth
+            .verify(
+                self.network_id,
+                &handshake_data.node_public_key,
+                handshake_data.expire_block_height,
+            )
+            .map_err(|_e| {
+                debug!(
+                    "{:?}: invalid handshake -- node key not signed by network key",
+                    &self
+                );
+                net_error::InvalidHandshake
+            })
+    }
+
     /// Validate a handshake request.
     /// Return Err(...) if the handshake request was invalid.
     fn validate_handshake(
@@ -1014,6 +1072,8 @@ impl ConversationP2P {
             return Err(net_error::InvalidHandshake);
         }
 
+        self.validate_network_auth(&handshake_data)?;
+
         Ok(())
     }
 
@@ -1187,6 +1247,7 @@ impl ConversationP2P {
         preamble: &Preamble,
         handshake_accept: &HandshakeAcceptData,
     ) -> Result<(), net_error> {
+        self.validate_network_auth(&handshake_accept.handshake)?;
         self.update_from_handshake_data(preamble, &handshake_accept.handshake)?;
         self.peer_heartbeat =
             if handshake_accept.heartbeat_interval > (MAX_PEER_HEARTBEAT_INTERVAL as u32) {
@@ -1755,6 +1816,63 @@ impl ConversationP2P {
         Ok(None)
     }
 
+    /// Validate a MemPoolTxs message.  It is only accepted as the reply to a MemPoolSync we sent
+    /// this peer, and may carry no more transactions than we asked for.  Its transactions are
+    /// counted against the transaction-push bandwidth limit one at a time, just as if each had
+    /// been pushed in its own message.
+    fn validate_mempool_txs_push(
+        &mut self,
+        local_peer: &LocalPeer,
+        chain_view: &BurnchainView,
+        preamble: &Preamble,
+        relayers: Vec<RelayData>,
+        data: &MemPoolTxsData,
+    ) -> Result<Option<ReplyHandleP2P>, net_error> {
+        let max_txs = match self.mempool_sync_max_txs.take() {
+            Some(max_txs) => max_txs,
+            None => {
+                debug!(
+                    "Drop MemPoolTxs from {:?} -- we did not ask it for transactions",
+                    &self.to_neighbor_key()
+                );
+                self.stats.msgs_err += 1;
+                return Err(net_error::InvalidMessage);
+            }
+        };
+        if data.txs.len() > (max_txs as usize) {
+            debug!(
+                "Drop MemPoolTxs from {:?} -- it sent {} transactions, but we asked for at most {}",
+                &self.to_neighbor_key(),
+                data.txs.len(),
+                max_txs
+            );
+            self.stats.msgs_err += 1;
+            return Err(net_error::InvalidMessage);
+        }
+
+        if !self.process_relayers(local_peer, preamble, &relayers) {
+            debug!("Drop MemPoolTxs -- invalid relayers {:?}", &relayers);
+            self.stats.msgs_err += 1;
+            return Err(net_error::InvalidMessage);
+        }
+
+        for tx in data.txs.iter() {
+            self.stats
+                .add_transaction_push(tx.serialize_to_vec().len() as u64);
+        }
+
+        if self.connection.options.max_transaction_push_bandwidth > 0
+            && self.stats.get_transaction_push_bandwidth()
+                > (self.connection.options.max_transaction_push_bandwidth as f64)
+        {
+            debug!("Neighbor {:?} exceeded max transaction-push bandwidth of {} bytes/sec (currently at {})", &self.to_neighbor_key(), self.connection.options.max_transaction_push_bandwidth, self.stats.get_transaction_push_bandwidth());
+            return self
+                .reply_nack(local_peer, chain_view, preamble, NackErrorCodes::Throttled)
+                .and_then(|handle| Ok(Some(handle)));
+        }
+        Ok(None)
+    }
+
     /// Handle an inbound authenticated p2p data-plane message.
     /// Return the message if not handled
     fn handle_data_message(
@@ -1789,7 +1907,9 @@ impl ConversationP2P {
                 &msg.preamble,
                 get_blocks_inv,
             ),
-            StacksMessageType::Blocks(_) => {
+            StacksMessageType::Blocks(_)
+            | StacksMessageType::CompactBlock(_)
+            | StacksMessageType::CompactBlockTxs(_) => {
                 monitoring::increment_stx_blocks_received_counter();
 
                 // not handled here, but do some accounting -- we can't receive blocks too often,
@@ -1825,6 +1945,23 @@ impl ConversationP2P {
                     }
                 }
             }
+            StacksMessageType::MemPoolTxs(ref data) => {
+                monitoring::increment_txs_received_counter();
+
+                match self.validate_mempool_txs_push(
+                    local_peer,
+                    chain_view,
+                    &msg.preamble,
+                    msg.relayers.clone(),
+                    data,
+                )? {
+                    Some(handle) => Ok(handle),
+                    None => {
+                        // will forward upstream
+                        return Ok(Some(msg));
+                    }
+                }
+            }
             StacksMessageType::Transaction(_) => {
                 monitoring::increment_txs_received_counter();
 
@@ -2901,6 +3038,149 @@ mod test {
         assert!(convo_2.connection.get_public_key().is_none());
     }
 
+    #[test]
+    fn convo_handshake_private_network() {
+        let network_key_1 = Secp256k1PrivateKey::new();
+        let network_key_2 = Secp256k1PrivateKey::new();
+
+        let conn_opts_1 = ConnectionOptions::default();
+        let mut conn_opts_2 = ConnectionOptions::default();
+        conn_opts_2.private_network_key = Some(network_key_2.clone());
+        conn_opts_2.private_network_peers = vec![Secp256k1PublicKey::from_private(&network_key_1)];
+
+        let socketaddr_1 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let socketaddr_2 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 8081);
+
+        let burnchain = testing_burnchain_config();
+
+        let mut chain_view = BurnchainView {
+            burn_block_height: 12348,
+            burn_block_hash: BurnchainHeaderHash([0x11; 32]),
+            burn_stable_block_height: 12341,
+            burn_stable_block_hash: BurnchainHeaderHash([0x22; 32]),
+            last_burn_block_hashes: HashMap::new(),
+        };
+        chain_view.make_test_data();
+
+        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
+            "convo_handshake_private_network_1",
+            &burnchain,
+            0x9abcdef0,
+            12350,
+            "http://peer1.com".into(),
+            &vec![],
+            &vec![],
+        );
+        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
+            "convo_handshake_private_network_2",
+            &burnchain,
+            0x9abcdef0,
+            12351,
+            "http://peer2.com".into(),
+            &vec![],
+            &vec![],
+        );
+
+        db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
+        db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
+
+        let mut local_peer_1 = PeerDB::get_local_peer(&peerdb_1.conn()).unwrap();
+        let mut local_peer_2 = PeerDB::get_local_peer(&peerdb_2.conn()).unwrap();
+        local_peer_2.network_key = Some(network_key_2.clone());
+
+        let mut convo_1 = ConversationP2P::new(
+            123,
+            456,
+            &burnchain,
+            &socketaddr_2,
+            &conn_opts_1,
+            true,
+            0,
+            StacksEpoch::unit_test_pre_2_05(0),
+        );
+        let mut convo_2 = ConversationP2P::new(
+            123,
+            456,
+            &burnchain,
+            &socketaddr_1,
+            &conn_opts_2,
+            true,
+            0,
+            StacksEpoch::unit_test_pre_2_05(0),
+        );
+
+        // peer 1 doesn't use a network key, then uses one that peer 2 doesn't allow, then uses
+        // one that peer 2 does allow
+        for (network_key_opt, accepted) in vec![
+            (None, false),
+            (Some(Secp256k1PrivateKey::new()), false),
+            (Some(network_key_1.clone()), true),
+        ]
+        .into_iter()
+        {
+            local_peer_1.network_key = network_key_opt;
+
+            let handshake_data_1 = HandshakeData::from_local_peer(&local_peer_1);
+            let handshake_1 = convo_1
+                .sign_message(
+                    &chain_view,
+                    &local_peer_1.private_key,
+                    StacksMessageType::Handshake(handshake_data_1.clone()),
+                )
+                .unwrap();
+
+            let mut rh_1 = convo_1.send_signed_request(handshake_1, 1000000).unwrap();
+
+            convo_send_recv(&mut convo_1, vec![&mut rh_1], &mut convo_2);
+            let unhandled_2 = convo_2
+                .chat(
+                    &local_peer_2,
+                    &mut peerdb_2,
+                    &sortdb_2,
+                    &pox_id_2,
+                    &mut chainstate_2,
+                    &mut BlockHeaderCache::new(),
+                    &chain_view,
+                )
+                .unwrap();
+
+            convo_send_recv(&mut convo_2, vec![&mut rh_1], &mut convo_1);
+            let unhandled_1 = convo_1
+                .chat(
+                    &local_peer_1,
+                    &mut peerdb_1,
+                    &sortdb_1,
+                    &pox_id_1,
+                    &mut chainstate_1,
+                    &mut BlockHeaderCache::new(),
+                    &chain_view,
+                )
+                .unwrap();
+
+            let reply_1 = rh_1.recv(0).unwrap();
+            assert_eq!(unhandled_1.len(), 0);
+
+            match reply_1.payload {
+                StacksMessageType::HandshakeAccept(ref data) => {
+                    assert!(accepted);
+                    assert_eq!(unhandled_2.len(), 1);
+
+                    // peer 2 proves its membership as well
+                    assert!(data.handshake.network_auth.is_some());
+                    assert!(convo_2.connection.get_public_key().is_some());
+                }
+                StacksMessageType::HandshakeReject => {
+                    assert!(!accepted);
+                    assert_eq!(unhandled_2.len(), 0);
+                    assert!(convo_2.connection.get_public_key().is_none());
+                }
+                _ => {
+                    assert!(false);
+                }
+            };
+        }
+    }
+
     #[test]
     fn convo_handshake_badsignature() {
         let conn_opts = ConnectionOptions::default();
@@ -4509,6 +4789,81 @@ mod test {
             assert_eq!(stats.num_bytes, (msg.preamble.payload_len - 1) as u64);
         }
     }
+
+    #[test]
+    fn convo_mempool_txs_only_when_solicited() {
+        use chainstate::stacks::test::make_codec_test_block;
+
+        let conn_opts = ConnectionOptions::default();
+        let socketaddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8091);
+        let burnchain = testing_burnchain_config();
+
+        let mut chain_view = BurnchainView {
+            burn_block_height: 12348,
+            burn_block_hash: BurnchainHeaderHash([0x11; 32]),
+            burn_stable_block_height: 12341,
+            burn_stable_block_hash: BurnchainHeaderHash([0x22; 32]),
+            last_burn_block_hashes: HashMap::new(),
+        };
+        chain_view.make_test_data();
+
+        let local_peer = LocalPeer::new(
+            123,
+            burnchain.network_id,
+            PeerAddress::from_ipv4(127, 0, 0, 1),
+            NETWORK_P2P_PORT,
+            None,
+            get_epoch_time_secs() + 123456,
+            UrlString::try_from("http://foo.com").unwrap(),
+        );
+        let mut convo = ConversationP2P::new(
+            123,
+            456,
+            &burnchain,
+            &socketaddr,
+            &conn_opts,
+            true,
+            0,
+            StacksEpoch::unit_test_pre_2_05(0),
+        );
+
+        let txs = make_codec_test_block(5).txs;
+        assert!(txs.len() > 1);
+        let data = MemPoolTxsData { txs: txs.clone() };
+        let msg = convo
+            .sign_reply(
+                &chain_view,
+                &local_peer.private_key,
+                StacksMessageType::MemPoolTxs(data.clone()),
+                123,
+            )
+            .unwrap();
+
+        // not asked for
+        assert!(convo
+            .validate_mempool_txs_push(&local_peer, &chain_view, &msg.preamble, vec![], &data)
+            .is_err());
+
+        // asked for fewer transactions than were sent
+        convo.mempool_sync_max_txs = Some((txs.len() - 1) as u32);
+        assert!(convo
+            .validate_mempool_txs_push(&local_peer, &chain_view, &msg.preamble, vec![], &data)
+            .is_err());
+
+        // asked for these transactions -- each one is counted as a transaction push
+        convo.mempool_sync_max_txs = Some(txs.len() as u32);
+        assert!(convo
+            .validate_mempool_txs_push(&local_peer, &chain_view, &msg.preamble, vec![], &data)
+            .unwrap()
+            .is_none());
+        assert_eq!(convo.stats.transaction_push_rx_counts.len(), txs.len());
+
+        // only one reply per request
+        assert!(convo.mempool_sync_max_txs.is_none());
+        assert!(convo
+            .validate_mempool_txs_push(&local_peer, &chain_view, &msg.preamble, vec![], &data)
+            .is_err());
+    }
 }
 
 // TODO: test bandwidth limits
@@ -29,6 +29,7 @@ use sha2::Sha512Trunc256;
 use burnchains::BurnchainView;
 use burnchains::PrivateKey;
 use burnchains::PublicKey;
+use burnchains::Txid;
 use chainstate::burn::ConsensusHash;
 use chainstate::stacks::StacksBlock;
 use chainstate::stacks::StacksMicroblock;
@@ -37,6 +38,7 @@ use chainstate::stacks::StacksTransaction;
 use chainstate::stacks::MAX_BLOCK_LEN;
 use codec::{read_next_at_most, read_next_exact, MAX_MESSAGE_LEN};
 use core::PEER_VERSION_TESTNET;
+use net::bloom::TxidBloomFilter;
 use net::db::LocalPeer;
 use net::Error as net_error;
 use net::*;
@@ -611,17 +613,109 @@ impl HandshakeData {
             UrlString::try_from("").unwrap()
         };
 
+        let node_public_key = StacksPublicKeyBuffer::from_public_key(
+            &Secp256k1PublicKey::from_private(&local_peer.private_key),
+        );
+
+        // prove our membership in a private network, if we're in one
+        let (services, network_auth) = match local_peer.network_key {
+            Some(ref network_key) => (
+                local_peer.services | (ServiceFlags::NETWORK_AUTH as u16),
+                Some(NetworkAuthData::new(
+                    network_key,
+                    local_peer.network_id,
+                    &node_public_key,
+                    local_peer.private_key_expire,
+                )),
+            ),
+            None => (local_peer.services, None),
+        };
+
         HandshakeData {
             addrbytes: addrbytes,
             port: port,
-            services: local_peer.services,
-            node_public_key: StacksPublicKeyBuffer::from_public_key(
-                &Secp256k1PublicKey::from_private(&local_peer.private_key),
-            ),
+            services: services,
+            node_public_key: node_public_key,
             expire_block_height: local_peer.private_key_expire,
             data_url: data_url,
+            network_auth: network_auth,
+        }
+    }
+}
+
+impl NetworkAuthData {
+    /// Digest of what a network key signs: the network ID, and the node key and its expiry
+    fn digest(
+        network_id: u32,
+        node_public_key: &StacksPublicKeyBuffer,
+        expire_block_height: u64,
+    ) -> [u8; 32] {
+        let mut sha2 = Sha512Trunc256::new();
+        sha2.input(&network_id.to_be_bytes());
+        sha2.input(&node_public_key.0[..]);
+        sha2.input(&expire_block_height.to_be_bytes());
+
+        let mut digest_bits = [0u8; 32];
+        digest_bits.copy_from_slice(sha2.result().as_slice());
+        digest_bits
+    }
+
+    /// Sign a node key with a private network key
+    pub fn new(
+        network_key: &Secp256k1PrivateKey,
+        network_id: u32,
+        node_public_key: &StacksPublicKeyBuffer,
+        expire_block_height: u64,
+    ) -> NetworkAuthData {
+        let digest_bits = NetworkAuthData::digest(network_id, node_public_key, expire_block_height);
+        let signature = network_key
+            .sign(&digest_bits)
+            .expect("BUG: failed to sign with network key");
+        NetworkAuthData {
+            network_public_key: StacksPublicKeyBuffer::from_public_key(
+                &Secp256k1PublicKey::from_private(network_key),
+            ),
+            signature,
         }
     }
+
+    /// Verify that this network key signed the given node key
+    pub fn verify(
+        &self,
+        network_id: u32,
+        node_public_key: &StacksPublicKeyBuffer,
+        expire_block_height: u64,
+    ) -> Result<(), net_error> {
+        let network_public_key = self.network_public_key.to_public_key()?;
+        let digest_bits = NetworkAuthData::digest(network_id, node_public_key, expire_block_height);
+        let res = network_public_key
+            .verify(&digest_bits, &self.signature)
+            .map_err(|_ve| net_error::VerifyingError("Failed to verify signature".to_string()))?;
+        if res {
+            Ok(())
+        } else {
+            Err(net_error::VerifyingError(
+                "Invalid network key signature".to_string(),
+            ))
+        }
+    }
+}
+
+impl StacksMessageCodec for NetworkAuthData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.network_public_key)?;
+        write_next(fd, &self.signature)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<NetworkAuthData, codec_error> {
+        let network_public_key: StacksPublicKeyBuffer = read_next(fd)?;
+        let signature: MessageSignature = read_next(fd)?;
+        Ok(NetworkAuthData {
+            network_public_key,
+            signature,
+        })
+    }
 }
 
 impl StacksMessageCodec for HandshakeData {
@@ -632,6 +726,16 @@ impl StacksMessageCodec for HandshakeData {
         write_next(fd, &self.node_public_key)?;
         write_next(fd, &self.expire_block_height)?;
         write_next(fd, &self.data_url)?;
+        if self.services & (ServiceFlags::NETWORK_AUTH as u16) != 0 {
+            match self.network_auth {
+                Some(ref network_auth) => write_next(fd, network_auth)?,
+                None => {
+                    return Err(codec_error::SerializeError(
+                        "Invalid handshake data: no network auth data".to_string(),
+                    ));
+                }
+            }
+        }
         Ok(())
     }
 
@@ -648,6 +752,11 @@ impl StacksMessageCodec for HandshakeData {
         let node_public_key: StacksPublicKeyBuffer = read_next(fd)?;
         let expire_block_height: u64 = read_next(fd)?;
         let data_url: UrlString = read_next(fd)?;
+        let network_auth = if services & (ServiceFlags::NETWORK_AUTH as u16) != 0 {
+            Some(read_next(fd)?)
+        } else {
+            None
+        };
         Ok(HandshakeData {
             addrbytes,
             port,
@@ -655,6 +764,7 @@ impl StacksMessageCodec for HandshakeData {
             node_public_key,
             expire_block_height,
             data_url,
+            network_auth,
         })
     }
 }
@@ -741,6 +851,131 @@ impl StacksMessageCodec for PongData {
     }
 }
 
+impl StacksMessageCodec for MemPoolSyncData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.txids)?;
+        write_next(fd, &self.max_txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<MemPoolSyncData, codec_error> {
+        let txids: TxidBloomFilter = read_next(fd)?;
+        let max_txs: u32 = read_next(fd)?;
+        Ok(MemPoolSyncData { txids, max_txs })
+    }
+}
+
+impl StacksMessageCodec for MemPoolTxsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<MemPoolTxsData, codec_error> {
+        let txs: Vec<StacksTransaction> =
+            read_next_at_most::<_, StacksTransaction>(fd, MEMPOOL_SYNC_MAX_TXS)?;
+        Ok(MemPoolTxsData { txs })
+    }
+}
+
+impl StacksMessageCodec for PrefilledTransaction {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.index)?;
+        write_next(fd, &self.tx)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<PrefilledTransaction, codec_error> {
+        let index: u32 = read_next(fd)?;
+        let tx: StacksTransaction = read_next(fd)?;
+        Ok(PrefilledTransaction { index, tx })
+    }
+}
+
+impl StacksMessageCodec for CompactBlockData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.consensus_hash)?;
+        write_next(fd, &self.header)?;
+        write_next(fd, &self.nonce)?;
+        write_next(fd, &self.short_txids)?;
+        write_next(fd, &self.prefilled_txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<CompactBlockData, codec_error> {
+        let consensus_hash: ConsensusHash = read_next(fd)?;
+        let header: StacksBlockHeader = read_next(fd)?;
+        let nonce: u64 = read_next(fd)?;
+        let short_txids: Vec<ShortTxid> =
+            read_next_at_most::<_, ShortTxid>(fd, COMPACT_BLOCK_MAX_TXS)?;
+        let prefilled_txs: Vec<PrefilledTransaction> =
+            read_next_at_most::<_, PrefilledTransaction>(fd, COMPACT_BLOCK_MAX_TXS)?;
+
+        if (short_txids.len() + prefilled_txs.len()) as u64 > COMPACT_BLOCK_MAX_TXS as u64 {
+            return Err(codec_error::DeserializeError(
+                "Too many transactions in compact block".to_string(),
+            ));
+        }
+
+        // prefilled transactions must be in increasing index order, and fit in the block
+        let num_txs = (short_txids.len() + prefilled_txs.len()) as u32;
+        let mut last_index = None;
+        for prefilled in prefilled_txs.iter() {
+            if prefilled.index >= num_txs
+                || last_index.map(|i| prefilled.index <= i).unwrap_or(false)
+            {
+                return Err(codec_error::DeserializeError(
+                    "Invalid prefilled transaction index in compact block".to_string(),
+                ));
+            }
+            last_index = Some(prefilled.index);
+        }
+
+        Ok(CompactBlockData {
+            consensus_hash,
+            header,
+            nonce,
+            short_txids,
+            prefilled_txs,
+        })
+    }
+}
+
+impl StacksMessageCodec for GetCompactBlockTxsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.index_block_hash)?;
+        write_next(fd, &self.indexes)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<GetCompactBlockTxsData, codec_error> {
+        let index_block_hash: StacksBlockId = read_next(fd)?;
+        let indexes: Vec<u32> = read_next_at_most::<_, u32>(fd, COMPACT_BLOCK_MAX_TXS)?;
+        Ok(GetCompactBlockTxsData {
+            index_block_hash,
+            indexes,
+        })
+    }
+}
+
+impl StacksMessageCodec for CompactBlockTxsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.index_block_hash)?;
+        write_next(fd, &self.txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<CompactBlockTxsData, codec_error> {
+        let index_block_hash: StacksBlockId = read_next(fd)?;
+        let txs: Vec<StacksTransaction> =
+            read_next_at_most::<_, StacksTransaction>(fd, COMPACT_BLOCK_MAX_TXS)?;
+        Ok(CompactBlockTxsData {
+            index_block_hash,
+            txs,
+        })
+    }
+}
+
 impl StacksMessageCodec for NatPunchData {
     fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
         write_next(fd, &self.addrbytes)?;
@@ -799,6 +1034,11 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => StacksMessageID::Pong,
             StacksMessageType::NatPunchRequest(ref _m) => StacksMessageID::NatPunchRequest,
             StacksMessageType::NatPunchReply(ref _m) => StacksMessageID::NatPunchReply,
+            StacksMessageType::MemPoolSync(ref _m) => StacksMessageID::MemPoolSync,
+            StacksMessageType::MemPoolTxs(ref _m) => StacksMessageID::MemPoolTxs,
+            StacksMessageType::CompactBlock(ref _m) => StacksMessageID::CompactBlock,
+            StacksMessageType::GetCompactBlockTxs(ref _m) => StacksMessageID::GetCompactBlockTxs,
+            StacksMessageType::CompactBlockTxs(ref _m) => StacksMessageID::CompactBlockTxs,
         }
     }
 
@@ -823,6 +1063,11 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => "Pong",
             StacksMessageType::NatPunchRequest(ref _m) => "NatPunchRequest",
             StacksMessageType::NatPunchReply(ref _m) => "NatPunchReply",
+            StacksMessageType::MemPoolSync(ref _m) => "MemPoolSync",
+            StacksMessageType::MemPoolTxs(ref _m) => "MemPoolTxs",
+            StacksMessageType::CompactBlock(ref _m) => "CompactBlock",
+            StacksMessageType::GetCompactBlockTxs(ref _m) => "GetCompactBlockTxs",
+            StacksMessageType::CompactBlockTxs(ref _m) => "CompactBlockTxs",
         }
     }
 
@@ -881,6 +1126,29 @@ impl StacksMessageType {
             StacksMessageType::NatPunchReply(ref m) => {
                 format!("NatPunchReply({},{}:{})", m.nonce, &m.addrbytes, m.port)
             }
+            StacksMessageType::MemPoolSync(ref m) => {
+                format!("MemPoolSync({},{})", m.txids.bits.len(), m.max_txs)
+            }
+            StacksMessageType::MemPoolTxs(ref m) => format!(
+                "MemPoolTxs({:?})",
+                m.txs.iter().map(|tx| tx.txid()).collect::<Vec<Txid>>()
+            ),
+            StacksMessageType::CompactBlock(ref m) => format!(
+                "CompactBlock({}/{},{},{})",
+                &m.consensus_hash,
+                &m.header.block_hash(),
+                m.short_txids.len(),
+                m.prefilled_txs.len()
+            ),
+            StacksMessageType::GetCompactBlockTxs(ref m) => {
+                format!(
+                    "GetCompactBlockTxs({},{:?})",
+                    &m.index_block_hash, &m.indexes
+                )
+            }
+            StacksMessageType::CompactBlockTxs(ref m) => {
+                format!("CompactBlockTxs({},{})", &m.index_block_hash, m.txs.len())
+            }
         }
     }
 }
@@ -914,6 +1182,13 @@ impl StacksMessageCodec for StacksMessageID {
             x if x == StacksMessageID::Pong as u8 => StacksMessageID::Pong,
             x if x == StacksMessageID::NatPunchRequest as u8 => StacksMessageID::NatPunchRequest,
             x if x == StacksMessageID::NatPunchReply as u8 => StacksMessageID::NatPunchReply,
+            x if x == StacksMessageID::MemPoolSync as u8 => StacksMessageID::MemPoolSync,
+            x if x == StacksMessageID::MemPoolTxs as u8 => StacksMessageID::MemPoolTxs,
+            x if x == StacksMessageID::CompactBlock as u8 => StacksMessageID::CompactBlock,
+            x if x == StacksMessageID::GetCompactBlockTxs as u8 => {
+                StacksMessageID::GetCompactBlockTxs
+            }
+            x if x == StacksMessageID::CompactBlockTxs as u8 => StacksMessageID::CompactBlockTxs,
             _ => {
                 return Err(codec_error::DeserializeError(
                     "Unknown message ID".to_string(),
@@ -947,6 +1222,11 @@ impl StacksMessageCodec for StacksMessageType {
             StacksMessageType::Pong(ref m) => write_next(fd, m)?,
             StacksMessageType::NatPunchRequest(ref nonce) => write_next(fd, nonce)?,
             StacksMessageType::NatPunchReply(ref m) => write_next(fd, m)?,
+            StacksMessageType::MemPoolSync(ref m) => write_next(fd, m)?,
+            StacksMessageType::MemPoolTxs(ref m) => write_next(fd, m)?,
+            StacksMessageType::CompactBlock(ref m) => write_next(fd, m)?,
+            StacksMessageType::GetCompactBlockTxs(ref m) => write_next(fd, m)?,
+            StacksMessageType::CompactBlockTxs(ref m) => write_next(fd, m)?,
         }
         Ok(())
     }
@@ -1024,6 +1304,26 @@ impl StacksMessageCodec for StacksMessageType {
                 let m: NatPunchData = read_next(fd)?;
                 StacksMessageType::NatPunchReply(m)
             }
+            StacksMessageID::MemPoolSync => {
+                let m: MemPoolSyncData = read_next(fd)?;
+                StacksMessageType::MemPoolSync(m)
+            }
+            StacksMessageID::MemPoolTxs => {
+                let m: MemPoolTxsData = read_next(fd)?;
+                StacksMessageType::MemPoolTxs(m)
+            }
+            StacksMessageID::CompactBlock => {
+                let m: CompactBlockData = read_next(fd)?;
+                StacksMessageType::CompactBlock(m)
+            }
+            StacksMessageID::GetCompactBlockTxs => {
+                let m: GetCompactBlockTxsData = read_next(fd)?;
+                StacksMessageType::GetCompactBlockTxs(m)
+            }
+            StacksMessageID::CompactBlockTxs => {
+                let m: CompactBlockTxsData = read_next(fd)?;
+                StacksMessageType::CompactBlockTxs(m)
+            }
             StacksMessageID::Reserved => {
                 return Err(codec_error::DeserializeError(
                     "Unsupported message ID 'reserved'".to_string(),
@@ -1299,6 +1599,7 @@ impl ProtocolFamily for StacksP2P {
 
 #[cfg(test)]
 pub mod test {
+    use chainstate::stacks::test::make_codec_test_block;
     use codec::NEIGHBOR_ADDRESS_ENCODED_SIZE;
     use util::hash::hex_bytes;
     use util::secp256k1::*;
@@ -1795,6 +2096,7 @@ pub mod test {
             .unwrap(),
             expire_block_height: 0x0102030405060708,
             data_url: UrlString::try_from("https://the-new-interwebs.com/data").unwrap(),
+            network_auth: None,
         };
         let mut bytes = vec![
             // addrbytes
@@ -1814,6 +2116,73 @@ pub mod test {
         check_codec_and_corruption::<HandshakeData>(&data, &bytes);
     }
 
+    #[test]
+    fn codec_HandshakeData_network_auth() {
+        let network_key = Secp256k1PrivateKey::from_hex(
+            "9f1f85a512a96a244e4c0d762788500687feb97481639572e3bffbd6860e6ab001",
+        )
+        .unwrap();
+        let node_public_key = StacksPublicKeyBuffer::from_bytes(
+            &hex_bytes("034e316be04870cef1795fba64d581cf64bad0c894b01a068fb9edf85321dcd9bb")
+                .unwrap(),
+        )
+        .unwrap();
+        let network_auth = NetworkAuthData::new(
+            &network_key,
+            0x80000000,
+            &node_public_key,
+            0x0102030405060708,
+        );
+        let data = HandshakeData {
+            addrbytes: PeerAddress([
+                0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+                0x0e, 0x0f,
+            ]),
+            port: 12345,
+            services: 0x0001 | (ServiceFlags::NETWORK_AUTH as u16),
+            node_public_key: node_public_key.clone(),
+            expire_block_height: 0x0102030405060708,
+            data_url: UrlString::try_from("https://the-new-interwebs.com/data").unwrap(),
+            network_auth: Some(network_auth.clone()),
+        };
+        let mut bytes = vec![
+            // addrbytes
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, // port
+            0x30, 0x39, // services
+            0x00, 0x05, // public key
+            0x03, 0x4e, 0x31, 0x6b, 0xe0, 0x48, 0x70, 0xce, 0xf1, 0x79, 0x5f, 0xba, 0x64, 0xd5,
+            0x81, 0xcf, 0x64, 0xba, 0xd0, 0xc8, 0x94, 0xb0, 0x1a, 0x06, 0x8f, 0xb9, 0xed, 0xf8,
+            0x53, 0x21, 0xdc, 0xd9, 0xbb, // expire block height
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+        ];
+        // data URL
+        bytes.push(data.data_url.len() as u8);
+        bytes.extend_from_slice(data.data_url.as_bytes());
+        // network auth
+        bytes.extend_from_slice(&network_auth.network_public_key.0);
+        bytes.extend_from_slice(&network_auth.signature.0);
+
+        check_codec_and_corruption::<HandshakeData>(&data, &bytes);
+
+        // the signature only covers this node key, expiry, and network
+        assert!(network_auth
+            .verify(0x80000000, &node_public_key, 0x0102030405060708)
+            .is_ok());
+        assert!(network_auth
+            .verify(0x80000001, &node_public_key, 0x0102030405060708)
+            .is_err());
+        assert!(network_auth
+            .verify(0x80000000, &node_public_key, 0x0102030405060709)
+            .is_err());
+
+        // can't claim network auth without sending it
+        let mut bad_data = data.clone();
+        bad_data.network_auth = None;
+        let mut bad_bytes = vec![];
+        assert!(bad_data.consensus_serialize(&mut bad_bytes).is_err());
+    }
+
     #[test]
     fn codec_HandshakeAcceptData() {
         let data = HandshakeAcceptData {
@@ -1833,6 +2202,7 @@ pub mod test {
                 .unwrap(),
                 expire_block_height: 0x0102030405060708,
                 data_url: UrlString::try_from("https://the-new-interwebs.com/data").unwrap(),
+                network_auth: None,
             },
             heartbeat_interval: 0x01020304,
         };
@@ -1943,6 +2313,46 @@ pub mod test {
         check_codec_and_corruption::<NatPunchData>(&data, &bytes);
     }
 
+    #[test]
+    fn codec_MemPoolSyncData() {
+        let data = MemPoolSyncData {
+            txids: TxidBloomFilter {
+                seed: 0x01020304,
+                num_hashes: 3,
+                bits: vec![0xaa, 0xbb],
+            },
+            max_txs: 0x100,
+        };
+        let bytes = vec![
+            // seed
+            0x01, 0x02, 0x03, 0x04, // num hashes
+            0x00, 0x00, 0x00, 0x03, // bits
+            0x00, 0x00, 0x00, 0x02, 0xaa, 0xbb, // max txs
+            0x00, 0x00, 0x01, 0x00,
+        ];
+
+        check_codec_and_corruption::<MemPoolSyncData>(&data, &bytes);
+    }
+
+    #[test]
+    fn codec_CompactBlockData() {
+        let block = make_codec_test_block(5);
+        let mut data = CompactBlockData::from_block(&ConsensusHash([0x11; 20]), &block, 1);
+
+        let mut bytes = vec![];
+        data.consensus_serialize(&mut bytes).unwrap();
+        assert_eq!(
+            CompactBlockData::consensus_deserialize(&mut &bytes[..]).unwrap(),
+            data
+        );
+
+        // prefilled transaction is out of range
+        data.prefilled_txs[0].index = block.txs.len() as u32;
+        let mut bytes = vec![];
+        data.consensus_serialize(&mut bytes).unwrap();
+        assert!(CompactBlockData::consensus_deserialize(&mut &bytes[..]).is_err());
+    }
+
     #[test]
     fn codec_StacksMessage() {
         let payloads: Vec<StacksMessageType> = vec![
@@ -1963,6 +2373,7 @@ pub mod test {
                 expire_block_height: 0x0102030405060708,
                 data_url: UrlString::try_from("https://the-new-interwebs.com:4008/the-data")
                     .unwrap(),
+                network_auth: None,
             }),
             StacksMessageType::HandshakeAccept(HandshakeAcceptData {
                 heartbeat_interval: 0x01020304,
@@ -1983,6 +2394,7 @@ pub mod test {
                     expire_block_height: 0x0102030405060708,
                     data_url: UrlString::try_from("https://the-new-interwebs.com:4008/the-data")
                         .unwrap(),
+                    network_auth: None,
                 },
             }),
             StacksMessageType::HandshakeReject,
@@ -2059,6 +2471,23 @@ pub mod test {
                 port: 12345,
                 nonce: 0x12345678,
             }),
+            StacksMessageType::MemPoolSync(MemPoolSyncData {
+                txids: TxidBloomFilter {
+                    seed: 0x01020304,
+                    num_hashes: 3,
+                    bits: vec![0xff; 16],
+                },
+                max_txs: 64,
+            }),
+            StacksMessageType::MemPoolTxs(MemPoolTxsData { txs: vec![] }),
+            StacksMessageType::GetCompactBlockTxs(GetCompactBlockTxsData {
+                index_block_hash: StacksBlockId([0x11; 32]),
+                indexes: vec![1, 2, 3],
+            }),
+            StacksMessageType::CompactBlockTxs(CompactBlockTxsData {
+                index_block_hash: StacksBlockId([0x22; 32]),
+                txs: vec![],
+            }),
         ];
 
         let mut maximal_relayers: Vec<RelayData> = vec![];
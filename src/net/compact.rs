@@ -0,0 +1,297 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Compact block relay.  Most of the transactions in a new block will already have been relayed
+//! to a peer, so instead of pushing the whole block, we push its header and a short ID for each
+//! transaction.  The peer fills in what it can from its mempool, and asks for the rest.
+
+use std::collections::HashMap;
+
+use burnchains::Txid;
+use chainstate::burn::ConsensusHash;
+use chainstate::stacks::{StacksBlock, StacksTransaction, TransactionPayload};
+use codec::StacksMessageCodec;
+use core::mempool::MemPoolDB;
+use net::{CompactBlockData, PrefilledTransaction, ShortTxid};
+use util::db::Error as db_error;
+use util::hash::Sha512Trunc256Sum;
+
+use crate::types::chainstate::{StacksBlockHeader, StacksBlockId};
+
+/// Short ID of a transaction in a compact block with the given nonce
+pub fn short_txid(nonce: u64, txid: &Txid) -> ShortTxid {
+    let mut preimage = Vec::with_capacity(40);
+    preimage.extend_from_slice(&nonce.to_be_bytes());
+    preimage.extend_from_slice(&txid.0);
+    let h = Sha512Trunc256Sum::from_data(&preimage);
+    let mut bytes = [0u8; 6];
+    bytes.copy_from_slice(&h.0[0..6]);
+    ShortTxid(bytes)
+}
+
+impl CompactBlockData {
+    /// Make a compact block from a full block.  The coinbase is always sent in full, since a
+    /// peer can't have it in its mempool.
+    pub fn from_block(
+        consensus_hash: &ConsensusHash,
+        block: &StacksBlock,
+        nonce: u64,
+    ) -> CompactBlockData {
+        let mut short_txids = vec![];
+        let mut prefilled_txs = vec![];
+        for (i, tx) in block.txs.iter().enumerate() {
+            match tx.payload {
+                TransactionPayload::Coinbase(_) => {
+                    prefilled_txs.push(PrefilledTransaction {
+                        index: i as u32,
+                        tx: tx.clone(),
+                    });
+                }
+                _ => {
+                    short_txids.push(short_txid(nonce, &tx.txid()));
+                }
+            }
+        }
+        CompactBlockData {
+            consensus_hash: consensus_hash.clone(),
+            header: block.header.clone(),
+            nonce,
+            short_txids,
+            prefilled_txs,
+        }
+    }
+
+    pub fn index_block_hash(&self) -> StacksBlockId {
+        self.header.index_block_hash(&self.consensus_hash)
+    }
+}
+
+/// A compact block being rebuilt from our mempool and from the transactions we fetch from the
+/// peer that announced it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialCompactBlock {
+    pub consensus_hash: ConsensusHash,
+    pub header: StacksBlockHeader,
+    pub nonce: u64,
+    /// short ID of each transaction we still need (None for prefilled transactions)
+    short_txids: Vec<Option<ShortTxid>>,
+    txs: Vec<Option<StacksTransaction>>,
+}
+
+impl PartialCompactBlock {
+    /// Start rebuilding a compact block.  Only the prefilled transactions are filled in.
+    pub fn new(data: &CompactBlockData) -> PartialCompactBlock {
+        let num_txs = data.short_txids.len() + data.prefilled_txs.len();
+        let mut short_txids = Vec::with_capacity(num_txs);
+        let mut txs = Vec::with_capacity(num_txs);
+        let mut prefilled_iter = data.prefilled_txs.iter().peekable();
+        let mut short_txid_iter = data.short_txids.iter();
+        for i in 0..num_txs {
+            match prefilled_iter.peek() {
+                Some(prefilled) if prefilled.index as usize == i => {
+                    short_txids.push(None);
+                    txs.push(Some(prefilled.tx.clone()));
+                    prefilled_iter.next();
+                }
+                _ => {
+                    short_txids.push(short_txid_iter.next().cloned());
+                    txs.push(None);
+                }
+            }
+        }
+        PartialCompactBlock {
+            consensus_hash: data.consensus_hash.clone(),
+            header: data.header.clone(),
+            nonce: data.nonce,
+            short_txids,
+            txs,
+        }
+    }
+
+    pub fn index_block_hash(&self) -> StacksBlockId {
+        self.header.index_block_hash(&self.consensus_hash)
+    }
+
+    /// Fill in missing transactions from a set of known txids, loading each matching transaction
+    /// with `load_tx`.  A short ID that matches more than one known txid is left missing.
+    pub fn fill_from_txids<F, E>(&mut self, txids: &[Txid], mut load_tx: F) -> Result<(), E>
+    where
+        F: FnMut(&Txid) -> Result<Option<StacksTransaction>, E>,
+    {
+        let mut candidates: HashMap<ShortTxid, Option<&Txid>> = HashMap::new();
+        for txid in txids.iter() {
+            let short_id = short_txid(self.nonce, txid);
+            if candidates.contains_key(&short_id) {
+                // ambiguous
+                candidates.insert(short_id, None);
+            } else {
+                candidates.insert(short_id, Some(txid));
+            }
+        }
+
+        for i in 0..self.txs.len() {
+            if self.txs[i].is_some() {
+                continue;
+            }
+            let txid = match self.short_txids[i]
+                .as_ref()
+                .and_then(|short_id| candidates.get(short_id))
+            {
+                Some(Some(txid)) => *txid,
+                _ => {
+                    continue;
+                }
+            };
+            self.txs[i] = load_tx(txid)?;
+        }
+        Ok(())
+    }
+
+    /// Fill in missing transactions from the mempool
+    pub fn fill_from_mempool(&mut self, mempool: &MemPoolDB) -> Result<(), db_error> {
+        let txids = MemPoolDB::get_all_txids(mempool.conn())?;
+        self.fill_from_txids(&txids, |txid| {
+            Ok(MemPoolDB::get_tx(mempool.conn(), txid)?.map(|txinfo| txinfo.tx))
+        })
+    }
+
+    /// Positions of the transactions we still need
+    pub fn missing_indexes(&self) -> Vec<u32> {
+        self.txs
+            .iter()
+            .enumerate()
+            .filter(|(_, tx_opt)| tx_opt.is_none())
+            .map(|(i, _)| i as u32)
+            .collect()
+    }
+
+    /// Fill in the transactions at the given positions, as sent by the announcing peer.  Returns
+    /// false (and changes nothing) if they don't match the short IDs we were given.
+    pub fn fill_from_peer(&mut self, indexes: &[u32], txs: &[StacksTransaction]) -> bool {
+        if indexes.len() != txs.len() {
+            return false;
+        }
+        for (index, tx) in indexes.iter().zip(txs.iter()) {
+            match self.short_txids.get(*index as usize) {
+                Some(Some(short_id)) if *short_id == short_txid(self.nonce, &tx.txid()) => {}
+                _ => {
+                    return false;
+                }
+            }
+        }
+        for (index, tx) in indexes.iter().zip(txs.iter()) {
+            self.txs[*index as usize] = Some(tx.clone());
+        }
+        true
+    }
+
+    /// Build the block, if we have all of its transactions and they are valid for its header.
+    pub fn to_block(&self) -> Option<StacksBlock> {
+        let mut txs = Vec::with_capacity(self.txs.len());
+        for tx_opt in self.txs.iter() {
+            txs.push(tx_opt.clone()?);
+        }
+        let block = StacksBlock {
+            header: self.header.clone(),
+            txs,
+        };
+
+        // re-use the block codec's checks (Merkle root, coinbase, etc.), since a short txid
+        // collision could have given us the wrong transaction
+        let mut bytes = vec![];
+        block
+            .consensus_serialize(&mut bytes)
+            .expect("BUG: failed to serialize block");
+        StacksBlock::consensus_deserialize(&mut &bytes[..]).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chainstate::stacks::test::make_codec_test_block;
+
+    #[test]
+    fn test_compact_block_reconstruct() {
+        let block = make_codec_test_block(10);
+        let consensus_hash = ConsensusHash([0x11; 20]);
+        let data = CompactBlockData::from_block(&consensus_hash, &block, 0x0102030405060708);
+        assert_eq!(data.prefilled_txs.len(), 1);
+        assert_eq!(data.prefilled_txs[0].index, 0);
+        assert_eq!(data.short_txids.len(), block.txs.len() - 1);
+        assert_eq!(
+            data.index_block_hash(),
+            block.header.index_block_hash(&consensus_hash)
+        );
+
+        // we have every other transaction in our "mempool"
+        let mempool: HashMap<Txid, StacksTransaction> = block
+            .txs
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .map(|tx| (tx.txid(), tx.clone()))
+            .collect();
+        let txids: Vec<Txid> = mempool.keys().cloned().collect();
+
+        let mut partial = PartialCompactBlock::new(&data);
+        assert_eq!(partial.missing_indexes().len(), block.txs.len() - 1);
+        partial
+            .fill_from_txids(&txids, |txid| -> Result<_, ()> {
+                Ok(mempool.get(txid).cloned())
+            })
+            .unwrap();
+
+        let missing = partial.missing_indexes();
+        let expected_missing: Vec<u32> = (2..block.txs.len() as u32).step_by(2).collect();
+        assert_eq!(missing, expected_missing);
+        assert!(partial.to_block().is_none());
+
+        // the peer sends the wrong transactions
+        let wrong_txs: Vec<StacksTransaction> = missing
+            .iter()
+            .map(|i| block.txs[*i as usize - 1].clone())
+            .collect();
+        assert!(!partial.fill_from_peer(&missing, &wrong_txs));
+        assert_eq!(partial.missing_indexes(), missing);
+
+        // the peer sends the right transactions
+        let missing_txs: Vec<StacksTransaction> = missing
+            .iter()
+            .map(|i| block.txs[*i as usize].clone())
+            .collect();
+        assert!(partial.fill_from_peer(&missing, &missing_txs));
+        assert!(partial.missing_indexes().is_empty());
+        assert_eq!(partial.to_block(), Some(block));
+    }
+
+    #[test]
+    fn test_compact_block_ambiguous_short_txid() {
+        let block = make_codec_test_block(3);
+        let data = CompactBlockData::from_block(&ConsensusHash([0x11; 20]), &block, 1);
+        let mut partial = PartialCompactBlock::new(&data);
+
+        // two known txids with the same short ID as the block's second transaction
+        let txid = block.txs[1].txid();
+        let txids = vec![txid.clone(), txid.clone()];
+        partial
+            .fill_from_txids(&txids, |_| -> Result<_, ()> {
+                Ok(Some(block.txs[1].clone()))
+            })
+            .unwrap();
+        assert_eq!(partial.missing_indexes(), vec![1, 2]);
+    }
+}
@@ -19,6 +19,7 @@ use std::convert::TryFrom;
 use std::io;
 use std::io::{Read, Write};
 use std::net;
+use std::net::IpAddr;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::time::Duration;
@@ -36,6 +37,7 @@ use mio::net as mio_net;
 
 use crate::codec::StacksMessageCodec;
 use crate::codec::MAX_MESSAGE_LEN;
+use chainstate::stacks::db::transactions::TransactionSimulationLimits;
 use net::codec::*;
 use net::Error as net_error;
 use net::HttpRequestPreamble;
@@ -44,9 +46,11 @@ use net::MessageSequence;
 use net::PeerAddress;
 use net::Preamble;
 use net::ProtocolFamily;
+use net::ReadOnlyCallLimits;
 use net::RelayData;
 use net::StacksHttp;
 use net::StacksP2P;
+use net::DENY_BAN_DURATION;
 
 use net::download::BLOCK_DOWNLOAD_INTERVAL;
 use net::inv::{FULL_INV_SYNC_INTERVAL, INV_REWARD_CYCLES, INV_SYNC_INTERVAL};
@@ -63,6 +67,7 @@ use util::get_epoch_time_secs;
 use util::hash::to_hex;
 use util::log;
 use util::pipe::*;
+use util::secp256k1::Secp256k1PrivateKey;
 use util::secp256k1::Secp256k1PublicKey;
 use util::sleep_ms;
 
@@ -337,6 +342,18 @@ pub struct ConnectionOptions {
     pub soft_max_clients_per_host: u64,
     pub max_neighbors_of_neighbor: u64,
     pub max_http_clients: u64,
+    pub http_keep_alive_timeout: u64,
+    pub max_http_requests_per_connection: u64,
+    pub http_compression_threshold: u64,
+    pub cors_allowed_origins: Vec<String>,
+    pub cors_allowed_methods: Vec<String>,
+    pub cors_allowed_headers: Vec<String>,
+    pub cors_max_age: u64,
+    pub rpc_admin_token: Option<String>,
+    pub rpc_monitor_token: Option<String>,
+    pub rpc_rate_limit_read: u64,
+    pub rpc_rate_limit_compute: u64,
+    pub rpc_rate_limit_submit: u64,
     pub neighbor_request_timeout: u64,
     pub num_initial_walks: u64,
     pub walk_retry_count: u64,
@@ -354,9 +371,18 @@ pub struct ConnectionOptions {
     pub pingback_timeout: u64,
     pub dns_timeout: u128,
     pub max_inflight_blocks: u64,
+    pub max_inflight_blocks_per_peer: u64,
     pub max_inflight_attachments: u64,
     pub max_attachment_retry_count: u64,
     pub read_only_call_limit: ExecutionCost,
+    pub read_only_call_time_limit_ms: u64,
+    pub read_only_call_max_result_size: u64,
+    pub read_only_call_workers: usize,
+    pub read_only_call_queue_size: usize,
+    pub health_max_burn_block_lag: u64,
+    pub health_max_stacks_block_lag: u64,
+    pub simulate_transaction_limit: ExecutionCost,
+    pub simulate_transaction_max_len: u64,
     pub maximum_call_argument_size: u32,
     pub max_block_push_bandwidth: u64,
     pub max_microblocks_push_bandwidth: u64,
@@ -374,6 +400,25 @@ pub struct ConnectionOptions {
     pub max_buffered_microblocks_available: u64,
     pub max_buffered_blocks: u64,
     pub max_buffered_microblocks: u64,
+    pub mempool_sync_interval: u64,
+    pub mempool_sync_max_txs: u64,
+    pub compact_block_relay: bool,
+    pub peer_ban_score_threshold: u64,
+    pub peer_score_ban_duration: u64,
+    pub peer_score_decay_interval: u64,
+    pub dns_seeds: Vec<(String, u16)>,
+    pub enable_port_mapping: bool,
+    pub port_mapping_gateway: Option<IpAddr>,
+    pub port_mapping_lifetime: u64,
+    pub max_clients_per_subnet: u64,
+    pub client_subnet_prefix_v4: u8,
+    pub client_subnet_prefix_v6: u8,
+    pub max_relay_bandwidth_per_peer: u64,
+    pub private_network_key: Option<Secp256k1PrivateKey>,
+    pub private_network_peers: Vec<Secp256k1PublicKey>,
+    pub subscribe_block_announcements: bool,
+    pub block_announcement_jitter_ms: u64,
+    pub pruned: bool,
 
     // fault injection
     pub disable_neighbor_walk: bool,
@@ -413,6 +458,22 @@ impl std::default::Default for ConnectionOptions {
             soft_max_clients_per_host: 10, // how many inbound connections we can have per IP address, before we start pruning them,
             max_neighbors_of_neighbor: 10,
             max_http_clients: 10,
+            http_keep_alive_timeout: 15, // how long a kept-alive HTTP connection can wait for its next request
+            max_http_requests_per_connection: 1000, // how many requests a client can send over one HTTP connection (0 = no limit)
+            http_compression_threshold: 4096, // compress JSON responses at least this big, if the client accepts it (0 = never)
+            cors_allowed_origins: vec!["*".to_string()], // browser origins that can query the RPC server
+            cors_allowed_methods: vec![
+                "POST".to_string(),
+                "GET".to_string(),
+                "OPTIONS".to_string(),
+            ],
+            cors_allowed_headers: vec!["origin".to_string(), "content-type".to_string()],
+            cors_max_age: 0, // how long browsers can cache a CORS preflight response (0 = don't say)
+            rpc_admin_token: None, // bearer token for admin RPC endpoints (if None, loopback clients only)
+            rpc_monitor_token: None, // bearer token for monitoring RPC endpoints (the admin token works too)
+            rpc_rate_limit_read: 0,  // RPC reads a client IP can make per minute (0 = unlimited)
+            rpc_rate_limit_compute: 0, // RPC read-only calls, simulations, and scans a client IP can make per minute (0 = unlimited)
+            rpc_rate_limit_submit: 0, // transactions and blocks a client IP can post per minute (0 = unlimited)
             neighbor_request_timeout: NEIGHBOR_REQUEST_TIMEOUT, // how long to wait for a neighbor request
             num_initial_walks: NUM_INITIAL_WALKS,
             walk_retry_count: WALK_RETRY_COUNT,
@@ -428,9 +489,10 @@ impl std::default::Default for ConnectionOptions {
             inv_reward_cycles: INV_REWARD_CYCLES, // how many reward cycles of blocks to sync in a non-full inventory sync
             download_interval: BLOCK_DOWNLOAD_INTERVAL, // how often to scan for blocks to download
             pingback_timeout: 60,
-            dns_timeout: 15_000,            // DNS timeout, in millis
-            max_inflight_blocks: 6,         // number of parallel block downloads
-            max_inflight_attachments: 6,    // number of parallel attachments downloads
+            dns_timeout: 15_000,             // DNS timeout, in millis
+            max_inflight_blocks: 6,          // number of parallel block downloads
+            max_inflight_blocks_per_peer: 3, // number of parallel block downloads from one peer
+            max_inflight_attachments: 6,     // number of parallel attachments downloads
             max_attachment_retry_count: 32, // how many attempt to get an attachment before giving up
             read_only_call_limit: ExecutionCost {
                 write_length: 0,
@@ -439,6 +501,20 @@ impl std::default::Default for ConnectionOptions {
                 read_count: 30,
                 runtime: 1_000_000_000,
             },
+            read_only_call_time_limit_ms: 5_000, // wall-clock limit on a single read-only call (0 = unlimited)
+            read_only_call_max_result_size: 0, // largest serialized read-only call result, in bytes (0 = unlimited)
+            read_only_call_workers: 2, // threads that run read-only calls (0 = run them on the p2p thread)
+            read_only_call_queue_size: 64, // read-only calls that can wait for a worker before we return 503
+            health_max_burn_block_lag: 1, // burnchain blocks or sortitions we may trail by before /v2/health returns 503
+            health_max_stacks_block_lag: 1, // sortitions our Stacks tip may trail peers' by before /v2/health returns 503
+            simulate_transaction_limit: ExecutionCost {
+                write_length: 100000,
+                write_count: 30,
+                read_length: 100000,
+                read_count: 30,
+                runtime: 1_000_000_000,
+            },
+            simulate_transaction_max_len: 128 * 1024, // largest transaction we will simulate, in bytes (0 = unlimited)
             maximum_call_argument_size: 20 * BOUND_VALUE_SERIALIZATION_HEX,
             max_block_push_bandwidth: 0, // infinite upload bandwidth allowed
             max_microblocks_push_bandwidth: 0, // infinite upload bandwidth allowed
@@ -456,6 +532,25 @@ impl std::default::Default for ConnectionOptions {
             max_buffered_microblocks_available: 1,
             max_buffered_blocks: 1,
             max_buffered_microblocks: 10,
+            mempool_sync_interval: 30, // how often to ask a neighbor for transactions we're missing
+            mempool_sync_max_txs: 128, // how many transactions to ask for at once
+            compact_block_relay: true, // push new blocks as header plus short txids to neighbors that support it
+            peer_ban_score_threshold: 100, // ban a peer once its misbehavior score reaches this
+            peer_score_ban_duration: DENY_BAN_DURATION, // how long, in seconds, such a ban lasts
+            peer_score_decay_interval: 60, // seconds for one penalty point to decay
+            dns_seeds: vec![], // (host, port) pairs to resolve for peers if our frontier is empty
+            enable_port_mapping: false, // ask our router to forward our p2p port (NAT-PMP or UPnP)
+            port_mapping_gateway: None, // router to ask via NAT-PMP (default: our default gateway)
+            port_mapping_lifetime: 3600, // how long, in seconds, to ask the router to keep the mapping
+            max_clients_per_subnet: 32, // how many inbound connections we can have per subnet, full-stop
+            client_subnet_prefix_v4: 24, // size of an IPv4 subnet for max_clients_per_subnet
+            client_subnet_prefix_v6: 48, // size of an IPv6 subnet for max_clients_per_subnet
+            max_relay_bandwidth_per_peer: 0, // bytes/sec of pushed data we'll take from one peer (0 = no limit)
+            private_network_key: None, // if set, only talk to peers whose network keys are in private_network_peers
+            private_network_peers: vec![],
+            subscribe_block_announcements: false, // ask neighbors to push new (micro)block announcements to us
+            block_announcement_jitter_ms: 1000, // spread pushed announcements to subscribers over this many millis
+            pruned: false, // advertise that we don't keep (and won't serve) old blocks and microblocks
 
             // no faults on by default
             disable_neighbor_walk: false,
@@ -475,6 +570,27 @@ impl std::default::Default for ConnectionOptions {
     }
 }
 
+impl ConnectionOptions {
+    /// The budget each read-only call runs under
+    pub fn read_only_call_limits(&self) -> ReadOnlyCallLimits {
+        ReadOnlyCallLimits {
+            cost: self.read_only_call_limit.clone(),
+            time_limit_ms: self.read_only_call_time_limit_ms,
+            max_result_size: self.read_only_call_max_result_size,
+        }
+    }
+
+    /// The budget each transaction simulation runs under.  Simulations share the read-only
+    /// call wall-clock limit, but get their own cost budget since they may write.
+    pub fn simulate_transaction_limits(&self) -> TransactionSimulationLimits {
+        TransactionSimulationLimits {
+            cost: self.simulate_transaction_limit.clone(),
+            time_limit_ms: self.read_only_call_time_limit_ms,
+            max_tx_len: self.simulate_transaction_max_len,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct NetworkConnection<P: ProtocolFamily> {
     pub options: ConnectionOptions,
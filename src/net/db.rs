@@ -26,6 +26,7 @@ use std::convert::TryFrom;
 use std::fs;
 
 use util::db::sqlite_open;
+use util::db::table_exists;
 use util::db::tx_begin_immediate;
 use util::db::DBConn;
 use util::db::Error as db_error;
@@ -49,6 +50,7 @@ use rand::Rng;
 use rand::RngCore;
 
 use net::asn::ASEntry4;
+use net::reputation::PeerScore;
 use net::Neighbor;
 use net::NeighborAddress;
 use net::NeighborKey;
@@ -62,7 +64,7 @@ use core::NETWORK_P2P_PORT;
 
 use util::strings::UrlString;
 
-pub const PEERDB_VERSION: &'static str = "1";
+pub const PEERDB_VERSION: &'static str = "2";
 
 const NUM_SLOTS: usize = 8;
 
@@ -111,6 +113,7 @@ pub struct LocalPeer {
 
     // filled in and curated at runtime
     pub public_ip_address: Option<(PeerAddress, u16)>,
+    pub network_key: Option<Secp256k1PrivateKey>, // our key in a private network, if we're in one
 }
 
 impl fmt::Display for LocalPeer {
@@ -179,6 +182,7 @@ impl LocalPeer {
             services: services as u16,
             data_url: data_url,
             public_ip_address: None,
+            network_key: None,
         }
     }
 
@@ -231,6 +235,7 @@ impl FromRow<LocalPeer> for LocalPeer {
             services: services,
             data_url: data_url,
             public_ip_address: None,
+            network_key: None,
         })
     }
 }
@@ -290,6 +295,30 @@ impl FromRow<Neighbor> for Neighbor {
     }
 }
 
+impl FromRow<PeerScore> for PeerScore {
+    fn from_row<'a>(row: &'a Row) -> Result<PeerScore, db_error> {
+        let network_id: u32 = row.get_unwrap("network_id");
+        let addrbytes: PeerAddress = PeerAddress::from_column(row, "addrbytes")?;
+        let port: u16 = row.get_unwrap("port");
+        let score = u64::from_column(row, "score")?;
+        let invalid_messages = u64::from_column(row, "invalid_messages")?;
+        let stalled_downloads = u64::from_column(row, "stalled_downloads")?;
+        let protocol_violations = u64::from_column(row, "protocol_violations")?;
+        let last_update = u64::from_column(row, "last_update")?;
+
+        Ok(PeerScore {
+            network_id,
+            addrbytes,
+            port,
+            score,
+            invalid_messages,
+            stalled_downloads,
+            protocol_violations,
+            last_update,
+        })
+    }
+}
+
 // In what is likely an abuse of Sqlite, the peer database is structured such that the `frontier`
 // table stores peers keyed by a deterministically-chosen random "slot," instead of their IP/port.
 // (i.e. the slot is determined by a cryptographic the hash of the IP/port).  The reason for this
@@ -358,6 +387,23 @@ const PEERDB_INITIAL_SCHEMA: &'static [&'static str] = &[
     );"#,
 ];
 
+const PEERDB_SCHEMA_2: &'static [&'static str] = &[
+    r#"
+    CREATE TABLE peer_scores(
+        network_id INTEGER NOT NULL,
+        addrbytes TEXT NOT NULL,
+        port INTEGER NOT NULL,
+        score INTEGER NOT NULL,
+        invalid_messages INTEGER NOT NULL,
+        stalled_downloads INTEGER NOT NULL,
+        protocol_violations INTEGER NOT NULL,
+        last_update INTEGER NOT NULL,
+
+        PRIMARY KEY(network_id,addrbytes,port)
+    );"#,
+    "UPDATE db_config SET version = 2;",
+];
+
 #[derive(Debug)]
 pub struct PeerDB {
     pub conn: Connection,
@@ -392,6 +438,9 @@ impl PeerDB {
         for row_text in PEERDB_INITIAL_SCHEMA {
             tx.execute_batch(row_text).map_err(db_error::SqliteError)?;
         }
+        for row_text in PEERDB_SCHEMA_2 {
+            tx.execute_batch(row_text).map_err(db_error::SqliteError)?;
+        }
 
         tx.execute(
             "INSERT INTO db_config (version) VALUES (?1)",
@@ -569,6 +618,11 @@ impl PeerDB {
 
             {
                 let mut tx = db.tx_begin()?;
+                if !table_exists(&tx, "peer_scores")? {
+                    for row_text in PEERDB_SCHEMA_2 {
+                        tx.execute_batch(row_text).map_err(db_error::SqliteError)?;
+                    }
+                }
                 PeerDB::refresh_allows(&mut tx)?;
                 PeerDB::refresh_denies(&mut tx)?;
                 PeerDB::clear_initial_peers(&mut tx)?;
@@ -851,6 +905,15 @@ impl PeerDB {
         Ok(allow_rows)
     }
 
+    /// Count the peers we learned about on our own -- i.e. not bootstrap peers, and not peers we
+    /// only know about because we deny them
+    pub fn get_num_discovered_peers(conn: &DBConn, network_id: u32) -> Result<u64, db_error> {
+        let sql = "SELECT COUNT(*) FROM frontier WHERE initial = 0 AND network_id = ?1 AND denied >= 0 AND denied < ?2";
+        let args: &[&dyn ToSql] = &[&network_id, &u64_to_sql(util::get_epoch_time_secs())?];
+        let count = query_count(conn, sql, args)?;
+        Ok(count as u64)
+    }
+
     /// Insert or replace a neighbor into a given slot
     pub fn insert_or_replace_peer<'a>(
         tx: &mut Transaction<'a>,
@@ -1392,11 +1455,48 @@ impl PeerDB {
         let rows = query_rows::<Neighbor, _>(conn, &qry, NO_PARAMS)?;
         Ok(rows)
     }
+
+    /// Get a peer's reputation score, if it has ever been penalized
+    pub fn get_peer_score(
+        conn: &DBConn,
+        network_id: u32,
+        peer_addr: &PeerAddress,
+        peer_port: u16,
+    ) -> Result<Option<PeerScore>, db_error> {
+        let qry =
+            "SELECT * FROM peer_scores WHERE network_id = ?1 AND addrbytes = ?2 AND port = ?3";
+        let args: &[&dyn ToSql] = &[&network_id, &peer_addr.to_bin(), &peer_port];
+        query_row::<PeerScore, _>(conn, qry, args)
+    }
+
+    /// Get the reputation scores of all peers that have ever been penalized
+    pub fn get_peer_scores(conn: &DBConn) -> Result<Vec<PeerScore>, db_error> {
+        let qry = "SELECT * FROM peer_scores ORDER BY addrbytes ASC, port ASC";
+        query_rows::<PeerScore, _>(conn, qry, NO_PARAMS)
+    }
+
+    /// Store a peer's reputation score
+    pub fn set_peer_score<'a>(tx: &mut Transaction<'a>, score: &PeerScore) -> Result<(), db_error> {
+        let args: &[&dyn ToSql] = &[
+            &score.network_id,
+            &score.addrbytes.to_bin(),
+            &score.port,
+            &u64_to_sql(score.score)?,
+            &u64_to_sql(score.invalid_messages)?,
+            &u64_to_sql(score.stalled_downloads)?,
+            &u64_to_sql(score.protocol_violations)?,
+            &u64_to_sql(score.last_update)?,
+        ];
+        tx.execute("INSERT OR REPLACE INTO peer_scores (network_id, addrbytes, port, score, invalid_messages, stalled_downloads, protocol_violations, last_update) VALUES (?1,?2,?3,?4,?5,?6,?7,?8)", args)
+            .map_err(db_error::SqliteError)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use net::reputation::PeerMisbehavior;
     use net::Neighbor;
     use net::NeighborKey;
     use net::PeerAddress;
@@ -2304,4 +2404,46 @@ mod test {
         assert_eq!(n1.allowed, -1);
         assert_eq!(n2.allowed, -1);
     }
+
+    #[test]
+    fn test_peer_scores() {
+        let mut db = PeerDB::connect_memory(
+            0x9abcdef0,
+            12345,
+            0,
+            "http://foo.com".into(),
+            &vec![],
+            &vec![],
+        )
+        .unwrap();
+
+        let addr = PeerAddress([
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0x7f, 0x00,
+            0x00, 0x01,
+        ]);
+        assert_eq!(
+            PeerDB::get_peer_score(db.conn(), 0x9abcdef0, &addr, 12345).unwrap(),
+            None
+        );
+
+        let mut score = PeerScore::new(0x9abcdef0, addr.clone(), 12345);
+        score.add_penalty(PeerMisbehavior::InvalidMessage, 1000, 60);
+        {
+            let mut tx = db.tx_begin().unwrap();
+            PeerDB::set_peer_score(&mut tx, &score).unwrap();
+            tx.commit().unwrap();
+        }
+        assert_eq!(
+            PeerDB::get_peer_score(db.conn(), 0x9abcdef0, &addr, 12345).unwrap(),
+            Some(score.clone())
+        );
+
+        score.add_penalty(PeerMisbehavior::StalledDownload, 1001, 60);
+        {
+            let mut tx = db.tx_begin().unwrap();
+            PeerDB::set_peer_score(&mut tx, &score).unwrap();
+            tx.commit().unwrap();
+        }
+        assert_eq!(PeerDB::get_peer_scores(db.conn()).unwrap(), vec![score]);
+    }
 }
@@ -215,9 +215,12 @@ pub struct BlockDownloader {
     pub finished_scan_at: u64,
     last_inv_update_at: u64,
 
-    /// Maximum number of concurrent requests
+    /// Maximum number of sortitions to fetch concurrently
     max_inflight_requests: u64,
 
+    /// Maximum number of concurrent requests to any one peer's data URL
+    max_inflight_requests_per_peer: u64,
+
     /// Block requests to try, grouped by block, keyed by sortition height
     blocks_to_try: HashMap<u64, VecDeque<BlockRequestKey>>,
 
@@ -240,6 +243,7 @@ pub struct BlockDownloader {
     dead_peers: Vec<usize>,
     broken_peers: Vec<usize>,
     broken_neighbors: Vec<NeighborKey>, // disconnect peers who report invalid block inventories too
+    stalled_neighbors: Vec<NeighborKey>, // peers whose data-plane endpoints we could not download from
 
     blocked_urls: HashMap<UrlString, u64>, // URLs that chronically don't work, and when we can try them again
 
@@ -256,6 +260,7 @@ impl BlockDownloader {
         dns_timeout: u128,
         download_interval: u64,
         max_inflight_requests: u64,
+        max_inflight_requests_per_peer: u64,
     ) -> BlockDownloader {
         BlockDownloader {
             state: BlockDownloaderState::DNSLookupBegin,
@@ -274,6 +279,7 @@ impl BlockDownloader {
             last_inv_update_at: 0,
 
             max_inflight_requests: max_inflight_requests,
+            max_inflight_requests_per_peer: max_inflight_requests_per_peer,
             blocks_to_try: HashMap::new(),
             microblocks_to_try: HashMap::new(),
 
@@ -289,6 +295,7 @@ impl BlockDownloader {
             dead_peers: vec![],
             broken_peers: vec![],
             broken_neighbors: vec![],
+            stalled_neighbors: vec![],
             blocked_urls: HashMap::new(),
 
             download_interval: download_interval,
@@ -468,6 +475,7 @@ impl BlockDownloader {
                             };
 
                             if !is_always_allowed {
+                                self.stalled_neighbors.push(block_key.neighbor.clone());
                                 debug!("Event {} ({:?}, {:?}) for block {} failed to connect. Temporarily blocking URL", event_id, &block_key.neighbor, &block_key.data_url, &block_key.index_block_hash);
 
                                 // don't try this again for a while
@@ -592,6 +600,7 @@ impl BlockDownloader {
                             };
 
                             if !is_always_allowed {
+                                self.stalled_neighbors.push(block_key.neighbor.clone());
                                 debug!(
                                     "Event {} ({:?}, {:?} for microblocks built by ({}) failed to connect.  Temporarily blocking URL.",
                                     event_id,
@@ -888,6 +897,11 @@ impl BlockDownloader {
         (disconnect, disconnect_neighbors)
     }
 
+    /// Take the neighbors whose downloads failed since we last asked, so they can be penalized
+    pub fn take_stalled_neighbors(&mut self) -> Vec<NeighborKey> {
+        std::mem::replace(&mut self.stalled_neighbors, vec![])
+    }
+
     /// Set a hint that a block is now available from a remote peer, if we're idling or we're ahead
     /// of the given height.
     pub fn hint_block_sortition_height_available(
@@ -967,6 +981,19 @@ impl BlockDownloader {
         self.microblocks_to_try.len() + self.blocks_to_try.len()
     }
 
+    /// How many block and microblock requests are outstanding to each data URL?
+    fn inflight_requests_by_url(&self) -> HashMap<UrlString, u64> {
+        let mut inflight = HashMap::new();
+        for request_key in self
+            .getblock_requests
+            .keys()
+            .chain(self.getmicroblocks_requests.keys())
+        {
+            *inflight.entry(request_key.data_url.clone()).or_insert(0) += 1;
+        }
+        inflight
+    }
+
     // is the downloader idle? i.e. did we already do a scan?
     pub fn is_download_idle(&self) -> bool {
         self.empty_block_download_passes > 0 && self.empty_microblock_download_passes > 0
@@ -1993,6 +2020,112 @@ impl PeerNetwork {
         None
     }
 
+    /// Start a request like begin_request, but pass over requests to data URLs that already have
+    /// `max_inflight_per_url` requests outstanding.  Passed-over requests stay queued in
+    /// `requestables`, so they can be tried once that peer's requests complete.
+    pub fn begin_peer_limited_request<T: Requestable>(
+        network: &mut PeerNetwork,
+        dns_lookups: &HashMap<UrlString, Option<Vec<SocketAddr>>>,
+        requestables: &mut VecDeque<T>,
+        inflight: &mut HashMap<UrlString, u64>,
+        max_inflight_per_url: u64,
+        chainstate: &mut StacksChainState,
+    ) -> Option<(T, usize)> {
+        let mut available = VecDeque::new();
+        let mut deferred = VecDeque::new();
+        for requestable in requestables.drain(..) {
+            if *inflight.get(requestable.get_url()).unwrap_or(&0) >= max_inflight_per_url {
+                deferred.push_back(requestable);
+            } else {
+                available.push_back(requestable);
+            }
+        }
+
+        let result = PeerNetwork::begin_request(network, dns_lookups, &mut available, chainstate);
+        if let Some((ref requestable, _)) = result {
+            *inflight.entry(requestable.get_url().clone()).or_insert(0) += 1;
+        }
+
+        requestables.append(&mut available);
+        requestables.append(&mut deferred);
+        result
+    }
+
+    /// Start a block (or confirmed microblock stream) request for each sortition we're trying
+    /// that does not have one in flight and has not been fetched yet, rarest first.  Requests are
+    /// spread across peers so that no data URL gets more than the per-peer limit.
+    fn begin_block_requests(
+        network: &mut PeerNetwork,
+        downloader: &mut BlockDownloader,
+        chainstate: &mut StacksChainState,
+        microblocks: bool,
+    ) -> HashMap<BlockRequestKey, usize> {
+        let mut inflight = downloader.inflight_requests_by_url();
+        let max_inflight_per_url = downloader.max_inflight_requests_per_peer;
+
+        // sortitions that are already in flight, or that we have data for and have not yet
+        // handed off
+        let busy: HashSet<u64> = if microblocks {
+            downloader
+                .getmicroblocks_requests
+                .keys()
+                .chain(downloader.microblocks.keys())
+                .map(|request_key| request_key.sortition_height)
+                .collect()
+        } else {
+            downloader
+                .getblock_requests
+                .keys()
+                .chain(downloader.blocks.keys())
+                .map(|request_key| request_key.sortition_height)
+                .collect()
+        };
+
+        let mut priority = if microblocks {
+            PeerNetwork::prioritize_requests(&downloader.microblocks_to_try)
+        } else {
+            PeerNetwork::prioritize_requests(&downloader.blocks_to_try)
+        };
+
+        let mut requests = HashMap::new();
+        for sortition_height in priority.drain(..) {
+            if busy.contains(&sortition_height) {
+                continue;
+            }
+            let keys_opt = if microblocks {
+                downloader.microblocks_to_try.get_mut(&sortition_height)
+            } else {
+                downloader.blocks_to_try.get_mut(&sortition_height)
+            };
+            match keys_opt {
+                Some(ref mut keys) => {
+                    match PeerNetwork::begin_peer_limited_request(
+                        network,
+                        &downloader.dns_lookups,
+                        keys,
+                        &mut inflight,
+                        max_inflight_per_url,
+                        chainstate,
+                    ) {
+                        Some((key, handle)) => {
+                            requests.insert(key.clone(), handle);
+                        }
+                        None => {}
+                    }
+                }
+                None => {
+                    debug!(
+                        "{:?}: No {} at sortition height {}",
+                        &network.local_peer,
+                        if microblocks { "microblocks" } else { "block" },
+                        sortition_height
+                    );
+                }
+            }
+        }
+        requests
+    }
+
     /// Start fetching blocks
     pub fn block_getblocks_begin(
         &mut self,
@@ -2000,42 +2133,37 @@ impl PeerNetwork {
     ) -> Result<(), net_error> {
         test_debug!("{:?}: block_getblocks_begin", &self.local_peer);
         PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
-            let mut priority = PeerNetwork::prioritize_requests(&downloader.blocks_to_try);
-            let mut requests = HashMap::new();
-            for sortition_height in priority.drain(..) {
-                match downloader.blocks_to_try.get_mut(&sortition_height) {
-                    Some(ref mut keys) => {
-                        match PeerNetwork::begin_request(
-                            network,
-                            &downloader.dns_lookups,
-                            keys,
-                            chainstate,
-                        ) {
-                            Some((key, handle)) => {
-                                requests.insert(key.clone(), handle);
-                            }
-                            None => {}
-                        }
-                    }
-                    None => {
-                        debug!(
-                            "{:?}: No block at sortition height {}",
-                            &network.local_peer, sortition_height
-                        );
-                    }
-                }
-            }
-
+            let requests =
+                PeerNetwork::begin_block_requests(network, downloader, chainstate, false);
             downloader.getblocks_begin(requests);
             Ok(())
         })
     }
 
     /// Try to see if all blocks are finished downloading
-    pub fn block_getblocks_try_finish(&mut self) -> Result<bool, net_error> {
+    /// As requests complete (or fail), start requests for sortitions that were waiting on a busy
+    /// peer or that need to be retried from a different peer, so the pipeline stays full.
+    pub fn block_getblocks_try_finish(
+        &mut self,
+        chainstate: &mut StacksChainState,
+    ) -> Result<bool, net_error> {
         test_debug!("{:?}: block_getblocks_try_finish", &self.local_peer);
         PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
-            downloader.getblocks_try_finish(network)
+            let done = downloader.getblocks_try_finish(network)?;
+            let requests =
+                PeerNetwork::begin_block_requests(network, downloader, chainstate, false);
+            if requests.len() == 0 {
+                return Ok(done);
+            }
+
+            debug!(
+                "{:?}: Started {} more block request(s)",
+                &network.local_peer,
+                requests.len()
+            );
+            downloader.getblock_requests.extend(requests);
+            downloader.state = BlockDownloaderState::GetBlocksFinish;
+            Ok(false)
         })
     }
 
@@ -2046,45 +2174,137 @@ impl PeerNetwork {
     ) -> Result<(), net_error> {
         test_debug!("{:?}: block_getmicroblocks_begin", &self.local_peer);
         PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
-            let mut priority = PeerNetwork::prioritize_requests(&downloader.microblocks_to_try);
-            let mut requests = HashMap::new();
-            for sortition_height in priority.drain(..) {
-                match downloader.microblocks_to_try.get_mut(&sortition_height) {
-                    Some(ref mut keys) => {
-                        match PeerNetwork::begin_request(
-                            network,
-                            &downloader.dns_lookups,
-                            keys,
-                            chainstate,
-                        ) {
-                            Some((key, handle)) => {
-                                requests.insert(key.clone(), handle);
-                            }
-                            None => {}
-                        }
-                    }
-                    None => {
-                        debug!(
-                            "{:?}: No microblocks at sortition height {}",
-                            &network.local_peer, sortition_height
-                        );
-                    }
-                }
-            }
-
+            let requests = PeerNetwork::begin_block_requests(network, downloader, chainstate, true);
             downloader.getmicroblocks_begin(requests);
             Ok(())
         })
     }
 
     /// Try to see if all microblocks are finished downloading
-    pub fn block_getmicroblocks_try_finish(&mut self) -> Result<bool, net_error> {
+    /// Like block_getblocks_try_finish, start more microblock requests as earlier ones complete.
+    pub fn block_getmicroblocks_try_finish(
+        &mut self,
+        chainstate: &mut StacksChainState,
+    ) -> Result<bool, net_error> {
         test_debug!("{:?}: block_getmicroblocks_try_finish", &self.local_peer);
         PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
-            downloader.getmicroblocks_try_finish(network)
+            let done = downloader.getmicroblocks_try_finish(network)?;
+            let requests = PeerNetwork::begin_block_requests(network, downloader, chainstate, true);
+            if requests.len() == 0 {
+                return Ok(done);
+            }
+
+            debug!(
+                "{:?}: Started {} more microblock request(s)",
+                &network.local_peer,
+                requests.len()
+            );
+            downloader.getmicroblocks_requests.extend(requests);
+            downloader.state = BlockDownloaderState::GetMicroblocksFinish;
+            Ok(false)
         })
     }
 
+    /// Hand off the blocks and microblock streams that have arrived so far, without waiting for
+    /// the rest of the pass to finish.  They may be out of sortition order; the chainstate stages
+    /// them until their parents arrive.
+    fn drain_downloads(
+        downloader: &mut BlockDownloader,
+        chainstate: &mut StacksChainState,
+    ) -> Result<
+        (
+            Vec<(ConsensusHash, StacksBlock, u64)>,
+            Vec<(ConsensusHash, Vec<StacksMicroblock>, u64)>,
+        ),
+        net_error,
+    > {
+        let mut blocks = vec![];
+        let mut microblocks = vec![];
+        let now = get_epoch_time_secs();
+
+        // extract blocks and microblocks downloaded
+        for (request_key, block) in downloader.blocks.drain() {
+            debug!(
+                "Downloaded block {}/{} ({}) at sortition height {}",
+                &request_key.consensus_hash,
+                &request_key.anchor_block_hash,
+                &request_key.index_block_hash,
+                request_key.sortition_height
+            );
+            blocks.push((
+                request_key.consensus_hash.clone(),
+                block,
+                now.saturating_sub(request_key.download_start),
+            ));
+            downloader.num_blocks_downloaded += 1;
+
+            // don't try this again
+            downloader
+                .blocks_to_try
+                .remove(&request_key.sortition_height);
+        }
+        for (request_key, mut microblock_stream) in downloader.microblocks.drain() {
+            // NOTE: microblock streams are served in reverse order, since they're forks
+            microblock_stream.reverse();
+
+            let block_header = StacksChainState::load_block_header(
+                &chainstate.blocks_path,
+                &request_key.consensus_hash,
+                &request_key.anchor_block_hash,
+            )?
+            .expect(&format!(
+                "BUG: missing Stacks block header for {}/{}",
+                &request_key.consensus_hash, &request_key.anchor_block_hash
+            ));
+
+            assert!(
+                request_key.parent_block_header.is_some()
+                    && request_key.parent_consensus_hash.is_some(),
+                "BUG: requested a microblock but didn't set the child block header"
+            );
+            let parent_block_header = request_key.parent_block_header.unwrap();
+            let parent_consensus_hash = request_key.parent_consensus_hash.unwrap();
+
+            if StacksChainState::validate_parent_microblock_stream(
+                &parent_block_header,
+                &block_header,
+                &microblock_stream,
+                true,
+            )
+            .is_some()
+            {
+                // stream is valid!
+                debug!(
+                    "Downloaded valid microblock stream confirmed by {}/{} at sortition height {}",
+                    &request_key.consensus_hash,
+                    &request_key.anchor_block_hash,
+                    request_key.sortition_height
+                );
+                microblocks.push((
+                    parent_consensus_hash,
+                    microblock_stream,
+                    now.saturating_sub(request_key.download_start),
+                ));
+                downloader.num_microblocks_downloaded += 1;
+            } else {
+                // stream is not well-formed
+                debug!(
+                    "Microblock stream {:?}: confirmed by {}/{} is invalid",
+                    request_key.sortition_height,
+                    &request_key.consensus_hash,
+                    &request_key.anchor_block_hash
+                );
+            }
+
+            // don't try again
+            downloader
+                .microblocks_to_try
+                .remove(&request_key.sortition_height);
+        }
+
+        Ok((blocks, microblocks))
+    }
+
     /// Process newly-fetched blocks and microblocks.
     /// Returns true if we've completed all requests.
     /// Returns (done?, at-chain-tip?, blocks-we-got, microblocks-we-got) on success
@@ -2102,14 +2322,10 @@ impl PeerNetwork {
         ),
         net_error,
     > {
-        let mut blocks = vec![];
-        let mut microblocks = vec![];
         let mut done = false;
         let mut at_chain_tip = false;
         let mut old_pox_id = None;
 
-        let now = get_epoch_time_secs();
-
         let inv_sortition_start = self
             .inv_state
             .as_ref()
@@ -2117,85 +2333,7 @@ impl PeerNetwork {
             .unwrap_or(0);
 
         PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
-            // extract blocks and microblocks downloaded
-            for (request_key, block) in downloader.blocks.drain() {
-                debug!(
-                    "Downloaded block {}/{} ({}) at sortition height {}",
-                    &request_key.consensus_hash,
-                    &request_key.anchor_block_hash,
-                    &request_key.index_block_hash,
-                    request_key.sortition_height
-                );
-                blocks.push((
-                    request_key.consensus_hash.clone(),
-                    block,
-                    now.saturating_sub(request_key.download_start),
-                ));
-                downloader.num_blocks_downloaded += 1;
-
-                // don't try this again
-                downloader
-                    .blocks_to_try
-                    .remove(&request_key.sortition_height);
-            }
-            for (request_key, mut microblock_stream) in downloader.microblocks.drain() {
-                // NOTE: microblock streams are served in reverse order, since they're forks
-                microblock_stream.reverse();
-
-                let block_header = StacksChainState::load_block_header(
-                    &chainstate.blocks_path,
-                    &request_key.consensus_hash,
-                    &request_key.anchor_block_hash,
-                )?
-                .expect(&format!(
-                    "BUG: missing Stacks block header for {}/{}",
-                    &request_key.consensus_hash, &request_key.anchor_block_hash
-                ));
-
-                assert!(
-                    request_key.parent_block_header.is_some()
-                        && request_key.parent_consensus_hash.is_some(),
-                    "BUG: requested a microblock but didn't set the child block header"
-                );
-                let parent_block_header = request_key.parent_block_header.unwrap();
-                let parent_consensus_hash = request_key.parent_consensus_hash.unwrap();
-
-                if StacksChainState::validate_parent_microblock_stream(
-                    &parent_block_header,
-                    &block_header,
-                    &microblock_stream,
-                    true,
-                )
-                .is_some()
-                {
-                    // stream is valid!
-                    debug!(
-                        "Downloaded valid microblock stream confirmed by {}/{} at sortition height {}",
-                        &request_key.consensus_hash,
-                        &request_key.anchor_block_hash,
-                        request_key.sortition_height
-                    );
-                    microblocks.push((
-                        parent_consensus_hash,
-                        microblock_stream,
-                        now.saturating_sub(request_key.download_start),
-                    ));
-                    downloader.num_microblocks_downloaded += 1;
-                } else {
-                    // stream is not well-formed
-                    debug!(
-                        "Microblock stream {:?}: confirmed by {}/{} is invalid",
-                        request_key.sortition_height,
-                        &request_key.consensus_hash,
-                        &request_key.anchor_block_hash
-                    );
-                }
-
-                // don't try again
-                downloader
-                    .microblocks_to_try
-                    .remove(&request_key.sortition_height);
-            }
+            let (blocks, microblocks) = PeerNetwork::drain_downloads(downloader, chainstate)?;
 
             // clear empties
             let mut blocks_empty = vec![];
@@ -2344,6 +2482,7 @@ impl PeerNetwork {
             self.connection_opts.dns_timeout,
             self.connection_opts.download_interval,
             self.connection_opts.max_inflight_blocks,
+            self.connection_opts.max_inflight_blocks_per_peer,
         ));
     }
 
@@ -2469,13 +2608,13 @@ impl PeerNetwork {
                     self.block_getblocks_begin(chainstate)?;
                 }
                 BlockDownloaderState::GetBlocksFinish => {
-                    self.block_getblocks_try_finish()?;
+                    self.block_getblocks_try_finish(chainstate)?;
                 }
                 BlockDownloaderState::GetMicroblocksBegin => {
                     self.block_getmicroblocks_begin(chainstate)?;
                 }
                 BlockDownloaderState::GetMicroblocksFinish => {
-                    self.block_getmicroblocks_try_finish()?;
+                    self.block_getmicroblocks_try_finish(chainstate)?;
                 }
                 BlockDownloaderState::Done => {
                     // did a pass.
@@ -2504,6 +2643,14 @@ impl PeerNetwork {
             }
         }
 
+        // don't hold on to blocks and microblocks that arrived ahead of the rest of this pass
+        if let Some(ref mut downloader) = self.block_downloader {
+            let (mut early_blocks, mut early_microblocks) =
+                PeerNetwork::drain_downloads(downloader, chainstate)?;
+            blocks.append(&mut early_blocks);
+            microblocks.append(&mut early_microblocks);
+        }
+
         // remove dead/broken peers
         let (broken_http_peers, broken_p2p_peers) = match self.block_downloader {
             Some(ref mut downloader) => downloader.clear_broken_peers(),
@@ -3121,6 +3268,77 @@ pub mod test {
         })
     }
 
+    #[test]
+    #[ignore]
+    pub fn test_get_blocks_and_microblocks_2_peers_download_one_request_per_peer() {
+        with_timeout(600, || {
+            run_get_blocks_and_microblocks(
+                "test_get_blocks_and_microblocks_2_peers_download_one_request_per_peer",
+                3270,
+                2,
+                |ref mut peer_configs| {
+                    // build initial network topology
+                    assert_eq!(peer_configs.len(), 2);
+
+                    peer_configs[0].connection_opts.disable_block_advertisement = true;
+                    peer_configs[1].connection_opts.disable_block_advertisement = true;
+
+                    // requests for the other sortitions must wait their turn
+                    peer_configs[0].connection_opts.max_inflight_blocks_per_peer = 1;
+                    peer_configs[1].connection_opts.max_inflight_blocks_per_peer = 1;
+
+                    let peer_0 = peer_configs[0].to_neighbor();
+                    let peer_1 = peer_configs[1].to_neighbor();
+                    peer_configs[0].add_neighbor(&peer_1);
+                    peer_configs[1].add_neighbor(&peer_0);
+                },
+                |num_blocks, ref mut peers| {
+                    // build up block data to replicate
+                    let mut block_data = vec![];
+                    for _ in 0..num_blocks {
+                        let (mut burn_ops, stacks_block, microblocks) =
+                            peers[1].make_default_tenure();
+
+                        let (_, burn_header_hash, consensus_hash) =
+                            peers[1].next_burnchain_block(burn_ops.clone());
+                        peers[1].process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+
+                        TestPeer::set_ops_burn_header_hash(&mut burn_ops, &burn_header_hash);
+
+                        peers[0].next_burnchain_block_raw(burn_ops);
+
+                        let sn = SortitionDB::get_canonical_burn_chain_tip(
+                            &peers[1].sortdb.as_ref().unwrap().conn(),
+                        )
+                        .unwrap();
+                        block_data.push((
+                            sn.consensus_hash.clone(),
+                            Some(stacks_block),
+                            Some(microblocks),
+                        ));
+                    }
+                    block_data
+                },
+                |_| {},
+                |peer| {
+                    // never more than one request outstanding to the other peer
+                    match peer.network.block_downloader {
+                        Some(ref dl) => {
+                            assert_eq!(dl.broken_peers.len(), 0);
+                            assert_eq!(dl.dead_peers.len(), 0);
+                            for (_, count) in dl.inflight_requests_by_url().iter() {
+                                assert!(*count <= 1);
+                            }
+                        }
+                        None => {}
+                    }
+                    true
+                },
+                |_| true,
+            );
+        })
+    }
+
     #[test]
     #[ignore]
     pub fn test_get_blocks_and_microblocks_5_peers_star() {
@@ -41,10 +41,12 @@ use chainstate::burn::ConsensusHash;
 use chainstate::stacks::{StacksBlock, StacksMicroblock, StacksPublicKey, StacksTransaction};
 use deps::httparse;
 use net::atlas::Attachment;
+use net::ratelimit::RPCEndpointClass;
 use net::CallReadOnlyRequestBody;
 use net::ClientError;
 use net::Error as net_error;
 use net::Error::ClarityError;
+use net::HttpContentEncoding;
 use net::HttpContentType;
 use net::HttpRequestMetadata;
 use net::HttpRequestPreamble;
@@ -53,27 +55,49 @@ use net::HttpResponseMetadata;
 use net::HttpResponsePreamble;
 use net::HttpResponseType;
 use net::HttpVersion;
+use net::MempoolNonceStatusResponse;
 use net::MessageSequence;
 use net::NeighborAddress;
 use net::PeerAddress;
 use net::PeerHost;
 use net::ProtocolFamily;
+use net::RPCBNSNameInfo;
+use net::RPCHealthData;
+use net::RPCRole;
 use net::StacksHttpMessage;
 use net::StacksHttpPreamble;
+use net::TipRequest;
+use net::TransactionSimulationResponse;
+use net::TransactionStatusResponse;
 use net::UnconfirmedTransactionResponse;
 use net::UnconfirmedTransactionStatus;
 use net::HTTP_PREAMBLE_MAX_ENCODED_SIZE;
 use net::HTTP_PREAMBLE_MAX_NUM_HEADERS;
 use net::HTTP_REQUEST_ID_RESERVED;
 use net::MAX_MICROBLOCKS_UNCONFIRMED;
+use net::{
+    AddressAssetsResponse, AddressTransactionsResponse, BlockHeaderResponse, NftHistoryResponse,
+    ADDRESS_TXS_PAGE_SIZE,
+};
+use net::{BatchAccountsResponse, PostAccountsRequestBody, MAX_BATCH_ACCOUNTS};
+use net::{
+    BatchReadOnlyCall, CallReadOnlyBatchRequestBody, CallReadOnlyBatchRequestEntry,
+    CallReadOnlyBatchResponse, MAX_BATCH_READ_ONLY_CALLS,
+};
+use net::{BurnBlockSelector, RPCSortitionInfo, RPCSortitionStats};
+use net::{EventReplayResponse, PostEventReplayRequestBody};
 use net::{GetAttachmentResponse, GetAttachmentsInvResponse, PostTransactionRequestBody};
+use net::{MempoolDroppedTransactionsResponse, MempoolTransactionsResponse, MEMPOOL_TXS_PAGE_SIZE};
+use net::{PostLogLevelRequestBody, RPCLogLevelsData};
+use net::{PostPeerBanRequestBody, RPCPeerReputationData};
 use util::hash::hex_bytes;
 use util::hash::to_hex;
 use util::hash::Hash160;
 use util::log;
 use util::retry::BoundReader;
 use util::retry::RetryReader;
-use vm::types::{StandardPrincipalData, TraitIdentifier};
+use vm::database::ClaritySerializable;
+use vm::types::{QualifiedContractIdentifier, StandardPrincipalData, TraitIdentifier};
 use vm::{
     ast::parser::{
         CLARITY_NAME_REGEX, CONTRACT_NAME_REGEX, PRINCIPAL_DATA_REGEX, STANDARD_PRINCIPAL_REGEX,
@@ -87,13 +111,18 @@ use crate::codec::{
     read_next, write_next, Error as codec_error, StacksMessageCodec, MAX_MESSAGE_LEN,
     MAX_PAYLOAD_LEN,
 };
-use crate::types::chainstate::{BlockHeaderHash, StacksAddress, StacksBlockId};
+use crate::types::chainstate::{
+    BlockHeaderHash, BurnchainHeaderHash, StacksAddress, StacksBlockId,
+};
 
 use super::FeeRateEstimateRequestBody;
 
 lazy_static! {
     static ref PATH_GETINFO: Regex = Regex::new(r#"^/v2/info$"#).unwrap();
     static ref PATH_GETPOXINFO: Regex = Regex::new(r#"^/v2/pox$"#).unwrap();
+    static ref PATH_GETPOXPROJECTION: Regex = Regex::new(r#"^/v2/pox/projection$"#).unwrap();
+    static ref PATH_GETREWARDSET: Regex =
+        Regex::new(r#"^/v2/pox/reward_set/([0-9]{1,20})$"#).unwrap();
     static ref PATH_GETNEIGHBORS: Regex = Regex::new(r#"^/v2/neighbors$"#).unwrap();
     static ref PATH_GETBLOCK: Regex = Regex::new(r#"^/v2/blocks/([0-9a-f]{64})$"#).unwrap();
     static ref PATH_GETMICROBLOCKS_INDEXED: Regex =
@@ -102,27 +131,83 @@ lazy_static! {
         Regex::new(r#"^/v2/microblocks/confirmed/([0-9a-f]{64})$"#).unwrap();
     static ref PATH_GETMICROBLOCKS_UNCONFIRMED: Regex =
         Regex::new(r#"^/v2/microblocks/unconfirmed/([0-9a-f]{64})/([0-9]{1,5})$"#).unwrap();
+    static ref PATH_GET_BLOCK_HEADER: Regex =
+        Regex::new(r#"^/v2/headers/([0-9a-f]{64})$"#).unwrap();
+    static ref PATH_GETBLOCK_BY_HEIGHT: Regex =
+        Regex::new(r#"^/v2/blocks/height/([0-9]{1,20})$"#).unwrap();
+    static ref PATH_GETMICROBLOCKS_BY_HEIGHT: Regex =
+        Regex::new(r#"^/v2/microblocks/height/([0-9]{1,20})$"#).unwrap();
+    static ref PATH_GET_SORTITION_BY_HEIGHT: Regex =
+        Regex::new(r#"^/v2/sortitions/height/([0-9]{1,20})$"#).unwrap();
+    static ref PATH_GET_SORTITION_BY_HASH: Regex =
+        Regex::new(r#"^/v2/sortitions/hash/([0-9a-f]{64})$"#).unwrap();
+    static ref PATH_GET_SORTITION_STATS: Regex = Regex::new(r#"^/v2/sortitions/stats$"#).unwrap();
+    static ref PATH_GET_BNS_NAME: Regex = Regex::new(
+        r#"^/v2/bns/names/(?P<name>[a-z0-9_-]{1,48})\.(?P<namespace>[a-z0-9_-]{1,20})$"#
+    )
+    .unwrap();
     static ref PATH_GETTRANSACTION_UNCONFIRMED: Regex =
         Regex::new(r#"^/v2/transactions/unconfirmed/([0-9a-f]{64})$"#).unwrap();
+    static ref PATH_GETTRANSACTION_STATUS: Regex =
+        Regex::new(r#"^/v2/transactions/status/([0-9a-f]{64})$"#).unwrap();
     static ref PATH_POSTTRANSACTION: Regex = Regex::new(r#"^/v2/transactions$"#).unwrap();
+    static ref PATH_POST_TRANSACTION_SIMULATION: Regex =
+        Regex::new(r#"^/v2/transactions/simulate$"#).unwrap();
     static ref PATH_POST_FEE_RATE_ESIMATE: Regex = Regex::new(r#"^/v2/fees/transaction$"#).unwrap();
     static ref PATH_POSTBLOCK: Regex = Regex::new(r#"^/v2/blocks/upload/([0-9a-f]{40})$"#).unwrap();
     static ref PATH_POSTMICROBLOCK: Regex = Regex::new(r#"^/v2/microblocks$"#).unwrap();
+    static ref PATH_POST_ACCOUNTS: Regex = Regex::new(r#"^/v2/accounts$"#).unwrap();
     static ref PATH_GET_ACCOUNT: Regex = Regex::new(&format!(
         "^/v2/accounts/(?P<principal>{})$",
         *PRINCIPAL_DATA_REGEX
     ))
     .unwrap();
+    static ref PATH_GET_ADDRESS_TRANSACTIONS: Regex = Regex::new(&format!(
+        "^/v2/addresses/(?P<principal>{})/transactions$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_ADDRESS_ASSETS: Regex = Regex::new(&format!(
+        "^/v2/addresses/(?P<principal>{})/assets$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_NFT_HISTORY: Regex = Regex::new(&format!(
+        "^/v2/nft_history/(?P<address>{})/(?P<contract>{})/(?P<asset>{})/(?P<value>(0x)?[0-9a-fA-F]+)$",
+        *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_MEMPOOL_TRANSACTIONS: Regex =
+        Regex::new(r#"^/v2/mempool/transactions$"#).unwrap();
+    static ref PATH_GET_MEMPOOL_DROPPED_TRANSACTIONS: Regex =
+        Regex::new(r#"^/v2/mempool/dropped$"#).unwrap();
+    static ref PATH_GET_MEMPOOL_ADDRESS_TRANSACTIONS: Regex = Regex::new(&format!(
+        "^/v2/mempool/addresses/(?P<address>{})/transactions$",
+        *STANDARD_PRINCIPAL_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_MEMPOOL_NONCE_STATUS: Regex = Regex::new(&format!(
+        "^/v2/mempool/addresses/(?P<address>{})/nonces$",
+        *STANDARD_PRINCIPAL_REGEX
+    ))
+    .unwrap();
     static ref PATH_GET_MAP_ENTRY: Regex = Regex::new(&format!(
         "^/v2/map_entry/(?P<address>{})/(?P<contract>{})/(?P<map>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
     ))
     .unwrap();
+    static ref PATH_GET_DATA_VAR: Regex = Regex::new(&format!(
+        "^/v2/data_var/(?P<address>{})/(?P<contract>{})/(?P<varname>{})$",
+        *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
+    ))
+    .unwrap();
     static ref PATH_POST_CALL_READ_ONLY: Regex = Regex::new(&format!(
         "^/v2/contracts/call-read/(?P<address>{})/(?P<contract>{})/(?P<function>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
     ))
     .unwrap();
+    static ref PATH_POST_CALL_READ_ONLY_BATCH: Regex =
+        Regex::new(r#"^/v2/contracts/call-read-batch$"#).unwrap();
     static ref PATH_GET_CONTRACT_SRC: Regex = Regex::new(&format!(
         "^/v2/contracts/source/(?P<address>{})/(?P<contract>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX
@@ -133,6 +218,11 @@ lazy_static! {
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
     ))
     .unwrap();
+    static ref PATH_GET_TRAIT_CONFORMANCE: Regex = Regex::new(&format!(
+        "^/v2/traits/conformance/(?P<address>{})/(?P<contract>{})/(?P<traitContractAddr>{})/(?P<traitContractName>{})/(?P<traitName>{})$",
+        *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
+    ))
+    .unwrap();
     static ref PATH_GET_CONTRACT_ABI: Regex = Regex::new(&format!(
         "^/v2/contracts/interface/(?P<address>{})/(?P<contract>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX
@@ -142,6 +232,13 @@ lazy_static! {
     static ref PATH_GET_ATTACHMENTS_INV: Regex = Regex::new("^/v2/attachments/inv$").unwrap();
     static ref PATH_GET_ATTACHMENT: Regex =
         Regex::new(r#"^/v2/attachments/([0-9a-f]{40})$"#).unwrap();
+    static ref PATH_GETHEALTH: Regex = Regex::new("^/v2/health$").unwrap();
+    static ref PATH_ADMIN_LOG_LEVELS: Regex = Regex::new("^/v2/admin/log_levels$").unwrap();
+    static ref PATH_ADMIN_EVENT_REPLAY: Regex =
+        Regex::new("^/v2/admin/events/replay$").unwrap();
+    static ref PATH_ADMIN_PEERS: Regex = Regex::new("^/v2/admin/peers$").unwrap();
+    static ref PATH_ADMIN_PEERS_BAN: Regex = Regex::new("^/v2/admin/peers/ban$").unwrap();
+    static ref PATH_ADMIN_PEERS_UNBAN: Regex = Regex::new("^/v2/admin/peers/unban$").unwrap();
     static ref PATH_OPTIONS_WILDCARD: Regex = Regex::new("^/v2/.{0,4096}$").unwrap();
 }
 
@@ -875,6 +972,111 @@ fn keep_alive_headers<W: Write>(fd: &mut W, md: &HttpResponseMetadata) -> Result
     Ok(())
 }
 
+/// Which cross-origin requests from browsers the RPC server allows.  CORS headers are only sent
+/// in reply to requests whose origin is allowed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpCorsPolicy {
+    /// origins allowed to query us ("*" means any origin; empty means none)
+    pub allowed_origins: Vec<String>,
+    /// methods a cross-origin request can use (reported in preflight responses)
+    pub allowed_methods: Vec<String>,
+    /// request headers a cross-origin request can set (reported in preflight responses)
+    pub allowed_headers: Vec<String>,
+    /// how long a browser can cache a preflight response, in seconds (0 = don't say)
+    pub max_age: u64,
+}
+
+impl std::default::Default for HttpCorsPolicy {
+    fn default() -> HttpCorsPolicy {
+        HttpCorsPolicy {
+            allowed_origins: vec!["*".to_string()],
+            allowed_methods: vec!["POST".to_string(), "GET".to_string(), "OPTIONS".to_string()],
+            allowed_headers: vec!["origin".to_string(), "content-type".to_string()],
+            max_age: 0,
+        }
+    }
+}
+
+impl HttpCorsPolicy {
+    /// What to send as Access-Control-Allow-Origin in reply to a request from the given origin,
+    /// if it is allowed at all.
+    pub fn allow_origin(&self, origin: Option<&str>) -> Option<String> {
+        if self.allowed_origins.iter().any(|allowed| allowed == "*") {
+            return Some("*".to_string());
+        }
+        let origin = origin?;
+        if self
+            .allowed_origins
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+        {
+            Some(origin.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Write the CORS headers for a response to a request from the given origin.  Preflight
+    /// responses also say which methods and headers are allowed.
+    pub fn write_headers<W: Write>(
+        &self,
+        fd: &mut W,
+        origin: Option<&str>,
+        preflight: bool,
+    ) -> Result<(), codec_error> {
+        let allow_origin = match self.allow_origin(origin) {
+            Some(allow_origin) => allow_origin,
+            None => {
+                return Ok(());
+            }
+        };
+        fd.write_all(format!("Access-Control-Allow-Origin: {}\r\n", &allow_origin).as_bytes())
+            .map_err(codec_error::WriteError)?;
+        if allow_origin != "*" {
+            // the response depends on who's asking
+            fd.write_all("Vary: Origin\r\n".as_bytes())
+                .map_err(codec_error::WriteError)?;
+        }
+        if preflight {
+            if self.allowed_methods.len() > 0 {
+                fd.write_all(
+                    format!(
+                        "Access-Control-Allow-Methods: {}\r\n",
+                        self.allowed_methods.join(", ")
+                    )
+                    .as_bytes(),
+                )
+                .map_err(codec_error::WriteError)?;
+            }
+            if self.allowed_headers.len() > 0 {
+                fd.write_all(
+                    format!(
+                        "Access-Control-Allow-Headers: {}\r\n",
+                        self.allowed_headers.join(", ")
+                    )
+                    .as_bytes(),
+                )
+                .map_err(codec_error::WriteError)?;
+            }
+            if self.max_age > 0 {
+                fd.write_all(format!("Access-Control-Max-Age: {}\r\n", self.max_age).as_bytes())
+                    .map_err(codec_error::WriteError)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Headers for every response to one of our clients
+fn response_headers<W: Write>(
+    fd: &mut W,
+    md: &HttpResponseMetadata,
+    cors_policy: &HttpCorsPolicy,
+) -> Result<(), codec_error> {
+    keep_alive_headers(fd, md)?;
+    cors_policy.write_headers(fd, md.client_origin.as_deref(), false)
+}
+
 fn write_headers<W: Write>(
     fd: &mut W,
     headers: &HashMap<String, String>,
@@ -1091,6 +1293,7 @@ impl HttpResponsePreamble {
     pub fn ok_JSON_from_md<W: Write>(
         fd: &mut W,
         md: &HttpResponseMetadata,
+        cors_policy: &HttpCorsPolicy,
     ) -> Result<(), codec_error> {
         HttpResponsePreamble::new_serialized(
             fd,
@@ -1099,7 +1302,7 @@ impl HttpResponsePreamble {
             md.content_length.clone(),
             &HttpContentType::JSON,
             md.request_id,
-            |ref mut fd| keep_alive_headers(fd, md),
+            |ref mut fd| response_headers(fd, md, cors_policy),
         )
     }
 
@@ -1123,12 +1326,6 @@ impl HttpResponsePreamble {
             .map_err(codec_error::WriteError)?;
         fd.write_all(rfc7231_now().as_bytes())
             .map_err(codec_error::WriteError)?;
-        fd.write_all("\r\nAccess-Control-Allow-Origin: *".as_bytes())
-            .map_err(codec_error::WriteError)?;
-        fd.write_all("\r\nAccess-Control-Allow-Headers: origin, content-type".as_bytes())
-            .map_err(codec_error::WriteError)?;
-        fd.write_all("\r\nAccess-Control-Allow-Methods: POST, GET, OPTIONS".as_bytes())
-            .map_err(codec_error::WriteError)?;
         fd.write_all("\r\nContent-Type: ".as_bytes())
             .map_err(codec_error::WriteError)?;
         fd.write_all(content_type.as_str().as_bytes())
@@ -1238,8 +1435,17 @@ impl HttpResponsePreamble {
     }
 
     pub fn add_CORS_headers(&mut self) -> () {
+        let cors_policy = HttpCorsPolicy::default();
         self.headers
             .insert("Access-Control-Allow-Origin".to_string(), "*".to_string());
+        self.headers.insert(
+            "Access-Control-Allow-Headers".to_string(),
+            cors_policy.allowed_headers.join(", "),
+        );
+        self.headers.insert(
+            "Access-Control-Allow-Methods".to_string(),
+            cors_policy.allowed_methods.join(", "),
+        );
     }
 
     // do we have Transfer-Encoding: chunked?
@@ -1453,12 +1659,27 @@ impl HttpRequestType {
         )] = &[
             ("GET", &PATH_GETINFO, &HttpRequestType::parse_getinfo),
             ("GET", &PATH_GETPOXINFO, &HttpRequestType::parse_getpoxinfo),
+            (
+                "GET",
+                &PATH_GETPOXPROJECTION,
+                &HttpRequestType::parse_getpoxprojection,
+            ),
+            (
+                "GET",
+                &PATH_GETREWARDSET,
+                &HttpRequestType::parse_getrewardset,
+            ),
             (
                 "GET",
                 &PATH_GETNEIGHBORS,
                 &HttpRequestType::parse_getneighbors,
             ),
             ("GET", &PATH_GETBLOCK, &HttpRequestType::parse_getblock),
+            (
+                "GET",
+                &PATH_GET_BLOCK_HEADER,
+                &HttpRequestType::parse_get_block_header,
+            ),
             (
                 "GET",
                 &PATH_GETMICROBLOCKS_INDEXED,
@@ -1474,11 +1695,46 @@ impl HttpRequestType {
                 &PATH_GETMICROBLOCKS_UNCONFIRMED,
                 &HttpRequestType::parse_getmicroblocks_unconfirmed,
             ),
+            (
+                "GET",
+                &PATH_GETBLOCK_BY_HEIGHT,
+                &HttpRequestType::parse_getblock_by_height,
+            ),
+            (
+                "GET",
+                &PATH_GETMICROBLOCKS_BY_HEIGHT,
+                &HttpRequestType::parse_getmicroblocks_by_height,
+            ),
+            (
+                "GET",
+                &PATH_GET_SORTITION_BY_HEIGHT,
+                &HttpRequestType::parse_get_sortition_by_height,
+            ),
+            (
+                "GET",
+                &PATH_GET_SORTITION_BY_HASH,
+                &HttpRequestType::parse_get_sortition_by_hash,
+            ),
+            (
+                "GET",
+                &PATH_GET_SORTITION_STATS,
+                &HttpRequestType::parse_get_sortition_stats,
+            ),
+            (
+                "GET",
+                &PATH_GET_BNS_NAME,
+                &HttpRequestType::parse_get_bns_name,
+            ),
             (
                 "GET",
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpRequestType::parse_gettransaction_unconfirmed,
             ),
+            (
+                "GET",
+                &PATH_GETTRANSACTION_STATUS,
+                &HttpRequestType::parse_gettransaction_status,
+            ),
             (
                 "POST",
                 &PATH_POST_FEE_RATE_ESIMATE,
@@ -1489,6 +1745,11 @@ impl HttpRequestType {
                 &PATH_POSTTRANSACTION,
                 &HttpRequestType::parse_posttransaction,
             ),
+            (
+                "POST",
+                &PATH_POST_TRANSACTION_SIMULATION,
+                &HttpRequestType::parse_post_transaction_simulation,
+            ),
             ("POST", &PATH_POSTBLOCK, &HttpRequestType::parse_postblock),
             (
                 "POST",
@@ -1500,11 +1761,56 @@ impl HttpRequestType {
                 &PATH_GET_ACCOUNT,
                 &HttpRequestType::parse_get_account,
             ),
+            (
+                "POST",
+                &PATH_POST_ACCOUNTS,
+                &HttpRequestType::parse_post_accounts,
+            ),
+            (
+                "GET",
+                &PATH_GET_ADDRESS_TRANSACTIONS,
+                &HttpRequestType::parse_get_address_transactions,
+            ),
+            (
+                "GET",
+                &PATH_GET_ADDRESS_ASSETS,
+                &HttpRequestType::parse_get_address_assets,
+            ),
+            (
+                "GET",
+                &PATH_GET_NFT_HISTORY,
+                &HttpRequestType::parse_get_nft_history,
+            ),
+            (
+                "GET",
+                &PATH_GET_MEMPOOL_TRANSACTIONS,
+                &HttpRequestType::parse_get_mempool_transactions,
+            ),
+            (
+                "GET",
+                &PATH_GET_MEMPOOL_ADDRESS_TRANSACTIONS,
+                &HttpRequestType::parse_get_mempool_address_transactions,
+            ),
+            (
+                "GET",
+                &PATH_GET_MEMPOOL_DROPPED_TRANSACTIONS,
+                &HttpRequestType::parse_get_mempool_dropped_transactions,
+            ),
+            (
+                "GET",
+                &PATH_GET_MEMPOOL_NONCE_STATUS,
+                &HttpRequestType::parse_get_mempool_nonce_status,
+            ),
             (
                 "POST",
                 &PATH_GET_MAP_ENTRY,
                 &HttpRequestType::parse_get_map_entry,
             ),
+            (
+                "GET",
+                &PATH_GET_DATA_VAR,
+                &HttpRequestType::parse_get_data_var,
+            ),
             (
                 "GET",
                 &PATH_GET_TRANSFER_COST,
@@ -1520,6 +1826,11 @@ impl HttpRequestType {
                 &PATH_GET_IS_TRAIT_IMPLEMENTED,
                 &HttpRequestType::parse_get_is_trait_implemented,
             ),
+            (
+                "GET",
+                &PATH_GET_TRAIT_CONFORMANCE,
+                &HttpRequestType::parse_get_trait_conformance,
+            ),
             (
                 "GET",
                 &PATH_GET_CONTRACT_ABI,
@@ -1530,6 +1841,11 @@ impl HttpRequestType {
                 &PATH_POST_CALL_READ_ONLY,
                 &HttpRequestType::parse_call_read_only,
             ),
+            (
+                "POST",
+                &PATH_POST_CALL_READ_ONLY_BATCH,
+                &HttpRequestType::parse_call_read_only_batch,
+            ),
             (
                 "OPTIONS",
                 &PATH_OPTIONS_WILDCARD,
@@ -1545,6 +1861,37 @@ impl HttpRequestType {
                 &PATH_GET_ATTACHMENTS_INV,
                 &HttpRequestType::parse_get_attachments_inv,
             ),
+            ("GET", &PATH_GETHEALTH, &HttpRequestType::parse_gethealth),
+            (
+                "GET",
+                &PATH_ADMIN_LOG_LEVELS,
+                &HttpRequestType::parse_get_log_levels,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_LOG_LEVELS,
+                &HttpRequestType::parse_post_log_level,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_EVENT_REPLAY,
+                &HttpRequestType::parse_post_event_replay,
+            ),
+            (
+                "GET",
+                &PATH_ADMIN_PEERS,
+                &HttpRequestType::parse_get_peer_reputations,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_PEERS_BAN,
+                &HttpRequestType::parse_post_peer_ban,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_PEERS_UNBAN,
+                &HttpRequestType::parse_post_peer_unban,
+            ),
         ];
 
         // use url::Url to parse path and query string
@@ -1631,6 +1978,59 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_getpoxprojection<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetPoxProjection".to_string(),
+            ));
+        }
+
+        let amount = HttpRequestType::get_u64_query(query, "amount")?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetPoxProjection(
+            HttpRequestMetadata::from_preamble(preamble),
+            amount,
+            tip,
+        ))
+    }
+
+    fn parse_getrewardset<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetRewardSet".to_string(),
+            ));
+        }
+
+        let reward_cycle = captures
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to reward cycle group".to_string(),
+            ))?
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_e| {
+                net_error::DeserializeError("Failed to parse reward cycle".to_string())
+            })?;
+
+        Ok(HttpRequestType::GetRewardSet(
+            HttpRequestMetadata::from_preamble(preamble),
+            reward_cycle,
+        ))
+    }
+
     fn parse_getneighbors<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -1683,6 +2083,25 @@ impl HttpRequestType {
         !no_proof
     }
 
+    /// get the chain tip query argument (`tip`), which may also name the unconfirmed state
+    /// (`tip=unconfirmed`).  Take the first value we can parse.
+    fn get_tip_request_query(query: Option<&str>) -> TipRequest {
+        if let Some(query_string) = query {
+            for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+                if key != "tip" {
+                    continue;
+                }
+                if value == "unconfirmed" {
+                    return TipRequest::UseLatestUnconfirmedTip;
+                }
+                if let Ok(tip) = StacksBlockId::from_hex(&value) {
+                    return TipRequest::SpecificTip(tip);
+                }
+            }
+        }
+        TipRequest::UseLatestAnchoredTip
+    }
+
     /// get the chain tip optional query argument (`tip`)
     /// Take the first value we can parse.
     fn get_chain_tip_query(query: Option<&str>) -> Option<StacksBlockId> {
@@ -1733,78 +2152,377 @@ impl HttpRequestType {
         ))
     }
 
-    fn parse_get_map_entry<R: Read>(
+    fn parse_post_accounts<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
-        captures: &Captures,
+        _captures: &Captures,
         query: Option<&str>,
         fd: &mut R,
     ) -> Result<HttpRequestType, net_error> {
         let content_len = preamble.get_content_length();
-        if !(content_len > 0 && content_len < (BOUND_VALUE_SERIALIZATION_HEX)) {
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
             return Err(net_error::DeserializeError(format!(
-                "Invalid Http request: invalid body length for GetMapEntry ({})",
+                "Invalid Http request: invalid body length for GetAccounts ({})",
                 content_len
             )));
         }
 
         if preamble.content_type != Some(HttpContentType::JSON) {
             return Err(net_error::DeserializeError(
-                "Invalid content-type: expected application/json".into(),
+                "Invalid content-type: expected application/json".to_string(),
             ));
         }
 
-        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
-            net_error::DeserializeError("Failed to parse contract address".into())
+        let bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: PostAccountsRequestBody = serde_json::from_reader(bound_fd).map_err(|e| {
+            net_error::DeserializeError(format!("Failed to parse JSON body: {}", e))
         })?;
-        let contract_name = ContractName::try_from(captures["contract"].to_string())
-            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
-        let map_name = ClarityName::try_from(captures["map"].to_string())
-            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
 
-        let value_hex: String = serde_json::from_reader(fd)
-            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))?;
+        if body.principals.len() > MAX_BATCH_ACCOUNTS {
+            return Err(net_error::DeserializeError(format!(
+                "Too many principals: at most {} may be queried at once",
+                MAX_BATCH_ACCOUNTS
+            )));
+        }
 
-        let value = Value::try_deserialize_hex_untyped(&value_hex)
-            .map_err(|_e| net_error::DeserializeError("Failed to deserialize key value".into()))?;
+        let mut principals = Vec::with_capacity(body.principals.len());
+        for principal_str in body.principals.iter() {
+            let principal = PrincipalData::parse(principal_str).map_err(|_e| {
+                net_error::DeserializeError(format!(
+                    "Failed to parse account principal '{}'",
+                    principal_str
+                ))
+            })?;
+            principals.push(principal);
+        }
 
-        let with_proof = HttpRequestType::get_proof_query(query);
         let tip = HttpRequestType::get_chain_tip_query(query);
 
-        Ok(HttpRequestType::GetMapEntry(
+        Ok(HttpRequestType::GetAccounts(
             HttpRequestMetadata::from_preamble(preamble),
-            contract_addr,
-            contract_name,
-            map_name,
-            value,
+            principals,
             tip,
-            with_proof,
         ))
     }
 
-    fn parse_call_read_only<R: Read>(
-        protocol: &mut StacksHttp,
+    /// get an optional u64 query argument, failing if it is present but malformed
+    fn get_u64_query(query: Option<&str>, name: &str) -> Result<Option<u64>, net_error> {
+        if let Some(query_string) = query {
+            for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+                if key != name {
+                    continue;
+                }
+                let value = value.parse::<u64>().map_err(|_e| {
+                    net_error::DeserializeError(format!("Failed to parse '{}' query", name))
+                })?;
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    fn parse_get_address_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
         captures: &Captures,
         query: Option<&str>,
-        fd: &mut R,
+        _fd: &mut R,
     ) -> Result<HttpRequestType, net_error> {
-        let content_len = preamble.get_content_length();
-        if !(content_len > 0 && content_len < protocol.maximum_call_argument_size) {
-            return Err(net_error::DeserializeError(format!(
-                "Invalid Http request: invalid body length for CallReadOnly ({})",
-                content_len
-            )));
-        }
-
-        if preamble.content_type != Some(HttpContentType::JSON) {
+        if preamble.get_content_length() != 0 {
             return Err(net_error::DeserializeError(
-                "Invalid content-type: expected application/json".to_string(),
+                "Invalid Http request: expected 0-length body for GetAddressTransactions"
+                    .to_string(),
             ));
         }
 
-        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
-            net_error::DeserializeError("Failed to parse contract address".into())
+        let principal = PrincipalData::parse(&captures["principal"])
+            .map_err(|_e| net_error::DeserializeError("Failed to parse principal".into()))?;
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+        let (offset, limit) = HttpRequestType::get_page_query(query, ADDRESS_TXS_PAGE_SIZE)?;
+
+        Ok(HttpRequestType::GetAddressTransactions(
+            HttpRequestMetadata::from_preamble(preamble),
+            principal,
+            tip,
+            offset,
+            limit,
+        ))
+    }
+
+    fn parse_get_address_assets<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetAddressAssets".to_string(),
+            ));
+        }
+
+        let principal = PrincipalData::parse(&captures["principal"])
+            .map_err(|_e| net_error::DeserializeError("Failed to parse principal".into()))?;
+
+        Ok(HttpRequestType::GetAddressAssets(
+            HttpRequestMetadata::from_preamble(preamble),
+            principal,
+            HttpRequestType::get_chain_tip_query(query),
+        ))
+    }
+
+    fn parse_get_nft_history<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetNftHistory".to_string(),
+            ));
+        }
+
+        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
+            net_error::DeserializeError("Failed to parse contract address".into())
+        })?;
+        let contract_name = ContractName::try_from(captures["contract"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
+        let asset_name = ClarityName::try_from(captures["asset"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse asset name".into()))?;
+        let value = Value::try_deserialize_hex_untyped(&captures["value"])
+            .map_err(|_e| net_error::DeserializeError("Failed to parse token value".into()))?;
+
+        Ok(HttpRequestType::GetNftHistory(
+            HttpRequestMetadata::from_preamble(preamble),
+            contract_addr,
+            contract_name,
+            asset_name,
+            value,
+            HttpRequestType::get_chain_tip_query(query),
+        ))
+    }
+
+    /// get the `offset` and `limit` pagination query arguments.  `limit` defaults to, and may not
+    /// exceed, `page_size`.
+    fn get_page_query(query: Option<&str>, page_size: u64) -> Result<(u64, u64), net_error> {
+        let offset = HttpRequestType::get_u64_query(query, "offset")?.unwrap_or(0);
+        let limit = HttpRequestType::get_u64_query(query, "limit")?.unwrap_or(page_size);
+        if limit == 0 || limit > page_size {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid limit: must be between 1 and {}",
+                page_size
+            )));
+        }
+        Ok((offset, limit))
+    }
+
+    fn parse_get_mempool_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetMempoolTransactions"
+                    .to_string(),
+            ));
+        }
+
+        let (offset, limit) = HttpRequestType::get_page_query(query, MEMPOOL_TXS_PAGE_SIZE)?;
+
+        Ok(HttpRequestType::GetMempoolTransactions(
+            HttpRequestMetadata::from_preamble(preamble),
+            offset,
+            limit,
+        ))
+    }
+
+    fn parse_get_mempool_dropped_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetMempoolDroppedTransactions"
+                    .to_string(),
+            ));
+        }
+
+        let (offset, limit) = HttpRequestType::get_page_query(query, MEMPOOL_TXS_PAGE_SIZE)?;
+
+        Ok(HttpRequestType::GetMempoolDroppedTransactions(
+            HttpRequestMetadata::from_preamble(preamble),
+            offset,
+            limit,
+        ))
+    }
+
+    fn parse_get_mempool_address_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetMempoolAddressTransactions"
+                    .to_string(),
+            ));
+        }
+
+        let address = StacksAddress::from_string(&captures["address"])
+            .ok_or_else(|| net_error::DeserializeError("Failed to parse address".into()))?;
+        let (offset, limit) = HttpRequestType::get_page_query(query, MEMPOOL_TXS_PAGE_SIZE)?;
+
+        Ok(HttpRequestType::GetMempoolAddressTransactions(
+            HttpRequestMetadata::from_preamble(preamble),
+            address,
+            offset,
+            limit,
+        ))
+    }
+
+    fn parse_get_mempool_nonce_status<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetMempoolNonceStatus"
+                    .to_string(),
+            ));
+        }
+
+        let address = StacksAddress::from_string(&captures["address"])
+            .ok_or_else(|| net_error::DeserializeError("Failed to parse address".into()))?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetMempoolNonceStatus(
+            HttpRequestMetadata::from_preamble(preamble),
+            address,
+            tip,
+        ))
+    }
+
+    fn parse_get_map_entry<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < (BOUND_VALUE_SERIALIZATION_HEX)) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for GetMapEntry ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".into(),
+            ));
+        }
+
+        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
+            net_error::DeserializeError("Failed to parse contract address".into())
+        })?;
+        let contract_name = ContractName::try_from(captures["contract"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
+        let map_name = ClarityName::try_from(captures["map"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
+
+        let value_hex: String = serde_json::from_reader(fd)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))?;
+
+        let value = Value::try_deserialize_hex_untyped(&value_hex)
+            .map_err(|_e| net_error::DeserializeError("Failed to deserialize key value".into()))?;
+
+        let with_proof = HttpRequestType::get_proof_query(query);
+        let tip_req = HttpRequestType::get_tip_request_query(query);
+
+        Ok(HttpRequestType::GetMapEntry(
+            HttpRequestMetadata::from_preamble(preamble),
+            contract_addr,
+            contract_name,
+            map_name,
+            value,
+            tip_req,
+            with_proof,
+        ))
+    }
+
+    fn parse_get_data_var<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetDataVar".to_string(),
+            ));
+        }
+
+        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
+            net_error::DeserializeError("Failed to parse contract address".into())
+        })?;
+        let contract_name = ContractName::try_from(captures["contract"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
+        let var_name = ClarityName::try_from(captures["varname"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse data var name".into()))?;
+
+        let with_proof = HttpRequestType::get_proof_query(query);
+        let tip_req = HttpRequestType::get_tip_request_query(query);
+
+        Ok(HttpRequestType::GetDataVar(
+            HttpRequestMetadata::from_preamble(preamble),
+            contract_addr,
+            contract_name,
+            var_name,
+            tip_req,
+            with_proof,
+        ))
+    }
+
+    fn parse_call_read_only<R: Read>(
+        protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < protocol.maximum_call_argument_size) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for CallReadOnly ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
+            net_error::DeserializeError("Failed to parse contract address".into())
         })?;
         let contract_name = ContractName::try_from(captures["contract"].to_string())
             .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
@@ -1839,6 +2557,96 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_call_read_only_batch<R: Read>(
+        protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        // the whole batch is held to the same size limit as a single call
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < protocol.maximum_call_argument_size) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for CallReadOnlyBatch ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: CallReadOnlyBatchRequestBody =
+            serde_json::from_reader(bound_fd).map_err(|e| {
+                net_error::DeserializeError(format!("Failed to parse JSON body: {}", e))
+            })?;
+
+        if body.calls.is_empty() {
+            return Err(net_error::DeserializeError(
+                "No calls given: expected at least one".to_string(),
+            ));
+        }
+        if body.calls.len() > MAX_BATCH_READ_ONLY_CALLS {
+            return Err(net_error::DeserializeError(format!(
+                "Too many calls: at most {} may be evaluated at once",
+                MAX_BATCH_READ_ONLY_CALLS
+            )));
+        }
+
+        let mut calls = Vec::with_capacity(body.calls.len());
+        for (i, entry) in body.calls.into_iter().enumerate() {
+            let contract_addr =
+                StacksAddress::from_string(&entry.contract_address).ok_or_else(|| {
+                    net_error::DeserializeError(format!(
+                        "Failed to parse contract address of call {}",
+                        i
+                    ))
+                })?;
+            let contract_name = ContractName::try_from(entry.contract_name).map_err(|_e| {
+                net_error::DeserializeError(format!("Failed to parse contract name of call {}", i))
+            })?;
+            let function = ClarityName::try_from(entry.function_name).map_err(|_e| {
+                net_error::DeserializeError(format!("Failed to parse function name of call {}", i))
+            })?;
+            let sender = PrincipalData::parse(&entry.sender).map_err(|_e| {
+                net_error::DeserializeError(format!(
+                    "Failed to parse sender principal of call {}",
+                    i
+                ))
+            })?;
+            let args = entry
+                .arguments
+                .into_iter()
+                .map(|hex| Value::try_deserialize_hex_untyped(&hex).ok())
+                .collect::<Option<Vec<Value>>>()
+                .ok_or_else(|| {
+                    net_error::DeserializeError(format!(
+                        "Failed to deserialize argument value of call {}",
+                        i
+                    ))
+                })?;
+
+            calls.push(BatchReadOnlyCall {
+                contract_id: QualifiedContractIdentifier::new(contract_addr.into(), contract_name),
+                function,
+                sender,
+                args,
+            });
+        }
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::CallReadOnlyFunctionBatch(
+            HttpRequestMetadata::from_preamble(preamble),
+            calls,
+            tip,
+        ))
+    }
+
     fn parse_get_contract_arguments(
         preamble: &HttpRequestPreamble,
         captures: &Captures,
@@ -1905,6 +2713,48 @@ impl HttpRequestType {
             ));
         }
 
+        let (contract_addr, contract_name, trait_id) =
+            HttpRequestType::parse_trait_captures(captures)?;
+
+        Ok(HttpRequestType::GetIsTraitImplemented(
+            HttpRequestMetadata::from_preamble(preamble),
+            contract_addr,
+            contract_name,
+            trait_id,
+            tip,
+        ))
+    }
+
+    fn parse_get_trait_conformance<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let tip = HttpRequestType::get_chain_tip_query(query);
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetTraitConformance".to_string(),
+            ));
+        }
+
+        let (contract_addr, contract_name, trait_id) =
+            HttpRequestType::parse_trait_captures(captures)?;
+
+        Ok(HttpRequestType::GetTraitConformance(
+            HttpRequestMetadata::from_preamble(preamble),
+            contract_addr,
+            contract_name,
+            trait_id,
+            tip,
+        ))
+    }
+
+    /// Get the contract and the trait out of a /v2/traits path
+    fn parse_trait_captures(
+        captures: &Captures,
+    ) -> Result<(StacksAddress, ContractName, TraitIdentifier), net_error> {
         let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
             net_error::DeserializeError("Failed to parse contract address".into())
         })?;
@@ -1920,14 +2770,7 @@ impl HttpRequestType {
                 net_error::DeserializeError("Failed to parse trait contract name".into())
             })?;
         let trait_id = TraitIdentifier::new(trait_contract_addr, trait_contract_name, trait_name);
-
-        Ok(HttpRequestType::GetIsTraitImplemented(
-            HttpRequestMetadata::from_preamble(preamble),
-            contract_addr,
-            contract_name,
-            trait_id,
-            tip,
-        ))
+        Ok((contract_addr, contract_name, trait_id))
     }
 
     fn parse_getblock<R: Read>(
@@ -1959,6 +2802,35 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_get_block_header<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetBlockHeader".to_string(),
+            ));
+        }
+
+        let block_hash_str = captures
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to block hash group".to_string(),
+            ))?
+            .as_str();
+
+        let block_hash = StacksBlockId::from_hex(block_hash_str)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse block hash".to_string()))?;
+
+        Ok(HttpRequestType::GetBlockHeader(
+            HttpRequestMetadata::from_preamble(preamble),
+            block_hash,
+        ))
+    }
+
     fn parse_getmicroblocks_indexed<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2020,6 +2892,146 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_block_height(
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        request_name: &str,
+    ) -> Result<u64, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: expected 0-length body for {}",
+                request_name
+            )));
+        }
+
+        captures
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to block height group".to_string(),
+            ))?
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_e| net_error::DeserializeError("Failed to parse block height".to_string()))
+    }
+
+    fn parse_getblock_by_height<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let height = HttpRequestType::parse_block_height(preamble, captures, "GetBlockByHeight")?;
+        Ok(HttpRequestType::GetBlockByHeight(
+            HttpRequestMetadata::from_preamble(preamble),
+            height,
+        ))
+    }
+
+    fn parse_getmicroblocks_by_height<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let height =
+            HttpRequestType::parse_block_height(preamble, captures, "GetMicroblocksByHeight")?;
+        Ok(HttpRequestType::GetMicroblocksByHeight(
+            HttpRequestMetadata::from_preamble(preamble),
+            height,
+        ))
+    }
+
+    fn parse_get_sortition_by_height<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let height = HttpRequestType::parse_block_height(preamble, captures, "GetSortition")?;
+        Ok(HttpRequestType::GetSortition(
+            HttpRequestMetadata::from_preamble(preamble),
+            BurnBlockSelector::Height(height),
+        ))
+    }
+
+    fn parse_get_bns_name<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetBNSName".to_string(),
+            ));
+        }
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+        Ok(HttpRequestType::GetBNSName(
+            HttpRequestMetadata::from_preamble(preamble),
+            captures["name"].to_string(),
+            captures["namespace"].to_string(),
+            tip,
+        ))
+    }
+
+    fn parse_get_sortition_by_hash<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetSortition".to_string(),
+            ));
+        }
+
+        let burn_hash_str = captures
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to burn block hash group".to_string(),
+            ))?
+            .as_str();
+
+        let burn_hash = BurnchainHeaderHash::from_hex(burn_hash_str).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse burn block hash".to_string())
+        })?;
+
+        Ok(HttpRequestType::GetSortition(
+            HttpRequestMetadata::from_preamble(preamble),
+            BurnBlockSelector::Hash(burn_hash),
+        ))
+    }
+
+    fn parse_get_sortition_stats<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetSortitionStats".to_string(),
+            ));
+        }
+
+        let start_height = HttpRequestType::get_u64_query(query, "start_height")?;
+        let end_height = HttpRequestType::get_u64_query(query, "end_height")?;
+
+        Ok(HttpRequestType::GetSortitionStats(
+            HttpRequestMetadata::from_preamble(preamble),
+            start_height,
+            end_height,
+        ))
+    }
+
     fn parse_getmicroblocks_unconfirmed<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2099,6 +3111,35 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_gettransaction_status<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetTransactionStatus".to_string(),
+            ));
+        }
+
+        let txid_hex = regex
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to txid group".to_string(),
+            ))?
+            .as_str();
+
+        let txid = Txid::from_hex(&txid_hex)
+            .map_err(|_e| net_error::DeserializeError("Failed to decode txid hex".to_string()))?;
+
+        Ok(HttpRequestType::GetTransactionStatus(
+            HttpRequestMetadata::from_preamble(preamble),
+            txid,
+        ))
+    }
+
     fn parse_post_fee_rate_estimate<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2249,10 +3290,66 @@ impl HttpRequestType {
             }
         };
 
-        Ok(HttpRequestType::PostTransaction(
+        Ok(HttpRequestType::PostTransaction(
+            HttpRequestMetadata::from_preamble(preamble),
+            tx,
+            attachment,
+        ))
+    }
+
+    fn parse_post_transaction_simulation<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for SimulateTransaction ({})",
+                content_len
+            )));
+        }
+
+        let mut bound_fd = BoundReader::from_reader(fd, content_len as u64);
+
+        let tx_bytes = match preamble.content_type {
+            Some(HttpContentType::Bytes) => {
+                let mut tx_bytes = vec![];
+                bound_fd
+                    .read_to_end(&mut tx_bytes)
+                    .map_err(net_error::ReadError)?;
+                tx_bytes
+            }
+            Some(HttpContentType::JSON) => {
+                let body: PostTransactionRequestBody = serde_json::from_reader(bound_fd)
+                    .map_err(|_e| net_error::DeserializeError("Failed to parse body".into()))?;
+                hex_bytes(&body.tx)
+                    .map_err(|_e| net_error::DeserializeError("Failed to parse tx".into()))?
+            }
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Wrong Content-Type for transaction; expected application/json or application/octet-stream".to_string(),
+                ));
+            }
+        };
+
+        let tx = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..]).map_err(|e| {
+            if let codec_error::DeserializeError(msg) = e {
+                net_error::ClientError(ClientError::Message(format!(
+                    "Failed to deserialize simulated transaction: {}",
+                    msg
+                )))
+            } else {
+                e.into()
+            }
+        })?;
+
+        Ok(HttpRequestType::SimulateTransaction(
             HttpRequestMetadata::from_preamble(preamble),
             tx,
-            attachment,
+            HttpRequestType::get_chain_tip_query(query),
         ))
     }
 
@@ -2459,6 +3556,206 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_gethealth<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetHealth".to_string(),
+            ));
+        }
+
+        Ok(HttpRequestType::GetHealth(
+            HttpRequestMetadata::from_preamble(preamble),
+        ))
+    }
+
+    fn parse_get_log_levels<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetLogLevels".to_string(),
+            ));
+        }
+
+        Ok(HttpRequestType::GetLogLevels(
+            HttpRequestMetadata::from_preamble(preamble),
+        ))
+    }
+
+    fn parse_post_log_level<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for PostLogLevel ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: PostLogLevelRequestBody = serde_json::from_reader(bound_fd).map_err(|e| {
+            net_error::DeserializeError(format!("Failed to parse JSON body: {}", e))
+        })?;
+
+        if body.module.len() == 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected a module path".to_string(),
+            ));
+        }
+
+        if let Some(ref level) = body.level {
+            if slog::Level::from_str(level).is_err() {
+                return Err(net_error::DeserializeError(format!(
+                    "Invalid Http request: unrecognized log level '{}'",
+                    level
+                )));
+            }
+        }
+
+        Ok(HttpRequestType::PostLogLevel(
+            HttpRequestMetadata::from_preamble(preamble),
+            body,
+        ))
+    }
+
+    fn parse_post_event_replay<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for PostEventReplay ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: PostEventReplayRequestBody = serde_json::from_reader(bound_fd).map_err(|e| {
+            net_error::DeserializeError(format!("Failed to parse JSON body: {}", e))
+        })?;
+
+        if body.endpoint.len() == 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected an observer endpoint".to_string(),
+            ));
+        }
+
+        Ok(HttpRequestType::PostEventReplay(
+            HttpRequestMetadata::from_preamble(preamble),
+            body,
+        ))
+    }
+
+    fn parse_get_peer_reputations<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetPeerReputations".to_string(),
+            ));
+        }
+
+        Ok(HttpRequestType::GetPeerReputations(
+            HttpRequestMetadata::from_preamble(preamble),
+        ))
+    }
+
+    fn parse_peer_ban_body<R: Read>(
+        preamble: &HttpRequestPreamble,
+        fd: &mut R,
+    ) -> Result<PostPeerBanRequestBody, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for peer ban ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: PostPeerBanRequestBody = serde_json::from_reader(bound_fd).map_err(|e| {
+            net_error::DeserializeError(format!("Failed to parse JSON body: {}", e))
+        })?;
+
+        if body.addr.parse::<SocketAddr>().is_err() {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: expected an IP address and port, got '{}'",
+                &body.addr
+            )));
+        }
+
+        Ok(body)
+    }
+
+    fn parse_post_peer_ban<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let body = HttpRequestType::parse_peer_ban_body(preamble, fd)?;
+        Ok(HttpRequestType::PostPeerBan(
+            HttpRequestMetadata::from_preamble(preamble),
+            body,
+        ))
+    }
+
+    fn parse_post_peer_unban<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let body = HttpRequestType::parse_peer_ban_body(preamble, fd)?;
+        Ok(HttpRequestType::PostPeerUnban(
+            HttpRequestMetadata::from_preamble(preamble),
+            body,
+        ))
+    }
+
     fn parse_options_preflight<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2476,26 +3773,54 @@ impl HttpRequestType {
         match *self {
             HttpRequestType::GetInfo(ref md) => md,
             HttpRequestType::GetPoxInfo(ref md, _) => md,
+            HttpRequestType::GetPoxProjection(ref md, ..) => md,
+            HttpRequestType::GetRewardSet(ref md, _) => md,
             HttpRequestType::GetNeighbors(ref md) => md,
             HttpRequestType::GetBlock(ref md, _) => md,
+            HttpRequestType::GetBlockHeader(ref md, _) => md,
             HttpRequestType::GetMicroblocksIndexed(ref md, _) => md,
             HttpRequestType::GetMicroblocksConfirmed(ref md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref md, _, _) => md,
+            HttpRequestType::GetBlockByHeight(ref md, _) => md,
+            HttpRequestType::GetMicroblocksByHeight(ref md, _) => md,
+            HttpRequestType::GetSortition(ref md, _) => md,
+            HttpRequestType::GetSortitionStats(ref md, ..) => md,
+            HttpRequestType::GetBNSName(ref md, ..) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref md, _) => md,
+            HttpRequestType::GetTransactionStatus(ref md, _) => md,
             HttpRequestType::PostTransaction(ref md, _, _) => md,
+            HttpRequestType::SimulateTransaction(ref md, ..) => md,
             HttpRequestType::PostBlock(ref md, ..) => md,
             HttpRequestType::PostMicroblock(ref md, ..) => md,
             HttpRequestType::GetAccount(ref md, ..) => md,
+            HttpRequestType::GetAccounts(ref md, ..) => md,
+            HttpRequestType::GetAddressTransactions(ref md, ..) => md,
+            HttpRequestType::GetAddressAssets(ref md, ..) => md,
+            HttpRequestType::GetNftHistory(ref md, ..) => md,
+            HttpRequestType::GetMempoolTransactions(ref md, ..) => md,
+            HttpRequestType::GetMempoolAddressTransactions(ref md, ..) => md,
+            HttpRequestType::GetMempoolDroppedTransactions(ref md, ..) => md,
+            HttpRequestType::GetMempoolNonceStatus(ref md, ..) => md,
             HttpRequestType::GetMapEntry(ref md, ..) => md,
+            HttpRequestType::GetDataVar(ref md, ..) => md,
             HttpRequestType::GetTransferCost(ref md) => md,
             HttpRequestType::GetContractABI(ref md, ..) => md,
             HttpRequestType::GetContractSrc(ref md, ..) => md,
             HttpRequestType::GetIsTraitImplemented(ref md, ..) => md,
+            HttpRequestType::GetTraitConformance(ref md, ..) => md,
             HttpRequestType::CallReadOnlyFunction(ref md, ..) => md,
+            HttpRequestType::CallReadOnlyFunctionBatch(ref md, ..) => md,
             HttpRequestType::OptionsPreflight(ref md, ..) => md,
             HttpRequestType::GetAttachmentsInv(ref md, ..) => md,
             HttpRequestType::GetAttachment(ref md, ..) => md,
             HttpRequestType::FeeRateEstimate(ref md, _, _) => md,
+            HttpRequestType::GetHealth(ref md) => md,
+            HttpRequestType::GetLogLevels(ref md) => md,
+            HttpRequestType::PostLogLevel(ref md, _) => md,
+            HttpRequestType::PostEventReplay(ref md, _) => md,
+            HttpRequestType::GetPeerReputations(ref md) => md,
+            HttpRequestType::PostPeerBan(ref md, _) => md,
+            HttpRequestType::PostPeerUnban(ref md, _) => md,
             HttpRequestType::ClientError(ref md, ..) => md,
         }
     }
@@ -2504,30 +3829,97 @@ impl HttpRequestType {
         match *self {
             HttpRequestType::GetInfo(ref mut md) => md,
             HttpRequestType::GetPoxInfo(ref mut md, _) => md,
+            HttpRequestType::GetPoxProjection(ref mut md, ..) => md,
+            HttpRequestType::GetRewardSet(ref mut md, _) => md,
             HttpRequestType::GetNeighbors(ref mut md) => md,
             HttpRequestType::GetBlock(ref mut md, _) => md,
+            HttpRequestType::GetBlockHeader(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksIndexed(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksConfirmed(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref mut md, _, _) => md,
+            HttpRequestType::GetBlockByHeight(ref mut md, _) => md,
+            HttpRequestType::GetMicroblocksByHeight(ref mut md, _) => md,
+            HttpRequestType::GetSortition(ref mut md, _) => md,
+            HttpRequestType::GetSortitionStats(ref mut md, ..) => md,
+            HttpRequestType::GetBNSName(ref mut md, ..) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref mut md, _) => md,
+            HttpRequestType::GetTransactionStatus(ref mut md, _) => md,
             HttpRequestType::PostTransaction(ref mut md, _, _) => md,
+            HttpRequestType::SimulateTransaction(ref mut md, ..) => md,
             HttpRequestType::PostBlock(ref mut md, ..) => md,
             HttpRequestType::PostMicroblock(ref mut md, ..) => md,
             HttpRequestType::GetAccount(ref mut md, ..) => md,
+            HttpRequestType::GetAccounts(ref mut md, ..) => md,
+            HttpRequestType::GetAddressTransactions(ref mut md, ..) => md,
+            HttpRequestType::GetAddressAssets(ref mut md, ..) => md,
+            HttpRequestType::GetNftHistory(ref mut md, ..) => md,
+            HttpRequestType::GetMempoolTransactions(ref mut md, ..) => md,
+            HttpRequestType::GetMempoolAddressTransactions(ref mut md, ..) => md,
+            HttpRequestType::GetMempoolDroppedTransactions(ref mut md, ..) => md,
+            HttpRequestType::GetMempoolNonceStatus(ref mut md, ..) => md,
             HttpRequestType::GetMapEntry(ref mut md, ..) => md,
+            HttpRequestType::GetDataVar(ref mut md, ..) => md,
             HttpRequestType::GetTransferCost(ref mut md) => md,
             HttpRequestType::GetContractABI(ref mut md, ..) => md,
             HttpRequestType::GetContractSrc(ref mut md, ..) => md,
             HttpRequestType::GetIsTraitImplemented(ref mut md, ..) => md,
+            HttpRequestType::GetTraitConformance(ref mut md, ..) => md,
             HttpRequestType::CallReadOnlyFunction(ref mut md, ..) => md,
+            HttpRequestType::CallReadOnlyFunctionBatch(ref mut md, ..) => md,
             HttpRequestType::OptionsPreflight(ref mut md, ..) => md,
             HttpRequestType::GetAttachmentsInv(ref mut md, ..) => md,
             HttpRequestType::GetAttachment(ref mut md, ..) => md,
             HttpRequestType::FeeRateEstimate(ref mut md, _, _) => md,
+            HttpRequestType::GetHealth(ref mut md) => md,
+            HttpRequestType::GetLogLevels(ref mut md) => md,
+            HttpRequestType::PostLogLevel(ref mut md, _) => md,
+            HttpRequestType::PostEventReplay(ref mut md, _) => md,
+            HttpRequestType::GetPeerReputations(ref mut md) => md,
+            HttpRequestType::PostPeerBan(ref mut md, _) => md,
+            HttpRequestType::PostPeerUnban(ref mut md, _) => md,
             HttpRequestType::ClientError(ref mut md, ..) => md,
         }
     }
 
+    /// What a client must be allowed to do in order to make this request
+    pub fn required_role(&self) -> RPCRole {
+        match *self {
+            HttpRequestType::GetMempoolTransactions(..)
+            | HttpRequestType::GetMempoolDroppedTransactions(..)
+            | HttpRequestType::GetLogLevels(..)
+            | HttpRequestType::GetPeerReputations(..) => RPCRole::Monitor,
+            HttpRequestType::PostLogLevel(..)
+            | HttpRequestType::PostEventReplay(..)
+            | HttpRequestType::PostPeerBan(..)
+            | HttpRequestType::PostPeerUnban(..) => RPCRole::Admin,
+            _ => RPCRole::Public,
+        }
+    }
+
+    /// Which rate limit this request counts against
+    pub fn endpoint_class(&self) -> RPCEndpointClass {
+        match *self {
+            HttpRequestType::PostTransaction(..)
+            | HttpRequestType::PostBlock(..)
+            | HttpRequestType::PostMicroblock(..) => RPCEndpointClass::Submit,
+            HttpRequestType::CallReadOnlyFunction(..)
+            | HttpRequestType::CallReadOnlyFunctionBatch(..)
+            | HttpRequestType::SimulateTransaction(..)
+            | HttpRequestType::FeeRateEstimate(..)
+            | HttpRequestType::GetAccounts(..)
+            | HttpRequestType::GetPoxProjection(..)
+            | HttpRequestType::GetRewardSet(..)
+            | HttpRequestType::GetSortitionStats(..)
+            | HttpRequestType::GetAddressTransactions(..)
+            | HttpRequestType::GetAddressAssets(..)
+            | HttpRequestType::GetNftHistory(..)
+            | HttpRequestType::GetMempoolTransactions(..)
+            | HttpRequestType::GetMempoolAddressTransactions(..)
+            | HttpRequestType::GetMempoolDroppedTransactions(..) => RPCEndpointClass::Compute,
+            _ => RPCEndpointClass::Read,
+        }
+    }
+
     fn make_query_string(tip_opt: Option<&StacksBlockId>, with_proof: bool) -> String {
         if let Some(tip) = tip_opt {
             format!("?tip={}{}", tip, if with_proof { "" } else { "&proof=0" })
@@ -2538,6 +3930,21 @@ impl HttpRequestType {
         }
     }
 
+    fn make_tip_request_query_string(tip_req: &TipRequest, with_proof: bool) -> String {
+        match tip_req {
+            TipRequest::UseLatestAnchoredTip => {
+                HttpRequestType::make_query_string(None, with_proof)
+            }
+            TipRequest::UseLatestUnconfirmedTip => format!(
+                "?tip=unconfirmed{}",
+                if with_proof { "" } else { "&proof=0" }
+            ),
+            TipRequest::SpecificTip(tip) => {
+                HttpRequestType::make_query_string(Some(tip), with_proof)
+            }
+        }
+    }
+
     pub fn request_path(&self) -> String {
         match self {
             HttpRequestType::GetInfo(_md) => "/v2/info".to_string(),
@@ -2545,10 +3952,30 @@ impl HttpRequestType {
                 "/v2/pox{}",
                 HttpRequestType::make_query_string(tip_opt.as_ref(), true)
             ),
+            HttpRequestType::GetPoxProjection(_md, amount_opt, tip_opt) => {
+                let mut query = vec![];
+                if let Some(amount) = amount_opt {
+                    query.push(format!("amount={}", amount));
+                }
+                if let Some(tip) = tip_opt {
+                    query.push(format!("tip={}", tip));
+                }
+                if query.len() > 0 {
+                    format!("/v2/pox/projection?{}", query.join("&"))
+                } else {
+                    "/v2/pox/projection".to_string()
+                }
+            }
+            HttpRequestType::GetRewardSet(_md, reward_cycle) => {
+                format!("/v2/pox/reward_set/{}", reward_cycle)
+            }
             HttpRequestType::GetNeighbors(_md) => "/v2/neighbors".to_string(),
             HttpRequestType::GetBlock(_md, block_hash) => {
                 format!("/v2/blocks/{}", block_hash.to_hex())
             }
+            HttpRequestType::GetBlockHeader(_md, block_hash) => {
+                format!("/v2/headers/{}", block_hash.to_hex())
+            }
             HttpRequestType::GetMicroblocksIndexed(_md, block_hash) => {
                 format!("/v2/microblocks/{}", block_hash.to_hex())
             }
@@ -2560,10 +3987,49 @@ impl HttpRequestType {
                 block_hash.to_hex(),
                 min_seq
             ),
+            HttpRequestType::GetBlockByHeight(_md, height) => {
+                format!("/v2/blocks/height/{}", height)
+            }
+            HttpRequestType::GetMicroblocksByHeight(_md, height) => {
+                format!("/v2/microblocks/height/{}", height)
+            }
+            HttpRequestType::GetSortition(_md, BurnBlockSelector::Height(height)) => {
+                format!("/v2/sortitions/height/{}", height)
+            }
+            HttpRequestType::GetSortition(_md, BurnBlockSelector::Hash(burn_hash)) => {
+                format!("/v2/sortitions/hash/{}", burn_hash.to_hex())
+            }
+            HttpRequestType::GetSortitionStats(_md, start_height_opt, end_height_opt) => {
+                let mut query = vec![];
+                if let Some(start_height) = start_height_opt {
+                    query.push(format!("start_height={}", start_height));
+                }
+                if let Some(end_height) = end_height_opt {
+                    query.push(format!("end_height={}", end_height));
+                }
+                if query.len() > 0 {
+                    format!("/v2/sortitions/stats?{}", query.join("&"))
+                } else {
+                    "/v2/sortitions/stats".to_string()
+                }
+            }
+            HttpRequestType::GetBNSName(_md, name, namespace, tip_opt) => format!(
+                "/v2/bns/names/{}.{}{}",
+                name,
+                namespace,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::GetTransactionUnconfirmed(_md, txid) => {
                 format!("/v2/transactions/unconfirmed/{}", txid)
             }
+            HttpRequestType::GetTransactionStatus(_md, txid) => {
+                format!("/v2/transactions/status/{}", txid)
+            }
             HttpRequestType::PostTransaction(_md, ..) => "/v2/transactions".to_string(),
+            HttpRequestType::SimulateTransaction(_md, _, tip_opt) => format!(
+                "/v2/transactions/simulate{}",
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::PostBlock(_md, ch, ..) => format!("/v2/blocks/upload/{}", &ch),
             HttpRequestType::PostMicroblock(_md, _, tip_opt) => format!(
                 "/v2/microblocks{}",
@@ -2574,20 +4040,87 @@ impl HttpRequestType {
                 &principal.to_string(),
                 HttpRequestType::make_query_string(tip_opt.as_ref(), *with_proof)
             ),
+            HttpRequestType::GetAccounts(_md, _, tip_opt) => format!(
+                "/v2/accounts{}",
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetAddressTransactions(_md, principal, tip_opt, offset, limit) => {
+                format!(
+                    "/v2/addresses/{}/transactions?offset={}&limit={}{}",
+                    &principal.to_string(),
+                    offset,
+                    limit,
+                    match tip_opt {
+                        Some(tip) => format!("&tip={}", tip),
+                        None => "".to_string(),
+                    }
+                )
+            }
+            HttpRequestType::GetAddressAssets(_md, principal, tip_opt) => format!(
+                "/v2/addresses/{}/assets{}",
+                &principal.to_string(),
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetNftHistory(
+                _md,
+                contract_addr,
+                contract_name,
+                asset_name,
+                value,
+                tip_opt,
+            ) => format!(
+                "/v2/nft_history/{}/{}/{}/{}{}",
+                &contract_addr.to_string(),
+                contract_name.as_str(),
+                asset_name.as_str(),
+                value.serialize(),
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetMempoolTransactions(_md, offset, limit) => {
+                format!("/v2/mempool/transactions?offset={}&limit={}", offset, limit)
+            }
+            HttpRequestType::GetMempoolAddressTransactions(_md, address, offset, limit) => {
+                format!(
+                    "/v2/mempool/addresses/{}/transactions?offset={}&limit={}",
+                    address, offset, limit
+                )
+            }
+            HttpRequestType::GetMempoolDroppedTransactions(_md, offset, limit) => {
+                format!("/v2/mempool/dropped?offset={}&limit={}", offset, limit)
+            }
+            HttpRequestType::GetMempoolNonceStatus(_md, address, tip_opt) => format!(
+                "/v2/mempool/addresses/{}/nonces{}",
+                address,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::GetMapEntry(
                 _md,
                 contract_addr,
                 contract_name,
                 map_name,
                 _key,
-                tip_opt,
+                tip_req,
                 with_proof,
             ) => format!(
                 "/v2/map_entry/{}/{}/{}{}",
                 &contract_addr.to_string(),
                 contract_name.as_str(),
                 map_name.as_str(),
-                HttpRequestType::make_query_string(tip_opt.as_ref(), *with_proof)
+                HttpRequestType::make_tip_request_query_string(tip_req, *with_proof)
+            ),
+            HttpRequestType::GetDataVar(
+                _md,
+                contract_addr,
+                contract_name,
+                var_name,
+                tip_req,
+                with_proof,
+            ) => format!(
+                "/v2/data_var/{}/{}/{}{}",
+                &contract_addr.to_string(),
+                contract_name.as_str(),
+                var_name.as_str(),
+                HttpRequestType::make_tip_request_query_string(tip_req, *with_proof)
             ),
             HttpRequestType::GetTransferCost(_md) => "/v2/fees/transfer".into(),
             HttpRequestType::GetContractABI(_, contract_addr, contract_name, tip_opt) => format!(
@@ -2623,6 +4156,21 @@ impl HttpRequestType {
                 trait_id.contract_identifier.name.as_str(),
                 HttpRequestType::make_query_string(tip_opt.as_ref(), true)
             ),
+            HttpRequestType::GetTraitConformance(
+                _,
+                contract_addr,
+                contract_name,
+                trait_id,
+                tip_opt,
+            ) => format!(
+                "/v2/traits/conformance/{}/{}/{}/{}/{}{}",
+                contract_addr,
+                contract_name.as_str(),
+                StacksAddress::from(trait_id.clone().contract_identifier.issuer),
+                trait_id.contract_identifier.name.as_str(),
+                trait_id.name.as_str(),
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::CallReadOnlyFunction(
                 _,
                 contract_addr,
@@ -2638,6 +4186,10 @@ impl HttpRequestType {
                 func_name.as_str(),
                 HttpRequestType::make_query_string(tip_opt.as_ref(), true)
             ),
+            HttpRequestType::CallReadOnlyFunctionBatch(_md, _, tip_opt) => format!(
+                "/v2/contracts/call-read-batch{}",
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::OptionsPreflight(_md, path) => path.to_string(),
             HttpRequestType::GetAttachmentsInv(_md, index_block_hash, pages_indexes) => {
                 let pages_query = match pages_indexes.len() {
@@ -2658,6 +4210,14 @@ impl HttpRequestType {
                 format!("/v2/attachments/{}", to_hex(&content_hash.0[..]))
             }
             HttpRequestType::FeeRateEstimate(_, _, _) => self.get_path().to_string(),
+            HttpRequestType::GetHealth(_) => self.get_path().to_string(),
+            HttpRequestType::GetLogLevels(_) | HttpRequestType::PostLogLevel(..) => {
+                self.get_path().to_string()
+            }
+            HttpRequestType::PostEventReplay(..) => self.get_path().to_string(),
+            HttpRequestType::GetPeerReputations(_)
+            | HttpRequestType::PostPeerBan(..)
+            | HttpRequestType::PostPeerUnban(..) => self.get_path().to_string(),
             HttpRequestType::ClientError(_md, e) => match e {
                 ClientError::NotFound(path) => path.to_string(),
                 _ => "error path unknown".into(),
@@ -2669,19 +4229,47 @@ impl HttpRequestType {
         match self {
             HttpRequestType::GetInfo(..) => "/v2/info",
             HttpRequestType::GetPoxInfo(..) => "/v2/pox",
+            HttpRequestType::GetPoxProjection(..) => "/v2/pox/projection",
+            HttpRequestType::GetRewardSet(..) => "/v2/pox/reward_set/:reward_cycle",
             HttpRequestType::GetNeighbors(..) => "/v2/neighbors",
             HttpRequestType::GetBlock(..) => "/v2/blocks/:hash",
+            HttpRequestType::GetBlockHeader(..) => "/v2/headers/:hash",
             HttpRequestType::GetMicroblocksIndexed(..) => "/v2/microblocks/:hash",
             HttpRequestType::GetMicroblocksConfirmed(..) => "/v2/microblocks/confirmed/:hash",
             HttpRequestType::GetMicroblocksUnconfirmed(..) => {
                 "/v2/microblocks/unconfirmed/:hash/:seq"
             }
+            HttpRequestType::GetBlockByHeight(..) => "/v2/blocks/height/:height",
+            HttpRequestType::GetMicroblocksByHeight(..) => "/v2/microblocks/height/:height",
+            HttpRequestType::GetSortition(_, BurnBlockSelector::Height(_)) => {
+                "/v2/sortitions/height/:height"
+            }
+            HttpRequestType::GetSortition(_, BurnBlockSelector::Hash(_)) => {
+                "/v2/sortitions/hash/:hash"
+            }
+            HttpRequestType::GetSortitionStats(..) => "/v2/sortitions/stats",
+            HttpRequestType::GetBNSName(..) => "/v2/bns/names/:name.:namespace",
             HttpRequestType::GetTransactionUnconfirmed(..) => "/v2/transactions/unconfirmed/:txid",
+            HttpRequestType::GetTransactionStatus(..) => "/v2/transactions/status/:txid",
             HttpRequestType::PostTransaction(..) => "/v2/transactions",
+            HttpRequestType::SimulateTransaction(..) => "/v2/transactions/simulate",
             HttpRequestType::PostBlock(..) => "/v2/blocks/upload/:block",
             HttpRequestType::PostMicroblock(..) => "/v2/microblocks",
             HttpRequestType::GetAccount(..) => "/v2/accounts/:principal",
+            HttpRequestType::GetAccounts(..) => "/v2/accounts",
+            HttpRequestType::GetAddressTransactions(..) => "/v2/addresses/:principal/transactions",
+            HttpRequestType::GetAddressAssets(..) => "/v2/addresses/:principal/assets",
+            HttpRequestType::GetNftHistory(..) => {
+                "/v2/nft_history/:principal/:contract_name/:asset_name/:value"
+            }
+            HttpRequestType::GetMempoolTransactions(..) => "/v2/mempool/transactions",
+            HttpRequestType::GetMempoolAddressTransactions(..) => {
+                "/v2/mempool/addresses/:address/transactions"
+            }
+            HttpRequestType::GetMempoolDroppedTransactions(..) => "/v2/mempool/dropped",
+            HttpRequestType::GetMempoolNonceStatus(..) => "/v2/mempool/addresses/:address/nonces",
             HttpRequestType::GetMapEntry(..) => "/v2/map_entry/:principal/:contract_name/:map_name",
+            HttpRequestType::GetDataVar(..) => "/v2/data_var/:principal/:contract_name/:var_name",
             HttpRequestType::GetTransferCost(..) => "/v2/fees/transfer",
             HttpRequestType::GetContractABI(..) => {
                 "/v2/contracts/interface/:principal/:contract_name"
@@ -2690,10 +4278,22 @@ impl HttpRequestType {
             HttpRequestType::CallReadOnlyFunction(..) => {
                 "/v2/contracts/call-read/:principal/:contract_name/:func_name"
             }
+            HttpRequestType::CallReadOnlyFunctionBatch(..) => "/v2/contracts/call-read-batch",
             HttpRequestType::GetAttachmentsInv(..) => "/v2/attachments/inv",
             HttpRequestType::GetAttachment(..) => "/v2/attachments/:hash",
             HttpRequestType::GetIsTraitImplemented(..) => "/v2/traits/:principal/:contract_name",
+            HttpRequestType::GetTraitConformance(..) => {
+                "/v2/traits/conformance/:principal/:contract_name"
+            }
             HttpRequestType::FeeRateEstimate(_, _, _) => "/v2/fees/transaction",
+            HttpRequestType::GetHealth(..) => "/v2/health",
+            HttpRequestType::GetLogLevels(..) | HttpRequestType::PostLogLevel(..) => {
+                "/v2/admin/log_levels"
+            }
+            HttpRequestType::PostEventReplay(..) => "/v2/admin/events/replay",
+            HttpRequestType::GetPeerReputations(..) => "/v2/admin/peers",
+            HttpRequestType::PostPeerBan(..) => "/v2/admin/peers/ban",
+            HttpRequestType::PostPeerUnban(..) => "/v2/admin/peers/unban",
             HttpRequestType::OptionsPreflight(..) | HttpRequestType::ClientError(..) => "/",
         }
     }
@@ -2744,6 +4344,23 @@ impl HttpRequestType {
                 fd.write_all(&request_body_bytes)
                     .map_err(net_error::WriteError)?;
             }
+            HttpRequestType::SimulateTransaction(md, tx, ..) => {
+                let mut tx_bytes = vec![];
+                write_next(&mut tx_bytes, tx)?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(tx_bytes.len() as u32),
+                    Some(&HttpContentType::Bytes),
+                    empty_headers,
+                )?;
+                fd.write_all(&tx_bytes).map_err(net_error::WriteError)?;
+            }
             HttpRequestType::PostBlock(md, _ch, block) => {
                 let mut block_bytes = vec![];
                 write_next(&mut block_bytes, block)?;
@@ -2849,6 +4466,146 @@ impl HttpRequestType {
                 fd.write_all(&request_body_bytes)
                     .map_err(net_error::WriteError)?;
             }
+            HttpRequestType::CallReadOnlyFunctionBatch(md, calls, ..) => {
+                let mut entries = Vec::with_capacity(calls.len());
+                for call in calls.iter() {
+                    let mut args = vec![];
+                    for arg in call.args.iter() {
+                        let mut arg_bytes = vec![];
+                        arg.serialize_write(&mut arg_bytes)
+                            .map_err(net_error::WriteError)?;
+                        args.push(to_hex(&arg_bytes));
+                    }
+                    entries.push(CallReadOnlyBatchRequestEntry {
+                        contract_address: StacksAddress::from(call.contract_id.issuer.clone())
+                            .to_string(),
+                        contract_name: call.contract_id.name.to_string(),
+                        function_name: call.function.to_string(),
+                        sender: call.sender.to_string(),
+                        arguments: args,
+                    });
+                }
+
+                let request_body = CallReadOnlyBatchRequestBody { calls: entries };
+
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, &request_body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize read-only call batch to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::GetAccounts(md, principals, ..) => {
+                let request_body = PostAccountsRequestBody {
+                    principals: principals.iter().map(|p| p.to_string()).collect(),
+                };
+
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, &request_body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize accounts request to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::PostLogLevel(md, body) => {
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize log level request to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::PostEventReplay(md, body) => {
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize event replay request to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::PostPeerBan(md, body) | HttpRequestType::PostPeerUnban(md, body) => {
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize peer ban request to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
             other_type => {
                 let md = other_type.metadata();
                 let request_path = other_type.request_path();
@@ -2926,6 +4683,14 @@ impl HttpResponseType {
             402 => HttpResponseType::PaymentRequired(md, error_text),
             403 => HttpResponseType::Forbidden(md, error_text),
             404 => HttpResponseType::NotFound(md, error_text),
+            429 => {
+                let retry_after = preamble
+                    .headers
+                    .get("retry-after")
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+                    .unwrap_or(0);
+                HttpResponseType::TooManyRequests(md, retry_after, error_text)
+            }
             500 => HttpResponseType::ServerError(md, error_text),
             503 => HttpResponseType::ServiceUnavailable(md, error_text),
             _ => HttpResponseType::Error(md, preamble.status_code, error_text),
@@ -3076,6 +4841,19 @@ impl HttpResponseType {
         fd: &mut R,
         len_hint: Option<usize>,
     ) -> Result<HttpResponseType, net_error> {
+        if preamble.status_code == 503
+            && preamble.content_type == HttpContentType::JSON
+            && PATH_GETHEALTH.is_match(&request_path)
+        {
+            // a node that is not synced still reports its health, with a 503
+            return HttpResponseType::parse_gethealth(
+                protocol,
+                request_version,
+                preamble,
+                fd,
+                len_hint,
+            );
+        }
         if preamble.status_code >= 400 {
             return HttpResponseType::parse_error(protocol, request_version, preamble, fd);
         }
@@ -3093,9 +4871,19 @@ impl HttpResponseType {
         )] = &[
             (&PATH_GETINFO, &HttpResponseType::parse_peerinfo),
             (&PATH_GETPOXINFO, &HttpResponseType::parse_poxinfo),
+            (
+                &PATH_GETPOXPROJECTION,
+                &HttpResponseType::parse_poxprojection,
+            ),
+            (&PATH_GETREWARDSET, &HttpResponseType::parse_rewardset),
             (&PATH_GETNEIGHBORS, &HttpResponseType::parse_neighbors),
             (&PATH_GETBLOCK, &HttpResponseType::parse_block),
+            (
+                &PATH_GET_BLOCK_HEADER,
+                &HttpResponseType::parse_get_block_header,
+            ),
             (&PATH_GET_MAP_ENTRY, &HttpResponseType::parse_get_map_entry),
+            (&PATH_GET_DATA_VAR, &HttpResponseType::parse_get_data_var),
             (
                 &PATH_GETMICROBLOCKS_INDEXED,
                 &HttpResponseType::parse_microblocks,
@@ -3108,11 +4896,37 @@ impl HttpResponseType {
                 &PATH_GETMICROBLOCKS_UNCONFIRMED,
                 &HttpResponseType::parse_microblocks_unconfirmed,
             ),
+            (&PATH_GETBLOCK_BY_HEIGHT, &HttpResponseType::parse_block),
+            (
+                &PATH_GETMICROBLOCKS_BY_HEIGHT,
+                &HttpResponseType::parse_microblocks,
+            ),
+            (
+                &PATH_GET_SORTITION_BY_HEIGHT,
+                &HttpResponseType::parse_sortition_info,
+            ),
+            (
+                &PATH_GET_SORTITION_BY_HASH,
+                &HttpResponseType::parse_sortition_info,
+            ),
+            (
+                &PATH_GET_SORTITION_STATS,
+                &HttpResponseType::parse_sortition_stats,
+            ),
+            (&PATH_GET_BNS_NAME, &HttpResponseType::parse_bns_name),
             (
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpResponseType::parse_transaction_unconfirmed,
             ),
+            (
+                &PATH_GETTRANSACTION_STATUS,
+                &HttpResponseType::parse_transaction_status,
+            ),
             (&PATH_POSTTRANSACTION, &HttpResponseType::parse_txid),
+            (
+                &PATH_POST_TRANSACTION_SIMULATION,
+                &HttpResponseType::parse_transaction_simulation,
+            ),
             (
                 &PATH_POSTBLOCK,
                 &HttpResponseType::parse_stacks_block_accepted,
@@ -3122,6 +4936,35 @@ impl HttpResponseType {
                 &HttpResponseType::parse_microblock_hash,
             ),
             (&PATH_GET_ACCOUNT, &HttpResponseType::parse_get_account),
+            (&PATH_POST_ACCOUNTS, &HttpResponseType::parse_get_accounts),
+            (
+                &PATH_GET_ADDRESS_TRANSACTIONS,
+                &HttpResponseType::parse_get_address_transactions,
+            ),
+            (
+                &PATH_GET_ADDRESS_ASSETS,
+                &HttpResponseType::parse_get_address_assets,
+            ),
+            (
+                &PATH_GET_NFT_HISTORY,
+                &HttpResponseType::parse_get_nft_history,
+            ),
+            (
+                &PATH_GET_MEMPOOL_TRANSACTIONS,
+                &HttpResponseType::parse_mempool_transactions,
+            ),
+            (
+                &PATH_GET_MEMPOOL_ADDRESS_TRANSACTIONS,
+                &HttpResponseType::parse_mempool_transactions,
+            ),
+            (
+                &PATH_GET_MEMPOOL_DROPPED_TRANSACTIONS,
+                &HttpResponseType::parse_mempool_dropped_transactions,
+            ),
+            (
+                &PATH_GET_MEMPOOL_NONCE_STATUS,
+                &HttpResponseType::parse_mempool_nonce_status,
+            ),
             (
                 &PATH_GET_CONTRACT_SRC,
                 &HttpResponseType::parse_get_contract_src,
@@ -3130,6 +4973,10 @@ impl HttpResponseType {
                 &PATH_GET_IS_TRAIT_IMPLEMENTED,
                 &HttpResponseType::parse_get_is_trait_implemented,
             ),
+            (
+                &PATH_GET_TRAIT_CONFORMANCE,
+                &HttpResponseType::parse_trait_conformance,
+            ),
             (
                 &PATH_GET_CONTRACT_ABI,
                 &HttpResponseType::parse_get_contract_abi,
@@ -3138,6 +4985,10 @@ impl HttpResponseType {
                 &PATH_POST_CALL_READ_ONLY,
                 &HttpResponseType::parse_call_read_only,
             ),
+            (
+                &PATH_POST_CALL_READ_ONLY_BATCH,
+                &HttpResponseType::parse_call_read_only_batch,
+            ),
             (
                 &PATH_GET_ATTACHMENT,
                 &HttpResponseType::parse_get_attachment,
@@ -3146,6 +4997,21 @@ impl HttpResponseType {
                 &PATH_GET_ATTACHMENTS_INV,
                 &HttpResponseType::parse_get_attachments_inv,
             ),
+            (&PATH_GETHEALTH, &HttpResponseType::parse_gethealth),
+            (&PATH_ADMIN_LOG_LEVELS, &HttpResponseType::parse_log_levels),
+            (
+                &PATH_ADMIN_EVENT_REPLAY,
+                &HttpResponseType::parse_event_replay,
+            ),
+            (&PATH_ADMIN_PEERS, &HttpResponseType::parse_peer_reputations),
+            (
+                &PATH_ADMIN_PEERS_BAN,
+                &HttpResponseType::parse_peer_reputation,
+            ),
+            (
+                &PATH_ADMIN_PEERS_UNBAN,
+                &HttpResponseType::parse_peer_reputation,
+            ),
         ];
 
         // use url::Url to parse path and query string
@@ -3224,6 +5090,36 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_poxprojection<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let projection =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::PoxProjection(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            projection,
+        ))
+    }
+
+    fn parse_rewardset<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let reward_set =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::RewardSet(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            reward_set,
+        ))
+    }
+
     fn parse_neighbors<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3269,18 +5165,93 @@ impl HttpResponseType {
         ))
     }
 
-    fn parse_get_account<R: Read>(
+    fn parse_get_account<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let account_entry =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetAccount(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            account_entry,
+        ))
+    }
+
+    fn parse_get_accounts<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let accounts: BatchAccountsResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetAccounts(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            accounts,
+        ))
+    }
+
+    fn parse_get_address_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let txs: AddressTransactionsResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetAddressTransactions(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            txs,
+        ))
+    }
+
+    fn parse_get_address_assets<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let assets: AddressAssetsResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetAddressAssets(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            assets,
+        ))
+    }
+
+    fn parse_get_block_header<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let header: BlockHeaderResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetBlockHeader(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            header,
+        ))
+    }
+
+    fn parse_get_nft_history<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
         preamble: &HttpResponsePreamble,
         fd: &mut R,
         len_hint: Option<usize>,
     ) -> Result<HttpResponseType, net_error> {
-        let account_entry =
+        let history: NftHistoryResponse =
             HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
-        Ok(HttpResponseType::GetAccount(
+        Ok(HttpResponseType::GetNftHistory(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            account_entry,
+            history,
         ))
     }
 
@@ -3299,6 +5270,21 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_get_data_var<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let data_var =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetDataVar(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            data_var,
+        ))
+    }
+
     fn parse_get_contract_src<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3329,6 +5315,21 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_trait_conformance<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let conformance =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::TraitConformance(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            conformance,
+        ))
+    }
+
     fn parse_get_contract_abi<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3358,6 +5359,21 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_call_read_only_batch<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let batch: CallReadOnlyBatchResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::CallReadOnlyFunctionBatch(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            batch,
+        ))
+    }
+
     fn parse_microblocks_unconfirmed<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3396,6 +5412,83 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_transaction_status<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let tx_status: TransactionStatusResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::TransactionStatus(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            tx_status,
+        ))
+    }
+
+    fn parse_mempool_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let txs: MempoolTransactionsResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::MempoolTransactions(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            txs,
+        ))
+    }
+
+    fn parse_mempool_dropped_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let txs: MempoolDroppedTransactionsResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::MempoolDroppedTransactions(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            txs,
+        ))
+    }
+
+    fn parse_mempool_nonce_status<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let status: MempoolNonceStatusResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::MempoolNonceStatus(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            status,
+        ))
+    }
+
+    fn parse_transaction_simulation<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let simulation: TransactionSimulationResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::TransactionSimulation(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            simulation,
+        ))
+    }
+
     fn parse_transaction_unconfirmed<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3476,6 +5569,134 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_sortition_info<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let info: RPCSortitionInfo =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::SortitionInfo(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            info,
+        ))
+    }
+
+    fn parse_sortition_stats<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let stats: RPCSortitionStats =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::SortitionStats(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            stats,
+        ))
+    }
+
+    fn parse_bns_name<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let info: RPCBNSNameInfo =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::BNSName(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            info,
+        ))
+    }
+
+    fn parse_gethealth<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let health: RPCHealthData =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::Health(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            health,
+        ))
+    }
+
+    fn parse_log_levels<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let res: RPCLogLevelsData =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::LogLevels(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            res,
+        ))
+    }
+
+    fn parse_event_replay<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let res: EventReplayResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::EventReplay(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            res,
+        ))
+    }
+
+    fn parse_peer_reputations<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let res: Vec<RPCPeerReputationData> =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::PeerReputations(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            res,
+        ))
+    }
+
+    fn parse_peer_reputation<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let res: RPCPeerReputationData =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        Ok(HttpResponseType::PeerReputation(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            res,
+        ))
+    }
+
     fn parse_stacks_block_accepted<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3522,6 +5743,7 @@ impl HttpResponseType {
             402 => "Payment Required",
             403 => "Forbidden",
             404 => "Not Found",
+            429 => "Too Many Requests",
             500 => "Internal Server Error",
             503 => "Service Temporarily Unavailable",
             _ => "Error",
@@ -3530,6 +5752,7 @@ impl HttpResponseType {
 
     fn error_response<W: Write>(
         &self,
+        protocol: &StacksHttp,
         fd: &mut W,
         code: u16,
         message: &str,
@@ -3542,7 +5765,7 @@ impl HttpResponseType {
             Some(message.len() as u32),
             &HttpContentType::Text,
             md.request_id,
-            |ref mut fd| keep_alive_headers(fd, md),
+            |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
         )?;
         fd.write_all(message.as_bytes())
             .map_err(net_error::WriteError)?;
@@ -3553,6 +5776,8 @@ impl HttpResponseType {
         match *self {
             HttpResponseType::PeerInfo(ref md, _) => md,
             HttpResponseType::PoxInfo(ref md, _) => md,
+            HttpResponseType::PoxProjection(ref md, _) => md,
+            HttpResponseType::RewardSet(ref md, _) => md,
             HttpResponseType::Neighbors(ref md, _) => md,
             HttpResponseType::Block(ref md, _) => md,
             HttpResponseType::BlockStream(ref md) => md,
@@ -3563,16 +5788,37 @@ impl HttpResponseType {
             HttpResponseType::MicroblockHash(ref md, _) => md,
             HttpResponseType::TokenTransferCost(ref md, _) => md,
             HttpResponseType::GetMapEntry(ref md, _) => md,
+            HttpResponseType::GetDataVar(ref md, _) => md,
             HttpResponseType::GetAccount(ref md, _) => md,
+            HttpResponseType::GetAccounts(ref md, _) => md,
+            HttpResponseType::GetAddressTransactions(ref md, _) => md,
+            HttpResponseType::GetAddressAssets(ref md, _) => md,
+            HttpResponseType::GetNftHistory(ref md, _) => md,
+            HttpResponseType::GetBlockHeader(ref md, _) => md,
             HttpResponseType::GetContractABI(ref md, _) => md,
             HttpResponseType::GetContractSrc(ref md, _) => md,
             HttpResponseType::GetIsTraitImplemented(ref md, _) => md,
+            HttpResponseType::TraitConformance(ref md, _) => md,
             HttpResponseType::CallReadOnlyFunction(ref md, _) => md,
+            HttpResponseType::CallReadOnlyFunctionBatch(ref md, _) => md,
             HttpResponseType::UnconfirmedTransaction(ref md, _) => md,
+            HttpResponseType::TransactionStatus(ref md, _) => md,
+            HttpResponseType::TransactionSimulation(ref md, _) => md,
+            HttpResponseType::MempoolTransactions(ref md, _) => md,
+            HttpResponseType::MempoolDroppedTransactions(ref md, _) => md,
+            HttpResponseType::MempoolNonceStatus(ref md, _) => md,
             HttpResponseType::GetAttachment(ref md, _) => md,
             HttpResponseType::GetAttachmentsInv(ref md, _) => md,
             HttpResponseType::OptionsPreflight(ref md) => md,
             HttpResponseType::TransactionFeeEstimation(ref md, _) => md,
+            HttpResponseType::Health(ref md, _) => md,
+            HttpResponseType::SortitionInfo(ref md, _) => md,
+            HttpResponseType::SortitionStats(ref md, _) => md,
+            HttpResponseType::BNSName(ref md, _) => md,
+            HttpResponseType::LogLevels(ref md, _) => md,
+            HttpResponseType::EventReplay(ref md, _) => md,
+            HttpResponseType::PeerReputations(ref md, _) => md,
+            HttpResponseType::PeerReputation(ref md, _) => md,
             // errors
             HttpResponseType::BadRequestJSON(ref md, _) => md,
             HttpResponseType::BadRequest(ref md, _) => md,
@@ -3580,6 +5826,7 @@ impl HttpResponseType {
             HttpResponseType::PaymentRequired(ref md, _) => md,
             HttpResponseType::Forbidden(ref md, _) => md,
             HttpResponseType::NotFound(ref md, _) => md,
+            HttpResponseType::TooManyRequests(ref md, ..) => md,
             HttpResponseType::ServerError(ref md, _) => md,
             HttpResponseType::ServiceUnavailable(ref md, _) => md,
             HttpResponseType::Error(ref md, _, _) => md,
@@ -3646,59 +5893,153 @@ impl HttpResponseType {
         }
     }
 
+    /// Send a 200 response with a JSON body.  If the body is big enough and the client accepts a
+    /// compressed encoding, the body is compressed and sent with an explicit content-length.
+    fn send_ok_json<W: Write, T: serde::ser::Serialize>(
+        protocol: &mut StacksHttp,
+        md: &HttpResponseMetadata,
+        fd: &mut W,
+        message: &T,
+    ) -> Result<(), net_error> {
+        if let Some(encoding) = md.client_accept_encoding {
+            if protocol.compression_threshold > 0 {
+                let body = serde_json::to_vec(message).map_err(|e| {
+                    net_error::SerializeError(format!("Failed to serialize as JSON: {:?}", &e))
+                })?;
+                if (body.len() as u64) >= protocol.compression_threshold {
+                    let compressed = encoding.encode(&body).map_err(net_error::WriteError)?;
+                    HttpResponsePreamble::new_serialized(
+                        fd,
+                        200,
+                        "OK",
+                        Some(compressed.len() as u32),
+                        &HttpContentType::JSON,
+                        md.request_id,
+                        |ref mut fd| {
+                            response_headers(fd, md, &protocol.cors_policy)?;
+                            fd.write_all(
+                                format!("Content-Encoding: {}\r\n", encoding.as_str()).as_bytes(),
+                            )
+                            .map_err(codec_error::WriteError)
+                        },
+                    )?;
+                    fd.write_all(&compressed).map_err(net_error::WriteError)?;
+                    return Ok(());
+                }
+            }
+        }
+        HttpResponsePreamble::ok_JSON_from_md(fd, md, &protocol.cors_policy)?;
+        HttpResponseType::send_json(protocol, md, fd, message)
+    }
+
     pub fn send<W: Write>(&self, protocol: &mut StacksHttp, fd: &mut W) -> Result<(), net_error> {
         match *self {
             HttpResponseType::GetAccount(ref md, ref account_data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, account_data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, account_data)?;
+            }
+            HttpResponseType::GetAccounts(ref md, ref accounts) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, accounts)?;
+            }
+            HttpResponseType::GetAddressTransactions(ref md, ref txs) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, txs)?;
+            }
+            HttpResponseType::GetAddressAssets(ref md, ref assets) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, assets)?;
+            }
+            HttpResponseType::GetNftHistory(ref md, ref history) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, history)?;
+            }
+            HttpResponseType::GetBlockHeader(ref md, ref header) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, header)?;
             }
             HttpResponseType::TransactionFeeEstimation(ref md, ref data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
             }
             HttpResponseType::GetContractABI(ref md, ref data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
             }
             HttpResponseType::GetContractSrc(ref md, ref data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
             }
             HttpResponseType::GetIsTraitImplemented(ref md, ref data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::TraitConformance(ref md, ref data) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
             }
             HttpResponseType::TokenTransferCost(ref md, ref cost) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, cost)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, cost)?;
             }
             HttpResponseType::CallReadOnlyFunction(ref md, ref data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::CallReadOnlyFunctionBatch(ref md, ref data) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
             }
             HttpResponseType::GetMapEntry(ref md, ref map_data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, map_data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, map_data)?;
+            }
+            HttpResponseType::GetDataVar(ref md, ref var_data) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, var_data)?;
             }
             HttpResponseType::PeerInfo(ref md, ref peer_info) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, peer_info)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, peer_info)?;
             }
             HttpResponseType::PoxInfo(ref md, ref pox_info) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, pox_info)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, pox_info)?;
+            }
+            HttpResponseType::PoxProjection(ref md, ref projection) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, projection)?;
+            }
+            HttpResponseType::RewardSet(ref md, ref reward_set) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, reward_set)?;
             }
             HttpResponseType::Neighbors(ref md, ref neighbor_data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, neighbor_data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, neighbor_data)?;
             }
             HttpResponseType::GetAttachment(ref md, ref zonefile_data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, zonefile_data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, zonefile_data)?;
             }
             HttpResponseType::GetAttachmentsInv(ref md, ref zonefile_data) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, zonefile_data)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, zonefile_data)?;
+            }
+            HttpResponseType::Health(ref md, ref health) => {
+                if health.synced {
+                    HttpResponseType::send_ok_json(protocol, md, fd, health)?;
+                } else {
+                    // so load balancers route around this node
+                    HttpResponsePreamble::new_serialized(
+                        fd,
+                        503,
+                        HttpResponseType::error_reason(503),
+                        md.content_length.clone(),
+                        &HttpContentType::JSON,
+                        md.request_id,
+                        |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
+                    )?;
+                    HttpResponseType::send_json(protocol, md, fd, health)?;
+                }
+            }
+            HttpResponseType::SortitionInfo(ref md, ref info) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, info)?;
+            }
+            HttpResponseType::SortitionStats(ref md, ref stats) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, stats)?;
+            }
+            HttpResponseType::BNSName(ref md, ref info) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, info)?;
+            }
+            HttpResponseType::LogLevels(ref md, ref data) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::EventReplay(ref md, ref data) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::PeerReputations(ref md, ref data) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::PeerReputation(ref md, ref data) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, data)?;
             }
             HttpResponseType::Block(ref md, ref block) => {
                 HttpResponsePreamble::new_serialized(
@@ -3708,7 +6049,7 @@ impl HttpResponseType {
                     md.content_length.clone(),
                     &HttpContentType::Bytes,
                     md.request_id,
-                    |ref mut fd| keep_alive_headers(fd, md),
+                    |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
                 )?;
                 HttpResponseType::send_bytestream(protocol, md, fd, block)?;
             }
@@ -3722,7 +6063,7 @@ impl HttpResponseType {
                     None,
                     &HttpContentType::Bytes,
                     md.request_id,
-                    |ref mut fd| keep_alive_headers(fd, md),
+                    |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
                 )?;
             }
             HttpResponseType::Microblocks(ref md, ref microblocks) => {
@@ -3733,7 +6074,7 @@ impl HttpResponseType {
                     md.content_length.clone(),
                     &HttpContentType::Bytes,
                     md.request_id,
-                    |ref mut fd| keep_alive_headers(fd, md),
+                    |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
                 )?;
                 HttpResponseType::send_bytestream(protocol, md, fd, microblocks)?;
             }
@@ -3747,7 +6088,7 @@ impl HttpResponseType {
                     None,
                     &HttpContentType::Bytes,
                     md.request_id,
-                    |ref mut fd| keep_alive_headers(fd, md),
+                    |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
                 )?;
             }
             HttpResponseType::TransactionID(ref md, ref txid) => {
@@ -3759,7 +6100,7 @@ impl HttpResponseType {
                     md.content_length.clone(),
                     &HttpContentType::JSON,
                     md.request_id,
-                    |ref mut fd| keep_alive_headers(fd, md),
+                    |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
                 )?;
                 HttpResponseType::send_json(protocol, md, fd, &txid_bytes)?;
             }
@@ -3775,7 +6116,7 @@ impl HttpResponseType {
                     md.content_length.clone(),
                     &HttpContentType::JSON,
                     md.request_id,
-                    |ref mut fd| keep_alive_headers(fd, md),
+                    |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
                 )?;
                 HttpResponseType::send_json(protocol, md, fd, &accepted_data)?;
             }
@@ -3788,13 +6129,27 @@ impl HttpResponseType {
                     md.content_length.clone(),
                     &HttpContentType::JSON,
                     md.request_id,
-                    |ref mut fd| keep_alive_headers(fd, md),
+                    |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
                 )?;
                 HttpResponseType::send_json(protocol, md, fd, &mblock_bytes)?;
             }
             HttpResponseType::UnconfirmedTransaction(ref md, ref unconfirmed_status) => {
-                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
-                HttpResponseType::send_json(protocol, md, fd, unconfirmed_status)?;
+                HttpResponseType::send_ok_json(protocol, md, fd, unconfirmed_status)?;
+            }
+            HttpResponseType::TransactionStatus(ref md, ref tx_status) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, tx_status)?;
+            }
+            HttpResponseType::TransactionSimulation(ref md, ref simulation) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, simulation)?;
+            }
+            HttpResponseType::MempoolTransactions(ref md, ref txs) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, txs)?;
+            }
+            HttpResponseType::MempoolDroppedTransactions(ref md, ref txs) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, txs)?;
+            }
+            HttpResponseType::MempoolNonceStatus(ref md, ref status) => {
+                HttpResponseType::send_ok_json(protocol, md, fd, status)?;
             }
             HttpResponseType::OptionsPreflight(ref md) => {
                 HttpResponsePreamble::new_serialized(
@@ -3804,7 +6159,12 @@ impl HttpResponseType {
                     None,
                     &HttpContentType::Text,
                     md.request_id,
-                    |ref mut fd| keep_alive_headers(fd, md),
+                    |ref mut fd| {
+                        keep_alive_headers(fd, md)?;
+                        protocol
+                            .cors_policy
+                            .write_headers(fd, md.client_origin.as_deref(), true)
+                    },
                 )?;
                 HttpResponseType::send_text(protocol, md, fd, "".as_bytes())?;
             }
@@ -3816,21 +6176,50 @@ impl HttpResponseType {
                     md.content_length.clone(),
                     &HttpContentType::JSON,
                     md.request_id,
-                    |ref mut fd| keep_alive_headers(fd, md),
+                    |ref mut fd| response_headers(fd, md, &protocol.cors_policy),
                 )?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
             }
-            HttpResponseType::BadRequest(_, ref msg) => self.error_response(fd, 400, msg)?,
-            HttpResponseType::Unauthorized(_, ref msg) => self.error_response(fd, 401, msg)?,
-            HttpResponseType::PaymentRequired(_, ref msg) => self.error_response(fd, 402, msg)?,
-            HttpResponseType::Forbidden(_, ref msg) => self.error_response(fd, 403, msg)?,
-            HttpResponseType::NotFound(_, ref msg) => self.error_response(fd, 404, msg)?,
-            HttpResponseType::ServerError(_, ref msg) => self.error_response(fd, 500, msg)?,
+            HttpResponseType::BadRequest(_, ref msg) => {
+                self.error_response(protocol, fd, 400, msg)?
+            }
+            HttpResponseType::Unauthorized(_, ref msg) => {
+                self.error_response(protocol, fd, 401, msg)?
+            }
+            HttpResponseType::PaymentRequired(_, ref msg) => {
+                self.error_response(protocol, fd, 402, msg)?
+            }
+            HttpResponseType::Forbidden(_, ref msg) => {
+                self.error_response(protocol, fd, 403, msg)?
+            }
+            HttpResponseType::NotFound(_, ref msg) => {
+                self.error_response(protocol, fd, 404, msg)?
+            }
+            HttpResponseType::TooManyRequests(ref md, ref retry_after, ref msg) => {
+                HttpResponsePreamble::new_serialized(
+                    fd,
+                    429,
+                    HttpResponseType::error_reason(429),
+                    Some(msg.len() as u32),
+                    &HttpContentType::Text,
+                    md.request_id,
+                    |ref mut fd| {
+                        response_headers(fd, md, &protocol.cors_policy)?;
+                        fd.write_all(format!("Retry-After: {}\r\n", retry_after).as_bytes())
+                            .map_err(codec_error::WriteError)
+                    },
+                )?;
+                fd.write_all(msg.as_bytes())
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpResponseType::ServerError(_, ref msg) => {
+                self.error_response(protocol, fd, 500, msg)?
+            }
             HttpResponseType::ServiceUnavailable(_, ref msg) => {
-                self.error_response(fd, 503, msg)?
+                self.error_response(protocol, fd, 503, msg)?
             }
             HttpResponseType::Error(_, ref error_code, ref msg) => {
-                self.error_response(fd, *error_code, msg)?
+                self.error_response(protocol, fd, *error_code, msg)?
             }
         };
         Ok(())
@@ -3888,44 +6277,88 @@ impl MessageSequence for StacksHttpMessage {
             StacksHttpMessage::Request(ref req) => match req {
                 HttpRequestType::GetInfo(_) => "HTTP(GetInfo)",
                 HttpRequestType::GetPoxInfo(_, _) => "HTTP(GetPoxInfo)",
+                HttpRequestType::GetPoxProjection(..) => "HTTP(GetPoxProjection)",
+                HttpRequestType::GetRewardSet(..) => "HTTP(GetRewardSet)",
                 HttpRequestType::GetNeighbors(_) => "HTTP(GetNeighbors)",
                 HttpRequestType::GetBlock(_, _) => "HTTP(GetBlock)",
+                HttpRequestType::GetBlockHeader(_, _) => "HTTP(GetBlockHeader)",
                 HttpRequestType::GetMicroblocksIndexed(_, _) => "HTTP(GetMicroblocksIndexed)",
                 HttpRequestType::GetMicroblocksConfirmed(_, _) => "HTTP(GetMicroblocksConfirmed)",
                 HttpRequestType::GetMicroblocksUnconfirmed(_, _, _) => {
                     "HTTP(GetMicroblocksUnconfirmed)"
                 }
+                HttpRequestType::GetBlockByHeight(_, _) => "HTTP(GetBlockByHeight)",
+                HttpRequestType::GetMicroblocksByHeight(_, _) => "HTTP(GetMicroblocksByHeight)",
+                HttpRequestType::GetSortition(_, _) => "HTTP(GetSortition)",
+                HttpRequestType::GetSortitionStats(..) => "HTTP(GetSortitionStats)",
+                HttpRequestType::GetBNSName(..) => "HTTP(GetBNSName)",
                 HttpRequestType::GetTransactionUnconfirmed(_, _) => {
                     "HTTP(GetTransactionUnconfirmed)"
                 }
+                HttpRequestType::GetTransactionStatus(_, _) => "HTTP(GetTransactionStatus)",
+                HttpRequestType::SimulateTransaction(..) => "HTTP(SimulateTransaction)",
+                HttpRequestType::GetMempoolTransactions(..) => "HTTP(GetMempoolTransactions)",
+                HttpRequestType::GetMempoolAddressTransactions(..) => {
+                    "HTTP(GetMempoolAddressTransactions)"
+                }
+                HttpRequestType::GetMempoolDroppedTransactions(..) => {
+                    "HTTP(GetMempoolDroppedTransactions)"
+                }
+                HttpRequestType::GetMempoolNonceStatus(..) => "HTTP(GetMempoolNonceStatus)",
                 HttpRequestType::PostTransaction(_, _, _) => "HTTP(PostTransaction)",
                 HttpRequestType::PostBlock(..) => "HTTP(PostBlock)",
                 HttpRequestType::PostMicroblock(..) => "HTTP(PostMicroblock)",
                 HttpRequestType::GetAccount(..) => "HTTP(GetAccount)",
+                HttpRequestType::GetAccounts(..) => "HTTP(GetAccounts)",
+                HttpRequestType::GetAddressTransactions(..) => "HTTP(GetAddressTransactions)",
+                HttpRequestType::GetAddressAssets(..) => "HTTP(GetAddressAssets)",
+                HttpRequestType::GetNftHistory(..) => "HTTP(GetNftHistory)",
                 HttpRequestType::GetMapEntry(..) => "HTTP(GetMapEntry)",
+                HttpRequestType::GetDataVar(..) => "HTTP(GetDataVar)",
                 HttpRequestType::GetTransferCost(_) => "HTTP(GetTransferCost)",
                 HttpRequestType::GetContractABI(..) => "HTTP(GetContractABI)",
                 HttpRequestType::GetContractSrc(..) => "HTTP(GetContractSrc)",
                 HttpRequestType::GetIsTraitImplemented(..) => "HTTP(GetIsTraitImplemented)",
+                HttpRequestType::GetTraitConformance(..) => "HTTP(GetTraitConformance)",
                 HttpRequestType::CallReadOnlyFunction(..) => "HTTP(CallReadOnlyFunction)",
+                HttpRequestType::CallReadOnlyFunctionBatch(..) => "HTTP(CallReadOnlyFunctionBatch)",
                 HttpRequestType::GetAttachment(..) => "HTTP(GetAttachment)",
                 HttpRequestType::GetAttachmentsInv(..) => "HTTP(GetAttachmentsInv)",
                 HttpRequestType::OptionsPreflight(..) => "HTTP(OptionsPreflight)",
                 HttpRequestType::ClientError(..) => "HTTP(ClientError)",
                 HttpRequestType::FeeRateEstimate(_, _, _) => "HTTP(FeeRateEstimate)",
+                HttpRequestType::GetHealth(_) => "HTTP(GetHealth)",
+                HttpRequestType::GetLogLevels(_) => "HTTP(GetLogLevels)",
+                HttpRequestType::PostLogLevel(..) => "HTTP(PostLogLevel)",
+                HttpRequestType::PostEventReplay(..) => "HTTP(PostEventReplay)",
+                HttpRequestType::GetPeerReputations(_) => "HTTP(GetPeerReputations)",
+                HttpRequestType::PostPeerBan(..) => "HTTP(PostPeerBan)",
+                HttpRequestType::PostPeerUnban(..) => "HTTP(PostPeerUnban)",
             },
             StacksHttpMessage::Response(ref res) => match res {
                 HttpResponseType::TokenTransferCost(_, _) => "HTTP(TokenTransferCost)",
                 HttpResponseType::GetMapEntry(_, _) => "HTTP(GetMapEntry)",
+                HttpResponseType::GetDataVar(_, _) => "HTTP(GetDataVar)",
                 HttpResponseType::GetAccount(_, _) => "HTTP(GetAccount)",
+                HttpResponseType::GetAccounts(_, _) => "HTTP(GetAccounts)",
+                HttpResponseType::GetAddressTransactions(_, _) => "HTTP(GetAddressTransactions)",
+                HttpResponseType::GetAddressAssets(_, _) => "HTTP(GetAddressAssets)",
+                HttpResponseType::GetNftHistory(_, _) => "HTTP(GetNftHistory)",
+                HttpResponseType::GetBlockHeader(_, _) => "HTTP(GetBlockHeader)",
                 HttpResponseType::GetContractABI(..) => "HTTP(GetContractABI)",
                 HttpResponseType::GetContractSrc(..) => "HTTP(GetContractSrc)",
                 HttpResponseType::GetIsTraitImplemented(..) => "HTTP(GetIsTraitImplemented)",
+                HttpResponseType::TraitConformance(..) => "HTTP(TraitConformance)",
                 HttpResponseType::CallReadOnlyFunction(..) => "HTTP(CallReadOnlyFunction)",
+                HttpResponseType::CallReadOnlyFunctionBatch(..) => {
+                    "HTTP(CallReadOnlyFunctionBatch)"
+                }
                 HttpResponseType::GetAttachment(_, _) => "HTTP(GetAttachment)",
                 HttpResponseType::GetAttachmentsInv(_, _) => "HTTP(GetAttachmentsInv)",
                 HttpResponseType::PeerInfo(_, _) => "HTTP(PeerInfo)",
                 HttpResponseType::PoxInfo(_, _) => "HTTP(PeerInfo)",
+                HttpResponseType::PoxProjection(_, _) => "HTTP(PoxProjection)",
+                HttpResponseType::RewardSet(_, _) => "HTTP(RewardSet)",
                 HttpResponseType::Neighbors(_, _) => "HTTP(Neighbors)",
                 HttpResponseType::Block(_, _) => "HTTP(Block)",
                 HttpResponseType::BlockStream(_) => "HTTP(BlockStream)",
@@ -3935,6 +6368,13 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::StacksBlockAccepted(..) => "HTTP(StacksBlockAccepted)",
                 HttpResponseType::MicroblockHash(_, _) => "HTTP(MicroblockHash)",
                 HttpResponseType::UnconfirmedTransaction(_, _) => "HTTP(UnconfirmedTransaction)",
+                HttpResponseType::TransactionStatus(_, _) => "HTTP(TransactionStatus)",
+                HttpResponseType::TransactionSimulation(_, _) => "HTTP(TransactionSimulation)",
+                HttpResponseType::MempoolTransactions(_, _) => "HTTP(MempoolTransactions)",
+                HttpResponseType::MempoolDroppedTransactions(_, _) => {
+                    "HTTP(MempoolDroppedTransactions)"
+                }
+                HttpResponseType::MempoolNonceStatus(_, _) => "HTTP(MempoolNonceStatus)",
                 HttpResponseType::OptionsPreflight(_) => "HTTP(OptionsPreflight)",
                 HttpResponseType::BadRequestJSON(..) | HttpResponseType::BadRequest(..) => {
                     "HTTP(400)"
@@ -3943,12 +6383,21 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::PaymentRequired(_, _) => "HTTP(402)",
                 HttpResponseType::Forbidden(_, _) => "HTTP(403)",
                 HttpResponseType::NotFound(_, _) => "HTTP(404)",
+                HttpResponseType::TooManyRequests(..) => "HTTP(429)",
                 HttpResponseType::ServerError(_, _) => "HTTP(500)",
                 HttpResponseType::ServiceUnavailable(_, _) => "HTTP(503)",
                 HttpResponseType::Error(_, _, _) => "HTTP(other)",
                 HttpResponseType::TransactionFeeEstimation(_, _) => {
                     "HTTP(TransactionFeeEstimation)"
                 }
+                HttpResponseType::Health(_, _) => "HTTP(Health)",
+                HttpResponseType::SortitionInfo(_, _) => "HTTP(SortitionInfo)",
+                HttpResponseType::SortitionStats(_, _) => "HTTP(SortitionStats)",
+                HttpResponseType::BNSName(_, _) => "HTTP(BNSName)",
+                HttpResponseType::LogLevels(_, _) => "HTTP(LogLevels)",
+                HttpResponseType::EventReplay(_, _) => "HTTP(EventReplay)",
+                HttpResponseType::PeerReputations(_, _) => "HTTP(PeerReputations)",
+                HttpResponseType::PeerReputation(_, _) => "HTTP(PeerReputation)",
             },
         }
     }
@@ -4053,6 +6502,10 @@ pub struct StacksHttp {
     chunk_size: usize,
     /// Maximum size of call arguments
     pub maximum_call_argument_size: u32,
+    /// Compress JSON response bodies at least this big, if the client accepts it (0 = never)
+    pub compression_threshold: u64,
+    /// Which cross-origin requests we allow
+    pub cors_policy: HttpCorsPolicy,
 }
 
 impl StacksHttp {
@@ -4064,6 +6517,8 @@ impl StacksHttp {
             request_path: None,
             chunk_size: 8192,
             maximum_call_argument_size: 20 * BOUND_VALUE_SERIALIZATION_HEX,
+            compression_threshold: 0,
+            cors_policy: HttpCorsPolicy::default(),
         }
     }
 
@@ -4458,6 +6913,7 @@ impl ProtocolFamily for StacksHttp {
 mod test {
     use std::error::Error;
 
+    use libflate;
     use rand;
     use rand::RngCore;
 
@@ -4475,12 +6931,17 @@ mod test {
     use chainstate::stacks::TransactionVersion;
     use net::codec::test::check_codec_and_corruption;
     use net::test::*;
+    use net::BlockStatsResponse;
+    use net::ContractSrcResponse;
+    use net::HttpAuthToken;
+    use net::RPCHealthStatus;
     use net::RPCNeighbor;
     use net::RPCNeighborsInfo;
     use util::hash::to_hex;
     use util::hash::Hash160;
     use util::hash::MerkleTree;
     use util::hash::Sha512Trunc256Sum;
+    use vm::costs::ExecutionCost;
 
     use crate::types::chainstate::StacksAddress;
     use crate::types::chainstate::StacksBlockHeader;
@@ -5421,16 +7882,26 @@ mod test {
                 12345,
             ),
             keep_alive: true,
+            accept_encoding: None,
+            origin: None,
+            auth_token: None,
         };
         let http_request_metadata_dns = HttpRequestMetadata {
             version: HttpVersion::Http11,
             peer: PeerHost::DNS("www.foo.com".to_string(), 80),
             keep_alive: true,
+            accept_encoding: None,
+            origin: None,
+            auth_token: None,
         };
 
         let tests = vec![
             HttpRequestType::GetNeighbors(http_request_metadata_ip.clone()),
             HttpRequestType::GetBlock(http_request_metadata_dns.clone(), StacksBlockId([2u8; 32])),
+            HttpRequestType::GetBlockHeader(
+                http_request_metadata_dns.clone(),
+                StacksBlockId([2u8; 32]),
+            ),
             HttpRequestType::GetMicroblocksIndexed(
                 http_request_metadata_ip.clone(),
                 StacksBlockId([3u8; 32]),
@@ -5574,6 +8045,29 @@ mod test {
 
     #[test]
     fn test_http_response_type_codec() {
+        let test_block_header = BlockHeaderResponse {
+            index_block_hash: StacksBlockId([0x11; 32]),
+            block_hash: BlockHeaderHash([0x22; 32]),
+            consensus_hash: ConsensusHash([0x33; 20]),
+            parent_block_hash: BlockHeaderHash([0x44; 32]),
+            block_height: 100,
+            burn_header_hash: BurnchainHeaderHash([0x55; 32]).to_hex(),
+            burn_header_height: 700_000,
+            burn_header_timestamp: 1_600_000_000,
+            anchored_block_size: 1234,
+            stats: Some(BlockStatsResponse {
+                tx_count: 3,
+                total_fees: format!("0x{}", to_hex(&600u128.to_be_bytes())),
+                total_burn: format!("0x{}", to_hex(&0u128.to_be_bytes())),
+                total_cost: ExecutionCost {
+                    write_length: 1,
+                    write_count: 2,
+                    read_length: 3,
+                    read_count: 4,
+                    runtime: 5,
+                },
+            }),
+        };
         let test_neighbors_info = RPCNeighborsInfo {
             sample: vec![
                 RPCNeighbor {
@@ -5688,6 +8182,13 @@ mod test {
                 ),
                 "/v2/neighbors".to_string(),
             ),
+            (
+                HttpResponseType::GetBlockHeader(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
+                    test_block_header.clone(),
+                ),
+                format!("/v2/headers/{}", StacksBlockId([0x11; 32]).to_hex()),
+            ),
             (
                 HttpResponseType::Block(
                     HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
@@ -6020,6 +8521,63 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_http_health_response_status() {
+        let synced = RPCHealthData {
+            status: RPCHealthStatus::Synced,
+            synced: true,
+            burn_block_height: 100,
+            sortition_height: 100,
+            peer_burn_block_height: 100,
+            stacks_tip_height: 50,
+            stacks_tip: BlockHeaderHash([0x11; 32]),
+            stacks_tip_consensus_hash: ConsensusHash([0x22; 20]),
+            stacks_tip_burn_height: 99,
+            peer_stacks_tip_burn_height: 99,
+            staging_blocks_pending: 0,
+        };
+        let mut behind = synced.clone();
+        behind.status = RPCHealthStatus::StacksBehind;
+        behind.synced = false;
+        behind.peer_stacks_tip_burn_height = 110;
+
+        for (health, status_code) in [(synced, 200), (behind, 503)].iter() {
+            let response = HttpResponseType::Health(
+                HttpResponseMetadata::new(
+                    HttpVersion::Http11,
+                    123,
+                    Some(serde_json::to_string(health).unwrap().len() as u32),
+                    true,
+                ),
+                health.clone(),
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let mut bytes = vec![];
+            http.begin_request(HttpVersion::Http11, "/v2/health".to_string());
+            http.write_message(&mut bytes, &StacksHttpMessage::Response(response))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            match preamble {
+                StacksHttpPreamble::Response(ref response_preamble) => {
+                    assert_eq!(response_preamble.status_code, *status_code);
+                }
+                StacksHttpPreamble::Request(_) => {
+                    panic!("parsed a request");
+                }
+            }
+
+            // the report is readable either way
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Response(HttpResponseType::Health(_, parsed)) => {
+                    assert_eq!(parsed, *health);
+                }
+                x => panic!("Expected a health report, got {:?}", x),
+            }
+        }
+    }
+
     #[test]
     fn test_http_response_type_codec_err() {
         let request_paths = vec![
@@ -6069,6 +8627,233 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_http_content_encoding_negotiate() {
+        let tests = vec![
+            ("gzip", Some(HttpContentEncoding::Gzip)),
+            ("gzip, deflate, br", Some(HttpContentEncoding::Gzip)),
+            ("deflate", Some(HttpContentEncoding::Deflate)),
+            ("GZIP;q=0.5", Some(HttpContentEncoding::Gzip)),
+            ("gzip;q=0, deflate", Some(HttpContentEncoding::Deflate)),
+            ("gzip;q=0, *", Some(HttpContentEncoding::Deflate)),
+            ("*", Some(HttpContentEncoding::Gzip)),
+            ("br", None),
+            ("identity", None),
+            ("*;q=0", None),
+            ("", None),
+        ];
+        for (header, expected) in tests {
+            assert_eq!(
+                HttpContentEncoding::negotiate(header),
+                expected,
+                "{}",
+                header
+            );
+        }
+
+        let preamble = HttpRequestPreamble::consensus_deserialize(
+            &mut "GET /v2/info HTTP/1.1\r\nHost: localhost:20443\r\nAccept-Encoding: gzip, deflate\r\n\r\n".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            HttpRequestMetadata::from_preamble(&preamble).accept_encoding,
+            Some(HttpContentEncoding::Gzip)
+        );
+    }
+
+    #[test]
+    fn test_http_response_compressed() {
+        let data = ContractSrcResponse {
+            source: "(define-read-only (hello) \"hello world\")\n".repeat(100),
+            publish_height: 1,
+            marf_proof: None,
+        };
+        let expected_body = serde_json::to_vec(&data).unwrap();
+
+        for encoding in [HttpContentEncoding::Gzip, HttpContentEncoding::Deflate].iter() {
+            let mut md = HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true);
+            md.client_accept_encoding = Some(*encoding);
+            let response = HttpResponseType::GetContractSrc(md, data.clone());
+
+            // not compressed if compression is off, or if the body is too small
+            for threshold in [0, expected_body.len() as u64 + 1].iter() {
+                let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+                http.compression_threshold = *threshold;
+                let mut bytes = vec![];
+                response.send(&mut http, &mut bytes).unwrap();
+                let txt = String::from_utf8_lossy(&bytes);
+                assert!(txt.find("Content-Encoding").is_none());
+            }
+
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            http.compression_threshold = expected_body.len() as u64;
+            let mut bytes = vec![];
+            response.send(&mut http, &mut bytes).unwrap();
+
+            let body_start = bytes.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+            let headers = String::from_utf8(bytes[0..body_start].to_vec()).unwrap();
+            assert!(headers
+                .find(&format!("Content-Encoding: {}\r\n", encoding.as_str()))
+                .is_some());
+            assert!(headers
+                .find(&format!("Content-Length: {}\r\n", bytes.len() - body_start))
+                .is_some());
+
+            let compressed = &bytes[body_start..];
+            assert!(compressed.len() < expected_body.len());
+
+            let mut body = vec![];
+            match encoding {
+                HttpContentEncoding::Gzip => {
+                    libflate::gzip::Decoder::new(compressed)
+                        .unwrap()
+                        .read_to_end(&mut body)
+                        .unwrap();
+                }
+                HttpContentEncoding::Deflate => {
+                    libflate::zlib::Decoder::new(compressed)
+                        .unwrap()
+                        .read_to_end(&mut body)
+                        .unwrap();
+                }
+            }
+            assert_eq!(body, expected_body);
+        }
+    }
+
+    #[test]
+    fn test_http_too_many_requests() {
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let response = HttpResponseType::TooManyRequests(
+            HttpResponseMetadata::new(HttpVersion::Http11, 123, Some(4), true),
+            30,
+            "slow".to_string(),
+        );
+        let mut bytes = vec![];
+        http.begin_request(HttpVersion::Http11, "/v2/info".to_string());
+        http.write_message(&mut bytes, &StacksHttpMessage::Response(response.clone()))
+            .unwrap();
+
+        let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+        match preamble {
+            StacksHttpPreamble::Response(ref resp) => {
+                assert_eq!(resp.status_code, 429);
+                assert_eq!(resp.headers.get("retry-after"), Some(&"30".to_string()));
+            }
+            StacksHttpPreamble::Request(_) => {
+                panic!("parsed a request");
+            }
+        }
+        let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+        assert_eq!(message, StacksHttpMessage::Response(response));
+    }
+
+    #[test]
+    fn test_http_auth_token() {
+        assert_eq!(
+            HttpAuthToken::from_header("Bearer abc123"),
+            Some(HttpAuthToken("abc123".to_string()))
+        );
+        assert_eq!(
+            HttpAuthToken::from_header("bearer  abc123 "),
+            Some(HttpAuthToken("abc123".to_string()))
+        );
+        assert_eq!(HttpAuthToken::from_header("Basic abc123"), None);
+        assert_eq!(HttpAuthToken::from_header("Bearer "), None);
+        assert_eq!(HttpAuthToken::from_header("abc"), None);
+
+        let token = HttpAuthToken("abc123".to_string());
+        assert!(token.matches("abc123"));
+        assert!(!token.matches("abc124"));
+        assert!(!token.matches("abc1234"));
+        assert!(!format!("{:?}", &token).contains("abc123"));
+
+        let request = "GET /v2/admin/peers HTTP/1.1\r\nHost: localhost:6270\r\nAuthorization: Bearer abc123\r\n\r\n";
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(req) => {
+                assert_eq!(req.metadata().auth_token, Some(token));
+                assert_eq!(req.required_role(), RPCRole::Monitor);
+            }
+            _ => panic!("Expected a request"),
+        }
+    }
+
+    #[test]
+    fn test_http_cors_policy() {
+        let default_policy = HttpCorsPolicy::default();
+        assert_eq!(default_policy.allow_origin(None), Some("*".to_string()));
+        assert_eq!(
+            default_policy.allow_origin(Some("https://app.example.com")),
+            Some("*".to_string())
+        );
+
+        let policy = HttpCorsPolicy {
+            allowed_origins: vec!["https://app.example.com".to_string()],
+            allowed_methods: vec!["GET".to_string(), "OPTIONS".to_string()],
+            allowed_headers: vec!["content-type".to_string()],
+            max_age: 600,
+        };
+        assert_eq!(
+            policy.allow_origin(Some("https://APP.example.com")),
+            Some("https://APP.example.com".to_string())
+        );
+        assert_eq!(policy.allow_origin(Some("https://evil.example.com")), None);
+        assert_eq!(policy.allow_origin(None), None);
+
+        let no_cors = HttpCorsPolicy {
+            allowed_origins: vec![],
+            ..HttpCorsPolicy::default()
+        };
+        assert_eq!(no_cors.allow_origin(Some("https://app.example.com")), None);
+
+        let send_with_origin = |request_type: &str, origin: Option<&str>| {
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            http.cors_policy = policy.clone();
+            let mut md = HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true);
+            md.client_origin = origin.map(|o| o.to_string());
+            let response = if request_type == "OPTIONS" {
+                HttpResponseType::OptionsPreflight(md)
+            } else {
+                HttpResponseType::NotFound(md, "not found".to_string())
+            };
+            let mut bytes = vec![];
+            response.send(&mut http, &mut bytes).unwrap();
+            String::from_utf8(bytes).unwrap()
+        };
+
+        // preflight from an allowed origin
+        let txt = send_with_origin("OPTIONS", Some("https://app.example.com"));
+        assert!(txt
+            .find("Access-Control-Allow-Origin: https://app.example.com\r\n")
+            .is_some());
+        assert!(txt.find("Vary: Origin\r\n").is_some());
+        assert!(txt
+            .find("Access-Control-Allow-Methods: GET, OPTIONS\r\n")
+            .is_some());
+        assert!(txt
+            .find("Access-Control-Allow-Headers: content-type\r\n")
+            .is_some());
+        assert!(txt.find("Access-Control-Max-Age: 600\r\n").is_some());
+
+        // ordinary response to an allowed origin
+        let txt = send_with_origin("GET", Some("https://app.example.com"));
+        assert!(txt
+            .find("Access-Control-Allow-Origin: https://app.example.com\r\n")
+            .is_some());
+        assert!(txt.find("Access-Control-Allow-Methods").is_none());
+
+        // nothing for other origins, or same-origin requests
+        for origin in [Some("https://evil.example.com"), None].iter() {
+            let txt = send_with_origin("OPTIONS", *origin);
+            assert!(txt.find("Access-Control-").is_none());
+        }
+    }
+
     #[test]
     fn test_http_headers_too_big() {
         let bad_header_value = std::iter::repeat("A")
@@ -6425,6 +9210,419 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_http_parse_tip_request_query() {
+        let tip = StacksBlockId::from_hex(
+            "7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392",
+        )
+        .unwrap();
+        assert_eq!(
+            HttpRequestType::get_tip_request_query(Some(
+                "tip=7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392"
+            )),
+            TipRequest::SpecificTip(tip.clone())
+        );
+        assert_eq!(
+            HttpRequestType::get_tip_request_query(Some("proof=0&tip=unconfirmed")),
+            TipRequest::UseLatestUnconfirmedTip
+        );
+        assert_eq!(
+            HttpRequestType::get_tip_request_query(Some("tip=bad")),
+            TipRequest::UseLatestAnchoredTip
+        );
+        assert_eq!(
+            HttpRequestType::get_tip_request_query(None),
+            TipRequest::UseLatestAnchoredTip
+        );
+
+        // the query string round-trips
+        for (tip_req, with_proof) in vec![
+            (TipRequest::UseLatestAnchoredTip, true),
+            (TipRequest::UseLatestUnconfirmedTip, false),
+            (TipRequest::UseLatestUnconfirmedTip, true),
+            (TipRequest::SpecificTip(tip), false),
+        ] {
+            let query_string = HttpRequestType::make_tip_request_query_string(&tip_req, with_proof);
+            let query = if query_string.len() > 0 {
+                Some(&query_string[1..])
+            } else {
+                None
+            };
+            assert_eq!(HttpRequestType::get_tip_request_query(query), tip_req);
+            assert_eq!(HttpRequestType::get_proof_query(query), with_proof);
+        }
+    }
+
+    #[test]
+    fn test_http_parse_address_transactions() {
+        let principal = StacksAddress {
+            version: 1,
+            bytes: Hash160([0x11; 20]),
+        }
+        .to_account_principal();
+
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let request_txt = format!("GET /v2/addresses/{}/transactions?offset=10&limit=20&tip=7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Length: 0\r\n\r\n", &principal);
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_txt.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::GetAddressTransactions(
+                _,
+                parsed_principal,
+                tip_opt,
+                offset,
+                limit,
+            )) => {
+                assert_eq!(parsed_principal, principal);
+                assert_eq!(
+                    tip_opt,
+                    Some(
+                        StacksBlockId::from_hex(
+                            "7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392"
+                        )
+                        .unwrap()
+                    )
+                );
+                assert_eq!(offset, 10);
+                assert_eq!(limit, 20);
+            }
+            _ => panic!("Unexpected message: {:?}", &message),
+        }
+
+        // defaults
+        let request_txt = format!("GET /v2/addresses/{}/transactions HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Length: 0\r\n\r\n", &principal);
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_txt.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::GetAddressTransactions(
+                _,
+                _,
+                tip_opt,
+                offset,
+                limit,
+            )) => {
+                assert_eq!(tip_opt, None);
+                assert_eq!(offset, 0);
+                assert_eq!(limit, ADDRESS_TXS_PAGE_SIZE);
+            }
+            _ => panic!("Unexpected message: {:?}", &message),
+        }
+
+        // bad pagination
+        for query in &["limit=0", "limit=51", "offset=-1", "offset=abc"] {
+            let request_txt = format!("GET /v2/addresses/{}/transactions?{} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Length: 0\r\n\r\n", &principal, query);
+            let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+            let res = http.read_payload(&preamble, &request_txt.as_bytes()[offset..]);
+            assert!(res.is_err(), "{:?}", &res);
+        }
+    }
+
+    #[test]
+    fn test_http_parse_address_assets() {
+        let principal =
+            PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.tokens").unwrap();
+
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let request_txt = format!("GET /v2/addresses/{}/assets?tip=7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Length: 0\r\n\r\n", &principal);
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_txt.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::GetAddressAssets(
+                _,
+                parsed_principal,
+                tip_opt,
+            )) => {
+                assert_eq!(parsed_principal, principal);
+                assert_eq!(
+                    tip_opt,
+                    Some(
+                        StacksBlockId::from_hex(
+                            "7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392"
+                        )
+                        .unwrap()
+                    )
+                );
+            }
+            _ => panic!("Unexpected message: {:?}", &message),
+        }
+
+        let request = HttpRequestType::GetAddressAssets(
+            HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 6270)),
+            principal.clone(),
+            None,
+        );
+        assert_eq!(
+            request.request_path(),
+            format!("/v2/addresses/{}/assets", &principal)
+        );
+    }
+
+    #[test]
+    fn test_http_parse_nft_history() {
+        let contract_addr =
+            StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let request_txt = format!("GET /v2/nft_history/{}/tokens/nft/0x{}?tip=7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Length: 0\r\n\r\n", &contract_addr, Value::UInt(2).serialize());
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_txt.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::GetNftHistory(
+                _,
+                parsed_addr,
+                contract_name,
+                asset_name,
+                value,
+                tip_opt,
+            )) => {
+                assert_eq!(parsed_addr, contract_addr);
+                assert_eq!(contract_name.as_str(), "tokens");
+                assert_eq!(asset_name.as_str(), "nft");
+                assert_eq!(value, Value::UInt(2));
+                assert_eq!(
+                    tip_opt,
+                    Some(
+                        StacksBlockId::from_hex(
+                            "7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392"
+                        )
+                        .unwrap()
+                    )
+                );
+            }
+            _ => panic!("Unexpected message: {:?}", &message),
+        }
+
+        // not a Clarity value
+        let request_txt = format!("GET /v2/nft_history/{}/tokens/nft/ff HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Length: 0\r\n\r\n", &contract_addr);
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        assert!(http
+            .read_payload(&preamble, &request_txt.as_bytes()[offset..])
+            .is_err());
+
+        let request = HttpRequestType::GetNftHistory(
+            HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 6270)),
+            contract_addr.clone(),
+            "tokens".into(),
+            "nft".into(),
+            Value::UInt(2),
+            None,
+        );
+        assert_eq!(
+            request.request_path(),
+            format!(
+                "/v2/nft_history/{}/tokens/nft/{}",
+                &contract_addr,
+                Value::UInt(2).serialize()
+            )
+        );
+    }
+
+    #[test]
+    fn test_http_parse_mempool_transactions() {
+        let address = StacksAddress {
+            version: 26,
+            bytes: Hash160([0x22; 20]),
+        };
+
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let request_txt = "GET /v2/mempool/transactions?offset=5&limit=10 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Length: 0\r\n\r\n";
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_txt.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::GetMempoolTransactions(
+                _,
+                offset,
+                limit,
+            )) => {
+                assert_eq!(offset, 5);
+                assert_eq!(limit, 10);
+            }
+            _ => panic!("Unexpected message: {:?}", &message),
+        }
+
+        let request_txt = format!("GET /v2/mempool/addresses/{}/transactions HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Length: 0\r\n\r\n", &address);
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_txt.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::GetMempoolAddressTransactions(
+                _,
+                parsed_address,
+                offset,
+                limit,
+            )) => {
+                assert_eq!(parsed_address, address);
+                assert_eq!(offset, 0);
+                assert_eq!(limit, MEMPOOL_TXS_PAGE_SIZE);
+            }
+            _ => panic!("Unexpected message: {:?}", &message),
+        }
+
+        // bad pagination
+        for query in &["limit=0", "limit=51", "offset=abc"] {
+            let request_txt = format!("GET /v2/mempool/transactions?{} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Length: 0\r\n\r\n", query);
+            let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+            let res = http.read_payload(&preamble, &request_txt.as_bytes()[offset..]);
+            assert!(res.is_err(), "{:?}", &res);
+        }
+    }
+
+    #[test]
+    fn test_http_parse_transaction_simulation() {
+        let tx = make_test_transaction();
+        let mut tx_bytes = vec![];
+        tx.consensus_serialize(&mut tx_bytes).unwrap();
+        let tip = StacksBlockId::from_hex(
+            "7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392",
+        )
+        .unwrap();
+
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+
+        // octet-stream body, as sent by send()
+        let request = HttpRequestType::SimulateTransaction(
+            HttpRequestMetadata::from_host(PeerHost::from_host_port(
+                "127.0.0.1".to_string(),
+                20443,
+            )),
+            tx.clone(),
+            Some(tip.clone()),
+        );
+        let mut request_bytes = vec![];
+        request.send(&mut http, &mut request_bytes).unwrap();
+        let (preamble, offset) = http.read_preamble(&request_bytes).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_bytes[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::SimulateTransaction(
+                _,
+                parsed_tx,
+                tip_opt,
+            )) => {
+                assert_eq!(parsed_tx, tx);
+                assert_eq!(tip_opt, Some(tip.clone()));
+            }
+            _ => panic!("Unexpected message: {:?}", &message),
+        }
+
+        // JSON body
+        let body = format!("{{\"tx\":\"{}\"}}", to_hex(&tx_bytes));
+        let request_txt = format!("POST /v2/transactions/simulate HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_txt.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::SimulateTransaction(
+                _,
+                parsed_tx,
+                tip_opt,
+            )) => {
+                assert_eq!(parsed_tx, tx);
+                assert_eq!(tip_opt, None);
+            }
+            _ => panic!("Unexpected message: {:?}", &message),
+        }
+
+        // not a transaction
+        let request_txt = "POST /v2/transactions/simulate HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Type: application/json\r\nContent-Length: 13\r\n\r\n{\"tx\":\"0011\"}";
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        let res = http.read_payload(&preamble, &request_txt.as_bytes()[offset..]);
+        assert!(res.is_err(), "{:?}", &res);
+    }
+
+    #[test]
+    fn test_http_parse_call_read_only_batch() {
+        let tip = StacksBlockId::from_hex(
+            "7070f213d719143d6045e08fd80f85014a161f8bbd3a42d1251576740826a392",
+        )
+        .unwrap();
+        let addr = StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+        let call = BatchReadOnlyCall {
+            contract_id: QualifiedContractIdentifier::new(
+                addr.clone().into(),
+                ContractName::try_from("hello-world").unwrap(),
+            ),
+            function: ClarityName::try_from("get-value").unwrap(),
+            sender: addr.to_account_principal(),
+            args: vec![Value::UInt(1), Value::some(Value::Int(-2)).unwrap()],
+        };
+
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+
+        // round-trips through send()
+        let request = HttpRequestType::CallReadOnlyFunctionBatch(
+            HttpRequestMetadata::from_host(PeerHost::from_host_port(
+                "127.0.0.1".to_string(),
+                20443,
+            )),
+            vec![call.clone(), call.clone()],
+            Some(tip.clone()),
+        );
+        let mut request_bytes = vec![];
+        request.send(&mut http, &mut request_bytes).unwrap();
+        let (preamble, offset) = http.read_preamble(&request_bytes).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_bytes[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::CallReadOnlyFunctionBatch(
+                _,
+                calls,
+                tip_opt,
+            )) => {
+                assert_eq!(calls, vec![call.clone(), call.clone()]);
+                assert_eq!(tip_opt, Some(tip.clone()));
+            }
+            _ => panic!("Unexpected message: {:?}", &message),
+        }
+
+        let entry = format!(
+            "{{\"contract_address\":\"{}\",\"contract_name\":\"hello-world\",\"function_name\":\"get-value\",\"sender\":\"{}\",\"arguments\":[]}}",
+            &addr, &addr
+        );
+
+        // empty and oversized batches are rejected
+        for num_calls in [0, MAX_BATCH_READ_ONLY_CALLS + 1].iter() {
+            let body = format!(
+                "{{\"calls\":[{}]}}",
+                vec![entry.clone(); *num_calls].join(",")
+            );
+            let request_txt = format!("POST /v2/contracts/call-read-batch HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+            let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+            let res = http.read_payload(&preamble, &request_txt.as_bytes()[offset..]);
+            assert!(res.is_err(), "{:?}", &res);
+        }
+
+        // a call with a bad argument is rejected
+        let body = format!(
+            "{{\"calls\":[{},{}]}}",
+            &entry,
+            entry.replace("\"arguments\":[]", "\"arguments\":[\"zz\"]")
+        );
+        let request_txt = format!("POST /v2/contracts/call-read-batch HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+        let (preamble, offset) = http.read_preamble(request_txt.as_bytes()).unwrap();
+        let res = http.read_payload(&preamble, &request_txt.as_bytes()[offset..]);
+        assert!(res.is_err(), "{:?}", &res);
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .find("argument value of call 1")
+            .is_some());
+    }
+
     #[test]
     fn test_http_live_headers() {
         // headers pulled from prod
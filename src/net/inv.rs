@@ -153,6 +153,18 @@ impl PeerBlocksInv {
         }
     }
 
+    /// What's the highest (absolute) burn block height for which this remote neighbor has an
+    /// anchored block?  Returns None if it has none.
+    pub fn get_highest_block_height(&self) -> Option<u64> {
+        for sortition_height in (0..self.num_sortitions).rev() {
+            let block_height = self.first_block_height + sortition_height;
+            if self.has_ith_block(block_height) {
+                return Some(block_height);
+            }
+        }
+        None
+    }
+
     /// Does this remote neighbor have the ith microblock stream (for the ith sortition)?
     /// (note that block_height is the _absolute_ block height)
     pub fn has_ith_microblock_stream(&self, block_height: u64) -> bool {
@@ -2712,6 +2724,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn peerblocksinv_get_highest_block_height() {
+        let peer_inv =
+            PeerBlocksInv::new(vec![0x55, 0x77], vec![0x11, 0x22], vec![0x01], 16, 1, 12345);
+        assert_eq!(peer_inv.get_highest_block_height(), Some(12345 + 14));
+
+        let peer_inv = PeerBlocksInv::new(vec![0x00, 0x00], vec![0x00, 0x00], vec![], 16, 0, 12345);
+        assert_eq!(peer_inv.get_highest_block_height(), None);
+
+        let peer_inv = PeerBlocksInv::empty(12345);
+        assert_eq!(peer_inv.get_highest_block_height(), None);
+    }
+
     #[test]
     fn peerblocksinv_merge() {
         let peer_inv = PeerBlocksInv::new(
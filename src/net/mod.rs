@@ -16,7 +16,7 @@
 
 use std::borrow::Borrow;
 use std::cmp::PartialEq;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::From;
 use std::convert::TryFrom;
 use std::error;
@@ -33,6 +33,7 @@ use std::net::SocketAddr;
 use std::ops::Deref;
 use std::str::FromStr;
 
+use libflate;
 use rand::thread_rng;
 use rand::RngCore;
 use regex::Regex;
@@ -72,7 +73,7 @@ use util::secp256k1::MessageSignature;
 use util::secp256k1::Secp256k1PublicKey;
 use util::secp256k1::MESSAGE_SIGNATURE_ENCODED_SIZE;
 use util::strings::UrlString;
-use vm::types::TraitIdentifier;
+use vm::types::{QualifiedContractIdentifier, TraitIdentifier};
 use vm::{
     analysis::contract_interface_builder::ContractInterface, types::PrincipalData, ClarityName,
     ContractName, Value,
@@ -82,7 +83,9 @@ use crate::codec::BURNCHAIN_HEADER_HASH_ENCODED_SIZE;
 use crate::cost_estimates::FeeRateEstimate;
 use crate::types::chainstate::BlockHeaderHash;
 use crate::types::chainstate::PoxId;
-use crate::types::chainstate::{BurnchainHeaderHash, StacksAddress, StacksBlockId};
+use crate::types::chainstate::{
+    BurnchainHeaderHash, StacksAddress, StacksBlockHeader, StacksBlockId,
+};
 use crate::types::StacksPublicKeyBuffer;
 use crate::util::hash::Sha256Sum;
 use crate::vm::costs::ExecutionCost;
@@ -94,19 +97,25 @@ use core::StacksEpoch;
 
 pub mod asn;
 pub mod atlas;
+pub mod bloom;
 pub mod chat;
 pub mod codec;
+pub mod compact;
 pub mod connection;
 pub mod db;
 pub mod dns;
 pub mod download;
 pub mod http;
 pub mod inv;
+pub mod natmap;
 pub mod neighbors;
 pub mod p2p;
 pub mod poll;
 pub mod prune;
+pub mod ratelimit;
+pub mod readonly_pool;
 pub mod relay;
+pub mod reputation;
 pub mod rpc;
 pub mod server;
 
@@ -216,6 +225,8 @@ pub enum Error {
     NotFoundError,
     /// Transient error (akin to EAGAIN)
     Transient(String),
+    /// Failed to map a port on our router
+    PortMappingError(String),
 }
 
 impl From<codec_error> for Error {
@@ -313,6 +324,7 @@ impl fmt::Display for Error {
             Error::ConnectionCycle => write!(f, "Tried to connect to myself"),
             Error::NotFoundError => write!(f, "Requested data not found"),
             Error::Transient(ref s) => write!(f, "Transient network error: {}", s),
+            Error::PortMappingError(ref s) => write!(f, "Port mapping error: {}", s),
         }
     }
 }
@@ -372,6 +384,7 @@ impl error::Error for Error {
             Error::ConnectionCycle => None,
             Error::NotFoundError => None,
             Error::Transient(ref _s) => None,
+            Error::PortMappingError(ref _s) => None,
         }
     }
 }
@@ -587,6 +600,34 @@ impl PeerAddress {
             self.0[0] >= 0xfc
         }
     }
+
+    /// Is this a loopback address?  i.e. 127.0.0.0/8 (v4) or ::1 (v6)?
+    pub fn is_loopback(&self) -> bool {
+        if self.is_ipv4() {
+            self.0[12] == 127
+        } else {
+            self.0[0..15] == [0x00; 15] && self.0[15] == 0x01
+        }
+    }
+
+    /// Get the subnet containing this address, as an address with all but the first
+    /// `prefix_len` bits of the IP address cleared.  For IPv4 addresses, `prefix_len` counts
+    /// from the start of the IPv4 address, not the start of the IPv4-mapped IPv6 address.
+    pub fn subnet(&self, prefix_len: u8) -> PeerAddress {
+        let (start, max_len) = if self.is_ipv4() { (12, 32) } else { (0, 128) };
+        let prefix_len = (prefix_len as usize).min(max_len);
+        let mut bytes = self.0.clone();
+        for i in 0..(max_len / 8) {
+            let bits_kept = prefix_len.saturating_sub(i * 8).min(8);
+            let mask = if bits_kept == 0 {
+                0x00
+            } else {
+                0xffu8 << (8 - bits_kept)
+            };
+            bytes[start + i] &= mask;
+        }
+        PeerAddress(bytes)
+    }
 }
 
 pub const STACKS_PUBLIC_KEY_ENCODED_SIZE: u32 = 33;
@@ -634,6 +675,68 @@ impl FromStr for HttpContentType {
     }
 }
 
+/// supported HTTP content encodings (for compressed responses)
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub enum HttpContentEncoding {
+    Gzip,
+    Deflate,
+}
+
+impl HttpContentEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            HttpContentEncoding::Gzip => "gzip",
+            HttpContentEncoding::Deflate => "deflate",
+        }
+    }
+
+    /// Pick the encoding to use from the value of an Accept-Encoding header, if the client accepts
+    /// one we support.  gzip is preferred.
+    pub fn negotiate(accept_encoding: &str) -> Option<HttpContentEncoding> {
+        let mut gzip = None;
+        let mut deflate = None;
+        let mut any = None;
+        for entry in accept_encoding.split(',') {
+            let mut parts = entry.split(';');
+            let name = parts.next().unwrap_or("").trim().to_lowercase();
+            // "q=0" means the client refuses this encoding
+            let refused = parts.any(|param| {
+                let param = param.trim().to_lowercase();
+                param.starts_with("q=") && param[2..].parse::<f64>().unwrap_or(1.0) <= 0.0
+            });
+            match name.as_str() {
+                "gzip" | "x-gzip" => gzip = Some(!refused),
+                "deflate" => deflate = Some(!refused),
+                "*" => any = Some(!refused),
+                _ => {}
+            }
+        }
+        if gzip.or(any) == Some(true) {
+            Some(HttpContentEncoding::Gzip)
+        } else if deflate.or(any) == Some(true) {
+            Some(HttpContentEncoding::Deflate)
+        } else {
+            None
+        }
+    }
+
+    /// Compress a response body
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        match *self {
+            HttpContentEncoding::Gzip => {
+                let mut encoder = libflate::gzip::Encoder::new(vec![])?;
+                encoder.write_all(data)?;
+                encoder.finish().into_result()
+            }
+            HttpContentEncoding::Deflate => {
+                let mut encoder = libflate::zlib::Encoder::new(vec![])?;
+                encoder.write_all(data)?;
+                encoder.finish().into_result()
+            }
+        }
+    }
+}
+
 /// HTTP request preamble
 #[derive(Debug, Clone, PartialEq)]
 pub struct HttpRequestPreamble {
@@ -795,12 +898,26 @@ pub struct HandshakeData {
     pub node_public_key: StacksPublicKeyBuffer,
     pub expire_block_height: u64, // burn block height after which this node's key will be revoked,
     pub data_url: UrlString,
+    pub network_auth: Option<NetworkAuthData>, // only sent if services has the NETWORK_AUTH bit set
+}
+
+/// Proof that a node belongs to a private network: its long-lived network key's signature over
+/// its current (short-lived) node key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkAuthData {
+    pub network_public_key: StacksPublicKeyBuffer,
+    pub signature: MessageSignature,
 }
 
 #[repr(u8)]
 pub enum ServiceFlags {
     RELAY = 0x01,
     RPC = 0x02,
+    NETWORK_AUTH = 0x04,
+    BLOCK_ANNOUNCE = 0x08, // push (micro)block announcements to this node as soon as we have them
+    PRUNED = 0x10,         // this node discards old blocks and microblocks, and won't serve them
+    COMPACT_BLOCKS = 0x20, // this node understands CompactBlock, GetCompactBlockTxs, and CompactBlockTxs
+    MEMPOOL_SYNC = 0x40,   // this node understands MemPoolSync and MemPoolTxs
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -838,6 +955,63 @@ pub struct NatPunchData {
     pub nonce: u32,
 }
 
+/// Ask a peer for the pending transactions it has that we don't.  The peer replies with a
+/// MemPoolTxs message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolSyncData {
+    /// txids we already have
+    pub txids: bloom::TxidBloomFilter,
+    /// most transactions to send back
+    pub max_txs: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolTxsData {
+    pub txs: Vec<StacksTransaction>,
+}
+
+/// A transaction ID shortened to its first 6 bytes, after being hashed with a compact block's
+/// nonce
+pub struct ShortTxid(pub [u8; 6]);
+impl_array_newtype!(ShortTxid, u8, 6);
+impl_array_hexstring_fmt!(ShortTxid);
+impl_byte_array_newtype!(ShortTxid, u8, 6);
+
+/// A transaction sent in full as part of a compact block
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefilledTransaction {
+    pub index: u32,
+    pub tx: StacksTransaction,
+}
+
+/// A new Stacks block, announced as its header plus short IDs of its transactions.  A peer
+/// rebuilds the block from its mempool, and asks for whichever transactions it doesn't have with
+/// a GetCompactBlockTxs message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactBlockData {
+    pub consensus_hash: ConsensusHash,
+    pub header: StacksBlockHeader,
+    pub nonce: u64,
+    /// short IDs of the transactions that are not prefilled, in block order
+    pub short_txids: Vec<ShortTxid>,
+    /// transactions sent in full, in increasing index order
+    pub prefilled_txs: Vec<PrefilledTransaction>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetCompactBlockTxsData {
+    pub index_block_hash: StacksBlockId,
+    /// positions of the wanted transactions in the block
+    pub indexes: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactBlockTxsData {
+    pub index_block_hash: StacksBlockId,
+    /// the requested transactions, in the order they were requested
+    pub txs: Vec<StacksTransaction>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RelayData {
     pub peer: NeighborAddress,
@@ -866,6 +1040,11 @@ pub enum StacksMessageType {
     Pong(PongData),
     NatPunchRequest(u32),
     NatPunchReply(NatPunchData),
+    MemPoolSync(MemPoolSyncData),
+    MemPoolTxs(MemPoolTxsData),
+    CompactBlock(CompactBlockData),
+    GetCompactBlockTxs(GetCompactBlockTxsData),
+    CompactBlockTxs(CompactBlockTxsData),
 }
 
 /// Peer address variants
@@ -1031,6 +1210,86 @@ pub struct RPCPoxInfoData {
     pub next_reward_cycle_in: u64,
 }
 
+/// Projected stacking figures for one reward cycle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPoxCycleProjection {
+    pub id: u64,
+    pub is_pox_active: bool,
+    pub reward_phase_start_block_height: u64,
+    /// uSTX locked for this cycle so far
+    pub stacked_ustx: u64,
+    /// minimum uSTX per reward slot, given what is locked so far
+    pub min_threshold_ustx: u64,
+    pub reward_slots: u64,
+    pub reward_slots_filled: u64,
+    /// the minimum uSTX per reward slot once the requested amount is stacked as well
+    pub amount_min_threshold_ustx: Option<u64>,
+    /// how many reward slots the requested amount would earn
+    pub amount_reward_slots: Option<u64>,
+    pub amount_eligible: Option<bool>,
+}
+
+/// The data we return on GET /v2/pox/projection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPoxProjection {
+    pub burn_block_height: u64,
+    pub total_liquid_supply_ustx: u64,
+    pub amount_ustx: Option<u64>,
+    pub current_cycle: RPCPoxCycleProjection,
+    pub next_cycle: RPCPoxCycleProjection,
+}
+
+/// A reward address in a reward set, and how many reward slots it holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardSetEntry {
+    pub bitcoin_address: String,
+    pub stacks_address: String,
+    pub slots: u64,
+}
+
+/// The data we return on GET /v2/pox/reward_set/...
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardSet {
+    pub reward_cycle: u64,
+    pub reward_phase_start_block_height: u64,
+    /// the PoX anchor block chosen for this reward cycle, if any
+    pub anchor_block_hash: Option<BlockHeaderHash>,
+    /// whether we have processed the anchor block (if not, the reward set is unknown)
+    pub anchor_block_known: bool,
+    pub total_slots: u64,
+    pub entries: Vec<RPCRewardSetEntry>,
+}
+
+/// Whether a BNS name resolves, as of the chain tip it was read from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BNSNameStatus {
+    Active,
+    /// the lease has ended, but the owner can still renew it
+    GracePeriod,
+    Expired,
+    Revoked,
+}
+
+/// The data we return on GET /v2/bns/names/...
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBNSNameInfo {
+    pub name: String,
+    pub namespace: String,
+    pub owner: String,
+    pub zonefile_hash: String,
+    pub status: BNSNameStatus,
+    pub registered_at: Option<u64>,
+    pub imported_at: Option<u64>,
+    pub revoked_at: Option<u64>,
+    pub lease_started_at: Option<u64>,
+    /// None if names in this namespace never expire
+    pub lease_ending_at: Option<u64>,
+    /// the chain tip the name was resolved at
+    pub index_block_hash: StacksBlockId,
+    pub stacks_block_height: u64,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RPCFeeEstimate {
     pub fee_rate: f64,
@@ -1065,6 +1324,179 @@ pub struct RPCFeeEstimateResponse {
     pub cost_scalar_change_by_byte: f64,
 }
 
+/// Machine-readable summary of how well-synchronized this node is, as reported by GET /v2/health
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RPCHealthStatus {
+    /// The node has processed everything it knows about, and is caught up to its peers
+    #[serde(rename = "synced")]
+    Synced,
+    /// One or more peers report a higher burnchain tip than the one we have downloaded
+    #[serde(rename = "burnchain_behind")]
+    BurnchainBehind,
+    /// We have downloaded burnchain blocks that we have not yet processed into sortitions
+    #[serde(rename = "sortitions_behind")]
+    SortitionsBehind,
+    /// One or more peers have Stacks blocks for sortitions after our canonical Stacks tip
+    #[serde(rename = "stacks_behind")]
+    StacksBehind,
+}
+
+/// The data we return on GET /v2/health
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCHealthData {
+    pub status: RPCHealthStatus,
+    pub synced: bool,
+    /// height of the highest burnchain block downloaded
+    pub burn_block_height: u64,
+    /// height of the highest burnchain block processed into a sortition
+    pub sortition_height: u64,
+    /// highest burnchain block height reported by any peer
+    pub peer_burn_block_height: u64,
+    pub stacks_tip_height: u64,
+    pub stacks_tip: BlockHeaderHash,
+    pub stacks_tip_consensus_hash: ConsensusHash,
+    /// sortition height at which the canonical Stacks tip was chosen
+    pub stacks_tip_burn_height: u64,
+    /// highest sortition height for which any peer has an anchored block
+    pub peer_stacks_tip_burn_height: u64,
+    /// number of anchored blocks in the staging queue that have not been processed
+    pub staging_blocks_pending: u64,
+}
+
+/// A burnchain block on the canonical burnchain fork, by height or by header hash
+#[derive(Debug, Clone, PartialEq)]
+pub enum BurnBlockSelector {
+    Height(u64),
+    Hash(BurnchainHeaderHash),
+}
+
+/// The winning block-commit of a sortition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBlockCommitInfo {
+    pub txid: String,
+    pub vtxindex: u32,
+    pub block_header_hash: BlockHeaderHash,
+    /// burnchain block height and vtxindex of the parent block's commit
+    pub parent_block_ptr: u32,
+    pub parent_vtxindex: u16,
+    pub burn_fee: u64,
+    pub sunset_burn: u64,
+}
+
+/// The data we return on GET /v2/sortitions/height/... and /v2/sortitions/hash/...
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCSortitionInfo {
+    pub burn_block_height: u64,
+    pub burn_block_hash: String,
+    pub burn_block_timestamp: u64,
+    pub consensus_hash: ConsensusHash,
+    /// burn tokens destroyed since genesis, as of this block
+    pub total_burn: u64,
+    /// did a block-commit win a sortition in this block?
+    pub sortition: bool,
+    pub winning_block_commit: Option<RPCBlockCommitInfo>,
+    /// index block hash and height of the winning Stacks block, if we have processed it
+    pub stacks_block_id: Option<StacksBlockId>,
+    pub stacks_block_height: Option<u64>,
+    /// there was a sortition, but we have not processed the winning Stacks block (it may never
+    /// have been produced, or may not have arrived yet)
+    pub missed: bool,
+}
+
+/// Burn amounts spent by winning block-commits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBurnAmountDistribution {
+    pub count: u64,
+    pub min: u64,
+    pub max: u64,
+    pub mean: u64,
+    pub p25: u64,
+    pub median: u64,
+    pub p75: u64,
+    pub p95: u64,
+}
+
+/// A run of orphaned burnchain blocks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBurnchainReorg {
+    pub fork_height: u64,
+    pub depth: u64,
+    pub orphaned_tip: String,
+}
+
+/// The data we return on GET /v2/sortitions/stats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCSortitionStats {
+    pub start_height: u64,
+    pub end_height: u64,
+    pub num_blocks: u64,
+    pub num_sortitions: u64,
+    /// blocks without a sortition
+    pub num_flash_blocks: u64,
+    /// sortitions whose winning Stacks block we never processed
+    pub num_missed_sortitions: u64,
+    pub winning_burn_amounts: RPCBurnAmountDistribution,
+    pub reorgs: Vec<RPCBurnchainReorg>,
+}
+
+/// The data we return on GET and POST /v2/admin/log_levels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCLogLevelsData {
+    pub default_level: String,
+    pub modules: BTreeMap<String, String>,
+}
+
+/// Request body for POST /v2/admin/log_levels.  A `level` of None clears the module's override.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostLogLevelRequestBody {
+    pub module: String,
+    #[serde(default)]
+    pub level: Option<String>,
+}
+
+/// Request body for POST /v2/admin/events/replay
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostEventReplayRequestBody {
+    /// the event observer endpoint, as given in the node's config
+    pub endpoint: String,
+    pub from_height: u64,
+}
+
+/// The data we return on POST /v2/admin/events/replay
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventReplayResponse {
+    pub endpoint: String,
+    pub from_height: u64,
+    /// number of payloads queued for redelivery
+    pub replayed: u64,
+}
+
+/// A peer's reputation, as reported by GET /v2/admin/peers and the ban/unban endpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPeerReputationData {
+    /// the peer's IP address and p2p port
+    pub addr: String,
+    /// current misbehavior score; the peer is banned when it reaches the node's threshold
+    pub score: u64,
+    pub invalid_messages: u64,
+    pub stalled_downloads: u64,
+    pub protocol_violations: u64,
+    /// when the peer's ban expires, or 0 if it is not banned
+    pub denied_until: u64,
+    pub connected: bool,
+}
+
+/// Request body for POST /v2/admin/peers/ban and POST /v2/admin/peers/unban
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostPeerBanRequestBody {
+    /// the peer's IP address and p2p port
+    pub addr: String,
+    /// how long to ban the peer, in seconds.  Defaults to the node's peer_score_ban_duration.
+    /// Ignored when unbanning.
+    #[serde(default)]
+    pub duration: Option<u64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Copy, Hash)]
 #[repr(u8)]
 pub enum HttpVersion {
@@ -1077,11 +1509,84 @@ pub struct HttpRequestMetadata {
     pub version: HttpVersion,
     pub peer: PeerHost,
     pub keep_alive: bool,
+    /// compressed encoding the client will accept for the response, if any
+    pub accept_encoding: Option<HttpContentEncoding>,
+    /// value of the Origin header, if this is a cross-origin request from a browser
+    pub origin: Option<String>,
+    /// bearer token from the Authorization header, for privileged endpoints
+    pub auth_token: Option<HttpAuthToken>,
+}
+
+/// A bearer token sent by an RPC client.  It is never printed.
+#[derive(Clone, PartialEq, Hash)]
+pub struct HttpAuthToken(pub String);
+
+impl fmt::Debug for HttpAuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HttpAuthToken(<redacted>)")
+    }
+}
+
+impl HttpAuthToken {
+    /// Extract the token from an `Authorization: Bearer <token>` header value
+    pub fn from_header(value: &str) -> Option<HttpAuthToken> {
+        let value = value.trim();
+        if value.len() < 7 || !value[0..7].eq_ignore_ascii_case("bearer ") {
+            return None;
+        }
+        let token = value[7..].trim();
+        if token.len() == 0 {
+            return None;
+        }
+        Some(HttpAuthToken(token.to_string()))
+    }
+
+    /// Compare against a configured token without leaking where they differ through timing
+    pub fn matches(&self, expected: &str) -> bool {
+        let ours = self.0.as_bytes();
+        let theirs = expected.as_bytes();
+        if ours.len() != theirs.len() {
+            return false;
+        }
+        ours.iter()
+            .zip(theirs.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+    }
+}
+
+/// What a client must be allowed to do in order to use an RPC endpoint.  Each role includes the
+/// ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RPCRole {
+    /// open to everyone
+    Public,
+    /// reads of sensitive node data, like peer reputations or raw mempool dumps
+    Monitor,
+    /// requests that change the node's state, like banning peers or replaying events
+    Admin,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MapEntryResponse {
     pub data: String,
+    /// the entry in the canonical JSON encoding (see `vm::types::json`)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_json: Option<serde_json::Value>,
+    #[serde(rename = "proof")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marf_proof: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataVarResponse {
+    pub data: String,
+    /// the value in the canonical JSON encoding (see `vm::types::json`)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_json: Option<serde_json::Value>,
     #[serde(rename = "proof")]
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1103,15 +1608,98 @@ pub struct GetIsTraitImplementedResponse {
     pub is_implemented: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraitFunctionStatus {
+    Compliant,
+    Missing,
+    BadArguments,
+    BadReturnType,
+}
+
+/// How one function of a trait is matched by a contract
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraitFunctionConformance {
+    pub name: String,
+    pub status: TraitFunctionStatus,
+    pub expected_args: Vec<String>,
+    pub expected_returns: String,
+    /// the contract's function's signature, if it has a public or read-only function by this name
+    pub args: Option<Vec<String>>,
+    pub returns: Option<String>,
+}
+
+/// The data we return on GET /v2/traits/conformance/...
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraitConformanceResponse {
+    /// the contract declares the trait with `impl-trait`
+    pub explicitly_implemented: bool,
+    /// every function of the trait is matched by the contract
+    pub conforms: bool,
+    pub functions: Vec<TraitFunctionConformance>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CallReadOnlyResponse {
     pub okay: bool,
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<String>,
+    /// the result in the canonical JSON encoding (see `vm::types::json`)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_json: Option<serde_json::Value>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    /// the budget the call ran under, if it failed by exceeding it
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits: Option<ReadOnlyCallLimits>,
+}
+
+/// The budget a read-only call runs under
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadOnlyCallLimits {
+    pub cost: ExecutionCost,
+    /// wall-clock budget, in milliseconds (0 = unlimited)
+    pub time_limit_ms: u64,
+    /// largest serialized result, in bytes (0 = unlimited)
+    pub max_result_size: u64,
+}
+
+/// One call's outcome in POST /v2/contracts/call-read-batch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallReadOnlyBatchEntry {
+    pub okay: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_json: Option<serde_json::Value>,
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cause: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits: Option<ReadOnlyCallLimits>,
+    /// what evaluating the call cost
+    pub cost: ExecutionCost,
+}
+
+/// The data we return on POST /v2/contracts/call-read-batch.  All calls are evaluated at the
+/// same chain tip, and results are in the same order as the calls.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallReadOnlyBatchResponse {
+    pub tip: StacksBlockId,
+    pub results: Vec<CallReadOnlyBatchEntry>,
+    /// what the whole batch cost
+    pub total_cost: ExecutionCost,
+    /// index of the call that used up the batch's budget, if any did
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_exhausted_at: Option<usize>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1126,6 +1714,186 @@ pub struct AccountEntryResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub nonce_proof: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub unlock_schedule: Option<Vec<AccountUnlockEntry>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountUnlockKind {
+    /// genesis lockup; `unlock_height` is a Stacks block height
+    Vesting,
+    /// PoX lock; `unlock_height` is a burnchain block height
+    Pox,
+}
+
+/// One tranche of an account's locked STX, as reported by GET /v2/accounts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountUnlockEntry {
+    pub kind: AccountUnlockKind,
+    pub amount: String,
+    pub unlock_height: u64,
+    pub unlocked: bool,
+}
+
+/// Request body for POST /v2/accounts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostAccountsRequestBody {
+    pub principals: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchAccountEntry {
+    pub principal: String,
+    pub balance: String,
+    pub locked: String,
+    pub unlock_height: u64,
+    pub nonce: u64,
+}
+
+/// The data we return on POST /v2/accounts.  All accounts are read at the same chain tip.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchAccountsResponse {
+    pub tip: StacksBlockId,
+    pub accounts: Vec<BatchAccountEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressTransactionEntry {
+    pub txid: String,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+}
+
+/// The data we return on GET /v2/addresses/{principal}/transactions.  Results are newest-first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressTransactionsResponse {
+    pub principal: String,
+    pub tip: StacksBlockId,
+    pub offset: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub results: Vec<AddressTransactionEntry>,
+}
+
+/// The data we return on GET /v2/addresses/{principal}/assets.  Assets are keyed by their
+/// fully-qualified identifier (`<contract-id>::<asset-name>`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressAssetsResponse {
+    pub principal: String,
+    pub tip: StacksBlockId,
+    /// hex-encoded big-endian balances
+    pub fungible_tokens: BTreeMap<String, String>,
+    /// hex-encoded serialized Clarity values of the tokens owned
+    pub non_fungible_tokens: BTreeMap<String, Vec<String>>,
+}
+
+/// Totals over the transactions processed with a block, as reported by GET /v2/headers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockStatsResponse {
+    pub tx_count: u64,
+    /// hex-encoded big-endian amounts of microSTX
+    pub total_fees: String,
+    pub total_burn: String,
+    pub total_cost: ExecutionCost,
+}
+
+/// The data we return on GET /v2/headers/{index block hash}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockHeaderResponse {
+    pub index_block_hash: StacksBlockId,
+    pub block_hash: BlockHeaderHash,
+    pub consensus_hash: ConsensusHash,
+    pub parent_block_hash: BlockHeaderHash,
+    pub block_height: u64,
+    pub burn_header_hash: String,
+    pub burn_header_height: u32,
+    pub burn_header_timestamp: u64,
+    pub anchored_block_size: u64,
+    /// None if the block was processed before the node recorded block statistics
+    pub stats: Option<BlockStatsResponse>,
+}
+
+/// A mint, transfer, or burn of a non-fungible token, as reported by GET /v2/nft_history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NftHistoryEntry {
+    /// "mint", "transfer", or "burn"
+    pub event: String,
+    pub sender: Option<String>,
+    pub recipient: Option<String>,
+    pub txid: Option<String>,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub event_index: u32,
+}
+
+/// The data we return on GET /v2/nft_history/{address}/{contract}/{asset}/{value}.  Results are
+/// oldest-first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NftHistoryResponse {
+    pub asset_identifier: String,
+    /// hex-encoded serialized Clarity value of the token
+    pub value: String,
+    pub tip: StacksBlockId,
+    pub results: Vec<NftHistoryEntry>,
+}
+
+/// A pending transaction, as reported by the mempool inspection endpoints.  `rank` is the number of
+/// pending transactions the miner would consider before this one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolTransactionEntry {
+    pub txid: String,
+    pub tx: String,
+    pub origin_address: String,
+    pub origin_nonce: u64,
+    pub sponsor_address: String,
+    pub sponsor_nonce: u64,
+    pub tx_fee: u64,
+    pub tx_len: u64,
+    pub accept_time: u64,
+    pub fee_rate: Option<f64>,
+    pub rank: u64,
+}
+
+/// The data we return on GET /v2/mempool/transactions and
+/// GET /v2/mempool/addresses/{address}/transactions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolTransactionsResponse {
+    pub offset: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub results: Vec<MempoolTransactionEntry>,
+}
+
+/// A transaction that was removed from the mempool without being mined
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolDroppedTransactionEntry {
+    pub txid: String,
+    pub reason: String,
+    pub dropped_at: u64,
+}
+
+/// The data we return on GET /v2/mempool/dropped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolDroppedTransactionsResponse {
+    pub offset: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub results: Vec<MempoolDroppedTransactionEntry>,
+}
+
+/// The data we return on GET /v2/mempool/addresses/{address}/nonces
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolNonceStatusResponse {
+    pub address: String,
+    pub account_nonce: u64,
+    pub next_nonce: u64,
+    pub pending_nonces: Vec<u64>,
+    pub future_nonces: Vec<u64>,
+    pub missing_nonces: Vec<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1143,6 +1911,53 @@ pub struct UnconfirmedTransactionResponse {
     pub status: UnconfirmedTransactionStatus,
 }
 
+/// Where a transaction is, as reported by GET /v2/transactions/status/{txid}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TransactionStatus {
+    /// Mined into an anchored block on the canonical fork
+    Anchored {
+        index_block_hash: StacksBlockId,
+        block_height: u64,
+        confirmations: u64,
+    },
+    /// Mined into a microblock in the node's unconfirmed state
+    Microblock {
+        block_hash: BlockHeaderHash,
+        seq: u16,
+    },
+    /// Pending in the mempool
+    Mempool { accept_time: u64 },
+    /// Removed from the mempool without being mined.  `reason` is one of `ReplaceByFee`,
+    /// `ReplaceAcrossFork`, `StaleGarbageCollect`, or `TooExpensive`.
+    Dropped { reason: String, dropped_at: u64 },
+    /// The node has no record of this transaction
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionStatusResponse {
+    pub txid: String,
+    pub status: TransactionStatus,
+}
+
+/// Result of a simulated (dry-run) transaction.  `result` is the hex-encoded Clarity value the
+/// transaction evaluated to; `cause` is set instead if the transaction could not be executed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSimulationResponse {
+    pub okay: bool,
+    pub txid: String,
+    pub tip: StacksBlockId,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    pub post_condition_aborted: bool,
+    pub events: Vec<serde_json::Value>,
+    pub execution_cost: ExecutionCost,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PostTransactionRequestBody {
     pub tx: String,
@@ -1195,6 +2010,9 @@ impl HttpRequestMetadata {
             version: HttpVersion::Http11,
             peer: PeerHost::from_host_port(host, port),
             keep_alive: true,
+            accept_encoding: None,
+            origin: None,
+            auth_token: None,
         }
     }
 
@@ -1203,6 +2021,9 @@ impl HttpRequestMetadata {
             version: HttpVersion::Http11,
             peer: peer_host,
             keep_alive: true,
+            accept_encoding: None,
+            origin: None,
+            auth_token: None,
         }
     }
 
@@ -1211,6 +2032,15 @@ impl HttpRequestMetadata {
             version: preamble.version,
             peer: preamble.host.clone(),
             keep_alive: preamble.keep_alive,
+            accept_encoding: preamble
+                .headers
+                .get("accept-encoding")
+                .and_then(|value| HttpContentEncoding::negotiate(value)),
+            origin: preamble.headers.get("origin").cloned(),
+            auth_token: preamble
+                .headers
+                .get("authorization")
+                .and_then(|value| HttpAuthToken::from_header(value)),
         }
     }
 }
@@ -1221,6 +2051,30 @@ pub struct CallReadOnlyRequestBody {
     pub arguments: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct CallReadOnlyBatchRequestEntry {
+    pub contract_address: String,
+    pub contract_name: String,
+    pub function_name: String,
+    pub sender: String,
+    pub arguments: Vec<String>,
+}
+
+/// Request body for POST /v2/contracts/call-read-batch
+#[derive(Serialize, Deserialize)]
+pub struct CallReadOnlyBatchRequestBody {
+    pub calls: Vec<CallReadOnlyBatchRequestEntry>,
+}
+
+/// A decoded call from POST /v2/contracts/call-read-batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchReadOnlyCall {
+    pub contract_id: QualifiedContractIdentifier,
+    pub function: ClarityName,
+    pub sender: PrincipalData,
+    pub args: Vec<Value>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct FeeRateEstimateRequestBody {
     #[serde(default)]
@@ -1261,18 +2115,44 @@ pub struct RPCNeighborsInfo {
     pub outbound: Vec<RPCNeighbor>,
 }
 
+/// Which chain tip a request should read from, given by its `?tip=` query argument
+#[derive(Debug, Clone, PartialEq)]
+pub enum TipRequest {
+    /// no `tip` given: the canonical Stacks chain tip
+    UseLatestAnchoredTip,
+    /// `tip=unconfirmed`: the node's unconfirmed microblock state, if it has one, and the
+    /// canonical Stacks chain tip otherwise
+    UseLatestUnconfirmedTip,
+    /// `tip=<index block hash>`: any processed Stacks block
+    SpecificTip(StacksBlockId),
+}
+
 /// All HTTP request paths we support, and the arguments they carry in their paths
 #[derive(Debug, Clone, PartialEq)]
 pub enum HttpRequestType {
     GetInfo(HttpRequestMetadata),
     GetPoxInfo(HttpRequestMetadata, Option<StacksBlockId>),
+    GetPoxProjection(HttpRequestMetadata, Option<u64>, Option<StacksBlockId>),
+    GetRewardSet(HttpRequestMetadata, u64),
     GetNeighbors(HttpRequestMetadata),
     GetBlock(HttpRequestMetadata, StacksBlockId),
+    GetBlockHeader(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksIndexed(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksConfirmed(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksUnconfirmed(HttpRequestMetadata, StacksBlockId, u16),
+    GetBlockByHeight(HttpRequestMetadata, u64),
+    GetMicroblocksByHeight(HttpRequestMetadata, u64),
+    GetSortition(HttpRequestMetadata, BurnBlockSelector),
+    GetSortitionStats(HttpRequestMetadata, Option<u64>, Option<u64>),
+    GetBNSName(HttpRequestMetadata, String, String, Option<StacksBlockId>),
     GetTransactionUnconfirmed(HttpRequestMetadata, Txid),
+    GetTransactionStatus(HttpRequestMetadata, Txid),
     PostTransaction(HttpRequestMetadata, StacksTransaction, Option<Attachment>),
+    SimulateTransaction(
+        HttpRequestMetadata,
+        StacksTransaction,
+        Option<StacksBlockId>,
+    ),
     PostBlock(HttpRequestMetadata, ConsensusHash, StacksBlock),
     PostMicroblock(HttpRequestMetadata, StacksMicroblock, Option<StacksBlockId>),
     GetAccount(
@@ -1281,15 +2161,48 @@ pub enum HttpRequestType {
         Option<StacksBlockId>,
         bool,
     ),
-    GetMapEntry(
+    GetAccounts(
+        HttpRequestMetadata,
+        Vec<PrincipalData>,
+        Option<StacksBlockId>,
+    ),
+    GetAddressTransactions(
+        HttpRequestMetadata,
+        PrincipalData,
+        Option<StacksBlockId>,
+        u64,
+        u64,
+    ),
+    GetAddressAssets(HttpRequestMetadata, PrincipalData, Option<StacksBlockId>),
+    GetNftHistory(
         HttpRequestMetadata,
         StacksAddress,
         ContractName,
         ClarityName,
         Value,
         Option<StacksBlockId>,
+    ),
+    GetMapEntry(
+        HttpRequestMetadata,
+        StacksAddress,
+        ContractName,
+        ClarityName,
+        Value,
+        TipRequest,
+        bool,
+    ),
+    GetDataVar(
+        HttpRequestMetadata,
+        StacksAddress,
+        ContractName,
+        ClarityName,
+        TipRequest,
         bool,
     ),
+    GetMempoolTransactions(HttpRequestMetadata, u64, u64),
+    GetMempoolAddressTransactions(HttpRequestMetadata, StacksAddress, u64, u64),
+    GetMempoolDroppedTransactions(HttpRequestMetadata, u64, u64),
+    GetMempoolNonceStatus(HttpRequestMetadata, StacksAddress, Option<StacksBlockId>),
     FeeRateEstimate(HttpRequestMetadata, TransactionPayload, u64),
     CallReadOnlyFunction(
         HttpRequestMetadata,
@@ -1300,6 +2213,11 @@ pub enum HttpRequestType {
         Vec<Value>,
         Option<StacksBlockId>,
     ),
+    CallReadOnlyFunctionBatch(
+        HttpRequestMetadata,
+        Vec<BatchReadOnlyCall>,
+        Option<StacksBlockId>,
+    ),
     GetTransferCost(HttpRequestMetadata),
     GetContractSrc(
         HttpRequestMetadata,
@@ -1324,6 +2242,20 @@ pub enum HttpRequestType {
         TraitIdentifier,
         Option<StacksBlockId>,
     ),
+    GetTraitConformance(
+        HttpRequestMetadata,
+        StacksAddress,
+        ContractName,
+        TraitIdentifier,
+        Option<StacksBlockId>,
+    ),
+    GetHealth(HttpRequestMetadata),
+    GetLogLevels(HttpRequestMetadata),
+    PostLogLevel(HttpRequestMetadata, PostLogLevelRequestBody),
+    PostEventReplay(HttpRequestMetadata, PostEventReplayRequestBody),
+    GetPeerReputations(HttpRequestMetadata),
+    PostPeerBan(HttpRequestMetadata, PostPeerBanRequestBody),
+    PostPeerUnban(HttpRequestMetadata, PostPeerBanRequestBody),
     /// catch-all for any errors we should surface from parsing
     ClientError(HttpRequestMetadata, ClientError),
 }
@@ -1335,6 +2267,10 @@ pub struct HttpResponseMetadata {
     pub client_keep_alive: bool,
     pub request_id: u32,
     pub content_length: Option<u32>,
+    /// encoding to compress the response body with, if it's big enough
+    pub client_accept_encoding: Option<HttpContentEncoding>,
+    /// origin of a cross-origin request, to check against our CORS policy
+    pub client_origin: Option<String>,
 }
 
 impl HttpResponseMetadata {
@@ -1358,6 +2294,8 @@ impl HttpResponseMetadata {
             client_keep_alive: client_keep_alive,
             request_id: request_id,
             content_length: content_length,
+            client_accept_encoding: None,
+            client_origin: None,
         }
     }
 
@@ -1370,6 +2308,8 @@ impl HttpResponseMetadata {
             client_keep_alive: preamble.keep_alive,
             request_id: preamble.request_id,
             content_length: preamble.content_length.clone(),
+            client_accept_encoding: None,
+            client_origin: None,
         }
     }
 
@@ -1379,6 +2319,8 @@ impl HttpResponseMetadata {
             client_keep_alive: false,
             request_id: HttpResponseMetadata::make_request_id(),
             content_length: Some(0),
+            client_accept_encoding: None,
+            client_origin: None,
         }
     }
 }
@@ -1386,12 +2328,15 @@ impl HttpResponseMetadata {
 impl From<&HttpRequestType> for HttpResponseMetadata {
     fn from(req: &HttpRequestType) -> HttpResponseMetadata {
         let metadata = req.metadata();
-        HttpResponseMetadata::new(
+        let mut md = HttpResponseMetadata::new(
             metadata.version,
             HttpResponseMetadata::make_request_id(),
             None,
             metadata.keep_alive,
-        )
+        );
+        md.client_accept_encoding = metadata.accept_encoding;
+        md.client_origin = metadata.origin.clone();
+        md
     }
 }
 
@@ -1400,6 +2345,8 @@ impl From<&HttpRequestType> for HttpResponseMetadata {
 pub enum HttpResponseType {
     PeerInfo(HttpResponseMetadata, RPCPeerInfoData),
     PoxInfo(HttpResponseMetadata, RPCPoxInfoData),
+    PoxProjection(HttpResponseMetadata, RPCPoxProjection),
+    RewardSet(HttpResponseMetadata, RPCRewardSet),
     Neighbors(HttpResponseMetadata, RPCNeighborsInfo),
     Block(HttpResponseMetadata, StacksBlock),
     BlockStream(HttpResponseMetadata),
@@ -1410,16 +2357,37 @@ pub enum HttpResponseType {
     MicroblockHash(HttpResponseMetadata, BlockHeaderHash),
     TokenTransferCost(HttpResponseMetadata, u64),
     GetMapEntry(HttpResponseMetadata, MapEntryResponse),
+    GetDataVar(HttpResponseMetadata, DataVarResponse),
     CallReadOnlyFunction(HttpResponseMetadata, CallReadOnlyResponse),
+    CallReadOnlyFunctionBatch(HttpResponseMetadata, CallReadOnlyBatchResponse),
     GetAccount(HttpResponseMetadata, AccountEntryResponse),
+    GetAccounts(HttpResponseMetadata, BatchAccountsResponse),
+    GetAddressTransactions(HttpResponseMetadata, AddressTransactionsResponse),
+    GetAddressAssets(HttpResponseMetadata, AddressAssetsResponse),
+    GetBlockHeader(HttpResponseMetadata, BlockHeaderResponse),
+    GetNftHistory(HttpResponseMetadata, NftHistoryResponse),
     GetContractABI(HttpResponseMetadata, ContractInterface),
     GetContractSrc(HttpResponseMetadata, ContractSrcResponse),
     GetIsTraitImplemented(HttpResponseMetadata, GetIsTraitImplementedResponse),
+    TraitConformance(HttpResponseMetadata, TraitConformanceResponse),
     UnconfirmedTransaction(HttpResponseMetadata, UnconfirmedTransactionResponse),
+    TransactionStatus(HttpResponseMetadata, TransactionStatusResponse),
+    TransactionSimulation(HttpResponseMetadata, TransactionSimulationResponse),
+    MempoolTransactions(HttpResponseMetadata, MempoolTransactionsResponse),
+    MempoolDroppedTransactions(HttpResponseMetadata, MempoolDroppedTransactionsResponse),
+    MempoolNonceStatus(HttpResponseMetadata, MempoolNonceStatusResponse),
     GetAttachment(HttpResponseMetadata, GetAttachmentResponse),
     GetAttachmentsInv(HttpResponseMetadata, GetAttachmentsInvResponse),
     OptionsPreflight(HttpResponseMetadata),
     TransactionFeeEstimation(HttpResponseMetadata, RPCFeeEstimateResponse),
+    Health(HttpResponseMetadata, RPCHealthData),
+    SortitionInfo(HttpResponseMetadata, RPCSortitionInfo),
+    SortitionStats(HttpResponseMetadata, RPCSortitionStats),
+    BNSName(HttpResponseMetadata, RPCBNSNameInfo),
+    LogLevels(HttpResponseMetadata, RPCLogLevelsData),
+    EventReplay(HttpResponseMetadata, EventReplayResponse),
+    PeerReputations(HttpResponseMetadata, Vec<RPCPeerReputationData>),
+    PeerReputation(HttpResponseMetadata, RPCPeerReputationData),
     // peer-given error responses
     BadRequest(HttpResponseMetadata, String),
     BadRequestJSON(HttpResponseMetadata, serde_json::Value),
@@ -1427,6 +2395,8 @@ pub enum HttpResponseType {
     PaymentRequired(HttpResponseMetadata, String),
     Forbidden(HttpResponseMetadata, String),
     NotFound(HttpResponseMetadata, String),
+    /// the client must wait this many seconds before trying again
+    TooManyRequests(HttpResponseMetadata, u64, String),
     ServerError(HttpResponseMetadata, String),
     ServiceUnavailable(HttpResponseMetadata, String),
     Error(HttpResponseMetadata, u16, String),
@@ -1460,6 +2430,11 @@ pub enum StacksMessageID {
     Pong = 16,
     NatPunchRequest = 17,
     NatPunchReply = 18,
+    MemPoolSync = 19,
+    MemPoolTxs = 20,
+    CompactBlock = 21,
+    GetCompactBlockTxs = 22,
+    CompactBlockTxs = 23,
     Reserved = 255,
 }
 
@@ -1551,6 +2526,21 @@ pub const ARRAY_MAX_LEN: u32 = u32::MAX;
 // maximum number of neighbors in a NeighborsData
 pub const MAX_NEIGHBORS_DATA_LEN: u32 = 128;
 
+// maximum number of principals that can be queried in one POST /v2/accounts
+pub const MAX_BATCH_ACCOUNTS: usize = 1024;
+
+// maximum number of calls that can be evaluated in one POST /v2/contracts/call-read-batch
+pub const MAX_BATCH_READ_ONLY_CALLS: usize = 32;
+
+// default and maximum number of results in one page of GET /v2/addresses/{principal}/transactions
+pub const ADDRESS_TXS_PAGE_SIZE: u64 = 50;
+
+// default and maximum number of results in one page of the mempool inspection endpoints
+pub const MEMPOOL_TXS_PAGE_SIZE: u64 = 50;
+
+// default and maximum number of burnchain blocks covered by one GET /v2/sortitions/stats
+pub const SORTITION_STATS_MAX_RANGE: u64 = 2016;
+
 // number of peers to relay to, depending on outbound or inbound
 pub const MAX_BROADCAST_OUTBOUND_RECEIVERS: usize = 8;
 pub const MAX_BROADCAST_INBOUND_RECEIVERS: usize = 16;
@@ -1564,6 +2554,22 @@ pub const GETPOXINV_MAX_BITLEN: u64 = 4096;
 #[cfg(test)]
 pub const GETPOXINV_MAX_BITLEN: u64 = 8;
 
+// maximum number of transactions that can be sent in one MemPoolTxs message
+pub const MEMPOOL_SYNC_MAX_TXS: u32 = 256;
+
+// maximum number of txids to put into a MemPoolSync bloom filter, and the false-positive rate to
+// size it for
+pub const MEMPOOL_SYNC_MAX_DIGEST_TXIDS: u64 = 32768;
+pub const MEMPOOL_SYNC_BLOOM_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+// maximum number of transactions in a compact block
+pub const COMPACT_BLOCK_MAX_TXS: u32 = 32768;
+
+// how many blocks we announced as compact blocks to remember, so we can send their transactions
+// to peers, and how many compact blocks we will rebuild at once
+pub const MAX_RECENT_COMPACT_BLOCKS: usize = 8;
+pub const MAX_PARTIAL_COMPACT_BLOCKS: usize = 8;
+
 // maximum number of blocks that can be pushed at once (even if the entire message is undersized).
 // This bound is needed since it bounds the amount of I/O a peer can be asked to do to validate the
 // message.
@@ -1576,6 +2582,7 @@ impl_byte_array_message_codec!(BlockHeaderHash, 32);
 impl_byte_array_message_codec!(StacksBlockId, 32);
 impl_byte_array_message_codec!(MessageSignature, 65);
 impl_byte_array_message_codec!(PeerAddress, 16);
+impl_byte_array_message_codec!(ShortTxid, 6);
 impl_byte_array_message_codec!(StacksPublicKeyBuffer, 33);
 
 impl_byte_array_serde!(ConsensusHash);
@@ -1823,6 +2830,16 @@ impl NetworkResult {
                                 .insert(neighbor_key.clone(), vec![(message.relayers, tx_data)]);
                         }
                     }
+                    StacksMessageType::MemPoolTxs(txs_data) => {
+                        // treat these like pushed transactions, so they get stored and relayed
+                        let tx_msgs = self
+                            .pushed_transactions
+                            .entry(neighbor_key.clone())
+                            .or_insert(vec![]);
+                        for tx in txs_data.txs.into_iter() {
+                            tx_msgs.push((message.relayers.clone(), tx));
+                        }
+                    }
                     _ => {
                         // forward along
                         if let Some(messages) = self.unhandled_messages.get_mut(&neighbor_key) {
@@ -2198,6 +3215,10 @@ pub mod test {
         fn dispatch_boot_receipts(&mut self, _receipts: Vec<events::StacksTransactionReceipt>) {
             // pass
         }
+
+        fn announce_reorg(&self, _fork_change: &CanonicalForkChange) {
+            // pass
+        }
     }
 
     // describes a peer's initial configuration
@@ -0,0 +1,577 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Port mapping on home routers, so a node behind a NAT can accept inbound p2p connections.
+//!
+//! Two protocols are supported.  NAT-PMP (RFC 6886) is a small UDP protocol spoken to the
+//! default gateway.  UPnP IGD is discovered with an SSDP multicast search, and driven with SOAP
+//! requests to the router's control URL.  NAT-PMP is tried first, since it is cheaper.
+//!
+//! All requests here block, but with short timeouts.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, TcpStream, UdpSocket};
+use std::time::Duration;
+
+use net::Error as net_error;
+use util::get_epoch_time_secs;
+
+/// UDP port NAT-PMP gateways listen on
+pub const NATPMP_PORT: u16 = 5351;
+/// SSDP multicast address and port
+pub const SSDP_ADDR: &'static str = "239.255.255.250:1900";
+
+const NATPMP_OP_EXTERNAL_ADDR: u8 = 0;
+const NATPMP_OP_MAP_TCP: u8 = 2;
+const NATPMP_RESPONSE_BIT: u8 = 128;
+
+/// How long to wait on a single request to the gateway
+const PORT_MAPPING_TIMEOUT_MS: u64 = 1000;
+/// Name we give our mapping in the router's UI
+const PORT_MAPPING_DESCRIPTION: &'static str = "stacks-node";
+
+const UPNP_WAN_SERVICES: [&'static str; 2] = [
+    "urn:schemas-upnp-org:service:WANIPConnection:1",
+    "urn:schemas-upnp-org:service:WANPPPConnection:1",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PortMappingGateway {
+    /// A NAT-PMP gateway at this address
+    NatPmp(SocketAddr),
+    /// A UPnP IGD, with the control URL and service type of its WAN connection service
+    Upnp {
+        control_url: String,
+        service: String,
+    },
+}
+
+/// A port mapping we made on our router
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortMapping {
+    pub gateway: PortMappingGateway,
+    pub internal_port: u16,
+    /// our address, as seen from outside the NAT
+    pub external_addr: SocketAddr,
+    /// how long the router will keep the mapping, in seconds
+    pub lifetime: u64,
+    /// when we made (or last renewed) the mapping
+    pub mapped_at: u64,
+}
+
+impl PortMapping {
+    /// Renew mappings halfway through their lifetime
+    pub fn needs_renewal(&self, now: u64) -> bool {
+        self.mapped_at + self.lifetime / 2 <= now
+    }
+}
+
+fn port_mapping_error<E: std::fmt::Debug>(what: &str, e: E) -> net_error {
+    net_error::PortMappingError(format!("{}: {:?}", what, e))
+}
+
+/// Find the IPv4 default gateway from the kernel's routing table.  Only works on Linux.
+pub fn default_gateway() -> Option<Ipv4Addr> {
+    let routes = fs::read_to_string("/proc/net/route").ok()?;
+    parse_proc_net_route(&routes)
+}
+
+/// Find the default route's gateway in the contents of /proc/net/route.  Addresses are printed
+/// as hex words in host byte order.
+fn parse_proc_net_route(routes: &str) -> Option<Ipv4Addr> {
+    for line in routes.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 3 || fields[1] != "00000000" {
+            continue;
+        }
+        let gateway = u32::from_str_radix(fields[2], 16).ok()?;
+        if gateway == 0 {
+            continue;
+        }
+        return Some(Ipv4Addr::from(gateway.to_ne_bytes()));
+    }
+    None
+}
+
+/// The local address we'd use to reach `remote`
+fn local_ip_for(remote: &SocketAddr) -> Result<IpAddr, net_error> {
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .map_err(|e| port_mapping_error("Failed to bind UDP socket", e))?;
+    socket
+        .connect(remote)
+        .map_err(|e| port_mapping_error("Failed to route to gateway", e))?;
+    let local_addr = socket
+        .local_addr()
+        .map_err(|e| port_mapping_error("Failed to get local address", e))?;
+    Ok(local_addr.ip())
+}
+
+fn natpmp_request(gateway: &SocketAddr, request: &[u8]) -> Result<Vec<u8>, net_error> {
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .map_err(|e| port_mapping_error("Failed to bind UDP socket", e))?;
+    socket
+        .connect(gateway)
+        .map_err(|e| port_mapping_error("Failed to connect to NAT-PMP gateway", e))?;
+
+    // RFC 6886 says to retry with a doubling timeout
+    let mut timeout = PORT_MAPPING_TIMEOUT_MS / 4;
+    for _ in 0..3 {
+        socket
+            .set_read_timeout(Some(Duration::from_millis(timeout)))
+            .map_err(|e| port_mapping_error("Failed to set socket timeout", e))?;
+        socket
+            .send(request)
+            .map_err(|e| port_mapping_error("Failed to send NAT-PMP request", e))?;
+
+        let mut buf = [0u8; 16];
+        match socket.recv(&mut buf) {
+            Ok(len) => {
+                return Ok(buf[0..len].to_vec());
+            }
+            Err(_) => {
+                timeout *= 2;
+            }
+        }
+    }
+    Err(net_error::PortMappingError(
+        "NAT-PMP gateway did not respond".to_string(),
+    ))
+}
+
+/// Check the header of a NAT-PMP response.  Returns the body after the result code and epoch.
+fn natpmp_check_response(opcode: u8, response: &[u8], len: usize) -> Result<&[u8], net_error> {
+    if response.len() < len {
+        return Err(net_error::PortMappingError(
+            "Short NAT-PMP response".to_string(),
+        ));
+    }
+    if response[0] != 0 || response[1] != NATPMP_RESPONSE_BIT + opcode {
+        return Err(net_error::PortMappingError(
+            "Unexpected NAT-PMP response".to_string(),
+        ));
+    }
+    let result = u16::from_be_bytes([response[2], response[3]]);
+    if result != 0 {
+        return Err(net_error::PortMappingError(format!(
+            "NAT-PMP gateway refused request with result code {}",
+            result
+        )));
+    }
+    Ok(&response[8..len])
+}
+
+fn natpmp_map_request(internal_port: u16, external_port: u16, lifetime: u32) -> Vec<u8> {
+    let mut request = vec![0, NATPMP_OP_MAP_TCP, 0, 0];
+    request.extend_from_slice(&internal_port.to_be_bytes());
+    request.extend_from_slice(&external_port.to_be_bytes());
+    request.extend_from_slice(&lifetime.to_be_bytes());
+    request
+}
+
+/// Map a TCP port with NAT-PMP.  The gateway may give us a different external port than the
+/// one we ask for.
+pub fn natpmp_map_port(
+    gateway: &SocketAddr,
+    internal_port: u16,
+    lifetime: u64,
+) -> Result<PortMapping, net_error> {
+    let response = natpmp_request(gateway, &[0, NATPMP_OP_EXTERNAL_ADDR])?;
+    let body = natpmp_check_response(NATPMP_OP_EXTERNAL_ADDR, &response, 12)?;
+    let external_ip = Ipv4Addr::new(body[0], body[1], body[2], body[3]);
+
+    let lifetime = lifetime.min(u32::MAX as u64) as u32;
+    let response = natpmp_request(
+        gateway,
+        &natpmp_map_request(internal_port, internal_port, lifetime),
+    )?;
+    let body = natpmp_check_response(NATPMP_OP_MAP_TCP, &response, 16)?;
+    let external_port = u16::from_be_bytes([body[2], body[3]]);
+    let granted_lifetime = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+
+    Ok(PortMapping {
+        gateway: PortMappingGateway::NatPmp(gateway.clone()),
+        internal_port,
+        external_addr: SocketAddr::V4(SocketAddrV4::new(external_ip, external_port)),
+        lifetime: granted_lifetime as u64,
+        mapped_at: get_epoch_time_secs(),
+    })
+}
+
+/// Get the value of an HTTP header (case-insensitive) in an SSDP response
+fn parse_ssdp_location(response: &str) -> Option<String> {
+    for line in response.lines() {
+        let mut parts = line.splitn(2, ":");
+        let name = parts.next()?.trim();
+        if name.eq_ignore_ascii_case("location") {
+            return parts.next().map(|value| value.trim().to_string());
+        }
+    }
+    None
+}
+
+/// Split an http:// URL into its host:port and path
+fn split_http_url(url: &str) -> Option<(String, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (hostport, path) = match rest.find('/') {
+        Some(i) => (&rest[0..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let hostport = if hostport.contains(':') {
+        hostport.to_string()
+    } else {
+        format!("{}:80", hostport)
+    };
+    Some((hostport, path.to_string()))
+}
+
+/// Find the control URL of the WAN connection service in a UPnP device description.  Relative
+/// control URLs are resolved against the description's URL.
+fn parse_upnp_control_url(description: &str, location: &str) -> Option<(String, String)> {
+    for service in UPNP_WAN_SERVICES.iter() {
+        let service_tag = format!("<serviceType>{}</serviceType>", service);
+        let start = match description.find(&service_tag) {
+            Some(i) => i,
+            None => continue,
+        };
+        let rest = &description[start..];
+        let end = rest.find("</service>").unwrap_or(rest.len());
+        let rest = &rest[0..end];
+        let url_start = rest.find("<controlURL>")? + "<controlURL>".len();
+        let url_end = rest.find("</controlURL>")?;
+        let control_url = rest[url_start..url_end].trim();
+
+        let control_url = if control_url.starts_with("http://") {
+            control_url.to_string()
+        } else {
+            let (hostport, _) = split_http_url(location)?;
+            let sep = if control_url.starts_with('/') {
+                ""
+            } else {
+                "/"
+            };
+            format!("http://{}{}{}", hostport, sep, control_url)
+        };
+        return Some((control_url, service.to_string()));
+    }
+    None
+}
+
+/// Get the text inside the first <tag>...</tag> in an XML document
+fn parse_xml_value(document: &str, tag: &str) -> Option<String> {
+    let start = document.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = document[start..].find(&format!("</{}>", tag))? + start;
+    Some(document[start..end].trim().to_string())
+}
+
+/// Make a blocking HTTP/1.0 request, and return the status code and body
+fn http_request(
+    url: &str,
+    method: &str,
+    headers: &str,
+    body: &str,
+) -> Result<(u16, String), net_error> {
+    let (hostport, path) = split_http_url(url).ok_or(net_error::PortMappingError(format!(
+        "Unsupported URL {}",
+        url
+    )))?;
+    let addr = hostport
+        .parse::<SocketAddr>()
+        .map_err(|e| port_mapping_error("Invalid gateway address", e))?;
+    let timeout = Duration::from_millis(PORT_MAPPING_TIMEOUT_MS);
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)
+        .map_err(|e| port_mapping_error("Failed to connect to UPnP gateway", e))?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+        .map_err(|e| port_mapping_error("Failed to set socket timeout", e))?;
+
+    let request = format!(
+        "{} {} HTTP/1.0\r\nHost: {}\r\n{}Content-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        hostport,
+        headers,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| port_mapping_error("Failed to send UPnP request", e))?;
+
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|e| port_mapping_error("Failed to read UPnP response", e))?;
+
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or(net_error::PortMappingError(
+            "Malformed UPnP response".to_string(),
+        ))?;
+    let body = match response.find("\r\n\r\n") {
+        Some(i) => response[i + 4..].to_string(),
+        None => String::new(),
+    };
+    Ok((status, body))
+}
+
+fn upnp_soap_request(
+    control_url: &str,
+    service: &str,
+    action: &str,
+    args: &str,
+) -> Result<String, net_error> {
+    let body = format!(
+        "<?xml version=\"1.0\"?>\r\n<s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\"><s:Body><u:{} xmlns:u=\"{}\">{}</u:{}></s:Body></s:Envelope>",
+        action, service, args, action
+    );
+    let headers = format!(
+        "Content-Type: text/xml; charset=\"utf-8\"\r\nSOAPAction: \"{}#{}\"\r\n",
+        service, action
+    );
+    let (status, response) = http_request(control_url, "POST", &headers, &body)?;
+    if status != 200 {
+        return Err(net_error::PortMappingError(format!(
+            "UPnP {} failed with status {}: {}",
+            action,
+            status,
+            parse_xml_value(&response, "errorDescription").unwrap_or_default()
+        )));
+    }
+    Ok(response)
+}
+
+/// Find a UPnP internet gateway device with an SSDP search, and return its WAN connection
+/// service's control URL and service type.
+pub fn upnp_discover() -> Result<PortMappingGateway, net_error> {
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .map_err(|e| port_mapping_error("Failed to bind UDP socket", e))?;
+    socket
+        .set_read_timeout(Some(Duration::from_millis(PORT_MAPPING_TIMEOUT_MS)))
+        .map_err(|e| port_mapping_error("Failed to set socket timeout", e))?;
+
+    let search = "M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nMAN: \"ssdp:discover\"\r\nMX: 1\r\nST: urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\n\r\n";
+    socket
+        .send_to(search.as_bytes(), SSDP_ADDR)
+        .map_err(|e| port_mapping_error("Failed to send SSDP search", e))?;
+
+    let mut buf = [0u8; 2048];
+    let len = socket
+        .recv(&mut buf)
+        .map_err(|e| port_mapping_error("No UPnP gateway found", e))?;
+    let response = String::from_utf8_lossy(&buf[0..len]);
+    let location = parse_ssdp_location(&response).ok_or(net_error::PortMappingError(
+        "SSDP response has no location".to_string(),
+    ))?;
+
+    let (_, description) = http_request(&location, "GET", "", "")?;
+    let (control_url, service) = parse_upnp_control_url(&description, &location).ok_or(
+        net_error::PortMappingError("UPnP gateway has no WAN connection service".to_string()),
+    )?;
+    Ok(PortMappingGateway::Upnp {
+        control_url,
+        service,
+    })
+}
+
+/// Map a TCP port with UPnP.  We ask for the same external port as our internal port.
+pub fn upnp_map_port(
+    control_url: &str,
+    service: &str,
+    internal_port: u16,
+    lifetime: u64,
+) -> Result<PortMapping, net_error> {
+    let (hostport, _) = split_http_url(control_url).ok_or(net_error::PortMappingError(format!(
+        "Unsupported URL {}",
+        control_url
+    )))?;
+    let gateway_addr = hostport
+        .parse::<SocketAddr>()
+        .map_err(|e| port_mapping_error("Invalid gateway address", e))?;
+    let local_ip = local_ip_for(&gateway_addr)?;
+
+    let response = upnp_soap_request(control_url, service, "GetExternalIPAddress", "")?;
+    let external_ip = parse_xml_value(&response, "NewExternalIPAddress")
+        .and_then(|ip| ip.parse::<IpAddr>().ok())
+        .ok_or(net_error::PortMappingError(
+            "UPnP gateway did not give its external address".to_string(),
+        ))?;
+
+    let args = format!(
+        "<NewRemoteHost></NewRemoteHost><NewExternalPort>{}</NewExternalPort><NewProtocol>TCP</NewProtocol><NewInternalPort>{}</NewInternalPort><NewInternalClient>{}</NewInternalClient><NewEnabled>1</NewEnabled><NewPortMappingDescription>{}</NewPortMappingDescription><NewLeaseDuration>{}</NewLeaseDuration>",
+        internal_port, internal_port, local_ip, PORT_MAPPING_DESCRIPTION, lifetime
+    );
+    upnp_soap_request(control_url, service, "AddPortMapping", &args)?;
+
+    Ok(PortMapping {
+        gateway: PortMappingGateway::Upnp {
+            control_url: control_url.to_string(),
+            service: service.to_string(),
+        },
+        internal_port,
+        external_addr: SocketAddr::new(external_ip, internal_port),
+        lifetime,
+        mapped_at: get_epoch_time_secs(),
+    })
+}
+
+/// Map a TCP port on our router.  Tries NAT-PMP on the given (or default) gateway first, and then
+/// UPnP.
+pub fn map_port(
+    gateway: Option<IpAddr>,
+    internal_port: u16,
+    lifetime: u64,
+) -> Result<PortMapping, net_error> {
+    let gateway = gateway.or_else(|| default_gateway().map(IpAddr::V4));
+    if let Some(gateway_ip) = gateway {
+        let gateway_addr = SocketAddr::new(gateway_ip, NATPMP_PORT);
+        match natpmp_map_port(&gateway_addr, internal_port, lifetime) {
+            Ok(mapping) => {
+                return Ok(mapping);
+            }
+            Err(e) => {
+                debug!(
+                    "NAT-PMP port mapping via {} failed: {:?}",
+                    &gateway_addr, &e
+                );
+            }
+        }
+    }
+
+    match upnp_discover()? {
+        PortMappingGateway::Upnp {
+            control_url,
+            service,
+        } => upnp_map_port(&control_url, &service, internal_port, lifetime),
+        PortMappingGateway::NatPmp(_) => unreachable!(),
+    }
+}
+
+/// Renew a port mapping with the gateway that made it
+pub fn renew_port_mapping(mapping: &PortMapping, lifetime: u64) -> Result<PortMapping, net_error> {
+    match mapping.gateway {
+        PortMappingGateway::NatPmp(ref gateway_addr) => {
+            natpmp_map_port(gateway_addr, mapping.internal_port, lifetime)
+        }
+        PortMappingGateway::Upnp {
+            ref control_url,
+            ref service,
+        } => upnp_map_port(control_url, service, mapping.internal_port, lifetime),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_parse_proc_net_route() {
+        let routes =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+                      eth0\t0000A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+                      eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+        assert_eq!(
+            parse_proc_net_route(routes),
+            Some(Ipv4Addr::new(192, 168, 1, 1))
+        );
+        assert_eq!(parse_proc_net_route(""), None);
+    }
+
+    #[test]
+    fn test_parse_upnp_description() {
+        let response = "HTTP/1.1 200 OK\r\nCACHE-CONTROL: max-age=120\r\nLocation: http://192.168.1.1:5000/rootDesc.xml\r\nST: urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\n\r\n";
+        let location = parse_ssdp_location(response).unwrap();
+        assert_eq!(location, "http://192.168.1.1:5000/rootDesc.xml");
+
+        let description = "<root><device><serviceList>\
+            <service><serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType><controlURL>/ctl/L3F</controlURL></service>\
+            <service><serviceType>urn:schemas-upnp-org:service:WANIPConnection:1</serviceType><controlURL>/ctl/IPConn</controlURL></service>\
+            </serviceList></device></root>";
+        assert_eq!(
+            parse_upnp_control_url(description, &location),
+            Some((
+                "http://192.168.1.1:5000/ctl/IPConn".to_string(),
+                "urn:schemas-upnp-org:service:WANIPConnection:1".to_string()
+            ))
+        );
+        assert_eq!(parse_upnp_control_url("<root></root>", &location), None);
+
+        let soap = "<s:Envelope><s:Body><u:GetExternalIPAddressResponse><NewExternalIPAddress>203.0.113.7</NewExternalIPAddress></u:GetExternalIPAddressResponse></s:Body></s:Envelope>";
+        assert_eq!(
+            parse_xml_value(soap, "NewExternalIPAddress"),
+            Some("203.0.113.7".to_string())
+        );
+    }
+
+    #[test]
+    fn test_natpmp_map_port() {
+        // a fake NAT-PMP gateway that maps port 20444 to 30444
+        let gateway = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let gateway_addr = gateway.local_addr().unwrap();
+        let gateway_thread = thread::spawn(move || {
+            let mut buf = [0u8; 16];
+            let (len, client) = gateway.recv_from(&mut buf).unwrap();
+            assert_eq!(&buf[0..len], &[0, NATPMP_OP_EXTERNAL_ADDR]);
+            gateway
+                .send_to(&[0, 128, 0, 0, 0, 0, 0, 1, 203, 0, 113, 7], &client)
+                .unwrap();
+
+            let (len, client) = gateway.recv_from(&mut buf).unwrap();
+            assert_eq!(&buf[0..len], &natpmp_map_request(20444, 20444, 3600)[..]);
+            let mut response = vec![0, 130, 0, 0, 0, 0, 0, 2];
+            response.extend_from_slice(&20444u16.to_be_bytes());
+            response.extend_from_slice(&30444u16.to_be_bytes());
+            response.extend_from_slice(&1800u32.to_be_bytes());
+            gateway.send_to(&response, &client).unwrap();
+        });
+
+        let mapping = natpmp_map_port(&gateway_addr, 20444, 3600).unwrap();
+        gateway_thread.join().unwrap();
+
+        assert_eq!(mapping.gateway, PortMappingGateway::NatPmp(gateway_addr));
+        assert_eq!(
+            mapping.external_addr,
+            "203.0.113.7:30444".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(mapping.lifetime, 1800);
+        assert!(!mapping.needs_renewal(mapping.mapped_at + 899));
+        assert!(mapping.needs_renewal(mapping.mapped_at + 900));
+    }
+
+    #[test]
+    fn test_natpmp_refused() {
+        let gateway = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let gateway_addr = gateway.local_addr().unwrap();
+        let gateway_thread = thread::spawn(move || {
+            let mut buf = [0u8; 16];
+            let (_, client) = gateway.recv_from(&mut buf).unwrap();
+            // result code 2: not authorized
+            gateway
+                .send_to(&[0, 128, 0, 2, 0, 0, 0, 1, 0, 0, 0, 0], &client)
+                .unwrap();
+        });
+
+        match natpmp_map_port(&gateway_addr, 20444, 3600) {
+            Err(net_error::PortMappingError(_)) => {}
+            res => panic!("Expected a port mapping error, got {:?}", &res),
+        }
+        gateway_thread.join().unwrap();
+    }
+}
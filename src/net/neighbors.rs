@@ -31,12 +31,15 @@ use net::connection::ReplyHandleP2P;
 
 use net::db::LocalPeer;
 
+use net::dns::DNSClient;
+
 use net::p2p::*;
 
 use util::db::DBConn;
 use util::db::DBTx;
 use util::db::Error as db_error;
 
+use util::secp256k1::Secp256k1PrivateKey;
 use util::secp256k1::Secp256k1PublicKey;
 
 use std::cmp;
@@ -53,6 +56,7 @@ use burnchains::PublicKey;
 
 use rand::prelude::*;
 use rand::thread_rng;
+use util::get_epoch_time_ms;
 use util::get_epoch_time_secs;
 use util::hash::*;
 use util::log;
@@ -1736,6 +1740,114 @@ impl PeerNetwork {
         Ok(neighbors)
     }
 
+    /// If we know of no peers besides our bootstrap peers, resolve our DNS seeds and add the
+    /// addresses they return to the frontier, so the walk has more places to start from.  We
+    /// don't know these peers' public keys yet, so they get a placeholder key until we handshake
+    /// with them.  Seeds are only consulted once.
+    pub fn bootstrap_from_dns_seeds(
+        &mut self,
+        dns_client: &mut DNSClient,
+    ) -> Result<(), net_error> {
+        if self.dns_seeds_done {
+            return Ok(());
+        }
+
+        let pending = match self.dns_seed_lookups.take() {
+            Some(pending) => pending,
+            None => {
+                if self.connection_opts.dns_seeds.len() == 0
+                    || PeerDB::get_num_discovered_peers(
+                        self.peerdb.conn(),
+                        self.local_peer.network_id,
+                    )? > 0
+                {
+                    self.dns_seeds_done = true;
+                    return Ok(());
+                }
+
+                let mut pending = vec![];
+                for (host, port) in self.connection_opts.dns_seeds.iter() {
+                    debug!("{:?}: Resolve DNS seed {}:{}", &self.local_peer, host, port);
+                    match dns_client.queue_lookup(
+                        host,
+                        *port,
+                        get_epoch_time_ms() + self.connection_opts.dns_timeout,
+                    ) {
+                        Ok(_) => pending.push((host.clone(), *port)),
+                        Err(e) => {
+                            warn!("Failed to queue lookup for DNS seed {}: {:?}", host, &e);
+                        }
+                    }
+                }
+                pending
+            }
+        };
+
+        dns_client.try_recv()?;
+
+        let mut still_pending = vec![];
+        let mut addrs = vec![];
+        for (host, port) in pending.into_iter() {
+            match dns_client.poll_lookup(&host, port) {
+                Ok(Some(response)) => match response.result {
+                    Ok(mut seed_addrs) => {
+                        debug!(
+                            "{:?}: DNS seed {}:{} gave {} address(es)",
+                            &self.local_peer,
+                            &host,
+                            port,
+                            seed_addrs.len()
+                        );
+                        addrs.append(&mut seed_addrs);
+                    }
+                    Err(msg) => {
+                        warn!("Failed to resolve DNS seed {}:{}: {}", &host, port, msg);
+                    }
+                },
+                Ok(None) => {
+                    still_pending.push((host, port));
+                }
+                Err(e) => {
+                    warn!("Failed to resolve DNS seed {}:{}: {:?}", &host, port, &e);
+                }
+            }
+        }
+
+        if addrs.len() > 0 {
+            let placeholder_key = Secp256k1PublicKey::from_private(&Secp256k1PrivateKey::new());
+            let network_id = self.local_peer.network_id;
+            let mut tx = self.peerdb.tx_begin()?;
+            for addr in addrs.iter() {
+                let addrbytes = PeerAddress::from_socketaddr(addr);
+                if (addrbytes == self.local_peer.addrbytes && addr.port() == self.local_peer.port)
+                    || PeerDB::get_peer(&tx, network_id, &addrbytes, addr.port())?.is_some()
+                {
+                    // don't clobber a key we already know
+                    continue;
+                }
+                let nk = NeighborKey {
+                    peer_version: self.peer_version,
+                    network_id: network_id,
+                    addrbytes: addrbytes,
+                    port: addr.port(),
+                };
+                let neighbor =
+                    Neighbor::empty(&nk, &placeholder_key, self.local_peer.private_key_expire);
+                if PeerDB::try_insert_peer(&mut tx, &neighbor)? {
+                    debug!("{:?}: Added DNS seed peer {:?}", &self.local_peer, &nk);
+                }
+            }
+            tx.commit()?;
+        }
+
+        if still_pending.len() > 0 {
+            self.dns_seed_lookups = Some(still_pending);
+        } else {
+            self.dns_seeds_done = true;
+        }
+        Ok(())
+    }
+
     /// Send off a handshake to a remote peer
     fn walk_handshake(
         &mut self,
@@ -3086,7 +3198,9 @@ mod test {
     use net::asn::*;
     use net::chat::*;
     use net::db::*;
+    use net::dns::*;
     use net::test::*;
+    use std::thread;
     use util::hash::*;
     use util::sleep_ms;
     use util::test::*;
@@ -3209,6 +3323,62 @@ mod test {
         })
     }
 
+    #[test]
+    fn test_step_walk_1_neighbor_from_dns_seed() {
+        with_timeout(600, || {
+            let mut peer_1_config = TestPeerConfig::from_port(33700);
+            let peer_2_config = TestPeerConfig::from_port(33702);
+
+            // peer 1 knows of no one, but its DNS seed resolves to peer 2
+            peer_1_config.connection_opts.dns_seeds = vec![("seed.stacks.test".to_string(), 33702)];
+
+            let mut peer_1 = TestPeer::new(peer_1_config);
+            let mut peer_2 = TestPeer::new(peer_2_config);
+
+            let (mut resolver, mut dns_client) = DNSResolver::new(10);
+            resolver.add_hardcoded(
+                "seed.stacks.test",
+                33702,
+                vec!["127.0.0.1:33702".parse::<SocketAddr>().unwrap()],
+            );
+            let dns_thread_handle = thread::spawn(move || {
+                resolver.thread_main();
+            });
+
+            let neighbor_2 = peer_2.to_neighbor();
+            let mut i = 0;
+            loop {
+                let _ = peer_1.step_dns(&mut dns_client);
+                let _ = peer_2.step();
+
+                let peer_opt = PeerDB::get_peer(
+                    peer_1.get_peerdb_conn(),
+                    neighbor_2.addr.network_id,
+                    &neighbor_2.addr.addrbytes,
+                    neighbor_2.addr.port,
+                )
+                .unwrap();
+
+                // peer 2's real public key replaced the placeholder once we handshaked
+                if let Some(p) = peer_opt {
+                    if p.public_key == neighbor_2.public_key {
+                        break;
+                    }
+                }
+                i += 1;
+            }
+
+            debug!("Completed walk round {} step(s)", i);
+            assert!(peer_1.network.dns_seeds_done);
+            assert!(peer_1
+                .network
+                .get_neighbor_stats(&neighbor_2.addr)
+                .is_some());
+
+            dns_thread_shutdown(dns_client, dns_thread_handle);
+        })
+    }
+
     #[test]
     #[ignore]
     fn test_step_walk_1_neighbor_plain_no_natpunch() {
@@ -34,6 +34,7 @@ use mio::net as mio_net;
 use rand::prelude::*;
 use rand::thread_rng;
 
+use burnchains::db::BurnchainDB;
 use burnchains::Address;
 use burnchains::Burnchain;
 use burnchains::BurnchainView;
@@ -42,12 +43,17 @@ use chainstate::burn::db::sortdb::{BlockHeaderCache, SortitionDB};
 use chainstate::burn::BlockSnapshot;
 use chainstate::stacks::db::StacksChainState;
 use chainstate::stacks::{MAX_BLOCK_LEN, MAX_TRANSACTION_LEN};
-use monitoring::{update_inbound_neighbors, update_outbound_neighbors};
+use monitoring::{
+    increment_inbound_connections_rejected_counter, update_inbound_neighbors,
+    update_outbound_neighbors,
+};
 use net::asn::ASEntry4;
 use net::atlas::AtlasDB;
 use net::atlas::{AttachmentInstance, AttachmentsDownloader};
+use net::bloom::TxidBloomFilter;
 use net::chat::ConversationP2P;
 use net::chat::NeighborStats;
+use net::compact::PartialCompactBlock;
 use net::connection::ConnectionOptions;
 use net::connection::NetworkReplyHandle;
 use net::connection::ReplyHandleHttp;
@@ -56,13 +62,17 @@ use net::db::LocalPeer;
 use net::db::PeerDB;
 use net::download::BlockDownloader;
 use net::inv::*;
+use net::natmap;
+use net::natmap::PortMapping;
 use net::neighbors::*;
 use net::poll::NetworkPollState;
 use net::poll::NetworkState;
 use net::prune::*;
+use net::ratelimit::RPCRateLimiter;
 use net::relay::RelayerStats;
 use net::relay::*;
 use net::relay::*;
+use net::reputation::{PeerMisbehavior, PeerScore};
 use net::rpc::RPCHandlerArgs;
 use net::server::*;
 use net::Error as net_error;
@@ -232,6 +242,10 @@ pub struct PeerNetwork {
     // info on the burn chain we're tracking
     pub burnchain: Burnchain,
 
+    // read-only handle to the burnchain DB, for reporting download progress.
+    // opened once the DB exists.
+    pub burnchain_db: Option<BurnchainDB>,
+
     // connection options
     pub connection_opts: ConnectionOptions,
 
@@ -285,6 +299,15 @@ pub struct PeerNetwork {
     public_ip_reply_handle: Option<ReplyHandleP2P>,
     public_ip_retries: u64,
 
+    // port mapping on our router, if we made one
+    pub port_mapping: Option<PortMapping>,
+
+    // (micro)block announcements waiting to be pushed to subscribed neighbors
+    pub scheduled_block_announcements: Vec<ScheduledBlockAnnouncement>,
+
+    // per-client RPC request budgets
+    pub rpc_rate_limiter: RPCRateLimiter,
+
     // how many loops of the state-machine have occured?
     // Used to coordinate with the chain synchronization logic to ensure that the node has at least
     // begun to download blocks after fetching the next reward cycles' sortitions.
@@ -303,6 +326,30 @@ pub struct PeerNetwork {
     antientropy_start_reward_cycle: u64,
     pub antientropy_last_push_ts: u64,
 
+    // when did we last ask a neighbor for the mempool transactions we're missing, and which
+    // neighbors (by event ID) have asked us for theirs?
+    pub mempool_sync_last_ts: u64,
+    mempool_sync_requests: HashMap<usize, MemPoolSyncData>,
+
+    // compact block relay: blocks we recently announced as compact blocks (so we can send their
+    // transactions to peers that ask), blocks we're rebuilding from compact blocks (along with
+    // when we give up on them), and compact block messages to process once we have the mempool
+    recent_compact_blocks: VecDeque<(StacksBlockId, StacksBlock)>,
+    partial_compact_blocks: HashMap<StacksBlockId, (u64, PartialCompactBlock)>,
+    pending_compact_blocks: Vec<(usize, CompactBlockData)>,
+    pending_compact_block_txs: Vec<(usize, CompactBlockTxsData)>,
+
+    // peer reputation: misbehaviors to add to peers' scores, how many invalid messages we've
+    // already counted for each conversation, and connections to drop because of a score-based or
+    // operator-requested ban
+    peer_penalties: Vec<(NeighborKey, PeerMisbehavior)>,
+    peer_msgs_err: HashMap<usize, u64>,
+    pending_disconnects: HashSet<usize>,
+
+    // DNS seeds we're still resolving, and whether or not we're done with them
+    pub dns_seed_lookups: Option<Vec<(String, u16)>>,
+    pub dns_seeds_done: bool,
+
     // pending messages (BlocksAvailable, MicroblocksAvailable, BlocksData, Microblocks) that we
     // can't process yet, but might be able to process on the next chain view update
     pub pending_messages: HashMap<usize, Vec<StacksMessage>>,
@@ -326,6 +373,7 @@ impl PeerNetwork {
         let pub_ip = connection_opts.public_ip_address.clone();
         let pub_ip_learned = pub_ip.is_none();
         local_peer.public_ip_address = pub_ip.clone();
+        local_peer.network_key = connection_opts.private_network_key.clone();
 
         if connection_opts.disable_inbound_handshakes {
             debug!("{:?}: disable inbound handshakes", &local_peer);
@@ -338,6 +386,8 @@ impl PeerNetwork {
         let first_burn_header_hash = burnchain.first_block_hash.clone();
         let first_burn_header_ts = burnchain.first_block_timestamp;
 
+        let rpc_rate_limiter = RPCRateLimiter::from_connection_options(&connection_opts);
+
         let mut network = PeerNetwork {
             peer_version: peer_version,
             epochs: epochs,
@@ -369,6 +419,7 @@ impl PeerNetwork {
             http_network_handle: 0,
 
             burnchain: burnchain,
+            burnchain_db: None,
             connection_opts: connection_opts,
 
             work_state: PeerNetworkWorkState::GetPublicIP,
@@ -406,9 +457,14 @@ impl PeerNetwork {
             public_ip_requested_at: 0,
             public_ip_learned_at: 0,
             public_ip_confirmed: false,
+            port_mapping: None,
             public_ip_reply_handle: None,
             public_ip_retries: 0,
 
+            scheduled_block_announcements: vec![],
+
+            rpc_rate_limiter: rpc_rate_limiter,
+
             num_state_machine_passes: 0,
             num_inv_sync_passes: 0,
             num_downloader_passes: 0,
@@ -418,6 +474,21 @@ impl PeerNetwork {
             antientropy_last_push_ts: 0,
             antientropy_start_reward_cycle: 0,
 
+            mempool_sync_last_ts: 0,
+            mempool_sync_requests: HashMap::new(),
+
+            recent_compact_blocks: VecDeque::new(),
+            partial_compact_blocks: HashMap::new(),
+            pending_compact_blocks: vec![],
+            pending_compact_block_txs: vec![],
+
+            peer_penalties: vec![],
+            peer_msgs_err: HashMap::new(),
+            pending_disconnects: HashSet::new(),
+
+            dns_seed_lookups: None,
+            dns_seeds_done: false,
+
             pending_messages: HashMap::new(),
 
             fault_last_disconnect: 0,
@@ -485,9 +556,113 @@ impl PeerNetwork {
             port: my_addr.port(),
         };
 
+        // tell our neighbors whether or not to push (micro)block announcements to us
+        let services = if self.connection_opts.subscribe_block_announcements {
+            self.local_peer.services | (ServiceFlags::BLOCK_ANNOUNCE as u16)
+        } else {
+            self.local_peer.services & !(ServiceFlags::BLOCK_ANNOUNCE as u16)
+        };
+        // ...and whether or not we still have old blocks to serve
+        let services = if self.connection_opts.pruned {
+            services | (ServiceFlags::PRUNED as u16)
+        } else {
+            services & !(ServiceFlags::PRUNED as u16)
+        };
+        // ...and whether or not we can receive compact block announcements
+        let services = if self.connection_opts.compact_block_relay {
+            services | (ServiceFlags::COMPACT_BLOCKS as u16)
+        } else {
+            services & !(ServiceFlags::COMPACT_BLOCKS as u16)
+        };
+        // ...and that we understand mempool sync requests
+        let services = services | (ServiceFlags::MEMPOOL_SYNC as u16);
+        if services != self.local_peer.services {
+            let mut tx = self.peerdb.tx_begin()?;
+            PeerDB::set_local_services(&mut tx, services)?;
+            tx.commit()?;
+            self.local_peer.services = services;
+        }
+
+        if self.connection_opts.enable_port_mapping {
+            match natmap::map_port(
+                self.connection_opts.port_mapping_gateway.clone(),
+                my_addr.port(),
+                self.connection_opts.port_mapping_lifetime,
+            ) {
+                Ok(mapping) => {
+                    info!(
+                        "Mapped p2p port {} to {} on {:?}",
+                        my_addr.port(),
+                        &mapping.external_addr,
+                        &mapping.gateway
+                    );
+                    self.set_port_mapping(mapping);
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to map p2p port {}; inbound peers may not reach us: {:?}",
+                        my_addr.port(),
+                        &e
+                    );
+                }
+            }
+        }
+
         Ok(())
     }
 
+    /// Use a port mapping's external address in our handshakes, unless we were given a public IP
+    /// address to use.
+    fn set_port_mapping(&mut self, mapping: PortMapping) {
+        if self.connection_opts.public_ip_address.is_none() {
+            self.local_peer.public_ip_address = Some((
+                PeerAddress::from_socketaddr(&mapping.external_addr),
+                mapping.external_addr.port(),
+            ));
+            // the router told us our address, so there's no need to go learn it
+            self.public_ip_learned = false;
+            self.public_ip_confirmed = true;
+        }
+        self.port_mapping = Some(mapping);
+    }
+
+    /// Renew our port mapping if it's halfway to expiring.  If the router won't renew it, then go
+    /// back to learning our public IP address from our peers.
+    fn renew_port_mapping(&mut self) {
+        let mapping = match self.port_mapping.take() {
+            Some(mapping) => mapping,
+            None => {
+                return;
+            }
+        };
+        if !mapping.needs_renewal(get_epoch_time_secs()) {
+            self.port_mapping = Some(mapping);
+            return;
+        }
+
+        match natmap::renew_port_mapping(&mapping, self.connection_opts.port_mapping_lifetime) {
+            Ok(new_mapping) => {
+                if new_mapping.external_addr != mapping.external_addr {
+                    info!(
+                        "Port mapping for p2p port {} moved from {} to {}",
+                        mapping.internal_port, &mapping.external_addr, &new_mapping.external_addr
+                    );
+                }
+                self.set_port_mapping(new_mapping);
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to renew port mapping for p2p port {}: {:?}",
+                    mapping.internal_port, &e
+                );
+                if self.connection_opts.public_ip_address.is_none() {
+                    self.public_ip_learned = true;
+                    self.public_ip_reset();
+                }
+            }
+        }
+    }
+
     /// Run a closure with the network state
     pub fn with_network_state<F, R>(
         peer_network: &mut PeerNetwork,
@@ -1005,6 +1180,9 @@ impl PeerNetwork {
             NetworkRequest::Broadcast(relay_hints, msg) => {
                 // pick some neighbors. Note that only some messages can be broadcasted.
                 let neighbor_keys = match msg {
+                    StacksMessageType::CompactBlock(ref data) => {
+                        self.sample_broadcast_peers(&relay_hints, data)
+                    }
                     StacksMessageType::Blocks(ref data) => {
                         // send to each neighbor that needs one
                         let mut all_neighbors = HashSet::new();
@@ -1036,6 +1214,22 @@ impl PeerNetwork {
                         return Err(net_error::InvalidMessage);
                     }
                 }?;
+
+                // neighbors that advertised compact block support get the compact form of a
+                // single new block; everyone else gets the full block.
+                let (compact_keys, full_keys): (Vec<_>, Vec<_>) = neighbor_keys
+                    .into_iter()
+                    .partition(|nk| self.neighbor_supports_compact_blocks(nk));
+                if !compact_keys.is_empty() {
+                    if let Some(compact_msg) = self.try_compact_block_message(&msg) {
+                        self.broadcast_message(compact_keys, relay_hints.clone(), compact_msg);
+                        if !full_keys.is_empty() {
+                            self.broadcast_message(full_keys, relay_hints, msg);
+                        }
+                        return Ok(());
+                    }
+                }
+                let neighbor_keys = compact_keys.into_iter().chain(full_keys).collect();
                 self.broadcast_message(neighbor_keys, relay_hints, msg);
                 Ok(())
             }
@@ -1135,6 +1329,8 @@ impl PeerNetwork {
             };
 
             disconnect.push(event_id);
+            self.peer_penalties
+                .push((neighbor_key.clone(), PeerMisbehavior::ProtocolViolation));
 
             let now = get_epoch_time_secs();
             let penalty = if let Some(neighbor_info) = neighbor_info_opt {
@@ -1174,6 +1370,156 @@ impl PeerNetwork {
         Ok(disconnect)
     }
 
+    /// Record that a peer misbehaved.  Its score is updated on the next call to
+    /// process_peer_penalties().
+    pub fn penalize_peer(&mut self, neighbor_key: &NeighborKey, misbehavior: PeerMisbehavior) {
+        debug!(
+            "{:?}: Penalize {:?} for {:?}",
+            &self.local_peer, neighbor_key, misbehavior
+        );
+        self.peer_penalties
+            .push((neighbor_key.clone(), misbehavior));
+    }
+
+    /// Event IDs of our conversations with the peer at the given address
+    pub fn find_peer_events(&self, peer_addr: &PeerAddress, peer_port: u16) -> Vec<usize> {
+        self.events
+            .iter()
+            .filter(|(nk, _)| nk.addrbytes == *peer_addr && nk.port == peer_port)
+            .map(|(_, event_id)| *event_id)
+            .collect()
+    }
+
+    /// Ban the peer at the given address until `deadline`, and disconnect from it.  Its score
+    /// starts over.
+    pub fn ban_peer_address(
+        &mut self,
+        peer_addr: &PeerAddress,
+        peer_port: u16,
+        deadline: u64,
+    ) -> Result<(), net_error> {
+        let network_id = self.local_peer.network_id;
+        let now = get_epoch_time_secs();
+        let mut tx = self.peerdb.tx_begin()?;
+        PeerDB::set_deny_peer(&mut tx, network_id, peer_addr, peer_port, deadline)?;
+        if let Some(mut score) = PeerDB::get_peer_score(&tx, network_id, peer_addr, peer_port)? {
+            score.reset(now);
+            PeerDB::set_peer_score(&mut tx, &score)?;
+        }
+        tx.commit()?;
+
+        for event_id in self.find_peer_events(peer_addr, peer_port) {
+            self.pending_disconnects.insert(event_id);
+        }
+        Ok(())
+    }
+
+    /// Lift a ban on the peer at the given address, and clear its score.  CIDR deny rules in the
+    /// config still apply.
+    pub fn unban_peer_address(
+        &mut self,
+        peer_addr: &PeerAddress,
+        peer_port: u16,
+    ) -> Result<(), net_error> {
+        let network_id = self.local_peer.network_id;
+        let now = get_epoch_time_secs();
+        let mut tx = self.peerdb.tx_begin()?;
+        PeerDB::set_deny_peer(&mut tx, network_id, peer_addr, peer_port, 0)?;
+        if let Some(mut score) = PeerDB::get_peer_score(&tx, network_id, peer_addr, peer_port)? {
+            score.reset(now);
+            PeerDB::set_peer_score(&mut tx, &score)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Count new invalid messages from each conversation, and add all recorded misbehaviors to
+    /// the peers' scores.  Peers whose scores reach the ban threshold are banned, unless they are
+    /// allowed.  Returns the event IDs to disconnect from.
+    fn process_peer_penalties(&mut self) -> Result<Vec<usize>, net_error> {
+        let mut msgs_err = HashMap::new();
+        for (event_id, convo) in self.peers.iter() {
+            let seen = *self.peer_msgs_err.get(event_id).unwrap_or(&0);
+            for _ in seen..convo.stats.msgs_err {
+                self.peer_penalties
+                    .push((convo.to_neighbor_key(), PeerMisbehavior::InvalidMessage));
+            }
+            msgs_err.insert(*event_id, convo.stats.msgs_err);
+        }
+        self.peer_msgs_err = msgs_err;
+
+        let mut disconnect: Vec<usize> = self.pending_disconnects.drain().collect();
+        if self.peer_penalties.len() == 0 {
+            return Ok(disconnect);
+        }
+
+        let now = get_epoch_time_secs();
+        let network_id = self.local_peer.network_id;
+        let threshold = self.connection_opts.peer_ban_score_threshold;
+        let decay_interval = self.connection_opts.peer_score_decay_interval;
+        let no_bans = cfg!(test) && self.connection_opts.disable_network_bans;
+
+        let mut to_ban = vec![];
+        let mut tx = self.peerdb.tx_begin()?;
+        for (neighbor_key, misbehavior) in self.peer_penalties.drain(..) {
+            let mut score = PeerDB::get_peer_score(
+                &tx,
+                network_id,
+                &neighbor_key.addrbytes,
+                neighbor_key.port,
+            )?
+            .unwrap_or(PeerScore::new(
+                network_id,
+                neighbor_key.addrbytes.clone(),
+                neighbor_key.port,
+            ));
+
+            let new_score = score.add_penalty(misbehavior, now, decay_interval);
+            if !no_bans && threshold > 0 && new_score >= threshold {
+                let allowed = match PeerDB::get_peer(
+                    &tx,
+                    network_id,
+                    &neighbor_key.addrbytes,
+                    neighbor_key.port,
+                )? {
+                    Some(neighbor) => neighbor.is_allowed(),
+                    None => false,
+                };
+                if allowed {
+                    debug!(
+                        "{:?}: Peer {:?} has score {}, but is allowed; will not ban",
+                        &self.local_peer, &neighbor_key, new_score
+                    );
+                } else {
+                    info!(
+                        "Ban peer {:?} for {}s: misbehavior score {} reached {}",
+                        &neighbor_key,
+                        self.connection_opts.peer_score_ban_duration,
+                        new_score,
+                        threshold
+                    );
+                    PeerDB::set_deny_peer(
+                        &mut tx,
+                        network_id,
+                        &neighbor_key.addrbytes,
+                        neighbor_key.port,
+                        now + self.connection_opts.peer_score_ban_duration,
+                    )?;
+                    score.reset(now);
+                    to_ban.push(neighbor_key);
+                }
+            }
+            PeerDB::set_peer_score(&mut tx, &score)?;
+        }
+        tx.commit()?;
+
+        for neighbor_key in to_ban.iter() {
+            disconnect
+                .append(&mut self.find_peer_events(&neighbor_key.addrbytes, neighbor_key.port));
+        }
+        Ok(disconnect)
+    }
+
     /// Get the neighbor if we know of it and it's public key is unexpired.
     fn lookup_peer(
         &self,
@@ -1296,6 +1642,80 @@ impl PeerNetwork {
             return Err(net_error::TooManyPeers);
         }
 
+        if !outbound {
+            self.check_inbound_host_limits(neighbor_key)?;
+        }
+
+        Ok(())
+    }
+
+    /// Count our inbound conversations with peers whose addresses fall in the same subnet as
+    /// `addrbytes`.
+    fn count_inbound_conversations_in_subnet(
+        &self,
+        addrbytes: &PeerAddress,
+        prefix_len: u8,
+    ) -> u64 {
+        let subnet = addrbytes.subnet(prefix_len);
+        let mut ret = 0;
+        for (_, convo) in self.peers.iter() {
+            if !convo.stats.outbound
+                && convo.peer_addrbytes.is_ipv4() == addrbytes.is_ipv4()
+                && convo.peer_addrbytes.subnet(prefix_len) == subnet
+            {
+                ret += 1;
+            }
+        }
+        ret
+    }
+
+    /// Enforce the per-host and per-subnet limits on inbound connections, so a handful of hosts
+    /// can't take up all of our inbound slots.  Only publicly-routable addresses are limited, and
+    /// always-allowed peers are exempt.
+    fn check_inbound_host_limits(&self, neighbor_key: &NeighborKey) -> Result<(), net_error> {
+        let addrbytes = &neighbor_key.addrbytes;
+        if addrbytes.is_loopback() || addrbytes.is_in_private_range() {
+            return Ok(());
+        }
+        if PeerDB::is_peer_always_allowed(
+            self.peerdb.conn(),
+            neighbor_key.network_id,
+            addrbytes,
+            neighbor_key.port,
+        )? {
+            return Ok(());
+        }
+
+        let (host_prefix_len, subnet_prefix_len) = if addrbytes.is_ipv4() {
+            (32, self.connection_opts.client_subnet_prefix_v4)
+        } else {
+            (128, self.connection_opts.client_subnet_prefix_v6)
+        };
+
+        if self.count_inbound_conversations_in_subnet(addrbytes, host_prefix_len)
+            >= self.connection_opts.max_clients_per_host
+        {
+            info!(
+                "{:?}: Too many inbound connections from host {:?}",
+                &self.local_peer,
+                &addrbytes.to_socketaddr(neighbor_key.port).ip()
+            );
+            increment_inbound_connections_rejected_counter("host");
+            return Err(net_error::TooManyPeers);
+        }
+
+        if self.count_inbound_conversations_in_subnet(addrbytes, subnet_prefix_len)
+            >= self.connection_opts.max_clients_per_subnet
+        {
+            info!(
+                "{:?}: Too many inbound connections from subnet of {:?}",
+                &self.local_peer,
+                &addrbytes.to_socketaddr(neighbor_key.port).ip()
+            );
+            increment_inbound_connections_rejected_counter("subnet");
+            return Err(net_error::TooManyPeers);
+        }
+
         Ok(())
     }
 
@@ -2515,6 +2935,14 @@ impl PeerNetwork {
             self.deregister_and_ban_neighbor(&broken_neighbor);
         }
 
+        let stalled_neighbors = match self.block_downloader {
+            Some(ref mut downloader) => downloader.take_stalled_neighbors(),
+            None => vec![],
+        };
+        for stalled_neighbor in stalled_neighbors.iter() {
+            self.penalize_peer(stalled_neighbor, PeerMisbehavior::StalledDownload);
+        }
+
         if done && at_chain_tip {
             debug!(
                 "{:?}: Completed downloader pass {}",
@@ -3981,6 +4409,25 @@ impl PeerNetwork {
                 // only forward to the relayer if we don't need to buffer it.
                 (to_buffer, true)
             }
+            StacksMessageType::MemPoolSync(ref data) => {
+                // answered at the end of this pass, once we have the mempool
+                self.mempool_sync_requests.insert(event_id, data.clone());
+                (false, false)
+            }
+            StacksMessageType::CompactBlock(ref data) => {
+                // rebuilt at the end of this pass, once we have the mempool
+                self.pending_compact_blocks.push((event_id, data.clone()));
+                (false, false)
+            }
+            StacksMessageType::CompactBlockTxs(ref data) => {
+                self.pending_compact_block_txs
+                    .push((event_id, data.clone()));
+                (false, false)
+            }
+            StacksMessageType::GetCompactBlockTxs(ref data) => {
+                self.handle_unsolicited_GetCompactBlockTxs(event_id, data);
+                (false, false)
+            }
             _ => (false, true),
         }
     }
@@ -4196,6 +4643,7 @@ impl PeerNetwork {
     pub fn load_local_peer(&self) -> Result<LocalPeer, net_error> {
         let mut lp = PeerDB::get_local_peer(&self.peerdb.conn())?;
         lp.public_ip_address = self.local_peer.public_ip_address.clone();
+        lp.network_key = self.local_peer.network_key.clone();
         Ok(lp)
     }
 
@@ -4212,6 +4660,21 @@ impl PeerNetwork {
         sortdb: &SortitionDB,
         chainstate: &StacksChainState,
     ) -> Result<HashMap<NeighborKey, Vec<StacksMessage>>, net_error> {
+        if self.burnchain_db.is_none() {
+            // the burnchain DB may not have existed when this network was instantiated
+            match BurnchainDB::open(&self.burnchain.get_burnchaindb_path(), false) {
+                Ok(burnchain_db) => {
+                    self.burnchain_db = Some(burnchain_db);
+                }
+                Err(e) => {
+                    debug!(
+                        "{:?}: burnchain DB is not available yet: {:?}",
+                        &self.local_peer, &e
+                    );
+                }
+            }
+        }
+
         // update burnchain snapshot if we need to (careful -- it's expensive)
         let sn = SortitionDB::get_canonical_burn_chain_tip(&sortdb.conn())?;
         let mut ret: HashMap<NeighborKey, Vec<StacksMessage>> = HashMap::new();
@@ -4306,6 +4769,19 @@ impl PeerNetwork {
         // schedule now-authenticated inbound convos for pingback
         self.schedule_network_pingbacks(unauthenticated_inbounds)?;
 
+        // keep our router forwarding inbound connections to us
+        self.renew_port_mapping();
+
+        // if we're starting from an empty frontier, find some peers to walk to
+        if let Some(ref mut dns_client) = dns_client_opt {
+            if let Err(e) = self.bootstrap_from_dns_seeds(dns_client) {
+                warn!(
+                    "{:?}: Failed to bootstrap from DNS seeds: {:?}",
+                    &self.local_peer, &e
+                );
+            }
+        }
+
         // do some Actual Work(tm)
         // do this _after_ processing new sockets, so the act of opening a socket doesn't trample
         // an already-used network ID.
@@ -4317,6 +4793,15 @@ impl PeerNetwork {
             ibd,
             network_result,
         )?;
+        // drop peers whose misbehavior scores got them banned
+        for dead in self.process_peer_penalties()? {
+            debug!(
+                "{:?}: Disconnect banned peer on event {}",
+                &self.local_peer, dead
+            );
+            self.deregister_peer(dead);
+        }
+
         if do_prune {
             // prune back our connections if it's been a while
             // (only do this if we're done with all other tasks).
@@ -4353,6 +4838,9 @@ impl PeerNetwork {
         // queue up pings to neighbors we haven't spoken to in a while
         self.queue_ping_heartbeats();
 
+        // push any (micro)block announcements that are due to subscribed neighbors
+        self.send_scheduled_block_announcements();
+
         // move conversations along
         let error_events = self.flush_relay_handles();
         for error_event in error_events {
@@ -4375,7 +4863,9 @@ impl PeerNetwork {
             self.rekey(Some(&old_local_peer));
         }
 
-        // update our relay statistics, so we know who to forward messages to
+        // drop pushed data from peers that are sending us more than their share, and update our
+        // relay statistics, so we know who to forward messages to
+        self.throttle_relayed_data(network_result);
         self.update_relayer_stats(&network_result);
 
         // finally, handle network I/O requests from other threads, and get back reply handles to them.
@@ -4399,6 +4889,349 @@ impl PeerNetwork {
         Ok(())
     }
 
+    /// Ask a random outbound neighbor for the pending transactions it has that we don't, by
+    /// sending it a bloom filter of the txids in our mempool.  Only neighbors that advertise
+    /// ServiceFlags::MEMPOOL_SYNC are asked.  The neighbor replies with a MemPoolTxs message,
+    /// whose transactions are stored like any other pushed transactions.
+    /// This lets a freshly-started node fill its mempool without waiting for new transactions to
+    /// be relayed to it.
+    fn try_request_mempool_sync(&mut self, mempool: &MemPoolDB) -> Result<(), net_error> {
+        if self.mempool_sync_last_ts + self.connection_opts.mempool_sync_interval
+            >= get_epoch_time_secs()
+        {
+            return Ok(());
+        }
+
+        let neighbor_keys: Vec<NeighborKey> = self
+            .peers
+            .values()
+            .filter(|convo| {
+                convo.is_outbound()
+                    && convo.is_authenticated()
+                    && convo.peer_services & (ServiceFlags::MEMPOOL_SYNC as u16) != 0
+            })
+            .map(|convo| convo.to_neighbor_key())
+            .collect();
+        if neighbor_keys.len() == 0 {
+            return Ok(());
+        }
+
+        self.mempool_sync_last_ts = get_epoch_time_secs();
+
+        let nk = neighbor_keys[thread_rng().gen::<usize>() % neighbor_keys.len()].clone();
+        let txids =
+            MemPoolDB::get_txids_by_fee_rate(mempool.conn(), MEMPOOL_SYNC_MAX_DIGEST_TXIDS)?;
+        let mut filter =
+            TxidBloomFilter::new(txids.len() as u64, MEMPOOL_SYNC_BLOOM_FALSE_POSITIVE_RATE);
+        for txid in txids.iter() {
+            filter.insert(txid);
+        }
+
+        debug!(
+            "{:?}: MemPoolSync: ask {:?} for transactions we don't have ({} txids in filter)",
+            &self.local_peer,
+            &nk,
+            txids.len()
+        );
+
+        let max_txs = cmp::min(
+            self.connection_opts.mempool_sync_max_txs,
+            MEMPOOL_SYNC_MAX_TXS as u64,
+        ) as u32;
+
+        // only the neighbor we ask may send us a MemPoolTxs, and only once
+        for (_, convo) in self.peers.iter_mut() {
+            convo.mempool_sync_max_txs = None;
+        }
+        if let Some(convo) = self
+            .events
+            .get(&nk)
+            .and_then(|event_id| self.peers.get_mut(event_id))
+        {
+            convo.mempool_sync_max_txs = Some(max_txs);
+        }
+
+        self.broadcast_message(
+            vec![nk],
+            vec![],
+            StacksMessageType::MemPoolSync(MemPoolSyncData {
+                txids: filter,
+                max_txs,
+            }),
+        );
+        Ok(())
+    }
+
+    /// Answer the mempool sync requests our neighbors sent us during this pass, by sending each
+    /// one the highest fee-rate pending transactions that are not in its bloom filter.  Neighbors
+    /// that don't advertise ServiceFlags::MEMPOOL_SYNC get no reply, since they would not
+    /// understand it.
+    fn answer_mempool_sync_requests(&mut self, mempool: &MemPoolDB) -> Result<(), net_error> {
+        let requests = mem::replace(&mut self.mempool_sync_requests, HashMap::new());
+        for (event_id, request) in requests.into_iter() {
+            let nk = match self.peers.get(&event_id) {
+                Some(convo) if convo.peer_services & (ServiceFlags::MEMPOOL_SYNC as u16) != 0 => {
+                    convo.to_neighbor_key()
+                }
+                _ => {
+                    continue;
+                }
+            };
+
+            let max_txs = cmp::min(request.max_txs, MEMPOOL_SYNC_MAX_TXS);
+            let txs = MemPoolDB::get_txs_missing_from(
+                mempool.conn(),
+                &request.txids,
+                max_txs as u64,
+                MAX_BLOCK_LEN as u64,
+            )?;
+
+            debug!(
+                "{:?}: MemPoolSync: send {} transactions to {:?}",
+                &self.local_peer,
+                txs.len(),
+                &nk
+            );
+
+            if txs.len() == 0 {
+                continue;
+            }
+
+            self.broadcast_message(
+                vec![nk],
+                vec![],
+                StacksMessageType::MemPoolTxs(MemPoolTxsData { txs }),
+            );
+        }
+        Ok(())
+    }
+
+    /// If compact block relay is enabled, turn a broadcast of a single block into a broadcast of
+    /// its compact block, and remember the block so we can send its transactions to the peers
+    /// that ask for them.  Returns None if the message can't be sent as a compact block.
+    fn try_compact_block_message(&mut self, msg: &StacksMessageType) -> Option<StacksMessageType> {
+        if !self.connection_opts.compact_block_relay {
+            return None;
+        }
+        let (consensus_hash, block) = match *msg {
+            StacksMessageType::Blocks(ref data) if data.blocks.len() == 1 => data.blocks[0].clone(),
+            _ => {
+                return None;
+            }
+        };
+
+        let data = CompactBlockData::from_block(&consensus_hash, &block, thread_rng().gen());
+        debug!(
+            "{:?}: Will announce block {}/{} as a compact block",
+            &self.local_peer,
+            &consensus_hash,
+            &block.block_hash()
+        );
+
+        self.recent_compact_blocks
+            .push_back((data.index_block_hash(), block));
+        while self.recent_compact_blocks.len() > MAX_RECENT_COMPACT_BLOCKS {
+            self.recent_compact_blocks.pop_front();
+        }
+        Some(StacksMessageType::CompactBlock(data))
+    }
+
+    /// Does the given neighbor advertise that it can receive compact block messages?
+    /// Peers that do not set the service bit only ever get full blocks from us.
+    fn neighbor_supports_compact_blocks(&self, nk: &NeighborKey) -> bool {
+        self.events
+            .get(nk)
+            .and_then(|event_id| self.peers.get(event_id))
+            .map(|convo| convo.peer_services & (ServiceFlags::COMPACT_BLOCKS as u16) != 0)
+            .unwrap_or(false)
+    }
+
+    /// Handle an unsolicited GetCompactBlockTxs, by sending back the requested transactions if
+    /// the block is one we recently announced.
+    fn handle_unsolicited_GetCompactBlockTxs(
+        &mut self,
+        event_id: usize,
+        request: &GetCompactBlockTxsData,
+    ) {
+        let nk = match self.peers.get(&event_id) {
+            Some(convo) => convo.to_neighbor_key(),
+            None => {
+                return;
+            }
+        };
+
+        let txs = match self
+            .recent_compact_blocks
+            .iter()
+            .find(|(index_block_hash, _)| *index_block_hash == request.index_block_hash)
+        {
+            Some((_, block)) => {
+                let mut txs = Vec::with_capacity(request.indexes.len());
+                for index in request.indexes.iter() {
+                    match block.txs.get(*index as usize) {
+                        Some(tx) => txs.push(tx.clone()),
+                        None => {
+                            debug!(
+                                "{:?}: Invalid GetCompactBlockTxs from {:?}: no tx {} in {}",
+                                &self.local_peer, &nk, index, &request.index_block_hash
+                            );
+                            return;
+                        }
+                    }
+                }
+                txs
+            }
+            None => {
+                debug!(
+                    "{:?}: Got GetCompactBlockTxs from {:?} for unknown block {}",
+                    &self.local_peer, &nk, &request.index_block_hash
+                );
+                return;
+            }
+        };
+
+        self.broadcast_message(
+            vec![nk],
+            vec![],
+            StacksMessageType::CompactBlockTxs(CompactBlockTxsData {
+                index_block_hash: request.index_block_hash.clone(),
+                txs,
+            }),
+        );
+    }
+
+    /// Rebuild the compact blocks our neighbors announced during this pass from our mempool, and
+    /// finish rebuilding the ones whose missing transactions have arrived.  Completed blocks are
+    /// handed to the relayer as if they had been pushed to us.  For blocks that are still
+    /// missing transactions, ask the announcing peer for them.  If a block can't be rebuilt, we
+    /// will still learn about it through the usual BlocksAvailable and download path.
+    fn process_compact_blocks(
+        &mut self,
+        mempool: &MemPoolDB,
+        network_result: &mut NetworkResult,
+    ) -> Result<(), net_error> {
+        let now = get_epoch_time_secs();
+        self.partial_compact_blocks
+            .retain(|_, (deadline, _)| *deadline >= now);
+
+        let compact_blocks = mem::replace(&mut self.pending_compact_blocks, vec![]);
+        for (event_id, data) in compact_blocks.into_iter() {
+            let nk = match self.peers.get(&event_id) {
+                Some(convo) => convo.to_neighbor_key(),
+                None => {
+                    continue;
+                }
+            };
+            let index_block_hash = data.index_block_hash();
+            if self.partial_compact_blocks.contains_key(&index_block_hash) {
+                continue;
+            }
+
+            let mut partial = PartialCompactBlock::new(&data);
+            partial.fill_from_mempool(mempool)?;
+
+            let missing = partial.missing_indexes();
+            if missing.len() == 0 {
+                self.finish_compact_block(&nk, partial, network_result);
+                continue;
+            }
+
+            if self.partial_compact_blocks.len() >= MAX_PARTIAL_COMPACT_BLOCKS {
+                debug!(
+                    "{:?}: Too many partial compact blocks; dropping {}",
+                    &self.local_peer, &index_block_hash
+                );
+                continue;
+            }
+
+            debug!(
+                "{:?}: Compact block {} is missing {} transactions; asking {:?}",
+                &self.local_peer,
+                &index_block_hash,
+                missing.len(),
+                &nk
+            );
+            self.broadcast_message(
+                vec![nk],
+                vec![],
+                StacksMessageType::GetCompactBlockTxs(GetCompactBlockTxsData {
+                    index_block_hash: index_block_hash.clone(),
+                    indexes: missing,
+                }),
+            );
+            self.partial_compact_blocks.insert(
+                index_block_hash,
+                (now + self.connection_opts.timeout, partial),
+            );
+        }
+
+        let compact_block_txs = mem::replace(&mut self.pending_compact_block_txs, vec![]);
+        for (event_id, data) in compact_block_txs.into_iter() {
+            let nk = match self.peers.get(&event_id) {
+                Some(convo) => convo.to_neighbor_key(),
+                None => {
+                    continue;
+                }
+            };
+            // the reply may arrive on a different conversation with the peer than the one we
+            // asked on, so take it from anyone -- the transactions are checked against the short
+            // txids and the header's Merkle root anyway.
+            let (deadline, mut partial) =
+                match self.partial_compact_blocks.remove(&data.index_block_hash) {
+                    Some(entry) => entry,
+                    None => {
+                        continue;
+                    }
+                };
+
+            let missing = partial.missing_indexes();
+            if !partial.fill_from_peer(&missing, &data.txs) {
+                debug!(
+                    "{:?}: Got the wrong transactions for compact block {} from {:?}",
+                    &self.local_peer, &data.index_block_hash, &nk
+                );
+                self.partial_compact_blocks
+                    .insert(data.index_block_hash, (deadline, partial));
+                continue;
+            }
+            self.finish_compact_block(&nk, partial, network_result);
+        }
+        Ok(())
+    }
+
+    /// Pass a fully rebuilt compact block to the relayer, as if it had been pushed to us
+    fn finish_compact_block(
+        &self,
+        nk: &NeighborKey,
+        partial: PartialCompactBlock,
+        network_result: &mut NetworkResult,
+    ) {
+        let index_block_hash = partial.index_block_hash();
+        let block = match partial.to_block() {
+            Some(block) => block,
+            None => {
+                debug!(
+                    "{:?}: Rebuilt compact block {} is invalid",
+                    &self.local_peer, &index_block_hash
+                );
+                return;
+            }
+        };
+
+        debug!(
+            "{:?}: Rebuilt compact block {} from {:?}",
+            &self.local_peer, &index_block_hash, nk
+        );
+        let blocks_data = BlocksData {
+            blocks: vec![(partial.consensus_hash, block)],
+        };
+        network_result
+            .pushed_blocks
+            .entry(nk.clone())
+            .or_insert(vec![])
+            .push(blocks_data);
+    }
+
     /// Store a single transaction
     /// Return true if stored; false if it was a dup.
     /// Has to be done here, since only the p2p network has the unconfirmed state.
@@ -4588,6 +5421,11 @@ impl PeerNetwork {
             p2p_poll_state,
         )?;
 
+        // rebuild compact blocks, and synchronize mempools with our neighbors
+        self.process_compact_blocks(mempool, &mut network_result)?;
+        self.answer_mempool_sync_requests(mempool)?;
+        self.try_request_mempool_sync(mempool)?;
+
         debug!("<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<< End Network Dispatch <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<");
         Ok(network_result)
     }
@@ -4711,6 +5549,72 @@ mod test {
         p2p
     }
 
+    #[test]
+    fn test_peer_address_subnet() {
+        let addr = PeerAddress::from_ipv4(203, 0, 113, 77);
+        assert_eq!(addr.subnet(32), addr);
+        assert_eq!(addr.subnet(24), PeerAddress::from_ipv4(203, 0, 113, 0));
+        assert_eq!(addr.subnet(20), PeerAddress::from_ipv4(203, 0, 112, 0));
+        assert_eq!(addr.subnet(0), PeerAddress::from_ipv4(0, 0, 0, 0));
+        assert!(addr.subnet(0).is_ipv4());
+        assert!(!addr.is_loopback());
+        assert!(PeerAddress::from_ipv4(127, 0, 0, 1).is_loopback());
+
+        let addr6 = PeerAddress([
+            0x20, 0x01, 0x0d, 0xb8, 0x12, 0x34, 0x56, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01,
+        ]);
+        assert_eq!(
+            addr6.subnet(48),
+            PeerAddress([
+                0x20, 0x01, 0x0d, 0xb8, 0x12, 0x34, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00
+            ])
+        );
+        assert_eq!(addr6.subnet(128), addr6);
+        assert!(!addr6.is_loopback());
+        assert!(PeerAddress([
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01
+        ])
+        .is_loopback());
+    }
+
+    #[test]
+    fn test_compact_blocks_only_to_peers_with_service_bit() {
+        let mut p2p = make_test_p2p_network(&vec![]);
+        let mut nks = vec![];
+        let relay = ServiceFlags::RELAY as u16;
+        let compact = ServiceFlags::COMPACT_BLOCKS as u16;
+        for (event_id, services) in [(1, relay), (2, relay | compact)].iter() {
+            let addr: SocketAddr = format!("127.0.0.1:{}", 2500 + event_id).parse().unwrap();
+            let mut convo = ConversationP2P::new(
+                p2p.local_peer.network_id,
+                p2p.peer_version,
+                &p2p.burnchain,
+                &addr,
+                &p2p.connection_opts,
+                true,
+                *event_id,
+                StacksEpoch::unit_test_pre_2_05(0),
+            );
+            convo.peer_services = *services;
+            let nk = convo.to_neighbor_key();
+            p2p.events.insert(nk.clone(), *event_id);
+            p2p.peers.insert(*event_id, convo);
+            nks.push(nk);
+        }
+
+        // peers that didn't advertise compact block support never get compact blocks
+        assert!(!p2p.neighbor_supports_compact_blocks(&nks[0]));
+        assert!(p2p.neighbor_supports_compact_blocks(&nks[1]));
+
+        // unknown peers don't either
+        let mut unknown = nks[1].clone();
+        unknown.port = 1;
+        assert!(!p2p.neighbor_supports_compact_blocks(&unknown));
+    }
+
     #[test]
     fn test_event_id_no_connecting_leaks() {
         with_timeout(100, || {
@@ -0,0 +1,223 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-client rate limiting of RPC requests.  Each client IP address gets a token bucket for each
+//! class of endpoint, which holds up to a minute's worth of requests and refills continuously.
+//! A request that finds its bucket empty is refused with a 429.  Loopback clients are never
+//! limited, since they are usually a local API server forwarding other clients' requests.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use net::connection::ConnectionOptions;
+
+/// Once we track this many buckets, forget the ones that have refilled completely
+pub const RPC_RATE_LIMIT_PRUNE_THRESHOLD: usize = 8192;
+
+/// Endpoints are rate-limited in classes, by how expensive they are to serve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RPCEndpointClass {
+    /// cheap reads of chain state or node status
+    Read,
+    /// requests that run Clarity code or scan the mempool or chainstate
+    Compute,
+    /// transactions, blocks, and microblocks posted to the node
+    Submit,
+}
+
+impl RPCEndpointClass {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            RPCEndpointClass::Read => "read",
+            RPCEndpointClass::Compute => "compute",
+            RPCEndpointClass::Submit => "submit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenBucket {
+    /// most tokens the bucket can hold
+    pub capacity: f64,
+    /// tokens added per millisecond
+    pub refill_rate: f64,
+    pub tokens: f64,
+    pub last_refill_ms: u64,
+}
+
+impl TokenBucket {
+    /// A full bucket that allows `per_minute` requests each minute
+    pub fn new(per_minute: u64, now_ms: u64) -> TokenBucket {
+        TokenBucket {
+            capacity: per_minute as f64,
+            refill_rate: (per_minute as f64) / 60_000.0,
+            tokens: per_minute as f64,
+            last_refill_ms: now_ms,
+        }
+    }
+
+    fn refill(&mut self, now_ms: u64) {
+        let elapsed = now_ms.saturating_sub(self.last_refill_ms) as f64;
+        self.tokens = (self.tokens + elapsed * self.refill_rate).min(self.capacity);
+        self.last_refill_ms = now_ms.max(self.last_refill_ms);
+    }
+
+    /// Take a token if there is one.  Otherwise, return how many milliseconds until there will be.
+    pub fn try_take(&mut self, now_ms: u64) -> Result<(), u64> {
+        self.refill(now_ms);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - self.tokens) / self.refill_rate).ceil() as u64)
+        }
+    }
+
+    /// Would the bucket be full by now?
+    pub fn is_full(&self, now_ms: u64) -> bool {
+        let elapsed = now_ms.saturating_sub(self.last_refill_ms) as f64;
+        self.tokens + elapsed * self.refill_rate >= self.capacity
+    }
+}
+
+pub struct RPCRateLimiter {
+    /// requests per minute per client for each class (0 = unlimited)
+    read_limit: u64,
+    compute_limit: u64,
+    submit_limit: u64,
+    buckets: HashMap<(IpAddr, RPCEndpointClass), TokenBucket>,
+}
+
+impl RPCRateLimiter {
+    pub fn new(read_limit: u64, compute_limit: u64, submit_limit: u64) -> RPCRateLimiter {
+        RPCRateLimiter {
+            read_limit,
+            compute_limit,
+            submit_limit,
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub fn from_connection_options(opts: &ConnectionOptions) -> RPCRateLimiter {
+        RPCRateLimiter::new(
+            opts.rpc_rate_limit_read,
+            opts.rpc_rate_limit_compute,
+            opts.rpc_rate_limit_submit,
+        )
+    }
+
+    fn limit(&self, class: RPCEndpointClass) -> u64 {
+        match class {
+            RPCEndpointClass::Read => self.read_limit,
+            RPCEndpointClass::Compute => self.compute_limit,
+            RPCEndpointClass::Submit => self.submit_limit,
+        }
+    }
+
+    /// Charge a request from `addr` to its bucket for `class`.  If the client is over its limit,
+    /// return how many milliseconds it should wait before trying again.
+    pub fn check(
+        &mut self,
+        addr: &IpAddr,
+        class: RPCEndpointClass,
+        now_ms: u64,
+    ) -> Result<(), u64> {
+        let limit = self.limit(class);
+        if limit == 0 || addr.is_loopback() {
+            return Ok(());
+        }
+        if self.buckets.len() >= RPC_RATE_LIMIT_PRUNE_THRESHOLD {
+            self.prune(now_ms);
+        }
+        self.buckets
+            .entry((addr.clone(), class))
+            .or_insert_with(|| TokenBucket::new(limit, now_ms))
+            .try_take(now_ms)
+    }
+
+    /// Forget clients whose buckets have refilled, since a new bucket would be the same
+    pub fn prune(&mut self, now_ms: u64) {
+        self.buckets.retain(|_, bucket| !bucket.is_full(now_ms));
+    }
+
+    pub fn num_buckets(&self) -> usize {
+        self.buckets.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let mut bucket = TokenBucket::new(60, 1000);
+        for _ in 0..60 {
+            assert!(bucket.try_take(1000).is_ok());
+        }
+        assert_eq!(bucket.try_take(1000), Err(1000));
+        assert_eq!(bucket.try_take(1500), Err(500));
+        assert!(!bucket.is_full(1500));
+
+        // one token per second
+        assert!(bucket.try_take(2000).is_ok());
+        assert!(bucket.try_take(2000).is_err());
+
+        // never fills past capacity
+        assert!(bucket.is_full(1000 + 120_000));
+        bucket.refill(1000 + 120_000);
+        assert_eq!(bucket.tokens, 60.0);
+    }
+
+    #[test]
+    fn test_rpc_rate_limiter() {
+        let mut limiter = RPCRateLimiter::new(2, 1, 0);
+        let client: IpAddr = "1.2.3.4".parse().unwrap();
+        let other: IpAddr = "5.6.7.8".parse().unwrap();
+        let local: IpAddr = "127.0.0.1".parse().unwrap();
+
+        assert!(limiter.check(&client, RPCEndpointClass::Read, 0).is_ok());
+        assert!(limiter.check(&client, RPCEndpointClass::Read, 0).is_ok());
+        assert_eq!(
+            limiter.check(&client, RPCEndpointClass::Read, 0),
+            Err(30_000)
+        );
+
+        // classes and clients are limited separately
+        assert!(limiter.check(&client, RPCEndpointClass::Compute, 0).is_ok());
+        assert!(limiter
+            .check(&client, RPCEndpointClass::Compute, 0)
+            .is_err());
+        assert!(limiter.check(&other, RPCEndpointClass::Read, 0).is_ok());
+
+        // unlimited classes and loopback clients
+        for _ in 0..100 {
+            assert!(limiter.check(&client, RPCEndpointClass::Submit, 0).is_ok());
+            assert!(limiter.check(&local, RPCEndpointClass::Read, 0).is_ok());
+        }
+        assert_eq!(limiter.num_buckets(), 3);
+
+        assert!(limiter
+            .check(&client, RPCEndpointClass::Read, 30_000)
+            .is_ok());
+
+        // only the other client's bucket has refilled
+        limiter.prune(30_000);
+        assert_eq!(limiter.num_buckets(), 2);
+        limiter.prune(120_000);
+        assert_eq!(limiter.num_buckets(), 0);
+    }
+}
@@ -0,0 +1,654 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Read-only contract calls made through the RPC interface.  By default they run on the p2p
+//! thread, but a node can hand them off to a pool of worker threads instead, so that an expensive
+//! call does not hold up block processing or the peer network.  Each worker has its own handles to
+//! the chainstate and sortition DB.  Calls wait in a bounded queue for a free worker; once the
+//! queue is full, new calls are refused (and the client gets a 503).
+
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, Sender, SyncSender, TryRecvError, TrySendError,
+};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use chainstate::burn::db::sortdb::SortitionDB;
+use chainstate::stacks::db::StacksChainState;
+use clarity_vm::clarity::{ClarityConnection, ClarityReadOnlyConnection};
+use net::Error as net_error;
+use net::{
+    BatchReadOnlyCall, CallReadOnlyBatchEntry, CallReadOnlyBatchResponse, CallReadOnlyResponse,
+    ReadOnlyCallLimits,
+};
+use vm::analysis::errors::CheckErrors;
+use vm::costs::{ExecutionCost, LimitedCostTracker};
+use vm::errors::Error as ClarityRuntimeError;
+use vm::errors::Error::Unchecked;
+use vm::errors::InterpreterError;
+use vm::types::{PrincipalData, QualifiedContractIdentifier};
+use vm::{ClarityName, SymbolicExpression, Value};
+
+use crate::types::chainstate::StacksBlockId;
+
+/// A read-only call to evaluate
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadOnlyCall {
+    pub tip: StacksBlockId,
+    pub contract_id: QualifiedContractIdentifier,
+    pub function: ClarityName,
+    pub sender: PrincipalData,
+    pub args: Vec<Value>,
+    /// budget for the call.  Its write limits are ignored, since the call may not write.
+    pub limits: ReadOnlyCallLimits,
+}
+
+/// Read-only calls to evaluate against the same chain tip.  The calls share one budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadOnlyCallBatch {
+    pub tip: StacksBlockId,
+    pub calls: Vec<BatchReadOnlyCall>,
+    /// budget for the whole batch, except for `max_result_size`, which applies to each call.
+    /// Its write limits are ignored, since the calls may not write.
+    pub limits: ReadOnlyCallLimits,
+}
+
+/// The deadline for work that starts now under `limits`, if it has one
+fn deadline_for(limits: &ReadOnlyCallLimits) -> Option<Instant> {
+    if limits.time_limit_ms > 0 {
+        Some(Instant::now() + Duration::from_millis(limits.time_limit_ms))
+    } else {
+        None
+    }
+}
+
+/// What is left of `limit` once `spent` has been used
+fn remaining_cost(limit: &ExecutionCost, spent: &ExecutionCost) -> ExecutionCost {
+    ExecutionCost {
+        write_length: limit.write_length.saturating_sub(spent.write_length),
+        write_count: limit.write_count.saturating_sub(spent.write_count),
+        read_length: limit.read_length.saturating_sub(spent.read_length),
+        read_count: limit.read_count.saturating_sub(spent.read_count),
+        runtime: limit.runtime.saturating_sub(spent.runtime),
+    }
+}
+
+/// Did the call stop because it ran out of cost or time budget?  A call that tried to write
+/// did not.
+fn is_out_of_budget(result: &Result<Value, ClarityRuntimeError>) -> bool {
+    match result {
+        Err(Unchecked(CheckErrors::CostBalanceExceeded(actual_cost, _))) => {
+            actual_cost.write_count == 0
+        }
+        Err(Unchecked(CheckErrors::ExecutionTimeExpired)) => true,
+        _ => false,
+    }
+}
+
+/// Evaluate one read-only call in `clarity_tx`, with at most `budget` to spend before
+/// `deadline`.  `limits` is what gets reported to the caller if the call runs out.  Returns the
+/// call's outcome, what it cost, and whether it ran out of budget.
+fn eval_read_only_call(
+    clarity_tx: &mut ClarityReadOnlyConnection,
+    mainnet: bool,
+    call: &BatchReadOnlyCall,
+    limits: &ReadOnlyCallLimits,
+    budget: &ExecutionCost,
+    deadline: Option<Instant>,
+) -> (CallReadOnlyResponse, ExecutionCost, bool) {
+    let args: Vec<_> = call
+        .args
+        .iter()
+        .map(|x| SymbolicExpression::atom_value(x.clone()))
+        .collect();
+    let mut limits = limits.clone();
+    limits.cost.write_length = 0;
+    limits.cost.write_count = 0;
+    let mut budget = budget.clone();
+    budget.write_length = 0;
+    budget.write_count = 0;
+
+    let epoch = clarity_tx.get_epoch();
+    let mut cost_track = match clarity_tx.with_clarity_db_readonly(|clarity_db| {
+        LimitedCostTracker::new_mid_block(mainnet, budget, clarity_db, epoch)
+    }) {
+        Ok(cost_track) => cost_track,
+        Err(_) => {
+            let e = ClarityRuntimeError::from(InterpreterError::CostContractLoadFailure);
+            return (
+                make_call_response(Err(e), &limits),
+                ExecutionCost::zero(),
+                false,
+            );
+        }
+    };
+    if let Some(deadline) = deadline {
+        cost_track.set_deadline(deadline);
+    }
+
+    let mut cost = ExecutionCost::zero();
+    let result =
+        clarity_tx.with_readonly_clarity_env(mainnet, call.sender.clone(), cost_track, |env| {
+            // we want to execute any function as long as no actual writes are made as
+            // opposed to be limited to purely calling `define-read-only` functions,
+            // so use `read_only = false`.  This broadens the number of functions that
+            // can be called, and also circumvents limitations on `define-read-only`
+            // functions that can not use `contrac-call?`, even when calling other
+            // read-only functions
+            let result =
+                env.execute_contract(&call.contract_id, call.function.as_str(), &args, false);
+            cost = env.global_context.cost_track.get_total();
+            result
+        });
+    let out_of_budget = is_out_of_budget(&result);
+    (make_call_response(result, &limits), cost, out_of_budget)
+}
+
+fn make_call_response(
+    result: Result<Value, ClarityRuntimeError>,
+    limits: &ReadOnlyCallLimits,
+) -> CallReadOnlyResponse {
+    let failed = |cause: String, limits: Option<ReadOnlyCallLimits>| CallReadOnlyResponse {
+        okay: false,
+        result: None,
+        result_json: None,
+        cause: Some(cause),
+        limits,
+    };
+    match result {
+        Ok(data) => {
+            let hex = data.serialize();
+            let result_size = (hex.len() / 2) as u64;
+            if limits.max_result_size > 0 && result_size > limits.max_result_size {
+                return failed(
+                    format!("ResultTooLarge({})", result_size),
+                    Some(limits.clone()),
+                );
+            }
+            CallReadOnlyResponse {
+                okay: true,
+                result: Some(format!("0x{}", hex)),
+                result_json: Some(data.to_json()),
+                cause: None,
+                limits: None,
+            }
+        }
+        Err(e) => match e {
+            Unchecked(CheckErrors::CostBalanceExceeded(actual_cost, _))
+                if actual_cost.write_count > 0 =>
+            {
+                failed("NotReadOnly".to_string(), None)
+            }
+            // report the budget the call ran out of, so the caller knows what it is up against
+            Unchecked(CheckErrors::CostBalanceExceeded(..))
+            | Unchecked(CheckErrors::ExecutionTimeExpired) => {
+                failed(e.to_string(), Some(limits.clone()))
+            }
+            _ => failed(e.to_string(), None),
+        },
+    }
+}
+
+impl ReadOnlyCall {
+    /// Evaluate the call.  Returns None if the chain tip is not known.
+    pub fn run(
+        &self,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+    ) -> Option<CallReadOnlyResponse> {
+        let mainnet = chainstate.mainnet;
+        let call = BatchReadOnlyCall {
+            contract_id: self.contract_id.clone(),
+            function: self.function.clone(),
+            sender: self.sender.clone(),
+            args: self.args.clone(),
+        };
+        match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), &self.tip, |clarity_tx| {
+            eval_read_only_call(
+                clarity_tx,
+                mainnet,
+                &call,
+                &self.limits,
+                &self.limits.cost,
+                deadline_for(&self.limits),
+            )
+        }) {
+            Ok(Some((response, ..))) => Some(response),
+            Ok(None) | Err(_) => None,
+        }
+    }
+}
+
+impl ReadOnlyCallBatch {
+    /// Evaluate the calls, in order, against the same view of the chain state.  Each call may
+    /// spend whatever the calls before it left of the batch's budget.  Once a call runs out, the
+    /// remaining calls are not run.  Returns None if the chain tip is not known.
+    pub fn run(
+        &self,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+    ) -> Option<CallReadOnlyBatchResponse> {
+        let mainnet = chainstate.mainnet;
+        let deadline = deadline_for(&self.limits);
+        match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), &self.tip, |clarity_tx| {
+            let mut spent = ExecutionCost::zero();
+            let mut budget_exhausted_at = None;
+            let mut results = Vec::with_capacity(self.calls.len());
+            for (i, call) in self.calls.iter().enumerate() {
+                if budget_exhausted_at.is_some() {
+                    results.push(CallReadOnlyBatchEntry {
+                        okay: false,
+                        result: None,
+                        result_json: None,
+                        cause: Some("BatchBudgetExhausted".to_string()),
+                        limits: None,
+                        cost: ExecutionCost::zero(),
+                    });
+                    continue;
+                }
+                let budget = remaining_cost(&self.limits.cost, &spent);
+                let (response, cost, out_of_budget) =
+                    eval_read_only_call(clarity_tx, mainnet, call, &self.limits, &budget, deadline);
+                if spent.add(&cost).is_err() {
+                    spent = ExecutionCost::max_value();
+                }
+                if out_of_budget {
+                    budget_exhausted_at = Some(i);
+                }
+                results.push(CallReadOnlyBatchEntry {
+                    okay: response.okay,
+                    result: response.result,
+                    result_json: response.result_json,
+                    cause: response.cause,
+                    limits: response.limits,
+                    cost,
+                });
+            }
+            CallReadOnlyBatchResponse {
+                tip: self.tip.clone(),
+                results,
+                total_cost: spent,
+                budget_exhausted_at,
+            }
+        }) {
+            Ok(Some(response)) => Some(response),
+            Ok(None) | Err(_) => None,
+        }
+    }
+}
+
+/// Work that can be handed to the pool
+#[derive(Debug, Clone, PartialEq)]
+enum ReadOnlyJob {
+    Call(ReadOnlyCall),
+    Batch(ReadOnlyCallBatch),
+}
+
+/// The outcome of work handed to the pool
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadOnlyCallResult {
+    Call(CallReadOnlyResponse),
+    Batch(CallReadOnlyBatchResponse),
+}
+
+impl ReadOnlyJob {
+    fn run(
+        &self,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+    ) -> Option<ReadOnlyCallResult> {
+        match self {
+            ReadOnlyJob::Call(call) => call.run(sortdb, chainstate).map(ReadOnlyCallResult::Call),
+            ReadOnlyJob::Batch(batch) => {
+                batch.run(sortdb, chainstate).map(ReadOnlyCallResult::Batch)
+            }
+        }
+    }
+}
+
+/// The eventual outcome of a call handed to the pool
+pub struct PendingReadOnlyCall {
+    result_rx: Receiver<Option<ReadOnlyCallResult>>,
+}
+
+impl PendingReadOnlyCall {
+    /// Get the call's result, if it has finished.  Returns Ok(None) if it is still running, and
+    /// an error if the worker running it went away.
+    pub fn try_get_result(&self) -> Result<Option<Option<ReadOnlyCallResult>>, net_error> {
+        match self.result_rx.try_recv() {
+            Ok(result) => Ok(Some(result)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(net_error::ChainstateError(
+                "Read-only call worker exited".to_string(),
+            )),
+        }
+    }
+}
+
+struct ReadOnlyCallJob {
+    work: ReadOnlyJob,
+    result_tx: Sender<Option<ReadOnlyCallResult>>,
+}
+
+pub struct ReadOnlyCallPool {
+    job_tx: Option<SyncSender<ReadOnlyCallJob>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ReadOnlyCallPool {
+    /// Start `num_workers` threads that evaluate read-only calls against their own handles to
+    /// `chainstate` and the sortition DB at `sortdb_path`.  Up to `queue_size` calls can wait for
+    /// a free worker.
+    pub fn new(
+        num_workers: usize,
+        queue_size: usize,
+        chainstate: &StacksChainState,
+        sortdb_path: &str,
+    ) -> Result<ReadOnlyCallPool, net_error> {
+        let (job_tx, job_rx) = sync_channel::<ReadOnlyCallJob>(queue_size);
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let mut workers = vec![];
+        for i in 0..num_workers {
+            let (mut worker_chainstate, _) = chainstate
+                .reopen()
+                .map_err(|e| net_error::ChainstateError(e.to_string()))?;
+            let worker_sortdb = SortitionDB::open(sortdb_path, false)?;
+            let job_rx = job_rx.clone();
+            let worker = thread::Builder::new()
+                .name(format!("readonly-call-{}", i))
+                .spawn(move || loop {
+                    let job = match job_rx.lock() {
+                        Ok(rx) => match rx.recv() {
+                            Ok(job) => job,
+                            Err(_) => {
+                                // pool is shutting down
+                                break;
+                            }
+                        },
+                        Err(_) => {
+                            // another worker panicked
+                            break;
+                        }
+                    };
+                    let result = job.work.run(&worker_sortdb, &mut worker_chainstate);
+                    let _ = job.result_tx.send(result);
+                })
+                .map_err(|e| {
+                    net_error::ChainstateError(format!("Failed to start worker: {:?}", &e))
+                })?;
+            workers.push(worker);
+        }
+        Ok(ReadOnlyCallPool {
+            job_tx: Some(job_tx),
+            workers,
+        })
+    }
+
+    /// Queue up work for the next free worker.  Gives the work back if the queue is full.
+    fn submit_job(&self, work: ReadOnlyJob) -> Result<PendingReadOnlyCall, ReadOnlyJob> {
+        let job_tx = match self.job_tx {
+            Some(ref job_tx) => job_tx,
+            None => return Err(work),
+        };
+        let (result_tx, result_rx) = channel();
+        match job_tx.try_send(ReadOnlyCallJob { work, result_tx }) {
+            Ok(()) => Ok(PendingReadOnlyCall { result_rx }),
+            Err(TrySendError::Full(job)) | Err(TrySendError::Disconnected(job)) => Err(job.work),
+        }
+    }
+
+    /// Queue up a call for the next free worker.  Gives the call back if the queue is full.
+    pub fn submit(&self, call: ReadOnlyCall) -> Result<PendingReadOnlyCall, ReadOnlyCall> {
+        self.submit_job(ReadOnlyJob::Call(call))
+            .map_err(|work| match work {
+                ReadOnlyJob::Call(call) => call,
+                ReadOnlyJob::Batch(..) => unreachable!("submitted a call, got back a batch"),
+            })
+    }
+
+    /// Queue up a batch of calls for the next free worker, which runs all of them.  Gives the
+    /// batch back if the queue is full.
+    pub fn submit_batch(
+        &self,
+        batch: ReadOnlyCallBatch,
+    ) -> Result<PendingReadOnlyCall, ReadOnlyCallBatch> {
+        self.submit_job(ReadOnlyJob::Batch(batch))
+            .map_err(|work| match work {
+                ReadOnlyJob::Batch(batch) => batch,
+                ReadOnlyJob::Call(..) => unreachable!("submitted a batch, got back a call"),
+            })
+    }
+
+    pub fn num_workers(&self) -> usize {
+        self.workers.len()
+    }
+}
+
+impl Drop for ReadOnlyCallPool {
+    fn drop(&mut self) {
+        // hanging up the queue stops each worker once it finishes its current call
+        self.job_tx.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chainstate::stacks::db::test::instantiate_chainstate;
+    use core::StacksEpoch;
+    use util::get_epoch_time_secs;
+    use vm::types::StandardPrincipalData;
+
+    use crate::types::chainstate::BurnchainHeaderHash;
+
+    fn make_call(tip: StacksBlockId) -> ReadOnlyCall {
+        ReadOnlyCall {
+            tip,
+            contract_id: QualifiedContractIdentifier::transient(),
+            function: "get-info".into(),
+            sender: StandardPrincipalData::transient().into(),
+            args: vec![Value::UInt(1)],
+            limits: ReadOnlyCallLimits {
+                cost: ExecutionCost::max_value(),
+                time_limit_ms: 1000,
+                max_result_size: 0,
+            },
+        }
+    }
+
+    fn make_batch(tip: StacksBlockId) -> ReadOnlyCallBatch {
+        let call = make_call(tip.clone());
+        ReadOnlyCallBatch {
+            tip,
+            calls: vec![
+                BatchReadOnlyCall {
+                    contract_id: call.contract_id,
+                    function: call.function,
+                    sender: call.sender,
+                    args: call.args,
+                };
+                2
+            ],
+            limits: call.limits,
+        }
+    }
+
+    #[test]
+    fn test_read_only_call_response_limits() {
+        let limits = ReadOnlyCallLimits {
+            cost: ExecutionCost {
+                write_length: 0,
+                write_count: 0,
+                read_length: 100,
+                read_count: 10,
+                runtime: 1000,
+            },
+            time_limit_ms: 0,
+            max_result_size: 17,
+        };
+
+        // an int serializes to 17 bytes, which fits
+        let response = make_call_response(Ok(Value::Int(1)), &limits);
+        assert!(response.okay);
+        assert!(response.limits.is_none());
+
+        let response = make_call_response(Ok(Value::some(Value::Int(1)).unwrap()), &limits);
+        assert!(!response.okay);
+        assert!(response.result.is_none());
+        assert_eq!(response.cause, Some("ResultTooLarge(18)".to_string()));
+        assert_eq!(response.limits, Some(limits.clone()));
+
+        // no limit on the result size
+        let mut unlimited = limits.clone();
+        unlimited.max_result_size = 0;
+        let response = make_call_response(Ok(Value::some(Value::Int(1)).unwrap()), &unlimited);
+        assert!(response.okay);
+
+        // running out of budget reports the budget
+        let mut spent = limits.cost.clone();
+        spent.runtime = 1001;
+        let response = make_call_response(
+            Err(CheckErrors::CostBalanceExceeded(spent.clone(), limits.cost.clone()).into()),
+            &limits,
+        );
+        assert!(!response.okay);
+        assert_eq!(response.limits, Some(limits.clone()));
+
+        let response = make_call_response(Err(CheckErrors::ExecutionTimeExpired.into()), &limits);
+        assert_eq!(response.limits, Some(limits.clone()));
+
+        // writing is not a budget problem
+        spent.write_count = 1;
+        let response = make_call_response(
+            Err(CheckErrors::CostBalanceExceeded(spent, limits.cost.clone()).into()),
+            &limits,
+        );
+        assert_eq!(response.cause, Some("NotReadOnly".to_string()));
+        assert!(response.limits.is_none());
+
+        let response = make_call_response(
+            Err(CheckErrors::NoSuchContract("foo".into()).into()),
+            &limits,
+        );
+        assert!(response.limits.is_none());
+    }
+
+    #[test]
+    fn test_read_only_batch_budget() {
+        let limit = ExecutionCost {
+            write_length: 0,
+            write_count: 0,
+            read_length: 100,
+            read_count: 10,
+            runtime: 1000,
+        };
+        let spent = ExecutionCost {
+            write_length: 0,
+            write_count: 0,
+            read_length: 40,
+            read_count: 11,
+            runtime: 1000,
+        };
+        assert_eq!(
+            remaining_cost(&limit, &spent),
+            ExecutionCost {
+                write_length: 0,
+                write_count: 0,
+                read_length: 60,
+                read_count: 0,
+                runtime: 0,
+            }
+        );
+        assert_eq!(remaining_cost(&limit, &ExecutionCost::zero()), limit);
+
+        assert!(is_out_of_budget(&Err(CheckErrors::CostBalanceExceeded(
+            spent.clone(),
+            limit.clone()
+        )
+        .into())));
+        assert!(is_out_of_budget(&Err(
+            CheckErrors::ExecutionTimeExpired.into()
+        )));
+
+        // trying to write, failing, or succeeding does not use up the batch's budget
+        let mut wrote = spent.clone();
+        wrote.write_count = 1;
+        assert!(!is_out_of_budget(&Err(CheckErrors::CostBalanceExceeded(
+            wrote, limit
+        )
+        .into())));
+        assert!(!is_out_of_budget(&Err(CheckErrors::NoSuchContract(
+            "foo".into()
+        )
+        .into())));
+        assert!(!is_out_of_budget(&Ok(Value::Int(1))));
+    }
+
+    #[test]
+    fn test_readonly_call_pool() {
+        let chainstate = instantiate_chainstate(false, 0x80000000, "test_readonly_call_pool");
+        let sortdb_path = format!("{}/sortdb", &chainstate.root_path);
+        SortitionDB::connect(
+            &sortdb_path,
+            0,
+            &BurnchainHeaderHash([0u8; 32]),
+            get_epoch_time_secs(),
+            &StacksEpoch::unit_test_pre_2_05(0),
+            true,
+        )
+        .unwrap();
+
+        // nothing will ever take calls from a pool without workers
+        let pool = ReadOnlyCallPool::new(0, 4, &chainstate, &sortdb_path).unwrap();
+        let call = make_call(StacksBlockId([0x11; 32]));
+        assert_eq!(pool.submit(call.clone()).err(), Some(call));
+        let batch = make_batch(StacksBlockId([0x11; 32]));
+        assert_eq!(pool.submit_batch(batch.clone()).err(), Some(batch));
+
+        let pool = ReadOnlyCallPool::new(2, 4, &chainstate, &sortdb_path).unwrap();
+        assert_eq!(pool.num_workers(), 2);
+
+        // calls and batches against an unknown chain tip have no result
+        let pending: Vec<_> = (0..4)
+            .map(|i| {
+                if i % 2 == 0 {
+                    pool.submit(make_call(StacksBlockId([0x11; 32]))).unwrap()
+                } else {
+                    pool.submit_batch(make_batch(StacksBlockId([0x11; 32])))
+                        .unwrap()
+                }
+            })
+            .collect();
+        for call in pending.iter() {
+            loop {
+                match call.try_get_result().unwrap() {
+                    Some(result) => {
+                        assert!(result.is_none());
+                        break;
+                    }
+                    None => {
+                        thread::sleep(Duration::from_millis(10));
+                    }
+                }
+            }
+        }
+    }
+}
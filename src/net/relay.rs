@@ -22,6 +22,7 @@ use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::mem;
 
 use rand::prelude::*;
 use rand::thread_rng;
@@ -38,6 +39,8 @@ use chainstate::stacks::db::{StacksChainState, StacksEpochReceipt, StacksHeaderI
 use chainstate::stacks::events::StacksTransactionReceipt;
 use core::mempool::MemPoolDB;
 use core::mempool::*;
+use core::mempool_policy::{MemPoolAdmissionInfo, MemPoolAdmissionPolicy};
+use monitoring::increment_relay_throttled_counter;
 use net::chat::*;
 use net::connection::*;
 use net::db::*;
@@ -47,6 +50,7 @@ use net::poll::*;
 use net::rpc::*;
 use net::Error as net_error;
 use net::*;
+use util::get_epoch_time_ms;
 use util::get_epoch_time_secs;
 use util::hash::Sha512Trunc256Sum;
 use vm::costs::ExecutionCost;
@@ -63,10 +67,24 @@ pub const MAX_RELAYER_STATS: usize = 4096;
 pub const MAX_RECENT_MESSAGES: usize = 256;
 pub const MAX_RECENT_MESSAGE_AGE: usize = 600; // seconds; equal to the expected epoch length
 pub const RELAY_DUPLICATE_INFERENCE_WARMUP: usize = 128;
+pub const RELAY_BANDWIDTH_WINDOW: u64 = 60; // seconds over which a neighbor's relay bandwidth is measured
 
 pub struct Relayer {
     /// Connection to the p2p thread
     p2p: NetworkHandle,
+    /// Node-local policies a transaction must pass before we forward it to our neighbors.  These
+    /// only affect relaying -- never what we store in the mempool or accept in blocks.
+    relay_policies: Vec<Box<dyn MemPoolAdmissionPolicy>>,
+}
+
+/// A (micro)block announcement to a neighbor that subscribed to them, held back until `send_at`
+/// (in milliseconds) so we don't announce to every subscriber at once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledBlockAnnouncement {
+    pub recipient: NeighborKey,
+    pub available: BlocksAvailableMap,
+    pub microblocks: bool,
+    pub send_at: u128,
 }
 
 #[derive(Debug)]
@@ -82,6 +100,9 @@ pub struct RelayerStats {
     recent_messages: HashMap<NeighborKey, VecDeque<(u64, Sha512Trunc256Sum)>>,
     recent_updates: BTreeMap<u64, NeighborKey>,
 
+    /// Bytes of pushed data sent from each neighbor recently, as (timestamp, length)
+    recent_bytes: HashMap<NeighborKey, VecDeque<(u64, u64)>>,
+
     next_priority: u64,
 }
 
@@ -122,6 +143,17 @@ impl RelayPayload for StacksBlock {
     }
 }
 
+impl RelayPayload for CompactBlockData {
+    /// same as the full block's digest, since it's the same block
+    fn get_digest(&self) -> Sha512Trunc256Sum {
+        let h = self.header.block_hash();
+        Sha512Trunc256Sum(h.0)
+    }
+    fn get_id(&self) -> String {
+        format!("CompactBlock({})", self.header.block_hash())
+    }
+}
+
 impl RelayPayload for StacksMicroblock {
     fn get_digest(&self) -> Sha512Trunc256Sum {
         let h = self.block_hash();
@@ -149,6 +181,7 @@ impl RelayerStats {
             relay_updates: BTreeMap::new(),
             recent_messages: HashMap::new(),
             recent_updates: BTreeMap::new(),
+            recent_bytes: HashMap::new(),
             next_priority: 0,
         }
     }
@@ -242,10 +275,54 @@ impl RelayerStats {
         }
     }
 
+    /// Record that a neighbor pushed us `len` bytes of data at time `now`.
+    pub fn add_relayed_bytes(&mut self, nk: &NeighborKey, len: u64, now: u64) -> () {
+        if !self.recent_bytes.contains_key(nk) && self.recent_bytes.len() >= MAX_RELAYER_STATS {
+            // forget neighbors that haven't sent us anything lately
+            self.recent_bytes.retain(|_, relayed| match relayed.back() {
+                Some((ts, _)) => ts + RELAY_BANDWIDTH_WINDOW >= now,
+                None => false,
+            });
+            if self.recent_bytes.len() >= MAX_RELAYER_STATS {
+                return;
+            }
+        }
+
+        let relayed = self
+            .recent_bytes
+            .entry(nk.clone())
+            .or_insert_with(VecDeque::new);
+        relayed.push_back((now, len));
+
+        // prune stale
+        while let Some((ts, _)) = relayed.front() {
+            if ts + RELAY_BANDWIDTH_WINDOW < now {
+                relayed.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// How many bytes per second of pushed data has this neighbor sent us, averaged over the last
+    /// RELAY_BANDWIDTH_WINDOW seconds?
+    pub fn get_relayed_bandwidth(&self, nk: &NeighborKey, now: u64) -> u64 {
+        let total: u64 = match self.recent_bytes.get(nk) {
+            Some(relayed) => relayed
+                .iter()
+                .filter(|(ts, _)| ts + RELAY_BANDWIDTH_WINDOW >= now)
+                .map(|(_, len)| *len)
+                .sum(),
+            None => 0,
+        };
+        total / RELAY_BANDWIDTH_WINDOW
+    }
+
     /// Process a neighbor ban -- remove any state for this neighbor
     pub fn process_neighbor_ban(&mut self, nk: &NeighborKey) -> () {
         let addr = NeighborAddress::from_neighbor_key((*nk).clone(), Hash160([0u8; 20]));
         self.recent_messages.remove(nk);
+        self.recent_bytes.remove(nk);
         self.relay_stats.remove(&addr);
 
         // old state in self.recent_updates and self.relay_updates will eventually be removed by
@@ -438,7 +515,10 @@ impl RelayerStats {
 
 impl Relayer {
     pub fn new(handle: NetworkHandle) -> Relayer {
-        Relayer { p2p: handle }
+        Relayer {
+            p2p: handle,
+            relay_policies: vec![],
+        }
     }
 
     pub fn from_p2p(network: &mut PeerNetwork) -> Relayer {
@@ -446,6 +526,73 @@ impl Relayer {
         Relayer::new(handle)
     }
 
+    /// Register a node-local policy that transactions must pass in order to be forwarded to our
+    /// neighbors.
+    pub fn add_relay_policy(&mut self, policy: Box<dyn MemPoolAdmissionPolicy>) {
+        self.relay_policies.push(policy);
+    }
+
+    /// Check a transaction against our relay policies.  Returns the name of the policy that
+    /// rejected it and why, if it should not be forwarded.
+    pub fn check_relay_policies(
+        policies: &[Box<dyn MemPoolAdmissionPolicy>],
+        tx: &StacksTransaction,
+    ) -> Result<(), (String, String)> {
+        if policies.len() == 0 {
+            return Ok(());
+        }
+        let info = MemPoolAdmissionInfo {
+            tx,
+            tx_len: tx.serialize_to_vec().len() as u64,
+            fee_rate: None,
+            estimated_cost: None,
+        };
+        for policy in policies.iter() {
+            policy
+                .check(&info)
+                .map_err(|reason| (policy.name().to_string(), reason))?;
+        }
+        Ok(())
+    }
+
+    /// Should we forward this transaction to our neighbors?
+    fn should_relay_tx(&self, tx: &StacksTransaction) -> bool {
+        match Relayer::check_relay_policies(&self.relay_policies, tx) {
+            Ok(()) => true,
+            Err((policy, reason)) => {
+                debug!(
+                    "Will not relay tx {}: rejected by relay policy {}: {}",
+                    &tx.txid(),
+                    &policy,
+                    &reason
+                );
+                false
+            }
+        }
+    }
+
+    /// Announce up to `max_txs` of the mempool's pending transactions to our neighbors, such as
+    /// after a restart when our peers may not have seen them.  Returns the number of transactions
+    /// announced.
+    pub fn rebroadcast_mempool_txs(
+        &mut self,
+        mempool: &MemPoolDB,
+        max_txs: u64,
+    ) -> Result<u64, net_error> {
+        let txs = MemPoolDB::get_txs_to_rebroadcast(mempool.conn(), max_txs)?;
+        let mut num_txs = 0;
+        for tx in txs.into_iter() {
+            if !self.should_relay_tx(&tx) {
+                continue;
+            }
+            debug!("Rebroadcast mempool tx {}", &tx.txid());
+            self.p2p
+                .broadcast_message(vec![], StacksMessageType::Transaction(tx))?;
+            num_txs += 1;
+        }
+        Ok(num_txs)
+    }
+
     /// Given blocks pushed to us, verify that they correspond to expected block data.
     pub fn validate_blocks_push(
         conn: &SortitionDBConn,
@@ -627,6 +774,17 @@ impl Relayer {
     ) -> HashSet<ConsensusHash> {
         let mut new_blocks = HashSet::new();
 
+        // check all the downloaded blocks' VRF proofs up front
+        if let Err(e) = chainstate.preverify_anchored_block_proofs(
+            sort_ic,
+            network_result
+                .blocks
+                .iter()
+                .map(|(consensus_hash, block, _)| (consensus_hash, block)),
+        ) {
+            warn!("Failed to pre-verify downloaded block VRF proofs: {:?}", &e);
+        }
+
         for (consensus_hash, block, download_time) in network_result.blocks.iter() {
             match Relayer::process_new_anchored_block(
                 sort_ic,
@@ -1018,8 +1176,14 @@ impl Relayer {
         mempool: &mut MemPoolDB,
         event_observer: Option<&dyn MemPoolEventDispatcher>,
     ) -> Result<Vec<(Vec<RelayData>, StacksTransaction)>, net_error> {
-        let chain_height = match chainstate.get_stacks_chain_tip(sortdb)? {
-            Some(tip) => tip.height,
+        let (chain_height, chain_tip) = match chainstate.get_stacks_chain_tip(sortdb)? {
+            Some(tip) => (
+                tip.height,
+                StacksBlockHeader::make_index_block_hash(
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                ),
+            ),
             None => {
                 debug!(
                     "No Stacks chain tip; dropping {} transaction(s)",
@@ -1063,10 +1227,26 @@ impl Relayer {
                 "Remove all transactions beneath block height {}",
                 min_height
             );
-            MemPoolDB::garbage_collect(&mut mempool_tx, min_height, event_observer)?;
+            MemPoolDB::garbage_collect(
+                &mut mempool_tx,
+                min_height,
+                Some((&*chainstate, &chain_tip)),
+                event_observer,
+            )?;
             mempool_tx.commit()?;
         }
 
+        // drop transactions that can no longer be mined because their nonces were used in the
+        // canonical fork
+        if let Err(e) = mempool.drop_stale_nonce_txs(
+            chainstate,
+            &sortdb.index_conn(),
+            &chain_tip,
+            event_observer,
+        ) {
+            warn!("Failed to drop stale-nonce mempool transactions: {:?}", &e);
+        }
+
         Ok(ret)
     }
 
@@ -1104,6 +1284,12 @@ impl Relayer {
         )
     }
 
+    /// Announce a transaction to our peers
+    pub fn broadcast_transaction(&mut self, tx: StacksTransaction) -> Result<(), net_error> {
+        self.p2p
+            .broadcast_message(vec![], StacksMessageType::Transaction(tx))
+    }
+
     /// Set up the unconfirmed chain state off of the canonical chain tip.
     pub fn setup_unconfirmed_state(
         chainstate: &mut StacksChainState,
@@ -1276,8 +1462,11 @@ impl Relayer {
 
         let mut mempool_txs_added = vec![];
         for (relayers, tx) in new_txs.into_iter() {
-            debug!("{:?}: Broadcast tx {}", &_local_peer, &tx.txid());
             mempool_txs_added.push(tx.clone());
+            if !self.should_relay_tx(&tx) {
+                continue;
+            }
+            debug!("{:?}: Broadcast tx {}", &_local_peer, &tx.txid());
             let msg = StacksMessageType::Transaction(tx);
             if let Err(e) = self.p2p.broadcast_message(relayers, msg) {
                 warn!("Failed to broadcast transaction: {:?}", &e);
@@ -1361,6 +1550,84 @@ impl PeerNetwork {
         Ok((outbound_recipients, inbound_recipients))
     }
 
+    /// Find the authenticated neighbors that asked us (via the BLOCK_ANNOUNCE service bit in their
+    /// handshakes) to push new block and microblock announcements to them.
+    fn find_block_subscribers(&self) -> HashSet<NeighborKey> {
+        let mut subscribers = HashSet::new();
+        for (_, convo) in self.peers.iter() {
+            if convo.is_authenticated()
+                && convo.peer_services & (ServiceFlags::BLOCK_ANNOUNCE as u16) != 0
+            {
+                subscribers.insert(convo.to_neighbor_key());
+            }
+        }
+        subscribers
+    }
+
+    /// Schedule full announcements of the given (micro)blocks to each subscriber, each at a random
+    /// time within the next connection_opts.block_announcement_jitter_ms milliseconds.
+    fn schedule_block_announcements(
+        &mut self,
+        subscribers: HashSet<NeighborKey>,
+        available: &BlocksAvailableMap,
+        microblocks: bool,
+    ) -> () {
+        let now = get_epoch_time_ms();
+        let max_jitter = self.connection_opts.block_announcement_jitter_ms;
+        for recipient in subscribers.into_iter() {
+            let jitter = if max_jitter > 0 {
+                thread_rng().gen_range(0, max_jitter + 1)
+            } else {
+                0
+            };
+            test_debug!(
+                "{:?}: Schedule announcement of {} {} to subscriber {} in {}ms",
+                &self.local_peer,
+                available.len(),
+                if microblocks {
+                    "microblock streams"
+                } else {
+                    "blocks"
+                },
+                &recipient,
+                jitter
+            );
+            self.scheduled_block_announcements
+                .push(ScheduledBlockAnnouncement {
+                    recipient,
+                    available: available.clone(),
+                    microblocks,
+                    send_at: now + (jitter as u128),
+                });
+        }
+    }
+
+    /// Send the scheduled block and microblock announcements whose time has come.
+    pub fn send_scheduled_block_announcements(&mut self) -> () {
+        let now = get_epoch_time_ms();
+        let (ready, pending): (Vec<_>, Vec<_>) =
+            mem::replace(&mut self.scheduled_block_announcements, vec![])
+                .into_iter()
+                .partition(|announcement| announcement.send_at <= now);
+        self.scheduled_block_announcements = pending;
+
+        for announcement in ready.into_iter() {
+            if announcement.microblocks {
+                let _ = self.advertize_to_inbound_peer(
+                    &announcement.recipient,
+                    &announcement.available,
+                    |payload| StacksMessageType::MicroblocksAvailable(payload),
+                );
+            } else {
+                let _ = self.advertize_to_inbound_peer(
+                    &announcement.recipient,
+                    &announcement.available,
+                    |payload| StacksMessageType::BlocksAvailable(payload),
+                );
+            }
+        }
+    }
+
     /// Announce the availability of a set of blocks or microblocks to a peer.
     /// Break the availability into (Micro)BlocksAvailable messages and queue them for transmission.
     fn advertize_to_peer<S>(
@@ -1476,12 +1743,17 @@ impl PeerNetwork {
     /// the inv state we synchronized from them.
     /// * Inbound peers are chosen uniformly at random to receive a full announcement, since we
     /// don't track their inventory state.
+    /// * Peers that subscribed to block announcements all receive a full announcement, after a
+    /// short random delay.
     pub fn advertize_blocks(
         &mut self,
         availability_data: BlocksAvailableMap,
     ) -> Result<(), net_error> {
         let (mut outbound_recipients, mut inbound_recipients) =
             self.find_block_recipients(&availability_data)?;
+        let subscribers = self.find_block_subscribers();
+        outbound_recipients.retain(|nk| !subscribers.contains(nk));
+        inbound_recipients.retain(|nk| !subscribers.contains(nk));
         debug!(
             "{:?}: Advertize {} blocks to {} inbound peers, {} outbound peers",
             &self.local_peer,
@@ -1510,6 +1782,7 @@ impl PeerNetwork {
                 StacksMessageType::BlocksAvailable(payload)
             })?;
         }
+        self.schedule_block_announcements(subscribers, &availability_data, false);
         Ok(())
     }
 
@@ -1518,12 +1791,17 @@ impl PeerNetwork {
     /// the inv state we synchronized from them.
     /// * Inbound peers are chosen uniformly at random to receive a full announcement, since we
     /// don't track their inventory state.
+    /// * Peers that subscribed to block announcements all receive a full announcement, after a
+    /// short random delay.
     pub fn advertize_microblocks(
         &mut self,
         availability_data: BlocksAvailableMap,
     ) -> Result<(), net_error> {
         let (mut outbound_recipients, mut inbound_recipients) =
             self.find_block_recipients(&availability_data)?;
+        let subscribers = self.find_block_subscribers();
+        outbound_recipients.retain(|nk| !subscribers.contains(nk));
+        inbound_recipients.retain(|nk| !subscribers.contains(nk));
         debug!("{:?}: Advertize {} confirmed microblock streams to {} inbound peers, {} outbound peers", &self.local_peer, availability_data.len(), outbound_recipients.len(), inbound_recipients.len());
 
         for recipient in outbound_recipients.drain(..) {
@@ -1546,11 +1824,82 @@ impl PeerNetwork {
                 StacksMessageType::MicroblocksAvailable(payload)
             })?;
         }
+        self.schedule_block_announcements(subscribers, &availability_data, true);
         Ok(())
     }
 
     /// Update accounting information for relayed messages from a network result.
     /// This influences selecting next-hop neighbors to get data from us.
+    /// Would accepting `len` more bytes of pushed data from this neighbor put it over its relay
+    /// bandwidth cap?  If not, count them against the cap.
+    fn try_accept_relayed_bytes(&mut self, nk: &NeighborKey, len: u64, now: u64) -> bool {
+        let cap = self.connection_opts.max_relay_bandwidth_per_peer;
+        if cap > 0
+            && self.relayer_stats.get_relayed_bandwidth(nk, now) + len / RELAY_BANDWIDTH_WINDOW
+                > cap
+        {
+            increment_relay_throttled_counter();
+            return false;
+        }
+        self.relayer_stats.add_relayed_bytes(nk, len, now);
+        true
+    }
+
+    /// Drop pushed blocks, microblocks, and transactions from neighbors that have sent us more
+    /// than connection_opts.max_relay_bandwidth_per_peer bytes/sec of them, so a few peers can't
+    /// monopolize the relayer.
+    pub fn throttle_relayed_data(&mut self, network_result: &mut NetworkResult) -> () {
+        let now = get_epoch_time_secs();
+
+        for (nk, blocks_data) in network_result.pushed_blocks.iter_mut() {
+            let num_pushed = blocks_data.len();
+            blocks_data.retain(|block_msg| {
+                let len = block_msg.serialize_to_vec().len() as u64;
+                self.try_accept_relayed_bytes(nk, len, now)
+            });
+            if blocks_data.len() < num_pushed {
+                debug!(
+                    "{:?}: Throttled {} BlocksData message(s) from {:?}",
+                    &self.local_peer,
+                    num_pushed - blocks_data.len(),
+                    nk
+                );
+            }
+        }
+
+        for (nk, microblocks_data) in network_result.pushed_microblocks.iter_mut() {
+            let num_pushed = microblocks_data.len();
+            microblocks_data.retain(|(_, microblock_msg)| {
+                let len = microblock_msg.serialize_to_vec().len() as u64;
+                self.try_accept_relayed_bytes(nk, len, now)
+            });
+            if microblocks_data.len() < num_pushed {
+                debug!(
+                    "{:?}: Throttled {} MicroblocksData message(s) from {:?}",
+                    &self.local_peer,
+                    num_pushed - microblocks_data.len(),
+                    nk
+                );
+            }
+        }
+
+        for (nk, txs) in network_result.pushed_transactions.iter_mut() {
+            let num_pushed = txs.len();
+            txs.retain(|(_, tx)| {
+                let len = tx.serialize_to_vec().len() as u64;
+                self.try_accept_relayed_bytes(nk, len, now)
+            });
+            if txs.len() < num_pushed {
+                debug!(
+                    "{:?}: Throttled {} transaction(s) from {:?}",
+                    &self.local_peer,
+                    num_pushed - txs.len(),
+                    nk
+                );
+            }
+        }
+    }
+
     pub fn update_relayer_stats(&mut self, network_result: &NetworkResult) -> () {
         // synchronize
         for (_, convo) in self.peers.iter_mut() {
@@ -1593,6 +1942,7 @@ mod test {
     use chainstate::stacks::test::*;
     use chainstate::stacks::*;
     use chainstate::stacks::*;
+    use core::mempool_policy::MaxTxSizePolicy;
     use net::asn::*;
     use net::chat::*;
     use net::codec::*;
@@ -1670,6 +2020,121 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_relayer_stats_relayed_bandwidth() {
+        let mut relay_stats = RelayerStats::new();
+        let nk = NeighborKey {
+            peer_version: 12345,
+            network_id: 0x80000000,
+            addrbytes: PeerAddress([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 127, 0, 0, 1]),
+            port: 54321,
+        };
+        let mut other_nk = nk.clone();
+        other_nk.port += 1;
+
+        assert_eq!(relay_stats.get_relayed_bandwidth(&nk, 1000), 0);
+
+        relay_stats.add_relayed_bytes(&nk, 60_000, 1000);
+        relay_stats.add_relayed_bytes(&nk, 60_000, 1030);
+        assert_eq!(
+            relay_stats.get_relayed_bandwidth(&nk, 1030),
+            120_000 / RELAY_BANDWIDTH_WINDOW
+        );
+        assert_eq!(relay_stats.get_relayed_bandwidth(&other_nk, 1030), 0);
+
+        // old data ages out
+        assert_eq!(
+            relay_stats.get_relayed_bandwidth(&nk, 1000 + RELAY_BANDWIDTH_WINDOW + 1),
+            60_000 / RELAY_BANDWIDTH_WINDOW
+        );
+        relay_stats.add_relayed_bytes(&nk, 0, 1030 + RELAY_BANDWIDTH_WINDOW + 1);
+        assert_eq!(relay_stats.recent_bytes.get(&nk).unwrap().len(), 1);
+
+        // banned neighbors are forgotten
+        relay_stats.process_neighbor_ban(&nk);
+        assert!(relay_stats.recent_bytes.get(&nk).is_none());
+
+        // never overflow number of neighbors tracked
+        for i in 0..(MAX_RELAYER_STATS + 1) {
+            let mut new_nk = nk.clone();
+            new_nk.peer_version += i as u32;
+            relay_stats.add_relayed_bytes(&new_nk, 1, 1000);
+            assert!(relay_stats.recent_bytes.len() <= MAX_RELAYER_STATS);
+        }
+    }
+
+    #[test]
+    fn test_send_scheduled_block_announcements() {
+        let peer_config =
+            TestPeerConfig::new("test_send_scheduled_block_announcements", 33704, 33705);
+        let mut peer = TestPeer::new(peer_config);
+
+        let nk = NeighborKey {
+            peer_version: 12345,
+            network_id: 0x80000000,
+            addrbytes: PeerAddress([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 127, 0, 0, 1]),
+            port: 54321,
+        };
+
+        // no one has subscribed yet
+        assert!(peer.network.find_block_subscribers().is_empty());
+
+        let mut subscribers = HashSet::new();
+        subscribers.insert(nk.clone());
+
+        // no jitter means the announcement is due right away
+        peer.network.connection_opts.block_announcement_jitter_ms = 0;
+        peer.network.schedule_block_announcements(
+            subscribers.clone(),
+            &BlocksAvailableMap::new(),
+            false,
+        );
+        assert_eq!(peer.network.scheduled_block_announcements.len(), 1);
+        assert_eq!(peer.network.scheduled_block_announcements[0].recipient, nk);
+        assert!(!peer.network.scheduled_block_announcements[0].microblocks);
+
+        peer.network.send_scheduled_block_announcements();
+        assert!(peer.network.scheduled_block_announcements.is_empty());
+
+        // announcements that aren't due yet stay scheduled
+        peer.network
+            .schedule_block_announcements(subscribers, &BlocksAvailableMap::new(), true);
+        peer.network.scheduled_block_announcements[0].send_at = get_epoch_time_ms() + 60_000;
+        peer.network.send_scheduled_block_announcements();
+        assert_eq!(peer.network.scheduled_block_announcements.len(), 1);
+        assert!(peer.network.scheduled_block_announcements[0].microblocks);
+    }
+
+    #[test]
+    fn test_relayer_check_relay_policies() {
+        let all_transactions = codec_all_transactions(
+            &TransactionVersion::Testnet,
+            0x80000000,
+            &TransactionAnchorMode::Any,
+            &TransactionPostConditionMode::Allow,
+        );
+        let tx = &all_transactions[0];
+        let tx_len = tx.serialize_to_vec().len() as u64;
+
+        assert!(Relayer::check_relay_policies(&[], tx).is_ok());
+
+        let policies: Vec<Box<dyn MemPoolAdmissionPolicy>> = vec![Box::new(MaxTxSizePolicy {
+            max_tx_size: tx_len,
+        })];
+        assert!(Relayer::check_relay_policies(&policies, tx).is_ok());
+
+        let policies: Vec<Box<dyn MemPoolAdmissionPolicy>> = vec![
+            Box::new(MaxTxSizePolicy {
+                max_tx_size: tx_len,
+            }),
+            Box::new(MaxTxSizePolicy {
+                max_tx_size: tx_len - 1,
+            }),
+        ];
+        let (policy, _) = Relayer::check_relay_policies(&policies, tx).unwrap_err();
+        assert_eq!(policy, "MaxTxSize");
+    }
+
     #[test]
     fn test_relayer_merge_stats() {
         let mut relayer_stats = RelayerStats::new();
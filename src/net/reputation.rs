@@ -0,0 +1,156 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Peer reputation.  Each time a peer misbehaves, penalty points are added to its score, which is
+//! kept in the peer DB so it survives restarts.  Points decay over time.  A peer whose score
+//! reaches the node's ban threshold is banned for a while, and its score starts over.
+
+use net::PeerAddress;
+
+/// Penalty points for an invalid message (bad preamble, bad signature, malformed payload, ...)
+pub const PENALTY_INVALID_MESSAGE: u64 = 10;
+/// Penalty points for a block or microblock download that could not be completed
+pub const PENALTY_STALLED_DOWNLOAD: u64 = 5;
+/// Penalty points for a protocol violation (e.g. an invalid block inventory)
+pub const PENALTY_PROTOCOL_VIOLATION: u64 = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PeerMisbehavior {
+    InvalidMessage,
+    StalledDownload,
+    ProtocolViolation,
+}
+
+impl PeerMisbehavior {
+    pub fn penalty(&self) -> u64 {
+        match *self {
+            PeerMisbehavior::InvalidMessage => PENALTY_INVALID_MESSAGE,
+            PeerMisbehavior::StalledDownload => PENALTY_STALLED_DOWNLOAD,
+            PeerMisbehavior::ProtocolViolation => PENALTY_PROTOCOL_VIOLATION,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerScore {
+    pub network_id: u32,
+    pub addrbytes: PeerAddress,
+    pub port: u16,
+    /// penalty points as of `last_update`
+    pub score: u64,
+    pub invalid_messages: u64,
+    pub stalled_downloads: u64,
+    pub protocol_violations: u64,
+    pub last_update: u64,
+}
+
+impl PeerScore {
+    pub fn new(network_id: u32, addrbytes: PeerAddress, port: u16) -> PeerScore {
+        PeerScore {
+            network_id,
+            addrbytes,
+            port,
+            score: 0,
+            invalid_messages: 0,
+            stalled_downloads: 0,
+            protocol_violations: 0,
+            last_update: 0,
+        }
+    }
+
+    /// The score at time `now`, if one point decays every `decay_interval` seconds.  A
+    /// `decay_interval` of 0 means points never decay.
+    pub fn current_score(&self, now: u64, decay_interval: u64) -> u64 {
+        if decay_interval == 0 {
+            return self.score;
+        }
+        let decayed = now.saturating_sub(self.last_update) / decay_interval;
+        self.score.saturating_sub(decayed)
+    }
+
+    /// Record a misbehavior at time `now`.  Returns the new score.
+    pub fn add_penalty(
+        &mut self,
+        misbehavior: PeerMisbehavior,
+        now: u64,
+        decay_interval: u64,
+    ) -> u64 {
+        self.score = self
+            .current_score(now, decay_interval)
+            .saturating_add(misbehavior.penalty());
+        self.last_update = now;
+        match misbehavior {
+            PeerMisbehavior::InvalidMessage => self.invalid_messages += 1,
+            PeerMisbehavior::StalledDownload => self.stalled_downloads += 1,
+            PeerMisbehavior::ProtocolViolation => self.protocol_violations += 1,
+        }
+        self.score
+    }
+
+    /// Start over after a ban.  The misbehavior counts are kept.
+    pub fn reset(&mut self, now: u64) {
+        self.score = 0;
+        self.last_update = now;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_peer_score_penalties() {
+        let mut score = PeerScore::new(0x80000000, PeerAddress([0x01; 16]), 20444);
+        assert_eq!(
+            score.add_penalty(PeerMisbehavior::InvalidMessage, 1000, 0),
+            PENALTY_INVALID_MESSAGE
+        );
+        assert_eq!(
+            score.add_penalty(PeerMisbehavior::StalledDownload, 1000, 0),
+            PENALTY_INVALID_MESSAGE + PENALTY_STALLED_DOWNLOAD
+        );
+        assert_eq!(
+            score.add_penalty(PeerMisbehavior::ProtocolViolation, 1000, 0),
+            PENALTY_INVALID_MESSAGE + PENALTY_STALLED_DOWNLOAD + PENALTY_PROTOCOL_VIOLATION
+        );
+        assert_eq!(score.invalid_messages, 1);
+        assert_eq!(score.stalled_downloads, 1);
+        assert_eq!(score.protocol_violations, 1);
+
+        score.reset(1000);
+        assert_eq!(score.current_score(1000, 0), 0);
+        assert_eq!(score.protocol_violations, 1);
+    }
+
+    #[test]
+    fn test_peer_score_decay() {
+        let mut score = PeerScore::new(0x80000000, PeerAddress([0x01; 16]), 20444);
+        score.add_penalty(PeerMisbehavior::ProtocolViolation, 1000, 10);
+        assert_eq!(score.current_score(1000, 10), PENALTY_PROTOCOL_VIOLATION);
+        assert_eq!(
+            score.current_score(1055, 10),
+            PENALTY_PROTOCOL_VIOLATION - 5
+        );
+        assert_eq!(score.current_score(1000 + 10 * 1000, 10), 0);
+
+        // decay is applied before the new penalty is added
+        assert_eq!(
+            score.add_penalty(PeerMisbehavior::InvalidMessage, 1100, 10),
+            PENALTY_PROTOCOL_VIOLATION - 10 + PENALTY_INVALID_MESSAGE
+        );
+        assert_eq!(score.last_update, 1100);
+    }
+}
@@ -17,6 +17,7 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::cmp;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
@@ -24,6 +25,7 @@ use std::io;
 use std::io::prelude::*;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::net::SocketAddr;
+use std::str::FromStr;
 use std::time::Instant;
 use std::{convert::TryFrom, fmt};
 
@@ -41,8 +43,15 @@ use burnchains::Burnchain;
 use burnchains::BurnchainView;
 use burnchains::*;
 use chainstate::burn::db::sortdb::SortitionDB;
+use chainstate::burn::BlockSnapshot;
 use chainstate::burn::ConsensusHash;
+use chainstate::coordinator::{
+    get_reward_set_for_cycle, OnChainRewardSetProvider, PoxAnchorBlockStatus,
+};
+use chainstate::stacks::boot::{BNS_NAMESPACE_LAUNCHABILITY_TTL, BNS_NAME_GRACE_PERIOD_DURATION};
+use chainstate::stacks::db::accounts::{UnlockScheduleEntry, UnlockScheduleKind};
 use chainstate::stacks::db::blocks::CheckError;
+use chainstate::stacks::db::transactions::TransactionSimulationLimits;
 use chainstate::stacks::db::{
     blocks::MINIMUM_TX_FEE_RATE_PER_BYTE, BlockStreamData, StacksChainState,
 };
@@ -59,53 +68,87 @@ use net::db::PeerDB;
 use net::http::*;
 use net::p2p::PeerMap;
 use net::p2p::PeerNetwork;
+use net::readonly_pool::{
+    PendingReadOnlyCall, ReadOnlyCall, ReadOnlyCallBatch, ReadOnlyCallPool, ReadOnlyCallResult,
+};
 use net::relay::Relayer;
+use net::BatchReadOnlyCall;
 use net::ClientError;
 use net::Error as net_error;
 use net::HttpRequestMetadata;
 use net::HttpRequestType;
 use net::HttpResponseMetadata;
 use net::HttpResponseType;
+use net::MempoolNonceStatusResponse;
 use net::MicroblocksData;
 use net::NeighborAddress;
 use net::NeighborsData;
 use net::PeerAddress;
 use net::PeerHost;
 use net::ProtocolFamily;
+use net::RPCRole;
 use net::StacksHttp;
 use net::StacksHttpMessage;
 use net::StacksMessageType;
+use net::TipRequest;
+use net::TransactionSimulationResponse;
+use net::TransactionStatus;
+use net::TransactionStatusResponse;
 use net::UnconfirmedTransactionResponse;
 use net::UnconfirmedTransactionStatus;
 use net::UrlString;
 use net::HTTP_REQUEST_ID_RESERVED;
 use net::MAX_NEIGHBORS_DATA_LEN;
 use net::{
-    AccountEntryResponse, AttachmentPage, CallReadOnlyResponse, ContractSrcResponse,
-    GetAttachmentResponse, GetAttachmentsInvResponse, MapEntryResponse,
+    AccountEntryResponse, AttachmentPage, BatchAccountEntry, BatchAccountsResponse,
+    CallReadOnlyResponse, ContractSrcResponse, DataVarResponse, GetAttachmentResponse,
+    GetAttachmentsInvResponse, MapEntryResponse,
+};
+use net::{AccountUnlockEntry, AccountUnlockKind};
+use net::{
+    AddressAssetsResponse, AddressTransactionEntry, AddressTransactionsResponse,
+    BlockHeaderResponse, BlockStatsResponse, NftHistoryEntry, NftHistoryResponse,
 };
+use net::{BNSNameStatus, RPCBNSNameInfo};
 use net::{BlocksData, GetIsTraitImplementedResponse};
+use net::{BurnBlockSelector, RPCBlockCommitInfo, RPCSortitionInfo};
+use net::{EventReplayResponse, PostEventReplayRequestBody};
+use net::{MempoolDroppedTransactionEntry, MempoolDroppedTransactionsResponse};
+use net::{MempoolTransactionEntry, MempoolTransactionsResponse};
+use net::{PostLogLevelRequestBody, RPCLogLevelsData};
+use net::{PostPeerBanRequestBody, RPCPeerReputationData};
+use net::{
+    RPCBurnAmountDistribution, RPCBurnchainReorg, RPCSortitionStats, SORTITION_STATS_MAX_RANGE,
+};
+use net::{RPCHealthData, RPCHealthStatus};
 use net::{RPCNeighbor, RPCNeighborsInfo};
 use net::{RPCPeerInfoData, RPCPoxInfoData};
+use net::{RPCPoxCycleProjection, RPCPoxProjection};
+use net::{RPCRewardSet, RPCRewardSetEntry};
+use net::{TraitConformanceResponse, TraitFunctionConformance, TraitFunctionStatus};
 use util::db::DBConn;
 use util::db::Error as db_error;
+use util::get_epoch_time_ms;
 use util::get_epoch_time_secs;
 use util::hash::Hash160;
 use util::hash::{hex_bytes, to_hex};
 use vm::database::clarity_store::make_contract_hash_key;
 use vm::types::TraitIdentifier;
 use vm::{
-    analysis::errors::CheckErrors,
+    analysis::types::TraitFunctionCompliance,
+    analysis::AnalysisDatabase,
     costs::{ExecutionCost, LimitedCostTracker},
     database::{
         clarity_store::ContractCommitment, BurnStateDB, ClarityDatabase, ClaritySerializable,
         STXBalance,
     },
     errors::Error as ClarityRuntimeError,
-    errors::Error::Unchecked,
-    errors::InterpreterError,
-    types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
-    ClarityName, ContractName, SymbolicExpression, Value,
+    errors::RuntimeErrorType,
+    types::{
+        AssetIdentifier, FunctionType, PrincipalData, QualifiedContractIdentifier,
+        StandardPrincipalData, TupleData,
+    },
+    ClarityName, ContractName, Value,
 };
 
 use crate::clarity_vm::database::marf::MarfedKV;
@@ -122,14 +165,25 @@ use super::{RPCPoxCurrentCycleInfo, RPCPoxNextCycleInfo};
 
 pub const STREAM_CHUNK_SIZE: u64 = 4096;
 
+/// Replays past event observer payloads on request.  Implemented by the node's event dispatcher
+/// when it keeps a durable event queue.
+pub trait EventReplayDispatcher {
+    /// Queue every payload sent to the observer at `endpoint` since the Stacks block at
+    /// `from_height` for redelivery.  Returns the number of payloads queued.
+    fn replay_events(&self, endpoint: &str, from_height: u64) -> Result<u64, String>;
+}
+
 #[derive(Default)]
 pub struct RPCHandlerArgs<'a> {
     pub exit_at_block_height: Option<&'a u64>,
     pub genesis_chainstate_hash: Sha256Sum,
     pub event_observer: Option<&'a dyn MemPoolEventDispatcher>,
+    pub event_replay: Option<&'a dyn EventReplayDispatcher>,
     pub cost_estimator: Option<&'a dyn CostEstimator>,
     pub fee_estimator: Option<&'a dyn FeeEstimator>,
     pub cost_metric: Option<&'a dyn CostMetric>,
+    /// if set, read-only calls are run on these worker threads instead of the p2p thread
+    pub read_only_call_pool: Option<&'a ReadOnlyCallPool>,
 }
 
 pub struct ConversationHttp {
@@ -140,17 +194,23 @@ pub struct ConversationHttp {
     outbound_url: Option<UrlString>,
     peer_addr: SocketAddr,
     keep_alive: bool,
+    // close the connection after this many requests (0 = no limit)
+    max_requests: u64,
+    // bearer tokens that grant the admin and monitor roles, if configured
+    admin_token: Option<String>,
+    monitor_token: Option<String>,
     total_request_count: u64,     // number of messages taken from the inbox
     total_reply_count: u64,       // number of messages responsed to
     last_request_timestamp: u64, // absolute timestamp of the last time we received at least 1 byte in a request
     last_response_timestamp: u64, // absolute timestamp of the last time we sent at least 1 byte in a response
     connection_time: u64,         // when this converation was instantiated
 
-    // ongoing block streams
+    // ongoing block streams, and replies waiting on read-only calls running in the worker pool
     reply_streams: VecDeque<(
         ReplyHandleHttp,
         Option<(HttpChunkedTransferWriterState, BlockStreamData)>,
         bool,
+        Option<(HttpResponseMetadata, PendingReadOnlyCall)>,
     )>,
 
     // our outstanding request/response to the remote peer, if any
@@ -242,7 +302,426 @@ impl RPCPeerInfoData {
     }
 }
 
+impl RPCHealthData {
+    pub fn from_network(
+        network: &PeerNetwork,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+    ) -> Result<RPCHealthData, net_error> {
+        let burnchain_db = network.burnchain_db.as_ref().ok_or_else(|| {
+            net_error::ChainstateError("Burnchain DB is not open yet".to_string())
+        })?;
+        let burn_block_height = burnchain_db
+            .get_canonical_chain_tip()
+            .map_err(|e| {
+                net_error::ChainstateError(format!("Failed to load burnchain tip: {:?}", &e))
+            })?
+            .block_height;
+
+        let sortition_height = network.burnchain_tip.block_height;
+        let peer_burn_block_height = network
+            .peers
+            .values()
+            .filter(|convo| convo.is_authenticated())
+            .map(|convo| convo.get_burnchain_tip_height())
+            .max()
+            .unwrap_or(0);
+
+        let stacks_tip_consensus_hash = network
+            .burnchain_tip
+            .canonical_stacks_tip_consensus_hash
+            .clone();
+        let stacks_tip_burn_height =
+            SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &stacks_tip_consensus_hash)?
+                .map(|sn| sn.block_height)
+                .unwrap_or(0);
+        let peer_stacks_tip_burn_height = match network.inv_state {
+            Some(ref inv_state) => inv_state
+                .block_stats
+                .values()
+                .filter_map(|stats| stats.inv.get_highest_block_height())
+                .max()
+                .unwrap_or(0),
+            None => 0,
+        };
+
+        let staging_blocks_pending =
+            StacksChainState::count_pending_staging_blocks(chainstate.db())?;
+
+        let burn_lag = network.connection_opts.health_max_burn_block_lag;
+        let stacks_lag = network.connection_opts.health_max_stacks_block_lag;
+        let status = if peer_burn_block_height > burn_block_height + burn_lag {
+            RPCHealthStatus::BurnchainBehind
+        } else if burn_block_height > sortition_height + burn_lag {
+            RPCHealthStatus::SortitionsBehind
+        } else if peer_stacks_tip_burn_height > stacks_tip_burn_height + stacks_lag {
+            RPCHealthStatus::StacksBehind
+        } else {
+            RPCHealthStatus::Synced
+        };
+
+        Ok(RPCHealthData {
+            status,
+            synced: status == RPCHealthStatus::Synced,
+            burn_block_height,
+            sortition_height,
+            peer_burn_block_height,
+            stacks_tip_height: network.burnchain_tip.canonical_stacks_tip_height,
+            stacks_tip: network.burnchain_tip.canonical_stacks_tip_hash.clone(),
+            stacks_tip_consensus_hash,
+            stacks_tip_burn_height,
+            peer_stacks_tip_burn_height,
+            staging_blocks_pending,
+        })
+    }
+}
+
+impl RPCSortitionInfo {
+    /// Look up a burnchain block on the canonical burnchain fork, and report on its sortition.
+    /// Returns NotFoundError if there is no such block.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        selector: &BurnBlockSelector,
+    ) -> Result<RPCSortitionInfo, net_error> {
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let snapshot_opt = match selector {
+            BurnBlockSelector::Height(height) => {
+                if *height > tip.block_height {
+                    None
+                } else {
+                    SortitionDB::get_ancestor_snapshot(
+                        &sortdb.index_conn(),
+                        *height,
+                        &tip.sortition_id,
+                    )?
+                }
+            }
+            BurnBlockSelector::Hash(burn_hash) => sortdb
+                .index_handle(&tip.sortition_id)
+                .get_block_snapshot(burn_hash)?,
+        };
+        let snapshot = snapshot_opt.ok_or(net_error::NotFoundError)?;
+
+        let winning_block_commit = if snapshot.sortition {
+            SortitionDB::get_block_commit(
+                sortdb.conn(),
+                &snapshot.winning_block_txid,
+                &snapshot.sortition_id,
+            )?
+            .map(|commit| RPCBlockCommitInfo {
+                txid: commit.txid.to_hex(),
+                vtxindex: commit.vtxindex,
+                block_header_hash: commit.block_header_hash,
+                parent_block_ptr: commit.parent_block_ptr,
+                parent_vtxindex: commit.parent_vtxindex,
+                burn_fee: commit.burn_fee,
+                sunset_burn: commit.sunset_burn,
+            })
+        } else {
+            None
+        };
+
+        let header_info_opt = if snapshot.sortition {
+            let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                &snapshot.consensus_hash,
+                &snapshot.winning_stacks_block_hash,
+            );
+            StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                chainstate.db(),
+                &index_block_hash,
+            )?
+        } else {
+            None
+        };
+
+        Ok(RPCSortitionInfo {
+            burn_block_height: snapshot.block_height,
+            burn_block_hash: snapshot.burn_header_hash.to_hex(),
+            burn_block_timestamp: snapshot.burn_header_timestamp,
+            consensus_hash: snapshot.consensus_hash.clone(),
+            total_burn: snapshot.total_burn,
+            sortition: snapshot.sortition,
+            winning_block_commit,
+            stacks_block_id: header_info_opt
+                .as_ref()
+                .map(|header_info| header_info.index_block_hash()),
+            stacks_block_height: header_info_opt
+                .as_ref()
+                .map(|header_info| header_info.block_height),
+            missed: snapshot.sortition && header_info_opt.is_none(),
+        })
+    }
+}
+
+impl RPCBNSNameInfo {
+    /// When a name's lease started, following `name-lease-started-at?` in the BNS contract
+    fn lease_started_at(
+        namespace_launched_at: Option<u64>,
+        namespace_revealed_at: u64,
+        registered_at: Option<u64>,
+        imported_at: Option<u64>,
+    ) -> Option<u64> {
+        match (namespace_launched_at, registered_at, imported_at) {
+            (None, _, imported_at) => imported_at,
+            (Some(_), Some(registered_at), _) => Some(registered_at),
+            (Some(launched_at), None, Some(imported_at)) => {
+                if imported_at >= namespace_revealed_at && imported_at <= launched_at {
+                    Some(launched_at)
+                } else {
+                    Some(0)
+                }
+            }
+            (Some(_), None, None) => None,
+        }
+    }
+
+    /// Whether a name resolves at `block_height`, following `name-resolve` in the BNS contract
+    fn name_status(
+        block_height: u64,
+        namespace_launched_at: Option<u64>,
+        namespace_revealed_at: u64,
+        lease_ending_at: Option<u64>,
+        revoked_at: Option<u64>,
+    ) -> BNSNameStatus {
+        if namespace_launched_at.is_none()
+            && namespace_revealed_at + BNS_NAMESPACE_LAUNCHABILITY_TTL <= block_height
+        {
+            return BNSNameStatus::Expired;
+        }
+        if let Some(lease_ending_at) = lease_ending_at {
+            if block_height > lease_ending_at + BNS_NAME_GRACE_PERIOD_DURATION {
+                return BNSNameStatus::Expired;
+            }
+            if block_height > lease_ending_at {
+                return BNSNameStatus::GracePeriod;
+            }
+        }
+        if revoked_at.is_some() {
+            return BNSNameStatus::Revoked;
+        }
+        BNSNameStatus::Active
+    }
+
+    /// Read a name's owner and properties out of the BNS contract's state.
+    /// Returns None if the name does not exist.
+    fn from_clarity_db(
+        clarity_db: &mut ClarityDatabase,
+        mainnet: bool,
+        tip: &StacksBlockId,
+        name: &str,
+        namespace: &str,
+    ) -> Result<Option<RPCBNSNameInfo>, ClarityRuntimeError> {
+        let bns_contract = util::boot::boot_code_id("bns", mainnet);
+        let namespace_key = Value::buff_from(namespace.as_bytes().to_vec())?;
+        let name_key = Value::Tuple(TupleData::from_data(vec![
+            ("name".into(), Value::buff_from(name.as_bytes().to_vec())?),
+            ("namespace".into(), namespace_key.clone()),
+        ])?);
+
+        let name_props = match clarity_db
+            .fetch_entry_unknown_descriptor(&bns_contract, "name-properties", &name_key)?
+            .expect_optional()
+        {
+            Some(props) => props.expect_tuple(),
+            None => {
+                return Ok(None);
+            }
+        };
+        let namespace_props = match clarity_db
+            .fetch_entry_unknown_descriptor(&bns_contract, "namespaces", &namespace_key)?
+            .expect_optional()
+        {
+            Some(props) => props.expect_tuple(),
+            None => {
+                return Ok(None);
+            }
+        };
+        let key_type = clarity_db.get_nft_key_type(&bns_contract, "names")?;
+        let owner = match clarity_db.get_nft_owner(&bns_contract, "names", &name_key, &key_type) {
+            Ok(owner) => owner,
+            Err(ClarityRuntimeError::Runtime(RuntimeErrorType::NoSuchToken, _)) => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        };
+
+        let get_u64 = |tuple: &TupleData, field: &str| -> Option<u64> {
+            tuple
+                .get(field)
+                .expect(&format!("FATAL: no '{}'", field))
+                .to_owned()
+                .expect_optional()
+                .map(|value| value.expect_u128() as u64)
+        };
+        let registered_at = get_u64(&name_props, "registered-at");
+        let imported_at = get_u64(&name_props, "imported-at");
+        let revoked_at = get_u64(&name_props, "revoked-at");
+        let zonefile_hash = name_props
+            .get("zonefile-hash")
+            .expect("FATAL: no 'zonefile-hash'")
+            .to_owned()
+            .expect_buff(20);
+
+        let launched_at = get_u64(&namespace_props, "launched-at");
+        let revealed_at = namespace_props
+            .get("revealed-at")
+            .expect("FATAL: no 'revealed-at'")
+            .to_owned()
+            .expect_u128() as u64;
+        let lifetime = namespace_props
+            .get("lifetime")
+            .expect("FATAL: no 'lifetime'")
+            .to_owned()
+            .expect_u128() as u64;
+
+        let lease_started_at =
+            RPCBNSNameInfo::lease_started_at(launched_at, revealed_at, registered_at, imported_at);
+        let lease_ending_at = match lease_started_at {
+            Some(started_at) if lifetime > 0 => Some(started_at + lifetime),
+            _ => None,
+        };
+        let stacks_block_height = clarity_db.get_current_block_height() as u64;
+        let status = RPCBNSNameInfo::name_status(
+            stacks_block_height,
+            launched_at,
+            revealed_at,
+            lease_ending_at,
+            revoked_at,
+        );
+
+        Ok(Some(RPCBNSNameInfo {
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            owner: owner.to_string(),
+            zonefile_hash: to_hex(&zonefile_hash),
+            status,
+            registered_at,
+            imported_at,
+            revoked_at,
+            lease_started_at,
+            lease_ending_at,
+            index_block_hash: tip.clone(),
+            stacks_block_height,
+        }))
+    }
+
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        name: &str,
+        namespace: &str,
+    ) -> Result<Option<RPCBNSNameInfo>, net_error> {
+        let mainnet = chainstate.mainnet;
+        match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+            clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                RPCBNSNameInfo::from_clarity_db(clarity_db, mainnet, tip, name, namespace)
+            })
+        })? {
+            Some(res) => res.map_err(|e| net_error::ClarityError(e.into())),
+            None => Err(net_error::NotFoundError),
+        }
+    }
+}
+
+impl RPCSortitionStats {
+    /// Gather sortition statistics over a range of the canonical burnchain fork.  The range
+    /// defaults to the last SORTITION_STATS_MAX_RANGE blocks, and may not be larger than that.
+    /// Returns Err(message) if the range is invalid.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        start_height_opt: Option<u64>,
+        end_height_opt: Option<u64>,
+    ) -> Result<Result<RPCSortitionStats, String>, net_error> {
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let (start_height, end_height) = match (start_height_opt, end_height_opt) {
+            (Some(start), Some(end)) => (start, end),
+            (Some(start), None) => (start, start.saturating_add(SORTITION_STATS_MAX_RANGE - 1)),
+            (None, Some(end)) => (end.saturating_sub(SORTITION_STATS_MAX_RANGE - 1), end),
+            (None, None) => (
+                tip.block_height
+                    .saturating_sub(SORTITION_STATS_MAX_RANGE - 1),
+                tip.block_height,
+            ),
+        };
+        if start_height > end_height {
+            return Ok(Err("start_height must not exceed end_height".to_string()));
+        }
+        if end_height - start_height >= SORTITION_STATS_MAX_RANGE {
+            return Ok(Err(format!(
+                "Range may span at most {} blocks",
+                SORTITION_STATS_MAX_RANGE
+            )));
+        }
+
+        let stats = sortdb.get_sortition_stats(start_height, end_height)?;
+        let dist = &stats.winning_burn_amounts;
+        Ok(Ok(RPCSortitionStats {
+            start_height: stats.start_height,
+            end_height: stats.end_height,
+            num_blocks: stats.num_blocks,
+            num_sortitions: stats.num_sortitions,
+            num_flash_blocks: stats.num_flash_blocks,
+            num_missed_sortitions: stats.num_missed_sortitions,
+            winning_burn_amounts: RPCBurnAmountDistribution {
+                count: dist.count,
+                min: dist.min,
+                max: dist.max,
+                mean: dist.mean,
+                p25: dist.p25,
+                median: dist.median,
+                p75: dist.p75,
+                p95: dist.p95,
+            },
+            reorgs: stats
+                .reorgs
+                .iter()
+                .map(|reorg| RPCBurnchainReorg {
+                    fork_height: reorg.fork_height,
+                    depth: reorg.depth,
+                    orphaned_tip: reorg.orphaned_tip.to_hex(),
+                })
+                .collect(),
+        }))
+    }
+}
+
 impl RPCPoxInfoData {
+    /// Find the burnchain snapshot to report PoX info against for the given Stacks block.  For
+    /// the canonical (or unconfirmed) Stacks tip, this is the canonical burnchain tip.  For a
+    /// historical block, this is the sortition in which that block was chosen, so that the
+    /// reward cycle information is consistent with the PoX contract state read at that block.
+    fn get_burnchain_view_at(
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+    ) -> Result<BlockSnapshot, net_error> {
+        let canonical_burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let canonical_stacks_tip = StacksBlockHeader::make_index_block_hash(
+            &canonical_burn_tip.canonical_stacks_tip_consensus_hash,
+            &canonical_burn_tip.canonical_stacks_tip_hash,
+        );
+        if *tip == canonical_stacks_tip {
+            return Ok(canonical_burn_tip);
+        }
+
+        match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            chainstate.db(),
+            tip,
+        )? {
+            Some(header_info) => SortitionDB::get_block_snapshot_consensus(
+                sortdb.conn(),
+                &header_info.consensus_hash,
+            )?
+            .ok_or(net_error::NotFoundError),
+            // not a processed block (e.g. the unconfirmed chain tip)
+            None => Ok(canonical_burn_tip),
+        }
+    }
+
     pub fn from_db(
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
@@ -323,7 +802,7 @@ impl RPCPoxInfoData {
 
         let rejection_votes_left_required = total_required.saturating_sub(current_rejection_votes);
 
-        let burnchain_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let burnchain_tip = RPCPoxInfoData::get_burnchain_view_at(sortdb, chainstate, tip)?;
 
         let pox_consts = &burnchain.pox_constants;
 
@@ -418,6 +897,182 @@ impl RPCPoxInfoData {
     }
 }
 
+impl RPCPoxProjection {
+    fn project_cycle(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        reward_cycle: u64,
+        liquid_ustx: u128,
+        amount_opt: Option<u64>,
+    ) -> Result<RPCPoxCycleProjection, net_error> {
+        let reward_slots = burnchain.pox_constants.reward_slots() as u128;
+        let is_pox_active = chainstate.is_pox_active(sortdb, tip, reward_cycle as u128)?;
+        let stacked_ustx = chainstate.get_total_ustx_stacked(sortdb, tip, reward_cycle as u128)?;
+        let addresses = chainstate.get_reward_addresses_in_cycle(sortdb, reward_cycle, tip)?;
+
+        let min_threshold_ustx = StacksChainState::get_threshold_from_participation(
+            liquid_ustx,
+            stacked_ustx,
+            reward_slots,
+        );
+        let reward_slots_filled = cmp::min(
+            StacksChainState::count_reward_slots(min_threshold_ustx, &addresses),
+            reward_slots,
+        );
+
+        // stacking the amount raises participation, which may raise the threshold
+        let amount_min_threshold_ustx = amount_opt.map(|amount| {
+            StacksChainState::get_threshold_from_participation(
+                liquid_ustx,
+                stacked_ustx.saturating_add(amount as u128),
+                reward_slots,
+            )
+        });
+        let amount_reward_slots = match (amount_opt, amount_min_threshold_ustx) {
+            (Some(amount), Some(threshold)) => Some((amount as u128) / threshold),
+            _ => None,
+        };
+
+        Ok(RPCPoxCycleProjection {
+            id: reward_cycle,
+            is_pox_active,
+            reward_phase_start_block_height: burnchain.reward_cycle_to_block_height(reward_cycle),
+            stacked_ustx: stacked_ustx as u64,
+            min_threshold_ustx: min_threshold_ustx as u64,
+            reward_slots: reward_slots as u64,
+            reward_slots_filled: reward_slots_filled as u64,
+            amount_min_threshold_ustx: amount_min_threshold_ustx.map(|t| t as u64),
+            amount_reward_slots: amount_reward_slots.map(|slots| slots as u64),
+            amount_eligible: amount_reward_slots.map(|slots| is_pox_active && slots > 0),
+        })
+    }
+
+    /// Project the stacking thresholds and reward slots of the current and next reward cycles
+    /// from the PoX contract state at `tip`.  If `amount_opt` is given, also report whether that
+    /// many uSTX would earn any reward slots.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        amount_opt: Option<u64>,
+    ) -> Result<RPCPoxProjection, net_error> {
+        let burnchain_tip = RPCPoxInfoData::get_burnchain_view_at(sortdb, chainstate, tip)?;
+        let reward_cycle = burnchain
+            .block_height_to_reward_cycle(burnchain_tip.block_height)
+            .ok_or(net_error::NotFoundError)?;
+
+        let liquid_ustx = chainstate
+            .maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|clarity_db| clarity_db.get_total_liquid_ustx())
+            })?
+            .ok_or(net_error::NotFoundError)?;
+
+        let current_cycle = RPCPoxProjection::project_cycle(
+            sortdb,
+            chainstate,
+            tip,
+            burnchain,
+            reward_cycle,
+            liquid_ustx,
+            amount_opt,
+        )?;
+        let next_cycle = RPCPoxProjection::project_cycle(
+            sortdb,
+            chainstate,
+            tip,
+            burnchain,
+            reward_cycle + 1,
+            liquid_ustx,
+            amount_opt,
+        )?;
+
+        Ok(RPCPoxProjection {
+            burn_block_height: burnchain_tip.block_height,
+            total_liquid_supply_ustx: liquid_ustx as u64,
+            amount_ustx: amount_opt,
+            current_cycle,
+            next_cycle,
+        })
+    }
+}
+
+impl RPCRewardSet {
+    /// Load the reward set chosen for a reward cycle, recomputing it at the cycle's anchor block.
+    /// Returns NotFoundError if the cycle's anchor block has not been chosen yet.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        burnchain: &Burnchain,
+        reward_cycle: u64,
+    ) -> Result<RPCRewardSet, net_error> {
+        let reward_cycle_info = get_reward_set_for_cycle(
+            reward_cycle,
+            burnchain,
+            chainstate,
+            sortdb,
+            &OnChainRewardSetProvider(),
+        )
+        .map_err(|e| net_error::ChainstateError(format!("Failed to load reward set: {:?}", &e)))?
+        .ok_or(net_error::NotFoundError)?;
+
+        let (anchor_block_hash, reward_set) = match reward_cycle_info.anchor_status {
+            PoxAnchorBlockStatus::SelectedAndKnown(anchor_block_hash, reward_set) => {
+                (Some(anchor_block_hash), Some(reward_set))
+            }
+            PoxAnchorBlockStatus::SelectedAndUnknown(anchor_block_hash) => {
+                (Some(anchor_block_hash), None)
+            }
+            PoxAnchorBlockStatus::NotSelected => (None, None),
+        };
+        let anchor_block_known = reward_set.is_some();
+
+        // each address's slots are adjacent in the reward set
+        let reward_set = reward_set.unwrap_or(vec![]);
+        let mut entries: Vec<RPCRewardSetEntry> = vec![];
+        let mut last_address = None;
+        for address in reward_set.iter() {
+            if last_address == Some(address) {
+                if let Some(entry) = entries.last_mut() {
+                    entry.slots += 1;
+                }
+                continue;
+            }
+            entries.push(RPCRewardSetEntry {
+                bitcoin_address: address.clone().to_b58(),
+                stacks_address: address.to_string(),
+                slots: 1,
+            });
+            last_address = Some(address);
+        }
+
+        Ok(RPCRewardSet {
+            reward_cycle,
+            reward_phase_start_block_height: burnchain.reward_cycle_to_block_height(reward_cycle),
+            anchor_block_hash,
+            anchor_block_known,
+            total_slots: reward_set.len() as u64,
+            entries,
+        })
+    }
+}
+
+impl AccountUnlockEntry {
+    pub fn from_schedule_entry(entry: UnlockScheduleEntry) -> AccountUnlockEntry {
+        AccountUnlockEntry {
+            kind: match entry.kind {
+                UnlockScheduleKind::Vesting => AccountUnlockKind::Vesting,
+                UnlockScheduleKind::Pox => AccountUnlockKind::Pox,
+            },
+            amount: format!("0x{}", to_hex(&entry.amount.to_be_bytes())),
+            unlock_height: entry.unlock_height,
+            unlocked: entry.unlocked,
+        }
+    }
+}
+
 impl RPCNeighborsInfo {
     /// Load neighbor address information from the peer network
     pub fn from_p2p(
@@ -486,6 +1141,13 @@ impl ConversationHttp {
     ) -> ConversationHttp {
         let mut stacks_http = StacksHttp::new(peer_addr.clone());
         stacks_http.maximum_call_argument_size = conn_opts.maximum_call_argument_size;
+        stacks_http.compression_threshold = conn_opts.http_compression_threshold;
+        stacks_http.cors_policy = HttpCorsPolicy {
+            allowed_origins: conn_opts.cors_allowed_origins.clone(),
+            allowed_methods: conn_opts.cors_allowed_methods.clone(),
+            allowed_headers: conn_opts.cors_allowed_headers.clone(),
+            max_age: conn_opts.cors_max_age,
+        };
         ConversationHttp {
             connection: ConnectionHttp::new(stacks_http, conn_opts, None),
             conn_id: conn_id,
@@ -498,6 +1160,9 @@ impl ConversationHttp {
             pending_response: None,
             pending_error_response: None,
             keep_alive: true,
+            max_requests: conn_opts.max_http_requests_per_connection,
+            admin_token: conn_opts.rpc_admin_token.clone(),
+            monitor_token: conn_opts.rpc_monitor_token.clone(),
             total_request_count: 0,
             total_reply_count: 0,
             last_request_timestamp: 0,
@@ -598,7 +1263,7 @@ impl ConversationHttp {
         let reply = self.connection.make_relay_handle(self.conn_id)?;
 
         self.pending_error_response = Some(res);
-        self.reply_streams.push_back((reply, None, false));
+        self.reply_streams.push_back((reply, None, false, None));
         Ok(())
     }
 
@@ -624,59 +1289,238 @@ impl ConversationHttp {
         response.send(http, fd)
     }
 
-    /// Handle a GET pox info.
+    /// Handle a GET node health.
     /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
-    fn handle_getpoxinfo<W: Write>(
+    fn handle_gethealth<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
+        network: &PeerNetwork,
         sortdb: &SortitionDB,
-        chainstate: &mut StacksChainState,
-        tip: &StacksBlockId,
-        burnchain: &Burnchain,
+        chainstate: &StacksChainState,
     ) -> Result<(), net_error> {
         let response_metadata = HttpResponseMetadata::from(req);
+        let response = match RPCHealthData::from_network(network, sortdb, chainstate) {
+            Ok(health) => HttpResponseType::Health(response_metadata, health),
+            Err(e) => {
+                warn!("Failed to get node health {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query node health".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
 
-        match RPCPoxInfoData::from_db(sortdb, chainstate, tip, burnchain) {
-            Ok(pi) => {
-                let response = HttpResponseType::PoxInfo(response_metadata, pi);
-                response.send(http, fd)
+    /// Handle a GET sortition info, by burnchain block height or hash.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_sortition<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        selector: &BurnBlockSelector,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let response = match RPCSortitionInfo::from_db(sortdb, chainstate, selector) {
+            Ok(info) => HttpResponseType::SortitionInfo(response_metadata, info),
+            Err(net_error::NotFoundError) => HttpResponseType::NotFound(
+                response_metadata,
+                "No such burnchain block in the canonical burnchain fork".to_string(),
+            ),
+            Err(e) => {
+                warn!("Failed to get sortition info {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query sortition".to_string(),
+                )
             }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET for a BNS name's owner and zonefile hash, as of the given chain tip.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_bns_name<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        name: &str,
+        namespace: &str,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let response = match RPCBNSNameInfo::from_db(sortdb, chainstate, tip, name, namespace) {
+            Ok(Some(info)) => HttpResponseType::BNSName(response_metadata, info),
+            Ok(None) => HttpResponseType::NotFound(
+                response_metadata,
+                format!("No such BNS name {}.{}", name, namespace),
+            ),
             Err(net_error::NotFoundError) => {
-                debug!("Chain tip not found during get PoX info: {:?}", req);
-                let response = HttpResponseType::NotFound(
-                    response_metadata,
-                    "Failed to find chain tip".to_string(),
-                );
-                response.send(http, fd)
+                HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
             }
             Err(e) => {
-                warn!("Failed to get PoX info {:?}: {:?}", req, &e);
-                let response = HttpResponseType::ServerError(
-                    response_metadata,
-                    "Failed to query peer info".to_string(),
+                warn!(
+                    "Failed to resolve BNS name {}.{}: {:?}",
+                    name, namespace, &e
                 );
-                response.send(http, fd)
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    format!("Failed to resolve BNS name {}.{}", name, namespace),
+                )
             }
-        }
+        };
+        response.send(http, fd).map(|_| ())
     }
 
-    fn handle_getattachmentsinv<W: Write>(
+    /// Handle a GET pox projection.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_getpoxprojection<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
-        atlasdb: &AtlasDB,
-        index_block_hash: &StacksBlockId,
-        pages_indexes: &HashSet<u32>,
-        _options: &ConnectionOptions,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        amount_opt: Option<u64>,
     ) -> Result<(), net_error> {
-        // We are receiving a list of page indexes with a chain tip hash.
-        // The amount of pages_indexes is capped by MAX_ATTACHMENT_INV_PAGES_PER_REQUEST (8)
-        // Pages sizes are controlled by the constant ATTACHMENTS_INV_PAGE_SIZE (8), which
-        // means that a `GET v2/attachments/inv` request can be requesting for a 64 bit vector
-        // at once.
-        // Since clients can be asking for non-consecutive pages indexes (1, 5_000, 10_000, ...),
-        // we will be handling each page index separately.
+        let response_metadata = HttpResponseMetadata::from(req);
+        let response =
+            match RPCPoxProjection::from_db(sortdb, chainstate, tip, burnchain, amount_opt) {
+                Ok(projection) => HttpResponseType::PoxProjection(response_metadata, projection),
+                Err(net_error::NotFoundError) => {
+                    debug!("Chain tip not found during get PoX projection: {:?}", req);
+                    HttpResponseType::NotFound(
+                        response_metadata,
+                        "Failed to find chain tip".to_string(),
+                    )
+                }
+                Err(e) => {
+                    warn!("Failed to get PoX projection {:?}: {:?}", req, &e);
+                    HttpResponseType::ServerError(
+                        response_metadata,
+                        "Failed to query PoX projection".to_string(),
+                    )
+                }
+            };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET reward set for a reward cycle.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_getrewardset<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        burnchain: &Burnchain,
+        reward_cycle: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let response = match RPCRewardSet::from_db(sortdb, chainstate, burnchain, reward_cycle) {
+            Ok(reward_set) => HttpResponseType::RewardSet(response_metadata, reward_set),
+            Err(net_error::NotFoundError) => HttpResponseType::NotFound(
+                response_metadata,
+                format!(
+                    "No PoX anchor block has been chosen for reward cycle {}",
+                    reward_cycle
+                ),
+            ),
+            Err(e) => {
+                warn!("Failed to get reward set {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query reward set".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET sortition stats over a range of burnchain blocks.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_sortition_stats<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        start_height_opt: Option<u64>,
+        end_height_opt: Option<u64>,
+        sortdb: &SortitionDB,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let response = match RPCSortitionStats::from_db(sortdb, start_height_opt, end_height_opt) {
+            Ok(Ok(stats)) => HttpResponseType::SortitionStats(response_metadata, stats),
+            Ok(Err(msg)) => HttpResponseType::BadRequest(response_metadata, msg),
+            Err(e) => {
+                warn!("Failed to get sortition stats {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query sortition stats".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET pox info.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_getpoxinfo<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        match RPCPoxInfoData::from_db(sortdb, chainstate, tip, burnchain) {
+            Ok(pi) => {
+                let response = HttpResponseType::PoxInfo(response_metadata, pi);
+                response.send(http, fd)
+            }
+            Err(net_error::NotFoundError) => {
+                debug!("Chain tip not found during get PoX info: {:?}", req);
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    "Failed to find chain tip".to_string(),
+                );
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!("Failed to get PoX info {:?}: {:?}", req, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query peer info".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
+    }
+
+    fn handle_getattachmentsinv<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        atlasdb: &AtlasDB,
+        index_block_hash: &StacksBlockId,
+        pages_indexes: &HashSet<u32>,
+        _options: &ConnectionOptions,
+    ) -> Result<(), net_error> {
+        // We are receiving a list of page indexes with a chain tip hash.
+        // The amount of pages_indexes is capped by MAX_ATTACHMENT_INV_PAGES_PER_REQUEST (8)
+        // Pages sizes are controlled by the constant ATTACHMENTS_INV_PAGE_SIZE (8), which
+        // means that a `GET v2/attachments/inv` request can be requesting for a 64 bit vector
+        // at once.
+        // Since clients can be asking for non-consecutive pages indexes (1, 5_000, 10_000, ...),
+        // we will be handling each page index separately.
         // We could also add the notion of "budget" so that a client could only get a limited number
         // of pages when they are spanning over many blocks.
         let response_metadata = HttpResponseMetadata::from(req);
@@ -840,6 +1684,85 @@ impl ConversationHttp {
         }
     }
 
+    /// Handle a GET for a processed block's header, along with the totals over the transactions
+    /// processed with it.
+    fn handle_get_block_header<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        index_block_hash: &StacksBlockId,
+        chainstate: &StacksChainState,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let response = match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            chainstate.db(),
+            index_block_hash,
+        ) {
+            Ok(Some(header_info)) => HttpResponseType::GetBlockHeader(
+                response_metadata,
+                BlockHeaderResponse {
+                    index_block_hash: index_block_hash.clone(),
+                    block_hash: header_info.anchored_header.block_hash(),
+                    consensus_hash: header_info.consensus_hash,
+                    parent_block_hash: header_info.anchored_header.parent_block,
+                    block_height: header_info.block_height,
+                    burn_header_hash: header_info.burn_header_hash.to_hex(),
+                    burn_header_height: header_info.burn_header_height,
+                    burn_header_timestamp: header_info.burn_header_timestamp,
+                    anchored_block_size: header_info.anchored_block_size,
+                    stats: header_info.block_stats.map(|stats| BlockStatsResponse {
+                        tx_count: stats.tx_count,
+                        total_fees: format!("0x{}", to_hex(&stats.total_fees.to_be_bytes())),
+                        total_burn: format!("0x{}", to_hex(&stats.total_burn.to_be_bytes())),
+                        total_cost: stats.total_cost,
+                    }),
+                },
+            ),
+            Ok(None) => HttpResponseType::NotFound(
+                response_metadata,
+                format!("No such block {}", index_block_hash.to_hex()),
+            ),
+            Err(e) => {
+                warn!("Failed to load block header {}: {:?}", index_block_hash, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    format!("Failed to query block {}", index_block_hash.to_hex()),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Find the index block hash of the block at the given height in the canonical Stacks fork.
+    /// If there is no such block, a 404 is sent and None is returned.
+    fn handle_load_canonical_block_id_at_height<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        height: u64,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+    ) -> Result<Option<StacksBlockId>, net_error> {
+        let (consensus_hash, block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())?;
+        let tip = StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash);
+        match chainstate
+            .index_conn()?
+            .get_ancestor_block_hash(height, &tip)?
+        {
+            Some(block_id) => Ok(Some(block_id)),
+            None => {
+                let response_metadata = HttpResponseMetadata::from(req);
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    format!("No block at height {} in the canonical Stacks fork", height),
+                );
+                response.send(http, fd).and_then(|_| Ok(None))
+            }
+        }
+    }
+
     /// Handle a GET confirmed microblock stream, by _anchor block hash_.  Start streaming the reply.
     /// The response's preamble (but not the block data) will be synchronously written to the fd
     /// (so use a fd that can buffer!)
@@ -1065,42 +1988,40 @@ impl ConversationHttp {
         let response =
             match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
                 clarity_tx.with_clarity_db_readonly(|clarity_db| {
-                    let key = ClarityDatabase::make_key_for_account_balance(&account);
+                    let entry =
+                        ConversationHttp::read_account_entry(clarity_db, account, with_proof);
+                    let balance = clarity_db.get_account_stx_balance(account);
+                    let stacks_block_height = clarity_db.get_current_block_height() as u64;
                     let burn_block_height = clarity_db.get_current_burnchain_block_height() as u64;
-                    let (balance, balance_proof) = clarity_db
-                        .get_with_proof::<STXBalance>(&key)
-                        .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
-                        .unwrap_or_else(|| (STXBalance::zero(), "".into()));
-                    let balance_proof = if with_proof {
-                        Some(balance_proof)
-                    } else {
-                        None
-                    };
-                    let key = ClarityDatabase::make_key_for_account_nonce(&account);
-                    let (nonce, nonce_proof) = clarity_db
-                        .get_with_proof(&key)
-                        .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
-                        .unwrap_or_else(|| (0, "".into()));
-                    let nonce_proof = if with_proof { Some(nonce_proof) } else { None };
-
-                    let unlocked = balance.get_available_balance_at_burn_block(burn_block_height);
-                    let (locked, unlock_height) =
-                        balance.get_locked_balance_at_burn_block(burn_block_height);
-
-                    let balance = format!("0x{}", to_hex(&unlocked.to_be_bytes()));
-                    let locked = format!("0x{}", to_hex(&locked.to_be_bytes()));
-
-                    AccountEntryResponse {
-                        balance,
-                        locked,
-                        unlock_height,
-                        nonce,
-                        balance_proof,
-                        nonce_proof,
-                    }
+                    (entry, balance, stacks_block_height, burn_block_height)
                 })
             }) {
-                Ok(Some(data)) => HttpResponseType::GetAccount(response_metadata, data),
+                Ok(Some((mut data, balance, stacks_block_height, burn_block_height))) => {
+                    match StacksChainState::get_account_unlock_schedule(
+                        chainstate.db(),
+                        account,
+                        &balance,
+                        stacks_block_height,
+                        burn_block_height,
+                    ) {
+                        Ok(schedule) => {
+                            data.unlock_schedule = Some(
+                                schedule
+                                    .into_iter()
+                                    .map(AccountUnlockEntry::from_schedule_entry)
+                                    .collect(),
+                            );
+                            HttpResponseType::GetAccount(response_metadata, data)
+                        }
+                        Err(e) => {
+                            warn!("Failed to load unlock schedule for {}: {:?}", account, &e);
+                            HttpResponseType::ServerError(
+                                response_metadata,
+                                format!("Failed to load unlock schedule for {}", account),
+                            )
+                        }
+                    }
+                }
                 Ok(None) | Err(_) => {
                     HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
                 }
@@ -1109,56 +2030,45 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
-    /// Handle a GET on a smart contract's data map, given the current chain tip.  Optionally
-    /// supplies a MARF proof for the value.
-    fn handle_get_map_entry<W: Write>(
+    /// Handle a POST for many accounts' balances and nonces at once.  All accounts are read from
+    /// the same chain tip, so the results are mutually consistent.
+    fn handle_get_accounts<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
         tip: &StacksBlockId,
-        contract_addr: &StacksAddress,
-        contract_name: &ContractName,
-        map_name: &ClarityName,
-        key: &Value,
-        with_proof: bool,
+        accounts: &[PrincipalData],
     ) -> Result<(), net_error> {
         let response_metadata = HttpResponseMetadata::from(req);
-        let contract_identifier =
-            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
 
         let response =
             match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
                 clarity_tx.with_clarity_db_readonly(|clarity_db| {
-                    let key = ClarityDatabase::make_key_for_data_map_entry(
-                        &contract_identifier,
-                        map_name,
-                        key,
-                    );
-                    let (value, marf_proof) = clarity_db
-                        .get_with_proof::<Value>(&key)
-                        .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
-                        .unwrap_or_else(|| {
-                            test_debug!("No value for '{}' in {}", &key, tip);
-                            (Value::none(), "".into())
-                        });
-                    let marf_proof = if with_proof {
-                        test_debug!(
-                            "Return a MARF proof of '{}' of {} bytes",
-                            &key,
-                            marf_proof.as_bytes().len()
-                        );
-                        Some(marf_proof)
-                    } else {
-                        None
-                    };
-
-                    let data = format!("0x{}", value.serialize());
-                    MapEntryResponse { data, marf_proof }
+                    accounts
+                        .iter()
+                        .map(|account| {
+                            let entry =
+                                ConversationHttp::read_account_entry(clarity_db, account, false);
+                            BatchAccountEntry {
+                                principal: account.to_string(),
+                                balance: entry.balance,
+                                locked: entry.locked,
+                                unlock_height: entry.unlock_height,
+                                nonce: entry.nonce,
+                            }
+                        })
+                        .collect::<Vec<_>>()
                 })
             }) {
-                Ok(Some(data)) => HttpResponseType::GetMapEntry(response_metadata, data),
+                Ok(Some(accounts)) => HttpResponseType::GetAccounts(
+                    response_metadata,
+                    BatchAccountsResponse {
+                        tip: tip.clone(),
+                        accounts,
+                    },
+                ),
                 Ok(None) | Err(_) => {
                     HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
                 }
@@ -1167,148 +2077,526 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
-    /// Handle a POST to run a read-only function call with the given parameters on the given chain
-    /// tip.  Returns the result of the function call.  Returns a CallReadOnlyResponse on success.
-    fn handle_readonly_function_call<W: Write>(
+    /// Handle a POST to simulate a transaction.  The transaction is executed against the given
+    /// chain tip and the resulting state changes are thrown away.  The transaction need not be
+    /// signed.
+    fn handle_post_transaction_simulation<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
         tip: &StacksBlockId,
-        contract_addr: &StacksAddress,
-        contract_name: &ContractName,
-        function: &ClarityName,
-        sender: &PrincipalData,
-        args: &[Value],
-        options: &ConnectionOptions,
+        tx: &StacksTransaction,
+        limits: &TransactionSimulationLimits,
     ) -> Result<(), net_error> {
         let response_metadata = HttpResponseMetadata::from(req);
-        let contract_identifier =
-            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
-
-        let args: Vec<_> = args
-            .iter()
-            .map(|x| SymbolicExpression::atom_value(x.clone()))
-            .collect();
-        let mainnet = chainstate.mainnet;
-        let mut cost_limit = options.read_only_call_limit.clone();
-        cost_limit.write_length = 0;
-        cost_limit.write_count = 0;
-
-        let data_opt_res =
-            chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
-                let epoch = clarity_tx.get_epoch();
-                let cost_track = clarity_tx
-                    .with_clarity_db_readonly(|clarity_db| {
-                        LimitedCostTracker::new_mid_block(mainnet, cost_limit, clarity_db, epoch)
-                    })
-                    .map_err(|_| {
-                        ClarityRuntimeError::from(InterpreterError::CostContractLoadFailure)
-                    })?;
 
-                clarity_tx.with_readonly_clarity_env(mainnet, sender.clone(), cost_track, |env| {
-                    // we want to execute any function as long as no actual writes are made as
-                    // opposed to be limited to purely calling `define-read-only` functions,
-                    // so use `read_only = false`.  This broadens the number of functions that
-                    // can be called, and also circumvents limitations on `define-read-only`
-                    // functions that can not use `contrac-call?`, even when calling other
-                    // read-only functions
-                    env.execute_contract(&contract_identifier, function.as_str(), &args, false)
-                })
-            });
+        // this thread's view of the unconfirmed state is read-only, so simulate on top of its
+        // anchored parent and replay the microblock stream first.
+        let unconfirmed_parent = match chainstate.unconfirmed_state {
+            Some(ref unconfirmed) if unconfirmed.unconfirmed_chain_tip == *tip => {
+                Some(unconfirmed.confirmed_chain_tip.clone())
+            }
+            _ => None,
+        };
+        let (anchored_tip, microblocks) = match unconfirmed_parent {
+            Some(parent) => {
+                let microblocks = StacksChainState::load_descendant_staging_microblock_stream(
+                    chainstate.db(),
+                    &parent,
+                    0,
+                    u16::MAX,
+                )?
+                .unwrap_or(vec![]);
+                (parent, microblocks)
+            }
+            None => (tip.clone(), vec![]),
+        };
 
-        let response = match data_opt_res {
-            Ok(Some(Ok(data))) => HttpResponseType::CallReadOnlyFunction(
-                response_metadata,
-                CallReadOnlyResponse {
-                    okay: true,
-                    result: Some(format!("0x{}", data.serialize())),
-                    cause: None,
-                },
-            ),
-            Ok(Some(Err(e))) => match e {
-                Unchecked(CheckErrors::CostBalanceExceeded(actual_cost, _))
-                    if actual_cost.write_count > 0 =>
-                {
-                    HttpResponseType::CallReadOnlyFunction(
-                        response_metadata,
-                        CallReadOnlyResponse {
-                            okay: false,
-                            result: None,
-                            cause: Some("NotReadOnly".to_string()),
-                        },
-                    )
-                }
-                _ => HttpResponseType::CallReadOnlyFunction(
-                    response_metadata,
-                    CallReadOnlyResponse {
-                        okay: false,
-                        result: None,
-                        cause: Some(e.to_string()),
-                    },
-                ),
+        let txid = tx.txid();
+        let simulation = match chainstate.simulate_transaction(
+            &sortdb.index_conn(),
+            &anchored_tip,
+            &microblocks,
+            tx,
+            limits,
+        ) {
+            Ok(receipt) => TransactionSimulationResponse {
+                okay: true,
+                txid: txid.to_hex(),
+                tip: tip.clone(),
+                result: Some(format!("0x{}", receipt.result.serialize())),
+                cause: None,
+                post_condition_aborted: receipt.post_condition_aborted,
+                events: receipt
+                    .events
+                    .iter()
+                    .enumerate()
+                    .map(|(event_index, event)| event.json_serialize(event_index, &txid, true))
+                    .collect(),
+                execution_cost: receipt.execution_cost,
             },
-            Ok(None) | Err(_) => {
-                HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+            Err(chain_error::NoSuchBlockError) => {
+                let response =
+                    HttpResponseType::NotFound(response_metadata, "Chain tip not found".into());
+                return response.send(http, fd).map(|_| ());
+            }
+            Err(e) => {
+                debug!("Simulated transaction {} failed: {:?}", &txid, &e);
+                TransactionSimulationResponse {
+                    okay: false,
+                    txid: txid.to_hex(),
+                    tip: tip.clone(),
+                    result: None,
+                    cause: Some(e.to_string()),
+                    post_condition_aborted: false,
+                    events: vec![],
+                    execution_cost: ExecutionCost::zero(),
+                }
             }
         };
+
+        let response = HttpResponseType::TransactionSimulation(response_metadata, simulation);
         response.send(http, fd).map(|_| ())
     }
 
-    /// Handle a GET to fetch a contract's source code, given the chain tip.  Optionally returns a
-    /// MARF proof as well.
-    fn handle_get_contract_src<W: Write>(
+    /// Handle a GET for a page of the transactions that affected a principal.
+    /// Only available if the node maintains the address transaction index.
+    fn handle_get_address_transactions<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
-        sortdb: &SortitionDB,
-        chainstate: &mut StacksChainState,
+        chainstate: &StacksChainState,
         tip: &StacksBlockId,
-        contract_addr: &StacksAddress,
-        contract_name: &ContractName,
-        with_proof: bool,
+        principal: &PrincipalData,
+        offset: u64,
+        limit: u64,
     ) -> Result<(), net_error> {
         let response_metadata = HttpResponseMetadata::from(req);
-        let contract_identifier =
-            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
+        if !chainstate.address_txs_index {
+            let response = HttpResponseType::NotFound(
+                response_metadata,
+                "Address transaction index is not enabled".into(),
+            );
+            return response.send(http, fd).map(|_| ());
+        }
 
-        let response =
-            match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
-                clarity_tx.with_clarity_db_readonly(|db| {
-                    let source = db.get_contract_src(&contract_identifier)?;
-                    let contract_commit_key = make_contract_hash_key(&contract_identifier);
-                    let (contract_commit, proof) = db
-                        .get_with_proof::<ContractCommitment>(&contract_commit_key)
-                        .expect("BUG: obtained source, but couldn't get MARF proof.");
-                    let marf_proof = if with_proof {
-                        Some(proof.to_hex())
-                    } else {
-                        None
-                    };
-                    let publish_height = contract_commit.block_height;
-                    Some(ContractSrcResponse {
-                        source,
-                        publish_height,
-                        marf_proof,
-                    })
-                })
-            }) {
-                Ok(Some(Some(data))) => HttpResponseType::GetContractSrc(response_metadata, data),
-                Ok(Some(None)) => HttpResponseType::NotFound(
+        // only anchored blocks are indexed, so read the unconfirmed tip's parent instead
+        let tip = match chainstate.unconfirmed_state {
+            Some(ref unconfirmed) if unconfirmed.unconfirmed_chain_tip == *tip => {
+                unconfirmed.confirmed_chain_tip.clone()
+            }
+            _ => tip.clone(),
+        };
+
+        let response = match chainstate.get_address_transactions(principal, &tip, offset, limit) {
+            Ok((total, txs)) => HttpResponseType::GetAddressTransactions(
+                response_metadata,
+                AddressTransactionsResponse {
+                    principal: principal.to_string(),
+                    tip,
+                    offset,
+                    limit,
+                    total,
+                    results: txs
+                        .into_iter()
+                        .map(|tx| AddressTransactionEntry {
+                            txid: tx.txid.to_hex(),
+                            index_block_hash: tx.index_block_hash,
+                            block_height: tx.block_height,
+                            tx_index: tx.tx_index,
+                        })
+                        .collect(),
+                },
+            ),
+            Err(e) => {
+                warn!("Failed to load address transactions: {:?}", &e);
+                HttpResponseType::ServerError(
                     response_metadata,
-                    "No contract source data found".into(),
-                ),
+                    "Failed to load address transactions".into(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET for the fungible token balances and non-fungible tokens held by a principal.
+    /// Only available if the node maintains the asset holdings index.
+    fn handle_get_address_assets<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        principal: &PrincipalData,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        if !chainstate.asset_holdings_index {
+            let response = HttpResponseType::NotFound(
+                response_metadata,
+                "Asset holdings index is not enabled".into(),
+            );
+            return response.send(http, fd).map(|_| ());
+        }
+
+        // only anchored blocks are indexed, so read the unconfirmed tip's parent instead
+        let tip = match chainstate.unconfirmed_state {
+            Some(ref unconfirmed) if unconfirmed.unconfirmed_chain_tip == *tip => {
+                unconfirmed.confirmed_chain_tip.clone()
+            }
+            _ => tip.clone(),
+        };
+
+        let response = match chainstate.get_asset_holdings(principal, &tip) {
+            Ok(holdings) => HttpResponseType::GetAddressAssets(
+                response_metadata,
+                AddressAssetsResponse {
+                    principal: principal.to_string(),
+                    tip,
+                    fungible_tokens: holdings
+                        .fungible_tokens
+                        .into_iter()
+                        .map(|(asset, balance)| {
+                            (asset, format!("0x{}", to_hex(&balance.to_be_bytes())))
+                        })
+                        .collect(),
+                    non_fungible_tokens: holdings
+                        .non_fungible_tokens
+                        .into_iter()
+                        .map(|(asset, owned)| {
+                            (
+                                asset,
+                                owned
+                                    .into_iter()
+                                    .map(|value| format!("0x{}", value))
+                                    .collect(),
+                            )
+                        })
+                        .collect(),
+                },
+            ),
+            Err(e) => {
+                warn!("Failed to load asset holdings: {:?}", &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load asset holdings".into(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET for the mints, transfers, and burns of a non-fungible token.
+    /// Only available if the node maintains the asset holdings index.
+    fn handle_get_nft_history<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        asset_identifier: &AssetIdentifier,
+        value: &Value,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        if !chainstate.asset_holdings_index {
+            let response = HttpResponseType::NotFound(
+                response_metadata,
+                "Asset holdings index is not enabled".into(),
+            );
+            return response.send(http, fd).map(|_| ());
+        }
+
+        // only anchored blocks are indexed, so read the unconfirmed tip's parent instead
+        let tip = match chainstate.unconfirmed_state {
+            Some(ref unconfirmed) if unconfirmed.unconfirmed_chain_tip == *tip => {
+                unconfirmed.confirmed_chain_tip.clone()
+            }
+            _ => tip.clone(),
+        };
+
+        let response = match chainstate.get_nft_history(&asset_identifier.to_string(), value, &tip)
+        {
+            Ok(history) => HttpResponseType::GetNftHistory(
+                response_metadata,
+                NftHistoryResponse {
+                    asset_identifier: asset_identifier.to_string(),
+                    value: format!("0x{}", value.serialize()),
+                    tip,
+                    results: history
+                        .into_iter()
+                        .map(|event| NftHistoryEntry {
+                            event: event.event_type.as_str().to_string(),
+                            sender: event.sender.map(|principal| principal.to_string()),
+                            recipient: event.recipient.map(|principal| principal.to_string()),
+                            txid: event.txid.map(|txid| txid.to_hex()),
+                            index_block_hash: event.index_block_hash,
+                            block_height: event.block_height,
+                            tx_index: event.tx_index,
+                            event_index: event.event_index,
+                        })
+                        .collect(),
+                },
+            ),
+            Err(e) => {
+                warn!("Failed to load NFT history: {:?}", &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load NFT history".into(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    fn make_mempool_transaction_entry(
+        txinfo: MemPoolTxFeeRate,
+        rank: u64,
+    ) -> MempoolTransactionEntry {
+        let MemPoolTxFeeRate { tx, fee_rate } = txinfo;
+        MempoolTransactionEntry {
+            txid: tx.metadata.txid.to_hex(),
+            tx: to_hex(&tx.tx.serialize_to_vec()),
+            origin_address: tx.metadata.origin_address.to_string(),
+            origin_nonce: tx.metadata.origin_nonce,
+            sponsor_address: tx.metadata.sponsor_address.to_string(),
+            sponsor_nonce: tx.metadata.sponsor_nonce,
+            tx_fee: tx.metadata.tx_fee,
+            tx_len: tx.metadata.len,
+            accept_time: tx.metadata.accept_time,
+            fee_rate,
+            rank,
+        }
+    }
+
+    /// Handle a GET for a page of the mempool, in the order in which the miner would consider
+    /// its transactions.
+    fn handle_get_mempool_transactions<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        mempool: &MemPoolDB,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let response = match MemPoolDB::get_txs_by_fee_rate(mempool.conn(), offset, limit) {
+            Ok((total, txs)) => HttpResponseType::MempoolTransactions(
+                response_metadata,
+                MempoolTransactionsResponse {
+                    offset,
+                    limit,
+                    total,
+                    results: txs
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, txinfo)| {
+                            ConversationHttp::make_mempool_transaction_entry(
+                                txinfo,
+                                offset.saturating_add(i as u64),
+                            )
+                        })
+                        .collect(),
+                },
+            ),
+            Err(e) => {
+                warn!("Failed to load mempool transactions: {:?}", &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load mempool transactions".into(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET for a page of the transactions recently dropped from the mempool, and why.
+    fn handle_get_mempool_dropped_transactions<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        mempool: &MemPoolDB,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let response = match MemPoolDB::get_dropped_txs(mempool.conn(), offset, limit) {
+            Ok((total, txs)) => HttpResponseType::MempoolDroppedTransactions(
+                response_metadata,
+                MempoolDroppedTransactionsResponse {
+                    offset,
+                    limit,
+                    total,
+                    results: txs
+                        .into_iter()
+                        .map(|dropped| MempoolDroppedTransactionEntry {
+                            txid: dropped.txid.to_hex(),
+                            reason: dropped.reason.to_string(),
+                            dropped_at: dropped.dropped_at,
+                        })
+                        .collect(),
+                },
+            ),
+            Err(e) => {
+                warn!("Failed to load dropped mempool transactions: {:?}", &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load dropped mempool transactions".into(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET for how an address's pending transactions line up with its account nonce,
+    /// including any gaps that keep later transactions from being mined.
+    fn handle_get_mempool_nonce_status<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        mempool: &MemPoolDB,
+        tip: &StacksBlockId,
+        address: &StacksAddress,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let principal = PrincipalData::from(address.clone());
+        let account_nonce =
+            match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx
+                    .with_clarity_db_readonly(|clarity_db| clarity_db.get_account_nonce(&principal))
+            }) {
+                Ok(Some(nonce)) => nonce,
                 Ok(None) | Err(_) => {
-                    HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+                    let response =
+                        HttpResponseType::NotFound(response_metadata, "Chain tip not found".into());
+                    return response.send(http, fd).map(|_| ());
                 }
             };
 
+        let response = match MemPoolDB::get_nonce_status(mempool.conn(), address, account_nonce) {
+            Ok(status) => HttpResponseType::MempoolNonceStatus(
+                response_metadata,
+                MempoolNonceStatusResponse {
+                    address: address.to_string(),
+                    account_nonce: status.account_nonce,
+                    next_nonce: status.next_nonce,
+                    pending_nonces: status.pending_nonces,
+                    future_nonces: status.future_nonces,
+                    missing_nonces: status.missing_nonces,
+                },
+            ),
+            Err(e) => {
+                warn!("Failed to load mempool nonces for {}: {:?}", address, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load mempool nonces".into(),
+                )
+            }
+        };
+
         response.send(http, fd).map(|_| ())
     }
 
-    /// Handle a GET to fetch whether or not a contract implements a certain trait
-    fn handle_get_is_trait_implemented<W: Write>(
+    /// Handle a GET for a page of the pending transactions originated or sponsored by an address.
+    fn handle_get_mempool_address_transactions<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        mempool: &MemPoolDB,
+        address: &StacksAddress,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let load_page = || -> Result<_, db_error> {
+            let (total, txs) =
+                MemPoolDB::get_txs_by_address(mempool.conn(), address, offset, limit)?;
+            let mut results = Vec::with_capacity(txs.len());
+            for txinfo in txs.into_iter() {
+                let rank = MemPoolDB::get_fee_rate_rank(mempool.conn(), &txinfo)?;
+                results.push(ConversationHttp::make_mempool_transaction_entry(
+                    txinfo, rank,
+                ));
+            }
+            Ok((total, results))
+        };
+
+        let response = match load_page() {
+            Ok((total, results)) => HttpResponseType::MempoolTransactions(
+                response_metadata,
+                MempoolTransactionsResponse {
+                    offset,
+                    limit,
+                    total,
+                    results,
+                },
+            ),
+            Err(e) => {
+                warn!(
+                    "Failed to load mempool transactions for {}: {:?}",
+                    address, &e
+                );
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load mempool transactions".into(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Read an account's balance and nonce out of the Clarity DB, optionally with MARF proofs.
+    fn read_account_entry(
+        clarity_db: &mut ClarityDatabase,
+        account: &PrincipalData,
+        with_proof: bool,
+    ) -> AccountEntryResponse {
+        let key = ClarityDatabase::make_key_for_account_balance(&account);
+        let burn_block_height = clarity_db.get_current_burnchain_block_height() as u64;
+        let (balance, balance_proof) = clarity_db
+            .get_with_proof::<STXBalance>(&key)
+            .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
+            .unwrap_or_else(|| (STXBalance::zero(), "".into()));
+        let balance_proof = if with_proof {
+            Some(balance_proof)
+        } else {
+            None
+        };
+        let key = ClarityDatabase::make_key_for_account_nonce(&account);
+        let (nonce, nonce_proof) = clarity_db
+            .get_with_proof(&key)
+            .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
+            .unwrap_or_else(|| (0, "".into()));
+        let nonce_proof = if with_proof { Some(nonce_proof) } else { None };
+
+        let unlocked = balance.get_available_balance_at_burn_block(burn_block_height);
+        let (locked, unlock_height) = balance.get_locked_balance_at_burn_block(burn_block_height);
+
+        let balance = format!("0x{}", to_hex(&unlocked.to_be_bytes()));
+        let locked = format!("0x{}", to_hex(&locked.to_be_bytes()));
+
+        AccountEntryResponse {
+            balance,
+            locked,
+            unlock_height,
+            nonce,
+            balance_proof,
+            nonce_proof,
+            unlock_schedule: None,
+        }
+    }
+
+    /// Handle a GET on a smart contract's data map, given the current chain tip.  Optionally
+    /// supplies a MARF proof for the value.
+    fn handle_get_map_entry<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
@@ -1317,7 +2605,9 @@ impl ConversationHttp {
         tip: &StacksBlockId,
         contract_addr: &StacksAddress,
         contract_name: &ContractName,
-        trait_id: &TraitIdentifier,
+        map_name: &ClarityName,
+        key: &Value,
+        with_proof: bool,
     ) -> Result<(), net_error> {
         let response_metadata = HttpResponseMetadata::from(req);
         let contract_identifier =
@@ -1325,31 +2615,40 @@ impl ConversationHttp {
 
         let response =
             match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
-                clarity_tx.with_clarity_db_readonly(|db| {
-                    let analysis = db.load_contract_analysis(&contract_identifier)?;
-                    if analysis.implemented_traits.contains(trait_id) {
-                        Some(GetIsTraitImplementedResponse {
-                            is_implemented: true,
-                        })
-                    } else {
-                        let trait_defining_contract =
-                            db.load_contract_analysis(&trait_id.contract_identifier)?;
-                        let trait_definition =
-                            trait_defining_contract.get_defined_trait(&trait_id.name)?;
-                        let is_implemented = analysis
-                            .check_trait_compliance(trait_id, trait_definition)
-                            .is_ok();
-                        Some(GetIsTraitImplementedResponse { is_implemented })
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let key = ClarityDatabase::make_key_for_data_map_entry(
+                        &contract_identifier,
+                        map_name,
+                        key,
+                    );
+                    let (value, marf_proof) = clarity_db
+                        .get_with_optional_proof::<Value>(&key, with_proof)
+                        .map(|(a, b)| (a, b.map(|proof| format!("0x{}", proof.to_hex()))))
+                        .unwrap_or_else(|| {
+                            test_debug!("No value for '{}' in {}", &key, tip);
+                            (
+                                Value::none(),
+                                if with_proof { Some("".into()) } else { None },
+                            )
+                        });
+                    if let Some(ref marf_proof) = marf_proof {
+                        test_debug!(
+                            "Return a MARF proof of '{}' of {} bytes",
+                            &key,
+                            marf_proof.as_bytes().len()
+                        );
+                    }
+
+                    let data = format!("0x{}", value.serialize());
+                    let data_json = Some(value.to_json());
+                    MapEntryResponse {
+                        data,
+                        data_json,
+                        marf_proof,
                     }
                 })
             }) {
-                Ok(Some(Some(data))) => {
-                    HttpResponseType::GetIsTraitImplemented(response_metadata, data)
-                }
-                Ok(Some(None)) => HttpResponseType::NotFound(
-                    response_metadata,
-                    "No contract analysis found or trait definition not found".into(),
-                ),
+                Ok(Some(data)) => HttpResponseType::GetMapEntry(response_metadata, data),
                 Ok(None) | Err(_) => {
                     HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
                 }
@@ -1358,12 +2657,9 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
-    /// Handle a GET to fetch a contract's analysis data, given the chain tip.  Note that this isn't
-    /// something that's anchored to the blockchain, and can be different across different versions
-    /// of Stacks -- callers must trust the Stacks node to return correct analysis data.
-    /// Callers who don't trust the Stacks node should just fetch the contract source
-    /// code and analyze it offline.
-    fn handle_get_contract_abi<W: Write>(
+    /// Handle a GET on a smart contract's data var, given the current chain tip.  Optionally
+    /// supplies a MARF proof for the value.
+    fn handle_get_data_var<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
@@ -1372,6 +2668,8 @@ impl ConversationHttp {
         tip: &StacksBlockId,
         contract_addr: &StacksAddress,
         contract_name: &ContractName,
+        var_name: &ClarityName,
+        with_proof: bool,
     ) -> Result<(), net_error> {
         let response_metadata = HttpResponseMetadata::from(req);
         let contract_identifier =
@@ -1379,16 +2677,349 @@ impl ConversationHttp {
 
         let response =
             match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
-                clarity_tx.with_analysis_db_readonly(|db| {
-                    let contract = db.load_contract(&contract_identifier)?;
-                    contract.contract_interface
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let key =
+                        ClarityDatabase::make_key_for_data_var(&contract_identifier, var_name);
+                    clarity_db
+                        .get_with_optional_proof::<Value>(&key, with_proof)
+                        .map(|(value, marf_proof)| {
+                            let marf_proof =
+                                marf_proof.map(|proof| format!("0x{}", proof.to_hex()));
+                            DataVarResponse {
+                                data: format!("0x{}", value.serialize()),
+                                data_json: Some(value.to_json()),
+                                marf_proof,
+                            }
+                        })
                 })
             }) {
-                Ok(Some(Some(data))) => HttpResponseType::GetContractABI(response_metadata, data),
-                Ok(Some(None)) => HttpResponseType::NotFound(
-                    response_metadata,
-                    "No contract interface data found".into(),
-                ),
+                Ok(Some(Some(data))) => HttpResponseType::GetDataVar(response_metadata, data),
+                Ok(Some(None)) => {
+                    HttpResponseType::NotFound(response_metadata, "Data var not found".into())
+                }
+                Ok(None) | Err(_) => {
+                    HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+                }
+            };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a POST to run a read-only function call with the given parameters on the given chain
+    /// tip.  If the node has a read-only call worker pool, the call is handed off to it and
+    /// returned, so the reply can be sent once it finishes.  Calls against the unconfirmed state
+    /// always run here, since the workers do not track it.
+    fn handle_readonly_function_call<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        call: ReadOnlyCall,
+        pool_opt: Option<&ReadOnlyCallPool>,
+    ) -> Result<Option<PendingReadOnlyCall>, net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let unconfirmed = chainstate
+            .unconfirmed_state
+            .as_ref()
+            .map(|unconfirmed_state| unconfirmed_state.unconfirmed_chain_tip == call.tip)
+            .unwrap_or(false);
+
+        let call = match pool_opt {
+            Some(pool) if !unconfirmed => match pool.submit(call) {
+                Ok(pending) => {
+                    return Ok(Some(pending));
+                }
+                Err(_call) => {
+                    debug!("Read-only call queue is full");
+                    let response = HttpResponseType::ServiceUnavailable(
+                        response_metadata,
+                        "Too many read-only calls in progress; try again later".into(),
+                    );
+                    return response.send(http, fd).map(|_| None);
+                }
+            },
+            _ => call,
+        };
+
+        let response = match call.run(sortdb, chainstate) {
+            Some(data) => HttpResponseType::CallReadOnlyFunction(response_metadata, data),
+            None => HttpResponseType::NotFound(response_metadata, "Chain tip not found".into()),
+        };
+        response.send(http, fd).map(|_| None)
+    }
+
+    /// Handle a POST to run a batch of read-only function calls on the given chain tip.  All
+    /// calls see the same chain state.  Like a single call, the batch is handed off to the
+    /// read-only call worker pool if there is one, unless it is against the unconfirmed state.
+    fn handle_readonly_function_call_batch<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        batch: ReadOnlyCallBatch,
+        pool_opt: Option<&ReadOnlyCallPool>,
+    ) -> Result<Option<PendingReadOnlyCall>, net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let unconfirmed = chainstate
+            .unconfirmed_state
+            .as_ref()
+            .map(|unconfirmed_state| unconfirmed_state.unconfirmed_chain_tip == batch.tip)
+            .unwrap_or(false);
+
+        let batch = match pool_opt {
+            Some(pool) if !unconfirmed => match pool.submit_batch(batch) {
+                Ok(pending) => {
+                    return Ok(Some(pending));
+                }
+                Err(_batch) => {
+                    debug!("Read-only call queue is full");
+                    let response = HttpResponseType::ServiceUnavailable(
+                        response_metadata,
+                        "Too many read-only calls in progress; try again later".into(),
+                    );
+                    return response.send(http, fd).map(|_| None);
+                }
+            },
+            _ => batch,
+        };
+
+        let response = match batch.run(sortdb, chainstate) {
+            Some(data) => HttpResponseType::CallReadOnlyFunctionBatch(response_metadata, data),
+            None => HttpResponseType::NotFound(response_metadata, "Chain tip not found".into()),
+        };
+        response.send(http, fd).map(|_| None)
+    }
+
+    /// Handle a GET to fetch a contract's source code, given the chain tip.  Optionally returns a
+    /// MARF proof as well.
+    fn handle_get_contract_src<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+        with_proof: bool,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let contract_identifier =
+            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
+
+        let response =
+            match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|db| {
+                    let source = db.get_contract_src(&contract_identifier)?;
+                    let contract_commit_key = make_contract_hash_key(&contract_identifier);
+                    let (contract_commit, proof) = db
+                        .get_with_proof::<ContractCommitment>(&contract_commit_key)
+                        .expect("BUG: obtained source, but couldn't get MARF proof.");
+                    let marf_proof = if with_proof {
+                        Some(proof.to_hex())
+                    } else {
+                        None
+                    };
+                    let publish_height = contract_commit.block_height;
+                    Some(ContractSrcResponse {
+                        source,
+                        publish_height,
+                        marf_proof,
+                    })
+                })
+            }) {
+                Ok(Some(Some(data))) => HttpResponseType::GetContractSrc(response_metadata, data),
+                Ok(Some(None)) => HttpResponseType::NotFound(
+                    response_metadata,
+                    "No contract source data found".into(),
+                ),
+                Ok(None) | Err(_) => {
+                    HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+                }
+            };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET to fetch whether or not a contract implements a certain trait
+    fn handle_get_is_trait_implemented<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+        trait_id: &TraitIdentifier,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let contract_identifier =
+            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
+
+        let response =
+            match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|db| {
+                    let analysis = db.load_contract_analysis(&contract_identifier)?;
+                    if analysis.implemented_traits.contains(trait_id) {
+                        Some(GetIsTraitImplementedResponse {
+                            is_implemented: true,
+                        })
+                    } else {
+                        let trait_defining_contract =
+                            db.load_contract_analysis(&trait_id.contract_identifier)?;
+                        let trait_definition =
+                            trait_defining_contract.get_defined_trait(&trait_id.name)?;
+                        let is_implemented = analysis
+                            .check_trait_compliance(trait_id, trait_definition)
+                            .is_ok();
+                        Some(GetIsTraitImplementedResponse { is_implemented })
+                    }
+                })
+            }) {
+                Ok(Some(Some(data))) => {
+                    HttpResponseType::GetIsTraitImplemented(response_metadata, data)
+                }
+                Ok(Some(None)) => HttpResponseType::NotFound(
+                    response_metadata,
+                    "No contract analysis found or trait definition not found".into(),
+                ),
+                Ok(None) | Err(_) => {
+                    HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+                }
+            };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Compare a contract against a trait, function by function
+    fn get_trait_conformance(
+        analysis_db: &mut AnalysisDatabase,
+        contract_identifier: &QualifiedContractIdentifier,
+        trait_id: &TraitIdentifier,
+    ) -> Option<TraitConformanceResponse> {
+        let explicitly_implemented = analysis_db
+            .get_implemented_traits(contract_identifier)
+            .ok()?
+            .contains(trait_id);
+        let trait_definition = analysis_db
+            .get_defined_trait(&trait_id.contract_identifier, &trait_id.name)
+            .ok()??;
+        let analysis = analysis_db.load_contract(contract_identifier)?;
+
+        let functions: Vec<_> = trait_definition
+            .iter()
+            .map(|(func_name, expected_sig)| {
+                let status = match analysis.check_trait_function_compliance(func_name, expected_sig)
+                {
+                    TraitFunctionCompliance::Compliant => TraitFunctionStatus::Compliant,
+                    TraitFunctionCompliance::Missing => TraitFunctionStatus::Missing,
+                    TraitFunctionCompliance::BadArguments => TraitFunctionStatus::BadArguments,
+                    TraitFunctionCompliance::BadReturnType => TraitFunctionStatus::BadReturnType,
+                };
+                let func_opt = match (
+                    analysis.get_public_function_type(func_name),
+                    analysis.get_read_only_function_type(func_name),
+                ) {
+                    (Some(FunctionType::Fixed(func)), None)
+                    | (None, Some(FunctionType::Fixed(func))) => Some(func),
+                    _ => None,
+                };
+                TraitFunctionConformance {
+                    name: func_name.to_string(),
+                    status,
+                    expected_args: expected_sig.args.iter().map(|a| a.to_string()).collect(),
+                    expected_returns: expected_sig.returns.to_string(),
+                    args: func_opt
+                        .map(|func| func.args.iter().map(|a| a.signature.to_string()).collect()),
+                    returns: func_opt.map(|func| func.returns.to_string()),
+                }
+            })
+            .collect();
+
+        let conforms = functions
+            .iter()
+            .all(|func| func.status == TraitFunctionStatus::Compliant);
+        Some(TraitConformanceResponse {
+            explicitly_implemented,
+            conforms,
+            functions,
+        })
+    }
+
+    /// Handle a GET to check a contract's conformance to a trait, both by declaration and by
+    /// the signatures of its functions.
+    fn handle_get_trait_conformance<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+        trait_id: &TraitIdentifier,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let contract_identifier =
+            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
+
+        let response =
+            match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_analysis_db_readonly(|analysis_db| {
+                    ConversationHttp::get_trait_conformance(
+                        analysis_db,
+                        &contract_identifier,
+                        trait_id,
+                    )
+                })
+            }) {
+                Ok(Some(Some(data))) => HttpResponseType::TraitConformance(response_metadata, data),
+                Ok(Some(None)) => HttpResponseType::NotFound(
+                    response_metadata,
+                    "No contract analysis found or trait definition not found".into(),
+                ),
+                Ok(None) | Err(_) => {
+                    HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+                }
+            };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET to fetch a contract's analysis data, given the chain tip.  Note that this isn't
+    /// something that's anchored to the blockchain, and can be different across different versions
+    /// of Stacks -- callers must trust the Stacks node to return correct analysis data.
+    /// Callers who don't trust the Stacks node should just fetch the contract source
+    /// code and analyze it offline.
+    fn handle_get_contract_abi<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let contract_identifier =
+            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
+
+        let response =
+            match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_analysis_db_readonly(|db| {
+                    let contract = db.load_contract(&contract_identifier)?;
+                    contract.contract_interface
+                })
+            }) {
+                Ok(Some(Some(data))) => HttpResponseType::GetContractABI(response_metadata, data),
+                Ok(Some(None)) => HttpResponseType::NotFound(
+                    response_metadata,
+                    "No contract interface data found".into(),
+                ),
                 Ok(None) | Err(_) => {
                     HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
                 }
@@ -1536,41 +3167,179 @@ impl ConversationHttp {
         return response.send(http, fd).map(|_| ());
     }
 
-    /// Load up the canonical Stacks chain tip.  Note that this is subject to both burn chain block
-    /// Stacks block availability -- different nodes with different partial replicas of the Stacks chain state
-    /// will return different values here.
-    /// tip_opt is given by the HTTP request as the optional query parameter for the chain tip
-    /// hash.  It will be None if there was no paramter given.
-    /// The order of chain tips this method prefers is as follows:
-    /// * tip_opt, if it's Some(..),
-    /// * the unconfirmed canonical stacks chain tip, if initialized
-    /// * the confirmed canonical stacks chain tip
-    fn handle_load_stacks_chain_tip<W: Write>(
+    /// Handle a GET transaction status.  Looks for the transaction in the canonical Stacks
+    /// fork, the unconfirmed microblock state, the mempool, and the mempool's record of dropped
+    /// transactions, in that order.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_gettransaction_status<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
-        tip_opt: Option<&StacksBlockId>,
-        sortdb: &SortitionDB,
+        network: &PeerNetwork,
         chainstate: &StacksChainState,
-    ) -> Result<Option<StacksBlockId>, net_error> {
-        match tip_opt {
-            Some(tip) => Ok(Some(*tip).clone()),
-            None => match chainstate.get_stacks_chain_tip(sortdb)? {
-                Some(tip) => Ok(Some(StacksBlockHeader::make_index_block_hash(
-                    &tip.consensus_hash,
-                    &tip.anchored_block_hash,
-                ))),
-                None => {
-                    let response_metadata = HttpResponseMetadata::from(req);
-                    warn!("Failed to load Stacks chain tip");
+        mempool: &MemPoolDB,
+        txid: &Txid,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let status =
+            match ConversationHttp::get_transaction_status(network, chainstate, mempool, txid) {
+                Ok(status) => status,
+                Err(e) => {
+                    warn!("Failed to query transaction status {}: {:?}", txid, &e);
                     let response = HttpResponseType::ServerError(
                         response_metadata,
-                        format!("Failed to load Stacks chain tip"),
+                        "Failed to query transaction status".to_string(),
                     );
-                    response.send(http, fd).and_then(|_| Ok(None))
+                    return response.send(http, fd);
                 }
-            },
-        }
+            };
+
+        let response = HttpResponseType::TransactionStatus(
+            response_metadata,
+            TransactionStatusResponse {
+                txid: txid.to_hex(),
+                status,
+            },
+        );
+        response.send(http, fd)
+    }
+
+    fn get_transaction_status(
+        network: &PeerNetwork,
+        chainstate: &StacksChainState,
+        mempool: &MemPoolDB,
+        txid: &Txid,
+    ) -> Result<TransactionStatus, net_error> {
+        // mined in the canonical fork?
+        let tip = StacksBlockHeader::make_index_block_hash(
+            &network.burnchain_tip.canonical_stacks_tip_consensus_hash,
+            &network.burnchain_tip.canonical_stacks_tip_hash,
+        );
+        let tip_height = network.burnchain_tip.canonical_stacks_tip_height;
+        if let Some((block_id, block_height)) =
+            chainstate.get_transaction_block_in_fork(txid, &tip)?
+        {
+            return Ok(TransactionStatus::Anchored {
+                index_block_hash: block_id,
+                block_height,
+                confirmations: tip_height.saturating_sub(block_height) + 1,
+            });
+        }
+
+        // mined in the unconfirmed state?
+        if let Some(ref unconfirmed) = chainstate.unconfirmed_state.as_ref() {
+            if let Some((_, mblock_hash, seq)) = unconfirmed.get_unconfirmed_transaction(txid) {
+                return Ok(TransactionStatus::Microblock {
+                    block_hash: mblock_hash,
+                    seq,
+                });
+            }
+        }
+
+        // pending?
+        if let Some(txinfo) = MemPoolDB::get_tx(mempool.conn(), txid)? {
+            return Ok(TransactionStatus::Mempool {
+                accept_time: txinfo.metadata.accept_time,
+            });
+        }
+
+        // dropped?
+        if let Some(dropped) = MemPoolDB::get_dropped_tx(mempool.conn(), txid)? {
+            return Ok(TransactionStatus::Dropped {
+                reason: dropped.reason.to_string(),
+                dropped_at: dropped.dropped_at,
+            });
+        }
+
+        Ok(TransactionStatus::Unknown)
+    }
+
+    /// Load up the canonical Stacks chain tip.  Note that this is subject to both burn chain block
+    /// Stacks block availability -- different nodes with different partial replicas of the Stacks chain state
+    /// will return different values here.
+    /// tip_opt is given by the HTTP request as the optional query parameter for the chain tip
+    /// hash.  It will be None if there was no paramter given.
+    /// The order of chain tips this method prefers is as follows:
+    /// * tip_opt, if it's Some(..),
+    /// * the unconfirmed canonical stacks chain tip, if initialized
+    /// * the confirmed canonical stacks chain tip
+    fn handle_load_stacks_chain_tip<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        tip_opt: Option<&StacksBlockId>,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+    ) -> Result<Option<StacksBlockId>, net_error> {
+        match tip_opt {
+            Some(tip) => {
+                // can be any processed block, not just the canonical tip
+                let is_unconfirmed_tip = match chainstate.unconfirmed_state {
+                    Some(ref unconfirmed) => unconfirmed.unconfirmed_chain_tip == *tip,
+                    None => false,
+                };
+                if is_unconfirmed_tip || StacksChainState::has_stacks_block(chainstate.db(), tip)? {
+                    Ok(Some(*tip).clone())
+                } else {
+                    let response_metadata = HttpResponseMetadata::from(req);
+                    debug!("No such Stacks block {}", tip);
+                    let response = HttpResponseType::NotFound(
+                        response_metadata,
+                        format!("No such Stacks block {}", tip),
+                    );
+                    response.send(http, fd).and_then(|_| Ok(None))
+                }
+            }
+            None => match chainstate.get_stacks_chain_tip(sortdb)? {
+                Some(tip) => Ok(Some(StacksBlockHeader::make_index_block_hash(
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                ))),
+                None => {
+                    let response_metadata = HttpResponseMetadata::from(req);
+                    warn!("Failed to load Stacks chain tip");
+                    let response = HttpResponseType::ServerError(
+                        response_metadata,
+                        format!("Failed to load Stacks chain tip"),
+                    );
+                    response.send(http, fd).and_then(|_| Ok(None))
+                }
+            },
+        }
+    }
+
+    /// Resolve a request's choice of chain tip.  Asking for the unconfirmed tip yields the tip of
+    /// the unconfirmed microblock stream if there is one, and the confirmed canonical stacks chain
+    /// tip otherwise.
+    fn handle_load_stacks_chain_tip_request<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        tip_req: &TipRequest,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+    ) -> Result<Option<StacksBlockId>, net_error> {
+        match tip_req {
+            TipRequest::SpecificTip(tip) => ConversationHttp::handle_load_stacks_chain_tip(
+                http,
+                fd,
+                req,
+                Some(tip),
+                sortdb,
+                chainstate,
+            ),
+            TipRequest::UseLatestAnchoredTip => ConversationHttp::handle_load_stacks_chain_tip(
+                http, fd, req, None, sortdb, chainstate,
+            ),
+            TipRequest::UseLatestUnconfirmedTip => match chainstate.unconfirmed_state {
+                Some(ref unconfirmed) if unconfirmed.is_readable() => {
+                    Ok(Some(unconfirmed.unconfirmed_chain_tip.clone()))
+                }
+                _ => ConversationHttp::handle_load_stacks_chain_tip(
+                    http, fd, req, None, sortdb, chainstate,
+                ),
+            },
+        }
     }
 
     fn handle_load_stacks_chain_tip_hashes<W: Write>(
@@ -1932,6 +3701,314 @@ impl ConversationHttp {
         response.send(http, fd).and_then(|_| Ok(accepted))
     }
 
+    /// Handle a GET or POST on the node's log levels.  If `update` is given, the per-module log
+    /// level override it names is set (or cleared) before the current levels are reported.
+    fn handle_log_levels<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        update: Option<&PostLogLevelRequestBody>,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        if let Some(update) = update {
+            let level = match update.level {
+                Some(ref level_str) => match slog::Level::from_str(level_str) {
+                    Ok(level) => Some(level),
+                    Err(_) => {
+                        let response = HttpResponseType::BadRequest(
+                            response_metadata,
+                            format!("Unrecognized log level '{}'", level_str),
+                        );
+                        return response.send(http, fd);
+                    }
+                },
+                None => None,
+            };
+            info!("Set log level override";
+                  "module" => %update.module,
+                  "level" => ?level);
+            util::log::set_module_loglevel(&update.module, level);
+        }
+
+        let data = RPCLogLevelsData {
+            default_level: util::log::get_loglevel().as_str().to_string(),
+            modules: util::log::get_module_loglevels()
+                .into_iter()
+                .map(|(module, level)| (module, level.as_str().to_string()))
+                .collect(),
+        };
+        let response = HttpResponseType::LogLevels(response_metadata, data);
+        response.send(http, fd)
+    }
+
+    /// Handle a request to replay past events to an event observer.
+    fn handle_event_replay<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        event_replay: Option<&dyn EventReplayDispatcher>,
+        body: &PostEventReplayRequestBody,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let event_replay = match event_replay {
+            Some(event_replay) => event_replay,
+            None => {
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    "Event replay is not enabled".into(),
+                );
+                return response.send(http, fd);
+            }
+        };
+
+        let response = match event_replay.replay_events(&body.endpoint, body.from_height) {
+            Ok(replayed) => {
+                info!("Replaying events to observer";
+                      "endpoint" => %body.endpoint,
+                      "from_height" => body.from_height,
+                      "replayed" => replayed);
+                HttpResponseType::EventReplay(
+                    response_metadata,
+                    EventReplayResponse {
+                        endpoint: body.endpoint.clone(),
+                        from_height: body.from_height,
+                        replayed,
+                    },
+                )
+            }
+            Err(msg) => HttpResponseType::BadRequest(response_metadata, msg),
+        };
+        response.send(http, fd)
+    }
+
+    /// Describe the reputation of the peer at the given address
+    fn make_peer_reputation_data(
+        network: &PeerNetwork,
+        peer_addr: &PeerAddress,
+        peer_port: u16,
+    ) -> Result<RPCPeerReputationData, net_error> {
+        let network_id = network.local_peer.network_id;
+        let conn = network.peerdb.conn();
+        let now = get_epoch_time_secs();
+
+        let denied_until = match PeerDB::get_peer(conn, network_id, peer_addr, peer_port)? {
+            Some(ref neighbor) if neighbor.is_denied() => {
+                if neighbor.denied < 0 {
+                    u64::MAX
+                } else {
+                    neighbor.denied as u64
+                }
+            }
+            _ => 0,
+        };
+
+        let mut data = RPCPeerReputationData {
+            addr: peer_addr.to_socketaddr(peer_port).to_string(),
+            score: 0,
+            invalid_messages: 0,
+            stalled_downloads: 0,
+            protocol_violations: 0,
+            denied_until,
+            connected: network.find_peer_events(peer_addr, peer_port).len() > 0,
+        };
+        if let Some(score) = PeerDB::get_peer_score(conn, network_id, peer_addr, peer_port)? {
+            data.score =
+                score.current_score(now, network.connection_opts.peer_score_decay_interval);
+            data.invalid_messages = score.invalid_messages;
+            data.stalled_downloads = score.stalled_downloads;
+            data.protocol_violations = score.protocol_violations;
+        }
+        Ok(data)
+    }
+
+    /// Handle a GET on the reputations of all peers we know about or have penalized.
+    fn handle_peer_reputations<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        network: &PeerNetwork,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let network_id = network.local_peer.network_id;
+        let data_res = PeerDB::get_all_peers(network.peerdb.conn())
+            .and_then(|peers| {
+                PeerDB::get_peer_scores(network.peerdb.conn()).map(|scores| (peers, scores))
+            })
+            .map_err(net_error::DBError)
+            .and_then(|(peers, scores)| {
+                let addrs = peers
+                    .into_iter()
+                    .filter(|neighbor| neighbor.addr.network_id == network_id)
+                    .map(|neighbor| (neighbor.addr.addrbytes, neighbor.addr.port))
+                    .chain(
+                        scores
+                            .into_iter()
+                            .filter(|score| score.network_id == network_id)
+                            .map(|score| (score.addrbytes, score.port)),
+                    );
+
+                let mut seen = HashSet::new();
+                let mut data = vec![];
+                for (addrbytes, port) in addrs {
+                    if seen.insert((addrbytes.clone(), port)) {
+                        data.push(ConversationHttp::make_peer_reputation_data(
+                            network, &addrbytes, port,
+                        )?);
+                    }
+                }
+                Ok(data)
+            });
+
+        let response = match data_res {
+            Ok(data) => HttpResponseType::PeerReputations(response_metadata, data),
+            Err(e) => {
+                warn!("Failed to load peer reputations: {:?}", &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load peer reputations".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle a request to ban or unban a peer.
+    fn handle_peer_ban<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        network: &mut PeerNetwork,
+        body: &PostPeerBanRequestBody,
+        ban: bool,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let target = match body.addr.parse::<SocketAddr>() {
+            Ok(addr) => addr,
+            Err(_) => {
+                let response = HttpResponseType::BadRequest(
+                    response_metadata,
+                    format!("Invalid peer address '{}'", &body.addr),
+                );
+                return response.send(http, fd);
+            }
+        };
+        let target_addr = PeerAddress::from_socketaddr(&target);
+
+        let res = if ban {
+            let duration = body
+                .duration
+                .unwrap_or(network.connection_opts.peer_score_ban_duration);
+            info!("Ban peer by operator request";
+                  "addr" => %target,
+                  "duration" => duration);
+            network.ban_peer_address(
+                &target_addr,
+                target.port(),
+                get_epoch_time_secs().saturating_add(duration),
+            )
+        } else {
+            info!("Unban peer by operator request"; "addr" => %target);
+            network.unban_peer_address(&target_addr, target.port())
+        };
+
+        let response = match res.and_then(|_| {
+            ConversationHttp::make_peer_reputation_data(network, &target_addr, target.port())
+        }) {
+            Ok(data) => HttpResponseType::PeerReputation(response_metadata, data),
+            Err(e) => {
+                warn!("Failed to update peer ban for {}: {:?}", &target, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to update peer ban".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Decide whether the client may make this request.  The admin token grants both the admin
+    /// and monitor roles, and the monitor token grants only the monitor role.  If no token that
+    /// could grant the required role is configured, only loopback clients may make the request.
+    /// Returns the response to send if the request is refused.
+    fn check_authorization(&self, req: &HttpRequestType) -> Option<HttpResponseType> {
+        let required_role = req.required_role();
+        if required_role == RPCRole::Public {
+            return None;
+        }
+
+        let mut granting_tokens = vec![];
+        if let Some(ref token) = self.admin_token {
+            granting_tokens.push(token);
+        }
+        if required_role == RPCRole::Monitor {
+            if let Some(ref token) = self.monitor_token {
+                granting_tokens.push(token);
+            }
+        }
+
+        let response_metadata = HttpResponseMetadata::from(req);
+        if granting_tokens.len() == 0 {
+            if self.peer_addr.ip().is_loopback() {
+                return None;
+            }
+            debug!(
+                "Refusing {:?} request from non-loopback peer {}",
+                required_role, &self.peer_addr
+            );
+            return Some(HttpResponseType::Forbidden(
+                response_metadata,
+                "Administrative endpoints are only available to loopback clients".into(),
+            ));
+        }
+
+        let token = match req.metadata().auth_token {
+            Some(ref token) => token,
+            None => {
+                debug!(
+                    "Refusing {:?} request without a bearer token from {}",
+                    required_role, &self.peer_addr
+                );
+                return Some(HttpResponseType::Unauthorized(
+                    response_metadata,
+                    "This endpoint requires a bearer token".into(),
+                ));
+            }
+        };
+        if granting_tokens
+            .iter()
+            .any(|expected| token.matches(expected))
+        {
+            return None;
+        }
+
+        let known_token = self
+            .monitor_token
+            .as_ref()
+            .map(|expected| token.matches(expected))
+            .unwrap_or(false);
+        debug!(
+            "Refusing {:?} request with {} bearer token from {}",
+            required_role,
+            if known_token {
+                "an insufficient"
+            } else {
+                "an invalid"
+            },
+            &self.peer_addr
+        );
+        if known_token {
+            Some(HttpResponseType::Forbidden(
+                response_metadata,
+                "This endpoint requires the admin token".into(),
+            ))
+        } else {
+            Some(HttpResponseType::Unauthorized(
+                response_metadata,
+                "Invalid bearer token".into(),
+            ))
+        }
+    }
+
     /// Handle an external HTTP request.
     /// Some requests, such as those for blocks, will create new reply streams.  This method adds
     /// those new streams into the `reply_streams` set.
@@ -1949,6 +4026,42 @@ impl ConversationHttp {
         let mut reply = self.connection.make_relay_handle(self.conn_id)?;
         let keep_alive = req.metadata().keep_alive;
         let mut ret = None;
+        let mut pending_call = None;
+
+        let endpoint_class = req.endpoint_class();
+        if let Err(wait_ms) = network.rpc_rate_limiter.check(
+            &self.peer_addr.ip(),
+            endpoint_class,
+            get_epoch_time_ms() as u64,
+        ) {
+            debug!(
+                "Rate-limiting {} request from {}",
+                endpoint_class.as_str(),
+                &self.peer_addr
+            );
+            monitoring::increment_rpc_rate_limited_counter(endpoint_class.as_str());
+            let retry_after = (wait_ms + 999) / 1000;
+            let response = HttpResponseType::TooManyRequests(
+                HttpResponseMetadata::from(&req),
+                retry_after,
+                format!(
+                    "Too many {} requests; try again in {} seconds",
+                    endpoint_class.as_str(),
+                    retry_after
+                ),
+            );
+            response.send(&mut self.connection.protocol, &mut reply)?;
+            self.reply_streams
+                .push_back((reply, None, keep_alive, None));
+            return Ok(ret);
+        }
+
+        if let Some(response) = self.check_authorization(&req) {
+            response.send(&mut self.connection.protocol, &mut reply)?;
+            self.reply_streams
+                .push_back((reply, None, keep_alive, None));
+            return Ok(ret);
+        }
 
         let stream_opt = match req {
             HttpRequestType::GetInfo(ref _md) => {
@@ -1983,6 +4096,40 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetPoxProjection(ref _md, ref amount_opt, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_getpoxprojection(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        &network.burnchain,
+                        amount_opt.clone(),
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetRewardSet(ref _md, reward_cycle) => {
+                ConversationHttp::handle_getrewardset(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    &network.burnchain,
+                    reward_cycle,
+                )?;
+                None
+            }
             HttpRequestType::GetNeighbors(ref _md) => {
                 ConversationHttp::handle_getneighbors(
                     &mut self.connection.protocol,
@@ -2001,6 +4148,16 @@ impl ConversationHttp {
                     chainstate,
                 )?
             }
+            HttpRequestType::GetBlockHeader(ref _md, ref index_block_hash) => {
+                ConversationHttp::handle_get_block_header(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    index_block_hash,
+                    chainstate,
+                )?;
+                None
+            }
             HttpRequestType::GetMicroblocksIndexed(ref _md, ref index_head_hash) => {
                 ConversationHttp::handle_getmicroblocks_indexed(
                     &mut self.connection.protocol,
@@ -2019,14 +4176,55 @@ impl ConversationHttp {
                     chainstate,
                 )?
             }
-            HttpRequestType::GetMicroblocksUnconfirmed(
-                ref _md,
-                ref index_anchor_block_hash,
-                ref min_seq,
-            ) => ConversationHttp::handle_getmicroblocks_unconfirmed(
-                &mut self.connection.protocol,
-                &mut reply,
-                &req,
+            HttpRequestType::GetBlockByHeight(ref _md, height) => {
+                match ConversationHttp::handle_load_canonical_block_id_at_height(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    height,
+                    sortdb,
+                    chainstate,
+                )? {
+                    Some(index_block_hash) => ConversationHttp::handle_getblock(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        &index_block_hash,
+                        chainstate,
+                    )?,
+                    None => None,
+                }
+            }
+            HttpRequestType::GetMicroblocksByHeight(ref _md, height) => {
+                // a block's microblock stream is confirmed by its child
+                match ConversationHttp::handle_load_canonical_block_id_at_height(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    height.saturating_add(1),
+                    sortdb,
+                    chainstate,
+                )? {
+                    Some(child_index_block_hash) => {
+                        ConversationHttp::handle_getmicroblocks_confirmed(
+                            &mut self.connection.protocol,
+                            &mut reply,
+                            &req,
+                            &child_index_block_hash,
+                            chainstate,
+                        )?
+                    }
+                    None => None,
+                }
+            }
+            HttpRequestType::GetMicroblocksUnconfirmed(
+                ref _md,
+                ref index_anchor_block_hash,
+                ref min_seq,
+            ) => ConversationHttp::handle_getmicroblocks_unconfirmed(
+                &mut self.connection.protocol,
+                &mut reply,
+                &req,
                 index_anchor_block_hash,
                 *min_seq,
                 chainstate,
@@ -2042,6 +4240,74 @@ impl ConversationHttp {
                 )?;
                 None
             }
+            HttpRequestType::GetMempoolTransactions(ref _md, offset, limit) => {
+                ConversationHttp::handle_get_mempool_transactions(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    mempool,
+                    offset,
+                    limit,
+                )?;
+                None
+            }
+            HttpRequestType::GetMempoolDroppedTransactions(ref _md, offset, limit) => {
+                ConversationHttp::handle_get_mempool_dropped_transactions(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    mempool,
+                    offset,
+                    limit,
+                )?;
+                None
+            }
+            HttpRequestType::GetMempoolAddressTransactions(ref _md, ref address, offset, limit) => {
+                ConversationHttp::handle_get_mempool_address_transactions(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    mempool,
+                    address,
+                    offset,
+                    limit,
+                )?;
+                None
+            }
+            HttpRequestType::GetMempoolNonceStatus(ref _md, ref address, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_mempool_nonce_status(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        mempool,
+                        &tip,
+                        address,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetTransactionStatus(ref _md, ref txid) => {
+                ConversationHttp::handle_gettransaction_status(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                    chainstate,
+                    mempool,
+                    txid,
+                )?;
+                None
+            }
             HttpRequestType::GetAccount(ref _md, ref principal, ref tip_opt, ref with_proof) => {
                 if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
@@ -2064,20 +4330,146 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetAccounts(ref _md, ref principals, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_accounts(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        principals,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::SimulateTransaction(ref _md, ref tx, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_post_transaction_simulation(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        tx,
+                        &self.connection.options.simulate_transaction_limits(),
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetAddressTransactions(
+                ref _md,
+                ref principal,
+                ref tip_opt,
+                offset,
+                limit,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_address_transactions(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        principal,
+                        offset,
+                        limit,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetAddressAssets(ref _md, ref principal, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_address_assets(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        principal,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetNftHistory(
+                ref _md,
+                ref contract_addr,
+                ref contract_name,
+                ref asset_name,
+                ref value,
+                ref tip_opt,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    let asset_identifier = AssetIdentifier {
+                        contract_identifier: QualifiedContractIdentifier::new(
+                            contract_addr.clone().into(),
+                            contract_name.clone(),
+                        ),
+                        asset_name: asset_name.clone(),
+                    };
+                    ConversationHttp::handle_get_nft_history(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        &asset_identifier,
+                        value,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetMapEntry(
                 ref _md,
                 ref contract_addr,
                 ref contract_name,
                 ref map_name,
                 ref key,
-                ref tip_opt,
+                ref tip_req,
                 ref with_proof,
             ) => {
-                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip_request(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
-                    tip_opt.as_ref(),
+                    tip_req,
                     sortdb,
                     chainstate,
                 )? {
@@ -2097,6 +4489,37 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetDataVar(
+                ref _md,
+                ref contract_addr,
+                ref contract_name,
+                ref var_name,
+                ref tip_req,
+                ref with_proof,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip_request(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_data_var(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        contract_addr,
+                        contract_name,
+                        var_name,
+                        *with_proof,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetTransferCost(ref _md) => {
                 ConversationHttp::handle_token_transfer_cost(
                     &mut self.connection.protocol,
@@ -2161,20 +4584,54 @@ impl ConversationHttp {
                     sortdb,
                     chainstate,
                 )? {
-                    ConversationHttp::handle_readonly_function_call(
+                    let call = ReadOnlyCall {
+                        tip,
+                        contract_id: QualifiedContractIdentifier::new(
+                            ctrct_addr.clone().into(),
+                            ctrct_name.clone(),
+                        ),
+                        function: func_name.clone(),
+                        sender: as_sender.clone(),
+                        args: args.clone(),
+                        limits: self.connection.options.read_only_call_limits(),
+                    };
+                    pending_call = ConversationHttp::handle_readonly_function_call(
                         &mut self.connection.protocol,
                         &mut reply,
                         &req,
                         sortdb,
                         chainstate,
-                        &tip,
-                        ctrct_addr,
-                        ctrct_name,
-                        func_name,
-                        as_sender,
-                        args,
-                        &self.connection.options,
-                    )?;
+                        call,
+                        handler_opts.read_only_call_pool,
+                    )?
+                    .map(|pending| (HttpResponseMetadata::from(&req), pending));
+                }
+                None
+            }
+            HttpRequestType::CallReadOnlyFunctionBatch(ref _md, ref calls, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    let batch = ReadOnlyCallBatch {
+                        tip,
+                        calls: calls.clone(),
+                        limits: self.connection.options.read_only_call_limits(),
+                    };
+                    pending_call = ConversationHttp::handle_readonly_function_call_batch(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        batch,
+                        handler_opts.read_only_call_pool,
+                    )?
+                    .map(|pending| (HttpResponseMetadata::from(&req), pending));
                 }
                 None
             }
@@ -2354,37 +4811,182 @@ impl ConversationHttp {
                 }
                 None
             }
-            HttpRequestType::ClientError(ref _md, ref err) => {
-                let response_metadata = HttpResponseMetadata::from(&req);
-                let response = match err {
-                    ClientError::Message(s) => HttpResponseType::BadRequestJSON(
-                        response_metadata,
-                        serde_json::Value::String(s.to_string()),
-                    ),
-                    ClientError::NotFound(path) => {
-                        HttpResponseType::NotFound(response_metadata, path.clone())
-                    }
-                };
-
-                response
-                    .send(&mut self.connection.protocol, &mut reply)
-                    .map(|_| ())?;
-                None
-            }
-        };
-
-        match stream_opt {
-            None => {
-                self.reply_streams.push_back((reply, None, keep_alive));
-            }
-            Some(stream) => {
-                self.reply_streams.push_back((
-                    reply,
+            HttpRequestType::GetTraitConformance(
+                ref _md,
+                ref contract_addr,
+                ref contract_name,
+                ref trait_id,
+                ref tip_opt,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_trait_conformance(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        contract_addr,
+                        contract_name,
+                        trait_id,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetSortition(ref _md, ref selector) => {
+                ConversationHttp::handle_get_sortition(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    selector,
+                    sortdb,
+                    chainstate,
+                )?;
+                None
+            }
+            HttpRequestType::GetSortitionStats(ref _md, start_height_opt, end_height_opt) => {
+                ConversationHttp::handle_get_sortition_stats(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    start_height_opt,
+                    end_height_opt,
+                    sortdb,
+                )?;
+                None
+            }
+            HttpRequestType::GetBNSName(ref _md, ref name, ref namespace, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_bns_name(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        name,
+                        namespace,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetHealth(ref _md) => {
+                ConversationHttp::handle_gethealth(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                    sortdb,
+                    chainstate,
+                )?;
+                None
+            }
+            HttpRequestType::GetLogLevels(ref _md) => {
+                ConversationHttp::handle_log_levels(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    None,
+                )?;
+                None
+            }
+            HttpRequestType::PostLogLevel(ref _md, ref body) => {
+                ConversationHttp::handle_log_levels(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    Some(body),
+                )?;
+                None
+            }
+            HttpRequestType::PostEventReplay(ref _md, ref body) => {
+                ConversationHttp::handle_event_replay(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    handler_opts.event_replay,
+                    body,
+                )?;
+                None
+            }
+            HttpRequestType::GetPeerReputations(ref _md) => {
+                ConversationHttp::handle_peer_reputations(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                )?;
+                None
+            }
+            HttpRequestType::PostPeerBan(ref _md, ref body) => {
+                ConversationHttp::handle_peer_ban(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                    body,
+                    true,
+                )?;
+                None
+            }
+            HttpRequestType::PostPeerUnban(ref _md, ref body) => {
+                ConversationHttp::handle_peer_ban(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                    body,
+                    false,
+                )?;
+                None
+            }
+            HttpRequestType::ClientError(ref _md, ref err) => {
+                let response_metadata = HttpResponseMetadata::from(&req);
+                let response = match err {
+                    ClientError::Message(s) => HttpResponseType::BadRequestJSON(
+                        response_metadata,
+                        serde_json::Value::String(s.to_string()),
+                    ),
+                    ClientError::NotFound(path) => {
+                        HttpResponseType::NotFound(response_metadata, path.clone())
+                    }
+                };
+
+                response
+                    .send(&mut self.connection.protocol, &mut reply)
+                    .map(|_| ())?;
+                None
+            }
+        };
+
+        match stream_opt {
+            None => {
+                self.reply_streams
+                    .push_back((reply, None, keep_alive, pending_call));
+            }
+            Some(stream) => {
+                self.reply_streams.push_back((
+                    reply,
                     Some((
                         HttpChunkedTransferWriterState::new(STREAM_CHUNK_SIZE as usize),
                         stream,
                     )),
                     keep_alive,
+                    None,
                 ));
             }
         }
@@ -2411,9 +5013,38 @@ impl ConversationHttp {
             self.reply_streams.len()
         );
         match self.reply_streams.front_mut() {
-            Some((ref mut reply, ref mut stream_opt, ref keep_alive)) => {
+            Some((ref mut reply, ref mut stream_opt, ref keep_alive, ref mut pending_call_opt)) => {
                 do_keep_alive = *keep_alive;
 
+                // if we're waiting on a read-only call, send its result once it's ready
+                if let Some((response_metadata, pending_call)) = pending_call_opt.take() {
+                    let response = match pending_call.try_get_result() {
+                        Ok(None) => {
+                            // still running
+                            *pending_call_opt = Some((response_metadata, pending_call));
+                            return Ok(());
+                        }
+                        Ok(Some(Some(ReadOnlyCallResult::Call(data)))) => {
+                            HttpResponseType::CallReadOnlyFunction(response_metadata, data)
+                        }
+                        Ok(Some(Some(ReadOnlyCallResult::Batch(data)))) => {
+                            HttpResponseType::CallReadOnlyFunctionBatch(response_metadata, data)
+                        }
+                        Ok(Some(None)) => HttpResponseType::NotFound(
+                            response_metadata,
+                            "Chain tip not found".into(),
+                        ),
+                        Err(e) => {
+                            warn!("Failed to run read-only call: {:?}", &e);
+                            HttpResponseType::ServerError(
+                                response_metadata,
+                                "Failed to run read-only call".into(),
+                            )
+                        }
+                    };
+                    response.send(&mut self.connection.protocol, reply)?;
+                }
+
                 // if we're streaming, make some progress on the stream
                 match stream_opt {
                     Some((ref mut http_chunk_state, ref mut stream)) => {
@@ -2610,6 +5241,18 @@ impl ConversationHttp {
         self.connection_time
     }
 
+    /// When was the last time we sent or received any data?
+    pub fn get_last_activity_time(&self) -> u64 {
+        self.connection_time
+            .max(self.last_request_timestamp)
+            .max(self.last_response_timestamp)
+    }
+
+    /// Is this a kept-alive connection that is just waiting for its next request?
+    pub fn is_idle_keep_alive(&self) -> bool {
+        self.keep_alive && self.is_drained()
+    }
+
     /// Make progress on in-flight requests and replies.
     /// Returns the list of transactions we'll need to forward to the peer network
     pub fn chat(
@@ -2639,9 +5282,14 @@ impl ConversationHttp {
             };
 
             match msg {
-                StacksHttpMessage::Request(req) => {
+                StacksHttpMessage::Request(mut req) => {
                     // new request
                     self.total_request_count += 1;
+                    if self.max_requests > 0 && self.total_request_count >= self.max_requests {
+                        // this is the last request we'll take on this connection, so tell the
+                        // client to open a new one
+                        req.metadata_mut().keep_alive = false;
+                    }
                     self.last_request_timestamp = get_epoch_time_secs();
                     let start_time = Instant::now();
                     let path = req.get_path();
@@ -2740,6 +5388,11 @@ impl ConversationHttp {
         HttpRequestType::GetInfo(HttpRequestMetadata::from_host(self.peer_host.clone()))
     }
 
+    /// Make a new gethealth request to this endpoint
+    pub fn new_gethealth(&self) -> HttpRequestType {
+        HttpRequestType::GetHealth(HttpRequestMetadata::from_host(self.peer_host.clone()))
+    }
+
     /// Make a new getinfo request to this endpoint
     pub fn new_getpoxinfo(&self, tip_opt: Option<StacksBlockId>) -> HttpRequestType {
         HttpRequestType::GetPoxInfo(
@@ -2748,6 +5401,27 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request for projected stacking figures, optionally for a given amount
+    pub fn new_getpoxprojection(
+        &self,
+        amount_opt: Option<u64>,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetPoxProjection(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            amount_opt,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request for the reward set of a reward cycle
+    pub fn new_getrewardset(&self, reward_cycle: u64) -> HttpRequestType {
+        HttpRequestType::GetRewardSet(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            reward_cycle,
+        )
+    }
+
     /// Make a new getneighbors request to this endpoint
     pub fn new_getneighbors(&self) -> HttpRequestType {
         HttpRequestType::GetNeighbors(HttpRequestMetadata::from_host(self.peer_host.clone()))
@@ -2761,6 +5435,14 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request for a block's header and statistics
+    pub fn new_get_block_header(&self, index_block_hash: StacksBlockId) -> HttpRequestType {
+        HttpRequestType::GetBlockHeader(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            index_block_hash,
+        )
+    }
+
     /// Make a new get-microblocks request to this endpoint
     pub fn new_getmicroblocks_indexed(
         &self,
@@ -2783,6 +5465,76 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request for the block at the given height in the canonical fork
+    pub fn new_getblock_by_height(&self, height: u64) -> HttpRequestType {
+        HttpRequestType::GetBlockByHeight(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            height,
+        )
+    }
+
+    /// Make a new request for the microblock stream produced by the block at the given height in
+    /// the canonical fork
+    pub fn new_getmicroblocks_by_height(&self, height: u64) -> HttpRequestType {
+        HttpRequestType::GetMicroblocksByHeight(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            height,
+        )
+    }
+
+    /// Make a new request for the sortition in a burnchain block
+    pub fn new_get_sortition(&self, selector: BurnBlockSelector) -> HttpRequestType {
+        HttpRequestType::GetSortition(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            selector,
+        )
+    }
+
+    /// Make a new request for sortition statistics over a range of burnchain blocks
+    pub fn new_get_sortition_stats(
+        &self,
+        start_height: Option<u64>,
+        end_height: Option<u64>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetSortitionStats(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            start_height,
+            end_height,
+        )
+    }
+
+    /// Make a new request to check a contract's conformance to a trait
+    pub fn new_gettraitconformance(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        trait_id: TraitIdentifier,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetTraitConformance(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            trait_id,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request to resolve a BNS name, optionally as of an ancestor block
+    pub fn new_get_bns_name(
+        &self,
+        name: String,
+        namespace: String,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetBNSName(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            name,
+            namespace,
+            tip_opt,
+        )
+    }
+
     /// Make a new get-microblocks request for unconfirmed microblocks
     pub fn new_getmicroblocks_unconfirmed(
         &self,
@@ -2804,6 +5556,14 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new get-tx-status request
+    pub fn new_gettransaction_status(&self, txid: Txid) -> HttpRequestType {
+        HttpRequestType::GetTransactionStatus(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            txid,
+        )
+    }
+
     /// Make a new post-transaction request
     pub fn new_post_transaction(&self, tx: StacksTransaction) -> HttpRequestType {
         HttpRequestType::PostTransaction(
@@ -2813,6 +5573,19 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new transaction simulation request
+    pub fn new_post_transaction_simulation(
+        &self,
+        tx: StacksTransaction,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::SimulateTransaction(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            tx,
+            tip_opt,
+        )
+    }
+
     /// Make a new post-block request
     pub fn new_post_block(&self, ch: ConsensusHash, block: StacksBlock) -> HttpRequestType {
         HttpRequestType::PostBlock(
@@ -2850,60 +5623,222 @@ impl ConversationHttp {
         )
     }
 
-    /// Make a new request for a data map
-    pub fn new_getmapentry(
+    /// Make a new request for many accounts at once
+    pub fn new_getaccounts(
         &self,
-        contract_addr: StacksAddress,
-        contract_name: ContractName,
-        map_name: ClarityName,
-        key: Value,
+        principals: Vec<PrincipalData>,
         tip_opt: Option<StacksBlockId>,
-        with_proof: bool,
     ) -> HttpRequestType {
-        HttpRequestType::GetMapEntry(
+        HttpRequestType::GetAccounts(
             HttpRequestMetadata::from_host(self.peer_host.clone()),
-            contract_addr,
-            contract_name,
-            map_name,
-            key,
+            principals,
             tip_opt,
-            with_proof,
         )
     }
 
-    /// Make a new request to get a contract's source
-    pub fn new_getcontractsrc(
+    /// Make a new request for a principal's token holdings
+    pub fn new_get_address_assets(
         &self,
-        contract_addr: StacksAddress,
-        contract_name: ContractName,
+        principal: PrincipalData,
         tip_opt: Option<StacksBlockId>,
-        with_proof: bool,
     ) -> HttpRequestType {
-        HttpRequestType::GetContractSrc(
+        HttpRequestType::GetAddressAssets(
             HttpRequestMetadata::from_host(self.peer_host.clone()),
-            contract_addr,
-            contract_name,
+            principal,
             tip_opt,
-            with_proof,
         )
     }
 
-    /// Make a new request to get a contract's ABI
-    pub fn new_getcontractabi(
+    /// Make a new request for the history of a non-fungible token
+    pub fn new_get_nft_history(
         &self,
         contract_addr: StacksAddress,
         contract_name: ContractName,
+        asset_name: ClarityName,
+        value: Value,
         tip_opt: Option<StacksBlockId>,
     ) -> HttpRequestType {
-        HttpRequestType::GetContractABI(
+        HttpRequestType::GetNftHistory(
             HttpRequestMetadata::from_host(self.peer_host.clone()),
             contract_addr,
             contract_name,
+            asset_name,
+            value,
             tip_opt,
         )
     }
 
-    /// Make a new request to run a read-only function
+    /// Make a new request for a page of a principal's transactions
+    pub fn new_get_address_transactions(
+        &self,
+        principal: PrincipalData,
+        tip_opt: Option<StacksBlockId>,
+        offset: u64,
+        limit: u64,
+    ) -> HttpRequestType {
+        HttpRequestType::GetAddressTransactions(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            principal,
+            tip_opt,
+            offset,
+            limit,
+        )
+    }
+
+    /// Make a new request to replay events to an event observer
+    pub fn new_post_event_replay(&self, endpoint: String, from_height: u64) -> HttpRequestType {
+        HttpRequestType::PostEventReplay(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            PostEventReplayRequestBody {
+                endpoint,
+                from_height,
+            },
+        )
+    }
+
+    /// Make a new request for the reputations of all known peers
+    pub fn new_get_peer_reputations(&self) -> HttpRequestType {
+        HttpRequestType::GetPeerReputations(HttpRequestMetadata::from_host(self.peer_host.clone()))
+    }
+
+    /// Make a new request to ban a peer
+    pub fn new_post_peer_ban(&self, addr: String, duration: Option<u64>) -> HttpRequestType {
+        HttpRequestType::PostPeerBan(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            PostPeerBanRequestBody { addr, duration },
+        )
+    }
+
+    /// Make a new request to unban a peer
+    pub fn new_post_peer_unban(&self, addr: String) -> HttpRequestType {
+        HttpRequestType::PostPeerUnban(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            PostPeerBanRequestBody {
+                addr,
+                duration: None,
+            },
+        )
+    }
+
+    /// Make a new request for a page of the mempool, in fee-rate order
+    pub fn new_get_mempool_transactions(&self, offset: u64, limit: u64) -> HttpRequestType {
+        HttpRequestType::GetMempoolTransactions(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            offset,
+            limit,
+        )
+    }
+
+    /// Make a new request for a page of recently-dropped mempool transactions
+    pub fn new_get_mempool_dropped_transactions(&self, offset: u64, limit: u64) -> HttpRequestType {
+        HttpRequestType::GetMempoolDroppedTransactions(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            offset,
+            limit,
+        )
+    }
+
+    /// Make a new request for the nonce status of an address's pending transactions
+    pub fn new_get_mempool_nonce_status(
+        &self,
+        address: StacksAddress,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetMempoolNonceStatus(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            address,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request for a page of an address's pending transactions
+    pub fn new_get_mempool_address_transactions(
+        &self,
+        address: StacksAddress,
+        offset: u64,
+        limit: u64,
+    ) -> HttpRequestType {
+        HttpRequestType::GetMempoolAddressTransactions(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            address,
+            offset,
+            limit,
+        )
+    }
+
+    /// Make a new request for a data map
+    pub fn new_getmapentry(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        map_name: ClarityName,
+        key: Value,
+        tip_req: TipRequest,
+        with_proof: bool,
+    ) -> HttpRequestType {
+        HttpRequestType::GetMapEntry(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            map_name,
+            key,
+            tip_req,
+            with_proof,
+        )
+    }
+
+    /// Make a new request for a data var
+    pub fn new_getdatavar(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        var_name: ClarityName,
+        tip_req: TipRequest,
+        with_proof: bool,
+    ) -> HttpRequestType {
+        HttpRequestType::GetDataVar(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            var_name,
+            tip_req,
+            with_proof,
+        )
+    }
+
+    /// Make a new request to get a contract's source
+    pub fn new_getcontractsrc(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        tip_opt: Option<StacksBlockId>,
+        with_proof: bool,
+    ) -> HttpRequestType {
+        HttpRequestType::GetContractSrc(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            tip_opt,
+            with_proof,
+        )
+    }
+
+    /// Make a new request to get a contract's ABI
+    pub fn new_getcontractabi(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetContractABI(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request to run a read-only function
     pub fn new_callreadonlyfunction(
         &self,
         contract_addr: StacksAddress,
@@ -2924,6 +5859,19 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request to run a batch of read-only function calls
+    pub fn new_callreadonlyfunction_batch(
+        &self,
+        calls: Vec<BatchReadOnlyCall>,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::CallReadOnlyFunctionBatch(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            calls,
+            tip_opt,
+        )
+    }
+
     /// Make a new request for attachment inventory page
     pub fn new_getattachmentsinv(
         &self,
@@ -2952,12 +5900,14 @@ mod test {
     use chainstate::stacks::db::blocks::test::*;
     use chainstate::stacks::db::BlockStreamData;
     use chainstate::stacks::db::StacksChainState;
+    use chainstate::stacks::db::TRANSACTION_LOG;
     use chainstate::stacks::miner::*;
     use chainstate::stacks::test::*;
     use chainstate::stacks::Error as chain_error;
     use chainstate::stacks::*;
     use net::codec::*;
     use net::http::*;
+    use net::reputation::{PeerMisbehavior, PeerScore};
     use net::test::*;
     use net::*;
     use util::get_epoch_time_secs;
@@ -3457,7 +6407,1495 @@ mod test {
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_gethealth() {
+        let peer_server_health = RefCell::new(None);
+        test_rpc(
+            "test_rpc_gethealth",
+            40190,
+            40191,
+            50190,
+            50191,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let health = RPCHealthData::from_network(
+                    &peer_server.network,
+                    peer_server.sortdb.as_ref().unwrap(),
+                    &peer_server.stacks_node.as_ref().unwrap().chainstate,
+                )
+                .unwrap();
+
+                *peer_server_health.borrow_mut() = Some(health);
+
+                convo_client.new_gethealth()
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Health(response_md, health) => {
+                        assert_eq!(Some((*health).clone()), *peer_server_health.borrow());
+                        assert_eq!(health.synced, health.status == RPCHealthStatus::Synced);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getpoxinfo() {
+        let pox_server_info = RefCell::new(None);
+        test_rpc(
+            "test_rpc_getpoxinfo",
+            40000,
+            40001,
+            50000,
+            50001,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = {
+                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                    StacksBlockHeader::make_index_block_hash(
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                    )
+                };
+                let pox_info = RPCPoxInfoData::from_db(
+                    &mut sortdb,
+                    chainstate,
+                    &stacks_block_id,
+                    &peer_client.config.burnchain,
+                )
+                .unwrap();
+                *pox_server_info.borrow_mut() = Some(pox_info);
+                convo_client.new_getpoxinfo(None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::PoxInfo(response_md, pox_data) => {
+                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getpoxprojection() {
+        let pox_server_projection = RefCell::new(None);
+        test_rpc(
+            "test_rpc_getpoxprojection",
+            40256,
+            40257,
+            50256,
+            50257,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = {
+                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                    StacksBlockHeader::make_index_block_hash(
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                    )
+                };
+                let projection = RPCPoxProjection::from_db(
+                    &mut sortdb,
+                    chainstate,
+                    &stacks_block_id,
+                    &peer_client.config.burnchain,
+                    Some(1_000_000_000),
+                )
+                .unwrap();
+                *pox_server_projection.borrow_mut() = Some(projection);
+                convo_client.new_getpoxprojection(Some(1_000_000_000), None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::PoxProjection(_, projection) => {
+                        assert_eq!(Some((*projection).clone()), *pox_server_projection.borrow());
+                        assert_eq!(projection.next_cycle.id, projection.current_cycle.id + 1);
+                        assert_eq!(projection.amount_ustx, Some(1_000_000_000));
+                        assert!(projection.next_cycle.amount_eligible.is_some());
+                        assert!(
+                            projection.next_cycle.amount_min_threshold_ustx.unwrap()
+                                >= projection.next_cycle.min_threshold_ustx
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getrewardset() {
+        test_rpc(
+            "test_rpc_getrewardset",
+            40258,
+            40259,
+            50258,
+            50259,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // no anchor block can be chosen before the first burnchain block
+                convo_client.new_getrewardset(0)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::RewardSet(_, reward_set) => {
+                        assert_eq!(reward_set.reward_cycle, 0);
+                        assert_eq!(reward_set.anchor_block_hash, None);
+                        assert!(!reward_set.anchor_block_known);
+                        assert_eq!(reward_set.total_slots, 0);
+                        assert_eq!(reward_set.entries.len(), 0);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getrewardset_future_cycle() {
+        test_rpc(
+            "test_rpc_getrewardset_future_cycle",
+            40260,
+            40261,
+            50260,
+            50261,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| { convo_client.new_getrewardset(1_000_000) },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::NotFound(..) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getneighbors() {
+        test_rpc(
+            "test_rpc_getneighbors",
+            40010,
+            40011,
+            50010,
+            50011,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| { convo_client.new_getneighbors() },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Neighbors(response_md, neighbor_info) => {
+                        assert_eq!(neighbor_info.sample.len(), 1);
+                        assert_eq!(neighbor_info.sample[0].port, peer_client.config.server_port); // we see ourselves as the neighbor
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_unconfirmed_getblock() {
+        let server_block_cell = RefCell::new(None);
+
+        test_rpc(
+            "test_rpc_unconfirmed_getblock",
+            40020,
+            40021,
+            50020,
+            50021,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // have "server" peer store a block to staging
+                let peer_server_block = make_codec_test_block(25);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block.block_hash(),
+                );
+
+                test_debug!("Store peer server index block {:?}", &index_block_hash);
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &peer_server_consensus_hash,
+                    &peer_server_block,
+                    &ConsensusHash([0x03; 20]),
+                    456,
+                    123,
+                );
+
+                *server_block_cell.borrow_mut() = Some(peer_server_block);
+
+                // now ask for it
+                convo_client.new_getblock(index_block_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Block(response_md, block_info) => {
+                        assert_eq!(
+                            block_info.block_hash(),
+                            (*server_block_cell.borrow()).as_ref().unwrap().block_hash()
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_confirmed_getblock() {
+        let server_block_cell = RefCell::new(None);
+
+        test_rpc(
+            "test_rpc_confirmed_getblock",
+            40030,
+            40031,
+            50030,
+            50031,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // have "server" peer store a block to staging
+                let peer_server_block = make_codec_test_block(25);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block.block_hash(),
+                );
+
+                test_debug!("Store peer server index block {:?}", &index_block_hash);
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &peer_server_consensus_hash,
+                    &peer_server_block,
+                    &ConsensusHash([0x03; 20]),
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &peer_server_consensus_hash,
+                    &peer_server_block.block_hash(),
+                    true,
+                );
+
+                *server_block_cell.borrow_mut() = Some(peer_server_block);
+
+                // now ask for it
+                convo_client.new_getblock(index_block_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Block(response_md, block_info) => {
+                        assert_eq!(
+                            block_info.block_hash(),
+                            (*server_block_cell.borrow()).as_ref().unwrap().block_hash()
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getblock_by_height() {
+        let tip_block_hash_cell = RefCell::new(None);
+
+        test_rpc(
+            "test_rpc_getblock_by_height",
+            40248,
+            40249,
+            50248,
+            50249,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let (_, block_hash) = SortitionDB::get_canonical_stacks_chain_tip_hash(
+                    peer_server.sortdb.as_ref().unwrap().conn(),
+                )
+                .unwrap();
+                *tip_block_hash_cell.borrow_mut() = Some(block_hash);
+
+                // the canonical tip is the first block
+                convo_client.new_getblock_by_height(1)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::Block(_, block) => {
+                        assert_eq!(Some(block.block_hash()), *tip_block_hash_cell.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_block_header() {
+        let tip_block_id_cell = RefCell::new(None);
+
+        test_rpc(
+            "test_rpc_get_block_header",
+            40276,
+            40277,
+            50276,
+            50277,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let (consensus_hash, block_hash) =
+                    SortitionDB::get_canonical_stacks_chain_tip_hash(
+                        peer_server.sortdb.as_ref().unwrap().conn(),
+                    )
+                    .unwrap();
+                let tip = StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash);
+                *tip_block_id_cell.borrow_mut() = Some(tip.clone());
+                convo_client.new_get_block_header(tip)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::GetBlockHeader(_, header) => {
+                        assert_eq!(
+                            Some(header.index_block_hash.clone()),
+                            *tip_block_id_cell.borrow()
+                        );
+                        assert_eq!(header.block_height, 1);
+                        // at least the coinbase was processed
+                        assert!(header.stats.as_ref().unwrap().tx_count >= 1);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_getblock_by_height() {
+        test_rpc(
+            "test_rpc_missing_getblock_by_height",
+            40250,
+            40251,
+            50250,
+            50251,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // the first block's microblock stream is not confirmed yet
+                convo_client.new_getmicroblocks_by_height(1)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert!(msg.contains("height 2"));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_sortition() {
+        let tip_cell = RefCell::new(None);
+
+        test_rpc(
+            "test_rpc_get_sortition",
+            40252,
+            40253,
+            50252,
+            50253,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let sortdb = peer_server.sortdb.as_ref().unwrap();
+                let (consensus_hash, block_hash) =
+                    SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn()).unwrap();
+                let snapshot =
+                    SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &consensus_hash)
+                        .unwrap()
+                        .unwrap();
+                *tip_cell.borrow_mut() = Some((
+                    snapshot.block_height,
+                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash),
+                ));
+
+                // the sortition that chose the canonical Stacks tip
+                convo_client
+                    .new_get_sortition(BurnBlockSelector::Hash(snapshot.burn_header_hash.clone()))
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::SortitionInfo(_, info) => {
+                        let (burn_height, index_block_hash) = tip_cell.borrow().clone().unwrap();
+                        assert_eq!(info.burn_block_height, burn_height);
+                        assert!(info.sortition);
+                        assert!(!info.missed);
+                        assert!(info.winning_block_commit.is_some());
+                        assert_eq!(info.stacks_block_id, Some(index_block_hash));
+                        assert_eq!(info.stacks_block_height, Some(1));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_sortition_stats() {
+        test_rpc(
+            "test_rpc_get_sortition_stats",
+            40268,
+            40269,
+            50268,
+            50269,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| { convo_client.new_get_sortition_stats(None, None) },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::SortitionStats(_, stats) => {
+                        let sortdb = peer_server.sortdb.as_ref().unwrap();
+                        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+                        assert_eq!(stats.end_height, tip.block_height);
+                        assert_eq!(
+                            stats.num_sortitions + stats.num_flash_blocks,
+                            stats.num_blocks
+                        );
+                        assert!(stats.num_sortitions > 0);
+                        assert_eq!(stats.winning_burn_amounts.count, stats.num_sortitions);
+                        assert_eq!(stats.reorgs, vec![]);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_sortition_stats_bad_range() {
+        test_rpc(
+            "test_rpc_sortition_stats_bad_range",
+            40270,
+            40271,
+            50270,
+            50271,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_sortition_stats(Some(0), Some(SORTITION_STATS_MAX_RANGE))
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::BadRequest(..) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_sortition() {
+        test_rpc(
+            "test_rpc_missing_sortition",
+            40254,
+            40255,
+            50254,
+            50255,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_sortition(BurnBlockSelector::Height(u32::MAX as u64))
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::NotFound(..) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_bns_name() {
+        test_rpc(
+            "test_rpc_missing_bns_name",
+            40262,
+            40263,
+            50262,
+            50263,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_bns_name("alice".to_string(), "btc".to_string(), None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::NotFound(..) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_bns_name_status() {
+        // registered after launch
+        assert_eq!(
+            RPCBNSNameInfo::lease_started_at(Some(100), 50, Some(150), None),
+            Some(150)
+        );
+        // imported between reveal and launch
+        assert_eq!(
+            RPCBNSNameInfo::lease_started_at(Some(100), 50, None, Some(60)),
+            Some(100)
+        );
+        // namespace not launched yet
+        assert_eq!(
+            RPCBNSNameInfo::lease_started_at(None, 50, None, Some(60)),
+            Some(60)
+        );
+
+        assert_eq!(
+            RPCBNSNameInfo::name_status(1000, Some(100), 50, Some(1000), None),
+            BNSNameStatus::Active
+        );
+        assert_eq!(
+            RPCBNSNameInfo::name_status(1001, Some(100), 50, Some(1000), None),
+            BNSNameStatus::GracePeriod
+        );
+        assert_eq!(
+            RPCBNSNameInfo::name_status(
+                1001 + BNS_NAME_GRACE_PERIOD_DURATION,
+                Some(100),
+                50,
+                Some(1000),
+                None
+            ),
+            BNSNameStatus::Expired
+        );
+        assert_eq!(
+            RPCBNSNameInfo::name_status(1000, Some(100), 50, None, Some(900)),
+            BNSNameStatus::Revoked
+        );
+        assert_eq!(
+            RPCBNSNameInfo::name_status(50 + BNS_NAMESPACE_LAUNCHABILITY_TTL, None, 50, None, None),
+            BNSNameStatus::Expired
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_indexed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            "test_rpc_indexed_microblocks",
+            40040,
+            40041,
+            50040,
+            50041,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let parent_block = make_codec_test_block(25);
+                let parent_consensus_hash = ConsensusHash([0x02; 20]);
+                let parent_index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &parent_consensus_hash,
+                    &parent_block.block_hash(),
+                );
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
+                mblocks.truncate(15);
+
+                let mut child_block = make_codec_test_block(25);
+                let child_consensus_hash = ConsensusHash([0x03; 20]);
+
+                child_block.header.parent_block = parent_block.block_hash();
+                child_block.header.parent_microblock =
+                    mblocks.last().as_ref().unwrap().block_hash();
+                child_block.header.parent_microblock_sequence =
+                    mblocks.last().as_ref().unwrap().header.sequence;
+
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &parent_consensus_hash,
+                    &parent_block,
+                    &ConsensusHash([0x01; 20]),
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &parent_consensus_hash,
+                    &parent_block.block_hash(),
+                    true,
+                );
+
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block,
+                    &parent_consensus_hash,
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                    true,
+                );
+
+                let index_microblock_hash = StacksBlockHeader::make_index_block_hash(
+                    &parent_consensus_hash,
+                    &mblocks.last().as_ref().unwrap().block_hash(),
+                );
+
+                for mblock in mblocks.iter() {
+                    store_staging_microblock(
+                        peer_server.chainstate(),
+                        &parent_consensus_hash,
+                        &parent_block.block_hash(),
+                        &mblock,
+                    );
+                }
+
+                set_microblocks_processed(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                    &mblocks.last().as_ref().unwrap().block_hash(),
+                );
+
+                *server_microblocks_cell.borrow_mut() = mblocks;
+
+                convo_client.new_getmicroblocks_indexed(index_microblock_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match (*http_response).clone() {
+                    HttpResponseType::Microblocks(_, mut microblocks) => {
+                        microblocks.reverse();
+                        assert_eq!(microblocks.len(), (*server_microblocks_cell.borrow()).len());
+                        assert_eq!(microblocks, *server_microblocks_cell.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_confirmed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            "test_rpc_confirmed_microblocks",
+            40042,
+            40043,
+            50042,
+            50043,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let parent_block = make_codec_test_block(25);
+                let parent_consensus_hash = ConsensusHash([0x02; 20]);
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
+                mblocks.truncate(15);
+
+                let mut child_block = make_codec_test_block(25);
+                let child_consensus_hash = ConsensusHash([0x03; 20]);
+
+                child_block.header.parent_block = parent_block.block_hash();
+                child_block.header.parent_microblock =
+                    mblocks.last().as_ref().unwrap().block_hash();
+                child_block.header.parent_microblock_sequence =
+                    mblocks.last().as_ref().unwrap().header.sequence;
+
+                let child_index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                );
+
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &parent_consensus_hash,
+                    &parent_block,
+                    &ConsensusHash([0x01; 20]),
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &parent_consensus_hash,
+                    &parent_block.block_hash(),
+                    true,
+                );
+
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block,
+                    &parent_consensus_hash,
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                    true,
+                );
+
+                for mblock in mblocks.iter() {
+                    store_staging_microblock(
+                        peer_server.chainstate(),
+                        &parent_consensus_hash,
+                        &parent_block.block_hash(),
+                        &mblock,
+                    );
+                }
+
+                set_microblocks_processed(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                    &mblocks.last().as_ref().unwrap().block_hash(),
+                );
+
+                *server_microblocks_cell.borrow_mut() = mblocks;
+
+                convo_client.new_getmicroblocks_confirmed(child_index_block_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match (*http_response).clone() {
+                    HttpResponseType::Microblocks(_, mut microblocks) => {
+                        microblocks.reverse();
+                        assert_eq!(microblocks.len(), (*server_microblocks_cell.borrow()).len());
+                        assert_eq!(microblocks, *server_microblocks_cell.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_unconfirmed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            "test_rpc_unconfirmed_microblocks",
+            40050,
+            40051,
+            50050,
+            50051,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let consensus_hash = ConsensusHash([0x02; 20]);
+                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
+                let index_block_hash =
+                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
+                mblocks.truncate(15);
+
+                for mblock in mblocks.iter() {
+                    store_staging_microblock(
+                        peer_server.chainstate(),
+                        &consensus_hash,
+                        &anchored_block_hash,
+                        &mblock,
+                    );
+                }
+
+                *server_microblocks_cell.borrow_mut() = mblocks;
+
+                // start at seq 5
+                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 5)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Microblocks(response_md, microblocks) => {
+                        assert_eq!(microblocks.len(), 10);
+                        assert_eq!(
+                            *microblocks,
+                            (*server_microblocks_cell.borrow())[5..].to_vec()
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_unconfirmed_transaction() {
+        let last_txid = RefCell::new(Txid([0u8; 32]));
+        let last_mblock = RefCell::new(BlockHeaderHash([0u8; 32]));
+
+        test_rpc(
+            "test_rpc_unconfirmed_transaction",
+            40052,
+            40053,
+            50052,
+            50053,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let sortdb = peer_server.sortdb.take().unwrap();
+                Relayer::setup_unconfirmed_state(peer_server.chainstate(), &sortdb).unwrap();
+                peer_server.sortdb = Some(sortdb);
+
+                assert!(peer_server.chainstate().unconfirmed_state.is_some());
+                let (txid, mblock_hash) = match peer_server.chainstate().unconfirmed_state {
+                    Some(ref unconfirmed) => {
+                        assert!(unconfirmed.mined_txs.len() > 0);
+                        let mut txid = Txid([0u8; 32]);
+                        let mut mblock_hash = BlockHeaderHash([0u8; 32]);
+                        for (next_txid, (_, mbh, ..)) in unconfirmed.mined_txs.iter() {
+                            txid = next_txid.clone();
+                            mblock_hash = mbh.clone();
+                            break;
+                        }
+                        (txid, mblock_hash)
+                    }
+                    None => {
+                        panic!("No unconfirmed state");
+                    }
+                };
+
+                *last_txid.borrow_mut() = txid.clone();
+                *last_mblock.borrow_mut() = mblock_hash.clone();
+
+                convo_client.new_gettransaction_unconfirmed(txid)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::UnconfirmedTransaction(response_md, unconfirmed_resp) => {
+                        assert_eq!(
+                            unconfirmed_resp.status,
+                            UnconfirmedTransactionStatus::Microblock {
+                                block_hash: (*last_mblock.borrow()).clone(),
+                                seq: 0
+                            }
+                        );
+                        let tx = StacksTransaction::consensus_deserialize(
+                            &mut &hex_bytes(&unconfirmed_resp.tx).unwrap()[..],
+                        )
+                        .unwrap();
+                        assert_eq!(tx.txid(), *last_txid.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_transaction_status_microblock() {
+        let last_txid = RefCell::new(Txid([0u8; 32]));
+        let last_mblock = RefCell::new(BlockHeaderHash([0u8; 32]));
+
+        test_rpc(
+            "test_rpc_transaction_status_microblock",
+            40200,
+            40201,
+            50200,
+            50201,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let sortdb = peer_server.sortdb.take().unwrap();
+                Relayer::setup_unconfirmed_state(peer_server.chainstate(), &sortdb).unwrap();
+                peer_server.sortdb = Some(sortdb);
+
+                let (txid, mblock_hash) = match peer_server.chainstate().unconfirmed_state {
+                    Some(ref unconfirmed) => {
+                        let (txid, (_, mblock_hash, ..)) = unconfirmed
+                            .mined_txs
+                            .iter()
+                            .next()
+                            .expect("No unconfirmed transactions");
+                        (txid.clone(), mblock_hash.clone())
+                    }
+                    None => {
+                        panic!("No unconfirmed state");
+                    }
+                };
+
+                *last_txid.borrow_mut() = txid.clone();
+                *last_mblock.borrow_mut() = mblock_hash.clone();
+
+                convo_client.new_gettransaction_status(txid)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::TransactionStatus(response_md, status_resp) => {
+                        assert_eq!(status_resp.txid, last_txid.borrow().to_hex());
+                        assert_eq!(
+                            status_resp.status,
+                            TransactionStatus::Microblock {
+                                block_hash: (*last_mblock.borrow()).clone(),
+                                seq: 0
+                            }
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_transaction_status_anchored() {
+        // the transaction log (STACKS_TRANSACTION_LOG) is not enabled in tests
+        assert!(!*TRANSACTION_LOG);
+        let mined_txid = RefCell::new(Txid([0u8; 32]));
+        let mined_block = RefCell::new(StacksBlockId([0u8; 32]));
+
+        test_rpc(
+            "test_rpc_transaction_status_anchored",
+            40280,
+            40281,
+            50280,
+            50281,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let sortdb = peer_server.sortdb.take().unwrap();
+                let tip = peer_server
+                    .chainstate()
+                    .get_stacks_chain_tip(&sortdb)
+                    .unwrap()
+                    .unwrap();
+                peer_server.sortdb = Some(sortdb);
+
+                let block = StacksChainState::load_block(
+                    &peer_server.chainstate().blocks_path,
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                )
+                .unwrap()
+                .unwrap();
+
+                // the contract-publish transaction after the coinbase
+                *mined_txid.borrow_mut() = block.txs[1].txid();
+                *mined_block.borrow_mut() = StacksBlockHeader::make_index_block_hash(
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                );
+                convo_client.new_gettransaction_status(block.txs[1].txid())
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::TransactionStatus(response_md, status_resp) => {
+                        assert_eq!(status_resp.txid, mined_txid.borrow().to_hex());
+                        match status_resp.status {
+                            TransactionStatus::Anchored {
+                                ref index_block_hash,
+                                confirmations,
+                                ..
+                            } => {
+                                assert_eq!(*index_block_hash, *mined_block.borrow());
+                                assert_eq!(confirmations, 1);
+                            }
+                            _ => panic!("Not anchored: {:?}", &status_resp.status),
+                        }
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_transaction_status_unknown() {
+        test_rpc(
+            "test_rpc_transaction_status_unknown",
+            40202,
+            40203,
+            50202,
+            50203,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_gettransaction_status(Txid([0x11; 32]))
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::TransactionStatus(response_md, status_resp) => {
+                        assert_eq!(status_resp.txid, Txid([0x11; 32]).to_hex());
+                        assert_eq!(status_resp.status, TransactionStatus::Unknown);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_getblock() {
+        test_rpc(
+            "test_rpc_missing_getblock",
+            40060,
+            40061,
+            50060,
+            50061,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block_hash,
+                );
+
+                // now ask for it
+                convo_client.new_getblock(index_block_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_index_getmicroblocks() {
+        test_rpc(
+            "test_rpc_missing_index_getmicroblocks",
+            40070,
+            40071,
+            50070,
+            50071,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block_hash,
+                );
+
+                // now ask for it
+                convo_client.new_getmicroblocks_indexed(index_block_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_confirmed_getmicroblocks() {
+        test_rpc(
+            "test_rpc_missing_confirmed_getmicroblocks",
+            40070,
+            40071,
+            50070,
+            50071,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block_hash,
+                );
+
+                // now ask for it
+                convo_client.new_getmicroblocks_confirmed(index_block_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_unconfirmed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            "test_rpc_missing_unconfirmed_microblocks",
+            40080,
+            40081,
+            50080,
+            50081,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let consensus_hash = ConsensusHash([0x02; 20]);
+                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
+                let index_block_hash =
+                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
+                mblocks.truncate(15);
+
+                for mblock in mblocks.iter() {
+                    store_staging_microblock(
+                        peer_server.chainstate(),
+                        &consensus_hash,
+                        &anchored_block_hash,
+                        &mblock,
+                    );
+                }
+
+                *server_microblocks_cell.borrow_mut() = mblocks;
+
+                // start at seq 16 (which doesn't exist)
+                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 16)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_src() {
+        test_rpc(
+            "test_rpc_get_contract_src",
+            40090,
+            40091,
+            50090,
+            50091,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getcontractsrc(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    None,
+                    false,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetContractSrc(response_md, data) => {
+                        assert_eq!(data.source, TEST_CONTRACT);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_src_unconfirmed() {
+        test_rpc(
+            "test_rpc_get_contract_src_unconfirmed",
+            40100,
+            40101,
+            50100,
+            50101,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let unconfirmed_tip = peer_client
+                    .chainstate()
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                convo_client.new_getcontractsrc(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    Some(unconfirmed_tip),
+                    false,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetContractSrc(response_md, data) => {
+                        assert_eq!(data.source, TEST_CONTRACT);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_account() {
+        test_rpc(
+            "test_rpc_get_account",
+            40110,
+            40111,
+            50110,
+            50111,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getaccount(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    None,
+                    false,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetAccount(response_md, data) => {
+                        assert_eq!(data.nonce, 2);
+                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
+                        assert_eq!(balance, 1000000000);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -3467,46 +7905,54 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_getpoxinfo() {
-        let pox_server_info = RefCell::new(None);
+    fn test_rpc_get_accounts() {
         test_rpc(
-            "test_rpc_getpoxinfo",
-            40000,
-            40001,
-            50000,
-            50001,
+            "test_rpc_get_accounts",
+            40210,
+            40211,
+            50210,
+            50211,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
-                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
-                let stacks_block_id = {
-                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
-                    StacksBlockHeader::make_index_block_hash(
-                        &tip.consensus_hash,
-                        &tip.anchored_block_hash,
-                    )
-                };
-                let pox_info = RPCPoxInfoData::from_db(
-                    &mut sortdb,
-                    chainstate,
-                    &stacks_block_id,
-                    &peer_client.config.burnchain,
+                convo_client.new_getaccounts(
+                    vec![
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap()
+                            .to_account_principal(),
+                        StacksAddress {
+                            version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                            bytes: Hash160([0x33; 20]),
+                        }
+                        .to_account_principal(),
+                    ],
+                    None,
                 )
-                .unwrap();
-                *pox_server_info.borrow_mut() = Some(pox_info);
-                convo_client.new_getpoxinfo(None)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::PoxInfo(response_md, pox_data) => {
-                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                    HttpResponseType::GetAccounts(response_md, data) => {
+                        assert_eq!(data.accounts.len(), 2);
+
+                        assert_eq!(
+                            data.accounts[0].principal,
+                            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R"
+                        );
+                        assert_eq!(data.accounts[0].nonce, 2);
+                        let balance =
+                            u128::from_str_radix(&data.accounts[0].balance[2..], 16).unwrap();
+                        assert_eq!(balance, 1000000000);
+
+                        // never-seen account is empty
+                        assert_eq!(data.accounts[1].nonce, 0);
+                        let balance =
+                            u128::from_str_radix(&data.accounts[1].balance[2..], 16).unwrap();
+                        assert_eq!(balance, 0);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -3516,27 +7962,47 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_getneighbors() {
+    fn test_rpc_get_account_historical() {
         test_rpc(
-            "test_rpc_getneighbors",
-            40010,
-            40011,
-            50010,
-            50011,
+            "test_rpc_get_account_historical",
+            40220,
+            40221,
+            50220,
+            50221,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
-             ref mut convo_server| { convo_client.new_getneighbors() },
+             ref mut convo_server| {
+                let (consensus_hash, block_hash) =
+                    SortitionDB::get_canonical_stacks_chain_tip_hash(
+                        peer_client.sortdb.as_ref().unwrap().conn(),
+                    )
+                    .unwrap();
+
+                // read the account as of the parent of the canonical tip
+                let parent_tip = StacksChainState::get_parent_block_id(
+                    peer_client.chainstate().db(),
+                    &StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash),
+                )
+                .unwrap()
+                .unwrap();
+                convo_client.new_getaccount(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    Some(parent_tip),
+                    false,
+                )
+            },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::Neighbors(response_md, neighbor_info) => {
-                        assert_eq!(neighbor_info.sample.len(), 1);
-                        assert_eq!(neighbor_info.sample[0].port, peer_client.config.server_port); // we see ourselves as the neighbor
+                    HttpResponseType::GetAccount(response_md, data) => {
+                        // no transactions sent yet
+                        assert_eq!(data.nonce, 0);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -3546,54 +8012,33 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_unconfirmed_getblock() {
-        let server_block_cell = RefCell::new(None);
-
+    fn test_rpc_get_account_unknown_tip() {
         test_rpc(
-            "test_rpc_unconfirmed_getblock",
-            40020,
-            40021,
-            50020,
-            50021,
+            "test_rpc_get_account_unknown_tip",
+            40222,
+            40223,
+            50222,
+            50223,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                // have "server" peer store a block to staging
-                let peer_server_block = make_codec_test_block(25);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block.block_hash(),
-                );
-
-                test_debug!("Store peer server index block {:?}", &index_block_hash);
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &peer_server_consensus_hash,
-                    &peer_server_block,
-                    &ConsensusHash([0x03; 20]),
-                    456,
-                    123,
-                );
-
-                *server_block_cell.borrow_mut() = Some(peer_server_block);
-
-                // now ask for it
-                convo_client.new_getblock(index_block_hash)
+                convo_client.new_getaccount(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    Some(StacksBlockId([0x11; 32])),
+                    false,
+                )
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::Block(response_md, block_info) => {
-                        assert_eq!(
-                            block_info.block_hash(),
-                            (*server_block_cell.borrow()).as_ref().unwrap().block_hash()
-                        );
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert!(msg.contains("No such Stacks block"));
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -3603,60 +8048,34 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_confirmed_getblock() {
-        let server_block_cell = RefCell::new(None);
-
+    fn test_rpc_get_address_transactions_disabled() {
         test_rpc(
-            "test_rpc_confirmed_getblock",
-            40030,
-            40031,
-            50030,
-            50031,
+            "test_rpc_get_address_transactions_disabled",
+            40224,
+            40225,
+            50224,
+            50225,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                // have "server" peer store a block to staging
-                let peer_server_block = make_codec_test_block(25);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block.block_hash(),
-                );
-
-                test_debug!("Store peer server index block {:?}", &index_block_hash);
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &peer_server_consensus_hash,
-                    &peer_server_block,
-                    &ConsensusHash([0x03; 20]),
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &peer_server_consensus_hash,
-                    &peer_server_block.block_hash(),
-                    true,
-                );
-
-                *server_block_cell.borrow_mut() = Some(peer_server_block);
-
-                // now ask for it
-                convo_client.new_getblock(index_block_hash)
+                convo_client.new_get_address_transactions(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    None,
+                    0,
+                    10,
+                )
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::Block(response_md, block_info) => {
-                        assert_eq!(
-                            block_info.block_hash(),
-                            (*server_block_cell.borrow()).as_ref().unwrap().block_hash()
-                        );
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert!(msg.contains("not enabled"));
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -3666,109 +8085,32 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_indexed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
-
+    fn test_rpc_get_address_assets_disabled() {
         test_rpc(
-            "test_rpc_indexed_microblocks",
-            40040,
-            40041,
-            50040,
-            50041,
+            "test_rpc_get_address_assets_disabled",
+            40272,
+            40273,
+            50272,
+            50273,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                convo_client.new_get_address_assets(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    None,
                 )
-                .unwrap();
-
-                let parent_block = make_codec_test_block(25);
-                let parent_consensus_hash = ConsensusHash([0x02; 20]);
-                let parent_index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &parent_consensus_hash,
-                    &parent_block.block_hash(),
-                );
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
-                mblocks.truncate(15);
-
-                let mut child_block = make_codec_test_block(25);
-                let child_consensus_hash = ConsensusHash([0x03; 20]);
-
-                child_block.header.parent_block = parent_block.block_hash();
-                child_block.header.parent_microblock =
-                    mblocks.last().as_ref().unwrap().block_hash();
-                child_block.header.parent_microblock_sequence =
-                    mblocks.last().as_ref().unwrap().header.sequence;
-
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &parent_consensus_hash,
-                    &parent_block,
-                    &ConsensusHash([0x01; 20]),
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &parent_consensus_hash,
-                    &parent_block.block_hash(),
-                    true,
-                );
-
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block,
-                    &parent_consensus_hash,
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                    true,
-                );
-
-                let index_microblock_hash = StacksBlockHeader::make_index_block_hash(
-                    &parent_consensus_hash,
-                    &mblocks.last().as_ref().unwrap().block_hash(),
-                );
-
-                for mblock in mblocks.iter() {
-                    store_staging_microblock(
-                        peer_server.chainstate(),
-                        &parent_consensus_hash,
-                        &parent_block.block_hash(),
-                        &mblock,
-                    );
-                }
-
-                set_microblocks_processed(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                    &mblocks.last().as_ref().unwrap().block_hash(),
-                );
-
-                *server_microblocks_cell.borrow_mut() = mblocks;
-
-                convo_client.new_getmicroblocks_indexed(index_microblock_hash)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
-                match (*http_response).clone() {
-                    HttpResponseType::Microblocks(_, mut microblocks) => {
-                        microblocks.reverse();
-                        assert_eq!(microblocks.len(), (*server_microblocks_cell.borrow()).len());
-                        assert_eq!(microblocks, *server_microblocks_cell.borrow());
+                match http_response {
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert!(msg.contains("not enabled"));
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -3778,105 +8120,34 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_confirmed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
-
+    fn test_rpc_get_nft_history_disabled() {
         test_rpc(
-            "test_rpc_confirmed_microblocks",
-            40042,
-            40043,
-            50042,
-            50043,
+            "test_rpc_get_nft_history_disabled",
+            40274,
+            40275,
+            50274,
+            50275,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                convo_client.new_get_nft_history(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "tokens".into(),
+                    "nft".into(),
+                    Value::UInt(1),
+                    None,
                 )
-                .unwrap();
-
-                let parent_block = make_codec_test_block(25);
-                let parent_consensus_hash = ConsensusHash([0x02; 20]);
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
-                mblocks.truncate(15);
-
-                let mut child_block = make_codec_test_block(25);
-                let child_consensus_hash = ConsensusHash([0x03; 20]);
-
-                child_block.header.parent_block = parent_block.block_hash();
-                child_block.header.parent_microblock =
-                    mblocks.last().as_ref().unwrap().block_hash();
-                child_block.header.parent_microblock_sequence =
-                    mblocks.last().as_ref().unwrap().header.sequence;
-
-                let child_index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                );
-
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &parent_consensus_hash,
-                    &parent_block,
-                    &ConsensusHash([0x01; 20]),
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &parent_consensus_hash,
-                    &parent_block.block_hash(),
-                    true,
-                );
-
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block,
-                    &parent_consensus_hash,
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                    true,
-                );
-
-                for mblock in mblocks.iter() {
-                    store_staging_microblock(
-                        peer_server.chainstate(),
-                        &parent_consensus_hash,
-                        &parent_block.block_hash(),
-                        &mblock,
-                    );
-                }
-
-                set_microblocks_processed(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                    &mblocks.last().as_ref().unwrap().block_hash(),
-                );
-
-                *server_microblocks_cell.borrow_mut() = mblocks;
-
-                convo_client.new_getmicroblocks_confirmed(child_index_block_hash)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
-                match (*http_response).clone() {
-                    HttpResponseType::Microblocks(_, mut microblocks) => {
-                        microblocks.reverse();
-                        assert_eq!(microblocks.len(), (*server_microblocks_cell.borrow()).len());
-                        assert_eq!(microblocks, *server_microblocks_cell.borrow());
+                match http_response {
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert!(msg.contains("not enabled"));
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -3886,59 +8157,57 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_unconfirmed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
-
+    fn test_rpc_simulate_transaction() {
         test_rpc(
-            "test_rpc_unconfirmed_microblocks",
-            40050,
-            40051,
-            50050,
-            50051,
+            "test_rpc_simulate_transaction",
+            40230,
+            40231,
+            50230,
+            50231,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
+                // STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW, which has sent no transactions
                 let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                    "94c319327cc5cd04da7147d32d836eb2e4c44f4db39aa5ede7314a761183d0c701",
                 )
                 .unwrap();
 
-                let consensus_hash = ConsensusHash([0x02; 20]);
-                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
-                let index_block_hash =
-                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
-                mblocks.truncate(15);
-
-                for mblock in mblocks.iter() {
-                    store_staging_microblock(
-                        peer_server.chainstate(),
-                        &consensus_hash,
-                        &anchored_block_hash,
-                        &mblock,
-                    );
-                }
-
-                *server_microblocks_cell.borrow_mut() = mblocks;
-
-                // start at seq 5
-                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 5)
+                // not signed
+                let mut tx = StacksTransaction::new(
+                    TransactionVersion::Testnet,
+                    TransactionAuth::from_p2pkh(&privk).unwrap(),
+                    TransactionPayload::new_contract_call(
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap(),
+                        "hello-world",
+                        "add-unit",
+                        vec![],
+                    )
+                    .unwrap(),
+                );
+                tx.chain_id = 0x80000000;
+                tx.set_tx_fee(0);
+                convo_client.new_post_transaction_simulation(tx, None)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::Microblocks(response_md, microblocks) => {
-                        assert_eq!(microblocks.len(), 10);
+                    HttpResponseType::TransactionSimulation(response_md, data) => {
+                        assert!(data.okay, "{:?}", &data.cause);
                         assert_eq!(
-                            *microblocks,
-                            (*server_microblocks_cell.borrow())[5..].to_vec()
+                            data.result,
+                            Some(format!(
+                                "0x{}",
+                                Value::okay(Value::Int(1)).unwrap().serialize()
+                            ))
                         );
+                        assert!(!data.post_condition_aborted);
+                        assert!(data.execution_cost.runtime > 0);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -3948,72 +8217,48 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_unconfirmed_transaction() {
-        let last_txid = RefCell::new(Txid([0u8; 32]));
-        let last_mblock = RefCell::new(BlockHeaderHash([0u8; 32]));
-
+    fn test_rpc_simulate_transaction_bad_nonce() {
         test_rpc(
-            "test_rpc_unconfirmed_transaction",
-            40052,
-            40053,
-            50052,
-            50053,
+            "test_rpc_simulate_transaction_bad_nonce",
+            40232,
+            40233,
+            50232,
+            50233,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
                 let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                    "94c319327cc5cd04da7147d32d836eb2e4c44f4db39aa5ede7314a761183d0c701",
                 )
                 .unwrap();
-
-                let sortdb = peer_server.sortdb.take().unwrap();
-                Relayer::setup_unconfirmed_state(peer_server.chainstate(), &sortdb).unwrap();
-                peer_server.sortdb = Some(sortdb);
-
-                assert!(peer_server.chainstate().unconfirmed_state.is_some());
-                let (txid, mblock_hash) = match peer_server.chainstate().unconfirmed_state {
-                    Some(ref unconfirmed) => {
-                        assert!(unconfirmed.mined_txs.len() > 0);
-                        let mut txid = Txid([0u8; 32]);
-                        let mut mblock_hash = BlockHeaderHash([0u8; 32]);
-                        for (next_txid, (_, mbh, ..)) in unconfirmed.mined_txs.iter() {
-                            txid = next_txid.clone();
-                            mblock_hash = mbh.clone();
-                            break;
-                        }
-                        (txid, mblock_hash)
-                    }
-                    None => {
-                        panic!("No unconfirmed state");
-                    }
-                };
-
-                *last_txid.borrow_mut() = txid.clone();
-                *last_mblock.borrow_mut() = mblock_hash.clone();
-
-                convo_client.new_gettransaction_unconfirmed(txid)
+                let mut tx = StacksTransaction::new(
+                    TransactionVersion::Testnet,
+                    TransactionAuth::from_p2pkh(&privk).unwrap(),
+                    TransactionPayload::TokenTransfer(
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap()
+                            .to_account_principal(),
+                        123,
+                        TokenTransferMemo([0u8; 34]),
+                    ),
+                );
+                tx.chain_id = 0x80000000;
+                tx.set_tx_fee(0);
+                tx.set_origin_nonce(10);
+                convo_client.new_post_transaction_simulation(tx, None)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::UnconfirmedTransaction(response_md, unconfirmed_resp) => {
-                        assert_eq!(
-                            unconfirmed_resp.status,
-                            UnconfirmedTransactionStatus::Microblock {
-                                block_hash: (*last_mblock.borrow()).clone(),
-                                seq: 0
-                            }
-                        );
-                        let tx = StacksTransaction::consensus_deserialize(
-                            &mut &hex_bytes(&unconfirmed_resp.tx).unwrap()[..],
-                        )
-                        .unwrap();
-                        assert_eq!(tx.txid(), *last_txid.borrow());
+                    HttpResponseType::TransactionSimulation(response_md, data) => {
+                        assert!(!data.okay);
+                        assert!(data.result.is_none());
+                        assert!(data.cause.is_some());
+                        assert_eq!(data.events.len(), 0);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4023,33 +8268,30 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_getblock() {
+    fn test_rpc_get_mempool_transactions() {
         test_rpc(
-            "test_rpc_missing_getblock",
-            40060,
-            40061,
-            50060,
-            50061,
+            "test_rpc_get_mempool_transactions",
+            40234,
+            40235,
+            50234,
+            50235,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
-             ref mut convo_server| {
-                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block_hash,
-                );
-
-                // now ask for it
-                convo_client.new_getblock(index_block_hash)
-            },
+             ref mut convo_server| { convo_client.new_get_mempool_transactions(0, 10) },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::MempoolTransactions(response_md, data) => {
+                        assert_eq!(data.offset, 0);
+                        assert_eq!(data.limit, 10);
+                        assert_eq!(data.total as usize, data.results.len());
+                        for (i, entry) in data.results.iter().enumerate() {
+                            assert_eq!(entry.rank, i as u64);
+                        }
+                        true
+                    }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4059,33 +8301,30 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_index_getmicroblocks() {
+    fn test_rpc_get_mempool_dropped_transactions() {
         test_rpc(
-            "test_rpc_missing_index_getmicroblocks",
-            40070,
-            40071,
-            50070,
-            50071,
+            "test_rpc_get_mempool_dropped_transactions",
+            40240,
+            40241,
+            50240,
+            50241,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block_hash,
-                );
-
-                // now ask for it
-                convo_client.new_getmicroblocks_indexed(index_block_hash)
+                convo_client.new_get_mempool_dropped_transactions(0, 10)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::MempoolDroppedTransactions(response_md, data) => {
+                        assert_eq!(data.offset, 0);
+                        assert_eq!(data.limit, 10);
+                        assert_eq!(data.total, 0);
+                        assert!(data.results.is_empty());
+                        true
+                    }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4095,33 +8334,36 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_confirmed_getmicroblocks() {
+    fn test_rpc_get_mempool_nonce_status() {
         test_rpc(
-            "test_rpc_missing_confirmed_getmicroblocks",
-            40070,
-            40071,
-            50070,
-            50071,
+            "test_rpc_get_mempool_nonce_status",
+            40242,
+            40243,
+            50242,
+            50243,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block_hash,
-                );
-
-                // now ask for it
-                convo_client.new_getmicroblocks_confirmed(index_block_hash)
+                convo_client.new_get_mempool_nonce_status(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    None,
+                )
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::MempoolNonceStatus(response_md, data) => {
+                        assert_eq!(data.address, "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R");
+                        assert!(data.next_nonce >= data.account_nonce);
+                        assert_eq!(
+                            data.pending_nonces.len() as u64,
+                            data.next_nonce - data.account_nonce
+                        );
+                        true
+                    }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4131,52 +8373,39 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_unconfirmed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
-
+    fn test_rpc_get_mempool_address_transactions() {
         test_rpc(
-            "test_rpc_missing_unconfirmed_microblocks",
-            40080,
-            40081,
-            50080,
-            50081,
+            "test_rpc_get_mempool_address_transactions",
+            40236,
+            40237,
+            50236,
+            50237,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                convo_client.new_get_mempool_address_transactions(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    0,
+                    10,
                 )
-                .unwrap();
-
-                let consensus_hash = ConsensusHash([0x02; 20]);
-                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
-                let index_block_hash =
-                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
-                mblocks.truncate(15);
-
-                for mblock in mblocks.iter() {
-                    store_staging_microblock(
-                        peer_server.chainstate(),
-                        &consensus_hash,
-                        &anchored_block_hash,
-                        &mblock,
-                    );
-                }
-
-                *server_microblocks_cell.borrow_mut() = mblocks;
-
-                // start at seq 16 (which doesn't exist)
-                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 16)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::MempoolTransactions(response_md, data) => {
+                        assert_eq!(data.limit, 10);
+                        for entry in data.results.iter() {
+                            assert!(
+                                entry.origin_address == "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R"
+                                    || entry.sponsor_address
+                                        == "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R"
+                            );
+                        }
+                        true
+                    }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4186,30 +8415,23 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_contract_src() {
+    fn test_rpc_event_replay_not_enabled() {
         test_rpc(
-            "test_rpc_get_contract_src",
-            40090,
-            40091,
-            50090,
-            50091,
+            "test_rpc_event_replay_not_enabled",
+            40238,
+            40239,
+            50238,
+            50239,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getcontractsrc(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world".try_into().unwrap(),
-                    None,
-                    false,
-                )
+                convo_client.new_post_event_replay("localhost:3700".into(), 1)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::GetContractSrc(response_md, data) => {
-                        assert_eq!(data.source, TEST_CONTRACT);
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert!(msg.contains("not enabled"));
                         true
                     }
                     _ => {
@@ -4223,37 +8445,43 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_contract_src_unconfirmed() {
+    fn test_rpc_peer_ban_and_unban() {
         test_rpc(
-            "test_rpc_get_contract_src_unconfirmed",
-            40100,
-            40101,
-            50100,
-            50101,
+            "test_rpc_peer_ban_and_unban",
+            40244,
+            40245,
+            50244,
+            50245,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let unconfirmed_tip = peer_client
-                    .chainstate()
-                    .unconfirmed_state
-                    .as_ref()
-                    .unwrap()
-                    .unconfirmed_chain_tip
-                    .clone();
-                convo_client.new_getcontractsrc(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world".try_into().unwrap(),
-                    Some(unconfirmed_tip),
-                    false,
-                )
+                convo_client.new_post_peer_ban("1.2.3.4:20444".into(), Some(3600))
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::GetContractSrc(response_md, data) => {
-                        assert_eq!(data.source, TEST_CONTRACT);
+                    HttpResponseType::PeerReputation(_, data) => {
+                        assert_eq!(data.addr, "1.2.3.4:20444");
+                        assert!(data.denied_until > get_epoch_time_secs());
+                        assert!(!data.connected);
+
+                        let peer_addr: SocketAddr = "1.2.3.4:20444".parse().unwrap();
+                        peer_server
+                            .network
+                            .unban_peer_address(
+                                &PeerAddress::from_socketaddr(&peer_addr),
+                                peer_addr.port(),
+                            )
+                            .unwrap();
+                        let neighbor = PeerDB::get_peer(
+                            peer_server.network.peerdb.conn(),
+                            peer_server.network.local_peer.network_id,
+                            &PeerAddress::from_socketaddr(&peer_addr),
+                            peer_addr.port(),
+                        )
+                        .unwrap()
+                        .unwrap();
+                        assert!(!neighbor.is_denied());
                         true
                     }
                     _ => {
@@ -4267,32 +8495,47 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_account() {
+    fn test_rpc_get_peer_reputations() {
         test_rpc(
-            "test_rpc_get_account",
-            40110,
-            40111,
-            50110,
-            50111,
+            "test_rpc_get_peer_reputations",
+            40246,
+            40247,
+            50246,
+            50247,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getaccount(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal(),
-                    None,
-                    false,
-                )
+                let peer_addr: SocketAddr = "1.2.3.4:20444".parse().unwrap();
+                let neighbor_key = NeighborKey {
+                    peer_version: 0,
+                    network_id: peer_server.network.local_peer.network_id,
+                    addrbytes: PeerAddress::from_socketaddr(&peer_addr),
+                    port: peer_addr.port(),
+                };
+                let mut score = PeerScore::new(
+                    neighbor_key.network_id,
+                    neighbor_key.addrbytes.clone(),
+                    neighbor_key.port,
+                );
+                score.add_penalty(PeerMisbehavior::StalledDownload, get_epoch_time_secs(), 0);
+
+                let mut tx = peer_server.network.peerdb.tx_begin().unwrap();
+                PeerDB::set_peer_score(&mut tx, &score).unwrap();
+                tx.commit().unwrap();
+
+                convo_client.new_get_peer_reputations()
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::GetAccount(response_md, data) => {
-                        assert_eq!(data.nonce, 2);
-                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
-                        assert_eq!(balance, 1000000000);
+                    HttpResponseType::PeerReputations(_, data) => {
+                        let entry = data
+                            .iter()
+                            .find(|entry| entry.addr == "1.2.3.4:20444")
+                            .unwrap();
+                        assert_eq!(entry.stalled_downloads, 1);
+                        assert!(entry.score > 0);
+                        assert_eq!(entry.denied_until, 0);
                         true
                     }
                     _ => {
@@ -4376,7 +8619,7 @@ mod test {
                         TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
                             .unwrap(),
                     ),
-                    None,
+                    TipRequest::UseLatestAnchoredTip,
                     false,
                 )
             },
@@ -4392,6 +8635,10 @@ mod test {
                             ))
                             .unwrap()
                         );
+                        assert_eq!(
+                            Value::from_json(data.data_json.as_ref().unwrap()).unwrap(),
+                            Value::try_deserialize_hex_untyped(&data.data).unwrap()
+                        );
                         true
                     }
                     _ => {
@@ -4436,7 +8683,60 @@ mod test {
                         TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
                             .unwrap(),
                     ),
-                    Some(unconfirmed_tip),
+                    TipRequest::SpecificTip(unconfirmed_tip),
+                    false,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetMapEntry(response_md, data) => {
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
+                            Value::some(Value::Tuple(
+                                TupleData::from_data(vec![("units".into(), Value::Int(1))])
+                                    .unwrap()
+                            ))
+                            .unwrap()
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_map_entry_tip_unconfirmed() {
+        test_rpc(
+            "test_rpc_get_map_entry_tip_unconfirmed",
+            40266,
+            40267,
+            50266,
+            50267,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let principal =
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal();
+                convo_client.new_getmapentry(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    "unit-map".try_into().unwrap(),
+                    Value::Tuple(
+                        TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
+                            .unwrap(),
+                    ),
+                    TipRequest::UseLatestUnconfirmedTip,
                     false,
                 )
             },
@@ -4444,6 +8744,7 @@ mod test {
                 let req_md = http_request.metadata().clone();
                 match http_response {
                     HttpResponseType::GetMapEntry(response_md, data) => {
+                        // sees the microblock's write
                         assert_eq!(
                             Value::try_deserialize_hex_untyped(&data.data).unwrap(),
                             Value::some(Value::Tuple(
@@ -4452,6 +8753,53 @@ mod test {
                             ))
                             .unwrap()
                         );
+                        assert!(data.marf_proof.is_none());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_data_var() {
+        test_rpc(
+            "test_rpc_get_data_var",
+            40264,
+            40265,
+            50264,
+            50265,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getdatavar(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    "bar".try_into().unwrap(),
+                    TipRequest::UseLatestAnchoredTip,
+                    true,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetDataVar(response_md, data) => {
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
+                            Value::Int(0)
+                        );
+                        assert_eq!(
+                            Value::from_json(data.data_json.as_ref().unwrap()).unwrap(),
+                            Value::Int(0)
+                        );
+                        assert!(data.marf_proof.is_some());
                         true
                     }
                     _ => {
@@ -4625,6 +8973,10 @@ mod test {
                                 .unwrap(),
                             Value::okay(Value::Int(1)).unwrap()
                         );
+                        assert_eq!(
+                            Value::from_json(data.result_json.as_ref().unwrap()).unwrap(),
+                            Value::okay(Value::Int(1)).unwrap()
+                        );
                         assert!(data.cause.is_none());
                         true
                     }
@@ -4637,6 +8989,84 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_call_read_only_batch() {
+        test_rpc(
+            "test_rpc_call_read_only_batch",
+            40278,
+            40279,
+            50278,
+            50279,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let unconfirmed_tip = peer_client
+                    .chainstate()
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                let addr = StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                    .unwrap();
+                let call = BatchReadOnlyCall {
+                    contract_id: QualifiedContractIdentifier::new(
+                        addr.clone().into(),
+                        "hello-world-unconfirmed".try_into().unwrap(),
+                    ),
+                    function: "ro-test".try_into().unwrap(),
+                    sender: addr.to_account_principal(),
+                    args: vec![],
+                };
+                let mut missing_call = call.clone();
+                missing_call.contract_id.name = "no-such-contract".try_into().unwrap();
+                convo_client
+                    .new_callreadonlyfunction_batch(vec![call, missing_call], Some(unconfirmed_tip))
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::CallReadOnlyFunctionBatch(response_md, data) => {
+                        assert_eq!(data.results.len(), 2);
+
+                        let found = &data.results[0];
+                        assert!(found.okay);
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&found.result.clone().unwrap())
+                                .unwrap(),
+                            Value::okay(Value::Int(1)).unwrap()
+                        );
+                        assert!(found.cause.is_none());
+                        assert!(found.cost.runtime > 0);
+
+                        let missing = &data.results[1];
+                        assert!(!missing.okay);
+                        assert!(missing.result.is_none());
+                        assert!(missing
+                            .cause
+                            .clone()
+                            .unwrap()
+                            .find("NoSuchContract")
+                            .is_some());
+
+                        // the calls share one budget, which neither used up
+                        let mut total_cost = found.cost.clone();
+                        total_cost.add(&missing.cost).unwrap();
+                        assert_eq!(data.total_cost, total_cost);
+                        assert!(data.budget_exhausted_at.is_none());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_getattachmentsinv_limit_reached() {
@@ -4669,4 +9099,74 @@ mod test {
             },
         );
     }
+
+    #[test]
+    fn test_rpc_check_authorization() {
+        let make_convo = |addr: &str, admin: Option<&str>, monitor: Option<&str>| {
+            let mut conn_opts = ConnectionOptions::default();
+            conn_opts.rpc_admin_token = admin.map(|t| t.to_string());
+            conn_opts.rpc_monitor_token = monitor.map(|t| t.to_string());
+            let peer_addr: SocketAddr = addr.parse().unwrap();
+            ConversationHttp::new(
+                peer_addr.clone(),
+                None,
+                PeerHost::from_socketaddr(&peer_addr),
+                &conn_opts,
+                0,
+            )
+        };
+        let make_md = |token: Option<&str>| {
+            let mut md = HttpRequestMetadata::new("127.0.0.1".to_string(), 20443);
+            md.auth_token = token.map(|t| HttpAuthToken(t.to_string()));
+            md
+        };
+        let status = |convo: &ConversationHttp, req: &HttpRequestType| match convo
+            .check_authorization(req)
+        {
+            None => 200,
+            Some(HttpResponseType::Unauthorized(..)) => 401,
+            Some(HttpResponseType::Forbidden(..)) => 403,
+            Some(resp) => panic!("Unexpected response {:?}", &resp),
+        };
+        let public = |token| HttpRequestType::GetInfo(make_md(token));
+        let monitor = |token| HttpRequestType::GetPeerReputations(make_md(token));
+        let admin = |token| {
+            HttpRequestType::PostPeerUnban(
+                make_md(token),
+                PostPeerBanRequestBody {
+                    addr: "1.2.3.4:20444".to_string(),
+                    duration: None,
+                },
+            )
+        };
+
+        // no tokens configured: loopback clients only
+        let local = make_convo("127.0.0.1:50000", None, None);
+        let remote = make_convo("1.2.3.4:50000", None, None);
+        assert_eq!(status(&local, &admin(None)), 200);
+        assert_eq!(status(&local, &monitor(None)), 200);
+        assert_eq!(status(&remote, &public(None)), 200);
+        assert_eq!(status(&remote, &monitor(None)), 403);
+        assert_eq!(status(&remote, &admin(Some("admin"))), 403);
+
+        // both tokens configured, even loopback clients need them
+        for addr in ["127.0.0.1:50000", "1.2.3.4:50000"].iter() {
+            let convo = make_convo(addr, Some("admin"), Some("monitor"));
+            assert_eq!(status(&convo, &public(None)), 200);
+            assert_eq!(status(&convo, &monitor(None)), 401);
+            assert_eq!(status(&convo, &monitor(Some("wrong"))), 401);
+            assert_eq!(status(&convo, &monitor(Some("monitor"))), 200);
+            assert_eq!(status(&convo, &monitor(Some("admin"))), 200);
+            assert_eq!(status(&convo, &admin(None)), 401);
+            assert_eq!(status(&convo, &admin(Some("monitor"))), 403);
+            assert_eq!(status(&convo, &admin(Some("admin"))), 200);
+        }
+
+        // only a monitor token: admin endpoints stay loopback-only
+        let local = make_convo("127.0.0.1:50000", None, Some("monitor"));
+        let remote = make_convo("1.2.3.4:50000", None, Some("monitor"));
+        assert_eq!(status(&remote, &monitor(Some("monitor"))), 200);
+        assert_eq!(status(&remote, &admin(Some("monitor"))), 403);
+        assert_eq!(status(&local, &admin(None)), 200);
+    }
 }
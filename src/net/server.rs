@@ -170,6 +170,25 @@ impl HttpPeer {
         count
     }
 
+    /// Find the inbound kept-alive conversation that has been waiting the longest for its next
+    /// request, if there is one.
+    fn find_idle_keep_alive_conversation(&self) -> Option<usize> {
+        let mut idlest: Option<(usize, u64)> = None;
+        for (event_id, convo) in self.peers.iter() {
+            if convo.get_url().is_some() || !convo.is_idle_keep_alive() {
+                continue;
+            }
+            let last_activity = convo.get_last_activity_time();
+            match idlest {
+                Some((_, ts)) if ts <= last_activity => {}
+                _ => {
+                    idlest = Some((*event_id, last_activity));
+                }
+            }
+        }
+        idlest.map(|(event_id, _)| event_id)
+    }
+
     /// Can we register this socket?
     fn can_register_http(
         &self,
@@ -230,6 +249,19 @@ impl HttpPeer {
             }
         };
 
+        if outbound_url.is_none()
+            && (self.peers.len() as u64) + 1 > self.connection_opts.max_http_clients
+        {
+            // we're full, but a client that's only keeping its connection open can make room
+            if let Some(idle_event_id) = self.find_idle_keep_alive_conversation() {
+                debug!(
+                    "HTTP: close idle kept-alive conversation {} to make room for {:?}",
+                    idle_event_id, &client_addr
+                );
+                self.deregister_http(network_state, idle_event_id);
+            }
+        }
+
         match self.can_register_http(&client_addr, outbound_url.as_ref()) {
             Ok(_) => {}
             Err(e) => {
@@ -331,6 +363,13 @@ impl HttpPeer {
                 // it's been too long
                 debug!("Removing idle HTTP conversation {:?}", convo);
                 to_remove.push(*event_id);
+            } else if convo.is_idle_keep_alive()
+                && convo.get_last_activity_time() + self.connection_opts.http_keep_alive_timeout
+                    < now
+            {
+                // client never sent another request
+                debug!("Removing idle kept-alive HTTP conversation {:?}", convo);
+                to_remove.push(*event_id);
             }
         }
 
@@ -1306,6 +1345,40 @@ mod test {
         assert_eq!(num_events, 2);
     }
 
+    #[test]
+    fn test_http_keep_alive_max_requests() {
+        let mut conn_opts = ConnectionOptions::default();
+        conn_opts.max_http_requests_per_connection = 2;
+
+        test_http_server(
+            "test_http_keep_alive_max_requests",
+            51090,
+            51091,
+            conn_opts,
+            1,
+            0,
+            |client_id, _| {
+                // two pipelined keep-alive requests on one connection
+                let request = HttpRequestType::GetInfo(HttpRequestMetadata::from_host(
+                    PeerHost::from_host_port("127.0.0.1".to_string(), 51091),
+                ));
+                let mut request_bytes = StacksHttp::serialize_request(&request).unwrap();
+                request_bytes.append(&mut StacksHttp::serialize_request(&request).unwrap());
+                request_bytes
+            },
+            |client_id, http_response_bytes_res| {
+                // both requests are answered, and the server closes the connection after the
+                // second one instead of waiting for a third
+                let http_response_bytes = http_response_bytes_res.unwrap();
+                let response_str = String::from_utf8_lossy(&http_response_bytes);
+                test_debug!("Client {} got:\n{}", client_id, &response_str);
+                assert_eq!(response_str.matches("HTTP/1.1 200 ").count(), 2);
+                assert_eq!(response_str.matches("Connection: close").count(), 1);
+                true
+            },
+        );
+    }
+
     #[test]
     fn test_http_noop() {
         if std::env::var("BLOCKSTACK_HTTP_TEST") != Ok("1".to_string()) {
@@ -0,0 +1,600 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Building and signing Stacks transactions from outside the node.
+//!
+//! `TransactionBuilder` takes addresses, contract identifiers and asset names as strings, and
+//! produces a `SignedTransaction` that can be serialized and broadcast (e.g. through
+//! `POST /v2/transactions`).  Services that only need to make transactions can use this module
+//! without working with the spending conditions and payload structures in `chainstate::stacks`.
+//!
+//! ```ignore
+//! let tx = TransactionBuilder::token_transfer("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7", 1000, b"")?
+//!     .nonce(12)
+//!     .fee(180)
+//!     .stx_post_condition("origin", FungibleConditionCode::SentEq, 1000)?
+//!     .sign_single_sig(&privk)?;
+//! let tx_hex = tx.to_hex();
+//! ```
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+use chainstate::stacks::{
+    AssetInfo, PostConditionPrincipal, StacksTransaction, StacksTransactionSigner,
+    TokenTransferMemo, TransactionAuth, TransactionPayload, TransactionPostCondition,
+    TransactionSpendingCondition, TransactionVersion,
+};
+use codec::StacksMessageCodec;
+use core::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET};
+use net::Error as net_error;
+use types::chainstate::StacksAddress;
+use util::hash::to_hex;
+use vm::types::{PrincipalData, QualifiedContractIdentifier};
+use vm::ClarityName;
+
+pub use chainstate::stacks::{
+    FungibleConditionCode, NonfungibleConditionCode, StacksPrivateKey, StacksPublicKey,
+    TransactionAnchorMode, TransactionPostConditionMode,
+};
+pub use vm::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// An argument could not be parsed, or is out of range
+    BadArgument(String),
+    /// The transaction could not be signed with the given keys
+    SigningError(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::BadArgument(ref s) => write!(f, "Bad argument: {}", s),
+            Error::SigningError(ref s) => write!(f, "Failed to sign transaction: {}", s),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl From<net_error> for Error {
+    fn from(e: net_error) -> Error {
+        Error::SigningError(e.to_string())
+    }
+}
+
+/// Builds a single transaction.  Transactions are for mainnet, standard (not sponsored),
+/// may be mined in anchored blocks or microblocks, and deny any asset transfers not covered
+/// by a post-condition, unless configured otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionBuilder {
+    payload: TransactionPayload,
+    mainnet: bool,
+    chain_id: u32,
+    nonce: u64,
+    fee: u64,
+    sponsored: bool,
+    anchor_mode: TransactionAnchorMode,
+    post_condition_mode: TransactionPostConditionMode,
+    post_conditions: Vec<TransactionPostCondition>,
+}
+
+/// A signed (or, if sponsored, origin-signed) transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedTransaction {
+    tx: StacksTransaction,
+}
+
+fn parse_contract_id(contract_id: &str) -> Result<QualifiedContractIdentifier, Error> {
+    QualifiedContractIdentifier::parse(contract_id)
+        .map_err(|_| Error::BadArgument(format!("Invalid contract identifier {}", contract_id)))
+}
+
+/// Parse a post-condition principal: a standard principal, a contract principal, or `origin`
+/// for the transaction's origin account.
+fn parse_post_condition_principal(principal: &str) -> Result<PostConditionPrincipal, Error> {
+    if principal == "origin" {
+        return Ok(PostConditionPrincipal::Origin);
+    }
+    match PrincipalData::parse(principal) {
+        Ok(PrincipalData::Standard(addr)) => {
+            Ok(PostConditionPrincipal::Standard(StacksAddress::from(addr)))
+        }
+        Ok(PrincipalData::Contract(contract_id)) => Ok(PostConditionPrincipal::Contract(
+            StacksAddress::from(contract_id.issuer),
+            contract_id.name,
+        )),
+        Err(_) => Err(Error::BadArgument(format!(
+            "Invalid principal {}",
+            principal
+        ))),
+    }
+}
+
+/// Parse an asset identifier of the form `{contract-address}.{contract-name}::{asset-name}`
+fn parse_asset_info(asset: &str) -> Result<AssetInfo, Error> {
+    let bad_asset = || Error::BadArgument(format!("Invalid asset identifier {}", asset));
+    let mut parts = asset.splitn(2, "::");
+    let contract_id = parse_contract_id(parts.next().ok_or_else(bad_asset)?)?;
+    let asset_name = parts
+        .next()
+        .and_then(|name| ClarityName::try_from(name.to_string()).ok())
+        .ok_or_else(bad_asset)?;
+    Ok(AssetInfo {
+        contract_address: StacksAddress::from(contract_id.issuer),
+        contract_name: contract_id.name,
+        asset_name,
+    })
+}
+
+impl TransactionBuilder {
+    fn new(payload: TransactionPayload) -> TransactionBuilder {
+        TransactionBuilder {
+            payload,
+            mainnet: true,
+            chain_id: CHAIN_ID_MAINNET,
+            nonce: 0,
+            fee: 0,
+            sponsored: false,
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Deny,
+            post_conditions: vec![],
+        }
+    }
+
+    /// Transfer `amount` microSTX to `recipient`, a standard or contract principal, with a
+    /// memo of up to 34 bytes.
+    pub fn token_transfer(
+        recipient: &str,
+        amount: u64,
+        memo: &[u8],
+    ) -> Result<TransactionBuilder, Error> {
+        let recipient = PrincipalData::parse(recipient)
+            .map_err(|_| Error::BadArgument(format!("Invalid recipient {}", recipient)))?;
+        if memo.len() > 34 {
+            return Err(Error::BadArgument(
+                "Memo must be at most 34 bytes".to_string(),
+            ));
+        }
+        let mut memo_bytes = [0u8; 34];
+        memo_bytes[..memo.len()].copy_from_slice(memo);
+        Ok(TransactionBuilder::new(TransactionPayload::TokenTransfer(
+            recipient,
+            amount,
+            TokenTransferMemo(memo_bytes),
+        )))
+    }
+
+    /// Deploy the Clarity contract `code` as `contract_name`
+    pub fn contract_deploy(contract_name: &str, code: &str) -> Result<TransactionBuilder, Error> {
+        let payload =
+            TransactionPayload::new_smart_contract(contract_name, code).ok_or_else(|| {
+                Error::BadArgument(format!(
+                    "Invalid contract name {} or contract code",
+                    contract_name
+                ))
+            })?;
+        Ok(TransactionBuilder::new(payload))
+    }
+
+    /// Call `function_name` in the contract `contract_id` (e.g. `SP000000000000000000002Q6VF78.pox`)
+    pub fn contract_call(
+        contract_id: &str,
+        function_name: &str,
+        args: Vec<Value>,
+    ) -> Result<TransactionBuilder, Error> {
+        let contract_id = parse_contract_id(contract_id)?;
+        let payload = TransactionPayload::new_contract_call(
+            StacksAddress::from(contract_id.issuer),
+            contract_id.name.as_str(),
+            function_name,
+            args,
+        )
+        .ok_or_else(|| Error::BadArgument(format!("Invalid function name {}", function_name)))?;
+        Ok(TransactionBuilder::new(payload))
+    }
+
+    /// Make a testnet transaction (with the testnet chain ID, unless `chain_id()` is used)
+    pub fn testnet(mut self) -> TransactionBuilder {
+        if self.mainnet && self.chain_id == CHAIN_ID_MAINNET {
+            self.chain_id = CHAIN_ID_TESTNET;
+        }
+        self.mainnet = false;
+        self
+    }
+
+    /// Sign for a specific chain ID, e.g. an app-chain's
+    pub fn chain_id(mut self, chain_id: u32) -> TransactionBuilder {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// The origin account's nonce
+    pub fn nonce(mut self, nonce: u64) -> TransactionBuilder {
+        self.nonce = nonce;
+        self
+    }
+
+    /// The fee, in microSTX.  Ignored for sponsored transactions, whose sponsor sets the fee.
+    pub fn fee(mut self, fee: u64) -> TransactionBuilder {
+        self.fee = fee;
+        self
+    }
+
+    /// Make a sponsored transaction.  Signing it produces an origin-signed transaction, which
+    /// the sponsor completes with `StacksTransaction::sign_sponsored_p2pkh()` or
+    /// `StacksTransactionSigner::new_sponsor()`.
+    pub fn sponsored(mut self) -> TransactionBuilder {
+        self.sponsored = true;
+        self
+    }
+
+    /// Where the transaction may be mined: in anchored blocks, microblocks, or either
+    pub fn anchor_mode(mut self, anchor_mode: TransactionAnchorMode) -> TransactionBuilder {
+        self.anchor_mode = anchor_mode;
+        self
+    }
+
+    /// Whether to allow asset transfers not covered by a post-condition
+    pub fn post_condition_mode(
+        mut self,
+        post_condition_mode: TransactionPostConditionMode,
+    ) -> TransactionBuilder {
+        self.post_condition_mode = post_condition_mode;
+        self
+    }
+
+    /// Require that `principal` (or `origin`) sends an amount of microSTX that compares to
+    /// `amount` as `condition_code` says.
+    pub fn stx_post_condition(
+        mut self,
+        principal: &str,
+        condition_code: FungibleConditionCode,
+        amount: u64,
+    ) -> Result<TransactionBuilder, Error> {
+        self.post_conditions.push(TransactionPostCondition::STX(
+            parse_post_condition_principal(principal)?,
+            condition_code,
+            amount,
+        ));
+        Ok(self)
+    }
+
+    /// Require that `principal` (or `origin`) sends an amount of the fungible token `asset`
+    /// (e.g. `SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.token::tok`) that compares to `amount`
+    /// as `condition_code` says.
+    pub fn ft_post_condition(
+        mut self,
+        principal: &str,
+        asset: &str,
+        condition_code: FungibleConditionCode,
+        amount: u64,
+    ) -> Result<TransactionBuilder, Error> {
+        self.post_conditions
+            .push(TransactionPostCondition::Fungible(
+                parse_post_condition_principal(principal)?,
+                parse_asset_info(asset)?,
+                condition_code,
+                amount,
+            ));
+        Ok(self)
+    }
+
+    /// Require that `principal` (or `origin`) does or does not send the instance `asset_value`
+    /// of the non-fungible token `asset`.
+    pub fn nft_post_condition(
+        mut self,
+        principal: &str,
+        asset: &str,
+        asset_value: Value,
+        condition_code: NonfungibleConditionCode,
+    ) -> Result<TransactionBuilder, Error> {
+        self.post_conditions
+            .push(TransactionPostCondition::Nonfungible(
+                parse_post_condition_principal(principal)?,
+                parse_asset_info(asset)?,
+                asset_value,
+                condition_code,
+            ));
+        Ok(self)
+    }
+
+    /// The unsigned transaction, spent by `origin`
+    fn build(&self, mut origin: TransactionSpendingCondition) -> StacksTransaction {
+        let version = if self.mainnet {
+            TransactionVersion::Mainnet
+        } else {
+            TransactionVersion::Testnet
+        };
+        origin.set_nonce(self.nonce);
+
+        let mut tx = if self.sponsored {
+            StacksTransaction::new_sponsored(version, origin, self.payload.clone())
+        } else {
+            origin.set_tx_fee(self.fee);
+            StacksTransaction::new(
+                version,
+                TransactionAuth::Standard(origin),
+                self.payload.clone(),
+            )
+        };
+        tx.chain_id = self.chain_id;
+        tx.anchor_mode = self.anchor_mode;
+        tx.post_condition_mode = self.post_condition_mode;
+        tx.post_conditions = self.post_conditions.clone();
+        tx
+    }
+
+    fn finish_signing(
+        &self,
+        tx_signer: StacksTransactionSigner,
+    ) -> Result<SignedTransaction, Error> {
+        let tx = if self.sponsored {
+            tx_signer.get_origin_signed_tx()
+        } else {
+            tx_signer.get_tx()
+        };
+        tx.map(|tx| SignedTransaction { tx })
+            .ok_or_else(|| Error::SigningError("Not enough signatures".to_string()))
+    }
+
+    /// Sign the transaction with the single key of a single-signature (p2pkh) account
+    pub fn sign_single_sig(&self, privk: &StacksPrivateKey) -> Result<SignedTransaction, Error> {
+        let origin =
+            TransactionSpendingCondition::new_singlesig_p2pkh(StacksPublicKey::from_private(privk))
+                .ok_or_else(|| Error::SigningError("Invalid private key".to_string()))?;
+        let mut tx_signer = StacksTransactionSigner::new(&self.build(origin));
+        tx_signer.sign_origin(privk)?;
+        self.finish_signing(tx_signer)
+    }
+
+    /// Sign the transaction for the `signatures_required`-of-`pubkeys.len()` multisig (p2sh)
+    /// account of `pubkeys`, which must be in the order the account was created with.
+    /// `privks` holds the private keys of the signers, in any order; the first
+    /// `signatures_required` of the account's keys with a private key in `privks` sign.
+    pub fn sign_multisig(
+        &self,
+        privks: &[StacksPrivateKey],
+        pubkeys: &[StacksPublicKey],
+        signatures_required: u16,
+    ) -> Result<SignedTransaction, Error> {
+        let origin =
+            TransactionSpendingCondition::new_multisig_p2sh(signatures_required, pubkeys.to_vec())
+                .ok_or_else(|| Error::BadArgument("Invalid multisig public keys".to_string()))?;
+        let mut tx_signer = StacksTransactionSigner::new(&self.build(origin));
+
+        let mut num_signed = 0;
+        for pubkey in pubkeys.iter() {
+            let privk_opt = privks
+                .iter()
+                .find(|privk| StacksPublicKey::from_private(privk) == *pubkey);
+            match privk_opt {
+                Some(privk) if num_signed < signatures_required => {
+                    tx_signer.sign_origin(privk)?;
+                    num_signed += 1;
+                }
+                _ => tx_signer.append_origin(pubkey)?,
+            }
+        }
+        self.finish_signing(tx_signer)
+    }
+}
+
+impl SignedTransaction {
+    /// The transaction ID, as a hex string
+    pub fn txid(&self) -> String {
+        self.tx.txid().to_hex()
+    }
+
+    /// The consensus serialization of the transaction, as broadcast to the network
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.tx.serialize_to_vec()
+    }
+
+    /// The consensus serialization of the transaction, as a hex string
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.to_bytes())
+    }
+
+    /// The underlying transaction
+    pub fn transaction(&self) -> &StacksTransaction {
+        &self.tx
+    }
+
+    pub fn into_transaction(self) -> StacksTransaction {
+        self.tx
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chainstate::stacks::C32_ADDRESS_VERSION_MAINNET_MULTISIG;
+    use util::hash::Hash160;
+
+    use super::*;
+
+    fn privk(hex: &str) -> StacksPrivateKey {
+        StacksPrivateKey::from_hex(hex).unwrap()
+    }
+
+    #[test]
+    fn test_build_token_transfer() {
+        let sender = privk("6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001");
+        let signed = TransactionBuilder::token_transfer(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+            1000,
+            b"hello",
+        )
+        .unwrap()
+        .nonce(12)
+        .fee(180)
+        .stx_post_condition("origin", FungibleConditionCode::SentEq, 1000)
+        .unwrap()
+        .sign_single_sig(&sender)
+        .unwrap();
+
+        let tx = signed.transaction();
+        tx.verify().unwrap();
+        assert_eq!(tx.version, TransactionVersion::Mainnet);
+        assert_eq!(tx.chain_id, CHAIN_ID_MAINNET);
+        assert_eq!(tx.get_origin_nonce(), 12);
+        assert_eq!(tx.get_tx_fee(), 180);
+        assert_eq!(tx.post_conditions.len(), 1);
+        match tx.payload {
+            TransactionPayload::TokenTransfer(_, amount, ref memo) => {
+                assert_eq!(amount, 1000);
+                assert_eq!(&memo.0[..5], b"hello");
+            }
+            _ => panic!("Expected a token transfer"),
+        }
+
+        let decoded =
+            StacksTransaction::consensus_deserialize(&mut &signed.to_bytes()[..]).unwrap();
+        assert_eq!(&decoded, tx);
+        assert_eq!(signed.txid(), tx.txid().to_hex());
+
+        assert!(TransactionBuilder::token_transfer("not-a-principal", 1, b"").is_err());
+        assert!(TransactionBuilder::token_transfer(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+            1,
+            &[0u8; 35]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_build_contract_txs() {
+        let sender = privk("6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001");
+
+        let deploy = TransactionBuilder::contract_deploy("hello", "(define-data-var x int 1)")
+            .unwrap()
+            .testnet()
+            .anchor_mode(TransactionAnchorMode::OnChainOnly)
+            .sign_single_sig(&sender)
+            .unwrap();
+        assert_eq!(deploy.transaction().version, TransactionVersion::Testnet);
+        assert_eq!(deploy.transaction().chain_id, CHAIN_ID_TESTNET);
+        assert_eq!(
+            deploy.transaction().anchor_mode,
+            TransactionAnchorMode::OnChainOnly
+        );
+        assert!(TransactionBuilder::contract_deploy("not a name", "").is_err());
+
+        let call = TransactionBuilder::contract_call(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.token",
+            "transfer",
+            vec![Value::UInt(10)],
+        )
+        .unwrap()
+        .testnet()
+        .chain_id(0x80000000)
+        .ft_post_condition(
+            "origin",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.token::tok",
+            FungibleConditionCode::SentLe,
+            10,
+        )
+        .unwrap()
+        .nft_post_condition(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.vault",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.token::nft",
+            Value::UInt(1),
+            NonfungibleConditionCode::NotSent,
+        )
+        .unwrap()
+        .sign_single_sig(&sender)
+        .unwrap();
+        assert_eq!(call.transaction().chain_id, 0x80000000);
+        assert_eq!(call.transaction().post_conditions.len(), 2);
+
+        assert!(TransactionBuilder::contract_call(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+            "f",
+            vec![]
+        )
+        .is_err());
+        assert!(TransactionBuilder::contract_call(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.token",
+            "f",
+            vec![]
+        )
+        .unwrap()
+        .ft_post_condition(
+            "origin",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.token",
+            FungibleConditionCode::SentLe,
+            10
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sign_multisig_and_sponsored() {
+        let privk_1 = privk("6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001");
+        let privk_2 = privk("2a584d899fed1d24e26b524f202763c8ab30260167429f157f1c119f550fa6af01");
+        let privk_3 = privk("d5200dee706ee53ae98a03fba6cf4fdcc5084c30cfa9e1b3462dcdeaa3e0f1d201");
+        let pubkeys: Vec<_> = [&privk_1, &privk_2, &privk_3]
+            .iter()
+            .map(|privk| StacksPublicKey::from_private(privk))
+            .collect();
+
+        let builder = TransactionBuilder::token_transfer(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+            1000,
+            b"",
+        )
+        .unwrap()
+        .fee(300);
+
+        // private keys can be given in any order
+        let signed = builder
+            .sign_multisig(&[privk_3.clone(), privk_1.clone()], &pubkeys, 2)
+            .unwrap();
+        signed.transaction().verify().unwrap();
+        assert_eq!(
+            signed.transaction().origin_address(),
+            StacksAddress {
+                version: C32_ADDRESS_VERSION_MAINNET_MULTISIG,
+                bytes: Hash160::from_hex("a23ea89d6529ac48ac766f720e480beec7f19273").unwrap()
+            }
+        );
+
+        assert!(builder
+            .sign_multisig(&[privk_3.clone()], &pubkeys, 2)
+            .is_err());
+
+        // a sponsored transaction is only signed by its origin
+        let origin_signed = builder
+            .clone()
+            .sponsored()
+            .sign_single_sig(&privk_1)
+            .unwrap();
+        assert!(origin_signed.transaction().auth().is_sponsored());
+        assert!(origin_signed.transaction().verify().is_err());
+
+        let signed = origin_signed
+            .transaction()
+            .sign_sponsored_p2pkh(&privk_2, 0, 300)
+            .unwrap();
+        signed.verify().unwrap();
+    }
+}
@@ -35,6 +35,7 @@ impl_array_hexstring_fmt!(SortitionId);
 impl_byte_array_newtype!(SortitionId, u8, 32);
 impl_byte_array_from_column!(SortitionId);
 impl_byte_array_message_codec!(SortitionId, 32);
+impl_byte_array_serde!(SortitionId);
 
 pub struct VRFSeed(pub [u8; 32]);
 impl_array_newtype!(VRFSeed, u8, 32);
@@ -558,6 +558,14 @@ pub fn tx_begin_immediate_sqlite<'a>(conn: &'a mut Connection) -> Result<DBTx<'a
     Ok(tx)
 }
 
+/// Begin a deferred-mode transaction.  It reads from a snapshot of the database taken when it
+/// first reads, and does not take the write lock until it first writes.
+pub fn tx_begin_deferred<'a>(conn: &'a mut Connection) -> Result<DBTx<'a>, Error> {
+    conn.busy_handler(Some(tx_busy_handler))?;
+    let tx = Transaction::new(conn, TransactionBehavior::Deferred)?;
+    Ok(tx)
+}
+
 /// Open a database connection and set some typically-used pragmas
 pub fn sqlite_open<P: AsRef<Path>>(
     path: P,
@@ -598,6 +606,118 @@ pub fn get_ancestor_block_height<T: MarfTrieId>(
     Ok(height_opt)
 }
 
+/// Skip-list index over the ancestry of a DB's blocks, so that the ancestor of a block at a given
+/// height can be found in O(log n) lookups instead of via the MARF.  Each block points to its
+/// parent and to one further ancestor, as in Bitcoin Core's `CBlockIndex::pskip`.  Heights are
+/// the blocks' MARF heights (so the first block is at height 0, and is its own parent).
+pub const ANCESTOR_SKIPS_SCHEMA: &'static str = r#"
+    CREATE TABLE IF NOT EXISTS ancestor_skips(
+        block_id TEXT PRIMARY KEY,
+        block_height INTEGER NOT NULL,
+        parent_block_id TEXT NOT NULL,
+        skip_block_id TEXT NOT NULL
+    );"#;
+
+/// Height of the ancestor that a block at the given height keeps a skip pointer to.
+pub fn ancestor_skip_height(block_height: u64) -> u64 {
+    // clear the lowest set bit
+    let invert_lowest_one = |n: u64| n & n.wrapping_sub(1);
+    if block_height < 2 {
+        0
+    } else if block_height & 1 == 1 {
+        invert_lowest_one(invert_lowest_one(block_height - 1)) + 1
+    } else {
+        invert_lowest_one(block_height)
+    }
+}
+
+/// Load a block's (height, parent, skip ancestor) from the ancestor skip index
+fn get_ancestor_skip<T: MarfTrieId>(
+    conn: &Connection,
+    block_id: &T,
+) -> Result<Option<(u64, T, T)>, Error> {
+    let sql = "SELECT block_height, parent_block_id, skip_block_id FROM ancestor_skips WHERE block_id = ?1";
+    conn.query_row(sql, &[block_id as &dyn ToSql], |row| {
+        let block_height: i64 = row.get(0)?;
+        Ok((block_height as u64, row.get(1)?, row.get(2)?))
+    })
+    .optional()
+    .map_err(Error::SqliteError)
+}
+
+/// Find the ancestor of a block at the given height using the ancestor skip index.
+/// Returns Ok(None) if there is no such ancestor, or if the block is not indexed -- callers
+/// should fall back to the MARF in that case.
+pub fn get_ancestor_by_skips<T: MarfTrieId>(
+    conn: &Connection,
+    tip_block_id: &T,
+    block_height: u64,
+) -> Result<Option<T>, Error> {
+    let (mut walk_height, mut walk_parent, mut walk_skip) =
+        match get_ancestor_skip(conn, tip_block_id)? {
+            Some(skip) => skip,
+            None => return Ok(None),
+        };
+    if block_height > walk_height {
+        return Ok(None);
+    }
+
+    let mut walk_id = tip_block_id.clone();
+    while walk_height > block_height {
+        // take the skip pointer unless it overshoots, or unless the parent's skip pointer gets
+        // us closer
+        let skip_height = ancestor_skip_height(walk_height);
+        let prev_skip_height = ancestor_skip_height(walk_height - 1);
+        walk_id = if skip_height == block_height
+            || (skip_height > block_height
+                && !(prev_skip_height + 2 < skip_height && prev_skip_height >= block_height))
+        {
+            walk_skip
+        } else {
+            walk_parent
+        };
+        let (next_height, next_parent, next_skip) = match get_ancestor_skip(conn, &walk_id)? {
+            Some(skip) => skip,
+            None => return Ok(None),
+        };
+        walk_height = next_height;
+        walk_parent = next_parent;
+        walk_skip = next_skip;
+    }
+    Ok(Some(walk_id))
+}
+
+/// Add a block to the ancestor skip index.  Its parent must already be indexed, unless it is at
+/// height 0.  Returns false, and does nothing, if the parent is not indexed.
+pub fn insert_ancestor_skip<T: MarfTrieId>(
+    conn: &Connection,
+    block_id: &T,
+    block_height: u64,
+    parent_block_id: &T,
+) -> Result<bool, Error> {
+    let (parent_block_id, skip_block_id) = if block_height == 0 {
+        (block_id.clone(), block_id.clone())
+    } else {
+        match get_ancestor_by_skips(conn, parent_block_id, ancestor_skip_height(block_height))? {
+            Some(skip_block_id) => (parent_block_id.clone(), skip_block_id),
+            None => {
+                return Ok(false);
+            }
+        }
+    };
+    let args: &[&dyn ToSql] = &[
+        block_id,
+        &u64_to_sql(block_height)?,
+        &parent_block_id,
+        &skip_block_id,
+    ];
+    conn.execute(
+        "INSERT OR IGNORE INTO ancestor_skips (block_id, block_height, parent_block_id, skip_block_id) VALUES (?1, ?2, ?3, ?4)",
+        args,
+    )?;
+    Ok(true)
+}
+
 /// Load some index data
 fn load_indexed(conn: &DBConn, marf_value: &MARFValue) -> Result<Option<String>, Error> {
     let mut stmt = conn
@@ -812,6 +932,82 @@ impl<'a, C: Clone, T: MarfTrieId> Drop for IndexDBTx<'a, C, T> {
 mod tests {
     use super::*;
     use std::fs;
+    use types::chainstate::StacksBlockId;
+    use types::proof::ClarityMarfTrieId;
+
+    #[test]
+    fn test_ancestor_skip_height() {
+        assert_eq!(ancestor_skip_height(0), 0);
+        assert_eq!(ancestor_skip_height(1), 0);
+        assert_eq!(ancestor_skip_height(2), 0);
+        assert_eq!(ancestor_skip_height(3), 1);
+        assert_eq!(ancestor_skip_height(12), 8);
+        assert_eq!(ancestor_skip_height(13), 9);
+        for height in 1..10000 {
+            assert!(ancestor_skip_height(height) < height);
+        }
+    }
+
+    #[test]
+    fn test_ancestor_skips() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(ANCESTOR_SKIPS_SCHEMA).unwrap();
+
+        let block_id = |fork: u8, height: u64| {
+            let mut bytes = [fork; 32];
+            bytes[0..8].copy_from_slice(&height.to_be_bytes());
+            StacksBlockId(bytes)
+        };
+
+        // fork 0 runs from height 0 to 999.  fork 1 branches off of it at height 500 and runs to
+        // height 1099.
+        let mut fork_0 = vec![];
+        for height in 0..1000 {
+            let parent = if height == 0 {
+                StacksBlockId::sentinel()
+            } else {
+                block_id(0, height - 1)
+            };
+            assert!(insert_ancestor_skip(&conn, &block_id(0, height), height, &parent).unwrap());
+            fork_0.push(block_id(0, height));
+        }
+        let mut fork_1 = fork_0[0..501].to_vec();
+        for height in 501..1100 {
+            let parent = fork_1[(height - 1) as usize].clone();
+            assert!(insert_ancestor_skip(&conn, &block_id(1, height), height, &parent).unwrap());
+            fork_1.push(block_id(1, height));
+        }
+
+        for fork in [&fork_0, &fork_1].iter() {
+            let tip = fork.last().unwrap();
+            for height in 0..fork.len() {
+                assert_eq!(
+                    get_ancestor_by_skips(&conn, tip, height as u64).unwrap(),
+                    Some(fork[height].clone())
+                );
+            }
+            for height in (0..fork.len()).step_by(37) {
+                for ancestor_height in 0..(height + 1) {
+                    assert_eq!(
+                        get_ancestor_by_skips(&conn, &fork[height], ancestor_height as u64)
+                            .unwrap(),
+                        Some(fork[ancestor_height].clone())
+                    );
+                }
+            }
+            assert_eq!(
+                get_ancestor_by_skips(&conn, tip, fork.len() as u64).unwrap(),
+                None
+            );
+        }
+
+        // unindexed blocks have no ancestors, and can't be parents
+        assert_eq!(
+            get_ancestor_by_skips(&conn, &block_id(2, 10), 0).unwrap(),
+            None
+        );
+        assert!(!insert_ancestor_skip(&conn, &block_id(2, 11), 11, &block_id(2, 10)).unwrap());
+    }
 
     #[test]
     fn test_pragma() {
@@ -17,15 +17,23 @@
 use chrono::prelude::*;
 use slog::{BorrowedKV, Drain, FnValue, Level, Logger, OwnedKVList, Record, KV};
 use slog_term::{CountingWriter, Decorator, RecordDecorator, Serializer};
+use std::collections::HashMap;
 use std::env;
 use std::io;
 use std::io::Write;
-use std::sync::Mutex;
+use std::str::FromStr;
+use std::sync::{Mutex, RwLock};
 use std::thread;
 use std::time::{Duration, SystemTime};
 
 lazy_static! {
     pub static ref LOGGER: Logger = make_logger();
+    /// Per-module log level overrides, keyed by module path prefix (e.g. `net::p2p`).
+    /// Seeded from `STACKS_LOG_MODULES` and adjustable at runtime.
+    static ref MODULE_LOG_LEVELS: RwLock<HashMap<String, Level>> =
+        RwLock::new(parse_module_loglevels(
+            &env::var("STACKS_LOG_MODULES").unwrap_or("".to_string())
+        ));
 }
 struct TermFormat<D: Decorator> {
     decorator: D,
@@ -187,6 +195,12 @@ fn make_json_logger() -> Logger {
                       "line" => FnValue(move |info| {
                           info.line()
                       }),
+                      "module" => FnValue(move |info| {
+                          info.module()
+                      }),
+                      "event" => FnValue(move |info| {
+                          info.tag()
+                      }),
                       "thread" => FnValue(move |_| {
                           match thread::current().name() {
                               None => format!("{:?}", thread::current().id()),
@@ -195,9 +209,9 @@ fn make_json_logger() -> Logger {
                       }),
     );
 
+    // level filtering happens in the logging macros, so per-module overrides can take effect
     let drain = Mutex::new(slog_json::Json::default(std::io::stderr())).map(slog::Fuse);
-    let filtered_drain = slog::LevelFilter::new(drain, get_loglevel()).fuse();
-    slog::Logger::root(filtered_drain, def_keys)
+    slog::Logger::root(drain, def_keys)
 }
 
 #[cfg(not(feature = "slog_json"))]
@@ -246,10 +260,87 @@ pub fn get_loglevel() -> slog::Level {
     }
 }
 
+/// Parse a comma-separated list of `module=level` pairs, such as
+/// `net::p2p=debug,chainstate::stacks::db=trace`.  Malformed entries are ignored.
+pub fn parse_module_loglevels(spec: &str) -> HashMap<String, Level> {
+    let mut levels = HashMap::new();
+    for entry in spec.split(",") {
+        let parts: Vec<&str> = entry.trim().splitn(2, "=").collect();
+        if parts.len() != 2 || parts[0].len() == 0 {
+            continue;
+        }
+        if let Ok(level) = Level::from_str(parts[1].trim()) {
+            levels.insert(parts[0].trim().to_string(), level);
+        }
+    }
+    levels
+}
+
+/// Does the module path filter `filter` apply to the module `module_path`?
+/// The filter matches on whole path segments, either from the crate root (e.g.
+/// `blockstack_lib::net`) or from just below it (e.g. `net`).
+fn module_filter_matches(filter: &str, module_path: &str) -> bool {
+    let segment_prefix = |path: &str| {
+        path == filter || (path.starts_with(filter) && path[filter.len()..].starts_with("::"))
+    };
+    if segment_prefix(module_path) {
+        return true;
+    }
+    match module_path.find("::") {
+        Some(idx) => segment_prefix(&module_path[idx + 2..]),
+        None => false,
+    }
+}
+
+/// Get the log level for a particular module.  The most specific matching per-module override
+/// wins; if there is none, the global log level applies.
+pub fn get_module_loglevel(module_path: &str) -> slog::Level {
+    if let Ok(levels) = MODULE_LOG_LEVELS.read() {
+        let mut best: Option<(usize, Level)> = None;
+        for (filter, level) in levels.iter() {
+            if module_filter_matches(filter, module_path) {
+                match best {
+                    Some((len, _)) if len >= filter.len() => {}
+                    _ => {
+                        best = Some((filter.len(), *level));
+                    }
+                }
+            }
+        }
+        if let Some((_, level)) = best {
+            return level;
+        }
+    }
+    get_loglevel()
+}
+
+/// Set (or clear, if `level` is None) the log level override for a module path prefix.
+pub fn set_module_loglevel(module: &str, level: Option<Level>) {
+    let mut levels = MODULE_LOG_LEVELS
+        .write()
+        .expect("FATAL: module log level lock poisoned");
+    match level {
+        Some(level) => {
+            levels.insert(module.to_string(), level);
+        }
+        None => {
+            levels.remove(module);
+        }
+    }
+}
+
+/// Get a copy of all per-module log level overrides
+pub fn get_module_loglevels() -> HashMap<String, Level> {
+    MODULE_LOG_LEVELS
+        .read()
+        .expect("FATAL: module log level lock poisoned")
+        .clone()
+}
+
 #[macro_export]
 macro_rules! trace {
     ($($arg:tt)*) => ({
-        let cur_level = ::util::log::get_loglevel();
+        let cur_level = ::util::log::get_module_loglevel(module_path!());
         if slog::Level::Trace.is_at_least(cur_level) {
             slog_trace!($crate::util::log::LOGGER, $($arg)*)
         }
@@ -259,7 +350,7 @@ macro_rules! trace {
 #[macro_export]
 macro_rules! error {
     ($($arg:tt)*) => ({
-        let cur_level = ::util::log::get_loglevel();
+        let cur_level = ::util::log::get_module_loglevel(module_path!());
         if slog::Level::Error.is_at_least(cur_level) {
             slog_error!($crate::util::log::LOGGER, $($arg)*)
         }
@@ -269,7 +360,7 @@ macro_rules! error {
 #[macro_export]
 macro_rules! warn {
     ($($arg:tt)*) => ({
-        let cur_level = ::util::log::get_loglevel();
+        let cur_level = ::util::log::get_module_loglevel(module_path!());
         if slog::Level::Warning.is_at_least(cur_level) {
             slog_warn!($crate::util::log::LOGGER, $($arg)*)
         }
@@ -279,7 +370,7 @@ macro_rules! warn {
 #[macro_export]
 macro_rules! info {
     ($($arg:tt)*) => ({
-        let cur_level = ::util::log::get_loglevel();
+        let cur_level = ::util::log::get_module_loglevel(module_path!());
         if slog::Level::Info.is_at_least(cur_level) {
             slog_info!($crate::util::log::LOGGER, $($arg)*)
         }
@@ -289,7 +380,7 @@ macro_rules! info {
 #[macro_export]
 macro_rules! debug {
     ($($arg:tt)*) => ({
-        let cur_level = ::util::log::get_loglevel();
+        let cur_level = ::util::log::get_module_loglevel(module_path!());
         if slog::Level::Debug.is_at_least(cur_level) {
             slog_debug!($crate::util::log::LOGGER, $($arg)*)
         }
@@ -299,7 +390,7 @@ macro_rules! debug {
 #[macro_export]
 macro_rules! fatal {
     ($($arg:tt)*) => ({
-        let cur_level = ::util::log::get_loglevel();
+        let cur_level = ::util::log::get_module_loglevel(module_path!());
         if slog::Level::Critical.is_at_least(cur_level) {
             slog_crit!($crate::util::log::LOGGER, $($arg)*)
         }
@@ -333,3 +424,30 @@ fn isatty(stream: Stream) -> bool {
 fn isatty(stream: Stream) -> bool {
     false
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_module_loglevels() {
+        let levels = parse_module_loglevels("net::p2p=debug, chainstate=TRACE,bad,=info,foo=nope");
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels.get("net::p2p"), Some(&Level::Debug));
+        assert_eq!(levels.get("chainstate"), Some(&Level::Trace));
+    }
+
+    #[test]
+    fn test_module_filter_matches() {
+        assert!(module_filter_matches("net", "blockstack_lib::net"));
+        assert!(module_filter_matches("net", "blockstack_lib::net::p2p"));
+        assert!(module_filter_matches("net::p2p", "blockstack_lib::net::p2p"));
+        assert!(module_filter_matches(
+            "blockstack_lib::net",
+            "blockstack_lib::net::p2p"
+        ));
+        assert!(!module_filter_matches("net", "blockstack_lib::network"));
+        assert!(!module_filter_matches("p2p", "blockstack_lib::net::p2p"));
+        assert!(!module_filter_matches("net::p2p", "blockstack_lib::net"));
+    }
+}
@@ -105,6 +105,15 @@ impl MessageSignature {
         MessageSignature(ret_bytes)
     }
 
+    /// The signature in the layout Clarity's `secp256k1-recover?` and `secp256k1-verify` take:
+    /// the 64-byte compact signature followed by the recovery ID.  A `MessageSignature` puts the
+    /// recovery ID first instead.
+    pub fn to_rsv(&self) -> Vec<u8> {
+        let mut rsv = self.0[1..].to_vec();
+        rsv.push(self.0[0]);
+        rsv
+    }
+
     pub fn to_secp256k1_recoverable(&self) -> Option<LibSecp256k1RecoverableSignature> {
         let recid = match LibSecp256k1RecoveryID::from_i32(self.0[0] as i32) {
             Ok(rid) => rid,
@@ -37,10 +37,12 @@ use ed25519_dalek::SecretKey as ed25519_PrivateKey;
 use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
 use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
 use curve25519_dalek::scalar::Scalar as ed25519_Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
 
 use sha2::Digest;
 use sha2::Sha512;
 
+use std::collections::HashMap;
 use std::error;
 use std::fmt;
 
@@ -580,6 +582,36 @@ impl VRF {
         Ok(c_prime == *(proof.c()))
     }
 
+    /// Same as verify(), but with an already-decompressed public key point, and with
+    /// variable-time double-base scalar multiplications.  Everything being checked here is public,
+    /// so there is no need to pay for constant-time arithmetic.
+    fn verify_decompressed(
+        Y_point: &VRFPublicKey,
+        Y_point_ed: &EdwardsPoint,
+        proof: &VRFProof,
+        alpha: &[u8],
+    ) -> Result<bool, Error> {
+        if proof.Gamma().is_small_order() {
+            return Err(Error::InvalidPublicKey);
+        }
+
+        let H_point = VRF::hash_to_curve(Y_point, alpha);
+        let s_reduced = proof.s().reduce();
+        let neg_c = -proof.c();
+
+        // U = s*B - c*Y
+        let U_point =
+            EdwardsPoint::vartime_double_scalar_mul_basepoint(&neg_c, Y_point_ed, &s_reduced);
+        // V = s*H - c*Gamma
+        let V_point =
+            EdwardsPoint::vartime_multiscalar_mul(&[s_reduced, neg_c], &[H_point, *proof.Gamma()]);
+
+        let c_prime_hashbuf = VRF::hash_points(&H_point, proof.Gamma(), &U_point, &V_point);
+        let c_prime = VRF::ed25519_scalar_from_hash128(&c_prime_hashbuf);
+
+        Ok(c_prime == *(proof.c()))
+    }
+
     /// Verify that a given byte string is a well-formed EdDSA public key (i.e. it's a compressed
     /// Edwards point that is valid).
     pub fn check_public_key(pubkey_bytes: &Vec<u8>) -> Option<VRFPublicKey> {
@@ -590,6 +622,94 @@ impl VRF {
     }
 }
 
+/// Maximum number of decompressed public keys a VRFCachingVerifier will hold on to
+pub const VRF_VERIFIER_MAX_CACHED_KEYS: usize = 4096;
+/// Maximum number of pre-computed verification results a VRFCachingVerifier will hold on to
+pub const VRF_VERIFIER_MAX_CACHED_RESULTS: usize = 4096;
+
+/// VRF proof verifier that caches the expensive parts of verification.  Proofs are still checked
+/// one at a time, but leader keys get reused across many sortitions, so each distinct key is
+/// decompressed only once.  Proofs can also be checked ahead of time with preverify() (e.g. for a
+/// run of downloaded blocks, before they are preprocessed one by one), and their results are
+/// remembered, so a subsequent call to verify() for the same (key, proof, message) is a lookup.
+pub struct VRFCachingVerifier {
+    points: HashMap<VRFPublicKey, EdwardsPoint>,
+    results: HashMap<(VRFPublicKey, Vec<u8>, Vec<u8>), bool>,
+}
+
+impl VRFCachingVerifier {
+    pub fn new() -> VRFCachingVerifier {
+        VRFCachingVerifier {
+            points: HashMap::new(),
+            results: HashMap::new(),
+        }
+    }
+
+    /// Get the decompressed point for a public key, caching it if need be.
+    fn get_point(&mut self, pubkey: &VRFPublicKey) -> Result<EdwardsPoint, Error> {
+        if let Some(point) = self.points.get(pubkey) {
+            return Ok(point.clone());
+        }
+        let point = CompressedEdwardsY(pubkey.to_bytes())
+            .decompress()
+            .ok_or(Error::InvalidPublicKey)?;
+
+        if self.points.len() >= VRF_VERIFIER_MAX_CACHED_KEYS {
+            self.points.clear();
+        }
+        self.points.insert(pubkey.clone(), point.clone());
+        Ok(point)
+    }
+
+    /// Verify a single proof.  Consumes the result of a prior preverify() of this same proof if
+    /// there is one.  Same return semantics as VRF::verify().
+    pub fn verify(
+        &mut self,
+        pubkey: &VRFPublicKey,
+        proof: &VRFProof,
+        alpha: &[u8],
+    ) -> Result<bool, Error> {
+        let result_key = (pubkey.clone(), proof.to_bytes().to_vec(), alpha.to_vec());
+        if let Some(result) = self.results.remove(&result_key) {
+            return Ok(result);
+        }
+        let point = self.get_point(pubkey)?;
+        VRF::verify_decompressed(pubkey, &point, proof, alpha)
+    }
+
+    /// Verify each of a list of (public key, proof, message) triples ahead of time.  Returns each
+    /// triple's result, in order, with the same semantics as VRF::verify().  Successfully-evaluated
+    /// results are remembered until they are consumed by verify().
+    pub fn preverify(
+        &mut self,
+        proofs: &[(VRFPublicKey, VRFProof, Vec<u8>)],
+    ) -> Vec<Result<bool, Error>> {
+        let mut ret = Vec::with_capacity(proofs.len());
+        for (pubkey, proof, alpha) in proofs.iter() {
+            let res = self
+                .get_point(pubkey)
+                .and_then(|point| VRF::verify_decompressed(pubkey, &point, proof, alpha));
+
+            if let Ok(valid) = res.as_ref() {
+                if self.results.len() >= VRF_VERIFIER_MAX_CACHED_RESULTS {
+                    self.results.clear();
+                }
+                self.results.insert(
+                    (pubkey.clone(), proof.to_bytes().to_vec(), alpha.clone()),
+                    *valid,
+                );
+            }
+            ret.push(res);
+        }
+        ret
+    }
+
+    /// How many verification results are waiting to be consumed
+    pub fn num_pending_results(&self) -> usize {
+        self.results.len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -659,6 +779,9 @@ mod tests {
             let res = VRF::verify(&pubk, &proof, &alpha.to_vec()).unwrap();
 
             assert!(res);
+
+            let mut verifier = VRFCachingVerifier::new();
+            assert!(verifier.verify(&pubk, &proof, &alpha).unwrap());
         }
     }
 
@@ -679,6 +802,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_preverify() {
+        let mut batch = vec![];
+        let mut expected = vec![];
+        let secret_keys: Vec<_> = (0..4).map(|_| VRFPrivateKey::new()).collect();
+        for i in 0..32 {
+            let secret_key = &secret_keys[i % secret_keys.len()];
+            let public_key = VRFPublicKey::from_private(secret_key);
+
+            let mut rng = rand::thread_rng();
+            let mut msg = [0u8; 32];
+            rng.fill_bytes(&mut msg);
+
+            let proof = VRF::prove(secret_key, &msg.to_vec());
+
+            // every third proof is checked against the wrong message
+            if i % 3 == 0 {
+                msg[0] ^= 0x01;
+            }
+            expected.push(VRF::verify(&public_key, &proof, &msg.to_vec()).unwrap());
+            batch.push((public_key, proof, msg.to_vec()));
+        }
+
+        let mut verifier = VRFCachingVerifier::new();
+        let results = verifier.preverify(&batch);
+        assert_eq!(results.len(), batch.len());
+        for (i, res) in results.into_iter().enumerate() {
+            assert_eq!(res.unwrap(), expected[i]);
+            assert_eq!(expected[i], i % 3 != 0);
+        }
+
+        // keys were only decompressed once
+        assert_eq!(verifier.points.len(), secret_keys.len());
+        assert_eq!(verifier.num_pending_results(), batch.len());
+
+        // results are consumed by verify(), and re-verification agrees
+        for (i, (public_key, proof, msg)) in batch.iter().enumerate() {
+            assert_eq!(
+                verifier.verify(public_key, proof, msg).unwrap(),
+                expected[i]
+            );
+        }
+        assert_eq!(verifier.num_pending_results(), 0);
+        for (i, (public_key, proof, msg)) in batch.iter().enumerate() {
+            assert_eq!(
+                verifier.verify(public_key, proof, msg).unwrap(),
+                expected[i]
+            );
+        }
+    }
+
     #[test]
     fn test_proof_codec() {
         let proof_fixtures = vec![
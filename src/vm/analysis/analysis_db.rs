@@ -17,12 +17,14 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
-use vm::analysis::type_checker::ContractAnalysis;
+use vm::analysis::type_checker::{trait_type_size, ContractAnalysis};
+use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::{runtime_cost, CostTracker};
 use vm::database::{
     ClarityBackingStore, ClarityDeserializable, ClaritySerializable, RollbackWrapper,
 };
 use vm::representations::ClarityName;
-use vm::types::signatures::FunctionSignature;
+use vm::types::signatures::{is_subtrait, FunctionSignature};
 use vm::types::{FunctionType, QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
 
 pub struct AnalysisDatabase<'a> {
@@ -155,6 +157,75 @@ impl<'a> AnalysisDatabase<'a> {
         Ok(contract.get_defined_trait(trait_name).cloned())
     }
 
+    /// Does every contract that complies with `sub_trait` also comply with `super_trait`?  See
+    /// `signatures::is_subtrait`.  Every trait definition loaded along the way is charged to
+    /// `tracker` like a `use-trait`.
+    pub fn is_subtrait<T: CostTracker>(
+        &mut self,
+        tracker: &mut T,
+        sub_trait: &TraitIdentifier,
+        super_trait: &TraitIdentifier,
+    ) -> CheckResult<bool> {
+        is_subtrait(sub_trait, super_trait, &mut |trait_id| {
+            let result = match self.get_defined_trait(&trait_id.contract_identifier, &trait_id.name)
+            {
+                Ok(result) => result,
+                Err(CheckError {
+                    err: CheckErrors::NoSuchContract(_),
+                    ..
+                }) => None,
+                Err(e) => return Err(e),
+            };
+            match result {
+                Some(ref trait_sig) => runtime_cost(
+                    ClarityCostFunction::AnalysisUseTraitEntry,
+                    tracker,
+                    trait_type_size(trait_sig)?,
+                )?,
+                // still had to do a db read, even if it didn't exist!
+                None => runtime_cost(ClarityCostFunction::AnalysisUseTraitEntry, tracker, 1)?,
+            }
+            Ok(result)
+        })
+    }
+
+    /// Check that `contract` complies with the trait `trait_identifier`.  If `allow_subtyping` is
+    /// set, the trait arguments of its functions may be supertraits of the trait's, and the trait
+    /// definitions this needs are charged to `tracker`.
+    pub fn check_trait_compliance<T: CostTracker>(
+        &mut self,
+        tracker: &mut T,
+        contract: &ContractAnalysis,
+        trait_identifier: &TraitIdentifier,
+        trait_definition: &BTreeMap<ClarityName, FunctionSignature>,
+        allow_subtyping: bool,
+    ) -> CheckResult<()> {
+        if allow_subtyping {
+            let mut error = None;
+            let result = contract.check_trait_compliance_with(
+                trait_identifier,
+                trait_definition,
+                &mut |sub_trait, super_trait| match self.is_subtrait(
+                    tracker,
+                    sub_trait,
+                    super_trait,
+                ) {
+                    Ok(admitted) => admitted,
+                    Err(e) => {
+                        error = Some(e);
+                        false
+                    }
+                },
+            );
+            match error {
+                Some(e) => Err(e),
+                None => result,
+            }
+        } else {
+            contract.check_trait_compliance(trait_identifier, trait_definition)
+        }
+    }
+
     pub fn get_implemented_traits(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
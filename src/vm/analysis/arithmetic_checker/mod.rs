@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use core::StacksEpochId;
 use vm::analysis::types::{AnalysisPass, ContractAnalysis};
 use vm::functions::define::{DefineFunctions, DefineFunctionsParsed};
 use vm::functions::tuples;
@@ -42,7 +43,7 @@ mod tests;
 ///  any database operations, traits, or iterating operations (e.g., list
 ///  operations)
 ///
-pub struct ArithmeticOnlyChecker();
+pub struct ArithmeticOnlyChecker(Option<StacksEpochId>);
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Error {
@@ -72,7 +73,11 @@ impl ArithmeticOnlyChecker {
     }
 
     pub fn run(contract_analysis: &ContractAnalysis) -> Result<(), Error> {
-        let checker = ArithmeticOnlyChecker();
+        let epoch = contract_analysis
+            .cost_track
+            .as_ref()
+            .and_then(|cost_track| cost_track.get_epoch());
+        let checker = ArithmeticOnlyChecker(epoch);
         for exp in contract_analysis.expressions.iter() {
             checker.check_top_levels(&exp)?;
         }
@@ -154,7 +159,7 @@ impl ArithmeticOnlyChecker {
         function: &str,
         args: &[SymbolicExpression],
     ) -> Option<Result<(), Error>> {
-        NativeFunctions::lookup_by_name(function)
+        NativeFunctions::lookup_by_name_at_epoch(function, self.0.as_ref())
             .map(|function| self.check_native_function(function, args))
     }
 
@@ -165,18 +170,19 @@ impl ArithmeticOnlyChecker {
     ) -> Result<(), Error> {
         use vm::functions::NativeFunctions::*;
         match function {
-            FetchVar | GetBlockInfo | GetTokenBalance | GetAssetOwner | FetchEntry | SetEntry
-            | DeleteEntry | InsertEntry | SetVar | MintAsset | MintToken | TransferAsset
-            | TransferToken | ContractCall | StxTransfer | StxBurn | AtBlock | GetStxBalance
-            | GetTokenSupply | BurnToken | BurnAsset => {
+            FetchVar | GetBlockInfo | GetBurnBlockInfo | GetTokenBalance | GetAssetOwner
+            | FetchEntry | SetEntry | DeleteEntry | InsertEntry | SetVar | MintAsset
+            | MintToken | TransferAsset | TransferToken | ContractCall | StxTransfer | StxBurn
+            | AtBlock | GetStxBalance | GetTokenSupply | BurnToken | BurnAsset => {
                 return Err(Error::FunctionNotPermitted(function));
             }
             Append | Concat | AsMaxLen | ContractOf | PrincipalOf | ListCons | Print
-            | AsContract | ElementAt | IndexOf | Map | Filter | Fold => {
+            | AsContract | ElementAt | IndexOf | Map | Filter | Fold | StringSlice
+            | StringIndexOf | ToLowercase | ToUppercase => {
                 return Err(Error::FunctionNotPermitted(function));
             }
             Sha512 | Sha512Trunc256 | Secp256k1Recover | Secp256k1Verify | Hash160 | Sha256
-            | Keccak256 => {
+            | Keccak256 | BuffToIntBe | BuffToUIntBe | IntToBuffBe | UIntToBuffBe => {
                 return Err(Error::FunctionNotPermitted(function));
             }
             Add | Subtract | Divide | Multiply | CmpGeq | CmpLeq | CmpLess | CmpGreater
@@ -153,6 +153,8 @@ fn test_functions() {
          FunctionNotPermitted(NativeFunctions::GetAssetOwner)),
         ("(get-block-info? id-header-hash 0)",
          FunctionNotPermitted(NativeFunctions::GetBlockInfo)),
+        ("(get-burn-block-info? header-hash u0)",
+         FunctionNotPermitted(NativeFunctions::GetBurnBlockInfo)),
         ("(define-private (foo) (contract-call? .bar outer-call))",
          FunctionNotPermitted(NativeFunctions::ContractCall)),
         ("(stx-get-balance 'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF)",
@@ -196,6 +198,12 @@ fn test_functions() {
          FunctionNotPermitted(NativeFunctions::Sha512)),
         ("(define-private (foo) (sha512/256 0))",
          FunctionNotPermitted(NativeFunctions::Sha512Trunc256)),
+        ("(define-private (foo) (to-lowercase \"ABC\"))",
+         FunctionNotPermitted(NativeFunctions::ToLowercase)),
+        ("(define-private (foo) (string-slice? \"ABC\" u0 u1))",
+         FunctionNotPermitted(NativeFunctions::StringSlice)),
+        ("(define-private (foo) (buff-to-uint-be 0x01))",
+         FunctionNotPermitted(NativeFunctions::BuffToUIntBe)),
 
     ];
 
@@ -30,6 +30,7 @@ pub enum CheckErrors {
     CostBalanceExceeded(ExecutionCost, ExecutionCost),
     MemoryBalanceExceeded(u64, u64),
     CostComputationFailed(String),
+    ExecutionTimeExpired,
 
     ValueTooLarge,
     ValueOutOfBounds,
@@ -125,6 +126,8 @@ pub enum CheckErrors {
     // get-block-info? errors
     NoSuchBlockInfoProperty(String),
     GetBlockInfoExpectPropertyName,
+    NoSuchBurnBlockInfoProperty(String),
+    GetBurnBlockInfoExpectPropertyName,
 
     NameAlreadyUsed(String),
 
@@ -245,6 +248,7 @@ impl From<CostErrors> for CheckErrors {
             CostErrors::CostContractLoadFailure => {
                 CheckErrors::CostComputationFailed("Failed to load cost contract".into())
             }
+            CostErrors::ExecutionTimeExpired => CheckErrors::ExecutionTimeExpired,
         }
     }
 }
@@ -314,6 +318,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::CostOverflow => "contract execution cost overflowed cost counter".into(),
             CheckErrors::CostBalanceExceeded(a, b) => format!("contract execution cost exceeded budget: {:?} > {:?}", a, b),
             CheckErrors::MemoryBalanceExceeded(a, b) => format!("contract execution cost exceeded memory budget: {:?} > {:?}", a, b),
+            CheckErrors::ExecutionTimeExpired => "contract execution exceeded its time limit".into(),
             CheckErrors::InvalidTypeDescription => "supplied type description is invalid".into(),
             CheckErrors::EmptyTuplesNotAllowed => "tuple types may not be empty".into(),
             CheckErrors::BadSyntaxExpectedListOfPairs => "bad syntax: function expects a list of pairs to bind names, e.g., ((name-0 a) (name-1 b) ...)".into(),
@@ -366,6 +371,8 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::ContractCallExpectName => format!("missing contract name for call"),
             CheckErrors::NoSuchBlockInfoProperty(property_name) => format!("use of block unknown property '{}'", property_name),
             CheckErrors::GetBlockInfoExpectPropertyName => format!("missing property name for block info introspection"),
+            CheckErrors::NoSuchBurnBlockInfoProperty(property_name) => format!("use of burn block unknown property '{}'", property_name),
+            CheckErrors::GetBurnBlockInfoExpectPropertyName => format!("missing property name for burn block info introspection"),
             CheckErrors::NameAlreadyUsed(name) => format!("defining '{}' conflicts with previous value", name),
             CheckErrors::NonFunctionApplication => format!("expecting expression of type function"),
             CheckErrors::ExpectedListApplication => format!("expecting expression of type list"),
@@ -426,6 +433,9 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::NoSuchBlockInfoProperty(_) => Some(format!(
                 "properties available: time, header-hash, burnchain-header-hash, vrf-seed"
             )),
+            CheckErrors::NoSuchBurnBlockInfoProperty(_) => {
+                Some(format!("properties available: header-hash, time"))
+            }
             _ => None,
         }
     }
@@ -91,6 +91,16 @@ pub fn type_check(
     .map_err(|(e, _cost_tracker)| e)
 }
 
+/// Whether an analysis charged to `cost_track` may let contracts comply with traits through
+/// supertraits (see `StacksEpochId::supports_trait_subtyping`).  A free cost tracker has no
+/// epoch, so its analyses use the strict rules.
+pub fn allows_trait_subtyping(cost_track: &LimitedCostTracker) -> bool {
+    cost_track
+        .get_epoch()
+        .map(|epoch| epoch.supports_trait_subtyping())
+        .unwrap_or(false)
+}
+
 pub fn run_analysis(
     contract_identifier: &QualifiedContractIdentifier,
     expressions: &mut [SymbolicExpression],
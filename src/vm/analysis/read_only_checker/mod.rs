@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use core::StacksEpochId;
 use vm::analysis::types::{AnalysisPass, ContractAnalysis};
 use vm::functions::define::DefineFunctionsParsed;
 use vm::functions::tuples;
@@ -38,6 +39,7 @@ mod tests;
 pub struct ReadOnlyChecker<'a, 'b> {
     db: &'a mut AnalysisDatabase<'b>,
     defined_functions: HashMap<ClarityName, bool>,
+    epoch: Option<StacksEpochId>,
 }
 
 impl<'a, 'b> AnalysisPass for ReadOnlyChecker<'a, 'b> {
@@ -45,17 +47,25 @@ impl<'a, 'b> AnalysisPass for ReadOnlyChecker<'a, 'b> {
         contract_analysis: &mut ContractAnalysis,
         analysis_db: &mut AnalysisDatabase,
     ) -> CheckResult<()> {
-        let mut command = ReadOnlyChecker::new(analysis_db);
+        let epoch = contract_analysis
+            .cost_track
+            .as_ref()
+            .and_then(|cost_track| cost_track.get_epoch());
+        let mut command = ReadOnlyChecker::new(analysis_db, epoch);
         command.run(contract_analysis)?;
         Ok(())
     }
 }
 
 impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
-    fn new(db: &'a mut AnalysisDatabase<'b>) -> ReadOnlyChecker<'a, 'b> {
+    fn new(
+        db: &'a mut AnalysisDatabase<'b>,
+        epoch: Option<StacksEpochId>,
+    ) -> ReadOnlyChecker<'a, 'b> {
         Self {
             db,
             defined_functions: HashMap::new(),
+            epoch,
         }
     }
 
@@ -159,7 +169,7 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
         function: &str,
         args: &[SymbolicExpression],
     ) -> Option<CheckResult<bool>> {
-        NativeFunctions::lookup_by_name(function)
+        NativeFunctions::lookup_by_name_at_epoch(function, self.epoch.as_ref())
             .map(|function| self.check_native_function(&function, args))
     }
 
@@ -179,7 +189,9 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
             | IsSome | TryRet | ToUInt | ToInt | Append | Concat | AsMaxLen | ContractOf
             | PrincipalOf | ListCons | GetBlockInfo | TupleGet | TupleMerge | Len | Print
             | AsContract | Begin | FetchVar | GetStxBalance | GetTokenBalance | GetAssetOwner
-            | GetTokenSupply | ElementAt | IndexOf => self.check_all_read_only(args),
+            | GetTokenSupply | ElementAt | IndexOf | StringSlice | StringIndexOf | ToLowercase
+            | ToUppercase | BuffToIntBe | BuffToUIntBe | IntToBuffBe | UIntToBuffBe
+            | GetBurnBlockInfo => self.check_all_read_only(args),
             AtBlock => {
                 check_argument_count(2, args)?;
 
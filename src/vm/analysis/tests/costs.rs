@@ -15,7 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use chainstate::stacks::index::storage::TrieFileStorage;
-use clarity_vm::clarity::ClarityInstance;
+use clarity_vm::clarity::{ClarityInstance, Error as ClarityError};
 use util::hash::hex_bytes;
 use vm::contexts::Environment;
 use vm::contexts::{AssetMap, AssetMapEntry, GlobalContext, OwnedEnvironment};
@@ -176,7 +176,10 @@ pub fn test_tracked_costs(prog: &str, use_mainnet: bool, epoch: StacksEpochId) -
 fn test_all(use_mainnet: bool) {
     let baseline = test_tracked_costs("1", use_mainnet, StacksEpochId::Epoch20);
 
-    for f in NativeFunctions::ALL.iter() {
+    for f in NativeFunctions::ALL
+        .iter()
+        .filter(|f| f.is_available_in(&StacksEpochId::Epoch20))
+    {
         let test = get_simple_test(f);
         let cost = test_tracked_costs(test, use_mainnet, StacksEpochId::Epoch20);
         assert!(cost.exceeds(&baseline));
@@ -196,7 +199,10 @@ fn test_all_testnet() {
 fn epoch_205_test_all(use_mainnet: bool) {
     let baseline = test_tracked_costs("1", use_mainnet, StacksEpochId::Epoch2_05);
 
-    for f in NativeFunctions::ALL.iter() {
+    for f in NativeFunctions::ALL
+        .iter()
+        .filter(|f| f.is_available_in(&StacksEpochId::Epoch2_05))
+    {
         let test = get_simple_test(f);
         let cost = test_tracked_costs(test, use_mainnet, StacksEpochId::Epoch2_05);
         assert!(cost.exceeds(&baseline));
@@ -212,3 +218,65 @@ fn epoch_205_test_all_mainnet() {
 fn epoch_205_test_all_testnet() {
     epoch_205_test_all(false)
 }
+
+#[test]
+fn epoch_205_string_natives_unavailable() {
+    let marf = MarfedKV::temporary();
+    let mut clarity_instance = ClarityInstance::new(false, marf);
+    let contract_id = QualifiedContractIdentifier::local("strings").unwrap();
+
+    clarity_instance
+        .begin_test_genesis_block(
+            &StacksBlockId::sentinel(),
+            &StacksBlockId([0 as u8; 32]),
+            &TEST_HEADER_DB,
+            &TEST_BURN_STATE_DB,
+        )
+        .commit_block();
+
+    let mut conn = clarity_instance.begin_block(
+        &StacksBlockId([0 as u8; 32]),
+        &StacksBlockId([1 as u8; 32]),
+        &TEST_HEADER_DB,
+        &TEST_BURN_STATE_DB,
+    );
+    conn.initialize_epoch_2_05().unwrap();
+
+    conn.as_transaction(|conn| {
+        // the natives are undefined in this epoch...
+        match conn
+            .analyze_smart_contract(&contract_id, "(define-read-only (f) (to-lowercase \"A\"))")
+            .unwrap_err()
+        {
+            ClarityError::Analysis(check_error) => assert_eq!(
+                check_error.err,
+                CheckErrors::UnknownFunction("to-lowercase".to_string())
+            ),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // ...so a contract's own function of the same name is the one that is checked.
+        conn.analyze_smart_contract(
+            &contract_id,
+            "(define-private (to-lowercase (s int)) (+ s 1))
+             (define-read-only (f) (to-lowercase 1))",
+        )
+        .unwrap();
+        match conn
+            .analyze_smart_contract(
+                &contract_id,
+                "(define-data-var v int 0)
+                 (define-private (to-lowercase (s int)) (var-set v s))
+                 (define-read-only (f) (to-lowercase 1))",
+            )
+            .unwrap_err()
+        {
+            ClarityError::Analysis(check_error) => {
+                assert_eq!(check_error.err, CheckErrors::WriteAttemptedInReadOnly)
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    });
+
+    conn.commit_block();
+}
@@ -18,7 +18,8 @@ use std::collections::HashMap;
 
 use vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use vm::analysis::types::{AnalysisPass, ContractAnalysis};
-use vm::analysis::AnalysisDatabase;
+use vm::analysis::{allows_trait_subtyping, AnalysisDatabase};
+use vm::costs::LimitedCostTracker;
 use vm::functions::define::{DefineFunctions, DefineFunctionsParsed};
 use vm::functions::NativeFunctions;
 use vm::representations::SymbolicExpressionType::{Atom, AtomValue, List, LiteralValue};
@@ -48,6 +49,25 @@ impl TraitChecker {
         contract_analysis: &mut ContractAnalysis,
         analysis_db: &mut AnalysisDatabase,
     ) -> CheckResult<()> {
+        // the analysis is borrowed by the checks, so charge them to its tracker separately
+        let mut cost_track = contract_analysis.cost_track.take();
+        let result = self.check_implemented_traits(contract_analysis, analysis_db, &mut cost_track);
+        contract_analysis.cost_track = cost_track;
+        result
+    }
+
+    fn check_implemented_traits(
+        &mut self,
+        contract_analysis: &ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        cost_track: &mut Option<LimitedCostTracker>,
+    ) -> CheckResult<()> {
+        let allow_subtyping = cost_track
+            .as_ref()
+            .map(allows_trait_subtyping)
+            .unwrap_or(false);
+        let mut free_tracker = LimitedCostTracker::new_free();
+        let tracker = cost_track.as_mut().unwrap_or(&mut free_tracker);
         for trait_identifier in &contract_analysis.implemented_traits {
             let trait_name = trait_identifier.name.to_string();
             let contract_defining_trait = analysis_db
@@ -62,7 +82,13 @@ impl TraitChecker {
                     trait_identifier.name.to_string(),
                 ))?;
 
-            contract_analysis.check_trait_compliance(trait_identifier, trait_definition)?;
+            analysis_db.check_trait_compliance(
+                tracker,
+                contract_analysis,
+                trait_identifier,
+                trait_definition,
+                allow_subtyping,
+            )?;
         }
         Ok(())
     }
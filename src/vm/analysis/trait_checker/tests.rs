@@ -15,13 +15,18 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::clarity_vm::database::MemoryBackingStore;
-use vm::analysis::errors::CheckErrors;
+use vm::analysis::errors::{CheckErrors, CheckResult};
 use vm::analysis::mem_type_check;
 use vm::analysis::type_check;
+use vm::analysis::types::TraitFunctionCompliance;
 use vm::analysis::{contract_interface_builder::build_contract_interface, AnalysisDatabase};
 use vm::ast::errors::ParseErrors;
 use vm::ast::{build_ast, parse};
-use vm::types::{QualifiedContractIdentifier, TypeSignature};
+use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::{CostErrors, CostTracker, ExecutionCost};
+use vm::representations::ClarityName;
+use vm::types::signatures::MAX_TRAIT_SUBTYPING_DEPTH;
+use vm::types::{QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
 
 #[test]
 fn test_dynamic_dispatch_by_defining_trait() {
@@ -191,6 +196,257 @@ fn test_complete_impl_trait() {
     .unwrap();
 }
 
+#[test]
+fn test_trait_function_compliance() {
+    let contract_defining_trait = "(define-trait trait-1 (
+            (get-1 (uint) (response uint uint))
+            (get-2 (uint) (response uint uint))
+            (get-3 (uint) (response uint uint))
+            (get-4 (uint) (response uint uint))))";
+    let impl_contract = "(define-read-only (get-1 (x uint)) (ok u1))
+        (define-public (get-2 (x int)) (ok u1))
+        (define-public (get-3 (x uint)) (ok 1))";
+    let def_contract_id = QualifiedContractIdentifier::local("defun").unwrap();
+    let impl_contract_id = QualifiedContractIdentifier::local("implem").unwrap();
+    let mut c1 = parse(&def_contract_id, contract_defining_trait).unwrap();
+    let mut c3 = parse(&impl_contract_id, impl_contract).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+    let (def_analysis, impl_analysis) = db
+        .execute(|db| -> CheckResult<_> {
+            let def_analysis = type_check(&def_contract_id, &mut c1, db, true)?;
+            let impl_analysis = type_check(&impl_contract_id, &mut c3, db, true)?;
+            Ok((def_analysis, impl_analysis))
+        })
+        .unwrap();
+
+    let trait_definition = def_analysis.get_defined_trait("trait-1").unwrap();
+    let compliance: Vec<_> = trait_definition
+        .iter()
+        .map(|(func_name, sig)| impl_analysis.check_trait_function_compliance(func_name, sig))
+        .collect();
+    assert_eq!(
+        compliance,
+        vec![
+            TraitFunctionCompliance::Compliant,
+            TraitFunctionCompliance::BadArguments,
+            TraitFunctionCompliance::BadReturnType,
+            TraitFunctionCompliance::Missing,
+        ]
+    );
+}
+
+#[test]
+fn test_impl_trait_subtyping() {
+    let contract_defining_trait = "(define-trait token-a (
+            (get-balance (principal) (response uint uint))
+            (transfer (uint principal) (response bool uint))))
+        (define-trait token-b (
+            (get-balance (principal) (response uint uint))))
+        (define-trait user (
+            (use-token (<token-a>) (response bool uint))))";
+    let contract_copying_trait = "(define-trait token-b (
+            (get-balance (principal) (response uint uint))))";
+    // only needs `get-balance`, so any `token-a` will do
+    let impl_contract = "(use-trait token-b .defun.token-b)
+        (define-public (use-token (t <token-b>)) (ok true))";
+    let def_contract_id = QualifiedContractIdentifier::local("defun").unwrap();
+    let copy_contract_id = QualifiedContractIdentifier::local("copy").unwrap();
+    let impl_contract_id = QualifiedContractIdentifier::local("implem").unwrap();
+    let mut c1 = parse(&def_contract_id, contract_defining_trait).unwrap();
+    let mut c2 = parse(&copy_contract_id, contract_copying_trait).unwrap();
+    let mut c3 = parse(&impl_contract_id, impl_contract).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+
+    let trait_id =
+        |contract_identifier: &QualifiedContractIdentifier, name: &str| TraitIdentifier {
+            name: name.into(),
+            contract_identifier: contract_identifier.clone(),
+        };
+    let token_a = trait_id(&def_contract_id, "token-a");
+    let token_b = trait_id(&def_contract_id, "token-b");
+    let token_b_copy = trait_id(&copy_contract_id, "token-b");
+    let user = trait_id(&def_contract_id, "user");
+
+    db.execute(|db| -> CheckResult<()> {
+        type_check(&def_contract_id, &mut c1, db, true)?;
+        type_check(&copy_contract_id, &mut c2, db, true)?;
+        let impl_analysis = type_check(&impl_contract_id, &mut c3, db, true)?;
+
+        assert!(db.is_subtrait(&mut (), &token_a, &token_b)?);
+        assert!(!db.is_subtrait(&mut (), &token_b, &token_a)?);
+        assert!(db.is_subtrait(&mut (), &token_b, &token_b_copy)?);
+        assert!(db.is_subtrait(&mut (), &token_b_copy, &token_b)?);
+        assert!(db.is_subtrait(&mut (), &token_a, &token_b_copy)?);
+
+        let user_definition = db.get_defined_trait(&def_contract_id, "user")?.unwrap();
+        assert!(db
+            .check_trait_compliance(&mut (), &impl_analysis, &user, &user_definition, true)
+            .is_ok());
+        match db
+            .check_trait_compliance(&mut (), &impl_analysis, &user, &user_definition, false)
+            .unwrap_err()
+            .err
+        {
+            CheckErrors::BadTraitImplementation(_, _) => {}
+            e => panic!("{:?}", e),
+        }
+        Ok(())
+    })
+    .unwrap();
+}
+
+/// Counts the trait definitions an analysis charges for loading.
+struct TraitLoadCounter(u64);
+
+impl CostTracker for TraitLoadCounter {
+    fn compute_cost(
+        &mut self,
+        cost_function: ClarityCostFunction,
+        _input: &[u64],
+    ) -> Result<ExecutionCost, CostErrors> {
+        if cost_function == ClarityCostFunction::AnalysisUseTraitEntry {
+            self.0 += 1;
+        }
+        Ok(ExecutionCost::zero())
+    }
+    fn add_cost(&mut self, _cost: ExecutionCost) -> Result<(), CostErrors> {
+        Ok(())
+    }
+    fn add_memory(&mut self, _memory: u64) -> Result<(), CostErrors> {
+        Ok(())
+    }
+    fn drop_memory(&mut self, _memory: u64) {}
+    fn reset_memory(&mut self) {}
+    fn short_circuit_contract_call(
+        &mut self,
+        _contract: &QualifiedContractIdentifier,
+        _function: &ClarityName,
+        _input: &[u64],
+    ) -> Result<bool, CostErrors> {
+        Ok(false)
+    }
+}
+
+/// Two chains of traits, `a-0` .. `a-{depth}` and `b-0` .. `b-{depth}`, where each trait's one
+/// function takes the previous trait of its chain as an argument.  Comparing `a-{depth}` with
+/// `b-{depth}` has to follow the chains all the way down.
+fn nested_traits_contract(depth: usize) -> String {
+    let mut src = String::new();
+    for chain in &["a", "b"] {
+        src.push_str(&format!(
+            "(define-trait {}-0 ((get-1 (uint) (response uint uint))))\n",
+            chain
+        ));
+        for i in 1..=depth {
+            src.push_str(&format!(
+                "(define-trait {c}-{i} ((get-1 (<{c}-{j}>) (response uint uint))))\n",
+                c = chain,
+                i = i,
+                j = i - 1
+            ));
+        }
+    }
+    src
+}
+
+#[test]
+fn test_subtrait_charges_trait_loads() {
+    let def_contract_id = QualifiedContractIdentifier::local("defun").unwrap();
+    let mut c1 = parse(&def_contract_id, &nested_traits_contract(2)).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+    let trait_id = |name: &str| TraitIdentifier {
+        name: name.into(),
+        contract_identifier: def_contract_id.clone(),
+    };
+
+    db.execute(|db| -> CheckResult<()> {
+        type_check(&def_contract_id, &mut c1, db, true)?;
+
+        let mut counter = TraitLoadCounter(0);
+        assert!(db.is_subtrait(&mut counter, &trait_id("a-2"), &trait_id("b-2"))?);
+        // both traits are loaded at each of the three levels
+        assert_eq!(counter.0, 6);
+
+        let mut counter = TraitLoadCounter(0);
+        assert!(db.is_subtrait(&mut counter, &trait_id("a-2"), &trait_id("a-2"))?);
+        assert_eq!(counter.0, 0);
+
+        // a missing trait still costs a lookup
+        let mut counter = TraitLoadCounter(0);
+        let missing = TraitIdentifier {
+            name: "a-0".into(),
+            contract_identifier: QualifiedContractIdentifier::local("missing").unwrap(),
+        };
+        assert!(!db.is_subtrait(&mut counter, &missing, &trait_id("a-0"))?);
+        assert_eq!(counter.0, 1);
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_subtrait_depth_is_bounded() {
+    let def_contract_id = QualifiedContractIdentifier::local("defun").unwrap();
+    let depth = MAX_TRAIT_SUBTYPING_DEPTH as usize;
+    let mut c1 = parse(&def_contract_id, &nested_traits_contract(depth)).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+    let trait_id = |name: String| TraitIdentifier {
+        name: name.as_str().into(),
+        contract_identifier: def_contract_id.clone(),
+    };
+
+    db.execute(|db| -> CheckResult<()> {
+        type_check(&def_contract_id, &mut c1, db, true)?;
+
+        let a = trait_id(format!("a-{}", depth - 1));
+        let b = trait_id(format!("b-{}", depth - 1));
+        assert!(db.is_subtrait(&mut (), &a, &b)?);
+
+        let a = trait_id(format!("a-{}", depth));
+        let b = trait_id(format!("b-{}", depth));
+        match db.is_subtrait(&mut (), &a, &b).unwrap_err().err {
+            CheckErrors::TypeSignatureTooDeep => {}
+            e => panic!("{:?}", e),
+        }
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_impl_trait_subtyping_not_in_current_epochs() {
+    let contract_defining_trait = "(define-trait token-a (
+            (get-balance (principal) (response uint uint))
+            (transfer (uint principal) (response bool uint))))
+        (define-trait token-b (
+            (get-balance (principal) (response uint uint))))
+        (define-trait user (
+            (use-token (<token-a>) (response bool uint))))";
+    let impl_contract = "(use-trait token-b .defun.token-b)
+        (impl-trait .defun.user)
+        (define-public (use-token (t <token-b>)) (ok true))";
+    let def_contract_id = QualifiedContractIdentifier::local("defun").unwrap();
+    let impl_contract_id = QualifiedContractIdentifier::local("implem").unwrap();
+    let mut c1 = parse(&def_contract_id, contract_defining_trait).unwrap();
+    let mut c3 = parse(&impl_contract_id, impl_contract).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+    let err = db
+        .execute(|db| {
+            type_check(&def_contract_id, &mut c1, db, true).unwrap();
+            type_check(&impl_contract_id, &mut c3, db, true)
+        })
+        .unwrap_err();
+    match err.err {
+        CheckErrors::BadTraitImplementation(_, _) => {}
+        _ => panic!("{:?}", err),
+    }
+}
+
 #[test]
 fn test_complete_impl_trait_mixing_readonly() {
     let contract_defining_trait = "(define-trait trait-1 (
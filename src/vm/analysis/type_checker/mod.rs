@@ -38,7 +38,7 @@ use vm::types::{
 use vm::variables::NativeVariables;
 
 pub use super::types::{AnalysisPass, ContractAnalysis};
-use super::AnalysisDatabase;
+use super::{allows_trait_subtyping, AnalysisDatabase};
 
 use self::contexts::{ContractContext, TypeMap, TypingContext};
 
@@ -240,7 +240,9 @@ impl FunctionType {
     pub fn check_args_by_allowing_trait_cast(
         &self,
         db: &mut AnalysisDatabase,
+        tracker: &mut LimitedCostTracker,
         func_args: &[Value],
+        allow_trait_subtyping: bool,
     ) -> CheckResult<TypeSignature> {
         let (expected_args, returns) = match self {
             FunctionType::Fixed(FixedFunction { args, returns }) => (args, returns),
@@ -263,7 +265,13 @@ impl FunctionType {
                         .ok_or(CheckErrors::NoSuchContract(
                             trait_id.contract_identifier.to_string(),
                         ))?;
-                    contract_to_check.check_trait_compliance(trait_id, &trait_definition)?;
+                    db.check_trait_compliance(
+                        tracker,
+                        &contract_to_check,
+                        trait_id,
+                        &trait_definition,
+                        allow_trait_subtyping,
+                    )?;
                 }
                 (expected_type, value) => {
                     if !expected_type.admits(&value) {
@@ -279,7 +287,7 @@ impl FunctionType {
     }
 }
 
-fn trait_type_size(trait_sig: &BTreeMap<ClarityName, FunctionSignature>) -> CheckResult<u64> {
+pub fn trait_type_size(trait_sig: &BTreeMap<ClarityName, FunctionSignature>) -> CheckResult<u64> {
     let mut total_size = 0;
     for (_func_name, value) in trait_sig.iter() {
         total_size = total_size.cost_overflow_add(value.total_type_size()? as u64)?;
@@ -424,7 +432,14 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
                     .get_defined_trait(&trait_identifier.name)
                     .ok_or(CheckErrors::NoSuchContract(contract_identifier.to_string()))?;
 
-                contract_to_check.check_trait_compliance(trait_identifier, trait_definition)?;
+                let allow_subtyping = allows_trait_subtyping(&self.cost_track);
+                self.db.check_trait_compliance(
+                    &mut self.cost_track,
+                    &contract_to_check,
+                    trait_identifier,
+                    trait_definition,
+                    allow_subtyping,
+                )?;
                 return Ok(expected_type.clone());
             }
             (_, _) => {}
@@ -606,7 +621,10 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
         args: &[SymbolicExpression],
         context: &TypingContext,
     ) -> Option<TypeResult> {
-        if let Some(ref native_function) = NativeFunctions::lookup_by_name(function) {
+        let epoch = self.cost_track.get_epoch();
+        if let Some(ref native_function) =
+            NativeFunctions::lookup_by_name_at_epoch(function, epoch.as_ref())
+        {
             let typed_function = TypedNativeFunction::type_native_function(native_function);
             Some(typed_function.type_check_appliction(self, args, context))
         } else {
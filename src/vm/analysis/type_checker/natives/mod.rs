@@ -22,9 +22,9 @@ use vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use vm::errors::{Error as InterpError, RuntimeErrorType};
 use vm::functions::{handle_binding_list, NativeFunctions};
 use vm::types::{
-    BlockInfoProperty, FixedFunction, FunctionArg, FunctionSignature, FunctionType, PrincipalData,
-    TupleTypeSignature, TypeSignature, Value, BUFF_20, BUFF_32, BUFF_33, BUFF_64, BUFF_65,
-    MAX_VALUE_SIZE,
+    BlockInfoProperty, BurnBlockInfoProperty, FixedFunction, FunctionArg, FunctionSignature,
+    FunctionType, PrincipalData, TupleTypeSignature, TypeSignature, Value, BUFF_16, BUFF_20,
+    BUFF_32, BUFF_33, BUFF_64, BUFF_65, MAX_VALUE_SIZE,
 };
 use vm::{ClarityName, SymbolicExpression, SymbolicExpressionType};
 
@@ -518,6 +518,29 @@ fn check_get_block_info(
     Ok(TypeSignature::new_option(block_info_prop.type_result())?)
 }
 
+fn check_get_burn_block_info(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    let burn_block_info_prop_str = args[0].match_atom().ok_or(CheckError::new(
+        CheckErrors::GetBurnBlockInfoExpectPropertyName,
+    ))?;
+
+    let burn_block_info_prop = BurnBlockInfoProperty::lookup_by_name(burn_block_info_prop_str)
+        .ok_or(CheckError::new(CheckErrors::NoSuchBurnBlockInfoProperty(
+            burn_block_info_prop_str.to_string(),
+        )))?;
+
+    checker.type_check_expects(&args[1], &context, &TypeSignature::UIntType)?;
+
+    Ok(TypeSignature::new_option(
+        burn_block_info_prop.type_result(),
+    )?)
+}
+
 impl TypedNativeFunction {
     pub fn type_check_appliction(
         &self,
@@ -568,6 +591,38 @@ impl TypedNativeFunction {
                 )],
                 returns: TypeSignature::IntType,
             }))),
+            BuffToIntBe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::IntType,
+            }))),
+            BuffToUIntBe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::UIntType,
+            }))),
+            IntToBuffBe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::IntType,
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: BUFF_16.clone(),
+            }))),
+            UIntToBuffBe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::UIntType,
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: BUFF_16.clone(),
+            }))),
             Not => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
                 args: vec![FunctionArg::new(
                     TypeSignature::BoolType,
@@ -694,6 +749,15 @@ impl TypedNativeFunction {
             Len => Special(SpecialNativeFunction(&sequences::check_special_len)),
             ElementAt => Special(SpecialNativeFunction(&sequences::check_special_element_at)),
             IndexOf => Special(SpecialNativeFunction(&sequences::check_special_index_of)),
+            StringSlice => Special(SpecialNativeFunction(
+                &sequences::check_special_string_slice,
+            )),
+            StringIndexOf => Special(SpecialNativeFunction(
+                &sequences::check_special_string_index_of,
+            )),
+            ToLowercase | ToUppercase => {
+                Special(SpecialNativeFunction(&sequences::check_special_string_case))
+            }
             ListCons => Special(SpecialNativeFunction(&check_special_list_cons)),
             FetchEntry => Special(SpecialNativeFunction(&maps::check_special_fetch_entry)),
             SetEntry => Special(SpecialNativeFunction(&maps::check_special_set_entry)),
@@ -709,6 +773,7 @@ impl TypedNativeFunction {
             ContractOf => Special(SpecialNativeFunction(&check_contract_of)),
             PrincipalOf => Special(SpecialNativeFunction(&check_principal_of)),
             GetBlockInfo => Special(SpecialNativeFunction(&check_get_block_info)),
+            GetBurnBlockInfo => Special(SpecialNativeFunction(&check_get_burn_block_info)),
             ConsSome => Special(SpecialNativeFunction(&options::check_special_some)),
             ConsOkay => Special(SpecialNativeFunction(&options::check_special_okay)),
             ConsError => Special(SpecialNativeFunction(&options::check_special_error)),
@@ -19,7 +19,7 @@ use std::convert::TryInto;
 use vm::functions::NativeFunctions;
 use vm::representations::{SymbolicExpression, SymbolicExpressionType};
 pub use vm::types::signatures::{BufferLength, ListTypeData, StringUTF8Length, BUFF_1};
-use vm::types::{FunctionType, TypeSignature};
+use vm::types::{FunctionType, StringSubtype, TypeSignature};
 use vm::types::{SequenceSubtype::*, StringSubtype::*};
 use vm::types::{Value, MAX_VALUE_SIZE};
 
@@ -37,7 +37,10 @@ fn get_simple_native_or_user_define(
     checker: &mut TypeChecker,
 ) -> CheckResult<FunctionType> {
     runtime_cost(ClarityCostFunction::AnalysisLookupFunction, checker, 0)?;
-    if let Some(ref native_function) = NativeFunctions::lookup_by_name(function_name) {
+    let epoch = checker.cost_track.get_epoch();
+    if let Some(ref native_function) =
+        NativeFunctions::lookup_by_name_at_epoch(function_name, epoch.as_ref())
+    {
         if let TypedNativeFunction::Simple(SimpleNativeFunction(function_type)) =
             TypedNativeFunction::type_native_function(native_function)
         {
@@ -389,3 +392,73 @@ pub fn check_special_index_of(
 
     TypeSignature::new_option(TypeSignature::UIntType).map_err(|e| e.into())
 }
+
+fn check_string_argument(
+    checker: &mut TypeChecker,
+    arg: &SymbolicExpression,
+    context: &TypingContext,
+) -> CheckResult<StringSubtype> {
+    let arg_type = checker.type_check(arg, context)?;
+    match arg_type {
+        TypeSignature::SequenceType(StringType(string_type)) => Ok(string_type),
+        _ => Err(CheckErrors::UnionTypeError(
+            vec![
+                TypeSignature::min_string_ascii(),
+                TypeSignature::min_string_utf8(),
+            ],
+            arg_type,
+        )
+        .into()),
+    }
+}
+
+pub fn check_special_string_slice(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(3, args)?;
+
+    runtime_cost(ClarityCostFunction::AnalysisIterableFunc, checker, 0)?;
+    let string_type = check_string_argument(checker, &args[0], context)?;
+    checker.type_check_expects(&args[1], context, &TypeSignature::UIntType)?;
+    checker.type_check_expects(&args[2], context, &TypeSignature::UIntType)?;
+
+    TypeSignature::new_option(TypeSignature::SequenceType(StringType(string_type)))
+        .map_err(|e| e.into())
+}
+
+pub fn check_special_string_index_of(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    runtime_cost(ClarityCostFunction::AnalysisIterableFunc, checker, 0)?;
+    let haystack_type = check_string_argument(checker, &args[0], context)?;
+    let needle_type = check_string_argument(checker, &args[1], context)?;
+
+    // the needle may be longer than the haystack, but it must be the same kind of string.
+    match (&haystack_type, &needle_type) {
+        (ASCII(_), ASCII(_)) | (UTF8(_), UTF8(_)) => {
+            TypeSignature::new_option(TypeSignature::UIntType).map_err(|e| e.into())
+        }
+        _ => Err(CheckErrors::TypeError(
+            TypeSignature::SequenceType(StringType(haystack_type)),
+            TypeSignature::SequenceType(StringType(needle_type)),
+        )
+        .into()),
+    }
+}
+
+pub fn check_special_string_case(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+
+    let string_type = check_string_argument(checker, &args[0], context)?;
+    Ok(TypeSignature::SequenceType(StringType(string_type)))
+}
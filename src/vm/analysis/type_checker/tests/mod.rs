@@ -94,6 +94,41 @@ fn test_get_block_info() {
     }
 }
 
+#[test]
+fn test_get_burn_block_info() {
+    let good = [
+        "(get-burn-block-info? header-hash u1)",
+        "(get-burn-block-info? time (* u2 u3))",
+    ];
+    let expected = ["(optional (buff 32))", "(optional uint)"];
+
+    let bad = [
+        "(get-burn-block-info? burnchain-header-hash u1)",
+        "(get-burn-block-info? time true)",
+        "(get-burn-block-info? time 1)",
+        "(get-burn-block-info? time)",
+        "(get-burn-block-info? time u1 u2)",
+    ];
+    let bad_expected = [
+        CheckErrors::NoSuchBurnBlockInfoProperty("burnchain-header-hash".to_string()),
+        CheckErrors::TypeError(UIntType, BoolType),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::IncorrectArgumentCount(2, 3),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_define_trait() {
     let good = [
@@ -1234,6 +1269,45 @@ fn test_simple_uints() {
     }
 }
 
+#[test]
+fn test_buffer_int_conversions() {
+    let good = [
+        "(buff-to-int-be 0x01)",
+        "(buff-to-uint-be 0x0102030405060708090a0b0c0d0e0f10)",
+        "(int-to-buff-be -1)",
+        "(uint-to-buff-be u1)",
+    ];
+
+    let expected = ["int", "uint", "(buff 16)", "(buff 16)"];
+
+    let bad = [
+        "(buff-to-int-be 0x0102030405060708090a0b0c0d0e0f1011)",
+        "(buff-to-uint-be u1)",
+        "(int-to-buff-be u1)",
+    ];
+
+    let bad_expected = [
+        CheckErrors::TypeError(
+            SequenceType(BufferType(16_u32.try_into().unwrap())),
+            SequenceType(BufferType(17_u32.try_into().unwrap())),
+        ),
+        CheckErrors::TypeError(
+            SequenceType(BufferType(16_u32.try_into().unwrap())),
+            UIntType,
+        ),
+        CheckErrors::TypeError(IntType, UIntType),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        let type_sig = mem_type_check(good_test).unwrap().0.unwrap();
+        assert_eq!(expected, &type_sig.to_string());
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(&mem_type_check(bad_test).unwrap_err().err, expected);
+    }
+}
+
 #[test]
 fn test_response_inference() {
     let good = [
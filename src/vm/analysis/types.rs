@@ -29,6 +29,16 @@ const DESERIALIZE_FAIL_MESSAGE: &str =
 const SERIALIZE_FAIL_MESSAGE: &str =
     "PANIC: Failed to deserialize bad database data in contract analysis.";
 
+/// How well a contract's function matches one function of a trait
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraitFunctionCompliance {
+    Compliant,
+    /// no public or read-only function with this name
+    Missing,
+    BadArguments,
+    BadReturnType,
+}
+
 pub trait AnalysisPass {
     fn run_pass(
         contract_analysis: &mut ContractAnalysis,
@@ -181,44 +191,76 @@ impl ContractAnalysis {
         self.defined_traits.get(name)
     }
 
+    /// Check whether this contract has a public or read-only function that can stand in for
+    /// one function of a trait.
+    pub fn check_trait_function_compliance(
+        &self,
+        func_name: &ClarityName,
+        expected_sig: &FunctionSignature,
+    ) -> TraitFunctionCompliance {
+        self.check_trait_function_compliance_with(
+            func_name,
+            expected_sig,
+            &mut |expected, candidate| expected == candidate,
+        )
+    }
+
+    /// Like `check_trait_function_compliance`, but a function's trait arguments need only be
+    /// supertraits of the trait's, according to `is_subtrait` (see
+    /// `FunctionSignature::check_args_trait_compliance_with`).
+    pub fn check_trait_function_compliance_with(
+        &self,
+        func_name: &ClarityName,
+        expected_sig: &FunctionSignature,
+        is_subtrait: &mut dyn FnMut(&TraitIdentifier, &TraitIdentifier) -> bool,
+    ) -> TraitFunctionCompliance {
+        match (
+            self.get_public_function_type(func_name),
+            self.get_read_only_function_type(func_name),
+        ) {
+            (Some(FunctionType::Fixed(func)), None) | (None, Some(FunctionType::Fixed(func))) => {
+                let args_sig = func.args.iter().map(|a| a.signature.clone()).collect();
+                if !expected_sig.check_args_trait_compliance_with(args_sig, is_subtrait) {
+                    TraitFunctionCompliance::BadArguments
+                } else if !expected_sig.returns.admits_type(&func.returns) {
+                    TraitFunctionCompliance::BadReturnType
+                } else {
+                    TraitFunctionCompliance::Compliant
+                }
+            }
+            (_, _) => TraitFunctionCompliance::Missing,
+        }
+    }
+
     pub fn check_trait_compliance(
         &self,
         trait_identifier: &TraitIdentifier,
         trait_definition: &BTreeMap<ClarityName, FunctionSignature>,
+    ) -> CheckResult<()> {
+        self.check_trait_compliance_with(
+            trait_identifier,
+            trait_definition,
+            &mut |expected, candidate| expected == candidate,
+        )
+    }
+
+    /// Like `check_trait_compliance`, but a function's trait arguments need only be supertraits
+    /// of the trait's, according to `is_subtrait`.
+    pub fn check_trait_compliance_with(
+        &self,
+        trait_identifier: &TraitIdentifier,
+        trait_definition: &BTreeMap<ClarityName, FunctionSignature>,
+        is_subtrait: &mut dyn FnMut(&TraitIdentifier, &TraitIdentifier) -> bool,
     ) -> CheckResult<()> {
         let trait_name = trait_identifier.name.to_string();
 
         for (func_name, expected_sig) in trait_definition.iter() {
-            match (
-                self.get_public_function_type(func_name),
-                self.get_read_only_function_type(func_name),
-            ) {
-                (Some(FunctionType::Fixed(func)), None)
-                | (None, Some(FunctionType::Fixed(func))) => {
-                    let args_sig = func.args.iter().map(|a| a.signature.clone()).collect();
-                    if !expected_sig.check_args_trait_compliance(args_sig) {
-                        return Err(CheckErrors::BadTraitImplementation(
-                            trait_name,
-                            func_name.to_string(),
-                        )
-                        .into());
-                    }
-
-                    if !expected_sig.returns.admits_type(&func.returns) {
-                        return Err(CheckErrors::BadTraitImplementation(
-                            trait_name,
-                            func_name.to_string(),
-                        )
-                        .into());
-                    }
-                }
-                (_, _) => {
-                    return Err(CheckErrors::BadTraitImplementation(
-                        trait_name,
-                        func_name.to_string(),
-                    )
-                    .into())
-                }
+            if self.check_trait_function_compliance_with(func_name, expected_sig, is_subtrait)
+                != TraitFunctionCompliance::Compliant
+            {
+                return Err(
+                    CheckErrors::BadTraitImplementation(trait_name, func_name.to_string()).into(),
+                );
             }
         }
         Ok(())
@@ -141,6 +141,9 @@ impl From<CostErrors> for ParseError {
             CostErrors::CostContractLoadFailure => ParseError::new(
                 ParseErrors::CostComputationFailed("Failed to load cost contract".into()),
             ),
+            CostErrors::ExecutionTimeExpired => ParseError::new(
+                ParseErrors::CostComputationFailed("Execution time expired".into()),
+            ),
         }
     }
 }
@@ -150,7 +150,6 @@ impl DefinedFunction {
             )?;
         }
 
-        let mut context = LocalContext::new();
         if args.len() != self.arguments.len() {
             Err(CheckErrors::IncorrectArgumentCount(
                 self.arguments.len(),
@@ -158,16 +157,14 @@ impl DefinedFunction {
             ))?
         }
 
-        let mut arg_iterator: Vec<_> = self
+        let mut context = LocalContext::new_pooled(&mut env.global_context.local_context_pool);
+        let arg_iterator = self
             .arguments
             .iter()
             .zip(self.arg_types.iter())
-            .zip(args.iter())
-            .collect();
-
-        for arg in arg_iterator.drain(..) {
-            let ((name, type_sig), value) = arg;
+            .zip(args.iter());
 
+        for ((name, type_sig), value) in arg_iterator {
             match (type_sig, value) {
                 (
                     TypeSignature::TraitReferenceType(trait_identifier),
@@ -195,6 +192,7 @@ impl DefinedFunction {
         }
 
         let result = eval(&self.body, env, &context);
+        context.release(&mut env.global_context.local_context_pool);
 
         // if the error wasn't actually an error, but a function return,
         //    pull that out and return it.
@@ -207,10 +205,14 @@ impl DefinedFunction {
         }
     }
 
+    /// Check that this function can stand in for its namesake in a trait.  Its trait arguments
+    /// are compared to the trait's with `is_subtrait` (see
+    /// `FunctionSignature::check_args_trait_compliance_with`).
     pub fn check_trait_expectations(
         &self,
         contract_defining_trait: &ContractContext,
         trait_identifier: &TraitIdentifier,
+        is_subtrait: &mut dyn FnMut(&TraitIdentifier, &TraitIdentifier) -> bool,
     ) -> Result<()> {
         let trait_name = trait_identifier.name.to_string();
         let constraining_trait = contract_defining_trait
@@ -225,7 +227,7 @@ impl DefinedFunction {
                 ))?;
 
         let args = self.arg_types.iter().map(|a| a.clone()).collect();
-        if !expected_sig.check_args_trait_compliance(args) {
+        if !expected_sig.check_args_trait_compliance_with(args, is_subtrait) {
             return Err(
                 CheckErrors::BadTraitImplementation(trait_name, self.name.to_string()).into(),
             );
@@ -273,7 +275,7 @@ impl CallableType {
 }
 
 impl FunctionIdentifier {
-    fn new_native_function(name: &str) -> FunctionIdentifier {
+    pub fn new_native_function(name: &str) -> FunctionIdentifier {
         let identifier = format!("_native_:{}", name);
         FunctionIdentifier {
             identifier: identifier,
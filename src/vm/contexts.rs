@@ -57,6 +57,11 @@ use vm::coverage::CoverageReporter;
 
 pub const MAX_CONTEXT_DEPTH: u16 = 256;
 
+// Bounds on what a LocalContextPool holds on to: at most one map per context depth,
+//   and no maps grown by unusually large binding lists.
+const MAX_POOLED_CONTEXTS: usize = MAX_CONTEXT_DEPTH as usize;
+const MAX_POOLED_CONTEXT_CAPACITY: usize = 64;
+
 // TODO:
 //    hide the environment's instance variables.
 //     we don't want many of these changing after instantiation.
@@ -195,6 +200,7 @@ pub struct GlobalContext<'a> {
     pub cost_track: LimitedCostTracker,
     pub mainnet: bool,
     pub coverage_reporting: Option<CoverageReporter>,
+    pub local_context_pool: LocalContextPool,
     /// This is the epoch of the the block that this transaction is executing within.
     epoch_id: StacksEpochId,
 }
@@ -215,6 +221,11 @@ pub struct ContractContext {
     pub meta_nft: HashMap<ClarityName, NonFungibleTokenMetadata>,
     pub meta_ft: HashMap<ClarityName, FungibleTokenMetadata>,
     pub data_size: u64,
+    // the epoch this contract was deployed in.  Natives enabled by a later epoch are not
+    //  reserved names in this contract, so its own definitions keep shadowing them.
+    //  Contracts stored without one were deployed before any such natives existed.
+    #[serde(default = "ContractContext::default_epoch")]
+    epoch: StacksEpochId,
 }
 
 pub struct LocalContext<'a> {
@@ -225,6 +236,14 @@ pub struct LocalContext<'a> {
     depth: u16,
 }
 
+/// The variable maps of finished `LocalContext`s, emptied but with their capacity intact, so
+/// that the `let`s, `match`es and function calls of a transaction reuse them instead of going
+/// to the allocator for each new context.
+pub struct LocalContextPool {
+    variables: Vec<HashMap<ClarityName, Value>>,
+    max_contexts: usize,
+}
+
 pub struct CallStack {
     stack: Vec<FunctionIdentifier>,
     set: HashSet<FunctionIdentifier>,
@@ -535,7 +554,7 @@ impl<'a> OwnedEnvironment<'a> {
         let epoch = StacksEpochId::Epoch2_05;
         OwnedEnvironment {
             context: GlobalContext::new(false, database, LimitedCostTracker::new_free(), epoch),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(QualifiedContractIdentifier::transient(), epoch),
             call_stack: CallStack::new(),
         }
     }
@@ -550,7 +569,7 @@ impl<'a> OwnedEnvironment<'a> {
             .expect("FAIL: problem instantiating cost tracking");
         OwnedEnvironment {
             context: GlobalContext::new(use_mainnet, database, cost_track, epoch),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(QualifiedContractIdentifier::transient(), epoch),
             call_stack: CallStack::new(),
         }
     }
@@ -563,6 +582,10 @@ impl<'a> OwnedEnvironment<'a> {
         self.context.coverage_reporting.take()
     }
 
+    pub fn set_local_context_pool(&mut self, pool: LocalContextPool) {
+        self.context.local_context_pool = pool
+    }
+
     pub fn new_free(
         mainnet: bool,
         database: ClarityDatabase<'a>,
@@ -575,7 +598,10 @@ impl<'a> OwnedEnvironment<'a> {
                 LimitedCostTracker::new_free(),
                 epoch_id,
             ),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                epoch_id,
+            ),
             call_stack: CallStack::new(),
         }
     }
@@ -588,7 +614,10 @@ impl<'a> OwnedEnvironment<'a> {
     ) -> OwnedEnvironment<'a> {
         OwnedEnvironment {
             context: GlobalContext::new(mainnet, database, cost_tracker, epoch_id),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                epoch_id,
+            ),
             call_stack: CallStack::new(),
         }
     }
@@ -1389,9 +1418,15 @@ impl<'a> GlobalContext<'a> {
             mainnet,
             epoch_id,
             coverage_reporting: None,
+            local_context_pool: LocalContextPool::default(),
         }
     }
 
+    /// The epoch of the block this transaction is executing within.
+    pub fn epoch(&self) -> &StacksEpochId {
+        &self.epoch_id
+    }
+
     pub fn is_top_level(&self) -> bool {
         self.asset_maps.len() == 0
     }
@@ -1553,7 +1588,7 @@ impl<'a> GlobalContext<'a> {
 }
 
 impl ContractContext {
-    pub fn new(contract_identifier: QualifiedContractIdentifier) -> Self {
+    pub fn new(contract_identifier: QualifiedContractIdentifier, epoch: StacksEpochId) -> Self {
         Self {
             contract_identifier,
             variables: HashMap::new(),
@@ -1566,9 +1601,20 @@ impl ContractContext {
             meta_data_var: HashMap::new(),
             meta_nft: HashMap::new(),
             meta_ft: HashMap::new(),
+            epoch,
         }
     }
 
+    fn default_epoch() -> StacksEpochId {
+        StacksEpochId::Epoch20
+    }
+
+    /// The epoch this contract was deployed in, which decides which natives its names may not
+    ///  shadow.
+    pub fn get_epoch(&self) -> &StacksEpochId {
+        &self.epoch
+    }
+
     pub fn lookup_variable(&self, name: &str) -> Option<&Value> {
         self.variables.get(name)
     }
@@ -1589,7 +1635,7 @@ impl ContractContext {
     }
 
     pub fn is_name_used(&self, name: &str) -> bool {
-        is_reserved(name)
+        is_reserved(name, &self.epoch)
             || self.variables.contains_key(name)
             || self.functions.contains_key(name)
             || self.persisted_names.contains(name)
@@ -1633,6 +1679,26 @@ impl<'a> LocalContext<'a> {
         }
     }
 
+    /// Like `new`, but takes its variable map from `pool`
+    pub fn new_pooled(pool: &mut LocalContextPool) -> LocalContext<'a> {
+        LocalContext {
+            variables: pool.take(),
+            ..LocalContext::new()
+        }
+    }
+
+    /// Like `extend`, but takes the new context's variable map from `pool`
+    pub fn extend_pooled(&'a self, pool: &mut LocalContextPool) -> Result<LocalContext<'a>> {
+        let mut context = self.extend()?;
+        context.variables = pool.take();
+        Ok(context)
+    }
+
+    /// Hand this context's variable map back to `pool` for reuse
+    pub fn release(self, pool: &mut LocalContextPool) {
+        pool.put(self.variables);
+    }
+
     pub fn lookup_variable(&self, name: &str) -> Option<&Value> {
         match self.variables.get(name) {
             Some(value) => Some(value),
@@ -1651,6 +1717,45 @@ impl<'a> LocalContext<'a> {
     }
 }
 
+impl Default for LocalContextPool {
+    fn default() -> LocalContextPool {
+        LocalContextPool {
+            variables: vec![],
+            max_contexts: MAX_POOLED_CONTEXTS,
+        }
+    }
+}
+
+impl LocalContextPool {
+    /// A pool that never holds on to a map, so every context allocates its own.  This is how
+    /// contexts were allocated before there was a pool, which is useful for benchmarking.
+    pub fn unpooled() -> LocalContextPool {
+        LocalContextPool {
+            variables: vec![],
+            max_contexts: 0,
+        }
+    }
+
+    fn take(&mut self) -> HashMap<ClarityName, Value> {
+        self.variables.pop().unwrap_or_default()
+    }
+
+    fn put(&mut self, mut variables: HashMap<ClarityName, Value>) {
+        if variables.capacity() == 0
+            || variables.capacity() > MAX_POOLED_CONTEXT_CAPACITY
+            || self.variables.len() >= self.max_contexts
+        {
+            return;
+        }
+        variables.clear();
+        self.variables.push(variables);
+    }
+
+    pub fn len(&self) -> usize {
+        self.variables.len()
+    }
+}
+
 impl CallStack {
     pub fn new() -> CallStack {
         CallStack {
@@ -1718,6 +1823,48 @@ impl CallStack {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_local_context_pool() {
+        let mut pool = LocalContextPool::default();
+        let root = LocalContext::new();
+
+        // empty maps never allocated anything, so aren't worth pooling
+        root.extend_pooled(&mut pool).unwrap().release(&mut pool);
+        assert_eq!(pool.len(), 0);
+
+        let mut inner = root.extend_pooled(&mut pool).unwrap();
+        inner.variables.insert("a".into(), Value::Int(1));
+        let capacity = inner.variables.capacity();
+        inner.release(&mut pool);
+        assert_eq!(pool.len(), 1);
+
+        // the next context reuses the emptied map
+        let inner = root.extend_pooled(&mut pool).unwrap();
+        assert_eq!(pool.len(), 0);
+        assert_eq!(inner.depth(), 1);
+        assert!(inner.variables.is_empty());
+        assert_eq!(inner.variables.capacity(), capacity);
+        assert!(inner.lookup_variable("a").is_none());
+        inner.release(&mut pool);
+
+        // oversized maps are dropped
+        let mut inner = LocalContext::new_pooled(&mut pool);
+        for i in 0..(MAX_POOLED_CONTEXT_CAPACITY + 1) {
+            inner
+                .variables
+                .insert(format!("a{}", i).as_str().into(), Value::Int(1));
+        }
+        inner.release(&mut pool);
+        assert_eq!(pool.len(), 0);
+
+        // an unpooled pool never holds on to anything
+        let mut pool = LocalContextPool::unpooled();
+        let mut inner = root.extend_pooled(&mut pool).unwrap();
+        inner.variables.insert("a".into(), Value::Int(1));
+        inner.release(&mut pool);
+        assert_eq!(pool.len(), 0);
+    }
+
     #[test]
     fn test_asset_map_abort() {
         let a_contract_id = QualifiedContractIdentifier::local("a").unwrap();
@@ -1869,4 +2016,29 @@ mod test {
         assert_eq!(table[&p1][&t7], AssetMapEntry::Burn(30 + 31));
         assert_eq!(table[&p2][&t7], AssetMapEntry::Burn(35 + 36));
     }
+
+    #[test]
+    fn test_contract_context_epoch() {
+        let contract_context = ContractContext::new(
+            QualifiedContractIdentifier::transient(),
+            StacksEpochId::Epoch2_05,
+        );
+        let mut json = serde_json::to_value(&contract_context).unwrap();
+        assert_eq!(
+            serde_json::from_value::<ContractContext>(json.clone())
+                .unwrap()
+                .get_epoch(),
+            &StacksEpochId::Epoch2_05
+        );
+
+        // contracts stored before the deploy epoch was recorded were deployed in Stacks 2.0 or
+        //  2.05, before any epoch-gated natives existed
+        json.as_object_mut().unwrap().remove("epoch");
+        assert_eq!(
+            serde_json::from_value::<ContractContext>(json)
+                .unwrap()
+                .get_epoch(),
+            &StacksEpochId::Epoch20
+        );
+    }
 }
@@ -36,7 +36,8 @@ impl Contract {
         contract: &ContractAST,
         global_context: &mut GlobalContext,
     ) -> Result<Contract> {
-        let mut contract_context = ContractContext::new(contract_identifier);
+        let mut contract_context =
+            ContractContext::new(contract_identifier, *global_context.epoch());
 
         eval_all(&contract.expressions, &mut contract_context, global_context)?;
 
@@ -0,0 +1,316 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Turning wall-clock measurements of Clarity natives into proposed cost-function constants.
+//!
+//! The `cost_functions_bench` benchmark times each native across a range of input sizes.  The
+//! functions here fit those timings to the shape (constant, linear, logn or nlogn) the native's
+//! cost function already has in a costs contract, convert them to runtime units, and render a
+//! copy of that contract with the recalibrated definitions swapped in, ready to be proposed
+//! through the `cost-voting` contract.
+//!
+//! Only cost functions that assess runtime alone are recalibrated; functions that also assess
+//! reads or writes are left as they are.
+
+use std::fmt;
+
+use vm::costs::cost_functions::ClarityCostFunction;
+
+/// The shape of a runtime cost function, in terms of the helpers the costs contracts define
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CostModel {
+    Constant,
+    Linear,
+    LogN,
+    NLogN,
+}
+
+/// The mean wall-clock time of one evaluation of a native with a given input size
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostSample {
+    pub input_size: u64,
+    pub nanos: f64,
+}
+
+/// Proposed constants for one cost function: its runtime is `a * model(n) + b`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostCalibration {
+    pub function: ClarityCostFunction,
+    pub model: CostModel,
+    pub a: u64,
+    pub b: u64,
+}
+
+/// Floor of log2, as the costs contracts' `log2` computes it
+fn clarity_log2(n: u64) -> f64 {
+    if n == 0 {
+        0.0
+    } else {
+        (63 - n.leading_zeros()) as f64
+    }
+}
+
+impl CostModel {
+    /// The helper's name in the costs contracts
+    fn helper_name(&self) -> &'static str {
+        match self {
+            CostModel::Constant => "",
+            CostModel::Linear => "linear",
+            CostModel::LogN => "logn",
+            CostModel::NLogN => "nlogn",
+        }
+    }
+
+    /// The term `a` is multiplied by
+    fn scale(&self, n: u64) -> f64 {
+        match self {
+            CostModel::Constant => 0.0,
+            CostModel::Linear => n as f64,
+            CostModel::LogN => clarity_log2(n),
+            CostModel::NLogN => n as f64 * clarity_log2(n),
+        }
+    }
+
+    /// Find the shape of `function`'s definition in the costs contract `contract`.
+    /// Returns None if the contract doesn't define it, or if it assesses more than runtime.
+    pub fn of_definition(contract: &str, function: &ClarityCostFunction) -> Option<CostModel> {
+        let (start, end) = find_definition(contract, function)?;
+        let body = contract[start..end].splitn(2, "(n uint))").nth(1)?.trim();
+        if !body.starts_with("(runtime ") {
+            return None;
+        }
+        let body = body["(runtime ".len()..].trim_start();
+        if body.starts_with("(linear ") {
+            Some(CostModel::Linear)
+        } else if body.starts_with("(logn ") {
+            Some(CostModel::LogN)
+        } else if body.starts_with("(nlogn ") {
+            Some(CostModel::NLogN)
+        } else if body.starts_with("u") {
+            Some(CostModel::Constant)
+        } else {
+            None
+        }
+    }
+}
+
+/// Find the byte range of `function`'s definition in the costs contract `contract`
+fn find_definition(contract: &str, function: &ClarityCostFunction) -> Option<(usize, usize)> {
+    let start = contract.find(&format!("(define-read-only ({} ", function.get_name_str()))?;
+    let end = contract[start..]
+        .find("\n\n")
+        .map(|len| start + len)
+        .unwrap_or(contract.trim_end().len());
+    Some((start, end))
+}
+
+/// Runtime units per nanosecond of reference hardware, if a block that exhausts
+/// `block_runtime_limit` should take `reference_block_ms` to evaluate.
+pub fn runtime_units_per_ns(block_runtime_limit: u64, reference_block_ms: u64) -> f64 {
+    block_runtime_limit as f64 / (reference_block_ms as f64 * 1_000_000.0)
+}
+
+impl CostCalibration {
+    /// Fit `samples` of `function` to `model` by least squares, and convert the result to
+    /// runtime units.  The fitted constants are never negative, and `b` is at least 1.
+    pub fn fit(
+        function: ClarityCostFunction,
+        model: CostModel,
+        samples: &[CostSample],
+        units_per_ns: f64,
+    ) -> CostCalibration {
+        let count = samples.len().max(1) as f64;
+        let mean_x = samples
+            .iter()
+            .map(|s| model.scale(s.input_size))
+            .sum::<f64>()
+            / count;
+        let mean_y = samples.iter().map(|s| s.nanos).sum::<f64>() / count;
+
+        let (cov, var) = samples.iter().fold((0.0, 0.0), |(cov, var), s| {
+            let dx = model.scale(s.input_size) - mean_x;
+            (cov + dx * (s.nanos - mean_y), var + dx * dx)
+        });
+
+        let (mut a, mut b) = if var > 0.0 {
+            let a = cov / var;
+            (a, mean_y - a * mean_x)
+        } else {
+            (0.0, mean_y)
+        };
+        if a < 0.0 {
+            // no measurable growth with input size
+            a = 0.0;
+            b = mean_y;
+        }
+        if b < 0.0 {
+            b = 0.0;
+        }
+
+        CostCalibration {
+            function,
+            model,
+            a: (a * units_per_ns).round() as u64,
+            b: ((b * units_per_ns).round() as u64).max(1),
+        }
+    }
+
+    /// Render this calibration as a definition in the format of the costs contracts
+    pub fn to_clarity(&self) -> String {
+        let runtime = match self.model {
+            CostModel::Constant => format!("u{}", self.b),
+            _ => format!("({} n u{} u{})", self.model.helper_name(), self.a, self.b),
+        };
+        format!(
+            "(define-read-only ({} (n uint))\n    (runtime {}))",
+            self.function.get_name_str(),
+            runtime
+        )
+    }
+}
+
+impl fmt::Display for CostCalibration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_clarity())
+    }
+}
+
+/// Render a copy of the costs contract `base_contract` with the definitions of the calibrated
+/// functions replaced by their calibrations.  Calibrations of functions the contract doesn't
+/// define are skipped.
+pub fn propose_costs_contract(base_contract: &str, calibrations: &[CostCalibration]) -> String {
+    let mut contract = base_contract.to_string();
+    for calibration in calibrations.iter() {
+        if let Some((start, end)) = find_definition(&contract, &calibration.function) {
+            contract.replace_range(start..end, &calibration.to_clarity());
+        }
+    }
+    contract
+}
+
+#[cfg(test)]
+mod test {
+    use chainstate::stacks::boot::{BOOT_CODE_COSTS_2, BOOT_CODE_COSTS_3};
+
+    use super::*;
+
+    fn samples(f: impl Fn(u64) -> f64) -> Vec<CostSample> {
+        [1u64, 2, 4, 8, 16, 32, 64, 128]
+            .iter()
+            .map(|n| CostSample {
+                input_size: *n,
+                nanos: f(*n),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_models() {
+        let fit = CostCalibration::fit(
+            ClarityCostFunction::Add,
+            CostModel::Linear,
+            &samples(|n| 14.0 * n as f64 + 157.0),
+            1.0,
+        );
+        assert_eq!((fit.a, fit.b), (14, 157));
+
+        let fit = CostCalibration::fit(
+            ClarityCostFunction::TupleGet,
+            CostModel::NLogN,
+            &samples(|n| 4.0 * n as f64 * clarity_log2(n) + 1736.0),
+            2.0,
+        );
+        assert_eq!((fit.a, fit.b), (8, 3472));
+
+        let fit = CostCalibration::fit(
+            ClarityCostFunction::Not,
+            CostModel::Constant,
+            &samples(|_| 162.0),
+            1.0,
+        );
+        assert_eq!((fit.a, fit.b), (0, 162));
+
+        // shrinking with input size is treated as constant
+        let fit = CostCalibration::fit(
+            ClarityCostFunction::Add,
+            CostModel::Linear,
+            &samples(|n| 1000.0 - n as f64),
+            1.0,
+        );
+        assert_eq!(fit.a, 0);
+    }
+
+    #[test]
+    fn test_runtime_units_per_ns() {
+        assert_eq!(runtime_units_per_ns(5_000_000_000, 5_000), 1.0);
+        assert_eq!(runtime_units_per_ns(5_000_000_000, 50_000), 0.1);
+    }
+
+    #[test]
+    fn test_of_definition() {
+        assert_eq!(
+            CostModel::of_definition(BOOT_CODE_COSTS_2, &ClarityCostFunction::Add),
+            Some(CostModel::Linear)
+        );
+        assert_eq!(
+            CostModel::of_definition(BOOT_CODE_COSTS_2, &ClarityCostFunction::Not),
+            Some(CostModel::Constant)
+        );
+        // assesses reads as well as runtime
+        assert_eq!(
+            CostModel::of_definition(BOOT_CODE_COSTS_2, &ClarityCostFunction::FetchVar),
+            None
+        );
+    }
+
+    #[test]
+    fn test_propose_costs_contract() {
+        let calibration = CostCalibration {
+            function: ClarityCostFunction::Add,
+            model: CostModel::Linear,
+            a: 20,
+            b: 300,
+        };
+        let proposal = propose_costs_contract(BOOT_CODE_COSTS_2, &[calibration.clone()]);
+
+        assert!(proposal.contains(
+            "(define-read-only (cost_add (n uint))\n    (runtime (linear n u20 u300)))\n\n"
+        ));
+        assert!(!BOOT_CODE_COSTS_2.contains(&calibration.to_clarity()));
+        // nothing else changed
+        let (start, end) = find_definition(BOOT_CODE_COSTS_2, &ClarityCostFunction::Add).unwrap();
+        assert_eq!(&proposal[..start], &BOOT_CODE_COSTS_2[..start]);
+        assert_eq!(
+            &proposal[start + calibration.to_clarity().len()..],
+            &BOOT_CODE_COSTS_2[end..]
+        );
+    }
+
+    #[test]
+    fn test_costs_3_defines_every_cost_function() {
+        for function in ClarityCostFunction::ALL.iter() {
+            assert!(
+                find_definition(BOOT_CODE_COSTS_3, function).is_some(),
+                "{} is not defined in .costs-3",
+                function
+            );
+        }
+        assert_eq!(
+            CostModel::of_definition(BOOT_CODE_COSTS_3, &ClarityCostFunction::StringIndexOf),
+            Some(CostModel::Linear)
+        );
+    }
+}
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use core::StacksEpochId;
+
 define_named_enum!(ClarityCostFunction {
     AnalysisTypeAnnotate("cost_analysis_type_annotate"),
     AnalysisTypeCheck("cost_analysis_type_check"),
@@ -130,4 +132,39 @@ define_named_enum!(ClarityCostFunction {
     NftOwner("cost_nft_owner"),
     NftBurn("cost_nft_burn"),
     PoisonMicroblock("poison_microblock"),
+    StringSlice("cost_string_slice"),
+    StringIndexOf("cost_string_index_of"),
+    ToLowercase("cost_to_lowercase"),
+    ToUppercase("cost_to_uppercase"),
+    BuffToIntBe("cost_buff_to_int_be"),
+    BuffToUIntBe("cost_buff_to_uint_be"),
+    IntToBuffBe("cost_int_to_buff_be"),
+    UIntToBuffBe("cost_uint_to_buff_be"),
+    BurnBlockInfo("cost_burn_block_info"),
 });
+
+impl ClarityCostFunction {
+    /// Whether this cost function exists in `epoch`.  The cost functions of natives that are
+    /// enabled by a later epoch do not exist before it, so cost-vote proposals that name them are
+    /// invalid until then.
+    pub fn is_available_in(&self, epoch: &StacksEpochId) -> bool {
+        use vm::costs::cost_functions::ClarityCostFunction::*;
+        match self {
+            StringSlice | StringIndexOf | ToLowercase | ToUppercase => {
+                epoch.supports_string_natives()
+            }
+            BuffToIntBe | BuffToUIntBe | IntToBuffBe | UIntToBuffBe => {
+                epoch.supports_buffer_int_natives()
+            }
+            BurnBlockInfo => epoch.supports_burn_block_info(),
+            _ => true,
+        }
+    }
+
+    /// Look up the cost function named `name`, treating cost functions that don't exist in
+    /// `epoch` as undefined.
+    pub fn lookup_by_name_at_epoch(name: &str, epoch: &StacksEpochId) -> Option<Self> {
+        ClarityCostFunction::lookup_by_name(name)
+            .filter(|cost_function| cost_function.is_available_in(epoch))
+    }
+}
@@ -16,6 +16,7 @@
 
 use std::collections::{BTreeMap, HashMap};
 use std::convert::{TryFrom, TryInto};
+use std::time::Instant;
 use std::{cmp, fmt};
 
 use regex::internal::Exec;
@@ -39,8 +40,10 @@ use vm::types::{
 };
 use vm::{ast, eval_all, ClarityName, SymbolicExpression, Value};
 
+pub mod calibration;
 pub mod constants;
 pub mod cost_functions;
+pub mod proposals;
 
 type Result<T> = std::result::Result<T, CostErrors>;
 
@@ -238,6 +241,65 @@ pub struct TrackerData {
     ///  evaluated, so no epoch identifier is necessary.
     epoch: StacksEpochId,
     mainnet: bool,
+    /// if set, adding any cost after this time fails.  This is only used to bound the wall-clock
+    /// time of read-only calls made on behalf of RPC clients, never when processing blocks.
+    deadline: Option<Instant>,
+    /// if set, runtime cost is attributed to cost functions and contract-call edges here
+    breakdown: Option<CostBreakdown>,
+}
+
+/// The runtime cost spent in one caller-to-callee `contract-call?` edge, including the cost of
+/// any calls the callee makes in turn
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractCallCost {
+    pub caller: String,
+    pub callee: String,
+    pub function: String,
+    pub count: u64,
+    pub runtime: u64,
+}
+
+/// Where the runtime cost of some Clarity evaluation went: per cost function (i.e. per native
+/// function, plus the analysis and AST costs), and per contract-call edge
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CostBreakdown {
+    pub cost_functions: BTreeMap<String, u64>,
+    pub contract_calls: Vec<ContractCallCost>,
+}
+
+impl CostBreakdown {
+    fn add_cost_function(&mut self, cost_function: &ClarityCostFunction, runtime: u64) {
+        let entry = self
+            .cost_functions
+            .entry(cost_function.get_name())
+            .or_insert(0);
+        *entry = entry.saturating_add(runtime);
+    }
+
+    fn add_contract_call(
+        &mut self,
+        caller: &QualifiedContractIdentifier,
+        callee: &QualifiedContractIdentifier,
+        function: &str,
+        runtime: u64,
+    ) {
+        let (caller, callee) = (caller.to_string(), callee.to_string());
+        match self.contract_calls.iter_mut().find(|edge| {
+            edge.caller == caller && edge.callee == callee && edge.function == function
+        }) {
+            Some(edge) => {
+                edge.count += 1;
+                edge.runtime = edge.runtime.saturating_add(runtime);
+            }
+            None => self.contract_calls.push(ContractCallCost {
+                caller,
+                callee,
+                function: function.to_string(),
+                count: 1,
+                runtime,
+            }),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -315,6 +377,7 @@ pub enum CostErrors {
     CostBalanceExceeded(ExecutionCost, ExecutionCost),
     MemoryBalanceExceeded(u64, u64),
     CostContractLoadFailure,
+    ExecutionTimeExpired,
 }
 
 fn load_state_summary(mainnet: bool, clarity_db: &mut ClarityDatabase) -> Result<CostStateSummary> {
@@ -401,6 +464,7 @@ fn load_cost_functions(
     if !apply_updates {
         return Ok(state_summary);
     }
+    let epoch = clarity_db.get_clarity_epoch_version();
 
     for confirmed_proposal in fetch_start..fetch_end {
         // fetch the proposal data
@@ -545,7 +609,10 @@ fn load_cost_functions(
 
         if target_contract == boot_code_id("costs", mainnet) {
             // refering to one of the boot code cost functions
-            let target = match ClarityCostFunction::lookup_by_name(&target_function) {
+            let target = match ClarityCostFunction::lookup_by_name_at_epoch(
+                &target_function,
+                &epoch,
+            ) {
                 Some(cost_func) => cost_func,
                 None => {
                     warn!("Confirmed cost proposal invalid: function-name does not reference a Clarity cost function";
@@ -628,6 +695,8 @@ impl LimitedCostTracker {
             memory: 0,
             epoch,
             mainnet,
+            deadline: None,
+            breakdown: None,
         };
         assert!(clarity_db.is_stack_empty());
         cost_tracker.load_costs(clarity_db, true)?;
@@ -650,6 +719,8 @@ impl LimitedCostTracker {
             memory: 0,
             epoch,
             mainnet,
+            deadline: None,
+            breakdown: None,
         };
         cost_tracker.load_costs(clarity_db, false)?;
         Ok(Self::Limited(cost_tracker))
@@ -775,6 +846,58 @@ impl LimitedCostTracker {
             Self::Free => ExecutionCost::max_value(),
         }
     }
+    /// The epoch this tracker evaluates cost functions in, or None if it is free
+    pub fn get_epoch(&self) -> Option<StacksEpochId> {
+        match self {
+            Self::Limited(TrackerData { epoch, .. }) => Some(*epoch),
+            Self::Free => None,
+        }
+    }
+    /// Fail any cost added after `deadline`.  Only for read-only calls -- the outcome of a block
+    /// must never depend on how fast the node evaluating it is.
+    pub fn set_deadline(&mut self, deadline: Instant) -> () {
+        match self {
+            Self::Limited(ref mut data) => data.deadline = Some(deadline),
+            Self::Free => panic!("Cannot set deadline on free tracker"),
+        }
+    }
+    /// Start attributing runtime cost to cost functions and contract-call edges.  A free tracker
+    /// has no cost to attribute, so this does nothing to it.
+    pub fn enable_cost_breakdown(&mut self) -> () {
+        if let Self::Limited(ref mut data) = self {
+            if data.breakdown.is_none() {
+                data.breakdown = Some(CostBreakdown::default());
+            }
+        }
+    }
+    /// Get the cost attributed since the last call, and start afresh.
+    /// Returns None if the cost breakdown isn't enabled.
+    pub fn take_cost_breakdown(&mut self) -> Option<CostBreakdown> {
+        match self {
+            Self::Limited(TrackerData {
+                breakdown: Some(ref mut breakdown),
+                ..
+            }) => Some(std::mem::replace(breakdown, CostBreakdown::default())),
+            _ => None,
+        }
+    }
+    /// Attribute `runtime` to a `contract-call?` from `caller` to `callee`'s `function`, if the
+    /// cost breakdown is enabled.
+    pub fn record_contract_call_cost(
+        &mut self,
+        caller: &QualifiedContractIdentifier,
+        callee: &QualifiedContractIdentifier,
+        function: &str,
+        runtime: u64,
+    ) -> () {
+        if let Self::Limited(TrackerData {
+            breakdown: Some(ref mut breakdown),
+            ..
+        }) = self
+        {
+            breakdown.add_contract_call(caller, callee, function, runtime);
+        }
+    }
 }
 
 fn parse_cost(
@@ -861,6 +984,11 @@ fn compute_cost(
 }
 
 fn add_cost(s: &mut TrackerData, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
+    if let Some(deadline) = s.deadline {
+        if Instant::now() >= deadline {
+            return Err(CostErrors::ExecutionTimeExpired);
+        }
+    }
     s.total.add(&cost)?;
     if s.total.exceeds(&s.limit) {
         Err(CostErrors::CostBalanceExceeded(
@@ -909,7 +1037,11 @@ impl CostTracker for LimitedCostTracker {
                     )))?
                     .clone();
 
-                compute_cost(data, cost_function_ref, input, data.epoch)
+                let cost = compute_cost(data, cost_function_ref, input, data.epoch)?;
+                if let Some(ref mut breakdown) = data.breakdown {
+                    breakdown.add_cost_function(&cost_function, cost.runtime);
+                }
+                Ok(cost)
             }
         }
     }
@@ -1187,6 +1319,32 @@ fn int_log2(input: u64) -> Option<u64> {
 mod unit_tests {
     use super::*;
 
+    #[test]
+    fn test_cost_functions_by_epoch() {
+        // cost-vote proposals may not name the cost functions of natives that aren't enabled yet
+        for name in [
+            "cost_string_slice",
+            "cost_buff_to_int_be",
+            "cost_burn_block_info",
+        ]
+        .iter()
+        {
+            assert!(ClarityCostFunction::lookup_by_name(name).is_some());
+            assert!(
+                ClarityCostFunction::lookup_by_name_at_epoch(name, &StacksEpochId::Epoch20)
+                    .is_none()
+            );
+            assert!(
+                ClarityCostFunction::lookup_by_name_at_epoch(name, &StacksEpochId::Epoch2_05)
+                    .is_none()
+            );
+        }
+        assert_eq!(
+            ClarityCostFunction::lookup_by_name_at_epoch("cost_add", &StacksEpochId::Epoch20),
+            Some(ClarityCostFunction::Add)
+        );
+    }
+
     #[test]
     fn test_simple_overflows() {
         assert_eq!(u64::MAX.cost_overflow_add(1), Err(CostErrors::CostOverflow));
@@ -1220,4 +1378,93 @@ mod unit_tests {
             assert_eq!(int_log2(*input).unwrap(), *expected);
         }
     }
+
+    #[test]
+    fn test_deadline() {
+        let mut tracker = LimitedCostTracker::Limited(TrackerData {
+            cost_function_references: HashMap::new(),
+            cost_contracts: HashMap::new(),
+            contract_call_circuits: HashMap::new(),
+            limit: ExecutionCost::max_value(),
+            memory_limit: CLARITY_MEMORY_LIMIT,
+            total: ExecutionCost::zero(),
+            memory: 0,
+            epoch: StacksEpochId::Epoch2_05,
+            mainnet: false,
+            deadline: None,
+            breakdown: None,
+        });
+        let cost = ExecutionCost::runtime(1);
+        assert!(tracker.add_cost(cost.clone()).is_ok());
+
+        tracker.set_deadline(Instant::now() + std::time::Duration::from_secs(3600));
+        assert!(tracker.add_cost(cost.clone()).is_ok());
+
+        tracker.set_deadline(Instant::now());
+        assert_eq!(
+            tracker.add_cost(cost.clone()),
+            Err(CostErrors::ExecutionTimeExpired)
+        );
+        assert_eq!(tracker.get_total(), ExecutionCost::runtime(2));
+    }
+
+    #[test]
+    fn test_contract_call_breakdown() {
+        let mut tracker = LimitedCostTracker::Limited(TrackerData {
+            cost_function_references: HashMap::new(),
+            cost_contracts: HashMap::new(),
+            contract_call_circuits: HashMap::new(),
+            limit: ExecutionCost::max_value(),
+            memory_limit: CLARITY_MEMORY_LIMIT,
+            total: ExecutionCost::zero(),
+            memory: 0,
+            epoch: StacksEpochId::Epoch2_05,
+            mainnet: false,
+            deadline: None,
+            breakdown: None,
+        });
+        let caller = QualifiedContractIdentifier::local("caller").unwrap();
+        let callee = QualifiedContractIdentifier::local("callee").unwrap();
+
+        // nothing is attributed until the breakdown is enabled
+        tracker.record_contract_call_cost(&caller, &callee, "foo", 10);
+        assert_eq!(tracker.take_cost_breakdown(), None);
+
+        tracker.enable_cost_breakdown();
+        tracker.record_contract_call_cost(&caller, &callee, "foo", 10);
+        tracker.record_contract_call_cost(&caller, &callee, "foo", 5);
+        tracker.record_contract_call_cost(&caller, &callee, "bar", 1);
+
+        let breakdown = tracker.take_cost_breakdown().unwrap();
+        assert_eq!(
+            breakdown.contract_calls,
+            vec![
+                ContractCallCost {
+                    caller: caller.to_string(),
+                    callee: callee.to_string(),
+                    function: "foo".to_string(),
+                    count: 2,
+                    runtime: 15,
+                },
+                ContractCallCost {
+                    caller: caller.to_string(),
+                    callee: callee.to_string(),
+                    function: "bar".to_string(),
+                    count: 1,
+                    runtime: 1,
+                },
+            ]
+        );
+
+        // taking the breakdown starts it afresh
+        assert_eq!(
+            tracker.take_cost_breakdown(),
+            Some(CostBreakdown::default())
+        );
+
+        // a free tracker has nothing to attribute
+        let mut free = LimitedCostTracker::new_free();
+        free.enable_cost_breakdown();
+        assert_eq!(free.take_cost_breakdown(), None);
+    }
 }
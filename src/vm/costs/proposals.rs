@@ -0,0 +1,316 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Building, checking and inspecting proposals to the `cost-voting` boot contract.
+//!
+//! A proposal replaces the cost function of a Clarity native (a function of the boot `costs`
+//! contract) or of a user-defined read-only function with a function from a cost contract.  The
+//! node only checks a proposal once it has been voted in and miner-confirmed, and skips it if it
+//! is invalid, so `CostProposal::validate` applies those checks up front.
+
+use std::convert::TryFrom;
+
+use chainstate::stacks::TransactionPayload;
+use util::boot::{boot_code_addr, boot_code_id};
+use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::COST_TUPLE_TYPE_SIGNATURE;
+use vm::database::ClarityDatabase;
+use vm::types::{
+    FunctionType, PrincipalData, QualifiedContractIdentifier, TupleData, TypeSignature,
+};
+use vm::{ClarityName, Value};
+
+pub const COST_VOTING_CONTRACT_NAME: &'static str = "cost-voting";
+
+/// A proposal to have `cost_function_contract.cost_function_name` compute the cost of
+/// `function_contract.function_name`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostProposal {
+    pub function_contract: QualifiedContractIdentifier,
+    pub function_name: ClarityName,
+    pub cost_function_contract: QualifiedContractIdentifier,
+    pub cost_function_name: ClarityName,
+}
+
+/// A proposal as stored in the `cost-voting` contract, along with its votes and vetos
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostProposalState {
+    pub proposal_id: u128,
+    pub function_contract: String,
+    pub function_name: String,
+    pub cost_function_contract: String,
+    pub cost_function_name: String,
+    /// Votes must be confirmed before this height
+    pub expiration_block_height: u128,
+    pub votes: u128,
+    pub vetos: u128,
+    /// Set once the votes are confirmed: vetos may be cast until this height
+    pub veto_expiration_block_height: Option<u128>,
+    /// Set once the proposal is miner-confirmed
+    pub confirmed_id: Option<u128>,
+}
+
+impl CostProposal {
+    /// The arguments to pass to `cost-voting`'s `submit-proposal`
+    pub fn submit_args(&self) -> Vec<Value> {
+        vec![
+            Value::from(self.function_contract.clone()),
+            Value::string_ascii_from_bytes(self.function_name.as_bytes().to_vec())
+                .expect("BUG: Clarity name is not a valid ASCII string"),
+            Value::from(self.cost_function_contract.clone()),
+            Value::string_ascii_from_bytes(self.cost_function_name.as_bytes().to_vec())
+                .expect("BUG: Clarity name is not a valid ASCII string"),
+        ]
+    }
+
+    /// A contract-call payload that submits this proposal
+    pub fn submit_payload(&self, mainnet: bool) -> TransactionPayload {
+        TransactionPayload::new_contract_call(
+            boot_code_addr(mainnet),
+            COST_VOTING_CONTRACT_NAME,
+            "submit-proposal",
+            self.submit_args(),
+        )
+        .expect("BUG: failed to construct submit-proposal payload")
+    }
+
+    /// Check that this proposal would be applied if it were confirmed against the state in
+    /// `clarity_db`.  This is at least as strict as the checks the node makes when it loads a
+    /// confirmed proposal.  `clarity_db` must be in a nested context.
+    pub fn validate(&self, mainnet: bool, clarity_db: &mut ClarityDatabase) -> Result<(), String> {
+        let cost_contract = clarity_db
+            .load_contract_analysis(&self.cost_function_contract)
+            .ok_or_else(|| {
+                format!(
+                    "cost-function-contract {} is not a published contract",
+                    &self.cost_function_contract
+                )
+            })?;
+        if !cost_contract.is_cost_contract_eligible {
+            return Err(format!(
+                "cost-function-contract {} uses non-arithmetic or otherwise illegal operations",
+                &self.cost_function_contract
+            ));
+        }
+
+        let cost_function_type = match cost_contract
+            .read_only_function_types
+            .get(&self.cost_function_name)
+            .or_else(|| {
+                cost_contract
+                    .private_function_types
+                    .get(&self.cost_function_name)
+            }) {
+            Some(FunctionType::Fixed(function_type)) => function_type,
+            _ => {
+                return Err(format!(
+                    "cost-function-name {} is not defined in {}",
+                    &self.cost_function_name, &self.cost_function_contract
+                ))
+            }
+        };
+        if cost_function_type.returns != *COST_TUPLE_TYPE_SIGNATURE {
+            return Err(format!(
+                "cost-function-name {} returns {}, not a cost tuple",
+                &self.cost_function_name, &cost_function_type.returns
+            ));
+        }
+        if cost_function_type.args.len() == 0 {
+            return Err(format!(
+                "cost-function-name {} takes no arguments",
+                &self.cost_function_name
+            ));
+        }
+        if let Some(arg) = cost_function_type
+            .args
+            .iter()
+            .find(|arg| arg.signature != TypeSignature::UIntType)
+        {
+            return Err(format!(
+                "cost-function-name {} argument {} is {}, not uint",
+                &self.cost_function_name, &arg.name, &arg.signature
+            ));
+        }
+
+        if self.function_contract == boot_code_id("costs", mainnet) {
+            let epoch = clarity_db.get_clarity_epoch_version();
+            if ClarityCostFunction::lookup_by_name_at_epoch(&self.function_name, &epoch).is_none() {
+                return Err(format!(
+                    "function-name {} does not reference a Clarity cost function",
+                    &self.function_name
+                ));
+            }
+            if cost_function_type.args.len() != 1 {
+                return Err(format!(
+                    "cost-function-name {} must take exactly one argument to replace a Clarity cost function",
+                    &self.cost_function_name
+                ));
+            }
+        } else {
+            let target_contract = clarity_db
+                .load_contract_analysis(&self.function_contract)
+                .ok_or_else(|| {
+                    format!(
+                        "function-contract {} is not a published contract",
+                        &self.function_contract
+                    )
+                })?;
+            match target_contract
+                .read_only_function_types
+                .get(&self.function_name)
+            {
+                Some(FunctionType::Fixed(target_type)) => {
+                    if target_type.args.len() != cost_function_type.args.len() {
+                        return Err(format!(
+                            "cost-function-name {} takes {} arguments, but function-name {} takes {}",
+                            &self.cost_function_name,
+                            cost_function_type.args.len(),
+                            &self.function_name,
+                            target_type.args.len()
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "function-name {} is not defined in {} or is not read-only",
+                        &self.function_name, &self.function_contract
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn fetch_proposal_entry(
+    clarity_db: &mut ClarityDatabase,
+    cost_voting_contract: &QualifiedContractIdentifier,
+    map_name: &str,
+    proposal_id: u128,
+) -> Result<Option<TupleData>, String> {
+    let key = TupleData::from_data(vec![("proposal-id".into(), Value::UInt(proposal_id))])
+        .expect("BUG: failed to construct simple tuple");
+    let entry = clarity_db
+        .fetch_entry_unknown_descriptor(cost_voting_contract, map_name, &Value::from(key))
+        .map_err(|e| e.to_string())?;
+    Ok(entry.expect_optional().map(|value| value.expect_tuple()))
+}
+
+fn expect_tuple_u128(tuple: &TupleData, name: &str) -> u128 {
+    tuple
+        .get(name)
+        .expect("BUG: malformed cost-voting tuple")
+        .clone()
+        .expect_u128()
+}
+
+fn expect_tuple_string(tuple: &TupleData, name: &str) -> String {
+    match tuple.get(name).expect("BUG: malformed cost-voting tuple") {
+        Value::Principal(principal) => principal.to_string(),
+        value => value.clone().expect_ascii(),
+    }
+}
+
+/// The number of proposals submitted to `cost-voting` so far.  Proposal IDs count up from 0.
+pub fn get_cost_proposal_count(
+    mainnet: bool,
+    clarity_db: &mut ClarityDatabase,
+) -> Result<u128, String> {
+    clarity_db
+        .lookup_variable_unknown_descriptor(
+            &boot_code_id(COST_VOTING_CONTRACT_NAME, mainnet),
+            "proposal-count",
+        )
+        .map(|count| count.expect_u128())
+        .map_err(|e| e.to_string())
+}
+
+/// Look up proposal `proposal_id` in `cost-voting`, or None if there is no such proposal.
+/// `clarity_db` must be in a nested context.
+pub fn get_cost_proposal_state(
+    mainnet: bool,
+    clarity_db: &mut ClarityDatabase,
+    proposal_id: u128,
+) -> Result<Option<CostProposalState>, String> {
+    let cost_voting_contract = boot_code_id(COST_VOTING_CONTRACT_NAME, mainnet);
+    let proposal =
+        match fetch_proposal_entry(clarity_db, &cost_voting_contract, "proposals", proposal_id)? {
+            Some(proposal) => proposal,
+            None => return Ok(None),
+        };
+
+    let votes = fetch_proposal_entry(
+        clarity_db,
+        &cost_voting_contract,
+        "proposal-votes",
+        proposal_id,
+    )?
+    .map(|entry| expect_tuple_u128(&entry, "votes"))
+    .unwrap_or(0);
+    let vetos = fetch_proposal_entry(
+        clarity_db,
+        &cost_voting_contract,
+        "proposal-vetos",
+        proposal_id,
+    )?
+    .map(|entry| expect_tuple_u128(&entry, "vetos"))
+    .unwrap_or(0);
+    let veto_expiration_block_height = fetch_proposal_entry(
+        clarity_db,
+        &cost_voting_contract,
+        "vote-confirmed-proposals",
+        proposal_id,
+    )?
+    .map(|entry| expect_tuple_u128(&entry, "expiration-block-height"));
+    let confirmed_id = fetch_proposal_entry(
+        clarity_db,
+        &cost_voting_contract,
+        "proposal-confirmed-id",
+        proposal_id,
+    )?
+    .map(|entry| expect_tuple_u128(&entry, "confirmed-id"));
+
+    Ok(Some(CostProposalState {
+        proposal_id,
+        function_contract: expect_tuple_string(&proposal, "function-contract"),
+        function_name: expect_tuple_string(&proposal, "function-name"),
+        cost_function_contract: expect_tuple_string(&proposal, "cost-function-contract"),
+        cost_function_name: expect_tuple_string(&proposal, "cost-function-name"),
+        expiration_block_height: expect_tuple_u128(&proposal, "expiration-block-height"),
+        votes,
+        vetos,
+        veto_expiration_block_height,
+        confirmed_id,
+    }))
+}
+
+impl CostProposalState {
+    /// The stored proposal, or None if it names a non-contract principal or an invalid function
+    /// name, in which case it can never be applied.
+    pub fn proposal(&self) -> Option<CostProposal> {
+        let contract = |principal: &str| match PrincipalData::parse(principal) {
+            Ok(PrincipalData::Contract(contract_id)) => Some(contract_id),
+            _ => None,
+        };
+        Some(CostProposal {
+            function_contract: contract(&self.function_contract)?,
+            function_name: ClarityName::try_from(self.function_name.clone()).ok()?,
+            cost_function_contract: contract(&self.cost_function_contract)?,
+            cost_function_name: ClarityName::try_from(self.cost_function_name.clone()).ok()?,
+        })
+    }
+}
@@ -17,6 +17,7 @@
 use std::collections::{HashMap, VecDeque};
 use std::convert::{TryFrom, TryInto};
 
+use chainstate::burn::ConsensusHash;
 use core::{
     StacksEpoch, StacksEpochId, BITCOIN_REGTEST_FIRST_BLOCK_HASH,
     BITCOIN_REGTEST_FIRST_BLOCK_HEIGHT, BITCOIN_REGTEST_FIRST_BLOCK_TIMESTAMP,
@@ -55,6 +56,7 @@ use crate::vm::types::byte_len_of_serialization;
 use core::PEER_VERSION_EPOCH_2_0;
 
 use super::key_value_wrapper::ValueResult;
+use super::lazy::{LazyValue, LazyValueResult};
 
 pub const STORE_CONTRACT_SRC_INTERFACE: bool = true;
 
@@ -95,6 +97,7 @@ pub trait HeadersDB {
     fn get_burn_block_time_for_block(&self, id_bhh: &StacksBlockId) -> Option<u64>;
     fn get_burn_block_height_for_block(&self, id_bhh: &StacksBlockId) -> Option<u32>;
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress>;
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash>;
 }
 
 pub trait BurnStateDB {
@@ -104,6 +107,11 @@ pub trait BurnStateDB {
         height: u32,
         sortition_id: &SortitionId,
     ) -> Option<BurnchainHeaderHash>;
+    fn get_burn_header_timestamp(&self, height: u32, sortition_id: &SortitionId) -> Option<u64>;
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId>;
     fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch>;
     fn get_stacks_epoch_by_epoch_id(&self, epoch_id: &StacksEpochId) -> Option<StacksEpoch>;
 }
@@ -130,6 +138,9 @@ impl HeadersDB for &dyn HeadersDB {
     fn get_miner_address(&self, bhh: &StacksBlockId) -> Option<StacksAddress> {
         (*self).get_miner_address(bhh)
     }
+    fn get_consensus_hash_for_block(&self, bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        (*self).get_consensus_hash_for_block(bhh)
+    }
 }
 
 impl BurnStateDB for &dyn BurnStateDB {
@@ -145,6 +156,17 @@ impl BurnStateDB for &dyn BurnStateDB {
         (*self).get_burn_header_hash(height, sortition_id)
     }
 
+    fn get_burn_header_timestamp(&self, height: u32, sortition_id: &SortitionId) -> Option<u64> {
+        (*self).get_burn_header_timestamp(height, sortition_id)
+    }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        (*self).get_sortition_id_from_consensus_hash(consensus_hash)
+    }
+
     fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch> {
         (*self).get_stacks_epoch(height)
     }
@@ -223,6 +245,18 @@ impl HeadersDB for NullHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        if *id_bhh
+            == StacksBlockHeader::make_index_block_hash(
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH,
+            )
+        {
+            Some(FIRST_BURNCHAIN_CONSENSUS_HASH)
+        } else {
+            None
+        }
+    }
 }
 
 impl BurnStateDB for NullBurnStateDB {
@@ -238,6 +272,17 @@ impl BurnStateDB for NullBurnStateDB {
         None
     }
 
+    fn get_burn_header_timestamp(&self, _height: u32, _sortition_id: &SortitionId) -> Option<u64> {
+        None
+    }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        _consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        None
+    }
+
     fn get_stacks_epoch(&self, _height: u32) -> Option<StacksEpoch> {
         Some(StacksEpoch {
             epoch_id: StacksEpochId::Epoch20,
@@ -329,6 +374,16 @@ impl<'a> ClarityDatabase<'a> {
         self.store.get_value(key, expected)
     }
 
+    /// Same as get_value, but leaves the value serialized until it is needed.
+    pub fn get_value_lazy(&mut self, key: &str) -> Option<LazyValueResult> {
+        self.store
+            .get::<LazyValue>(key)
+            .map(|value| LazyValueResult {
+                serialized_byte_len: value.serialized_byte_len(),
+                value,
+            })
+    }
+
     pub fn get_with_proof<T>(&mut self, key: &str) -> Option<(T, TrieMerkleProof<StacksBlockId>)>
     where
         T: ClarityDeserializable<T>,
@@ -336,6 +391,24 @@ impl<'a> ClarityDatabase<'a> {
         self.store.get_with_proof(key)
     }
 
+    /// Look up a value, along with a MARF proof of it if `with_proof` is set.  Reads that do not
+    /// need a proof skip generating one.
+    pub fn get_with_optional_proof<T>(
+        &mut self,
+        key: &str,
+        with_proof: bool,
+    ) -> Option<(T, Option<TrieMerkleProof<StacksBlockId>>)>
+    where
+        T: ClarityDeserializable<T>,
+    {
+        if with_proof {
+            self.get_with_proof(key)
+                .map(|(value, proof)| (value, Some(proof)))
+        } else {
+            self.get(key).map(|value| (value, None))
+        }
+    }
+
     pub fn make_key_for_trip(
         contract_identifier: &QualifiedContractIdentifier,
         data: StoreType,
@@ -845,6 +918,13 @@ impl<'a> ClarityDatabase<'a> {
         })
     }
 
+    pub fn make_key_for_data_var(
+        contract_identifier: &QualifiedContractIdentifier,
+        variable_name: &str,
+    ) -> String {
+        ClarityDatabase::make_key_for_trip(contract_identifier, StoreType::Variable, variable_name)
+    }
+
     pub fn lookup_variable_unknown_descriptor(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
@@ -1035,6 +1115,46 @@ impl<'a> ClarityDatabase<'a> {
         }
     }
 
+    /// Same as fetch_entry_with_size, but leaves the entry serialized until it is needed.
+    pub fn fetch_entry_lazy(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        map_name: &str,
+        key_value: &Value,
+        map_descriptor: &DataMapMetadata,
+    ) -> Result<LazyValueResult> {
+        if !map_descriptor.key_type.admits(key_value) {
+            return Err(CheckErrors::TypeValueError(
+                map_descriptor.key_type.clone(),
+                (*key_value).clone(),
+            )
+            .into());
+        }
+
+        let key_serialized = key_value.serialize();
+        let key = ClarityDatabase::make_key_for_data_map_entry_serialized(
+            contract_identifier,
+            map_name,
+            &key_serialized,
+        );
+
+        match self.get_value_lazy(&key) {
+            None => Ok(LazyValueResult {
+                value: LazyValue::from(&Value::none()),
+                serialized_byte_len: byte_len_of_serialization(&key_serialized),
+            }),
+            Some(LazyValueResult {
+                value,
+                serialized_byte_len,
+            }) => Ok(LazyValueResult {
+                value,
+                serialized_byte_len: serialized_byte_len
+                    .checked_add(byte_len_of_serialization(&key_serialized))
+                    .expect("Overflowed Clarity key/value size"),
+            }),
+        }
+    }
+
     pub fn set_entry(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
@@ -1559,6 +1679,35 @@ impl<'a> ClarityDatabase<'a> {
             .get_burn_header_hash(height, sortition_id)
     }
 
+    pub fn get_burn_header_timestamp(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<u64> {
+        self.burn_state_db
+            .get_burn_header_timestamp(height, sortition_id)
+    }
+
+    /// Get the sortition in which the parent of the block being evaluated was mined.  This
+    /// identifies the burnchain fork that the block builds on, and so which burnchain blocks
+    /// are known to it.
+    pub fn get_sortition_id_for_stacks_tip(&mut self) -> Option<SortitionId> {
+        let cur_stacks_height = self.store.get_current_block_height();
+        let last_mined_bhh = if cur_stacks_height == 0 {
+            StacksBlockHeader::make_index_block_hash(
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH,
+            )
+        } else {
+            self.get_index_block_header_hash(cur_stacks_height - 1)
+        };
+        let consensus_hash = self
+            .headers_db
+            .get_consensus_hash_for_block(&last_mined_bhh)?;
+        self.burn_state_db
+            .get_sortition_id_from_consensus_hash(&consensus_hash)
+    }
+
     /// This function obtains the stacks epoch version, which is based on the burn block height.
     /// Valid epochs include stacks 1.0, 2.0, 2.05, and so on.
     pub fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch> {
@@ -0,0 +1,186 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use codec::StacksMessageCodec;
+use util::hash::hex_bytes;
+use vm::database::ClarityDeserializable;
+use vm::types::{TupleTypeSignature, TypeSignature, Value};
+
+/// A Clarity value read from the store, left in its consensus serialization until it is
+///  needed. Reading a single field of a stored tuple through `tuple_get` only deserializes
+///  that field, rather than the whole tuple.
+///
+/// Like `Value::deserialize`, these methods panic if the stored value is malformed: values are
+///  type-checked before they are stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LazyValue {
+    serialized: Vec<u8>,
+    /// where the wrapped value starts in `serialized`
+    offset: usize,
+    /// how deeply the wrapped value is nested in the stored value
+    depth: u8,
+}
+
+/// Result structure for values fetched lazily from the underlying store.
+pub struct LazyValueResult {
+    pub value: LazyValue,
+    pub serialized_byte_len: u64,
+}
+
+impl LazyValue {
+    pub fn new(serialized: Vec<u8>) -> LazyValue {
+        LazyValue {
+            serialized,
+            offset: 0,
+            depth: 0,
+        }
+    }
+
+    /// The length of the stored value's serialization
+    pub fn serialized_byte_len(&self) -> u64 {
+        self.serialized.len() as u64
+    }
+
+    fn remaining(&self) -> &[u8] {
+        &self.serialized[self.offset..]
+    }
+
+    /// Fully deserialize the wrapped value
+    pub fn deserialize(&self, expected: &TypeSignature) -> Value {
+        Value::deserialize_read_nested(&mut self.remaining(), Some(expected), self.depth)
+            .expect("ERROR: Failed to parse Clarity hex string")
+    }
+
+    /// If the wrapped value is `(some x)`, a lazy `x`. If it is `none`, None.
+    pub fn unwrap_optional(mut self) -> Option<LazyValue> {
+        let is_some = Value::deserialize_optional_prefix_read(&mut self.remaining(), self.depth)
+            .expect("ERROR: Failed to parse Clarity hex string");
+        if is_some {
+            self.offset += 1;
+            self.depth += 1;
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    /// Deserialize the field `field_name` of the wrapped tuple, without deserializing its other
+    ///  fields. Returns None if the tuple has no such field.
+    pub fn tuple_get(&self, expected: &TupleTypeSignature, field_name: &str) -> Option<Value> {
+        Value::deserialize_tuple_field_read(&mut self.remaining(), expected, field_name, self.depth)
+            .expect("ERROR: Failed to parse Clarity hex string")
+    }
+}
+
+impl ClarityDeserializable<LazyValue> for LazyValue {
+    fn deserialize(hex: &str) -> LazyValue {
+        LazyValue::new(hex_bytes(hex).expect("ERROR: Failed to parse Clarity hex string"))
+    }
+}
+
+impl From<&Value> for LazyValue {
+    fn from(value: &Value) -> LazyValue {
+        LazyValue::new(value.serialize_to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryFrom;
+    use vm::types::{ListTypeData, PrincipalData, TupleData};
+
+    #[test]
+    fn test_lazy_tuple_get() {
+        let inner = TupleData::from_data(vec![
+            ("x".into(), Value::Int(-1)),
+            ("y".into(), Value::buff_from(vec![1, 2, 3]).unwrap()),
+        ])
+        .unwrap();
+        let contract =
+            PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5.contract").unwrap();
+        let tuple = Value::from(
+            TupleData::from_data(vec![
+                ("a".into(), Value::UInt(1)),
+                (
+                    "b".into(),
+                    Value::list_from(vec![Value::Int(1), Value::Int(2)]).unwrap(),
+                ),
+                ("c".into(), Value::from(inner)),
+                ("d".into(), Value::okay(Value::none()).unwrap()),
+                (
+                    "e".into(),
+                    Value::string_utf8_from_bytes("héllo".as_bytes().to_vec()).unwrap(),
+                ),
+                ("f".into(), Value::from(contract)),
+                ("g".into(), Value::Bool(true)),
+            ])
+            .unwrap(),
+        );
+
+        // the stored type may be wider than the stored value's own type
+        let list_type = ListTypeData::new_list(TypeSignature::IntType, 10).unwrap();
+        let expected = match TypeSignature::type_of(&tuple) {
+            TypeSignature::TupleType(tuple_type) => TupleTypeSignature::try_from(
+                tuple_type
+                    .get_type_map()
+                    .iter()
+                    .map(|(name, field_type)| match name.as_str() {
+                        "b" => (name.clone(), TypeSignature::from(list_type.clone())),
+                        _ => (name.clone(), field_type.clone()),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap(),
+            _ => panic!("Expected a tuple type"),
+        };
+
+        let stored = LazyValue::from(&tuple);
+        let full = stored
+            .deserialize(&TypeSignature::from(expected.clone()))
+            .expect_tuple();
+        for name in ["a", "b", "c", "d", "e", "f", "g"].iter() {
+            assert_eq!(
+                stored.tuple_get(&expected, name).as_ref(),
+                full.get(name).ok()
+            );
+        }
+        assert_eq!(
+            stored.tuple_get(&expected, "b"),
+            Some(Value::list_with_type(vec![Value::Int(1), Value::Int(2)], list_type).unwrap())
+        );
+        assert_eq!(stored.tuple_get(&expected, "z"), None);
+
+        let some = LazyValue::from(&Value::some(tuple.clone()).unwrap());
+        assert_eq!(
+            some.unwrap_optional()
+                .unwrap()
+                .tuple_get(&expected, "c")
+                .as_ref(),
+            full.get("c").ok()
+        );
+        assert_eq!(LazyValue::from(&Value::none()).unwrap_optional(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse Clarity hex string")]
+    fn test_lazy_tuple_get_mistyped() {
+        let tuple = Value::from(TupleData::from_data(vec![("a".into(), Value::UInt(1))]).unwrap());
+        let wrong_type =
+            TupleTypeSignature::try_from(vec![("a".into(), TypeSignature::IntType)]).unwrap();
+        LazyValue::from(&tuple).tuple_get(&wrong_type, "a");
+    }
+}
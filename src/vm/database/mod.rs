@@ -22,6 +22,7 @@ pub use self::clarity_db::{
 };
 pub use self::clarity_store::ClarityBackingStore;
 pub use self::key_value_wrapper::{RollbackWrapper, RollbackWrapperPersistedLog};
+pub use self::lazy::{LazyValue, LazyValueResult};
 pub use self::sqlite::SqliteConnection;
 pub use self::structures::{
     ClarityDeserializable, ClaritySerializable, DataMapMetadata, DataVariableMetadata,
@@ -31,5 +32,6 @@ pub use self::structures::{
 mod clarity_db;
 pub mod clarity_store;
 mod key_value_wrapper;
+mod lazy;
 mod sqlite;
 mod structures;
@@ -167,6 +167,45 @@ const TO_INT_API: SimpleFunctionAPI = SimpleFunctionAPI {
     example: "(to-int u238) ;; Returns 238"
 };
 
+const BUFF_TO_INT_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-int-be b)",
+    description: "Converts a big-endian buffer of at most 16 bytes to an `int`, reading it as a two's complement
+number. Shorter buffers are padded on the left with zero bytes, so their leading bit is never a sign bit.
+This function is not available before the epoch that enables it.",
+    example: "(buff-to-int-be 0x01ff) ;; Returns 511
+(buff-to-int-be 0xffffffffffffffffffffffffffffffff) ;; Returns -1
+(buff-to-int-be 0xff) ;; Returns 255
+(buff-to-int-be 0x) ;; Returns 0"
+};
+
+const BUFF_TO_UINT_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-uint-be b)",
+    description: "Converts a big-endian buffer of at most 16 bytes to a `uint`. Shorter buffers are padded on
+the left with zero bytes. This function is not available before the epoch that enables it.",
+    example: "(buff-to-uint-be 0x01ff) ;; Returns u511
+(buff-to-uint-be 0xffffffffffffffffffffffffffffffff) ;; Returns u340282366920938463463374607431768211455"
+};
+
+const INT_TO_BUFF_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(int-to-buff-be i)",
+    description: "Converts an `int` to its 16-byte, big-endian, two's complement representation. This function
+is not available before the epoch that enables it.",
+    example: "(int-to-buff-be 511) ;; Returns 0x000000000000000000000000000001ff
+(int-to-buff-be -1) ;; Returns 0xffffffffffffffffffffffffffffffff"
+};
+
+const UINT_TO_BUFF_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(uint-to-buff-be u)",
+    description:
+        "Converts a `uint` to its 16-byte, big-endian representation. This function is not available
+before the epoch that enables it.",
+    example: "(uint-to-buff-be u511) ;; Returns 0x000000000000000000000000000001ff",
+};
+
 const ADD_API: SimpleFunctionAPI = SimpleFunctionAPI {
     name: Some("+ (add)"),
     signature: "(+ i1 i2...)",
@@ -627,6 +666,69 @@ supplied), this function returns `none`.
 "#,
 };
 
+const STRING_SLICE_API: SpecialAPI = SpecialAPI {
+    input_type: "string-ascii|string-utf8, uint, uint",
+    output_type: "(optional string-ascii|string-utf8)",
+    signature: "(string-slice? string start end)",
+    description:
+        "The `string-slice?` function returns the characters of `string` from index `start`
+(inclusive) up to index `end` (exclusive), as a string of the same type. Indices count characters,
+not bytes. If `start` is greater than `end`, or `end` is greater than the length of `string`, the
+function returns `none`. This function is not available before the epoch that enables it.
+",
+    example: r#"
+(string-slice? "blockstack" u5 u10) ;; Returns (some "stack")
+(string-slice? u"st\u{e4}cks" u2 u4) ;; Returns (some u"\u{c3a4}c")
+(string-slice? "blockstack" u3 u3) ;; Returns (some "")
+(string-slice? "blockstack" u5 u11) ;; Returns none
+"#,
+};
+
+const STRING_INDEX_OF_API: SpecialAPI = SpecialAPI {
+    input_type: "string-ascii|string-utf8, string-ascii|string-utf8",
+    output_type: "(optional uint)",
+    signature: "(string-index-of? string substring)",
+    description: "The `string-index-of?` function returns the character index at which `substring`
+first occurs in `string`, or `none` if it does not occur. Both arguments must be of the same string
+type. An empty `substring` occurs at index `u0`. This function is not available before the epoch
+that enables it.
+",
+    example: r#"
+(string-index-of? "blockstack" "stack") ;; Returns (some u5)
+(string-index-of? u"st\u{e4}cks" u"cks") ;; Returns (some u3)
+(string-index-of? "blockstack" "") ;; Returns (some u0)
+(string-index-of? "blockstack" "stacks") ;; Returns none
+"#,
+};
+
+const TO_LOWERCASE_API: SpecialAPI = SpecialAPI {
+    input_type: "string-ascii|string-utf8",
+    output_type: "string-ascii|string-utf8",
+    signature: "(to-lowercase string)",
+    description: "The `to-lowercase` function returns `string` with each ASCII uppercase letter
+replaced by its lowercase counterpart. Every other character, including non-ASCII letters, is left
+unchanged. This function is not available before the epoch that enables it.
+",
+    example: r#"
+(to-lowercase "BlockStack") ;; Returns "blockstack"
+(to-lowercase u"ST\u{c4}CKS") ;; Returns u"st\u{c384}cks"
+"#,
+};
+
+const TO_UPPERCASE_API: SpecialAPI = SpecialAPI {
+    input_type: "string-ascii|string-utf8",
+    output_type: "string-ascii|string-utf8",
+    signature: "(to-uppercase string)",
+    description: "The `to-uppercase` function returns `string` with each ASCII lowercase letter
+replaced by its uppercase counterpart. Every other character, including non-ASCII letters, is left
+unchanged. This function is not available before the epoch that enables it.
+",
+    example: r#"
+(to-uppercase "BlockStack") ;; Returns "BLOCKSTACK"
+(to-uppercase u"st\u{e4}cks") ;; Returns u"ST\u{c3a4}CKS"
+"#,
+};
+
 const LIST_API: SpecialAPI = SpecialAPI {
     input_type: "A, ...",
     output_type: "(list A)",
@@ -1170,6 +1272,29 @@ The `id-header-hash` is the block identifier value that must be used as input to
 "
 };
 
+const GET_BURN_BLOCK_INFO_API: SpecialAPI = SpecialAPI {
+    input_type: "BurnBlockInfoPropertyName, BurnBlockHeightInt",
+    output_type: "(optional buff) | (optional uint)",
+    signature: "(get-burn-block-info? prop-name burn-block-height-expr)",
+    description: "The `get-burn-block-info?` function fetches data for the burnchain block of the given
+burnchain block height. The value and type returned are determined by the specified `BurnBlockInfoPropertyName`.
+Only the burnchain blocks in the fork that the current block builds on can be read: if the provided
+`BurnBlockHeightInt` is greater than the burnchain block height in which the current block's parent was mined,
+or lower than the first burnchain block height, the function returns `none`. The currently available property
+names are `header-hash` and `time`.
+
+The `header-hash` property returns the 32-byte burnchain block header hash.
+
+The `time` property returns the burnchain block header's timestamp. This is a Unix epoch timestamp in seconds,
+subject to the same caveats as the `time` property of `get-block-info?`.
+
+This function is not available before the epoch that enables it.
+",
+    example: "(get-burn-block-info? header-hash u677050) ;; Returns (some 0xe67141016c88a7f1203eca0b4312f2ed141531f59303a1c267d7d83ab6b977d8)
+(get-burn-block-info? time u677050) ;; Returns (some u1557860301)
+"
+};
+
 const DEFINE_TOKEN_API: DefineAPI = DefineAPI {
     input_type: "TokenName, <uint>",
     output_type: "Not Applicable",
@@ -1674,6 +1799,7 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         PrincipalOf => make_for_special(&PRINCIPAL_OF_API, name),
         AsContract => make_for_special(&AS_CONTRACT_API, name),
         GetBlockInfo => make_for_special(&GET_BLOCK_INFO_API, name),
+        GetBurnBlockInfo => make_for_special(&GET_BURN_BLOCK_INFO_API, name),
         ConsOkay => make_for_special(&CONS_OK_API, name),
         ConsError => make_for_special(&CONS_ERR_API, name),
         ConsSome => make_for_special(&CONS_SOME_API, name),
@@ -1702,6 +1828,14 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         GetStxBalance => make_for_simple_native(&STX_GET_BALANCE, &GetStxBalance, name),
         StxTransfer => make_for_simple_native(&STX_TRANSFER, &StxTransfer, name),
         StxBurn => make_for_simple_native(&STX_BURN, &StxBurn, name),
+        StringSlice => make_for_special(&STRING_SLICE_API, name),
+        StringIndexOf => make_for_special(&STRING_INDEX_OF_API, name),
+        ToLowercase => make_for_special(&TO_LOWERCASE_API, name),
+        ToUppercase => make_for_special(&TO_UPPERCASE_API, name),
+        BuffToIntBe => make_for_simple_native(&BUFF_TO_INT_BE_API, &BuffToIntBe, name),
+        BuffToUIntBe => make_for_simple_native(&BUFF_TO_UINT_BE_API, &BuffToUIntBe, name),
+        IntToBuffBe => make_for_simple_native(&INT_TO_BUFF_BE_API, &IntToBuffBe, name),
+        UIntToBuffBe => make_for_simple_native(&UINT_TO_BUFF_BE_API, &UIntToBuffBe, name),
     }
 }
 
@@ -1803,6 +1937,7 @@ mod test {
         Value,
     };
 
+    use crate::chainstate::burn::ConsensusHash;
     use crate::clarity_vm::database::marf::MarfedKV;
     use crate::types::chainstate::VRFSeed;
     use crate::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash};
@@ -1854,6 +1989,9 @@ mod test {
         fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
             None
         }
+        fn get_consensus_hash_for_block(&self, _id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+            Some(ConsensusHash([0; 20]))
+        }
     }
 
     struct DocBurnStateDB {}
@@ -1875,6 +2013,19 @@ mod test {
                 .unwrap(),
             )
         }
+        fn get_burn_header_timestamp(
+            &self,
+            _height: u32,
+            _sortition_id: &SortitionId,
+        ) -> Option<u64> {
+            Some(1557860301)
+        }
+        fn get_sortition_id_from_consensus_hash(
+            &self,
+            _consensus_hash: &ConsensusHash,
+        ) -> Option<SortitionId> {
+            Some(SortitionId([0; 32]))
+        }
         fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch> {
             Some(StacksEpoch {
                 epoch_id: StacksEpochId::Epoch20,
@@ -1925,7 +2076,8 @@ mod test {
         }
 
         let conn = store.as_clarity_db(&DOC_HEADER_DB, &DOC_POX_STATE_DB);
-        let mut contract_context = ContractContext::new(contract_id.clone());
+        let mut contract_context =
+            ContractContext::new(contract_id.clone(), StacksEpochId::Epoch2_05);
         let mut global_context = GlobalContext::new(
             false,
             conn,
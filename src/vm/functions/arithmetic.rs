@@ -15,8 +15,15 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::convert::TryFrom;
-use vm::errors::{check_argument_count, CheckErrors, InterpreterResult, RuntimeErrorType};
-use vm::types::{TypeSignature, Value};
+use vm::callables::FunctionIdentifier;
+use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::{runtime_cost, CostTracker, MemoryConsumer};
+use vm::errors::{check_argument_count, CheckErrors, Error, InterpreterResult, RuntimeErrorType};
+use vm::functions::NativeFunctions;
+use vm::representations::{SymbolicExpression, SymbolicExpressionType};
+use vm::types::{BuffData, SequenceData, TypeSignature, Value, BUFF_16};
+use vm::variables::is_reserved_name;
+use vm::{add_stack_trace, Environment, LocalContext, MAX_CALL_STACK_DEPTH};
 
 use integer_sqrt::IntegerSquareRoot;
 
@@ -301,3 +308,367 @@ pub fn native_to_int(input: Value) -> InterpreterResult<Value> {
         Err(CheckErrors::TypeValueError(TypeSignature::UIntType, input).into())
     }
 }
+
+/// The 16 big-endian bytes of a buffer of at most 16 bytes, padded on the left with zeros.
+fn buff_to_be_bytes(input: Value) -> InterpreterResult<[u8; 16]> {
+    match input {
+        Value::Sequence(SequenceData::Buffer(BuffData { ref data })) if data.len() <= 16 => {
+            let mut bytes = [0u8; 16];
+            bytes[16 - data.len()..].copy_from_slice(data);
+            Ok(bytes)
+        }
+        _ => Err(CheckErrors::TypeValueError(BUFF_16.clone(), input).into()),
+    }
+}
+
+pub fn native_buff_to_int_be(input: Value) -> InterpreterResult<Value> {
+    buff_to_be_bytes(input).map(|bytes| Value::Int(i128::from_be_bytes(bytes)))
+}
+
+pub fn native_buff_to_uint_be(input: Value) -> InterpreterResult<Value> {
+    buff_to_be_bytes(input).map(|bytes| Value::UInt(u128::from_be_bytes(bytes)))
+}
+
+pub fn native_int_to_buff_be(input: Value) -> InterpreterResult<Value> {
+    if let Value::Int(int_val) = input {
+        Value::buff_from(int_val.to_be_bytes().to_vec())
+    } else {
+        Err(CheckErrors::TypeValueError(TypeSignature::IntType, input).into())
+    }
+}
+
+pub fn native_uint_to_buff_be(input: Value) -> InterpreterResult<Value> {
+    if let Value::UInt(uint_val) = input {
+        Value::buff_from(uint_val.to_be_bytes().to_vec())
+    } else {
+        Err(CheckErrors::TypeValueError(TypeSignature::UIntType, input).into())
+    }
+}
+
+// The fast path below evaluates chains of arithmetic and comparison natives whose leaves are
+//   int or uint literals and locals on native integers, without building argument vectors or
+//   intermediate Values.  It must be indistinguishable from the general path in `eval` and
+//   `apply`: it assesses the same costs, memory and call stack depth in the same order, and
+//   fails with the same errors.  Expressions it can't type ahead of time take the general path.
+
+#[derive(Clone, Copy, PartialEq)]
+enum FastType {
+    Int,
+    UInt,
+    Bool,
+}
+
+#[derive(Clone, Copy)]
+enum FastValue {
+    Int(i128),
+    UInt(u128),
+    Bool(bool),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FastOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Geq,
+    Leq,
+    Less,
+    Greater,
+}
+
+impl FastValue {
+    fn from_value(value: &Value) -> Option<FastValue> {
+        match value {
+            Value::Int(x) => Some(FastValue::Int(*x)),
+            Value::UInt(x) => Some(FastValue::UInt(*x)),
+            Value::Bool(x) => Some(FastValue::Bool(*x)),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            FastValue::Int(x) => Value::Int(x),
+            FastValue::UInt(x) => Value::UInt(x),
+            FastValue::Bool(x) => Value::Bool(x),
+        }
+    }
+}
+
+// Applies a FastOp to two native integers of the same type, with the same
+//   overflow and division checks (and errors) as the make_arithmetic_ops functions.
+macro_rules! fast_binary_op {
+    ($op: expr, $variant: ident, $x: expr, $y: expr) => {{
+        match $op {
+            FastOp::Add => $x
+                .checked_add($y)
+                .map(FastValue::$variant)
+                .ok_or(RuntimeErrorType::ArithmeticOverflow),
+            FastOp::Sub => $x
+                .checked_sub($y)
+                .map(FastValue::$variant)
+                .ok_or(RuntimeErrorType::ArithmeticUnderflow),
+            FastOp::Mul => $x
+                .checked_mul($y)
+                .map(FastValue::$variant)
+                .ok_or(RuntimeErrorType::ArithmeticOverflow),
+            FastOp::Div => $x
+                .checked_div($y)
+                .map(FastValue::$variant)
+                .ok_or(RuntimeErrorType::DivisionByZero),
+            FastOp::Mod => $x
+                .checked_rem($y)
+                .map(FastValue::$variant)
+                .ok_or(RuntimeErrorType::DivisionByZero),
+            FastOp::Geq => Ok(FastValue::Bool($x >= $y)),
+            FastOp::Leq => Ok(FastValue::Bool($x <= $y)),
+            FastOp::Less => Ok(FastValue::Bool($x < $y)),
+            FastOp::Greater => Ok(FastValue::Bool($x > $y)),
+        }
+    }};
+}
+
+impl FastOp {
+    fn lookup(name: &str) -> Option<FastOp> {
+        match NativeFunctions::lookup_by_name(name)? {
+            NativeFunctions::Add => Some(FastOp::Add),
+            NativeFunctions::Subtract => Some(FastOp::Sub),
+            NativeFunctions::Multiply => Some(FastOp::Mul),
+            NativeFunctions::Divide => Some(FastOp::Div),
+            NativeFunctions::Modulo => Some(FastOp::Mod),
+            NativeFunctions::CmpGeq => Some(FastOp::Geq),
+            NativeFunctions::CmpLeq => Some(FastOp::Leq),
+            NativeFunctions::CmpLess => Some(FastOp::Less),
+            NativeFunctions::CmpGreater => Some(FastOp::Greater),
+            _ => None,
+        }
+    }
+
+    /// The name and cost function `lookup_reserved_functions` gives this native
+    fn native(&self) -> (&'static str, ClarityCostFunction) {
+        match self {
+            FastOp::Add => ("native_add", ClarityCostFunction::Add),
+            FastOp::Sub => ("native_sub", ClarityCostFunction::Sub),
+            FastOp::Mul => ("native_mul", ClarityCostFunction::Mul),
+            FastOp::Div => ("native_div", ClarityCostFunction::Div),
+            FastOp::Mod => ("native_mod", ClarityCostFunction::Mod),
+            FastOp::Geq => ("native_geq", ClarityCostFunction::Geq),
+            FastOp::Leq => ("native_leq", ClarityCostFunction::Leq),
+            FastOp::Less => ("native_le", ClarityCostFunction::Le),
+            FastOp::Greater => ("native_ge", ClarityCostFunction::Ge),
+        }
+    }
+
+    fn is_variadic(&self) -> bool {
+        match self {
+            FastOp::Add | FastOp::Sub | FastOp::Mul | FastOp::Div => true,
+            _ => false,
+        }
+    }
+
+    fn is_comparison(&self) -> bool {
+        match self {
+            FastOp::Geq | FastOp::Leq | FastOp::Less | FastOp::Greater => true,
+            _ => false,
+        }
+    }
+
+    fn apply_binary(
+        &self,
+        x: FastValue,
+        y: FastValue,
+    ) -> std::result::Result<FastValue, RuntimeErrorType> {
+        match (x, y) {
+            (FastValue::Int(x), FastValue::Int(y)) => fast_binary_op!(self, Int, x, y),
+            (FastValue::UInt(x), FastValue::UInt(y)) => fast_binary_op!(self, UInt, x, y),
+            _ => unreachable!("BUG: fast path applied to mistyped arguments"),
+        }
+    }
+
+    /// (- x) with a single argument
+    fn negate(x: FastValue) -> std::result::Result<FastValue, RuntimeErrorType> {
+        match x {
+            FastValue::Int(x) => x.checked_neg().map(FastValue::Int),
+            FastValue::UInt(x) => x.checked_neg().map(FastValue::UInt),
+            FastValue::Bool(_) => unreachable!("BUG: fast path applied to mistyped arguments"),
+        }
+        .ok_or(RuntimeErrorType::ArithmeticUnderflow)
+    }
+}
+
+/// The type `exp` evaluates to, if the fast path can evaluate it without erroring on its
+/// types or argument counts.  Has no side effects.
+fn fast_path_type(exp: &SymbolicExpression, context: &LocalContext) -> Option<FastType> {
+    match exp.expr {
+        SymbolicExpressionType::AtomValue(ref value)
+        | SymbolicExpressionType::LiteralValue(ref value) => match value {
+            Value::Int(_) => Some(FastType::Int),
+            Value::UInt(_) => Some(FastType::UInt),
+            _ => None,
+        },
+        SymbolicExpressionType::Atom(ref name) => {
+            if is_reserved_name(name) {
+                return None;
+            }
+            match context.lookup_variable(name)? {
+                Value::Int(_) => Some(FastType::Int),
+                Value::UInt(_) => Some(FastType::UInt),
+                _ => None,
+            }
+        }
+        SymbolicExpressionType::List(ref children) => {
+            let (function_variable, args) = children.split_first()?;
+            let op = FastOp::lookup(function_variable.match_atom()?)?;
+            fast_path_apply_type(op, args, context)
+        }
+        _ => None,
+    }
+}
+
+fn fast_path_apply_type(
+    op: FastOp,
+    args: &[SymbolicExpression],
+    context: &LocalContext,
+) -> Option<FastType> {
+    if args.len() == 0 || (!op.is_variadic() && args.len() != 2) {
+        return None;
+    }
+    let arg_type = fast_path_type(&args[0], context)?;
+    if arg_type == FastType::Bool {
+        return None;
+    }
+    for arg in args[1..].iter() {
+        if fast_path_type(arg, context)? != arg_type {
+            return None;
+        }
+    }
+    if op.is_comparison() {
+        Some(FastType::Bool)
+    } else {
+        Some(arg_type)
+    }
+}
+
+fn fast_eval(
+    exp: &SymbolicExpression,
+    env: &mut Environment,
+    context: &LocalContext,
+) -> InterpreterResult<FastValue> {
+    if let Some(ref mut coverage_tracker) = env.global_context.coverage_reporting {
+        coverage_tracker.report_eval(exp, &env.contract_context.contract_identifier);
+    }
+
+    match exp.expr {
+        SymbolicExpressionType::AtomValue(ref value)
+        | SymbolicExpressionType::LiteralValue(ref value) => {
+            Ok(FastValue::from_value(value).expect("BUG: fast path evaluated a mistyped literal"))
+        }
+        SymbolicExpressionType::Atom(ref name) => {
+            runtime_cost(
+                ClarityCostFunction::LookupVariableDepth,
+                env,
+                context.depth(),
+            )?;
+            let value = context
+                .lookup_variable(name)
+                .expect("BUG: fast path evaluated an unbound variable");
+            runtime_cost(ClarityCostFunction::LookupVariableSize, env, value.size())?;
+            Ok(FastValue::from_value(value).expect("BUG: fast path evaluated a mistyped variable"))
+        }
+        SymbolicExpressionType::List(ref children) => {
+            let (function_variable, args) = children
+                .split_first()
+                .expect("BUG: fast path evaluated an empty list");
+            if let Some(ref mut coverage_tracker) = env.global_context.coverage_reporting {
+                coverage_tracker
+                    .report_eval(function_variable, &env.contract_context.contract_identifier);
+            }
+            let op = function_variable
+                .match_atom()
+                .and_then(|name| FastOp::lookup(name))
+                .expect("BUG: fast path evaluated a non-arithmetic function");
+            fast_apply(op, args, env, context)
+        }
+        _ => unreachable!("BUG: fast path evaluated a non-arithmetic expression"),
+    }
+}
+
+/// The fast path's equivalent of `lookup_function` followed by `apply`
+fn fast_apply(
+    op: FastOp,
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> InterpreterResult<FastValue> {
+    let (native_name, cost_function) = op.native();
+    runtime_cost(ClarityCostFunction::LookupFunction, env, 0)?;
+
+    if env.call_stack.depth() >= MAX_CALL_STACK_DEPTH {
+        return Err(RuntimeErrorType::MaxStackDepthReached.into());
+    }
+
+    let mut used_memory = 0;
+    let mut result: Option<std::result::Result<FastValue, RuntimeErrorType>> = None;
+    env.call_stack.incr_apply_depth();
+    for arg_x in args.iter() {
+        let arg_value = match fast_eval(arg_x, env, context) {
+            Ok(x) => x,
+            Err(e) => {
+                env.drop_memory(used_memory);
+                env.call_stack.decr_apply_depth();
+                return Err(e);
+            }
+        };
+        let arg_use = arg_value.into_value().get_memory_use();
+        if let Err(e) = env.add_memory(arg_use) {
+            env.drop_memory(used_memory);
+            env.call_stack.decr_apply_depth();
+            return Err(Error::from(e));
+        }
+        used_memory += arg_use;
+        // like the natives, stop at the first failure, but still evaluate every argument
+        result = match result {
+            None => Some(Ok(arg_value)),
+            Some(Ok(acc)) => Some(op.apply_binary(acc, arg_value)),
+            failed => failed,
+        };
+    }
+    env.call_stack.decr_apply_depth();
+
+    let mut resp = runtime_cost(cost_function, env, args.len())
+        .map_err(Error::from)
+        .and_then(|_| {
+            let result = result.expect("BUG: fast path applied to no arguments");
+            if op == FastOp::Sub && args.len() == 1 {
+                result.and_then(FastOp::negate)
+            } else {
+                result
+            }
+            .map_err(Error::from)
+        });
+    if resp.is_err() {
+        // only failures need the native on the call stack, for their stack trace
+        let identifier = FunctionIdentifier::new_native_function(native_name);
+        env.call_stack.insert(&identifier, false);
+        add_stack_trace(&mut resp, env);
+        env.call_stack.remove(&identifier, false)?;
+    }
+    env.drop_memory(used_memory);
+    resp
+}
+
+/// Evaluate the application of `function_name` to `args` on native integers, if it is an
+/// arithmetic or comparison native whose arguments are (chains of such natives on) int or uint
+/// literals and locals of matching types.  Returns None, having done nothing, otherwise.
+pub fn eval_arithmetic_fast_path(
+    function_name: &str,
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Option<InterpreterResult<Value>> {
+    let op = FastOp::lookup(function_name)?;
+    fast_path_apply_type(op, args, context)?;
+    Some(fast_apply(op, args, env, context).map(FastValue::into_value))
+}
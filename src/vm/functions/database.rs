@@ -21,20 +21,23 @@ use vm::functions::tuples;
 
 use core::StacksEpochId;
 use types::chainstate::StacksBlockId;
-use vm::callables::DefineType;
+use vm::callables::{DefineType, FunctionIdentifier};
 use vm::costs::{
     constants as cost_constants, cost_functions, runtime_cost, CostTracker, MemoryConsumer,
 };
+use vm::database::{DataMapMetadata, LazyValue};
 use vm::errors::{
     check_argument_count, check_arguments_at_least, CheckErrors, InterpreterError,
     InterpreterResult as Result, RuntimeErrorType,
 };
+use vm::functions::NativeFunctions;
 use vm::representations::{SymbolicExpression, SymbolicExpressionType};
+use vm::types::signatures::is_subtrait;
 use vm::types::{
-    BlockInfoProperty, BuffData, OptionalData, PrincipalData, SequenceData, TypeSignature, Value,
-    BUFF_32,
+    BlockInfoProperty, BuffData, BurnBlockInfoProperty, OptionalData, PrincipalData, SequenceData,
+    TupleTypeSignature, TypeSignature, Value, BUFF_32,
 };
-use vm::{eval, Environment, LocalContext};
+use vm::{add_stack_trace, eval, Environment, LocalContext, MAX_CALL_STACK_DEPTH};
 
 use vm::costs::cost_functions::ClarityCostFunction;
 use vm::functions::special::handle_contract_call_special_cases;
@@ -157,10 +160,50 @@ pub fn special_contract_call(
                             .into());
                         }
 
-                        function_to_check.check_trait_expectations(
+                        let allow_subtyping = env.epoch().supports_trait_subtyping();
+                        let database = &mut env.global_context.database;
+                        let cost_track = &mut env.global_context.cost_track;
+                        let mut error = None;
+                        let result = function_to_check.check_trait_expectations(
                             &contract_context_defining_trait,
                             &trait_identifier,
-                        )?;
+                            &mut |sub_trait, super_trait| {
+                                if !allow_subtyping {
+                                    return sub_trait == super_trait;
+                                }
+                                // every trait definition loaded is charged like a contract load
+                                let result: Result<bool> =
+                                    is_subtrait(sub_trait, super_trait, &mut |trait_id| {
+                                        let contract_id = &trait_id.contract_identifier;
+                                        if !database.has_contract(contract_id) {
+                                            return Ok(None);
+                                        }
+                                        let contract_size =
+                                            database.get_contract_size(contract_id)?;
+                                        runtime_cost(
+                                            ClarityCostFunction::LoadContract,
+                                            cost_track,
+                                            contract_size,
+                                        )?;
+                                        Ok(database.load_contract_analysis(contract_id).and_then(
+                                            |analysis| {
+                                                analysis.get_defined_trait(&trait_id.name).cloned()
+                                            },
+                                        ))
+                                    });
+                                match result {
+                                    Ok(admitted) => admitted,
+                                    Err(e) => {
+                                        error = Some(e);
+                                        false
+                                    }
+                                }
+                            },
+                        );
+                        if let Some(e) = error {
+                            return Err(e);
+                        }
+                        result?;
 
                         // Retrieve the expected method signature
                         let constraining_trait = contract_context_defining_trait
@@ -178,9 +221,10 @@ pub fn special_contract_call(
         _ => return Err(CheckErrors::ContractCallExpectName.into()),
     };
 
-    let contract_principal = env.contract_context.contract_identifier.clone().into();
+    let caller = env.contract_context.contract_identifier.clone();
+    let runtime_before = env.global_context.cost_track.get_total().runtime;
 
-    let mut nested_env = env.nest_with_caller(contract_principal);
+    let mut nested_env = env.nest_with_caller(caller.clone().into());
     let result = if nested_env.short_circuit_contract_call(
         &contract_identifier,
         function_name,
@@ -191,7 +235,19 @@ pub fn special_contract_call(
         })
     } else {
         nested_env.execute_contract(&contract_identifier, function_name, &rest_args, false)
-    }?;
+    };
+
+    let runtime = nested_env
+        .global_context
+        .cost_track
+        .get_total()
+        .runtime
+        .saturating_sub(runtime_before);
+    nested_env
+        .global_context
+        .cost_track
+        .record_contract_call_cost(&caller, &contract_identifier, function_name, runtime);
+    let result = result?;
 
     // Ensure that the expected type from the trait spec admits
     // the type of the value returned by the dynamic dispatch.
@@ -414,6 +470,158 @@ pub fn special_fetch_entry_v205(
     result.map(|data| data.value)
 }
 
+// The fast path below evaluates `(get field (map-get? map key))` on a map of tuples by
+//   deserializing only `field` of the stored entry, rather than the whole tuple.  It must be
+//   indistinguishable from the general path in `eval`, `apply`, `tuple_get` and
+//   `special_fetch_entry`: it assesses the same costs in the same order, tracks the same call
+//   stack, and fails with the same errors.  Applications it can't check ahead of time take the
+//   general path.
+
+/// The body of `special_fetch_entry`, leaving the fetched entry serialized
+fn special_fetch_entry_lazy(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<LazyValue> {
+    check_argument_count(2, args)?;
+
+    let map_name = args[0].match_atom().ok_or(CheckErrors::ExpectedName)?;
+
+    let key = eval(&args[1], env, &context)?;
+
+    let contract = &env.contract_context.contract_identifier;
+
+    let data_types = env
+        .contract_context
+        .meta_data_map
+        .get(map_name)
+        .ok_or(CheckErrors::NoSuchMap(map_name.to_string()))?;
+
+    if env.epoch() >= &StacksEpochId::Epoch2_05 {
+        let result = env
+            .global_context
+            .database
+            .fetch_entry_lazy(contract, map_name, &key, data_types);
+
+        let result_size = match &result {
+            Ok(data) => data.serialized_byte_len,
+            Err(_e) => (data_types.value_type.size() + data_types.key_type.size()) as u64,
+        };
+
+        runtime_cost(ClarityCostFunction::FetchEntry, env, result_size)?;
+
+        result.map(|data| data.value)
+    } else {
+        runtime_cost(
+            ClarityCostFunction::FetchEntry,
+            env,
+            data_types.value_type.size() + data_types.key_type.size(),
+        )?;
+
+        env.global_context
+            .database
+            .fetch_entry_lazy(contract, map_name, &key, data_types)
+            .map(|data| data.value)
+    }
+}
+
+/// The fast path's equivalent of `eval` on a `map-get?`
+fn eval_fetch_entry_lazy(
+    exp: &SymbolicExpression,
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<LazyValue> {
+    let children = exp
+        .match_list()
+        .expect("BUG: fast path evaluated a non-application");
+
+    if let Some(ref mut coverage_tracker) = env.global_context.coverage_reporting {
+        coverage_tracker.report_eval(exp, &env.contract_context.contract_identifier);
+        coverage_tracker.report_eval(&children[0], &env.contract_context.contract_identifier);
+    }
+
+    runtime_cost(ClarityCostFunction::LookupFunction, env, 0)?;
+
+    let identifier = FunctionIdentifier::new_native_function("special_map-get?");
+    env.call_stack.insert(&identifier, false);
+    let mut resp = special_fetch_entry_lazy(&children[1..], env, context);
+    add_stack_trace(&mut resp, env);
+    env.call_stack.remove(&identifier, false)?;
+    resp
+}
+
+/// The fast path's equivalent of `lookup_function` followed by `apply` on a `get`
+fn apply_tuple_get_lazy(
+    field_name: &str,
+    tuple_type: &TupleTypeSignature,
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    runtime_cost(ClarityCostFunction::LookupFunction, env, 0)?;
+
+    let identifier = FunctionIdentifier::new_native_function("special_get-tuple");
+    env.call_stack.insert(&identifier, false);
+    let mut resp = eval_fetch_entry_lazy(&args[1], env, context).and_then(|entry| {
+        match entry.unwrap_optional() {
+            Some(tuple) => {
+                runtime_cost(ClarityCostFunction::TupleGet, env, tuple_type.len())?;
+                let field = tuple
+                    .tuple_get(tuple_type, field_name)
+                    .expect("BUG: fast path fetched a field missing from its tuple type");
+                Ok(Value::some(field)
+                    .expect("Tuple contents should *always* fit in a some wrapper"))
+            }
+            None => Ok(Value::none()),
+        }
+    });
+    add_stack_trace(&mut resp, env);
+    env.call_stack.remove(&identifier, false)?;
+    resp
+}
+
+/// Evaluate the application of `function_name` to `args` by deserializing only the field it
+/// reads, if it is a `get` of a field of a `map-get?` on a map of tuples with that field.
+/// Returns None, having done nothing, otherwise.
+pub fn eval_tuple_get_fast_path(
+    function_name: &str,
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Option<Result<Value>> {
+    if NativeFunctions::lookup_by_name(function_name)? != NativeFunctions::TupleGet
+        || args.len() != 2
+    {
+        return None;
+    }
+    let field_name = args[0].match_atom()?;
+
+    let fetch = args[1].match_list()?;
+    if NativeFunctions::lookup_by_name(fetch.first()?.match_atom()?)? != NativeFunctions::FetchEntry
+        || fetch.len() != 3
+    {
+        return None;
+    }
+    let contract_context = env.contract_context;
+    let tuple_type = match contract_context.meta_data_map.get(fetch[1].match_atom()?)? {
+        DataMapMetadata {
+            value_type: TypeSignature::TupleType(tuple_type),
+            ..
+        } => tuple_type,
+        _ => return None,
+    };
+    tuple_type.field_type(field_name)?;
+
+    // leave running out of call stack to the general path
+    if env.call_stack.depth() + 1 >= MAX_CALL_STACK_DEPTH {
+        return None;
+    }
+
+    Some(apply_tuple_get_lazy(
+        field_name, tuple_type, args, env, context,
+    ))
+}
+
 pub fn special_at_block(
     args: &[SymbolicExpression],
     env: &mut Environment,
@@ -769,3 +977,67 @@ pub fn special_get_block_info(
 
     Ok(Value::some(result)?)
 }
+
+pub fn special_get_burn_block_info(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (get-burn-block-info? property-name burn-block-height-int)
+    runtime_cost(ClarityCostFunction::BurnBlockInfo, env, 0)?;
+
+    check_argument_count(2, args)?;
+
+    // Handle the block property name input arg.
+    let property_name = args[0]
+        .match_atom()
+        .ok_or(CheckErrors::GetBurnBlockInfoExpectPropertyName)?;
+
+    let burn_block_info_prop = BurnBlockInfoProperty::lookup_by_name(property_name)
+        .ok_or(CheckErrors::GetBurnBlockInfoExpectPropertyName)?;
+
+    // Handle the burn-block-height input arg clause.
+    let height_eval = eval(&args[1], env, context)?;
+    let height_value = match height_eval {
+        Value::UInt(result) => Ok(result),
+        x => Err(CheckErrors::TypeValueError(TypeSignature::UIntType, x)),
+    }?;
+
+    let height_value = match u32::try_from(height_value) {
+        Ok(result) => result,
+        _ => return Ok(Value::none()),
+    };
+
+    // only the burnchain blocks in the fork that this block builds on are visible, which are
+    //  those up to and including the burnchain block its parent was mined in.
+    let sortition_id = match env
+        .global_context
+        .database
+        .get_sortition_id_for_stacks_tip()
+    {
+        Some(sortition_id) => sortition_id,
+        None => return Ok(Value::none()),
+    };
+
+    let result = match burn_block_info_prop {
+        BurnBlockInfoProperty::HeaderHash => env
+            .global_context
+            .database
+            .get_burn_header_hash(height_value, &sortition_id)
+            .map(|burnchain_header_hash| {
+                Value::Sequence(SequenceData::Buffer(BuffData {
+                    data: burnchain_header_hash.as_bytes().to_vec(),
+                }))
+            }),
+        BurnBlockInfoProperty::Time => env
+            .global_context
+            .database
+            .get_burn_header_timestamp(height_value, &sortition_id)
+            .map(|timestamp| Value::UInt(timestamp as u128)),
+    };
+
+    match result {
+        Some(result) => Ok(Value::some(result)?),
+        None => Ok(Value::none()),
+    }
+}
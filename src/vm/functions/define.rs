@@ -16,7 +16,7 @@
 
 use std::collections::{BTreeMap, HashMap};
 use vm::callables::{DefineType, DefinedFunction};
-use vm::contexts::{ContractContext, Environment, LocalContext};
+use vm::contexts::{Environment, LocalContext};
 use vm::errors::{
     check_argument_count, check_arguments_at_least, CheckErrors, InterpreterResult as Result,
     RuntimeErrorType,
@@ -108,8 +108,8 @@ pub enum DefineResult {
     NoDefine,
 }
 
-fn check_legal_define(name: &str, contract_context: &ContractContext) -> Result<()> {
-    if contract_context.is_name_used(name) {
+fn check_legal_define(name: &str, env: &Environment) -> Result<()> {
+    if env.contract_context.is_name_used(name) {
         Err(CheckErrors::NameAlreadyUsed(name.to_string()).into())
     } else {
         Ok(())
@@ -122,7 +122,7 @@ fn handle_define_variable(
     env: &mut Environment,
 ) -> Result<DefineResult> {
     // is the variable name legal?
-    check_legal_define(variable, &env.contract_context)?;
+    check_legal_define(variable, env)?;
     let context = LocalContext::new();
     let value = eval(expression, env, &context)?;
     Ok(DefineResult::Variable(variable.clone(), value))
@@ -142,12 +142,12 @@ fn handle_define_function(
         .match_atom()
         .ok_or(CheckErrors::ExpectedName)?;
 
-    check_legal_define(&function_name, &env.contract_context)?;
+    check_legal_define(&function_name, env)?;
 
     let arguments = parse_name_type_pairs(arg_symbols, env)?;
 
     for (argument, _) in arguments.iter() {
-        check_legal_define(argument, &env.contract_context)?;
+        check_legal_define(argument, env)?;
     }
 
     let function = DefinedFunction::new(
@@ -167,7 +167,7 @@ fn handle_define_persisted_variable(
     value: &SymbolicExpression,
     env: &mut Environment,
 ) -> Result<DefineResult> {
-    check_legal_define(&variable_str, &env.contract_context)?;
+    check_legal_define(&variable_str, env)?;
 
     let value_type_signature = TypeSignature::parse_type_repr(value_type, env)?;
 
@@ -186,7 +186,7 @@ fn handle_define_nonfungible_asset(
     key_type: &SymbolicExpression,
     env: &mut Environment,
 ) -> Result<DefineResult> {
-    check_legal_define(&asset_name, &env.contract_context)?;
+    check_legal_define(&asset_name, env)?;
 
     let key_type_signature = TypeSignature::parse_type_repr(key_type, env)?;
 
@@ -201,7 +201,7 @@ fn handle_define_fungible_token(
     total_supply: Option<&SymbolicExpression>,
     env: &mut Environment,
 ) -> Result<DefineResult> {
-    check_legal_define(&asset_name, &env.contract_context)?;
+    check_legal_define(&asset_name, env)?;
 
     if let Some(total_supply_expr) = total_supply {
         let context = LocalContext::new();
@@ -225,7 +225,7 @@ fn handle_define_map(
     value_type: &SymbolicExpression,
     env: &mut Environment,
 ) -> Result<DefineResult> {
-    check_legal_define(&map_str, &env.contract_context)?;
+    check_legal_define(&map_str, env)?;
 
     let key_type_signature = TypeSignature::parse_type_repr(key_type, env)?;
     let value_type_signature = TypeSignature::parse_type_repr(value_type, env)?;
@@ -242,7 +242,7 @@ fn handle_define_trait(
     functions: &[SymbolicExpression],
     env: &mut Environment,
 ) -> Result<DefineResult> {
-    check_legal_define(&name, &env.contract_context)?;
+    check_legal_define(&name, env)?;
 
     let trait_signature = TypeSignature::parse_trait_type_repr(&functions, env)?;
 
@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use address::AddressHashMode;
+use core::StacksEpochId;
 use util::hash;
 use vm::callables::{CallableType, NativeHandle};
 use vm::costs::cost_functions::ClarityCostFunction;
@@ -25,7 +26,9 @@ use vm::errors::{
     check_argument_count, check_arguments_at_least, CheckErrors, Error,
     InterpreterResult as Result, RuntimeErrorType, ShortReturnType,
 };
+pub use vm::functions::arithmetic::eval_arithmetic_fast_path;
 pub use vm::functions::assets::stx_transfer_consolidated;
+pub use vm::functions::database::eval_tuple_get_fast_path;
 pub use vm::functions::special::handle_contract_call_special_cases;
 use vm::is_reserved;
 use vm::representations::SymbolicExpressionType::{Atom, List};
@@ -151,8 +154,46 @@ define_named_enum!(NativeFunctions {
     GetStxBalance("stx-get-balance"),
     StxTransfer("stx-transfer?"),
     StxBurn("stx-burn?"),
+    StringSlice("string-slice?"),
+    StringIndexOf("string-index-of?"),
+    ToLowercase("to-lowercase"),
+    ToUppercase("to-uppercase"),
+    BuffToIntBe("buff-to-int-be"),
+    BuffToUIntBe("buff-to-uint-be"),
+    IntToBuffBe("int-to-buff-be"),
+    UIntToBuffBe("uint-to-buff-be"),
+    GetBurnBlockInfo("get-burn-block-info?"),
 });
 
+impl NativeFunctions {
+    /// Whether code evaluated in `epoch` may call this native.  Natives introduced after a
+    /// chain's launch are not reserved before their epoch, so until then their names may be
+    /// used by contracts for their own definitions.
+    pub fn is_available_in(&self, epoch: &StacksEpochId) -> bool {
+        use vm::functions::NativeFunctions::*;
+        match self {
+            StringSlice | StringIndexOf | ToLowercase | ToUppercase => {
+                epoch.supports_string_natives()
+            }
+            BuffToIntBe | BuffToUIntBe | IntToBuffBe | UIntToBuffBe => {
+                epoch.supports_buffer_int_natives()
+            }
+            GetBurnBlockInfo => epoch.supports_burn_block_info(),
+            _ => true,
+        }
+    }
+
+    /// Look up the native named `name`, treating natives that are not available in `epoch` as
+    /// undefined.  Without an epoch (e.g., in analyses charged to a free cost tracker), every
+    /// native is available.
+    pub fn lookup_by_name_at_epoch(name: &str, epoch: Option<&StacksEpochId>) -> Option<Self> {
+        NativeFunctions::lookup_by_name(name).filter(|native_function| match epoch {
+            Some(epoch) => native_function.is_available_in(epoch),
+            None => true,
+        })
+    }
+}
+
 pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
     use vm::callables::CallableType::{NativeFunction, NativeFunction205, SpecialFunction};
     use vm::functions::NativeFunctions::*;
@@ -208,6 +249,30 @@ pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
                 NativeHandle::SingleArg(&arithmetic::native_to_int),
                 ClarityCostFunction::IntCast,
             ),
+            BuffToIntBe => NativeFunction205(
+                "native_buff_to_int_be",
+                NativeHandle::SingleArg(&arithmetic::native_buff_to_int_be),
+                ClarityCostFunction::BuffToIntBe,
+                &cost_input_sized_vararg,
+            ),
+            BuffToUIntBe => NativeFunction205(
+                "native_buff_to_uint_be",
+                NativeHandle::SingleArg(&arithmetic::native_buff_to_uint_be),
+                ClarityCostFunction::BuffToUIntBe,
+                &cost_input_sized_vararg,
+            ),
+            IntToBuffBe => NativeFunction205(
+                "native_int_to_buff_be",
+                NativeHandle::SingleArg(&arithmetic::native_int_to_buff_be),
+                ClarityCostFunction::IntToBuffBe,
+                &cost_input_sized_vararg,
+            ),
+            UIntToBuffBe => NativeFunction205(
+                "native_uint_to_buff_be",
+                NativeHandle::SingleArg(&arithmetic::native_uint_to_buff_be),
+                ClarityCostFunction::UIntToBuffBe,
+                &cost_input_sized_vararg,
+            ),
             Modulo => NativeFunction(
                 "native_mod",
                 NativeHandle::DoubleArg(&arithmetic::native_mod),
@@ -273,6 +338,30 @@ pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
                 &cost_input_sized_vararg,
             ),
             ListCons => SpecialFunction("special_list_cons", &sequences::list_cons),
+            StringSlice => NativeFunction205(
+                "native_string_slice",
+                NativeHandle::MoreArg(&sequences::native_string_slice),
+                ClarityCostFunction::StringSlice,
+                &cost_input_sized_vararg,
+            ),
+            StringIndexOf => NativeFunction205(
+                "native_string_index_of",
+                NativeHandle::DoubleArg(&sequences::native_string_index_of),
+                ClarityCostFunction::StringIndexOf,
+                &cost_input_sized_vararg,
+            ),
+            ToLowercase => NativeFunction205(
+                "native_to_lowercase",
+                NativeHandle::SingleArg(&sequences::native_to_lowercase),
+                ClarityCostFunction::ToLowercase,
+                &cost_input_sized_vararg,
+            ),
+            ToUppercase => NativeFunction205(
+                "native_to_uppercase",
+                NativeHandle::SingleArg(&sequences::native_to_uppercase),
+                ClarityCostFunction::ToUppercase,
+                &cost_input_sized_vararg,
+            ),
             FetchEntry => SpecialFunction("special_map-get?", &database::special_fetch_entry),
             SetEntry => SpecialFunction("special_set-entry", &database::special_set_entry),
             InsertEntry => SpecialFunction("special_insert-entry", &database::special_insert_entry),
@@ -337,6 +426,10 @@ pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
             GetBlockInfo => {
                 SpecialFunction("special_get_block_info", &database::special_get_block_info)
             }
+            GetBurnBlockInfo => SpecialFunction(
+                "special_get_burn_block_info",
+                &database::special_get_burn_block_info,
+            ),
             ConsSome => NativeFunction(
                 "native_some",
                 NativeHandle::SingleArg(&options::native_some),
@@ -577,13 +670,13 @@ fn special_let(
     runtime_cost(ClarityCostFunction::Let, env, bindings.len())?;
 
     // create a new context.
-    let mut inner_context = context.extend()?;
+    let mut inner_context = context.extend_pooled(&mut env.global_context.local_context_pool)?;
 
     let mut memory_use = 0;
 
-    finally_drop_memory!( env, memory_use; {
+    let result = finally_drop_memory!( env, memory_use; {
         handle_binding_list::<_, Error>(bindings, |binding_name, var_sexp| {
-            if is_reserved(binding_name) ||
+            if is_reserved(binding_name, env.contract_context.get_epoch()) ||
                 env.contract_context.lookup_function(binding_name).is_some() ||
                 inner_context.lookup_variable(binding_name).is_some() {
                     return Err(CheckErrors::NameAlreadyUsed(binding_name.clone().into()).into())
@@ -606,7 +699,10 @@ fn special_let(
         }
         // last_result should always be Some(...), because of the arg len check above.
         Ok(last_result.unwrap())
-    })
+    });
+
+    inner_context.release(&mut env.global_context.local_context_pool);
+    result
 }
 
 fn special_as_contract(
@@ -113,11 +113,12 @@ fn eval_with_new_binding(
     env: &mut Environment,
     context: &LocalContext,
 ) -> Result<Value> {
-    let mut inner_context = context.extend()?;
-    if vm::is_reserved(&bind_name)
+    let mut inner_context = context.extend_pooled(&mut env.global_context.local_context_pool)?;
+    if vm::is_reserved(&bind_name, env.contract_context.get_epoch())
         || env.contract_context.lookup_function(&bind_name).is_some()
         || inner_context.lookup_variable(&bind_name).is_some()
     {
+        inner_context.release(&mut env.global_context.local_context_pool);
         return Err(CheckErrors::NameAlreadyUsed(bind_name.into()).into());
     }
 
@@ -128,6 +129,7 @@ fn eval_with_new_binding(
     let result = vm::eval(body, env, &inner_context);
 
     env.drop_memory(memory_use);
+    inner_context.release(&mut env.global_context.local_context_pool);
 
     result
 }
@@ -17,6 +17,7 @@
 use core::StacksEpochId;
 use std::cmp;
 use std::convert::{TryFrom, TryInto};
+use std::str;
 use vm::costs::cost_functions::ClarityCostFunction;
 use vm::costs::{cost_functions, runtime_cost, CostOverflowingMath};
 use vm::errors::{
@@ -343,3 +344,123 @@ pub fn native_element_at(sequence: Value, index: Value) -> Result<Value> {
         Ok(Value::none())
     }
 }
+
+fn expect_string(value: Value) -> Result<CharType> {
+    match value {
+        Value::Sequence(SequenceData::String(char_data)) => Ok(char_data),
+        _ => Err(CheckErrors::UnionTypeValueError(
+            vec![
+                TypeSignature::min_string_ascii(),
+                TypeSignature::min_string_utf8(),
+            ],
+            value,
+        )
+        .into()),
+    }
+}
+
+pub fn native_string_slice(mut args: Vec<Value>) -> Result<Value> {
+    check_argument_count(3, &args)?;
+    let end = args.pop().unwrap();
+    let start = args.pop().unwrap();
+    let char_data = expect_string(args.pop().unwrap())?;
+
+    let (start, end) = match (start, end) {
+        (Value::UInt(start), Value::UInt(end)) => (start, end),
+        (Value::UInt(_), end) => {
+            return Err(CheckErrors::TypeValueError(TypeSignature::UIntType, end).into())
+        }
+        (start, _) => {
+            return Err(CheckErrors::TypeValueError(TypeSignature::UIntType, start).into())
+        }
+    };
+
+    let len = match char_data {
+        CharType::ASCII(ref data) => data.data.len(),
+        CharType::UTF8(ref data) => data.data.len(),
+    };
+    if start > end || end > len as u128 {
+        return Ok(Value::none());
+    }
+    // both bounds are at most `len`, so they fit in a usize.
+    let (start, end) = (start as usize, end as usize);
+
+    let sliced = match char_data {
+        CharType::ASCII(mut data) => {
+            data.data.truncate(end);
+            data.data.drain(..start);
+            CharType::ASCII(data)
+        }
+        CharType::UTF8(mut data) => {
+            data.data.truncate(end);
+            data.data.drain(..start);
+            CharType::UTF8(data)
+        }
+    };
+    Value::some(Value::Sequence(SequenceData::String(sliced)))
+}
+
+/// The character index of the first occurrence of `needle` in `haystack`, both given as UTF-8.
+/// An encoded character never starts in the middle of another one, so a byte-wise match is
+/// always a match of whole characters.
+fn find_string(haystack: &[u8], needle: &[u8]) -> Result<Option<usize>> {
+    let haystack = str::from_utf8(haystack).map_err(|_| CheckErrors::InvalidCharactersDetected)?;
+    let needle = str::from_utf8(needle).map_err(|_| CheckErrors::InvalidCharactersDetected)?;
+    Ok(haystack
+        .find(needle)
+        .map(|offset| haystack[..offset].chars().count()))
+}
+
+pub fn native_string_index_of(haystack: Value, needle: Value) -> Result<Value> {
+    let found = match (expect_string(haystack)?, expect_string(needle)?) {
+        (CharType::ASCII(haystack), CharType::ASCII(needle)) => {
+            find_string(&haystack.data, &needle.data)?
+        }
+        (CharType::UTF8(haystack), CharType::UTF8(needle)) => {
+            find_string(&haystack.data.concat(), &needle.data.concat())?
+        }
+        (haystack, needle) => {
+            let haystack = Value::Sequence(SequenceData::String(haystack));
+            let needle = Value::Sequence(SequenceData::String(needle));
+            return Err(
+                CheckErrors::TypeValueError(TypeSignature::type_of(&haystack), needle).into(),
+            );
+        }
+    };
+    match found {
+        Some(index) => Value::some(Value::UInt(index as u128)),
+        None => Ok(Value::none()),
+    }
+}
+
+/// Case conversion only maps the ASCII letters: Unicode's case tables change between
+/// versions, and some of its mappings change a string's length.
+fn map_ascii_case(value: Value, to_upper: bool) -> Result<Value> {
+    let convert = |bytes: &mut Vec<u8>| {
+        if to_upper {
+            bytes.make_ascii_uppercase()
+        } else {
+            bytes.make_ascii_lowercase()
+        }
+    };
+    let converted = match expect_string(value)? {
+        CharType::ASCII(mut data) => {
+            convert(&mut data.data);
+            CharType::ASCII(data)
+        }
+        CharType::UTF8(mut data) => {
+            // the bytes of a multi-byte character are all non-ASCII, so they are left as-is.
+            data.data.iter_mut().for_each(convert);
+            CharType::UTF8(data)
+        }
+    };
+    Ok(Value::Sequence(SequenceData::String(converted)))
+}
+
+pub fn native_to_lowercase(value: Value) -> Result<Value> {
+    map_ascii_case(value, false)
+}
+
+pub fn native_to_uppercase(value: Value) -> Result<Value> {
+    map_ascii_case(value, true)
+}
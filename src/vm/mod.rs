@@ -56,6 +56,7 @@ use vm::errors::{
     CheckErrors, Error, InterpreterError, InterpreterResult as Result, RuntimeErrorType,
 };
 use vm::functions::define::DefineResult;
+use vm::functions::NativeFunctions;
 pub use vm::types::Value;
 use vm::types::{PrincipalData, QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
 
@@ -108,6 +109,16 @@ pub fn lookup_function(name: &str, env: &mut Environment) -> Result<CallableType
     runtime_cost(ClarityCostFunction::LookupFunction, env, 0)?;
 
     if let Some(result) = functions::lookup_reserved_functions(name) {
+        // a native that was not yet available when the contract was deployed may be shadowed by
+        //  a function the contract defined under its name.
+        let is_available =
+            NativeFunctions::lookup_by_name_at_epoch(name, Some(env.contract_context.get_epoch()))
+                .is_some();
+        if !is_available {
+            if let Some(user_function) = env.contract_context.lookup_function(name) {
+                return Ok(CallableType::UserFunction(user_function));
+            }
+        }
         Ok(result)
     } else {
         let user_function = env
@@ -118,7 +129,7 @@ pub fn lookup_function(name: &str, env: &mut Environment) -> Result<CallableType
     }
 }
 
-fn add_stack_trace(result: &mut Result<Value>, env: &Environment) {
+fn add_stack_trace<T>(result: &mut Result<T>, env: &Environment) {
     if let Err(Error::Runtime(_, ref mut stack_trace)) = result {
         if stack_trace.is_none() {
             stack_trace.replace(env.call_stack.make_stack_trace());
@@ -241,6 +252,16 @@ pub fn eval<'a>(
             let function_name = function_variable
                 .match_atom()
                 .ok_or(CheckErrors::BadFunctionName)?;
+            if let Some(result) =
+                functions::eval_arithmetic_fast_path(function_name, rest, env, context)
+            {
+                return result;
+            }
+            if let Some(result) =
+                functions::eval_tuple_get_fast_path(function_name, rest, env, context)
+            {
+                return result;
+            }
             let f = lookup_function(&function_name, env)?;
             apply(&f, &rest, env, context)
         }
@@ -248,8 +269,9 @@ pub fn eval<'a>(
     }
 }
 
-pub fn is_reserved(name: &str) -> bool {
-    if let Some(_result) = functions::lookup_reserved_functions(name) {
+/// Whether `name` may not be used for a definition or binding in code evaluated in `epoch`.
+pub fn is_reserved(name: &str, epoch: &StacksEpochId) -> bool {
+    if let Some(_result) = NativeFunctions::lookup_by_name_at_epoch(name, Some(epoch)) {
         true
     } else if variables::is_reserved_name(name) {
         true
@@ -401,7 +423,7 @@ pub fn execute_in_epoch(
     use_mainnet: bool,
 ) -> Result<Option<Value>> {
     let contract_id = QualifiedContractIdentifier::transient();
-    let mut contract_context = ContractContext::new(contract_id.clone());
+    let mut contract_context = ContractContext::new(contract_id.clone(), epoch);
     let mut marf = MemoryBackingStore::new();
     let conn = marf.as_clarity_db();
     let mut global_context = GlobalContext::new(false, conn, LimitedCostTracker::new_free(), epoch);
@@ -456,7 +478,10 @@ mod test {
         );
 
         let context = LocalContext::new();
-        let mut contract_context = ContractContext::new(QualifiedContractIdentifier::transient());
+        let mut contract_context = ContractContext::new(
+            QualifiedContractIdentifier::transient(),
+            StacksEpochId::Epoch2_05,
+        );
 
         let mut marf = MemoryBackingStore::new();
         let mut global_context = GlobalContext::new(
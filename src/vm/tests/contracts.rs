@@ -19,6 +19,7 @@ use crate::types::chainstate::StacksBlockId;
 use crate::types::proof::ClarityMarfTrieId;
 use chainstate::stacks::index::storage::TrieFileStorage;
 use clarity_vm::clarity::ClarityInstance;
+use core::{BITCOIN_REGTEST_FIRST_BLOCK_HASH, BITCOIN_REGTEST_FIRST_BLOCK_TIMESTAMP};
 use util::hash::hex_bytes;
 use vm::ast;
 use vm::ast::errors::ParseErrors;
@@ -167,6 +168,58 @@ fn test_get_block_info_eval() {
     }
 }
 
+#[test]
+fn test_get_burn_block_info_eval() {
+    let contract =
+        "(define-read-only (header-hash (height uint)) (get-burn-block-info? header-hash height))
+         (define-read-only (time (height uint)) (get-burn-block-info? time height))";
+    let contract_identifier = QualifiedContractIdentifier::local("test-contract").unwrap();
+
+    let test = |owned_env: &mut OwnedEnvironment| {
+        owned_env
+            .initialize_contract(contract_identifier.clone(), contract)
+            .unwrap();
+        let mut env = owned_env.get_exec_environment(None);
+
+        // the parent of the block being evaluated was mined in the first burnchain block
+        assert_eq!(
+            env.eval_read_only(&contract_identifier, "(header-hash u0)")
+                .unwrap(),
+            Value::some(
+                Value::buff_from(hex_bytes(BITCOIN_REGTEST_FIRST_BLOCK_HASH).unwrap()).unwrap()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            env.eval_read_only(&contract_identifier, "(time u0)")
+                .unwrap(),
+            Value::some(Value::UInt(BITCOIN_REGTEST_FIRST_BLOCK_TIMESTAMP as u128)).unwrap()
+        );
+
+        // later burnchain blocks are not known to it, and neither are out-of-range heights
+        for program in ["(header-hash u1)", "(time u1)", "(time u4294967296)"].iter() {
+            assert_eq!(
+                env.eval_read_only(&contract_identifier, program).unwrap(),
+                Value::none()
+            );
+        }
+    };
+    with_marfed_environment(test, true);
+
+    // without a burnchain, nothing can be read
+    let mut marf = MemoryBackingStore::new();
+    let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db());
+    owned_env
+        .initialize_contract(contract_identifier.clone(), contract)
+        .unwrap();
+    let mut env = owned_env.get_exec_environment(None);
+    assert_eq!(
+        env.eval_read_only(&contract_identifier, "(header-hash u0)")
+            .unwrap(),
+        Value::none()
+    );
+}
+
 fn is_committed(v: &Value) -> bool {
     match v {
         Value::Response(ref data) => data.committed,
@@ -41,6 +41,7 @@ use vm::tests::{
     with_marfed_environment, with_memory_environment, TEST_BURN_STATE_DB, TEST_HEADER_DB,
 };
 use vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier, ResponseData, Value};
+use vm::{apply, ast, eval, lookup_function, LocalContext};
 
 use crate::clarity_vm::database::marf::MarfedKV;
 use crate::clarity_vm::database::MemoryBackingStore;
@@ -137,6 +138,15 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         GetStxBalance => "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         StxTransfer => "(stx-transfer? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         StxBurn => "(stx-burn? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        StringSlice => "(string-slice? \"blockstack\" u5 u10)",
+        StringIndexOf => "(string-index-of? \"blockstack\" \"stack\")",
+        ToLowercase => "(to-lowercase \"BlockStack\")",
+        ToUppercase => "(to-uppercase \"BlockStack\")",
+        BuffToIntBe => "(buff-to-int-be 0x01)",
+        BuffToUIntBe => "(buff-to-uint-be 0x01)",
+        IntToBuffBe => "(int-to-buff-be 1)",
+        UIntToBuffBe => "(uint-to-buff-be u1)",
+        GetBurnBlockInfo => "(get-burn-block-info? time u0)",
     }
 }
 
@@ -860,7 +870,10 @@ fn test_tracked_costs(prog: &str, use_mainnet: bool, epoch: StacksEpochId) -> Ex
 fn test_all(use_mainnet: bool) {
     let baseline = test_tracked_costs("1", use_mainnet, StacksEpochId::Epoch20);
 
-    for f in NativeFunctions::ALL.iter() {
+    for f in NativeFunctions::ALL
+        .iter()
+        .filter(|f| f.is_available_in(&StacksEpochId::Epoch20))
+    {
         let test = get_simple_test(f);
         let cost = test_tracked_costs(test, use_mainnet, StacksEpochId::Epoch20);
         assert!(cost.exceeds(&baseline));
@@ -882,7 +895,10 @@ fn test_all_testnet() {
 fn epoch_205_test_all(use_mainnet: bool) {
     let baseline = test_tracked_costs("1", use_mainnet, StacksEpochId::Epoch2_05);
 
-    for f in NativeFunctions::ALL.iter() {
+    for f in NativeFunctions::ALL
+        .iter()
+        .filter(|f| f.is_available_in(&StacksEpochId::Epoch2_05))
+    {
         let test = get_simple_test(f);
         let cost = test_tracked_costs(test, use_mainnet, StacksEpochId::Epoch2_05);
         assert!(cost.exceeds(&baseline));
@@ -1534,3 +1550,122 @@ fn test_cost_voting_integration(use_mainnet: bool) {
 fn test_cost_voting_integration_testnet() {
     test_cost_voting_integration(false)
 }
+
+#[test]
+fn test_arithmetic_fast_path_matches_general_path() {
+    let programs = [
+        "(+ 1 2 3)",
+        "(- u5 u2)",
+        "(- a)",
+        "(- u0)",
+        "(- u1)",
+        "(* a b 3)",
+        "(/ a b)",
+        "(/ a 0)",
+        "(mod a 0)",
+        "(< a b)",
+        "(>= (* a 2) b)",
+        "(+ (* a a) (- b 1) (/ a b) (mod a b))",
+        "(* 170141183460469231731687303715884105727 a)",
+        "(- u1 u2 u3)",
+        "(+ a u1)",
+        "(< a)",
+    ];
+
+    with_owned_env(StacksEpochId::Epoch2_05, false, |mut owned_env| {
+        let mut context = LocalContext::new();
+        context.variables.insert("a".into(), Value::Int(7));
+        context.variables.insert("b".into(), Value::Int(3));
+
+        for program in programs.iter() {
+            let expr = &ast::build_ast(&QualifiedContractIdentifier::transient(), program, &mut ())
+                .unwrap()
+                .expressions[0];
+
+            let start = owned_env.get_cost_total();
+            let fast_result = eval(expr, &mut owned_env.get_exec_environment(None), &context);
+            let mut fast_cost = owned_env.get_cost_total();
+            fast_cost.sub(&start).unwrap();
+
+            // only the outermost application is forced down the general path
+            let start = owned_env.get_cost_total();
+            let general_result = {
+                let mut env = owned_env.get_exec_environment(None);
+                let children = expr.match_list().unwrap();
+                lookup_function(children[0].match_atom().unwrap(), &mut env)
+                    .and_then(|f| apply(&f, &children[1..], &mut env, &context))
+            };
+            let mut general_cost = owned_env.get_cost_total();
+            general_cost.sub(&start).unwrap();
+
+            assert_eq!(fast_result, general_result, "{}", program);
+            assert_eq!(fast_cost, general_cost, "{}", program);
+        }
+    });
+}
+
+#[test]
+fn test_tuple_get_fast_path_matches_general_path() {
+    let contract = "
+(define-map entries { id: int } { a: int, b: (list 20 int), c: (buff 64), d: (optional { x: int }) })
+(map-set entries { id: 1 } { a: 1, b: (list 1 2 3), c: 0x0102, d: (some { x: 2 }) })";
+    let programs = [
+        "(get a (map-get? entries { id: 1 }))",
+        "(get b (map-get? entries { id: 1 }))",
+        "(get d (map-get? entries { id: 1 }))",
+        "(get a (map-get? entries { id: 2 }))",
+        "(get a (map-get? entries { id: (/ 1 0) }))",
+        "(get a (map-get? entries { id: u1 }))",
+    ];
+
+    for epoch in [StacksEpochId::Epoch20, StacksEpochId::Epoch2_05].iter() {
+        with_owned_env(*epoch, false, |mut owned_env| {
+            let contract_id = QualifiedContractIdentifier::local("tuples").unwrap();
+            owned_env
+                .initialize_contract(contract_id.clone(), contract)
+                .unwrap();
+
+            for program in programs.iter() {
+                let expr = &ast::build_ast(&contract_id, program, &mut ())
+                    .unwrap()
+                    .expressions[0];
+
+                let (results, _, _) = owned_env
+                    .execute_in_env(contract_id.issuer.clone().into(), |env| {
+                        let contract = env.global_context.database.get_contract(&contract_id)?;
+                        let mut env = Environment::new(
+                            &mut env.global_context,
+                            &contract.contract_context,
+                            env.call_stack,
+                            None,
+                            None,
+                        );
+                        let context = LocalContext::new();
+
+                        let start = env.global_context.cost_track.get_total();
+                        let fast_result = eval(expr, &mut env, &context);
+                        let mut fast_cost = env.global_context.cost_track.get_total();
+                        fast_cost.sub(&start).unwrap();
+
+                        // only the `get` is forced down the general path: its `map-get?` can't
+                        //  take the fast path on its own
+                        let start = env.global_context.cost_track.get_total();
+                        let general_result = {
+                            let children = expr.match_list().unwrap();
+                            lookup_function(children[0].match_atom().unwrap(), &mut env)
+                                .and_then(|f| apply(&f, &children[1..], &mut env, &context))
+                        };
+                        let mut general_cost = env.global_context.cost_track.get_total();
+                        general_cost.sub(&start).unwrap();
+
+                        Ok::<_, Error>((fast_result, fast_cost, general_result, general_cost))
+                    })
+                    .unwrap();
+                let (fast_result, fast_cost, general_result, general_cost) = results;
+
+                assert_eq!(fast_result, general_result, "{}", program);
+                assert_eq!(fast_cost, general_cost, "{}", program);
+            }
+        });
+    }
+}
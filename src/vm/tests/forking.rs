@@ -191,6 +191,56 @@ fn test_at_block_good() {
     );
 }
 
+#[test]
+fn test_at_block_repeated_reads() {
+    fn initialize(owned_env: &mut OwnedEnvironment) {
+        let c = QualifiedContractIdentifier::local("contract").unwrap();
+        let contract = "(define-data-var datum int 1)
+             (define-private (datum-at (block (buff 32)))
+               (at-block block (var-get datum)))
+             (define-public (read-all)
+               (begin
+                 (var-set datum 5)
+                 (ok (list
+                   (datum-at 0x0101010101010101010101010101010101010101010101010101010101010101)
+                   (datum-at 0x0202020202020202020202020202020202020202020202020202020202020202)
+                   (var-get datum)
+                   (datum-at 0x0101010101010101010101010101010101010101010101010101010101010101)
+                   (datum-at 0x0202020202020202020202020202020202020202020202020202020202020202)))))
+             (define-public (set-val)
+               (begin
+                 (var-set datum 10)
+                 (ok (var-get datum))))";
+
+        owned_env.initialize_contract(c.clone(), &contract).unwrap();
+    }
+
+    fn call(owned_env: &mut OwnedEnvironment, to_exec: &str) -> Value {
+        let c = QualifiedContractIdentifier::local("contract").unwrap();
+        let p1 = execute(p1_str).expect_principal();
+        owned_env
+            .execute_transaction(p1, c, to_exec, &vec![])
+            .unwrap()
+            .0
+    }
+
+    with_separate_forks_environment(
+        initialize,
+        |x| {
+            call(x, "set-val");
+        },
+        |_x| {},
+        |x| {
+            let expected =
+                Value::list_from([1, 10, 5, 1, 10].iter().map(|x| Value::Int(*x)).collect())
+                    .unwrap();
+            // writes at the open block never show up in reads at earlier blocks, cached or not
+            assert_eq!(call(x, "read-all"), Value::okay(expected.clone()).unwrap());
+            assert_eq!(call(x, "read-all"), Value::okay(expected).unwrap());
+        },
+    );
+}
+
 #[test]
 fn test_at_block_missing_defines() {
     fn initialize_1(owned_env: &mut OwnedEnvironment) {
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use chainstate::burn::ConsensusHash;
 use chainstate::stacks::index::storage::TrieFileStorage;
 use core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH, PEER_VERSION_EPOCH_2_0};
 use util::hash::hex_bytes;
@@ -125,6 +126,20 @@ impl HeadersDB for UnitTestHeaderDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        if *id_bhh
+            == StacksBlockHeader::make_index_block_hash(
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH,
+            )
+        {
+            Some(FIRST_BURNCHAIN_CONSENSUS_HASH)
+        } else {
+            let mut bytes = [0; 20];
+            bytes.copy_from_slice(&id_bhh.as_bytes()[0..20]);
+            Some(ConsensusHash(bytes))
+        }
+    }
 }
 
 impl BurnStateDB for UnitTestBurnStateDB {
@@ -134,10 +149,31 @@ impl BurnStateDB for UnitTestBurnStateDB {
 
     fn get_burn_header_hash(
         &self,
-        _height: u32,
+        height: u32,
         _sortition_id: &SortitionId,
     ) -> Option<BurnchainHeaderHash> {
-        None
+        if height == BITCOIN_REGTEST_FIRST_BLOCK_HEIGHT as u32 {
+            Some(BurnchainHeaderHash::from_hex(BITCOIN_REGTEST_FIRST_BLOCK_HASH).unwrap())
+        } else {
+            None
+        }
+    }
+
+    fn get_burn_header_timestamp(&self, height: u32, _sortition_id: &SortitionId) -> Option<u64> {
+        if height == BITCOIN_REGTEST_FIRST_BLOCK_HEIGHT as u32 {
+            Some(BITCOIN_REGTEST_FIRST_BLOCK_TIMESTAMP as u64)
+        } else {
+            None
+        }
+    }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        let mut bytes = [0; 32];
+        bytes[0..20].copy_from_slice(consensus_hash.as_bytes());
+        Some(SortitionId(bytes))
     }
 
     fn get_stacks_epoch(&self, _height: u32) -> Option<StacksEpoch> {
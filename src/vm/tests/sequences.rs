@@ -14,12 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use vm::types::signatures::{ListTypeData, SequenceSubtype};
+use vm::types::signatures::{ListTypeData, SequenceSubtype, StringSubtype};
 use vm::types::TypeSignature::{BoolType, IntType, SequenceType, UIntType};
 use vm::types::{TypeSignature, Value};
 
 use std::convert::TryInto;
 use vm::analysis::errors::CheckError;
+use vm::analysis::mem_type_check;
 use vm::errors::{CheckErrors, Error, RuntimeErrorType};
 use vm::execute;
 
@@ -174,6 +175,107 @@ fn test_element_at() {
     }
 }
 
+#[test]
+fn test_string_natives() {
+    let good = [
+        "(string-slice? \"blockstack\" u5 u10)",
+        "(string-slice? \"blockstack\" u0 u0)",
+        "(string-slice? \"blockstack\" u5 u11)",
+        "(string-slice? \"blockstack\" u6 u5)",
+        "(string-slice? u\"st\\u{e4}cks\" u1 u3)",
+        "(string-index-of? \"blockstack\" \"stack\")",
+        "(string-index-of? \"blockstack\" \"\")",
+        "(string-index-of? \"stack\" \"blockstack\")",
+        "(string-index-of? u\"\\u{e4}\\u{e4}ck\" u\"ck\")",
+        "(string-index-of? u\"abc\" u\"d\")",
+        "(to-lowercase \"BlockStack 2.1\")",
+        "(to-uppercase \"BlockStack 2.1\")",
+        "(to-lowercase u\"ST\\u{c4}CKS\")",
+        "(to-uppercase u\"st\\u{e4}cks\")",
+    ];
+
+    let expected = [
+        "(some \"stack\")",
+        "(some \"\")",
+        "none",
+        "none",
+        "(some u\"t\\u{c3a4}\")",
+        "(some u5)",
+        "(some u0)",
+        "none",
+        "(some u2)",
+        "none",
+        "\"blockstack 2.1\"",
+        "\"BLOCKSTACK 2.1\"",
+        "u\"st\\u{c384}cks\"",
+        "u\"ST\\u{c3a4}CKS\"",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", execute(&good_test).unwrap().unwrap())
+        );
+    }
+
+    let bad = [
+        "(string-slice? 0xfedb u0 u1)",
+        "(string-index-of? u\"a\" \"a\")",
+        "(to-lowercase 1)",
+    ];
+
+    let bad_expected = [
+        CheckErrors::UnionTypeError(
+            vec![
+                TypeSignature::min_string_ascii(),
+                TypeSignature::min_string_utf8(),
+            ],
+            SequenceType(SequenceSubtype::BufferType(2_u32.try_into().unwrap())),
+        ),
+        CheckErrors::TypeError(
+            SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(
+                1_u32.try_into().unwrap(),
+            ))),
+            SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+                1_u32.try_into().unwrap(),
+            ))),
+        ),
+        CheckErrors::UnionTypeError(
+            vec![
+                TypeSignature::min_string_ascii(),
+                TypeSignature::min_string_utf8(),
+            ],
+            IntType,
+        ),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(&mem_type_check(&bad_test).unwrap_err().err, expected);
+    }
+}
+
+#[test]
+fn test_string_natives_shadowed_before_epoch() {
+    // before the epoch that enables them, the string natives' names are not reserved, and
+    //  contracts' own definitions take precedence.
+    let defines = "(define-private (to-lowercase (s (string-ascii 10))) (concat s \"!\"))
+         (define-constant string-slice? u3)";
+    let program = format!(
+        "{} (let ((to-uppercase u2)) (list (to-lowercase \"ABC\") (to-lowercase \"DEF\")))",
+        defines
+    );
+    assert_eq!(
+        "(\"ABC!\" \"DEF!\")",
+        &format!("{}", execute(&program).unwrap().unwrap())
+    );
+    assert_eq!(
+        Value::UInt(3),
+        execute(&format!("{} string-slice?", defines))
+            .unwrap()
+            .unwrap()
+    );
+}
+
 #[test]
 fn test_string_ascii_admission() {
     let defines = "(define-private (set-name (x (string-ascii 11))) x)";
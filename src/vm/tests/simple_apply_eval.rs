@@ -19,9 +19,10 @@ use std::collections::HashMap;
 
 use address::c32;
 use address::AddressHashMode;
+use burnchains::PrivateKey;
 use chainstate::stacks::StacksPrivateKey;
 use chainstate::stacks::StacksPublicKey;
-use util::hash::{hex_bytes, to_hex};
+use util::hash::{hex_bytes, to_hex, Sha256Sum};
 use vm::ast::parse;
 use vm::callables::DefinedFunction;
 use vm::contexts::OwnedEnvironment;
@@ -382,7 +383,10 @@ fn test_simple_if_functions() {
         );
 
         let context = LocalContext::new();
-        let mut contract_context = ContractContext::new(QualifiedContractIdentifier::transient());
+        let mut contract_context = ContractContext::new(
+            QualifiedContractIdentifier::transient(),
+            StacksEpochId::Epoch2_05,
+        );
         let mut marf = MemoryBackingStore::new();
         let mut global_context = GlobalContext::new(
             false,
@@ -543,6 +547,92 @@ fn test_simple_arithmetic_functions() {
         .for_each(|(program, expectation)| assert_eq!(expectation.clone(), execute(program)));
 }
 
+#[test]
+fn test_secp256k1_externally_signed_message() {
+    // a message signed off-chain, e.g. by an oracle, can be checked against the signer's key
+    let privk = StacksPrivateKey::from_hex(
+        "510f96a8efd0b11e211733c1ac5e3fa6f3d3fcdd62869e376c47decb3e14fea101",
+    )
+    .unwrap();
+    let pubkey = StacksPublicKey::from_private(&privk);
+    let message = Sha256Sum::from_data(b"BTC-USD 43210.15 @ 1640995200");
+    let signature = privk.sign(message.as_bytes()).unwrap().to_rsv();
+
+    let message_hex = to_hex(message.as_bytes());
+    let signature_hex = to_hex(&signature);
+    let pubkey_hex = to_hex(&pubkey.to_bytes_compressed());
+
+    assert_eq!(
+        Value::okay(Value::buff_from(pubkey.to_bytes_compressed()).unwrap()).unwrap(),
+        execute(&format!(
+            "(secp256k1-recover? 0x{} 0x{})",
+            &message_hex, &signature_hex
+        ))
+    );
+    assert_eq!(
+        Value::Bool(true),
+        execute(&format!(
+            "(secp256k1-verify 0x{} 0x{} 0x{})",
+            &message_hex, &signature_hex, &pubkey_hex
+        ))
+    );
+
+    // the signature does not cover any other message
+    let other_message_hex = to_hex(Sha256Sum::from_data(b"BTC-USD 1.00 @ 1640995200").as_bytes());
+    assert_eq!(
+        Value::Bool(false),
+        execute(&format!(
+            "(secp256k1-verify 0x{} 0x{} 0x{})",
+            &other_message_hex, &signature_hex, &pubkey_hex
+        ))
+    );
+}
+
+#[test]
+fn test_buffer_int_conversions() {
+    let tests = [
+        "(buff-to-int-be 0x01ff)",
+        "(buff-to-int-be 0xff)",
+        "(buff-to-int-be 0x)",
+        "(buff-to-int-be 0xffffffffffffffffffffffffffffffff)",
+        "(buff-to-int-be 0x80000000000000000000000000000000)",
+        "(buff-to-uint-be 0x01ff)",
+        "(buff-to-uint-be 0xffffffffffffffffffffffffffffffff)",
+        "(buff-to-int-be (int-to-buff-be -12345))",
+        "(buff-to-uint-be (uint-to-buff-be u12345))",
+    ];
+
+    let expectations = [
+        Value::Int(511),
+        Value::Int(255),
+        Value::Int(0),
+        Value::Int(-1),
+        Value::Int(i128::MIN),
+        Value::UInt(511),
+        Value::UInt(u128::MAX),
+        Value::Int(-12345),
+        Value::UInt(12345),
+    ];
+
+    tests
+        .iter()
+        .zip(expectations.iter())
+        .for_each(|(program, expectation)| assert_eq!(expectation.clone(), execute(program)));
+
+    assert_eq!(
+        Value::buff_from(hex_bytes("000000000000000000000000000001ff").unwrap()).unwrap(),
+        execute("(int-to-buff-be 511)")
+    );
+    assert_eq!(
+        Value::buff_from(vec![0xff; 16]).unwrap(),
+        execute("(int-to-buff-be -1)")
+    );
+    assert_eq!(
+        Value::buff_from(hex_bytes("ffffffffffffffffffffffffffffffff").unwrap()).unwrap(),
+        execute("(uint-to-buff-be u340282366920938463463374607431768211455)")
+    );
+}
+
 #[test]
 fn test_simple_arithmetic_errors() {
     let tests = [
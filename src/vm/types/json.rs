@@ -0,0 +1,298 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Canonical JSON encoding of Clarity values.
+//!
+//! Every value is encoded as an object with a `type` tag and a `value`:
+//!
+//! * `int` and `uint` values are decimal strings, since they do not fit in a JSON number.
+//! * `bool` values are JSON booleans.
+//! * `buff` values are `0x`-prefixed hex strings.
+//! * `string-ascii` and `string-utf8` values are JSON strings.
+//! * `principal` values are the principal's address (and contract name, if any).
+//! * `list` values are arrays of encoded values.
+//! * `tuple` values are objects mapping each field name to its encoded value.
+//! * `optional` values are `null` for `none`, or the encoded value for `(some ...)`.
+//! * `response` values also have a `success` field, which is `true` for `(ok ...)`.
+//!
+//! The encoding is lossless, so `Value::from_json` undoes `Value::to_json`.
+
+use std::convert::TryFrom;
+
+use serde_json;
+
+use util::hash::{hex_bytes, to_hex};
+use vm::analysis::contract_interface_builder::ContractInterfaceAtomType;
+use vm::errors::Error as InterpreterError;
+use vm::representations::ClarityName;
+use vm::types::serialization::SerializationError;
+use vm::types::{
+    BuffData, CharType, OptionalData, PrincipalData, ResponseData, SequenceData, TupleData,
+    TypeSignature, Value,
+};
+
+fn json_error(msg: &str, json: &serde_json::Value) -> SerializationError {
+    SerializationError::DeserializationError(format!("{}: {}", msg, json))
+}
+
+fn value_error(e: InterpreterError) -> SerializationError {
+    SerializationError::DeserializationError(format!("Bad value: {:?}", e))
+}
+
+impl Value {
+    /// Encode this value as canonical JSON
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Int(i) => json!({ "type": "int", "value": i.to_string() }),
+            Value::UInt(u) => json!({ "type": "uint", "value": u.to_string() }),
+            Value::Bool(b) => json!({ "type": "bool", "value": b }),
+            Value::Sequence(SequenceData::Buffer(BuffData { data })) => {
+                json!({ "type": "buff", "value": format!("0x{}", to_hex(data)) })
+            }
+            Value::Sequence(SequenceData::String(CharType::ASCII(data))) => {
+                json!({ "type": "string-ascii", "value": String::from_utf8_lossy(&data.data) })
+            }
+            Value::Sequence(SequenceData::String(CharType::UTF8(data))) => {
+                let bytes: Vec<u8> = data.data.iter().flatten().cloned().collect();
+                json!({ "type": "string-utf8", "value": String::from_utf8_lossy(&bytes) })
+            }
+            Value::Sequence(SequenceData::List(list)) => {
+                let items: Vec<_> = list.data.iter().map(|item| item.to_json()).collect();
+                json!({ "type": "list", "value": items })
+            }
+            Value::Principal(principal) => {
+                json!({ "type": "principal", "value": principal.to_string() })
+            }
+            Value::Tuple(tuple) => {
+                let fields: serde_json::Map<_, _> = tuple
+                    .data_map
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_json()))
+                    .collect();
+                json!({ "type": "tuple", "value": fields })
+            }
+            Value::Optional(OptionalData { data }) => match data {
+                Some(data) => json!({ "type": "optional", "value": data.to_json() }),
+                None => json!({ "type": "optional", "value": null }),
+            },
+            Value::Response(ResponseData { committed, data }) => {
+                json!({ "type": "response", "success": committed, "value": data.to_json() })
+            }
+        }
+    }
+
+    /// Encode this value as canonical JSON, along with a descriptor of its type in the same
+    /// format as contract ABIs.
+    pub fn to_json_with_type(&self) -> serde_json::Value {
+        let type_descriptor =
+            ContractInterfaceAtomType::from_type_signature(&TypeSignature::type_of(self));
+        json!({
+            "value": self.to_json(),
+            "type_signature": type_descriptor,
+        })
+    }
+
+    /// Decode a value from canonical JSON
+    pub fn from_json(json: &serde_json::Value) -> Result<Value, SerializationError> {
+        let type_tag = json
+            .get("type")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| json_error("Missing type tag", json))?;
+        let value = json
+            .get("value")
+            .ok_or_else(|| json_error("Missing value", json))?;
+        let as_str = || {
+            value
+                .as_str()
+                .ok_or_else(|| json_error("Expected a string", value))
+        };
+
+        let result = match type_tag {
+            "int" => Value::Int(
+                as_str()?
+                    .parse::<i128>()
+                    .map_err(|_| json_error("Bad int", value))?,
+            ),
+            "uint" => Value::UInt(
+                as_str()?
+                    .parse::<u128>()
+                    .map_err(|_| json_error("Bad uint", value))?,
+            ),
+            "bool" => Value::Bool(
+                value
+                    .as_bool()
+                    .ok_or_else(|| json_error("Expected a bool", value))?,
+            ),
+            "buff" => {
+                let hex = as_str()?;
+                if !hex.starts_with("0x") {
+                    return Err(json_error("Expected a 0x-prefixed hex string", value));
+                }
+                let bytes = hex_bytes(&hex[2..]).map_err(|_| json_error("Bad hex", value))?;
+                Value::buff_from(bytes).map_err(value_error)?
+            }
+            "string-ascii" => Value::string_ascii_from_bytes(as_str()?.as_bytes().to_vec())
+                .map_err(value_error)?,
+            "string-utf8" => {
+                Value::string_utf8_from_bytes(as_str()?.as_bytes().to_vec()).map_err(value_error)?
+            }
+            "principal" => Value::Principal(
+                PrincipalData::parse(as_str()?).map_err(|_| json_error("Bad principal", value))?,
+            ),
+            "list" => {
+                let items = value
+                    .as_array()
+                    .ok_or_else(|| json_error("Expected an array", value))?
+                    .iter()
+                    .map(Value::from_json)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::list_from(items).map_err(value_error)?
+            }
+            "tuple" => {
+                let mut fields = vec![];
+                for (name, field) in value
+                    .as_object()
+                    .ok_or_else(|| json_error("Expected an object", value))?
+                    .iter()
+                {
+                    let name = ClarityName::try_from(name.clone())
+                        .map_err(|_| json_error("Bad tuple field name", value))?;
+                    fields.push((name, Value::from_json(field)?));
+                }
+                Value::Tuple(TupleData::from_data(fields).map_err(value_error)?)
+            }
+            "optional" => {
+                if value.is_null() {
+                    Value::none()
+                } else {
+                    Value::some(Value::from_json(value)?).map_err(value_error)?
+                }
+            }
+            "response" => {
+                let success = json
+                    .get("success")
+                    .and_then(|s| s.as_bool())
+                    .ok_or_else(|| json_error("Missing success flag", json))?;
+                let data = Value::from_json(value)?;
+                if success {
+                    Value::okay(data).map_err(value_error)?
+                } else {
+                    Value::error(data).map_err(value_error)?
+                }
+            }
+            _ => {
+                return Err(json_error("Unknown type tag", json));
+            }
+        };
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use vm::types::QualifiedContractIdentifier;
+
+    #[test]
+    fn test_json_round_trip() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.foo")
+                .unwrap();
+        let values = vec![
+            Value::Int(-170141183460469231731687303715884105728),
+            Value::UInt(340282366920938463463374607431768211455),
+            Value::Bool(true),
+            Value::buff_from(vec![0xde, 0xad, 0xbe, 0xef]).unwrap(),
+            Value::string_ascii_from_bytes("hello world".as_bytes().to_vec()).unwrap(),
+            Value::string_utf8_from_bytes("héllo wörld".as_bytes().to_vec()).unwrap(),
+            Value::Principal(contract_id.clone().into()),
+            Value::Principal(contract_id.issuer.clone().into()),
+            Value::list_from(vec![Value::UInt(1), Value::UInt(2)]).unwrap(),
+            Value::list_from(vec![]).unwrap(),
+            Value::none(),
+            Value::some(Value::Int(1)).unwrap(),
+            Value::okay(Value::Bool(false)).unwrap(),
+            Value::error(Value::UInt(404)).unwrap(),
+            Value::Tuple(
+                TupleData::from_data(vec![
+                    ("a".into(), Value::Int(1)),
+                    ("b".into(), Value::some(Value::none()).unwrap()),
+                ])
+                .unwrap(),
+            ),
+        ];
+        for value in values.into_iter() {
+            let json = value.to_json();
+            assert_eq!(Value::from_json(&json).unwrap(), value, "{}", &json);
+        }
+    }
+
+    #[test]
+    fn test_json_encoding() {
+        let value = Value::okay(Value::Tuple(
+            TupleData::from_data(vec![
+                ("amount".into(), Value::UInt(100)),
+                (
+                    "memo".into(),
+                    Value::some(Value::buff_from(vec![0x01, 0x02]).unwrap()).unwrap(),
+                ),
+            ])
+            .unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(
+            value.to_json(),
+            json!({
+                "type": "response",
+                "success": true,
+                "value": {
+                    "type": "tuple",
+                    "value": {
+                        "amount": { "type": "uint", "value": "100" },
+                        "memo": {
+                            "type": "optional",
+                            "value": { "type": "buff", "value": "0x0102" }
+                        }
+                    }
+                }
+            })
+        );
+        assert_eq!(
+            Value::UInt(1).to_json_with_type(),
+            json!({
+                "value": { "type": "uint", "value": "1" },
+                "type_signature": "uint128",
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_decode_errors() {
+        let bad = vec![
+            json!({ "value": "1" }),
+            json!({ "type": "uint" }),
+            json!({ "type": "uint", "value": 1 }),
+            json!({ "type": "uint", "value": "-1" }),
+            json!({ "type": "buff", "value": "0102" }),
+            json!({ "type": "principal", "value": "not-a-principal" }),
+            json!({ "type": "response", "value": { "type": "bool", "value": true } }),
+            json!({ "type": "float", "value": "1.0" }),
+        ];
+        for json in bad.iter() {
+            assert!(Value::from_json(json).is_err(), "{}", json);
+        }
+    }
+}
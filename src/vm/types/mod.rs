@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod json;
 pub mod serialization;
 pub mod signatures;
 
@@ -35,8 +36,8 @@ use vm::representations::{ClarityName, ContractName, SymbolicExpression, Symboli
 pub use vm::types::signatures::{
     parse_name_type_pairs, AssetIdentifier, BufferLength, FixedFunction, FunctionArg,
     FunctionSignature, FunctionType, ListTypeData, SequenceSubtype, StringSubtype,
-    StringUTF8Length, TupleTypeSignature, TypeSignature, BUFF_1, BUFF_20, BUFF_32, BUFF_33,
-    BUFF_64, BUFF_65,
+    StringUTF8Length, TupleTypeSignature, TypeSignature, BUFF_1, BUFF_16, BUFF_20, BUFF_32,
+    BUFF_33, BUFF_64, BUFF_65,
 };
 
 pub const MAX_VALUE_SIZE: u32 = 1024 * 1024; // 1MB
@@ -566,6 +567,11 @@ define_named_enum!(BlockInfoProperty {
     MinerAddress("miner-address"),
 });
 
+define_named_enum!(BurnBlockInfoProperty {
+    HeaderHash("header-hash"),
+    Time("time"),
+});
+
 impl OptionalData {
     pub fn type_signature(&self) -> TypeSignature {
         let type_result = match self.data {
@@ -603,6 +609,16 @@ impl BlockInfoProperty {
     }
 }
 
+impl BurnBlockInfoProperty {
+    pub fn type_result(&self) -> TypeSignature {
+        use self::BurnBlockInfoProperty::*;
+        match self {
+            HeaderHash => BUFF_32.clone(),
+            Time => TypeSignature::UIntType,
+        }
+    }
+}
+
 impl PartialEq for ListData {
     fn eq(&self, other: &ListData) -> bool {
         self.data == other.data
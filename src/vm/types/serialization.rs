@@ -33,7 +33,8 @@ use vm::representations::{ClarityName, ContractName, MAX_STRING_LEN};
 use vm::types::{
     BufferLength, CharType, OptionalData, PrincipalData, QualifiedContractIdentifier, ResponseData,
     SequenceData, SequenceSubtype, StandardPrincipalData, StringSubtype, StringUTF8Length,
-    TupleData, TypeSignature, Value, BOUND_VALUE_SERIALIZATION_BYTES, MAX_VALUE_SIZE,
+    TupleData, TupleTypeSignature, TypeSignature, Value, BOUND_VALUE_SERIALIZATION_BYTES,
+    MAX_VALUE_SIZE,
 };
 
 use crate::codec::{Error as codec_error, StacksMessageCodec};
@@ -555,6 +556,144 @@ impl Value {
         }
     }
 
+    /// Deserialize a value nested `depth` levels deep in the value being read, as it would be
+    ///  deserialized as part of the enclosing value.
+    pub fn deserialize_read_nested<R: Read>(
+        r: &mut R,
+        expected_type: Option<&TypeSignature>,
+        depth: u8,
+    ) -> Result<Value, SerializationError> {
+        let mut bound_reader = BoundReader::from_reader(r, BOUND_VALUE_SERIALIZATION_BYTES as u64);
+        Value::inner_deserialize_read(&mut bound_reader, expected_type, depth)
+    }
+
+    /// Read the prefix of a serialized optional nested `depth` levels deep, returning whether
+    ///  it is a `some`. The contained value, if any, is left unread.
+    pub fn deserialize_optional_prefix_read<R: Read>(
+        r: &mut R,
+        depth: u8,
+    ) -> Result<bool, SerializationError> {
+        if depth >= 16 {
+            return Err(CheckErrors::TypeSignatureTooDeep.into());
+        }
+
+        let mut header = [0];
+        r.read_exact(&mut header)?;
+
+        match TypePrefix::from_u8(header[0]).ok_or_else(|| "Bad type prefix")? {
+            TypePrefix::OptionalNone => Ok(false),
+            TypePrefix::OptionalSome => Ok(true),
+            _ => Err("Expected an optional".into()),
+        }
+    }
+
+    /// Deserialize only the field `field_name` of a serialized tuple of type `expected_type`
+    ///  nested `depth` levels deep, reading past its other fields without deserializing them.
+    ///  Returns None if the tuple has no such field.
+    pub fn deserialize_tuple_field_read<R: Read>(
+        r: &mut R,
+        expected_type: &TupleTypeSignature,
+        field_name: &str,
+        depth: u8,
+    ) -> Result<Option<Value>, SerializationError> {
+        let mut r = BoundReader::from_reader(r, BOUND_VALUE_SERIALIZATION_BYTES as u64);
+        let type_error =
+            || SerializationError::DeserializeExpected(TypeSignature::from(expected_type.clone()));
+
+        if depth >= 16 {
+            return Err(CheckErrors::TypeSignatureTooDeep.into());
+        }
+
+        let mut header = [0];
+        r.read_exact(&mut header)?;
+        if TypePrefix::from_u8(header[0]) != Some(TypePrefix::Tuple) {
+            return Err(type_error());
+        }
+
+        let mut len = [0; 4];
+        r.read_exact(&mut len)?;
+        if u32::from_be_bytes(len) as u64 != expected_type.len() {
+            return Err(type_error());
+        }
+
+        let mut field = None;
+        for _i in 0..expected_type.len() {
+            let key = ClarityName::deserialize_read(&mut r)?;
+            let expected_field_type = expected_type.field_type(&key).ok_or_else(type_error)?;
+
+            if field.is_none() && key.as_str() == field_name {
+                field = Some(Value::inner_deserialize_read(
+                    &mut r,
+                    Some(expected_field_type),
+                    depth + 1,
+                )?);
+            } else {
+                Value::skip_serialized_read(&mut r, depth + 1)?;
+            }
+        }
+
+        Ok(field)
+    }
+
+    /// Read past a serialized value, checking only enough of it to find where it ends.
+    fn skip_serialized_read<R: Read>(r: &mut R, depth: u8) -> Result<(), SerializationError> {
+        if depth >= 16 {
+            return Err(CheckErrors::TypeSignatureTooDeep.into());
+        }
+
+        let mut header = [0];
+        r.read_exact(&mut header)?;
+
+        let prefix = TypePrefix::from_u8(header[0]).ok_or_else(|| "Bad type prefix")?;
+
+        let skip_len = match prefix {
+            TypePrefix::Int | TypePrefix::UInt => 16,
+            TypePrefix::BoolTrue | TypePrefix::BoolFalse | TypePrefix::OptionalNone => 0,
+            TypePrefix::PrincipalStandard => 21,
+            TypePrefix::PrincipalContract => {
+                let mut issuer = [0; 21];
+                r.read_exact(&mut issuer)?;
+                let mut name_len = [0];
+                r.read_exact(&mut name_len)?;
+                name_len[0] as u64
+            }
+            TypePrefix::Buffer | TypePrefix::StringASCII | TypePrefix::StringUTF8 => {
+                let mut len = [0; 4];
+                r.read_exact(&mut len)?;
+                u32::from_be_bytes(len) as u64
+            }
+            TypePrefix::ResponseOk | TypePrefix::ResponseErr | TypePrefix::OptionalSome => {
+                return Value::skip_serialized_read(r, depth + 1);
+            }
+            TypePrefix::List => {
+                let mut len = [0; 4];
+                r.read_exact(&mut len)?;
+                for _i in 0..u32::from_be_bytes(len) {
+                    Value::skip_serialized_read(r, depth + 1)?;
+                }
+                return Ok(());
+            }
+            TypePrefix::Tuple => {
+                let mut len = [0; 4];
+                r.read_exact(&mut len)?;
+                for _i in 0..u32::from_be_bytes(len) {
+                    let mut name_len = [0];
+                    r.read_exact(&mut name_len)?;
+                    let mut name = vec![0; name_len[0] as usize];
+                    r.read_exact(&mut name)?;
+                    Value::skip_serialized_read(r, depth + 1)?;
+                }
+                return Ok(());
+            }
+        };
+
+        let skipped = std::io::copy(&mut r.by_ref().take(skip_len), &mut std::io::sink())?;
+        if skipped != skip_len {
+            return Err("Truncated serialized value".into());
+        }
+        Ok(())
+    }
+
     pub fn serialize_write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
         use super::CharType::*;
         use super::PrincipalData::*;
@@ -602,6 +602,19 @@ impl FunctionSignature {
     }
 
     pub fn check_args_trait_compliance(&self, args: Vec<TypeSignature>) -> bool {
+        self.check_args_trait_compliance_with(args, &mut |expected, candidate| {
+            expected == candidate
+        })
+    }
+
+    /// Like `check_args_trait_compliance`, but a trait argument `<candidate>` admits an expected
+    /// trait argument `<expected>` whenever `is_subtrait(expected, candidate)` holds, rather than
+    /// only when they are the same trait.
+    pub fn check_args_trait_compliance_with(
+        &self,
+        args: Vec<TypeSignature>,
+        is_subtrait: &mut dyn FnMut(&TraitIdentifier, &TraitIdentifier) -> bool,
+    ) -> bool {
         if args.len() != self.args.len() {
             return false;
         }
@@ -612,7 +625,7 @@ impl FunctionSignature {
                     TypeSignature::TraitReferenceType(expected),
                     TypeSignature::TraitReferenceType(candidate),
                 ) => {
-                    if candidate != expected {
+                    if !is_subtrait(expected, candidate) {
                         return false;
                     }
                 }
@@ -625,6 +638,86 @@ impl FunctionSignature {
         }
         true
     }
+
+    /// Could a function with the signature `candidate` stand in for this one?  Its arguments
+    /// must admit this function's arguments, and this function's return type must admit its
+    /// return type.  Trait arguments are compared with `is_subtrait`.
+    pub fn admits_signature(
+        &self,
+        candidate: &FunctionSignature,
+        is_subtrait: &mut dyn FnMut(&TraitIdentifier, &TraitIdentifier) -> bool,
+    ) -> bool {
+        self.check_args_trait_compliance_with(candidate.args.clone(), is_subtrait)
+            && self.returns.admits_type(&candidate.returns)
+    }
+}
+
+/// How many traits deep `is_subtrait` will follow trait arguments before giving up.
+pub const MAX_TRAIT_SUBTYPING_DEPTH: u8 = 8;
+
+/// Does every contract that complies with the trait `sub_trait` also comply with `super_trait`?
+/// This is the case if every function of `super_trait` is declared by `sub_trait` with a
+/// signature that could stand in for it.  Traits are compared by structure, not by name, so the
+/// two may be defined in different contracts.  Trait definitions are looked up with `lookup`,
+/// which is expected to charge for the load, and a trait that cannot be found is not a subtrait
+/// of anything but itself.  Comparing traits whose arguments nest deeper than
+/// `MAX_TRAIT_SUBTYPING_DEPTH` is an error.
+pub fn is_subtrait<F, E>(
+    sub_trait: &TraitIdentifier,
+    super_trait: &TraitIdentifier,
+    lookup: &mut F,
+) -> Result<bool, E>
+where
+    F: FnMut(&TraitIdentifier) -> Result<Option<BTreeMap<ClarityName, FunctionSignature>>, E>,
+    E: From<CheckErrors>,
+{
+    is_subtrait_at_depth(sub_trait, super_trait, lookup, 0)
+}
+
+fn is_subtrait_at_depth<F, E>(
+    sub_trait: &TraitIdentifier,
+    super_trait: &TraitIdentifier,
+    lookup: &mut F,
+    depth: u8,
+) -> Result<bool, E>
+where
+    F: FnMut(&TraitIdentifier) -> Result<Option<BTreeMap<ClarityName, FunctionSignature>>, E>,
+    E: From<CheckErrors>,
+{
+    if sub_trait == super_trait {
+        return Ok(true);
+    }
+    if depth >= MAX_TRAIT_SUBTYPING_DEPTH {
+        return Err(CheckErrors::TypeSignatureTooDeep.into());
+    }
+    let sub_definition = match lookup(sub_trait)? {
+        Some(sub_definition) => sub_definition,
+        None => return Ok(false),
+    };
+    let super_definition = match lookup(super_trait)? {
+        Some(super_definition) => super_definition,
+        None => return Ok(false),
+    };
+    let mut error = None;
+    let admitted =
+        super_definition.iter().all(
+            |(func_name, super_sig)| match sub_definition.get(func_name) {
+                Some(sub_sig) => super_sig.admits_signature(sub_sig, &mut |expected, candidate| {
+                    match is_subtrait_at_depth(expected, candidate, lookup, depth + 1) {
+                        Ok(admitted) => admitted,
+                        Err(e) => {
+                            error = Some(e);
+                            false
+                        }
+                    }
+                }),
+                None => false,
+            },
+        );
+    match error {
+        Some(e) => Err(e),
+        None => Ok(admitted),
+    }
 }
 
 impl FunctionArg {
@@ -1,7 +1,11 @@
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io::prelude::*;
 use std::io::{self, BufReader};
-use std::io::{prelude::*, Cursor, Lines};
+use std::sync::Arc;
 
-use libflate::deflate::{self, Decoder};
+use libflate::deflate;
+use sha2::{Digest, Sha256};
 
 pub struct GenesisAccountBalance {
     /// A STX or BTC address (BTC addresses should be converted to STX when used).
@@ -44,62 +48,236 @@ pub struct GenesisZonefile {
 pub static GENESIS_CHAINSTATE_HASH: &str =
     include_str!(concat!(env!("OUT_DIR"), "/chainstate.txt.sha256"));
 
+/// Where a GenesisData reads its records from.
+#[derive(Clone)]
+enum GenesisSource {
+    /// The chainstate.txt (or chainstate-test.txt) data compiled into this crate
+    Embedded { use_test_chainstate_data: bool },
+    /// A custom chainstate file (and optional name zonefiles file) loaded at runtime
+    Custom {
+        chainstate: Arc<String>,
+        name_zonefiles: Option<Arc<String>>,
+    },
+}
+
+#[derive(Clone)]
 pub struct GenesisData {
-    use_test_chainstate_data: bool,
+    source: GenesisSource,
 }
 
 impl GenesisData {
     pub fn new(use_test_chainstate_data: bool) -> GenesisData {
         GenesisData {
-            use_test_chainstate_data,
+            source: GenesisSource::Embedded {
+                use_test_chainstate_data,
+            },
+        }
+    }
+
+    /// Load custom genesis data from a file in the same format as chainstate.txt, and
+    /// optionally a name zonefiles file in the same format as name_zonefiles.txt.  Each file
+    /// must be accompanied by a `.sha256` file holding its hex-encoded SHA-256 hash, just like
+    /// the embedded data.  Every record is parsed up front, so a malformed file is rejected
+    /// here instead of partway through booting the chain.
+    pub fn from_files(
+        chainstate_path: &str,
+        name_zonefiles_path: Option<&str>,
+    ) -> Result<GenesisData, String> {
+        let chainstate = read_verified_file(chainstate_path)?;
+        let name_zonefiles = match name_zonefiles_path {
+            Some(path) => Some(Arc::new(read_verified_file(path)?)),
+            None => None,
+        };
+        let genesis_data = GenesisData {
+            source: GenesisSource::Custom {
+                chainstate: Arc::new(chainstate),
+                name_zonefiles,
+            },
+        };
+        genesis_data.validate()?;
+        Ok(genesis_data)
+    }
+
+    /// Check that every record in a custom genesis file is well-formed
+    fn validate(&self) -> Result<(), String> {
+        let chainstate = match self.source {
+            GenesisSource::Custom { ref chainstate, .. } => chainstate,
+            GenesisSource::Embedded { .. } => {
+                return Ok(());
+            }
+        };
+        for (section_name, num_cols, int_cols) in [
+            (BALANCES_SECTION, 2, &[1usize][..]),
+            (LOCKUPS_SECTION, 3, &[1, 2][..]),
+            (NAMESPACES_SECTION, 8, &[3, 4, 5, 6, 7][..]),
+            (NAMES_SECTION, 3, &[][..]),
+        ]
+        .iter()
+        {
+            let rows = section_lines(chainstate, section_name)
+                .ok_or_else(|| format!("Missing section '{}'", section_name))?;
+            for (i, line) in rows.iter().enumerate() {
+                let cols: Vec<&str> = line.split(",").collect();
+                if cols.len() != *num_cols {
+                    return Err(format!(
+                        "Row {} of section '{}' has {} columns, expected {}",
+                        i,
+                        section_name,
+                        cols.len(),
+                        num_cols
+                    ));
+                }
+                for col in int_cols.iter() {
+                    if cols[*col].parse::<i64>().map(|x| x < 0).unwrap_or(true) {
+                        return Err(format!(
+                            "Row {} of section '{}' has a bad integer '{}' in column {}",
+                            i, section_name, cols[*col], col
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_rows(
+        &self,
+        section_name: &str,
+        deflate_bytes: &'static [u8],
+        test_deflate_bytes: &'static [u8],
+    ) -> Box<dyn Iterator<Item = Vec<String>>> {
+        match self.source {
+            GenesisSource::Embedded {
+                use_test_chainstate_data,
+            } => iter_deflated_csv(if use_test_chainstate_data {
+                test_deflate_bytes
+            } else {
+                deflate_bytes
+            }),
+            GenesisSource::Custom { ref chainstate, .. } => {
+                let rows: Vec<Vec<String>> = section_lines(chainstate, section_name)
+                    .unwrap_or(vec![])
+                    .into_iter()
+                    .map(|line| line.split(",").map(String::from).collect())
+                    .collect();
+                Box::new(rows.into_iter())
+            }
         }
     }
+
     pub fn read_balances(&self) -> Box<dyn Iterator<Item = GenesisAccountBalance>> {
-        read_balances(if self.use_test_chainstate_data {
-            include_bytes!(concat!(env!("OUT_DIR"), "/account_balances-test.gz"))
-        } else {
-            include_bytes!(concat!(env!("OUT_DIR"), "/account_balances.gz"))
-        })
+        read_balances(self.read_rows(
+            BALANCES_SECTION,
+            include_bytes!(concat!(env!("OUT_DIR"), "/account_balances.gz")),
+            include_bytes!(concat!(env!("OUT_DIR"), "/account_balances-test.gz")),
+        ))
     }
     pub fn read_lockups(&self) -> Box<dyn Iterator<Item = GenesisAccountLockup>> {
-        read_lockups(if self.use_test_chainstate_data {
-            include_bytes!(concat!(env!("OUT_DIR"), "/account_lockups-test.gz"))
-        } else {
-            include_bytes!(concat!(env!("OUT_DIR"), "/account_lockups.gz"))
-        })
+        read_lockups(self.read_rows(
+            LOCKUPS_SECTION,
+            include_bytes!(concat!(env!("OUT_DIR"), "/account_lockups.gz")),
+            include_bytes!(concat!(env!("OUT_DIR"), "/account_lockups-test.gz")),
+        ))
     }
     pub fn read_namespaces(&self) -> Box<dyn Iterator<Item = GenesisNamespace>> {
-        read_namespaces(if self.use_test_chainstate_data {
-            include_bytes!(concat!(env!("OUT_DIR"), "/namespaces-test.gz"))
-        } else {
-            include_bytes!(concat!(env!("OUT_DIR"), "/namespaces.gz"))
-        })
+        read_namespaces(self.read_rows(
+            NAMESPACES_SECTION,
+            include_bytes!(concat!(env!("OUT_DIR"), "/namespaces.gz")),
+            include_bytes!(concat!(env!("OUT_DIR"), "/namespaces-test.gz")),
+        ))
     }
     pub fn read_names(&self) -> Box<dyn Iterator<Item = GenesisName>> {
-        read_names(if self.use_test_chainstate_data {
-            include_bytes!(concat!(env!("OUT_DIR"), "/names-test.gz"))
-        } else {
-            include_bytes!(concat!(env!("OUT_DIR"), "/names.gz"))
-        })
+        read_names(self.read_rows(
+            NAMES_SECTION,
+            include_bytes!(concat!(env!("OUT_DIR"), "/names.gz")),
+            include_bytes!(concat!(env!("OUT_DIR"), "/names-test.gz")),
+        ))
     }
     pub fn read_name_zonefiles(&self) -> Box<dyn Iterator<Item = GenesisZonefile>> {
-        read_deflated_zonefiles(if self.use_test_chainstate_data {
-            include_bytes!(concat!(env!("OUT_DIR"), "/name_zonefiles-test.gz"))
-        } else {
-            include_bytes!(concat!(env!("OUT_DIR"), "/name_zonefiles.gz"))
-        })
+        match self.source {
+            GenesisSource::Embedded {
+                use_test_chainstate_data,
+            } => read_deflated_zonefiles(if use_test_chainstate_data {
+                include_bytes!(concat!(env!("OUT_DIR"), "/name_zonefiles-test.gz"))
+            } else {
+                include_bytes!(concat!(env!("OUT_DIR"), "/name_zonefiles.gz"))
+            }),
+            GenesisSource::Custom {
+                ref name_zonefiles, ..
+            } => {
+                let lines: Vec<String> = match name_zonefiles {
+                    Some(name_zonefiles) => name_zonefiles.lines().map(String::from).collect(),
+                    None => vec![],
+                };
+                read_zonefiles(Box::new(lines.into_iter()))
+            }
+        }
+    }
+}
+
+static BALANCES_SECTION: &str = "STX BALANCES";
+static LOCKUPS_SECTION: &str = "STX VESTING";
+static NAMESPACES_SECTION: &str = "NAMESPACES";
+static NAMES_SECTION: &str = "NAMES";
+
+/// Read a file, and check it against the SHA-256 hash declared in its `.sha256` file
+fn read_verified_file(path: &str) -> Result<String, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {:?}", path, e))?;
+    let sha256_path = format!("{}.sha256", path);
+    let expected_sha = fs::read_to_string(&sha256_path)
+        .map_err(|e| format!("Failed to read {}: {:?}", &sha256_path, e))?;
+    let actual_sha = encode_hex(&Sha256::digest(contents.as_bytes()));
+    if !actual_sha.eq_ignore_ascii_case(expected_sha.trim()) {
+        return Err(format!(
+            "{} hash mismatch, expected {}, got {}",
+            path,
+            expected_sha.trim(),
+            actual_sha
+        ));
+    }
+    Ok(contents)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        write!(&mut s, "{:02x}", b).unwrap();
+    }
+    s
+}
+
+/// Get the rows of a section of a chainstate.txt-formatted file, without its table header.
+/// This is the same extraction that build.rs does for the embedded data.
+fn section_lines(chainstate: &str, section_name: &str) -> Option<Vec<String>> {
+    let section_header = format!("-----BEGIN {}-----", section_name);
+    let section_footer = format!("-----END {}-----", section_name);
+    let mut lines = chainstate
+        .lines()
+        .skip_while(|line| *line != section_header);
+    // skip section header and table header lines
+    lines.next()?;
+    lines.next()?;
+    let mut rows = vec![];
+    for line in lines {
+        if line == section_footer {
+            return Some(rows);
+        }
+        rows.push(line.to_string());
     }
+    // no footer
+    None
 }
 
 struct LinePairReader {
-    val: Lines<BufReader<Decoder<Cursor<&'static [u8]>>>>,
+    val: Box<dyn Iterator<Item = String>>,
 }
 
 impl Iterator for LinePairReader {
     type Item = [String; 2];
     fn next(&mut self) -> Option<Self::Item> {
         if let (Some(l1), Some(l2)) = (self.val.next(), self.val.next()) {
-            Some([l1.unwrap(), l2.unwrap()])
+            Some([l1, l2])
         } else {
             None
         }
@@ -112,9 +290,13 @@ fn read_deflated_zonefiles(
     let cursor = io::Cursor::new(deflate_bytes);
     let deflate_decoder = deflate::Decoder::new(cursor);
     let buff_reader = BufReader::new(deflate_decoder);
-    let pairs = LinePairReader {
-        val: buff_reader.lines(),
-    };
+    read_zonefiles(Box::new(buff_reader.lines().map(|line| line.unwrap())))
+}
+
+fn read_zonefiles(
+    lines: Box<dyn Iterator<Item = String>>,
+) -> Box<dyn Iterator<Item = GenesisZonefile>> {
+    let pairs = LinePairReader { val: lines };
     let pair_iter = pairs.into_iter().map(|pair| GenesisZonefile {
         zonefile_hash: pair[0].to_owned(),
         zonefile_content: pair[1].replace("\\n", "\n"),
@@ -133,16 +315,20 @@ fn iter_deflated_csv(deflate_bytes: &'static [u8]) -> Box<dyn Iterator<Item = Ve
     return Box::new(line_iter);
 }
 
-fn read_balances(deflate_bytes: &'static [u8]) -> Box<dyn Iterator<Item = GenesisAccountBalance>> {
-    let balances = iter_deflated_csv(deflate_bytes).map(|cols| GenesisAccountBalance {
+fn read_balances(
+    rows: Box<dyn Iterator<Item = Vec<String>>>,
+) -> Box<dyn Iterator<Item = GenesisAccountBalance>> {
+    let balances = rows.map(|cols| GenesisAccountBalance {
         address: cols[0].to_string(),
         amount: cols[1].parse::<u64>().unwrap(),
     });
     return Box::new(balances);
 }
 
-fn read_lockups(deflate_bytes: &'static [u8]) -> Box<dyn Iterator<Item = GenesisAccountLockup>> {
-    let lockups = iter_deflated_csv(deflate_bytes).map(|cols| GenesisAccountLockup {
+fn read_lockups(
+    rows: Box<dyn Iterator<Item = Vec<String>>>,
+) -> Box<dyn Iterator<Item = GenesisAccountLockup>> {
+    let lockups = rows.map(|cols| GenesisAccountLockup {
         address: cols[0].to_string(),
         amount: cols[1].parse::<u64>().unwrap(),
         block_height: cols[2].parse::<u64>().unwrap(),
@@ -150,8 +336,10 @@ fn read_lockups(deflate_bytes: &'static [u8]) -> Box<dyn Iterator<Item = Genesis
     return Box::new(lockups);
 }
 
-fn read_namespaces(deflate_bytes: &'static [u8]) -> Box<dyn Iterator<Item = GenesisNamespace>> {
-    let namespaces = iter_deflated_csv(deflate_bytes).map(|cols| GenesisNamespace {
+fn read_namespaces(
+    rows: Box<dyn Iterator<Item = Vec<String>>>,
+) -> Box<dyn Iterator<Item = GenesisNamespace>> {
+    let namespaces = rows.map(|cols| GenesisNamespace {
         namespace_id: cols[0].to_string(),
         importer: cols[1].to_string(),
         buckets: cols[2].to_string(),
@@ -164,8 +352,10 @@ fn read_namespaces(deflate_bytes: &'static [u8]) -> Box<dyn Iterator<Item = Gene
     return Box::new(namespaces);
 }
 
-fn read_names(deflate_bytes: &'static [u8]) -> Box<dyn Iterator<Item = GenesisName>> {
-    let names = iter_deflated_csv(deflate_bytes).map(|cols| GenesisName {
+fn read_names(
+    rows: Box<dyn Iterator<Item = Vec<String>>>,
+) -> Box<dyn Iterator<Item = GenesisName>> {
+    let names = rows.map(|cols| GenesisName {
         fully_qualified_name: cols[0].to_string(),
         owner: cols[1].to_string(),
         zonefile_hash: cols[2].to_string(),
@@ -219,6 +409,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_custom_genesis_files() {
+        let dir = std::env::temp_dir().join(format!("stx-genesis-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let copy_with_hash = |src: &str| {
+            let dest = dir.join(src);
+            fs::copy(src, &dest).unwrap();
+            fs::copy(
+                format!("{}.sha256", src),
+                dir.join(format!("{}.sha256", src)),
+            )
+            .unwrap();
+            dest.to_str().unwrap().to_string()
+        };
+        let chainstate_path = copy_with_hash("chainstate-test.txt");
+        let zonefiles_path = copy_with_hash("name_zonefiles-test.txt");
+
+        let embedded = GenesisData::new(true);
+        let custom = GenesisData::from_files(&chainstate_path, Some(&zonefiles_path)).unwrap();
+        assert_eq!(
+            custom
+                .read_balances()
+                .map(|b| (b.address, b.amount))
+                .collect::<Vec<_>>(),
+            embedded
+                .read_balances()
+                .map(|b| (b.address, b.amount))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            custom
+                .read_lockups()
+                .map(|l| (l.address, l.amount, l.block_height))
+                .collect::<Vec<_>>(),
+            embedded
+                .read_lockups()
+                .map(|l| (l.address, l.amount, l.block_height))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            custom.read_namespaces().count(),
+            embedded.read_namespaces().count()
+        );
+        assert_eq!(
+            custom
+                .read_names()
+                .map(|n| n.fully_qualified_name)
+                .collect::<Vec<_>>(),
+            embedded
+                .read_names()
+                .map(|n| n.fully_qualified_name)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            custom
+                .read_name_zonefiles()
+                .map(|z| z.zonefile_content)
+                .collect::<Vec<_>>(),
+            embedded
+                .read_name_zonefiles()
+                .map(|z| z.zonefile_content)
+                .collect::<Vec<_>>()
+        );
+
+        // without a zonefiles file, there are no zonefiles
+        let custom = GenesisData::from_files(&chainstate_path, None).unwrap();
+        assert_eq!(custom.read_name_zonefiles().count(), 0);
+
+        // the declared hash must match
+        let mut contents = fs::read_to_string(&chainstate_path).unwrap();
+        contents.push_str("\n");
+        fs::write(&chainstate_path, &contents).unwrap();
+        assert!(GenesisData::from_files(&chainstate_path, None).is_err());
+
+        // and so must the format
+        let bad_contents = contents.replacen("-----END NAMES-----", "", 1);
+        fs::write(&chainstate_path, &bad_contents).unwrap();
+        fs::write(
+            format!("{}.sha256", &chainstate_path),
+            encode_hex(&Sha256::digest(bad_contents.as_bytes())),
+        )
+        .unwrap();
+        assert!(GenesisData::from_files(&chainstate_path, None).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_name_zonefiles_read() {
         for zonefile in GenesisData::new(false).read_name_zonefiles() {
@@ -15,6 +15,7 @@ use serde_json::value::RawValue;
 
 use std::cmp;
 
+use super::super::config::BitcoindEndpoint;
 use super::super::operations::BurnchainOpSigner;
 use super::super::Config;
 use super::{BurnchainController, BurnchainTip, Error as BurnchainControllerError};
@@ -24,6 +25,7 @@ use stacks::burnchains::bitcoin::indexer::{
 };
 use stacks::burnchains::bitcoin::spv::SpvClient;
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
+use stacks::burnchains::bitcoin::Error as btc_error;
 use stacks::burnchains::db::BurnchainDB;
 use stacks::burnchains::indexer::BurnchainIndexer;
 use stacks::burnchains::BurnchainStateTransitionOps;
@@ -36,6 +38,7 @@ use stacks::burnchains::{
 };
 use stacks::burnchains::{Burnchain, BurnchainParameters};
 use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::burn::operations::leader_block_commit::BURN_BLOCK_MINED_AT_MODULUS;
 use stacks::chainstate::burn::operations::{
     BlockstackOperationType, LeaderBlockCommitOp, LeaderKeyRegisterOp, PreStxOp, TransferStxOp,
     UserBurnSupportOp,
@@ -65,6 +68,49 @@ use stacks::types::chainstate::BurnchainHeaderHash;
 const UTXO_CACHE_STALENESS_LIMIT: u64 = 6;
 const DUST_UTXO_LIMIT: u64 = 5500;
 
+/// The redeem script of a P2SH-wrapped P2WPKH output: a version-0 witness program of the public
+/// key hash
+fn p2wpkh_redeem_script(pubkey_hash: &Hash160) -> Script {
+    Builder::new()
+        .push_int(0)
+        .push_slice(pubkey_hash.as_bytes())
+        .into_script()
+}
+
+/// The Bitcoin address at which the miner's UTXOs are held: the P2PKH address of its public key
+/// or, with `burnchain.segwit`, the P2SH-wrapped P2WPKH address of its compressed public key.
+pub fn get_miner_bitcoin_address(
+    config: &Config,
+    public_key: &Secp256k1PublicKey,
+) -> BitcoinAddress {
+    let (_, network_id) = config.burnchain.get_bitcoin_network();
+    if config.burnchain.segwit {
+        let pubkey_hash = Hash160::from_data(&public_key.to_bytes_compressed());
+        let script_hash = Hash160::from_data(&p2wpkh_redeem_script(&pubkey_hash).to_bytes());
+        BitcoinAddress::from_bytes(
+            network_id,
+            BitcoinAddressType::ScriptHash,
+            script_hash.as_bytes(),
+        )
+        .expect("Public key incorrect")
+    } else {
+        let pubkey_hash = Hash160::from_data(&public_key.to_bytes());
+        BitcoinAddress::from_bytes(
+            network_id,
+            BitcoinAddressType::PublicKeyHash,
+            pubkey_hash.as_bytes(),
+        )
+        .expect("Public key incorrect")
+    }
+}
+
+/// Size of a transaction in virtual bytes (its BIP141 weight divided by 4, rounded up), which is
+/// what its fee rate is measured against.  For a transaction without segwit inputs, this is its
+/// serialized size.
+fn get_tx_vsize(tx: &Transaction) -> u64 {
+    (tx.get_weight() + 3) / 4
+}
+
 pub struct BitcoinRegtestController {
     config: Config,
     indexer_config: BitcoinIndexerConfig,
@@ -180,6 +226,12 @@ impl LeaderBlockCommitFees {
         cmp::max(self.final_size, self.default_tx_size)
     }
 
+    /// Pay at least the going fee rate, up to the configured ceiling
+    pub fn track_fee_market(&mut self, market_fee_rate: u64, config: &Config) {
+        let fee_rate = cmp::min(market_fee_rate, config.burnchain.get_max_fee_rate());
+        self.fee_rate = cmp::max(self.fee_rate, fee_rate);
+    }
+
     pub fn register_replacement(&mut self, tx_size: u64) {
         let new_size = cmp::max(tx_size, self.final_size);
         if self.is_rbf_enabled {
@@ -321,13 +373,25 @@ impl BitcoinRegtestController {
         }
     }
 
-    fn setup_indexer_runtime(&self) -> (Burnchain, BitcoinIndexer) {
+    /// Make an indexer that talks to the given Bitcoin node
+    fn make_indexer(&self, endpoint: BitcoindEndpoint) -> BitcoinIndexer {
         let (_, network_type) = self.config.burnchain.get_bitcoin_network();
         let indexer_runtime = BitcoinIndexerRuntime::new(network_type);
-        let burnchain_indexer = BitcoinIndexer {
-            config: self.indexer_config.clone(),
+        let mut indexer_config = self.indexer_config.clone();
+        indexer_config.peer_host = endpoint.peer_host;
+        indexer_config.peer_port = endpoint.peer_port;
+        indexer_config.rpc_port = endpoint.rpc_port;
+        indexer_config.rpc_ssl = endpoint.rpc_ssl;
+        indexer_config.username = endpoint.username;
+        indexer_config.password = endpoint.password;
+        BitcoinIndexer {
+            config: indexer_config,
             runtime: indexer_runtime,
-        };
+        }
+    }
+
+    fn setup_indexer_runtime(&self) -> (Burnchain, BitcoinIndexer) {
+        let burnchain_indexer = self.make_indexer(self.config.burnchain.get_active_endpoint());
         (self.get_burnchain(), burnchain_indexer)
     }
 
@@ -390,6 +454,62 @@ impl BitcoinRegtestController {
         rest
     }
 
+    /// Ask a Bitcoin peer for its block height with a peer-to-peer handshake
+    fn get_peer_block_height(&self, endpoint: &BitcoindEndpoint) -> Result<u64, btc_error> {
+        let mut indexer = self.make_indexer(endpoint.clone());
+        indexer.connect().map_err(|_| btc_error::ConnectionError)?;
+        indexer.peer_handshake()
+    }
+
+    /// If more than one bitcoind node is configured, ask each of them for its block height, and
+    /// fail over to another one if the node in use is unreachable or has fallen too far behind
+    /// (or switch back to a more preferred one once it is healthy again).  Returns true if the
+    /// node in use changed.
+    fn check_bitcoind_endpoints(&mut self) -> bool {
+        let endpoints = self.config.burnchain.get_endpoints();
+        if endpoints.len() < 2 {
+            return false;
+        }
+        let heights: Vec<_> = endpoints
+            .iter()
+            .map(|endpoint| {
+                let height_res = if self.config.burnchain.spv_only {
+                    self.get_peer_block_height(endpoint)
+                        .map_err(|e| format!("{:?}", &e))
+                } else {
+                    BitcoinRPCRequest::get_block_count(endpoint).map_err(|e| format!("{:?}", &e))
+                };
+                match height_res {
+                    Ok(height) => Some(height),
+                    Err(e) => {
+                        warn!(
+                            "Failed to query Bitcoin node {}:{}: {}",
+                            &endpoint.peer_host, endpoint.peer_port, &e
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let active = self.config.burnchain.get_active_endpoint_index();
+        let chosen = self.config.burnchain.choose_endpoint(&heights);
+        if chosen == active {
+            return false;
+        }
+        info!(
+            "Switching Bitcoin node from {}:{} (height {:?}) to {}:{} (height {:?})",
+            &endpoints[active].peer_host,
+            endpoints[active].peer_port,
+            heights[active],
+            &endpoints[chosen].peer_host,
+            endpoints[chosen].peer_port,
+            heights[chosen]
+        );
+        self.config.burnchain.set_active_endpoint_index(chosen);
+        true
+    }
+
     fn receive_blocks(
         &mut self,
         block_for_sortitions: bool,
@@ -405,6 +525,7 @@ impl BitcoinRegtestController {
             }
         };
 
+        self.check_bitcoind_endpoints();
         let (mut burnchain, mut burnchain_indexer) = self.setup_indexer_runtime();
         let (block_snapshot, burnchain_height, state_transition) = loop {
             if !self.should_keep_running() {
@@ -462,8 +583,14 @@ impl BitcoinRegtestController {
                             continue;
                         }
                         _ => {
-                            // delay and try again
+                            // delay and try again, on another bitcoind node if this one has
+                            // become unreachable
                             sleep_ms(5000);
+                            if self.check_bitcoind_endpoints() {
+                                let (new_burnchain, new_indexer) = self.setup_indexer_runtime();
+                                burnchain = new_burnchain;
+                                burnchain_indexer = new_indexer;
+                            }
                             continue;
                         }
                     }
@@ -493,13 +620,7 @@ impl BitcoinRegtestController {
     #[cfg(test)]
     pub fn get_all_utxos(&self, public_key: &Secp256k1PublicKey) -> Vec<UTXO> {
         // Configure UTXO filter
-        let pkh = Hash160::from_data(&public_key.to_bytes())
-            .to_bytes()
-            .to_vec();
-        let (_, network_id) = self.config.burnchain.get_bitcoin_network();
-        let address =
-            BitcoinAddress::from_bytes(network_id, BitcoinAddressType::PublicKeyHash, &pkh)
-                .expect("Public key incorrect");
+        let address = get_miner_bitcoin_address(&self.config, public_key);
         let filter_addresses = vec![address.to_b58()];
         let _result = BitcoinRPCRequest::import_public_key(&self.config, &public_key);
 
@@ -599,13 +720,7 @@ impl BitcoinRegtestController {
         }
 
         // Configure UTXO filter
-        let pkh = Hash160::from_data(&public_key.to_bytes())
-            .to_bytes()
-            .to_vec();
-        let (_, network_id) = self.config.burnchain.get_bitcoin_network();
-        let address =
-            BitcoinAddress::from_bytes(network_id, BitcoinAddressType::PublicKeyHash, &pkh)
-                .expect("Public key incorrect");
+        let address = get_miner_bitcoin_address(&self.config, public_key);
         let filter_addresses = vec![address.to_b58()];
 
         let mut utxos = loop {
@@ -939,6 +1054,9 @@ impl BitcoinRegtestController {
             Some(fees) => fees.fees_from_previous_tx(&payload, &self.config),
             None => LeaderBlockCommitFees::estimated_fees_from_payload(&payload, &self.config),
         };
+        if let Some(market_fee_rate) = self.get_market_fee_rate() {
+            estimated_fees.track_fee_market(market_fee_rate, &self.config);
+        }
 
         let public_key = signer.get_public_key();
         let (mut tx, mut utxos) = self.prepare_tx(
@@ -986,9 +1104,7 @@ impl BitcoinRegtestController {
             signer,
         )?;
 
-        let serialized_tx = SerializedTx::new(tx.clone());
-
-        let tx_size = serialized_tx.bytes.len() as u64;
+        let tx_size = get_tx_vsize(&tx);
         estimated_fees.register_replacement(tx_size);
         let mut txid = tx.txid().as_bytes().to_vec();
         txid.reverse();
@@ -1078,9 +1194,7 @@ impl BitcoinRegtestController {
         }
 
         // Stop as soon as the fee_rate is ${self.config.burnchain.max_rbf} percent higher, stop RBF
-        if ongoing_op.fees.fee_rate
-            > (self.config.burnchain.satoshis_per_byte * self.config.burnchain.max_rbf / 100)
-        {
+        if ongoing_op.fees.fee_rate > self.config.burnchain.get_max_fee_rate() {
             warn!(
                 "RBF'd block commits reached {}% satoshi per byte fee rate, not resubmitting",
                 self.config.burnchain.max_rbf
@@ -1136,6 +1250,114 @@ impl BitcoinRegtestController {
         res
     }
 
+    /// The fee rate, in satoshis per byte, that bitcoind expects to get a transaction confirmed
+    /// within the configured number of blocks.  None if fee estimation is disabled or bitcoind
+    /// has no estimate.
+    fn get_market_fee_rate(&self) -> Option<u64> {
+        let target = self.config.burnchain.fee_estimate_target_blocks;
+        if target == 0 {
+            return None;
+        }
+        match BitcoinRPCRequest::estimate_smart_fee(&self.config, target) {
+            Ok(fee_rate) => fee_rate,
+            Err(e) => {
+                warn!("Bitcoin RPC failure: error estimating fee rate {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Replace our in-flight block-commit by fee if the burnchain fee market has moved past the
+    /// fee rate it pays.  The replacement carries the very same commit payload, so its consensus
+    /// fields are unchanged, and spends the same UTXOs.  This only happens while the commit can
+    /// still be mined in the next burnchain block; once the burnchain advances, the miner builds
+    /// a new commit instead.  Returns true if a replacement was sent.
+    pub fn replace_block_commit_by_fee(&mut self, signer: &mut BurnchainOpSigner) -> bool {
+        let market_fee_rate = match self.get_market_fee_rate() {
+            Some(fee_rate) => fee_rate,
+            None => return false,
+        };
+        let ongoing_op = match self.ongoing_block_commit.take() {
+            Some(op) => op,
+            None => return false,
+        };
+        if market_fee_rate <= ongoing_op.fees.fee_rate {
+            self.ongoing_block_commit = Some(ongoing_op);
+            return false;
+        }
+        if ongoing_op.fees.fee_rate >= self.config.burnchain.get_max_fee_rate() {
+            debug!(
+                "Block-commit fee rate is already at the {}% ceiling, not replacing it",
+                self.config.burnchain.max_rbf
+            );
+            self.ongoing_block_commit = Some(ongoing_op);
+            return false;
+        }
+
+        let _ = self.sortdb_mut();
+        let burnchain_db = self.burnchain_db.as_ref().expect("BurnchainDB not opened");
+        if ongoing_op
+            .txids
+            .iter()
+            .any(|txid| burnchain_db.get_burnchain_op(txid).is_some())
+        {
+            // already mined
+            self.ongoing_block_commit = Some(ongoing_op);
+            return false;
+        }
+
+        // the commit only counts if it is mined in the block after the one it was made in
+        let burn_chain_tip = match burnchain_db.get_canonical_chain_tip() {
+            Ok(tip) => tip,
+            Err(e) => {
+                warn!("Failed to load the burnchain tip: {:?}", &e);
+                self.ongoing_block_commit = Some(ongoing_op);
+                return false;
+            }
+        };
+        if (burn_chain_tip.block_height % BURN_BLOCK_MINED_AT_MODULUS) as u8
+            != ongoing_op.payload.burn_parent_modulus
+        {
+            debug!("Burnchain has advanced since our block-commit was made, not replacing it");
+            self.ongoing_block_commit = Some(ongoing_op);
+            return false;
+        }
+
+        let mut fees = ongoing_op
+            .fees
+            .fees_from_previous_tx(&ongoing_op.payload, &self.config);
+        fees.track_fee_market(market_fee_rate, &self.config);
+        if fees.estimated_amount_required() > ongoing_op.sum_utxos() {
+            info!(
+                "Not enough funds in the UTXOs of our block-commit to replace it at fee rate {}",
+                fees.fee_rate
+            );
+            self.ongoing_block_commit = Some(ongoing_op);
+            return false;
+        }
+
+        info!(
+            "Burnchain fee rate rose to {} sat/vB; replacing our block-commit by fee",
+            market_fee_rate;
+            "previous_fee_rate" => ongoing_op.fees.fee_rate
+        );
+        let tx = self.send_block_commit_operation(
+            ongoing_op.payload.clone(),
+            signer,
+            Some(ongoing_op.utxos.clone()),
+            None,
+            Some(ongoing_op.fees.clone()),
+            &ongoing_op.txids,
+        );
+        match tx {
+            Some(tx) => self.send_transaction(SerializedTx::new(tx)),
+            None => {
+                self.ongoing_block_commit = Some(ongoing_op);
+                false
+            }
+        }
+    }
+
     fn prepare_tx(
         &mut self,
         public_key: &Secp256k1PublicKey,
@@ -1210,8 +1432,7 @@ impl BitcoinRegtestController {
                 &mut utxos_cloned,
                 signer,
             );
-            let serialized_tx = SerializedTx::new(tx_cloned);
-            cmp::max(min_tx_size, serialized_tx.bytes.len() as u64)
+            cmp::max(min_tx_size, get_tx_vsize(&tx_cloned))
         };
 
         let rbf_fee = if spent_in_rbf == 0 {
@@ -1260,20 +1481,32 @@ impl BitcoinRegtestController {
         }
 
         // Append the change output
-        let change_address_hash = Hash160::from_data(&public_key.to_bytes());
+        let change_address = get_miner_bitcoin_address(&self.config, &public_key);
         let value = total_consumed - total_to_spend;
         debug!(
             "Payments value: {:?}, total_consumed: {:?}, total_spent: {:?}",
             value, total_consumed, total_to_spend
         );
         if value >= DUST_UTXO_LIMIT {
-            let change_output = BitcoinAddress::to_p2pkh_tx_out(&change_address_hash, value);
+            let change_output = match change_address.addrtype {
+                BitcoinAddressType::ScriptHash => {
+                    BitcoinAddress::to_p2sh_tx_out(&change_address.bytes, value)
+                }
+                BitcoinAddressType::PublicKeyHash => {
+                    BitcoinAddress::to_p2pkh_tx_out(&change_address.bytes, value)
+                }
+            };
             tx.output.push(change_output);
         } else {
             // Instead of leaving that change to the BTC miner, we could / should bump the sortition fee
             debug!("Not enough change to clear dust limit. Not adding change address.");
         }
 
+        if self.config.burnchain.segwit {
+            BitcoinRegtestController::sign_segwit_inputs(tx, utxos_set, signer);
+            return true;
+        }
+
         for (i, utxo) in utxos_set.utxos.iter().enumerate() {
             let input = TxIn {
                 previous_output: OutPoint {
@@ -1309,6 +1542,55 @@ impl BitcoinRegtestController {
         true
     }
 
+    /// Spend each of the given UTXOs, which are held at the miner's P2SH-wrapped P2WPKH address.
+    /// Unlike legacy inputs, each input's BIP143 signature hash commits to all of the inputs, so
+    /// they are all added before any of them is signed.
+    fn sign_segwit_inputs(
+        tx: &mut Transaction,
+        utxos_set: &UTXOSet,
+        signer: &mut BurnchainOpSigner,
+    ) {
+        let public_key_bytes = signer.get_public_key().to_bytes_compressed();
+        let pubkey_hash = Hash160::from_data(&public_key_bytes);
+        let redeem_script = p2wpkh_redeem_script(&pubkey_hash);
+        let script_code = BitcoinAddress::to_p2pkh_tx_out(&pubkey_hash, 0).script_pubkey;
+
+        for utxo in utxos_set.utxos.iter() {
+            tx.input.push(TxIn {
+                previous_output: OutPoint {
+                    txid: utxo.txid,
+                    vout: utxo.vout,
+                },
+                script_sig: Builder::new()
+                    .push_slice(&redeem_script.to_bytes())
+                    .into_script(),
+                sequence: 0xFFFFFFFD, // allow RBF
+                witness: vec![],
+            });
+        }
+
+        for (i, utxo) in utxos_set.utxos.iter().enumerate() {
+            let sig_hash_all = 0x01;
+            let sig_hash = tx.segwit_signature_hash(i, &script_code, utxo.amount, sig_hash_all);
+
+            let sig1_der = {
+                let message = signer
+                    .sign_message(sig_hash.as_bytes())
+                    .expect("Unable to sign message");
+                message
+                    .to_secp256k1_recoverable()
+                    .expect("Unable to get recoverable signature")
+                    .to_standard()
+                    .serialize_der()
+            };
+
+            tx.input[i].witness = vec![
+                [&*sig1_der, &[sig_hash_all as u8][..]].concat(),
+                public_key_bytes.clone(),
+            ];
+        }
+    }
+
     fn build_user_burn_support_tx(
         &mut self,
         _payload: UserBurnSupportOp,
@@ -1384,15 +1666,13 @@ impl BitcoinRegtestController {
     pub fn build_next_block(&self, num_blocks: u64) {
         debug!("Generate {} block(s)", num_blocks);
         let public_key = match &self.config.burnchain.local_mining_public_key {
-            Some(public_key) => hex_bytes(public_key).expect("Invalid byte sequence"),
+            Some(public_key) => {
+                Secp256k1PublicKey::from_hex(public_key).expect("Invalid byte sequence")
+            }
             None => panic!("Unable to make new block, mining public key"),
         };
 
-        let pkh = Hash160::from_data(&public_key).to_bytes().to_vec();
-        let (_, network_id) = self.config.burnchain.get_bitcoin_network();
-        let address =
-            BitcoinAddress::from_bytes(network_id, BitcoinAddressType::PublicKeyHash, &pkh)
-                .expect("Public key incorrect");
+        let address = get_miner_bitcoin_address(&self.config, &public_key);
 
         let result =
             BitcoinRPCRequest::generate_to_address(&self.config, num_blocks, address.to_b58());
@@ -1562,17 +1842,11 @@ impl BurnchainController for BitcoinRegtestController {
     #[cfg(test)]
     fn bootstrap_chain(&mut self, num_blocks: u64) {
         if let Some(local_mining_pubkey) = &self.config.burnchain.local_mining_public_key {
-            let pk = hex_bytes(&local_mining_pubkey).expect("Invalid byte sequence");
-            let pkh = Hash160::from_data(&pk).to_bytes().to_vec();
-            let (_, network_id) = self.config.burnchain.get_bitcoin_network();
-            let address =
-                BitcoinAddress::from_bytes(network_id, BitcoinAddressType::PublicKeyHash, &pkh)
-                    .expect("Public key incorrect");
-
-            let _result = BitcoinRPCRequest::import_public_key(
-                &self.config,
-                &Secp256k1PublicKey::from_hex(local_mining_pubkey).unwrap(),
-            );
+            let public_key =
+                Secp256k1PublicKey::from_hex(local_mining_pubkey).expect("Invalid byte sequence");
+            let address = get_miner_bitcoin_address(&self.config, &public_key);
+
+            let _result = BitcoinRPCRequest::import_public_key(&self.config, &public_key);
 
             let result =
                 BitcoinRPCRequest::generate_to_address(&self.config, num_blocks, address.to_b58());
@@ -1740,19 +2014,19 @@ pub enum RPCError {
 type RPCResult<T> = Result<T, RPCError>;
 
 impl BitcoinRPCRequest {
-    fn build_rpc_request(config: &Config) -> Request {
+    fn build_rpc_request(endpoint: &BitcoindEndpoint) -> Request {
         let url = {
-            let url = config.burnchain.get_rpc_url();
+            let url = endpoint.get_rpc_url();
             Url::parse(&url).expect(&format!("Unable to parse {} as a URL", url))
         };
         debug!(
             "BitcoinRPC builder: {:?}:{:?}@{}",
-            &config.burnchain.username, &config.burnchain.password, &url
+            &endpoint.username, &endpoint.password, &url
         );
 
         let mut req = Request::new(Method::Post, url);
 
-        match (&config.burnchain.username, &config.burnchain.password) {
+        match (&endpoint.username, &endpoint.password) {
             (Some(username), Some(password)) => {
                 let auth_token = format!("Basic {}", encode(format!("{}:{}", username, password)));
                 req.append_header("Authorization", auth_token)
@@ -1913,17 +2187,39 @@ impl BitcoinRPCRequest {
         Ok(())
     }
 
+    /// Calls `estimatesmartfee`, and returns the estimated fee rate in satoshis per (virtual)
+    /// byte, if bitcoind has one
+    pub fn estimate_smart_fee(config: &Config, conf_target: u64) -> RPCResult<Option<u64>> {
+        let payload = BitcoinRPCRequest {
+            method: "estimatesmartfee".to_string(),
+            params: vec![conf_target.into()],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+
+        let res = BitcoinRPCRequest::send(&config, payload)?;
+        let fee_rate = match res.get("result").and_then(|result| result.get("feerate")) {
+            Some(fee_rate) => fee_rate,
+            None => return Ok(None),
+        };
+        // reported in BTC per 1000 bytes
+        let btc_per_kb = fee_rate
+            .as_f64()
+            .ok_or(RPCError::Parsing("Failed to parse fee rate".to_string()))?;
+        let sats_per_byte = (btc_per_kb * 100_000_000.0 / 1000.0).ceil() as u64;
+        Ok(Some(sats_per_byte))
+    }
+
     pub fn import_public_key(config: &Config, public_key: &Secp256k1PublicKey) -> RPCResult<()> {
         let rescan = true;
         let label = "";
 
-        let pkh = Hash160::from_data(&public_key.to_bytes())
-            .to_bytes()
-            .to_vec();
-        let (_, network_id) = config.burnchain.get_bitcoin_network();
-        let address =
-            BitcoinAddress::from_bytes(network_id, BitcoinAddressType::PublicKeyHash, &pkh)
-                .expect("Public key incorrect");
+        let address = get_miner_bitcoin_address(config, public_key);
+
+        // descriptor wallets (the default since bitcoind 23.0) do not support `importaddress`
+        if BitcoinRPCRequest::is_descriptor_wallet(config)? {
+            return BitcoinRPCRequest::import_address_descriptor(config, &address, label);
+        }
 
         let payload = BitcoinRPCRequest {
             method: "importaddress".to_string(),
@@ -1936,6 +2232,77 @@ impl BitcoinRPCRequest {
         Ok(())
     }
 
+    /// Calls `getblockcount` on one bitcoind node, without failing over to any other
+    pub fn get_block_count(endpoint: &BitcoindEndpoint) -> RPCResult<u64> {
+        let payload = BitcoinRPCRequest {
+            method: "getblockcount".to_string(),
+            params: vec![],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        let body = serde_json::to_vec(&json!(payload))
+            .map_err(|e| RPCError::Network(format!("RPC Error: {}", e)))?;
+
+        let res = BitcoinRPCRequest::send_to(endpoint, body)??;
+        res.get("result")
+            .and_then(|result| result.as_u64())
+            .ok_or(RPCError::Parsing("Failed to get block count".to_string()))
+    }
+
+    /// Calls `getwalletinfo`, and returns whether or not the wallet is a descriptor wallet
+    pub fn is_descriptor_wallet(config: &Config) -> RPCResult<bool> {
+        let payload = BitcoinRPCRequest {
+            method: "getwalletinfo".to_string(),
+            params: vec![],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+
+        let res = BitcoinRPCRequest::send(&config, payload)?;
+        // older versions of bitcoind do not report this, and only have legacy wallets
+        Ok(res
+            .get("result")
+            .and_then(|result| result.get("descriptors"))
+            .and_then(|descriptors| descriptors.as_bool())
+            .unwrap_or(false))
+    }
+
+    /// Watch an address in a descriptor wallet, by importing an `addr()` descriptor for it.  The
+    /// wallet is rescanned from genesis, like `importaddress` does.
+    pub fn import_address_descriptor(
+        config: &Config,
+        address: &BitcoinAddress,
+        label: &str,
+    ) -> RPCResult<()> {
+        // imported descriptors must carry their checksum
+        let payload = BitcoinRPCRequest {
+            method: "getdescriptorinfo".to_string(),
+            params: vec![format!("addr({})", address.to_b58()).into()],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        let res = BitcoinRPCRequest::send(&config, payload)?;
+        let descriptor = res
+            .get("result")
+            .and_then(|result| result.get("descriptor"))
+            .and_then(|descriptor| descriptor.as_str())
+            .ok_or(RPCError::Parsing("Failed to get descriptor".to_string()))?
+            .to_string();
+
+        let payload = BitcoinRPCRequest {
+            method: "importdescriptors".to_string(),
+            params: vec![json!([{
+                "desc": descriptor,
+                "timestamp": 0,
+                "label": label,
+            }])],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        BitcoinRPCRequest::send(&config, payload)?;
+        Ok(())
+    }
+
     /// Calls `listwallets` method through RPC call and returns wallet names as a vector of Strings
     pub fn list_wallets(config: &Config) -> RPCResult<Vec<String>> {
         let payload = BitcoinRPCRequest {
@@ -1987,8 +2354,15 @@ impl BitcoinRPCRequest {
         Ok(())
     }
 
+    /// Send a request to the bitcoind node in use.  If it cannot be reached, try each of the other
+    /// configured nodes in turn, and fail over to the first one that answers.
     fn send(config: &Config, payload: BitcoinRPCRequest) -> RPCResult<serde_json::Value> {
-        let mut request = BitcoinRPCRequest::build_rpc_request(&config);
+        if config.burnchain.spv_only {
+            return Err(RPCError::Network(format!(
+                "Bitcoin RPC: `{}` is unavailable with `spv_only`",
+                &payload.method
+            )));
+        }
 
         let body = match serde_json::to_vec(&json!(payload)) {
             Ok(body) => body,
@@ -1996,13 +2370,53 @@ impl BitcoinRPCRequest {
                 return Err(RPCError::Network(format!("RPC Error: {}", err)));
             }
         };
+
+        let endpoints = config.burnchain.get_endpoints();
+        let active = config.burnchain.get_active_endpoint_index();
+        let mut last_error = None;
+        for i in 0..endpoints.len() {
+            let index = (active + i) % endpoints.len();
+            match BitcoinRPCRequest::send_to(&endpoints[index], body.clone()) {
+                Ok(result) => {
+                    if index != active {
+                        info!(
+                            "Failing over to bitcoind at {}",
+                            endpoints[index].get_rpc_url()
+                        );
+                        config.burnchain.set_active_endpoint_index(index);
+                    }
+                    return result;
+                }
+                Err(e) => {
+                    if endpoints.len() > 1 {
+                        warn!(
+                            "Unable to reach bitcoind at {}: {:?}",
+                            endpoints[index].get_rpc_url(),
+                            &e
+                        );
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.expect("BUG: no bitcoind endpoints"))
+    }
+
+    /// Send a request body to one bitcoind node.  Fails outright if the node could not be
+    /// reached; otherwise, returns the outcome of the call.
+    fn send_to(
+        endpoint: &BitcoindEndpoint,
+        body: Vec<u8>,
+    ) -> Result<RPCResult<serde_json::Value>, RPCError> {
+        let mut request = BitcoinRPCRequest::build_rpc_request(endpoint);
         request
             .append_header("Content-Type", "application/json")
             .expect("Unable to set header");
         request.set_body(body);
 
+        let socket_addr = endpoint.get_rpc_socket_addr();
         let mut response = async_std::task::block_on(async move {
-            let stream = match TcpStream::connect(config.burnchain.get_rpc_socket_addr()).await {
+            let stream = match TcpStream::connect(socket_addr).await {
                 Ok(stream) => stream,
                 Err(err) => {
                     return Err(RPCError::Network(format!(
@@ -2033,7 +2447,7 @@ impl BitcoinRPCRequest {
         });
 
         if !status.is_success() {
-            return Err(RPCError::Network(format!(
+            return Ok(Err(RPCError::Network(format!(
                 "Bitcoin RPC: status({}) != success, body is '{:?}'",
                 status,
                 match serde_json::from_slice::<serde_json::Value>(&buffer[..]) {
@@ -2041,18 +2455,17 @@ impl BitcoinRPCRequest {
                     Err(_e) => serde_json::from_str("\"(unparseable)\"")
                         .expect("Failed to parse JSON literal"),
                 }
-            )));
+            ))));
         }
 
         if res.is_err() {
-            return Err(RPCError::Network(format!(
+            return Ok(Err(RPCError::Network(format!(
                 "Bitcoin RPC: unable to read body - {:?}",
                 res
-            )));
+            ))));
         }
 
-        let payload = serde_json::from_slice::<serde_json::Value>(&buffer[..])
-            .map_err(|e| RPCError::Parsing(format!("Bitcoin RPC: {}", e)))?;
-        Ok(payload)
+        Ok(serde_json::from_slice::<serde_json::Value>(&buffer[..])
+            .map_err(|e| RPCError::Parsing(format!("Bitcoin RPC: {}", e))))
     }
 }
@@ -0,0 +1,300 @@
+//! Subscriber for bitcoind's ZMQ `hashblock` notifications (`-zmqpubhashblock`).
+//!
+//! This speaks just enough of ZMTP 3.0 (the ZeroMQ wire protocol) to act as a SUB socket with
+//! the NULL security mechanism, so no native ZeroMQ library is needed.  Each notification wakes
+//! up the run loop so it syncs with the burnchain right away, instead of at the next poll.  If
+//! the subscription cannot be set up or breaks, the run loop keeps polling at
+//! `poll_time_secs` while the subscriber reconnects.
+
+use std::io::{Error as IOError, ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::Duration;
+
+use stacks::util::hash::to_hex;
+use stacks::util::sleep_ms;
+
+use crate::syncctl::PoxSyncWatchdogComms;
+
+/// Topic bitcoind publishes new block hashes under
+pub const ZMQ_HASHBLOCK_TOPIC: &[u8] = b"hashblock";
+
+/// How long to wait before trying to reconnect to bitcoind
+const ZMQ_RECONNECT_MS: u64 = 5000;
+/// Reconnect if nothing at all arrives for this long, in case the connection silently died
+const ZMQ_READ_TIMEOUT_SECS: u64 = 3600;
+
+const ZMTP_FLAG_MORE: u8 = 0x01;
+const ZMTP_FLAG_LONG: u8 = 0x02;
+const ZMTP_FLAG_COMMAND: u8 = 0x04;
+
+/// A single ZMTP frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZmtpFrame {
+    pub more: bool,
+    pub command: bool,
+    pub body: Vec<u8>,
+}
+
+impl ZmtpFrame {
+    pub fn read<R: Read>(fd: &mut R) -> Result<ZmtpFrame, IOError> {
+        let mut flags = [0u8; 1];
+        fd.read_exact(&mut flags)?;
+        let flags = flags[0];
+        let size = if flags & ZMTP_FLAG_LONG != 0 {
+            let mut size = [0u8; 8];
+            fd.read_exact(&mut size)?;
+            u64::from_be_bytes(size)
+        } else {
+            let mut size = [0u8; 1];
+            fd.read_exact(&mut size)?;
+            size[0] as u64
+        };
+        if size > 1024 * 1024 {
+            return Err(IOError::new(
+                ErrorKind::InvalidData,
+                format!("ZMQ frame too big ({} bytes)", size),
+            ));
+        }
+        let mut body = vec![0u8; size as usize];
+        fd.read_exact(&mut body)?;
+        Ok(ZmtpFrame {
+            more: flags & ZMTP_FLAG_MORE != 0,
+            command: flags & ZMTP_FLAG_COMMAND != 0,
+            body,
+        })
+    }
+
+    pub fn write<W: Write>(&self, fd: &mut W) -> Result<(), IOError> {
+        let mut flags = 0u8;
+        if self.more {
+            flags |= ZMTP_FLAG_MORE;
+        }
+        if self.command {
+            flags |= ZMTP_FLAG_COMMAND;
+        }
+        let mut bytes = vec![];
+        if self.body.len() > 255 {
+            bytes.push(flags | ZMTP_FLAG_LONG);
+            bytes.extend_from_slice(&(self.body.len() as u64).to_be_bytes());
+        } else {
+            bytes.push(flags);
+            bytes.push(self.body.len() as u8);
+        }
+        bytes.extend_from_slice(&self.body);
+        fd.write_all(&bytes)
+    }
+}
+
+/// The ZMTP 3.0 greeting for a client using the NULL mechanism
+fn zmtp_greeting() -> Vec<u8> {
+    let mut greeting = vec![0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0x7f, 3, 0];
+    let mut mechanism = [0u8; 20];
+    mechanism[0..4].copy_from_slice(b"NULL");
+    greeting.extend_from_slice(&mechanism);
+    // as-server, then filler
+    greeting.extend_from_slice(&[0u8; 32]);
+    greeting
+}
+
+/// The READY command announcing a SUB socket
+fn zmtp_ready_command() -> ZmtpFrame {
+    let mut body = vec![5];
+    body.extend_from_slice(b"READY");
+    body.push(11);
+    body.extend_from_slice(b"Socket-Type");
+    body.extend_from_slice(&3u32.to_be_bytes());
+    body.extend_from_slice(b"SUB");
+    ZmtpFrame {
+        more: false,
+        command: true,
+        body,
+    }
+}
+
+/// A ZMTP 3.0 subscription message for a topic
+fn zmtp_subscribe_message(topic: &[u8]) -> ZmtpFrame {
+    let mut body = vec![1];
+    body.extend_from_slice(topic);
+    ZmtpFrame {
+        more: false,
+        command: false,
+        body,
+    }
+}
+
+/// Read one whole (possibly multi-part) message, skipping any commands
+pub fn read_zmq_message<R: Read>(fd: &mut R) -> Result<Vec<Vec<u8>>, IOError> {
+    let mut parts = vec![];
+    loop {
+        let frame = ZmtpFrame::read(fd)?;
+        if frame.command {
+            continue;
+        }
+        parts.push(frame.body);
+        if !frame.more {
+            return Ok(parts);
+        }
+    }
+}
+
+/// Get the block hash out of a `hashblock` notification, if that's what the message is.
+/// bitcoind sends the topic, the hash (in the usual big-endian display order), and a sequence
+/// number.
+pub fn parse_hashblock_message(parts: &[Vec<u8>]) -> Option<[u8; 32]> {
+    if parts.len() < 2 || parts[0] != ZMQ_HASHBLOCK_TOPIC || parts[1].len() != 32 {
+        return None;
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&parts[1]);
+    Some(hash)
+}
+
+pub struct BitcoinZmqListener {
+    endpoint: String,
+    comms: PoxSyncWatchdogComms,
+    should_keep_running: Arc<AtomicBool>,
+}
+
+impl BitcoinZmqListener {
+    /// Subscribe to block notifications at `endpoint` (e.g. `tcp://127.0.0.1:28332`) on a
+    /// background thread, and tell the run loop about each one through `comms`.
+    pub fn spawn(
+        endpoint: String,
+        comms: PoxSyncWatchdogComms,
+        should_keep_running: Arc<AtomicBool>,
+    ) {
+        let mut listener = BitcoinZmqListener {
+            endpoint,
+            comms,
+            should_keep_running,
+        };
+        thread::Builder::new()
+            .name("bitcoin-zmq".to_string())
+            .spawn(move || listener.run())
+            .expect("FATAL: failed to start bitcoind ZMQ listener");
+    }
+
+    fn run(&mut self) {
+        info!(
+            "Subscribing to bitcoind block notifications at {}",
+            &self.endpoint
+        );
+        while self.should_keep_running.load(Ordering::SeqCst) {
+            if let Err(e) = self.listen() {
+                warn!(
+                    "Lost bitcoind block notifications from {} ({:?}); polling until reconnected",
+                    &self.endpoint, &e
+                );
+                sleep_ms(ZMQ_RECONNECT_MS);
+            }
+        }
+    }
+
+    fn connect(&self) -> Result<TcpStream, IOError> {
+        let host = self.endpoint.trim_start_matches("tcp://");
+        let addr = host.to_socket_addrs()?.next().ok_or(IOError::new(
+            ErrorKind::NotFound,
+            format!("Could not resolve {}", host),
+        ))?;
+        let mut stream = TcpStream::connect(addr)?;
+        stream.set_read_timeout(Some(Duration::from_secs(ZMQ_READ_TIMEOUT_SECS)))?;
+
+        stream.write_all(&zmtp_greeting())?;
+        let mut greeting = [0u8; 64];
+        stream.read_exact(&mut greeting)?;
+        if greeting[0] != 0xff || greeting[9] != 0x7f || greeting[10] < 3 {
+            return Err(IOError::new(
+                ErrorKind::InvalidData,
+                "Peer does not speak ZMTP 3",
+            ));
+        }
+
+        zmtp_ready_command().write(&mut stream)?;
+        let ready = ZmtpFrame::read(&mut stream)?;
+        if !ready.command || !ready.body.starts_with(b"\x05READY") {
+            return Err(IOError::new(
+                ErrorKind::InvalidData,
+                "Peer did not complete the ZMTP handshake",
+            ));
+        }
+
+        zmtp_subscribe_message(ZMQ_HASHBLOCK_TOPIC).write(&mut stream)?;
+        Ok(stream)
+    }
+
+    fn listen(&mut self) -> Result<(), IOError> {
+        let mut stream = self.connect()?;
+        debug!("Subscribed to bitcoind block notifications");
+        while self.should_keep_running.load(Ordering::SeqCst) {
+            let parts = read_zmq_message(&mut stream)?;
+            if let Some(hash) = parse_hashblock_message(&parts) {
+                debug!("bitcoind announced block {}", to_hex(&hash));
+                self.comms.notify_burnchain_block();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_zmtp_frames() {
+        let mut bytes = vec![];
+        zmtp_ready_command().write(&mut bytes).unwrap();
+        assert_eq!(&bytes[0..2], &[ZMTP_FLAG_COMMAND, 25]);
+
+        let long = ZmtpFrame {
+            more: true,
+            command: false,
+            body: vec![0xab; 300],
+        };
+        let mut long_bytes = vec![];
+        long.write(&mut long_bytes).unwrap();
+        assert_eq!(long_bytes[0], ZMTP_FLAG_MORE | ZMTP_FLAG_LONG);
+        assert_eq!(long_bytes.len(), 1 + 8 + 300);
+        assert_eq!(ZmtpFrame::read(&mut Cursor::new(long_bytes)).unwrap(), long);
+
+        assert_eq!(zmtp_greeting().len(), 64);
+    }
+
+    #[test]
+    fn test_parse_hashblock_message() {
+        // a stray command, then topic / hash / sequence number
+        let mut bytes = vec![];
+        zmtp_ready_command().write(&mut bytes).unwrap();
+        for (body, more) in vec![
+            (ZMQ_HASHBLOCK_TOPIC.to_vec(), true),
+            (vec![0x11; 32], true),
+            (vec![1, 0, 0, 0], false),
+        ] {
+            ZmtpFrame {
+                more,
+                command: false,
+                body,
+            }
+            .write(&mut bytes)
+            .unwrap();
+        }
+        let parts = read_zmq_message(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parse_hashblock_message(&parts), Some([0x11; 32]));
+
+        assert_eq!(
+            parse_hashblock_message(&[b"hashtx".to_vec(), vec![0x11; 32]]),
+            None
+        );
+        assert_eq!(
+            parse_hashblock_message(&[ZMQ_HASHBLOCK_TOPIC.to_vec()]),
+            None
+        );
+    }
+}
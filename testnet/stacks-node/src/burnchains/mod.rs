@@ -1,4 +1,5 @@
 pub mod bitcoin_regtest_controller;
+pub mod bitcoin_zmq;
 pub mod mocknet_controller;
 
 pub use self::bitcoin_regtest_controller::BitcoinRegtestController;
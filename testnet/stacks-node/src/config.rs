@@ -1,19 +1,36 @@
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::fs;
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use rand::RngCore;
 
+use stacks::burnchains::bitcoin::indexer::get_bitcoin_stacks_epochs;
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
 use stacks::burnchains::{MagicBytes, BLOCKSTACK_MAGIC_MAINNET};
-use stacks::chainstate::stacks::miner::BlockBuilderSettings;
+use stacks::chainstate::stacks::boot::STACKS_BOOT_CODE_TESTNET;
+use stacks::chainstate::stacks::miner::{BlockBuilderSettings, MicroblockPolicy};
 use stacks::chainstate::stacks::MAX_BLOCK_LEN;
-use stacks::core::mempool::MemPoolWalkSettings;
-use stacks::core::StacksEpoch;
+use stacks::core::mempool::{
+    MemPoolLimits, MemPoolWalkSettings, MEMPOOL_DEFAULT_MAX_BYTES_PER_ORIGIN,
+    MEMPOOL_DEFAULT_MAX_TXS, MEMPOOL_DEFAULT_MAX_TXS_PER_ORIGIN,
+    MEMPOOL_DEFAULT_REPLACE_BY_FEE_INCREMENT,
+};
+use stacks::core::mempool_policy::{
+    ContractCallPolicy, MaxCostPolicy, MaxTxSizePolicy, MemPoolAdmissionPolicy, MinFeeRatePolicy,
+};
+use stacks::core::mempool_walk::{
+    FeeRateWalkStrategy, FifoWalkStrategy, KnapsackWalkStrategy, MemPoolWalkStrategy,
+    KNAPSACK_WALK_CANDIDATES,
+};
+use stacks::core::{NetworkParameters, StacksEpoch, StacksEpochId};
 use stacks::core::{
-    CHAIN_ID_MAINNET, CHAIN_ID_TESTNET, PEER_VERSION_MAINNET, PEER_VERSION_TESTNET,
+    CHAIN_ID_MAINNET, CHAIN_ID_TESTNET, PEER_VERSION_EPOCH_2_0, PEER_VERSION_MAINNET,
+    PEER_VERSION_TESTNET, STACKS_EPOCH_MAX,
 };
+use stacks::cost_estimates::fee_medians::WeightedMedianFeeRateEstimator;
 use stacks::cost_estimates::fee_scalar::ScalarFeeRateEstimator;
 use stacks::cost_estimates::metrics::CostMetric;
 use stacks::cost_estimates::metrics::ProportionalDotProduct;
@@ -23,17 +40,23 @@ use stacks::cost_estimates::PessimisticEstimator;
 use stacks::net::connection::ConnectionOptions;
 use stacks::net::{Neighbor, NeighborKey, PeerAddress};
 use stacks::util::get_epoch_time_ms;
-use stacks::util::hash::hex_bytes;
+use stacks::util::hash::{hex_bytes, Sha256Sum};
 use stacks::util::secp256k1::Secp256k1PrivateKey;
 use stacks::util::secp256k1::Secp256k1PublicKey;
+use stacks::vm::costs::ExecutionCost;
 use stacks::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier};
+use stacks::vm::ContractName;
 
 const DEFAULT_SATS_PER_VB: u64 = 50;
 const DEFAULT_MAX_RBF_RATE: u64 = 150; // 1.5x
 const DEFAULT_RBF_FEE_RATE_INCREMENT: u64 = 5;
 const LEADER_KEY_TX_ESTIM_SIZE: u64 = 290;
 const BLOCK_COMMIT_TX_ESTIM_SIZE: u64 = 350;
+// spending a P2SH-wrapped P2WPKH input instead of a P2PKH one saves about 55 vbytes
+const SEGWIT_LEADER_KEY_TX_ESTIM_SIZE: u64 = 235;
+const SEGWIT_BLOCK_COMMIT_TX_ESTIM_SIZE: u64 = 295;
 const INV_REWARD_CYCLES_TESTNET: u64 = 6;
+const DEFAULT_FAILOVER_MAX_LAG: u64 = 2;
 
 #[derive(Clone, Deserialize, Default)]
 pub struct ConfigFile {
@@ -44,6 +67,9 @@ pub struct ConfigFile {
     pub connection_options: Option<ConnectionOptionsFile>,
     pub fee_estimation: Option<FeeEstimationConfigFile>,
     pub miner: Option<MinerConfigFile>,
+    pub mempool_policy: Option<MemPoolPolicyConfigFile>,
+    pub relay_policy: Option<RelayPolicyConfigFile>,
+    pub boot_contract: Option<Vec<BootContractFile>>,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -54,13 +80,14 @@ pub struct LegacyMstxConfigFile {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use stacks::core::STACKS_EPOCHS_REGTEST;
 
     #[test]
     fn should_load_legacy_mstx_balances_toml() {
         let config = ConfigFile::from_str(
             r#"
             [[ustx_balance]]
-            address = "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2"
+            address = "ST3EQ88S02BXXD0T5ZVT3KW947CRMQ1C6DMQY8H19"
             amount = 10000000000000000
 
             [[ustx_balance]]
@@ -83,7 +110,7 @@ mod tests {
         assert_eq!(balances.len(), 4);
         assert_eq!(
             balances[0].address,
-            "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2"
+            "ST3EQ88S02BXXD0T5ZVT3KW947CRMQ1C6DMQY8H19"
         );
         assert_eq!(
             balances[1].address,
@@ -98,6 +125,644 @@ mod tests {
             "ST2TFVBMRPS5SSNP98DQKQ5JNB2B6NZM91C4K3P7B"
         );
     }
+
+    #[test]
+    fn should_load_epoch_schedule_toml() {
+        let config = ConfigFile::from_str(
+            r#"
+            [burnchain]
+            mode = "krypton"
+
+            [[burnchain.epochs]]
+            epoch_name = "2.05"
+            start_height = 150
+
+            [[burnchain.epochs]]
+            epoch_name = "2.0"
+            start_height = 100
+            block_limit = { write_length = 1, write_count = 2, read_length = 3, read_count = 4, runtime = 5 }
+            "#,
+        );
+        let config = Config::from_config_file(config);
+        let epochs = config
+            .burnchain
+            .epochs
+            .expect("Failed to parse epochs from toml");
+        assert_eq!(epochs.len(), 3);
+        assert_eq!(epochs[0].epoch_id, StacksEpochId::Epoch10);
+        assert_eq!((epochs[0].start_height, epochs[0].end_height), (0, 100));
+        assert_eq!(epochs[1].epoch_id, StacksEpochId::Epoch20);
+        assert_eq!((epochs[1].start_height, epochs[1].end_height), (100, 150));
+        assert_eq!(epochs[1].block_limit.runtime, 5);
+        assert_eq!(epochs[2].epoch_id, StacksEpochId::Epoch2_05);
+        assert_eq!(
+            (epochs[2].start_height, epochs[2].end_height),
+            (150, STACKS_EPOCH_MAX)
+        );
+        assert_eq!(
+            epochs[2].block_limit,
+            StacksEpochId::Epoch2_05.default_block_limit()
+        );
+        assert_eq!(
+            epochs[2].network_epoch,
+            StacksEpochId::Epoch2_05.network_epoch()
+        );
+    }
+
+    #[test]
+    fn should_apply_block_limit_overrides() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            mode = "krypton"
+
+            [[burnchain.block_limits]]
+            epoch_name = "2.05"
+            runtime = 1000
+            write_count = 10
+            "#,
+        ));
+        let epochs = config.burnchain.epochs.unwrap();
+        let defaults = STACKS_EPOCHS_REGTEST.to_vec();
+        assert_eq!(epochs.len(), defaults.len());
+        for (epoch, default) in epochs.iter().zip(defaults.iter()) {
+            assert_eq!(epoch.start_height, default.start_height);
+            assert_eq!(epoch.end_height, default.end_height);
+            if epoch.epoch_id == StacksEpochId::Epoch2_05 {
+                assert_eq!(epoch.block_limit.runtime, 1000);
+                assert_eq!(epoch.block_limit.write_count, 10);
+                assert_eq!(epoch.block_limit.read_count, default.block_limit.read_count);
+            } else {
+                assert_eq!(epoch.block_limit, default.block_limit);
+            }
+        }
+
+        // overrides apply on top of a custom schedule too
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            mode = "krypton"
+
+            [[burnchain.epochs]]
+            epoch_name = "2.0"
+            start_height = 0
+
+            [[burnchain.block_limits]]
+            epoch_name = "2.0"
+            read_length = 5
+            "#,
+        ));
+        let epochs = config.burnchain.epochs.unwrap();
+        assert_eq!(epochs.len(), 2);
+        assert_eq!(epochs[1].block_limit.read_length, 5);
+
+        let mut epochs = STACKS_EPOCHS_REGTEST.to_vec();
+        epochs.retain(|epoch| epoch.epoch_id != StacksEpochId::Epoch2_05);
+        let overrides = vec![EpochBlockLimitConfigFile {
+            epoch_name: "2.05".to_string(),
+            runtime: Some(1),
+            ..EpochBlockLimitConfigFile::default()
+        }];
+        assert!(apply_block_limits(&mut epochs, &overrides).is_err());
+    }
+
+    #[test]
+    fn should_reject_bad_epoch_schedules() {
+        let epoch = |name: &str, start_height| StacksEpochConfigFile {
+            epoch_name: name.to_string(),
+            start_height,
+            block_limit: None,
+        };
+        assert!(make_epochs(&[]).is_err());
+        assert!(make_epochs(&[epoch("3.0", 0)]).is_err());
+        assert!(make_epochs(&[epoch("2.0", 10), epoch("2.0", 20)]).is_err());
+        // 2.05 can't activate before 2.0
+        assert!(make_epochs(&[epoch("2.0", 20), epoch("2.05", 10)]).is_err());
+        assert!(make_epochs(&[epoch("1.0", 5), epoch("2.0", 10)]).is_err());
+        assert!(make_epochs(&[epoch("2.0", 0), epoch("2.05", 0)]).is_ok());
+    }
+
+    #[test]
+    fn should_load_app_chain_network_parameters() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            mode = "krypton"
+            magic_bytes = "A1"
+            chain_id = 0x00c0ffee
+            peer_version = 0x0a000005
+
+            [[ustx_balance]]
+            address = "ST3EQ88S02BXXD0T5ZVT3KW947CRMQ1C6DMQY8H19"
+            amount = 10000000000000000
+            "#,
+        ));
+        let network = config.get_network_parameters();
+        assert_eq!(network.chain_id, 0x00c0ffee);
+        assert_eq!(network.peer_version, 0x0a000005);
+        assert_eq!(network.magic_bytes, MagicBytes::from("A1".as_bytes()));
+        assert!(!network.mainnet);
+        assert_eq!(config.burnchain.chain_id, 0x00c0ffee);
+        assert_eq!(config.burnchain.peer_version, 0x0a000005);
+
+        let default_network = Config::default().get_network_parameters();
+        assert_eq!(default_network.chain_id, CHAIN_ID_TESTNET);
+        assert_eq!(default_network.peer_version, PEER_VERSION_TESTNET);
+    }
+
+    #[test]
+    #[should_panic(expected = "reserved for a public network")]
+    fn should_reject_public_chain_id_for_app_chain() {
+        Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            mode = "krypton"
+            chain_id = 0x80000000
+            peer_version = 0x0a000005
+            "#,
+        ));
+    }
+
+    #[test]
+    fn should_allow_loopback_event_stream_without_token() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            event_stream_bind = "127.0.0.1:3701"
+            "#,
+        ));
+        assert_eq!(
+            config.node.event_stream_bind,
+            Some("127.0.0.1:3701".to_string())
+        );
+        assert!(config.node.event_stream_auth_token.is_none());
+
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            event_stream_bind = "0.0.0.0:3701"
+            event_stream_auth_token = "secret"
+            "#,
+        ));
+        assert_eq!(
+            config.node.event_stream_auth_token,
+            Some("secret".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a loopback address")]
+    fn should_reject_public_event_stream_without_token() {
+        Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            event_stream_bind = "0.0.0.0:3701"
+            "#,
+        ));
+    }
+
+    #[test]
+    fn should_default_to_scalar_fee_estimator() {
+        let config = FeeEstimationConfig::from(FeeEstimationConfigFile::default());
+        match config.fee_estimator {
+            Some(FeeEstimatorName::ScalarFeeRate) => {}
+            x => panic!("Expected the scalar fee rate estimator, got {:?}", x),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Bad fee_rate_window_size")]
+    fn should_reject_oversized_fee_rate_window() {
+        FeeEstimationConfig::from(FeeEstimationConfigFile {
+            fee_estimator: Some("weighted_median_fee_rate".to_string()),
+            fee_rate_window_size: Some(u64::from(u32::MAX) + 1),
+            ..FeeEstimationConfigFile::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Bad fee_rate_window_size")]
+    fn should_reject_empty_fee_rate_window() {
+        FeeEstimationConfig::from(FeeEstimationConfigFile {
+            fee_rate_window_size: Some(0),
+            ..FeeEstimationConfigFile::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Bad `ustx_balance` address")]
+    fn should_reject_mainnet_balance_address_on_testnet() {
+        Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            mode = "krypton"
+
+            [[ustx_balance]]
+            address = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7"
+            amount = 10000000000000000
+            "#,
+        ));
+    }
+
+    #[test]
+    fn should_load_boot_contracts() {
+        let dir = std::env::temp_dir().join(format!("boot-contracts-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app-chain-config.clar");
+        fs::write(&path, "(define-read-only (get-block-reward) u1000)").unwrap();
+
+        let config = Config::from_config_file(ConfigFile::from_str(&format!(
+            r#"
+            [burnchain]
+            mode = "krypton"
+
+            [[boot_contract]]
+            name = "app-chain-config"
+            path = "{}"
+            "#,
+            path.to_str().unwrap()
+        )));
+        assert_eq!(
+            config.boot_contracts,
+            vec![(
+                "app-chain-config".to_string(),
+                "(define-read-only (get-block-reward) u1000)".to_string()
+            )]
+        );
+        assert!(Config::default().boot_contracts.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate boot contract name")]
+    fn should_reject_boot_contract_shadowing_builtin() {
+        Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            mode = "krypton"
+
+            [[boot_contract]]
+            name = "pox"
+            path = "/dev/null"
+            "#,
+        ));
+    }
+
+    #[test]
+    fn should_parse_filtered_event_keys() {
+        let config = ConfigFile::from_str(
+            r#"
+            [[events_observer]]
+            endpoint = "localhost:3700"
+            events_keys = [
+                "ST000000000000000000002AMW42H.bns",
+                "ST000000000000000000002AMW42H.bns::print",
+                "ft_transfer_event",
+            ]
+            filter_transactions = true
+            "#,
+        );
+        let observers = config.events_observer.unwrap();
+        assert_eq!(observers[0].filter_transactions, Some(true));
+
+        let keys: Vec<_> = observers[0]
+            .events_keys
+            .iter()
+            .map(|key| EventKeyType::from_string(key).unwrap())
+            .collect();
+        match &keys[0] {
+            EventKeyType::ContractEvents(contract_identifier) => assert_eq!(
+                contract_identifier.to_string(),
+                "ST000000000000000000002AMW42H.bns"
+            ),
+            _ => panic!("Expected a contract events key"),
+        }
+        assert!(matches!(&keys[1], EventKeyType::SmartContractEvent(_)));
+        match &keys[2] {
+            EventKeyType::EventType(event_type) => assert_eq!(event_type, "ft_transfer_event"),
+            _ => panic!("Expected an event type key"),
+        }
+
+        assert!(EventKeyType::from_string("ft_unknown_event").is_none());
+        assert!(EventKeyType::from_string("not a contract.bns").is_none());
+    }
+
+    #[test]
+    fn should_parse_mempool_policy() {
+        let config_file = ConfigFile::from_str(
+            r#"
+            [mempool_policy]
+            min_fee_rate = 1.5
+            contract_deny_list = ["ST000000000000000000002AMW42H.bns"]
+            max_tx_runtime = 1000
+            "#,
+        );
+        let mempool_policy = MemPoolPolicyConfig::from(config_file.mempool_policy.unwrap());
+        assert_eq!(mempool_policy.min_fee_rate, Some(1.5));
+        assert!(mempool_policy.contract_allow_list.is_none());
+        assert_eq!(
+            mempool_policy.contract_deny_list[0].to_string(),
+            "ST000000000000000000002AMW42H.bns"
+        );
+        let max_tx_cost = mempool_policy.max_tx_cost.as_ref().unwrap();
+        assert_eq!(max_tx_cost.runtime, 1000);
+        assert_eq!(max_tx_cost.read_count, u64::MAX);
+
+        let config = Config {
+            mempool_policy,
+            ..Config::default()
+        };
+        assert_eq!(config.make_mempool_admission_policies().len(), 3);
+        assert!(Config::default()
+            .make_mempool_admission_policies()
+            .is_empty());
+    }
+
+    #[test]
+    fn should_parse_relay_policy() {
+        let config_file = ConfigFile::from_str(
+            r#"
+            [relay_policy]
+            min_fee_rate = 2.5
+            max_tx_size = 4096
+            contract_deny_list = ["ST000000000000000000002AMW42H.bns"]
+            "#,
+        );
+        let relay_policy = RelayPolicyConfig::from(config_file.relay_policy.unwrap());
+        assert_eq!(relay_policy.min_fee_rate, Some(2.5));
+        assert_eq!(relay_policy.max_tx_size, Some(4096));
+        assert_eq!(
+            relay_policy.contract_deny_list[0].to_string(),
+            "ST000000000000000000002AMW42H.bns"
+        );
+
+        let config = Config {
+            relay_policy,
+            ..Config::default()
+        };
+        assert_eq!(config.make_relay_policies().len(), 3);
+        assert!(Config::default().make_relay_policies().is_empty());
+    }
+
+    #[test]
+    fn should_parse_mempool_walk_strategy() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [miner]
+            mempool_walk_strategy = "knapsack"
+            "#,
+        ));
+        assert_eq!(
+            config.miner.mempool_walk_strategy,
+            MemPoolWalkStrategyName::Knapsack
+        );
+        assert_eq!(config.make_mempool_walk_strategy().name(), "Knapsack");
+        assert_eq!(
+            Config::default().make_mempool_walk_strategy().name(),
+            "FeeRate"
+        );
+    }
+
+    #[test]
+    fn should_load_miner_contract_lists() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [miner]
+            contract_deny_list = ["ST000000000000000000002AMW42H.bns"]
+            "#,
+        ));
+        let policy = config.make_miner_contract_call_policy().unwrap();
+        assert!(policy.allow_list.is_none());
+        assert_eq!(policy.deny_list.len(), 1);
+        assert_eq!(
+            config
+                .make_block_builder_settings(1)
+                .mempool_settings
+                .contract_call_policy,
+            Some(policy)
+        );
+
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [miner]
+            contract_allow_list = []
+            "#,
+        ));
+        assert_eq!(config.miner.contract_allow_list, Some(vec![]));
+        assert!(config.make_miner_contract_call_policy().is_some());
+        assert!(Config::default()
+            .make_miner_contract_call_policy()
+            .is_none());
+    }
+
+    #[test]
+    fn should_load_fee_estimate_target() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            satoshis_per_byte = 50
+            max_rbf = 150
+            fee_estimate_target_blocks = 2
+            "#,
+        ));
+        assert_eq!(config.burnchain.fee_estimate_target_blocks, 2);
+        assert_eq!(config.burnchain.get_max_fee_rate(), 75);
+        assert_eq!(Config::default().burnchain.fee_estimate_target_blocks, 0);
+    }
+
+    #[test]
+    fn should_load_failover_endpoints() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            peer_host = "127.0.0.1"
+            rpc_port = 18443
+            username = "user"
+            password = "pass"
+            failover_max_lag = 3
+
+            [[burnchain.failover_endpoints]]
+            peer_host = "127.0.0.2"
+
+            [[burnchain.failover_endpoints]]
+            peer_host = "127.0.0.3"
+            rpc_port = 8332
+            username = "other"
+            "#,
+        ));
+        let endpoints = config.burnchain.get_endpoints();
+        assert_eq!(endpoints.len(), 3);
+        assert_eq!(endpoints[1].get_rpc_url(), "http://127.0.0.2:18443");
+        assert_eq!(endpoints[1].username, Some("user".to_string()));
+        assert_eq!(endpoints[2].get_rpc_url(), "http://127.0.0.3:8332");
+        assert_eq!(endpoints[2].username, Some("other".to_string()));
+        assert_eq!(endpoints[2].password, Some("pass".to_string()));
+        assert_eq!(config.burnchain.get_rpc_url(), "http://127.0.0.1:18443");
+
+        // the most preferred node that is reachable and not too far behind
+        let choose = |heights: &[Option<u64>]| config.burnchain.choose_endpoint(heights);
+        assert_eq!(choose(&[Some(100), Some(101), Some(90)]), 0);
+        assert_eq!(choose(&[None, Some(101), Some(100)]), 1);
+        assert_eq!(choose(&[Some(97), None, Some(101)]), 2);
+        assert_eq!(choose(&[Some(98), Some(101), None]), 0);
+
+        // stays put if nothing answers, and every copy of the config sees a switch
+        let copy = config.clone();
+        config.burnchain.set_active_endpoint_index(2);
+        assert_eq!(choose(&[None, None, None]), 2);
+        assert_eq!(copy.burnchain.get_rpc_url(), "http://127.0.0.3:8332");
+    }
+
+    #[test]
+    fn should_load_spv_only() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            spv_only = true
+            "#,
+        ));
+        assert!(config.burnchain.spv_only);
+        assert!(!Config::default().burnchain.spv_only);
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_not_mine_spv_only() {
+        Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            miner = true
+            seed = "0000000000000000000000000000000000000000000000000000000000000000"
+
+            [burnchain]
+            spv_only = true
+            "#,
+        ));
+    }
+
+    #[test]
+    fn should_load_prune_depth() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            prune_depth = 1000
+            "#,
+        ));
+        assert_eq!(config.node.prune_depth, Some(1000));
+        assert!(config.connection_options.pruned);
+
+        let config = Config::default();
+        assert_eq!(config.node.prune_depth, None);
+        assert!(!config.connection_options.pruned);
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_not_mine_pruned() {
+        Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            miner = true
+            seed = "0000000000000000000000000000000000000000000000000000000000000000"
+            prune_depth = 1000
+            "#,
+        ));
+    }
+
+    #[test]
+    fn should_use_segwit_size_estimates() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            segwit = true
+            "#,
+        ));
+        assert!(config.burnchain.segwit);
+        assert_eq!(
+            config.burnchain.block_commit_tx_estimated_size,
+            SEGWIT_BLOCK_COMMIT_TX_ESTIM_SIZE
+        );
+        assert_eq!(
+            config.burnchain.leader_key_tx_estimated_size,
+            SEGWIT_LEADER_KEY_TX_ESTIM_SIZE
+        );
+
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            segwit = true
+            block_commit_tx_estimated_size = 400
+            "#,
+        ));
+        assert_eq!(config.burnchain.block_commit_tx_estimated_size, 400);
+        assert!(!Config::default().burnchain.segwit);
+    }
+
+    #[test]
+    fn should_compute_block_assembly_deadline() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            block_interval_secs = 600
+
+            [miner]
+            assembly_deadline_pct = 10
+            "#,
+        ));
+        assert_eq!(config.get_block_assembly_deadline(1000), Some(61_000));
+        assert_eq!(Config::default().get_block_assembly_deadline(1000), None);
+    }
+
+    #[test]
+    fn should_load_microblock_policy() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            microblock_frequency = 30000
+
+            [miner]
+            microblock_interval_ms = 5000
+            microblock_max_cost_pct = 25
+            microblock_min_tx_fee = 300
+            "#,
+        ));
+        assert_eq!(config.node.microblock_frequency, 5000);
+        assert_eq!(
+            config.make_block_builder_settings(2).microblock_policy,
+            MicroblockPolicy {
+                max_cost_pct: 25,
+                min_tx_fee: 300,
+            }
+        );
+
+        let default_policy = Config::default()
+            .make_block_builder_settings(2)
+            .microblock_policy;
+        assert_eq!(default_policy, MicroblockPolicy::unlimited());
+    }
+
+    #[test]
+    fn should_load_poison_microblock_reporter() {
+        let key_hex = "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01";
+        let config = Config::from_config_file(ConfigFile::from_str(&format!(
+            r#"
+            [node]
+            poison_microblock_reporter_key = "{}"
+            poison_microblock_report_fee = 2000
+            "#,
+            key_hex
+        )));
+        assert_eq!(
+            config.node.poison_microblock_reporter_key,
+            Some(Secp256k1PrivateKey::from_hex(key_hex).unwrap())
+        );
+        assert_eq!(config.node.poison_microblock_report_fee, 2000);
+
+        let config = Config::default();
+        assert_eq!(config.node.poison_microblock_reporter_key, None);
+        assert_eq!(config.node.poison_microblock_report_fee, 10_000);
+    }
 }
 
 impl ConfigFile {
@@ -137,7 +802,7 @@ impl ConfigFile {
 
         let balances = vec![
             InitialBalanceFile {
-                address: "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2".to_string(),
+                address: "ST3EQ88S02BXXD0T5ZVT3KW947CRMQ1C6DMQY8H19".to_string(),
                 amount: 10000000000000000,
             },
             InitialBalanceFile {
@@ -289,6 +954,10 @@ pub struct Config {
     pub connection_options: ConnectionOptions,
     pub miner: MinerConfig,
     pub estimation: FeeEstimationConfig,
+    pub mempool_policy: MemPoolPolicyConfig,
+    pub relay_policy: RelayPolicyConfig,
+    /// (name, source) of extra contracts to deploy into the boot address at genesis
+    pub boot_contracts: Vec<(String, String)>,
 }
 
 lazy_static! {
@@ -317,6 +986,7 @@ lazy_static! {
         download_interval: 10,          // how often, in seconds, we do a block download scan (should be less than inv_sync_interval)
         dns_timeout: 15_000,
         max_inflight_blocks: 6,
+        max_inflight_blocks_per_peer: 3,
         max_inflight_attachments: 6,
         .. std::default::Default::default()
     };
@@ -328,6 +998,16 @@ impl Config {
         let (mut node, bootstrap_node, deny_nodes) = match config_file.node {
             Some(node) => {
                 let rpc_bind = node.rpc_bind.unwrap_or(default_node_config.rpc_bind);
+                if let Some(ref event_stream_bind) = node.event_stream_bind {
+                    if node.event_stream_auth_token.is_none()
+                        && !is_loopback_address(event_stream_bind)
+                    {
+                        panic!(
+                            "`event_stream_bind` ({}) is not a loopback address; set `event_stream_auth_token` to stream events to other hosts",
+                            event_stream_bind
+                        );
+                    }
+                }
                 let node_config = NodeConfig {
                     name: node.name.unwrap_or(default_node_config.name),
                     seed: match node.seed {
@@ -371,6 +1051,54 @@ impl Config {
                         .pox_sync_sample_secs
                         .unwrap_or(default_node_config.pox_sync_sample_secs),
                     use_test_genesis_chainstate: node.use_test_genesis_chainstate,
+                    genesis_chainstate_file: node.genesis_chainstate_file,
+                    genesis_name_zonefiles_file: node.genesis_name_zonefiles_file,
+                    bootstrap_snapshot_url: node.bootstrap_snapshot_url,
+                    prune_depth: node.prune_depth,
+                    bootstrap_snapshot_digest: node.bootstrap_snapshot_digest.map(|digest_hex| {
+                        Sha256Sum::from_hex(&digest_hex).unwrap_or_else(|_| {
+                            panic!("Invalid `bootstrap_snapshot_digest`: {}", &digest_hex)
+                        })
+                    }),
+                    address_txs_index: node
+                        .address_txs_index
+                        .unwrap_or(default_node_config.address_txs_index),
+                    asset_holdings_index: node
+                        .asset_holdings_index
+                        .unwrap_or(default_node_config.asset_holdings_index),
+                    cost_breakdown: node
+                        .cost_breakdown
+                        .unwrap_or(default_node_config.cost_breakdown),
+                    event_stream_bind: node.event_stream_bind,
+                    event_stream_auth_token: node.event_stream_auth_token,
+                    event_queue: node.event_queue.unwrap_or(default_node_config.event_queue),
+                    event_replay_depth: node
+                        .event_replay_depth
+                        .unwrap_or(default_node_config.event_replay_depth),
+                    mempool_warm_restart: node
+                        .mempool_warm_restart
+                        .unwrap_or(default_node_config.mempool_warm_restart),
+                    mempool_replace_by_fee_increment: node
+                        .mempool_replace_by_fee_increment
+                        .unwrap_or(default_node_config.mempool_replace_by_fee_increment),
+                    mempool_max_txs_per_origin: node
+                        .mempool_max_txs_per_origin
+                        .unwrap_or(default_node_config.mempool_max_txs_per_origin),
+                    mempool_max_bytes_per_origin: node
+                        .mempool_max_bytes_per_origin
+                        .unwrap_or(default_node_config.mempool_max_bytes_per_origin),
+                    mempool_max_txs: node
+                        .mempool_max_txs
+                        .unwrap_or(default_node_config.mempool_max_txs),
+                    poison_microblock_reporter_key: node.poison_microblock_reporter_key.map(
+                        |key_hex| {
+                            Secp256k1PrivateKey::from_hex(&key_hex)
+                                .expect("Invalid poison_microblock_reporter_key")
+                        },
+                    ),
+                    poison_microblock_report_fee: node
+                        .poison_microblock_report_fee
+                        .unwrap_or(default_node_config.poison_microblock_report_fee),
                 };
                 (node_config, node.bootstrap_node, node.deny_nodes)
             }
@@ -404,6 +1132,9 @@ impl Config {
                     if node.use_test_genesis_chainstate == Some(true) {
                         panic!("Attempted to run mainnet node with `use_test_genesis_chainstate`");
                     }
+                    if node.genesis_chainstate_file.is_some() {
+                        panic!("Attempted to run mainnet node with `genesis_chainstate_file`");
+                    }
                     if let Some(ref balances) = config_file.ustx_balance {
                         if balances.len() > 0 {
                             panic!(
@@ -411,9 +1142,39 @@ impl Config {
                             );
                         }
                     }
+                    if let Some(ref boot_contracts) = config_file.boot_contract {
+                        if boot_contracts.len() > 0 {
+                            panic!("Attempted to run mainnet node with specified `boot_contract`");
+                        }
+                    }
                 }
 
-                BurnchainConfig {
+                let segwit = burnchain.segwit.unwrap_or(default_burnchain_config.segwit);
+                let mut failover_endpoints = default_burnchain_config.failover_endpoints.clone();
+                for endpoint in burnchain.failover_endpoints.take().unwrap_or(vec![]) {
+                    // anything not given is the same as for the primary node
+                    failover_endpoints.push(BitcoindEndpoint {
+                        peer_host: resolve_peer_host(&endpoint.peer_host),
+                        peer_port: endpoint
+                            .peer_port
+                            .or(burnchain.peer_port)
+                            .unwrap_or(default_burnchain_config.peer_port),
+                        rpc_port: endpoint
+                            .rpc_port
+                            .or(burnchain.rpc_port)
+                            .unwrap_or(default_burnchain_config.rpc_port),
+                        rpc_ssl: endpoint
+                            .rpc_ssl
+                            .or(burnchain.rpc_ssl)
+                            .unwrap_or(default_burnchain_config.rpc_ssl),
+                        username: endpoint.username.or(burnchain.username.clone()),
+                        password: endpoint.password.or(burnchain.password.clone()),
+                    });
+                }
+                let block_limits = burnchain.block_limits.take();
+                let (chain_id, peer_version) =
+                    (burnchain.chain_id.take(), burnchain.peer_version.take());
+                let mut burnchain_config = BurnchainConfig {
                     chain: burnchain.chain.unwrap_or(default_burnchain_config.chain),
                     chain_id: if &burnchain_mode == "mainnet" {
                         CHAIN_ID_MAINNET
@@ -433,16 +1194,8 @@ impl Config {
                         .commit_anchor_block_within
                         .unwrap_or(default_burnchain_config.commit_anchor_block_within),
                     peer_host: match burnchain.peer_host {
-                        Some(peer_host) => {
-                            // Using std::net::LookupHost would be preferable, but it's
-                            // unfortunately unstable at this point.
-                            // https://doc.rust-lang.org/1.6.0/std/net/struct.LookupHost.html
-                            let mut addrs_iter =
-                                format!("{}:1", peer_host).to_socket_addrs().unwrap();
-                            let sock_addr = addrs_iter.next().unwrap();
-                            format!("{}", sock_addr.ip())
-                        }
-                        None => default_burnchain_config.peer_host,
+                        Some(ref peer_host) => resolve_peer_host(peer_host),
+                        None => default_burnchain_config.peer_host.clone(),
                     },
                     peer_port: burnchain
                         .peer_port
@@ -455,6 +1208,15 @@ impl Config {
                         .unwrap_or(default_burnchain_config.rpc_ssl),
                     username: burnchain.username,
                     password: burnchain.password,
+                    failover_endpoints,
+                    failover_max_lag: burnchain
+                        .failover_max_lag
+                        .unwrap_or(default_burnchain_config.failover_max_lag),
+                    zmq_block_endpoint: burnchain.zmq_block_endpoint,
+                    spv_only: burnchain
+                        .spv_only
+                        .unwrap_or(default_burnchain_config.spv_only),
+                    active_endpoint: default_burnchain_config.active_endpoint,
                     timeout: burnchain
                         .timeout
                         .unwrap_or(default_burnchain_config.timeout),
@@ -471,26 +1233,77 @@ impl Config {
                     poll_time_secs: burnchain
                         .poll_time_secs
                         .unwrap_or(default_burnchain_config.poll_time_secs),
+                    block_interval_secs: burnchain
+                        .block_interval_secs
+                        .unwrap_or(default_burnchain_config.block_interval_secs),
                     satoshis_per_byte: burnchain
                         .satoshis_per_byte
                         .unwrap_or(default_burnchain_config.satoshis_per_byte),
                     max_rbf: burnchain
                         .max_rbf
                         .unwrap_or(default_burnchain_config.max_rbf),
-                    leader_key_tx_estimated_size: burnchain
-                        .leader_key_tx_estimated_size
-                        .unwrap_or(default_burnchain_config.leader_key_tx_estimated_size),
+                    leader_key_tx_estimated_size: burnchain.leader_key_tx_estimated_size.unwrap_or(
+                        if segwit {
+                            SEGWIT_LEADER_KEY_TX_ESTIM_SIZE
+                        } else {
+                            default_burnchain_config.leader_key_tx_estimated_size
+                        },
+                    ),
                     block_commit_tx_estimated_size: burnchain
                         .block_commit_tx_estimated_size
-                        .unwrap_or(default_burnchain_config.block_commit_tx_estimated_size),
+                        .unwrap_or(if segwit {
+                            SEGWIT_BLOCK_COMMIT_TX_ESTIM_SIZE
+                        } else {
+                            default_burnchain_config.block_commit_tx_estimated_size
+                        }),
                     rbf_fee_increment: burnchain
                         .rbf_fee_increment
                         .unwrap_or(default_burnchain_config.rbf_fee_increment),
+                    fee_estimate_target_blocks: burnchain
+                        .fee_estimate_target_blocks
+                        .unwrap_or(default_burnchain_config.fee_estimate_target_blocks),
+                    segwit,
                     epochs: match burnchain.epochs {
-                        Some(epochs) => Some(epochs),
+                        Some(epochs) => {
+                            if &burnchain_mode == "mainnet" {
+                                panic!("Attempted to run mainnet node with custom `epochs`");
+                            }
+                            Some(make_epochs(&epochs).unwrap_or_else(|msg| {
+                                panic!("Invalid `epochs` in burnchain config: {}", msg)
+                            }))
+                        }
                         None => default_burnchain_config.epochs,
                     },
+                };
+                if let Some(block_limits) = block_limits {
+                    if burnchain_config.mode == "mainnet" {
+                        panic!("Attempted to run mainnet node with custom `block_limits`");
+                    }
+                    let mut epochs = burnchain_config
+                        .epochs
+                        .take()
+                        .unwrap_or_else(|| burnchain_config.get_default_epochs());
+                    apply_block_limits(&mut epochs, &block_limits).unwrap_or_else(|msg| {
+                        panic!("Invalid `block_limits` in burnchain config: {}", msg)
+                    });
+                    burnchain_config.epochs = Some(epochs);
                 }
+                if chain_id.is_some() || peer_version.is_some() {
+                    if burnchain_config.mode == "mainnet" {
+                        panic!("Attempted to run mainnet node with custom `chain_id` or `peer_version`");
+                    }
+                    let network = NetworkParameters::app_chain(
+                        chain_id.unwrap_or(burnchain_config.chain_id),
+                        peer_version.unwrap_or(burnchain_config.peer_version),
+                        burnchain_config.magic_bytes.clone(),
+                    )
+                    .unwrap_or_else(|msg| {
+                        panic!("Invalid network parameters in burnchain config: {}", msg)
+                    });
+                    burnchain_config.chain_id = network.chain_id;
+                    burnchain_config.peer_version = network.peer_version;
+                }
+                burnchain_config
             }
             None => default_burnchain_config,
         };
@@ -508,10 +1321,52 @@ impl Config {
                 probability_pick_no_estimate_tx: miner
                     .probability_pick_no_estimate_tx
                     .unwrap_or(miner_default_config.probability_pick_no_estimate_tx),
+                mempool_walk_strategy: miner
+                    .mempool_walk_strategy
+                    .clone()
+                    .map(MemPoolWalkStrategyName::panic_parse)
+                    .unwrap_or(miner_default_config.mempool_walk_strategy),
+                assembly_deadline_pct: miner
+                    .assembly_deadline_pct
+                    .unwrap_or(miner_default_config.assembly_deadline_pct),
+                max_fork_candidates: miner
+                    .max_fork_candidates
+                    .unwrap_or(miner_default_config.max_fork_candidates),
+                contract_allow_list: miner
+                    .contract_allow_list
+                    .as_ref()
+                    .map(|ids| parse_miner_contract_ids(ids)),
+                contract_deny_list: miner
+                    .contract_deny_list
+                    .as_ref()
+                    .map(|ids| parse_miner_contract_ids(ids))
+                    .unwrap_or(miner_default_config.contract_deny_list),
+                remote_signer_url: miner.remote_signer_url.clone(),
+                remote_signer_auth_token: miner.remote_signer_auth_token.clone(),
+                microblock_max_cost_pct: miner
+                    .microblock_max_cost_pct
+                    .unwrap_or(miner_default_config.microblock_max_cost_pct),
+                microblock_min_tx_fee: miner
+                    .microblock_min_tx_fee
+                    .unwrap_or(miner_default_config.microblock_min_tx_fee),
             },
             None => miner_default_config,
         };
 
+        if miner.microblock_max_cost_pct > 100 {
+            panic!("Setting miner.microblock_max_cost_pct must be at most 100");
+        }
+
+        // the interval between microblocks can be set with the rest of the miner's microblock
+        // policy
+        if let Some(interval_ms) = config_file
+            .miner
+            .as_ref()
+            .and_then(|miner| miner.microblock_interval_ms)
+        {
+            node.microblock_frequency = interval_ms;
+        }
+
         let supported_modes = vec![
             "mocknet", "helium", "neon", "argon", "krypton", "xenon", "mainnet",
         ];
@@ -543,14 +1398,15 @@ impl Config {
             node.set_deny_nodes(deny_nodes, burnchain.chain_id, burnchain.peer_version);
         }
 
+        let network = burnchain.get_network_parameters();
         let initial_balances: Vec<InitialBalance> = match config_file.ustx_balance {
             Some(balances) => balances
                 .iter()
                 .map(|balance| {
-                    let address: PrincipalData =
-                        PrincipalData::parse_standard_principal(&balance.address)
-                            .unwrap()
-                            .into();
+                    let address: PrincipalData = network
+                        .parse_address(&balance.address)
+                        .unwrap_or_else(|msg| panic!("Bad `ustx_balance` address: {}", msg))
+                        .into();
                     InitialBalance {
                         address,
                         amount: balance.amount,
@@ -560,6 +1416,29 @@ impl Config {
             None => vec![],
         };
 
+        let mut boot_contracts = vec![];
+        for boot_contract in config_file.boot_contract.unwrap_or(vec![]).into_iter() {
+            if ContractName::try_from(boot_contract.name.clone()).is_err() {
+                panic!("Bad boot contract name: {}", &boot_contract.name);
+            }
+            if STACKS_BOOT_CODE_TESTNET
+                .iter()
+                .any(|(name, _)| *name == boot_contract.name.as_str())
+                || boot_contracts
+                    .iter()
+                    .any(|(name, _): &(String, String)| *name == boot_contract.name)
+            {
+                panic!("Duplicate boot contract name: {}", &boot_contract.name);
+            }
+            let source = fs::read_to_string(&boot_contract.path).unwrap_or_else(|e| {
+                panic!(
+                    "Failed to read boot contract {} from {}: {:?}",
+                    &boot_contract.name, &boot_contract.path, &e
+                )
+            });
+            boot_contracts.push((boot_contract.name, source));
+        }
+
         let mut events_observers = match config_file.events_observer {
             Some(raw_observers) => {
                 let mut observers = vec![];
@@ -575,6 +1454,8 @@ impl Config {
                     observers.push(EventObserverConfig {
                         endpoint,
                         events_keys,
+                        filter_transactions: observer.filter_transactions.unwrap_or(false),
+                        include_tx_details: observer.include_tx_details.unwrap_or(false),
                     });
                 }
                 observers
@@ -587,11 +1468,13 @@ impl Config {
             Ok(val) => events_observers.push(EventObserverConfig {
                 endpoint: val,
                 events_keys: vec![EventKeyType::AnyEvent],
+                filter_transactions: false,
+                include_tx_details: false,
             }),
             _ => (),
         };
 
-        let connection_options = match config_file.connection_options {
+        let mut connection_options = match config_file.connection_options {
             Some(opts) => {
                 let ip_addr = match opts.public_ip_address {
                     Some(public_ip_address) => {
@@ -601,6 +1484,66 @@ impl Config {
                     }
                     None => None,
                 };
+                let dns_seeds = match opts.dns_seeds {
+                    Some(dns_seeds) => dns_seeds
+                        .split(",")
+                        .filter(|seed| seed.len() > 0)
+                        .map(|seed| {
+                            let parts: Vec<&str> = seed.rsplitn(2, ":").collect();
+                            if parts.len() != 2 {
+                                panic!("Invalid DNS seed '{}': expected HOST:PORT", seed);
+                            }
+                            let port = parts[0]
+                                .parse::<u16>()
+                                .expect(&format!("Invalid port in DNS seed '{}'", seed));
+                            (parts[1].to_string(), port)
+                        })
+                        .collect(),
+                    None => HELIUM_DEFAULT_CONNECTION_OPTIONS.dns_seeds.clone(),
+                };
+                let parse_list = |list: Option<String>, default: &Vec<String>| match list {
+                    Some(list) => list
+                        .split(",")
+                        .map(|item| item.trim().to_string())
+                        .filter(|item| item.len() > 0)
+                        .collect(),
+                    None => default.clone(),
+                };
+                let cors_allowed_origins = parse_list(
+                    opts.cors_allowed_origins,
+                    &HELIUM_DEFAULT_CONNECTION_OPTIONS.cors_allowed_origins,
+                );
+                let cors_allowed_methods = parse_list(
+                    opts.cors_allowed_methods,
+                    &HELIUM_DEFAULT_CONNECTION_OPTIONS.cors_allowed_methods,
+                );
+                let cors_allowed_headers = parse_list(
+                    opts.cors_allowed_headers,
+                    &HELIUM_DEFAULT_CONNECTION_OPTIONS.cors_allowed_headers,
+                );
+                let private_network_key = opts.private_network_key.map(|key| {
+                    Secp256k1PrivateKey::from_hex(&key).expect("Invalid private_network_key")
+                });
+                let private_network_peers: Vec<Secp256k1PublicKey> =
+                    match opts.private_network_peers {
+                        Some(peers) => peers
+                            .split(",")
+                            .filter(|pubk| pubk.len() > 0)
+                            .map(|pubk| {
+                                Secp256k1PublicKey::from_hex(pubk).expect(&format!(
+                                    "Invalid public key '{}' in private_network_peers",
+                                    pubk
+                                ))
+                            })
+                            .collect(),
+                        None => vec![],
+                    };
+                if private_network_key.is_some() && private_network_peers.len() == 0 {
+                    panic!("private_network_key is set, but private_network_peers is empty");
+                }
+                if private_network_key.is_none() && private_network_peers.len() > 0 {
+                    panic!("private_network_peers is set, but private_network_key is not");
+                }
                 let mut read_only_call_limit = HELIUM_DEFAULT_CONNECTION_OPTIONS
                     .read_only_call_limit
                     .clone();
@@ -619,8 +1562,50 @@ impl Config {
                 opts.read_only_call_limit_runtime.map(|x| {
                     read_only_call_limit.runtime = x;
                 });
+                let mut simulate_transaction_limit = HELIUM_DEFAULT_CONNECTION_OPTIONS
+                    .simulate_transaction_limit
+                    .clone();
+                opts.simulate_transaction_limit_write_length.map(|x| {
+                    simulate_transaction_limit.write_length = x;
+                });
+                opts.simulate_transaction_limit_write_count.map(|x| {
+                    simulate_transaction_limit.write_count = x;
+                });
+                opts.simulate_transaction_limit_read_length.map(|x| {
+                    simulate_transaction_limit.read_length = x;
+                });
+                opts.simulate_transaction_limit_read_count.map(|x| {
+                    simulate_transaction_limit.read_count = x;
+                });
+                opts.simulate_transaction_limit_runtime.map(|x| {
+                    simulate_transaction_limit.runtime = x;
+                });
                 ConnectionOptions {
                     read_only_call_limit,
+                    read_only_call_time_limit_ms: opts.read_only_call_time_limit_ms.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.read_only_call_time_limit_ms,
+                    ),
+                    read_only_call_max_result_size: opts
+                        .read_only_call_max_result_size
+                        .unwrap_or_else(|| {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS.read_only_call_max_result_size
+                        }),
+                    read_only_call_workers: opts.read_only_call_workers.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.read_only_call_workers
+                    }),
+                    read_only_call_queue_size: opts.read_only_call_queue_size.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.read_only_call_queue_size,
+                    ),
+                    health_max_burn_block_lag: opts.health_max_burn_block_lag.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.health_max_burn_block_lag,
+                    ),
+                    health_max_stacks_block_lag: opts.health_max_stacks_block_lag.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.health_max_stacks_block_lag,
+                    ),
+                    simulate_transaction_limit,
+                    simulate_transaction_max_len: opts.simulate_transaction_max_len.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.simulate_transaction_max_len,
+                    ),
                     inbox_maxlen: opts
                         .inbox_maxlen
                         .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.inbox_maxlen.clone()),
@@ -633,6 +1618,34 @@ impl Config {
                     idle_timeout: opts
                         .idle_timeout
                         .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.idle_timeout.clone()),
+                    http_keep_alive_timeout: opts.http_keep_alive_timeout.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.http_keep_alive_timeout
+                    }),
+                    max_http_requests_per_connection: opts
+                        .max_http_requests_per_connection
+                        .unwrap_or_else(|| {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS.max_http_requests_per_connection
+                        }),
+                    http_compression_threshold: opts.http_compression_threshold.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.http_compression_threshold,
+                    ),
+                    cors_allowed_origins,
+                    cors_allowed_methods,
+                    cors_allowed_headers,
+                    cors_max_age: opts
+                        .cors_max_age
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.cors_max_age),
+                    rpc_admin_token: opts.rpc_admin_token.clone().filter(|t| t.len() > 0),
+                    rpc_monitor_token: opts.rpc_monitor_token.clone().filter(|t| t.len() > 0),
+                    rpc_rate_limit_read: opts
+                        .rpc_rate_limit_read
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.rpc_rate_limit_read),
+                    rpc_rate_limit_compute: opts.rpc_rate_limit_compute.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.rpc_rate_limit_compute
+                    }),
+                    rpc_rate_limit_submit: opts
+                        .rpc_rate_limit_submit
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.rpc_rate_limit_submit),
                     heartbeat: opts
                         .heartbeat
                         .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.heartbeat.clone()),
@@ -695,6 +1708,13 @@ impl Config {
                             .max_inflight_blocks
                             .clone()
                     }),
+                    max_inflight_blocks_per_peer: opts.max_inflight_blocks_per_peer.unwrap_or_else(
+                        || {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS
+                                .max_inflight_blocks_per_peer
+                                .clone()
+                        },
+                    ),
                     max_inflight_attachments: opts.max_inflight_attachments.unwrap_or_else(|| {
                         HELIUM_DEFAULT_CONNECTION_OPTIONS
                             .max_inflight_attachments
@@ -738,6 +1758,54 @@ impl Config {
                     handshake_timeout: opts.connect_timeout.unwrap_or(5),
                     max_sockets: opts.max_sockets.unwrap_or(800) as usize,
                     antientropy_public: opts.antientropy_public.unwrap_or(true),
+                    mempool_sync_interval: opts
+                        .mempool_sync_interval
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.mempool_sync_interval),
+                    mempool_sync_max_txs: opts
+                        .mempool_sync_max_txs
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.mempool_sync_max_txs),
+                    compact_block_relay: opts.compact_block_relay.unwrap_or(true),
+                    peer_ban_score_threshold: opts.peer_ban_score_threshold.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.peer_ban_score_threshold
+                    }),
+                    peer_score_ban_duration: opts.peer_score_ban_duration.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.peer_score_ban_duration
+                    }),
+                    peer_score_decay_interval: opts.peer_score_decay_interval.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.peer_score_decay_interval,
+                    ),
+                    dns_seeds,
+                    enable_port_mapping: opts.port_mapping.unwrap_or(false),
+                    port_mapping_gateway: opts.port_mapping_gateway.map(|gateway| {
+                        gateway
+                            .parse::<IpAddr>()
+                            .expect(&format!("Invalid port mapping gateway '{}'", gateway))
+                    }),
+                    port_mapping_lifetime: opts
+                        .port_mapping_lifetime
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.port_mapping_lifetime),
+                    max_clients_per_subnet: opts.max_clients_per_subnet.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.max_clients_per_subnet
+                    }),
+                    client_subnet_prefix_v4: opts.client_subnet_prefix_v4.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.client_subnet_prefix_v4
+                    }),
+                    client_subnet_prefix_v6: opts.client_subnet_prefix_v6.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.client_subnet_prefix_v6
+                    }),
+                    max_relay_bandwidth_per_peer: opts.max_relay_bandwidth_per_peer.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.max_relay_bandwidth_per_peer,
+                    ),
+                    private_network_key,
+                    private_network_peers,
+                    subscribe_block_announcements: opts
+                        .subscribe_block_announcements
+                        .unwrap_or_else(|| {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS.subscribe_block_announcements
+                        }),
+                    block_announcement_jitter_ms: opts.block_announcement_jitter_ms.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.block_announcement_jitter_ms,
+                    ),
                     ..ConnectionOptions::default()
                 }
             }
@@ -749,6 +1817,29 @@ impl Config {
             None => FeeEstimationConfig::default(),
         };
 
+        let mempool_policy = match config_file.mempool_policy {
+            Some(f) => MemPoolPolicyConfig::from(f),
+            None => MemPoolPolicyConfig::default(),
+        };
+
+        let relay_policy = match config_file.relay_policy {
+            Some(f) => RelayPolicyConfig::from(f),
+            None => RelayPolicyConfig::default(),
+        };
+
+        if burnchain.spv_only && node.miner {
+            panic!("Attempted to run a miner with `spv_only`, which cannot send burnchain transactions");
+        }
+
+        if node.prune_depth.is_some() {
+            if node.miner {
+                panic!(
+                    "Attempted to run a miner with `prune_depth`, which only supports followers"
+                );
+            }
+            connection_options.pruned = true;
+        }
+
         Config {
             node,
             burnchain,
@@ -757,6 +1848,9 @@ impl Config {
             connection_options,
             estimation,
             miner,
+            mempool_policy,
+            relay_policy,
+            boot_contracts,
         }
     }
 
@@ -807,6 +1901,12 @@ impl Config {
         path.to_str().expect("Unable to produce path").to_string()
     }
 
+    pub fn get_event_queue_file_path(&self) -> String {
+        let mut path = self.get_chainstate_path();
+        path.set_file_name("event_observers.sqlite");
+        path.to_str().expect("Unable to produce path").to_string()
+    }
+
     pub fn get_peer_db_file_path(&self) -> String {
         let mut path = self.get_chainstate_path();
         path.set_file_name("peer.sqlite");
@@ -837,6 +1937,10 @@ impl Config {
         total
     }
 
+    pub fn get_network_parameters(&self) -> NetworkParameters {
+        self.burnchain.get_network_parameters()
+    }
+
     pub fn is_mainnet(&self) -> bool {
         match self.burnchain.mode.as_str() {
             "mainnet" => true,
@@ -867,8 +1971,28 @@ impl Config {
                     self.miner.subsequent_attempt_time_ms
                 },
                 consider_no_estimate_tx_prob: self.miner.probability_pick_no_estimate_tx,
+                contract_call_policy: self.make_miner_contract_call_policy(),
             },
+            microblock_policy: MicroblockPolicy {
+                max_cost_pct: self.miner.microblock_max_cost_pct,
+                min_tx_fee: self.miner.microblock_min_tx_fee,
+            },
+        }
+    }
+
+    /// The policy restricting which contract-calls the miner includes in its blocks, if any
+    pub fn make_miner_contract_call_policy(&self) -> Option<ContractCallPolicy> {
+        if self.miner.contract_allow_list.is_none() && self.miner.contract_deny_list.is_empty() {
+            return None;
         }
+        Some(ContractCallPolicy {
+            allow_list: self
+                .miner
+                .contract_allow_list
+                .as_ref()
+                .map(|ids| ids.iter().cloned().collect()),
+            deny_list: self.miner.contract_deny_list.iter().cloned().collect(),
+        })
     }
 }
 
@@ -894,6 +2018,9 @@ impl std::default::Default for Config {
             connection_options,
             estimation,
             miner: MinerConfig::default(),
+            mempool_policy: MemPoolPolicyConfig::default(),
+            relay_policy: RelayPolicyConfig::default(),
+            boot_contracts: vec![],
         }
     }
 }
@@ -912,16 +2039,46 @@ pub struct BurnchainConfig {
     pub rpc_ssl: bool,
     pub username: Option<String>,
     pub password: Option<String>,
+    /// Other bitcoind nodes to fail over to, in order of preference, if the one at `peer_host`
+    /// becomes unreachable or falls behind
+    pub failover_endpoints: Vec<BitcoindEndpoint>,
+    /// How many blocks the bitcoind node in use may fall behind the best of the configured nodes
+    /// before the node fails over to another one
+    pub failover_max_lag: u64,
+    /// bitcoind's ZMQ `hashblock` publisher (e.g. `tcp://127.0.0.1:28332`).  If set, the node
+    /// syncs with the burnchain as soon as bitcoind announces a new block, instead of waiting
+    /// for the next poll.
+    pub zmq_block_endpoint: Option<String>,
+    /// Follow the burnchain over the Bitcoin peer-to-peer protocol alone, without bitcoind's RPC
+    /// interface.  `peer_host` and any `failover_endpoints` are then plain Bitcoin peers (only
+    /// their `peer_port` is used), which need not be trusted: headers are checked for
+    /// proof-of-work, and blocks against their headers' merkle roots.  Such a node cannot mine.
+    pub spv_only: bool,
+    /// Index into `get_endpoints()` of the bitcoind node in use.  Shared by all copies of this
+    /// config, so a failover is seen by every thread that talks to bitcoind.
+    #[serde(skip)]
+    pub active_endpoint: Arc<AtomicUsize>,
     pub timeout: u32,
     pub magic_bytes: MagicBytes,
     pub local_mining_public_key: Option<String>,
     pub process_exit_at_block_height: Option<u64>,
     pub poll_time_secs: u64,
+    /// expected time between burnchain blocks
+    pub block_interval_secs: u64,
     pub satoshis_per_byte: u64,
     pub max_rbf: u64,
     pub leader_key_tx_estimated_size: u64,
     pub block_commit_tx_estimated_size: u64,
     pub rbf_fee_increment: u64,
+    /// If nonzero, the miner asks bitcoind for a fee rate that should get its block-commits
+    /// confirmed within this many blocks, and pays it (up to the `max_rbf` ceiling) whenever it
+    /// exceeds `satoshis_per_byte`.  An in-flight block-commit is replaced by fee when this
+    /// estimate rises past the fee rate it pays.
+    pub fee_estimate_target_blocks: u64,
+    /// If set, the miner's UTXOs are held at the P2SH-wrapped P2WPKH address of its key instead
+    /// of its P2PKH address, so its burnchain transactions spend segwit inputs.  (Native P2WPKH
+    /// inputs and outputs are not recognized in burnchain operations.)
+    pub segwit: bool,
     /// Custom override for the definitions of the epochs. This will only be applied for testnet and
     /// regtest nodes.
     pub epochs: Option<Vec<StacksEpoch>>,
@@ -942,34 +2099,100 @@ impl BurnchainConfig {
             rpc_ssl: false,
             username: None,
             password: None,
+            failover_endpoints: vec![],
+            failover_max_lag: DEFAULT_FAILOVER_MAX_LAG,
+            zmq_block_endpoint: None,
+            spv_only: false,
+            active_endpoint: Arc::new(AtomicUsize::new(0)),
             timeout: 300,
             magic_bytes: BLOCKSTACK_MAGIC_MAINNET.clone(),
             local_mining_public_key: None,
             process_exit_at_block_height: None,
             poll_time_secs: 10, // TODO: this is a testnet specific value.
+            block_interval_secs: 600,
             satoshis_per_byte: DEFAULT_SATS_PER_VB,
             max_rbf: DEFAULT_MAX_RBF_RATE,
             leader_key_tx_estimated_size: LEADER_KEY_TX_ESTIM_SIZE,
             block_commit_tx_estimated_size: BLOCK_COMMIT_TX_ESTIM_SIZE,
             rbf_fee_increment: DEFAULT_RBF_FEE_RATE_INCREMENT,
+            fee_estimate_target_blocks: 0,
+            segwit: false,
             epochs: None,
         }
     }
 
-    pub fn get_rpc_url(&self) -> String {
-        let scheme = match self.rpc_ssl {
-            true => "https://",
-            false => "http://",
+    /// The chain ID, peer version, address versions, and magic bytes this node runs with
+    pub fn get_network_parameters(&self) -> NetworkParameters {
+        let defaults = if self.mode == "mainnet" {
+            NetworkParameters::mainnet()
+        } else {
+            NetworkParameters::testnet()
         };
-        format!("{}{}:{}", scheme, self.peer_host, self.rpc_port)
+        NetworkParameters {
+            chain_id: self.chain_id,
+            peer_version: self.peer_version,
+            magic_bytes: self.magic_bytes.clone(),
+            ..defaults
+        }
+    }
+
+    /// Highest fee rate, in satoshis per byte, that block-commits may pay
+    pub fn get_max_fee_rate(&self) -> u64 {
+        self.satoshis_per_byte * self.max_rbf / 100
+    }
+
+    /// All of the configured bitcoind nodes, in order of preference
+    pub fn get_endpoints(&self) -> Vec<BitcoindEndpoint> {
+        let mut endpoints = vec![BitcoindEndpoint {
+            peer_host: self.peer_host.clone(),
+            peer_port: self.peer_port,
+            rpc_port: self.rpc_port,
+            rpc_ssl: self.rpc_ssl,
+            username: self.username.clone(),
+            password: self.password.clone(),
+        }];
+        endpoints.extend(self.failover_endpoints.iter().cloned());
+        endpoints
+    }
+
+    pub fn get_active_endpoint_index(&self) -> usize {
+        self.active_endpoint.load(Ordering::SeqCst) % (self.failover_endpoints.len() + 1)
+    }
+
+    pub fn set_active_endpoint_index(&self, index: usize) {
+        self.active_endpoint.store(index, Ordering::SeqCst);
+    }
+
+    /// The bitcoind node currently in use
+    pub fn get_active_endpoint(&self) -> BitcoindEndpoint {
+        let index = self.get_active_endpoint_index();
+        self.get_endpoints().swap_remove(index)
+    }
+
+    /// Decide which bitcoind node to use, given the block height each one reports (or None if it
+    /// could not be reached).  This is the most preferred reachable node that is no more than
+    /// `failover_max_lag` blocks behind the best of them.  If none can be reached, the node in
+    /// use is kept.
+    pub fn choose_endpoint(&self, heights: &[Option<u64>]) -> usize {
+        let best = match heights.iter().filter_map(|h| *h).max() {
+            Some(best) => best,
+            None => return self.get_active_endpoint_index(),
+        };
+        heights
+            .iter()
+            .position(|h| match h {
+                Some(height) => best - *height <= self.failover_max_lag,
+                None => false,
+            })
+            .expect("BUG: no endpoint at the best height")
+    }
+
+    pub fn get_rpc_url(&self) -> String {
+        self.get_active_endpoint().get_rpc_url()
     }
 
     pub fn get_rpc_socket_addr(&self) -> SocketAddr {
-        let mut addrs_iter = format!("{}:{}", self.peer_host, self.rpc_port)
-            .to_socket_addrs()
-            .unwrap();
-        let sock_addr = addrs_iter.next().unwrap();
-        sock_addr
+        self.get_active_endpoint().get_rpc_socket_addr()
     }
 
     pub fn get_bitcoin_network(&self) -> (String, BitcoinNetworkType) {
@@ -982,6 +2205,21 @@ impl BurnchainConfig {
             _ => panic!("Invalid bitcoin mode -- expected mainnet, testnet, or regtest"),
         }
     }
+
+    /// The epoch schedule this node runs with if `epochs` is not set
+    pub fn get_default_epochs(&self) -> Vec<StacksEpoch> {
+        if self.mode == "mocknet" {
+            vec![StacksEpoch {
+                epoch_id: StacksEpochId::Epoch20,
+                start_height: 0,
+                end_height: STACKS_EPOCH_MAX,
+                block_limit: ExecutionCost::max_value(),
+                network_epoch: PEER_VERSION_EPOCH_2_0,
+            }]
+        } else {
+            get_bitcoin_stacks_epochs(self.get_bitcoin_network().1)
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -1001,12 +2239,174 @@ pub struct BurnchainConfigFile {
     pub local_mining_public_key: Option<String>,
     pub process_exit_at_block_height: Option<u64>,
     pub poll_time_secs: Option<u64>,
+    pub block_interval_secs: Option<u64>,
     pub satoshis_per_byte: Option<u64>,
     pub leader_key_tx_estimated_size: Option<u64>,
     pub block_commit_tx_estimated_size: Option<u64>,
     pub rbf_fee_increment: Option<u64>,
     pub max_rbf: Option<u64>,
-    pub epochs: Option<Vec<StacksEpoch>>,
+    pub fee_estimate_target_blocks: Option<u64>,
+    pub segwit: Option<bool>,
+    pub failover_endpoints: Option<Vec<BitcoindEndpointFile>>,
+    pub failover_max_lag: Option<u64>,
+    pub zmq_block_endpoint: Option<String>,
+    pub spv_only: Option<bool>,
+    pub epochs: Option<Vec<StacksEpochConfigFile>>,
+    pub block_limits: Option<Vec<EpochBlockLimitConfigFile>>,
+    pub chain_id: Option<u32>,
+    pub peer_version: Option<u32>,
+}
+
+/// Connection details for one bitcoind node
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct BitcoindEndpoint {
+    pub peer_host: String,
+    pub peer_port: u16,
+    pub rpc_port: u16,
+    pub rpc_ssl: bool,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl BitcoindEndpoint {
+    pub fn get_rpc_url(&self) -> String {
+        let scheme = match self.rpc_ssl {
+            true => "https://",
+            false => "http://",
+        };
+        format!("{}{}:{}", scheme, self.peer_host, self.rpc_port)
+    }
+
+    pub fn get_rpc_socket_addr(&self) -> SocketAddr {
+        let mut addrs_iter = format!("{}:{}", self.peer_host, self.rpc_port)
+            .to_socket_addrs()
+            .unwrap();
+        let sock_addr = addrs_iter.next().unwrap();
+        sock_addr
+    }
+}
+
+/// A failover bitcoind node.  Unset ports, credentials, and `rpc_ssl` are the same as the
+/// primary node's.
+#[derive(Clone, Deserialize, Default)]
+pub struct BitcoindEndpointFile {
+    pub peer_host: String,
+    pub peer_port: Option<u16>,
+    pub rpc_port: Option<u16>,
+    pub rpc_ssl: Option<bool>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// One entry of a custom epoch schedule.  An epoch ends where the next one starts, and the last
+/// one never ends.  Epoch 1.0 is filled in from height 0 if it is not given.
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct StacksEpochConfigFile {
+    /// The epoch's name -- one of "1.0", "2.0", or "2.05"
+    pub epoch_name: String,
+    pub start_height: u64,
+    /// Defaults to the epoch's mainnet block limit
+    pub block_limit: Option<ExecutionCost>,
+}
+
+/// Build and validate an epoch schedule from the `[[burnchain.epochs]]` entries of a config file.
+pub fn make_epochs(epoch_files: &[StacksEpochConfigFile]) -> Result<Vec<StacksEpoch>, String> {
+    let mut schedule = vec![];
+    for epoch_file in epoch_files.iter() {
+        let epoch_id = StacksEpochId::from_name(&epoch_file.epoch_name)
+            .ok_or_else(|| format!("Unknown epoch '{}'", &epoch_file.epoch_name))?;
+        let block_limit = epoch_file
+            .block_limit
+            .clone()
+            .unwrap_or_else(|| epoch_id.default_block_limit());
+        schedule.push((epoch_id, epoch_file.start_height, block_limit));
+    }
+    if schedule.len() == 0 {
+        return Err("No epochs given".to_string());
+    }
+    schedule.sort_by_key(|(epoch_id, ..)| *epoch_id);
+    if schedule[0].0 != StacksEpochId::Epoch10 {
+        // 1.0 covers all burnchain history before the first configured epoch (if any)
+        schedule.insert(
+            0,
+            (
+                StacksEpochId::Epoch10,
+                0,
+                StacksEpochId::Epoch10.default_block_limit(),
+            ),
+        );
+    }
+
+    let mut epochs = vec![];
+    for (i, (epoch_id, start_height, block_limit)) in schedule.iter().enumerate() {
+        let end_height = match schedule.get(i + 1) {
+            Some((_, next_start_height, _)) => *next_start_height,
+            None => STACKS_EPOCH_MAX,
+        };
+        epochs.push(StacksEpoch {
+            epoch_id: *epoch_id,
+            start_height: *start_height,
+            end_height,
+            block_limit: block_limit.clone(),
+            network_epoch: epoch_id.network_epoch(),
+        });
+    }
+    StacksEpoch::validate_epochs(&epochs)
+}
+
+/// An override of some or all of one epoch's block limit.  Unset fields keep the epoch's
+/// configured (or default) value.
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct EpochBlockLimitConfigFile {
+    /// The epoch's name -- one of "1.0", "2.0", or "2.05"
+    pub epoch_name: String,
+    pub runtime: Option<u64>,
+    pub read_count: Option<u64>,
+    pub read_length: Option<u64>,
+    pub write_count: Option<u64>,
+    pub write_length: Option<u64>,
+}
+
+/// Apply the `[[burnchain.block_limits]]` entries of a config file to an epoch schedule.
+pub fn apply_block_limits(
+    epochs: &mut Vec<StacksEpoch>,
+    block_limits: &[EpochBlockLimitConfigFile],
+) -> Result<(), String> {
+    for block_limit in block_limits.iter() {
+        let epoch_id = StacksEpochId::from_name(&block_limit.epoch_name)
+            .ok_or_else(|| format!("Unknown epoch '{}'", &block_limit.epoch_name))?;
+        let epoch = epochs
+            .iter_mut()
+            .find(|epoch| epoch.epoch_id == epoch_id)
+            .ok_or_else(|| format!("Epoch {} is not in the epoch schedule", &epoch_id))?;
+        let limit = &mut epoch.block_limit;
+        limit.runtime = block_limit.runtime.unwrap_or(limit.runtime);
+        limit.read_count = block_limit.read_count.unwrap_or(limit.read_count);
+        limit.read_length = block_limit.read_length.unwrap_or(limit.read_length);
+        limit.write_count = block_limit.write_count.unwrap_or(limit.write_count);
+        limit.write_length = block_limit.write_length.unwrap_or(limit.write_length);
+    }
+    Ok(())
+}
+
+fn resolve_peer_host(peer_host: &str) -> String {
+    // Using std::net::LookupHost would be preferable, but it's
+    // unfortunately unstable at this point.
+    // https://doc.rust-lang.org/1.6.0/std/net/struct.LookupHost.html
+    let mut addrs_iter = format!("{}:1", peer_host).to_socket_addrs().unwrap();
+    let sock_addr = addrs_iter.next().unwrap();
+    format!("{}", sock_addr.ip())
+}
+
+/// Does `bind_address` (a `host:port`) only resolve to loopback addresses?
+fn is_loopback_address(bind_address: &str) -> bool {
+    match bind_address.to_socket_addrs() {
+        Ok(addrs) => {
+            let addrs: Vec<_> = addrs.collect();
+            addrs.len() > 0 && addrs.iter().all(|addr| addr.ip().is_loopback())
+        }
+        Err(_) => false,
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -1030,6 +2430,53 @@ pub struct NodeConfig {
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: u64,
     pub use_test_genesis_chainstate: Option<bool>,
+    /// A custom genesis file in the same format as stx-genesis/chainstate.txt, with its hash in
+    /// a `.sha256` file next to it.  Takes precedence over `use_test_genesis_chainstate`.
+    pub genesis_chainstate_file: Option<String>,
+    /// A custom genesis name zonefiles file in the same format as
+    /// stx-genesis/name_zonefiles.txt, with its hash in a `.sha256` file next to it.  Only used
+    /// with `genesis_chainstate_file`.
+    pub genesis_name_zonefiles_file: Option<String>,
+    /// An `http://` URL of a chainstate snapshot archive to import at first boot, instead of
+    /// syncing from genesis.
+    pub bootstrap_snapshot_url: Option<String>,
+    /// The snapshot digest the downloaded archive must match.
+    pub bootstrap_snapshot_digest: Option<Sha256Sum>,
+    /// If set, run as a pruned follower: discard block bodies, microblocks, and non-canonical
+    /// Clarity state more than this many blocks below the canonical Stacks tip.  Reorgs deeper
+    /// than this cannot be handled.
+    pub prune_depth: Option<u64>,
+    pub address_txs_index: bool,
+    /// Record each principal's fungible and non-fungible token movements as blocks are
+    /// processed, to serve /v2/addresses/{principal}/assets.
+    pub asset_holdings_index: bool,
+    /// Break down each transaction's runtime cost by cost function and contract-call edge in its
+    /// receipt (and so in event observer payloads).  Costs some speed when processing blocks.
+    pub cost_breakdown: bool,
+    pub event_stream_bind: Option<String>,
+    /// If set, event stream clients must present this token.  Required unless
+    /// `event_stream_bind` is a loopback address.
+    pub event_stream_auth_token: Option<String>,
+    pub event_queue: bool,
+    pub event_replay_depth: u64,
+    /// On startup, refresh the persisted mempool's cached state and announce its pending
+    /// transactions to peers once the node has synced with them.
+    pub mempool_warm_restart: bool,
+    /// How much higher (in microSTX) the fee of a transaction must be in order to replace a
+    /// pending transaction with the same nonce.
+    pub mempool_replace_by_fee_increment: u64,
+    /// Maximum number of pending transactions from any one origin account
+    pub mempool_max_txs_per_origin: u64,
+    /// Maximum total size (in bytes) of the pending transactions from any one origin account
+    pub mempool_max_bytes_per_origin: u64,
+    /// Maximum number of pending transactions.  Once reached, the lowest fee-rate transactions
+    /// are evicted to make room.
+    pub mempool_max_txs: u64,
+    /// If set, report each fork in a miner's microblock stream that this node sees with a
+    /// poison-microblock transaction signed by this key.
+    pub poison_microblock_reporter_key: Option<Secp256k1PrivateKey>,
+    /// The fee (in microSTX) to pay for each poison-microblock report
+    pub poison_microblock_report_fee: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -1040,6 +2487,7 @@ pub enum CostEstimatorName {
 #[derive(Clone, Debug)]
 pub enum FeeEstimatorName {
     ScalarFeeRate,
+    WeightedMedianFeeRate,
 }
 
 #[derive(Clone, Debug)]
@@ -1082,6 +2530,8 @@ impl FeeEstimatorName {
     fn panic_parse(s: String) -> FeeEstimatorName {
         if &s.to_lowercase() == "scalar_fee_rate" {
             FeeEstimatorName::ScalarFeeRate
+        } else if &s.to_lowercase() == "weighted_median_fee_rate" {
+            FeeEstimatorName::WeightedMedianFeeRate
         } else {
             panic!(
                 "Bad fee estimator name supplied in configuration file: {}",
@@ -1107,6 +2557,7 @@ pub struct FeeEstimationConfig {
     pub fee_estimator: Option<FeeEstimatorName>,
     pub cost_metric: Option<CostMetricName>,
     pub log_error: bool,
+    pub fee_rate_window_size: u64,
 }
 
 impl Default for FeeEstimationConfig {
@@ -1116,6 +2567,7 @@ impl Default for FeeEstimationConfig {
             fee_estimator: Some(FeeEstimatorName::default()),
             cost_metric: Some(CostMetricName::default()),
             log_error: false,
+            fee_rate_window_size: 5,
         }
     }
 }
@@ -1128,6 +2580,7 @@ impl From<FeeEstimationConfigFile> for FeeEstimationConfig {
                 fee_estimator: None,
                 cost_metric: None,
                 log_error: false,
+                fee_rate_window_size: 0,
             };
         }
         let cost_estimator = f
@@ -1143,16 +2596,204 @@ impl From<FeeEstimationConfigFile> for FeeEstimationConfig {
             .map(CostMetricName::panic_parse)
             .unwrap_or_default();
         let log_error = f.log_error.unwrap_or(false);
+        let fee_rate_window_size = f
+            .fee_rate_window_size
+            .unwrap_or(FeeEstimationConfig::default().fee_rate_window_size);
+        if fee_rate_window_size == 0 || u32::try_from(fee_rate_window_size).is_err() {
+            panic!(
+                "Bad fee_rate_window_size supplied in configuration file: {} (must be between 1 and {})",
+                fee_rate_window_size,
+                u32::MAX
+            );
+        }
         Self {
             cost_estimator: Some(cost_estimator),
             fee_estimator: Some(fee_estimator),
             cost_metric: Some(cost_metric),
             log_error,
+            fee_rate_window_size,
+        }
+    }
+}
+
+/// Node-local mempool admission policies.  These are checked after a transaction passes the
+/// consensus validity checks, and are not part of consensus.
+#[derive(Clone, Debug, Default)]
+pub struct MemPoolPolicyConfig {
+    pub min_fee_rate: Option<f64>,
+    pub contract_allow_list: Option<Vec<QualifiedContractIdentifier>>,
+    pub contract_deny_list: Vec<QualifiedContractIdentifier>,
+    pub max_tx_cost: Option<ExecutionCost>,
+}
+
+fn parse_miner_contract_ids(ids: &Vec<String>) -> Vec<QualifiedContractIdentifier> {
+    ids.iter()
+        .map(|id| {
+            QualifiedContractIdentifier::parse(id)
+                .unwrap_or_else(|_| panic!("Bad contract identifier supplied in miner: {}", id))
+        })
+        .collect()
+}
+
+impl From<MemPoolPolicyConfigFile> for MemPoolPolicyConfig {
+    fn from(f: MemPoolPolicyConfigFile) -> Self {
+        let parse_contract_ids = |ids: Vec<String>| -> Vec<QualifiedContractIdentifier> {
+            ids.iter()
+                .map(|id| {
+                    QualifiedContractIdentifier::parse(id).unwrap_or_else(|_| {
+                        panic!("Bad contract identifier supplied in mempool_policy: {}", id)
+                    })
+                })
+                .collect()
+        };
+
+        let max_tx_cost = if f.max_tx_runtime.is_some()
+            || f.max_tx_read_count.is_some()
+            || f.max_tx_read_length.is_some()
+            || f.max_tx_write_count.is_some()
+            || f.max_tx_write_length.is_some()
+        {
+            Some(ExecutionCost {
+                runtime: f.max_tx_runtime.unwrap_or(u64::MAX),
+                read_count: f.max_tx_read_count.unwrap_or(u64::MAX),
+                read_length: f.max_tx_read_length.unwrap_or(u64::MAX),
+                write_count: f.max_tx_write_count.unwrap_or(u64::MAX),
+                write_length: f.max_tx_write_length.unwrap_or(u64::MAX),
+            })
+        } else {
+            None
+        };
+
+        Self {
+            min_fee_rate: f.min_fee_rate,
+            contract_allow_list: f.contract_allow_list.map(parse_contract_ids),
+            contract_deny_list: f
+                .contract_deny_list
+                .map(parse_contract_ids)
+                .unwrap_or_default(),
+            max_tx_cost,
+        }
+    }
+}
+
+/// Node-local transaction relay policies.  These decide which transactions are forwarded to
+/// neighbors, and never affect what is stored in the mempool or accepted in blocks.
+#[derive(Clone, Debug, Default)]
+pub struct RelayPolicyConfig {
+    pub min_fee_rate: Option<f64>,
+    pub max_tx_size: Option<u64>,
+    pub contract_deny_list: Vec<QualifiedContractIdentifier>,
+}
+
+impl From<RelayPolicyConfigFile> for RelayPolicyConfig {
+    fn from(f: RelayPolicyConfigFile) -> Self {
+        let contract_deny_list = f
+            .contract_deny_list
+            .unwrap_or_default()
+            .iter()
+            .map(|id| {
+                QualifiedContractIdentifier::parse(id).unwrap_or_else(|_| {
+                    panic!("Bad contract identifier supplied in relay_policy: {}", id)
+                })
+            })
+            .collect();
+
+        Self {
+            min_fee_rate: f.min_fee_rate,
+            max_tx_size: f.max_tx_size,
+            contract_deny_list,
         }
     }
 }
 
 impl Config {
+    /// Instantiate the configured transaction relay policies
+    pub fn make_relay_policies(&self) -> Vec<Box<dyn MemPoolAdmissionPolicy>> {
+        let mut policies: Vec<Box<dyn MemPoolAdmissionPolicy>> = vec![];
+        if let Some(min_fee_rate) = self.relay_policy.min_fee_rate {
+            policies.push(Box::new(MinFeeRatePolicy { min_fee_rate }));
+        }
+        if let Some(max_tx_size) = self.relay_policy.max_tx_size {
+            policies.push(Box::new(MaxTxSizePolicy { max_tx_size }));
+        }
+        if !self.relay_policy.contract_deny_list.is_empty() {
+            policies.push(Box::new(ContractCallPolicy {
+                allow_list: None,
+                deny_list: self
+                    .relay_policy
+                    .contract_deny_list
+                    .iter()
+                    .cloned()
+                    .collect(),
+            }));
+        }
+        policies
+    }
+
+    /// Instantiate the configured mempool admission policies
+    pub fn make_mempool_admission_policies(&self) -> Vec<Box<dyn MemPoolAdmissionPolicy>> {
+        let mut policies: Vec<Box<dyn MemPoolAdmissionPolicy>> = vec![];
+        if let Some(min_fee_rate) = self.mempool_policy.min_fee_rate {
+            policies.push(Box::new(MinFeeRatePolicy { min_fee_rate }));
+        }
+        if self.mempool_policy.contract_allow_list.is_some()
+            || !self.mempool_policy.contract_deny_list.is_empty()
+        {
+            policies.push(Box::new(ContractCallPolicy {
+                allow_list: self
+                    .mempool_policy
+                    .contract_allow_list
+                    .as_ref()
+                    .map(|ids| ids.iter().cloned().collect()),
+                deny_list: self
+                    .mempool_policy
+                    .contract_deny_list
+                    .iter()
+                    .cloned()
+                    .collect(),
+            }));
+        }
+        if let Some(ref max_cost) = self.mempool_policy.max_tx_cost {
+            policies.push(Box::new(MaxCostPolicy {
+                max_cost: max_cost.clone(),
+            }));
+        }
+        policies
+    }
+
+    /// When a miner that started assembling a block at `start_ms` must commit to its best
+    /// candidate, if it assembles blocks iteratively.
+    pub fn get_block_assembly_deadline(&self, start_ms: u128) -> Option<u128> {
+        if self.miner.assembly_deadline_pct == 0 {
+            return None;
+        }
+        let interval_ms = (self.burnchain.block_interval_secs as u128) * 1000;
+        Some(start_ms + interval_ms * (self.miner.assembly_deadline_pct.min(100) as u128) / 100)
+    }
+
+    /// Walk strategy for the last round of iterative block assembly, which re-solves the block's
+    /// cost knapsack once the configured strategy has run out of transactions to add.  None if
+    /// the configured strategy already does this.
+    pub fn make_block_assembly_resolve_strategy(&self) -> Option<Box<dyn MemPoolWalkStrategy>> {
+        match self.miner.mempool_walk_strategy {
+            MemPoolWalkStrategyName::Knapsack => None,
+            _ => Some(Box::new(KnapsackWalkStrategy::new(
+                KNAPSACK_WALK_CANDIDATES,
+            ))),
+        }
+    }
+
+    /// Instantiate the configured mempool walk strategy for the miner
+    pub fn make_mempool_walk_strategy(&self) -> Box<dyn MemPoolWalkStrategy> {
+        match self.miner.mempool_walk_strategy {
+            MemPoolWalkStrategyName::FeeRate => Box::new(FeeRateWalkStrategy),
+            MemPoolWalkStrategyName::Knapsack => {
+                Box::new(KnapsackWalkStrategy::new(KNAPSACK_WALK_CANDIDATES))
+            }
+            MemPoolWalkStrategyName::Fifo => Box::new(FifoWalkStrategy),
+        }
+    }
+
     pub fn make_cost_estimator(&self) -> Option<Box<dyn CostEstimator>> {
         let cost_estimator: Box<dyn CostEstimator> =
             match self.estimation.cost_estimator.as_ref()? {
@@ -1182,6 +2823,10 @@ impl Config {
                 self.estimation
                     .make_scalar_fee_estimator(self.get_chainstate_path(), metric),
             ),
+            FeeEstimatorName::WeightedMedianFeeRate => Box::new(
+                self.estimation
+                    .make_weighted_median_fee_estimator(self.get_chainstate_path(), metric),
+            ),
         };
 
         Some(fee_estimator)
@@ -1215,6 +2860,22 @@ impl FeeEstimationConfig {
             panic!("BUG: Expected to configure a scalar fee estimator");
         }
     }
+
+    pub fn make_weighted_median_fee_estimator<CM: CostMetric>(
+        &self,
+        mut chainstate_path: PathBuf,
+        metric: CM,
+    ) -> WeightedMedianFeeRateEstimator<CM> {
+        if let Some(FeeEstimatorName::WeightedMedianFeeRate) = self.fee_estimator.as_ref() {
+            chainstate_path.push("fee_estimator_median_rate.sqlite");
+            let window_size = u32::try_from(self.fee_rate_window_size)
+                .expect("BUG: fee_rate_window_size does not fit in a u32");
+            WeightedMedianFeeRateEstimator::open(&chainstate_path, metric, window_size)
+                .expect("Error opening fee estimator")
+        } else {
+            panic!("BUG: Expected to configure a weighted median fee estimator");
+        }
+    }
 }
 
 impl NodeConfig {
@@ -1256,6 +2917,33 @@ impl NodeConfig {
             prometheus_bind: None,
             pox_sync_sample_secs: 30,
             use_test_genesis_chainstate: None,
+            genesis_chainstate_file: None,
+            genesis_name_zonefiles_file: None,
+            bootstrap_snapshot_url: None,
+            bootstrap_snapshot_digest: None,
+            prune_depth: None,
+            address_txs_index: false,
+            asset_holdings_index: false,
+            cost_breakdown: false,
+            event_stream_bind: None,
+            event_stream_auth_token: None,
+            event_queue: false,
+            event_replay_depth: 144,
+            mempool_warm_restart: true,
+            mempool_replace_by_fee_increment: MEMPOOL_DEFAULT_REPLACE_BY_FEE_INCREMENT,
+            mempool_max_txs_per_origin: MEMPOOL_DEFAULT_MAX_TXS_PER_ORIGIN,
+            mempool_max_bytes_per_origin: MEMPOOL_DEFAULT_MAX_BYTES_PER_ORIGIN,
+            mempool_max_txs: MEMPOOL_DEFAULT_MAX_TXS,
+            poison_microblock_reporter_key: None,
+            poison_microblock_report_fee: 10_000,
+        }
+    }
+
+    pub fn get_mempool_limits(&self) -> MemPoolLimits {
+        MemPoolLimits {
+            max_txs_per_origin: self.mempool_max_txs_per_origin,
+            max_bytes_per_origin: self.mempool_max_bytes_per_origin,
+            max_txs: self.mempool_max_txs,
         }
     }
 
@@ -1341,6 +3029,28 @@ pub struct MinerConfig {
     pub first_attempt_time_ms: u64,
     pub subsequent_attempt_time_ms: u64,
     pub probability_pick_no_estimate_tx: u8,
+    /// order in which the miner considers mempool transactions
+    pub mempool_walk_strategy: MemPoolWalkStrategyName,
+    /// Percentage of the burnchain block interval the miner may spend improving its candidate
+    /// block before committing to it.  0 means the miner assembles its block in one shot.
+    pub assembly_deadline_pct: u64,
+    /// Most candidate blocks to assemble on top of Stacks blocks that compete with the canonical
+    /// tip, alongside the block on the canonical tip.  0 means only the canonical tip is mined.
+    pub max_fork_candidates: u64,
+    /// If given, only contract-calls to these contracts are mined
+    pub contract_allow_list: Option<Vec<QualifiedContractIdentifier>>,
+    /// Contract-calls to these contracts are never mined
+    pub contract_deny_list: Vec<QualifiedContractIdentifier>,
+    /// URL of the external service that holds the miner's burnchain and VRF keys, if any
+    pub remote_signer_url: Option<String>,
+    /// bearer token to authenticate to the remote signer with
+    pub remote_signer_auth_token: Option<String>,
+    /// Most of the block budget, as a percentage, that the microblocks streamed after a mined
+    /// block may spend
+    pub microblock_max_cost_pct: u64,
+    /// Minimum fee a transaction must pay to be mined into a microblock, rather than waiting for
+    /// the next anchored block
+    pub microblock_min_tx_fee: u64,
 }
 
 impl MinerConfig {
@@ -1350,6 +3060,42 @@ impl MinerConfig {
             first_attempt_time_ms: 1_000,
             subsequent_attempt_time_ms: 60_000,
             probability_pick_no_estimate_tx: 5,
+            mempool_walk_strategy: MemPoolWalkStrategyName::default(),
+            assembly_deadline_pct: 0,
+            max_fork_candidates: 0,
+            contract_allow_list: None,
+            contract_deny_list: vec![],
+            remote_signer_url: None,
+            remote_signer_auth_token: None,
+            microblock_max_cost_pct: 100,
+            microblock_min_tx_fee: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MemPoolWalkStrategyName {
+    FeeRate,
+    Knapsack,
+    Fifo,
+}
+
+impl Default for MemPoolWalkStrategyName {
+    fn default() -> Self {
+        MemPoolWalkStrategyName::FeeRate
+    }
+}
+
+impl MemPoolWalkStrategyName {
+    fn panic_parse(s: String) -> MemPoolWalkStrategyName {
+        match s.to_lowercase().as_str() {
+            "fee_rate" => MemPoolWalkStrategyName::FeeRate,
+            "knapsack" => MemPoolWalkStrategyName::Knapsack,
+            "fifo" => MemPoolWalkStrategyName::Fifo,
+            _ => panic!(
+                "Bad mempool walk strategy name supplied in configuration file: {}",
+                s
+            ),
         }
     }
 }
@@ -1362,6 +3108,18 @@ pub struct ConnectionOptionsFile {
     pub handshake_timeout: Option<u64>,
     pub timeout: Option<u64>,
     pub idle_timeout: Option<u64>,
+    pub http_keep_alive_timeout: Option<u64>,
+    pub max_http_requests_per_connection: Option<u64>,
+    pub http_compression_threshold: Option<u64>,
+    pub cors_allowed_origins: Option<String>,
+    pub cors_allowed_methods: Option<String>,
+    pub cors_allowed_headers: Option<String>,
+    pub cors_max_age: Option<u64>,
+    pub rpc_admin_token: Option<String>,
+    pub rpc_monitor_token: Option<String>,
+    pub rpc_rate_limit_read: Option<u64>,
+    pub rpc_rate_limit_compute: Option<u64>,
+    pub rpc_rate_limit_submit: Option<u64>,
     pub heartbeat: Option<u32>,
     pub private_key_lifetime: Option<u64>,
     pub num_neighbors: Option<u64>,
@@ -1378,12 +3136,25 @@ pub struct ConnectionOptionsFile {
     pub walk_interval: Option<u64>,
     pub dns_timeout: Option<u64>,
     pub max_inflight_blocks: Option<u64>,
+    pub max_inflight_blocks_per_peer: Option<u64>,
     pub max_inflight_attachments: Option<u64>,
     pub read_only_call_limit_write_length: Option<u64>,
     pub read_only_call_limit_read_length: Option<u64>,
     pub read_only_call_limit_write_count: Option<u64>,
     pub read_only_call_limit_read_count: Option<u64>,
     pub read_only_call_limit_runtime: Option<u64>,
+    pub read_only_call_time_limit_ms: Option<u64>,
+    pub read_only_call_max_result_size: Option<u64>,
+    pub read_only_call_workers: Option<usize>,
+    pub read_only_call_queue_size: Option<usize>,
+    pub health_max_burn_block_lag: Option<u64>,
+    pub health_max_stacks_block_lag: Option<u64>,
+    pub simulate_transaction_limit_write_length: Option<u64>,
+    pub simulate_transaction_limit_read_length: Option<u64>,
+    pub simulate_transaction_limit_write_count: Option<u64>,
+    pub simulate_transaction_limit_read_count: Option<u64>,
+    pub simulate_transaction_limit_runtime: Option<u64>,
+    pub simulate_transaction_max_len: Option<u64>,
     pub maximum_call_argument_size: Option<u32>,
     pub download_interval: Option<u64>,
     pub inv_sync_interval: Option<u64>,
@@ -1395,6 +3166,24 @@ pub struct ConnectionOptionsFile {
     pub disable_block_download: Option<bool>,
     pub force_disconnect_interval: Option<u64>,
     pub antientropy_public: Option<bool>,
+    pub mempool_sync_interval: Option<u64>,
+    pub mempool_sync_max_txs: Option<u64>,
+    pub compact_block_relay: Option<bool>,
+    pub peer_ban_score_threshold: Option<u64>,
+    pub peer_score_ban_duration: Option<u64>,
+    pub peer_score_decay_interval: Option<u64>,
+    pub dns_seeds: Option<String>,
+    pub port_mapping: Option<bool>,
+    pub port_mapping_gateway: Option<String>,
+    pub port_mapping_lifetime: Option<u64>,
+    pub max_clients_per_subnet: Option<u64>,
+    pub client_subnet_prefix_v4: Option<u8>,
+    pub client_subnet_prefix_v6: Option<u8>,
+    pub max_relay_bandwidth_per_peer: Option<u64>,
+    pub private_network_key: Option<String>,
+    pub private_network_peers: Option<String>,
+    pub subscribe_block_announcements: Option<bool>,
+    pub block_announcement_jitter_ms: Option<u64>,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -1418,6 +3207,44 @@ pub struct NodeConfigFile {
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: Option<u64>,
     pub use_test_genesis_chainstate: Option<bool>,
+    pub genesis_chainstate_file: Option<String>,
+    pub genesis_name_zonefiles_file: Option<String>,
+    pub bootstrap_snapshot_url: Option<String>,
+    pub bootstrap_snapshot_digest: Option<String>,
+    pub prune_depth: Option<u64>,
+    pub address_txs_index: Option<bool>,
+    pub asset_holdings_index: Option<bool>,
+    pub cost_breakdown: Option<bool>,
+    pub event_stream_bind: Option<String>,
+    pub event_stream_auth_token: Option<String>,
+    pub event_queue: Option<bool>,
+    pub event_replay_depth: Option<u64>,
+    pub mempool_warm_restart: Option<bool>,
+    pub mempool_replace_by_fee_increment: Option<u64>,
+    pub mempool_max_txs_per_origin: Option<u64>,
+    pub mempool_max_bytes_per_origin: Option<u64>,
+    pub mempool_max_txs: Option<u64>,
+    pub poison_microblock_reporter_key: Option<String>,
+    pub poison_microblock_report_fee: Option<u64>,
+}
+
+#[derive(Clone, Deserialize, Default)]
+pub struct MemPoolPolicyConfigFile {
+    pub min_fee_rate: Option<f64>,
+    pub contract_allow_list: Option<Vec<String>>,
+    pub contract_deny_list: Option<Vec<String>>,
+    pub max_tx_runtime: Option<u64>,
+    pub max_tx_read_count: Option<u64>,
+    pub max_tx_read_length: Option<u64>,
+    pub max_tx_write_count: Option<u64>,
+    pub max_tx_write_length: Option<u64>,
+}
+
+#[derive(Clone, Deserialize, Default)]
+pub struct RelayPolicyConfigFile {
+    pub min_fee_rate: Option<f64>,
+    pub max_tx_size: Option<u64>,
+    pub contract_deny_list: Option<Vec<String>>,
 }
 
 #[derive(Clone, Deserialize)]
@@ -1427,6 +3254,7 @@ pub struct FeeEstimationConfigFile {
     pub cost_metric: Option<String>,
     pub disabled: Option<bool>,
     pub log_error: Option<bool>,
+    pub fee_rate_window_size: Option<u64>,
 }
 
 impl Default for FeeEstimationConfigFile {
@@ -1437,6 +3265,7 @@ impl Default for FeeEstimationConfigFile {
             cost_metric: None,
             disabled: None,
             log_error: None,
+            fee_rate_window_size: None,
         }
     }
 }
@@ -1447,24 +3276,61 @@ pub struct MinerConfigFile {
     pub first_attempt_time_ms: Option<u64>,
     pub subsequent_attempt_time_ms: Option<u64>,
     pub probability_pick_no_estimate_tx: Option<u8>,
+    pub mempool_walk_strategy: Option<String>,
+    pub assembly_deadline_pct: Option<u64>,
+    pub max_fork_candidates: Option<u64>,
+    pub contract_allow_list: Option<Vec<String>>,
+    pub contract_deny_list: Option<Vec<String>>,
+    pub remote_signer_url: Option<String>,
+    pub remote_signer_auth_token: Option<String>,
+    /// overrides `[node] microblock_frequency`
+    pub microblock_interval_ms: Option<u64>,
+    pub microblock_max_cost_pct: Option<u64>,
+    pub microblock_min_tx_fee: Option<u64>,
 }
 
 #[derive(Clone, Deserialize, Default)]
 pub struct EventObserverConfigFile {
     pub endpoint: String,
     pub events_keys: Vec<String>,
+    pub filter_transactions: Option<bool>,
+    pub include_tx_details: Option<bool>,
 }
 
 #[derive(Clone, Default)]
 pub struct EventObserverConfig {
     pub endpoint: String,
     pub events_keys: Vec<EventKeyType>,
+    /// Only send the transactions that emitted events this observer subscribed to, and skip
+    /// block and microblock payloads with no such events.
+    pub filter_transactions: bool,
+    /// Add the decoded transaction (sender, nonce, fee and payload, including contract-call
+    /// arguments) to every transaction in block and microblock payloads.
+    pub include_tx_details: bool,
 }
 
+/// Event type names that can be used as event keys, matching the `type` field of serialized
+/// transaction events.
+const EVENT_TYPE_KEYS: &[&str] = &[
+    "contract_event",
+    "stx_transfer_event",
+    "stx_mint_event",
+    "stx_burn_event",
+    "stx_lock_event",
+    "nft_transfer_event",
+    "nft_mint_event",
+    "nft_burn_event",
+    "ft_transfer_event",
+    "ft_mint_event",
+    "ft_burn_event",
+];
+
 #[derive(Clone)]
 pub enum EventKeyType {
     SmartContractEvent((QualifiedContractIdentifier, String)),
     AssetEvent(AssetIdentifier),
+    ContractEvents(QualifiedContractIdentifier),
+    EventType(String),
     STXEvent,
     MemPoolTransactions,
     Microblocks,
@@ -1474,7 +3340,7 @@ pub enum EventKeyType {
 }
 
 impl EventKeyType {
-    fn from_string(raw_key: &str) -> Option<EventKeyType> {
+    pub fn from_string(raw_key: &str) -> Option<EventKeyType> {
         if raw_key == "*" {
             return Some(EventKeyType::AnyEvent);
         }
@@ -1495,9 +3361,19 @@ impl EventKeyType {
             return Some(EventKeyType::Microblocks);
         }
 
+        if EVENT_TYPE_KEYS.contains(&raw_key) {
+            return Some(EventKeyType::EventType(raw_key.to_string()));
+        }
+
         let comps: Vec<_> = raw_key.split("::").collect();
         if comps.len() == 1 {
             let split: Vec<_> = comps[0].split(".").collect();
+            if split.len() == 2 {
+                // every event emitted by a contract
+                return QualifiedContractIdentifier::parse(comps[0])
+                    .ok()
+                    .map(EventKeyType::ContractEvents);
+            }
             if split.len() != 3 {
                 return None;
             }
@@ -1543,3 +3419,11 @@ pub struct InitialBalanceFile {
     pub address: String,
     pub amount: u64,
 }
+
+/// An extra contract to deploy into the boot address at genesis
+#[derive(Clone, Deserialize, Default)]
+pub struct BootContractFile {
+    pub name: String,
+    /// Path to the contract's Clarity source
+    pub path: String,
+}
@@ -1,4 +1,5 @@
 use std::collections::hash_map::Entry;
+use std::thread;
 use std::thread::sleep;
 use std::time::Duration;
 use std::{
@@ -15,7 +16,7 @@ use http_types::{Method, Request, Url};
 use serde_json::json;
 
 use stacks::burnchains::Txid;
-use stacks::chainstate::coordinator::BlockEventDispatcher;
+use stacks::chainstate::coordinator::{BlockEventDispatcher, CanonicalForkChange};
 use stacks::chainstate::stacks::db::StacksHeaderInfo;
 use stacks::chainstate::stacks::events::{
     FTEventType, NFTEventType, STXEventType, StacksTransactionEvent, StacksTransactionReceipt,
@@ -23,11 +24,12 @@ use stacks::chainstate::stacks::events::{
 };
 use stacks::chainstate::stacks::StacksBlock;
 use stacks::chainstate::stacks::{
-    db::accounts::MinerReward, db::MinerRewardInfo, StacksTransaction,
+    db::accounts::MinerReward, db::MinerRewardInfo, StacksTransaction, TransactionPayload,
 };
 use stacks::codec::StacksMessageCodec;
 use stacks::core::mempool::{MemPoolDropReason, MemPoolEventDispatcher};
 use stacks::net::atlas::{Attachment, AttachmentInstance};
+use stacks::net::rpc::EventReplayDispatcher;
 use stacks::types::chainstate::{BurnchainHeaderHash, StacksAddress, StacksBlockId};
 use stacks::util::hash::bytes_to_hex;
 use stacks::vm::analysis::contract_interface_builder::build_contract_interface;
@@ -35,13 +37,21 @@ use stacks::vm::costs::ExecutionCost;
 use stacks::vm::types::{AssetIdentifier, QualifiedContractIdentifier, Value};
 
 use super::config::{EventKeyType, EventObserverConfig};
+use super::event_queue::EventQueueDB;
+use super::event_stream::{EventStreamServer, EventStreamSubscription};
 use super::node::ChainTip;
 use stacks::chainstate::stacks::db::unconfirmed::ProcessedUnconfirmedState;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 struct EventObserver {
     endpoint: String,
     should_keep_running: Arc<AtomicBool>,
+    filter_transactions: bool,
+    include_tx_details: bool,
+    /// durable queue of payloads, shared by all observers, if enabled
+    queue: Option<Arc<Mutex<EventQueueDB>>>,
+    /// held while delivering, so that queued payloads go out in order and only once
+    delivery_lock: Arc<Mutex<()>>,
 }
 
 struct ReceiptPayloadInfo<'a> {
@@ -64,6 +74,11 @@ pub const PATH_MINED_BLOCK: &str = "mined_block";
 pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
 pub const PATH_BLOCK_PROCESSED: &str = "new_block";
 pub const PATH_ATTACHMENT_PROCESSED: &str = "attachments/new";
+pub const PATH_STACKS_REORG: &str = "stacks_reorg";
+
+/// Delay before retrying a failed POST, doubled on each consecutive failure up to the maximum
+const BACKOFF_INITIAL_MS: u64 = 1_000;
+const BACKOFF_MAX_MS: u64 = 60_000;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MinedBlockEvent {
@@ -77,7 +92,7 @@ pub struct MinedBlockEvent {
 
 impl EventObserver {
     fn send_payload(&self, payload: &serde_json::Value, path: &str) {
-        let body = match serde_json::to_vec(&payload) {
+        let body = match serde_json::to_string(&payload) {
             Ok(body) => body,
             Err(err) => {
                 error!("Event dispatcher: serialization failed  - {:?}", err);
@@ -85,6 +100,94 @@ impl EventObserver {
             }
         };
 
+        let queue = match self.queue {
+            Some(ref queue) => queue,
+            None => {
+                self.post_until_delivered(body.as_bytes(), path);
+                return;
+            }
+        };
+
+        let block_height = if path == PATH_BLOCK_PROCESSED {
+            payload
+                .get("block_height")
+                .and_then(|height| height.as_u64())
+        } else {
+            None
+        };
+
+        let _delivery = self
+            .delivery_lock
+            .lock()
+            .expect("FATAL: event observer delivery lock poisoned");
+        let enqueued = queue
+            .lock()
+            .expect("FATAL: event queue lock poisoned")
+            .enqueue(&self.endpoint, path, &body, block_height);
+        match enqueued {
+            Ok(_) => self.deliver_queued(queue),
+            Err(e) => {
+                error!(
+                    "Event dispatcher: failed to queue payload for {}: {:?}",
+                    &self.endpoint, &e
+                );
+                self.post_until_delivered(body.as_bytes(), path);
+            }
+        }
+    }
+
+    /// Deliver every queued payload for this observer, oldest first
+    fn deliver_pending(&self) {
+        if let Some(ref queue) = self.queue {
+            let _delivery = self
+                .delivery_lock
+                .lock()
+                .expect("FATAL: event observer delivery lock poisoned");
+            self.deliver_queued(queue);
+        }
+    }
+
+    /// Deliver queued payloads.  The caller must hold the delivery lock.
+    fn deliver_queued(&self, queue: &Mutex<EventQueueDB>) {
+        loop {
+            let next = queue
+                .lock()
+                .expect("FATAL: event queue lock poisoned")
+                .next_pending(&self.endpoint);
+            let queued = match next {
+                Ok(Some(queued)) => queued,
+                Ok(None) => return,
+                Err(e) => {
+                    error!(
+                        "Event dispatcher: failed to read queued payloads for {}: {:?}",
+                        &self.endpoint, &e
+                    );
+                    return;
+                }
+            };
+
+            if !self.post_until_delivered(queued.payload.as_bytes(), &queued.path) {
+                // shutting down; the payload stays queued for next time
+                return;
+            }
+
+            if let Err(e) = queue
+                .lock()
+                .expect("FATAL: event queue lock poisoned")
+                .mark_delivered(queued.id)
+            {
+                error!(
+                    "Event dispatcher: failed to mark payload {} delivered to {}: {:?}",
+                    queued.id, &self.endpoint, &e
+                );
+                return;
+            }
+        }
+    }
+
+    /// POST a payload until the observer accepts it, backing off exponentially between
+    /// attempts.  Returns false if the node shut down before the payload was delivered.
+    fn post_until_delivered(&self, body: &[u8], path: &str) -> bool {
         let url = {
             let joined_components = match path.starts_with("/") {
                 true => format!("{}{}", &self.endpoint, path),
@@ -97,19 +200,18 @@ impl EventObserver {
             ))
         };
 
-        let backoff = Duration::from_millis((1.0 * 1_000.0) as u64);
+        let mut backoff = Duration::from_millis(BACKOFF_INITIAL_MS);
 
         loop {
             if !self.should_keep_running.load(Ordering::SeqCst) {
                 info!("Terminating event observer");
-                return;
+                return false;
             }
 
-            let body = body.clone();
             let mut req = Request::new(Method::Post, url.clone());
             req.append_header("Content-Type", "application/json")
                 .expect("Unable to set header");
-            req.set_body(body);
+            req.set_body(body.to_vec());
 
             let response = async_std::task::block_on(async {
                 let stream = match TcpStream::connect(self.endpoint.clone()).await {
@@ -131,7 +233,7 @@ impl EventObserver {
 
             if let Some(response) = response {
                 if response.status().is_success() {
-                    break;
+                    return true;
                 } else {
                     error!(
                         "Event dispatcher: POST {}/{} failed with error {:?}",
@@ -140,6 +242,7 @@ impl EventObserver {
                 }
             }
             sleep(backoff);
+            backoff = std::cmp::min(backoff * 2, Duration::from_millis(BACKOFF_MAX_MS));
         }
     }
 
@@ -244,12 +347,78 @@ impl EventObserver {
             "raw_tx": format!("0x{}", &receipt_payload_info.raw_tx),
             "contract_abi": receipt_payload_info.contract_interface_json,
             "execution_cost": receipt.execution_cost,
+            "cost_breakdown": receipt.cost_breakdown,
             "microblock_sequence": receipt.microblock_header.as_ref().map(|x| x.sequence),
             "microblock_hash": receipt.microblock_header.as_ref().map(|x| format!("0x{}", x.block_hash())),
             "microblock_parent_hash": receipt.microblock_header.as_ref().map(|x| format!("0x{}", x.prev_block)),
         })
     }
 
+    /// Decodes a transaction into a JSON object, so observers don't have to parse `raw_tx`
+    /// themselves.  Contract-call arguments are given both as consensus-serialized hex and as
+    /// decoded Clarity values, and smart contracts carry their source code.
+    fn make_tx_details_payload(tx: &TransactionOrigin) -> serde_json::Value {
+        let tx = match tx {
+            TransactionOrigin::Stacks(ref tx) => tx,
+            TransactionOrigin::Burn(_) => return json!(null),
+        };
+
+        let payload = match tx.payload {
+            TransactionPayload::TokenTransfer(ref recipient, ref amount, ref memo) => json!({
+                "type": "token_transfer",
+                "recipient": format!("{}", recipient),
+                "amount": format!("{}", amount),
+                "memo": format!("0x{}", bytes_to_hex(&memo.0)),
+            }),
+            TransactionPayload::ContractCall(ref cc) => {
+                let function_args: Vec<serde_json::Value> = cc
+                    .function_args
+                    .iter()
+                    .map(|arg| {
+                        json!({
+                            "hex": format!("0x{}", bytes_to_hex(&arg.serialize_to_vec())),
+                            "repr": format!("{}", arg),
+                            "value": arg.to_json(),
+                        })
+                    })
+                    .collect();
+                json!({
+                    "type": "contract_call",
+                    "contract_id": format!("{}.{}", cc.address, cc.contract_name),
+                    "function_name": cc.function_name.as_str(),
+                    "function_args": function_args,
+                })
+            }
+            TransactionPayload::SmartContract(ref sc) => json!({
+                "type": "smart_contract",
+                "contract_id": format!("{}.{}", tx.origin_address(), sc.name),
+                "source_code": sc.code_body.to_string(),
+            }),
+            TransactionPayload::PoisonMicroblock(ref h1, ref h2) => json!({
+                "type": "poison_microblock",
+                "microblock_hashes": [
+                    format!("0x{}", h1.block_hash()),
+                    format!("0x{}", h2.block_hash()),
+                ],
+            }),
+            TransactionPayload::Coinbase(ref payload) => json!({
+                "type": "coinbase",
+                "payload": format!("0x{}", bytes_to_hex(&payload.0)),
+            }),
+        };
+
+        json!({
+            "sender_address": format!("{}", tx.origin_address()),
+            "sponsor_address": tx.sponsor_address().map(|addr| format!("{}", addr)),
+            "nonce": tx.get_origin_nonce(),
+            "fee_rate": tx.get_tx_fee(),
+            "anchor_mode": format!("{:?}", tx.anchor_mode),
+            "post_condition_mode": format!("{:?}", tx.post_condition_mode),
+            "post_conditions_count": tx.post_conditions.len(),
+            "payload": payload,
+        })
+    }
+
     fn make_new_attachment_payload(
         attachment: &(AttachmentInstance, Attachment),
     ) -> serde_json::Value {
@@ -273,16 +442,15 @@ impl EventObserver {
         self.send_payload(payload, PATH_MEMPOOL_TX_SUBMIT);
     }
 
-    /// Serializes new microblocks data into a JSON payload and sends it off to the correct path
-    fn send_new_microblocks(
-        &self,
+    /// Serializes new microblocks data into a JSON payload
+    fn make_new_microblocks_payload(
         parent_index_block_hash: StacksBlockId,
         filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
         serialized_txs: &Vec<serde_json::Value>,
         burn_block_hash: BurnchainHeaderHash,
         burn_block_height: u32,
         burn_block_timestamp: u64,
-    ) {
+    ) -> serde_json::Value {
         // Serialize events to JSON
         let serialized_events: Vec<serde_json::Value> = filtered_events
             .iter()
@@ -291,14 +459,54 @@ impl EventObserver {
             })
             .collect();
 
-        let payload = json!({
+        json!({
             "parent_index_block_hash": format!("0x{}", parent_index_block_hash),
             "events": serialized_events,
             "transactions": serialized_txs,
             "burn_block_hash": format!("0x{}", burn_block_hash),
             "burn_block_height": burn_block_height,
             "burn_block_timestamp": burn_block_timestamp,
-        });
+        })
+    }
+
+    /// Serializes new microblocks data into a JSON payload and sends it off to the correct path
+    fn send_new_microblocks(
+        &self,
+        parent_index_block_hash: StacksBlockId,
+        filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
+        serialized_txs: &Vec<(&StacksTransactionReceipt, serde_json::Value)>,
+        burn_block_hash: BurnchainHeaderHash,
+        burn_block_height: u32,
+        burn_block_timestamp: u64,
+    ) {
+        if self.filter_transactions && filtered_events.is_empty() {
+            return;
+        }
+
+        let matched_txids = event_txids(&filtered_events);
+        let serialized_txs = serialized_txs
+            .iter()
+            .filter(|(receipt, _)| {
+                !self.filter_transactions || matched_txids.contains(&receipt.transaction.txid())
+            })
+            .map(|(receipt, payload)| {
+                let mut payload = payload.clone();
+                if self.include_tx_details {
+                    payload["tx_details"] =
+                        EventObserver::make_tx_details_payload(&receipt.transaction);
+                }
+                payload
+            })
+            .collect();
+
+        let payload = EventObserver::make_new_microblocks_payload(
+            parent_index_block_hash,
+            filtered_events,
+            &serialized_txs,
+            burn_block_hash,
+            burn_block_height,
+            burn_block_timestamp,
+        );
 
         self.send_payload(&payload, PATH_MICROBLOCK_SUBMIT);
     }
@@ -315,9 +523,33 @@ impl EventObserver {
         self.send_payload(payload, PATH_BURN_BLOCK_SUBMIT);
     }
 
-    fn send(
-        &self,
+    fn send_stacks_reorg(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_STACKS_REORG);
+    }
+
+    fn make_stacks_reorg_payload(fork_change: &CanonicalForkChange) -> serde_json::Value {
+        let block_json = |(index_block_hash, block_height): &(StacksBlockId, u64)| {
+            json!({
+                "index_block_hash": format!("0x{}", index_block_hash),
+                "block_height": block_height,
+            })
+        };
+        json!({
+            "old_tip": block_json(&fork_change.old_tip),
+            "new_tip": block_json(&fork_change.new_tip),
+            "fork_point": block_json(&fork_change.fork_point),
+            "orphaned_blocks": fork_change.orphaned.iter().map(block_json).collect::<Vec<_>>(),
+            "new_canonical_blocks": fork_change.joined.iter().map(block_json).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Serializes a processed block into a JSON payload.  If `filter_transactions` is set, only
+    /// the transactions that emitted one of `filtered_events` are included.  If
+    /// `include_tx_details` is set, each transaction carries its decoded form.
+    fn make_new_block_processed_payload(
         filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
+        filter_transactions: bool,
+        include_tx_details: bool,
         chain_tip: &ChainTip,
         parent_index_hash: &StacksBlockId,
         boot_receipts: &Vec<StacksTransactionReceipt>,
@@ -328,7 +560,7 @@ impl EventObserver {
         parent_burn_block_timestamp: u64,
         anchored_consumed: &ExecutionCost,
         mblock_confirmed_consumed: &ExecutionCost,
-    ) {
+    ) -> serde_json::Value {
         // Serialize events to JSON
         let serialized_events: Vec<serde_json::Value> = filtered_events
             .iter()
@@ -337,17 +569,24 @@ impl EventObserver {
             })
             .collect();
 
+        let matched_txids = event_txids(&filtered_events);
         let mut tx_index: u32 = 0;
         let mut serialized_txs = vec![];
 
         for receipt in chain_tip.receipts.iter().chain(boot_receipts.iter()) {
-            let payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
-            serialized_txs.push(payload);
+            if !filter_transactions || matched_txids.contains(&receipt.transaction.txid()) {
+                let mut payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
+                if include_tx_details {
+                    payload["tx_details"] =
+                        EventObserver::make_tx_details_payload(&receipt.transaction);
+                }
+                serialized_txs.push(payload);
+            }
             tx_index += 1;
         }
 
         // Wrap events
-        let payload = json!({
+        json!({
             "block_hash": format!("0x{}", chain_tip.block.block_hash()),
             "block_height": chain_tip.metadata.block_height,
             "burn_block_hash": format!("0x{}", chain_tip.metadata.burn_header_hash),
@@ -367,18 +606,122 @@ impl EventObserver {
             "parent_burn_block_timestamp": parent_burn_block_timestamp,
             "anchored_cost": anchored_consumed,
             "confirmed_microblocks_cost": mblock_confirmed_consumed,
-        });
+        })
+    }
+
+    fn send(
+        &self,
+        filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
+        chain_tip: &ChainTip,
+        parent_index_hash: &StacksBlockId,
+        boot_receipts: &Vec<StacksTransactionReceipt>,
+        winner_txid: &Txid,
+        mature_rewards: &serde_json::Value,
+        parent_burn_block_hash: BurnchainHeaderHash,
+        parent_burn_block_height: u32,
+        parent_burn_block_timestamp: u64,
+        anchored_consumed: &ExecutionCost,
+        mblock_confirmed_consumed: &ExecutionCost,
+    ) {
+        if self.filter_transactions && filtered_events.is_empty() {
+            return;
+        }
+
+        let payload = EventObserver::make_new_block_processed_payload(
+            filtered_events,
+            self.filter_transactions,
+            self.include_tx_details,
+            chain_tip,
+            parent_index_hash,
+            boot_receipts,
+            winner_txid,
+            mature_rewards,
+            parent_burn_block_hash,
+            parent_burn_block_height,
+            parent_burn_block_timestamp,
+            anchored_consumed,
+            mblock_confirmed_consumed,
+        );
 
         // Send payload
         self.send_payload(&payload, PATH_BLOCK_PROCESSED);
     }
 }
 
+/// The transactions that emitted any of the given events
+fn event_txids(
+    filtered_events: &[(usize, &(bool, Txid, &StacksTransactionEvent))],
+) -> HashSet<Txid> {
+    filtered_events
+        .iter()
+        .map(|(_, (_, txid, _))| txid.clone())
+        .collect()
+}
+
+/// Does this event key select this transaction event?
+pub fn event_key_matches(key: &EventKeyType, event: &StacksTransactionEvent) -> bool {
+    match (key, event) {
+        (EventKeyType::AnyEvent, _) => true,
+        (EventKeyType::STXEvent, StacksTransactionEvent::STXEvent(_)) => true,
+        (
+            EventKeyType::SmartContractEvent(event_key),
+            StacksTransactionEvent::SmartContractEvent(event_data),
+        ) => *event_key == event_data.key,
+        (EventKeyType::AssetEvent(asset_identifier), StacksTransactionEvent::NFTEvent(_))
+        | (EventKeyType::AssetEvent(asset_identifier), StacksTransactionEvent::FTEvent(_)) => {
+            event_asset_identifier(event) == Some(asset_identifier)
+        }
+        (EventKeyType::ContractEvents(contract_identifier), _) => {
+            event.contract_identifier() == Some(contract_identifier)
+        }
+        (EventKeyType::EventType(event_type), _) => event.event_type() == event_type.as_str(),
+        (_, _) => false,
+    }
+}
+
+fn event_asset_identifier(event: &StacksTransactionEvent) -> Option<&AssetIdentifier> {
+    match event {
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        _ => None,
+    }
+}
+
+/// Select the events a stream client has subscribed to, in the same form as a dispatch matrix row
+fn filter_events_for_subscription<'a, 'b>(
+    subscription: &EventStreamSubscription,
+    events: &'b Vec<(bool, Txid, &'a StacksTransactionEvent)>,
+) -> Vec<(usize, &'b (bool, Txid, &'a StacksTransactionEvent))> {
+    events
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, _, event))| subscription.wants_event(event))
+        .collect()
+}
+
 #[derive(Clone)]
 pub struct EventDispatcher {
     registered_observers: Vec<EventObserver>,
     contract_events_observers_lookup: HashMap<(QualifiedContractIdentifier, String), HashSet<u16>>,
     assets_observers_lookup: HashMap<AssetIdentifier, HashSet<u16>>,
+    contracts_observers_lookup: HashMap<QualifiedContractIdentifier, HashSet<u16>>,
+    event_types_observers_lookup: HashMap<String, HashSet<u16>>,
     burn_block_observers_lookup: HashSet<u16>,
     mempool_observers_lookup: HashSet<u16>,
     microblock_observers_lookup: HashSet<u16>,
@@ -386,6 +729,37 @@ pub struct EventDispatcher {
     any_event_observers_lookup: HashSet<u16>,
     miner_observers_lookup: HashSet<u16>,
     boot_receipts: Arc<Mutex<Option<Vec<StacksTransactionReceipt>>>>,
+    event_stream: Option<EventStreamServer>,
+    event_queue: Option<Arc<Mutex<EventQueueDB>>>,
+}
+
+impl EventReplayDispatcher for EventDispatcher {
+    fn replay_events(&self, endpoint: &str, from_height: u64) -> Result<u64, String> {
+        let queue = self
+            .event_queue
+            .as_ref()
+            .ok_or_else(|| "Durable event queue is not enabled".to_string())?;
+        let observer = self
+            .registered_observers
+            .iter()
+            .find(|observer| observer.endpoint == endpoint)
+            .ok_or_else(|| format!("No such event observer '{}'", endpoint))?
+            .clone();
+
+        let replayed = queue
+            .lock()
+            .expect("FATAL: event queue lock poisoned")
+            .requeue_from_height(endpoint, from_height)
+            .map_err(|e| format!("Failed to requeue events: {:?}", &e))?;
+
+        if replayed > 0 {
+            thread::Builder::new()
+                .name(format!("event-replay-{}", endpoint))
+                .spawn(move || observer.deliver_pending())
+                .map_err(|e| format!("Failed to start replay: {:?}", &e))?;
+        }
+        Ok(replayed)
+    }
 }
 
 impl MemPoolEventDispatcher for EventDispatcher {
@@ -468,6 +842,10 @@ impl BlockEventDispatcher for EventDispatcher {
     fn dispatch_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>) {
         self.process_boot_receipts(receipts)
     }
+
+    fn announce_reorg(&self, fork_change: &CanonicalForkChange) {
+        self.process_stacks_reorg(fork_change)
+    }
 }
 
 impl EventDispatcher {
@@ -476,6 +854,8 @@ impl EventDispatcher {
             registered_observers: vec![],
             contract_events_observers_lookup: HashMap::new(),
             assets_observers_lookup: HashMap::new(),
+            contracts_observers_lookup: HashMap::new(),
+            event_types_observers_lookup: HashMap::new(),
             stx_observers_lookup: HashSet::new(),
             any_event_observers_lookup: HashSet::new(),
             burn_block_observers_lookup: HashSet::new(),
@@ -483,6 +863,39 @@ impl EventDispatcher {
             microblock_observers_lookup: HashSet::new(),
             boot_receipts: Arc::new(Mutex::new(None)),
             miner_observers_lookup: HashSet::new(),
+            event_stream: None,
+            event_queue: None,
+        }
+    }
+
+    /// Write every payload to the durable queue at `path` before delivering it.  Must be called
+    /// before any observers are registered.
+    pub fn enable_event_queue(&mut self, path: &str, replay_depth: u64) {
+        assert!(
+            self.registered_observers.is_empty(),
+            "The event queue must be enabled before observers are registered"
+        );
+        let queue = EventQueueDB::open(path, replay_depth)
+            .expect("FATAL: failed to open event observer queue");
+        info!("Event dispatcher: using durable event queue at {}", path);
+        self.event_queue = Some(Arc::new(Mutex::new(queue)));
+    }
+
+    /// Are any stream clients connected?
+    fn has_stream_clients(&self) -> bool {
+        self.event_stream
+            .as_ref()
+            .map(|server| server.has_clients())
+            .unwrap_or(false)
+    }
+
+    /// Queue a payload for every stream client for which `make_payload` returns one
+    fn broadcast_to_stream<F>(&self, path: &'static str, make_payload: F)
+    where
+        F: FnMut(&EventStreamSubscription) -> Option<serde_json::Value>,
+    {
+        if let Some(server) = self.event_stream.as_ref() {
+            server.broadcast(path, make_payload);
         }
     }
 
@@ -504,7 +917,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_stream_clients() {
             return;
         }
 
@@ -519,6 +932,36 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_new_burn_block(&payload);
         }
+
+        self.broadcast_to_stream(PATH_BURN_BLOCK_SUBMIT, |subscription| {
+            if subscription.wants_burn_blocks() {
+                Some(payload.clone())
+            } else {
+                None
+            }
+        });
+    }
+
+    /// Every observer gets `new_block` payloads, so every observer is told when blocks it was
+    /// sent are no longer canonical.
+    pub fn process_stacks_reorg(&self, fork_change: &CanonicalForkChange) {
+        if self.registered_observers.is_empty() && !self.has_stream_clients() {
+            return;
+        }
+
+        let payload = EventObserver::make_stacks_reorg_payload(fork_change);
+
+        for observer in self.registered_observers.iter() {
+            observer.send_stacks_reorg(&payload);
+        }
+
+        self.broadcast_to_stream(PATH_STACKS_REORG, |subscription| {
+            if subscription.wants_blocks() {
+                Some(payload.clone())
+            } else {
+                None
+            }
+        });
     }
 
     /// Iterates through tx receipts, and then the events corresponding to each receipt to
@@ -609,6 +1052,22 @@ impl EventDispatcher {
                         );
                     }
                 }
+                if let Some(contract_identifier) = event.contract_identifier() {
+                    if let Some(observer_indexes) =
+                        self.contracts_observers_lookup.get(contract_identifier)
+                    {
+                        for o_i in observer_indexes {
+                            dispatch_matrix[*o_i as usize].insert(i);
+                        }
+                    }
+                }
+                if let Some(observer_indexes) =
+                    self.event_types_observers_lookup.get(event.event_type())
+                {
+                    for o_i in observer_indexes {
+                        dispatch_matrix[*o_i as usize].insert(i);
+                    }
+                }
                 events.push((!receipt.post_condition_aborted, tx_hash, event));
                 for o_i in &self.any_event_observers_lookup {
                     dispatch_matrix[*o_i as usize].insert(i);
@@ -655,7 +1114,7 @@ impl EventDispatcher {
 
         let (dispatch_matrix, events) = self.create_dispatch_matrix_and_event_vector(&receipts);
 
-        if dispatch_matrix.len() > 0 {
+        if dispatch_matrix.len() > 0 || self.has_stream_clients() {
             let mature_rewards_vec = if let Some(rewards_info) = mature_rewards_info {
                 mature_rewards
                     .iter()
@@ -698,6 +1157,28 @@ impl EventDispatcher {
                     mblock_confirmed_consumed,
                 );
             }
+
+            self.broadcast_to_stream(PATH_BLOCK_PROCESSED, |subscription| {
+                let filtered_events = filter_events_for_subscription(subscription, &events);
+                if !subscription.wants_blocks() && filtered_events.is_empty() {
+                    return None;
+                }
+                Some(EventObserver::make_new_block_processed_payload(
+                    filtered_events,
+                    false,
+                    false,
+                    chain_tip,
+                    parent_index_hash,
+                    &boot_receipts,
+                    &winner_txid,
+                    &mature_rewards,
+                    parent_burn_block_hash,
+                    parent_burn_block_height,
+                    parent_burn_block_timestamp,
+                    anchored_consumed,
+                    mblock_confirmed_consumed,
+                ))
+            });
         }
     }
 
@@ -719,7 +1200,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_stream_clients() {
             return;
         }
         let flattened_receipts = processed_unconfirmed_state
@@ -738,7 +1219,7 @@ impl EventDispatcher {
             tx_index = 0;
             for receipt in receipts.iter() {
                 let payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
-                serialized_txs.push(payload);
+                serialized_txs.push((receipt, payload));
                 tx_index += 1;
             }
         }
@@ -759,6 +1240,23 @@ impl EventDispatcher {
                 processed_unconfirmed_state.burn_block_timestamp,
             );
         }
+
+        self.broadcast_to_stream(PATH_MICROBLOCK_SUBMIT, |subscription| {
+            if !subscription.wants_microblocks() {
+                return None;
+            }
+            Some(EventObserver::make_new_microblocks_payload(
+                parent_index_block_hash,
+                filter_events_for_subscription(subscription, &events),
+                &serialized_txs
+                    .iter()
+                    .map(|(_, payload)| payload.clone())
+                    .collect(),
+                processed_unconfirmed_state.burn_block_hash,
+                processed_unconfirmed_state.burn_block_height,
+                processed_unconfirmed_state.burn_block_timestamp,
+            ))
+        });
     }
 
     pub fn process_new_mempool_txs(&self, txs: Vec<StacksTransaction>) {
@@ -772,7 +1270,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_stream_clients() {
             return;
         }
 
@@ -781,6 +1279,14 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_new_mempool_txs(&payload);
         }
+
+        self.broadcast_to_stream(PATH_MEMPOOL_TX_SUBMIT, |subscription| {
+            if subscription.wants_mempool() {
+                Some(payload.clone())
+            } else {
+                None
+            }
+        });
     }
 
     pub fn process_mined_block_event(
@@ -797,7 +1303,7 @@ impl EventDispatcher {
             .enumerate()
             .filter(|(obs_id, _observer)| self.miner_observers_lookup.contains(&(*obs_id as u16)))
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_stream_clients() {
             return;
         }
 
@@ -814,6 +1320,14 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_mined_block(&payload);
         }
+
+        self.broadcast_to_stream(PATH_MINED_BLOCK, |subscription| {
+            if subscription.wants_mined_blocks() {
+                Some(payload.clone())
+            } else {
+                None
+            }
+        });
     }
 
     pub fn process_dropped_mempool_txs(&self, txs: Vec<Txid>, reason: MemPoolDropReason) {
@@ -827,7 +1341,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_stream_clients() {
             return;
         }
 
@@ -844,11 +1358,19 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_dropped_mempool_txs(&payload);
         }
+
+        self.broadcast_to_stream(PATH_MEMPOOL_TX_DROP, |subscription| {
+            if subscription.wants_mempool() {
+                Some(payload.clone())
+            } else {
+                None
+            }
+        });
     }
 
     pub fn process_new_attachments(&self, attachments: &Vec<(AttachmentInstance, Attachment)>) {
         let interested_observers: Vec<_> = self.registered_observers.iter().enumerate().collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_stream_clients() {
             return;
         }
 
@@ -861,6 +1383,14 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_new_attachments(&json!(serialized_attachments));
         }
+
+        self.broadcast_to_stream(PATH_ATTACHMENT_PROCESSED, |subscription| {
+            if subscription.wants_any_event() {
+                Some(json!(serialized_attachments))
+            } else {
+                None
+            }
+        });
     }
 
     pub fn process_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>) {
@@ -880,6 +1410,22 @@ impl EventDispatcher {
         }
     }
 
+    /// Start accepting event stream clients on `bind_address`.  If `auth_token` is set, clients
+    /// must present it.
+    pub fn register_event_stream(&mut self, bind_address: &str, auth_token: Option<String>) {
+        match EventStreamServer::spawn(bind_address, auth_token) {
+            Ok(server) => {
+                self.event_stream = Some(server);
+            }
+            Err(e) => {
+                error!(
+                    "Event dispatcher: failed to start event stream on {}: {:?}",
+                    bind_address, &e
+                );
+            }
+        }
+    }
+
     pub fn register_observer(
         &mut self,
         conf: &EventObserverConfig,
@@ -889,8 +1435,21 @@ impl EventDispatcher {
         let event_observer = EventObserver {
             endpoint: conf.endpoint.clone(),
             should_keep_running,
+            filter_transactions: conf.filter_transactions,
+            include_tx_details: conf.include_tx_details,
+            queue: self.event_queue.clone(),
+            delivery_lock: Arc::new(Mutex::new(())),
         };
 
+        if event_observer.queue.is_some() {
+            // send anything left over from before the node restarted
+            let observer = event_observer.clone();
+            thread::Builder::new()
+                .name(format!("event-resume-{}", &conf.endpoint))
+                .spawn(move || observer.deliver_pending())
+                .expect("FATAL: failed to start event observer delivery thread");
+        }
+
         let observer_index = self.registered_observers.len() as u16;
 
         for event_key_type in conf.events_keys.iter() {
@@ -934,6 +1493,18 @@ impl EventDispatcher {
                         }
                     };
                 }
+                EventKeyType::ContractEvents(contract_identifier) => {
+                    self.contracts_observers_lookup
+                        .entry(contract_identifier.clone())
+                        .or_insert_with(HashSet::new)
+                        .insert(observer_index);
+                }
+                EventKeyType::EventType(event_type) => {
+                    self.event_types_observers_lookup
+                        .entry(event_type.clone())
+                        .or_insert_with(HashSet::new)
+                        .insert(observer_index);
+                }
                 EventKeyType::AnyEvent => {
                     self.any_event_observers_lookup.insert(observer_index);
                 }
@@ -946,3 +1517,155 @@ impl EventDispatcher {
         self.registered_observers.push(event_observer);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{make_contract_call, make_contract_publish, make_stacks_transfer, to_addr};
+    use stacks::chainstate::stacks::StacksPrivateKey;
+    use stacks::vm::types::PrincipalData;
+
+    fn parse_tx(tx_bytes: &[u8]) -> StacksTransaction {
+        StacksTransaction::consensus_deserialize(&mut &tx_bytes[..]).unwrap()
+    }
+
+    fn tx_details(tx_bytes: &[u8]) -> serde_json::Value {
+        EventObserver::make_tx_details_payload(&parse_tx(tx_bytes).into())
+    }
+
+    #[test]
+    fn test_tx_details_payload() {
+        let sk = StacksPrivateKey::new();
+        let addr = to_addr(&sk);
+
+        let details = tx_details(&make_contract_call(
+            &sk,
+            2,
+            300,
+            &addr,
+            "counter",
+            "add",
+            &[Value::UInt(10), Value::some(Value::Int(-3)).unwrap()],
+        ));
+        assert_eq!(details["sender_address"], addr.to_string());
+        assert_eq!(details["sponsor_address"], serde_json::Value::Null);
+        assert_eq!(details["nonce"], 2);
+        assert_eq!(details["fee_rate"], 300);
+        let payload = &details["payload"];
+        assert_eq!(payload["type"], "contract_call");
+        assert_eq!(payload["contract_id"], format!("{}.counter", addr));
+        assert_eq!(payload["function_name"], "add");
+        let args = payload["function_args"].as_array().unwrap();
+        assert_eq!(args.len(), 2);
+        assert_eq!(args[0]["hex"], "0x010000000000000000000000000000000a");
+        assert_eq!(args[0]["repr"], "u10");
+        assert_eq!(args[0]["value"], json!({ "type": "uint", "value": "10" }));
+        assert_eq!(args[1]["repr"], "(some -3)");
+        assert_eq!(
+            args[1]["value"],
+            json!({ "type": "optional", "value": { "type": "int", "value": "-3" } })
+        );
+        // the encoding is the one the RPC API uses, so it decodes back to the argument
+        assert_eq!(
+            Value::from_json(&args[1]["value"]).unwrap(),
+            Value::some(Value::Int(-3)).unwrap()
+        );
+
+        let source = "(define-data-var count uint u0)";
+        let details = tx_details(&make_contract_publish(&sk, 0, 100, "counter", source));
+        let payload = &details["payload"];
+        assert_eq!(payload["type"], "smart_contract");
+        assert_eq!(payload["contract_id"], format!("{}.counter", addr));
+        assert_eq!(payload["source_code"], source);
+
+        let recipient = PrincipalData::from(to_addr(&StacksPrivateKey::new()));
+        let details = tx_details(&make_stacks_transfer(&sk, 1, 180, &recipient, 1000));
+        let payload = &details["payload"];
+        assert_eq!(payload["type"], "token_transfer");
+        assert_eq!(payload["recipient"], recipient.to_string());
+        assert_eq!(payload["amount"], "1000");
+        assert_eq!(payload["memo"], format!("0x{}", "00".repeat(34)));
+    }
+
+    #[test]
+    fn test_new_block_payload_tx_details_opt_in() {
+        let sk = StacksPrivateKey::new();
+        let recipient = PrincipalData::from(to_addr(&StacksPrivateKey::new()));
+        let tx = parse_tx(&make_stacks_transfer(&sk, 0, 180, &recipient, 1000));
+        let chain_tip = ChainTip {
+            metadata: StacksHeaderInfo::regtest_genesis(),
+            block: StacksBlock::genesis_block(),
+            receipts: vec![StacksTransactionReceipt::from_stx_transfer(
+                tx,
+                vec![],
+                Value::okay_true(),
+                ExecutionCost::zero(),
+            )],
+        };
+        let make_payload = |include_tx_details: bool| {
+            EventObserver::make_new_block_processed_payload(
+                vec![],
+                false,
+                include_tx_details,
+                &chain_tip,
+                &StacksBlockId([0; 32]),
+                &vec![],
+                &Txid([0; 32]),
+                &serde_json::Value::Null,
+                BurnchainHeaderHash([0; 32]),
+                0,
+                0,
+                &ExecutionCost::zero(),
+                &ExecutionCost::zero(),
+            )
+        };
+
+        // without the option, transactions carry no decoded form
+        let without_details = make_payload(false);
+        assert!(without_details["transactions"][0]
+            .get("tx_details")
+            .is_none());
+
+        // with it, the decoded form is the only difference
+        let mut with_details = make_payload(true);
+        let details = with_details["transactions"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("tx_details")
+            .unwrap();
+        assert_eq!(details["payload"]["type"], "token_transfer");
+        assert_eq!(with_details, without_details);
+    }
+
+    #[test]
+    fn test_stacks_reorg_payload() {
+        let block = |byte: u8, height: u64| (StacksBlockId([byte; 32]), height);
+        // a_1 -> a_2 -> a_3 is replaced by a_1 -> b_2 -> b_3 -> b_4
+        let fork_change = CanonicalForkChange {
+            old_tip: block(0xa3, 3),
+            new_tip: block(0xb4, 4),
+            fork_point: block(0xa1, 1),
+            orphaned: vec![block(0xa3, 3), block(0xa2, 2)],
+            joined: vec![block(0xb2, 2), block(0xb3, 3), block(0xb4, 4)],
+        };
+
+        let payload = EventObserver::make_stacks_reorg_payload(&fork_change);
+        let heights = |key: &str| -> Vec<u64> {
+            payload[key]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|block| block["block_height"].as_u64().unwrap())
+                .collect()
+        };
+        assert_eq!(payload["old_tip"]["block_height"], 3);
+        assert_eq!(
+            payload["old_tip"]["index_block_hash"],
+            format!("0x{}", StacksBlockId([0xa3; 32]))
+        );
+        assert_eq!(payload["new_tip"]["block_height"], 4);
+        assert_eq!(payload["fork_point"]["block_height"], 1);
+        assert_eq!(heights("orphaned_blocks"), vec![3, 2]);
+        assert_eq!(heights("new_canonical_blocks"), vec![2, 3, 4]);
+    }
+}
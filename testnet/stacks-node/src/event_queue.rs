@@ -0,0 +1,235 @@
+/// Durable outbound queue for event observer payloads.
+///
+/// Every payload is written to disk before it is POSTed, and is only marked delivered once the
+/// observer acknowledges it.  Payloads that were not delivered before the node stopped are sent
+/// again when it restarts.  Delivered payloads are kept for `replay_depth` Stacks blocks, so an
+/// observer that lost data can ask for everything since a given block height to be sent again.
+use std::fs;
+use std::path::Path;
+
+use rusqlite::types::ToSql;
+use rusqlite::{Connection, OpenFlags};
+
+use stacks::util::db::Error as db_error;
+use stacks::util::db::{query_row, query_rows, sqlite_open, tx_begin_immediate, u64_to_sql};
+use stacks::util::db::{FromColumn, FromRow};
+
+const EVENT_QUEUE_SCHEMA: &[&str] = &[
+    r#"
+    CREATE TABLE IF NOT EXISTS event_payloads(
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        endpoint TEXT NOT NULL,
+        path TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        -- Stacks block height, for new_block payloads
+        block_height INTEGER,
+        delivered INTEGER NOT NULL DEFAULT 0
+    );
+    "#,
+    "CREATE INDEX IF NOT EXISTS event_payloads_by_endpoint ON event_payloads(endpoint, delivered, id);",
+    "CREATE INDEX IF NOT EXISTS event_payloads_by_height ON event_payloads(endpoint, block_height);",
+];
+
+/// A payload waiting to be delivered to an observer
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedPayload {
+    pub id: u64,
+    pub path: String,
+    pub payload: String,
+}
+
+impl FromRow<QueuedPayload> for QueuedPayload {
+    fn from_row<'a>(row: &'a rusqlite::Row) -> Result<QueuedPayload, db_error> {
+        let id = u64::from_column(row, "id")?;
+        let path: String = row.get_unwrap("path");
+        let payload: String = row.get_unwrap("payload");
+        Ok(QueuedPayload { id, path, payload })
+    }
+}
+
+pub struct EventQueueDB {
+    conn: Connection,
+    replay_depth: u64,
+}
+
+impl EventQueueDB {
+    /// Open (or create) the queue at `path`
+    pub fn open<P: AsRef<Path>>(path: P, replay_depth: u64) -> Result<EventQueueDB, db_error> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent).map_err(db_error::IOError)?;
+        }
+        let conn = sqlite_open(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+            false,
+        )?;
+        for statement in EVENT_QUEUE_SCHEMA.iter() {
+            conn.execute_batch(statement)?;
+        }
+        Ok(EventQueueDB { conn, replay_depth })
+    }
+
+    #[cfg(test)]
+    pub fn open_memory(replay_depth: u64) -> Result<EventQueueDB, db_error> {
+        let conn = Connection::open_in_memory()?;
+        for statement in EVENT_QUEUE_SCHEMA.iter() {
+            conn.execute_batch(statement)?;
+        }
+        Ok(EventQueueDB { conn, replay_depth })
+    }
+
+    /// Queue a payload for delivery to `endpoint`.  If this is a new block, delivered payloads
+    /// older than the replay window are pruned.
+    pub fn enqueue(
+        &mut self,
+        endpoint: &str,
+        path: &str,
+        payload: &str,
+        block_height: Option<u64>,
+    ) -> Result<u64, db_error> {
+        let tx = tx_begin_immediate(&mut self.conn)?;
+        let height_arg = match block_height {
+            Some(height) => Some(u64_to_sql(height)?),
+            None => None,
+        };
+        let args: &[&dyn ToSql] = &[&endpoint, &path, &payload, &height_arg];
+        tx.execute(
+            "INSERT INTO event_payloads (endpoint, path, payload, block_height) VALUES (?1, ?2, ?3, ?4)",
+            args,
+        )?;
+        let id = tx.last_insert_rowid() as u64;
+
+        if let Some(height) = block_height {
+            let horizon = u64_to_sql(height.saturating_sub(self.replay_depth))?;
+            let args: &[&dyn ToSql] = &[&endpoint, &horizon];
+            tx.execute(
+                "DELETE FROM event_payloads WHERE endpoint = ?1 AND delivered = 1 AND id < \
+                 (SELECT IFNULL(MIN(id), 0) FROM event_payloads WHERE endpoint = ?1 AND block_height >= ?2)",
+                args,
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Get the oldest payload not yet delivered to `endpoint`
+    pub fn next_pending(&self, endpoint: &str) -> Result<Option<QueuedPayload>, db_error> {
+        query_row(
+            &self.conn,
+            "SELECT id, path, payload FROM event_payloads WHERE endpoint = ?1 AND delivered = 0 ORDER BY id ASC LIMIT 1",
+            &[&endpoint],
+        )
+    }
+
+    pub fn mark_delivered(&mut self, id: u64) -> Result<(), db_error> {
+        self.conn.execute(
+            "UPDATE event_payloads SET delivered = 1 WHERE id = ?1",
+            &[&u64_to_sql(id)?],
+        )?;
+        Ok(())
+    }
+
+    /// Mark every payload sent to `endpoint` since the block at `from_height` as undelivered, so
+    /// it will be sent again.  Returns the number of payloads requeued.
+    pub fn requeue_from_height(
+        &mut self,
+        endpoint: &str,
+        from_height: u64,
+    ) -> Result<u64, db_error> {
+        let args: &[&dyn ToSql] = &[&endpoint, &u64_to_sql(from_height)?];
+        let first_id: Option<i64> = self.conn.query_row(
+            "SELECT MIN(id) FROM event_payloads WHERE endpoint = ?1 AND block_height >= ?2",
+            args,
+            |row| row.get(0),
+        )?;
+        let first_id = match first_id {
+            Some(id) => id,
+            None => return Ok(0),
+        };
+        let args: &[&dyn ToSql] = &[&endpoint, &first_id];
+        let count = self.conn.execute(
+            "UPDATE event_payloads SET delivered = 0 WHERE endpoint = ?1 AND id >= ?2 AND delivered = 1",
+            args,
+        )?;
+        Ok(count as u64)
+    }
+
+    /// Lowest Stacks block height that can still be replayed to `endpoint`
+    pub fn get_replay_horizon(&self, endpoint: &str) -> Result<Option<u64>, db_error> {
+        let heights: Vec<u64> = query_rows(
+            &self.conn,
+            "SELECT block_height FROM event_payloads WHERE endpoint = ?1 AND block_height IS NOT NULL ORDER BY id ASC LIMIT 1",
+            &[&endpoint],
+        )?;
+        Ok(heights.into_iter().next())
+    }
+
+    #[cfg(test)]
+    fn count_payloads(&self, endpoint: &str) -> u64 {
+        let count: i64 = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM event_payloads WHERE endpoint = ?1",
+                &[&endpoint],
+                |row| row.get(0),
+            )
+            .unwrap();
+        count as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_queue_delivery_order() {
+        let mut db = EventQueueDB::open_memory(10).unwrap();
+        let first = db.enqueue("a:1", "new_block", "{}", Some(1)).unwrap();
+        let second = db.enqueue("a:1", "new_mempool_tx", "[]", None).unwrap();
+        db.enqueue("b:1", "new_block", "{}", Some(1)).unwrap();
+
+        let next = db.next_pending("a:1").unwrap().unwrap();
+        assert_eq!(next.id, first);
+        assert_eq!(next.path, "new_block");
+        db.mark_delivered(first).unwrap();
+
+        let next = db.next_pending("a:1").unwrap().unwrap();
+        assert_eq!(next.id, second);
+        db.mark_delivered(second).unwrap();
+        assert!(db.next_pending("a:1").unwrap().is_none());
+
+        // other observers are unaffected
+        assert!(db.next_pending("b:1").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_event_queue_replay_and_prune() {
+        let mut db = EventQueueDB::open_memory(2).unwrap();
+        for height in 1..=3 {
+            let id = db.enqueue("a:1", "new_block", "{}", Some(height)).unwrap();
+            db.mark_delivered(id).unwrap();
+            let id = db.enqueue("a:1", "new_mempool_tx", "[]", None).unwrap();
+            db.mark_delivered(id).unwrap();
+        }
+        assert_eq!(db.count_payloads("a:1"), 6);
+        assert_eq!(db.get_replay_horizon("a:1").unwrap(), Some(1));
+
+        // requeue everything from height 2 onwards
+        assert_eq!(db.requeue_from_height("a:1", 2).unwrap(), 4);
+        let next = db.next_pending("a:1").unwrap().unwrap();
+        assert_eq!(next.path, "new_block");
+        assert_eq!(db.requeue_from_height("a:1", 100).unwrap(), 0);
+
+        // delivered payloads from before height 2 get pruned at height 4
+        let mut next = db.next_pending("a:1").unwrap();
+        while let Some(payload) = next {
+            db.mark_delivered(payload.id).unwrap();
+            next = db.next_pending("a:1").unwrap();
+        }
+        db.enqueue("a:1", "new_block", "{}", Some(4)).unwrap();
+        assert_eq!(db.get_replay_horizon("a:1").unwrap(), Some(2));
+        assert_eq!(db.count_payloads("a:1"), 5);
+    }
+}
@@ -0,0 +1,723 @@
+/// Streaming interface to the event dispatcher.
+///
+/// Clients connect to the node's `event_stream_bind` address and receive the same payloads that
+/// are POSTed to HTTP event observers, either as server-sent events or as WebSocket text frames.
+/// Each client picks the event classes it wants with an `?events=` querystring, using the same
+/// keys as the `events_keys` of an `[[events_observer]]` (plus `blocks`), e.g.
+/// `GET /v1/events?events=blocks,memtx,ST000000000000000000002AMW42H.bns::print`.
+/// If the node has an `event_stream_auth_token`, clients must present it, either as a `token`
+/// query parameter or as an `Authorization: Bearer` header.
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use http_types::Url;
+use serde_json::json;
+use sha1::{Digest, Sha1};
+
+use stacks::chainstate::stacks::events::StacksTransactionEvent;
+
+use super::config::EventKeyType;
+use super::event_dispatcher::event_key_matches;
+
+/// Path on which clients connect to the event stream
+pub const PATH_EVENT_STREAM: &str = "/v1/events";
+
+/// Number of messages that may be queued for a client before it is considered too slow to keep
+/// up and is disconnected.
+const CLIENT_QUEUE_LEN: usize = 1024;
+
+/// How often an idle connection gets a keep-alive message
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Maximum size of a client's request headers
+const MAX_REQUEST_HEADER_LEN: usize = 8192;
+
+/// Maximum number of open client connections, including those still handshaking
+const MAX_CLIENTS: usize = 64;
+
+/// How long a write to a client may block before the client is disconnected
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest WebSocket frame a client may send us.  Clients only need to send control frames,
+/// whose payloads are at most 125 bytes.
+const MAX_CLIENT_FRAME_LEN: u64 = 4096;
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The event classes a stream client has subscribed to
+#[derive(Clone)]
+pub struct EventStreamSubscription {
+    /// receive every `new_block` payload, even if none of its events match `keys`
+    pub blocks: bool,
+    pub keys: Vec<EventKeyType>,
+}
+
+impl EventStreamSubscription {
+    /// Parse a comma-separated list of event keys.  Returns None if any key is malformed.
+    pub fn from_keys(raw_keys: &str) -> Option<EventStreamSubscription> {
+        let mut subscription = EventStreamSubscription {
+            blocks: false,
+            keys: vec![],
+        };
+        for raw_key in raw_keys
+            .split(',')
+            .map(|k| k.trim())
+            .filter(|k| k.len() > 0)
+        {
+            if raw_key == "blocks" {
+                subscription.blocks = true;
+            } else {
+                subscription.keys.push(EventKeyType::from_string(raw_key)?);
+            }
+        }
+        Some(subscription)
+    }
+
+    fn has_key<F: Fn(&EventKeyType) -> bool>(&self, f: F) -> bool {
+        self.keys.iter().any(|key| f(key))
+    }
+
+    pub fn wants_any_event(&self) -> bool {
+        self.has_key(|key| matches!(key, EventKeyType::AnyEvent))
+    }
+
+    pub fn wants_blocks(&self) -> bool {
+        self.blocks || self.wants_any_event()
+    }
+
+    pub fn wants_burn_blocks(&self) -> bool {
+        self.wants_any_event() || self.has_key(|key| matches!(key, EventKeyType::BurnchainBlocks))
+    }
+
+    pub fn wants_microblocks(&self) -> bool {
+        self.wants_any_event() || self.has_key(|key| matches!(key, EventKeyType::Microblocks))
+    }
+
+    pub fn wants_mempool(&self) -> bool {
+        self.wants_any_event()
+            || self.has_key(|key| matches!(key, EventKeyType::MemPoolTransactions))
+    }
+
+    /// Mined block events must be asked for explicitly, as with HTTP observers
+    pub fn wants_mined_blocks(&self) -> bool {
+        self.has_key(|key| matches!(key, EventKeyType::MinedBlocks))
+    }
+
+    /// Does this client want to see this transaction event?
+    pub fn wants_event(&self, event: &StacksTransactionEvent) -> bool {
+        self.keys.iter().any(|key| event_key_matches(key, event))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StreamProtocol {
+    ServerSentEvents,
+    WebSocket,
+}
+
+/// A message queued for delivery to a stream client: the observer path it corresponds to
+/// (e.g. `new_block`), and its JSON payload.
+struct StreamMessage {
+    path: &'static str,
+    payload: serde_json::Value,
+}
+
+/// What a client's connection thread writes out next
+enum StreamItem {
+    Message(StreamMessage),
+    /// answer a WebSocket ping with this payload
+    Pong(Vec<u8>),
+    /// the client went away, or asked to close the connection
+    Close,
+}
+
+struct EventStreamClient {
+    id: usize,
+    peer_addr: SocketAddr,
+    subscription: EventStreamSubscription,
+    sender: SyncSender<StreamItem>,
+    /// the client's connection, so it can be closed if the client falls behind
+    stream: TcpStream,
+}
+
+/// Counts an open connection for as long as it lives
+struct ConnectionSlot {
+    num_connections: Arc<AtomicUsize>,
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.num_connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Accepts stream clients and fans out event payloads to them.  Cloning the server yields a
+/// handle to the same set of clients.
+#[derive(Clone)]
+pub struct EventStreamServer {
+    clients: Arc<Mutex<Vec<EventStreamClient>>>,
+    num_connections: Arc<AtomicUsize>,
+    next_client_id: Arc<AtomicUsize>,
+    auth_token: Option<String>,
+}
+
+impl EventStreamServer {
+    /// Bind to `bind_address` and start accepting clients on a background thread.  If
+    /// `auth_token` is set, clients must present it.
+    pub fn spawn(
+        bind_address: &str,
+        auth_token: Option<String>,
+    ) -> Result<EventStreamServer, std::io::Error> {
+        let listener = TcpListener::bind(bind_address)?;
+        info!("Event stream: listening on {}", listener.local_addr()?);
+
+        let server = EventStreamServer {
+            clients: Arc::new(Mutex::new(vec![])),
+            num_connections: Arc::new(AtomicUsize::new(0)),
+            next_client_id: Arc::new(AtomicUsize::new(0)),
+            auth_token,
+        };
+        let accept_server = server.clone();
+        thread::Builder::new()
+            .name("event-stream".to_string())
+            .spawn(move || accept_server.accept_loop(listener))?;
+        Ok(server)
+    }
+
+    /// Reserve a connection slot, unless all of them are taken
+    fn take_connection_slot(&self) -> Option<ConnectionSlot> {
+        if self.num_connections.fetch_add(1, Ordering::SeqCst) >= MAX_CLIENTS {
+            self.num_connections.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(ConnectionSlot {
+            num_connections: self.num_connections.clone(),
+        })
+    }
+
+    fn accept_loop(&self, listener: TcpListener) {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("Event stream: failed to accept connection: {:?}", &e);
+                    continue;
+                }
+            };
+            let slot = match self.take_connection_slot() {
+                Some(slot) => slot,
+                None => {
+                    debug!("Event stream: too many clients; refusing connection");
+                    let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                    let _ = write_error_response(
+                        &mut stream,
+                        "503 Service Unavailable",
+                        "Too many event stream clients",
+                    );
+                    continue;
+                }
+            };
+            let server = self.clone();
+            let spawned = thread::Builder::new()
+                .name("event-stream-client".to_string())
+                .spawn(move || {
+                    let _slot = slot;
+                    if let Err(e) = server.handle_client(stream) {
+                        debug!("Event stream: client connection closed: {:?}", &e);
+                    }
+                });
+            if let Err(e) = spawned {
+                warn!("Event stream: failed to spawn client thread: {:?}", &e);
+            }
+        }
+    }
+
+    /// Read the client's request, complete the handshake, and register it.  The calling thread
+    /// then writes out the client's messages until it disconnects.
+    fn handle_client(&self, mut stream: TcpStream) -> Result<(), std::io::Error> {
+        let peer_addr = stream.peer_addr()?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let request = read_request_head(&mut stream)?;
+
+        let (protocol, subscription) = match parse_request(&request, self.auth_token.as_deref()) {
+            Ok(parsed) => parsed,
+            Err((status, reason)) => {
+                return write_error_response(&mut stream, status, &reason);
+            }
+        };
+
+        match protocol {
+            StreamProtocol::ServerSentEvents => {
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n"
+                )?;
+            }
+            StreamProtocol::WebSocket => {
+                let key = header_value(&request, "sec-websocket-key").unwrap_or("");
+                write!(
+                    stream,
+                    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                    websocket_accept_key(key)
+                )?;
+            }
+        }
+        stream.flush()?;
+
+        let (sender, receiver) = sync_channel(CLIENT_QUEUE_LEN);
+        let id = self.next_client_id.fetch_add(1, Ordering::SeqCst);
+        info!(
+            "Event stream: client {} connected ({:?})",
+            &peer_addr, protocol
+        );
+        self.clients
+            .lock()
+            .expect("FATAL: event stream client list lock poisoned")
+            .push(EventStreamClient {
+                id,
+                peer_addr,
+                subscription,
+                sender: sender.clone(),
+                stream: stream.try_clone()?,
+            });
+
+        // watch for the client hanging up (or, over WebSocket, sending control frames) while
+        // this thread writes out its messages
+        stream.set_read_timeout(None)?;
+        let mut read_stream = stream.try_clone()?;
+        let reader = thread::Builder::new()
+            .name("event-stream-reader".to_string())
+            .spawn(move || read_client(&mut read_stream, protocol, sender));
+
+        let result = write_messages(&mut stream, protocol, receiver);
+
+        self.clients
+            .lock()
+            .expect("FATAL: event stream client list lock poisoned")
+            .retain(|client| client.id != id);
+        let _ = stream.shutdown(Shutdown::Both);
+        if let Ok(reader) = reader {
+            let _ = reader.join();
+        }
+        info!("Event stream: client {} disconnected", &peer_addr);
+        result
+    }
+
+    pub fn has_clients(&self) -> bool {
+        !self
+            .clients
+            .lock()
+            .expect("FATAL: event stream client list lock poisoned")
+            .is_empty()
+    }
+
+    /// Queue a payload for each client.  `make_payload` decides, from the client's subscription,
+    /// what (if anything) that client receives.  Clients whose queue is full are disconnected.
+    pub fn broadcast<F>(&self, path: &'static str, mut make_payload: F)
+    where
+        F: FnMut(&EventStreamSubscription) -> Option<serde_json::Value>,
+    {
+        let mut clients = self
+            .clients
+            .lock()
+            .expect("FATAL: event stream client list lock poisoned");
+        clients.retain(|client| {
+            let payload = match make_payload(&client.subscription) {
+                Some(payload) => payload,
+                None => return true,
+            };
+            match client
+                .sender
+                .try_send(StreamItem::Message(StreamMessage { path, payload }))
+            {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!(
+                        "Event stream: client {} is not keeping up; disconnecting",
+                        &client.peer_addr
+                    );
+                    let _ = client.stream.shutdown(Shutdown::Both);
+                    false
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+}
+
+fn write_error_response(
+    stream: &mut TcpStream,
+    status: &str,
+    reason: &str,
+) -> Result<(), std::io::Error> {
+    let body = format!("{}\n", reason);
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Read bytes until the end of the HTTP request headers
+fn read_request_head(stream: &mut TcpStream) -> Result<String, std::io::Error> {
+    let mut buf = vec![];
+    let mut byte = [0u8; 1];
+    while !buf.ends_with(b"\r\n\r\n") {
+        if buf.len() >= MAX_REQUEST_HEADER_LEN {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "request headers too long",
+            ));
+        }
+        if stream.read(&mut byte)? == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.push(byte[0]);
+    }
+    String::from_utf8(buf)
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "non-UTF-8 request"))
+}
+
+fn header_value<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request.lines().skip(1).find_map(|line| {
+        let mut parts = line.splitn(2, ':');
+        let header = parts.next()?.trim();
+        if header.eq_ignore_ascii_case(name) {
+            parts.next().map(|value| value.trim())
+        } else {
+            None
+        }
+    })
+}
+
+/// Parse the client's request line and headers into the protocol to speak and the events to
+/// send.  If `auth_token` is set, the request must carry it.  On failure, returns the HTTP status
+/// line and reason to reply with.
+fn parse_request(
+    request: &str,
+    auth_token: Option<&str>,
+) -> Result<(StreamProtocol, EventStreamSubscription), (&'static str, String)> {
+    let request_line = request.lines().next().unwrap_or("");
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => return Err(("400 Bad Request", "Malformed request".into())),
+    };
+    if method != "GET" {
+        return Err(("405 Method Not Allowed", "Only GET is supported".into()));
+    }
+
+    let url = Url::parse(&format!("http://localhost{}", target))
+        .map_err(|_| ("400 Bad Request", "Malformed request path".to_string()))?;
+    if url.path() != PATH_EVENT_STREAM {
+        return Err(("404 Not Found", format!("No such stream {}", url.path())));
+    }
+
+    if let Some(auth_token) = auth_token {
+        let query_token = url
+            .query_pairs()
+            .find(|(name, _)| name == "token")
+            .map(|(_, value)| value.into_owned());
+        let header_token = header_value(request, "authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|value| value.trim().to_string());
+        let authorized = query_token.as_deref() == Some(auth_token)
+            || header_token.as_deref() == Some(auth_token);
+        if !authorized {
+            return Err(("401 Unauthorized", "Missing or invalid token".into()));
+        }
+    }
+
+    let raw_keys = url
+        .query_pairs()
+        .find(|(name, _)| name == "events")
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or("*".to_string());
+    let subscription = EventStreamSubscription::from_keys(&raw_keys).ok_or_else(|| {
+        (
+            "400 Bad Request",
+            format!("Invalid event keys '{}'", raw_keys),
+        )
+    })?;
+
+    let is_websocket = header_value(request, "upgrade")
+        .map(|value| value.eq_ignore_ascii_case("websocket"))
+        .unwrap_or(false);
+    if is_websocket {
+        if header_value(request, "sec-websocket-key").is_none() {
+            return Err(("400 Bad Request", "Missing Sec-WebSocket-Key".into()));
+        }
+        Ok((StreamProtocol::WebSocket, subscription))
+    } else {
+        Ok((StreamProtocol::ServerSentEvents, subscription))
+    }
+}
+
+/// Compute the Sec-WebSocket-Accept header value for a client's Sec-WebSocket-Key
+fn websocket_accept_key(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.input(key.as_bytes());
+    hasher.input(WEBSOCKET_GUID.as_bytes());
+    base64::encode(hasher.result())
+}
+
+/// Frame a WebSocket message from the server.  Server frames are never masked.
+fn websocket_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    if payload.len() < 126 {
+        frame.push(payload.len() as u8);
+    } else if payload.len() <= u16::MAX as usize {
+        frame.push(126);
+        frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    } else {
+        frame.push(127);
+        frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+fn encode_message(protocol: StreamProtocol, message: &StreamMessage) -> Vec<u8> {
+    match protocol {
+        StreamProtocol::ServerSentEvents => format!(
+            "event: {}\ndata: {}\n\n",
+            message.path,
+            serde_json::to_string(&message.payload).unwrap_or_default()
+        )
+        .into_bytes(),
+        StreamProtocol::WebSocket => {
+            let text = json!({
+                "event": message.path,
+                "payload": message.payload,
+            })
+            .to_string();
+            websocket_frame(0x1, text.as_bytes())
+        }
+    }
+}
+
+fn encode_keep_alive(protocol: StreamProtocol) -> Vec<u8> {
+    match protocol {
+        StreamProtocol::ServerSentEvents => b": keep-alive\n\n".to_vec(),
+        StreamProtocol::WebSocket => websocket_frame(0x9, &[]),
+    }
+}
+
+/// Read one WebSocket frame from a client.  Returns its opcode and unmasked payload.  Client
+/// frames must be masked, and may be no larger than MAX_CLIENT_FRAME_LEN.
+fn read_websocket_frame<R: Read>(stream: &mut R) -> Result<(u8, Vec<u8>), std::io::Error> {
+    let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+
+    let mut head = [0u8; 2];
+    stream.read_exact(&mut head)?;
+    let opcode = head[0] & 0x0f;
+    if head[1] & 0x80 == 0 {
+        return Err(invalid("unmasked client frame"));
+    }
+    let len = match head[1] & 0x7f {
+        126 => {
+            let mut len = [0u8; 2];
+            stream.read_exact(&mut len)?;
+            u16::from_be_bytes(len) as u64
+        }
+        127 => {
+            let mut len = [0u8; 8];
+            stream.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+        len => len as u64,
+    };
+    if len > MAX_CLIENT_FRAME_LEN {
+        return Err(invalid("client frame too large"));
+    }
+
+    let mut mask = [0u8; 4];
+    stream.read_exact(&mut mask)?;
+    let mut payload = vec![0u8; len as usize];
+    stream.read_exact(&mut payload)?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok((opcode, payload))
+}
+
+/// Read from the client until it hangs up.  WebSocket pings are answered, and anything else the
+/// client sends is ignored.  Once the client is gone (or asks to close), its connection thread is
+/// told to stop.
+fn read_client(stream: &mut TcpStream, protocol: StreamProtocol, sender: SyncSender<StreamItem>) {
+    match protocol {
+        StreamProtocol::ServerSentEvents => {
+            let mut buf = [0u8; 1024];
+            loop {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+            }
+        }
+        StreamProtocol::WebSocket => loop {
+            match read_websocket_frame(stream) {
+                Ok((0x8, _)) | Err(_) => break,
+                Ok((0x9, payload)) => {
+                    // a client that can't keep up gets disconnected by the next broadcast
+                    let _ = sender.try_send(StreamItem::Pong(payload));
+                }
+                Ok(_) => {}
+            }
+        },
+    }
+    if sender.try_send(StreamItem::Close).is_err() {
+        // the queue is full, so unblock the writer by closing the connection under it
+        let _ = stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// Write out queued messages until the client goes away or is dropped by the server
+fn write_messages(
+    stream: &mut TcpStream,
+    protocol: StreamProtocol,
+    receiver: Receiver<StreamItem>,
+) -> Result<(), std::io::Error> {
+    loop {
+        let bytes = match receiver.recv_timeout(KEEP_ALIVE_INTERVAL) {
+            Ok(StreamItem::Message(message)) => encode_message(protocol, &message),
+            Ok(StreamItem::Pong(payload)) => websocket_frame(0xa, &payload),
+            Err(RecvTimeoutError::Timeout) => encode_keep_alive(protocol),
+            Ok(StreamItem::Close) | Err(RecvTimeoutError::Disconnected) => {
+                if protocol == StreamProtocol::WebSocket {
+                    let _ = stream.write_all(&websocket_frame(0x8, &[]));
+                }
+                return Ok(());
+            }
+        };
+        stream.write_all(&bytes)?;
+        stream.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_accept_key() {
+        // example from RFC 6455, section 1.3
+        assert_eq!(
+            websocket_accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_websocket_frame_lengths() {
+        assert_eq!(websocket_frame(0x1, b"hi"), vec![0x81, 2, b'h', b'i']);
+        let frame = websocket_frame(0x1, &[0u8; 300]);
+        assert_eq!(&frame[0..4], &[0x81, 126, 0x01, 0x2c]);
+        assert_eq!(frame.len(), 304);
+        let frame = websocket_frame(0x1, &[0u8; 70000]);
+        assert_eq!(&frame[0..2], &[0x81, 127]);
+        assert_eq!(&frame[2..10], &70000u64.to_be_bytes());
+    }
+
+    #[test]
+    fn test_parse_stream_request() {
+        let (protocol, subscription) = parse_request(
+            "GET /v1/events?events=blocks,memtx,ST000000000000000000002AMW42H.bns::print HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            None,
+        )
+        .unwrap();
+        assert_eq!(protocol, StreamProtocol::ServerSentEvents);
+        assert!(subscription.wants_blocks());
+        assert!(subscription.wants_mempool());
+        assert!(!subscription.wants_microblocks());
+        assert!(!subscription.wants_any_event());
+        assert_eq!(subscription.keys.len(), 2);
+
+        let (protocol, subscription) = parse_request(
+            "GET /v1/events HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+            None,
+        )
+        .unwrap();
+        assert_eq!(protocol, StreamProtocol::WebSocket);
+        assert!(subscription.wants_any_event());
+        assert!(subscription.wants_microblocks());
+        assert!(!subscription.wants_mined_blocks());
+
+        assert!(parse_request("GET /v1/other HTTP/1.1\r\n\r\n", None).is_err());
+        assert!(parse_request("POST /v1/events HTTP/1.1\r\n\r\n", None).is_err());
+        assert!(parse_request("GET /v1/events?events=not-a-key HTTP/1.1\r\n\r\n", None).is_err());
+        assert!(parse_request(
+            "GET /v1/events HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\r\n",
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_stream_request_token() {
+        let token = Some("secret");
+        assert!(parse_request("GET /v1/events?token=secret HTTP/1.1\r\n\r\n", token).is_ok());
+        assert!(parse_request(
+            "GET /v1/events HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n",
+            token
+        )
+        .is_ok());
+
+        match parse_request("GET /v1/events HTTP/1.1\r\n\r\n", token) {
+            Err((status, _)) => assert_eq!(status, "401 Unauthorized"),
+            Ok(_) => panic!("Expected a request without a token to be refused"),
+        }
+        assert!(parse_request("GET /v1/events?token=wrong HTTP/1.1\r\n\r\n", token).is_err());
+        assert!(parse_request(
+            "GET /v1/events HTTP/1.1\r\nAuthorization: Bearer wrong\r\n\r\n",
+            token
+        )
+        .is_err());
+    }
+
+    fn masked_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x37, 0xfa, 0x21, 0x3d];
+        let mut frame = websocket_frame(opcode, payload);
+        let header_len = frame.len() - payload.len();
+        frame[1] |= 0x80;
+        let mut masked = frame[0..header_len].to_vec();
+        masked.extend_from_slice(&mask);
+        masked.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        masked
+    }
+
+    #[test]
+    fn test_read_websocket_frame() {
+        let mut frames = masked_frame(0x9, b"ping");
+        frames.extend(masked_frame(0x1, &[0x55; 300]));
+        frames.extend(masked_frame(0x8, &[]));
+        let mut reader = std::io::Cursor::new(frames);
+        assert_eq!(
+            read_websocket_frame(&mut reader).unwrap(),
+            (0x9, b"ping".to_vec())
+        );
+        assert_eq!(
+            read_websocket_frame(&mut reader).unwrap(),
+            (0x1, vec![0x55; 300])
+        );
+        assert_eq!(read_websocket_frame(&mut reader).unwrap(), (0x8, vec![]));
+        assert!(read_websocket_frame(&mut reader).is_err());
+
+        // clients must mask their frames
+        let mut reader = std::io::Cursor::new(websocket_frame(0x9, b"ping"));
+        assert!(read_websocket_frame(&mut reader).is_err());
+
+        // and may not send us anything large
+        let mut reader = std::io::Cursor::new(masked_frame(0x2, &[0u8; 5000]));
+        assert!(read_websocket_frame(&mut reader).is_err());
+    }
+}
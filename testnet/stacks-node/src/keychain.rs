@@ -1,5 +1,3 @@
-use std::collections::HashMap;
-
 use stacks::address::AddressHashMode;
 use stacks::burnchains::{BurnchainSigner, PrivateKey};
 use stacks::chainstate::stacks::{
@@ -7,9 +5,11 @@ use stacks::chainstate::stacks::{
 };
 use stacks::types::chainstate::StacksAddress;
 use stacks::util::hash::{Hash160, Sha256Sum};
-use stacks::util::vrf::{VRFPrivateKey, VRFProof, VRFPublicKey, VRF};
+use stacks::util::vrf::{VRFProof, VRFPublicKey, VRF};
 
 use super::operations::BurnchainOpSigner;
+use super::signer::{LocalSigner, MinerSigner, RemoteSigner};
+use super::Config;
 
 #[derive(Clone)]
 pub struct Keychain {
@@ -18,8 +18,10 @@ pub struct Keychain {
     hash_mode: AddressHashMode,
     pub hashed_secret_state: Sha256Sum,
     microblocks_secret_keys: Vec<StacksPrivateKey>,
-    vrf_secret_keys: Vec<VRFPrivateKey>,
-    vrf_map: HashMap<VRFPublicKey, VRFPrivateKey>,
+    /// signs with the keys derived from our secret keys
+    local_signer: LocalSigner,
+    /// if set, signs burnchain operations and VRF proofs instead of `local_signer`
+    remote_signer: Option<Box<dyn MinerSigner>>,
     rotations: u64,
 }
 
@@ -44,11 +46,11 @@ impl Keychain {
             hash_mode,
             hashed_secret_state,
             microblocks_secret_keys: vec![],
+            local_signer: LocalSigner::new(secret_keys[0], hashed_secret_state.clone()),
+            remote_signer: None,
             secret_keys,
             threshold,
             rotations: 0,
-            vrf_secret_keys: vec![],
-            vrf_map: HashMap::new(),
         }
     }
 
@@ -71,26 +73,46 @@ impl Keychain {
         Keychain::new(vec![secret_key], threshold, hash_mode)
     }
 
-    pub fn rotate_vrf_keypair(&mut self, block_height: u64) -> VRFPublicKey {
-        let mut seed = {
-            let mut secret_state = self.hashed_secret_state.to_bytes().to_vec();
-            secret_state.extend_from_slice(&block_height.to_be_bytes());
-            Sha256Sum::from_data(&secret_state)
-        };
+    /// The miner's keychain: derived from the node's seed, with its burnchain and VRF keys held
+    /// by the configured remote signer, if there is one.  Panics if the remote signer cannot be
+    /// reached.
+    pub fn for_miner(config: &Config) -> Keychain {
+        let mut keychain = Keychain::default(config.node.seed.clone());
+        if let Some(ref url) = config.miner.remote_signer_url {
+            let signer = RemoteSigner::connect(url, config.miner.remote_signer_auth_token.clone())
+                .unwrap_or_else(|e| panic!("Failed to connect to remote signer {}: {}", url, &e));
+            keychain.set_remote_signer(Box::new(signer));
+        }
+        keychain
+    }
 
-        // Not every 256-bit number is a valid Ed25519 secret key.
-        // As such, we continuously generate seeds through re-hashing until one works.
-        let sk = loop {
-            match VRFPrivateKey::from_bytes(seed.as_bytes()) {
-                Some(sk) => break sk,
-                None => seed = Sha256Sum::from_data(seed.as_bytes()),
-            }
-        };
-        let pk = VRFPublicKey::from_private(&sk);
+    /// Sign burnchain operations and VRF proofs with `signer` instead of our own keys
+    pub fn set_remote_signer(&mut self, signer: Box<dyn MinerSigner>) {
+        self.remote_signer = Some(signer);
+    }
 
-        self.vrf_secret_keys.push(sk.clone());
-        self.vrf_map.insert(pk.clone(), sk);
-        pk
+    fn miner_signer(&mut self) -> &mut dyn MinerSigner {
+        match self.remote_signer {
+            Some(ref mut signer) => signer.as_mut(),
+            None => &mut self.local_signer,
+        }
+    }
+
+    /// Get the VRF public key to register at a given block height.  Returns None if the signer
+    /// cannot provide one.
+    pub fn rotate_vrf_keypair(&mut self, block_height: u64) -> Option<VRFPublicKey> {
+        let signer = self.miner_signer();
+        match signer.get_vrf_public_key(block_height) {
+            Ok(pk) => Some(pk),
+            Err(e) => {
+                error!(
+                    "Failed to get VRF public key from {} signer: {}",
+                    signer.name(),
+                    &e
+                );
+                None
+            }
+        }
     }
 
     pub fn rotate_microblock_keypair(&mut self, burn_block_height: u64) -> StacksPrivateKey {
@@ -140,18 +162,21 @@ impl Keychain {
     }
 
     /// Given a VRF public key, generates a VRF Proof
-    pub fn generate_proof(&self, vrf_pk: &VRFPublicKey, bytes: &[u8; 32]) -> Option<VRFProof> {
-        // Retrieve the corresponding VRF secret key
-        let vrf_sk = match self.vrf_map.get(vrf_pk) {
-            Some(vrf_pk) => vrf_pk,
-            None => {
-                warn!("No VRF secret key on file for {:?}", vrf_pk);
+    pub fn generate_proof(&mut self, vrf_pk: &VRFPublicKey, bytes: &[u8; 32]) -> Option<VRFProof> {
+        let signer = self.miner_signer();
+        let proof = match signer.prove_vrf(vrf_pk, bytes) {
+            Ok(proof) => proof,
+            Err(e) => {
+                warn!(
+                    "Failed to prove with VRF key {:?} from {} signer: {}",
+                    vrf_pk,
+                    signer.name(),
+                    &e
+                );
                 return None;
             }
         };
 
-        // Generate the proof
-        let proof = VRF::prove(&vrf_sk, &bytes.to_vec());
         // Ensure that the proof is valid by verifying
         let is_valid = match VRF::verify(vrf_pk, &proof, &bytes.to_vec()) {
             Ok(v) => v,
@@ -201,6 +226,13 @@ impl Keychain {
     }
 
     pub fn get_burnchain_signer(&self) -> BurnchainSigner {
+        if let Some(ref signer) = self.remote_signer {
+            return BurnchainSigner {
+                hash_mode: AddressHashMode::SerializeP2PKH,
+                num_sigs: 1,
+                public_keys: vec![signer.get_public_key()],
+            };
+        }
         let public_keys = self
             .secret_keys
             .iter()
@@ -241,6 +273,9 @@ impl Keychain {
     }
 
     pub fn generate_op_signer(&self) -> BurnchainOpSigner {
-        BurnchainOpSigner::new(self.secret_keys[0], false)
+        match self.remote_signer {
+            Some(ref signer) => BurnchainOpSigner::from_signer(signer.clone(), false),
+            None => BurnchainOpSigner::new(self.secret_keys[0], false),
+        }
     }
 }
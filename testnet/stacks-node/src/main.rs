@@ -15,20 +15,26 @@ extern crate stacks;
 #[macro_use(o, slog_log, slog_trace, slog_debug, slog_info, slog_warn, slog_error)]
 extern crate slog;
 
+use stacks::chainstate::snapshot;
 pub use stacks::util;
-use stacks::util::hash::hex_bytes;
+use stacks::util::hash::{hex_bytes, Sha256Sum};
 
 pub mod monitoring;
 
 pub mod burnchains;
 pub mod config;
 pub mod event_dispatcher;
+pub mod event_queue;
+pub mod event_stream;
 pub mod genesis_data;
 pub mod keychain;
 pub mod neon_node;
 pub mod node;
 pub mod operations;
+pub mod reindex;
 pub mod run_loop;
+pub mod signer;
+pub mod snapshot_bootstrap;
 pub mod syncctl;
 pub mod tenure;
 
@@ -136,11 +142,87 @@ fn main() {
             let keychain = Keychain::default(seed);
             println!(
                 "Hex formatted secret key: {}",
-                keychain.generate_op_signer().get_sk_as_hex()
+                keychain
+                    .generate_op_signer()
+                    .get_sk_as_hex()
+                    .expect("FATAL: no local secret key")
             );
             println!(
                 "WIF formatted secret key: {}",
-                keychain.generate_op_signer().get_sk_as_wif()
+                keychain
+                    .generate_op_signer()
+                    .get_sk_as_wif()
+                    .expect("FATAL: no local secret key")
+            );
+            return;
+        }
+        "export-snapshot" => {
+            let config_path: String = args.value_from_str("--config").unwrap();
+            let output_path: String = args.value_from_str("--output").unwrap();
+            args.finish().unwrap();
+            let conf = Config::from_config_file(ConfigFile::from_path(&config_path));
+            let manifest = snapshot::export_snapshot(
+                &conf.get_chainstate_path_str(),
+                &conf.get_burnchain_path_str(),
+                &output_path,
+            )
+            .unwrap_or_else(|e| panic!("Failed to export chainstate snapshot: {}", e));
+            println!(
+                "Exported snapshot of Stacks block {}/{} (height {}) to {}",
+                &manifest.stacks_consensus_hash,
+                &manifest.stacks_block_hash,
+                manifest.stacks_block_height,
+                &output_path
+            );
+            println!(
+                "Snapshot digest: {}",
+                manifest.digest().expect("FATAL: failed to encode manifest")
+            );
+            return;
+        }
+        "import-snapshot" => {
+            let config_path: String = args.value_from_str("--config").unwrap();
+            let input_path: String = args.value_from_str("--input").unwrap();
+            let expected_digest: Option<String> = args.opt_value_from_str("--digest").unwrap();
+            args.finish().unwrap();
+            let expected_digest = expected_digest.map(|digest_hex| {
+                Sha256Sum::from_hex(&digest_hex).expect("`--digest` must be a hex SHA256 digest")
+            });
+            let conf = Config::from_config_file(ConfigFile::from_path(&config_path));
+            let manifest = snapshot::import_snapshot(
+                &input_path,
+                &conf.get_chainstate_path_str(),
+                &conf.get_burnchain_path_str(),
+                expected_digest.as_ref(),
+            )
+            .unwrap_or_else(|e| panic!("Failed to import chainstate snapshot: {}", e));
+            if manifest.mainnet != conf.is_mainnet() || manifest.chain_id != conf.burnchain.chain_id
+            {
+                warn!(
+                    "Imported snapshot is for mainnet={} chain ID {:08x}, but the config is for mainnet={} chain ID {:08x}",
+                    manifest.mainnet,
+                    manifest.chain_id,
+                    conf.is_mainnet(),
+                    conf.burnchain.chain_id
+                );
+            }
+            println!(
+                "Imported snapshot of Stacks block {}/{} (height {}); start the node to sync from there",
+                &manifest.stacks_consensus_hash,
+                &manifest.stacks_block_hash,
+                manifest.stacks_block_height
+            );
+            return;
+        }
+        "reindex" => {
+            let config_path: String = args.value_from_str("--config").unwrap();
+            args.finish().unwrap();
+            let conf = Config::from_config_file(ConfigFile::from_path(&config_path));
+            let stats = reindex::reindex_chainstate(&conf)
+                .unwrap_or_else(|e| panic!("Failed to reindex chainstate: {}", e));
+            println!(
+                "Reindexed chainstate: processed {} of {} blocks ({} missing)",
+                stats.blocks_processed, stats.blocks_total, stats.blocks_missing
             );
             return;
         }
@@ -224,6 +306,23 @@ key-for-seed\tOutput the associated secret key for a burnchain signer created wi
 \t\tCan be passed a config file for the seed via the `--config=<file>` option *or* by supplying the hex seed on
 \t\tthe command line directly.
 
+export-snapshot\tWrite a verifiable archive of this node's chainstate and burnchain state.
+\t\tArguments:
+\t\t  --config: path of the node's config
+\t\t  --output: path of the archive to write
+\t\tThe snapshot digest is printed on success; importers can use it to check the archive.
+
+import-snapshot\tBootstrap a fresh node from a snapshot archive, then start it to sync subsequent blocks.
+\t\tArguments:
+\t\t  --config: path of the node's config
+\t\t  --input: path of the archive to import
+\t\t  --digest: optional hex snapshot digest the archive must match
+
+reindex\t\tRebuild this node's chainstate from the blocks it has already stored, without downloading them again.
+\t\tThe node must not be running.  If interrupted, run it again to resume.
+\t\tArguments:
+\t\t  --config: path of the node's config
+
 help\t\tDisplay this help.
 
 OPTIONAL ARGUMENTS:
@@ -11,8 +11,9 @@ use std::sync::{
 };
 use std::{thread, thread::JoinHandle};
 
+use stacks::address::AddressHashMode;
 use stacks::burnchains::{Burnchain, BurnchainParameters, Txid};
-use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::burn::db::sortdb::{SortitionDB, SortitionDBConn};
 use stacks::chainstate::burn::operations::{
     leader_block_commit::{RewardSetInfo, BURN_BLOCK_MINED_AT_MODULUS},
     BlockstackOperationType, LeaderBlockCommitOp, LeaderKeyRegisterOp,
@@ -28,14 +29,17 @@ use stacks::chainstate::stacks::db::{
 use stacks::chainstate::stacks::Error as ChainstateError;
 use stacks::chainstate::stacks::StacksPublicKey;
 use stacks::chainstate::stacks::{
-    miner::BlockBuilderSettings, miner::StacksMicroblockBuilder, StacksBlockBuilder,
+    miner::AnchoredBlockCandidate, miner::BlockBuilderSettings, miner::CandidateBlockAssembly,
+    miner::StacksMicroblockBuilder, StacksBlockBuilder,
 };
 use stacks::chainstate::stacks::{
     CoinbasePayload, StacksBlock, StacksMicroblock, StacksTransaction, StacksTransactionSigner,
-    TransactionAnchorMode, TransactionPayload, TransactionVersion,
+    TransactionAnchorMode, TransactionAuth, TransactionPayload, TransactionVersion,
 };
 use stacks::codec::StacksMessageCodec;
-use stacks::core::mempool::MemPoolDB;
+use stacks::core::mempool::{
+    MemPoolDB, MemPoolEventDispatcher, MemPoolSubscription, MEMPOOL_REBROADCAST_MAX_TXS,
+};
 use stacks::core::FIRST_BURNCHAIN_CONSENSUS_HASH;
 use stacks::core::STACKS_EPOCH_2_05_MARKER;
 use stacks::cost_estimates::metrics::UnitMetric;
@@ -46,19 +50,21 @@ use stacks::net::{
     db::{LocalPeer, PeerDB},
     dns::DNSResolver,
     p2p::PeerNetwork,
+    readonly_pool::ReadOnlyCallPool,
     relay::Relayer,
     rpc::RPCHandlerArgs,
     Error as NetError, NetworkResult, PeerAddress,
 };
 use stacks::types::chainstate::{
-    BlockHeaderHash, BurnchainHeaderHash, SortitionId, StacksAddress, StacksBlockHeader, VRFSeed,
+    BlockHeaderHash, BurnchainHeaderHash, SortitionId, StacksAddress, StacksBlockHeader,
+    StacksBlockId, VRFSeed,
 };
 use stacks::util::get_epoch_time_ms;
 use stacks::util::get_epoch_time_secs;
 use stacks::util::hash::{to_hex, Hash160, Sha256Sum};
 use stacks::util::secp256k1::Secp256k1PrivateKey;
 use stacks::util::strings::{UrlString, VecDisplay};
-use stacks::util::vrf::VRFPublicKey;
+use stacks::util::vrf::{VRFProof, VRFPublicKey};
 use stacks::vm::costs::ExecutionCost;
 use stacks::{burnchains::BurnchainSigner, chainstate::stacks::db::StacksHeaderInfo};
 
@@ -89,6 +95,8 @@ struct MicroblockMinerState {
     quantity: u64,
     cost_so_far: ExecutionCost,
     settings: BlockBuilderSettings,
+    /// transactions that arrived in the mempool since the last microblock was mined
+    mempool_subscription: MemPoolSubscription,
 }
 
 enum RelayerDirective {
@@ -203,6 +211,53 @@ fn inner_process_tenure(
     Ok(true)
 }
 
+/// Assemble an anchored block, either in one shot or, if the miner is configured with a block
+/// assembly deadline, by improving on it until the deadline.
+fn inner_build_anchored_block(
+    config: &Config,
+    chain_state: &StacksChainState,
+    burn_dbconn: &SortitionDBConn,
+    mem_pool: &mut MemPoolDB,
+    parent_stacks_header: &StacksHeaderInfo,
+    total_burn: u64,
+    vrf_proof: VRFProof,
+    mblock_pubkey_hash: Hash160,
+    coinbase_tx: &StacksTransaction,
+    attempt: u64,
+    tenure_start_ms: u128,
+    event_observer: &EventDispatcher,
+) -> Result<(StacksBlock, ExecutionCost, u64), ChainstateError> {
+    let settings = config.make_block_builder_settings(attempt);
+    match config.get_block_assembly_deadline(tenure_start_ms) {
+        Some(deadline_ms) => StacksBlockBuilder::build_anchored_block_iterative(
+            chain_state,
+            burn_dbconn,
+            mem_pool,
+            parent_stacks_header,
+            total_burn,
+            vrf_proof,
+            mblock_pubkey_hash,
+            coinbase_tx,
+            settings,
+            deadline_ms,
+            config.make_block_assembly_resolve_strategy(),
+            Some(event_observer),
+        ),
+        None => StacksBlockBuilder::build_anchored_block(
+            chain_state,
+            burn_dbconn,
+            mem_pool,
+            parent_stacks_header,
+            total_burn,
+            vrf_proof,
+            mblock_pubkey_hash,
+            coinbase_tx,
+            settings,
+            Some(event_observer),
+        ),
+    }
+}
+
 fn inner_generate_coinbase_tx(
     keychain: &mut Keychain,
     nonce: u64,
@@ -254,6 +309,115 @@ fn inner_generate_poison_microblock_tx(
     tx_signer.get_tx().unwrap()
 }
 
+/// Report each microblock stream fork we have seen but not yet reported with a
+/// poison-microblock transaction signed by `reporter_key`, and announce the reports to our
+/// peers.  Evidence whose report the mempool rejects is not retried.
+fn report_poison_microblocks(
+    config: &Config,
+    reporter_key: &Secp256k1PrivateKey,
+    chainstate: &mut StacksChainState,
+    sortdb: &SortitionDB,
+    mem_pool: &mut MemPoolDB,
+    relayer: &mut Relayer,
+    event_dispatcher: &EventDispatcher,
+) -> Result<(), ChainstateError> {
+    let evidence = StacksChainState::get_unreported_poison_microblocks(chainstate.db())?;
+    if evidence.is_empty() {
+        return Ok(());
+    }
+
+    let (tip_consensus_hash, tip_block_hash) =
+        SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())?;
+    let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+    let stacks_epoch = sortdb
+        .index_conn()
+        .get_stacks_epoch(burn_tip.block_height as u32)
+        .expect("Could not find a stacks epoch.");
+
+    let hash_mode = AddressHashMode::SerializeP2PKH;
+    let (tx_version, address_version) = if config.is_mainnet() {
+        (TransactionVersion::Mainnet, hash_mode.to_version_mainnet())
+    } else {
+        (TransactionVersion::Testnet, hash_mode.to_version_testnet())
+    };
+    let reporter_address = StacksAddress::from_public_keys(
+        address_version,
+        &hash_mode,
+        1,
+        &vec![StacksPublicKey::from_private(reporter_key)],
+    )
+    .expect("BUG: failed to make poison-microblock reporter address");
+
+    let account_nonce = chainstate
+        .with_read_only_clarity_tx(
+            &sortdb.index_conn(),
+            &StacksBlockHeader::make_index_block_hash(&tip_consensus_hash, &tip_block_hash),
+            |conn| StacksChainState::get_account(conn, &reporter_address.clone().into()).nonce,
+        )
+        .ok_or(ChainstateError::NoSuchBlockError)?;
+    // don't conflict with earlier reports that are still pending
+    let mut nonce =
+        MemPoolDB::get_nonce_status(mem_pool.conn(), &reporter_address, account_nonce)?.next_nonce;
+
+    for evidence in evidence.into_iter() {
+        let mut tx_auth = TransactionAuth::from_p2pkh(reporter_key)
+            .expect("BUG: failed to make poison-microblock reporter auth");
+        tx_auth.set_origin_nonce(nonce);
+        tx_auth.set_tx_fee(config.node.poison_microblock_report_fee);
+
+        let mut tx = StacksTransaction::new(tx_version.clone(), tx_auth, evidence.payload());
+        tx.chain_id = config.burnchain.chain_id;
+        tx.anchor_mode = TransactionAnchorMode::OnChainOnly;
+        let mut tx_signer = StacksTransactionSigner::new(&tx);
+        tx_signer.sign_origin(reporter_key)?;
+        let tx = tx_signer
+            .get_tx()
+            .expect("BUG: failed to sign poison-microblock report");
+        let txid = tx.txid();
+
+        match mem_pool.submit(
+            chainstate,
+            &tip_consensus_hash,
+            &tip_block_hash,
+            &tx,
+            Some(event_dispatcher),
+            &stacks_epoch.block_limit,
+            &stacks_epoch.epoch_id,
+        ) {
+            Ok(()) => {
+                info!("Reported microblock stream fork";
+                      "parent_index_block_hash" => %evidence.parent_index_block_hash,
+                      "sequence" => evidence.sequence,
+                      "txid" => %txid);
+                nonce += 1;
+                chainstate.set_poison_microblock_reported(
+                    &evidence.parent_index_block_hash,
+                    evidence.sequence,
+                    Some(&txid),
+                )?;
+                if let Err(e) = relayer.broadcast_transaction(tx) {
+                    warn!(
+                        "Failed to announce poison-microblock report {}: {:?}",
+                        &txid, &e
+                    );
+                }
+            }
+            Err(e) => {
+                warn!("Failed to report microblock stream fork";
+                      "parent_index_block_hash" => %evidence.parent_index_block_hash,
+                      "sequence" => evidence.sequence,
+                      "error" => ?e);
+                chainstate.set_poison_microblock_reported(
+                    &evidence.parent_index_block_hash,
+                    evidence.sequence,
+                    None,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Constructs and returns a LeaderKeyRegisterOp out of the provided params
 fn inner_generate_leader_key_register_op(
     address: StacksAddress,
@@ -278,7 +442,10 @@ fn rotate_vrf_and_register(
     burn_block: &BlockSnapshot,
     btc_controller: &mut BitcoinRegtestController,
 ) -> bool {
-    let vrf_pk = keychain.rotate_vrf_keypair(burn_block.block_height);
+    let vrf_pk = match keychain.rotate_vrf_keypair(burn_block.block_height) {
+        Some(vrf_pk) => vrf_pk,
+        None => return false,
+    };
     let burnchain_tip_consensus_hash = &burn_block.consensus_hash;
     let op = inner_generate_leader_key_register_op(
         keychain.get_address(is_mainnet),
@@ -368,7 +535,17 @@ fn mine_one_microblock(
 
         let t1 = get_epoch_time_ms();
 
-        let mblock = microblock_miner.mine_next_microblock(mempool, &microblock_state.miner_key)?;
+        // once the mempool has been walked for the first microblock, only new arrivals need to be
+        // considered for the ones after it
+        let mblock = if microblock_state.quantity == 0 {
+            microblock_miner.mine_next_microblock(mempool, &microblock_state.miner_key)?
+        } else {
+            microblock_miner.mine_next_microblock_from_subscription(
+                mempool,
+                &mut microblock_state.mempool_subscription,
+                &microblock_state.miner_key,
+            )?
+        };
         let new_cost_so_far = microblock_miner.get_cost_so_far().expect("BUG: cannot read cost so far from miner -- indicates that the underlying Clarity Tx is somehow in use still.");
         let t2 = get_epoch_time_ms();
 
@@ -431,6 +608,7 @@ fn try_mine_microblock(
                     &parent_index_hash,
                 )?
                 .ok_or(NetError::NotFoundError)?;
+                let mempool_subscription = mem_pool.subscribe()?;
                 microblock_miner_state.replace(MicroblockMinerState {
                     parent_consensus_hash: ch.clone(),
                     parent_block_hash: bhh.clone(),
@@ -440,6 +618,7 @@ fn try_mine_microblock(
                     quantity: 0,
                     cost_so_far: cost_so_far,
                     settings: config.make_block_builder_settings(2),
+                    mempool_subscription,
                 });
             }
             Ok(None) => {
@@ -639,6 +818,23 @@ fn spawn_peer(
         &stacks_chainstate_path,
     )
     .map_err(|e| NetError::ChainstateError(e.to_string()))?;
+    chainstate.address_txs_index = config.node.address_txs_index;
+    chainstate.asset_holdings_index = config.node.asset_holdings_index;
+    chainstate
+        .clarity_state
+        .set_cost_breakdown(config.node.cost_breakdown);
+
+    // run read-only calls off of the p2p thread, unless configured not to
+    let read_only_call_pool = if config.connection_options.read_only_call_workers > 0 {
+        Some(ReadOnlyCallPool::new(
+            config.connection_options.read_only_call_workers,
+            config.connection_options.read_only_call_queue_size,
+            &chainstate,
+            &burn_db_path,
+        )?)
+    } else {
+        None
+    };
 
     // buffer up blocks to store without stalling the p2p thread
     let mut results_with_data = VecDeque::new();
@@ -661,6 +857,11 @@ fn spawn_peer(
                 metric,
             )
             .expect("Database failure opening mempool");
+            mem_pool.set_replace_by_fee_increment(config.node.mempool_replace_by_fee_increment);
+            mem_pool.set_limits(config.node.get_mempool_limits());
+            for policy in config.make_mempool_admission_policies() {
+                mem_pool.add_admission_policy(policy);
+            }
 
             // create estimators, metric instances for RPC handler
             let cost_estimator = config
@@ -676,9 +877,11 @@ fn spawn_peer(
                 genesis_chainstate_hash: Sha256Sum::from_hex(stx_genesis::GENESIS_CHAINSTATE_HASH)
                     .unwrap(),
                 event_observer: Some(&event_observer),
+                event_replay: Some(&event_observer),
                 cost_estimator: Some(cost_estimator.as_ref()),
                 cost_metric: Some(metric.as_ref()),
                 fee_estimator: fee_estimator.as_ref().map(|x| x.as_ref()),
+                read_only_call_pool: read_only_call_pool.as_ref(),
                 ..RPCHandlerArgs::default()
             };
 
@@ -852,6 +1055,7 @@ fn spawn_miner_relayer(
     let mut miner_tip = None; // only set if we won the last sortition
     let mut last_microblock_tenure_time = 0;
     let mut last_tenure_issue_time = 0;
+    let mut pruned_height = 0;
 
     let relayer_handle = thread::Builder::new().name("relayer".to_string()).spawn(move || {
         let cost_estimator = config.make_cost_estimator()
@@ -861,6 +1065,26 @@ fn spawn_miner_relayer(
 
         let mut mem_pool = MemPoolDB::open(is_mainnet, chain_id, &stacks_chainstate_path, cost_estimator, metric)
             .expect("Database failure opening mempool");
+        mem_pool.set_replace_by_fee_increment(config.node.mempool_replace_by_fee_increment);
+        mem_pool.set_limits(config.node.get_mempool_limits());
+        for policy in config.make_mempool_admission_policies() {
+            mem_pool.add_admission_policy(policy);
+        }
+        mem_pool.set_walk_strategy(config.make_mempool_walk_strategy());
+
+        // announce the transactions we kept from before the restart, once we've synced with our peers
+        let mut mempool_rebroadcast_pending = false;
+        if config.node.mempool_warm_restart {
+            match mem_pool.prepare_warm_restart() {
+                Ok(num_txs) => {
+                    info!("Relayer: mempool has {} pending transaction(s) from before restart", num_txs);
+                    mempool_rebroadcast_pending = num_txs > 0;
+                }
+                Err(e) => {
+                    warn!("Relayer: failed to prepare mempool for warm restart: {:?}", &e);
+                }
+            }
+        }
 
         while let Ok(mut directive) = relay_channel.recv() {
             match directive {
@@ -900,6 +1124,45 @@ fn spawn_miner_relayer(
 
                     // synchronize unconfirmed tx index to p2p thread
                     send_unconfirmed_txs(&chainstate, unconfirmed_txs.clone());
+
+                    if let Some(ref reporter_key) = config.node.poison_microblock_reporter_key {
+                        if let Err(e) = report_poison_microblocks(
+                            &config,
+                            reporter_key,
+                            &mut chainstate,
+                            &sortdb,
+                            &mut mem_pool,
+                            &mut relayer,
+                            &event_dispatcher,
+                        ) {
+                            warn!("Relayer: failed to report microblock stream forks: {:?}", &e);
+                        }
+                    }
+
+                    if mempool_rebroadcast_pending && net_result.num_inv_sync_passes > 0 {
+                        mempool_rebroadcast_pending = false;
+                        match relayer.rebroadcast_mempool_txs(&mem_pool, MEMPOOL_REBROADCAST_MAX_TXS) {
+                            Ok(num_txs) => {
+                                info!("Relayer: announced {} mempool transaction(s) to peers", num_txs);
+                            }
+                            Err(e) => {
+                                warn!("Relayer: failed to announce mempool transactions: {:?}", &e);
+                            }
+                        }
+                    }
+
+                    if let Some(prune_depth) = config.node.prune_depth {
+                        let (tip_consensus_hash, tip_block_hash) = SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())
+                            .expect("BUG: failed to load canonical stacks chain tip hash");
+                        match chainstate.prune(&tip_consensus_hash, &tip_block_hash, prune_depth, pruned_height) {
+                            Ok(stats) => {
+                                pruned_height = stats.pruned_height;
+                            }
+                            Err(e) => {
+                                debug!("Relayer: did not prune chainstate: {:?}", &e);
+                            }
+                        }
+                    }
                 }
                 RelayerDirective::ProcessTenure(consensus_hash, burn_hash, block_header_hash) => {
                     debug!(
@@ -1079,6 +1342,12 @@ fn spawn_miner_relayer(
                         }
                         last_mined_blocks_vec.push((last_mined_block, microblock_privkey));
                     }
+                    else if last_mined_blocks_vec.len() > 0 {
+                        // nothing new to commit to, but our last commit may be stuck behind a
+                        // rising burnchain fee rate
+                        let mut op_signer = keychain.generate_op_signer();
+                        bitcoin_controller.replace_block_commit_by_fee(&mut op_signer);
+                    }
                     last_mined_blocks.insert(burn_header_hash, last_mined_blocks_vec);
 
                     last_tenure_issue_time = get_epoch_time_ms();
@@ -1326,12 +1595,17 @@ impl InitializedNeonNode {
             _ => {}
         }
 
-        let relayer = Relayer::from_p2p(&mut p2p_net);
+        let mut relayer = Relayer::from_p2p(&mut p2p_net);
+        for policy in config.make_relay_policies() {
+            relayer.add_relay_policy(policy);
+        }
         let shared_unconfirmed_txs = Arc::new(Mutex::new(UnconfirmedTxMap::new()));
 
         let leader_key_registration_state = if config.node.mock_mining {
             // mock mining, pretend to have a registered key
-            let vrf_public_key = keychain.rotate_vrf_keypair(1);
+            let vrf_public_key = keychain
+                .rotate_vrf_keypair(1)
+                .expect("FATAL: failed to get a VRF public key");
             LeaderKeyRegistrationState::Active(RegisteredKey {
                 block_height: 1,
                 op_vtxindex: 1,
@@ -1565,6 +1839,130 @@ impl InitializedNeonNode {
         })
     }
 
+    /// Start assembling candidate blocks on top of the Stacks blocks that compete with our
+    /// parent (i.e. the other processed blocks at its height), up to the configured number of
+    /// fork candidates.  Each candidate is assembled on its own thread, against a detached
+    /// chainstate handle and a snapshot of the mempool, so that it can run alongside the block
+    /// built on top of the canonical tip.
+    fn spawn_fork_candidates(
+        config: &Config,
+        chain_state: &mut StacksChainState,
+        burn_db: &mut SortitionDB,
+        burn_block: &BlockSnapshot,
+        keychain: &mut Keychain,
+        mem_pool: &MemPoolDB,
+        parent_block_id: &StacksBlockId,
+        parent_height: u64,
+        vrf_proof: &VRFProof,
+        mblock_pubkey_hash: &Hash160,
+        attempt: u64,
+    ) -> Vec<(MiningTenureInformation, CandidateBlockAssembly)> {
+        let mut candidates = vec![];
+        if config.miner.max_fork_candidates == 0 {
+            return candidates;
+        }
+
+        let competitors = match chain_state.get_stacks_chain_tips_at_height(parent_height) {
+            Ok(competitors) => competitors,
+            Err(e) => {
+                warn!("Failed to load competing Stacks tips: {:?}", &e);
+                return candidates;
+            }
+        };
+
+        let miner_address = match keychain.origin_address(config.is_mainnet()) {
+            Some(addr) => addr,
+            None => return candidates,
+        };
+
+        for competitor in competitors.into_iter() {
+            if (candidates.len() as u64) >= config.miner.max_fork_candidates {
+                break;
+            }
+            let competitor_id = StacksBlockHeader::make_index_block_hash(
+                &competitor.consensus_hash,
+                &competitor.anchored_block_hash,
+            );
+            if competitor_id == *parent_block_id {
+                continue;
+            }
+
+            let tenure_info = match Self::get_mining_tenure_information(
+                chain_state,
+                burn_db,
+                burn_block,
+                miner_address.clone(),
+                &competitor.consensus_hash,
+                &competitor.anchored_block_hash,
+            ) {
+                Ok(info) => info,
+                Err(_) => continue,
+            };
+
+            let coinbase_tx = inner_generate_coinbase_tx(
+                keychain,
+                tenure_info.coinbase_nonce,
+                config.is_mainnet(),
+                config.burnchain.chain_id,
+            );
+
+            let snapshot_path = format!(
+                "{}/fork-candidate-mempool-{}.sqlite",
+                &config.get_chainstate_path_str(),
+                candidates.len()
+            );
+            let cost_estimator = config
+                .make_cost_estimator()
+                .unwrap_or_else(|| Box::new(UnitEstimator));
+            let metric = config
+                .make_cost_metric()
+                .unwrap_or_else(|| Box::new(UnitMetric));
+            let mut mempool_snapshot =
+                match mem_pool.snapshot(&snapshot_path, cost_estimator, metric) {
+                    Ok(snapshot) => snapshot,
+                    Err(e) => {
+                        warn!(
+                            "Failed to snapshot the mempool for a fork candidate: {:?}",
+                            &e
+                        );
+                        break;
+                    }
+                };
+            mempool_snapshot.set_walk_strategy(config.make_mempool_walk_strategy());
+
+            let candidate = AnchoredBlockCandidate {
+                parent_stacks_header: tenure_info.stacks_parent_header.clone(),
+                total_burn: tenure_info.parent_block_total_burn,
+                proof: vrf_proof.clone(),
+                pubkey_hash: mblock_pubkey_hash.clone(),
+                coinbase_tx,
+            };
+            match StacksBlockBuilder::spawn_candidate_assembly(
+                chain_state,
+                &config.get_burn_db_file_path(),
+                mempool_snapshot,
+                candidate,
+                config.make_block_builder_settings(attempt),
+            ) {
+                Ok(assembly) => {
+                    debug!(
+                        "Assembling fork candidate block";
+                        "parent_block_id" => %assembly.parent_block_id,
+                        "canonical_parent_block_id" => %parent_block_id
+                    );
+                    candidates.push((tenure_info, assembly));
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to start assembling a fork candidate block: {:?}",
+                        &e
+                    );
+                }
+            }
+        }
+        candidates
+    }
+
     /// Return the assembled anchor block info and microblock private key on success.
     /// Return None if we couldn't build a block for whatever reason
     fn relayer_run_tenure(
@@ -1581,12 +1979,13 @@ impl InitializedNeonNode {
         last_mined_blocks: &Vec<&AssembledAnchorBlock>,
         event_observer: &EventDispatcher,
     ) -> Option<(AssembledAnchorBlock, Secp256k1PrivateKey)> {
+        let tenure_start_ms = get_epoch_time_ms();
         let MiningTenureInformation {
             mut stacks_parent_header,
-            parent_consensus_hash,
-            parent_block_burn_height,
-            parent_block_total_burn,
-            parent_winning_vtxindex,
+            mut parent_consensus_hash,
+            mut parent_block_burn_height,
+            mut parent_block_total_burn,
+            mut parent_winning_vtxindex,
             coinbase_nonce,
         } = if let Some(stacks_tip) = chain_state.get_stacks_chain_tip(burn_db).unwrap() {
             let miner_address = keychain.origin_address(config.is_mainnet()).unwrap();
@@ -1862,7 +2261,26 @@ impl InitializedNeonNode {
             }
         }
 
-        let (anchored_block, _, _) = match StacksBlockBuilder::build_anchored_block(
+        let parent_block_id = StacksBlockHeader::make_index_block_hash(
+            &parent_consensus_hash,
+            &stacks_parent_header.anchored_header.block_hash(),
+        );
+        let fork_candidates = Self::spawn_fork_candidates(
+            config,
+            chain_state,
+            burn_db,
+            &burn_block,
+            keychain,
+            mem_pool,
+            &parent_block_id,
+            stacks_parent_header.block_height,
+            &vrf_proof,
+            &mblock_pubkey_hash,
+            (last_mined_blocks.len() + 1) as u64,
+        );
+
+        let (mut anchored_block, _, _) = match inner_build_anchored_block(
+            config,
             chain_state,
             &burn_db.index_conn(),
             mem_pool,
@@ -1871,8 +2289,9 @@ impl InitializedNeonNode {
             vrf_proof.clone(),
             mblock_pubkey_hash,
             &coinbase_tx,
-            config.make_block_builder_settings((last_mined_blocks.len() + 1) as u64),
-            Some(event_observer),
+            (last_mined_blocks.len() + 1) as u64,
+            tenure_start_ms,
+            event_observer,
         ) {
             Ok(block) => block,
             Err(ChainstateError::InvalidStacksMicroblock(msg, mblock_header_hash)) => {
@@ -1902,7 +2321,8 @@ impl InitializedNeonNode {
                 };
 
                 // try again
-                match StacksBlockBuilder::build_anchored_block(
+                match inner_build_anchored_block(
+                    config,
                     chain_state,
                     &burn_db.index_conn(),
                     mem_pool,
@@ -1911,8 +2331,9 @@ impl InitializedNeonNode {
                     vrf_proof.clone(),
                     mblock_pubkey_hash,
                     &coinbase_tx,
-                    config.make_block_builder_settings((last_mined_blocks.len() + 1) as u64),
-                    Some(event_observer),
+                    (last_mined_blocks.len() + 1) as u64,
+                    tenure_start_ms,
+                    event_observer,
                 ) {
                     Ok(block) => block,
                     Err(e) => {
@@ -1926,6 +2347,56 @@ impl InitializedNeonNode {
                 return None;
             }
         };
+
+        // if one of the competing tips became the canonical tip while we were assembling our
+        // block, commit to the block we built on top of it instead
+        if fork_candidates.len() > 0 {
+            let canonical_tip_id = match chain_state.get_stacks_chain_tip(burn_db) {
+                Ok(Some(tip)) => Some(StacksBlockHeader::make_index_block_hash(
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                )),
+                _ => None,
+            };
+            for (tenure_info, assembly) in fork_candidates.into_iter() {
+                let candidate_parent_id = assembly.parent_block_id.clone();
+                let assembled = match assembly.join() {
+                    Ok(assembled) => assembled,
+                    Err(e) => {
+                        warn!(
+                            "Failed to assemble fork candidate block";
+                            "parent_block_id" => %candidate_parent_id,
+                            "error" => ?e
+                        );
+                        continue;
+                    }
+                };
+                if canonical_tip_id.as_ref() != Some(&candidate_parent_id)
+                    || candidate_parent_id == parent_block_id
+                {
+                    continue;
+                }
+
+                info!(
+                    "Stacks tip moved to {} while assembling our block; committing to the fork candidate block {} instead",
+                    &candidate_parent_id,
+                    &assembled.block.block_hash()
+                );
+                event_observer.mined_block_event(
+                    burn_block.block_height + 1,
+                    &assembled.block,
+                    assembled.size,
+                    &assembled.consumed,
+                    &assembled.confirmed_mblock_cost,
+                );
+                anchored_block = assembled.block;
+                parent_consensus_hash = tenure_info.parent_consensus_hash;
+                parent_block_burn_height = tenure_info.parent_block_burn_height;
+                parent_block_total_burn = tenure_info.parent_block_total_burn;
+                parent_winning_vtxindex = tenure_info.parent_winning_vtxindex;
+            }
+        }
+
         let block_height = anchored_block.header.total_work.work;
         info!(
             "Succeeded assembling {} block #{}: {}, with {} txs, attempt {}",
@@ -2113,7 +2584,7 @@ impl NeonGenesisNode {
         burnchain: Burnchain,
         boot_block_exec: Box<dyn FnOnce(&mut ClarityTx) -> ()>,
     ) -> Self {
-        let keychain = Keychain::default(config.node.seed.clone());
+        let keychain = Keychain::for_miner(&config);
         let initial_balances = config
             .initial_balances
             .iter()
@@ -6,6 +6,8 @@ use std::sync::{atomic::AtomicBool, Arc};
 use std::{collections::HashSet, env};
 use std::{thread, thread::JoinHandle, time};
 
+use stx_genesis::GenesisData;
+
 use stacks::chainstate::burn::operations::{
     leader_block_commit::{RewardSetInfo, BURN_BLOCK_MINED_AT_MODULUS},
     BlockstackOperationType, LeaderBlockCommitOp, LeaderKeyRegisterOp,
@@ -99,10 +101,10 @@ pub struct Node {
 }
 
 pub fn get_account_lockups(
-    use_test_chainstate_data: bool,
+    genesis_data: &GenesisData,
 ) -> Box<dyn Iterator<Item = ChainstateAccountLockup>> {
     Box::new(
-        stx_genesis::GenesisData::new(use_test_chainstate_data)
+        genesis_data
             .read_lockups()
             .map(|item| ChainstateAccountLockup {
                 address: item.address,
@@ -113,10 +115,10 @@ pub fn get_account_lockups(
 }
 
 pub fn get_account_balances(
-    use_test_chainstate_data: bool,
+    genesis_data: &GenesisData,
 ) -> Box<dyn Iterator<Item = ChainstateAccountBalance>> {
     Box::new(
-        stx_genesis::GenesisData::new(use_test_chainstate_data)
+        genesis_data
             .read_balances()
             .map(|item| ChainstateAccountBalance {
                 address: item.address,
@@ -126,10 +128,10 @@ pub fn get_account_balances(
 }
 
 pub fn get_namespaces(
-    use_test_chainstate_data: bool,
+    genesis_data: &GenesisData,
 ) -> Box<dyn Iterator<Item = ChainstateBNSNamespace>> {
     Box::new(
-        stx_genesis::GenesisData::new(use_test_chainstate_data)
+        genesis_data
             .read_namespaces()
             .map(|item| ChainstateBNSNamespace {
                 namespace_id: item.namespace_id,
@@ -144,16 +146,12 @@ pub fn get_namespaces(
     )
 }
 
-pub fn get_names(use_test_chainstate_data: bool) -> Box<dyn Iterator<Item = ChainstateBNSName>> {
-    Box::new(
-        stx_genesis::GenesisData::new(use_test_chainstate_data)
-            .read_names()
-            .map(|item| ChainstateBNSName {
-                fully_qualified_name: item.fully_qualified_name,
-                owner: item.owner,
-                zonefile_hash: item.zonefile_hash,
-            }),
-    )
+pub fn get_names(genesis_data: &GenesisData) -> Box<dyn Iterator<Item = ChainstateBNSName>> {
+    Box::new(genesis_data.read_names().map(|item| ChainstateBNSName {
+        fully_qualified_name: item.fully_qualified_name,
+        owner: item.owner,
+        zonefile_hash: item.zonefile_hash,
+    }))
 }
 
 fn spawn_peer(
@@ -273,6 +271,57 @@ pub fn use_test_genesis_chainstate(config: &Config) -> bool {
     }
 }
 
+/// Load the genesis data this node boots from: the custom genesis files in the config if there
+/// are any, and the embedded (test or production) data otherwise.
+pub fn get_genesis_data(config: &Config, use_test_genesis_data: bool) -> GenesisData {
+    match config.node.genesis_chainstate_file {
+        Some(ref chainstate_path) => GenesisData::from_files(
+            chainstate_path,
+            config.node.genesis_name_zonefiles_file.as_deref(),
+        )
+        .unwrap_or_else(|e| {
+            panic!(
+                "FATAL: failed to load custom genesis data from {}: {}",
+                chainstate_path, e
+            )
+        }),
+        None => GenesisData::new(use_test_genesis_data),
+    }
+}
+
+/// Make the boot data for a neon node's chainstate, given its burnchain and genesis data.
+pub fn make_chainstate_boot_data(
+    config: &Config,
+    burnchain: &Burnchain,
+    genesis_data: GenesisData,
+) -> ChainStateBootData {
+    let initial_balances = config
+        .initial_balances
+        .iter()
+        .map(|e| (e.address.clone(), e.amount))
+        .collect();
+
+    let (lockups_data, balances_data, namespaces_data, names_data) = (
+        genesis_data.clone(),
+        genesis_data.clone(),
+        genesis_data.clone(),
+        genesis_data,
+    );
+    ChainStateBootData {
+        initial_balances,
+        post_flight_callback: None,
+        first_burnchain_block_hash: burnchain.first_block_hash,
+        first_burnchain_block_height: burnchain.first_block_height as u32,
+        first_burnchain_block_timestamp: burnchain.first_block_timestamp,
+        pox_constants: burnchain.pox_constants.clone(),
+        get_bulk_initial_lockups: Some(Box::new(move || get_account_lockups(&lockups_data))),
+        get_bulk_initial_balances: Some(Box::new(move || get_account_balances(&balances_data))),
+        get_bulk_initial_namespaces: Some(Box::new(move || get_namespaces(&namespaces_data))),
+        get_bulk_initial_names: Some(Box::new(move || get_names(&names_data))),
+        extra_boot_contracts: config.boot_contracts.clone(),
+    }
+}
+
 impl Node {
     /// Instantiate and initialize a new node, given a config
     pub fn new(
@@ -285,6 +334,13 @@ impl Node {
         } else {
             USE_TEST_GENESIS_CHAINSTATE
         };
+        let genesis_data = get_genesis_data(&config, use_test_genesis_data);
+        let (lockups_data, balances_data, namespaces_data, names_data) = (
+            genesis_data.clone(),
+            genesis_data.clone(),
+            genesis_data.clone(),
+            genesis_data,
+        );
 
         let keychain = Keychain::default(config.node.seed.clone());
 
@@ -306,16 +362,11 @@ impl Node {
             first_burnchain_block_timestamp: 0,
             pox_constants,
             post_flight_callback: Some(boot_block_exec),
-            get_bulk_initial_lockups: Some(Box::new(move || {
-                get_account_lockups(use_test_genesis_data)
-            })),
-            get_bulk_initial_balances: Some(Box::new(move || {
-                get_account_balances(use_test_genesis_data)
-            })),
-            get_bulk_initial_namespaces: Some(Box::new(move || {
-                get_namespaces(use_test_genesis_data)
-            })),
-            get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
+            get_bulk_initial_lockups: Some(Box::new(move || get_account_lockups(&lockups_data))),
+            get_bulk_initial_balances: Some(Box::new(move || get_account_balances(&balances_data))),
+            get_bulk_initial_namespaces: Some(Box::new(move || get_namespaces(&namespaces_data))),
+            get_bulk_initial_names: Some(Box::new(move || get_names(&names_data))),
+            extra_boot_contracts: config.boot_contracts.clone(),
         };
 
         let chain_state_result = StacksChainState::open_and_exec(
@@ -563,7 +614,8 @@ impl Node {
         let burnchain_tip = burnchain_controller.get_chain_tip();
         let vrf_pk = self
             .keychain
-            .rotate_vrf_keypair(burnchain_tip.block_snapshot.block_height);
+            .rotate_vrf_keypair(burnchain_tip.block_snapshot.block_height)
+            .expect("FATAL: failed to get a VRF public key");
         let consensus_hash = burnchain_tip.block_snapshot.consensus_hash;
         let key_reg_op = self.generate_leader_key_register_op(vrf_pk, &consensus_hash);
         let mut op_signer = self.keychain.generate_op_signer();
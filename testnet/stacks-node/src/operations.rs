@@ -1,8 +1,13 @@
+use stacks::util::hash::Sha256Sum;
 use stacks::util::secp256k1::{MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey};
 use stacks::{burnchains::PrivateKey, util::hash::hex_bytes};
 
+use super::signer::{LocalSigner, MinerSigner};
+
 pub struct BurnchainOpSigner {
-    secret_key: Secp256k1PrivateKey,
+    signer: Box<dyn MinerSigner>,
+    /// only known if the key is held locally
+    secret_key: Option<Secp256k1PrivateKey>,
     is_one_off: bool,
     is_disposed: bool,
     usages: u8,
@@ -10,28 +15,36 @@ pub struct BurnchainOpSigner {
 
 impl BurnchainOpSigner {
     pub fn new(secret_key: Secp256k1PrivateKey, is_one_off: bool) -> BurnchainOpSigner {
+        let signer = LocalSigner::new(secret_key, Sha256Sum::from_data(&secret_key.to_bytes()));
+        let mut op_signer = BurnchainOpSigner::from_signer(Box::new(signer), is_one_off);
+        op_signer.secret_key = Some(secret_key);
+        op_signer
+    }
+
+    /// Sign with the given signer, which may not hold the key locally
+    pub fn from_signer(signer: Box<dyn MinerSigner>, is_one_off: bool) -> BurnchainOpSigner {
         BurnchainOpSigner {
-            secret_key,
+            signer,
+            secret_key: None,
             usages: 0,
             is_one_off,
             is_disposed: false,
         }
     }
 
-    pub fn get_sk_as_wif(&self) -> String {
-        let hex_encoded = self.secret_key.to_hex();
+    pub fn get_sk_as_wif(&self) -> Option<String> {
+        let hex_encoded = self.secret_key.as_ref()?.to_hex();
         let mut as_bytes = hex_bytes(&hex_encoded).unwrap();
         as_bytes.insert(0, 0x80);
-        stacks::address::b58::check_encode_slice(&as_bytes)
+        Some(stacks::address::b58::check_encode_slice(&as_bytes))
     }
 
-    pub fn get_sk_as_hex(&self) -> String {
-        self.secret_key.to_hex()
+    pub fn get_sk_as_hex(&self) -> Option<String> {
+        self.secret_key.as_ref().map(|sk| sk.to_hex())
     }
 
     pub fn get_public_key(&mut self) -> Secp256k1PublicKey {
-        let public_key = Secp256k1PublicKey::from_private(&self.secret_key);
-        public_key
+        self.signer.get_public_key()
     }
 
     pub fn sign_message(&mut self, hash: &[u8]) -> Option<MessageSignature> {
@@ -39,9 +52,16 @@ impl BurnchainOpSigner {
             return None;
         }
 
-        let signature = match self.secret_key.sign(hash) {
+        let signature = match self.signer.sign_message(hash) {
             Ok(r) => r,
-            _ => return None,
+            Err(e) => {
+                error!(
+                    "Failed to sign burnchain operation with {} signer: {}",
+                    self.signer.name(),
+                    &e
+                );
+                return None;
+            }
         };
         self.usages += 1;
 
@@ -72,7 +92,7 @@ mod test {
         for (secret_key, expected_wif) in examples.iter() {
             let secp_k = Secp256k1PrivateKey::from_hex(secret_key).unwrap();
             let op_signer = BurnchainOpSigner::new(secp_k, false);
-            assert_eq!(expected_wif, &op_signer.get_sk_as_wif());
+            assert_eq!(expected_wif, &op_signer.get_sk_as_wif().unwrap());
         }
     }
 }
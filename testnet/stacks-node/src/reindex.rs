@@ -0,0 +1,105 @@
+//! Rebuilding a node's chainstate from the blocks it already has, without re-downloading them.
+//!
+//! The existing chainstate directory is moved aside, a fresh chainstate is booted in its place,
+//! and `StacksChainState::reindex_from` replays the old directory's blocks into it against the
+//! node's sortition DB.  If the reindex is interrupted, running it again picks up where it left
+//! off; the old directory is only removed once the reindex completes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::stacks::db::reindex::ReindexStats;
+use stacks::chainstate::stacks::db::StacksChainState;
+
+use crate::node::{get_genesis_data, make_chainstate_boot_data, use_test_genesis_chainstate};
+use crate::{BitcoinRegtestController, Config};
+
+/// Number of blocks to stage and process between progress reports
+const REINDEX_BATCH_SIZE: usize = 100;
+
+/// Where the chainstate being reindexed is kept while the new one is built
+fn reindex_source_path(chainstate_path: &str) -> String {
+    format!("{}.reindex-source", chainstate_path)
+}
+
+/// Rebuild this node's chainstate from its locally stored blocks and sortition history.
+pub fn reindex_chainstate(config: &Config) -> Result<ReindexStats, String> {
+    let chainstate_path = config.get_chainstate_path_str();
+    let source_path = reindex_source_path(&chainstate_path);
+
+    if Path::new(&source_path).exists() {
+        info!("Resuming reindex of chainstate from {}", &source_path);
+    } else {
+        if !StacksChainState::header_index_root_path(PathBuf::from(&chainstate_path)).exists() {
+            return Err(format!("No chainstate to reindex at {}", &chainstate_path));
+        }
+        fs::rename(&chainstate_path, &source_path).map_err(|e| {
+            format!(
+                "Failed to move {} to {}: {:?}",
+                &chainstate_path, &source_path, &e
+            )
+        })?;
+        info!(
+            "Moved chainstate to {}; rebuilding it in {}",
+            &source_path, &chainstate_path
+        );
+    }
+
+    let burnchain = BitcoinRegtestController::new_dummy(config.clone()).get_burnchain();
+    let mut sortdb = SortitionDB::open(&config.get_burn_db_file_path(), true)
+        .map_err(|e| format!("Failed to open sortition DB: {:?}", &e))?;
+
+    let genesis_data = get_genesis_data(config, use_test_genesis_chainstate(config));
+    let mut boot_data = make_chainstate_boot_data(config, &burnchain, genesis_data);
+    let (mut chainstate, _) = StacksChainState::open_and_exec(
+        config.is_mainnet(),
+        config.burnchain.chain_id,
+        &chainstate_path,
+        Some(&mut boot_data),
+    )
+    .map_err(|e| {
+        format!(
+            "Failed to boot chainstate at {}: {:?}",
+            &chainstate_path, &e
+        )
+    })?;
+    chainstate.address_txs_index = config.node.address_txs_index;
+    chainstate.asset_holdings_index = config.node.asset_holdings_index;
+    chainstate
+        .clarity_state
+        .set_cost_breakdown(config.node.cost_breakdown);
+
+    let stats = chainstate
+        .reindex_from(&source_path, &mut sortdb, REINDEX_BATCH_SIZE, |stats| {
+            info!(
+                "Reindexed {} of {} blocks (height {}); {} processed, {} missing",
+                stats.blocks_considered,
+                stats.blocks_total,
+                stats.height,
+                stats.blocks_processed,
+                stats.blocks_missing
+            );
+        })
+        .map_err(|e| format!("Failed to reindex chainstate: {:?}", &e))?;
+
+    fs::remove_dir_all(&source_path)
+        .map_err(|e| format!("Failed to remove {}: {:?}", &source_path, &e))?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reindex_without_chainstate_fails() {
+        let mut config = Config::default();
+        config.node.working_dir = format!(
+            "/tmp/stacks-node-tests/test_reindex_without_chainstate_fails-{}",
+            std::process::id()
+        );
+        assert!(reindex_chainstate(&config).is_err());
+        assert!(!Path::new(&reindex_source_path(&config.get_chainstate_path_str())).exists());
+    }
+}
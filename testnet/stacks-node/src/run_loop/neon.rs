@@ -7,23 +7,23 @@ use std::thread;
 use stacks::deps::ctrlc as termination;
 use stacks::deps::ctrlc::SignalId;
 
-use stacks::burnchains::bitcoin::address::BitcoinAddress;
-use stacks::burnchains::bitcoin::address::BitcoinAddressType;
-use stacks::burnchains::{Address, Burnchain};
+use stacks::burnchains::Burnchain;
 use stacks::chainstate::burn::db::sortdb::SortitionDB;
 use stacks::chainstate::coordinator::comm::{CoordinatorChannels, CoordinatorReceivers};
 use stacks::chainstate::coordinator::{
-    check_chainstate_db_versions, BlockEventDispatcher, ChainsCoordinator, CoordinatorCommunication,
+    check_chainstate_db_versions, check_sortition_db_epochs, BlockEventDispatcher,
+    ChainsCoordinator, CoordinatorCommunication,
 };
-use stacks::chainstate::stacks::db::{ChainStateBootData, StacksChainState};
+use stacks::chainstate::stacks::db::StacksChainState;
 use stacks::net::atlas::{AtlasConfig, Attachment};
-use stx_genesis::GenesisData;
 
+use crate::burnchains::bitcoin_regtest_controller::get_miner_bitcoin_address;
+use crate::burnchains::bitcoin_zmq::BitcoinZmqListener;
 use crate::monitoring::start_serving_monitoring_metrics;
-use crate::node::use_test_genesis_chainstate;
+use crate::node::{get_genesis_data, make_chainstate_boot_data, use_test_genesis_chainstate};
+use crate::snapshot_bootstrap;
 use crate::syncctl::PoxSyncWatchdog;
 use crate::{
-    node::{get_account_balances, get_account_lockups, get_names, get_namespaces},
     BitcoinRegtestController, BurnchainController, Config, EventDispatcher, Keychain,
     NeonGenesisNode,
 };
@@ -158,6 +158,11 @@ impl RunLoop {
             }
         }
 
+        // A fresh node may start from a snapshot instead of from genesis.
+        if let Err(e) = snapshot_bootstrap::bootstrap_from_snapshot(&self.config) {
+            panic!("FATAL: failed to bootstrap from snapshot: {}", e);
+        }
+
         // Initialize and start the burnchain.
         let mut burnchain = BitcoinRegtestController::with_burnchain(
             self.config.clone(),
@@ -179,19 +184,18 @@ impl RunLoop {
                 "FATAL: chainstate database(s) are not compatible with the current system epoch"
             );
         }
+        if !check_sortition_db_epochs(&epochs, &self.config.get_burn_db_file_path())
+            .expect("FATAL: unable to query filesystem or databases for epoch information")
+        {
+            panic!("FATAL: sortition database was instantiated with a different epoch schedule");
+        }
 
         let is_miner = if self.config.node.miner {
-            let keychain = Keychain::default(self.config.node.seed.clone());
-            let node_address = Keychain::address_from_burnchain_signer(
-                &keychain.get_burnchain_signer(),
-                self.config.is_mainnet(),
+            let keychain = Keychain::for_miner(&self.config);
+            let btc_addr = get_miner_bitcoin_address(
+                &self.config,
+                &keychain.generate_op_signer().get_public_key(),
             );
-            let btc_addr = BitcoinAddress::from_bytes(
-                self.config.burnchain.get_bitcoin_network().1,
-                BitcoinAddressType::PublicKeyHash,
-                &node_address.to_bytes(),
-            )
-            .unwrap();
             info!("Miner node: checking UTXOs at address: {}", btc_addr);
 
             match burnchain.create_wallet_if_dne() {
@@ -238,23 +242,30 @@ impl RunLoop {
 
         let mainnet = self.config.is_mainnet();
         let chainid = self.config.burnchain.chain_id;
-        let initial_balances = self
-            .config
-            .initial_balances
-            .iter()
-            .map(|e| (e.address.clone(), e.amount))
-            .collect();
 
         // setup dispatcher
         let mut event_dispatcher = EventDispatcher::new();
+        if self.config.node.event_queue {
+            event_dispatcher.enable_event_queue(
+                &self.config.get_event_queue_file_path(),
+                self.config.node.event_replay_depth,
+            );
+        }
         for observer in self.config.events_observers.iter() {
             event_dispatcher.register_observer(observer, should_keep_running.clone());
         }
+        if let Some(ref event_stream_bind) = self.config.node.event_stream_bind {
+            event_dispatcher.register_event_stream(
+                event_stream_bind,
+                self.config.node.event_stream_auth_token.clone(),
+            );
+        }
 
         let use_test_genesis_data = use_test_genesis_chainstate(&self.config);
 
         let mut atlas_config = AtlasConfig::default(false);
-        let genesis_attachments = GenesisData::new(use_test_genesis_data)
+        let genesis_data = get_genesis_data(&self.config, use_test_genesis_data);
+        let genesis_attachments = genesis_data
             .read_name_zonefiles()
             .into_iter()
             .map(|z| Attachment::new(z.zonefile_content.as_bytes().to_vec()))
@@ -268,32 +279,21 @@ impl RunLoop {
 
         let (attachments_tx, attachments_rx) = sync_channel(1);
 
-        let mut boot_data = ChainStateBootData {
-            initial_balances,
-            post_flight_callback: None,
-            first_burnchain_block_hash: coordinator_burnchain_config.first_block_hash,
-            first_burnchain_block_height: coordinator_burnchain_config.first_block_height as u32,
-            first_burnchain_block_timestamp: coordinator_burnchain_config.first_block_timestamp,
-            pox_constants: coordinator_burnchain_config.pox_constants.clone(),
-            get_bulk_initial_lockups: Some(Box::new(move || {
-                get_account_lockups(use_test_genesis_data)
-            })),
-            get_bulk_initial_balances: Some(Box::new(move || {
-                get_account_balances(use_test_genesis_data)
-            })),
-            get_bulk_initial_namespaces: Some(Box::new(move || {
-                get_namespaces(use_test_genesis_data)
-            })),
-            get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
-        };
+        let mut boot_data =
+            make_chainstate_boot_data(&self.config, &coordinator_burnchain_config, genesis_data);
 
-        let (chain_state_db, receipts) = StacksChainState::open_and_exec(
+        let (mut chain_state_db, receipts) = StacksChainState::open_and_exec(
             mainnet,
             chainid,
             &chainstate_path,
             Some(&mut boot_data),
         )
         .unwrap();
+        chain_state_db.address_txs_index = self.config.node.address_txs_index;
+        chain_state_db.asset_holdings_index = self.config.node.asset_holdings_index;
+        chain_state_db
+            .clarity_state
+            .set_cost_breakdown(self.config.node.cost_breakdown);
         coordinator_dispatcher.dispatch_boot_receipts(receipts);
 
         let atlas_config = AtlasConfig::default(mainnet);
@@ -341,6 +341,14 @@ impl RunLoop {
         )
         .unwrap();
 
+        if let Some(ref zmq_endpoint) = self.config.burnchain.zmq_block_endpoint {
+            BitcoinZmqListener::spawn(
+                zmq_endpoint.clone(),
+                pox_watchdog.make_comms_handle(),
+                should_keep_running.clone(),
+            );
+        }
+
         // setup genesis
         let node = NeonGenesisNode::new(
             self.config.clone(),
@@ -0,0 +1,354 @@
+//! Signers for the miner's keys: the VRF keys it registers and proves sortitions with, and the
+//! Bitcoin key that signs its leader key registrations and block-commits.
+//!
+//! By default the keys are derived from the node's seed and kept in memory (`LocalSigner`).
+//! A production miner can instead hand them to an external signing service (`RemoteSigner`),
+//! which may in turn keep them in an HSM, so that no private key that controls the miner's
+//! BTC or its VRF keys lives on the mining host.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use async_h1::client;
+use async_std::io::ReadExt;
+use async_std::net::TcpStream;
+use http_types::{Method, Request, Url};
+
+use stacks::burnchains::{PrivateKey, PublicKey};
+use stacks::util::hash::{hex_bytes, to_hex, Sha256Sum};
+use stacks::util::secp256k1::{MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey};
+use stacks::util::vrf::{VRFPrivateKey, VRFProof, VRFPublicKey, VRF};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignerError {
+    /// could not reach the signer
+    Network(String),
+    /// the signer refused to sign, or sent back something unusable
+    Rejected(String),
+    /// the signer does not have the requested key
+    UnknownKey(String),
+}
+
+impl fmt::Display for SignerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignerError::Network(msg) => write!(f, "Signer unreachable: {}", msg),
+            SignerError::Rejected(msg) => write!(f, "Signer rejected request: {}", msg),
+            SignerError::UnknownKey(key) => write!(f, "Signer has no key {}", key),
+        }
+    }
+}
+
+pub trait MinerSigner: Send {
+    /// Name of this signer, for logging
+    fn name(&self) -> &str;
+
+    /// Public key of the Bitcoin key that signs the miner's burnchain operations
+    fn get_public_key(&self) -> Secp256k1PublicKey;
+
+    /// Sign a Bitcoin transaction input's signature hash with the miner's Bitcoin key
+    fn sign_message(&mut self, hash: &[u8]) -> Result<MessageSignature, SignerError>;
+
+    /// Get the VRF public key to register at the given burnchain block height.  The same height
+    /// always gives the same key, so a key registered by an earlier run can be recovered.
+    fn get_vrf_public_key(&mut self, block_height: u64) -> Result<VRFPublicKey, SignerError>;
+
+    /// Prove `message` with the private key of `vrf_pk`
+    fn prove_vrf(&mut self, vrf_pk: &VRFPublicKey, message: &[u8])
+        -> Result<VRFProof, SignerError>;
+
+    fn box_clone(&self) -> Box<dyn MinerSigner>;
+}
+
+impl Clone for Box<dyn MinerSigner> {
+    fn clone(&self) -> Box<dyn MinerSigner> {
+        self.box_clone()
+    }
+}
+
+/// Signs with keys held in memory.  VRF keys are derived from `hashed_secret_state` and the
+/// block height they are registered at.
+#[derive(Clone)]
+pub struct LocalSigner {
+    secret_key: Secp256k1PrivateKey,
+    hashed_secret_state: Sha256Sum,
+    vrf_map: HashMap<VRFPublicKey, VRFPrivateKey>,
+}
+
+impl LocalSigner {
+    pub fn new(secret_key: Secp256k1PrivateKey, hashed_secret_state: Sha256Sum) -> LocalSigner {
+        LocalSigner {
+            secret_key,
+            hashed_secret_state,
+            vrf_map: HashMap::new(),
+        }
+    }
+}
+
+impl MinerSigner for LocalSigner {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn get_public_key(&self) -> Secp256k1PublicKey {
+        Secp256k1PublicKey::from_private(&self.secret_key)
+    }
+
+    fn sign_message(&mut self, hash: &[u8]) -> Result<MessageSignature, SignerError> {
+        self.secret_key
+            .sign(hash)
+            .map_err(|e| SignerError::Rejected(e.to_string()))
+    }
+
+    fn get_vrf_public_key(&mut self, block_height: u64) -> Result<VRFPublicKey, SignerError> {
+        let mut seed = {
+            let mut secret_state = self.hashed_secret_state.to_bytes().to_vec();
+            secret_state.extend_from_slice(&block_height.to_be_bytes());
+            Sha256Sum::from_data(&secret_state)
+        };
+
+        // Not every 256-bit number is a valid Ed25519 secret key.
+        // As such, we continuously generate seeds through re-hashing until one works.
+        let sk = loop {
+            match VRFPrivateKey::from_bytes(seed.as_bytes()) {
+                Some(sk) => break sk,
+                None => seed = Sha256Sum::from_data(seed.as_bytes()),
+            }
+        };
+        let pk = VRFPublicKey::from_private(&sk);
+        self.vrf_map.insert(pk.clone(), sk);
+        Ok(pk)
+    }
+
+    fn prove_vrf(
+        &mut self,
+        vrf_pk: &VRFPublicKey,
+        message: &[u8],
+    ) -> Result<VRFProof, SignerError> {
+        match self.vrf_map.get(vrf_pk) {
+            Some(vrf_sk) => Ok(VRF::prove(vrf_sk, &message.to_vec())),
+            None => Err(SignerError::UnknownKey(vrf_pk.to_hex())),
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn MinerSigner> {
+        Box::new(self.clone())
+    }
+}
+
+/// Signs by asking an external signing service over HTTP.
+///
+/// Each request is a POST of a JSON object `{"method": ..., "params": {...}}` to the
+/// service's URL, and each reply is either `{"result": {...}}` or `{"error": "..."}`:
+///
+/// * `get_public_key` -- no params; replies with `public_key`, the hex-encoded compressed
+///   secp256k1 public key of the miner's Bitcoin key.
+/// * `sign` -- `message_hash` (hex); replies with `signature`, a hex-encoded 65-byte recoverable
+///   signature in the same format as `MessageSignature`.
+/// * `get_vrf_public_key` -- `block_height`; replies with `public_key`, the hex-encoded VRF
+///   public key for that height.
+/// * `prove_vrf` -- `public_key` and `message` (both hex); replies with `proof` (hex).
+///
+/// Everything the service returns is checked before it is used.
+#[derive(Clone)]
+pub struct RemoteSigner {
+    url: Url,
+    auth_token: Option<String>,
+    public_key: Secp256k1PublicKey,
+}
+
+impl RemoteSigner {
+    /// Connect to the signing service at `url`, and fetch the miner's Bitcoin public key
+    pub fn connect(url: &str, auth_token: Option<String>) -> Result<RemoteSigner, SignerError> {
+        let url = Url::parse(url)
+            .map_err(|e| SignerError::Network(format!("Bad signer URL {}: {:?}", url, &e)))?;
+        let reply = RemoteSigner::call(&url, &auth_token, "get_public_key", json!({}))?;
+        let public_key =
+            Secp256k1PublicKey::from_hex(&RemoteSigner::get_str(&reply, "public_key")?)
+                .map_err(|e| SignerError::Rejected(format!("Bad public key: {}", e)))?;
+        info!(
+            "Miner keys are held by the signer at {}; burnchain public key is {}",
+            &url,
+            &public_key.to_hex()
+        );
+        Ok(RemoteSigner {
+            url,
+            auth_token,
+            public_key,
+        })
+    }
+
+    fn get_str(reply: &serde_json::Value, field: &str) -> Result<String, SignerError> {
+        reply
+            .get(field)
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string())
+            .ok_or_else(|| SignerError::Rejected(format!("Reply has no {}: {}", field, reply)))
+    }
+
+    fn get_bytes(reply: &serde_json::Value, field: &str) -> Result<Vec<u8>, SignerError> {
+        hex_bytes(&RemoteSigner::get_str(reply, field)?)
+            .map_err(|_| SignerError::Rejected(format!("Bad hex in {}", field)))
+    }
+
+    fn call(
+        url: &Url,
+        auth_token: &Option<String>,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, SignerError> {
+        let mut request = Request::new(Method::Post, url.clone());
+        request
+            .append_header("Content-Type", "application/json")
+            .expect("Unable to set header");
+        if let Some(ref auth_token) = auth_token {
+            request
+                .append_header("Authorization", format!("Bearer {}", auth_token))
+                .expect("Unable to set header");
+        }
+        request.set_body(json!({ "method": method, "params": params }).to_string());
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| SignerError::Network(format!("No host in signer URL {}", url)))?
+            .to_string();
+        let port = url.port_or_known_default().unwrap_or(80);
+
+        let (status, res, buffer) = async_std::task::block_on(async move {
+            let stream = TcpStream::connect((host.as_str(), port))
+                .await
+                .map_err(|e| SignerError::Network(format!("connection failed - {:?}", &e)))?;
+            let mut response = client::connect(stream, request)
+                .await
+                .map_err(|e| SignerError::Network(format!("request failed - {:?}", &e)))?;
+            let status = response.status();
+            let mut buffer = Vec::new();
+            let mut body = response.take_body();
+            let res = body.read_to_end(&mut buffer).await;
+            Ok::<_, SignerError>((status, res, buffer))
+        })?;
+
+        if res.is_err() {
+            return Err(SignerError::Network(format!(
+                "unable to read body - {:?}",
+                res
+            )));
+        }
+        let reply: serde_json::Value = serde_json::from_slice(&buffer)
+            .map_err(|_| SignerError::Rejected(format!("unparseable reply (status {})", status)))?;
+        if let Some(error) = reply.get("error") {
+            return Err(SignerError::Rejected(error.to_string()));
+        }
+        if !status.is_success() {
+            return Err(SignerError::Rejected(format!("status {}", status)));
+        }
+        reply
+            .get("result")
+            .cloned()
+            .ok_or_else(|| SignerError::Rejected(format!("Reply has no result: {}", reply)))
+    }
+}
+
+impl MinerSigner for RemoteSigner {
+    fn name(&self) -> &str {
+        "remote"
+    }
+
+    fn get_public_key(&self) -> Secp256k1PublicKey {
+        self.public_key.clone()
+    }
+
+    fn sign_message(&mut self, hash: &[u8]) -> Result<MessageSignature, SignerError> {
+        let reply = RemoteSigner::call(
+            &self.url,
+            &self.auth_token,
+            "sign",
+            json!({ "message_hash": to_hex(hash) }),
+        )?;
+        let bytes = RemoteSigner::get_bytes(&reply, "signature")?;
+        if bytes.len() != 65 {
+            return Err(SignerError::Rejected(format!(
+                "Signature is {} bytes",
+                bytes.len()
+            )));
+        }
+        let signature = MessageSignature::from_raw(&bytes);
+        match self.public_key.verify(hash, &signature) {
+            Ok(true) => Ok(signature),
+            _ => Err(SignerError::Rejected(
+                "Signature does not verify against the miner's public key".to_string(),
+            )),
+        }
+    }
+
+    fn get_vrf_public_key(&mut self, block_height: u64) -> Result<VRFPublicKey, SignerError> {
+        let reply = RemoteSigner::call(
+            &self.url,
+            &self.auth_token,
+            "get_vrf_public_key",
+            json!({ "block_height": block_height }),
+        )?;
+        VRFPublicKey::from_hex(&RemoteSigner::get_str(&reply, "public_key")?)
+            .ok_or_else(|| SignerError::Rejected("Bad VRF public key".to_string()))
+    }
+
+    fn prove_vrf(
+        &mut self,
+        vrf_pk: &VRFPublicKey,
+        message: &[u8],
+    ) -> Result<VRFProof, SignerError> {
+        let reply = RemoteSigner::call(
+            &self.url,
+            &self.auth_token,
+            "prove_vrf",
+            json!({ "public_key": vrf_pk.to_hex(), "message": to_hex(message) }),
+        )?;
+        let proof = VRFProof::from_bytes(&RemoteSigner::get_bytes(&reply, "proof")?)
+            .ok_or_else(|| SignerError::Rejected("Bad VRF proof".to_string()))?;
+        match VRF::verify(vrf_pk, &proof, &message.to_vec()) {
+            Ok(true) => Ok(proof),
+            _ => Err(SignerError::Rejected(
+                "VRF proof does not verify against the VRF public key".to_string(),
+            )),
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn MinerSigner> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_signer() {
+        let secret_key = Secp256k1PrivateKey::new();
+        let mut signer = LocalSigner::new(secret_key, Sha256Sum::from_data(&[0x01; 32]));
+
+        let hash = Sha256Sum::from_data(b"hello world");
+        let signature = signer.sign_message(hash.as_bytes()).unwrap();
+        assert!(signer
+            .get_public_key()
+            .verify(hash.as_bytes(), &signature)
+            .unwrap());
+
+        // VRF keys are a function of the block height
+        let vrf_pk = signer.get_vrf_public_key(100).unwrap();
+        assert_eq!(signer.get_vrf_public_key(100).unwrap(), vrf_pk);
+        assert!(signer.get_vrf_public_key(101).unwrap() != vrf_pk);
+
+        let proof = signer.prove_vrf(&vrf_pk, &[0x02; 32]).unwrap();
+        assert!(VRF::verify(&vrf_pk, &proof, &vec![0x02; 32]).unwrap());
+
+        // a fresh signer can recover the key, given its height
+        let mut other = LocalSigner::new(secret_key, Sha256Sum::from_data(&[0x01; 32]));
+        assert_eq!(
+            other.prove_vrf(&vrf_pk, &[0x02; 32]),
+            Err(SignerError::UnknownKey(vrf_pk.to_hex()))
+        );
+        other.get_vrf_public_key(100).unwrap();
+        assert_eq!(other.prove_vrf(&vrf_pk, &[0x02; 32]).unwrap(), proof);
+    }
+}
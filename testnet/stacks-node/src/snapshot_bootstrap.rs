@@ -0,0 +1,208 @@
+//! Bootstrapping a fresh node from a chainstate snapshot archive served over HTTP.
+//!
+//! The archive is streamed straight into `snapshot::import_snapshot_from_reader`, which hashes
+//! every file as it is written and checks the manifest against the configured digest, so the
+//! download is never buffered in memory or trusted before it is verified.
+
+use std::io::{self, Read};
+use std::net::ToSocketAddrs;
+use std::path::PathBuf;
+
+use async_h1::client;
+use async_std::io::ReadExt;
+use async_std::net::TcpStream;
+use http_types::{Body, Method, Request, Url};
+
+use stacks::chainstate::snapshot;
+use stacks::chainstate::stacks::db::StacksChainState;
+
+use crate::config::Config;
+
+/// Log download progress every time this many more bytes arrive
+const SNAPSHOT_PROGRESS_INTERVAL_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Synchronous reader over an HTTP response body
+struct SnapshotDownload {
+    body: Body,
+    bytes_read: u64,
+    total_bytes: Option<u64>,
+    next_report: u64,
+}
+
+impl Read for SnapshotDownload {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let body = &mut self.body;
+        let nr = async_std::task::block_on(async move { body.read(buf).await })?;
+        self.bytes_read += nr as u64;
+        if self.bytes_read >= self.next_report {
+            match self.total_bytes {
+                Some(total_bytes) => info!(
+                    "Downloaded {} of {} snapshot bytes",
+                    self.bytes_read, total_bytes
+                ),
+                None => info!("Downloaded {} snapshot bytes", self.bytes_read),
+            }
+            self.next_report += SNAPSHOT_PROGRESS_INTERVAL_BYTES;
+        }
+        Ok(nr)
+    }
+}
+
+fn open_snapshot_download(url_str: &str) -> Result<SnapshotDownload, String> {
+    let url =
+        Url::parse(url_str).map_err(|e| format!("Invalid snapshot URL {}: {}", url_str, e))?;
+    if url.scheme() != "http" {
+        return Err(format!(
+            "Unsupported snapshot URL {}: only http:// is supported",
+            url_str
+        ));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("Snapshot URL {} has no host", url_str))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let socket_addr = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("Failed to resolve {}", host))?;
+
+    let request = Request::new(Method::Get, url);
+    let mut response = async_std::task::block_on(async move {
+        let stream = TcpStream::connect(socket_addr)
+            .await
+            .map_err(|e| format!("Snapshot download: connection failed - {:?}", e))?;
+        client::connect(stream, request)
+            .await
+            .map_err(|e| format!("Snapshot download: request failed - {:?}", e))
+    })?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Snapshot download: status({}) != success",
+            response.status()
+        ));
+    }
+
+    Ok(SnapshotDownload {
+        total_bytes: response.len().map(|len| len as u64),
+        body: response.take_body(),
+        bytes_read: 0,
+        next_report: SNAPSHOT_PROGRESS_INTERVAL_BYTES,
+    })
+}
+
+/// If a snapshot URL is configured and this node has no chainstate yet, download the snapshot
+/// and import it, so the node only needs to sync blocks after the snapshot's tip.
+/// Returns whether or not a snapshot was imported.
+pub fn bootstrap_from_snapshot(config: &Config) -> Result<bool, String> {
+    let url = match config.node.bootstrap_snapshot_url {
+        Some(ref url) => url,
+        None => {
+            return Ok(false);
+        }
+    };
+
+    let chainstate_path = config.get_chainstate_path_str();
+    let burnchain_path = config.get_burnchain_path_str();
+    if StacksChainState::header_index_root_path(PathBuf::from(&chainstate_path)).exists() {
+        debug!(
+            "Chainstate already exists at {}; not bootstrapping from {}",
+            &chainstate_path, url
+        );
+        return Ok(false);
+    }
+
+    let expected_digest = config.node.bootstrap_snapshot_digest.as_ref();
+    if expected_digest.is_none() {
+        warn!("No `bootstrap_snapshot_digest` is configured; the snapshot will only be checked against its own embedded hashes");
+    }
+
+    info!("Bootstrapping chainstate from snapshot at {}", url);
+    let mut download = open_snapshot_download(url)?;
+    let manifest = snapshot::import_snapshot_from_reader(
+        &mut download,
+        &chainstate_path,
+        &burnchain_path,
+        expected_digest,
+    )
+    .map_err(|e| format!("Failed to import snapshot from {}: {}", url, e))?;
+
+    if manifest.mainnet != config.is_mainnet() || manifest.chain_id != config.burnchain.chain_id {
+        // don't leave another network's chainstate behind
+        let _ = std::fs::remove_dir_all(&chainstate_path);
+        let _ = std::fs::remove_dir_all(&burnchain_path);
+        return Err(format!(
+            "Snapshot from {} is for mainnet={} chain ID {:08x}, but this node is mainnet={} chain ID {:08x}",
+            url,
+            manifest.mainnet,
+            manifest.chain_id,
+            config.is_mainnet(),
+            config.burnchain.chain_id
+        ));
+    }
+
+    info!(
+        "Bootstrapped from snapshot at Stacks block {}/{} (height {}); resuming sync from there",
+        &manifest.stacks_consensus_hash, &manifest.stacks_block_hash, manifest.stacks_block_height
+    );
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_snapshot_download_streams_body() {
+        let body: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let served_body = body.clone();
+        let server = thread::spawn(move || {
+            let (mut sock, _) = listener.accept().unwrap();
+            // read the request headers
+            let mut request = vec![];
+            let mut byte = [0u8; 1];
+            while !request.ends_with(b"\r\n\r\n") {
+                sock.read_exact(&mut byte).unwrap();
+                request.push(byte[0]);
+            }
+            write!(
+                sock,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                served_body.len()
+            )
+            .unwrap();
+            sock.write_all(&served_body).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let mut download =
+            open_snapshot_download(&format!("http://{}/snapshot.bin", &addr)).unwrap();
+        assert_eq!(download.total_bytes, Some(body.len() as u64));
+
+        let mut received = vec![];
+        download.read_to_end(&mut received).unwrap();
+        assert_eq!(received, body);
+        assert_eq!(download.bytes_read, body.len() as u64);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /snapshot.bin HTTP/1.1"));
+    }
+
+    #[test]
+    fn test_snapshot_download_rejects_unsupported_urls() {
+        assert!(open_snapshot_download("https://example.com/snapshot.bin").is_err());
+        assert!(open_snapshot_download("not a url").is_err());
+    }
+
+    #[test]
+    fn test_bootstrap_without_url_is_a_noop() {
+        assert_eq!(bootstrap_from_snapshot(&Config::default()), Ok(false));
+    }
+}
@@ -18,6 +18,9 @@ use std::sync::{
 // network if your node is actualy waiting a day in-between reward cycles).
 const SYNC_WAIT_SECS: u64 = 24 * 3600;
 
+// how often to check for new burnchain block notifications while sleeping
+const BURNCHAIN_NOTIFICATION_CHECK_MS: u64 = 100;
+
 #[derive(Clone)]
 pub struct PoxSyncWatchdogComms {
     /// how many passes in the p2p state machine have taken place since startup?
@@ -30,6 +33,8 @@ pub struct PoxSyncWatchdogComms {
     burnchain_tip_height: Arc<AtomicU64>,
     /// What's our last IBD status?
     last_ibd: Arc<AtomicBool>,
+    /// how many new burnchain blocks has bitcoind told us about?
+    burnchain_notifications: Arc<AtomicU64>,
     /// Should keep running?
     should_keep_running: Arc<AtomicBool>,
 }
@@ -42,6 +47,7 @@ impl PoxSyncWatchdogComms {
             download_passes: Arc::new(AtomicU64::new(0)),
             burnchain_tip_height: Arc::new(AtomicU64::new(0)),
             last_ibd: Arc::new(AtomicBool::new(true)),
+            burnchain_notifications: Arc::new(AtomicU64::new(0)),
             should_keep_running,
         }
     }
@@ -58,6 +64,10 @@ impl PoxSyncWatchdogComms {
         self.download_passes.load(Ordering::SeqCst)
     }
 
+    pub fn get_burnchain_notifications(&self) -> u64 {
+        self.burnchain_notifications.load(Ordering::SeqCst)
+    }
+
     pub fn get_ibd(&self) -> bool {
         self.last_ibd.load(Ordering::SeqCst)
     }
@@ -78,13 +88,19 @@ impl PoxSyncWatchdogComms {
         return Ok(true);
     }
 
+    /// Sleep for up to `secs` seconds, or until bitcoind tells us about a new burnchain block
     fn interruptable_sleep(&self, secs: u64) -> Result<(), burnchain_error> {
         let deadline = secs + get_epoch_time_secs();
+        let notifications = self.get_burnchain_notifications();
         while get_epoch_time_secs() < deadline {
-            sleep_ms(1000);
+            sleep_ms(BURNCHAIN_NOTIFICATION_CHECK_MS);
             if !self.should_keep_running() {
                 return Err(burnchain_error::CoordinatorClosed);
             }
+            if self.get_burnchain_notifications() != notifications {
+                debug!("PoX watchdog comms: woken up by a new burnchain block");
+                return Ok(());
+            }
         }
         Ok(())
     }
@@ -120,6 +136,10 @@ impl PoxSyncWatchdogComms {
         self.download_passes.fetch_add(1, Ordering::SeqCst);
     }
 
+    pub fn notify_burnchain_block(&mut self) {
+        self.burnchain_notifications.fetch_add(1, Ordering::SeqCst);
+    }
+
     pub fn set_ibd(&mut self, value: bool) {
         self.last_ibd.store(value, Ordering::SeqCst);
     }
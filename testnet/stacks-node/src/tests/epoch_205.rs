@@ -122,6 +122,8 @@ fn test_exact_block_costs() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent, EventKeyType::MinedBlocks],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -343,6 +345,8 @@ fn test_dynamic_db_method_costs() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -796,6 +800,8 @@ fn test_cost_limit_switch_version205() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
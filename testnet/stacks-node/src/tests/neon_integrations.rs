@@ -128,6 +128,7 @@ pub mod test_observer {
         pub static ref MEMTXS: Mutex<Vec<String>> = Mutex::new(Vec::new());
         pub static ref MEMTXS_DROPPED: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
         pub static ref ATTACHMENTS: Mutex<Vec<serde_json::Value>> = Mutex::new(Vec::new());
+        pub static ref STACKS_REORGS: Mutex<Vec<serde_json::Value>> = Mutex::new(Vec::new());
     }
 
     async fn handle_burn_block(
@@ -152,6 +153,12 @@ pub mod test_observer {
         Ok(warp::http::StatusCode::OK)
     }
 
+    async fn handle_stacks_reorg(reorg: serde_json::Value) -> Result<impl warp::Reply, Infallible> {
+        let mut reorgs = STACKS_REORGS.lock().unwrap();
+        reorgs.push(reorg);
+        Ok(warp::http::StatusCode::OK)
+    }
+
     async fn handle_mined_block(block: serde_json::Value) -> Result<impl warp::Reply, Infallible> {
         let mut mined_blocks = MINED_BLOCKS.lock().unwrap();
         mined_blocks.push(serde_json::from_value(block).unwrap());
@@ -259,6 +266,10 @@ pub mod test_observer {
             .and(warp::post())
             .and(warp::body::json())
             .and_then(handle_mined_block);
+        let stacks_reorgs = warp::path!("stacks_reorg")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and_then(handle_stacks_reorg);
 
         info!("Spawning warp server");
         warp::serve(
@@ -268,7 +279,8 @@ pub mod test_observer {
                 .or(new_burn_blocks)
                 .or(new_attachments)
                 .or(new_microblocks)
-                .or(mined_blocks),
+                .or(mined_blocks)
+                .or(stacks_reorgs),
         )
         .run(([127, 0, 0, 1], EVENT_OBSERVER_PORT))
         .await
@@ -289,6 +301,7 @@ pub mod test_observer {
         MEMTXS.lock().unwrap().clear();
         MEMTXS_DROPPED.lock().unwrap().clear();
         MINED_BLOCKS.lock().unwrap().clear();
+        STACKS_REORGS.lock().unwrap().clear();
     }
 }
 
@@ -723,6 +736,8 @@ fn liquid_ustx_integration() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let spender_bal = 10_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -851,6 +866,8 @@ fn lockup_integration() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -1493,6 +1510,8 @@ fn microblock_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -2166,6 +2185,8 @@ fn mining_transactions_is_fair() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -2486,6 +2507,8 @@ fn size_overflow_unconfirmed_microblocks_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -2682,6 +2705,8 @@ fn size_overflow_unconfirmed_stream_microblocks_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -2876,6 +2901,8 @@ fn size_overflow_unconfirmed_invalid_stream_microblocks_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -3142,6 +3169,8 @@ fn runtime_overflow_unconfirmed_microblocks_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -3315,6 +3344,8 @@ fn block_replay_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -3439,6 +3470,8 @@ fn cost_voting_integration() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let spender_bal = 10_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -3863,6 +3896,8 @@ fn pox_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        filter_transactions: false,
+        include_tx_details: false,
     });
 
     let first_bal = 6_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -4390,6 +4425,8 @@ fn atlas_integration_test() {
         .push(EventObserverConfig {
             endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
             events_keys: vec![EventKeyType::AnyEvent],
+            filter_transactions: false,
+            include_tx_details: false,
         });
 
     // Our 2 nodes will share the bitcoind node
@@ -4917,6 +4954,8 @@ fn antientropy_integration_test() {
         .push(EventObserverConfig {
             endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
             events_keys: vec![EventKeyType::AnyEvent],
+            filter_transactions: false,
+            include_tx_details: false,
         });
 
     // Our 2 nodes will share the bitcoind node